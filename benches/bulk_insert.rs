@@ -0,0 +1,186 @@
+//! Compares ways of inserting a batch of contacts in one request.
+//!
+//! `per_row` issues one `INSERT ... ON CONFLICT (workspace_id, code) DO NOTHING RETURNING` per contact, as
+//! `create_by_workspace` does; `unnest` sends the whole batch as column arrays in a single
+//! statement, as `create_many_by_workspace` does; `copy` streams it with `COPY FROM STDIN`,
+//! which is the lower bound but can neither skip duplicate codes nor return the new rows.
+//! Every iteration runs inside a savepoint that is rolled back, so it can run against any
+//! development database:
+//!
+//! ```sh
+//! DATABASE_URL=postgres://postgres@localhost/myapp cargo bench --bench bulk_insert
+//! ```
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use sqlx::{Connection, PgConnection};
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+const BATCH_SIZES: [usize; 3] = [100, 1_000, 5_000];
+
+const PER_ROW_INSERT: &str = r#"
+  INSERT INTO contacts (code, name, email, position, type, address, workspace_id, created_by)
+  VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+  ON CONFLICT (workspace_id, code) DO NOTHING
+  RETURNING id
+"#;
+
+const UNNEST_INSERT: &str = r#"
+  INSERT INTO contacts (code, name, email, position, type, address, workspace_id, created_by)
+  SELECT code, name, email, position, type, address, $7, $8
+  FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[], $5::text[], $6::text[])
+    AS rows(code, name, email, position, type, address)
+  ON CONFLICT (workspace_id, code) DO NOTHING
+  RETURNING id
+"#;
+
+const COPY_IN: &str = "COPY contacts (code, name, email, type, workspace_id, created_by) FROM STDIN (FORMAT csv)";
+
+struct Fixture {
+  workspace_id: Uuid,
+  user_id: Uuid,
+}
+
+struct Batch {
+  codes: Vec<String>,
+  names: Vec<String>,
+  emails: Vec<String>,
+  positions: Vec<Option<String>>,
+  types: Vec<String>,
+  addresses: Vec<Option<String>>,
+}
+
+impl Batch {
+  fn new(size: usize) -> Self {
+    Self {
+      codes: (0..size).map(|n| format!("BULK-{:08}", n)).collect(),
+      names: (0..size).map(|n| format!("Contact {}", n)).collect(),
+      emails: (0..size).map(|n| format!("bulk{}@example.com", n)).collect(),
+      positions: vec![None; size],
+      types: vec!["customer".to_string(); size],
+      addresses: vec![None; size],
+    }
+  }
+
+  fn csv(&self, fixture: &Fixture) -> Vec<u8> {
+    let mut csv = String::new();
+    for i in 0..self.codes.len() {
+      csv.push_str(&format!(
+        "{},{},{},{},{},{}\n",
+        self.codes[i], self.names[i], self.emails[i], self.types[i], fixture.workspace_id, fixture.user_id
+      ));
+    }
+    csv.into_bytes()
+  }
+}
+
+/// Creates the user and workspace the batches are inserted into, in the open transaction.
+async fn seed(conn: &mut PgConnection) -> Result<Fixture, sqlx::Error> {
+  let user_id: Uuid = sqlx::query_scalar(
+    "INSERT INTO users (username, email, password_hash)
+     VALUES ('bench_' || left(md5(random()::text), 8), 'bench_' || md5(random()::text) || '@example.com', 'x')
+     RETURNING id",
+  )
+  .fetch_one(&mut *conn)
+  .await?;
+
+  let workspace_id: Uuid =
+    sqlx::query_scalar("INSERT INTO workspaces (name, owner_id, created_by) VALUES ('Benchmark workspace', $1, $1) RETURNING id")
+      .bind(user_id)
+      .fetch_one(&mut *conn)
+      .await?;
+
+  Ok(Fixture { workspace_id, user_id })
+}
+
+async fn per_row(conn: &mut PgConnection, fixture: &Fixture, batch: &Batch) -> Result<usize, sqlx::Error> {
+  let mut inserted = 0;
+  for i in 0..batch.codes.len() {
+    let row: Option<Uuid> = sqlx::query_scalar(PER_ROW_INSERT)
+      .bind(&batch.codes[i])
+      .bind(&batch.names[i])
+      .bind(&batch.emails[i])
+      .bind(&batch.positions[i])
+      .bind(&batch.types[i])
+      .bind(&batch.addresses[i])
+      .bind(fixture.workspace_id)
+      .bind(fixture.user_id)
+      .fetch_optional(&mut *conn)
+      .await?;
+    inserted += usize::from(row.is_some());
+  }
+  Ok(inserted)
+}
+
+async fn unnest(conn: &mut PgConnection, fixture: &Fixture, batch: &Batch) -> Result<usize, sqlx::Error> {
+  let rows: Vec<Uuid> = sqlx::query_scalar(UNNEST_INSERT)
+    .bind(&batch.codes)
+    .bind(&batch.names)
+    .bind(&batch.emails)
+    .bind(&batch.positions)
+    .bind(&batch.types)
+    .bind(&batch.addresses)
+    .bind(fixture.workspace_id)
+    .bind(fixture.user_id)
+    .fetch_all(&mut *conn)
+    .await?;
+  Ok(rows.len())
+}
+
+async fn copy(conn: &mut PgConnection, csv: &[u8]) -> Result<usize, sqlx::Error> {
+  let mut copy = conn.copy_in_raw(COPY_IN).await?;
+  copy.send(csv).await?;
+  Ok(copy.finish().await? as usize)
+}
+
+fn bulk_insert(c: &mut Criterion) {
+  let Ok(database_url) = std::env::var("DATABASE_URL") else {
+    eprintln!("DATABASE_URL is not set, skipping bulk insert benchmarks");
+    return;
+  };
+
+  let runtime = Runtime::new().expect("failed to start Tokio runtime");
+  let mut conn = runtime
+    .block_on(PgConnection::connect(&database_url))
+    .expect("failed to connect to DATABASE_URL");
+  runtime
+    .block_on(sqlx::query("BEGIN").execute(&mut conn))
+    .expect("failed to open transaction");
+  let fixture = runtime.block_on(seed(&mut conn)).expect("failed to seed benchmark data");
+
+  let mut group = c.benchmark_group("contact_bulk_insert");
+  group.sample_size(10);
+  for size in BATCH_SIZES {
+    let batch = Batch::new(size);
+    let csv = batch.csv(&fixture);
+    group.throughput(Throughput::Elements(size as u64));
+
+    for name in ["per_row", "unnest", "copy"] {
+      group.bench_function(BenchmarkId::new(name, size), |b| {
+        b.iter(|| {
+          runtime
+            .block_on(async {
+              sqlx::query("SAVEPOINT bulk_insert").execute(&mut conn).await?;
+              let inserted = match name {
+                "per_row" => per_row(&mut conn, &fixture, &batch).await?,
+                "unnest" => unnest(&mut conn, &fixture, &batch).await?,
+                _ => copy(&mut conn, &csv).await?,
+              };
+              assert_eq!(inserted, size);
+              sqlx::query("ROLLBACK TO SAVEPOINT bulk_insert").execute(&mut conn).await?;
+              Ok::<_, sqlx::Error>(())
+            })
+            .expect("bulk insert failed")
+        })
+      });
+    }
+  }
+  group.finish();
+
+  runtime
+    .block_on(sqlx::query("ROLLBACK").execute(&mut conn))
+    .expect("failed to roll back benchmark data");
+}
+
+criterion_group!(benches, bulk_insert);
+criterion_main!(benches);
@@ -0,0 +1,90 @@
+//! Compares the latest `hot_paths` results in `target/criterion` with the recorded baselines in
+//! `benches/baselines/hot_paths.json`, and exits with an error when a benchmark's mean got slower
+//! than the tolerance allows.
+//!
+//! ```sh
+//! cargo bench --bench hot_paths && cargo bench --bench check_baselines
+//! ```
+//!
+//! `BENCH_TOLERANCE` is the allowed slowdown as a fraction (default `0.25`). Run with
+//! `BENCH_UPDATE_BASELINE=1` to record the latest results as the new baselines instead. Benchmarks
+//! missing from either side are reported but never fail the check.
+
+use std::{collections::BTreeMap, fs, path::Path, process::ExitCode};
+
+use serde_json::Value;
+
+const BASELINE_FILE: &str = "benches/baselines/hot_paths.json";
+const CRITERION_DIR: &str = "target/criterion";
+
+/// Benchmark ids of the `hot_paths` suite, as Criterion lays them out under `target/criterion`.
+const BENCHMARKS: &[&str] = &[
+  "jwt/decode",
+  "jwt/middleware",
+  "query_builder/contacts",
+  "query_builder/products",
+  "pagination/contacts/page_1",
+  "pagination/contacts/page_50",
+  "pagination/contacts/search",
+  "code_generator/next_code",
+];
+
+/// Mean time in nanoseconds of the last run of `id`.
+fn latest_mean(id: &str) -> Option<f64> {
+  let path = Path::new(CRITERION_DIR).join(id).join("new/estimates.json");
+  let estimates: Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+  estimates["mean"]["point_estimate"].as_f64()
+}
+
+fn main() -> ExitCode {
+  let tolerance = std::env::var("BENCH_TOLERANCE").ok().and_then(|v| v.parse().ok()).unwrap_or(0.25);
+  let latest: BTreeMap<&str, f64> = BENCHMARKS.iter().filter_map(|id| Some((*id, latest_mean(id)?))).collect();
+
+  if latest.is_empty() {
+    eprintln!("No hot_paths results in {CRITERION_DIR}, run `cargo bench --bench hot_paths` first");
+    return ExitCode::SUCCESS;
+  }
+
+  if std::env::var("BENCH_UPDATE_BASELINE").is_ok_and(|v| v == "1") {
+    let baselines: BTreeMap<&str, u64> = latest.iter().map(|(id, mean)| (*id, mean.round() as u64)).collect();
+    let json = serde_json::to_string_pretty(&baselines).expect("failed to serialize baselines");
+    fs::write(BASELINE_FILE, json + "\n").expect("failed to write baselines");
+    println!("Recorded {} baselines in {BASELINE_FILE}", baselines.len());
+    return ExitCode::SUCCESS;
+  }
+
+  let baselines: BTreeMap<String, f64> = match fs::read_to_string(BASELINE_FILE) {
+    Ok(contents) => serde_json::from_str(&contents).expect("invalid baseline file"),
+    Err(e) => {
+      eprintln!("Cannot read {BASELINE_FILE}: {e}");
+      return ExitCode::FAILURE;
+    }
+  };
+
+  let mut regressions = 0;
+  for id in BENCHMARKS {
+    let (Some(mean), Some(baseline)) = (latest.get(id), baselines.get(*id)) else {
+      println!("{id:<32} skipped, missing from the results or the baselines");
+      continue;
+    };
+    let change = mean / baseline - 1.0;
+    let regressed = change > tolerance;
+    println!(
+      "{id:<32} {:>12.0} ns  baseline {:>12.0} ns  {:>+7.1}%{}",
+      mean,
+      baseline,
+      change * 100.0,
+      if regressed { "  REGRESSION" } else { "" }
+    );
+    regressions += usize::from(regressed);
+  }
+
+  if regressions > 0 {
+    eprintln!(
+      "{regressions} benchmark(s) slower than the baseline by more than {:.0}%",
+      tolerance * 100.0
+    );
+    return ExitCode::FAILURE;
+  }
+  ExitCode::SUCCESS
+}
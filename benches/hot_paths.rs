@@ -0,0 +1,228 @@
+//! Measures the code every request goes through: JWT validation on its own and the whole JWT
+//! middleware, list query construction, paginated list queries and code generation.
+//!
+//! The data is seeded in a transaction that is rolled back at the end, so the suite can run
+//! against any development database:
+//!
+//! ```sh
+//! DATABASE_URL=postgres://postgres@localhost/myapp cargo bench --bench hot_paths
+//! cargo bench --bench check_baselines
+//! ```
+//!
+//! `check_baselines` then compares the results with `benches/baselines/hot_paths.json` and fails
+//! when one got slower than the tolerance allows. Set `BENCH_CONTACTS` to change the number of
+//! seeded contacts (default 10 000).
+
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  body::Body,
+  http::{Request, StatusCode},
+  middleware::from_fn_with_state,
+  routing::get,
+};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use myapp_api_rust::{
+  AppState,
+  config::AppConfig,
+  modules::{
+    auth::{
+      auth_repository::AuthRepositoryImpl,
+      auth_service,
+      jwt_middleware::{decode_access_token, jwt_middleware},
+      token_revocation::PostgresTokenRevocationStore,
+    },
+    datastores::{
+      contacts::{
+        contact_models::{ContactFilters, GetContactsQuery},
+        contact_repository::{ContactRepository, SqlxContactRepository},
+      },
+      products::{
+        product_models::{GetProductsQuery, ProductFilters},
+        product_repository::SqlxProductRepository,
+      },
+      workspaces::workspace_repository::PostgresWorkspaceRepository,
+    },
+  },
+  utils::{
+    DbExecutor,
+    code_generator::{CodeGenerator, CodeGeneratorConfig},
+    paginated_repository::PaginatedRepository,
+  },
+};
+use serde_json::json;
+use sqlx::PgPool;
+use tokio::runtime::Runtime;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+struct Fixture {
+  workspace_id: Uuid,
+  user_id: Uuid,
+}
+
+/// Seeds a user, their workspace and `contacts` contacts in the executor's transaction.
+async fn seed(db: &DbExecutor, contacts: i64) -> Result<Fixture, sqlx::Error> {
+  let mut conn = db.acquire().await?;
+  let user_id: Uuid = sqlx::query_scalar(
+    "INSERT INTO users (username, email, password_hash)
+     VALUES ('bench_' || left(md5(random()::text), 8), 'bench_' || md5(random()::text) || '@example.com', 'x')
+     RETURNING id",
+  )
+  .fetch_one(&mut *conn)
+  .await?;
+
+  // The creator is added as admin by a trigger
+  let workspace_id: Uuid =
+    sqlx::query_scalar("INSERT INTO workspaces (name, owner_id, created_by) VALUES ('Benchmark workspace', $1, $1) RETURNING id")
+      .bind(user_id)
+      .fetch_one(&mut *conn)
+      .await?;
+
+  sqlx::query(
+    "INSERT INTO contacts (code, name, email, type, workspace_id, created_by, created_at)
+     SELECT 'BE-' || lpad(n::text, 5, '0'), 'Bench Contact ' || n, 'contact' || n || '@example.com',
+            (ARRAY['customer', 'supplier', 'employee'])[n % 3 + 1], $1, $2, now() - n * interval '1 second'
+     FROM generate_series(1, $3) AS n",
+  )
+  .bind(workspace_id)
+  .bind(user_id)
+  .bind(contacts)
+  .execute(&mut *conn)
+  .await?;
+
+  sqlx::query("ANALYZE contacts").execute(&mut *conn).await?;
+
+  Ok(Fixture { workspace_id, user_id })
+}
+
+fn contact_filters() -> ContactFilters {
+  serde_json::from_value::<GetContactsQuery>(json!({
+    "search": "bench contact",
+    "contact_type": "customer",
+    "is_active": true,
+    "exclude_types": "employee,supplier",
+    "sort_by": "name",
+    "sort_order": "asc"
+  }))
+  .expect("invalid contact query")
+  .into()
+}
+
+fn product_filters() -> ProductFilters {
+  serde_json::from_value::<GetProductsQuery>(json!({
+    "search": "widget",
+    "is_active": true,
+    "track_inventory": true,
+    "min_selling_price": "10.00",
+    "max_selling_price": "500.00",
+    "low_stock": true,
+    "sort_by": "selling_price",
+    "sort_order": "desc"
+  }))
+  .expect("invalid product query")
+  .into()
+}
+
+fn hot_paths(c: &mut Criterion) {
+  let Ok(database_url) = std::env::var("DATABASE_URL") else {
+    eprintln!("DATABASE_URL is not set, skipping hot path benchmarks");
+    return;
+  };
+  let contacts = std::env::var("BENCH_CONTACTS").ok().and_then(|v| v.parse().ok()).unwrap_or(10_000);
+
+  let runtime = Runtime::new().expect("failed to start Tokio runtime");
+  let pool = runtime
+    .block_on(PgPool::connect(&database_url))
+    .expect("failed to connect to DATABASE_URL");
+  let db = runtime.block_on(DbExecutor::rolled_back(&pool)).expect("failed to open transaction");
+  let fixture = runtime.block_on(seed(&db, contacts)).expect("failed to seed benchmark data");
+
+  let state = AppState::builder(pool, "bench-secret")
+    .with_config(AppConfig::default())
+    .with_contact_repository(Arc::new(SqlxContactRepository::new(db.clone())))
+    .with_product_repository(Arc::new(SqlxProductRepository::new(db.clone())))
+    .with_auth_repository(Arc::new(AuthRepositoryImpl::new(db.clone())))
+    .with_workspace_repository(Arc::new(PostgresWorkspaceRepository::new(db.clone())))
+    .with_token_revocations(Arc::new(PostgresTokenRevocationStore::new(db.clone())))
+    .build();
+  let (token, _) = runtime
+    .block_on(auth_service::switch_workspace(&state, fixture.user_id, fixture.workspace_id))
+    .expect("failed to issue token");
+
+  let mut group = c.benchmark_group("jwt");
+  group.bench_function("decode", |b| b.iter(|| decode_access_token(&state, &token).expect("token rejected")));
+  // Everything the middleware adds to a request: revocation check, role lookup (from the role
+  // cache once warm) and the RLS-scoped connection
+  let probe = Router::new()
+    .route("/probe", get(|| async { StatusCode::NO_CONTENT }))
+    .layer(from_fn_with_state(state.clone(), jwt_middleware));
+  group.bench_function("middleware", |b| {
+    b.iter(|| {
+      let request = Request::builder()
+        .uri("/probe")
+        .header("Authorization", format!("Bearer {}", token))
+        .header("X-Workspace-ID", fixture.workspace_id.to_string())
+        .body(Body::empty())
+        .unwrap();
+      let response = runtime.block_on(probe.clone().oneshot(request)).unwrap();
+      assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    })
+  });
+  group.finish();
+
+  let mut group = c.benchmark_group("query_builder");
+  let contact_filters = contact_filters();
+  group.bench_function("contacts", |b| {
+    b.iter(|| SqlxContactRepository::build_page_query(fixture.workspace_id, fixture.user_id, &contact_filters, 20, 40))
+  });
+  let product_filters = product_filters();
+  group.bench_function("products", |b| {
+    b.iter(|| SqlxProductRepository::build_page_query(fixture.workspace_id, fixture.user_id, &product_filters, 20, 40))
+  });
+  group.finish();
+
+  let mut group = c.benchmark_group("pagination");
+  let repository = SqlxContactRepository::new(db.clone());
+  for page in [1, 50] {
+    group.bench_function(BenchmarkId::new("contacts", format!("page_{page}")), |b| {
+      b.iter(|| {
+        let filters = ContactFilters::from(serde_json::from_value::<GetContactsQuery>(json!({})).unwrap());
+        runtime
+          .block_on(repository.find_by_filters_paginated(fixture.workspace_id, fixture.user_id, page, 20, filters))
+          .expect("page query failed")
+      })
+    });
+  }
+  group.bench_function(BenchmarkId::new("contacts", "search"), |b| {
+    b.iter(|| {
+      runtime
+        .block_on(repository.find_by_filters_paginated(fixture.workspace_id, fixture.user_id, 1, 20, contact_filters.clone()))
+        .expect("search query failed")
+    })
+  });
+  group.finish();
+
+  let mut group = c.benchmark_group("code_generator");
+  let generator = CodeGenerator::new(db.clone());
+  let config = CodeGeneratorConfig {
+    table_name: "contacts".to_string(),
+    ..CodeGeneratorConfig::default()
+  };
+  group.bench_function("next_code", |b| {
+    b.iter(|| {
+      runtime
+        .block_on(generator.get_next_available_code(&config, "Bench Contact", Some(fixture.workspace_id)))
+        .expect("code generation failed")
+    })
+  });
+  group.finish();
+
+  // Dropping the last executor clone rolls the seeded data back, which needs the runtime
+  let _guard = runtime.enter();
+  drop((state, probe, repository, generator, db));
+}
+
+criterion_group!(benches, hot_paths);
+criterion_main!(benches);
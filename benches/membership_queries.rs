@@ -0,0 +1,165 @@
+//! Compares the workspace membership check used by the contact and product repositories.
+//!
+//! The legacy form re-joined `workspaces` and `workspace_users` inside an `id IN (...)`
+//! subquery for every row; the current form checks membership once with an uncorrelated
+//! `EXISTS`. The benchmark seeds a large workspace inside a transaction that is rolled back at
+//! the end, so it can run against any development database:
+//!
+//! ```sh
+//! DATABASE_URL=postgres://postgres@localhost/myapp cargo bench --bench membership_queries
+//! ```
+//!
+//! Set `BENCH_CONTACTS` to change the number of seeded contacts (default 50 000).
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use sqlx::{Connection, PgConnection};
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+/// Members added to the seeded workspace, so the legacy join has more than one row to match.
+const MEMBERS: i64 = 25;
+
+const LEGACY_COUNT: &str = r#"
+  SELECT COUNT(*) FROM contacts
+  WHERE workspace_id = $1
+    AND id IN (
+      SELECT c.id FROM contacts c
+      JOIN workspaces w ON c.workspace_id = w.id
+      JOIN workspace_users wu ON w.id = wu.workspace_id
+      WHERE wu.user_id = $2
+    )
+"#;
+
+const EXISTS_COUNT: &str = r#"
+  SELECT COUNT(*) FROM contacts
+  WHERE workspace_id = $1
+    AND EXISTS (
+      SELECT 1 FROM workspace_users wu
+      WHERE wu.workspace_id = $1 AND wu.user_id = $2
+    )
+"#;
+
+const LEGACY_PAGE: &str = r#"
+  SELECT id, code, name FROM contacts
+  WHERE workspace_id = $1
+    AND id IN (
+      SELECT c.id FROM contacts c
+      JOIN workspaces w ON c.workspace_id = w.id
+      JOIN workspace_users wu ON w.id = wu.workspace_id
+      WHERE wu.user_id = $2
+    )
+  ORDER BY created_at DESC
+  LIMIT 20 OFFSET 0
+"#;
+
+const EXISTS_PAGE: &str = r#"
+  SELECT id, code, name FROM contacts
+  WHERE workspace_id = $1
+    AND EXISTS (
+      SELECT 1 FROM workspace_users wu
+      WHERE wu.workspace_id = $1 AND wu.user_id = $2
+    )
+  ORDER BY created_at DESC
+  LIMIT 20 OFFSET 0
+"#;
+
+struct Fixture {
+  workspace_id: Uuid,
+  user_id: Uuid,
+}
+
+/// Seeds a workspace with `contacts` contacts and `MEMBERS` members in the open transaction.
+async fn seed(conn: &mut PgConnection, contacts: i64) -> Result<Fixture, sqlx::Error> {
+  let members: Vec<Uuid> = sqlx::query_scalar(
+    "INSERT INTO users (username, email, password_hash)
+     SELECT 'bench_' || n || '_' || left(md5(random()::text), 8), 'bench_' || n || '_' || md5(random()::text) || '@example.com', 'x'
+     FROM generate_series(1, $1) AS n
+     RETURNING id",
+  )
+  .bind(MEMBERS)
+  .fetch_all(&mut *conn)
+  .await?;
+  let user_id = members[0];
+
+  let workspace_id: Uuid =
+    sqlx::query_scalar("INSERT INTO workspaces (name, owner_id, created_by) VALUES ('Benchmark workspace', $1, $1) RETURNING id")
+      .bind(user_id)
+      .fetch_one(&mut *conn)
+      .await?;
+
+  // The creator is added as admin by a trigger; the rest join as members
+  sqlx::query(
+    "INSERT INTO workspace_users (workspace_id, user_id, role)
+     SELECT $1, unnest($2::uuid[]), 'member'",
+  )
+  .bind(workspace_id)
+  .bind(&members[1..])
+  .execute(&mut *conn)
+  .await?;
+
+  sqlx::query(
+    "INSERT INTO contacts (code, name, email, type, workspace_id, created_at)
+     SELECT 'BENCH-' || lpad(n::text, 8, '0'), 'Contact ' || n, 'contact' || n || '@example.com', 'customer', $1,
+            now() - n * interval '1 second'
+     FROM generate_series(1, $2) AS n",
+  )
+  .bind(workspace_id)
+  .bind(contacts)
+  .execute(&mut *conn)
+  .await?;
+
+  sqlx::query("ANALYZE contacts").execute(&mut *conn).await?;
+  sqlx::query("ANALYZE workspace_users").execute(&mut *conn).await?;
+
+  Ok(Fixture { workspace_id, user_id })
+}
+
+fn membership_queries(c: &mut Criterion) {
+  let Ok(database_url) = std::env::var("DATABASE_URL") else {
+    eprintln!("DATABASE_URL is not set, skipping membership query benchmarks");
+    return;
+  };
+  let contacts = std::env::var("BENCH_CONTACTS").ok().and_then(|v| v.parse().ok()).unwrap_or(50_000);
+
+  let runtime = Runtime::new().expect("failed to start Tokio runtime");
+  let mut conn = runtime
+    .block_on(PgConnection::connect(&database_url))
+    .expect("failed to connect to DATABASE_URL");
+  runtime
+    .block_on(sqlx::query("BEGIN").execute(&mut conn))
+    .expect("failed to open transaction");
+  let fixture = runtime.block_on(seed(&mut conn, contacts)).expect("failed to seed benchmark data");
+
+  let mut group = c.benchmark_group("workspace_membership");
+  for (name, sql) in [("count/legacy_in", LEGACY_COUNT), ("count/exists", EXISTS_COUNT)] {
+    group.bench_function(BenchmarkId::new(name, contacts), |b| {
+      b.iter(|| {
+        runtime
+          .block_on(
+            sqlx::query_scalar::<_, Option<i64>>(sql)
+              .bind(fixture.workspace_id)
+              .bind(fixture.user_id)
+              .fetch_one(&mut conn),
+          )
+          .expect("count query failed")
+      })
+    });
+  }
+  for (name, sql) in [("page/legacy_in", LEGACY_PAGE), ("page/exists", EXISTS_PAGE)] {
+    group.bench_function(BenchmarkId::new(name, contacts), |b| {
+      b.iter(|| {
+        runtime
+          .block_on(sqlx::query(sql).bind(fixture.workspace_id).bind(fixture.user_id).fetch_all(&mut conn))
+          .expect("page query failed")
+      })
+    });
+  }
+  group.finish();
+
+  runtime
+    .block_on(sqlx::query("ROLLBACK").execute(&mut conn))
+    .expect("failed to roll back benchmark data");
+}
+
+criterion_group!(benches, membership_queries);
+criterion_main!(benches);
@@ -0,0 +1,53 @@
+fn main() {
+  println!("cargo:rerun-if-changed=build.rs");
+
+  #[cfg(feature = "grpc")]
+  grpc::generate();
+}
+
+/// Generates the tonic client/server stubs for the internal gRPC API.
+///
+/// The services are described in Rust with `tonic_build::manual` instead of being compiled from
+/// `proto/myapp.proto`, so building does not need `protoc`. The message types live in
+/// `src/grpc/messages.rs`; keep both in sync with the `.proto` file.
+#[cfg(feature = "grpc")]
+mod grpc {
+  use tonic_build::manual::{Builder, Method, Service};
+
+  const PACKAGE: &str = "myapp.v1";
+  const CODEC: &str = "tonic_prost::ProstCodec";
+
+  fn method(name: &str, route_name: &str, input: &str, output: &str) -> Method {
+    Method::builder()
+      .name(name)
+      .route_name(route_name)
+      .input_type(format!("crate::grpc::messages::{input}"))
+      .output_type(format!("crate::grpc::messages::{output}"))
+      .codec_path(CODEC)
+      .build()
+  }
+
+  pub fn generate() {
+    let contact_service = Service::builder()
+      .name("ContactService")
+      .package(PACKAGE)
+      .method(method("list_contacts", "ListContacts", "ListContactsRequest", "ListContactsResponse"))
+      .method(method("get_contact", "GetContact", "GetContactRequest", "Contact"))
+      .method(method("create_contact", "CreateContact", "CreateContactRequest", "Contact"))
+      .method(method("update_contact", "UpdateContact", "UpdateContactRequest", "Contact"))
+      .method(method("delete_contact", "DeleteContact", "DeleteContactRequest", "DeleteResponse"))
+      .build();
+
+    let product_service = Service::builder()
+      .name("ProductService")
+      .package(PACKAGE)
+      .method(method("list_products", "ListProducts", "ListProductsRequest", "ListProductsResponse"))
+      .method(method("get_product", "GetProduct", "GetProductRequest", "Product"))
+      .method(method("create_product", "CreateProduct", "CreateProductRequest", "Product"))
+      .method(method("update_product", "UpdateProduct", "UpdateProductRequest", "Product"))
+      .method(method("delete_product", "DeleteProduct", "DeleteProductRequest", "DeleteResponse"))
+      .build();
+
+    Builder::new().compile(&[contact_service, product_service]);
+  }
+}
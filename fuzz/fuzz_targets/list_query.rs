@@ -0,0 +1,121 @@
+//! Feeds arbitrary query strings through what the contact and product list endpoints do with
+//! them: `Query` deserialization, validation, conversion to filters and the page, count and
+//! stream queries. Besides panics, it fails when a query would carry user input in its SQL text
+//! instead of in the bound values, or when the search expression is not a well-formed tsquery.
+//!
+//! ```sh
+//! cd fuzz && SQLX_OFFLINE=true cargo +nightly fuzz run list_query
+//! ```
+
+#![no_main]
+
+use std::collections::BTreeSet;
+
+use axum::{extract::Query, http::Uri};
+use libfuzzer_sys::fuzz_target;
+use myapp_api_rust::{
+  helper::ValidatedQuery,
+  modules::datastores::{
+    contacts::{
+      contact_models::{ContactFilters, GetContactsQuery},
+      contact_repository::SqlxContactRepository,
+    },
+    products::{
+      product_models::{GetProductsQuery, ProductFilters},
+      product_repository::SqlxProductRepository,
+    },
+  },
+  utils::{
+    paginated_repository::{BoundQuery, PaginatedRepository},
+    search_index::prefix_tsquery,
+  },
+};
+use uuid::Uuid;
+
+/// The only string literal the list queries contain: the text search configuration.
+const STATIC_LITERAL: &str = "'simple'";
+
+fuzz_target!(|data: &[u8]| {
+  let Some((&target, query)) = data.split_first() else {
+    return;
+  };
+  let Ok(query) = std::str::from_utf8(query) else {
+    return;
+  };
+  // Anything that is not a valid request URI is rejected before it reaches the extractor
+  let Ok(uri) = format!("/?{}", query).parse::<Uri>() else {
+    return;
+  };
+
+  let (workspace_id, user_id) = (Uuid::from_u128(1), Uuid::from_u128(2));
+  let limit = u64::from(target % 100) + 1;
+  let offset = u64::from(target) * 7;
+
+  if target % 2 == 0 {
+    let Some(query) = parse::<GetContactsQuery>(&uri) else {
+      return;
+    };
+    if let Some(search) = &query.search {
+      check_tsquery(search);
+    }
+    let filters = ContactFilters::from(query);
+    let (page, count) = SqlxContactRepository::build_page_query(workspace_id, user_id, &filters, limit, offset);
+    check_bound(&page);
+    check_bound(&count);
+    check_bound(&SqlxContactRepository::build_stream_query(workspace_id, user_id, &filters));
+  } else {
+    let Some(query) = parse::<GetProductsQuery>(&uri) else {
+      return;
+    };
+    if let Some(search) = &query.search {
+      check_tsquery(search);
+    }
+    let filters = ProductFilters::from(query);
+    let (page, count) = SqlxProductRepository::build_page_query(workspace_id, user_id, &filters, limit, offset);
+    check_bound(&page);
+    check_bound(&count);
+    check_bound(&SqlxProductRepository::build_stream_query(workspace_id, user_id, &filters));
+  }
+});
+
+/// Deserializes and validates the query like `ValidatedQuery` does, `None` when it is rejected.
+fn parse<T>(uri: &Uri) -> Option<T>
+where
+  T: serde::de::DeserializeOwned + validator::Validate,
+{
+  let Query(query) = Query::<T>::try_from_uri(uri).ok()?;
+  ValidatedQuery::new(query).ok().map(|ValidatedQuery(query)| query)
+}
+
+/// Every user-supplied value must be a bound parameter: the SQL text holds no string literal but
+/// the static one, and one `$n` placeholder per value.
+fn check_bound((sql, values): &BoundQuery) {
+  assert!(!sql.replace(STATIC_LITERAL, "").contains('\''), "string literal in SQL: {}", sql);
+  let placeholders: BTreeSet<usize> = sql
+    .split('$')
+    .skip(1)
+    .filter_map(|rest| rest.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok())
+    .collect();
+  assert!(
+    placeholders.iter().copied().eq(1..=values.0.0.len()),
+    "placeholders do not match the {} values: {}",
+    values.0.0.len(),
+    sql
+  );
+}
+
+/// The search expression must consist of `'lexeme':*` terms joined by ` & `, with quotes inside
+/// a lexeme doubled, so user input can never add tsquery operators.
+fn check_tsquery(search: &str) {
+  let Some(tsquery) = prefix_tsquery(search) else {
+    assert!(search.split_whitespace().next().is_none(), "no tsquery for {:?}", search);
+    return;
+  };
+  for term in tsquery.split(" & ") {
+    let lexeme = term
+      .strip_prefix('\'')
+      .and_then(|term| term.strip_suffix("':*"))
+      .unwrap_or_else(|| panic!("malformed term {:?} in {:?}", term, tsquery));
+    assert!(!lexeme.replace("''", "").contains('\''), "unescaped quote in {:?}", tsquery);
+  }
+}
@@ -0,0 +1,3 @@
+-- Down migration: revoked_tokens
+DROP INDEX IF EXISTS idx_revoked_tokens_expires_at;
+DROP TABLE IF EXISTS revoked_tokens;
@@ -0,0 +1,10 @@
+-- Up migration: revoked_tokens
+-- Access tokens revoked before their expiry (logout). Rows are only needed until the
+-- token would have expired anyway and are purged after that.
+CREATE TABLE IF NOT EXISTS revoked_tokens (
+    jti UUID PRIMARY KEY,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_revoked_tokens_expires_at ON revoked_tokens(expires_at);
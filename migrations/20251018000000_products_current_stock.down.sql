@@ -0,0 +1,2 @@
+-- Down migration: products_current_stock
+ALTER TABLE products RENAME COLUMN current_stock TO stock;
@@ -0,0 +1,4 @@
+-- Up migration: products_current_stock
+-- The on-hand quantity is named current_stock everywhere else (models, filters, query
+-- builder); align the column with it.
+ALTER TABLE products RENAME COLUMN stock TO current_stock;
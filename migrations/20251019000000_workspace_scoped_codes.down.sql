@@ -0,0 +1,6 @@
+-- Down migration: workspace_scoped_codes
+DROP INDEX IF EXISTS contacts_workspace_id_code_key;
+DROP INDEX IF EXISTS products_workspace_id_code_key;
+
+ALTER TABLE contacts ADD CONSTRAINT contacts_code_key UNIQUE (code);
+ALTER TABLE products ADD CONSTRAINT products_code_key UNIQUE (code);
@@ -0,0 +1,10 @@
+-- Up migration: workspace_scoped_codes
+-- Codes only need to be unique within a workspace. The indexes also replace the
+-- application-level existence check, which raced with concurrent inserts; a violation is
+-- reported to clients as DUPLICATE_CODE by constraint name, so keep the names in sync with
+-- errors.rs.
+ALTER TABLE contacts DROP CONSTRAINT IF EXISTS contacts_code_key;
+ALTER TABLE products DROP CONSTRAINT IF EXISTS products_code_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS contacts_workspace_id_code_key ON contacts (workspace_id, code);
+CREATE UNIQUE INDEX IF NOT EXISTS products_workspace_id_code_key ON products (workspace_id, code);
@@ -0,0 +1,5 @@
+-- Down migration: case_insensitive_emails
+DROP INDEX IF EXISTS users_email_lower_key;
+
+ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email);
+CREATE INDEX IF NOT EXISTS idx_users_email ON users (email);
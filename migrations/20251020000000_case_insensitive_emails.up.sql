@@ -0,0 +1,12 @@
+-- Up migration: case_insensitive_emails
+-- Emails are stored trimmed and lowercased by the application. The unique index is on
+-- lower(email) so that `User@X.com` and `user@x.com` cannot both be registered even if a row
+-- is written by another path; a violation is reported as a conflict by constraint name, so keep
+-- the name in sync with auth_repository.rs. Existing rows that only differ by case make the
+-- index creation fail and have to be merged by hand first.
+UPDATE users SET email = lower(btrim(email)) WHERE email <> lower(btrim(email));
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_key;
+DROP INDEX IF EXISTS idx_users_email;
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_lower_key ON users (lower(email));
@@ -0,0 +1,10 @@
+-- Down migration: product_value_checks
+ALTER TABLE products
+  DROP CONSTRAINT IF EXISTS products_selling_price_check,
+  DROP CONSTRAINT IF EXISTS products_unit_cost_check,
+  DROP CONSTRAINT IF EXISTS products_tax_rate_check,
+  DROP CONSTRAINT IF EXISTS products_tax_amount_check,
+  DROP CONSTRAINT IF EXISTS products_minimum_stock_check,
+  DROP CONSTRAINT IF EXISTS products_maximum_stock_check,
+  DROP CONSTRAINT IF EXISTS products_reorder_level_check,
+  DROP CONSTRAINT IF EXISTS products_stock_levels_check;
@@ -0,0 +1,13 @@
+-- Up migration: product_value_checks
+-- Backs the product payload validation for rows written by any path. A violation is reported
+-- to clients as a validation error on the matching field by constraint name, so keep the names
+-- in sync with errors.rs.
+ALTER TABLE products
+  ADD CONSTRAINT products_selling_price_check CHECK (selling_price >= 0),
+  ADD CONSTRAINT products_unit_cost_check CHECK (unit_cost >= 0),
+  ADD CONSTRAINT products_tax_rate_check CHECK (tax_rate >= 0 AND tax_rate <= 100),
+  ADD CONSTRAINT products_tax_amount_check CHECK (tax_amount >= 0),
+  ADD CONSTRAINT products_minimum_stock_check CHECK (minimum_stock >= 0),
+  ADD CONSTRAINT products_maximum_stock_check CHECK (maximum_stock >= 0),
+  ADD CONSTRAINT products_reorder_level_check CHECK (reorder_level >= 0),
+  ADD CONSTRAINT products_stock_levels_check CHECK (minimum_stock <= maximum_stock);
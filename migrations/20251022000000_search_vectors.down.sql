@@ -0,0 +1,18 @@
+-- Down migration: search_vectors
+DROP TRIGGER IF EXISTS contacts_notify_search_index ON contacts;
+DROP TRIGGER IF EXISTS product_categories_notify_search_index ON product_categories;
+DROP TRIGGER IF EXISTS products_search_vector ON products;
+DROP TRIGGER IF EXISTS contacts_search_vector ON contacts;
+
+DROP FUNCTION IF EXISTS refresh_product_search_vectors(text, uuid);
+DROP FUNCTION IF EXISTS notify_search_index();
+DROP FUNCTION IF EXISTS products_search_vector_trigger();
+DROP FUNCTION IF EXISTS contacts_search_vector_trigger();
+DROP FUNCTION IF EXISTS product_search_vector(products);
+DROP FUNCTION IF EXISTS contact_search_vector(contacts);
+
+DROP INDEX IF EXISTS idx_products_search_vector;
+DROP INDEX IF EXISTS idx_contacts_search_vector;
+
+ALTER TABLE products DROP COLUMN IF EXISTS search_vector;
+ALTER TABLE contacts DROP COLUMN IF EXISTS search_vector;
@@ -0,0 +1,101 @@
+-- Up migration: search_vectors
+-- Full-text search columns for contacts and products, kept in sync by triggers.
+--
+-- Columns of the row itself are indexed by a BEFORE trigger as the row is written. Products
+-- also index the names of their category and supplier; renaming one of those would have to
+-- rewrite every product referencing it, so instead the rename sends a `search_index`
+-- notification and the application's refresher (utils::search_index) calls
+-- refresh_product_search_vectors outside of the renaming transaction.
+
+ALTER TABLE contacts ADD COLUMN IF NOT EXISTS search_vector tsvector;
+ALTER TABLE products ADD COLUMN IF NOT EXISTS search_vector tsvector;
+
+CREATE OR REPLACE FUNCTION contact_search_vector(c contacts)
+RETURNS tsvector AS $$
+  SELECT
+    setweight(to_tsvector('simple', coalesce(c.code, '') || ' ' || coalesce(c.name, '')), 'A') ||
+    -- The local part and domain of an email are searchable on their own as well
+    setweight(to_tsvector('simple', coalesce(c.email, '') || ' ' || replace(coalesce(c.email, ''), '@', ' ')), 'B') ||
+    setweight(to_tsvector('simple', coalesce(c.position, '') || ' ' || coalesce(c.address, '')), 'C');
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION product_search_vector(p products)
+RETURNS tsvector AS $$
+  SELECT
+    setweight(to_tsvector('simple',
+      coalesce(p.code, '') || ' ' || coalesce(p.name, '') || ' ' || coalesce(p.sku, '') || ' ' || coalesce(p.barcode, '')), 'A') ||
+    setweight(to_tsvector('simple',
+      coalesce((SELECT name FROM product_categories WHERE id = p.category_id), '') || ' ' ||
+      coalesce((SELECT name FROM contacts WHERE id = p.supplier_id), '')), 'B') ||
+    setweight(to_tsvector('simple', coalesce(p.description, '')), 'C');
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION contacts_search_vector_trigger()
+RETURNS TRIGGER AS $$
+BEGIN
+  NEW.search_vector := contact_search_vector(NEW);
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION products_search_vector_trigger()
+RETURNS TRIGGER AS $$
+BEGIN
+  NEW.search_vector := product_search_vector(NEW);
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS contacts_search_vector ON contacts;
+CREATE TRIGGER contacts_search_vector
+  BEFORE INSERT OR UPDATE OF code, name, email, position, address ON contacts
+  FOR EACH ROW EXECUTE FUNCTION contacts_search_vector_trigger();
+
+DROP TRIGGER IF EXISTS products_search_vector ON products;
+CREATE TRIGGER products_search_vector
+  BEFORE INSERT OR UPDATE OF code, name, sku, barcode, description, category_id, supplier_id ON products
+  FOR EACH ROW EXECUTE FUNCTION products_search_vector_trigger();
+
+-- Notifies the refresher that products referencing a renamed category or supplier are stale
+CREATE OR REPLACE FUNCTION notify_search_index()
+RETURNS TRIGGER AS $$
+BEGIN
+  IF NEW.name IS DISTINCT FROM OLD.name THEN
+    PERFORM pg_notify('search_index', json_build_object('source', TG_TABLE_NAME, 'id', NEW.id)::text);
+  END IF;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS product_categories_notify_search_index ON product_categories;
+CREATE TRIGGER product_categories_notify_search_index
+  AFTER UPDATE OF name ON product_categories
+  FOR EACH ROW EXECUTE FUNCTION notify_search_index();
+
+DROP TRIGGER IF EXISTS contacts_notify_search_index ON contacts;
+CREATE TRIGGER contacts_notify_search_index
+  AFTER UPDATE OF name ON contacts
+  FOR EACH ROW EXECUTE FUNCTION notify_search_index();
+
+-- Recomputes the vectors of the products referencing `source_id` in `source`, or of every
+-- product when `source` is NULL (after the refresher missed notifications). Runs as the owner
+-- because the refresher has no workspace to satisfy Row Level Security with.
+CREATE OR REPLACE FUNCTION refresh_product_search_vectors(source text, source_id uuid)
+RETURNS integer AS $$
+DECLARE
+  refreshed integer;
+BEGIN
+  UPDATE products p SET search_vector = product_search_vector(p)
+  WHERE source IS NULL
+     OR (source = 'product_categories' AND p.category_id = source_id)
+     OR (source = 'contacts' AND p.supplier_id = source_id);
+  GET DIAGNOSTICS refreshed = ROW_COUNT;
+  RETURN refreshed;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER SET search_path = public;
+
+UPDATE contacts c SET search_vector = contact_search_vector(c);
+UPDATE products p SET search_vector = product_search_vector(p);
+
+CREATE INDEX IF NOT EXISTS idx_contacts_search_vector ON contacts USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS idx_products_search_vector ON products USING GIN (search_vector);
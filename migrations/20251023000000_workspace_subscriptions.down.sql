@@ -0,0 +1,4 @@
+-- Down migration: workspace_subscriptions
+DROP TRIGGER IF EXISTS update_workspace_subscriptions_updated_at ON workspace_subscriptions;
+DROP TABLE IF EXISTS workspace_subscriptions;
+DROP TYPE IF EXISTS subscription_status;
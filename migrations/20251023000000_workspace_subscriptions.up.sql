@@ -0,0 +1,28 @@
+-- Up migration: workspace_subscriptions
+-- The Stripe customer and subscription of a workspace. A row is created when the first
+-- checkout session is started and kept up to date by the Stripe webhook, which has no user
+-- context; the table is therefore only accessed by the server and has no RLS policies.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'subscription_status') THEN
+        CREATE TYPE subscription_status AS ENUM ('active', 'past_due', 'canceled');
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS workspace_subscriptions (
+    workspace_id UUID PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+    stripe_customer_id TEXT NOT NULL UNIQUE,
+    stripe_subscription_id TEXT,
+    -- NULL until Stripe reports the first subscription of the customer
+    status subscription_status,
+    current_period_end TIMESTAMPTZ,
+    -- Creation time of the last applied Stripe event; older events delivered late are ignored
+    last_event_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_workspace_subscriptions_updated_at
+BEFORE UPDATE ON workspace_subscriptions
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
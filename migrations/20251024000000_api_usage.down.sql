@@ -0,0 +1,2 @@
+-- Down migration: api_usage
+DROP TABLE IF EXISTS api_usage;
@@ -0,0 +1,15 @@
+-- Up migration: api_usage
+-- API requests per workspace, user, route and day. The server counts requests in memory and
+-- adds them here in batches (see modules::usage::usage_meter), so rows lag behind by up to
+-- one flush interval.
+CREATE TABLE IF NOT EXISTS api_usage (
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- The route template, e.g. /api/v1/contacts/:id
+    route TEXT NOT NULL,
+    day DATE NOT NULL,
+    request_count BIGINT NOT NULL DEFAULT 0,
+    request_bytes BIGINT NOT NULL DEFAULT 0,
+    response_bytes BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (workspace_id, day, user_id, route)
+);
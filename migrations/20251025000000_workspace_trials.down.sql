@@ -0,0 +1,4 @@
+-- Down migration: workspace_trials
+DROP TRIGGER IF EXISTS start_workspace_trial ON workspaces;
+DROP FUNCTION IF EXISTS start_workspace_trial();
+DROP TABLE IF EXISTS workspace_trials;
@@ -0,0 +1,36 @@
+-- Up migration: workspace_trials
+-- The trial period of each workspace. Every new workspace starts a 14-day trial; once it ends
+-- without an active or past due subscription the workspace is read-only (see
+-- middleware::trial_middleware). The notice columns record which notifications were sent, so
+-- each is sent once even with several server instances.
+CREATE TABLE IF NOT EXISTS workspace_trials (
+    workspace_id UUID PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+    started_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    warning_sent_at TIMESTAMPTZ,
+    expiry_sent_at TIMESTAMPTZ,
+    CHECK (ends_at >= started_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_workspace_trials_ends_at ON workspace_trials(ends_at);
+
+CREATE OR REPLACE FUNCTION start_workspace_trial()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO workspace_trials (workspace_id, started_at, ends_at)
+    VALUES (NEW.id, NEW.created_at, NEW.created_at + INTERVAL '14 days')
+    ON CONFLICT (workspace_id) DO NOTHING;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER start_workspace_trial
+AFTER INSERT ON workspaces
+FOR EACH ROW
+EXECUTE FUNCTION start_workspace_trial();
+
+-- Existing workspaces get a full trial from now on rather than expiring on deployment
+INSERT INTO workspace_trials (workspace_id, started_at, ends_at)
+SELECT id, created_at, GREATEST(created_at, NOW()) + INTERVAL '14 days'
+FROM workspaces
+ON CONFLICT (workspace_id) DO NOTHING;
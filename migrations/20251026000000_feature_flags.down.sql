@@ -0,0 +1,3 @@
+-- Down migration: feature_flags
+DROP TABLE IF EXISTS workspace_feature_flags;
+DROP TABLE IF EXISTS feature_flags;
@@ -0,0 +1,32 @@
+-- Up migration: feature_flags
+-- Flags gating experimental features. `enabled` applies to every workspace without an override
+-- in workspace_feature_flags. Both tables are managed by the server's admin API and have no RLS
+-- policies.
+CREATE TABLE IF NOT EXISTS feature_flags (
+    key TEXT PRIMARY KEY CHECK (key ~ '^[a-z0-9_]{1,64}$'),
+    description TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS workspace_feature_flags (
+    flag_key TEXT NOT NULL REFERENCES feature_flags(key) ON DELETE CASCADE,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    enabled BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (flag_key, workspace_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_workspace_feature_flags_workspace_id ON workspace_feature_flags(workspace_id);
+
+CREATE TRIGGER update_feature_flags_updated_at
+BEFORE UPDATE ON feature_flags
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TRIGGER update_workspace_feature_flags_updated_at
+BEFORE UPDATE ON workspace_feature_flags
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
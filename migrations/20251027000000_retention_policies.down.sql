@@ -0,0 +1,4 @@
+-- Down migration: retention_policies
+DROP TABLE IF EXISTS retention_purges;
+DROP TABLE IF EXISTS retention_policies;
+DROP TYPE IF EXISTS retention_category;
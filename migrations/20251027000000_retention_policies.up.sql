@@ -0,0 +1,39 @@
+-- Up migration: retention_policies
+-- How long a workspace keeps each category of data, and a log of what the scheduled purge
+-- removed (see modules::retention). Categories without a policy are kept forever. Written by
+-- workspace admins through the API and by the purge job, which has no user context; neither
+-- table has RLS policies.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'retention_category') THEN
+        CREATE TYPE retention_category AS ENUM ('api_usage');
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS retention_policies (
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    category retention_category NOT NULL,
+    retain_days INTEGER NOT NULL CHECK (retain_days > 0),
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (workspace_id, category)
+);
+
+CREATE TRIGGER update_retention_policies_updated_at
+BEFORE UPDATE ON retention_policies
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+-- One row per purge that removed anything
+CREATE TABLE IF NOT EXISTS retention_purges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    category retention_category NOT NULL,
+    -- Data older than this was removed
+    cutoff TIMESTAMPTZ NOT NULL,
+    rows_removed BIGINT NOT NULL,
+    purged_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_retention_purges_workspace_id ON retention_purges(workspace_id, purged_at DESC);
@@ -0,0 +1,3 @@
+-- Down migration: superadmins
+DROP INDEX IF EXISTS idx_users_is_superadmin;
+ALTER TABLE users DROP COLUMN IF EXISTS is_superadmin;
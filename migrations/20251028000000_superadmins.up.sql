@@ -0,0 +1,6 @@
+-- Up migration: superadmins
+-- Platform administrators, independent of workspace roles: they manage the platform across
+-- every workspace (see helper::RequireSuperadmin). Granted with the `grant-superadmin` subcommand.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_superadmin BOOLEAN NOT NULL DEFAULT false;
+
+CREATE INDEX IF NOT EXISTS idx_users_is_superadmin ON users(id) WHERE is_superadmin;
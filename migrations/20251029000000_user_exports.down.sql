@@ -0,0 +1,3 @@
+-- Down migration: user_exports
+DROP TABLE IF EXISTS user_exports;
+DROP TYPE IF EXISTS user_export_status;
@@ -0,0 +1,28 @@
+-- Up migration: user_exports
+-- Personal data exports requested by users (see modules::user_export). The archive is built in
+-- the background and kept as JSON until `expires_at`; it is only ever read by its own user, so
+-- the table has no RLS policies.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'user_export_status') THEN
+        CREATE TYPE user_export_status AS ENUM ('pending', 'ready', 'failed');
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS user_exports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status user_export_status NOT NULL DEFAULT 'pending',
+    archive JSONB,
+    -- Why a failed export failed
+    error TEXT,
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    -- When a ready archive is removed
+    expires_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_exports_user_id ON user_exports(user_id, requested_at DESC);
+
+-- A user has at most one export being built
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_exports_one_pending ON user_exports(user_id) WHERE status = 'pending';
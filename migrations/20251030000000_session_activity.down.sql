@@ -0,0 +1,2 @@
+-- Down migration: session_activity
+DROP TABLE IF EXISTS session_activity;
@@ -0,0 +1,11 @@
+-- Up migration: session_activity
+-- When each access token was last used, to end sessions idle for longer than
+-- SESSION_IDLE_TIMEOUT_SECS (see modules::auth::session_activity). Like revoked_tokens, rows are
+-- only needed until the token expires and are purged after that.
+CREATE TABLE IF NOT EXISTS session_activity (
+    jti UUID PRIMARY KEY,
+    last_active_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_activity_expires_at ON session_activity(expires_at);
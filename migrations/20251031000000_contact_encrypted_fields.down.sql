@@ -0,0 +1,4 @@
+-- Down migration: contact_encrypted_fields
+ALTER TABLE contacts
+    DROP COLUMN IF EXISTS bank_account,
+    DROP COLUMN IF EXISTS tax_id;
@@ -0,0 +1,6 @@
+-- Up migration: contact_encrypted_fields
+-- Sensitive contact details, encrypted by the application (see utils::field_encryption): the
+-- columns only ever hold ciphertext of the form enc:v1:{key_id}:{base64}.
+ALTER TABLE contacts
+    ADD COLUMN IF NOT EXISTS tax_id TEXT,
+    ADD COLUMN IF NOT EXISTS bank_account TEXT;
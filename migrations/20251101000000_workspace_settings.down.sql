@@ -0,0 +1,2 @@
+-- Down migration: workspace_settings
+DROP TABLE IF EXISTS workspace_settings;
@@ -0,0 +1,16 @@
+-- Up migration: workspace_settings
+-- Settings of a workspace managed by its admins (see modules::workspace_settings), starting with
+-- the IP ranges the workspace may be used from. Workspaces without a row use the defaults. Read
+-- by the middleware of every workspace request before handlers run, so it has no RLS policies.
+CREATE TABLE IF NOT EXISTS workspace_settings (
+    workspace_id UUID PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+    allowed_ip_ranges CIDR[] NOT NULL DEFAULT '{}',
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_workspace_settings_updated_at
+BEFORE UPDATE ON workspace_settings
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,3 @@
+-- Down migration: audit_events
+DROP TABLE IF EXISTS audit_events;
+DROP TYPE IF EXISTS audit_priority;
@@ -0,0 +1,25 @@
+-- Up migration: audit_events
+-- Security-relevant changes made in a workspace, such as members added or removed and roles
+-- changed (see modules::audit). Rows are only inserted, never updated. Listed by workspace
+-- admins through the API; no RLS policies.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'audit_priority') THEN
+        CREATE TYPE audit_priority AS ENUM ('normal', 'high');
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS audit_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    -- e.g. member.added, member.removed, member.role_changed
+    action VARCHAR(100) NOT NULL,
+    priority audit_priority NOT NULL DEFAULT 'normal',
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    target_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    -- The time of the change itself, also when several are made in one transaction
+    created_at TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_workspace_id ON audit_events(workspace_id, created_at DESC);
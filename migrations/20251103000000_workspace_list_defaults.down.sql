@@ -0,0 +1,6 @@
+-- Down migration: workspace_list_defaults
+ALTER TABLE workspace_settings
+    DROP COLUMN IF EXISTS default_page_size,
+    DROP COLUMN IF EXISTS max_page_size,
+    DROP COLUMN IF EXISTS default_sort_by,
+    DROP COLUMN IF EXISTS default_sort_order;
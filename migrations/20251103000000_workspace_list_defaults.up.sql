@@ -0,0 +1,9 @@
+-- Up migration: workspace_list_defaults
+-- Defaults applied by list endpoints when the client omits `limit`, `sort_by` or `sort_order`,
+-- and a cap on `limit` below the global maximum (see helper::Pagination). NULL keeps the
+-- built-in default.
+ALTER TABLE workspace_settings
+    ADD COLUMN IF NOT EXISTS default_page_size INTEGER CHECK (default_page_size BETWEEN 1 AND 100),
+    ADD COLUMN IF NOT EXISTS max_page_size INTEGER CHECK (max_page_size BETWEEN 1 AND 100),
+    ADD COLUMN IF NOT EXISTS default_sort_by VARCHAR(50),
+    ADD COLUMN IF NOT EXISTS default_sort_order VARCHAR(4) CHECK (default_sort_order IN ('asc', 'desc'));
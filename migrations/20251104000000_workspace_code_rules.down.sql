@@ -0,0 +1,3 @@
+-- Down migration: workspace_code_rules
+ALTER TABLE workspace_settings
+    DROP COLUMN IF EXISTS code_rules;
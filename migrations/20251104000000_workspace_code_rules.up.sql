@@ -0,0 +1,5 @@
+-- Up migration: workspace_code_rules
+-- Code formats chosen by the workspace, keyed by the table of the entity (see
+-- utils::code_generator::CodeRules). Entities without rules keep their built-in format.
+ALTER TABLE workspace_settings
+    ADD COLUMN IF NOT EXISTS code_rules JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,4 @@
+-- Down migration: report_schedules
+DROP TABLE IF EXISTS report_schedules;
+DROP TYPE IF EXISTS report_frequency;
+DROP TYPE IF EXISTS report_kind;
@@ -0,0 +1,42 @@
+-- Up migration: report_schedules
+-- Reports generated on a schedule and delivered by email and/or webhook (see modules::reports).
+-- `next_run_at` is advanced by the instance claiming a run, so each run is delivered once even
+-- with several instances polling.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'report_kind') THEN
+        CREATE TYPE report_kind AS ENUM ('low_stock', 'stock_value');
+    END IF;
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'report_frequency') THEN
+        CREATE TYPE report_frequency AS ENUM ('daily', 'weekly', 'monthly');
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS report_schedules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    report report_kind NOT NULL,
+    frequency report_frequency NOT NULL,
+    recipients TEXT[] NOT NULL DEFAULT '{}',
+    webhook_url TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_run_at TIMESTAMPTZ,
+    -- Why the last run failed to be generated or delivered, NULL when it succeeded
+    last_error TEXT,
+    -- Reports are generated with the access of their creator
+    created_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT report_schedules_destination CHECK (cardinality(recipients) > 0 OR webhook_url IS NOT NULL)
+);
+
+CREATE TRIGGER update_report_schedules_updated_at
+BEFORE UPDATE ON report_schedules
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+CREATE INDEX IF NOT EXISTS idx_report_schedules_workspace_id ON report_schedules(workspace_id);
+CREATE INDEX IF NOT EXISTS idx_report_schedules_due ON report_schedules(next_run_at) WHERE is_active;
@@ -0,0 +1,3 @@
+-- Down migration: email_templates
+DROP TABLE IF EXISTS email_templates;
+DROP TYPE IF EXISTS email_template_kind;
@@ -0,0 +1,27 @@
+-- Up migration: email_templates
+-- The subject and body of the emails a workspace sends, in Handlebars syntax (see
+-- modules::email_templates). A workspace customizes at most one template per kind of email.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'email_template_kind') THEN
+        CREATE TYPE email_template_kind AS ENUM ('invitation', 'invoice', 'reminder');
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS email_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    kind email_template_kind NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT email_templates_workspace_id_kind_key UNIQUE (workspace_id, kind)
+);
+
+CREATE TRIGGER update_email_templates_updated_at
+BEFORE UPDATE ON email_templates
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,3 @@
+-- Down migration: print_templates
+DROP TABLE IF EXISTS print_templates;
+DROP TYPE IF EXISTS print_entity;
@@ -0,0 +1,24 @@
+-- Up migration: print_templates
+-- HTML templates a workspace prints its records with (see modules::rendering). Entities without a
+-- row are printed with the built-in template.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'print_entity') THEN
+        CREATE TYPE print_entity AS ENUM ('contacts', 'products');
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS print_templates (
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    entity print_entity NOT NULL,
+    html TEXT NOT NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (workspace_id, entity)
+);
+
+CREATE TRIGGER update_print_templates_updated_at
+BEFORE UPDATE ON print_templates
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
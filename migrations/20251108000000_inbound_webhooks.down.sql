@@ -0,0 +1,4 @@
+-- Down migration: inbound_webhooks
+DROP TABLE IF EXISTS inbound_events;
+DROP TABLE IF EXISTS inbound_integrations;
+DROP TYPE IF EXISTS inbound_event_status;
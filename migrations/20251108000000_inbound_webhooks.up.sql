@@ -0,0 +1,48 @@
+-- Up migration: inbound_webhooks
+-- Third-party systems (e.g. e-commerce platforms) post to /api/v1/inbound/{key}, signing each
+-- payload with the secret of their integration (see modules::inbound). Accepted payloads wait in
+-- inbound_events until they are mapped into records of the workspace.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'inbound_event_status') THEN
+        CREATE TYPE inbound_event_status AS ENUM ('pending', 'processed', 'failed');
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS inbound_integrations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    source VARCHAR(50) NOT NULL,
+    -- Public part of the endpoint URL
+    key VARCHAR(64) NOT NULL UNIQUE,
+    -- Signing secret, encrypted with the field encryption keys
+    secret TEXT NOT NULL,
+    last_received_at TIMESTAMPTZ,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_inbound_integrations_workspace_id ON inbound_integrations(workspace_id);
+
+CREATE TRIGGER update_inbound_integrations_updated_at
+BEFORE UPDATE ON inbound_integrations
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE IF NOT EXISTS inbound_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    integration_id UUID NOT NULL REFERENCES inbound_integrations(id) ON DELETE CASCADE,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    payload JSONB NOT NULL,
+    status inbound_event_status NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    processed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_inbound_events_integration_id ON inbound_events(integration_id, received_at DESC);
+-- Consumers claim the oldest pending events first
+CREATE INDEX IF NOT EXISTS idx_inbound_events_pending ON inbound_events(received_at) WHERE status = 'pending';
@@ -0,0 +1,4 @@
+-- Down migration: connectors
+DROP TABLE IF EXISTS connector_orders;
+DROP TABLE IF EXISTS connector_syncs;
+ALTER TABLE workspace_settings DROP COLUMN IF EXISTS connectors;
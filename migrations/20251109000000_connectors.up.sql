@@ -0,0 +1,45 @@
+-- Up migration: connectors
+-- Online stores (Shopify, WooCommerce) a workspace syncs with (see modules::connectors). Their
+-- settings are kept with the other workspace settings, keyed by platform, the credentials
+-- encrypted with the field encryption keys.
+ALTER TABLE workspace_settings ADD COLUMN IF NOT EXISTS connectors JSONB NOT NULL DEFAULT '{}';
+
+-- Where each connector is in its syncs, claimed by moving next_sync_at
+CREATE TABLE IF NOT EXISTS connector_syncs (
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    connector VARCHAR(32) NOT NULL,
+    next_sync_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_synced_at TIMESTAMPTZ,
+    -- Products changed since are pushed with the next sync, all of them while NULL
+    stock_synced_at TIMESTAMPTZ,
+    -- Orders changed since are pulled with the next sync
+    orders_synced_at TIMESTAMPTZ,
+    last_error TEXT,
+    PRIMARY KEY (workspace_id, connector)
+);
+
+CREATE INDEX IF NOT EXISTS idx_connector_syncs_next_sync_at ON connector_syncs(next_sync_at);
+
+-- Orders pulled from the stores, kept as the store sent them until they are mapped into records
+CREATE TABLE IF NOT EXISTS connector_orders (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    connector VARCHAR(32) NOT NULL,
+    external_id VARCHAR(64) NOT NULL,
+    number VARCHAR(64),
+    status VARCHAR(50),
+    currency VARCHAR(3),
+    total NUMERIC(15, 2),
+    ordered_at TIMESTAMPTZ,
+    payload JSONB NOT NULL,
+    pulled_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (workspace_id, connector, external_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_connector_orders_workspace_id ON connector_orders(workspace_id, ordered_at DESC);
+
+CREATE TRIGGER update_connector_orders_updated_at
+BEFORE UPDATE ON connector_orders
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,2 @@
+-- Down migration: exchange_rates
+DROP TABLE IF EXISTS exchange_rates;
@@ -0,0 +1,26 @@
+-- Up migration: exchange_rates
+-- Daily exchange rates (see modules::currencies): one unit of `currency` is worth `rate` of
+-- `base`. Rates fetched from the provider have no workspace; a workspace overrides the rate of a
+-- day with a row of its own.
+CREATE TABLE IF NOT EXISTS exchange_rates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID REFERENCES workspaces(id) ON DELETE CASCADE,
+    base CHAR(3) NOT NULL,
+    currency CHAR(3) NOT NULL,
+    rate_date DATE NOT NULL,
+    rate NUMERIC(24, 10) NOT NULL CHECK (rate > 0),
+    -- The provider, or `manual` for overrides
+    source VARCHAR(32) NOT NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT exchange_rates_unique UNIQUE NULLS NOT DISTINCT (workspace_id, base, currency, rate_date)
+);
+
+-- Rates as of a date are the latest on or before it
+CREATE INDEX IF NOT EXISTS idx_exchange_rates_lookup ON exchange_rates(base, currency, rate_date DESC);
+
+CREATE TRIGGER update_exchange_rates_updated_at
+BEFORE UPDATE ON exchange_rates
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,2 @@
+-- Down migration: geo_lookups
+DROP TABLE IF EXISTS geo_lookups;
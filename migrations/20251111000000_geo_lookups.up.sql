@@ -0,0 +1,9 @@
+-- Up migration: geo_lookups
+-- Address lookups each workspace sent to the geocoding provider per day (see modules::geo),
+-- counted against its daily quota. Answers served from the cache are not counted.
+CREATE TABLE IF NOT EXISTS geo_lookups (
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    lookups INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (workspace_id, day)
+);
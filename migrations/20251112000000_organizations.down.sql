@@ -0,0 +1,88 @@
+-- Down migration: organizations
+-- Back to the policies of the workspaces, contacts, products, departments, projects, discounts
+-- and taxes migrations.
+DROP POLICY IF EXISTS taxes_policy ON taxes;
+CREATE POLICY taxes_policy ON taxes
+    FOR ALL
+    USING (workspace_id = (SELECT current_setting('app.current_workspace_id', true)::UUID))
+    WITH CHECK (workspace_id = (SELECT current_setting('app.current_workspace_id', true)::UUID));
+
+DO $$
+DECLARE
+    target TEXT;
+BEGIN
+    FOREACH target IN ARRAY ARRAY['contacts', 'product_categories', 'products', 'departments', 'projects', 'discounts'] LOOP
+        EXECUTE format('DROP POLICY IF EXISTS %1$s_select_policy ON %1$I', target);
+        EXECUTE format('CREATE POLICY %1$s_select_policy ON %1$I FOR SELECT
+            USING ( has_workspace_access(workspace_id, ARRAY[''admin'', ''member'', ''viewer'']) )', target);
+        EXECUTE format('DROP POLICY IF EXISTS %1$s_insert_policy ON %1$I', target);
+        EXECUTE format('CREATE POLICY %1$s_insert_policy ON %1$I FOR INSERT
+            WITH CHECK ( has_workspace_access(workspace_id, ARRAY[''admin'', ''member'']) )', target);
+        EXECUTE format('DROP POLICY IF EXISTS %1$s_update_policy ON %1$I', target);
+        EXECUTE format('CREATE POLICY %1$s_update_policy ON %1$I FOR UPDATE
+            USING ( has_workspace_access(workspace_id, ARRAY[''admin'', ''member'']) )
+            WITH CHECK ( has_workspace_access(workspace_id, ARRAY[''admin'', ''member'']) )', target);
+        EXECUTE format('DROP POLICY IF EXISTS %1$s_delete_policy ON %1$I', target);
+        EXECUTE format('CREATE POLICY %1$s_delete_policy ON %1$I FOR DELETE
+            USING ( has_workspace_access(workspace_id, ARRAY[''admin'']) )', target);
+    END LOOP;
+END $$;
+
+DROP POLICY IF EXISTS workspace_users_modify_policy ON workspace_users;
+CREATE POLICY workspace_users_modify_policy ON workspace_users
+    FOR ALL
+    USING (
+        EXISTS (
+            SELECT 1 FROM workspace_users wu
+            WHERE wu.workspace_id = workspace_users.workspace_id
+            AND wu.user_id = current_setting('app.current_user_id', true)::UUID
+            AND wu.role = 'admin'
+        )
+    )
+    WITH CHECK (
+        EXISTS (
+            SELECT 1 FROM workspace_users wu
+            WHERE wu.workspace_id = workspace_users.workspace_id
+            AND wu.user_id = current_setting('app.current_user_id', true)::UUID
+            AND wu.role = 'admin'
+        )
+    );
+
+DROP POLICY IF EXISTS workspace_users_select_policy ON workspace_users;
+CREATE POLICY workspace_users_select_policy ON workspace_users
+    FOR SELECT
+    USING (
+        has_workspace_access(workspace_id, ARRAY['admin'])
+        OR
+        user_id = current_setting('app.current_user_id', true)::UUID
+    );
+
+DROP POLICY IF EXISTS workspaces_update_policy ON workspaces;
+CREATE POLICY workspaces_update_policy ON workspaces
+    FOR UPDATE
+    USING (
+        owner_id = current_setting('app.current_user_id', true)::UUID
+        OR
+        EXISTS (
+            SELECT 1 FROM workspace_users
+            WHERE workspace_id = id
+            AND user_id = current_setting('app.current_user_id', true)::UUID
+            AND role = 'admin'
+        )
+    );
+
+DROP POLICY IF EXISTS workspaces_select_policy ON workspaces;
+CREATE POLICY workspaces_select_policy ON workspaces
+    FOR SELECT
+    USING (id IN (
+        SELECT workspace_id FROM workspace_users WHERE user_id = current_setting('app.current_user_id', true)::UUID
+    ));
+
+DROP FUNCTION IF EXISTS has_workspace_grant(UUID, TEXT[]);
+DROP FUNCTION IF EXISTS current_workspace_role(UUID);
+DROP FUNCTION IF EXISTS billed_subscription_status(UUID);
+DROP VIEW IF EXISTS workspace_access;
+ALTER TABLE workspaces DROP COLUMN IF EXISTS organization_id;
+DROP TABLE IF EXISTS organization_members;
+DROP TABLE IF EXISTS organizations;
+DROP TYPE IF EXISTS organization_role;
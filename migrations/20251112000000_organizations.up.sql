@@ -0,0 +1,165 @@
+-- Up migration: organizations
+-- Organizations group the workspaces of a company (see modules::organizations). Members of an
+-- organization get a role in each of its workspaces without being added to them one by one, and
+-- the subscription of its billing workspace pays for all of them.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'organization_role') THEN
+        CREATE TYPE organization_role AS ENUM ('admin', 'member');
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS organizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    owner_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- The workspace whose subscription covers every workspace of the organization
+    billing_workspace_id UUID,
+    created_by UUID REFERENCES users(id),
+    updated_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_organizations_owner_id ON organizations(owner_id);
+
+CREATE TRIGGER update_organizations_updated_at
+BEFORE UPDATE ON organizations
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+-- Admins manage the organization and are admins of each of its workspaces; members get
+-- `workspace_role` in each of them.
+CREATE TABLE IF NOT EXISTS organization_members (
+    organization_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role organization_role NOT NULL,
+    workspace_role workspace_role NOT NULL DEFAULT 'viewer',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (organization_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_organization_members_user_id ON organization_members(user_id);
+
+CREATE TRIGGER update_organization_members_updated_at
+BEFORE UPDATE ON organization_members
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE workspaces ADD COLUMN IF NOT EXISTS organization_id UUID REFERENCES organizations(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_workspaces_organization_id ON workspaces(organization_id);
+
+ALTER TABLE organizations
+    ADD CONSTRAINT organizations_billing_workspace_fk
+    FOREIGN KEY (billing_workspace_id) REFERENCES workspaces(id) ON DELETE SET NULL;
+
+-- The role of each user in each workspace: the highest of the role they were given in the
+-- workspace and the one their organization membership grants (enum order: admin first).
+CREATE OR REPLACE VIEW workspace_access AS
+SELECT workspace_id, user_id, MIN(role) AS role
+FROM (
+    SELECT workspace_id, user_id, role
+    FROM workspace_users
+    UNION ALL
+    SELECT w.id, om.user_id, CASE om.role WHEN 'admin' THEN 'admin'::workspace_role ELSE om.workspace_role END
+    FROM workspaces w
+    JOIN organization_members om ON om.organization_id = w.organization_id
+) grants
+GROUP BY workspace_id, user_id;
+
+-- The status of the subscription paying for a workspace: its own, or that of its organization's
+-- billing workspace, whichever is in the better standing (enum order: active first).
+CREATE OR REPLACE FUNCTION billed_subscription_status(target_workspace_id UUID)
+RETURNS subscription_status
+LANGUAGE sql STABLE AS $$
+    SELECT s.status
+    FROM workspace_subscriptions s
+    WHERE s.workspace_id = target_workspace_id
+       OR s.workspace_id = (
+           SELECT o.billing_workspace_id
+           FROM workspaces w
+           JOIN organizations o ON o.id = w.organization_id
+           WHERE w.id = target_workspace_id
+       )
+    ORDER BY s.status NULLS LAST, s.workspace_id = target_workspace_id DESC
+    LIMIT 1
+$$;
+
+-- The row level security policies grant access by the role in `workspace_access` rather than
+-- `workspace_users`, so that members of an organization see the data of its workspaces. The
+-- lookup runs as the owner of the function: policies on `workspace_users` read the view too.
+CREATE OR REPLACE FUNCTION current_workspace_role(target_workspace_id UUID)
+RETURNS TEXT
+LANGUAGE sql STABLE SECURITY DEFINER SET search_path = public AS $$
+    SELECT role::text
+    FROM workspace_access
+    WHERE workspace_id = target_workspace_id
+      AND user_id = NULLIF(current_setting('app.current_user_id', true), '')::UUID
+$$;
+
+-- Like `has_workspace_access`, with the role looked up in `workspace_access` instead of trusted
+-- from `app.current_user_role`.
+CREATE OR REPLACE FUNCTION has_workspace_grant(record_workspace_id UUID, allowed_roles TEXT[])
+RETURNS BOOLEAN
+LANGUAGE sql STABLE AS $$
+    SELECT record_workspace_id = NULLIF(current_setting('app.current_workspace_id', true), '')::UUID
+       AND COALESCE(current_workspace_role(record_workspace_id) = ANY(allowed_roles), false)
+$$;
+
+DROP POLICY IF EXISTS workspaces_select_policy ON workspaces;
+CREATE POLICY workspaces_select_policy ON workspaces
+    FOR SELECT
+    USING ( current_workspace_role(id) IS NOT NULL );
+
+DROP POLICY IF EXISTS workspaces_update_policy ON workspaces;
+CREATE POLICY workspaces_update_policy ON workspaces
+    FOR UPDATE
+    USING (
+        owner_id = current_setting('app.current_user_id', true)::UUID
+        OR
+        current_workspace_role(id) = 'admin'
+    );
+
+DROP POLICY IF EXISTS workspace_users_select_policy ON workspace_users;
+CREATE POLICY workspace_users_select_policy ON workspace_users
+    FOR SELECT
+    USING (
+        current_workspace_role(workspace_id) = 'admin'
+        OR
+        user_id = current_setting('app.current_user_id', true)::UUID
+    );
+
+DROP POLICY IF EXISTS workspace_users_modify_policy ON workspace_users;
+CREATE POLICY workspace_users_modify_policy ON workspace_users
+    FOR ALL
+    USING ( current_workspace_role(workspace_id) = 'admin' )
+    WITH CHECK ( current_workspace_role(workspace_id) = 'admin' );
+
+DO $$
+DECLARE
+    target TEXT;
+BEGIN
+    FOREACH target IN ARRAY ARRAY['contacts', 'product_categories', 'products', 'departments', 'projects', 'discounts'] LOOP
+        EXECUTE format('DROP POLICY IF EXISTS %1$s_select_policy ON %1$I', target);
+        EXECUTE format('CREATE POLICY %1$s_select_policy ON %1$I FOR SELECT
+            USING ( has_workspace_grant(workspace_id, ARRAY[''admin'', ''member'', ''viewer'']) )', target);
+        EXECUTE format('DROP POLICY IF EXISTS %1$s_insert_policy ON %1$I', target);
+        EXECUTE format('CREATE POLICY %1$s_insert_policy ON %1$I FOR INSERT
+            WITH CHECK ( has_workspace_grant(workspace_id, ARRAY[''admin'', ''member'']) )', target);
+        EXECUTE format('DROP POLICY IF EXISTS %1$s_update_policy ON %1$I', target);
+        EXECUTE format('CREATE POLICY %1$s_update_policy ON %1$I FOR UPDATE
+            USING ( has_workspace_grant(workspace_id, ARRAY[''admin'', ''member'']) )
+            WITH CHECK ( has_workspace_grant(workspace_id, ARRAY[''admin'', ''member'']) )', target);
+        EXECUTE format('DROP POLICY IF EXISTS %1$s_delete_policy ON %1$I', target);
+        EXECUTE format('CREATE POLICY %1$s_delete_policy ON %1$I FOR DELETE
+            USING ( has_workspace_grant(workspace_id, ARRAY[''admin'']) )', target);
+    END LOOP;
+END $$;
+
+DROP POLICY IF EXISTS taxes_policy ON taxes;
+CREATE POLICY taxes_policy ON taxes
+    FOR ALL
+    USING ( has_workspace_grant(workspace_id, ARRAY['admin', 'member', 'viewer']) )
+    WITH CHECK ( has_workspace_grant(workspace_id, ARRAY['admin', 'member', 'viewer']) );
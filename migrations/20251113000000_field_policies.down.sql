@@ -0,0 +1,3 @@
+-- Down migration: field_policies
+ALTER TABLE workspace_settings
+    DROP COLUMN IF EXISTS field_policies;
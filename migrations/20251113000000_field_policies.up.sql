@@ -0,0 +1,5 @@
+-- Up migration: field_policies
+-- Fields of contacts and products hidden from or read-only for the members and viewers of the
+-- workspace, keyed by entity and field (see modules::workspace_settings::field_policy_service).
+ALTER TABLE workspace_settings
+    ADD COLUMN IF NOT EXISTS field_policies JSONB NOT NULL DEFAULT '{}';
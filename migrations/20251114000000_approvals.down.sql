@@ -0,0 +1,6 @@
+-- Down migration: approvals
+DROP TABLE IF EXISTS approval_requests;
+DROP TYPE IF EXISTS approval_status;
+DROP TYPE IF EXISTS approval_action;
+ALTER TABLE workspace_settings
+    DROP COLUMN IF EXISTS approval_rules;
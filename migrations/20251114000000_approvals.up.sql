@@ -0,0 +1,35 @@
+-- Up migration: approvals
+-- Changes that members of a workspace make only once an admin accepts them (see
+-- modules::approvals). The rules are part of the workspace settings; each change waiting for a
+-- decision is an approval request holding what to apply.
+ALTER TABLE workspace_settings
+    ADD COLUMN IF NOT EXISTS approval_rules JSONB NOT NULL DEFAULT '{}';
+
+CREATE TYPE approval_action AS ENUM ('price_change', 'stock_write_off', 'deletion');
+CREATE TYPE approval_status AS ENUM ('pending', 'approved', 'rejected', 'cancelled');
+
+CREATE TABLE IF NOT EXISTS approval_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    action approval_action NOT NULL,
+    -- `contacts` or `products`
+    entity TEXT NOT NULL,
+    record_id UUID NOT NULL,
+    -- Merge patch applied to the record once approved, NULL for deletions
+    changes JSONB,
+    summary TEXT NOT NULL,
+    status approval_status NOT NULL DEFAULT 'pending',
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    decided_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    decided_at TIMESTAMPTZ,
+    decision_note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_approval_requests_workspace_status ON approval_requests (workspace_id, status, created_at DESC);
+
+CREATE TRIGGER update_approval_requests_updated_at
+BEFORE UPDATE ON approval_requests
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,5 @@
+-- Down migration: product_status
+DROP INDEX IF EXISTS idx_products_workspace_status;
+ALTER TABLE products
+    DROP COLUMN IF EXISTS status;
+DROP TYPE IF EXISTS product_status;
@@ -0,0 +1,10 @@
+-- Up migration: product_status
+-- The lifecycle of a product: drafts (such as products imported in bulk) are kept out of the
+-- sales flows until published, discontinued products are no longer sold. Existing products are
+-- published.
+CREATE TYPE product_status AS ENUM ('draft', 'active', 'discontinued');
+
+ALTER TABLE products
+    ADD COLUMN IF NOT EXISTS status product_status NOT NULL DEFAULT 'active';
+
+CREATE INDEX IF NOT EXISTS idx_products_workspace_status ON products (workspace_id, status);
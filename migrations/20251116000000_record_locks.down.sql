@@ -0,0 +1,2 @@
+-- Down migration: record_locks
+DROP TABLE IF EXISTS record_locks;
@@ -0,0 +1,13 @@
+-- Up migration: record_locks
+-- Soft locks taken by the member editing a record (see modules::record_locks). A lock only warns
+-- other members; it does not block writes. Expired rows are taken over by the next lock.
+CREATE TABLE IF NOT EXISTS record_locks (
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    -- `contacts` or `products`
+    entity TEXT NOT NULL,
+    record_id UUID NOT NULL,
+    locked_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    locked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (workspace_id, entity, record_id)
+);
@@ -0,0 +1,3 @@
+-- Down migration: operations
+DROP TABLE IF EXISTS operations;
+DROP TYPE IF EXISTS operation_status;
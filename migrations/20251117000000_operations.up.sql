@@ -0,0 +1,28 @@
+-- Up migration: operations
+-- Long requests run in the background (see modules::operations). The result is kept as JSON
+-- once the operation succeeds; operations are only read by the member who started them, so the
+-- table has no RLS policies.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'operation_status') THEN
+        CREATE TYPE operation_status AS ENUM ('running', 'succeeded', 'failed');
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS operations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- What runs, e.g. `products.import` or `contacts.export`
+    kind TEXT NOT NULL,
+    status operation_status NOT NULL DEFAULT 'running',
+    progress SMALLINT NOT NULL DEFAULT 0 CHECK (progress BETWEEN 0 AND 100),
+    result JSONB,
+    -- Why a failed operation failed
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_operations_workspace_user ON operations(workspace_id, user_id, created_at DESC);
@@ -0,0 +1,3 @@
+-- Down migration: export_profiles
+DROP TABLE IF EXISTS export_profiles;
+DROP TYPE IF EXISTS export_entity;
@@ -0,0 +1,32 @@
+-- Up migration: export_profiles
+-- The export layouts members save for themselves (see modules::exports): the columns of a CSV
+-- export in order, their headers, the date format and the filters of the records exported.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'export_entity') THEN
+        CREATE TYPE export_entity AS ENUM ('contacts', 'products');
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS export_profiles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    entity export_entity NOT NULL,
+    columns TEXT[] NOT NULL,
+    -- Column name to header, for the columns not headed by their name
+    headers JSONB NOT NULL DEFAULT '{}',
+    -- strftime format of the dates, RFC 3339 when unset
+    date_format TEXT,
+    -- The query parameters of the list endpoint of `entity`
+    filters JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (workspace_id, user_id, name)
+);
+
+CREATE TRIGGER update_export_profiles_updated_at
+BEFORE UPDATE ON export_profiles
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,4 @@
+-- Down migration: product_revisions
+DROP TRIGGER IF EXISTS record_product_revisions ON products;
+DROP FUNCTION IF EXISTS record_product_revision();
+DROP TABLE IF EXISTS product_revisions;
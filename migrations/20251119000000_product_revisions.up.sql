@@ -0,0 +1,47 @@
+-- Up migration: product_revisions
+-- Every version of each product, written by a trigger as products are created, changed and
+-- deleted, so a product can be read as it was at a past time (see
+-- `product_repository::find_as_of`). Rows are only inserted. The revisions of the products of
+-- workspaces isolated in a schema of their own are kept here too, the trigger being copied with
+-- the table.
+CREATE TABLE IF NOT EXISTS product_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    product_id UUID NOT NULL,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    -- The row as written, NULL once the product is deleted
+    data JSONB,
+    -- The time of the change itself, also when several are made in one transaction
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp()
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_revisions_product_id ON product_revisions (product_id, recorded_at DESC);
+
+CREATE OR REPLACE FUNCTION record_product_revision()
+RETURNS TRIGGER AS $$
+BEGIN
+  -- Rows moved between the shared tables and a workspace schema keep their history
+  IF current_setting('app.skip_revisions', true) = 'on' THEN
+    RETURN NULL;
+  END IF;
+  IF TG_OP = 'DELETE' THEN
+    -- Products deleted with their workspace leave nothing to read
+    INSERT INTO product_revisions (product_id, workspace_id, data)
+    SELECT OLD.id, OLD.workspace_id, NULL
+    WHERE EXISTS (SELECT 1 FROM workspaces WHERE id = OLD.workspace_id);
+  ELSE
+    INSERT INTO product_revisions (product_id, workspace_id, data)
+    VALUES (NEW.id, NEW.workspace_id, to_jsonb(NEW) - 'search_vector');
+  END IF;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_product_revisions
+AFTER INSERT OR UPDATE OR DELETE ON products
+FOR EACH ROW
+EXECUTE FUNCTION record_product_revision();
+
+-- Existing products are known as of their last change
+INSERT INTO product_revisions (product_id, workspace_id, data, recorded_at)
+SELECT id, workspace_id, to_jsonb(p) - 'search_vector', updated_at
+FROM products p;
@@ -0,0 +1,2 @@
+-- Down migration: password_reset_tokens
+DROP TABLE IF EXISTS password_reset_tokens;
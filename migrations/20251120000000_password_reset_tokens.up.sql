@@ -0,0 +1,15 @@
+-- Up migration: password_reset_tokens
+-- Single-use tokens emailed to users who forgot their password (see
+-- auth::password_reset_repository). Only a SHA-256 hash of each token is kept, so the table
+-- cannot be used to reset passwords. It is only read by the public reset endpoints, so it has no
+-- RLS policies.
+CREATE TABLE IF NOT EXISTS password_reset_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash BYTEA NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_reset_tokens_user_id ON password_reset_tokens(user_id);
@@ -0,0 +1,3 @@
+-- Down migration: product_name_trigrams
+DROP INDEX IF EXISTS idx_products_name_trgm;
+-- The extension is kept, other database objects may have come to use it
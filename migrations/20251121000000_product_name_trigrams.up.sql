@@ -0,0 +1,6 @@
+-- Up migration: product_name_trigrams
+-- Trigram index on product names, so a new product can be checked against the workspace's
+-- products with similar names (see ProductRepository::find_possible_duplicates).
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_products_name_trgm ON products USING gin (name gin_trgm_ops);
@@ -0,0 +1,9 @@
+-- Down migration: soft_delete
+-- Records deleted in the meantime are deleted for good
+DELETE FROM contacts WHERE deleted_at IS NOT NULL;
+ALTER TABLE contacts
+    DROP COLUMN IF EXISTS deleted_at;
+
+DELETE FROM products WHERE deleted_at IS NOT NULL;
+ALTER TABLE products
+    DROP COLUMN IF EXISTS deleted_at;
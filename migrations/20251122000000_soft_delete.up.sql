@@ -0,0 +1,9 @@
+-- Up migration: soft_delete
+-- Deleting a contact or product only stamps `deleted_at`, so an accidental deletion can be
+-- restored. Deleted records are left out of every read unless asked for with `include_deleted`,
+-- and keep their code, so restoring one cannot clash with a record created since.
+ALTER TABLE contacts
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+ALTER TABLE products
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
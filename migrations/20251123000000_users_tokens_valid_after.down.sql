@@ -0,0 +1,3 @@
+-- Down migration: users_tokens_valid_after
+ALTER TABLE users
+    DROP COLUMN IF EXISTS tokens_valid_after;
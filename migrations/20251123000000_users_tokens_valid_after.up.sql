@@ -0,0 +1,5 @@
+-- Up migration: users_tokens_valid_after
+-- Access tokens issued before this time are rejected (see auth::jwt_middleware). A password
+-- reset sets it, so whoever held a session under the old password is signed out.
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS tokens_valid_after TIMESTAMPTZ;
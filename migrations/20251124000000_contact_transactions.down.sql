@@ -0,0 +1,6 @@
+-- Down migration: contact_transactions
+DROP TABLE IF EXISTS contact_transactions;
+DROP TYPE IF EXISTS contact_transaction_kind;
+
+-- Enum values cannot be dropped; only the templates printing statements go
+DELETE FROM print_templates WHERE entity::text = 'statements';
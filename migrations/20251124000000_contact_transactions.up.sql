@@ -0,0 +1,48 @@
+-- Up migration: contact_transactions
+-- Invoices, payments and credit notes of a contact, from which its statement is drawn (see
+-- modules::statements). Invoices raise what the contact owes; payments and credit notes lower it.
+-- Like contacts, they live in the schema of their workspace when it has one (see
+-- utils::tenant_schema), where `contact_id` references the workspace's own contacts.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'contact_transaction_kind') THEN
+        CREATE TYPE contact_transaction_kind AS ENUM ('invoice', 'payment', 'credit_note');
+    END IF;
+END $$;
+
+CREATE TABLE IF NOT EXISTS contact_transactions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    contact_id UUID NOT NULL REFERENCES contacts(id) ON DELETE CASCADE,
+    kind contact_transaction_kind NOT NULL,
+    reference VARCHAR(100) NOT NULL,
+    occurred_on DATE NOT NULL,
+    amount NUMERIC(15,2) NOT NULL CHECK (amount > 0),
+    description TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_contact_transactions_contact ON contact_transactions(contact_id, occurred_on);
+
+ALTER TABLE contact_transactions ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY contact_transactions_select_policy ON contact_transactions
+    FOR SELECT
+    USING ( has_workspace_grant(workspace_id, ARRAY['admin', 'member', 'viewer']) );
+
+CREATE POLICY contact_transactions_insert_policy ON contact_transactions
+    FOR INSERT
+    WITH CHECK ( has_workspace_grant(workspace_id, ARRAY['admin', 'member']) );
+
+CREATE POLICY contact_transactions_update_policy ON contact_transactions
+    FOR UPDATE
+    USING ( has_workspace_grant(workspace_id, ARRAY['admin', 'member']) )
+    WITH CHECK ( has_workspace_grant(workspace_id, ARRAY['admin', 'member']) );
+
+CREATE POLICY contact_transactions_delete_policy ON contact_transactions
+    FOR DELETE
+    USING ( has_workspace_grant(workspace_id, ARRAY['admin']) );
+
+-- Statements print with a template of their own
+ALTER TYPE print_entity ADD VALUE IF NOT EXISTS 'statements';
@@ -0,0 +1,4 @@
+-- Down migration: retention_deleted_records
+-- Enum values cannot be dropped; the policies and purges of these categories are.
+DELETE FROM retention_policies WHERE category::text IN ('deleted_contacts', 'deleted_products');
+DELETE FROM retention_purges WHERE category::text IN ('deleted_contacts', 'deleted_products');
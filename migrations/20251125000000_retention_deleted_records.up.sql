@@ -0,0 +1,5 @@
+-- Up migration: retention_deleted_records
+-- Deleted contacts and products are kept for restoring (see the soft_delete migration), holding
+-- their code, until a retention policy of these categories removes them for good.
+ALTER TYPE retention_category ADD VALUE IF NOT EXISTS 'deleted_contacts';
+ALTER TYPE retention_category ADD VALUE IF NOT EXISTS 'deleted_products';
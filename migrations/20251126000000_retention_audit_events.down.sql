@@ -0,0 +1,4 @@
+-- Down migration: retention_audit_events
+-- Enum values cannot be dropped; the policies and purges of this category are.
+DELETE FROM retention_policies WHERE category::text = 'audit_events';
+DELETE FROM retention_purges WHERE category::text = 'audit_events';
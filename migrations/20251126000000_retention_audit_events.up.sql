@@ -0,0 +1,3 @@
+-- Up migration: retention_audit_events
+-- Audit events are kept forever unless a retention policy of this category removes the old ones.
+ALTER TYPE retention_category ADD VALUE IF NOT EXISTS 'audit_events';
@@ -0,0 +1,26 @@
+-- Down migration: search_index_workspaces
+DROP FUNCTION IF EXISTS refresh_product_search_vectors(text, uuid, uuid);
+
+CREATE OR REPLACE FUNCTION refresh_product_search_vectors(source text, source_id uuid)
+RETURNS integer AS $$
+DECLARE
+  refreshed integer;
+BEGIN
+  UPDATE products p SET search_vector = product_search_vector(p)
+  WHERE source IS NULL
+     OR (source = 'product_categories' AND p.category_id = source_id)
+     OR (source = 'contacts' AND p.supplier_id = source_id);
+  GET DIAGNOSTICS refreshed = ROW_COUNT;
+  RETURN refreshed;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER SET search_path = public;
+
+CREATE OR REPLACE FUNCTION notify_search_index()
+RETURNS TRIGGER AS $$
+BEGIN
+  IF NEW.name IS DISTINCT FROM OLD.name THEN
+    PERFORM pg_notify('search_index', json_build_object('source', TG_TABLE_NAME, 'id', NEW.id)::text);
+  END IF;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
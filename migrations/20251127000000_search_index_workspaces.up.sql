@@ -0,0 +1,42 @@
+-- Up migration: search_index_workspaces
+-- Workspaces with a schema of their own (utils::tenant_schema) keep their products there, so the
+-- refresher has to know which workspace a renamed category or supplier belongs to.
+
+CREATE OR REPLACE FUNCTION notify_search_index()
+RETURNS TRIGGER AS $$
+BEGIN
+  IF NEW.name IS DISTINCT FROM OLD.name THEN
+    PERFORM pg_notify(
+      'search_index',
+      json_build_object('source', TG_TABLE_NAME, 'id', NEW.id, 'workspace_id', NEW.workspace_id)::text
+    );
+  END IF;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP FUNCTION IF EXISTS refresh_product_search_vectors(text, uuid);
+
+-- Recomputes the vectors of the products referencing `source_id` in `source`, or of every
+-- product when `source` is NULL, in the schema of `workspace` when it has one and in the shared
+-- tables otherwise. Runs as the owner because the refresher has no workspace to satisfy Row Level
+-- Security with.
+CREATE OR REPLACE FUNCTION refresh_product_search_vectors(source text, source_id uuid, workspace uuid)
+RETURNS integer AS $$
+DECLARE
+  workspace_schema text := 'ws_' || replace(workspace::text, '-', '');
+  refreshed integer;
+BEGIN
+  IF to_regnamespace(quote_ident(workspace_schema)) IS NOT NULL THEN
+    -- Local to the call: the function's own search_path is restored when it returns
+    PERFORM set_config('search_path', quote_ident(workspace_schema) || ', public', true);
+  END IF;
+
+  UPDATE products p SET search_vector = product_search_vector(p)
+  WHERE source IS NULL
+     OR (source = 'product_categories' AND p.category_id = source_id)
+     OR (source = 'contacts' AND p.supplier_id = source_id);
+  GET DIAGNOSTICS refreshed = ROW_COUNT;
+  RETURN refreshed;
+END;
+$$ LANGUAGE plpgsql SECURITY DEFINER SET search_path = public;
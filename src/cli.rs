@@ -0,0 +1,453 @@
+//! Command line interface for the API binary.
+//!
+//! Running the binary without a subcommand starts the server, so existing deployments
+//! keep working. The other subcommands cover operational tasks and share `setup_state()`
+//! with the server, so they read the same environment and `.env` file.
+
+use std::{
+  io::Write,
+  path::{Path, PathBuf},
+  sync::Arc,
+};
+
+use clap::{Parser, Subcommand};
+use rand::RngCore;
+use sqlx::Connection;
+use uuid::Uuid;
+
+use crate::{
+  AppResult,
+  config::TenantIsolation,
+  errors::AppError,
+  modules::auth::auth_service::register_user,
+  modules::auth::user_dto::RegisterUserDto,
+  openapi::openapi_document,
+  run,
+  seed::{SeedOptions, seed_workspace},
+  setup_state,
+  state::AppState,
+  utils::{field_encryption::FieldCipher, tenant_schema},
+};
+#[cfg(feature = "backups")]
+use crate::{
+  config::AppConfig,
+  modules::backups::{backup_models::is_valid_environment, backup_service, backup_store::S3Store},
+};
+
+/// My App API server and operational tooling.
+#[derive(Debug, Parser)]
+#[command(name = "myapp-api-rust", version, about)]
+pub struct Cli {
+  #[command(subcommand)]
+  pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+  /// Start the HTTP server (the default when no subcommand is given).
+  Serve,
+  /// Apply pending database migrations.
+  Migrate,
+  /// Fill a workspace with generated demo data (the demo user's workspace by default).
+  Seed {
+    /// Workspace to populate; created together with a demo user when omitted.
+    #[arg(long)]
+    workspace: Option<Uuid>,
+    #[arg(long, default_value_t = 50)]
+    contacts: usize,
+    #[arg(long, default_value_t = 10)]
+    suppliers: usize,
+    #[arg(long, default_value_t = 8)]
+    categories: usize,
+    #[arg(long, default_value_t = 100)]
+    products: usize,
+    /// Fixed RNG seed for reproducible data.
+    #[arg(long)]
+    seed: Option<u64>,
+  },
+  /// Create a user who administers their own workspace.
+  CreateAdmin {
+    #[arg(long)]
+    username: String,
+    #[arg(long)]
+    email: String,
+    /// Read from `ADMIN_PASSWORD` when not passed, to keep it out of shell history.
+    #[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+    password: String,
+  },
+  /// Make a user a superadmin, who administers the platform across workspaces.
+  GrantSuperadmin {
+    #[arg(long)]
+    email: String,
+  },
+  /// Take the superadmin role away from a user.
+  RevokeSuperadmin {
+    #[arg(long)]
+    email: String,
+  },
+  /// Write the OpenAPI document to a file, or to stdout when no path is given.
+  GenerateOpenapi {
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+  },
+  /// Generate a new JWT signing secret and print the environment settings to roll it out.
+  RotateJwtKey {
+    /// Write the settings, the current secret as `JWT_PREVIOUS_SECRET` included, to this new
+    /// file instead. Without it the current secret is never printed.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+  },
+  /// Generate a new key for the encrypted fields and print `FIELD_ENCRYPTION_KEYS` with it first.
+  RotateEncryptionKey {
+    /// Id of the new key, stored with every value it encrypts.
+    #[arg(long)]
+    key_id: String,
+  },
+  /// Re-encrypt the fields still encrypted with an older key with the current one.
+  #[cfg(feature = "contacts")]
+  ReencryptFields {
+    #[arg(long, default_value_t = 500)]
+    batch_size: u32,
+  },
+  /// Give workspaces a schema of their own and move their contacts, products and categories
+  /// into it (`TENANT_ISOLATION=schema`).
+  IsolateWorkspaces {
+    /// Workspace to isolate; every workspace when omitted.
+    #[arg(long)]
+    workspace: Option<Uuid>,
+  },
+  /// Back up the database to the backup bucket now and delete the backups past `BACKUP_KEEP`.
+  #[cfg(feature = "backups")]
+  Backup,
+  /// Replace the contents of the database with a backup.
+  #[cfg(feature = "backups")]
+  Restore {
+    /// Key or file name of the backup, e.g. `20251028T020000Z.dump`, or `latest`.
+    #[arg(default_value = "latest")]
+    backup: String,
+    /// Environment the backup was taken in, `BACKUP_ENVIRONMENT` by default.
+    #[arg(long)]
+    environment: Option<String>,
+    /// Database to restore into, `BACKUP_DATABASE_URL` or `DATABASE_URL` by default.
+    #[arg(long, env = "RESTORE_DATABASE_URL", hide_env_values = true)]
+    database_url: Option<String>,
+    /// Confirm that the contents of the database are replaced.
+    #[arg(long)]
+    yes: bool,
+  },
+}
+
+/// Runs the selected subcommand.
+///
+/// # Returns
+///
+/// * `AppResult<()>` - An error when the subcommand fails; the server itself panics on startup failures.
+pub async fn execute(cli: Cli) -> AppResult<()> {
+  match cli.command.unwrap_or(Command::Serve) {
+    Command::Serve => {
+      run().await;
+      Ok(())
+    }
+    Command::Migrate => migrate().await,
+    Command::Seed {
+      workspace,
+      contacts,
+      suppliers,
+      categories,
+      products,
+      seed: rng_seed,
+    } => {
+      let counts = SeedCounts {
+        contacts,
+        suppliers,
+        categories,
+        products,
+      };
+      seed(workspace, counts, rng_seed).await
+    }
+    Command::CreateAdmin { username, email, password } => create_admin(username, email, password).await,
+    Command::GrantSuperadmin { email } => set_superadmin(email, true).await,
+    Command::RevokeSuperadmin { email } => set_superadmin(email, false).await,
+    Command::GenerateOpenapi { output } => generate_openapi(output),
+    Command::RotateJwtKey { output } => rotate_jwt_key(output),
+    Command::RotateEncryptionKey { key_id } => rotate_encryption_key(key_id),
+    #[cfg(feature = "contacts")]
+    Command::ReencryptFields { batch_size } => reencrypt_fields(batch_size).await,
+    Command::IsolateWorkspaces { workspace } => isolate_workspaces(workspace).await,
+    #[cfg(feature = "backups")]
+    Command::Backup => backup().await,
+    #[cfg(feature = "backups")]
+    Command::Restore {
+      backup,
+      environment,
+      database_url,
+      yes,
+    } => restore(backup, environment, database_url, yes).await,
+  }
+}
+
+async fn migrate() -> AppResult<()> {
+  let state = setup_state().await;
+  let mut conn = state.db.acquire().await?;
+  // Schema changes may legitimately run longer than the request statement timeout
+  sqlx::query("SET statement_timeout = 0").execute(&mut *conn).await?;
+  sqlx::migrate!("./migrations")
+    .run(&mut *conn)
+    .await
+    .map_err(|e| AppError::Internal(format!("Migration failed: {}", e)))?;
+  println!("✅ Migrations applied");
+  for workspace_id in tenant_schema::migrate(&mut conn).await? {
+    println!(
+      "✅ Rebuilt schema {} of workspace {}",
+      tenant_schema::schema_name(workspace_id),
+      workspace_id
+    );
+  }
+  Ok(())
+}
+
+const DEMO_USERNAME: &str = "demo";
+const DEMO_EMAIL: &str = "demo@example.com";
+const DEMO_PASSWORD: &str = "demo-password";
+
+struct SeedCounts {
+  contacts: usize,
+  suppliers: usize,
+  categories: usize,
+  products: usize,
+}
+
+async fn seed(workspace: Option<Uuid>, counts: SeedCounts, rng_seed: Option<u64>) -> AppResult<()> {
+  let state = setup_state().await;
+
+  let (workspace_id, user_id) = match workspace {
+    Some(workspace_id) => {
+      let owner_id: Uuid = sqlx::query_scalar("SELECT owner_id FROM workspaces WHERE id = $1")
+        .bind(workspace_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::not_found_with_id("Workspace", workspace_id))?;
+      (workspace_id, owner_id)
+    }
+    None => demo_workspace(state.clone()).await?,
+  };
+
+  let options = SeedOptions {
+    workspace_id,
+    user_id,
+    contacts: counts.contacts,
+    suppliers: counts.suppliers,
+    categories: counts.categories,
+    products: counts.products,
+    rng_seed,
+  };
+  let summary = seed_workspace(&state.db, &options).await?;
+  println!(
+    "✅ Seeded workspace {}: {} contacts, {} suppliers, {} categories, {} products",
+    workspace_id, summary.contacts, summary.suppliers, summary.categories, summary.products
+  );
+  Ok(())
+}
+
+/// Returns the demo user's first workspace, registering the user on first use.
+async fn demo_workspace(state: Arc<AppState>) -> AppResult<(Uuid, Uuid)> {
+  if let Some(user) = state.auth_repository.find_by_email(DEMO_EMAIL).await? {
+    let workspace_id: Uuid = sqlx::query_scalar("SELECT id FROM workspaces WHERE owner_id = $1 ORDER BY created_at LIMIT 1")
+      .bind(user.id)
+      .fetch_optional(&state.db)
+      .await?
+      .ok_or_else(|| AppError::Internal(format!("Demo user {} has no workspace", DEMO_EMAIL)))?;
+    return Ok((workspace_id, user.id));
+  }
+
+  let payload = RegisterUserDto {
+    username: DEMO_USERNAME.to_string(),
+    email: DEMO_EMAIL.to_string(),
+    password: DEMO_PASSWORD.to_string(),
+  };
+  let (user, workspace) = register_user(state, payload).await?;
+  println!("Created demo user {} (password: {})", user.email, DEMO_PASSWORD);
+  Ok((workspace.id, user.id))
+}
+
+async fn create_admin(username: String, email: String, password: String) -> AppResult<()> {
+  let state = setup_state().await;
+  let (user, workspace) = register_user(state, RegisterUserDto { username, email, password }).await?;
+  println!("✅ Created user {} ({}) as admin of workspace {}", user.email, user.id, workspace.id);
+  Ok(())
+}
+
+async fn set_superadmin(email: String, is_superadmin: bool) -> AppResult<()> {
+  let state = setup_state().await;
+  let user = state
+    .auth_repository
+    .set_superadmin(&email, is_superadmin)
+    .await?
+    .ok_or_else(|| AppError::not_found(&format!("User with email {}", email)))?;
+  if is_superadmin {
+    println!("✅ {} ({}) is now a superadmin", user.email, user.id);
+  } else {
+    println!("✅ {} ({}) is no longer a superadmin", user.email, user.id);
+  }
+  Ok(())
+}
+
+fn generate_openapi(output: Option<PathBuf>) -> AppResult<()> {
+  let document = serde_json::to_string_pretty(&openapi_document())?;
+  match output {
+    Some(path) => {
+      std::fs::write(&path, document).map_err(|e| AppError::Internal(format!("Failed to write {}: {}", path.display(), e)))?;
+      println!("✅ OpenAPI document written to {}", path.display());
+    }
+    None => println!("{}", document),
+  }
+  Ok(())
+}
+
+/// Prints a fresh secret, or writes it with the current one to `output`, readable by the owner
+/// only. Tokens signed with the old secret stay valid while it is kept in `JWT_PREVIOUS_SECRET`,
+/// which can be removed once they have expired (24 hours).
+fn rotate_jwt_key(output: Option<PathBuf>) -> AppResult<()> {
+  let mut bytes = [0u8; 48];
+  rand::thread_rng().fill_bytes(&mut bytes);
+  let secret: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+  match output {
+    Some(path) => {
+      let current = std::env::var("JWT_SECRET").unwrap_or_default();
+      if current.is_empty() {
+        return Err(AppError::Internal("JWT_SECRET is not set".to_string()));
+      }
+      write_private(&path, &format!("JWT_SECRET={}\nJWT_PREVIOUS_SECRET={}\n", secret, current))
+        .map_err(|e| AppError::Internal(format!("Failed to write {}: {}", path.display(), e)))?;
+      println!("✅ Settings written to {}", path.display());
+      println!("Set the environment variables in it and restart the server, then delete the file.");
+    }
+    None => {
+      println!("Set the following environment variables and restart the server:");
+      println!();
+      println!("JWT_SECRET={}", secret);
+      println!("JWT_PREVIOUS_SECRET=<the current JWT_SECRET>");
+    }
+  }
+  println!();
+  println!("Remove JWT_PREVIOUS_SECRET after 24 hours, once all tokens signed with it have expired.");
+  Ok(())
+}
+
+/// Writes `contents` to a new file at `path` that only its owner can read.
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+  let mut options = std::fs::OpenOptions::new();
+  options.write(true).create_new(true);
+  #[cfg(unix)]
+  std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+  options.open(path)?.write_all(contents.as_bytes())
+}
+
+/// Prints `FIELD_ENCRYPTION_KEYS` with a fresh key in front of the current ones. Once it is rolled
+/// out, `reencrypt-fields` moves the stored values to the new key and the old ones can be dropped.
+fn rotate_encryption_key(key_id: String) -> AppResult<()> {
+  let key_id = key_id.trim();
+  if key_id.is_empty() || key_id.contains([':', ',']) {
+    return Err(AppError::BadRequest("The key id must not be empty or contain ':' or ','".to_string()));
+  }
+  let current = std::env::var("FIELD_ENCRYPTION_KEYS").unwrap_or_default();
+  if current.split(',').any(|entry| entry.split(':').next().map(str::trim) == Some(key_id)) {
+    return Err(AppError::Conflict(format!("Key id {} is already in FIELD_ENCRYPTION_KEYS", key_id)));
+  }
+
+  let mut keys = format!("{}:{}", key_id, FieldCipher::generate_key());
+  if !current.trim().is_empty() {
+    keys = format!("{},{}", keys, current.trim());
+  }
+  println!("Set the following environment variable (or secret) and restart the server:");
+  println!();
+  println!("FIELD_ENCRYPTION_KEYS={}", keys);
+  println!();
+  println!("Then run `reencrypt-fields`, after which the older keys can be removed.");
+  Ok(())
+}
+
+#[cfg(feature = "contacts")]
+async fn reencrypt_fields(batch_size: u32) -> AppResult<()> {
+  let state = setup_state().await;
+  if !state.field_cipher.is_enabled() {
+    return Err(AppError::Internal("FIELD_ENCRYPTION_KEYS is not set".to_string()));
+  }
+  let updated = state.contact_repository.reencrypt_sensitive_fields(batch_size.max(1)).await?;
+  println!("✅ Re-encrypted the sensitive fields of {} contacts", updated);
+  Ok(())
+}
+
+async fn isolate_workspaces(workspace: Option<Uuid>) -> AppResult<()> {
+  let state = setup_state().await;
+  if state.config.database.tenant_isolation != TenantIsolation::Schema {
+    return Err(AppError::Internal("TENANT_ISOLATION is not set to schema".to_string()));
+  }
+  let mut conn = state.db.acquire().await?;
+  let workspace_ids: Vec<Uuid> = match workspace {
+    Some(workspace_id) => vec![workspace_id],
+    None => {
+      sqlx::query_scalar("SELECT id FROM workspaces ORDER BY created_at")
+        .fetch_all(&mut *conn)
+        .await?
+    }
+  };
+  for workspace_id in workspace_ids {
+    let mut tx = conn.begin().await?;
+    tenant_schema::provision(&mut tx, workspace_id).await?;
+    let moved = tenant_schema::move_rows(&mut tx, workspace_id).await?;
+    tx.commit().await?;
+    println!(
+      "✅ Moved {} rows of workspace {} into schema {}",
+      moved,
+      workspace_id,
+      tenant_schema::schema_name(workspace_id)
+    );
+  }
+  Ok(())
+}
+
+/// The configured backup bucket. Backups do not need the rest of the state, so a database can be
+/// restored before the server could start on it.
+#[cfg(feature = "backups")]
+fn backup_store() -> AppResult<(AppConfig, S3Store)> {
+  dotenvy::dotenv().ok();
+  let config = AppConfig::from_env();
+  let store = S3Store::from_config(&config.backups)
+    .ok_or_else(|| AppError::Internal("Backups are not configured: BACKUP_S3_BUCKET and its access keys must be set".to_string()))?;
+  Ok((config, store))
+}
+
+#[cfg(feature = "backups")]
+async fn backup() -> AppResult<()> {
+  let (config, store) = backup_store()?;
+  let backup = backup_service::run_backup(&config.backups, &store).await?;
+  println!("✅ Backup {} uploaded ({} bytes)", backup.key, backup.size_bytes);
+  Ok(())
+}
+
+#[cfg(feature = "backups")]
+async fn restore(backup: String, environment: Option<String>, database_url: Option<String>, yes: bool) -> AppResult<()> {
+  let (config, store) = backup_store()?;
+  let config = config.backups;
+  let environment = environment.unwrap_or_else(|| config.environment.clone());
+  if !is_valid_environment(&environment) {
+    return Err(AppError::BadRequest(format!("Invalid environment: {}", environment)));
+  }
+  let database_url = database_url
+    .or_else(|| config.database_url.clone())
+    .ok_or_else(|| AppError::Internal("No database to restore into: pass --database-url or set DATABASE_URL".to_string()))?;
+
+  let backup = backup_service::find_backup(&config, &store, &environment, &backup).await?;
+  println!("Backup {} ({} bytes, taken {})", backup.key, backup.size_bytes, backup.created_at);
+  if !yes {
+    return Err(AppError::BadRequest(
+      "Restoring replaces the contents of the database; pass --yes to continue".to_string(),
+    ));
+  }
+
+  backup_service::restore_backup(&config, &store, &backup, &database_url).await?;
+  println!("✅ Restored {}", backup.name());
+  Ok(())
+}
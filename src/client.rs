@@ -0,0 +1,232 @@
+//! Typed HTTP client for the v1 API, compiled with the `client` feature.
+//!
+//! Requests and responses are the DTOs the handlers use, so services calling the API do not
+//! redeclare them and break when they change. Contact and product calls also need the
+//! `contacts` and `products` features.
+//!
+//! ```ignore
+//! let mut client = ApiClient::new("http://localhost:3000");
+//! client.login(&LoginUserDto { email, password, workspace_id: None }).await?;
+//! let client = client.with_workspace(workspace_id);
+//! let page = client.list_contacts(&GetContactsQuery { search: Some("acme".into()), ..Default::default() }).await?;
+//! ```
+
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+#[cfg(feature = "contacts")]
+use crate::modules::datastores::contacts::contact_models::{ContactResponse, CreateContactRequest, GetContactsQuery, UpdateContactRequest};
+#[cfg(feature = "products")]
+use crate::modules::datastores::products::product_models::{CreateProductRequest, GetProductsQuery, ProductResponse, UpdateProductRequest};
+#[cfg(any(feature = "contacts", feature = "products"))]
+use crate::responses::PaginatedResponse;
+use crate::{
+  errors::ErrorResponse,
+  modules::auth::user_dto::{
+    CurrentUserResponse, ForgotPasswordDto, LoginResponse, LoginUserDto, RegisterResponse, RegisterUserDto, ResetPasswordDto, SwitchWorkspaceDto,
+    SwitchWorkspaceResponse,
+  },
+  responses::ApiResponse,
+};
+
+/// Errors returned by `ApiClient`.
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+  /// The request could not be sent or its response not read or decoded.
+  #[error("Request failed: {0}")]
+  Http(#[from] reqwest::Error),
+  /// The API answered with an error response.
+  #[error("API error ({status}): {}", .error.message)]
+  Api { status: StatusCode, error: ErrorResponse },
+  /// The API answered with an error status but without an `ErrorResponse` body.
+  #[error("Unexpected response ({status}): {body}")]
+  Unexpected { status: StatusCode, body: String },
+  /// A successful response carried no `results`.
+  #[error("Response contained no results")]
+  MissingResults,
+}
+
+pub type ClientResult<T> = Result<T, ClientError>;
+
+/// Calls the v1 API of one server, as one user and, optionally, in one workspace.
+#[derive(Clone)]
+pub struct ApiClient {
+  http: reqwest::Client,
+  base_url: String,
+  token: Option<String>,
+  workspace_id: Option<Uuid>,
+}
+
+impl ApiClient {
+  /// A client for the server at `base_url`, e.g. `http://localhost:3000`.
+  pub fn new(base_url: impl Into<String>) -> Self {
+    Self {
+      http: reqwest::Client::new(),
+      base_url: base_url.into().trim_end_matches('/').to_string(),
+      token: None,
+      workspace_id: None,
+    }
+  }
+
+  /// Sends requests through `http`, e.g. one with timeouts or a proxy configured.
+  pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+    self.http = http;
+    self
+  }
+
+  /// Authenticates requests with an access token obtained elsewhere.
+  pub fn with_token(mut self, token: impl Into<String>) -> Self {
+    self.token = Some(token.into());
+    self
+  }
+
+  /// Sends `X-Workspace-ID` with every request.
+  pub fn with_workspace(mut self, workspace_id: Uuid) -> Self {
+    self.workspace_id = Some(workspace_id);
+    self
+  }
+
+  /// The access token requests are authenticated with, if any.
+  pub fn token(&self) -> Option<&str> {
+    self.token.as_deref()
+  }
+
+  pub async fn register(&self, request: &RegisterUserDto) -> ClientResult<RegisterResponse> {
+    self.send(self.request(Method::POST, "/api/v1/auth/register").json(request)).await
+  }
+
+  /// Logs in and authenticates the following requests with the issued token.
+  pub async fn login(&mut self, request: &LoginUserDto) -> ClientResult<LoginResponse> {
+    let response: LoginResponse = self.send(self.request(Method::POST, "/api/v1/auth/login").json(request)).await?;
+    self.token = Some(response.token.clone());
+    Ok(response)
+  }
+
+  /// Asks for a password reset link to be emailed; succeeds whether or not the email is registered.
+  pub async fn forgot_password(&self, request: &ForgotPasswordDto) -> ClientResult<()> {
+    self
+      .send_empty(self.request(Method::POST, "/api/v1/auth/forgot-password").json(request))
+      .await
+  }
+
+  pub async fn reset_password(&self, request: &ResetPasswordDto) -> ClientResult<()> {
+    self
+      .send_empty(self.request(Method::POST, "/api/v1/auth/reset-password").json(request))
+      .await
+  }
+
+  pub async fn me(&self) -> ClientResult<CurrentUserResponse> {
+    self.send(self.request(Method::GET, "/api/v1/auth/me")).await
+  }
+
+  /// Switches to another workspace, authenticating the following requests with the new token.
+  pub async fn switch_workspace(&mut self, workspace_id: Uuid) -> ClientResult<SwitchWorkspaceResponse> {
+    let request = SwitchWorkspaceDto { workspace_id };
+    let response: SwitchWorkspaceResponse = self
+      .send(self.request(Method::POST, "/api/v1/auth/switch-workspace").json(&request))
+      .await?;
+    self.token = Some(response.token.clone());
+    self.workspace_id = Some(workspace_id);
+    Ok(response)
+  }
+
+  /// Revokes the current token.
+  pub async fn logout(&mut self) -> ClientResult<()> {
+    self.send_empty(self.request(Method::POST, "/api/v1/auth/logout")).await?;
+    self.token = None;
+    Ok(())
+  }
+
+  #[cfg(feature = "contacts")]
+  pub async fn list_contacts(&self, query: &GetContactsQuery) -> ClientResult<PaginatedResponse<ContactResponse>> {
+    self.send(self.request(Method::GET, "/api/v1/contacts").query(query)).await
+  }
+
+  #[cfg(feature = "contacts")]
+  pub async fn get_contact(&self, id: Uuid) -> ClientResult<ContactResponse> {
+    self.send(self.request(Method::GET, &format!("/api/v1/contacts/{}", id))).await
+  }
+
+  /// Creates a contact; an empty `code` is generated by the server.
+  #[cfg(feature = "contacts")]
+  pub async fn create_contact(&self, request: &CreateContactRequest) -> ClientResult<ContactResponse> {
+    self.send(self.request(Method::POST, "/api/v1/contacts").json(request)).await
+  }
+
+  #[cfg(feature = "contacts")]
+  pub async fn update_contact(&self, id: Uuid, request: &UpdateContactRequest) -> ClientResult<ContactResponse> {
+    self
+      .send(self.request(Method::PUT, &format!("/api/v1/contacts/{}", id)).json(request))
+      .await
+  }
+
+  #[cfg(feature = "contacts")]
+  pub async fn delete_contact(&self, id: Uuid) -> ClientResult<()> {
+    self.send_empty(self.request(Method::DELETE, &format!("/api/v1/contacts/{}", id))).await
+  }
+
+  #[cfg(feature = "products")]
+  pub async fn list_products(&self, query: &GetProductsQuery) -> ClientResult<PaginatedResponse<ProductResponse>> {
+    self.send(self.request(Method::GET, "/api/v1/products").query(query)).await
+  }
+
+  #[cfg(feature = "products")]
+  pub async fn get_product(&self, id: Uuid) -> ClientResult<ProductResponse> {
+    self.send(self.request(Method::GET, &format!("/api/v1/products/{}", id))).await
+  }
+
+  /// Creates a product; an empty `code` is generated by the server.
+  #[cfg(feature = "products")]
+  pub async fn create_product(&self, request: &CreateProductRequest) -> ClientResult<ProductResponse> {
+    self.send(self.request(Method::POST, "/api/v1/products").json(request)).await
+  }
+
+  #[cfg(feature = "products")]
+  pub async fn update_product(&self, id: Uuid, request: &UpdateProductRequest) -> ClientResult<ProductResponse> {
+    self
+      .send(self.request(Method::PUT, &format!("/api/v1/products/{}", id)).json(request))
+      .await
+  }
+
+  #[cfg(feature = "products")]
+  pub async fn delete_product(&self, id: Uuid) -> ClientResult<()> {
+    self.send_empty(self.request(Method::DELETE, &format!("/api/v1/products/{}", id))).await
+  }
+
+  /// A request to `path` carrying the token and workspace header.
+  fn request(&self, method: Method, path: &str) -> RequestBuilder {
+    let mut request = self.http.request(method, format!("{}{}", self.base_url, path));
+    if let Some(token) = &self.token {
+      request = request.bearer_auth(token);
+    }
+    if let Some(workspace_id) = self.workspace_id {
+      request = request.header("X-Workspace-ID", workspace_id.to_string());
+    }
+    request
+  }
+
+  /// Sends `request` and returns the `results` of its `ApiResponse`.
+  async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> ClientResult<T> {
+    Self::parse::<T>(request).await?.results.ok_or(ClientError::MissingResults)
+  }
+
+  /// Sends `request`, whose `ApiResponse` carries no results.
+  async fn send_empty(&self, request: RequestBuilder) -> ClientResult<()> {
+    Self::parse::<serde_json::Value>(request).await.map(|_| ())
+  }
+
+  async fn parse<T: DeserializeOwned>(request: RequestBuilder) -> ClientResult<ApiResponse<T>> {
+    let response = request.send().await?;
+    let status = response.status();
+    if status.is_success() {
+      return Ok(response.json().await?);
+    }
+
+    let body = response.text().await?;
+    Err(match serde_json::from_str::<ErrorResponse>(&body) {
+      Ok(error) => ClientError::Api { status, error },
+      Err(_) => ClientError::Unexpected { status, body },
+    })
+  }
+}
@@ -0,0 +1,1340 @@
+//! Application configuration loaded from environment variables.
+//!
+//! Every setting has a sensible default so the API can start with only
+//! `DATABASE_URL` and `JWT_SECRET` set. Values are read once in `setup_state()`
+//! and stored in `AppState`.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::modules::workspace_settings::workspace_settings_models::IpRange;
+
+/// Top-level application configuration.
+#[derive(Debug, Clone, Default)]
+pub struct AppConfig {
+  pub server: ServerConfig,
+  pub database: DatabaseConfig,
+  pub db_resilience: DbResilienceConfig,
+  pub rate_limit: RateLimitConfig,
+  pub body_limit: BodyLimitConfig,
+  pub tls: TlsConfig,
+  pub api_versions: ApiVersionConfig,
+  pub grpc: GrpcConfig,
+  pub realtime: RealtimeConfig,
+  pub redis: RedisConfig,
+  pub idempotency: IdempotencyConfig,
+  pub role_cache: RoleCacheConfig,
+  pub workspace_settings: WorkspaceSettingsConfig,
+  pub sessions: SessionConfig,
+  pub access_log: AccessLogConfig,
+  pub billing: BillingConfig,
+  pub usage: UsageConfig,
+  pub trial: TrialConfig,
+  pub feature_flags: FeatureFlagConfig,
+  pub retention: RetentionConfig,
+  pub backups: BackupConfig,
+  pub reports: ReportConfig,
+  pub triggers: TriggerConfig,
+  pub rendering: RenderingConfig,
+  pub connectors: ConnectorConfig,
+  pub exchange_rates: ExchangeRateConfig,
+  pub geo: GeoConfig,
+  pub registration: RegistrationConfig,
+  pub user_exports: UserExportConfig,
+  pub password_reset: PasswordResetConfig,
+  pub secrets: SecretsConfig,
+  pub encryption: EncryptionConfig,
+}
+
+/// Settings protecting the server from slow or excessive traffic.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+  /// Maximum time a request may take before it is aborted with a 504 (`REQUEST_TIMEOUT_SECS`).
+  pub request_timeout_secs: u64,
+  /// Maximum number of requests processed at once; extra requests are shed with a 503 (`MAX_CONCURRENT_REQUESTS`).
+  pub max_concurrent_requests: usize,
+  /// Proxies and load balancers whose forwarding headers (`Fly-Client-IP`, `X-Real-IP`,
+  /// `X-Forwarded-For`) give the client address (`TRUSTED_PROXIES`, comma-separated addresses or
+  /// CIDR ranges). Without any, the client address is the peer of the connection, so behind a
+  /// proxy it must be set (fly.toml does for Fly.io), or all clients share the proxy's address.
+  pub trusted_proxies: Vec<IpRange>,
+}
+
+impl Default for ServerConfig {
+  fn default() -> Self {
+    Self {
+      request_timeout_secs: 30,
+      max_concurrent_requests: 512,
+      trusted_proxies: Vec::new(),
+    }
+  }
+}
+
+/// Settings for the PostgreSQL connection pool.
+#[derive(Debug, Clone)]
+pub struct DatabaseConfig {
+  /// Upper bound of open connections (`DB_MAX_CONNECTIONS`).
+  pub max_connections: u32,
+  /// Connections kept open even when idle (`DB_MIN_CONNECTIONS`).
+  pub min_connections: u32,
+  /// Maximum wait for a free connection before the query fails (`DB_ACQUIRE_TIMEOUT_SECS`).
+  pub acquire_timeout_secs: u64,
+  /// Idle time after which a connection above `min_connections` is closed; 0 keeps them open (`DB_IDLE_TIMEOUT_SECS`).
+  pub idle_timeout_secs: u64,
+  /// Server-side `statement_timeout` set on every connection, in milliseconds; 0 disables it (`DB_STATEMENT_TIMEOUT_MS`).
+  pub statement_timeout_ms: u64,
+  /// `shared` or `schema`: whether the data of each workspace lives in its own schema (`TENANT_ISOLATION`).
+  pub tenant_isolation: TenantIsolation,
+}
+
+/// Where the contacts, products and categories of a workspace are stored (see `utils::tenant_schema`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TenantIsolation {
+  /// In the shared tables, separated by Row Level Security.
+  #[default]
+  Shared,
+  /// In a schema of the workspace's own, for customers with strict isolation requirements.
+  Schema,
+}
+
+impl FromStr for TenantIsolation {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    match value.to_ascii_lowercase().as_str() {
+      "shared" => Ok(Self::Shared),
+      "schema" => Ok(Self::Schema),
+      other => Err(format!("unknown tenant isolation mode '{}'", other)),
+    }
+  }
+}
+
+impl Default for DatabaseConfig {
+  fn default() -> Self {
+    Self {
+      max_connections: 10,
+      min_connections: 1,
+      acquire_timeout_secs: 30,
+      idle_timeout_secs: 600,
+      statement_timeout_ms: 30_000,
+      tenant_isolation: TenantIsolation::default(),
+    }
+  }
+}
+
+/// Retries and circuit breaker for transient database failures.
+#[derive(Debug, Clone)]
+pub struct DbResilienceConfig {
+  /// Attempts made for an operation failing with a transient error, the first included (`DB_RETRY_ATTEMPTS`).
+  pub retry_attempts: u32,
+  /// Backoff before the first retry, doubled for every further one (`DB_RETRY_BASE_DELAY_MS`).
+  pub retry_base_delay_ms: u64,
+  /// Upper bound of the backoff between retries (`DB_RETRY_MAX_DELAY_MS`).
+  pub retry_max_delay_ms: u64,
+  /// Consecutive failed connection attempts that open the circuit; 0 disables it (`DB_CIRCUIT_FAILURE_THRESHOLD`).
+  pub circuit_failure_threshold: u32,
+  /// How long an open circuit fails requests at once before probing the database again (`DB_CIRCUIT_OPEN_SECS`).
+  pub circuit_open_secs: u64,
+}
+
+impl Default for DbResilienceConfig {
+  fn default() -> Self {
+    Self {
+      retry_attempts: 3,
+      retry_base_delay_ms: 50,
+      retry_max_delay_ms: 1_000,
+      circuit_failure_threshold: 5,
+      circuit_open_secs: 30,
+    }
+  }
+}
+
+/// Lifecycle of the API versions.
+///
+/// v1 routes that have a v2 successor only carry deprecation headers once
+/// `v1_deprecated_at` is set.
+#[derive(Debug, Clone, Default)]
+pub struct ApiVersionConfig {
+  /// When v1 was deprecated, as an RFC 3339 timestamp or `YYYY-MM-DD` date (`API_V1_DEPRECATED_AT`).
+  pub v1_deprecated_at: Option<DateTime<Utc>>,
+  /// When v1 will be removed (`API_V1_SUNSET_AT`).
+  pub v1_sunset_at: Option<DateTime<Utc>>,
+}
+
+/// Settings for real-time event delivery.
+#[derive(Debug, Clone)]
+pub struct RealtimeConfig {
+  /// Events buffered per subscriber before a slow client starts missing events (`EVENT_BUS_CAPACITY`).
+  pub event_bus_capacity: usize,
+  /// Interval between WebSocket pings sent to keep idle connections open (`WS_HEARTBEAT_SECS`).
+  pub heartbeat_secs: u64,
+  /// How long the lock on a record being edited lasts unless renewed (`RECORD_LOCK_TTL_SECS`).
+  pub lock_ttl_secs: u64,
+}
+
+impl Default for RealtimeConfig {
+  fn default() -> Self {
+    Self {
+      event_bus_capacity: 1024,
+      heartbeat_secs: 30,
+      lock_ttl_secs: 120,
+    }
+  }
+}
+
+/// Optional Redis connection shared by all instances.
+///
+/// When set, the rate limiter, the token revocation list, session activity and the idempotency store live in
+/// Redis so every instance enforces the same state; otherwise they fall back to in-memory
+/// stores (revocations and session activity to Postgres).
+#[derive(Debug, Clone, Default)]
+pub struct RedisConfig {
+  /// Connection URL, e.g. `redis://localhost:6379` (`REDIS_URL`).
+  pub url: Option<String>,
+}
+
+/// Settings for `Idempotency-Key` handling on write requests.
+#[derive(Debug, Clone)]
+pub struct IdempotencyConfig {
+  /// How long a stored response is replayed for the same key (`IDEMPOTENCY_TTL_SECS`).
+  pub ttl_secs: u64,
+}
+
+impl Default for IdempotencyConfig {
+  fn default() -> Self {
+    Self { ttl_secs: 24 * 60 * 60 }
+  }
+}
+
+/// Settings for the cache of workspace roles checked on every request.
+#[derive(Debug, Clone)]
+pub struct RoleCacheConfig {
+  /// How long a loaded role is reused; other instances may see a role change this late, 0 disables the cache (`WORKSPACE_ROLE_CACHE_TTL_SECS`).
+  pub ttl_secs: u64,
+  /// How long the workspace and role embedded in an access token at login or switch are trusted
+  /// without a lookup; role changes and removals are not seen before, 0 disables embedding (`WORKSPACE_CLAIM_TTL_SECS`).
+  pub claim_ttl_secs: u64,
+}
+
+impl Default for RoleCacheConfig {
+  fn default() -> Self {
+    Self {
+      ttl_secs: 30,
+      claim_ttl_secs: 0,
+    }
+  }
+}
+
+/// Settings for the cache of workspace settings, whose IP allowlist is checked on every request.
+#[derive(Debug, Clone)]
+pub struct WorkspaceSettingsConfig {
+  /// How long loaded settings are reused; other instances may see a change this late, 0 disables the cache (`WORKSPACE_SETTINGS_CACHE_TTL_SECS`).
+  pub cache_ttl_secs: u64,
+}
+
+impl Default for WorkspaceSettingsConfig {
+  fn default() -> Self {
+    Self { cache_ttl_secs: 30 }
+  }
+}
+
+/// Settings for the sessions of access tokens, on top of their absolute expiry.
+#[derive(Debug, Clone, Default)]
+pub struct SessionConfig {
+  /// A token unused for this long stops being accepted with `SESSION_IDLE_TIMEOUT`, even before it
+  /// expires; 0 disables the idle timeout (`SESSION_IDLE_TIMEOUT_SECS`).
+  pub idle_timeout_secs: u64,
+}
+
+/// Settings for the HTTP access log.
+#[derive(Debug, Clone)]
+pub struct AccessLogConfig {
+  /// Also log request headers and JSON bodies, with credentials redacted (`ACCESS_LOG_PAYLOADS`).
+  pub log_payloads: bool,
+  /// Largest body logged when payload logging is on, in bytes (`ACCESS_LOG_MAX_BODY_BYTES`).
+  pub max_body_bytes: usize,
+}
+
+impl Default for AccessLogConfig {
+  fn default() -> Self {
+    Self {
+      log_payloads: false,
+      max_body_bytes: 16 * 1024,
+    }
+  }
+}
+
+/// Settings for API usage metering and the monthly request quotas of workspaces.
+///
+/// Workspaces with an active or past due subscription are on the paid plan, all others on the
+/// free plan. A quota or record limit of 0 leaves the plan unlimited.
+#[derive(Debug, Clone)]
+pub struct UsageConfig {
+  /// Whether requests are metered and quotas enforced at all (`USAGE_METERING_ENABLED`).
+  pub enabled: bool,
+  /// Interval between writes of the buffered counters to `api_usage` (`USAGE_FLUSH_INTERVAL_SECS`).
+  pub flush_interval_secs: u64,
+  /// Requests per calendar month (UTC) for workspaces on the free plan (`USAGE_FREE_MONTHLY_REQUESTS`).
+  pub free_monthly_requests: u64,
+  /// Requests per calendar month (UTC) for workspaces on the paid plan (`USAGE_PAID_MONTHLY_REQUESTS`).
+  pub paid_monthly_requests: u64,
+  /// How long a workspace's monthly total and plan are reused before they are reloaded (`USAGE_QUOTA_CACHE_SECS`).
+  pub quota_cache_secs: u64,
+  /// Contacts and products a workspace on the free plan may hold (`USAGE_FREE_MAX_RECORDS`).
+  pub free_max_records: u64,
+  /// Contacts and products a workspace on the paid plan may hold (`USAGE_PAID_MAX_RECORDS`).
+  pub paid_max_records: u64,
+}
+
+impl Default for UsageConfig {
+  fn default() -> Self {
+    Self {
+      enabled: true,
+      flush_interval_secs: 10,
+      free_monthly_requests: 10_000,
+      paid_monthly_requests: 0,
+      quota_cache_secs: 60,
+      free_max_records: 0,
+      paid_max_records: 0,
+    }
+  }
+}
+
+/// Settings for the trial period of workspaces.
+///
+/// Trials start with the workspace and last 14 days (set in `workspace_trials`, where they can be
+/// extended); afterwards a workspace without an active or past due subscription is read-only.
+#[derive(Debug, Clone)]
+pub struct TrialConfig {
+  /// Whether writes to workspaces with an expired trial are rejected (`TRIAL_ENFORCED`).
+  pub enforced: bool,
+  /// Days before the end of a trial at which its workspace is warned (`TRIAL_WARNING_DAYS`).
+  pub warning_days: u32,
+  /// Interval between checks for trials to warn about or expire (`TRIAL_CHECK_INTERVAL_SECS`).
+  pub check_interval_secs: u64,
+}
+
+impl Default for TrialConfig {
+  fn default() -> Self {
+    Self {
+      enforced: true,
+      warning_days: 3,
+      check_interval_secs: 3600,
+    }
+  }
+}
+
+/// Settings for feature flags and their admin API.
+#[derive(Debug, Clone)]
+pub struct FeatureFlagConfig {
+  /// How long a resolved flag is reused; other instances may see a toggle this late, 0 disables the cache (`FEATURE_FLAG_CACHE_TTL_SECS`).
+  pub cache_ttl_secs: u64,
+  /// Users allowed to manage flags, as comma-separated user ids (`FEATURE_FLAG_ADMIN_IDS`).
+  pub admin_user_ids: Vec<Uuid>,
+}
+
+impl Default for FeatureFlagConfig {
+  fn default() -> Self {
+    Self {
+      cache_ttl_secs: 30,
+      admin_user_ids: Vec::new(),
+    }
+  }
+}
+
+/// Settings for the purge of data past the retention policies of workspaces.
+#[derive(Debug, Clone)]
+pub struct RetentionConfig {
+  /// Interval between purges (`RETENTION_PURGE_INTERVAL_SECS`).
+  pub purge_interval_secs: u64,
+}
+
+impl Default for RetentionConfig {
+  fn default() -> Self {
+    Self { purge_interval_secs: 3600 }
+  }
+}
+
+/// Settings for the scheduled reports (see `modules::reports`).
+#[derive(Debug, Clone)]
+pub struct ReportConfig {
+  /// Interval between checks for due report schedules (`REPORT_CHECK_INTERVAL_SECS`).
+  pub check_interval_secs: u64,
+  /// Time allowed for a webhook to answer a posted report (`REPORT_WEBHOOK_TIMEOUT_SECS`).
+  pub webhook_timeout_secs: u64,
+}
+
+impl Default for ReportConfig {
+  fn default() -> Self {
+    Self {
+      check_interval_secs: 60,
+      webhook_timeout_secs: 10,
+    }
+  }
+}
+
+/// Settings for the polling triggers of automation platforms (see `modules::triggers`).
+#[derive(Debug, Clone)]
+pub struct TriggerConfig {
+  /// Changes younger than this are held back, so a slow transaction committing an older
+  /// `updated_at` cannot land behind a cursor already handed out (`TRIGGER_SETTLE_SECS`).
+  pub settle_secs: u64,
+}
+
+impl Default for TriggerConfig {
+  fn default() -> Self {
+    Self { settle_secs: 5 }
+  }
+}
+
+/// Settings of the PDF rendering of records (see `modules::rendering`).
+#[derive(Debug, Clone)]
+pub struct RenderingConfig {
+  /// Chrome or Chromium binary printing the PDFs; rendering is disabled while unset (`PDF_CHROMIUM`).
+  pub chromium: Option<String>,
+  /// Extra arguments for the browser, e.g. `--no-sandbox` in containers (`PDF_CHROMIUM_ARGS`, space-separated).
+  pub chromium_args: Vec<String>,
+  /// Maximum time the browser may take to print one document (`PDF_RENDER_TIMEOUT_SECS`).
+  pub timeout_secs: u64,
+}
+
+impl Default for RenderingConfig {
+  fn default() -> Self {
+    Self {
+      chromium: None,
+      chromium_args: Vec::new(),
+      timeout_secs: 20,
+    }
+  }
+}
+
+/// Settings of the syncs with online stores (see `modules::connectors`).
+#[derive(Debug, Clone)]
+pub struct ConnectorConfig {
+  /// Interval between checks for due syncs (`CONNECTOR_CHECK_INTERVAL_SECS`).
+  pub check_interval_secs: u64,
+  /// Time allowed for a store to answer one request (`CONNECTOR_REQUEST_TIMEOUT_SECS`).
+  pub request_timeout_secs: u64,
+}
+
+impl Default for ConnectorConfig {
+  fn default() -> Self {
+    Self {
+      check_interval_secs: 60,
+      request_timeout_secs: 20,
+    }
+  }
+}
+
+/// Where daily exchange rates are fetched from (see `modules::currencies`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExchangeRateSource {
+  /// The euro foreign exchange reference rates of the European Central Bank, without a key.
+  #[default]
+  Ecb,
+  /// openexchangerates.org, with the app ID of an account.
+  OpenExchangeRates,
+}
+
+impl FromStr for ExchangeRateSource {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    match value.to_ascii_lowercase().as_str() {
+      "ecb" => Ok(Self::Ecb),
+      "openexchangerates" | "oxr" => Ok(Self::OpenExchangeRates),
+      other => Err(format!("unknown exchange rate provider '{}'", other)),
+    }
+  }
+}
+
+/// Settings of the exchange rate sync (see `modules::currencies`).
+#[derive(Debug, Clone)]
+pub struct ExchangeRateConfig {
+  /// `ecb` or `openexchangerates` (`EXCHANGE_RATE_PROVIDER`).
+  pub provider: ExchangeRateSource,
+  /// App ID of the openexchangerates.org account; that provider is disabled while unset (`OPENEXCHANGERATES_APP_ID`).
+  pub openexchangerates_app_id: Option<String>,
+  /// Currency the rates are expressed in: one unit of each currency is worth `rate` of it (`EXCHANGE_RATE_BASE`).
+  pub base_currency: String,
+  /// Interval between fetches of the latest rates; providers publish once a day (`EXCHANGE_RATE_SYNC_INTERVAL_SECS`).
+  pub sync_interval_secs: u64,
+  /// Time allowed for the provider to answer (`EXCHANGE_RATE_TIMEOUT_SECS`).
+  pub timeout_secs: u64,
+}
+
+impl Default for ExchangeRateConfig {
+  fn default() -> Self {
+    Self {
+      provider: ExchangeRateSource::default(),
+      openexchangerates_app_id: None,
+      base_currency: "IDR".to_string(),
+      sync_interval_secs: 6 * 3600,
+      timeout_secs: 20,
+    }
+  }
+}
+
+/// Which geocoding service address lookups are sent to (see `modules::geo`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GeocoderSource {
+  /// Nominatim, the OpenStreetMap geocoder, public or self-hosted, without a key.
+  #[default]
+  Nominatim,
+  /// The Mapbox Geocoding API, with the access token of an account.
+  Mapbox,
+}
+
+impl FromStr for GeocoderSource {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    match value.to_ascii_lowercase().as_str() {
+      "nominatim" | "osm" => Ok(Self::Nominatim),
+      "mapbox" => Ok(Self::Mapbox),
+      other => Err(format!("unknown geocoding provider '{}'", other)),
+    }
+  }
+}
+
+/// Settings of the address autocomplete proxy (see `modules::geo`).
+#[derive(Debug, Clone)]
+pub struct GeoConfig {
+  /// `nominatim` or `mapbox` (`GEOCODER_PROVIDER`).
+  pub provider: GeocoderSource,
+  /// Address of the Nominatim instance; the public one allows a request per second (`NOMINATIM_URL`).
+  pub nominatim_url: String,
+  /// Access token of the Mapbox account; that provider is disabled while unset (`MAPBOX_ACCESS_TOKEN`).
+  pub mapbox_access_token: Option<String>,
+  /// Seconds the suggestions for a query are kept and served again; 0 disables the cache (`GEOCODER_CACHE_TTL_SECS`).
+  pub cache_ttl_secs: u64,
+  /// Lookups a workspace may send to the provider per day, UTC; 0 lifts the limit (`GEOCODER_DAILY_QUOTA`).
+  pub daily_quota: u32,
+  /// Time allowed for the provider to answer (`GEOCODER_TIMEOUT_SECS`).
+  pub timeout_secs: u64,
+}
+
+impl Default for GeoConfig {
+  fn default() -> Self {
+    Self {
+      provider: GeocoderSource::default(),
+      nominatim_url: "https://nominatim.openstreetmap.org".to_string(),
+      mapbox_access_token: None,
+      cache_ttl_secs: 24 * 3600,
+      daily_quota: 1000,
+      timeout_secs: 10,
+    }
+  }
+}
+
+/// Which email domains may register (see `modules::auth::email_domains`).
+///
+/// A domain also covers its subdomains: denying `example.com` denies `mail.example.com`.
+#[derive(Debug, Clone, Default)]
+pub struct RegistrationConfig {
+  /// When not empty, only these domains may register (`REGISTRATION_ALLOWED_DOMAINS`, comma-separated).
+  pub allowed_email_domains: Vec<String>,
+  /// Domains that may not register, checked after the allowlist (`REGISTRATION_DENIED_DOMAINS`, comma-separated).
+  pub denied_email_domains: Vec<String>,
+  /// Also refuse the well-known disposable email providers (`REGISTRATION_BLOCK_DISPOSABLE`).
+  pub block_disposable_domains: bool,
+}
+
+/// Personal data exports of users (see `modules::user_export`).
+#[derive(Debug, Clone)]
+pub struct UserExportConfig {
+  /// Page of the frontend linked from the email sent when an export is ready; the export id is
+  /// appended as a path segment (`EXPORT_LINK_BASE_URL`).
+  pub link_base_url: String,
+  /// Days a ready export can be downloaded before it is removed (`EXPORT_RETENTION_DAYS`).
+  pub retention_days: u32,
+}
+
+impl Default for UserExportConfig {
+  fn default() -> Self {
+    Self {
+      link_base_url: "http://localhost:3000/account/exports".to_string(),
+      retention_days: 7,
+    }
+  }
+}
+
+/// Password resets by emailed token (see `auth::password_reset_repository`).
+#[derive(Debug, Clone)]
+pub struct PasswordResetConfig {
+  /// Page of the frontend linked from the password reset email; the token is appended as a path
+  /// segment (`PASSWORD_RESET_LINK_BASE_URL`).
+  pub link_base_url: String,
+  /// Minutes a reset token can be used after it was emailed (`PASSWORD_RESET_TOKEN_TTL_MINUTES`).
+  pub token_ttl_minutes: u32,
+}
+
+impl Default for PasswordResetConfig {
+  fn default() -> Self {
+    Self {
+      link_base_url: "http://localhost:3000/reset-password".to_string(),
+      token_ttl_minutes: 60,
+    }
+  }
+}
+
+/// Where `JWT_SECRET`, `JWT_PREVIOUS_SECRET` and `DATABASE_URL` are read from (see `secrets`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SecretsBackend {
+  /// The environment, like every other setting.
+  #[default]
+  Env,
+  /// A KV version 2 secret of HashiCorp Vault.
+  Vault,
+  /// A JSON secret of AWS Secrets Manager.
+  Aws,
+}
+
+impl FromStr for SecretsBackend {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    match value.to_ascii_lowercase().as_str() {
+      "env" => Ok(Self::Env),
+      "vault" => Ok(Self::Vault),
+      "aws" | "aws-secrets-manager" => Ok(Self::Aws),
+      other => Err(format!("unknown secrets backend '{}'", other)),
+    }
+  }
+}
+
+/// Secrets loaded from a secrets backend at startup instead of the environment. The Vault and
+/// AWS backends need the `secrets` feature.
+///
+/// The backend's secret is a JSON object whose keys are the environment variables it replaces;
+/// variables it does not have are still read from the environment.
+#[derive(Debug, Clone)]
+pub struct SecretsConfig {
+  /// `env`, `vault` or `aws` (`SECRETS_PROVIDER`).
+  pub backend: SecretsBackend,
+  /// Interval between reloads of the secrets from the backend, 0 only loads them at startup (`SECRETS_REFRESH_INTERVAL_SECS`).
+  pub refresh_interval_secs: u64,
+  /// Address of the Vault server, e.g. `https://vault.internal:8200` (`VAULT_ADDR`).
+  pub vault_addr: Option<String>,
+  /// Token authenticating with Vault (`VAULT_TOKEN`).
+  pub vault_token: Option<String>,
+  /// Mount of the KV version 2 secrets engine (`VAULT_KV_MOUNT`).
+  pub vault_mount: String,
+  /// Path of the secret in the mount (`VAULT_SECRET_PATH`).
+  pub vault_path: String,
+  /// Name or ARN of the secret in AWS Secrets Manager (`AWS_SECRET_ID`).
+  pub aws_secret_id: Option<String>,
+  /// Region of the secret (`AWS_REGION`).
+  pub aws_region: String,
+  /// Endpoint replacing `https://secretsmanager.{region}.amazonaws.com`, e.g. for LocalStack (`AWS_SECRETS_MANAGER_ENDPOINT`).
+  pub aws_endpoint: Option<String>,
+  /// Credentials of an IAM user or role allowed to read the secret (`AWS_ACCESS_KEY_ID`,
+  /// `AWS_SECRET_ACCESS_KEY` and, for temporary credentials, `AWS_SESSION_TOKEN`).
+  pub aws_access_key_id: Option<String>,
+  pub aws_secret_access_key: Option<String>,
+  pub aws_session_token: Option<String>,
+}
+
+impl Default for SecretsConfig {
+  fn default() -> Self {
+    Self {
+      backend: SecretsBackend::Env,
+      refresh_interval_secs: 300,
+      vault_addr: None,
+      vault_token: None,
+      vault_mount: "secret".to_string(),
+      vault_path: "myapp".to_string(),
+      aws_secret_id: None,
+      aws_region: "us-east-1".to_string(),
+      aws_endpoint: None,
+      aws_access_key_id: None,
+      aws_secret_access_key: None,
+      aws_session_token: None,
+    }
+  }
+}
+
+/// Keys of the application-level encryption of sensitive columns (see `utils::field_encryption`).
+#[derive(Debug, Clone, Default)]
+pub struct EncryptionConfig {
+  /// `key_id:base64_key` entries, the first encrypting new values (`FIELD_ENCRYPTION_KEYS`,
+  /// comma-separated, also read from the secrets backend). Without keys, sensitive fields cannot be stored.
+  pub keys: Vec<String>,
+}
+
+/// Database backups, only used when built with the `backups` feature.
+///
+/// Backups are taken with `pg_dump` and stored in an S3 bucket (or an S3 compatible store such
+/// as MinIO) under `{s3_prefix}/{environment}/`; nothing is scheduled until `s3_bucket` is set.
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+  /// Name of this deployment, separating its backups from those of other environments (`BACKUP_ENVIRONMENT`).
+  pub environment: String,
+  /// Interval between scheduled backups (`BACKUP_INTERVAL_SECS`).
+  pub interval_secs: u64,
+  /// Number of backups kept per environment; older ones are deleted after each backup (`BACKUP_KEEP`).
+  pub keep: usize,
+  /// Database dumped and restored, `DATABASE_URL` unless set (`BACKUP_DATABASE_URL`).
+  pub database_url: Option<String>,
+  /// `pg_dump` binary (`BACKUP_PG_DUMP`).
+  pub pg_dump: String,
+  /// `pg_restore` binary (`BACKUP_PG_RESTORE`).
+  pub pg_restore: String,
+  /// Bucket the backups are stored in (`BACKUP_S3_BUCKET`).
+  pub s3_bucket: Option<String>,
+  /// Region of the bucket (`BACKUP_S3_REGION`).
+  pub s3_region: String,
+  /// Endpoint of an S3 compatible store, AWS when unset (`BACKUP_S3_ENDPOINT`).
+  pub s3_endpoint: Option<String>,
+  /// Key prefix of all backups in the bucket (`BACKUP_S3_PREFIX`).
+  pub s3_prefix: String,
+  /// Access key ID (`BACKUP_S3_ACCESS_KEY_ID`).
+  pub s3_access_key_id: Option<String>,
+  /// Secret access key (`BACKUP_S3_SECRET_ACCESS_KEY`).
+  pub s3_secret_access_key: Option<String>,
+  /// Users allowed to list backups through the API (`BACKUP_ADMIN_IDS`, comma-separated).
+  pub admin_user_ids: Vec<Uuid>,
+}
+
+impl Default for BackupConfig {
+  fn default() -> Self {
+    Self {
+      environment: "development".to_string(),
+      interval_secs: 86_400,
+      keep: 14,
+      database_url: None,
+      pg_dump: "pg_dump".to_string(),
+      pg_restore: "pg_restore".to_string(),
+      s3_bucket: None,
+      s3_region: "us-east-1".to_string(),
+      s3_endpoint: None,
+      s3_prefix: "backups".to_string(),
+      s3_access_key_id: None,
+      s3_secret_access_key: None,
+      admin_user_ids: Vec::new(),
+    }
+  }
+}
+
+/// Stripe billing, only used when built with the `billing` feature.
+///
+/// Checkout and the customer portal need `secret_key` and `price_id`; webhooks are only accepted
+/// once `webhook_secret` is set.
+#[derive(Debug, Clone)]
+pub struct BillingConfig {
+  /// Secret API key of the Stripe account (`STRIPE_SECRET_KEY`).
+  pub secret_key: Option<String>,
+  /// Signing secret of the webhook endpoint, `whsec_...` (`STRIPE_WEBHOOK_SECRET`).
+  pub webhook_secret: Option<String>,
+  /// Price the checkout session subscribes a workspace to (`STRIPE_PRICE_ID`).
+  pub price_id: Option<String>,
+  /// Where Stripe sends the user after a completed checkout (`BILLING_SUCCESS_URL`).
+  pub success_url: String,
+  /// Where Stripe sends the user after an abandoned checkout (`BILLING_CANCEL_URL`).
+  pub cancel_url: String,
+  /// Where the customer portal links back to (`BILLING_PORTAL_RETURN_URL`).
+  pub portal_return_url: String,
+  /// Maximum age of a webhook signature, in seconds, against replayed deliveries (`STRIPE_WEBHOOK_TOLERANCE_SECS`).
+  pub webhook_tolerance_secs: u64,
+  /// Base URL of the Stripe API, overridden to point at a mock server (`STRIPE_API_BASE`).
+  pub api_base: String,
+}
+
+impl Default for BillingConfig {
+  fn default() -> Self {
+    Self {
+      secret_key: None,
+      webhook_secret: None,
+      price_id: None,
+      success_url: "http://localhost:3000/billing/success".to_string(),
+      cancel_url: "http://localhost:3000/billing/cancel".to_string(),
+      portal_return_url: "http://localhost:3000/billing".to_string(),
+      webhook_tolerance_secs: 300,
+      api_base: "https://api.stripe.com".to_string(),
+    }
+  }
+}
+
+/// Settings for the internal gRPC server, which only runs when built with the `grpc` feature.
+#[derive(Debug, Clone)]
+pub struct GrpcConfig {
+  /// Port the gRPC server listens on, on the same host as the HTTP server (`GRPC_PORT`).
+  pub port: u16,
+}
+
+impl Default for GrpcConfig {
+  fn default() -> Self {
+    Self { port: 50051 }
+  }
+}
+
+/// Optional native TLS settings.
+///
+/// HTTPS is served only when both paths are set; otherwise the server speaks plain HTTP
+/// and is expected to sit behind a TLS-terminating proxy.
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+  /// Path to the PEM encoded certificate chain (`TLS_CERT_PATH`).
+  pub cert_path: Option<String>,
+  /// Path to the PEM encoded private key (`TLS_KEY_PATH`).
+  pub key_path: Option<String>,
+}
+
+/// Maximum request body sizes, applied per route group.
+#[derive(Debug, Clone)]
+pub struct BodyLimitConfig {
+  /// Limit for regular JSON endpoints, in bytes (`BODY_LIMIT_BYTES`).
+  pub default_bytes: usize,
+  /// Limit for import and upload endpoints, in bytes (`UPLOAD_BODY_LIMIT_BYTES`).
+  pub upload_bytes: usize,
+}
+
+impl Default for BodyLimitConfig {
+  fn default() -> Self {
+    Self {
+      default_bytes: 2 * 1024 * 1024,
+      upload_bytes: 25 * 1024 * 1024,
+    }
+  }
+}
+
+/// Settings for the request rate limiter.
+///
+/// Budgets are counted per fixed window and keyed by user and workspace, so a
+/// user working in two workspaces gets an independent budget in each one.
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+  /// Whether rate limiting is applied at all (`RATE_LIMIT_ENABLED`).
+  pub enabled: bool,
+  /// Length of a rate limit window in seconds (`RATE_LIMIT_WINDOW_SECS`).
+  pub window_secs: u64,
+  /// Requests allowed per window for read routes: GET, HEAD, OPTIONS (`RATE_LIMIT_READ_MAX`).
+  pub read_max: u32,
+  /// Requests allowed per window for write routes: POST, PUT, PATCH, DELETE (`RATE_LIMIT_WRITE_MAX`).
+  pub write_max: u32,
+  /// Requests allowed per window for unauthenticated callers, keyed by client IP (`RATE_LIMIT_ANONYMOUS_MAX`).
+  pub anonymous_max: u32,
+}
+
+impl Default for RateLimitConfig {
+  fn default() -> Self {
+    Self {
+      enabled: true,
+      window_secs: 60,
+      read_max: 300,
+      write_max: 60,
+      anonymous_max: 30,
+    }
+  }
+}
+
+impl AppConfig {
+  /// Builds the configuration from environment variables, falling back to defaults.
+  pub fn from_env() -> Self {
+    Self {
+      server: ServerConfig::from_env(),
+      database: DatabaseConfig::from_env(),
+      db_resilience: DbResilienceConfig::from_env(),
+      rate_limit: RateLimitConfig::from_env(),
+      body_limit: BodyLimitConfig::from_env(),
+      tls: TlsConfig::from_env(),
+      api_versions: ApiVersionConfig::from_env(),
+      grpc: GrpcConfig::from_env(),
+      realtime: RealtimeConfig::from_env(),
+      redis: RedisConfig::from_env(),
+      idempotency: IdempotencyConfig::from_env(),
+      role_cache: RoleCacheConfig::from_env(),
+      workspace_settings: WorkspaceSettingsConfig::from_env(),
+      sessions: SessionConfig::from_env(),
+      access_log: AccessLogConfig::from_env(),
+      billing: BillingConfig::from_env(),
+      usage: UsageConfig::from_env(),
+      trial: TrialConfig::from_env(),
+      feature_flags: FeatureFlagConfig::from_env(),
+      retention: RetentionConfig::from_env(),
+      backups: BackupConfig::from_env(),
+      reports: ReportConfig::from_env(),
+      triggers: TriggerConfig::from_env(),
+      rendering: RenderingConfig::from_env(),
+      connectors: ConnectorConfig::from_env(),
+      exchange_rates: ExchangeRateConfig::from_env(),
+      geo: GeoConfig::from_env(),
+      registration: RegistrationConfig::from_env(),
+      user_exports: UserExportConfig::from_env(),
+      password_reset: PasswordResetConfig::from_env(),
+      secrets: SecretsConfig::from_env(),
+      encryption: EncryptionConfig::from_env(),
+    }
+  }
+}
+
+impl ServerConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      request_timeout_secs: env_or("REQUEST_TIMEOUT_SECS", defaults.request_timeout_secs).max(1),
+      max_concurrent_requests: env_or("MAX_CONCURRENT_REQUESTS", defaults.max_concurrent_requests).max(1),
+      trusted_proxies: env_list("TRUSTED_PROXIES"),
+    }
+  }
+}
+
+impl DatabaseConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    let max_connections = env_or("DB_MAX_CONNECTIONS", defaults.max_connections).max(1);
+    Self {
+      max_connections,
+      min_connections: env_or("DB_MIN_CONNECTIONS", defaults.min_connections).min(max_connections),
+      acquire_timeout_secs: env_or("DB_ACQUIRE_TIMEOUT_SECS", defaults.acquire_timeout_secs).max(1),
+      idle_timeout_secs: env_or("DB_IDLE_TIMEOUT_SECS", defaults.idle_timeout_secs),
+      statement_timeout_ms: env_or("DB_STATEMENT_TIMEOUT_MS", defaults.statement_timeout_ms),
+      tenant_isolation: env_or("TENANT_ISOLATION", defaults.tenant_isolation),
+    }
+  }
+}
+
+impl DbResilienceConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      retry_attempts: env_or("DB_RETRY_ATTEMPTS", defaults.retry_attempts).max(1),
+      retry_base_delay_ms: env_or("DB_RETRY_BASE_DELAY_MS", defaults.retry_base_delay_ms),
+      retry_max_delay_ms: env_or("DB_RETRY_MAX_DELAY_MS", defaults.retry_max_delay_ms),
+      circuit_failure_threshold: env_or("DB_CIRCUIT_FAILURE_THRESHOLD", defaults.circuit_failure_threshold),
+      circuit_open_secs: env_or("DB_CIRCUIT_OPEN_SECS", defaults.circuit_open_secs).max(1),
+    }
+  }
+}
+
+impl ApiVersionConfig {
+  pub fn from_env() -> Self {
+    Self {
+      v1_deprecated_at: env_datetime("API_V1_DEPRECATED_AT"),
+      v1_sunset_at: env_datetime("API_V1_SUNSET_AT"),
+    }
+  }
+}
+
+impl RealtimeConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      event_bus_capacity: env_or("EVENT_BUS_CAPACITY", defaults.event_bus_capacity).max(1),
+      heartbeat_secs: env_or("WS_HEARTBEAT_SECS", defaults.heartbeat_secs).max(1),
+      lock_ttl_secs: env_or("RECORD_LOCK_TTL_SECS", defaults.lock_ttl_secs).max(1),
+    }
+  }
+}
+
+impl RedisConfig {
+  pub fn from_env() -> Self {
+    Self {
+      url: std::env::var("REDIS_URL").ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty()),
+    }
+  }
+}
+
+impl IdempotencyConfig {
+  pub fn from_env() -> Self {
+    Self {
+      ttl_secs: env_or("IDEMPOTENCY_TTL_SECS", Self::default().ttl_secs).max(1),
+    }
+  }
+}
+
+impl RoleCacheConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      ttl_secs: env_or("WORKSPACE_ROLE_CACHE_TTL_SECS", defaults.ttl_secs),
+      claim_ttl_secs: env_or("WORKSPACE_CLAIM_TTL_SECS", defaults.claim_ttl_secs),
+    }
+  }
+}
+
+impl WorkspaceSettingsConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      cache_ttl_secs: env_or("WORKSPACE_SETTINGS_CACHE_TTL_SECS", defaults.cache_ttl_secs),
+    }
+  }
+}
+
+impl SessionConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      idle_timeout_secs: env_or("SESSION_IDLE_TIMEOUT_SECS", defaults.idle_timeout_secs),
+    }
+  }
+}
+
+impl AccessLogConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      log_payloads: env_or("ACCESS_LOG_PAYLOADS", defaults.log_payloads),
+      max_body_bytes: env_or("ACCESS_LOG_MAX_BODY_BYTES", defaults.max_body_bytes),
+    }
+  }
+}
+
+impl UsageConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      enabled: env_or("USAGE_METERING_ENABLED", defaults.enabled),
+      flush_interval_secs: env_or("USAGE_FLUSH_INTERVAL_SECS", defaults.flush_interval_secs).max(1),
+      free_monthly_requests: env_or("USAGE_FREE_MONTHLY_REQUESTS", defaults.free_monthly_requests),
+      paid_monthly_requests: env_or("USAGE_PAID_MONTHLY_REQUESTS", defaults.paid_monthly_requests),
+      quota_cache_secs: env_or("USAGE_QUOTA_CACHE_SECS", defaults.quota_cache_secs),
+      free_max_records: env_or("USAGE_FREE_MAX_RECORDS", defaults.free_max_records),
+      paid_max_records: env_or("USAGE_PAID_MAX_RECORDS", defaults.paid_max_records),
+    }
+  }
+
+  /// The monthly request quota of a plan, `None` when it is unlimited.
+  pub fn monthly_quota(&self, paid: bool) -> Option<u64> {
+    let quota = if paid { self.paid_monthly_requests } else { self.free_monthly_requests };
+    (quota > 0).then_some(quota)
+  }
+
+  /// The records a plan may hold, `None` when it is unlimited.
+  pub fn record_limit(&self, paid: bool) -> Option<u64> {
+    let limit = if paid { self.paid_max_records } else { self.free_max_records };
+    (limit > 0).then_some(limit)
+  }
+}
+
+impl TrialConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      enforced: env_or("TRIAL_ENFORCED", defaults.enforced),
+      warning_days: env_or("TRIAL_WARNING_DAYS", defaults.warning_days),
+      check_interval_secs: env_or("TRIAL_CHECK_INTERVAL_SECS", defaults.check_interval_secs).max(1),
+    }
+  }
+}
+
+impl FeatureFlagConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      cache_ttl_secs: env_or("FEATURE_FLAG_CACHE_TTL_SECS", defaults.cache_ttl_secs),
+      admin_user_ids: env_list("FEATURE_FLAG_ADMIN_IDS"),
+    }
+  }
+}
+
+impl RetentionConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      purge_interval_secs: env_or("RETENTION_PURGE_INTERVAL_SECS", defaults.purge_interval_secs).max(1),
+    }
+  }
+}
+
+impl ReportConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      check_interval_secs: env_or("REPORT_CHECK_INTERVAL_SECS", defaults.check_interval_secs).max(1),
+      webhook_timeout_secs: env_or("REPORT_WEBHOOK_TIMEOUT_SECS", defaults.webhook_timeout_secs).max(1),
+    }
+  }
+}
+
+impl TriggerConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      settle_secs: env_or("TRIGGER_SETTLE_SECS", defaults.settle_secs),
+    }
+  }
+}
+
+impl RenderingConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      chromium: std::env::var("PDF_CHROMIUM").ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty()),
+      chromium_args: std::env::var("PDF_CHROMIUM_ARGS")
+        .map(|args| args.split_whitespace().map(str::to_string).collect())
+        .unwrap_or(defaults.chromium_args),
+      timeout_secs: env_or("PDF_RENDER_TIMEOUT_SECS", defaults.timeout_secs).max(1),
+    }
+  }
+}
+
+impl ConnectorConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      check_interval_secs: env_or("CONNECTOR_CHECK_INTERVAL_SECS", defaults.check_interval_secs).max(1),
+      request_timeout_secs: env_or("CONNECTOR_REQUEST_TIMEOUT_SECS", defaults.request_timeout_secs).max(1),
+    }
+  }
+}
+
+impl GeoConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      provider: env_or("GEOCODER_PROVIDER", defaults.provider),
+      nominatim_url: std::env::var("NOMINATIM_URL")
+        .ok()
+        .map(|v| v.trim().trim_end_matches('/').to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or(defaults.nominatim_url),
+      mapbox_access_token: std::env::var("MAPBOX_ACCESS_TOKEN")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty()),
+      cache_ttl_secs: env_or("GEOCODER_CACHE_TTL_SECS", defaults.cache_ttl_secs),
+      daily_quota: env_or("GEOCODER_DAILY_QUOTA", defaults.daily_quota),
+      timeout_secs: env_or("GEOCODER_TIMEOUT_SECS", defaults.timeout_secs).max(1),
+    }
+  }
+
+  /// The lookups a workspace may send per day, `None` without a limit.
+  pub fn daily_quota(&self) -> Option<u32> {
+    (self.daily_quota > 0).then_some(self.daily_quota)
+  }
+}
+
+impl ExchangeRateConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    let base_currency = std::env::var("EXCHANGE_RATE_BASE")
+      .ok()
+      .map(|v| v.trim().to_ascii_uppercase())
+      .filter(|v| v.len() == 3 && v.chars().all(|c| c.is_ascii_uppercase()));
+    Self {
+      provider: env_or("EXCHANGE_RATE_PROVIDER", defaults.provider),
+      openexchangerates_app_id: std::env::var("OPENEXCHANGERATES_APP_ID")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty()),
+      base_currency: base_currency.unwrap_or(defaults.base_currency),
+      sync_interval_secs: env_or("EXCHANGE_RATE_SYNC_INTERVAL_SECS", defaults.sync_interval_secs).max(60),
+      timeout_secs: env_or("EXCHANGE_RATE_TIMEOUT_SECS", defaults.timeout_secs).max(1),
+    }
+  }
+}
+
+impl RegistrationConfig {
+  pub fn from_env() -> Self {
+    // Accept `@example.com` and `.example.com` as well, in any case
+    let domains = |key: &str| -> Vec<String> {
+      env_list::<String>(key)
+        .into_iter()
+        .map(|domain| domain.trim_start_matches(['@', '.']).to_lowercase())
+        .filter(|domain| !domain.is_empty())
+        .collect()
+    };
+    Self {
+      allowed_email_domains: domains("REGISTRATION_ALLOWED_DOMAINS"),
+      denied_email_domains: domains("REGISTRATION_DENIED_DOMAINS"),
+      block_disposable_domains: env_or("REGISTRATION_BLOCK_DISPOSABLE", false),
+    }
+  }
+}
+
+impl UserExportConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      link_base_url: env_or("EXPORT_LINK_BASE_URL", defaults.link_base_url).trim_end_matches('/').to_string(),
+      retention_days: env_or("EXPORT_RETENTION_DAYS", defaults.retention_days).max(1),
+    }
+  }
+}
+
+impl PasswordResetConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      link_base_url: env_or("PASSWORD_RESET_LINK_BASE_URL", defaults.link_base_url)
+        .trim_end_matches('/')
+        .to_string(),
+      token_ttl_minutes: env_or("PASSWORD_RESET_TOKEN_TTL_MINUTES", defaults.token_ttl_minutes).max(1),
+    }
+  }
+}
+
+impl EncryptionConfig {
+  pub fn from_env() -> Self {
+    Self {
+      keys: env_list("FIELD_ENCRYPTION_KEYS"),
+    }
+  }
+}
+
+impl SecretsConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    let non_empty = |key: &str| std::env::var(key).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    Self {
+      backend: env_or("SECRETS_PROVIDER", defaults.backend),
+      refresh_interval_secs: env_or("SECRETS_REFRESH_INTERVAL_SECS", defaults.refresh_interval_secs),
+      vault_addr: non_empty("VAULT_ADDR").map(|addr| addr.trim_end_matches('/').to_string()),
+      vault_token: non_empty("VAULT_TOKEN"),
+      vault_mount: non_empty("VAULT_KV_MOUNT").unwrap_or(defaults.vault_mount).trim_matches('/').to_string(),
+      vault_path: non_empty("VAULT_SECRET_PATH")
+        .unwrap_or(defaults.vault_path)
+        .trim_matches('/')
+        .to_string(),
+      aws_secret_id: non_empty("AWS_SECRET_ID"),
+      aws_region: non_empty("AWS_REGION").unwrap_or(defaults.aws_region),
+      aws_endpoint: non_empty("AWS_SECRETS_MANAGER_ENDPOINT"),
+      aws_access_key_id: non_empty("AWS_ACCESS_KEY_ID"),
+      aws_secret_access_key: non_empty("AWS_SECRET_ACCESS_KEY"),
+      aws_session_token: non_empty("AWS_SESSION_TOKEN"),
+    }
+  }
+}
+
+impl BackupConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    let non_empty = |key: &str| std::env::var(key).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    Self {
+      environment: non_empty("BACKUP_ENVIRONMENT").unwrap_or(defaults.environment),
+      interval_secs: env_or("BACKUP_INTERVAL_SECS", defaults.interval_secs).max(60),
+      keep: env_or("BACKUP_KEEP", defaults.keep).max(1),
+      database_url: non_empty("BACKUP_DATABASE_URL").or_else(|| non_empty("DATABASE_URL")),
+      pg_dump: non_empty("BACKUP_PG_DUMP").unwrap_or(defaults.pg_dump),
+      pg_restore: non_empty("BACKUP_PG_RESTORE").unwrap_or(defaults.pg_restore),
+      s3_bucket: non_empty("BACKUP_S3_BUCKET"),
+      s3_region: non_empty("BACKUP_S3_REGION").unwrap_or(defaults.s3_region),
+      s3_endpoint: non_empty("BACKUP_S3_ENDPOINT"),
+      s3_prefix: non_empty("BACKUP_S3_PREFIX").unwrap_or(defaults.s3_prefix).trim_matches('/').to_string(),
+      s3_access_key_id: non_empty("BACKUP_S3_ACCESS_KEY_ID"),
+      s3_secret_access_key: non_empty("BACKUP_S3_SECRET_ACCESS_KEY"),
+      admin_user_ids: env_list("BACKUP_ADMIN_IDS"),
+    }
+  }
+}
+
+impl BillingConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    let non_empty = |key: &str| std::env::var(key).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    Self {
+      secret_key: non_empty("STRIPE_SECRET_KEY"),
+      webhook_secret: non_empty("STRIPE_WEBHOOK_SECRET"),
+      price_id: non_empty("STRIPE_PRICE_ID"),
+      success_url: non_empty("BILLING_SUCCESS_URL").unwrap_or(defaults.success_url),
+      cancel_url: non_empty("BILLING_CANCEL_URL").unwrap_or(defaults.cancel_url),
+      portal_return_url: non_empty("BILLING_PORTAL_RETURN_URL").unwrap_or(defaults.portal_return_url),
+      webhook_tolerance_secs: env_or("STRIPE_WEBHOOK_TOLERANCE_SECS", defaults.webhook_tolerance_secs).max(1),
+      api_base: non_empty("STRIPE_API_BASE").unwrap_or(defaults.api_base),
+    }
+  }
+}
+
+impl GrpcConfig {
+  pub fn from_env() -> Self {
+    Self {
+      port: env_or("GRPC_PORT", Self::default().port),
+    }
+  }
+}
+
+impl TlsConfig {
+  pub fn from_env() -> Self {
+    let non_empty = |key: &str| std::env::var(key).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    Self {
+      cert_path: non_empty("TLS_CERT_PATH"),
+      key_path: non_empty("TLS_KEY_PATH"),
+    }
+  }
+
+  /// Returns the certificate and key paths when TLS is fully configured.
+  pub fn paths(&self) -> Option<(&str, &str)> {
+    match (&self.cert_path, &self.key_path) {
+      (Some(cert), Some(key)) => Some((cert.as_str(), key.as_str())),
+      _ => None,
+    }
+  }
+}
+
+impl BodyLimitConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      default_bytes: env_or("BODY_LIMIT_BYTES", defaults.default_bytes).max(1),
+      upload_bytes: env_or("UPLOAD_BODY_LIMIT_BYTES", defaults.upload_bytes).max(1),
+    }
+  }
+}
+
+impl RateLimitConfig {
+  pub fn from_env() -> Self {
+    let defaults = Self::default();
+    Self {
+      enabled: env_or("RATE_LIMIT_ENABLED", defaults.enabled),
+      window_secs: env_or("RATE_LIMIT_WINDOW_SECS", defaults.window_secs).max(1),
+      read_max: env_or("RATE_LIMIT_READ_MAX", defaults.read_max),
+      write_max: env_or("RATE_LIMIT_WRITE_MAX", defaults.write_max),
+      anonymous_max: env_or("RATE_LIMIT_ANONYMOUS_MAX", defaults.anonymous_max),
+    }
+  }
+}
+
+/// Reads and parses an environment variable, returning `default` when it is unset or invalid.
+pub(crate) fn env_or<T: FromStr>(key: &str, default: T) -> T {
+  match std::env::var(key) {
+    Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+      tracing::warn!("Invalid value for {}: '{}', using default", key, value);
+      default
+    }),
+    Err(_) => default,
+  }
+}
+
+/// Reads a comma-separated list from the environment, skipping (and logging) invalid items.
+fn env_list<T: FromStr>(key: &str) -> Vec<T> {
+  let Ok(value) = std::env::var(key) else {
+    return Vec::new();
+  };
+  value
+    .split(',')
+    .map(str::trim)
+    .filter(|item| !item.is_empty())
+    .filter_map(|item| {
+      let parsed = item.parse().ok();
+      if parsed.is_none() {
+        tracing::warn!("Invalid item in {}: '{}', ignoring", key, item);
+      }
+      parsed
+    })
+    .collect()
+}
+
+/// Reads an RFC 3339 timestamp or a `YYYY-MM-DD` date (midnight UTC) from the environment.
+fn env_datetime(key: &str) -> Option<DateTime<Utc>> {
+  let value = std::env::var(key).ok()?;
+  let value = value.trim();
+  if value.is_empty() {
+    return None;
+  }
+
+  let parsed = DateTime::parse_from_rfc3339(value).map(|dt| dt.with_timezone(&Utc)).ok().or_else(|| {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+      .ok()
+      .and_then(|d| d.and_hms_opt(0, 0, 0))
+      .map(|dt| dt.and_utc())
+  });
+
+  if parsed.is_none() {
+    tracing::warn!("Invalid date for {}: '{}', ignoring", key, value);
+  }
+  parsed
+}
@@ -14,85 +14,147 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::fmt;
 use tracing::error;
 use uuid::Uuid;
 use validator::ValidationErrors;
 
+use crate::utils::db_resilience;
+
 /// The main application error type.
 ///
 /// This enum consolidates all possible error types that can occur within the application.
 /// It is designed to be the single source of truth for error handling, providing a consistent
 /// way to represent and manage errors, from database issues to validation failures.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum AppError {
   /// For authentication-related failures.
-  Authentication(AuthError),
+  #[error("Authentication error: {0}")]
+  Authentication(#[from] AuthError),
   /// For authorization-related failures (e.g., insufficient permissions).
+  #[error("Authorization error: {0}")]
   Authorization(String),
   /// For failures in data validation, typically from user input.
+  #[error("Validation error: {0}")]
   Validation(serde_json::Value),
   /// For errors originating from the database.
-  Database(DatabaseError),
+  #[error("Database error: {0}")]
+  Database(#[from] DatabaseError),
   /// For cases where a requested resource could not be found.
-  NotFound(NotFoundError),
+  #[error("Not found: {0}")]
+  NotFound(#[from] NotFoundError),
   /// For when a resource already exists.
+  #[error("Conflict: {0}")]
   Conflict(String),
+  /// For creates that look like duplicates of existing records; holds the records they may duplicate.
+  #[error("Possible duplicate of existing records")]
+  PossibleDuplicates(serde_json::Value),
   /// For malformed requests that cannot be parsed or processed.
+  #[error("Bad request: {0}")]
   BadRequest(String),
   /// For errors related to handling HTTP cookies.
-  Cookie(CookieError),
+  #[error("Cookie error: {0}")]
+  Cookie(#[from] CookieError),
   /// For errors during data serialization or deserialization.
+  #[error("Serialization error: {0}")]
   Serialization(String),
   /// For any other internal server errors that are not covered by other variants.
+  #[error("Internal error: {0}")]
   Internal(String),
   /// For requests using an unsupported HTTP method.
+  #[error("Not allowed: {0}")]
   NotAllowed(String),
+  /// For clients that exhausted their request budget; holds the seconds until the limit resets.
+  #[error("Rate limit exceeded, retry after {0}s")]
+  RateLimited(u64),
+  /// For workspaces that used up the monthly API quota of their plan.
+  #[error("Monthly API quota of {limit} requests exceeded")]
+  QuotaExceeded { limit: u64, resets_at: chrono::DateTime<chrono::Utc> },
+  /// For creates that would take a workspace past the records allowed by its plan; holds the limit.
+  #[error("Record limit of {0} reached")]
+  RecordQuotaExceeded(u64),
+  /// For writes to a workspace whose trial ended without a paid plan; holds when it ended.
+  #[error("Trial ended at {0}")]
+  TrialExpired(chrono::DateTime<chrono::Utc>),
+  /// For requests to a workspace from an address outside its IP allowlist; holds the address.
+  #[error("IP address {0} is not allowed in this workspace")]
+  IpNotAllowed(String),
+  /// For changes held until a workspace admin approves them; holds the approval request. Not a
+  /// failure: it responds `202 Accepted`.
+  #[error("Change awaits approval in request {0}")]
+  ApprovalRequired(Uuid),
+  /// For requests that did not complete within the configured request timeout.
+  #[error("Timeout: {0}")]
+  Timeout(String),
+  /// For requests rejected because the server is at its concurrency limit.
+  #[error("Overloaded: {0}")]
+  Overloaded(String),
   /// A catch-all for unhandled or unexpected errors.
+  #[error("Unhandled error: {0}")]
   Unhandled(String),
 }
 
 /// Represents authentication-specific errors.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum AuthError {
   /// The provided login credentials are invalid.
+  #[error("Invalid email or password")]
   InvalidCredentials,
   /// An authentication token is missing from the request.
+  #[error("Authentication token is missing")]
   MissingToken,
   /// The provided token is invalid, malformed, or cannot be parsed.
+  #[error("Authentication token is invalid")]
   InvalidToken,
+  #[error("Invalid workspace access or workspace not found")]
   InvalidWorkspace,
   /// The provided token has expired.
+  #[error("Authentication token has expired")]
   ExpiredToken,
+  /// A workspace-scoped request did not send the `X-Workspace-ID` header.
+  #[error("X-Workspace-ID header is required")]
+  MissingWorkspace,
+  /// The token is still valid, but its session was idle for longer than `SESSION_IDLE_TIMEOUT_SECS`.
+  #[error("Session expired after a period of inactivity")]
+  SessionIdleTimeout,
 }
 
 /// Represents database-specific errors.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum DatabaseError {
   /// Failed to establish a connection to the database.
+  #[error("Database connection failed: {0}")]
   ConnectionFailed(String),
   /// A database query failed to execute.
+  #[error("Database query failed: {0}")]
   QueryFailed(String),
   /// A database transaction failed.
+  #[error("Database transaction failed: {0}")]
   TransactionFailed(String),
   /// A database migration failed.
+  #[error("Database migration failed: {0}")]
   MigrationFailed(String),
   /// Trial user has exceeded their database size limit.
+  #[error("Database size limit exceeded: {0}")]
   SizeExceeded(String),
   /// Database schema doesn't match expected structure.
+  #[error("Database schema mismatch: {0}")]
   SchemaMismatch(String),
   /// Column not found in database table.
+  #[error("Column not found: {0}")]
   ColumnNotFound(String),
 }
 
 /// Represents errors related to HTTP cookies.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum CookieError {
   /// The cookie format is invalid.
+  #[error("Cookie format is invalid")]
   InvalidFormat,
   /// A required cookie is missing from the request.
+  #[error("Required cookie is missing")]
   Missing,
   /// The cookie has expired.
+  #[error("Cookie has expired")]
   Expired,
 }
 
@@ -108,7 +170,8 @@ pub struct ValidationError {
 }
 
 /// Represents an error for a resource that could not be found.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{resource}{} not found", .id.map(|id| format!(" with id {}", id)).unwrap_or_default())]
 pub struct NotFoundError {
   /// The type of the resource that was not found (e.g., "Contact", "User").
   pub resource: String,
@@ -180,6 +243,20 @@ impl IntoResponse for AppError {
           None,
           Some("AUTH_005".to_string()),
         ),
+        AuthError::MissingWorkspace => (
+          StatusCode::BAD_REQUEST,
+          "WORKSPACE_MISSING",
+          "X-Workspace-ID header is required".to_string(),
+          None,
+          Some("AUTH_006".to_string()),
+        ),
+        AuthError::SessionIdleTimeout => (
+          StatusCode::UNAUTHORIZED,
+          "SESSION_IDLE_TIMEOUT",
+          "Session expired after a period of inactivity, please log in again".to_string(),
+          None,
+          Some("AUTH_007".to_string()),
+        ),
       },
       AppError::Authorization(msg) => (
         StatusCode::FORBIDDEN,
@@ -197,6 +274,16 @@ impl IntoResponse for AppError {
       ),
       AppError::Database(db_err) => {
         match &db_err {
+          DatabaseError::ConnectionFailed(msg) => {
+            error!("Database unavailable: {}", msg);
+            (
+              StatusCode::SERVICE_UNAVAILABLE,
+              "DATABASE_UNAVAILABLE",
+              "The database is temporarily unavailable. Please retry later.".to_string(),
+              None,
+              Some("DB_CONN_001".to_string()),
+            )
+          }
           DatabaseError::SchemaMismatch(msg) => {
             error!("Database schema mismatch: {}", msg);
             (
@@ -237,6 +324,13 @@ impl IntoResponse for AppError {
         Some("NF_001".to_string()),
       ),
       AppError::Conflict(msg) => (StatusCode::CONFLICT, "RESOURCE_CONFLICT", msg, None, Some("CONFLICT_001".to_string())),
+      AppError::PossibleDuplicates(candidates) => (
+        StatusCode::CONFLICT,
+        "POSSIBLE_DUPLICATE",
+        "Similar records already exist; repeat the request with force=true to create it anyway".to_string(),
+        Some(json!({ "candidates": candidates })),
+        Some("CONFLICT_002".to_string()),
+      ),
       AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "BAD_REQUEST", msg, None, Some("BR_001".to_string())),
       AppError::Cookie(cookie_err) => (
         StatusCode::BAD_REQUEST,
@@ -272,6 +366,68 @@ impl IntoResponse for AppError {
         None,
         Some("NOT_ALLOWED_001".to_string()),
       ),
+      AppError::RateLimited(retry_after) => (
+        StatusCode::TOO_MANY_REQUESTS,
+        "RATE_LIMIT_EXCEEDED",
+        "Too many requests. Please slow down and retry later.".to_string(),
+        Some(json!({ "retry_after": retry_after })),
+        Some("RATE_001".to_string()),
+      ),
+      AppError::QuotaExceeded { limit, resets_at } => (
+        StatusCode::TOO_MANY_REQUESTS,
+        "QUOTA_EXCEEDED",
+        format!("The workspace used up its monthly quota of {} API requests", limit),
+        Some(json!({ "limit": limit, "resets_at": resets_at.to_rfc3339() })),
+        Some("QUOTA_001".to_string()),
+      ),
+      AppError::RecordQuotaExceeded(limit) => (
+        StatusCode::PAYMENT_REQUIRED,
+        "RECORD_QUOTA_EXCEEDED",
+        format!("The workspace reached the limit of {} records of its plan", limit),
+        Some(json!({ "limit": limit })),
+        Some("QUOTA_002".to_string()),
+      ),
+      AppError::TrialExpired(ended_at) => (
+        StatusCode::PAYMENT_REQUIRED,
+        "TRIAL_EXPIRED",
+        "The trial of this workspace has ended. It stays readable, but changes require a paid plan.".to_string(),
+        Some(json!({ "ended_at": ended_at.to_rfc3339() })),
+        Some("TRIAL_001".to_string()),
+      ),
+      AppError::IpNotAllowed(ip) => (
+        StatusCode::FORBIDDEN,
+        "IP_NOT_ALLOWED",
+        "This workspace cannot be accessed from your IP address".to_string(),
+        Some(json!({ "ip": ip })),
+        Some("IP_001".to_string()),
+      ),
+      AppError::ApprovalRequired(approval_id) => (
+        StatusCode::ACCEPTED,
+        "APPROVAL_REQUIRED",
+        "The change was not applied yet: it awaits the approval of a workspace admin".to_string(),
+        Some(json!({ "approval_id": approval_id })),
+        Some("APPROVAL_001".to_string()),
+      ),
+      AppError::Timeout(msg) => {
+        error!("Request timed out: {}", msg);
+        (
+          StatusCode::GATEWAY_TIMEOUT,
+          "REQUEST_TIMEOUT",
+          "The request took too long to complete. Please retry later.".to_string(),
+          None,
+          Some("TIMEOUT_001".to_string()),
+        )
+      }
+      AppError::Overloaded(msg) => {
+        error!("Request shed: {}", msg);
+        (
+          StatusCode::SERVICE_UNAVAILABLE,
+          "SERVICE_OVERLOADED",
+          "The server is handling too many requests. Please retry later.".to_string(),
+          None,
+          Some("OVERLOAD_001".to_string()),
+        )
+      }
       AppError::Unhandled(msg) => {
         error!("Unhandled error: {}", msg);
         (
@@ -288,7 +444,7 @@ impl IntoResponse for AppError {
       error: error_type.to_string(),
       message: message.to_string(),
       details,
-      code: code.map(String::from),
+      code,
       timestamp: chrono::Utc::now().to_rfc3339(),
     };
 
@@ -296,74 +452,6 @@ impl IntoResponse for AppError {
   }
 }
 
-impl fmt::Display for AppError {
-  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    match self {
-      AppError::Authentication(err) => write!(f, "Authentication error: {}", err),
-      AppError::Authorization(msg) => write!(f, "Authorization error: {}", msg),
-      AppError::Validation(err) => write!(f, "Validation error: {}", err),
-      AppError::Database(err) => write!(f, "Database error: {}", err),
-      AppError::NotFound(err) => write!(f, "Not found: {}", err),
-      AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
-      AppError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
-      AppError::Cookie(err) => write!(f, "Cookie error: {}", err),
-      AppError::Serialization(msg) => write!(f, "Serialization error: {}", msg),
-      AppError::Internal(msg) => write!(f, "Internal error: {}", msg),
-      AppError::NotAllowed(msg) => write!(f, "Not allowed: {}", msg),
-      AppError::Unhandled(msg) => write!(f, "Unhandled error: {}", msg),
-    }
-  }
-}
-
-impl fmt::Display for AuthError {
-  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    match self {
-      AuthError::InvalidCredentials => write!(f, "Invalid email or password"),
-      AuthError::MissingToken => write!(f, "Authentication token is missing"),
-      AuthError::InvalidToken => write!(f, "Authentication token is invalid"),
-      AuthError::InvalidWorkspace => write!(f, "Invalid workspace access or workspace not found"),
-      AuthError::ExpiredToken => write!(f, "Authentication token has expired"),
-    }
-  }
-}
-
-impl fmt::Display for DatabaseError {
-  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    match self {
-      DatabaseError::ConnectionFailed(msg) => {
-        write!(f, "Database connection failed: {}", msg)
-      }
-      DatabaseError::QueryFailed(msg) => write!(f, "Database query failed: {}", msg),
-      DatabaseError::TransactionFailed(msg) => {
-        write!(f, "Database transaction failed: {}", msg)
-      }
-      DatabaseError::MigrationFailed(msg) => write!(f, "Database migration failed: {}", msg),
-      DatabaseError::SizeExceeded(msg) => write!(f, "Database size limit exceeded: {}", msg),
-      DatabaseError::SchemaMismatch(msg) => write!(f, "Database schema mismatch: {}", msg),
-      DatabaseError::ColumnNotFound(msg) => write!(f, "Column not found: {}", msg),
-    }
-  }
-}
-
-impl fmt::Display for CookieError {
-  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    match self {
-      CookieError::InvalidFormat => write!(f, "Cookie format is invalid"),
-      CookieError::Missing => write!(f, "Required cookie is missing"),
-      CookieError::Expired => write!(f, "Cookie has expired"),
-    }
-  }
-}
-
-impl fmt::Display for NotFoundError {
-  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    match &self.id {
-      Some(id) => write!(f, "{} with id {} not found", self.resource, id),
-      None => write!(f, "{} not found", self.resource),
-    }
-  }
-}
-
 /// Converts `sqlx::Error` into `AppError`.
 ///
 /// This allows for the use of the `?` operator on `sqlx::Result`, automatically
@@ -371,23 +459,32 @@ impl fmt::Display for NotFoundError {
 /// It also handles specific database errors, like unique constraint violations.
 impl From<sqlx::Error> for AppError {
   fn from(err: sqlx::Error) -> Self {
+    if db_resilience::is_connection_error(&err) {
+      return AppError::Database(DatabaseError::ConnectionFailed(err.to_string()));
+    }
+
     match &err {
-      sqlx::Error::RowNotFound => AppError::NotFound(NotFoundError {
-        resource: "resource".to_string(), // Can be made more specific in the calling code
-        id: None,
-      }),
+      sqlx::Error::RowNotFound => AppError::not_found("resource"), // Can be made more specific in the calling code
       sqlx::Error::ColumnNotFound(col_name) => {
         AppError::Database(DatabaseError::ColumnNotFound(format!("Column '{}' not found in query result", col_name)))
       }
       sqlx::Error::Database(db_err) => {
-        if let Some(code) = db_err.code() {
-          if code == "23505" {
-            // Unique violation
-            return AppError::Validation(json!({
-                "code": "duplicate_entry",
-                "message": "An entry with this value already exists."
-            }));
+        if let Some(invalid) = Self::failed_value_check(db_err.as_ref()) {
+          return invalid;
+        }
+
+        if let Some(code) = db_err.code()
+          && code == "23505"
+        {
+          if let Some(duplicate) = Self::duplicate_code(db_err.as_ref()) {
+            return duplicate;
           }
+
+          // Unique violation
+          return AppError::Validation(json!({
+              "code": "duplicate_entry",
+              "message": "An entry with this value already exists."
+          }));
         }
 
         // Check for schema-related errors
@@ -418,8 +515,13 @@ impl From<ValidationErrors> for AppError {
 ///
 /// This handles errors that occur during the JSON deserialization of a request body.
 /// If Axum fails to parse the JSON, this converts the rejection into a clear `BadRequest` error.
+/// Bodies cut off by the route's size limit get a message naming the limit instead of the
+/// buffering error.
 impl From<JsonRejection> for AppError {
   fn from(rejection: JsonRejection) -> Self {
+    if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+      return crate::middleware::body_limit::body_too_large();
+    }
     AppError::BadRequest(rejection.to_string())
   }
 }
@@ -435,11 +537,11 @@ impl From<QueryRejection> for AppError {
     // Make the error message more user-friendly
     if error_msg.contains("unknown field") {
       // Extract field name from error message
-      if let Some(field_start) = error_msg.find("`") {
-        if let Some(field_end) = error_msg[field_start + 1..].find("`") {
-          let field_name = &error_msg[field_start + 1..field_start + 1 + field_end];
-          return AppError::BadRequest(format!("Unknown query parameter: '{}'", field_name));
-        }
+      if let Some(field_start) = error_msg.find("`")
+        && let Some(field_end) = error_msg[field_start + 1..].find("`")
+      {
+        let field_name = &error_msg[field_start + 1..field_start + 1 + field_end];
+        return AppError::BadRequest(format!("Unknown query parameter: '{}'", field_name));
       }
       AppError::BadRequest("Invalid query parameter provided".to_string())
     } else if error_msg.contains("Failed to deserialize query string") {
@@ -460,16 +562,13 @@ impl From<serde_json::Error> for AppError {
   }
 }
 
-/// Converts `uuid::Error` into `AppError::NotFound`.
+/// Converts `uuid::Error` into `AppError::BadRequest`.
 ///
-/// This is useful for handlers that parse a `Uuid` from a path or query parameter.
-/// If the string is not a valid UUID, it results in a `NotFound` error indicating the resource doesn't exist.
+/// A malformed UUID is a client error, not a missing resource. Prefer
+/// `helper::path_uuid::parse_uuid`, which also names the field and the offending value.
 impl From<uuid::Error> for AppError {
-  fn from(_err: uuid::Error) -> Self {
-    AppError::NotFound(NotFoundError {
-      resource: "Resource".to_string(),
-      id: None,
-    })
+  fn from(err: uuid::Error) -> Self {
+    AppError::BadRequest(format!("Invalid UUID: {}", err))
   }
 }
 
@@ -489,7 +588,60 @@ impl From<password_hash::Error> for AppError {
   }
 }
 
+/// Converts boxed errors, e.g. from a middleware layer or a third-party crate, into an internal error.
+impl From<Box<dyn std::error::Error + Send + Sync>> for AppError {
+  fn from(err: Box<dyn std::error::Error + Send + Sync>) -> Self {
+    AppError::Internal(err.to_string())
+  }
+}
+
 impl AppError {
+  /// Create a validation error on `field`.
+  pub fn validation(field: &str, message: &str) -> Self {
+    let validation_error = ValidationError {
+      field: field.to_string(),
+      message: message.to_string(),
+      code: None,
+    };
+    AppError::Validation(json!({ field: [validation_error] }))
+  }
+
+  /// Create a not found error for a `resource` such as "Contact".
+  pub fn not_found(resource: &str) -> Self {
+    AppError::NotFound(NotFoundError {
+      resource: resource.to_string(),
+      id: None,
+    })
+  }
+
+  /// Create a not found error for the `resource` with the given ID.
+  pub fn not_found_with_id(resource: &str, id: Uuid) -> Self {
+    AppError::NotFound(NotFoundError {
+      resource: resource.to_string(),
+      id: Some(id),
+    })
+  }
+
+  /// Create an error for a login with a wrong email or password.
+  pub fn invalid_credentials() -> Self {
+    AppError::Authentication(AuthError::InvalidCredentials)
+  }
+
+  /// Create an error for a request without an authentication token.
+  pub fn missing_token() -> Self {
+    AppError::Authentication(AuthError::MissingToken)
+  }
+
+  /// Create an error for a malformed or unverifiable authentication token.
+  pub fn invalid_token() -> Self {
+    AppError::Authentication(AuthError::InvalidToken)
+  }
+
+  /// Create an error for a request using an unsupported HTTP method.
+  pub fn not_allowed(message: &str) -> Self {
+    AppError::NotAllowed(message.to_string())
+  }
+
   /// Create a validation error with a code.
   pub fn validation_with_code(field: &str, message: &str, code: &str) -> Self {
     let validation_error = ValidationError {
@@ -500,6 +652,47 @@ impl AppError {
     AppError::Validation(json!({ field: [validation_error] }))
   }
 
+  /// The `DELETED_CODE` validation error on the `code` field, for a code still held by a deleted
+  /// `entity`, which is to be restored rather than created again.
+  pub fn deleted_code(entity: &str) -> Self {
+    let message = format!("Code belongs to a deleted {entity}; restore it instead");
+    Self::validation_with_code("code", &message, "DELETED_CODE")
+  }
+
+  /// Maps a violation of one of the per-workspace code indexes to the `DUPLICATE_CODE`
+  /// validation error on the `code` field. Other unique violations are left to the caller.
+  fn duplicate_code(db_err: &dyn sqlx::error::DatabaseError) -> Option<Self> {
+    if db_err.code().as_deref() != Some("23505") {
+      return None;
+    }
+    let message = match db_err.constraint()? {
+      "contacts_workspace_id_code_key" => "Contact code already exists in this workspace",
+      "products_workspace_id_code_key" => "Product code already exists in this workspace",
+      _ => return None,
+    };
+    Some(Self::validation_with_code("code", message, "DUPLICATE_CODE"))
+  }
+
+  /// Maps a violation of one of the product value checks to a validation error on the field it
+  /// guards, for writes that bypassed the payload validation. Other check violations are left to the caller.
+  fn failed_value_check(db_err: &dyn sqlx::error::DatabaseError) -> Option<Self> {
+    if db_err.code().as_deref() != Some("23514") {
+      return None;
+    }
+    let (field, message) = match db_err.constraint()? {
+      "products_selling_price_check" => ("selling_price", "Selling price cannot be negative"),
+      "products_unit_cost_check" => ("unit_cost", "Unit cost cannot be negative"),
+      "products_tax_rate_check" => ("tax_rate", "Tax rate must be between 0 and 100"),
+      "products_tax_amount_check" => ("tax_amount", "Tax amount cannot be negative"),
+      "products_minimum_stock_check" => ("minimum_stock", "Minimum stock cannot be negative"),
+      "products_maximum_stock_check" => ("maximum_stock", "Maximum stock cannot be negative"),
+      "products_reorder_level_check" => ("reorder_level", "Reorder level cannot be negative"),
+      "products_stock_levels_check" => ("maximum_stock", "Minimum stock cannot exceed maximum stock"),
+      _ => return None,
+    };
+    Some(Self::validation_with_code(field, message, "INVALID_VALUE"))
+  }
+
   /// Create a database size exceeded error for trial users.
   pub fn database_size_exceeded(message: &str) -> Self {
     AppError::Database(DatabaseError::SizeExceeded(message.to_string()))
@@ -517,12 +710,26 @@ impl AppError {
 
   /// Enhanced error handling for SQLx errors with context
   pub fn from_sqlx_error(error: sqlx::Error, query_context: &str) -> Self {
+    if db_resilience::is_connection_error(&error) {
+      tracing::error!("Database unavailable for query: {}, error: {}", query_context, error);
+      return Self::Database(DatabaseError::ConnectionFailed(error.to_string()));
+    }
+
     match error {
       sqlx::Error::ColumnNotFound(column) => {
         tracing::error!("Column not found: {} in query: {}", column, query_context);
         Self::column_not_found(&column)
       }
       sqlx::Error::Database(db_err) => {
+        if let Some(duplicate) = Self::duplicate_code(db_err.as_ref()) {
+          tracing::debug!("Duplicate code in query: {}, error: {}", query_context, db_err.message());
+          return duplicate;
+        }
+        if let Some(invalid) = Self::failed_value_check(db_err.as_ref()) {
+          tracing::debug!("Value check failed in query: {}, error: {}", query_context, db_err.message());
+          return invalid;
+        }
+
         let message = db_err.message();
 
         // Check for schema-related errors
@@ -537,10 +744,7 @@ impl AppError {
           Self::Database(DatabaseError::QueryFailed(message.to_string()))
         }
       }
-      sqlx::Error::RowNotFound => Self::NotFound(NotFoundError {
-        resource: "Resource".to_string(),
-        id: None,
-      }),
+      sqlx::Error::RowNotFound => Self::not_found("Resource"),
       _ => {
         let error_msg = error.to_string();
         tracing::error!("SQLx error in query: {}, error: {}", query_context, error_msg);
@@ -549,3 +753,30 @@ impl AppError {
     }
   }
 }
+
+/// Creates an `AppError::NotFound` for a resource, optionally with its ID.
+///
+/// ```ignore
+/// return Err(not_found!("Contact", id));
+/// ```
+#[macro_export]
+macro_rules! not_found {
+  ($resource:expr) => {
+    $crate::errors::AppError::not_found($resource)
+  };
+  ($resource:expr, $id:expr) => {
+    $crate::errors::AppError::not_found_with_id($resource, $id)
+  };
+}
+
+/// Creates an `AppError::Internal` from a message or `format!` arguments.
+///
+/// ```ignore
+/// return Err(internal_error!("Failed to publish event for {}", id));
+/// ```
+#[macro_export]
+macro_rules! internal_error {
+  ($($arg:tt)+) => {
+    $crate::errors::AppError::Internal(format!($($arg)+))
+  };
+}
@@ -0,0 +1,209 @@
+//! In-process event bus for real-time workspace updates.
+//!
+//! Handlers publish a `WorkspaceEvent` after every successful write; the WebSocket endpoint
+//! (`/api/v1/ws`) and the Server-Sent Events endpoint (`/api/v1/events`) subscribe and forward
+//! the events of the caller's workspace. The most recent events are kept in memory so a client
+//! can resume after a reconnect; they are not persisted and are only delivered to clients
+//! connected to the same server instance.
+
+use std::{collections::VecDeque, sync::Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// The kind of change made to a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordAction {
+  Created,
+  Updated,
+  Deleted,
+  /// A deleted record was brought back.
+  Restored,
+}
+
+impl RecordAction {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      RecordAction::Created => "created",
+      RecordAction::Updated => "updated",
+      RecordAction::Deleted => "deleted",
+      RecordAction::Restored => "restored",
+    }
+  }
+}
+
+/// An event scoped to a single workspace, as sent to real-time clients.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceEvent {
+  /// Monotonic id assigned on publish, unique for the lifetime of the process.
+  pub id: u64,
+  /// Event name, e.g. `contact.created`, `product.low_stock` or `notification`.
+  pub event: String,
+  pub workspace_id: Uuid,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub resource_id: Option<Uuid>,
+  /// The user whose request caused the event.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub actor_id: Option<Uuid>,
+  /// Restricts delivery to a single user; `None` broadcasts to the whole workspace.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub recipient_id: Option<Uuid>,
+  pub data: Value,
+  pub timestamp: DateTime<Utc>,
+}
+
+impl WorkspaceEvent {
+  fn new(event: String, workspace_id: Uuid, data: Value) -> Self {
+    Self {
+      id: 0,
+      event,
+      workspace_id,
+      resource_id: None,
+      actor_id: None,
+      recipient_id: None,
+      data,
+      timestamp: Utc::now(),
+    }
+  }
+
+  /// A record was created, updated or deleted. `record` is the public representation of the
+  /// record after the change, or `None` for deletions.
+  pub fn record<T: Serialize>(resource: &str, action: RecordAction, workspace_id: Uuid, record_id: Uuid, actor_id: Uuid, record: Option<&T>) -> Self {
+    let data = record.and_then(|r| serde_json::to_value(r).ok()).unwrap_or(Value::Null);
+    Self {
+      resource_id: Some(record_id),
+      actor_id: Some(actor_id),
+      ..Self::new(format!("{}.{}", resource, action.as_str()), workspace_id, data)
+    }
+  }
+
+  /// A member started or stopped editing a record. `lock` is the lock taken, or the one released.
+  pub fn lock<T: Serialize>(resource: &str, locked: bool, workspace_id: Uuid, record_id: Uuid, actor_id: Uuid, lock: &T) -> Self {
+    let event = format!("{}.{}", resource, if locked { "locked" } else { "unlocked" });
+    Self {
+      resource_id: Some(record_id),
+      actor_id: Some(actor_id),
+      ..Self::new(event, workspace_id, serde_json::to_value(lock).unwrap_or(Value::Null))
+    }
+  }
+
+  /// A tracked product reached its reorder level.
+  pub fn low_stock<T: Serialize>(workspace_id: Uuid, product_id: Uuid, product: &T) -> Self {
+    let data = serde_json::to_value(product).unwrap_or(Value::Null);
+    Self {
+      resource_id: Some(product_id),
+      ..Self::new("product.low_stock".to_string(), workspace_id, data)
+    }
+  }
+
+  /// A user-facing notification, either for the whole workspace or for one member.
+  pub fn notification(workspace_id: Uuid, recipient_id: Option<Uuid>, title: &str, message: &str) -> Self {
+    Self {
+      recipient_id,
+      ..Self::new(
+        "notification".to_string(),
+        workspace_id,
+        serde_json::json!({ "title": title, "message": message }),
+      )
+    }
+  }
+
+  /// Whether a client of `workspace_id` authenticated as `user_id` should receive this event.
+  pub fn is_visible_to(&self, workspace_id: Uuid, user_id: Uuid) -> bool {
+    self.workspace_id == workspace_id && self.recipient_id.is_none_or(|recipient| recipient == user_id)
+  }
+}
+
+/// The payload sent instead of events a client can no longer receive, telling it to refetch.
+/// `missed` is omitted when the number of dropped events is unknown.
+pub fn resync_payload(missed: Option<u64>) -> Value {
+  match missed {
+    Some(missed) => serde_json::json!({ "event": "resync", "missed": missed }),
+    None => serde_json::json!({ "event": "resync" }),
+  }
+}
+
+/// A subscription that starts after a given event id.
+pub struct Replay {
+  /// Retained events published after the requested id, oldest first.
+  pub events: Vec<WorkspaceEvent>,
+  /// Whether events between the requested id and `events` were already dropped (or the id comes
+  /// from before a restart), in which case the client must resync.
+  pub incomplete: bool,
+  /// Live events published after `events`.
+  pub receiver: broadcast::Receiver<WorkspaceEvent>,
+}
+
+/// Fan-out channel for `WorkspaceEvent`s.
+///
+/// Publishing never blocks or fails: with no subscribers the event is only kept in the history,
+/// and a subscriber that falls more than `capacity` events behind skips the oldest ones.
+pub struct EventBus {
+  sender: broadcast::Sender<WorkspaceEvent>,
+  history: Mutex<History>,
+}
+
+struct History {
+  next_id: u64,
+  capacity: usize,
+  events: VecDeque<WorkspaceEvent>,
+}
+
+impl EventBus {
+  pub fn new(capacity: usize) -> Self {
+    let capacity = capacity.max(1);
+    let (sender, _) = broadcast::channel(capacity);
+    Self {
+      sender,
+      history: Mutex::new(History {
+        next_id: 1,
+        capacity,
+        events: VecDeque::with_capacity(capacity),
+      }),
+    }
+  }
+
+  /// Assigns the event its id, records it in the history and delivers it to every current subscriber.
+  pub fn publish(&self, mut event: WorkspaceEvent) {
+    let mut history = self.history.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    event.id = history.next_id;
+    history.next_id += 1;
+
+    if history.events.len() == history.capacity {
+      history.events.pop_front();
+    }
+    history.events.push_back(event.clone());
+
+    // Sent while holding the lock so `subscribe_from` never sees an event twice or not at all
+    let _ = self.sender.send(event);
+  }
+
+  pub fn subscribe(&self) -> broadcast::Receiver<WorkspaceEvent> {
+    self.sender.subscribe()
+  }
+
+  /// Subscribes to live events, first replaying the retained events published after
+  /// `last_event_id`. Without an id only live events are delivered.
+  pub fn subscribe_from(&self, last_event_id: Option<u64>) -> Replay {
+    let history = self.history.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let receiver = self.sender.subscribe();
+
+    let Some(last_event_id) = last_event_id else {
+      return Replay {
+        events: Vec::new(),
+        incomplete: false,
+        receiver,
+      };
+    };
+
+    let oldest_retained = history.events.front().map_or(history.next_id, |event| event.id);
+    Replay {
+      events: history.events.iter().filter(|event| event.id > last_event_id).cloned().collect(),
+      incomplete: last_event_id + 1 < oldest_retained || last_event_id >= history.next_id,
+      receiver,
+    }
+  }
+}
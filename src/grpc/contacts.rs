@@ -0,0 +1,204 @@
+//! `myapp.v1.ContactService`, backed by the v1 contact handlers.
+
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use tonic::{Request, Response, Status};
+
+use crate::{
+  helper::{Pagination, PathUuid, ValidatedQuery},
+  modules::datastores::contacts::{
+    contact_handlers,
+    contact_models::{self, ContactResponse, GetContactsQuery},
+  },
+  responses::Created,
+  state::AppState,
+};
+
+use super::{
+  IdempotencyKey, Rpc, authenticate,
+  contact_service::contact_service_server::ContactService,
+  idempotent, in_session,
+  messages::{
+    Contact, CreateContactRequest, DeleteContactRequest, DeleteResponse, GetContactRequest, ListContactsRequest, ListContactsResponse,
+    UpdateContactRequest,
+  },
+  parse_uuid, results, timestamp,
+};
+
+const LIST: Rpc = Rpc::read("/myapp.v1.ContactService/ListContacts");
+const GET: Rpc = Rpc::read("/myapp.v1.ContactService/GetContact");
+const CREATE: Rpc = Rpc::write("/myapp.v1.ContactService/CreateContact");
+const UPDATE: Rpc = Rpc::write("/myapp.v1.ContactService/UpdateContact");
+const DELETE: Rpc = Rpc::write("/myapp.v1.ContactService/DeleteContact");
+
+pub struct ContactGrpcService {
+  state: Arc<AppState>,
+}
+
+impl ContactGrpcService {
+  pub fn new(state: Arc<AppState>) -> Self {
+    Self { state }
+  }
+}
+
+#[tonic::async_trait]
+impl ContactService for ContactGrpcService {
+  async fn list_contacts(&self, request: Request<ListContactsRequest>) -> Result<Response<ListContactsResponse>, Status> {
+    let (current_user, workspace, member) = authenticate(&self.state, &request, LIST).await?;
+    let message = request.into_inner();
+
+    let pagination = Pagination::new(message.page, message.limit)?;
+    let query = GetContactsQuery {
+      page: message.page,
+      limit: message.limit,
+      search: message.search,
+      contact_type: message.contact_type,
+      is_active: message.is_active,
+      sort_by: message.sort_by,
+      sort_order: message.sort_order,
+      ..Default::default()
+    };
+
+    let Json(response) = in_session(
+      &self.state,
+      current_user.user_id,
+      workspace.0,
+      member.role,
+      contact_handlers::get_list(
+        State(self.state.clone()),
+        current_user,
+        workspace,
+        member,
+        ValidatedQuery::new(query)?,
+        pagination,
+      ),
+    )
+    .await??;
+    let page = results(response)?;
+
+    Ok(Response::new(ListContactsResponse {
+      contacts: page.list.into_iter().map(|contact| contact.into_inner().into()).collect(),
+      pagination: Some(page.pagination.into()),
+    }))
+  }
+
+  async fn get_contact(&self, request: Request<GetContactRequest>) -> Result<Response<Contact>, Status> {
+    let (current_user, workspace, member) = authenticate(&self.state, &request, GET).await?;
+    let id = parse_uuid("id", &request.into_inner().id)?;
+
+    let Json(response) = in_session(
+      &self.state,
+      current_user.user_id,
+      workspace.0,
+      member.role,
+      contact_handlers::get_by_id(State(self.state.clone()), PathUuid(id), current_user, workspace, member),
+    )
+    .await??;
+    Ok(Response::new(results(response)?.into_inner().into()))
+  }
+
+  async fn create_contact(&self, request: Request<CreateContactRequest>) -> Result<Response<Contact>, Status> {
+    let (current_user, workspace, member) = authenticate(&self.state, &request, CREATE).await?;
+    let idempotency_key = IdempotencyKey::of(&request, CREATE, current_user.user_id, workspace.0)?;
+    let message = request.into_inner();
+
+    let payload = contact_models::CreateContactRequest {
+      code: message.code,
+      name: message.name,
+      email: message.email,
+      position: message.position,
+      contact_type: message.contact_type,
+      address: message.address,
+      // The encrypted fields are only exposed by the HTTP API
+      tax_id: None,
+      bank_account: None,
+    };
+
+    idempotent(&self.state, idempotency_key, async move {
+      let Created { body: response, .. } = in_session(
+        &self.state,
+        current_user.user_id,
+        workspace.0,
+        member.role,
+        contact_handlers::create(State(self.state.clone()), current_user, workspace, member, Ok(Json(payload))),
+      )
+      .await??;
+      Ok(results(response)?.into_inner().into())
+    })
+    .await
+  }
+
+  async fn update_contact(&self, request: Request<UpdateContactRequest>) -> Result<Response<Contact>, Status> {
+    let (current_user, workspace, member) = authenticate(&self.state, &request, UPDATE).await?;
+    let idempotency_key = IdempotencyKey::of(&request, UPDATE, current_user.user_id, workspace.0)?;
+    let message = request.into_inner();
+
+    let payload = contact_models::UpdateContactRequest {
+      code: message.code,
+      name: message.name,
+      email: message.email,
+      position: message.position,
+      contact_type: message.contact_type,
+      address: message.address,
+      tax_id: None,
+      bank_account: None,
+      is_active: message.is_active,
+    };
+
+    idempotent(&self.state, idempotency_key, async move {
+      let Json(response) = in_session(
+        &self.state,
+        current_user.user_id,
+        workspace.0,
+        member.role,
+        contact_handlers::update(
+          State(self.state.clone()),
+          PathUuid(parse_uuid("id", &message.id)?),
+          current_user,
+          workspace,
+          member,
+          Ok(Json(payload)),
+        ),
+      )
+      .await??;
+      Ok(results(response)?.into_inner().into())
+    })
+    .await
+  }
+
+  async fn delete_contact(&self, request: Request<DeleteContactRequest>) -> Result<Response<DeleteResponse>, Status> {
+    let (current_user, workspace, member) = authenticate(&self.state, &request, DELETE).await?;
+    let id = parse_uuid("id", &request.into_inner().id)?;
+
+    let _ = in_session(
+      &self.state,
+      current_user.user_id,
+      workspace.0,
+      member.role,
+      contact_handlers::delete(State(self.state.clone()), PathUuid(id), current_user, workspace, member),
+    )
+    .await??;
+    Ok(Response::new(DeleteResponse {}))
+  }
+}
+
+impl From<ContactResponse> for Contact {
+  fn from(contact: ContactResponse) -> Self {
+    Self {
+      id: contact.id.to_string(),
+      code: contact.code,
+      name: contact.name,
+      email: contact.email,
+      position: contact.position,
+      contact_type: contact.contact_type,
+      address: contact.address,
+      is_active: contact.is_active,
+      workspace_id: contact.workspace_id.map(|id| id.to_string()),
+      created_by: contact.created_by.map(|id| id.to_string()),
+      updated_by: contact.updated_by.map(|id| id.to_string()),
+      created_at: timestamp(contact.created_at),
+      updated_at: timestamp(contact.updated_at),
+    }
+  }
+}
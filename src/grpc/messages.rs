@@ -0,0 +1,312 @@
+//! Protobuf messages of the `myapp.v1` package, mirroring `proto/myapp.proto` field for field.
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Pagination {
+  #[prost(uint32, tag = "1")]
+  pub page: u32,
+  #[prost(uint32, tag = "2")]
+  pub limit: u32,
+  #[prost(uint64, tag = "3")]
+  pub total: u64,
+  #[prost(uint32, tag = "4")]
+  pub total_pages: u32,
+  #[prost(bool, tag = "5")]
+  pub has_next: bool,
+  #[prost(bool, tag = "6")]
+  pub has_prev: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteResponse {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Contact {
+  #[prost(string, tag = "1")]
+  pub id: String,
+  #[prost(string, tag = "2")]
+  pub code: String,
+  #[prost(string, tag = "3")]
+  pub name: String,
+  #[prost(string, tag = "4")]
+  pub email: String,
+  #[prost(string, optional, tag = "5")]
+  pub position: Option<String>,
+  #[prost(string, tag = "6")]
+  pub contact_type: String,
+  #[prost(string, optional, tag = "7")]
+  pub address: Option<String>,
+  #[prost(bool, tag = "8")]
+  pub is_active: bool,
+  #[prost(string, optional, tag = "9")]
+  pub workspace_id: Option<String>,
+  #[prost(string, optional, tag = "10")]
+  pub created_by: Option<String>,
+  #[prost(string, optional, tag = "11")]
+  pub updated_by: Option<String>,
+  #[prost(string, tag = "12")]
+  pub created_at: String,
+  #[prost(string, tag = "13")]
+  pub updated_at: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListContactsRequest {
+  #[prost(uint32, optional, tag = "1")]
+  pub page: Option<u32>,
+  #[prost(uint32, optional, tag = "2")]
+  pub limit: Option<u32>,
+  #[prost(string, optional, tag = "3")]
+  pub search: Option<String>,
+  #[prost(string, optional, tag = "4")]
+  pub contact_type: Option<String>,
+  #[prost(bool, optional, tag = "5")]
+  pub is_active: Option<bool>,
+  #[prost(string, optional, tag = "6")]
+  pub sort_by: Option<String>,
+  #[prost(string, optional, tag = "7")]
+  pub sort_order: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListContactsResponse {
+  #[prost(message, repeated, tag = "1")]
+  pub contacts: Vec<Contact>,
+  #[prost(message, optional, tag = "2")]
+  pub pagination: Option<Pagination>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetContactRequest {
+  #[prost(string, tag = "1")]
+  pub id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CreateContactRequest {
+  #[prost(string, tag = "1")]
+  pub code: String,
+  #[prost(string, tag = "2")]
+  pub name: String,
+  #[prost(string, tag = "3")]
+  pub email: String,
+  #[prost(string, optional, tag = "4")]
+  pub position: Option<String>,
+  #[prost(string, tag = "5")]
+  pub contact_type: String,
+  #[prost(string, optional, tag = "6")]
+  pub address: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct UpdateContactRequest {
+  #[prost(string, tag = "1")]
+  pub id: String,
+  #[prost(string, optional, tag = "2")]
+  pub code: Option<String>,
+  #[prost(string, optional, tag = "3")]
+  pub name: Option<String>,
+  #[prost(string, optional, tag = "4")]
+  pub email: Option<String>,
+  #[prost(string, optional, tag = "5")]
+  pub position: Option<String>,
+  #[prost(string, optional, tag = "6")]
+  pub contact_type: Option<String>,
+  #[prost(string, optional, tag = "7")]
+  pub address: Option<String>,
+  #[prost(bool, optional, tag = "8")]
+  pub is_active: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteContactRequest {
+  #[prost(string, tag = "1")]
+  pub id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Product {
+  #[prost(string, tag = "1")]
+  pub id: String,
+  #[prost(string, tag = "2")]
+  pub code: String,
+  #[prost(string, tag = "3")]
+  pub name: String,
+  #[prost(string, optional, tag = "4")]
+  pub category_id: Option<String>,
+  #[prost(string, tag = "5")]
+  pub base_unit: String,
+  #[prost(string, optional, tag = "6")]
+  pub unit_on_report_preview: Option<String>,
+  #[prost(string, tag = "7")]
+  pub selling_price: String,
+  #[prost(string, tag = "8")]
+  pub unit_cost: String,
+  #[prost(string, optional, tag = "9")]
+  pub supplier_id: Option<String>,
+  #[prost(bool, tag = "10")]
+  pub track_inventory: bool,
+  #[prost(string, optional, tag = "11")]
+  pub description: Option<String>,
+  #[prost(string, optional, tag = "12")]
+  pub sku: Option<String>,
+  #[prost(string, optional, tag = "13")]
+  pub barcode: Option<String>,
+  #[prost(int32, optional, tag = "14")]
+  pub minimum_stock: Option<i32>,
+  #[prost(int32, optional, tag = "15")]
+  pub maximum_stock: Option<i32>,
+  #[prost(int32, optional, tag = "16")]
+  pub reorder_level: Option<i32>,
+  #[prost(int32, optional, tag = "17")]
+  pub current_stock: Option<i32>,
+  #[prost(string, optional, tag = "18")]
+  pub tax_type: Option<String>,
+  #[prost(string, optional, tag = "19")]
+  pub tax_rate: Option<String>,
+  #[prost(string, optional, tag = "20")]
+  pub tax_amount: Option<String>,
+  #[prost(bool, tag = "21")]
+  pub is_active: bool,
+  #[prost(string, optional, tag = "22")]
+  pub workspace_id: Option<String>,
+  #[prost(string, optional, tag = "23")]
+  pub created_by: Option<String>,
+  #[prost(string, optional, tag = "24")]
+  pub updated_by: Option<String>,
+  #[prost(string, tag = "25")]
+  pub created_at: String,
+  #[prost(string, tag = "26")]
+  pub updated_at: String,
+  #[prost(string, tag = "27")]
+  pub status: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListProductsRequest {
+  #[prost(uint32, optional, tag = "1")]
+  pub page: Option<u32>,
+  #[prost(uint32, optional, tag = "2")]
+  pub limit: Option<u32>,
+  #[prost(string, optional, tag = "3")]
+  pub search: Option<String>,
+  #[prost(string, optional, tag = "4")]
+  pub category_id: Option<String>,
+  #[prost(string, optional, tag = "5")]
+  pub supplier_id: Option<String>,
+  #[prost(bool, optional, tag = "6")]
+  pub is_active: Option<bool>,
+  #[prost(string, optional, tag = "7")]
+  pub sort_by: Option<String>,
+  #[prost(string, optional, tag = "8")]
+  pub sort_order: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListProductsResponse {
+  #[prost(message, repeated, tag = "1")]
+  pub products: Vec<Product>,
+  #[prost(message, optional, tag = "2")]
+  pub pagination: Option<Pagination>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetProductRequest {
+  #[prost(string, tag = "1")]
+  pub id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CreateProductRequest {
+  #[prost(string, tag = "1")]
+  pub code: String,
+  #[prost(string, tag = "2")]
+  pub name: String,
+  #[prost(string, optional, tag = "3")]
+  pub category_id: Option<String>,
+  #[prost(string, tag = "4")]
+  pub base_unit: String,
+  #[prost(string, optional, tag = "5")]
+  pub unit_on_report_preview: Option<String>,
+  #[prost(string, tag = "6")]
+  pub selling_price: String,
+  #[prost(string, tag = "7")]
+  pub unit_cost: String,
+  #[prost(string, optional, tag = "8")]
+  pub supplier_id: Option<String>,
+  #[prost(bool, optional, tag = "9")]
+  pub track_inventory: Option<bool>,
+  #[prost(string, optional, tag = "10")]
+  pub description: Option<String>,
+  #[prost(string, optional, tag = "11")]
+  pub sku: Option<String>,
+  #[prost(string, optional, tag = "12")]
+  pub barcode: Option<String>,
+  #[prost(int32, optional, tag = "13")]
+  pub minimum_stock: Option<i32>,
+  #[prost(int32, optional, tag = "14")]
+  pub maximum_stock: Option<i32>,
+  #[prost(int32, optional, tag = "15")]
+  pub reorder_level: Option<i32>,
+  #[prost(int32, optional, tag = "16")]
+  pub current_stock: Option<i32>,
+  #[prost(string, optional, tag = "17")]
+  pub tax_type: Option<String>,
+  #[prost(string, optional, tag = "18")]
+  pub tax_rate: Option<String>,
+  #[prost(string, optional, tag = "19")]
+  pub tax_amount: Option<String>,
+  #[prost(bool, tag = "20")]
+  pub force: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct UpdateProductRequest {
+  #[prost(string, tag = "1")]
+  pub id: String,
+  #[prost(string, optional, tag = "2")]
+  pub code: Option<String>,
+  #[prost(string, optional, tag = "3")]
+  pub name: Option<String>,
+  #[prost(string, optional, tag = "4")]
+  pub category_id: Option<String>,
+  #[prost(string, optional, tag = "5")]
+  pub base_unit: Option<String>,
+  #[prost(string, optional, tag = "6")]
+  pub unit_on_report_preview: Option<String>,
+  #[prost(string, optional, tag = "7")]
+  pub selling_price: Option<String>,
+  #[prost(string, optional, tag = "8")]
+  pub unit_cost: Option<String>,
+  #[prost(string, optional, tag = "9")]
+  pub supplier_id: Option<String>,
+  #[prost(bool, optional, tag = "10")]
+  pub track_inventory: Option<bool>,
+  #[prost(string, optional, tag = "11")]
+  pub description: Option<String>,
+  #[prost(string, optional, tag = "12")]
+  pub sku: Option<String>,
+  #[prost(string, optional, tag = "13")]
+  pub barcode: Option<String>,
+  #[prost(int32, optional, tag = "14")]
+  pub minimum_stock: Option<i32>,
+  #[prost(int32, optional, tag = "15")]
+  pub maximum_stock: Option<i32>,
+  #[prost(int32, optional, tag = "16")]
+  pub reorder_level: Option<i32>,
+  #[prost(int32, optional, tag = "17")]
+  pub current_stock: Option<i32>,
+  #[prost(string, optional, tag = "18")]
+  pub tax_type: Option<String>,
+  #[prost(string, optional, tag = "19")]
+  pub tax_rate: Option<String>,
+  #[prost(string, optional, tag = "20")]
+  pub tax_amount: Option<String>,
+  #[prost(bool, optional, tag = "21")]
+  pub is_active: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteProductRequest {
+  #[prost(string, tag = "1")]
+  pub id: String,
+}
@@ -0,0 +1,280 @@
+//! Internal gRPC API (`grpc` feature).
+//!
+//! Exposes contact and product CRUD over protobuf for service-to-service consumers. The service
+//! implementations call the same v1 handlers as the REST API, so validation, permission checks
+//! and repository access are shared; only the wire format differs. The contract is documented in
+//! `proto/myapp.proto`. Read-only fields of the workspace's field policies are enforced, but
+//! hidden fields are returned, as the messages have no optional fields to leave them out.
+
+pub mod contacts;
+pub mod messages;
+pub mod products;
+
+use std::{net::SocketAddr, str::FromStr, sync::Arc};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use chrono::{DateTime, Utc};
+use prost::Message;
+use tonic::{
+  Request, Response, Status,
+  metadata::{MetadataMap, MetadataValue},
+  transport::Server,
+};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+  errors::{AppError, AuthError, DatabaseError},
+  helper::{RequireRole, RequiredWorkspace, path_uuid::parse_uuid as parse_path_uuid, workspace::role::Member},
+  middleware::{
+    idempotency::{self, Reservation, StoredResponse},
+    rate_limit::{self, client_ip},
+    trial, usage,
+  },
+  modules::{
+    auth::{current_user::CurrentUser, jwt_middleware::authenticate_workspace_member},
+    datastores::workspaces::workspace_models::WorkspaceRole,
+  },
+  responses::{ApiResponse, PaginationMeta},
+  state::AppState,
+  utils::{
+    db_resilience,
+    db_session::{self, SessionSettings},
+  },
+};
+
+/// Generated `myapp.v1.ContactService` client and server stubs.
+pub mod contact_service {
+  include!(concat!(env!("OUT_DIR"), "/myapp.v1.ContactService.rs"));
+}
+
+/// Generated `myapp.v1.ProductService` client and server stubs.
+pub mod product_service {
+  include!(concat!(env!("OUT_DIR"), "/myapp.v1.ProductService.rs"));
+}
+
+/// Serves the gRPC API on `addr` until the process exits.
+pub async fn serve(state: Arc<AppState>, addr: SocketAddr) {
+  info!("gRPC server listening on {}", addr);
+
+  let result = Server::builder()
+    .add_service(contact_service::contact_service_server::ContactServiceServer::new(
+      contacts::ContactGrpcService::new(state.clone()),
+    ))
+    .add_service(product_service::product_service_server::ProductServiceServer::new(
+      products::ProductGrpcService::new(state),
+    ))
+    .serve(addr)
+    .await;
+
+  if let Err(e) = result {
+    error!("gRPC server stopped: {}", e);
+  }
+}
+
+/// A method of the gRPC API, as the REST middleware would see it: usage is metered under its
+/// path, and writes count against the write budget and stop once the workspace's trial expired.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Rpc {
+  path: &'static str,
+  writes: bool,
+}
+
+impl Rpc {
+  pub(crate) const fn read(path: &'static str) -> Self {
+    Self { path, writes: false }
+  }
+
+  pub(crate) const fn write(path: &'static str) -> Self {
+    Self { path, writes: true }
+  }
+}
+
+/// Authenticates a call from its `authorization` and `x-workspace-id` metadata, mirroring the
+/// JWT middleware: the token must be valid, the user must be at least a member of the workspace
+/// and the caller's address must be allowed by the workspace's IP allowlist. The call then goes
+/// through the checks of the REST middleware stack: the user's rate limit budget, the trial for
+/// writes and the workspace's usage quota. It is metered with the size of its message; responses
+/// count as empty, like streamed REST responses.
+pub(crate) async fn authenticate<T: Message>(
+  state: &AppState,
+  request: &Request<T>,
+  rpc: Rpc,
+) -> Result<(CurrentUser, RequiredWorkspace, RequireRole<Member>), AppError> {
+  let metadata = request.metadata();
+
+  let token = metadata_str(metadata, "authorization")
+    .ok_or(AppError::missing_token())?
+    .strip_prefix("Bearer ")
+    .ok_or(AppError::invalid_token())?;
+  let workspace_id = metadata_str(metadata, "x-workspace-id")
+    .ok_or_else(|| AppError::BadRequest("x-workspace-id metadata is required".to_string()))
+    .and_then(|value| parse_path_uuid("x-workspace-id", value))?;
+  // Proxies in front of the gRPC port forward the client address as metadata, like HTTP headers
+  let headers = metadata.clone().into_headers();
+  let ip = client_ip(&headers, request.remote_addr(), &state.config.server.trusted_proxies);
+  let (user_id, role) = authenticate_workspace_member(state, token, workspace_id, ip).await?;
+
+  rate_limit::check_user_budget(state, user_id, workspace_id, !rpc.writes).await?;
+  if rpc.writes {
+    trial::check_writable(state, workspace_id).await?;
+  }
+  if state.config.usage.enabled {
+    usage::check_quota(state, workspace_id).await?;
+    let request_bytes = request.get_ref().encoded_len() as u64;
+    state.usage_meter.record(workspace_id, user_id, rpc.path, request_bytes, 0);
+  }
+
+  Ok((CurrentUser { user_id }, RequiredWorkspace(workspace_id), RequireRole::check(role)?))
+}
+
+/// The `idempotency-key` of a write, scoped and fingerprinted like the REST header.
+pub(crate) struct IdempotencyKey {
+  store_key: String,
+  fingerprint: String,
+}
+
+impl IdempotencyKey {
+  /// Reads the `idempotency-key` metadata of an authenticated call. The fingerprint covers the
+  /// method and the encoded message, as the REST one covers the URI and body.
+  pub(crate) fn of<T: Message>(request: &Request<T>, rpc: Rpc, user_id: Uuid, workspace_id: Uuid) -> Result<Option<Self>, AppError> {
+    let Some(value) = request.metadata().get("idempotency-key") else {
+      return Ok(None);
+    };
+    let key = idempotency::valid_key(value.to_str().ok())?;
+    Ok(Some(Self {
+      store_key: idempotency::scoped_key(user_id, Some(workspace_id), key),
+      fingerprint: idempotency::fingerprint("POST", rpc.path, &request.get_ref().encode_to_vec()),
+    }))
+  }
+}
+
+/// Runs a write at most once per idempotency key, like `idempotency_middleware`: a retry with
+/// the same key and message gets the stored response back, marked with `idempotent-replayed`
+/// metadata. Failed calls are not stored, so they can be retried.
+pub(crate) async fn idempotent<M: Message + Default>(
+  state: &AppState,
+  key: Option<IdempotencyKey>,
+  call: impl Future<Output = Result<M, Status>>,
+) -> Result<Response<M>, Status> {
+  let Some(IdempotencyKey { store_key, fingerprint }) = key else {
+    return call.await.map(Response::new);
+  };
+  match idempotency::reserve(state, &store_key, &fingerprint).await? {
+    Reservation::Reserved => {}
+    Reservation::Replay(stored) => {
+      let message = BASE64_STANDARD
+        .decode(&stored.body)
+        .ok()
+        .and_then(|bytes| M::decode(bytes.as_slice()).ok())
+        .ok_or_else(|| Status::internal("Stored response cannot be replayed"))?;
+      let mut response = Response::new(message);
+      response.metadata_mut().insert("idempotent-replayed", MetadataValue::from_static("true"));
+      return Ok(response);
+    }
+    Reservation::Unavailable => return call.await.map(Response::new),
+  }
+
+  let result = call.await;
+  let stored = result.as_ref().ok().map(|message| StoredResponse {
+    status: 200,
+    content_type: Some("application/grpc".to_string()),
+    body: BASE64_STANDARD.encode(message.encode_to_vec()),
+  });
+  idempotency::finish(state, &store_key, fingerprint, stored).await;
+  result.map(Response::new)
+}
+
+/// Runs a v1 handler on a connection carrying the caller's RLS session variables, as
+/// `jwt_middleware` does for HTTP requests.
+pub(crate) async fn in_session<F: Future>(
+  state: &AppState,
+  user_id: Uuid,
+  workspace_id: Uuid,
+  role: WorkspaceRole,
+  handler: F,
+) -> Result<F::Output, Status> {
+  let settings = SessionSettings {
+    user_id,
+    workspace_id: Some(workspace_id),
+    role: Some(role),
+  };
+  db_session::scope(&state.db, settings, handler).await.map_err(|e| {
+    error!("Failed to set database session: {}", e);
+    if db_resilience::is_connection_error(&e) {
+      return Status::from(AppError::from(e));
+    }
+    Status::internal("Failed to set database session")
+  })
+}
+
+fn metadata_str<'a>(metadata: &'a MetadataMap, key: &str) -> Option<&'a str> {
+  metadata.get(key).and_then(|value| value.to_str().ok())
+}
+
+/// Unwraps the payload of a successful v1 handler response.
+pub(crate) fn results<T>(response: ApiResponse<T>) -> Result<T, Status> {
+  response.results.ok_or_else(|| Status::internal("Handler returned no results"))
+}
+
+/// Parses a UUID field of a request message.
+pub(crate) fn parse_uuid(field: &str, value: &str) -> Result<Uuid, Status> {
+  value
+    .parse()
+    .map_err(|_| Status::invalid_argument(format!("{field} must be a valid UUID")))
+}
+
+/// Parses an optional UUID field of a request message.
+pub(crate) fn parse_optional_uuid(field: &str, value: Option<&str>) -> Result<Option<Uuid>, Status> {
+  value.map(|v| parse_uuid(field, v)).transpose()
+}
+
+/// Parses a decimal field, sent as a string to keep its precision.
+pub(crate) fn parse_decimal(field: &str, value: &str) -> Result<rust_decimal::Decimal, Status> {
+  rust_decimal::Decimal::from_str(value).map_err(|_| Status::invalid_argument(format!("{field} must be a decimal number")))
+}
+
+/// Parses an optional decimal field.
+pub(crate) fn parse_optional_decimal(field: &str, value: Option<&str>) -> Result<Option<rust_decimal::Decimal>, Status> {
+  value.map(|v| parse_decimal(field, v)).transpose()
+}
+
+pub(crate) fn timestamp(value: DateTime<Utc>) -> String {
+  value.to_rfc3339()
+}
+
+impl From<PaginationMeta> for messages::Pagination {
+  fn from(meta: PaginationMeta) -> Self {
+    Self {
+      page: meta.page,
+      limit: meta.limit,
+      total: meta.total,
+      total_pages: meta.total_pages,
+      has_next: meta.has_next,
+      has_prev: meta.has_prev,
+    }
+  }
+}
+
+/// Maps application errors onto gRPC status codes. Internal details stay in the server log.
+impl From<AppError> for Status {
+  fn from(err: AppError) -> Self {
+    match err {
+      AppError::Authentication(AuthError::MissingWorkspace) => Status::invalid_argument(err.to_string()),
+      AppError::Authentication(_) => Status::unauthenticated(err.to_string()),
+      AppError::Authorization(_) | AppError::IpNotAllowed(_) => Status::permission_denied(err.to_string()),
+      AppError::Validation(_) | AppError::BadRequest(_) | AppError::Cookie(_) => Status::invalid_argument(err.to_string()),
+      AppError::NotFound(_) => Status::not_found(err.to_string()),
+      AppError::Conflict(_) | AppError::PossibleDuplicates(_) => Status::already_exists(err.to_string()),
+      AppError::NotAllowed(_) => Status::unimplemented(err.to_string()),
+      AppError::RateLimited(_) | AppError::QuotaExceeded { .. } => Status::resource_exhausted(err.to_string()),
+      AppError::TrialExpired(_) | AppError::RecordQuotaExceeded(_) | AppError::ApprovalRequired(_) => Status::failed_precondition(err.to_string()),
+      AppError::Timeout(_) => Status::deadline_exceeded(err.to_string()),
+      AppError::Overloaded(_) | AppError::Database(DatabaseError::ConnectionFailed(_)) => Status::unavailable(err.to_string()),
+      AppError::Database(_) | AppError::Serialization(_) | AppError::Internal(_) | AppError::Unhandled(_) => {
+        error!("gRPC call failed: {}", err);
+        Status::internal("An unexpected error occurred")
+      }
+    }
+  }
+}
@@ -0,0 +1,273 @@
+//! `myapp.v1.ProductService`, backed by the v1 product handlers.
+
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use tonic::{Request, Response, Status};
+
+use crate::{
+  helper::{Pagination, PathUuid, ValidatedQuery},
+  modules::datastores::products::{
+    product_handlers,
+    product_models::{self, CreateProductQuery, GetProductQuery, GetProductsQuery, ProductResponse, TaxType},
+  },
+  responses::Created,
+  state::AppState,
+};
+
+use super::{
+  IdempotencyKey, Rpc, authenticate, idempotent, in_session,
+  messages::{
+    CreateProductRequest, DeleteProductRequest, DeleteResponse, GetProductRequest, ListProductsRequest, ListProductsResponse, Product,
+    UpdateProductRequest,
+  },
+  parse_decimal, parse_optional_decimal, parse_optional_uuid, parse_uuid,
+  product_service::product_service_server::ProductService,
+  results, timestamp,
+};
+
+const LIST: Rpc = Rpc::read("/myapp.v1.ProductService/ListProducts");
+const GET: Rpc = Rpc::read("/myapp.v1.ProductService/GetProduct");
+const CREATE: Rpc = Rpc::write("/myapp.v1.ProductService/CreateProduct");
+const UPDATE: Rpc = Rpc::write("/myapp.v1.ProductService/UpdateProduct");
+const DELETE: Rpc = Rpc::write("/myapp.v1.ProductService/DeleteProduct");
+
+pub struct ProductGrpcService {
+  state: Arc<AppState>,
+}
+
+impl ProductGrpcService {
+  pub fn new(state: Arc<AppState>) -> Self {
+    Self { state }
+  }
+}
+
+#[tonic::async_trait]
+impl ProductService for ProductGrpcService {
+  async fn list_products(&self, request: Request<ListProductsRequest>) -> Result<Response<ListProductsResponse>, Status> {
+    let (current_user, workspace, member) = authenticate(&self.state, &request, LIST).await?;
+    let message = request.into_inner();
+
+    let pagination = Pagination::new(message.page, message.limit)?;
+    let query = GetProductsQuery {
+      page: message.page,
+      limit: message.limit,
+      search: message.search,
+      category_id: parse_optional_uuid("category_id", message.category_id.as_deref())?,
+      supplier_id: parse_optional_uuid("supplier_id", message.supplier_id.as_deref())?,
+      is_active: message.is_active,
+      sort_by: message.sort_by,
+      sort_order: message.sort_order,
+      ..Default::default()
+    };
+
+    let Json(response) = in_session(
+      &self.state,
+      current_user.user_id,
+      workspace.0,
+      member.role,
+      product_handlers::get_list(
+        State(self.state.clone()),
+        current_user,
+        workspace,
+        member,
+        ValidatedQuery::new(query)?,
+        pagination,
+      ),
+    )
+    .await??;
+    let page = results(response)?;
+
+    Ok(Response::new(ListProductsResponse {
+      products: page.list.into_iter().map(|product| product.into_inner().into()).collect(),
+      pagination: Some(page.pagination.into()),
+    }))
+  }
+
+  async fn get_product(&self, request: Request<GetProductRequest>) -> Result<Response<Product>, Status> {
+    let (current_user, workspace, member) = authenticate(&self.state, &request, GET).await?;
+    let id = parse_uuid("id", &request.get_ref().id)?;
+
+    let Json(response) = in_session(
+      &self.state,
+      current_user.user_id,
+      workspace.0,
+      member.role,
+      product_handlers::get_by_id(
+        State(self.state.clone()),
+        PathUuid(id),
+        current_user,
+        workspace,
+        member,
+        ValidatedQuery::new(GetProductQuery { as_of: None })?,
+      ),
+    )
+    .await??;
+    Ok(Response::new(results(response)?.into_inner().into()))
+  }
+
+  async fn create_product(&self, request: Request<CreateProductRequest>) -> Result<Response<Product>, Status> {
+    let (current_user, workspace, member) = authenticate(&self.state, &request, CREATE).await?;
+    let idempotency_key = IdempotencyKey::of(&request, CREATE, current_user.user_id, workspace.0)?;
+    let message = request.into_inner();
+
+    let payload = product_models::CreateProductRequest {
+      code: message.code,
+      name: message.name,
+      category_id: parse_optional_uuid("category_id", message.category_id.as_deref())?,
+      base_unit: message.base_unit,
+      unit_on_report_preview: message.unit_on_report_preview,
+      selling_price: parse_decimal("selling_price", &message.selling_price)?,
+      unit_cost: parse_decimal("unit_cost", &message.unit_cost)?,
+      supplier_id: parse_optional_uuid("supplier_id", message.supplier_id.as_deref())?,
+      track_inventory: message.track_inventory,
+      description: message.description,
+      sku: message.sku,
+      barcode: message.barcode,
+      minimum_stock: message.minimum_stock,
+      maximum_stock: message.maximum_stock,
+      reorder_level: message.reorder_level,
+      current_stock: message.current_stock,
+      tax_type: parse_tax_type(message.tax_type.as_deref())?,
+      tax_rate: parse_optional_decimal("tax_rate", message.tax_rate.as_deref())?,
+      tax_amount: parse_optional_decimal("tax_amount", message.tax_amount.as_deref())?,
+      status: None,
+    };
+
+    idempotent(&self.state, idempotency_key, async move {
+      let Created { body: response, .. } = in_session(
+        &self.state,
+        current_user.user_id,
+        workspace.0,
+        member.role,
+        product_handlers::create(
+          State(self.state.clone()),
+          current_user,
+          workspace,
+          member,
+          ValidatedQuery::new(CreateProductQuery { force: message.force })?,
+          Ok(Json(payload)),
+        ),
+      )
+      .await??;
+      Ok(results(response)?.into_inner().into())
+    })
+    .await
+  }
+
+  async fn update_product(&self, request: Request<UpdateProductRequest>) -> Result<Response<Product>, Status> {
+    let (current_user, workspace, member) = authenticate(&self.state, &request, UPDATE).await?;
+    let idempotency_key = IdempotencyKey::of(&request, UPDATE, current_user.user_id, workspace.0)?;
+    let message = request.into_inner();
+    let id = parse_uuid("id", &message.id)?;
+
+    let payload = product_models::UpdateProductRequest {
+      code: message.code,
+      name: message.name,
+      category_id: parse_optional_uuid("category_id", message.category_id.as_deref())?,
+      base_unit: message.base_unit,
+      unit_on_report_preview: message.unit_on_report_preview,
+      selling_price: parse_optional_decimal("selling_price", message.selling_price.as_deref())?,
+      unit_cost: parse_optional_decimal("unit_cost", message.unit_cost.as_deref())?,
+      supplier_id: parse_optional_uuid("supplier_id", message.supplier_id.as_deref())?,
+      track_inventory: message.track_inventory,
+      description: message.description,
+      sku: message.sku,
+      barcode: message.barcode,
+      minimum_stock: message.minimum_stock,
+      maximum_stock: message.maximum_stock,
+      reorder_level: message.reorder_level,
+      current_stock: message.current_stock,
+      tax_type: parse_tax_type(message.tax_type.as_deref())?,
+      tax_rate: parse_optional_decimal("tax_rate", message.tax_rate.as_deref())?,
+      tax_amount: parse_optional_decimal("tax_amount", message.tax_amount.as_deref())?,
+      is_active: message.is_active,
+    };
+
+    idempotent(&self.state, idempotency_key, async move {
+      let Json(response) = in_session(
+        &self.state,
+        current_user.user_id,
+        workspace.0,
+        member.role,
+        product_handlers::update(
+          State(self.state.clone()),
+          PathUuid(id),
+          current_user,
+          workspace,
+          member,
+          Ok(Json(payload)),
+        ),
+      )
+      .await??;
+      Ok(results(response)?.into_inner().into())
+    })
+    .await
+  }
+
+  async fn delete_product(&self, request: Request<DeleteProductRequest>) -> Result<Response<DeleteResponse>, Status> {
+    let (current_user, workspace, member) = authenticate(&self.state, &request, DELETE).await?;
+    let id = parse_uuid("id", &request.get_ref().id)?;
+
+    let _ = in_session(
+      &self.state,
+      current_user.user_id,
+      workspace.0,
+      member.role,
+      product_handlers::delete(State(self.state.clone()), PathUuid(id), current_user, workspace, member),
+    )
+    .await??;
+    Ok(Response::new(DeleteResponse {}))
+  }
+}
+
+fn parse_tax_type(value: Option<&str>) -> Result<Option<TaxType>, Status> {
+  match value {
+    None => Ok(None),
+    Some("percentage") => Ok(Some(TaxType::Percentage)),
+    Some("fixed_amount") => Ok(Some(TaxType::FixedAmount)),
+    Some(_) => Err(Status::invalid_argument("tax_type must be \"percentage\" or \"fixed_amount\"")),
+  }
+}
+
+fn tax_type_name(tax_type: TaxType) -> String {
+  match tax_type {
+    TaxType::Percentage => "percentage",
+    TaxType::FixedAmount => "fixed_amount",
+  }
+  .to_string()
+}
+
+impl From<ProductResponse> for Product {
+  fn from(product: ProductResponse) -> Self {
+    Self {
+      id: product.id.to_string(),
+      code: product.code,
+      name: product.name,
+      category_id: product.category_id.map(|id| id.to_string()),
+      base_unit: product.base_unit,
+      unit_on_report_preview: product.unit_on_report_preview,
+      selling_price: product.selling_price.to_string(),
+      unit_cost: product.unit_cost.to_string(),
+      supplier_id: product.supplier_id.map(|id| id.to_string()),
+      track_inventory: product.track_inventory,
+      description: product.description,
+      sku: product.sku,
+      barcode: product.barcode,
+      minimum_stock: product.minimum_stock,
+      maximum_stock: product.maximum_stock,
+      reorder_level: product.reorder_level,
+      current_stock: product.current_stock,
+      tax_type: product.tax_type.map(tax_type_name),
+      tax_rate: product.tax_rate.map(|rate| rate.to_string()),
+      tax_amount: product.tax_amount.map(|amount| amount.to_string()),
+      is_active: product.is_active,
+      workspace_id: product.workspace_id.map(|id| id.to_string()),
+      status: product.status.as_str().to_string(),
+      created_by: product.created_by.map(|id| id.to_string()),
+      updated_by: product.updated_by.map(|id| id.to_string()),
+      created_at: timestamp(product.created_at),
+      updated_at: timestamp(product.updated_at),
+    }
+  }
+}
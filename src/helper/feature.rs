@@ -0,0 +1,42 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+
+use crate::{errors::AppError, helper::OptionalWorkspace, modules::feature_flags::feature_flag_service, state::AppState};
+
+/// Marker types naming the flag a `RequireFeature` extractor checks.
+pub mod flag {
+  /// A feature flag key, as a type.
+  pub trait FeatureFlag: Send + Sync + 'static {
+    const KEY: &'static str;
+  }
+
+  /// The reworked inventory endpoints.
+  pub struct InventoryV2;
+
+  impl FeatureFlag for InventoryV2 {
+    const KEY: &'static str = "inventory_v2";
+  }
+}
+
+/// Proof that flag `F` is on for the request's workspace (see `modules::feature_flags`).
+///
+/// Gates experimental routes: `_: RequireFeature<flag::InventoryV2>` answers a 404 while the flag
+/// is off, as if the route did not exist. Requests without a workspace see the global state of the flag.
+pub struct RequireFeature<F: flag::FeatureFlag> {
+  _flag: PhantomData<F>,
+}
+
+#[async_trait]
+impl<F: flag::FeatureFlag> FromRequestParts<Arc<AppState>> for RequireFeature<F> {
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+    let OptionalWorkspace(workspace_id) = OptionalWorkspace::from_request_parts(parts, state).await?;
+    if feature_flag_service::is_enabled(state, F::KEY, workspace_id).await? {
+      Ok(Self { _flag: PhantomData })
+    } else {
+      Err(AppError::not_found(&format!("Feature '{}'", F::KEY)))
+    }
+  }
+}
@@ -1,2 +1,12 @@
+pub mod feature;
+pub mod pagination;
+pub mod path_uuid;
+pub mod superadmin;
+pub mod validated_query;
 pub mod workspace;
-pub use workspace::WorkspaceContext;
+pub use feature::RequireFeature;
+pub use pagination::Pagination;
+pub use path_uuid::PathUuid;
+pub use superadmin::RequireSuperadmin;
+pub use validated_query::ValidatedQuery;
+pub use workspace::{OptionalWorkspace, RequireRole, RequiredWorkspace};
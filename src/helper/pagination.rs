@@ -0,0 +1,200 @@
+use std::{fmt, str::FromStr, sync::Arc};
+
+use axum::{
+  async_trait,
+  extract::{FromRequestParts, Query},
+  http::request::Parts,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+  AppResult, AppState,
+  errors::AppError,
+  modules::{auth::current_user::WorkspaceId, workspace_settings::workspace_settings_models::WorkspaceSettings},
+};
+
+pub const DEFAULT_PAGE: u32 = 1;
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 100;
+
+/// A column every list endpoint can be sorted by, usable as a workspace default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortColumn {
+  Code,
+  Name,
+  CreatedAt,
+  UpdatedAt,
+}
+
+impl SortColumn {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      SortColumn::Code => "code",
+      SortColumn::Name => "name",
+      SortColumn::CreatedAt => "created_at",
+      SortColumn::UpdatedAt => "updated_at",
+    }
+  }
+}
+
+impl FromStr for SortColumn {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    match value {
+      "code" => Ok(SortColumn::Code),
+      "name" => Ok(SortColumn::Name),
+      "created_at" => Ok(SortColumn::CreatedAt),
+      "updated_at" => Ok(SortColumn::UpdatedAt),
+      _ => Err(format!("'{}' is not a sort column shared by all lists", value)),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+  Asc,
+  Desc,
+}
+
+impl fmt::Display for SortOrder {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      SortOrder::Asc => "asc",
+      SortOrder::Desc => "desc",
+    })
+  }
+}
+
+impl FromStr for SortOrder {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    match value {
+      "asc" => Ok(SortOrder::Asc),
+      "desc" => Ok(SortOrder::Desc),
+      _ => Err(format!("'{}' is not a sort order", value)),
+    }
+  }
+}
+
+/// The defaults of the list endpoints of a workspace, set by its admins in the workspace
+/// settings. Missing values keep the built-in ones (`DEFAULT_LIMIT`, `MAX_LIMIT` and each
+/// list's own sort, usually newest first).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ListDefaults {
+  /// `limit` when the client sends none.
+  #[validate(range(min = 1, max = MAX_LIMIT, message = "Page size must be between 1 and 100"))]
+  pub page_size: Option<u32>,
+  /// The largest `limit` accepted, below `MAX_LIMIT`.
+  #[validate(range(min = 1, max = MAX_LIMIT, message = "Page size must be between 1 and 100"))]
+  pub max_page_size: Option<u32>,
+  pub sort_by: Option<SortColumn>,
+  pub sort_order: Option<SortOrder>,
+}
+
+impl ListDefaults {
+  fn max_limit(&self) -> u32 {
+    self.max_page_size.unwrap_or(MAX_LIMIT)
+  }
+
+  fn default_limit(&self) -> u32 {
+    self.page_size.unwrap_or(DEFAULT_LIMIT).min(self.max_limit())
+  }
+}
+
+/// The `page` and `limit` query parameters of a list endpoint, validated, with the list defaults
+/// of the workspace.
+///
+/// Rejects `page=0` and a `limit` outside `1..=MAX_LIMIT` (or the workspace's lower cap) with a
+/// 422, so handlers can compute offsets and page counts without guarding against zero. Other
+/// query parameters are left to the endpoint's own query struct; handlers pass its sort
+/// parameters through `apply_sort_defaults`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pagination {
+  pub page: u32,
+  pub limit: u32,
+  /// The sort of the workspace for requests without `sort_by`/`sort_order`.
+  pub sort_by: Option<SortColumn>,
+  pub sort_order: Option<SortOrder>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct PaginationParams {
+  #[validate(range(min = 1, message = "Page must be at least 1"))]
+  page: Option<u32>,
+  #[validate(range(min = 1, max = MAX_LIMIT, message = "Limit must be between 1 and 100"))]
+  limit: Option<u32>,
+}
+
+impl Pagination {
+  /// Validates optional `page`/`limit` values, applying the defaults for missing ones. Used by
+  /// callers that do not go through the extractor, such as the gRPC services.
+  pub fn new(page: Option<u32>, limit: Option<u32>) -> AppResult<Self> {
+    Self::with_defaults(page, limit, &ListDefaults::default())
+  }
+
+  /// Like `new`, applying the list defaults of a workspace.
+  pub fn with_defaults(page: Option<u32>, limit: Option<u32>, defaults: &ListDefaults) -> AppResult<Self> {
+    let params = PaginationParams { page, limit };
+    params.validate()?;
+    let max_limit = defaults.max_limit();
+    if params.limit.is_some_and(|limit| limit > max_limit) {
+      return Err(AppError::validation("limit", &format!("Limit must be between 1 and {}", max_limit)));
+    }
+
+    Ok(Self {
+      page: params.page.unwrap_or(DEFAULT_PAGE),
+      limit: params.limit.unwrap_or_else(|| defaults.default_limit()),
+      sort_by: defaults.sort_by,
+      sort_order: defaults.sort_order,
+    })
+  }
+
+  /// Fills in the sort parameters of a list query the client left out with the workspace's.
+  pub fn apply_sort_defaults(&self, sort_by: &mut Option<String>, sort_order: &mut Option<String>) {
+    if sort_by.is_none() {
+      *sort_by = self.sort_by.map(|column| column.as_str().to_string());
+    }
+    if sort_order.is_none() {
+      *sort_order = self.sort_order.map(|order| order.to_string());
+    }
+  }
+
+  /// Number of rows to skip to reach this page.
+  pub fn offset(&self) -> u64 {
+    crate::utils::pagination::offset(self.page, self.limit)
+  }
+}
+
+impl Default for Pagination {
+  fn default() -> Self {
+    Self {
+      page: DEFAULT_PAGE,
+      limit: DEFAULT_LIMIT,
+      sort_by: None,
+      sort_order: None,
+    }
+  }
+}
+
+/// Uses the settings placed in the request extensions by `ip_allowlist_middleware`, loading them
+/// only for routes outside that middleware.
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for Pagination {
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+    let Query(params) = Query::<PaginationParams>::from_request_parts(parts, state).await?;
+    let defaults = match (parts.extensions.get::<WorkspaceSettings>(), parts.extensions.get::<WorkspaceId>()) {
+      (Some(settings), _) => settings.list_defaults,
+      (None, Some(&WorkspaceId(workspace_id))) => state.workspace_settings_repository.get(workspace_id).await?.list_defaults,
+      (None, None) => ListDefaults::default(),
+    };
+    Self::with_defaults(params.page, params.limit, &defaults)
+  }
+}
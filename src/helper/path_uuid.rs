@@ -0,0 +1,71 @@
+use axum::{
+  async_trait,
+  extract::{FromRequestParts, Path},
+  http::request::Parts,
+};
+use uuid::Uuid;
+
+use crate::{AppResult, errors::AppError};
+
+/// UUID path parameters, parsed with an error that names the parameter.
+///
+/// `PathUuid(id): PathUuid` extracts a single `:id` segment and
+/// `PathUuid((workspace_id, user_id)): PathUuid<(Uuid, Uuid)>` extracts two, in route order.
+/// A malformed value is rejected with a 400 such as `Invalid UUID for 'id': 'abc'` instead of
+/// being reported as a missing resource.
+#[derive(Debug, Clone, Copy)]
+pub struct PathUuid<T = Uuid>(pub T);
+
+/// Shapes a `PathUuid` can be extracted into, built from the route's UUIDs in order.
+pub trait FromPathUuids: Sized {
+  fn from_path_uuids(ids: &[Uuid]) -> Option<Self>;
+}
+
+impl FromPathUuids for Uuid {
+  fn from_path_uuids(ids: &[Uuid]) -> Option<Self> {
+    match ids {
+      [id] => Some(*id),
+      _ => None,
+    }
+  }
+}
+
+impl FromPathUuids for (Uuid, Uuid) {
+  fn from_path_uuids(ids: &[Uuid]) -> Option<Self> {
+    match ids {
+      [first, second] => Some((*first, *second)),
+      _ => None,
+    }
+  }
+}
+
+/// Parses `value` as a UUID, reporting `field` and the offending value on failure.
+pub fn parse_uuid(field: &str, value: &str) -> AppResult<Uuid> {
+  value
+    .parse::<Uuid>()
+    .map_err(|_| AppError::BadRequest(format!("Invalid UUID for '{}': '{}'", field, value)))
+}
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for PathUuid<T>
+where
+  T: FromPathUuids,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    let Path(params) = Path::<Vec<(String, String)>>::from_request_parts(parts, state)
+      .await
+      .map_err(|rejection| AppError::BadRequest(rejection.body_text()))?;
+
+    let ids = params
+      .iter()
+      .map(|(name, value)| parse_uuid(name, value))
+      .collect::<AppResult<Vec<_>>>()?;
+
+    T::from_path_uuids(&ids)
+      .map(Self)
+      .ok_or_else(|| AppError::Internal(format!("Route has {} path parameters, which does not match the handler", ids.len())))
+  }
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use uuid::Uuid;
+
+use crate::{AppResult, errors::AppError, modules::auth::current_user::CurrentUser, state::AppState};
+
+/// Whether `user_id` is an active superadmin.
+///
+/// Read from the database on every call rather than from the token, so revoking the flag or
+/// deactivating the user takes effect on the next request.
+pub async fn is_superadmin(state: &AppState, user_id: Uuid) -> AppResult<bool> {
+  let user = state.auth_repository.find_by_id(user_id).await?;
+  Ok(user.is_some_and(|user| user.is_active && user.is_superadmin))
+}
+
+/// Proof that the caller is a superadmin, a platform-level role independent of workspace roles.
+///
+/// Guards cross-tenant administration, such as the routes of `modules::admin`: workspace roles
+/// only grant access within one workspace. Rejects other users with a 403.
+pub struct RequireSuperadmin {
+  pub user_id: Uuid,
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for RequireSuperadmin {
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+    let current_user = CurrentUser::from_request_parts(parts, state).await?;
+    if is_superadmin(state, current_user.user_id).await? {
+      Ok(Self {
+        user_id: current_user.user_id,
+      })
+    } else {
+      Err(AppError::Authorization("Superadmin access required".to_string()))
+    }
+  }
+}
@@ -0,0 +1,40 @@
+use axum::{
+  async_trait,
+  extract::{FromRequestParts, Query},
+  http::request::Parts,
+};
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use crate::{AppResult, errors::AppError};
+
+/// Query string extractor that deserializes into `T` and runs its `Validate` rules.
+///
+/// Deserialization failures (including unknown parameters on `#[serde(deny_unknown_fields)]`
+/// types) are mapped through `From<QueryRejection> for AppError` into a 400, and validation
+/// failures into a 422, so handlers receive a query that is already known to be well-formed.
+#[derive(Debug, Clone)]
+pub struct ValidatedQuery<T>(pub T);
+
+impl<T: Validate> ValidatedQuery<T> {
+  /// Validates an already deserialized query. Used by callers that do not go through the
+  /// extractor, such as the gRPC services.
+  pub fn new(query: T) -> AppResult<Self> {
+    query.validate()?;
+    Ok(Self(query))
+  }
+}
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for ValidatedQuery<T>
+where
+  T: DeserializeOwned + Validate,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    let Query(query) = Query::<T>::from_request_parts(parts, state).await?;
+    Self::new(query)
+  }
+}
@@ -1,55 +1,144 @@
-use std::sync::Arc;
+use std::marker::PhantomData;
 
 use crate::{
   AppResult,
-  errors::AppError,
-  modules::datastores::workspaces::{WorkspaceRepository, WorkspaceRole},
+  errors::{AppError, AuthError},
+  helper::path_uuid::parse_uuid,
+  modules::{auth::current_user::WorkspaceId, datastores::workspaces::WorkspaceRole},
+};
+use axum::{
+  async_trait,
+  extract::FromRequestParts,
+  http::{HeaderMap, request::Parts},
 };
-use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
 use uuid::Uuid;
 
-pub struct WorkspaceContext(pub Uuid);
+/// Header carrying the workspace a request operates on.
+pub const WORKSPACE_HEADER: &str = "X-Workspace-ID";
+
+/// Reads the `X-Workspace-ID` header, if present.
+fn workspace_from_headers(headers: &HeaderMap) -> AppResult<Option<Uuid>> {
+  headers
+    .get(WORKSPACE_HEADER)
+    .map(|value| {
+      let value = value.to_str().map_err(|_| AppError::BadRequest("Invalid workspace header".to_string()))?;
+      parse_uuid(WORKSPACE_HEADER, value)
+    })
+    .transpose()
+}
+
+/// The workspace a request operates on: the `X-Workspace-ID` header, or else the workspace
+/// claim of the access token, which `jwt_middleware` stores as `WorkspaceId`.
+fn request_workspace(parts: &Parts) -> AppResult<Option<Uuid>> {
+  Ok(workspace_from_headers(&parts.headers)?.or_else(|| parts.extensions.get::<WorkspaceId>().map(|workspace| workspace.0)))
+}
+
+/// The workspace a request operates on, taken from the `X-Workspace-ID` header or the token's
+/// workspace claim.
+///
+/// Used by the datastore routes, which are always scoped to a workspace: a missing workspace is
+/// rejected with `AuthError::MissingWorkspace` and a malformed header with a 400.
+#[derive(Debug, Clone, Copy)]
+pub struct RequiredWorkspace(pub Uuid);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RequiredWorkspace
+where
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    request_workspace(parts)?
+      .map(RequiredWorkspace)
+      .ok_or(AppError::Authentication(AuthError::MissingWorkspace))
+  }
+}
+
+/// The workspace of `RequiredWorkspace` for routes that also work without it, or that accept the
+/// workspace from elsewhere (such as the realtime query parameters). A malformed header is
+/// still rejected.
+#[derive(Debug, Clone, Copy)]
+pub struct OptionalWorkspace(pub Option<Uuid>);
 
 #[async_trait]
-impl<S> FromRequestParts<S> for WorkspaceContext
+impl<S> FromRequestParts<S> for OptionalWorkspace
 where
   S: Send + Sync,
 {
   type Rejection = AppError;
 
   async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-    if let Some(workspace_header) = parts.headers.get("X-Workspace-ID") {
-      let workspace_str = workspace_header
-        .to_str()
-        .map_err(|_| AppError::BadRequest("Invalid workspace header".to_string()))?;
+    request_workspace(parts).map(OptionalWorkspace)
+  }
+}
 
-      let workspace_id = workspace_str.parse::<Uuid>()?;
+/// Marker types naming the minimum role a `RequireRole` extractor accepts.
+pub mod role {
+  use crate::modules::datastores::workspaces::WorkspaceRole;
 
-      Ok(WorkspaceContext(workspace_id))
+  /// A minimum workspace role, as a type.
+  pub trait MinimumRole: Send + Sync + 'static {
+    const ROLE: WorkspaceRole;
+  }
+
+  pub struct Viewer;
+  pub struct Member;
+  pub struct Admin;
+
+  impl MinimumRole for Viewer {
+    const ROLE: WorkspaceRole = WorkspaceRole::Viewer;
+  }
+
+  impl MinimumRole for Member {
+    const ROLE: WorkspaceRole = WorkspaceRole::Member;
+  }
+
+  impl MinimumRole for Admin {
+    const ROLE: WorkspaceRole = WorkspaceRole::Admin;
+  }
+}
+
+/// Proof that the caller holds at least role `R` in the request's workspace.
+///
+/// `jwt_middleware` already looks up the caller's role when it validates `X-Workspace-ID` and
+/// stores it in the request extensions; this extractor only compares it against `R`, so handlers
+/// no longer query `workspace_users` a second time. Requests without a role (no workspace header)
+/// are rejected as unauthorized.
+pub struct RequireRole<R: role::MinimumRole> {
+  pub role: WorkspaceRole,
+  _required: PhantomData<R>,
+}
+
+impl<R: role::MinimumRole> RequireRole<R> {
+  /// Checks an already loaded role. Used by callers that do not go through the JWT middleware,
+  /// such as the gRPC services.
+  pub fn check(role: WorkspaceRole) -> AppResult<Self> {
+    if role.includes(R::ROLE) {
+      Ok(Self {
+        role,
+        _required: PhantomData,
+      })
     } else {
-      Err(AppError::BadRequest("X-Workspace-ID header is required".to_string()))
+      Err(AppError::Authorization("You don't have permission to access this workspace".to_string()))
     }
   }
 }
 
-/// Helper function to check if user has required role or higher in workspace
-pub async fn check_workspace_permission(
-  workspace_repository: &Arc<dyn WorkspaceRepository + Send + Sync>,
-  workspace_id: Uuid,
-  user_id: Uuid,
-  required_role: WorkspaceRole,
-) -> AppResult<bool> {
-  let user_role = workspace_repository.check_user_workspace_access(user_id, workspace_id).await?;
-
-  match user_role {
-    Some(role) => {
-      let has_permission = match required_role {
-        WorkspaceRole::Viewer => matches!(role, WorkspaceRole::Viewer | WorkspaceRole::Member | WorkspaceRole::Admin),
-        WorkspaceRole::Member => matches!(role, WorkspaceRole::Member | WorkspaceRole::Admin),
-        WorkspaceRole::Admin => matches!(role, WorkspaceRole::Admin),
-      };
-      Ok(has_permission)
-    }
-    None => Ok(false),
+#[async_trait]
+impl<R, S> FromRequestParts<S> for RequireRole<R>
+where
+  R: role::MinimumRole,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    let role = parts
+      .extensions
+      .get::<WorkspaceRole>()
+      .copied()
+      .ok_or_else(|| AppError::Authorization("You don't have permission to access this workspace".to_string()))?;
+    Self::check(role)
   }
 }
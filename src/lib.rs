@@ -7,30 +7,62 @@
 //! - `app()`: Builds the Axum router and defines the application's routes.
 //! - `setup_state()`: Initializes the application state, including the database connection pool.
 //! - `run()`: Starts the web server.
+//! - `cli`: The command line interface wrapping `run()` and the operational subcommands.
 //!
 //! The application follows a modular structure, with features like contacts, errors, and state
 //! management organized into their respective modules.
+//!
+//! The `contacts` and `products` Cargo features (both on by default) compile those modules and
+//! register their routes; `grpc` enables both. Build with `--no-default-features` to embed only
+//! auth and workspaces. The `billing` feature, also on by default, adds Stripe subscriptions per
+//! workspace, `backups` scheduled database backups to S3, `secrets` loading secrets from
+//! HashiCorp Vault or AWS Secrets Manager, `reports` scheduled reports delivered by email and
+//! webhook, `import` CSV imports of contacts and products from accounting tools, `exports` CSV
+//! exports of them, `email_templates` per-workspace email templates, `rendering` PDF printing and
+//! barcode labels, `inbound` signed webhooks from third-party systems, `connectors` Shopify and
+//! WooCommerce stock and order syncs, `currencies` daily exchange rates, and `geo` address
+//! autocomplete. All of these are on by default as well.
 
-use axum::{Router, middleware, routing::get};
-use sqlx::postgres::PgPoolOptions;
-use std::sync::Arc;
+use axum::{Router, error_handling::HandleErrorLayer, routing::get};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use std::{sync::Arc, time::Duration};
+use tower::{ServiceBuilder, limit::GlobalConcurrencyLimitLayer};
 use tracing::{Level, info};
 
-use crate::modules::auth::auth_repository::AuthRepositoryImpl;
+use crate::config::{AppConfig, DatabaseConfig};
+#[cfg(any(feature = "contacts", feature = "products"))]
+use crate::middleware::{DeprecationNotice, with_deprecation};
+use crate::middleware::{
+  access_log_layer, etag_middleware, handle_middleware_error, idempotency_middleware, ip_allowlist_middleware, payload_logging_middleware,
+  rate_limit_middleware, trial_middleware, usage_middleware, with_body_limit,
+};
 use crate::modules::auth::jwt_middleware::jwt_middleware;
-use crate::modules::datastores::contacts::contact_repository::SqlxContactRepository;
-use crate::modules::datastores::products::product_repository::SqlxProductRepository;
-use crate::modules::datastores::workspaces::workspace_repository::PostgresWorkspaceRepository;
+use crate::redis_stores::{RedisIdempotencyStore, RedisRateLimitStore, RedisSessionActivityStore, RedisTokenRevocationStore};
+use crate::utils::ReadPool;
 
+pub mod cli;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod config;
 pub mod errors;
+pub mod events;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod helper;
+pub mod mailer;
+pub mod middleware;
 pub mod modules;
+pub mod openapi;
+pub mod redis_stores;
 pub mod responses;
+pub mod secrets;
+pub mod seed;
 pub mod state;
+pub mod tls;
 pub mod utils;
 
 pub use errors::AppError;
-pub use state::AppState;
+pub use state::{AppState, AppStateBuilder};
 
 /// A convenient `Result` type alias for the application.
 ///
@@ -54,24 +86,150 @@ pub fn app(app_state: Arc<AppState>) -> Router {
   let public_auth_routes = modules::auth::auth_routes::public_auth_routes();
   let protected_auth_routes = modules::auth::auth_routes::protected_auth_routes();
 
+  // v1 routes with a v2 successor advertise it once v1 is deprecated in the config
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  let versions = app_state.config.api_versions.clone();
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  let v1_deprecation = |successor: &str| {
+    versions.v1_deprecated_at.map(|deprecated_at| DeprecationNotice {
+      deprecated_at,
+      sunset_at: versions.v1_sunset_at,
+      successor: successor.to_string(),
+    })
+  };
+
+  // Body limits are applied per route group; import and upload routes use `body_limit.upload_bytes`
+  let body_limit = &app_state.config.body_limit;
+
   let public_routes = Router::new()
     .route("/", get(|| async { "🚀 Welcome to the My Rust Base API!" }))
-    .nest("/api/v1/auth", public_auth_routes);
+    .nest("/api/v1/auth", public_auth_routes)
+    // Real-time updates authenticate the connection themselves
+    .nest("/api/v1/ws", modules::realtime::realtime_routes::ws_router())
+    .nest("/api/v1/events", modules::realtime::realtime_routes::events_router());
+  let public_routes =
+    with_body_limit(public_routes, body_limit.default_bytes).layer(axum::middleware::from_fn_with_state(app_state.clone(), rate_limit_middleware));
 
-  let private_routes = Router::new()
-    .nest("/api/v1/auth", protected_auth_routes)
-    //datastores
-    .nest("/api/v1/contacts", modules::datastores::contacts::contact_routes::router())
-    .nest("/api/v1/products", modules::datastores::products::product_routes::router())
+  let private_routes = Router::new().nest("/api/v1/auth", protected_auth_routes);
+  //datastores, each behind its Cargo feature
+  #[cfg(feature = "contacts")]
+  let private_routes = private_routes.nest(
+    "/api/v1/contacts",
+    with_deprecation(
+      modules::datastores::contacts::contact_routes::router(),
+      v1_deprecation("/api/v2/contacts"),
+    ),
+  );
+  #[cfg(feature = "products")]
+  let private_routes = private_routes.nest(
+    "/api/v1/products",
+    with_deprecation(
+      modules::datastores::products::product_routes::router(),
+      v1_deprecation("/api/v2/products"),
+    ),
+  );
+  #[cfg(feature = "products")]
+  let private_routes = private_routes.nest("/api/v1/categories", modules::datastores::products::product_routes::category_router());
+  #[cfg(feature = "billing")]
+  let private_routes = private_routes.nest("/api/v1/billing", modules::billing::billing_routes::router());
+  #[cfg(feature = "backups")]
+  let private_routes = private_routes.nest("/api/v1/admin/backups", modules::backups::backup_routes::admin_router());
+  #[cfg(feature = "reports")]
+  let private_routes = private_routes.nest("/api/v1/workspaces", modules::reports::report_routes::router());
+  #[cfg(feature = "email_templates")]
+  let private_routes = private_routes.nest("/api/v1/templates", modules::email_templates::email_template_routes::router());
+  #[cfg(feature = "rendering")]
+  let private_routes = private_routes.nest("/api/v1/print-templates", modules::rendering::rendering_routes::router());
+  #[cfg(feature = "exports")]
+  let private_routes = private_routes.nest("/api/v1/exports", modules::exports::export_routes::router());
+  #[cfg(feature = "inbound")]
+  let private_routes = private_routes.nest("/api/v1/inbound-integrations", modules::inbound::inbound_routes::router());
+  #[cfg(feature = "connectors")]
+  let private_routes = private_routes.nest("/api/v1/connectors", modules::connectors::connector_routes::router());
+  #[cfg(feature = "currencies")]
+  let private_routes = private_routes.nest("/api/v1/exchange-rates", modules::currencies::currency_routes::router());
+  #[cfg(feature = "geo")]
+  let private_routes = private_routes.nest("/api/v1/geo", modules::geo::geo_routes::router());
+  // Polling triggers for automation platforms
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  let private_routes = private_routes.nest("/api/v1/triggers", modules::triggers::trigger_routes::router());
+  // Changes waiting for an admin
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  let private_routes = private_routes.nest("/api/v1/approvals", modules::approvals::approval_routes::router());
+  // Requests running in the background
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  let private_routes = private_routes.nest("/api/v1/operations", modules::operations::operation_routes::router());
+  // Search across the records of the workspace
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  let private_routes = private_routes.nest("/api/v1/search", modules::search::search_routes::router());
+  let private_routes = private_routes
+    // Feature flags
+    .nest("/api/v1/feature-flags", modules::feature_flags::feature_flag_routes::router())
+    .nest("/api/v1/admin/feature-flags", modules::feature_flags::feature_flag_routes::admin_router())
+    // Platform administration
+    .nest("/api/v1/admin", modules::admin::admin_routes::router())
+    // The key numbers of all the caller's workspaces
+    .nest("/api/v1/overview", modules::overview::overview_routes::router())
+    // The caller's rate limits and plan quotas
+    .nest("/api/v1/limits", modules::limits::limits_routes::router())
+    // Organizations grouping workspaces
+    .nest("/api/v1/organizations", modules::organizations::organization_routes::router())
     // Workspaces
     .nest("/api/v1", modules::datastores::workspaces::workspace_routes::workspace_routes())
-    .layer(middleware::from_fn_with_state(app_state.clone(), jwt_middleware));
+    // API v2: same repositories, new response shapes
+    .nest("/api/v2", modules::v2::router());
+  let private_routes = with_body_limit(private_routes, body_limit.default_bytes);
+  #[cfg(feature = "import")]
+  let private_routes = private_routes.nest(
+    "/api/v1/import",
+    with_body_limit(modules::import::import_routes::router(), body_limit.upload_bytes),
+  );
+  let private_routes = private_routes
+    .layer(axum::middleware::from_fn(etag_middleware))
+    .layer(axum::middleware::from_fn_with_state(app_state.clone(), idempotency_middleware))
+    .layer(axum::middleware::from_fn_with_state(app_state.clone(), usage_middleware))
+    // Requests rejected by the rate limiter or for an expired trial are not metered
+    .layer(axum::middleware::from_fn_with_state(app_state.clone(), trial_middleware))
+    // Layers run bottom-up: the JWT middleware identifies the caller before rate limiting, trial checks, metering and idempotency
+    .layer(axum::middleware::from_fn_with_state(app_state.clone(), rate_limit_middleware))
+    // Requests from outside the IP allowlist of their workspace are turned away before anything else
+    .layer(axum::middleware::from_fn_with_state(app_state.clone(), ip_allowlist_middleware))
+    .layer(axum::middleware::from_fn_with_state(app_state.clone(), jwt_middleware));
+
+  // Stripe signs its webhooks and retries them until they succeed, so they skip the JWT middleware and rate limits
+  #[cfg(feature = "billing")]
+  let webhook_routes = with_body_limit(
+    Router::new().nest("/api/v1/billing/webhooks", modules::billing::billing_routes::webhook_routes()),
+    body_limit.default_bytes,
+  );
 
-  Router::new()
+  // Abort slow requests and shed load once the global concurrency limit is reached
+  let server_config = &app_state.config.server;
+  let resilience_layers = ServiceBuilder::new()
+    .layer(HandleErrorLayer::new(handle_middleware_error))
+    .load_shed()
+    .layer(GlobalConcurrencyLimitLayer::new(server_config.max_concurrent_requests))
+    .timeout(Duration::from_secs(server_config.request_timeout_secs));
+
+  let router = Router::new()
     .merge(public_routes) // Public routes without auth
-    .merge(private_routes) // Private routes with JWT auth
-    .with_state(app_state)
+    .merge(private_routes); // Private routes with JWT auth
+  #[cfg(feature = "billing")]
+  let router = router.merge(webhook_routes); // Webhooks authenticated by their signature
+  // Third-party deliveries are signed with the secret of their integration, like Stripe's
+  #[cfg(feature = "inbound")]
+  let router = router.merge(with_body_limit(
+    Router::new().nest("/api/v1/inbound", modules::inbound::inbound_routes::receive_routes()),
+    body_limit.default_bytes,
+  ));
+
+  router
     .fallback(modules::method_not_allowed_handler::fallback)
+    .layer(axum::middleware::from_fn_with_state(app_state.clone(), payload_logging_middleware))
+    .layer(resilience_layers)
+    // Outermost, so timeouts and shed requests are logged too
+    .layer(access_log_layer(app_state.request_stats.clone()))
+    .with_state(app_state)
 }
 
 /// Initializes the shared `AppState`.
@@ -79,35 +237,99 @@ pub fn app(app_state: Arc<AppState>) -> Router {
 /// This asynchronous function is responsible for setting up the application's initial state.
 /// It performs the following key tasks:
 /// 1. Loads environment variables from a `.env` file.
-/// 2. Establishes a connection pool to the PostgreSQL database.
-/// 3. Creates and returns an `AppState` instance containing the database pool and initialized repositories.
+/// 2. Loads the `AppConfig` (rate limits, body limits and other tunables) from the environment, and
+///    the JWT secrets and database URL from the secrets backend selected by `SECRETS_PROVIDER`.
+/// 3. Establishes a connection pool to the PostgreSQL database, sized and timed out as configured, plus
+///    a lazily connected read replica pool when `DATABASE_READ_URL` is set.
+/// 4. Connects to Redis when `REDIS_URL` is set, to share the rate limit, revocation, session activity and idempotency stores.
+/// 5. Creates and returns an `AppState` instance containing the database pool and initialized repositories.
+///
+/// Applications embedding this crate, and tests needing other parts, can assemble the state
+/// themselves with `AppState::builder` instead.
 ///
 /// # Panics
 ///
 /// This function will panic if:
-/// - The `DATABASE_URL` environment variable is not set.
-/// - It fails to connect to the database.
+/// - `JWT_SECRET` or `DATABASE_URL` are neither in the secrets backend nor in the environment,
+///   or the secrets backend cannot be reached.
+/// - It fails to connect to the database, or to Redis when `REDIS_URL` is set.
+/// - `FIELD_ENCRYPTION_KEYS` is set but not a list of `key_id:base64_key` entries of 32 byte keys.
 pub async fn setup_state() -> Arc<AppState> {
   dotenvy::dotenv().ok();
-  let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-  let jwt_secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+  let mut config = AppConfig::from_env();
+  let provider = secrets::provider(&config.secrets).expect("Invalid secrets backend settings");
+  let secrets = secrets::load(provider.as_ref()).await.expect("Failed to load secrets");
+  let db_url = secrets.database_url;
+  // Backups of a database whose URL is kept in the secrets backend
+  config.backups.database_url.get_or_insert_with(|| db_url.clone());
+  if let Some(keys) = &secrets.field_encryption_keys {
+    config.encryption.keys = keys.split(',').map(str::trim).filter(|key| !key.is_empty()).map(String::from).collect();
+  }
 
-  let db_pool = PgPoolOptions::new()
-    .max_connections(10)
-    .min_connections(1)
-    .connect(&db_url)
+  let db_options = connect_options(&db_url, &config.database);
+  // Only where the database is: the URL itself carries the password
+  let db_location = format!(
+    "{}:{}/{}",
+    db_options.get_host(),
+    db_options.get_port(),
+    db_options.get_database().unwrap_or_default()
+  );
+  let db_pool = pool_options(&config.database)
+    .connect_with(db_options)
     .await
     .expect("Failed to connect to the database");
-  info!("✅ Connected to database {}", db_url);
-
-  Arc::new(AppState {
-    db: db_pool.clone(),
-    contact_repository: Arc::new(SqlxContactRepository::new(db_pool.clone())),
-    product_repository: Arc::new(SqlxProductRepository::new(db_pool.clone())),
-    auth_repository: Arc::new(AuthRepositoryImpl::new(db_pool.clone())),
-    workspace_repository: Arc::new(PostgresWorkspaceRepository::new(db_pool.clone())),
-    jwt_secret,
-  })
+  info!("✅ Connected to database {}", db_location);
+
+  // The replica connects lazily and only serves reads once it passes a health check
+  let db_read = match std::env::var("DATABASE_READ_URL").ok().filter(|url| !url.trim().is_empty()) {
+    Some(read_url) => {
+      let replica = pool_options(&config.database).connect_lazy_with(connect_options(&read_url, &config.database));
+      info!("Read replica configured, reads fall back to the primary while it is unavailable");
+      ReadPool::with_replica(db_pool.clone(), replica)
+    }
+    None => ReadPool::primary_only(db_pool.clone()),
+  };
+
+  let mut builder = AppState::builder(db_pool, secrets.jwt_secret).with_read_pool(db_read);
+  if let Some(secret) = secrets.jwt_previous_secret {
+    builder = builder.with_jwt_previous_secret(secret);
+  }
+
+  if let Some(redis_url) = &config.redis.url {
+    let connection = redis_stores::connect(redis_url).await.expect("Failed to connect to Redis");
+    info!("✅ Connected to Redis");
+    builder = builder
+      .with_rate_limiter(Arc::new(RedisRateLimitStore::new(connection.clone())))
+      .with_token_revocations(Arc::new(RedisTokenRevocationStore::new(connection.clone())))
+      .with_session_activity(Arc::new(RedisSessionActivityStore::new(connection.clone())))
+      .with_idempotency_store(Arc::new(RedisIdempotencyStore::new(connection)));
+  }
+
+  builder.with_config(config).build()
+}
+
+/// Pool sizing and timeouts shared by the primary and the read replica.
+fn pool_options(config: &DatabaseConfig) -> PgPoolOptions {
+  let idle_timeout = (config.idle_timeout_secs > 0).then(|| Duration::from_secs(config.idle_timeout_secs));
+  PgPoolOptions::new()
+    .max_connections(config.max_connections)
+    .min_connections(config.min_connections)
+    .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
+    .idle_timeout(idle_timeout)
+}
+
+/// Parses a database URL and applies the per-session settings.
+///
+/// # Panics
+///
+/// This function will panic if `url` is not a valid PostgreSQL URL.
+pub(crate) fn connect_options(url: &str, config: &DatabaseConfig) -> PgConnectOptions {
+  let options: PgConnectOptions = url.parse().expect("Database URL must be a valid PostgreSQL URL");
+  if config.statement_timeout_ms == 0 {
+    return options;
+  }
+  // Sent as a startup parameter, so it applies to every connection of the pool
+  options.options([("statement_timeout", config.statement_timeout_ms.to_string())])
 }
 
 /// The main entry point for running the application server.
@@ -116,12 +338,21 @@ pub async fn setup_state() -> Arc<AppState> {
 /// 1. Initializes the `tracing` subscriber for structured logging.
 /// 2. Reads the `HOST` and `PORT` from environment variables, with default fallbacks.
 /// 3. Calls `setup_state()` to create the application state.
-/// 4. Binds a TCP listener to the specified address.
-/// 5. Starts the Axum server and serves the application.
+/// 4. Starts the search index refresher (see `utils::search_index`), the usage flusher
+///    (see `modules::usage::usage_meter`), the trial notifier (see `modules::trial::trial_notifier`),
+///    the retention purger (see `modules::retention::retention_purger`), the secrets refresher
+///    (see `secrets`), with the `backups` feature, the backup scheduler (see `modules::backups::backup_service`),
+///    with the `reports` feature, the report scheduler (see `modules::reports::report_service`), with
+///    the `connectors` feature, the connector scheduler (see `modules::connectors::connector_service`),
+///    with the `currencies` feature, the exchange rate scheduler (see `modules::currencies::exchange_rate_service`)
+///    and, with the `grpc` feature, the gRPC server on `GRPC_PORT` (see `grpc`).
+/// 5. Binds a TCP listener to the specified address.
+/// 6. Starts the Axum server and serves the application, over HTTPS when `TLS_CERT_PATH`
+///    and `TLS_KEY_PATH` are set (the certificate is reloaded on `SIGHUP`).
 ///
 /// # Panics
 ///
-/// This function will panic if it fails to bind the TCP listener or start the server.
+/// This function will panic if it fails to bind the TCP listener, load the TLS certificate, or start the server.
 pub async fn run() {
   dotenvy::dotenv().ok();
   tracing_subscriber::fmt().with_max_level(Level::INFO).init();
@@ -131,10 +362,51 @@ pub async fn run() {
   let addr = format!("{}:{}", host, port);
 
   let app_state = setup_state().await;
+  let tls_config = app_state.config.tls.clone();
+
+  tokio::spawn(utils::search_index::run_refresher(app_state.db.clone(), app_state.task_health.clone()));
+  tokio::spawn(modules::usage::usage_meter::run_flusher(app_state.clone()));
+  tokio::spawn(modules::trial::trial_notifier::run_notifier(app_state.clone()));
+  tokio::spawn(modules::retention::retention_purger::run_purger(app_state.clone()));
+  tokio::spawn(secrets::run_refresher(app_state.clone()));
+  #[cfg(feature = "backups")]
+  tokio::spawn(modules::backups::backup_service::run_scheduler(app_state.clone()));
+  #[cfg(feature = "reports")]
+  tokio::spawn(modules::reports::report_service::run_scheduler(app_state.clone()));
+  #[cfg(feature = "connectors")]
+  tokio::spawn(modules::connectors::connector_service::run_scheduler(app_state.clone()));
+  #[cfg(feature = "currencies")]
+  tokio::spawn(modules::currencies::exchange_rate_service::run_scheduler(app_state.clone()));
+
+  #[cfg(feature = "grpc")]
+  {
+    let grpc_addr = format!("{}:{}", host, app_state.config.grpc.port)
+      .parse()
+      .expect("HOST and GRPC_PORT must form a valid socket address");
+    tokio::spawn(grpc::serve(app_state.clone(), grpc_addr));
+  }
+
   let app = app(app_state);
 
+  if let Some((cert_path, key_path)) = tls_config.paths() {
+    let rustls_config = tls::load_rustls_config(cert_path, key_path)
+      .await
+      .expect("Failed to load TLS certificate and key");
+    tls::spawn_reload_on_sighup(rustls_config.clone(), cert_path.to_string(), key_path.to_string());
+
+    let socket_addr: std::net::SocketAddr = addr.parse().expect("HOST and PORT must form a valid socket address");
+    info!("🚀 Server running on https://{}", &addr);
+    axum_server::bind_rustls(socket_addr, rustls_config)
+      .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>())
+      .await
+      .expect("Failed to start server");
+    return;
+  }
+
   let listener = tokio::net::TcpListener::bind(&addr).await.expect("Failed to bind to address");
 
   info!("🚀 Server running on http://{}", &addr);
-  axum::serve(listener, app).await.expect("Failed to start server");
+  axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>())
+    .await
+    .expect("Failed to start server");
 }
@@ -0,0 +1,34 @@
+//! Outgoing email.
+//!
+//! Features send their emails through the `Mailer` in `AppState`. The default `LogMailer` only
+//! logs them, so nothing leaves the process until an application injects a mailer backed by its
+//! email provider with `AppStateBuilder::with_mailer`.
+
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::AppResult;
+
+/// A plain text email to a single recipient.
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+  pub to: String,
+  pub subject: String,
+  pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+  async fn send(&self, message: EmailMessage) -> AppResult<()>;
+}
+
+/// Writes emails to the log instead of sending them.
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+  async fn send(&self, message: EmailMessage) -> AppResult<()> {
+    info!("Email to {} ({}):\n{}", message.to, message.subject, message.body);
+    Ok(())
+  }
+}
@@ -1,17 +1,22 @@
 //! The main entry point for the application binary.
 //!
-//! This file is responsible for setting up the Tokio runtime and starting the
-//! application server by calling the `run` function from the `myapp_api_rust` library crate.
+//! This file is responsible for setting up the Tokio runtime, parsing the command line
+//! and dispatching to the `cli` module of the `myapp_api_rust` library crate.
 //! Keeping `main.rs` minimal allows the core application logic to reside in the library,
 //! which makes it easier to test and reuse.
 
-use myapp_api_rust::run;
+use clap::Parser;
+use myapp_api_rust::cli::{Cli, execute};
 
 /// The asynchronous main function.
 ///
-/// It initializes the Tokio runtime using the `#[tokio::main]` macro and
-/// awaits the `run` function, which contains the application's primary logic.
+/// It initializes the Tokio runtime using the `#[tokio::main]` macro, parses the
+/// subcommand (defaulting to `serve`) and exits with a non-zero status if it fails.
 #[tokio::main]
 async fn main() {
-  run().await;
+  let cli = Cli::parse();
+  if let Err(e) = execute(cli).await {
+    eprintln!("❌ {}", e);
+    std::process::exit(1);
+  }
 }
@@ -0,0 +1,192 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+  body::{Body, HttpBody, to_bytes},
+  extract::{Request, State},
+  http::{HeaderMap, StatusCode, Uri, header::CONTENT_TYPE},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use tower_http::{
+  classify::{ServerErrorsAsFailures, SharedClassifier},
+  trace::{MakeSpan, OnResponse, TraceLayer},
+};
+use tracing::{Span, field, info, warn};
+use uuid::Uuid;
+
+use crate::{modules::admin::request_stats::RequestStats, state::AppState};
+
+/// Placeholder written instead of a sensitive value.
+const REDACTED: &str = "[REDACTED]";
+
+/// Headers whose values never appear in the logs.
+const SENSITIVE_HEADERS: &[&str] = &["authorization", "cookie", "set-cookie", "x-api-key", "proxy-authorization"];
+
+/// Substrings marking JSON fields and query parameters whose values never appear in the logs:
+/// credentials, and the contact fields encrypted at rest.
+const SENSITIVE_FIELDS: &[&str] = &["password", "secret", "token", "authorization", "api_key", "tax_id", "bank_account"];
+
+/// Builds the access log layer.
+///
+/// Every request is logged once it completes, at `INFO`, with its method, path, status and
+/// latency, plus the user and workspace once `jwt_middleware` identified them. Query strings are
+/// logged with sensitive parameters (e.g. the `token` used by WebSocket clients) redacted.
+/// The status is also counted in `stats`.
+pub fn access_log_layer(stats: Arc<RequestStats>) -> TraceLayer<SharedClassifier<ServerErrorsAsFailures>, AccessLogSpan, (), AccessLogResponse> {
+  TraceLayer::new_for_http()
+    .make_span_with(AccessLogSpan)
+    .on_request(())
+    .on_response(AccessLogResponse { stats })
+}
+
+/// Records the authenticated caller on the access log entry of the current request.
+pub fn record_caller(user_id: Uuid, workspace_id: Option<Uuid>) {
+  let span = Span::current();
+  span.record("user_id", field::display(user_id));
+  if let Some(workspace_id) = workspace_id {
+    span.record("workspace_id", field::display(workspace_id));
+  }
+}
+
+/// Opens the `http_request` span the access log entry is written in.
+#[derive(Debug, Clone, Copy)]
+pub struct AccessLogSpan;
+
+impl<B> MakeSpan<B> for AccessLogSpan {
+  fn make_span(&mut self, request: &axum::http::Request<B>) -> Span {
+    let span = tracing::info_span!(
+      "http_request",
+      method = %request.method(),
+      path = %request.uri().path(),
+      query = field::Empty,
+      user_id = field::Empty,
+      workspace_id = field::Empty,
+    );
+    if let Some(query) = redacted_query(request.uri()) {
+      span.record("query", field::display(query));
+    }
+    span
+  }
+}
+
+/// Writes the access log entry once the response headers are ready.
+#[derive(Debug, Clone)]
+pub struct AccessLogResponse {
+  stats: Arc<RequestStats>,
+}
+
+impl<B> OnResponse<B> for AccessLogResponse {
+  fn on_response(self, response: &axum::http::Response<B>, latency: Duration, _span: &Span) {
+    let status = response.status();
+    self.stats.record(status.as_u16());
+    let latency_ms = u64::try_from(latency.as_millis()).unwrap_or(u64::MAX);
+    if status.is_server_error() {
+      warn!(status = status.as_u16(), latency_ms, "request failed");
+    } else {
+      info!(status = status.as_u16(), latency_ms, "request completed");
+    }
+  }
+}
+
+/// Middleware logging request headers and JSON request and response bodies, with credentials,
+/// password, secret and token fields redacted.
+///
+/// Only active when `ACCESS_LOG_PAYLOADS` is set, as payloads may still contain personal data.
+/// Streaming and oversized bodies are passed through without being logged.
+pub async fn payload_logging_middleware(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+  let config = &state.config.access_log;
+  if !config.log_payloads {
+    return next.run(request).await;
+  }
+  info!(headers = ?redacted_headers(request.headers()), "request headers");
+
+  let (parts, body) = request.into_parts();
+  let body = match log_body("request body", &parts.headers, body, config.max_body_bytes).await {
+    Ok(body) => body,
+    Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+  };
+  let response = next.run(Request::from_parts(parts, body)).await;
+
+  let (parts, body) = response.into_parts();
+  match log_body("response body", &parts.headers, body, config.max_body_bytes).await {
+    Ok(body) => Response::from_parts(parts, body),
+    Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+  }
+}
+
+/// Logs a small JSON body and hands back an equivalent one.
+async fn log_body(label: &str, headers: &HeaderMap, body: Body, max_bytes: usize) -> Result<Body, axum::Error> {
+  let is_json = headers
+    .get(CONTENT_TYPE)
+    .and_then(|v| v.to_str().ok())
+    .is_some_and(|v| v.starts_with("application/json") || v.contains("+json"));
+  let fits = body.size_hint().exact().is_some_and(|len| len > 0 && len <= max_bytes as u64);
+  if !is_json || !fits {
+    return Ok(body);
+  }
+
+  let bytes = to_bytes(body, max_bytes)
+    .await
+    .inspect_err(|e| warn!("Failed to buffer {} for logging: {}", label, e))?;
+
+  match serde_json::from_slice::<Value>(&bytes) {
+    Ok(mut value) => {
+      redact_json(&mut value);
+      info!(body = %value, "{}", label);
+    }
+    Err(_) => info!("{} is not valid JSON ({} bytes)", label, bytes.len()),
+  }
+  Ok(Body::from(bytes))
+}
+
+fn is_sensitive(name: &str) -> bool {
+  let name = name.to_ascii_lowercase();
+  SENSITIVE_FIELDS.iter().any(|field| name.contains(field))
+}
+
+/// Replaces the values of sensitive fields, at any depth.
+pub fn redact_json(value: &mut Value) {
+  match value {
+    Value::Object(map) => {
+      for (key, value) in map.iter_mut() {
+        if is_sensitive(key) {
+          *value = Value::String(REDACTED.to_string());
+        } else {
+          redact_json(value);
+        }
+      }
+    }
+    Value::Array(items) => items.iter_mut().for_each(redact_json),
+    _ => {}
+  }
+}
+
+/// The request headers with credentials replaced.
+pub fn redacted_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+  headers
+    .iter()
+    .map(|(name, value)| {
+      let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+        REDACTED.to_string()
+      } else {
+        String::from_utf8_lossy(value.as_bytes()).into_owned()
+      };
+      (name.to_string(), value)
+    })
+    .collect()
+}
+
+/// The query string with the values of sensitive parameters replaced.
+fn redacted_query(uri: &Uri) -> Option<String> {
+  let query = uri.query().filter(|q| !q.is_empty())?;
+  let redacted = query
+    .split('&')
+    .map(|pair| match pair.split_once('=') {
+      Some((name, _)) if is_sensitive(name) => format!("{}={}", name, REDACTED),
+      _ => pair.to_string(),
+    })
+    .collect::<Vec<_>>()
+    .join("&");
+  Some(redacted)
+}
@@ -0,0 +1,84 @@
+use axum::{
+  Router,
+  extract::{DefaultBodyLimit, Request, State},
+  http::{StatusCode, header::CONTENT_LENGTH},
+  middleware::{Next, from_fn_with_state},
+  response::{IntoResponse, Response},
+};
+
+use crate::errors::AppError;
+
+tokio::task_local! {
+  /// The body limit of the route being served, for the rejections raised while reading the body.
+  static BODY_LIMIT: usize;
+}
+
+/// Applies a request body size limit to every route currently in `router`.
+///
+/// Limits are per route group: call this on a router before merging or nesting it so that
+/// import and upload routes can get a larger budget than regular JSON endpoints.
+pub fn with_body_limit<S>(router: Router<S>, max_bytes: usize) -> Router<S>
+where
+  S: Clone + Send + Sync + 'static,
+{
+  router
+    .layer(from_fn_with_state(max_bytes, body_limit_middleware))
+    .layer(DefaultBodyLimit::max(max_bytes))
+}
+
+/// Rejects requests whose declared `Content-Length` exceeds the limit.
+///
+/// Streamed bodies without a length are still capped by `DefaultBodyLimit` when an
+/// extractor buffers them. `Json` rejections are mapped in `errors.rs` with `body_too_large`, which
+/// reads the limit set here; the bare 413 of `Bytes` extractors is replaced with the same error.
+pub async fn body_limit_middleware(State(max_bytes): State<usize>, request: Request, next: Next) -> Response {
+  let content_length = request
+    .headers()
+    .get(CONTENT_LENGTH)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.parse::<u64>().ok());
+
+  if let Some(length) = content_length
+    && length > max_bytes as u64
+  {
+    return AppError::BadRequest(format!(
+      "Request body is too large ({}); the maximum allowed size is {}",
+      format_size(length),
+      format_size(max_bytes as u64)
+    ))
+    .into_response();
+  }
+
+  let response = BODY_LIMIT.scope(max_bytes, next.run(request)).await;
+  if response.status() == StatusCode::PAYLOAD_TOO_LARGE {
+    return BODY_LIMIT.sync_scope(max_bytes, body_too_large).into_response();
+  }
+  response
+}
+
+/// The error for a body cut off at the size limit of the current route.
+pub fn body_too_large() -> AppError {
+  match BODY_LIMIT.try_with(|max_bytes| *max_bytes) {
+    Ok(max_bytes) => AppError::BadRequest(format!(
+      "Request body exceeds the maximum allowed size of {} for this endpoint",
+      format_size(max_bytes as u64)
+    )),
+    Err(_) => AppError::BadRequest("Request body exceeds the maximum allowed size for this endpoint".to_string()),
+  }
+}
+
+/// Formats a byte count for error messages, e.g. `2 MiB` or `512 bytes`.
+fn format_size(bytes: u64) -> String {
+  const KIB: u64 = 1024;
+  const MIB: u64 = 1024 * KIB;
+
+  if bytes >= MIB && bytes.is_multiple_of(MIB) {
+    format!("{} MiB", bytes / MIB)
+  } else if bytes >= MIB {
+    format!("{:.1} MiB", bytes as f64 / MIB as f64)
+  } else if bytes >= KIB && bytes.is_multiple_of(KIB) {
+    format!("{} KiB", bytes / KIB)
+  } else {
+    format!("{} bytes", bytes)
+  }
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  extract::{Request, State},
+  http::{HeaderName, HeaderValue, header::LINK},
+  middleware::{Next, from_fn_with_state},
+  response::Response,
+};
+use chrono::{DateTime, Utc};
+
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+/// Describes a deprecated group of routes and where clients should move to.
+#[derive(Debug, Clone)]
+pub struct DeprecationNotice {
+  /// When the routes were deprecated, sent as the `Deprecation` header (RFC 9745).
+  pub deprecated_at: DateTime<Utc>,
+  /// When the routes will stop working, sent as the `Sunset` header (RFC 8594).
+  pub sunset_at: Option<DateTime<Utc>>,
+  /// Path of the replacement API, sent as a `successor-version` link.
+  pub successor: String,
+}
+
+/// Marks every route currently in `router` as deprecated.
+///
+/// When `notice` is `None` the router is returned unchanged, so a version can be
+/// deprecated purely through configuration.
+pub fn with_deprecation<S>(router: Router<S>, notice: Option<DeprecationNotice>) -> Router<S>
+where
+  S: Clone + Send + Sync + 'static,
+{
+  match notice {
+    Some(notice) => router.layer(from_fn_with_state(Arc::new(notice), deprecation_middleware)),
+    None => router,
+  }
+}
+
+/// Adds `Deprecation`, `Sunset` and `Link` headers to every response.
+pub async fn deprecation_middleware(State(notice): State<Arc<DeprecationNotice>>, request: Request, next: Next) -> Response {
+  let mut response = next.run(request).await;
+  let headers = response.headers_mut();
+
+  if let Ok(value) = HeaderValue::from_str(&format!("@{}", notice.deprecated_at.timestamp())) {
+    headers.insert(DEPRECATION, value);
+  }
+  if let Some(sunset_at) = notice.sunset_at
+    && let Ok(value) = HeaderValue::from_str(&sunset_at.format("%a, %d %b %Y %H:%M:%S GMT").to_string())
+  {
+    headers.insert(SUNSET, value);
+  }
+  if let Ok(value) = HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", notice.successor)) {
+    headers.append(LINK, value);
+  }
+
+  response
+}
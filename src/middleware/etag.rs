@@ -0,0 +1,100 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use axum::{
+  body::{Body, HttpBody, to_bytes},
+  extract::Request,
+  http::{
+    HeaderMap, HeaderValue, Method, StatusCode,
+    header::{CONTENT_LENGTH, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+  },
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use tracing::warn;
+
+/// Largest response body that is buffered to compute an ETag; bigger bodies, and bodies of unknown
+/// size, are passed through untouched.
+const MAX_ETAG_BODY_BYTES: usize = 8 * 1024 * 1024;
+
+/// Middleware adding weak ETags to successful JSON `GET` responses.
+///
+/// The tag is a hash of the response body with the top-level `timestamp` field removed, so
+/// single resources and list pages get the same tag for as long as their data is unchanged.
+/// When the request's `If-None-Match` matches, the body is dropped and a `304 Not Modified`
+/// is returned instead.
+pub async fn etag_middleware(request: Request, next: Next) -> Response {
+  if request.method() != Method::GET {
+    return next.run(request).await;
+  }
+  let if_none_match = request.headers().get(IF_NONE_MATCH).cloned();
+
+  let response = next.run(request).await;
+  if response.status() != StatusCode::OK || !is_json(response.headers()) {
+    return response;
+  }
+
+  let (mut parts, body) = response.into_parts();
+  let len = body.size_hint().upper().or_else(|| content_length(&parts.headers));
+  if len.is_none_or(|len| len > MAX_ETAG_BODY_BYTES as u64) {
+    return Response::from_parts(parts, body);
+  }
+
+  let bytes = match to_bytes(body, MAX_ETAG_BODY_BYTES).await {
+    Ok(bytes) => bytes,
+    Err(e) => {
+      warn!("Failed to buffer response body for ETag: {}", e);
+      return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+  };
+
+  let Some(etag) = compute_etag(&bytes) else {
+    return Response::from_parts(parts, Body::from(bytes));
+  };
+
+  if let Some(if_none_match) = if_none_match
+    && etag_matches(&if_none_match, &etag)
+  {
+    let mut not_modified = StatusCode::NOT_MODIFIED.into_response();
+    not_modified.headers_mut().insert(ETAG, etag);
+    return not_modified;
+  }
+
+  parts.headers.insert(ETAG, etag);
+  Response::from_parts(parts, Body::from(bytes))
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+  headers
+    .get(CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .is_some_and(|value| value.starts_with("application/json"))
+}
+
+fn content_length(headers: &HeaderMap) -> Option<u64> {
+  headers.get(CONTENT_LENGTH)?.to_str().ok()?.parse().ok()
+}
+
+/// Hashes the JSON body, ignoring the per-request `timestamp` of `ApiResponse`.
+fn compute_etag(bytes: &[u8]) -> Option<HeaderValue> {
+  let mut value: serde_json::Value = serde_json::from_slice(bytes).ok()?;
+  if let Some(object) = value.as_object_mut() {
+    object.remove("timestamp");
+  }
+
+  let mut hasher = DefaultHasher::new();
+  value.to_string().hash(&mut hasher);
+  HeaderValue::from_str(&format!("W/\"{:016x}\"", hasher.finish())).ok()
+}
+
+/// Weak comparison as described in RFC 9110: the `W/` prefix is ignored on both sides.
+fn etag_matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+  let Ok(candidates) = if_none_match.to_str() else {
+    return false;
+  };
+  let etag = etag.to_str().unwrap_or_default().trim_start_matches("W/");
+
+  candidates
+    .split(',')
+    .map(str::trim)
+    .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
@@ -0,0 +1,269 @@
+use std::{
+  collections::HashMap,
+  hash::{DefaultHasher, Hash, Hasher},
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use axum::{
+  body::{Body, to_bytes},
+  extract::{Request, State},
+  http::{HeaderName, HeaderValue, Method, StatusCode, header::CONTENT_TYPE},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+  AppResult,
+  errors::AppError,
+  modules::auth::current_user::{UserId, WorkspaceId},
+  state::AppState,
+};
+
+const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// Longest accepted `Idempotency-Key` value.
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Number of tracked keys above which expired entries are purged on the next request.
+const PURGE_THRESHOLD: usize = 10_000;
+
+/// A response kept for replay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredResponse {
+  pub status: u16,
+  pub content_type: Option<String>,
+  pub body: String,
+}
+
+/// What is known about an idempotency key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+  /// Hash of the method, URI and body of the request that first used the key.
+  pub fingerprint: String,
+  /// The response to replay; `None` while the first request is still being processed.
+  pub response: Option<StoredResponse>,
+}
+
+/// Storage backend for `Idempotency-Key` records.
+///
+/// The in-memory store is enough for a single instance; the Redis store lets a retry that
+/// lands on another instance be replayed too.
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+  /// Atomically reserves `key` for a new request. Returns `None` when the reservation
+  /// succeeded, or the existing record when the key was already used.
+  async fn begin(&self, key: &str, fingerprint: &str, lock_ttl: Duration) -> AppResult<Option<IdempotencyRecord>>;
+  /// Stores the final response of a reserved key.
+  async fn complete(&self, key: &str, record: &IdempotencyRecord, ttl: Duration) -> AppResult<()>;
+  /// Drops a reservation so the request can be retried, e.g. after a server error.
+  async fn release(&self, key: &str) -> AppResult<()>;
+}
+
+/// Process-local idempotency store.
+#[derive(Default)]
+pub struct InMemoryIdempotencyStore {
+  records: Mutex<HashMap<String, (IdempotencyRecord, Instant)>>,
+}
+
+impl InMemoryIdempotencyStore {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  fn lock(&self) -> AppResult<std::sync::MutexGuard<'_, HashMap<String, (IdempotencyRecord, Instant)>>> {
+    self
+      .records
+      .lock()
+      .map_err(|_| AppError::Internal("Idempotency store lock poisoned".to_string()))
+  }
+}
+
+#[async_trait]
+impl IdempotencyStore for InMemoryIdempotencyStore {
+  async fn begin(&self, key: &str, fingerprint: &str, lock_ttl: Duration) -> AppResult<Option<IdempotencyRecord>> {
+    let now = Instant::now();
+    let mut records = self.lock()?;
+
+    if records.len() > PURGE_THRESHOLD {
+      records.retain(|_, (_, expires_at)| *expires_at > now);
+    }
+
+    if let Some((record, expires_at)) = records.get(key)
+      && *expires_at > now
+    {
+      return Ok(Some(record.clone()));
+    }
+
+    let record = IdempotencyRecord {
+      fingerprint: fingerprint.to_string(),
+      response: None,
+    };
+    records.insert(key.to_string(), (record, now + lock_ttl));
+    Ok(None)
+  }
+
+  async fn complete(&self, key: &str, record: &IdempotencyRecord, ttl: Duration) -> AppResult<()> {
+    self.lock()?.insert(key.to_string(), (record.clone(), Instant::now() + ttl));
+    Ok(())
+  }
+
+  async fn release(&self, key: &str) -> AppResult<()> {
+    self.lock()?.remove(key);
+    Ok(())
+  }
+}
+
+/// Middleware making `POST` and `PATCH` requests safe to retry.
+///
+/// When a request carries an `Idempotency-Key` header, its response is stored for
+/// `idempotency.ttl_secs` and a retry with the same key gets the stored response back
+/// (marked with `Idempotent-Replayed: true`) instead of repeating the write. Keys are scoped
+/// to the user and workspace, so this layer must run after `jwt_middleware`. Reusing a key for
+/// a different request, or while the first one is still running, is rejected with a 409.
+/// Server errors are not stored, so they can be retried.
+pub async fn idempotency_middleware(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+  if !matches!(*request.method(), Method::POST | Method::PATCH) {
+    return next.run(request).await;
+  }
+  let Some(key) = request.headers().get(IDEMPOTENCY_KEY) else {
+    return next.run(request).await;
+  };
+  let key = match valid_key(key.to_str().ok()) {
+    Ok(key) => key,
+    Err(e) => return e.into_response(),
+  };
+  let Some(UserId(user_id)) = request.extensions().get::<UserId>().copied() else {
+    return next.run(request).await;
+  };
+  let workspace_id = request.extensions().get::<WorkspaceId>().map(|WorkspaceId(id)| *id);
+  let store_key = scoped_key(user_id, workspace_id, key);
+
+  // The body is part of the fingerprint, so buffer it; route body limits still apply downstream
+  let (parts, body) = request.into_parts();
+  let bytes = match to_bytes(body, state.config.body_limit.upload_bytes).await {
+    Ok(bytes) => bytes,
+    Err(_) => return AppError::BadRequest("Request body is too large".to_string()).into_response(),
+  };
+  let fingerprint = fingerprint(parts.method.as_str(), &parts.uri.to_string(), &bytes);
+  let request = Request::from_parts(parts, Body::from(bytes));
+
+  match reserve(&state, &store_key, &fingerprint).await {
+    Ok(Reservation::Reserved) => {}
+    Ok(Reservation::Replay(stored)) => return replay(stored),
+    Ok(Reservation::Unavailable) => return next.run(request).await,
+    Err(e) => return e.into_response(),
+  }
+
+  let response = next.run(request).await;
+  let (parts, body) = response.into_parts();
+  let bytes = match to_bytes(body, usize::MAX).await {
+    Ok(bytes) => bytes,
+    Err(e) => {
+      finish(&state, &store_key, fingerprint, None).await;
+      return AppError::Internal(format!("Failed to read response body: {}", e)).into_response();
+    }
+  };
+
+  let stored = match String::from_utf8(bytes.to_vec()) {
+    Ok(body) if !parts.status.is_server_error() => Some(StoredResponse {
+      status: parts.status.as_u16(),
+      content_type: parts.headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(str::to_string),
+      body,
+    }),
+    _ => None,
+  };
+  finish(&state, &store_key, fingerprint, stored).await;
+
+  Response::from_parts(parts, Body::from(bytes))
+}
+
+/// What `reserve` found for an idempotency key.
+pub(crate) enum Reservation {
+  /// The key was free and is now held by this request.
+  Reserved,
+  /// The key was used for the same request, whose response is replayed.
+  Replay(StoredResponse),
+  /// The store failed; the request is processed without protection.
+  Unavailable,
+}
+
+/// Checks an idempotency key; `None` stands for a value that is not visible ASCII.
+pub(crate) fn valid_key(key: Option<&str>) -> AppResult<&str> {
+  key
+    .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LENGTH)
+    .ok_or_else(|| AppError::BadRequest(format!("Idempotency-Key must be 1 to {} visible ASCII characters", MAX_KEY_LENGTH)))
+}
+
+/// The store key of `key`, scoped to the user and workspace that sent it.
+pub(crate) fn scoped_key(user_id: Uuid, workspace_id: Option<Uuid>, key: &str) -> String {
+  let workspace = workspace_id.map_or_else(|| "-".to_string(), |id| id.to_string());
+  format!("{}:{}:{}", user_id, workspace, key)
+}
+
+/// Reserves `store_key` for the request with `fingerprint`. Reusing a key for a different
+/// request, or while the first one is still running, is a conflict.
+pub(crate) async fn reserve(state: &AppState, store_key: &str, fingerprint: &str) -> AppResult<Reservation> {
+  let lock_ttl = Duration::from_secs(state.config.server.request_timeout_secs.saturating_mul(2));
+  match state.idempotency_store.begin(store_key, fingerprint, lock_ttl).await {
+    Ok(None) => Ok(Reservation::Reserved),
+    Ok(Some(record)) if record.fingerprint != fingerprint => {
+      Err(AppError::Conflict("Idempotency-Key was already used for a different request".to_string()))
+    }
+    Ok(Some(IdempotencyRecord { response: None, .. })) => Err(AppError::Conflict(
+      "A request with this Idempotency-Key is still being processed".to_string(),
+    )),
+    Ok(Some(IdempotencyRecord { response: Some(stored), .. })) => Ok(Reservation::Replay(stored)),
+    Err(e) => {
+      // Never turn a store failure into an outage; process the request without protection.
+      warn!("Idempotency store unavailable, processing request without it: {}", e);
+      Ok(Reservation::Unavailable)
+    }
+  }
+}
+
+/// Stores the response of a reserved key for replay, or, without one, drops the reservation
+/// so the request can be retried.
+pub(crate) async fn finish(state: &AppState, store_key: &str, fingerprint: String, response: Option<StoredResponse>) {
+  let store = &state.idempotency_store;
+  let result = match response {
+    Some(response) => {
+      let record = IdempotencyRecord {
+        fingerprint,
+        response: Some(response),
+      };
+      store
+        .complete(store_key, &record, Duration::from_secs(state.config.idempotency.ttl_secs))
+        .await
+    }
+    None => store.release(store_key).await,
+  };
+  if let Err(e) = result {
+    warn!("Failed to update idempotency record: {}", e);
+  }
+}
+
+pub(crate) fn fingerprint(method: &str, uri: &str, body: &[u8]) -> String {
+  let mut hasher = DefaultHasher::new();
+  method.hash(&mut hasher);
+  uri.hash(&mut hasher);
+  body.hash(&mut hasher);
+  format!("{:016x}", hasher.finish())
+}
+
+fn replay(stored: StoredResponse) -> Response {
+  let status = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+  let mut response = (status, stored.body).into_response();
+  let headers = response.headers_mut();
+  headers.remove(CONTENT_TYPE);
+  if let Some(content_type) = stored.content_type.and_then(|v| HeaderValue::from_str(&v).ok()) {
+    headers.insert(CONTENT_TYPE, content_type);
+  }
+  headers.insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+  response
+}
@@ -0,0 +1,70 @@
+use std::{net::IpAddr, sync::Arc};
+
+use axum::{
+  extract::{Request, State},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use uuid::Uuid;
+
+use super::rate_limit::{client_ip, peer_addr};
+use crate::{
+  AppResult,
+  errors::AppError,
+  modules::{auth::current_user::WorkspaceId, workspace_settings::workspace_settings_models::WorkspaceSettings},
+  state::AppState,
+};
+
+/// The workspace a request is made in: the one placed in the extensions by `jwt_middleware`, or
+/// else the one managed through `/api/v1/workspaces/{id}/...`.
+fn request_workspace(request: &Request) -> Option<Uuid> {
+  if let Some(&WorkspaceId(workspace_id)) = request.extensions().get::<WorkspaceId>() {
+    return Some(workspace_id);
+  }
+  let rest = request.uri().path().strip_prefix("/api/v1/workspaces/")?;
+  rest.split('/').next()?.parse().ok()
+}
+
+/// Checks `ip` against the IP ranges allowed by the settings of a workspace, returning the settings.
+///
+/// Shared by `ip_allowlist_middleware` and the connections authenticated outside of it, the
+/// real-time streams and the gRPC services. Fails with `AppError::IpNotAllowed` when the workspace
+/// has allowed ranges and `ip` is unknown or in none of them. The settings come from
+/// `AppState::workspace_settings_cache` while fresh, so updates through another instance apply
+/// once they expire there.
+pub(crate) async fn check_client_ip(state: &AppState, workspace_id: Uuid, ip: Option<IpAddr>) -> AppResult<WorkspaceSettings> {
+  let settings = match state.workspace_settings_cache.get(workspace_id) {
+    Some(settings) => settings,
+    None => {
+      let settings = state.workspace_settings_repository.get(workspace_id).await?;
+      state.workspace_settings_cache.insert(workspace_id, settings.clone());
+      settings
+    }
+  };
+  if settings.allows(ip) {
+    Ok(settings)
+  } else {
+    Err(AppError::IpNotAllowed(ip.map_or_else(|| "unknown".to_string(), |ip| ip.to_string())))
+  }
+}
+
+/// Middleware restricting workspaces to the IP ranges set in their settings.
+///
+/// Needs the workspace placed in the request extensions by `jwt_middleware`, so this layer must
+/// run after it. Requests are checked by `check_client_ip`, and the settings placed in the
+/// request extensions for later extractors such as `Pagination`.
+pub async fn ip_allowlist_middleware(State(state): State<Arc<AppState>>, mut request: Request, next: Next) -> Response {
+  let Some(workspace_id) = request_workspace(&request) else {
+    return next.run(request).await;
+  };
+
+  let ip = client_ip(request.headers(), peer_addr(&request), &state.config.server.trusted_proxies);
+  // Unlike the trial check, a restriction asked for by the customer is never skipped
+  match check_client_ip(&state, workspace_id, ip).await {
+    Ok(settings) => {
+      request.extensions_mut().insert(settings);
+      next.run(request).await
+    }
+    Err(e) => e.into_response(),
+  }
+}
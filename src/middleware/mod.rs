@@ -0,0 +1,21 @@
+pub mod access_log;
+pub mod body_limit;
+pub mod deprecation;
+pub mod etag;
+pub mod idempotency;
+pub mod ip_allowlist;
+pub mod rate_limit;
+pub mod timeout;
+pub mod trial;
+pub mod usage;
+
+pub use access_log::{access_log_layer, payload_logging_middleware};
+pub use body_limit::with_body_limit;
+pub use deprecation::{DeprecationNotice, with_deprecation};
+pub use etag::etag_middleware;
+pub use idempotency::{IdempotencyStore, InMemoryIdempotencyStore, idempotency_middleware};
+pub use ip_allowlist::ip_allowlist_middleware;
+pub use rate_limit::{InMemoryRateLimitStore, RateLimitStore, rate_limit_middleware};
+pub use timeout::handle_middleware_error;
+pub use trial::trial_middleware;
+pub use usage::usage_middleware;
@@ -0,0 +1,261 @@
+use std::{
+  collections::HashMap,
+  net::{IpAddr, SocketAddr},
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use axum::{
+  extract::{ConnectInfo, Request, State},
+  http::{HeaderMap, HeaderName, HeaderValue, Method, header::RETRY_AFTER},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::{
+  AppResult,
+  errors::AppError,
+  modules::{
+    auth::current_user::{UserId, WorkspaceId},
+    workspace_settings::workspace_settings_models::IpRange,
+  },
+  state::AppState,
+};
+
+const X_RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+const X_RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+const X_RATELIMIT_RESET: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+
+/// Number of tracked keys above which expired windows are purged on the next hit.
+const PURGE_THRESHOLD: usize = 10_000;
+
+/// The outcome of counting one request against a rate limit bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDecision {
+  /// Whether the request fits in the current window.
+  pub allowed: bool,
+  /// The budget of the bucket for one window.
+  pub limit: u32,
+  /// Requests left in the current window after this one.
+  pub remaining: u32,
+  /// Seconds until the current window resets.
+  pub reset_after: u64,
+}
+
+/// Storage backend for rate limit counters.
+///
+/// Implementations count hits per key in fixed windows. The in-memory store is
+/// enough for a single instance; shared stores let several instances enforce one budget.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+  async fn hit(&self, key: &str, limit: u32, window: Duration) -> AppResult<RateLimitDecision>;
+  /// The current window of `key` as `hit` would leave it, without counting a request. `allowed`
+  /// tells whether the next request would fit.
+  async fn peek(&self, key: &str, limit: u32, window: Duration) -> AppResult<RateLimitDecision>;
+}
+
+struct WindowCounter {
+  started_at: Instant,
+  count: u32,
+}
+
+/// Process-local fixed-window rate limit store.
+#[derive(Default)]
+pub struct InMemoryRateLimitStore {
+  windows: Mutex<HashMap<String, WindowCounter>>,
+}
+
+impl InMemoryRateLimitStore {
+  pub fn new() -> Self {
+    Self::default()
+  }
+}
+
+#[async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+  async fn hit(&self, key: &str, limit: u32, window: Duration) -> AppResult<RateLimitDecision> {
+    let now = Instant::now();
+    let mut windows = self
+      .windows
+      .lock()
+      .map_err(|_| AppError::Internal("Rate limit store lock poisoned".to_string()))?;
+
+    if windows.len() > PURGE_THRESHOLD {
+      windows.retain(|_, counter| now.duration_since(counter.started_at) < window);
+    }
+
+    let counter = windows.entry(key.to_string()).or_insert(WindowCounter { started_at: now, count: 0 });
+    if now.duration_since(counter.started_at) >= window {
+      counter.started_at = now;
+      counter.count = 0;
+    }
+
+    counter.count = counter.count.saturating_add(1);
+    let elapsed = now.duration_since(counter.started_at);
+    let reset_after = window.saturating_sub(elapsed).as_secs().max(1);
+
+    Ok(RateLimitDecision {
+      allowed: counter.count <= limit,
+      limit,
+      remaining: limit.saturating_sub(counter.count),
+      reset_after,
+    })
+  }
+
+  async fn peek(&self, key: &str, limit: u32, window: Duration) -> AppResult<RateLimitDecision> {
+    let now = Instant::now();
+    let windows = self
+      .windows
+      .lock()
+      .map_err(|_| AppError::Internal("Rate limit store lock poisoned".to_string()))?;
+
+    let (count, reset_after) = match windows.get(key) {
+      Some(counter) if now.duration_since(counter.started_at) < window => (
+        counter.count,
+        window.saturating_sub(now.duration_since(counter.started_at)).as_secs().max(1),
+      ),
+      _ => (0, window.as_secs().max(1)),
+    };
+
+    Ok(RateLimitDecision {
+      allowed: count < limit,
+      limit,
+      remaining: limit.saturating_sub(count),
+      reset_after,
+    })
+  }
+}
+
+/// Middleware enforcing the configured request budgets.
+///
+/// Authenticated requests are keyed by user and workspace (both placed in the request
+/// extensions by `jwt_middleware`, so this layer must run after it) with separate budgets
+/// for read and write methods. Anonymous requests share one budget per client IP.
+/// Every response carries `X-RateLimit-*` headers; rejected requests get a 429 `ErrorResponse`.
+pub async fn rate_limit_middleware(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+  let config = &state.config.rate_limit;
+  if !config.enabled {
+    return next.run(request).await;
+  }
+
+  let (key, limit) = bucket_for(&request, config, &state.config.server.trusted_proxies);
+  let Some(decision) = count(&state, &key, limit).await else {
+    return next.run(request).await;
+  };
+
+  let mut response = if decision.allowed {
+    next.run(request).await
+  } else {
+    debug!("Rate limit exceeded for key {}", key);
+    let mut response = AppError::RateLimited(decision.reset_after).into_response();
+    response.headers_mut().insert(RETRY_AFTER, HeaderValue::from(decision.reset_after));
+    response
+  };
+
+  let headers = response.headers_mut();
+  headers.insert(X_RATELIMIT_LIMIT, HeaderValue::from(decision.limit));
+  headers.insert(X_RATELIMIT_REMAINING, HeaderValue::from(decision.remaining));
+  headers.insert(X_RATELIMIT_RESET, HeaderValue::from(decision.reset_after));
+
+  response
+}
+
+/// Counts a call of `user_id` in `workspace_id` against the same read or write budget as their
+/// REST requests, rejecting it with `AppError::RateLimited` once the budget is spent.
+#[cfg(feature = "grpc")]
+pub(crate) async fn check_user_budget(state: &AppState, user_id: Uuid, workspace_id: Uuid, is_read: bool) -> AppResult<()> {
+  let config = &state.config.rate_limit;
+  if !config.enabled {
+    return Ok(());
+  }
+
+  let (key, limit) = user_bucket(config, user_id, Some(workspace_id), is_read);
+  match count(state, &key, limit).await {
+    Some(decision) if !decision.allowed => {
+      debug!("Rate limit exceeded for key {}", key);
+      Err(AppError::RateLimited(decision.reset_after))
+    }
+    _ => Ok(()),
+  }
+}
+
+/// Counts one request against the bucket `key`. `None` when the limiter failed, in which case
+/// the request is let through.
+async fn count(state: &AppState, key: &str, limit: u32) -> Option<RateLimitDecision> {
+  let window = Duration::from_secs(state.config.rate_limit.window_secs);
+  match state.rate_limiter.hit(key, limit, window).await {
+    Ok(decision) => Some(decision),
+    Err(e) => {
+      // Never turn a limiter failure into an outage; let the request through.
+      warn!("Rate limiter unavailable, allowing request: {}", e);
+      None
+    }
+  }
+}
+
+/// Picks the bucket key and budget for a request.
+fn bucket_for(request: &Request, config: &crate::config::RateLimitConfig, trusted_proxies: &[IpRange]) -> (String, u32) {
+  let is_read = matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+
+  match request.extensions().get::<UserId>() {
+    Some(UserId(user_id)) => {
+      let workspace_id = request.extensions().get::<WorkspaceId>().map(|WorkspaceId(id)| *id);
+      user_bucket(config, *user_id, workspace_id, is_read)
+    }
+    None => {
+      let ip = client_ip(request.headers(), peer_addr(request), trusted_proxies).map_or_else(|| "unknown".to_string(), |ip| ip.to_string());
+      (format!("ip:{}", ip), config.anonymous_max)
+    }
+  }
+}
+
+/// The bucket key and budget of a user's reads or writes in a workspace, or outside any.
+pub(crate) fn user_bucket(config: &crate::config::RateLimitConfig, user_id: Uuid, workspace_id: Option<Uuid>, is_read: bool) -> (String, u32) {
+  let workspace = workspace_id.map(|id| id.to_string()).unwrap_or_else(|| "none".to_string());
+  let (kind, limit) = if is_read {
+    ("read", config.read_max)
+  } else {
+    ("write", config.write_max)
+  };
+  (format!("user:{}:ws:{}:{}", user_id, workspace, kind), limit)
+}
+
+/// The address of the peer of the connection, as recorded by `into_make_service_with_connect_info`.
+pub(crate) fn peer_addr(request: &Request) -> Option<SocketAddr> {
+  request.extensions().get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| *addr)
+}
+
+/// The client address: the peer of the connection, or, when the peer is one of
+/// `trusted_proxies`, the address given by the headers of Fly.io and most load balancers. Taken
+/// from anyone else, those headers could be set to any address. `None` when the peer is unknown,
+/// such as requests not served through `into_make_service_with_connect_info`.
+pub(crate) fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>, trusted_proxies: &[IpRange]) -> Option<IpAddr> {
+  let peer = peer?.ip().to_canonical();
+  let trusted = |ip: IpAddr| trusted_proxies.iter().any(|range| range.contains(ip));
+  if !trusted(peer) {
+    return Some(peer);
+  }
+
+  let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+  if let Some(ip) = header("fly-client-ip")
+    .or_else(|| header("x-real-ip"))
+    .and_then(|value| value.trim().parse().ok())
+  {
+    return Some(ip);
+  }
+  // Each proxy appends the address it got the request from, so the nearest untrusted one is the
+  // client; the addresses before it are whatever the client sent
+  let forwarded = header("x-forwarded-for").into_iter().flat_map(|value| value.rsplit(','));
+  for hop in forwarded {
+    let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+      return Some(peer);
+    };
+    if !trusted(ip) {
+      return Some(ip);
+    }
+  }
+  Some(peer)
+}
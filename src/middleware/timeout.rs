@@ -0,0 +1,20 @@
+use axum::{
+  BoxError,
+  response::{IntoResponse, Response},
+};
+
+use crate::errors::AppError;
+
+/// Converts errors raised by the timeout and load-shedding layers into `AppError` responses.
+///
+/// Used with `HandleErrorLayer` so the tower layers wrapping the router surface the same
+/// JSON `ErrorResponse` shape as handlers do, instead of dropping the connection.
+pub async fn handle_middleware_error(err: BoxError) -> Response {
+  if err.is::<tower::timeout::error::Elapsed>() {
+    AppError::Timeout("request exceeded the configured timeout".to_string()).into_response()
+  } else if err.is::<tower::load_shed::error::Overloaded>() {
+    AppError::Overloaded("concurrency limit reached".to_string()).into_response()
+  } else {
+    AppError::Unhandled(format!("middleware error: {}", err)).into_response()
+  }
+}
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use axum::{
+  extract::{Request, State},
+  http::Method,
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+  AppResult,
+  errors::AppError,
+  modules::{auth::current_user::WorkspaceId, trial::trial_models::TrialState},
+  state::AppState,
+};
+
+/// Routes that stay writable after a trial ended: signing in, managing the workspace itself and
+/// upgrading its plan.
+const TRIAL_EXEMPT_PREFIXES: &[&str] = &["/api/v1/auth/", "/api/v1/workspaces", "/api/v1/billing/"];
+
+/// Middleware making workspaces read-only once their trial ended without a paid plan.
+///
+/// Needs the workspace placed in the request extensions by `jwt_middleware`, so this layer must
+/// run after it. Safe methods always pass; other requests made in an expired workspace are
+/// rejected with `AppError::TrialExpired` (see `check_writable`).
+pub async fn trial_middleware(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+  let Some(&WorkspaceId(workspace_id)) = request.extensions().get::<WorkspaceId>() else {
+    return next.run(request).await;
+  };
+  let read_only = matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+  let path = request.uri().path();
+  if read_only || TRIAL_EXEMPT_PREFIXES.iter().any(|prefix| path.starts_with(prefix)) {
+    return next.run(request).await;
+  }
+
+  match check_writable(&state, workspace_id).await {
+    Ok(()) => next.run(request).await,
+    Err(e) => e.into_response(),
+  }
+}
+
+/// Rejects a write in `workspace_id` with `AppError::TrialExpired` once its trial ended without a
+/// paid plan. The trial is looked up on every write, so a workspace can write again as soon as its
+/// subscription is recorded.
+pub(crate) async fn check_writable(state: &AppState, workspace_id: Uuid) -> AppResult<()> {
+  if !state.config.trial.enforced {
+    return Ok(());
+  }
+  match state.trial_repository.get_trial(workspace_id).await {
+    Ok(Some(trial)) if trial.state(Utc::now()) == TrialState::Expired => Err(AppError::TrialExpired(trial.ends_at)),
+    Ok(_) => Ok(()),
+    // Never turn a failed lookup into an outage; let the write through.
+    Err(e) => {
+      warn!("Trial status unavailable, allowing request: {}", e);
+      Ok(())
+    }
+  }
+}
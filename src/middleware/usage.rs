@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use axum::{
+  body::HttpBody,
+  extract::{MatchedPath, Request, State},
+  http::header::CONTENT_LENGTH,
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+  AppResult,
+  errors::AppError,
+  modules::auth::current_user::{UserId, WorkspaceId},
+  state::AppState,
+};
+
+/// Routes that stay available to a workspace over its quota: signing in, managing the workspace,
+/// reading its usage and limits, and upgrading its plan.
+const QUOTA_EXEMPT_PREFIXES: &[&str] = &["/api/v1/auth/", "/api/v1/workspaces", "/api/v1/limits", "/api/v1/billing/"];
+
+/// Middleware metering requests made in a workspace and enforcing the monthly quota of its plan.
+///
+/// Needs the user and workspace placed in the request extensions by `jwt_middleware`, so this
+/// layer must run after it; requests without a workspace are neither metered nor limited.
+/// Requests are counted with the size of their body (`Content-Length`) and of the response body
+/// when it is known up front; streamed responses count as empty. Rejected requests are not counted.
+pub async fn usage_middleware(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+  let config = &state.config.usage;
+  let (Some(&UserId(user_id)), Some(&WorkspaceId(workspace_id))) = (request.extensions().get::<UserId>(), request.extensions().get::<WorkspaceId>())
+  else {
+    return next.run(request).await;
+  };
+  if !config.enabled {
+    return next.run(request).await;
+  }
+
+  let path = request.uri().path();
+  if !QUOTA_EXEMPT_PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+    && let Err(e) = check_quota(&state, workspace_id).await
+  {
+    return e.into_response();
+  }
+
+  let route = request
+    .extensions()
+    .get::<MatchedPath>()
+    .map(|path| path.as_str().to_string())
+    .unwrap_or_else(|| path.to_string());
+  let request_bytes = request
+    .headers()
+    .get(CONTENT_LENGTH)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.parse().ok())
+    .unwrap_or(0);
+
+  let response = next.run(request).await;
+  let response_bytes = response.body().size_hint().exact().unwrap_or(0);
+  state.usage_meter.record(workspace_id, user_id, &route, request_bytes, response_bytes);
+
+  response
+}
+
+/// Rejects a request in `workspace_id` with `AppError::QuotaExceeded` once the workspace used up
+/// the monthly quota of its plan.
+pub(crate) async fn check_quota(state: &AppState, workspace_id: Uuid) -> AppResult<()> {
+  match state
+    .usage_meter
+    .check_quota(workspace_id, state.usage_repository.as_ref(), &state.config.usage)
+    .await
+  {
+    Err(e @ AppError::QuotaExceeded { .. }) => Err(e),
+    // Never turn a metering failure into an outage; let the request through.
+    Err(e) => {
+      warn!("Usage quota unavailable, allowing request: {}", e);
+      Ok(())
+    }
+    Ok(()) => Ok(()),
+  }
+}
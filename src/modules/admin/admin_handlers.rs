@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use axum::{extract::State, response::Json};
+use chrono::Utc;
+
+use super::admin_models::{AdminStatsQuery, AdminStatsResponse, BackgroundTaskHealth, RequestOutcomes};
+use crate::{
+  AppResult,
+  helper::{RequireSuperadmin, ValidatedQuery},
+  modules::usage::usage_meter::month_start,
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// Workspaces listed by size unless the query asks for another number.
+const DEFAULT_TOP_WORKSPACES: i64 = 10;
+
+/// Platform statistics for operational dashboards.
+pub async fn get_stats(
+  State(state): State<Arc<AppState>>,
+  _superadmin: RequireSuperadmin,
+  ValidatedQuery(query): ValidatedQuery<AdminStatsQuery>,
+) -> AppResult<Json<ApiResponse<AdminStatsResponse>>> {
+  let now = Utc::now();
+  let repository = &state.admin_repository;
+  let totals = repository.platform_totals().await?;
+  let top_workspaces = repository
+    .top_workspaces(query.top.unwrap_or(DEFAULT_TOP_WORKSPACES), month_start(now))
+    .await?;
+
+  let tasks = state.task_health.snapshot();
+  let response = ApiResponse::success(
+    AdminStatsResponse {
+      generated_at: now,
+      totals,
+      top_workspaces,
+      requests: RequestOutcomes {
+        last_5_minutes: state.request_stats.last_minutes(5),
+        last_hour: state.request_stats.last_minutes(60),
+      },
+      background_tasks: BackgroundTaskHealth {
+        healthy: tasks.iter().all(|task| task.consecutive_failures == 0),
+        tasks,
+      },
+    },
+    "Platform statistics retrieved successfully",
+  );
+  Ok(Json(response))
+}
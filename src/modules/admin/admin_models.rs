@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::request_stats::RequestCounts;
+use crate::utils::task_health::TaskStatus;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AdminStatsQuery {
+  /// Number of workspaces listed by size, 10 by default.
+  #[validate(range(min = 1, max = 100, message = "top must be between 1 and 100"))]
+  pub top: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlatformTotals {
+  pub users: i64,
+  /// Users whose account is not deactivated.
+  pub active_users: i64,
+  pub workspaces: i64,
+  /// Users who made an API request in the last 30 days, as metered in `api_usage`.
+  pub monthly_active_users: i64,
+}
+
+/// A workspace and the records it holds.
+#[derive(Debug, Serialize)]
+pub struct WorkspaceSize {
+  pub workspace_id: Uuid,
+  pub name: String,
+  pub members: i64,
+  pub contacts: i64,
+  pub products: i64,
+  /// Contacts and products together, the order of the list.
+  pub records: i64,
+  pub requests_this_month: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RequestOutcomes {
+  pub last_5_minutes: RequestCounts,
+  pub last_hour: RequestCounts,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackgroundTaskHealth {
+  /// Whether every task succeeded in its last run.
+  pub healthy: bool,
+  pub tasks: Vec<TaskStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminStatsResponse {
+  pub generated_at: DateTime<Utc>,
+  pub totals: PlatformTotals,
+  /// Largest workspaces first.
+  pub top_workspaces: Vec<WorkspaceSize>,
+  pub requests: RequestOutcomes,
+  pub background_tasks: BackgroundTaskHealth,
+}
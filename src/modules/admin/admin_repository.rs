@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+use super::admin_models::{PlatformTotals, WorkspaceSize};
+use crate::{
+  errors::AppError,
+  utils::{DbExecutor, ReadPool, tenant_schema},
+};
+
+#[async_trait]
+pub trait AdminRepository: Send + Sync {
+  async fn platform_totals(&self) -> Result<PlatformTotals, AppError>;
+  /// The `limit` workspaces holding the most records, with their API requests since `month_start`.
+  async fn top_workspaces(&self, limit: i64, month_start: NaiveDate) -> Result<Vec<WorkspaceSize>, AppError>;
+}
+
+pub struct PostgresAdminRepository {
+  read_pool: ReadPool,
+}
+
+impl PostgresAdminRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self {
+      read_pool: ReadPool::primary_only(db),
+    }
+  }
+
+  /// Runs the statistics on `read_pool` instead of the primary: they scan whole tables, and a
+  /// dashboard can do with figures a little behind.
+  pub fn with_read_pool(mut self, read_pool: ReadPool) -> Self {
+    self.read_pool = read_pool;
+    self
+  }
+}
+
+#[async_trait]
+impl AdminRepository for PostgresAdminRepository {
+  async fn platform_totals(&self) -> Result<PlatformTotals, AppError> {
+    let mut conn = self.read_pool.acquire().await?;
+    let row = sqlx::query!(
+      r#"
+        SELECT
+          (SELECT COUNT(*) FROM users) AS "users!",
+          (SELECT COUNT(*) FROM users WHERE is_active) AS "active_users!",
+          (SELECT COUNT(*) FROM workspaces) AS "workspaces!",
+          (SELECT COUNT(DISTINCT user_id) FROM api_usage WHERE day > CURRENT_DATE - 30) AS "monthly_active_users!"
+        "#
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(PlatformTotals {
+      users: row.users,
+      active_users: row.active_users,
+      workspaces: row.workspaces,
+      monthly_active_users: row.monthly_active_users,
+    })
+  }
+
+  async fn top_workspaces(&self, limit: i64, month_start: NaiveDate) -> Result<Vec<WorkspaceSize>, AppError> {
+    let mut conn = self.read_pool.acquire().await?;
+    // The records of isolated workspaces are in their own schema, counted there
+    let isolated = tenant_schema::isolated_workspaces(&mut conn).await?;
+    let isolated_counts = tenant_schema::for_each_workspace(&mut conn, isolated.clone(), |conn, _| {
+      Box::pin(
+        sqlx::query!(
+          r#"
+            SELECT
+              (SELECT COUNT(*) FROM contacts WHERE deleted_at IS NULL) AS "contacts!",
+              (SELECT COUNT(*) FROM products WHERE deleted_at IS NULL) AS "products!"
+            "#
+        )
+        .fetch_one(conn),
+      )
+    })
+    .await?;
+    let (isolated_contacts, isolated_products): (Vec<i64>, Vec<i64>) =
+      isolated_counts.into_iter().map(|counts| (counts.contacts, counts.products)).unzip();
+
+    let rows = sqlx::query!(
+      r#"
+        WITH isolated_counts AS (
+          SELECT * FROM UNNEST($3::UUID[], $4::BIGINT[], $5::BIGINT[]) AS i(workspace_id, contacts, products)
+        ), contact_counts AS (
+          SELECT workspace_id, COUNT(*) AS contacts FROM contacts WHERE deleted_at IS NULL GROUP BY workspace_id
+          UNION ALL
+          SELECT workspace_id, contacts FROM isolated_counts
+        ), product_counts AS (
+          SELECT workspace_id, COUNT(*) AS products FROM products WHERE deleted_at IS NULL GROUP BY workspace_id
+          UNION ALL
+          SELECT workspace_id, products FROM isolated_counts
+        ), sizes AS (
+          SELECT w.id, w.name, COALESCE(c.contacts, 0) AS contacts, COALESCE(p.products, 0) AS products
+          FROM workspaces w
+          LEFT JOIN (SELECT workspace_id, SUM(contacts)::BIGINT AS contacts FROM contact_counts GROUP BY workspace_id) c
+            ON c.workspace_id = w.id
+          LEFT JOIN (SELECT workspace_id, SUM(products)::BIGINT AS products FROM product_counts GROUP BY workspace_id) p
+            ON p.workspace_id = w.id
+          ORDER BY COALESCE(c.contacts, 0) + COALESCE(p.products, 0) DESC, w.created_at
+          LIMIT $1
+        )
+        SELECT
+          s.id AS workspace_id,
+          s.name,
+          (SELECT COUNT(*) FROM workspace_users wu WHERE wu.workspace_id = s.id) AS "members!",
+          s.contacts AS "contacts!",
+          s.products AS "products!",
+          (SELECT COALESCE(SUM(request_count), 0)::BIGINT FROM api_usage u WHERE u.workspace_id = s.id AND u.day >= $2)
+            AS "requests_this_month!"
+        FROM sizes s
+        ORDER BY s.contacts + s.products DESC
+        "#,
+      limit,
+      month_start,
+      &isolated,
+      &isolated_contacts,
+      &isolated_products
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|row| WorkspaceSize {
+          workspace_id: row.workspace_id,
+          name: row.name,
+          members: row.members,
+          contacts: row.contacts,
+          products: row.products,
+          records: row.contacts + row.products,
+          requests_this_month: row.requests_this_month,
+        })
+        .collect(),
+    )
+  }
+}
@@ -0,0 +1,11 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+
+use super::admin_handlers::get_stats;
+use crate::state::AppState;
+
+/// Platform administration for the superadmins, mounted at `/api/v1/admin` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new().route("/stats", get(get_stats))
+}
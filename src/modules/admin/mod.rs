@@ -0,0 +1,11 @@
+//! Platform administration: statistics across all workspaces for operational dashboards.
+//!
+//! Only superadmins (see `helper::RequireSuperadmin`) can call these routes, mounted under
+//! `/api/v1/admin`. Database totals cover the whole platform; request outcomes and background
+//! task health are those of the instance answering the request.
+
+pub mod admin_handlers;
+pub mod admin_models;
+pub mod admin_repository;
+pub mod admin_routes;
+pub mod request_stats;
@@ -0,0 +1,89 @@
+//! Request outcomes of the last hour, counted per minute for the admin statistics.
+//!
+//! The access log layer records the status of every response, including those of requests that
+//! timed out or were shed. Counts are kept in memory per process, so with several instances each
+//! one reports its own traffic.
+
+use std::sync::Mutex;
+
+use chrono::Utc;
+use serde::Serialize;
+
+/// Minutes of history kept.
+const WINDOW_MINUTES: usize = 60;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct MinuteBucket {
+  /// Minutes since the Unix epoch the counts belong to.
+  minute: i64,
+  requests: u64,
+  client_errors: u64,
+  server_errors: u64,
+}
+
+/// Requests answered within a period, by outcome.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct RequestCounts {
+  pub requests: u64,
+  /// 4xx responses.
+  pub client_errors: u64,
+  /// 5xx responses, including timeouts and shed requests.
+  pub server_errors: u64,
+  /// Share of the requests answered with a 5xx, between 0 and 1.
+  pub server_error_rate: f64,
+}
+
+#[derive(Debug)]
+pub struct RequestStats {
+  buckets: Mutex<[MinuteBucket; WINDOW_MINUTES]>,
+}
+
+impl Default for RequestStats {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl RequestStats {
+  pub fn new() -> Self {
+    Self {
+      buckets: Mutex::new([MinuteBucket::default(); WINDOW_MINUTES]),
+    }
+  }
+
+  /// Counts a response with `status` in the current minute.
+  pub fn record(&self, status: u16) {
+    let minute = Utc::now().timestamp().div_euclid(60);
+    let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let bucket = &mut buckets[minute.rem_euclid(WINDOW_MINUTES as i64) as usize];
+    if bucket.minute != minute {
+      *bucket = MinuteBucket {
+        minute,
+        ..MinuteBucket::default()
+      };
+    }
+    bucket.requests += 1;
+    match status {
+      400..=499 => bucket.client_errors += 1,
+      500..=599 => bucket.server_errors += 1,
+      _ => {}
+    }
+  }
+
+  /// The responses of the last `minutes` minutes (at most an hour), the current one included.
+  pub fn last_minutes(&self, minutes: usize) -> RequestCounts {
+    let current = Utc::now().timestamp().div_euclid(60);
+    let oldest = current - minutes.min(WINDOW_MINUTES) as i64;
+    let buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut counts = RequestCounts::default();
+    for bucket in buckets.iter().filter(|bucket| bucket.minute > oldest && bucket.minute <= current) {
+      counts.requests += bucket.requests;
+      counts.client_errors += bucket.client_errors;
+      counts.server_errors += bucket.server_errors;
+    }
+    if counts.requests > 0 {
+      counts.server_error_rate = counts.server_errors as f64 / counts.requests as f64;
+    }
+    counts
+  }
+}
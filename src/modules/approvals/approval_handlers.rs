@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use axum::{
+  Json,
+  extract::{State, rejection::JsonRejection},
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{
+  approval_models::{ApprovalRequest, ApprovalStatus, ApprovalsQuery, DEFAULT_APPROVAL_LIMIT, DecideApprovalRequest},
+  approval_service,
+};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{
+    PathUuid, RequireRole, RequiredWorkspace, ValidatedQuery,
+    workspace::role::{Admin, Member},
+  },
+  modules::{auth::current_user::CurrentUser, datastores::workspaces::WorkspaceRole},
+  responses::ApiResponse,
+  state::AppState,
+  utils::db_session,
+};
+
+/// The request `approval_id` as seen by the caller: members only see the requests they made.
+async fn visible_request(state: &AppState, approval_id: Uuid, workspace_id: Uuid, user_id: Uuid, role: WorkspaceRole) -> AppResult<ApprovalRequest> {
+  state
+    .approval_repository
+    .get(approval_id, workspace_id)
+    .await?
+    .filter(|request| role == WorkspaceRole::Admin || request.requested_by == Some(user_id))
+    .ok_or_else(|| AppError::not_found_with_id("Approval request", approval_id))
+}
+
+fn already_decided(request: &ApprovalRequest) -> AppError {
+  AppError::Conflict(format!("Approval request {} is no longer pending", request.id))
+}
+
+/// Lists the approval requests of the workspace, newest first. Admins see every request,
+/// members only their own.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the requests listed.
+/// * `ValidatedQuery(query)`: The optional status to list and the number of requests.
+///
+/// # Returns
+///
+/// A `Json` response with the approval requests.
+pub async fn list_approvals(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  member: RequireRole<Member>,
+  ValidatedQuery(query): ValidatedQuery<ApprovalsQuery>,
+) -> AppResult<Json<ApiResponse<Vec<ApprovalRequest>>>> {
+  let requested_by = (member.role != WorkspaceRole::Admin).then_some(current_user.user_id);
+  let requests = state
+    .approval_repository
+    .list(workspace_id, query.status, requested_by, query.limit.unwrap_or(DEFAULT_APPROVAL_LIMIT))
+    .await?;
+
+  let response = ApiResponse::success(requests, "Approval requests retrieved successfully");
+  Ok(Json(response))
+}
+
+pub async fn get_approval(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  member: RequireRole<Member>,
+  PathUuid(approval_id): PathUuid,
+) -> AppResult<Json<ApiResponse<ApprovalRequest>>> {
+  let request = visible_request(&state, approval_id, workspace_id, current_user.user_id, member.role).await?;
+
+  let response = ApiResponse::success(request, "Approval request retrieved successfully");
+  Ok(Json(response))
+}
+
+/// Approves a pending request and applies its change as the approving admin. The decision and
+/// the change are written in one transaction: when the change fails, e.g. because the record
+/// was deleted meanwhile, the request stays pending and can still be rejected.
+///
+/// # Returns
+///
+/// A `Json` response with the approved request, or a 409 when the request is no longer pending.
+pub async fn approve(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  PathUuid(approval_id): PathUuid,
+  payload: Result<Json<DecideApprovalRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<ApprovalRequest>>> {
+  let Json(decision) = payload?;
+  decision.validate()?;
+  let request = visible_request(&state, approval_id, workspace_id, current_user.user_id, WorkspaceRole::Admin).await?;
+
+  let approved = db_session::transaction::<_, _, AppError>(&state.db, async {
+    let approved = state
+      .approval_repository
+      .decide(
+        approval_id,
+        workspace_id,
+        ApprovalStatus::Approved,
+        current_user.user_id,
+        decision.note.as_deref(),
+      )
+      .await?
+      .ok_or_else(|| already_decided(&request))?;
+    approval_service::apply(&state, &approved, current_user.clone()).await?;
+    Ok(approved)
+  })
+  .await?;
+
+  tracing::info!("Approval request approved: id={}, by={}", approved.id, current_user.user_id);
+  let response = ApiResponse::success(approved, "Approval request approved and applied");
+  Ok(Json(response))
+}
+
+/// Rejects a pending request, discarding its change.
+///
+/// # Returns
+///
+/// A `Json` response with the rejected request, or a 409 when the request is no longer pending.
+pub async fn reject(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  PathUuid(approval_id): PathUuid,
+  payload: Result<Json<DecideApprovalRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<ApprovalRequest>>> {
+  let Json(decision) = payload?;
+  decision.validate()?;
+  let request = visible_request(&state, approval_id, workspace_id, current_user.user_id, WorkspaceRole::Admin).await?;
+
+  let rejected = state
+    .approval_repository
+    .decide(
+      approval_id,
+      workspace_id,
+      ApprovalStatus::Rejected,
+      current_user.user_id,
+      decision.note.as_deref(),
+    )
+    .await?
+    .ok_or_else(|| already_decided(&request))?;
+
+  let response = ApiResponse::success(rejected, "Approval request rejected");
+  Ok(Json(response))
+}
+
+/// Withdraws a pending request. Only the member who made it can cancel it.
+///
+/// # Returns
+///
+/// A `Json` response with the cancelled request, or a 409 when the request is no longer pending.
+pub async fn cancel(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  member: RequireRole<Member>,
+  PathUuid(approval_id): PathUuid,
+) -> AppResult<Json<ApiResponse<ApprovalRequest>>> {
+  let request = visible_request(&state, approval_id, workspace_id, current_user.user_id, member.role).await?;
+  if request.requested_by != Some(current_user.user_id) {
+    return Err(AppError::Authorization(
+      "Only the member who asked for the change can cancel it".to_string(),
+    ));
+  }
+
+  let cancelled = state
+    .approval_repository
+    .decide(approval_id, workspace_id, ApprovalStatus::Cancelled, current_user.user_id, None)
+    .await?
+    .ok_or_else(|| already_decided(&request))?;
+
+  let response = ApiResponse::success(cancelled, "Approval request cancelled");
+  Ok(Json(response))
+}
@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Requests listed when no limit is given.
+pub const DEFAULT_APPROVAL_LIMIT: i64 = 50;
+
+/// Why a change waits for approval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "approval_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalAction {
+  PriceChange,
+  StockWriteOff,
+  Deletion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "approval_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+  Pending,
+  /// Accepted by an admin, and the change applied.
+  Approved,
+  Rejected,
+  /// Withdrawn by the member who asked.
+  Cancelled,
+}
+
+/// A change of a contact or product waiting for, or given, the decision of an admin.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ApprovalRequest {
+  pub id: Uuid,
+  pub workspace_id: Uuid,
+  pub action: ApprovalAction,
+  /// `contacts` or `products`.
+  pub entity: String,
+  pub record_id: Uuid,
+  /// The merge patch applied to the record once approved, `None` for deletions.
+  pub changes: Option<Value>,
+  /// What the change does, e.g. `selling_price 10 → 15 (+50%)`.
+  pub summary: String,
+  pub status: ApprovalStatus,
+  pub requested_by: Option<Uuid>,
+  pub decided_by: Option<Uuid>,
+  pub decided_at: Option<DateTime<Utc>>,
+  pub decision_note: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// A change held for approval, before it is recorded.
+#[derive(Debug, Clone)]
+pub struct HeldChange {
+  pub action: ApprovalAction,
+  pub entity: &'static str,
+  pub record_id: Uuid,
+  pub changes: Option<Value>,
+  pub summary: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ApprovalsQuery {
+  pub status: Option<ApprovalStatus>,
+  #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
+  pub limit: Option<i64>,
+}
+
+/// The decision on a request, with an optional note for the member who asked.
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct DecideApprovalRequest {
+  #[validate(length(max = 500, message = "Note cannot exceed 500 characters"))]
+  pub note: Option<String>,
+}
@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::approval_models::{ApprovalAction, ApprovalRequest, ApprovalStatus, HeldChange};
+use crate::{AppResult, utils::DbExecutor};
+
+#[async_trait]
+pub trait ApprovalRepository: Send + Sync {
+  /// Records a pending request for `change`.
+  async fn create(&self, workspace_id: Uuid, change: &HeldChange, requested_by: Uuid) -> AppResult<ApprovalRequest>;
+  /// The latest requests of the workspace, newest first, optionally of one status or requester.
+  async fn list(&self, workspace_id: Uuid, status: Option<ApprovalStatus>, requested_by: Option<Uuid>, limit: i64)
+  -> AppResult<Vec<ApprovalRequest>>;
+  async fn get(&self, id: Uuid, workspace_id: Uuid) -> AppResult<Option<ApprovalRequest>>;
+  /// Gives a pending request its final `status`, `None` when it is not pending (or does not exist).
+  async fn decide(
+    &self,
+    id: Uuid,
+    workspace_id: Uuid,
+    status: ApprovalStatus,
+    decided_by: Uuid,
+    note: Option<&str>,
+  ) -> AppResult<Option<ApprovalRequest>>;
+}
+
+pub struct PostgresApprovalRepository {
+  db: DbExecutor,
+}
+
+impl PostgresApprovalRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl ApprovalRepository for PostgresApprovalRepository {
+  async fn create(&self, workspace_id: Uuid, change: &HeldChange, requested_by: Uuid) -> AppResult<ApprovalRequest> {
+    let mut conn = self.db.acquire().await?;
+    let request = sqlx::query_as!(
+      ApprovalRequest,
+      r#"
+        INSERT INTO approval_requests (workspace_id, action, entity, record_id, changes, summary, requested_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, workspace_id, action as "action: ApprovalAction", entity, record_id, changes, summary,
+          status as "status: ApprovalStatus", requested_by, decided_by, decided_at, decision_note, created_at, updated_at
+        "#,
+      workspace_id,
+      change.action as ApprovalAction,
+      change.entity,
+      change.record_id,
+      change.changes,
+      change.summary,
+      requested_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(request)
+  }
+
+  async fn list(
+    &self,
+    workspace_id: Uuid,
+    status: Option<ApprovalStatus>,
+    requested_by: Option<Uuid>,
+    limit: i64,
+  ) -> AppResult<Vec<ApprovalRequest>> {
+    let mut conn = self.db.acquire().await?;
+    let requests = sqlx::query_as!(
+      ApprovalRequest,
+      r#"
+        SELECT id, workspace_id, action as "action: ApprovalAction", entity, record_id, changes, summary,
+          status as "status: ApprovalStatus", requested_by, decided_by, decided_at, decision_note, created_at, updated_at
+        FROM approval_requests
+        WHERE workspace_id = $1
+          AND ($2::approval_status IS NULL OR status = $2)
+          AND ($3::UUID IS NULL OR requested_by = $3)
+        ORDER BY created_at DESC
+        LIMIT $4
+        "#,
+      workspace_id,
+      status as Option<ApprovalStatus>,
+      requested_by,
+      limit
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(requests)
+  }
+
+  async fn get(&self, id: Uuid, workspace_id: Uuid) -> AppResult<Option<ApprovalRequest>> {
+    let mut conn = self.db.acquire().await?;
+    let request = sqlx::query_as!(
+      ApprovalRequest,
+      r#"
+        SELECT id, workspace_id, action as "action: ApprovalAction", entity, record_id, changes, summary,
+          status as "status: ApprovalStatus", requested_by, decided_by, decided_at, decision_note, created_at, updated_at
+        FROM approval_requests
+        WHERE id = $1 AND workspace_id = $2
+        "#,
+      id,
+      workspace_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(request)
+  }
+
+  async fn decide(
+    &self,
+    id: Uuid,
+    workspace_id: Uuid,
+    status: ApprovalStatus,
+    decided_by: Uuid,
+    note: Option<&str>,
+  ) -> AppResult<Option<ApprovalRequest>> {
+    let mut conn = self.db.acquire().await?;
+    let request = sqlx::query_as!(
+      ApprovalRequest,
+      r#"
+        UPDATE approval_requests
+        SET status = $3, decided_by = $4, decided_at = NOW(), decision_note = $5
+        WHERE id = $1 AND workspace_id = $2 AND status = 'pending'
+        RETURNING id, workspace_id, action as "action: ApprovalAction", entity, record_id, changes, summary,
+          status as "status: ApprovalStatus", requested_by, decided_by, decided_at, decision_note, created_at, updated_at
+        "#,
+      id,
+      workspace_id,
+      status as ApprovalStatus,
+      decided_by,
+      note
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(request)
+  }
+}
@@ -0,0 +1,19 @@
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  routing::{get, post},
+};
+
+use super::approval_handlers::{approve, cancel, get_approval, list_approvals, reject};
+use crate::state::AppState;
+
+/// The approval requests of the workspace, mounted at `/api/v1/approvals` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/", get(list_approvals))
+    .route("/:approval_id", get(get_approval))
+    .route("/:approval_id/approve", post(approve))
+    .route("/:approval_id/reject", post(reject))
+    .route("/:approval_id/cancel", post(cancel))
+}
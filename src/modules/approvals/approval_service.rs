@@ -0,0 +1,200 @@
+//! Which changes of members wait for approval, and applying them once an admin accepts them.
+
+use std::sync::Arc;
+
+#[cfg(feature = "products")]
+use rust_decimal::Decimal;
+#[cfg(feature = "products")]
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::approval_models::{ApprovalAction, ApprovalRequest, HeldChange};
+#[cfg(feature = "contacts")]
+use crate::modules::datastores::contacts::contact_handlers;
+#[cfg(feature = "products")]
+use crate::modules::datastores::products::{
+  product_handlers,
+  product_models::{Product, ProductPatchTarget},
+};
+#[cfg(feature = "products")]
+use crate::utils::merge_patch::{MergePatch, apply_merge_patch};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{PathUuid, RequireRole, RequiredWorkspace, workspace::role::Member},
+  internal_error,
+  modules::{auth::current_user::CurrentUser, datastores::workspaces::WorkspaceRole, workspace_settings::workspace_settings_models::ApprovalRules},
+  state::AppState,
+};
+use axum::extract::State;
+#[cfg(feature = "products")]
+use validator::Validate;
+
+/// The approval rules holding the changes of `role` in `workspace_id`, `None` for admins.
+async fn rules_for(state: &AppState, workspace_id: Uuid, role: WorkspaceRole) -> AppResult<Option<ApprovalRules>> {
+  if role == WorkspaceRole::Admin {
+    return Ok(None);
+  }
+  let rules = state.workspace_settings_repository.get(workspace_id).await?.approval_rules;
+  Ok((rules != ApprovalRules::default()).then_some(rules))
+}
+
+/// Records `change` as a pending request of `user_id`, and returns the `APPROVAL_REQUIRED` error
+/// handlers answer with instead of applying it.
+async fn hold(state: &AppState, workspace_id: Uuid, user_id: Uuid, change: HeldChange) -> AppError {
+  match state.approval_repository.create(workspace_id, &change, user_id).await {
+    Ok(request) => {
+      tracing::info!(
+        "Held {:?} of {} {} for approval: request={}",
+        change.action,
+        change.entity,
+        change.record_id,
+        request.id
+      );
+      AppError::ApprovalRequired(request.id)
+    }
+    Err(e) => e,
+  }
+}
+
+/// Holds a change of product `id`, given as a merge patch, when the rules of the workspace
+/// require an admin to accept it. Changes that would be rejected anyway are reported as such
+/// rather than held. Missing products are left for the handler to report.
+///
+/// # Returns
+///
+/// `Ok(())` when the change can be applied, or `AppError::ApprovalRequired` once held.
+#[cfg(feature = "products")]
+pub async fn hold_product_change(
+  state: &AppState,
+  workspace_id: Uuid,
+  role: WorkspaceRole,
+  user_id: Uuid,
+  id: Uuid,
+  changes: &Value,
+) -> AppResult<()> {
+  let Some(rules) = rules_for(state, workspace_id, role).await? else {
+    return Ok(());
+  };
+  if rules.price_change_percent.is_none() && !rules.stock_write_offs {
+    return Ok(());
+  }
+  let Some(current) = state.product_repository.find_by_id_and_workspace(id, workspace_id, user_id).await? else {
+    return Ok(());
+  };
+  let Some((action, summary)) = product_change(&rules, &current, changes) else {
+    return Ok(());
+  };
+  apply_merge_patch(&ProductPatchTarget::from(&current), changes)?.validate()?;
+
+  let change = HeldChange {
+    action,
+    entity: "products",
+    record_id: id,
+    changes: Some(changes.clone()),
+    summary,
+  };
+  Err(hold(state, workspace_id, user_id, change).await)
+}
+
+/// Why `changes` to `current` need approval under `rules`, with a summary of the held fields.
+/// A price change to or from zero always exceeds the percentage.
+#[cfg(feature = "products")]
+fn product_change(rules: &ApprovalRules, current: &Product, changes: &Value) -> Option<(ApprovalAction, String)> {
+  let mut action = None;
+  let mut summary = Vec::new();
+
+  let new_price = changes
+    .get("selling_price")
+    .and_then(|price| serde_json::from_value::<Decimal>(price.clone()).ok());
+  if let (Some(limit), Some(new_price)) = (rules.price_change_percent, new_price) {
+    let old_price = current.selling_price;
+    let percent = (!old_price.is_zero()).then(|| ((new_price - old_price) / old_price * Decimal::ONE_HUNDRED).round_dp(2));
+    if new_price != old_price && percent.is_none_or(|percent| percent.abs() > limit) {
+      action.get_or_insert(ApprovalAction::PriceChange);
+      let percent = percent.map(|percent| format!(" ({:+}%)", percent.normalize())).unwrap_or_default();
+      summary.push(format!("selling_price {} → {}{}", old_price.normalize(), new_price.normalize(), percent));
+    }
+  }
+
+  // Clearing the stock writes it off entirely
+  if let (true, Some(new_stock)) = (rules.stock_write_offs, changes.get("current_stock")) {
+    let old_stock = current.current_stock.unwrap_or(0);
+    let new_stock = new_stock.as_i64().unwrap_or(0);
+    if new_stock < i64::from(old_stock) {
+      action.get_or_insert(ApprovalAction::StockWriteOff);
+      summary.push(format!("current_stock {} → {}", old_stock, new_stock));
+    }
+  }
+
+  action.map(|action| (action, summary.join(", ")))
+}
+
+/// Holds the deletion of product `id` when the rules of the workspace require approval.
+#[cfg(feature = "products")]
+pub async fn hold_product_deletion(state: &AppState, workspace_id: Uuid, role: WorkspaceRole, user_id: Uuid, id: Uuid) -> AppResult<()> {
+  if !rules_for(state, workspace_id, role).await?.is_some_and(|rules| rules.deletions) {
+    return Ok(());
+  }
+  let Some(product) = state.product_repository.find_by_id_and_workspace(id, workspace_id, user_id).await? else {
+    return Ok(());
+  };
+  let change = HeldChange {
+    action: ApprovalAction::Deletion,
+    entity: "products",
+    record_id: id,
+    changes: None,
+    summary: format!("Delete product {} ({})", product.code, product.name),
+  };
+  Err(hold(state, workspace_id, user_id, change).await)
+}
+
+/// Holds the deletion of contact `id` when the rules of the workspace require approval.
+#[cfg(feature = "contacts")]
+pub async fn hold_contact_deletion(state: &AppState, workspace_id: Uuid, role: WorkspaceRole, user_id: Uuid, id: Uuid) -> AppResult<()> {
+  if !rules_for(state, workspace_id, role).await?.is_some_and(|rules| rules.deletions) {
+    return Ok(());
+  }
+  let Some(contact) = state.contact_repository.find_by_id_and_workspace(id, workspace_id, user_id).await? else {
+    return Ok(());
+  };
+  let change = HeldChange {
+    action: ApprovalAction::Deletion,
+    entity: "contacts",
+    record_id: id,
+    changes: None,
+    summary: format!("Delete contact {} ({})", contact.code, contact.name),
+  };
+  Err(hold(state, workspace_id, user_id, change).await)
+}
+
+/// Applies the change held by an approved `request` on behalf of `admin`, through the same
+/// handlers as the API, so it is validated and published as any other write.
+pub async fn apply(state: &Arc<AppState>, request: &ApprovalRequest, admin: CurrentUser) -> AppResult<()> {
+  let workspace = RequiredWorkspace(request.workspace_id);
+  let role = RequireRole::<Member>::check(WorkspaceRole::Admin)?;
+  let id = PathUuid(request.record_id);
+
+  match (request.entity.as_str(), &request.changes) {
+    #[cfg(feature = "products")]
+    ("products", Some(changes)) => {
+      let _ = product_handlers::patch(State(state.clone()), id, admin, workspace, role, MergePatch(changes.clone())).await?;
+    }
+    #[cfg(feature = "products")]
+    ("products", None) => {
+      let _ = product_handlers::delete(State(state.clone()), id, admin, workspace, role).await?;
+    }
+    #[cfg(feature = "contacts")]
+    ("contacts", None) => {
+      let _ = contact_handlers::delete(State(state.clone()), id, admin, workspace, role).await?;
+    }
+    _ => {
+      return Err(internal_error!(
+        "Approval request {} holds no change that can be applied to {}",
+        request.id,
+        request.entity
+      ));
+    }
+  }
+  Ok(())
+}
@@ -0,0 +1,14 @@
+//! Approval workflows: changes of members that an admin must accept before they are applied.
+//!
+//! The workspace settings choose the changes held (`ApprovalRules`): selling price changes above a
+//! percentage, lowering the stock of a product, and deleting contacts or products. When a member
+//! makes one, the handler records an approval request holding the change and responds
+//! `202 Accepted` with `APPROVAL_REQUIRED` instead of applying it. Admins are never held. Under
+//! `/api/v1/approvals` admins approve the request, which applies the change through the same
+//! handlers as the API, or reject it; the member who asked can cancel it while it is pending.
+
+pub mod approval_handlers;
+pub mod approval_models;
+pub mod approval_repository;
+pub mod approval_routes;
+pub mod approval_service;
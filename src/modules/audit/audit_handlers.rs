@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use axum::{extract::State, response::Json};
+use uuid::Uuid;
+
+use super::audit_models::{AuditEvent, AuditEventsQuery};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{PathUuid, ValidatedQuery},
+  modules::{auth::current_user::CurrentUser, datastores::workspaces::WorkspaceRole},
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// Events listed unless the query asks for another number.
+const DEFAULT_LIMIT: i64 = 50;
+
+/// The audit log is read by the admins of the workspace.
+async fn require_admin(state: &AppState, user_id: Uuid, workspace_id: Uuid) -> AppResult<()> {
+  let role = state.workspace_repository.check_user_workspace_access(user_id, workspace_id).await?;
+  match role {
+    Some(role) if role.includes(WorkspaceRole::Admin) => Ok(()),
+    _ => Err(AppError::Authorization("Only workspace admins can view the audit log".to_string())),
+  }
+}
+
+/// The most recent audit events of a workspace, newest first, optionally of one priority.
+pub async fn list_audit_events(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(workspace_id): PathUuid,
+  ValidatedQuery(query): ValidatedQuery<AuditEventsQuery>,
+) -> AppResult<Json<ApiResponse<Vec<AuditEvent>>>> {
+  require_admin(&state, current_user.user_id, workspace_id).await?;
+
+  let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+  let events = state.audit_repository.list(workspace_id, query.priority, limit).await?;
+  let response = ApiResponse::success(events, "Audit events retrieved successfully");
+  Ok(Json(response))
+}
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// How urgently admins should hear about an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "audit_priority", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditPriority {
+  /// Only kept in the log.
+  Normal,
+  /// Kept in the log and sent to the admins of the workspace.
+  High,
+}
+
+/// A change recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+  MemberAdded,
+  MemberRemoved,
+  RoleChanged,
+}
+
+impl AuditAction {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AuditAction::MemberAdded => "member.added",
+      AuditAction::MemberRemoved => "member.removed",
+      AuditAction::RoleChanged => "member.role_changed",
+    }
+  }
+
+  /// Changes to who can access a workspace, and how, are all high priority.
+  pub fn priority(&self) -> AuditPriority {
+    match self {
+      AuditAction::MemberAdded | AuditAction::MemberRemoved | AuditAction::RoleChanged => AuditPriority::High,
+    }
+  }
+
+  /// The title of the alert sent to admins.
+  pub fn title(&self) -> &'static str {
+    match self {
+      AuditAction::MemberAdded => "Member added",
+      AuditAction::MemberRemoved => "Member removed",
+      AuditAction::RoleChanged => "Member role changed",
+    }
+  }
+}
+
+/// An event to record, see `audit_service::record`.
+#[derive(Debug, Clone)]
+pub struct NewAuditEvent {
+  pub workspace_id: Uuid,
+  pub action: AuditAction,
+  pub actor_id: Uuid,
+  pub target_user_id: Option<Uuid>,
+  /// What changed, e.g. the previous and new role.
+  pub details: Value,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AuditEvent {
+  pub id: Uuid,
+  pub workspace_id: Uuid,
+  pub action: String,
+  pub priority: AuditPriority,
+  pub actor_id: Option<Uuid>,
+  pub target_user_id: Option<Uuid>,
+  pub details: Value,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Someone alerted of high priority events.
+#[derive(Debug, Clone, FromRow)]
+pub struct AuditRecipient {
+  pub user_id: Uuid,
+  pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AuditEventsQuery {
+  /// Only events of this priority.
+  pub priority: Option<AuditPriority>,
+  /// Number of events listed, 50 by default.
+  #[validate(range(min = 1, max = 500, message = "limit must be between 1 and 500"))]
+  pub limit: Option<i64>,
+}
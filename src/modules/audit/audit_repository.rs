@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::audit_models::{AuditEvent, AuditPriority, AuditRecipient, NewAuditEvent};
+use crate::{AppResult, modules::datastores::workspaces::WorkspaceRole, utils::DbExecutor};
+
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+  async fn record(&self, event: &NewAuditEvent) -> AppResult<AuditEvent>;
+  /// The most recent events of a workspace, newest first.
+  async fn list(&self, workspace_id: Uuid, priority: Option<AuditPriority>, limit: i64) -> AppResult<Vec<AuditEvent>>;
+  /// The admins of a workspace, who are alerted of its high priority events.
+  async fn admins(&self, workspace_id: Uuid) -> AppResult<Vec<AuditRecipient>>;
+}
+
+pub struct PostgresAuditRepository {
+  db: DbExecutor,
+}
+
+impl PostgresAuditRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl AuditRepository for PostgresAuditRepository {
+  async fn record(&self, event: &NewAuditEvent) -> AppResult<AuditEvent> {
+    let mut conn = self.db.acquire().await?;
+    let event = sqlx::query_as!(
+      AuditEvent,
+      r#"
+        INSERT INTO audit_events (workspace_id, action, priority, actor_id, target_user_id, details)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, workspace_id, action, priority as "priority: AuditPriority", actor_id, target_user_id, details, created_at
+        "#,
+      event.workspace_id,
+      event.action.as_str(),
+      event.action.priority() as AuditPriority,
+      event.actor_id,
+      event.target_user_id,
+      event.details
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(event)
+  }
+
+  async fn list(&self, workspace_id: Uuid, priority: Option<AuditPriority>, limit: i64) -> AppResult<Vec<AuditEvent>> {
+    let mut conn = self.db.acquire().await?;
+    let events = sqlx::query_as!(
+      AuditEvent,
+      r#"
+        SELECT id, workspace_id, action, priority as "priority: AuditPriority", actor_id, target_user_id, details, created_at
+        FROM audit_events
+        WHERE workspace_id = $1 AND ($2::audit_priority IS NULL OR priority = $2)
+        ORDER BY created_at DESC, id
+        LIMIT $3
+        "#,
+      workspace_id,
+      priority as Option<AuditPriority>,
+      limit
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(events)
+  }
+
+  async fn admins(&self, workspace_id: Uuid) -> AppResult<Vec<AuditRecipient>> {
+    let mut conn = self.db.acquire().await?;
+    let admins = sqlx::query_as!(
+      AuditRecipient,
+      r#"
+        SELECT u.id as user_id, u.email
+        FROM workspace_users wu
+        JOIN users u ON u.id = wu.user_id
+        WHERE wu.workspace_id = $1 AND wu.role = $2
+        ORDER BY wu.created_at
+        "#,
+      workspace_id,
+      WorkspaceRole::Admin as WorkspaceRole
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(admins)
+  }
+}
@@ -0,0 +1,95 @@
+//! Recording audit events and alerting admins of the high priority ones.
+//!
+//! Events are recorded after the change they describe succeeded, so a failure to record one is
+//! logged rather than returned: the caller's change is already made. Alert emails are sent on a
+//! spawned task so a slow mail server does not hold up the request.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use super::audit_models::{AuditAction, AuditEvent, AuditPriority, NewAuditEvent};
+use crate::{AppResult, events::WorkspaceEvent, mailer::EmailMessage, state::AppState};
+
+/// Records `event` and, when it is high priority, alerts the admins of its workspace.
+pub async fn record(state: &Arc<AppState>, event: NewAuditEvent) {
+  let recorded = match state.audit_repository.record(&event).await {
+    Ok(recorded) => recorded,
+    Err(e) => {
+      error!(
+        "Failed to record audit event {} in workspace {}: {}",
+        event.action.as_str(),
+        event.workspace_id,
+        e
+      );
+      return;
+    }
+  };
+
+  if recorded.priority == AuditPriority::High
+    && let Err(e) = alert_admins(state, &event, &recorded).await
+  {
+    warn!(
+      "Failed to alert the admins of workspace {} of audit event {}: {}",
+      event.workspace_id, recorded.id, e
+    );
+  }
+}
+
+/// Sends each admin of the workspace a real-time notification and an email about `recorded`.
+async fn alert_admins(state: &Arc<AppState>, event: &NewAuditEvent, recorded: &AuditEvent) -> AppResult<()> {
+  let admins = state.audit_repository.admins(event.workspace_id).await?;
+  let title = event.action.title();
+  let message = describe(state, event).await;
+
+  for admin in &admins {
+    state
+      .events
+      .publish(WorkspaceEvent::notification(event.workspace_id, Some(admin.user_id), title, &message));
+  }
+
+  let state = state.clone();
+  let (workspace_id, event_id) = (event.workspace_id, recorded.id);
+  tokio::spawn(async move {
+    for admin in admins {
+      let email = EmailMessage {
+        to: admin.email,
+        subject: format!("Security alert: {}", title.to_lowercase()),
+        body: format!(
+          "{}\n\nIf this change was not expected, review the members of the workspace and its audit log.\n\nWorkspace: {}\nEvent: {}\n",
+          message, workspace_id, event_id
+        ),
+      };
+      if let Err(e) = state.mailer.send(email).await {
+        warn!("Failed to email admin {} about audit event {}: {}", admin.user_id, event_id, e);
+      }
+    }
+  });
+  Ok(())
+}
+
+/// A one-line description of the change, naming the users involved.
+async fn describe(state: &AppState, event: &NewAuditEvent) -> String {
+  let actor = user_name(state, event.actor_id).await;
+  let target = match event.target_user_id {
+    Some(user_id) => user_name(state, user_id).await,
+    None => "a user".to_string(),
+  };
+  let role = |key: &str| event.details.get(key).and_then(Value::as_str).unwrap_or("unknown").to_lowercase();
+
+  match event.action {
+    AuditAction::MemberAdded => format!("{} added {} to the workspace as {}.", actor, target, role("role")),
+    AuditAction::MemberRemoved => format!("{} removed {} from the workspace.", actor, target),
+    AuditAction::RoleChanged => format!("{} changed the role of {} from {} to {}.", actor, target, role("from"), role("to")),
+  }
+}
+
+/// The username of a user, or their id when they cannot be loaded.
+async fn user_name(state: &AppState, user_id: Uuid) -> String {
+  match state.auth_repository.find_by_id(user_id).await {
+    Ok(Some(user)) => user.username,
+    _ => user_id.to_string(),
+  }
+}
@@ -0,0 +1,12 @@
+//! Audit log of security-relevant workspace changes.
+//!
+//! Handlers record an `AuditEvent` through `audit_service::record` after a change succeeds. High
+//! priority events, such as members added or removed and roles changed, also alert the admins of
+//! the workspace: each gets a real-time notification and an email. Admins list the log through
+//! `GET /api/v1/workspaces/{id}/audit-events`. Events are kept until an `audit_events` retention
+//! policy removes the old ones.
+
+pub mod audit_handlers;
+pub mod audit_models;
+pub mod audit_repository;
+pub mod audit_service;
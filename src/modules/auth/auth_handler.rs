@@ -1,42 +1,95 @@
 use std::sync::Arc;
 
-use axum::{Json, extract::State, http::StatusCode};
-use serde_json::{Value, json};
+use axum::{
+  Extension, Json,
+  extract::{State, rejection::JsonRejection},
+};
 
 use crate::{
-  errors::{AppError, AuthError},
+  errors::AppError,
   modules::auth::{
-    auth_service::{login_user, register_user},
+    auth_service::{Claims, login_user, register_user, request_password_reset, reset_password, switch_workspace},
     current_user::CurrentUser,
-    user_dto::{LoginUserDto, RegisterUserDto},
+    user_dto::{
+      CurrentUserResponse, ForgotPasswordDto, LoginResponse, LoginUserDto, RegisterResponse, RegisterUserDto, ResetPasswordDto, SwitchWorkspaceDto,
+      SwitchWorkspaceResponse, UserResponse,
+    },
   },
+  responses::{ApiResponse, Created},
   state::AppState,
 };
 
 pub async fn register_user_handler(
   State(state): State<Arc<AppState>>,
-  Json(body): Json<RegisterUserDto>,
-) -> Result<(StatusCode, Json<Value>), AppError> {
+  payload: Result<Json<RegisterUserDto>, JsonRejection>,
+) -> Result<Created<ApiResponse<RegisterResponse>>, AppError> {
+  let Json(body) = payload?;
   let (user, workspace) = register_user(state, body).await?;
-  let user_response = json!({"status": "success", "user": user, "workspace": workspace});
-  Ok((StatusCode::CREATED, Json(user_response)))
+  let response = RegisterResponse {
+    user: user.into(),
+    workspace,
+  };
+  Ok(ApiResponse::created(
+    response,
+    "User registered successfully",
+    "/api/v1/auth/me".to_string(),
+  ))
 }
 
-pub async fn login_user_handler(State(state): State<Arc<AppState>>, Json(body): Json<LoginUserDto>) -> Result<(StatusCode, Json<Value>), AppError> {
+pub async fn login_user_handler(
+  State(state): State<Arc<AppState>>,
+  payload: Result<Json<LoginUserDto>, JsonRejection>,
+) -> Result<Json<ApiResponse<LoginResponse>>, AppError> {
+  let Json(body) = payload?;
   let (token, user) = login_user(state.clone(), body).await?;
-  let workspace = state.clone().workspace_repository.get_user_workspaces(user.id).await?;
-  let token_response = json!({"status": "success", "token": token, "user": user, "workspace": workspace});
-  Ok((StatusCode::OK, Json(token_response)))
+  let workspaces = state.workspace_repository.get_user_workspaces(user.id).await?;
+  let response = LoginResponse {
+    token,
+    user: user.into(),
+    workspaces,
+  };
+  Ok(Json(ApiResponse::success(response, "Logged in successfully")))
+}
+
+/// Public endpoint emailing a link to reset the password of an account.
+///
+/// Responds the same whether or not the email is registered, so it cannot be used to find out
+/// which accounts exist.
+pub async fn forgot_password_handler(
+  State(state): State<Arc<AppState>>,
+  payload: Result<Json<ForgotPasswordDto>, JsonRejection>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+  let Json(body) = payload?;
+  request_password_reset(&state, body).await?;
+  Ok(Json(ApiResponse::success(
+    (),
+    "If an account uses this email, a link to reset its password has been sent",
+  )))
+}
+
+/// Public endpoint setting a new password with the token emailed by `forgot-password`.
+///
+/// Each token can be used once, until it expires (`PASSWORD_RESET_TOKEN_TTL_MINUTES`).
+pub async fn reset_password_handler(
+  State(state): State<Arc<AppState>>,
+  payload: Result<Json<ResetPasswordDto>, JsonRejection>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+  let Json(body) = payload?;
+  reset_password(&state, body).await?;
+  Ok(Json(ApiResponse::success((), "Password reset successfully")))
 }
 
 /// Protected endpoint that returns information about the current authenticated user.
 ///
 /// This handler demonstrates how to use the `CurrentUser` extractor to access
 /// the authenticated user's information in protected routes.
-/// 
+///
 /// Note: With RLS enabled, the workspace query will automatically be filtered
 /// based on the current session variables set by the JWT middleware.
-pub async fn get_current_user_handler(State(state): State<Arc<AppState>>, current_user: CurrentUser) -> Result<(StatusCode, Json<Value>), AppError> {
+pub async fn get_current_user_handler(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+) -> Result<Json<ApiResponse<CurrentUserResponse>>, AppError> {
   // Find the user in the database using the ID from the JWT token
   let user = state.auth_repository.find_by_id(current_user.user_id).await?;
 
@@ -44,12 +97,54 @@ pub async fn get_current_user_handler(State(state): State<Arc<AppState>>, curren
     // Get user default workspace - this query is now protected by RLS and will only return
     // the default workspace accessible to the current user based on session variables
     let workspace = state.workspace_repository.get_user_default_workspace(user.id).await?;
-    let response = json!({"status": "success", "user": user, "workspace": workspace});
-    Ok((StatusCode::OK, Json(response)))
+    let response = CurrentUserResponse {
+      user: UserResponse::from(user),
+      workspace,
+    };
+    Ok(Json(ApiResponse::success_default(response)))
   } else {
     // If JWT token is valid but user doesn't exist in database,
     // this indicates an invalid/expired token or data inconsistency
     // Return authentication error instead of not found error
-    Err(AppError::Authentication(AuthError::InvalidToken))
+    Err(AppError::invalid_token())
   }
 }
+
+/// Protected endpoint that revokes the access token used for the request.
+///
+/// The token is rejected by every instance from then on, although it has not expired yet.
+/// Tokens issued before revocation was introduced carry no `jti` and cannot be revoked.
+pub async fn logout_user_handler(
+  State(state): State<Arc<AppState>>,
+  Extension(claims): Extension<Claims>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+  let jti = claims
+    .jti
+    .ok_or_else(|| AppError::BadRequest("This token cannot be revoked; sign in again to get a revocable token".to_string()))?;
+  let expires_at = chrono::DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_else(chrono::Utc::now);
+
+  state.token_revocations.revoke(jti, expires_at).await?;
+
+  Ok(Json(ApiResponse::success((), "Logged out successfully")))
+}
+
+/// Protected endpoint that issues a new access token for another workspace of the caller.
+///
+/// When workspace claims are enabled (`WORKSPACE_CLAIM_TTL_SECS`), the token carries the
+/// workspace and the caller's role in it, and requests without an `X-Workspace-ID` header use
+/// that workspace. The current token stays valid. A 403 if the caller is not a member of the
+/// workspace.
+pub async fn switch_workspace_handler(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  payload: Result<Json<SwitchWorkspaceDto>, JsonRejection>,
+) -> Result<Json<ApiResponse<SwitchWorkspaceResponse>>, AppError> {
+  let Json(body) = payload?;
+  let (token, role) = switch_workspace(&state, current_user.user_id, body.workspace_id).await?;
+  let response = SwitchWorkspaceResponse {
+    token,
+    workspace_id: body.workspace_id,
+    role,
+  };
+  Ok(Json(ApiResponse::success(response, "Workspace switched successfully")))
+}
@@ -1,8 +1,8 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
 
 use crate::errors::AppError;
 use crate::modules::auth::user_model::User;
+use crate::utils::{DbExecutor, ReadPool};
 
 use super::user_dto::RegisterUserDto;
 
@@ -11,63 +11,96 @@ pub trait AuthRepository: Send + Sync {
   async fn find_by_email(&self, email: &str) -> Result<Option<User>, AppError>;
   async fn find_by_id(&self, user_id: uuid::Uuid) -> Result<Option<User>, AppError>;
   async fn create_user(&self, user_data: &RegisterUserDto, hashed_password: &str) -> Result<User, AppError>;
-  async fn get_db_size(&self) -> Result<i64, AppError>;
+  /// Grants or revokes the superadmin flag of the user with `email`; `None` if there is no such user.
+  async fn set_superadmin(&self, email: &str, is_superadmin: bool) -> Result<Option<User>, AppError>;
 }
 
 pub struct AuthRepositoryImpl {
-  pool: PgPool,
+  db: DbExecutor,
+  read_pool: ReadPool,
 }
 
 impl AuthRepositoryImpl {
-  pub fn new(pool: PgPool) -> Self {
-    Self { pool }
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    let db = db.into();
+    Self {
+      read_pool: ReadPool::primary_only(db.clone()),
+      db,
+    }
+  }
+
+  /// Runs the read-only queries (lookups, lists and counts) on `read_pool` instead of the primary.
+  pub fn with_read_pool(mut self, read_pool: ReadPool) -> Self {
+    self.read_pool = read_pool;
+    self
   }
 }
 
 #[async_trait]
 impl AuthRepository for AuthRepositoryImpl {
   async fn find_by_email(&self, email: &str) -> Result<Option<User>, AppError> {
+    let mut conn = self.db.acquire().await?;
     let user = sqlx::query_as!(
       User,
-      "SELECT id, username, email, password_hash, is_active, created_at, updated_at FROM users WHERE email = $1",
+      "SELECT id, username, email, password_hash, is_active, is_superadmin, created_at, updated_at FROM users WHERE lower(email) = lower($1)",
       email
     )
-    .fetch_optional(&self.pool)
+    .fetch_optional(&mut *conn)
     .await?;
 
     Ok(user)
   }
 
   async fn find_by_id(&self, user_id: uuid::Uuid) -> Result<Option<User>, AppError> {
+    let mut conn = self.db.acquire().await?;
     let user = sqlx::query_as!(
       User,
-      "SELECT id, username, email, password_hash, is_active, created_at, updated_at FROM users WHERE id = $1",
+      "SELECT id, username, email, password_hash, is_active, is_superadmin, created_at, updated_at FROM users WHERE id = $1",
       user_id
     )
-    .fetch_optional(&self.pool)
+    .fetch_optional(&mut *conn)
     .await?;
 
     Ok(user)
   }
 
   async fn create_user(&self, user_data: &RegisterUserDto, hashed_password: &str) -> Result<User, AppError> {
+    let mut conn = self.db.acquire().await?;
     let user = sqlx::query_as!(
             User,
-            "INSERT INTO users (username, email, password_hash) VALUES ($1, $2, $3) RETURNING id, username, email, password_hash, is_active, created_at, updated_at",
+            "INSERT INTO users (username, email, password_hash) VALUES ($1, $2, $3) RETURNING id, username, email, password_hash, is_active, is_superadmin, created_at, updated_at",
             user_data.username,
             user_data.email,
             hashed_password
         )
-        .fetch_one(&self.pool)
-        .await?;
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| match &e {
+          // Lost a race with a concurrent registration of the same email
+          sqlx::Error::Database(db_err) if db_err.constraint() == Some("users_email_lower_key") => {
+            AppError::Conflict("User with this email already exists".to_string())
+          }
+          _ => AppError::from(e),
+        })?;
 
     Ok(user)
   }
-  async fn get_db_size(&self) -> Result<i64, AppError> {
-    let size: i64 = sqlx::query_scalar("SELECT pg_database_size(current_database())")
-      .fetch_one(&self.pool)
-      .await?;
 
-    Ok(size)
+  async fn set_superadmin(&self, email: &str, is_superadmin: bool) -> Result<Option<User>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let user = sqlx::query_as!(
+      User,
+      r#"
+        UPDATE users SET is_superadmin = $2
+        WHERE lower(email) = lower($1)
+        RETURNING id, username, email, password_hash, is_active, is_superadmin, created_at, updated_at
+        "#,
+      email,
+      is_superadmin
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(user)
   }
 }
@@ -6,18 +6,30 @@ use axum::{
 };
 
 use crate::{
-  modules::auth::auth_handler::{get_current_user_handler, login_user_handler, register_user_handler},
+  modules::auth::auth_handler::{
+    forgot_password_handler, get_current_user_handler, login_user_handler, logout_user_handler, register_user_handler, reset_password_handler,
+    switch_workspace_handler,
+  },
+  modules::user_export::user_export_handlers::{download_user_export, get_user_export, request_user_export},
   state::AppState,
 };
 
-/// Returns public authentication routes (register and login)
+/// Returns public authentication routes (register, login and password reset)
 pub fn public_auth_routes() -> Router<Arc<AppState>> {
   Router::new()
     .route("/register", post(register_user_handler))
     .route("/login", post(login_user_handler))
+    .route("/forgot-password", post(forgot_password_handler))
+    .route("/reset-password", post(reset_password_handler))
 }
 
-/// Returns protected authentication routes (me endpoint)
+/// Returns protected authentication routes (me, logout, workspace switch and data export endpoints)
 pub fn protected_auth_routes() -> Router<Arc<AppState>> {
-  Router::new().route("/me", get(get_current_user_handler))
+  Router::new()
+    .route("/me", get(get_current_user_handler))
+    .route("/logout", post(logout_user_handler))
+    .route("/switch-workspace", post(switch_workspace_handler))
+    .route("/me/export", post(request_user_export))
+    .route("/me/exports/:id", get(get_user_export))
+    .route("/me/exports/:id/download", get(download_user_export))
 }
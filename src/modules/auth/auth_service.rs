@@ -2,9 +2,11 @@ use argon2::{
   Argon2,
   password_hash::{PasswordHasher, SaltString, rand_core::OsRng},
 };
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use jsonwebtoken::{EncodingKey, Header, encode};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tracing::info;
 use uuid::Uuid;
 use validator::Validate;
 
@@ -12,23 +14,59 @@ use crate::{
   errors::{AppError, AuthError},
   modules::{
     auth::{
-      user_dto::{LoginUserDto, RegisterUserDto},
+      email_domains::check_email_domain,
+      jwt_middleware::workspace_role,
+      user_dto::{ForgotPasswordDto, LoginUserDto, RegisterUserDto, ResetPasswordDto, normalize_email},
       user_model::User,
     },
-    datastores::workspaces::{Workspace, workspace_models::CreateWorkspaceRequest},
+    datastores::workspaces::{
+      Workspace,
+      workspace_models::{CreateWorkspaceRequest, WorkspaceRole},
+    },
+    notifications::notification_service,
   },
   state::AppState,
 };
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
   pub sub: Uuid,
   pub exp: usize,
   pub iat: usize,
+  /// Unique token id, used to revoke the token on logout. Absent from tokens issued before revocation existed.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub jti: Option<Uuid>,
+  /// The workspace the token was issued for and the user's role in it, see `WorkspaceClaim`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub workspace: Option<WorkspaceClaim>,
+}
+
+/// A workspace and role embedded in an access token at login or workspace switch, so
+/// `jwt_middleware` can skip the membership lookup. It is trusted until its own `exp`, which is
+/// much shorter than the token's, and the role is looked up again afterwards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceClaim {
+  pub id: Uuid,
+  pub role: WorkspaceRole,
+  pub exp: usize,
+}
+
+impl Claims {
+  /// The role the token vouches for in `workspace_id`, if it was issued for that workspace and
+  /// the claim has not expired.
+  pub fn workspace_role(&self, workspace_id: Uuid) -> Option<WorkspaceRole> {
+    self
+      .workspace
+      .as_ref()
+      .filter(|claim| claim.id == workspace_id && claim.exp > chrono::Utc::now().timestamp() as usize)
+      .map(|claim| claim.role)
+  }
 }
 
-pub async fn register_user(state: Arc<AppState>, user_data: RegisterUserDto) -> Result<(User, Workspace), AppError> {
+pub async fn register_user(state: Arc<AppState>, mut user_data: RegisterUserDto) -> Result<(User, Workspace), AppError> {
+  user_data.email = normalize_email(&user_data.email);
   user_data.validate()?;
+  check_email_domain(&state.config.registration, &user_data.email)?;
 
   if state.auth_repository.find_by_email(&user_data.email).await?.is_some() {
     return Err(AppError::Conflict("User with this email already exists".to_string()));
@@ -46,45 +84,124 @@ pub async fn register_user(state: Arc<AppState>, user_data: RegisterUserDto) ->
     description: Some("Default personal workspace.".to_string()),
   };
 
-  let workspace = state.workspace_repository.create_and_assign_owner(workspace_payload, user.id).await?;
+  let workspace = state
+    .workspace_repository
+    .create_and_assign_owner(workspace_payload, user.id, state.config.database.tenant_isolation)
+    .await?;
 
   Ok((user, workspace))
 }
 
-pub async fn login_user(state: Arc<AppState>, login_data: LoginUserDto) -> Result<(String, User), AppError> {
+pub async fn login_user(state: Arc<AppState>, mut login_data: LoginUserDto) -> Result<(String, User), AppError> {
+  login_data.email = normalize_email(&login_data.email);
   login_data.validate()?;
 
   let user = state
     .auth_repository
     .find_by_email(&login_data.email)
     .await?
-    .ok_or_else(|| AppError::Authentication(AuthError::InvalidCredentials))?;
+    .ok_or(AppError::invalid_credentials())?;
 
   let is_password_valid = argon2::PasswordHash::new(&user.password_hash)?
     .verify_password(&[&Argon2::default()], login_data.password.as_bytes())
     .is_ok();
 
-  let dbsize = state.auth_repository.get_db_size().await?;
-  // Limit for trial users is set to 100MB
-  let max_allowed = 100 * 1024 * 1024;
-
-  if dbsize > max_allowed {
-    return Err(AppError::database_size_exceeded(
-      "Trial users are limited to 100MB records. Upgrade to add more data.",
-    ));
-  }
-
   if !is_password_valid {
-    return Err(AppError::Authentication(AuthError::InvalidCredentials));
+    return Err(AppError::invalid_credentials());
   }
 
-  let now = chrono::Utc::now();
-  let iat = now.timestamp() as usize;
-  let exp = (now + chrono::Duration::hours(24)).timestamp() as usize;
+  let workspace = match login_data.workspace_id {
+    Some(workspace_id) => Some((workspace_id, member_role(&state, user.id, workspace_id).await?)),
+    None => None,
+  };
+  let token = issue_access_token(&state, user.id, workspace)?;
 
-  let claims = Claims { sub: user.id, exp, iat };
+  Ok((token, user))
+}
 
-  let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(state.jwt_secret.as_ref()))?;
+/// Issues a new access token for `user_id` carrying `workspace_id` as its workspace claim. The
+/// caller is already signed in, so switching to a workspace they are not a member of is forbidden.
+pub async fn switch_workspace(state: &AppState, user_id: Uuid, workspace_id: Uuid) -> Result<(String, WorkspaceRole), AppError> {
+  let role = workspace_role(state, user_id, workspace_id)
+    .await?
+    .ok_or_else(|| AppError::Authorization("Access denied to workspace".to_string()))?;
+  let token = issue_access_token(state, user_id, Some((workspace_id, role)))?;
+  Ok((token, role))
+}
 
-  Ok((token, user))
+/// Emails a password reset link to the active user registered with `request.email`, if any.
+/// Succeeds the same way whether or not there is one, so the endpoint cannot be used to find out
+/// which emails are registered. For the same reason, the email is sent in the background and a
+/// failure to send it is only logged.
+pub async fn request_password_reset(state: &AppState, mut request: ForgotPasswordDto) -> Result<(), AppError> {
+  request.email = normalize_email(&request.email);
+  request.validate()?;
+
+  let Some(user) = state.auth_repository.find_by_email(&request.email).await?.filter(|user| user.is_active) else {
+    return Ok(());
+  };
+
+  let token = URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>());
+  let ttl_minutes = state.config.password_reset.token_ttl_minutes;
+  let expires_at = chrono::Utc::now() + chrono::Duration::minutes(ttl_minutes.into());
+  state.password_reset_repository.create(user.id, &token, expires_at).await?;
+
+  notification_service::send_password_reset(state, &user, &token);
+  Ok(())
+}
+
+/// Sets a new password with a token emailed by `request_password_reset`. The access tokens issued
+/// before are no longer accepted, signing the user out everywhere.
+pub async fn reset_password(state: &AppState, request: ResetPasswordDto) -> Result<(), AppError> {
+  request.validate()?;
+
+  let salt = SaltString::generate(&mut OsRng);
+  let password_hash = Argon2::default().hash_password(request.password.as_bytes(), &salt)?.to_string();
+
+  let user_id = state
+    .password_reset_repository
+    .reset_password(&request.token, &password_hash)
+    .await?
+    .ok_or_else(|| AppError::validation("token", "The reset link is invalid or has expired"))?;
+  state.token_revocations.revoke_issued_before(user_id, chrono::Utc::now()).await?;
+  info!("User {} reset their password", user_id);
+  Ok(())
+}
+
+async fn member_role(state: &AppState, user_id: Uuid, workspace_id: Uuid) -> Result<WorkspaceRole, AppError> {
+  workspace_role(state, user_id, workspace_id)
+    .await?
+    .ok_or(AppError::Authentication(AuthError::InvalidWorkspace))
+}
+
+/// How long an access token is accepted after it was issued.
+pub const ACCESS_TOKEN_LIFETIME: chrono::TimeDelta = chrono::TimeDelta::hours(24);
+
+/// Signs a 24-hour access token. `workspace` is only embedded when workspace claims are enabled
+/// (`WORKSPACE_CLAIM_TTL_SECS`).
+fn issue_access_token(state: &AppState, user_id: Uuid, workspace: Option<(Uuid, WorkspaceRole)>) -> Result<String, AppError> {
+  let now = chrono::Utc::now();
+  let iat = now.timestamp() as usize;
+  let exp = (now + ACCESS_TOKEN_LIFETIME).timestamp() as usize;
+
+  let claim_ttl_secs = state.config.role_cache.claim_ttl_secs;
+  let workspace = workspace.filter(|_| claim_ttl_secs > 0).map(|(id, role)| WorkspaceClaim {
+    id,
+    role,
+    exp: iat.saturating_add(claim_ttl_secs as usize).min(exp),
+  });
+
+  let claims = Claims {
+    sub: user_id,
+    exp,
+    iat,
+    jti: Some(Uuid::new_v4()),
+    workspace,
+  };
+
+  Ok(encode(
+    &Header::default(),
+    &claims,
+    &EncodingKey::from_secret(state.jwt_keys.current().as_ref()),
+  )?)
 }
@@ -1,7 +1,7 @@
 use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
 use uuid::Uuid;
 
-use crate::errors::{AppError, AuthError};
+use crate::errors::AppError;
 
 /// Wrapper for User ID to distinguish from Workspace ID in request extensions
 #[derive(Debug, Clone, Copy)]
@@ -19,7 +19,7 @@ pub struct WorkspaceId(pub Uuid);
 ///
 /// # Example
 ///
-/// ```rust
+/// ```rust,ignore
 /// pub async fn protected_handler(
 ///     current_user: CurrentUser,
 /// ) -> Result<Json<String>, AppError> {
@@ -41,11 +41,7 @@ where
 
   async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
     // Extract the user ID from the request extensions using the typed wrapper
-    let user_id = parts
-      .extensions
-      .get::<UserId>()
-      .map(|uid| uid.0)
-      .ok_or_else(|| AppError::Authentication(AuthError::MissingToken))?;
+    let user_id = parts.extensions.get::<UserId>().map(|uid| uid.0).ok_or(AppError::missing_token())?;
 
     Ok(CurrentUser { user_id })
   }
@@ -0,0 +1,60 @@
+//! The email domains allowed to register, as configured in `RegistrationConfig`.
+
+use crate::{AppResult, config::RegistrationConfig, errors::AppError};
+
+/// Validation code of registrations refused because of their email domain.
+pub const EMAIL_DOMAIN_NOT_ALLOWED: &str = "EMAIL_DOMAIN_NOT_ALLOWED";
+
+/// Well-known disposable email providers, refused when `block_disposable_domains` is set.
+/// Operators can refuse others with `REGISTRATION_DENIED_DOMAINS`.
+pub const DISPOSABLE_DOMAINS: &[&str] = &[
+  "10minutemail.com",
+  "discard.email",
+  "dispostable.com",
+  "emailondeck.com",
+  "fakeinbox.com",
+  "getnada.com",
+  "guerrillamail.com",
+  "guerrillamail.net",
+  "maildrop.cc",
+  "mailinator.com",
+  "mailnesia.com",
+  "mintemail.com",
+  "mohmal.com",
+  "sharklasers.com",
+  "spamgourmet.com",
+  "temp-mail.org",
+  "tempmail.com",
+  "tempmailo.com",
+  "throwawaymail.com",
+  "trashmail.com",
+  "yopmail.com",
+];
+
+/// Whether `domain` is `parent` or one of its subdomains.
+fn is_within(domain: &str, parent: &str) -> bool {
+  domain == parent || domain.strip_suffix(parent).is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// Checks the domain of an already normalized (trimmed, lowercased) email against the allowlist,
+/// the denylist and, when enabled, the disposable providers.
+///
+/// Refusals are validation errors on the `email` field with the `EMAIL_DOMAIN_NOT_ALLOWED` code.
+pub fn check_email_domain(config: &RegistrationConfig, email: &str) -> AppResult<()> {
+  let Some((_, domain)) = email.rsplit_once('@') else {
+    // Malformed addresses are rejected by the email validation
+    return Ok(());
+  };
+  let refuse = |message: &str| Err(AppError::validation_with_code("email", message, EMAIL_DOMAIN_NOT_ALLOWED));
+
+  if !config.allowed_email_domains.is_empty() && !config.allowed_email_domains.iter().any(|allowed| is_within(domain, allowed)) {
+    return refuse("Registration is not open to this email domain");
+  }
+  if config.denied_email_domains.iter().any(|denied| is_within(domain, denied)) {
+    return refuse("Registration is not open to this email domain");
+  }
+  if config.block_disposable_domains && DISPOSABLE_DOMAINS.iter().any(|disposable| is_within(domain, disposable)) {
+    return refuse("Disposable email addresses cannot be used to register");
+  }
+  Ok(())
+}
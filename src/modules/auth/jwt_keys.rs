@@ -0,0 +1,47 @@
+use std::sync::RwLock;
+
+/// The secrets signing and verifying access tokens.
+///
+/// Tokens are signed with the current secret. A secret replaced by a rotation, either
+/// `JWT_PREVIOUS_SECRET` at startup or the current one when a refreshed secret differs (see
+/// `secrets`), is still accepted when verifying tokens, so tokens issued before keep working.
+pub struct JwtKeys {
+  keys: RwLock<Keys>,
+}
+
+struct Keys {
+  current: String,
+  previous: Option<String>,
+}
+
+impl JwtKeys {
+  pub fn new(current: impl Into<String>, previous: Option<String>) -> Self {
+    Self {
+      keys: RwLock::new(Keys {
+        current: current.into(),
+        previous,
+      }),
+    }
+  }
+
+  /// The secret new tokens are signed with.
+  pub fn current(&self) -> String {
+    self.keys.read().expect("JWT keys lock poisoned").current.clone()
+  }
+
+  /// The secret replaced by the last rotation.
+  pub fn previous(&self) -> Option<String> {
+    self.keys.read().expect("JWT keys lock poisoned").previous.clone()
+  }
+
+  /// Signs new tokens with `secret`, keeping the current secret to verify older ones. Returns
+  /// `false`, changing nothing, when `secret` already is the current one.
+  pub fn rotate(&self, secret: String) -> bool {
+    let mut keys = self.keys.write().expect("JWT keys lock poisoned");
+    if keys.current == secret {
+      return false;
+    }
+    keys.previous = Some(std::mem::replace(&mut keys.current, secret));
+    true
+  }
+}
@@ -1,11 +1,12 @@
 use axum::{
   extract::{Request, State},
-  http::{HeaderName, HeaderValue, header::AUTHORIZATION, Method},
+  http::{HeaderName, HeaderValue, Method, header::AUTHORIZATION},
   middleware::Next,
   response::Response,
 };
+use chrono::{DateTime, SubsecRound, Utc};
 use jsonwebtoken::{DecodingKey, Validation, decode};
-use std::sync::Arc;
+use std::{net::IpAddr, sync::Arc, time::Duration};
 use tracing::{debug, error};
 use uuid::Uuid;
 
@@ -13,106 +14,197 @@ use crate::modules::datastores::workspaces::workspace_models::WorkspaceRole;
 
 use crate::{
   errors::{AppError, AuthError},
-  modules::auth::{auth_service::Claims, current_user::{UserId, WorkspaceId}},
+  middleware::{access_log::record_caller, ip_allowlist::check_client_ip},
+  modules::auth::{
+    auth_service::Claims,
+    current_user::{UserId, WorkspaceId},
+  },
   state::AppState,
-  utils::PostgresSessionExt,
+  utils::{
+    db_resilience,
+    db_session::{self, SessionSettings},
+  },
 };
 
+/// Validates an access token and returns its claims, falling back to the pre-rotation secret
+/// while it is still configured. Shared by the HTTP middleware and the gRPC services.
+pub fn decode_access_token(state: &AppState, token: &str) -> Result<Claims, AppError> {
+  let validation = Validation::default();
+  let claims = decode::<Claims>(token, &DecodingKey::from_secret(state.jwt_keys.current().as_ref()), &validation)
+    .or_else(|e| match state.jwt_keys.previous() {
+      Some(previous) => decode::<Claims>(token, &DecodingKey::from_secret(previous.as_ref()), &validation),
+      None => Err(e),
+    })
+    .map_err(|e| {
+      error!("JWT validation failed: {}", e);
+      AppError::invalid_token()
+    })?
+    .claims;
+
+  Ok(claims)
+}
+
+/// Validates an access token and rejects it if it was revoked by a logout, issued before its
+/// user's password was reset or, with `SESSION_IDLE_TIMEOUT_SECS` set, left unused for longer than
+/// that. Each accepted use counts as activity of the session.
+pub async fn verify_access_token(state: &AppState, token: &str) -> Result<Claims, AppError> {
+  let claims = decode_access_token(state, token)?;
+
+  // `iat` only has whole seconds, so tokens issued in the second of a reset are still accepted
+  if let Some(valid_after) = state.token_revocations.revoked_before(claims.sub).await?
+    && timestamp(claims.iat) < valid_after.trunc_subsecs(0)
+  {
+    debug!("Rejected token of user {} issued before {}", claims.sub, valid_after);
+    return Err(AppError::invalid_token());
+  }
+
+  if let Some(jti) = claims.jti {
+    if state.token_revocations.is_revoked(jti).await? {
+      debug!("Rejected revoked token {}", jti);
+      return Err(AppError::invalid_token());
+    }
+
+    let idle_timeout_secs = state.config.sessions.idle_timeout_secs;
+    if idle_timeout_secs > 0 {
+      let issued_at = timestamp(claims.iat);
+      let expires_at = timestamp(claims.exp);
+      let active = state
+        .session_activity
+        .touch(jti, issued_at, expires_at, Duration::from_secs(idle_timeout_secs))
+        .await?;
+      if !active {
+        debug!("Rejected token {} of a session idle for over {}s", jti, idle_timeout_secs);
+        return Err(AppError::Authentication(AuthError::SessionIdleTimeout));
+      }
+    }
+  }
+
+  Ok(claims)
+}
+
+/// A JWT `iat` or `exp` claim as a point in time.
+fn timestamp(seconds: usize) -> DateTime<Utc> {
+  DateTime::from_timestamp(i64::try_from(seconds).unwrap_or(i64::MAX), 0).unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+/// The role of `user_id` in `workspace_id`, or `None` if they are not a member. Served from
+/// `state.role_cache` while a recent lookup is still fresh.
+pub async fn workspace_role(state: &AppState, user_id: Uuid, workspace_id: Uuid) -> Result<Option<WorkspaceRole>, AppError> {
+  if let Some(role) = state.role_cache.get(user_id, workspace_id) {
+    return Ok(Some(role));
+  }
+
+  let role = state.workspace_repository.check_user_workspace_access(user_id, workspace_id).await?;
+  if let Some(role) = role {
+    state.role_cache.insert(user_id, workspace_id, role);
+  }
+
+  Ok(role)
+}
+
+/// Authenticates a caller outside of the JWT middleware (gRPC calls, WebSocket and SSE
+/// connections): the token must be valid, its user must be a member of `workspace_id` and
+/// `client_ip` must be allowed by the workspace's IP allowlist. Returns the authenticated user's id
+/// and role in the workspace.
+pub async fn authenticate_workspace_member(
+  state: &AppState,
+  token: &str,
+  workspace_id: Uuid,
+  client_ip: Option<IpAddr>,
+) -> Result<(Uuid, WorkspaceRole), AppError> {
+  let claims = verify_access_token(state, token).await?;
+
+  let role = match claims.workspace_role(workspace_id) {
+    Some(role) => role,
+    None => workspace_role(state, claims.sub, workspace_id)
+      .await?
+      .ok_or(AppError::Authentication(AuthError::InvalidWorkspace))?,
+  };
+  check_client_ip(state, workspace_id, client_ip).await?;
+
+  Ok((claims.sub, role))
+}
+
 pub async fn jwt_middleware(State(state): State<Arc<AppState>>, mut request: Request, next: Next) -> Result<Response, AppError> {
   // Get token from Authorization header
   let auth_header = request
     .headers()
     .get(AUTHORIZATION)
     .and_then(|header| header.to_str().ok())
-    .ok_or(AppError::Authentication(AuthError::MissingToken))?;
+    .ok_or(AppError::missing_token())?;
 
   // Get workspace_id from header and parse as UUID
-  let workspace_id = request
+  let header_workspace_id = request
     .headers()
     .get("X-Workspace-ID")
     .and_then(|header| header.to_str().ok())
     .and_then(|s| Uuid::parse_str(s).ok());
 
   if !auth_header.starts_with("Bearer ") {
-    return Err(AppError::Authentication(AuthError::InvalidToken));
+    return Err(AppError::invalid_token());
   }
 
   let token = auth_header[7..].to_string();
 
   // Validate JWT token
-  let claims = decode::<Claims>(&token, &DecodingKey::from_secret(state.jwt_secret.as_ref()), &Validation::default())
-    .map_err(|e| {
-      error!("JWT validation failed: {}", e);
-      AppError::Authentication(AuthError::InvalidToken)
-    })?
-    .claims;
+  let claims = verify_access_token(&state, &token).await?;
 
   // Get user_id from claims
   let user_id = claims.sub;
 
+  // Without a header, requests run in the workspace the token was issued for, if any
+  let workspace_id = header_workspace_id.or(claims.workspace.as_ref().map(|claim| claim.id));
+
   // Check if this is an endpoint that doesn't require workspace validation
   let path = request.uri().path();
   let is_workspace_list_endpoint = path == "/api/v1/workspaces" && request.method() == Method::GET;
 
-  // Only validate workspace access if X-Workspace-ID is provided AND it's not the workspace list endpoint
-  if let Some(ws_id) = workspace_id {
-    if !is_workspace_list_endpoint {
-      // Check access and get role for workspace-specific operations
-      let role_access = sqlx::query!(
-        "SELECT role as \"role!: WorkspaceRole\" 
-         FROM workspace_users 
-         WHERE user_id = $1 AND workspace_id = $2",
-        user_id,
-        ws_id
-      )
-      .fetch_optional(&state.db)
-      .await
-      .map_err(|e| {
-        error!("Failed to verify workspace access: {}", e);
-        AppError::Internal("Database error while verifying workspace access".to_string())
-      })?;
-
-      match role_access {
-        Some(row) => {
-          // Add role to request extensions for route-level authorization
-          request.extensions_mut().insert(row.role);
-        }
-        None => return Err(AppError::Authentication(AuthError::InvalidWorkspace)),
-      }
+  // Only validate workspace access if X-Workspace-ID is provided AND it's not the workspace list endpoint.
+  // The role is loaded once here and reused for route-level authorization and the RLS session
+  let role = match workspace_id {
+    Some(ws_id) if !is_workspace_list_endpoint => {
+      // A fresh workspace claim in the token vouches for the role without a lookup
+      let role = match claims.workspace_role(ws_id) {
+        Some(role) => role,
+        None => workspace_role(&state, user_id, ws_id)
+          .await?
+          .ok_or(AppError::Authentication(AuthError::InvalidWorkspace))?,
+      };
+      // Add role to request extensions for route-level authorization
+      request.extensions_mut().insert(role);
+      Some(role)
     }
-  }
+    _ => None,
+  };
 
   // Set database session settings for RLS
   // For workspace list endpoint, always set session without workspace context to get all user's workspaces
-  let workspace_id_for_session = if is_workspace_list_endpoint { 
-    None 
-  } else { 
-    workspace_id.as_ref() 
+  let session = SessionSettings {
+    user_id,
+    workspace_id: if is_workspace_list_endpoint { None } else { workspace_id },
+    role,
   };
-  
-  if let Err(e) = state.db.set_session_settings(&user_id, workspace_id_for_session).await {
-    error!("Failed to set session settings: {}", e);
-    // Convert SQLx error to AppError properly
-    return Err(AppError::Internal(format!("Failed to set database session: {}", e)));
-  }
 
-  debug!("Session settings configured for user: {}, workspace: {:?}", user_id, workspace_id);
+  record_caller(user_id, workspace_id);
 
   // Add user to request using typed wrapper
   request.extensions_mut().insert(UserId(user_id));
+  request.extensions_mut().insert(claims);
 
   // Add workspace_id to request if present using typed wrapper
   if let Some(ws_id) = workspace_id {
     request.extensions_mut().insert(WorkspaceId(ws_id));
   }
 
-  // Process request
-  let mut response = next.run(request).await;
-
-  if let Err(e) = state.db.clear_session_settings().await {
-    error!("Failed to clear session settings: {}", e);
-    // Do not fail the request, just log the error.
-  }
+  // Process request on one connection carrying the session settings; they are cleared when it is released
+  let mut response = db_session::scope(&state.db, session, next.run(request)).await.map_err(|e| {
+    error!("Failed to set session settings: {}", e);
+    // An unreachable database is reported as such (503), not as an internal error
+    if db_resilience::is_connection_error(&e) {
+      return AppError::from(e);
+    }
+    AppError::Internal(format!("Failed to set database session: {}", e))
+  })?;
 
   // Add response headers
   response
@@ -3,6 +3,11 @@ pub mod auth_repository;
 pub mod auth_routes;
 pub mod auth_service;
 pub mod current_user;
+pub mod email_domains;
+pub mod jwt_keys;
 pub mod jwt_middleware;
+pub mod password_reset_repository;
+pub mod session_activity;
+pub mod token_revocation;
 pub mod user_dto;
 pub mod user_model;
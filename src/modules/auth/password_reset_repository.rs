@@ -0,0 +1,84 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{AppResult, utils::DbExecutor};
+
+/// Storage for the single-use tokens emailed to users who forgot their password.
+///
+/// Only a SHA-256 hash of each token is stored, so the table cannot be used to reset passwords.
+/// A user has at most one token at a time: issuing one replaces the previous.
+#[async_trait]
+pub trait PasswordResetRepository: Send + Sync {
+  /// Stores `token` for `user_id` until `expires_at`, replacing the user's previous token.
+  async fn create(&self, user_id: Uuid, token: &str, expires_at: DateTime<Utc>) -> AppResult<()>;
+
+  /// Uses `token` to set the password of its user to `password_hash`, in one statement, so a
+  /// token cannot be used twice.
+  ///
+  /// # Returns
+  ///
+  /// The user whose password was changed, or `None` if the token is unknown, used or expired,
+  /// or its user has been deactivated.
+  async fn reset_password(&self, token: &str, password_hash: &str) -> AppResult<Option<Uuid>>;
+}
+
+pub struct PostgresPasswordResetRepository {
+  db: DbExecutor,
+}
+
+impl PostgresPasswordResetRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl PasswordResetRepository for PostgresPasswordResetRepository {
+  async fn create(&self, user_id: Uuid, token: &str, expires_at: DateTime<Utc>) -> AppResult<()> {
+    let mut conn = self.db.acquire().await?;
+    // The previous token of the user, and expired tokens of anyone, are no longer needed
+    sqlx::query!("DELETE FROM password_reset_tokens WHERE user_id = $1 OR expires_at < NOW()", user_id)
+      .execute(&mut *conn)
+      .await?;
+
+    sqlx::query!(
+      r#"
+        INSERT INTO password_reset_tokens (user_id, token_hash, expires_at)
+        VALUES ($1, sha256(convert_to($2, 'UTF8')), $3)
+        "#,
+      user_id,
+      token,
+      expires_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn reset_password(&self, token: &str, password_hash: &str) -> AppResult<Option<Uuid>> {
+    let mut conn = self.db.acquire().await?;
+    let user_id = sqlx::query_scalar!(
+      r#"
+        WITH used AS (
+          UPDATE password_reset_tokens
+          SET used_at = NOW()
+          WHERE token_hash = sha256(convert_to($1, 'UTF8')) AND used_at IS NULL AND expires_at >= NOW()
+          RETURNING user_id
+        )
+        UPDATE users
+        SET password_hash = $2
+        FROM used
+        WHERE users.id = used.user_id AND users.is_active
+        RETURNING users.id
+        "#,
+      token,
+      password_hash
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(user_id)
+  }
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{AppResult, utils::DbExecutor};
+
+/// When each access token was last used, keyed by the token's `jti` claim, so sessions left idle
+/// for longer than `SESSION_IDLE_TIMEOUT_SECS` end before the token itself expires.
+///
+/// Like the revocation list, the Postgres store is shared by all instances using the same
+/// database; a Redis store is used instead when `REDIS_URL` is set.
+#[async_trait]
+pub trait SessionActivityStore: Send + Sync {
+  /// Records a use of the token and returns `true`, unless it was last used (or, if never,
+  /// issued) more than `idle_timeout` ago: then the session has ended, nothing is recorded and
+  /// `false` is returned.
+  async fn touch(&self, jti: Uuid, issued_at: DateTime<Utc>, expires_at: DateTime<Utc>, idle_timeout: Duration) -> AppResult<bool>;
+}
+
+pub struct PostgresSessionActivityStore {
+  db: DbExecutor,
+}
+
+impl PostgresSessionActivityStore {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl SessionActivityStore for PostgresSessionActivityStore {
+  async fn touch(&self, jti: Uuid, issued_at: DateTime<Utc>, expires_at: DateTime<Utc>, idle_timeout: Duration) -> AppResult<bool> {
+    let idle_since = Utc::now() - idle_timeout;
+    let mut conn = self.db.acquire().await?;
+    // The first use is only recorded if the token was issued recently enough, later uses only
+    // while the previous one is
+    let touched = sqlx::query_scalar!(
+      r#"
+        INSERT INTO session_activity (jti, expires_at)
+        SELECT $1::UUID, $2::TIMESTAMPTZ WHERE $3::TIMESTAMPTZ >= $4::TIMESTAMPTZ
+        ON CONFLICT (jti) DO UPDATE SET last_active_at = NOW()
+        WHERE session_activity.last_active_at >= $4::TIMESTAMPTZ
+        RETURNING (xmax = 0) as "first_use!"
+        "#,
+      jti,
+      expires_at,
+      issued_at,
+      idle_since
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    // Activity of expired tokens is no longer needed, cleaned up once per new session
+    if touched == Some(true) {
+      sqlx::query!("DELETE FROM session_activity WHERE expires_at < NOW()")
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(touched.is_some())
+  }
+}
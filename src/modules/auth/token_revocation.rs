@@ -0,0 +1,89 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{AppResult, utils::DbExecutor};
+
+/// Storage for access tokens revoked before their expiry, keyed by the token's `jti` claim, and
+/// for the revocation of all the tokens a user was issued before a point in time.
+///
+/// Entries only need to live until the token expires. The Postgres store is shared by all
+/// instances using the same database; a Redis store is used instead when `REDIS_URL` is set.
+#[async_trait]
+pub trait TokenRevocationStore: Send + Sync {
+  async fn revoke(&self, jti: Uuid, expires_at: DateTime<Utc>) -> AppResult<()>;
+  async fn is_revoked(&self, jti: Uuid) -> AppResult<bool>;
+  /// Revokes every token of `user_id` issued before `at`, such as by a password reset.
+  async fn revoke_issued_before(&self, user_id: Uuid, at: DateTime<Utc>) -> AppResult<()>;
+  /// The latest point in time the tokens of `user_id` were revoked up to, if any.
+  async fn revoked_before(&self, user_id: Uuid) -> AppResult<Option<DateTime<Utc>>>;
+}
+
+pub struct PostgresTokenRevocationStore {
+  db: DbExecutor,
+}
+
+impl PostgresTokenRevocationStore {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl TokenRevocationStore for PostgresTokenRevocationStore {
+  async fn revoke(&self, jti: Uuid, expires_at: DateTime<Utc>) -> AppResult<()> {
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!(
+      r#"
+        INSERT INTO revoked_tokens (jti, expires_at)
+        VALUES ($1, $2)
+        ON CONFLICT (jti) DO NOTHING
+        "#,
+      jti,
+      expires_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    // Revocations of expired tokens are no longer needed
+    sqlx::query!("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+      .execute(&mut *conn)
+      .await?;
+
+    Ok(())
+  }
+
+  async fn is_revoked(&self, jti: Uuid) -> AppResult<bool> {
+    let mut conn = self.db.acquire().await?;
+    let revoked = sqlx::query_scalar!(
+      r#"SELECT EXISTS(SELECT 1 FROM revoked_tokens WHERE jti = $1 AND expires_at >= NOW()) as "revoked!""#,
+      jti
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(revoked)
+  }
+  async fn revoke_issued_before(&self, user_id: Uuid, at: DateTime<Utc>) -> AppResult<()> {
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!(
+      "UPDATE users SET tokens_valid_after = GREATEST(tokens_valid_after, $2) WHERE id = $1",
+      user_id,
+      at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn revoked_before(&self, user_id: Uuid) -> AppResult<Option<DateTime<Utc>>> {
+    let mut conn = self.db.acquire().await?;
+    let revoked_before = sqlx::query_scalar!("SELECT tokens_valid_after FROM users WHERE id = $1", user_id)
+      .fetch_optional(&mut *conn)
+      .await?
+      .flatten();
+
+    Ok(revoked_before)
+  }
+}
@@ -1,7 +1,15 @@
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 use validator::Validate;
 
+use crate::modules::{
+  auth::user_model::User,
+  datastores::workspaces::workspace_models::{Workspace, WorkspaceRole, WorkspaceWithRole},
+};
+
 #[derive(Deserialize, Validate)]
+#[cfg_attr(feature = "client", derive(Serialize))]
 pub struct RegisterUserDto {
   #[validate(length(min = 3, message = "Username must be at least 3 characters long"))]
   pub username: String,
@@ -12,9 +20,103 @@ pub struct RegisterUserDto {
 }
 
 #[derive(Deserialize, Validate)]
+#[cfg_attr(feature = "client", derive(Serialize))]
 pub struct LoginUserDto {
   #[validate(email(message = "Invalid email format"))]
   pub email: String,
   #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
   pub password: String,
+  /// Workspace to embed in the token, so requests to it skip the membership lookup.
+  #[serde(default)]
+  pub workspace_id: Option<Uuid>,
+}
+
+#[derive(Deserialize, Validate)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+pub struct ForgotPasswordDto {
+  #[validate(email(message = "Invalid email format"))]
+  pub email: String,
+}
+
+/// The token emailed by `forgot-password` and the new password.
+#[derive(Deserialize, Validate)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+pub struct ResetPasswordDto {
+  #[validate(length(min = 1, message = "Token is required"))]
+  pub token: String,
+  #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
+  pub password: String,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+pub struct SwitchWorkspaceDto {
+  pub workspace_id: Uuid,
+}
+
+/// A user as returned by the auth endpoints; never carries the password hash.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct UserResponse {
+  pub id: Uuid,
+  pub username: String,
+  pub email: String,
+  pub is_active: bool,
+  pub is_superadmin: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+impl From<User> for UserResponse {
+  fn from(user: User) -> Self {
+    Self {
+      id: user.id,
+      username: user.username,
+      email: user.email,
+      is_active: user.is_active,
+      is_superadmin: user.is_superadmin,
+      created_at: user.created_at,
+      updated_at: user.updated_at,
+    }
+  }
+}
+
+/// The registered user and the personal workspace created for them.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct RegisterResponse {
+  pub user: UserResponse,
+  pub workspace: Workspace,
+}
+
+/// The access token, the user it was issued to and the workspaces they belong to.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct LoginResponse {
+  pub token: String,
+  pub user: UserResponse,
+  pub workspaces: Vec<WorkspaceWithRole>,
+}
+
+/// The authenticated user and their default workspace, if they have one.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct CurrentUserResponse {
+  pub user: UserResponse,
+  pub workspace: Option<WorkspaceWithRole>,
+}
+
+/// An access token for the workspace switched to, and the caller's role in it.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct SwitchWorkspaceResponse {
+  pub token: String,
+  pub workspace_id: Uuid,
+  pub role: WorkspaceRole,
+}
+
+/// Emails are compared case-insensitively, so they are stored and looked up trimmed and
+/// lowercased.
+pub fn normalize_email(email: &str) -> String {
+  email.trim().to_lowercase()
 }
@@ -1,16 +1,18 @@
 use chrono::{DateTime, Utc};
-use serde::Serialize;
 use sqlx::FromRow;
 use uuid::Uuid;
 
-#[derive(Debug, FromRow, Serialize)]
+/// A row of `users`. Deliberately not `Serialize`, as it carries the password hash: responses
+/// use `UserResponse`.
+#[derive(Debug, FromRow)]
 pub struct User {
   pub id: Uuid,
   pub username: String,
   pub email: String,
-  #[serde(skip_serializing)]
   pub password_hash: String,
   pub is_active: bool,
+  /// Administers the platform across workspaces (see `helper::RequireSuperadmin`).
+  pub is_superadmin: bool,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
 }
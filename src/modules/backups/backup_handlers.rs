@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::{
+  extract::{FromRequestParts, Query, State},
+  http::request::Parts,
+  response::Json,
+};
+
+use super::{
+  backup_models::{BackupListQuery, BackupListResponse, is_valid_environment},
+  backup_service::list_backups,
+};
+use crate::{
+  AppResult, errors::AppError, helper::superadmin::is_superadmin, internal_error, modules::auth::current_user::CurrentUser, responses::ApiResponse,
+  state::AppState,
+};
+
+/// Proof that the caller is a superadmin or one of the backup admins (`BACKUP_ADMIN_IDS`).
+pub struct BackupAdmin;
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for BackupAdmin {
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+    let current_user = CurrentUser::from_request_parts(parts, state).await?;
+    if state.config.backups.admin_user_ids.contains(&current_user.user_id) || is_superadmin(state, current_user.user_id).await? {
+      Ok(BackupAdmin)
+    } else {
+      Err(AppError::Authorization("Only backup admins can list backups".to_string()))
+    }
+  }
+}
+
+/// The backups available for an environment, newest first.
+pub async fn get_backups(
+  State(state): State<Arc<AppState>>,
+  _admin: BackupAdmin,
+  Query(query): Query<BackupListQuery>,
+) -> AppResult<Json<ApiResponse<BackupListResponse>>> {
+  let config = &state.config.backups;
+  let environment = query.environment.unwrap_or_else(|| config.environment.clone());
+  if !is_valid_environment(&environment) {
+    return Err(AppError::BadRequest(format!("Invalid environment: {}", environment)));
+  }
+  let store = state
+    .backup_store
+    .as_ref()
+    .ok_or_else(|| internal_error!("Backups are not configured: BACKUP_S3_BUCKET and its access keys must be set"))?;
+
+  let backups = list_backups(config, store.as_ref(), &environment).await?;
+  let response = ApiResponse::success(BackupListResponse { environment, backups }, "Backups retrieved successfully");
+  Ok(Json(response))
+}
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Extension of the `pg_dump` custom format archives the backups are stored as.
+pub const BACKUP_EXTENSION: &str = ".dump";
+
+/// A backup in the store.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BackupObject {
+  /// Full key in the bucket, e.g. `backups/production/20251028T020000Z.dump`.
+  pub key: String,
+  pub size_bytes: u64,
+  pub created_at: DateTime<Utc>,
+}
+
+impl BackupObject {
+  /// The last segment of the key, which the `restore` subcommand accepts as well.
+  pub fn name(&self) -> &str {
+    self.key.rsplit('/').next().unwrap_or(&self.key)
+  }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackupListQuery {
+  /// Environment to list, the server's own by default.
+  pub environment: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupListResponse {
+  pub environment: String,
+  /// Newest first.
+  pub backups: Vec<BackupObject>,
+}
+
+/// Environment names end up in object keys, so they are limited to lowercase letters, digits,
+/// `-` and `_`.
+pub fn is_valid_environment(environment: &str) -> bool {
+  !environment.is_empty()
+    && environment.len() <= 64
+    && environment
+      .bytes()
+      .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_')
+}
+
+/// The key prefix of the backups of `environment`, ending with a `/`.
+pub fn environment_prefix(prefix: &str, environment: &str) -> String {
+  if prefix.is_empty() {
+    format!("{}/", environment)
+  } else {
+    format!("{}/{}/", prefix, environment)
+  }
+}
+
+/// The key of a backup of `environment` taken at `now`. Keys sort in the order backups were taken.
+pub fn backup_key(prefix: &str, environment: &str, now: DateTime<Utc>) -> String {
+  format!(
+    "{}{}{}",
+    environment_prefix(prefix, environment),
+    now.format("%Y%m%dT%H%M%SZ"),
+    BACKUP_EXTENSION
+  )
+}
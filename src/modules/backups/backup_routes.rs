@@ -0,0 +1,11 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+
+use super::backup_handlers::get_backups;
+use crate::state::AppState;
+
+/// Backup listing for the backup admins, mounted at `/api/v1/admin/backups` behind the JWT middleware.
+pub fn admin_router() -> Router<Arc<AppState>> {
+  Router::new().route("/", get(get_backups))
+}
@@ -0,0 +1,175 @@
+//! Taking, rotating, listing and restoring backups, and the scheduler running them.
+
+use std::{
+  path::{Path, PathBuf},
+  sync::Arc,
+  time::Duration,
+};
+
+use chrono::Utc;
+use tokio::process::Command;
+use uuid::Uuid;
+
+use super::{
+  backup_models::{BACKUP_EXTENSION, BackupObject, backup_key, environment_prefix},
+  backup_store::BackupStore,
+};
+use crate::{AppResult, config::BackupConfig, internal_error, state::AppState};
+
+/// A scratch file for a dump, removed when dropped.
+struct DumpFile(PathBuf);
+
+impl DumpFile {
+  fn new() -> Self {
+    Self(std::env::temp_dir().join(format!("myapp-backup-{}{}", Uuid::new_v4(), BACKUP_EXTENSION)))
+  }
+}
+
+impl Drop for DumpFile {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_file(&self.0);
+  }
+}
+
+fn database_url(config: &BackupConfig) -> AppResult<&str> {
+  config
+    .database_url
+    .as_deref()
+    .ok_or_else(|| internal_error!("No database to back up: BACKUP_DATABASE_URL or DATABASE_URL must be set"))
+}
+
+/// Runs one of the PostgreSQL client tools, failing with its stderr when it exits unsuccessfully.
+async fn run_tool(program: &str, args: &[&str]) -> AppResult<()> {
+  let output = Command::new(program)
+    .args(args)
+    .output()
+    .await
+    .map_err(|e| internal_error!("Failed to run {}: {}", program, e))?;
+  if output.status.success() {
+    Ok(())
+  } else {
+    Err(internal_error!(
+      "{} exited with {}: {}",
+      program,
+      output.status,
+      String::from_utf8_lossy(&output.stderr).trim()
+    ))
+  }
+}
+
+/// The backups of `environment`, newest first.
+pub async fn list_backups(config: &BackupConfig, store: &dyn BackupStore, environment: &str) -> AppResult<Vec<BackupObject>> {
+  let mut backups: Vec<BackupObject> = store
+    .list(&environment_prefix(&config.s3_prefix, environment))
+    .await?
+    .into_iter()
+    .filter(|object| object.key.ends_with(BACKUP_EXTENSION))
+    .collect();
+  backups.sort_by(|a, b| b.key.cmp(&a.key));
+  Ok(backups)
+}
+
+/// Deletes the backups of this environment past the newest `config.keep` and returns their keys.
+pub async fn rotate_backups(config: &BackupConfig, store: &dyn BackupStore) -> AppResult<Vec<String>> {
+  let backups = list_backups(config, store, &config.environment).await?;
+  let mut deleted = Vec::new();
+  for backup in backups.into_iter().skip(config.keep) {
+    store.delete(&backup.key).await?;
+    deleted.push(backup.key);
+  }
+  Ok(deleted)
+}
+
+/// Dumps the database with `pg_dump`, uploads the dump and rotates the backups of this environment.
+pub async fn run_backup(config: &BackupConfig, store: &dyn BackupStore) -> AppResult<BackupObject> {
+  let dump = DumpFile::new();
+  let path = dump.0.to_string_lossy();
+  run_tool(
+    &config.pg_dump,
+    &[
+      "--format=custom",
+      "--no-owner",
+      "--no-privileges",
+      "--file",
+      &path,
+      "--dbname",
+      database_url(config)?,
+    ],
+  )
+  .await?;
+
+  let created_at = Utc::now();
+  let key = backup_key(&config.s3_prefix, &config.environment, created_at);
+  let size_bytes = store.upload(&key, &dump.0).await?;
+  let deleted = rotate_backups(config, store).await?;
+  tracing::info!("Backup {} uploaded ({} bytes), {} old backups deleted", key, size_bytes, deleted.len());
+
+  Ok(BackupObject { key, size_bytes, created_at })
+}
+
+/// Finds a backup of `environment` by key or name, or the newest one for `latest`.
+pub async fn find_backup(config: &BackupConfig, store: &dyn BackupStore, environment: &str, backup: &str) -> AppResult<BackupObject> {
+  let backups = list_backups(config, store, environment).await?;
+  let found = if backup == "latest" {
+    backups.into_iter().next()
+  } else {
+    backups.into_iter().find(|object| object.key == backup || object.name() == backup)
+  };
+  found.ok_or_else(|| internal_error!("No backup {} found for environment {}", backup, environment))
+}
+
+/// Replaces the contents of the database at `database_url` with `backup`.
+///
+/// Objects in the backup are dropped and recreated in one transaction, so a failed restore leaves
+/// the database as it was. Tables created after the backup was taken are left in place.
+pub async fn restore_backup(config: &BackupConfig, store: &dyn BackupStore, backup: &BackupObject, database_url: &str) -> AppResult<()> {
+  let dump = DumpFile::new();
+  store.download(&backup.key, &dump.0).await?;
+  let path: &Path = &dump.0;
+  run_tool(
+    &config.pg_restore,
+    &[
+      "--clean",
+      "--if-exists",
+      "--no-owner",
+      "--no-privileges",
+      "--single-transaction",
+      "--dbname",
+      database_url,
+      &path.to_string_lossy(),
+    ],
+  )
+  .await
+}
+
+/// Takes a backup every `BACKUP_INTERVAL_SECS` while a backup store is configured.
+///
+/// The schedule is kept by the age of the newest backup in the store rather than by the uptime of
+/// the server, so restarts do not delay backups and several instances rarely take one each.
+pub async fn run_scheduler(state: Arc<AppState>) {
+  let Some(store) = state.backup_store.clone() else {
+    return;
+  };
+  let config = &state.config.backups;
+  let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs.min(3600)));
+  loop {
+    interval.tick().await;
+    let due = match list_backups(config, store.as_ref(), &config.environment).await {
+      Ok(backups) => backups
+        .first()
+        .is_none_or(|newest| (Utc::now() - newest.created_at).num_seconds() >= config.interval_secs as i64),
+      Err(e) => {
+        tracing::error!("Failed to list backups: {}", e);
+        state.task_health.failed("backup_scheduler", e);
+        continue;
+      }
+    };
+    if due {
+      let result = run_backup(config, store.as_ref()).await;
+      state.task_health.record("backup_scheduler", &result);
+      if let Err(e) = result {
+        tracing::error!("Scheduled backup failed: {}", e);
+      }
+    }
+  }
+}
@@ -0,0 +1,255 @@
+//! Where backups are kept: the `BackupStore` trait and its S3 implementation.
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Method, Url};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+
+use super::backup_models::BackupObject;
+use crate::{AppResult, config::BackupConfig, internal_error};
+
+/// The object store backups are uploaded to. `S3Store` talks to S3; tests inject another
+/// implementation with `AppStateBuilder::with_backup_store`.
+#[async_trait]
+pub trait BackupStore: Send + Sync {
+  /// Uploads the file at `path` as `key` and returns its size.
+  async fn upload(&self, key: &str, path: &Path) -> AppResult<u64>;
+  /// Downloads `key` into the file at `path`.
+  async fn download(&self, key: &str, path: &Path) -> AppResult<()>;
+  /// Lists the objects whose key starts with `prefix`, in any order.
+  async fn list(&self, prefix: &str) -> AppResult<Vec<BackupObject>>;
+  async fn delete(&self, key: &str) -> AppResult<()>;
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Objects are uploaded without signing their content, which would require hashing the whole dump first.
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// An S3 bucket, or a bucket of an S3 compatible store, addressed path-style and signed with
+/// AWS Signature Version 4.
+pub struct S3Store {
+  http: reqwest::Client,
+  endpoint: Url,
+  bucket: String,
+  region: String,
+  access_key_id: String,
+  secret_access_key: String,
+}
+
+impl S3Store {
+  /// A store for the configured bucket, `None` until the bucket and both keys are set.
+  pub fn from_config(config: &BackupConfig) -> Option<Self> {
+    let endpoint = config
+      .s3_endpoint
+      .clone()
+      .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", config.s3_region));
+    let endpoint = match Url::parse(&endpoint) {
+      Ok(endpoint) => endpoint,
+      Err(e) => {
+        tracing::error!("Ignoring invalid BACKUP_S3_ENDPOINT {}: {}", endpoint, e);
+        return None;
+      }
+    };
+
+    Some(Self {
+      http: reqwest::Client::new(),
+      endpoint,
+      bucket: config.s3_bucket.clone()?,
+      region: config.s3_region.clone(),
+      access_key_id: config.s3_access_key_id.clone()?,
+      secret_access_key: config.s3_secret_access_key.clone()?,
+    })
+  }
+
+  /// A signed request for `key` in the bucket, or for the bucket itself when `key` is `None`.
+  fn request(&self, method: Method, key: Option<&str>, query: &[(&str, &str)]) -> reqwest::RequestBuilder {
+    let path = match key {
+      Some(key) => format!("/{}/{}", uri_encode(&self.bucket, false), uri_encode(key, true)),
+      None => format!("/{}", uri_encode(&self.bucket, false)),
+    };
+    let mut params: Vec<String> = query
+      .iter()
+      .map(|(name, value)| format!("{}={}", uri_encode(name, false), uri_encode(value, false)))
+      .collect();
+    params.sort();
+    let query = params.join("&");
+
+    let host = match self.endpoint.port() {
+      Some(port) => format!("{}:{}", self.endpoint.host_str().unwrap_or_default(), port),
+      None => self.endpoint.host_str().unwrap_or_default().to_string(),
+    };
+    let now = Utc::now();
+    let authorization = self.authorization(method.as_str(), &path, &query, &host, now);
+
+    let origin = self.endpoint.origin().ascii_serialization();
+    let url = if query.is_empty() {
+      format!("{}{}", origin, path)
+    } else {
+      format!("{}{}?{}", origin, path, query)
+    };
+    self
+      .http
+      .request(method, url)
+      .header("x-amz-content-sha256", UNSIGNED_PAYLOAD)
+      .header("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string())
+      .header(reqwest::header::AUTHORIZATION, authorization)
+  }
+
+  /// The `Authorization` header of a request signed at `now`.
+  fn authorization(&self, method: &str, path: &str, query: &str, host: &str, now: DateTime<Utc>) -> String {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+      "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+      method, path, query, host, UNSIGNED_PAYLOAD, amz_date, signed_headers, UNSIGNED_PAYLOAD
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+    let string_to_sign = format!(
+      "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+      amz_date,
+      scope,
+      hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = [date.as_str(), self.region.as_str(), "s3", "aws4_request"]
+      .iter()
+      .fold(format!("AWS4{}", self.secret_access_key).into_bytes(), |key, part| hmac(&key, part));
+    format!(
+      "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+      self.access_key_id,
+      scope,
+      signed_headers,
+      hex::encode(hmac(&key, &string_to_sign))
+    )
+  }
+
+  /// Sends `request`, failing unless S3 responds with a success status.
+  async fn send(request: reqwest::RequestBuilder, action: &str) -> AppResult<reqwest::Response> {
+    let response = request.send().await.map_err(|e| internal_error!("S3 {} failed: {}", action, e))?;
+    let status = response.status();
+    if !status.is_success() {
+      let body = response.text().await.unwrap_or_default();
+      return Err(internal_error!("S3 responded {} to {}: {}", status, action, body));
+    }
+    Ok(response)
+  }
+}
+
+#[async_trait]
+impl BackupStore for S3Store {
+  async fn upload(&self, key: &str, path: &Path) -> AppResult<u64> {
+    let body = tokio::fs::read(path)
+      .await
+      .map_err(|e| internal_error!("Failed to read {}: {}", path.display(), e))?;
+    let size = body.len() as u64;
+    Self::send(self.request(Method::PUT, Some(key), &[]).body(body), &format!("upload of {}", key)).await?;
+    Ok(size)
+  }
+
+  async fn download(&self, key: &str, path: &Path) -> AppResult<()> {
+    let mut response = Self::send(self.request(Method::GET, Some(key), &[]), &format!("download of {}", key)).await?;
+    let mut file = tokio::fs::File::create(path)
+      .await
+      .map_err(|e| internal_error!("Failed to create {}: {}", path.display(), e))?;
+    while let Some(chunk) = response
+      .chunk()
+      .await
+      .map_err(|e| internal_error!("S3 download of {} failed: {}", key, e))?
+    {
+      file
+        .write_all(&chunk)
+        .await
+        .map_err(|e| internal_error!("Failed to write {}: {}", path.display(), e))?;
+    }
+    file
+      .flush()
+      .await
+      .map_err(|e| internal_error!("Failed to write {}: {}", path.display(), e))
+  }
+
+  async fn list(&self, prefix: &str) -> AppResult<Vec<BackupObject>> {
+    let mut objects = Vec::new();
+    let mut continuation_token: Option<String> = None;
+    loop {
+      let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+      if let Some(token) = &continuation_token {
+        query.push(("continuation-token", token));
+      }
+      let response = Self::send(self.request(Method::GET, None, &query), "listing").await?;
+      let body = response.text().await.map_err(|e| internal_error!("S3 listing failed: {}", e))?;
+
+      for contents in elements(&body, "Contents") {
+        let field = |name| elements(contents, name).next().map(xml_unescape);
+        let (Some(key), Some(size), Some(last_modified)) = (field("Key"), field("Size"), field("LastModified")) else {
+          continue;
+        };
+        objects.push(BackupObject {
+          key,
+          size_bytes: size.parse().unwrap_or_default(),
+          created_at: DateTime::parse_from_rfc3339(&last_modified)
+            .map_err(|e| internal_error!("Invalid LastModified {} in S3 listing: {}", last_modified, e))?
+            .with_timezone(&Utc),
+        });
+      }
+
+      continuation_token = elements(&body, "NextContinuationToken").next().map(xml_unescape);
+      if continuation_token.is_none() {
+        return Ok(objects);
+      }
+    }
+  }
+
+  async fn delete(&self, key: &str) -> AppResult<()> {
+    Self::send(self.request(Method::DELETE, Some(key), &[]), &format!("deletion of {}", key)).await?;
+    Ok(())
+  }
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+  let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+  mac.update(data.as_bytes());
+  mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encodes everything but the unreserved characters, and `/` when `keep_slash` is set,
+/// as Signature Version 4 requires.
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+  let mut encoded = String::with_capacity(value.len());
+  for byte in value.bytes() {
+    match byte {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+      b'/' if keep_slash => encoded.push('/'),
+      _ => encoded.push_str(&format!("%{:02X}", byte)),
+    }
+  }
+  encoded
+}
+
+/// The contents of the `<name>` elements of an XML document, which is all the listing needs.
+fn elements<'a>(xml: &'a str, name: &str) -> impl Iterator<Item = &'a str> {
+  let open = format!("<{}>", name);
+  let close = format!("</{}>", name);
+  let mut rest = xml;
+  std::iter::from_fn(move || {
+    let start = rest.find(&open)? + open.len();
+    let end = start + rest[start..].find(&close)?;
+    let contents = &rest[start..end];
+    rest = &rest[end + close.len()..];
+    Some(contents)
+  })
+}
+
+fn xml_unescape(value: &str) -> String {
+  value
+    .replace("&lt;", "<")
+    .replace("&gt;", ">")
+    .replace("&quot;", "\"")
+    .replace("&apos;", "'")
+    .replace("&amp;", "&")
+}
@@ -0,0 +1,13 @@
+//! Database backups, compiled with the `backups` feature.
+//!
+//! The server takes a `pg_dump` of its database every `BACKUP_INTERVAL_SECS` and uploads it to
+//! `{BACKUP_S3_PREFIX}/{BACKUP_ENVIRONMENT}/` in the backup bucket, keeping the newest
+//! `BACKUP_KEEP`. The bucket is the record of which backups exist, so several environments can
+//! share it and a database can be restored from the backups of another environment with the
+//! `restore` subcommand. Backup admins (`BACKUP_ADMIN_IDS`) list them under `/api/v1/admin/backups`.
+
+pub mod backup_handlers;
+pub mod backup_models;
+pub mod backup_routes;
+pub mod backup_service;
+pub mod backup_store;
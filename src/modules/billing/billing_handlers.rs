@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use axum::{body::Bytes, extract::State, http::HeaderMap, response::Json};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use super::{
+  billing_models::{
+    CheckoutSessionResponse, PortalSessionResponse, StripeCheckoutSession, StripeEvent, StripeSubscription, SubscriptionResponse, SubscriptionStatus,
+    SubscriptionUpdate,
+  },
+  stripe::{self, SIGNATURE_HEADER, StripeGateway},
+};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{
+    RequireRole, RequiredWorkspace,
+    workspace::role::{Admin, Viewer},
+  },
+  internal_error,
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// The Stripe gateway, or an error when billing is not configured on this server.
+fn stripe_gateway(state: &AppState) -> AppResult<&Arc<dyn StripeGateway>> {
+  state
+    .stripe
+    .as_ref()
+    .ok_or_else(|| internal_error!("Billing is not configured: STRIPE_SECRET_KEY and STRIPE_PRICE_ID must be set"))
+}
+
+pub async fn get_subscription(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _viewer: RequireRole<Viewer>,
+) -> AppResult<Json<ApiResponse<SubscriptionResponse>>> {
+  let subscription = state.billing_repository.get_subscription(workspace_id).await?;
+
+  let response = ApiResponse::success(
+    SubscriptionResponse::new(workspace_id, subscription),
+    "Subscription retrieved successfully",
+  );
+  Ok(Json(response))
+}
+
+/// Starts a Stripe Checkout session subscribing the workspace, creating its Stripe customer on
+/// first use. Workspaces with an active or past due subscription manage it in the portal instead.
+pub async fn create_checkout_session(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+) -> AppResult<Json<ApiResponse<CheckoutSessionResponse>>> {
+  let stripe = stripe_gateway(&state)?;
+
+  let customer_id = match state.billing_repository.get_subscription(workspace_id).await? {
+    Some(subscription) if matches!(subscription.status, Some(SubscriptionStatus::Active | SubscriptionStatus::PastDue)) => {
+      return Err(AppError::Conflict(
+        "Workspace already has a subscription, manage it in the customer portal".to_string(),
+      ));
+    }
+    Some(subscription) => subscription.stripe_customer_id,
+    None => {
+      let workspace = state
+        .workspace_repository
+        .get_workspace_by_id(workspace_id)
+        .await?
+        .ok_or_else(|| AppError::not_found_with_id("Workspace", workspace_id))?;
+      let customer_id = stripe.create_customer(&workspace).await?;
+      state
+        .billing_repository
+        .link_customer(workspace_id, &customer_id)
+        .await?
+        .stripe_customer_id
+    }
+  };
+
+  let session = stripe.create_checkout_session(&customer_id, workspace_id).await?;
+
+  let response = ApiResponse::success(session, "Checkout session created successfully");
+  Ok(Json(response))
+}
+
+/// Returns the URL of a Stripe customer portal session, where admins update payment methods,
+/// download invoices and cancel the subscription.
+pub async fn get_portal_session(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+) -> AppResult<Json<ApiResponse<PortalSessionResponse>>> {
+  let stripe = stripe_gateway(&state)?;
+
+  let subscription = state
+    .billing_repository
+    .get_subscription(workspace_id)
+    .await?
+    .ok_or_else(|| AppError::not_found("Billing account"))?;
+  let session = stripe.create_portal_session(&subscription.stripe_customer_id).await?;
+
+  let response = ApiResponse::success(session, "Customer portal session created successfully");
+  Ok(Json(response))
+}
+
+/// Receives Stripe webhook deliveries.
+///
+/// The signature is checked against the raw body before anything is parsed. Events the API does
+/// not use are acknowledged as well, since Stripe retries every delivery not answered with a 2xx.
+pub async fn stripe_webhook(State(state): State<Arc<AppState>>, headers: HeaderMap, body: Bytes) -> AppResult<Json<ApiResponse<()>>> {
+  let config = &state.config.billing;
+  let secret = config
+    .webhook_secret
+    .as_deref()
+    .ok_or_else(|| internal_error!("Stripe webhook received, but STRIPE_WEBHOOK_SECRET is not set"))?;
+  let signature = headers
+    .get(SIGNATURE_HEADER)
+    .and_then(|value| value.to_str().ok())
+    .ok_or_else(|| AppError::BadRequest(format!("Missing {} header", SIGNATURE_HEADER)))?;
+  stripe::verify_signature(&body, signature, secret, config.webhook_tolerance_secs, Utc::now().timestamp())?;
+
+  let event: StripeEvent = serde_json::from_slice(&body).map_err(|e| AppError::BadRequest(format!("Invalid Stripe event: {}", e)))?;
+  apply_event(&state, event).await?;
+
+  let response = ApiResponse::success((), "Webhook processed successfully");
+  Ok(Json(response))
+}
+
+/// Applies a verified event to the workspace subscriptions.
+async fn apply_event(state: &AppState, event: StripeEvent) -> AppResult<()> {
+  let event_at = DateTime::from_timestamp(event.created, 0).unwrap_or_else(Utc::now);
+
+  match event.event_type.as_str() {
+    // Normally the customer is linked before checkout; this covers sessions created elsewhere
+    "checkout.session.completed" => {
+      let session: StripeCheckoutSession = event_object(&event)?;
+      let workspace_id = session.client_reference_id.as_deref().and_then(|id| Uuid::parse_str(id).ok());
+      match (workspace_id, session.customer) {
+        (Some(workspace_id), Some(customer_id)) => {
+          state.billing_repository.link_customer(workspace_id, &customer_id).await?;
+        }
+        _ => tracing::warn!("Stripe event {}: checkout session without a workspace or customer", event.id),
+      }
+    }
+    "customer.subscription.created" | "customer.subscription.updated" | "customer.subscription.deleted" => {
+      let subscription: StripeSubscription = event_object(&event)?;
+      let status = match event.event_type.as_str() {
+        "customer.subscription.deleted" => Some(SubscriptionStatus::Canceled),
+        _ => SubscriptionStatus::from_stripe(&subscription.status),
+      };
+      let Some(status) = status else {
+        tracing::debug!("Stripe event {}: ignoring subscription status {}", event.id, subscription.status);
+        return Ok(());
+      };
+
+      let update = SubscriptionUpdate {
+        stripe_customer_id: subscription.customer,
+        stripe_subscription_id: subscription.id,
+        status,
+        current_period_end: subscription.current_period_end.and_then(|end| DateTime::from_timestamp(end, 0)),
+        event_at,
+      };
+      match state.billing_repository.apply_subscription_update(&update).await? {
+        // The plan may have changed, and with it the API quota of the workspace and of those it pays for
+        Some(workspace_id) => {
+          state.usage_meter.invalidate(workspace_id);
+          for covered in state.billing_repository.covered_workspaces(workspace_id).await? {
+            state.usage_meter.invalidate(covered);
+          }
+        }
+        None => tracing::info!(
+          "Stripe event {}: no workspace for customer {} or a newer event was applied",
+          event.id,
+          update.stripe_customer_id
+        ),
+      }
+    }
+    other => tracing::debug!("Stripe event {}: ignoring {}", event.id, other),
+  }
+
+  Ok(())
+}
+
+/// Deserializes the `data.object` of an event.
+fn event_object<T: DeserializeOwned>(event: &StripeEvent) -> AppResult<T> {
+  T::deserialize(&event.data.object).map_err(|e| AppError::BadRequest(format!("Invalid {} event: {}", event.event_type, e)))
+}
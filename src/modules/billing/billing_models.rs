@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Subscription state of a workspace, condensed from the Stripe subscription status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "subscription_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionStatus {
+  Active,
+  PastDue,
+  Canceled,
+}
+
+impl SubscriptionStatus {
+  /// Maps a Stripe subscription status. `incomplete` and `paused` subscriptions have no
+  /// counterpart and leave the workspace's state unchanged.
+  pub fn from_stripe(status: &str) -> Option<Self> {
+    match status {
+      "active" | "trialing" => Some(Self::Active),
+      "past_due" | "unpaid" => Some(Self::PastDue),
+      "canceled" | "incomplete_expired" => Some(Self::Canceled),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WorkspaceSubscription {
+  pub workspace_id: Uuid,
+  pub stripe_customer_id: String,
+  pub stripe_subscription_id: Option<String>,
+  pub status: Option<SubscriptionStatus>,
+  pub current_period_end: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// The subscription of a workspace as returned by `GET /billing/subscription`; `status` is
+/// `null` for a workspace that never subscribed.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct SubscriptionResponse {
+  pub workspace_id: Uuid,
+  pub status: Option<SubscriptionStatus>,
+  pub current_period_end: Option<DateTime<Utc>>,
+}
+
+impl SubscriptionResponse {
+  pub fn new(workspace_id: Uuid, subscription: Option<WorkspaceSubscription>) -> Self {
+    Self {
+      workspace_id,
+      status: subscription.as_ref().and_then(|subscription| subscription.status),
+      current_period_end: subscription.and_then(|subscription| subscription.current_period_end),
+    }
+  }
+}
+
+/// A Stripe Checkout session; the client redirects the user to `url`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckoutSessionResponse {
+  pub id: String,
+  pub url: String,
+}
+
+/// A Stripe customer portal session; the client redirects the user to `url`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortalSessionResponse {
+  pub url: String,
+}
+
+/// A change to a subscription reported by a `customer.subscription.*` webhook event.
+#[derive(Debug, Clone)]
+pub struct SubscriptionUpdate {
+  pub stripe_customer_id: String,
+  pub stripe_subscription_id: String,
+  pub status: SubscriptionStatus,
+  pub current_period_end: Option<DateTime<Utc>>,
+  /// When Stripe created the event, to skip events delivered after a newer one.
+  pub event_at: DateTime<Utc>,
+}
+
+/// A Stripe webhook event, with only the fields the webhook reads.
+#[derive(Debug, Deserialize)]
+pub struct StripeEvent {
+  pub id: String,
+  #[serde(rename = "type")]
+  pub event_type: String,
+  /// Unix timestamp of the event.
+  pub created: i64,
+  pub data: StripeEventData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StripeEventData {
+  pub object: serde_json::Value,
+}
+
+/// The `checkout.session` object of a `checkout.session.completed` event.
+#[derive(Debug, Deserialize)]
+pub struct StripeCheckoutSession {
+  pub customer: Option<String>,
+  /// The workspace ID the session was started for.
+  pub client_reference_id: Option<String>,
+}
+
+/// The `subscription` object of a `customer.subscription.*` event.
+#[derive(Debug, Deserialize)]
+pub struct StripeSubscription {
+  pub id: String,
+  pub customer: String,
+  pub status: String,
+  /// Unix timestamp; only sent at the top level by API versions before 2025-03-31.
+  pub current_period_end: Option<i64>,
+}
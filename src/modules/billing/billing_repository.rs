@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::billing_models::{SubscriptionStatus, SubscriptionUpdate, WorkspaceSubscription};
+use crate::{
+  errors::AppError,
+  utils::{DbExecutor, ReadPool},
+};
+
+#[async_trait]
+pub trait BillingRepository: Send + Sync {
+  async fn get_subscription(&self, workspace_id: Uuid) -> Result<Option<WorkspaceSubscription>, AppError>;
+  /// Links a workspace to its Stripe customer. A workspace keeps the customer it was linked to first.
+  async fn link_customer(&self, workspace_id: Uuid, stripe_customer_id: &str) -> Result<WorkspaceSubscription, AppError>;
+  /// Applies a subscription change to the workspace of its customer and returns the workspace.
+  /// Returns `None` when no workspace has that customer, or when a newer event was applied already.
+  async fn apply_subscription_update(&self, update: &SubscriptionUpdate) -> Result<Option<Uuid>, AppError>;
+  /// The other workspaces the subscription of `workspace_id` pays for, as the billing workspace
+  /// of their organization.
+  async fn covered_workspaces(&self, workspace_id: Uuid) -> Result<Vec<Uuid>, AppError>;
+}
+
+pub struct PostgresBillingRepository {
+  db: DbExecutor,
+  read_pool: ReadPool,
+}
+
+impl PostgresBillingRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    let db = db.into();
+    Self {
+      read_pool: ReadPool::primary_only(db.clone()),
+      db,
+    }
+  }
+
+  /// Runs the lookups on `read_pool` instead of the primary.
+  pub fn with_read_pool(mut self, read_pool: ReadPool) -> Self {
+    self.read_pool = read_pool;
+    self
+  }
+}
+
+#[async_trait]
+impl BillingRepository for PostgresBillingRepository {
+  async fn get_subscription(&self, workspace_id: Uuid) -> Result<Option<WorkspaceSubscription>, AppError> {
+    let mut conn = self.read_pool.acquire().await?;
+    let subscription = sqlx::query_as!(
+      WorkspaceSubscription,
+      r#"
+        SELECT workspace_id, stripe_customer_id, stripe_subscription_id, status as "status: SubscriptionStatus",
+               current_period_end, created_at, updated_at
+        FROM workspace_subscriptions
+        WHERE workspace_id = $1
+        "#,
+      workspace_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(subscription)
+  }
+
+  async fn link_customer(&self, workspace_id: Uuid, stripe_customer_id: &str) -> Result<WorkspaceSubscription, AppError> {
+    let mut conn = self.db.acquire().await?;
+    // The no-op update makes RETURNING yield the existing row on conflict
+    let subscription = sqlx::query_as!(
+      WorkspaceSubscription,
+      r#"
+        INSERT INTO workspace_subscriptions (workspace_id, stripe_customer_id)
+        VALUES ($1, $2)
+        ON CONFLICT (workspace_id) DO UPDATE SET stripe_customer_id = workspace_subscriptions.stripe_customer_id
+        RETURNING workspace_id, stripe_customer_id, stripe_subscription_id, status as "status: SubscriptionStatus",
+                  current_period_end, created_at, updated_at
+        "#,
+      workspace_id,
+      stripe_customer_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(subscription)
+  }
+
+  async fn apply_subscription_update(&self, update: &SubscriptionUpdate) -> Result<Option<Uuid>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let workspace_id = sqlx::query_scalar!(
+      r#"
+        UPDATE workspace_subscriptions
+        SET stripe_subscription_id = $2,
+            status = $3,
+            current_period_end = COALESCE($4, current_period_end),
+            last_event_at = $5
+        WHERE stripe_customer_id = $1
+          AND (last_event_at IS NULL OR last_event_at <= $5)
+        RETURNING workspace_id
+        "#,
+      update.stripe_customer_id,
+      update.stripe_subscription_id,
+      update.status as SubscriptionStatus,
+      update.current_period_end,
+      update.event_at
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(workspace_id)
+  }
+
+  async fn covered_workspaces(&self, workspace_id: Uuid) -> Result<Vec<Uuid>, AppError> {
+    let mut conn = self.read_pool.acquire().await?;
+    let workspace_ids = sqlx::query_scalar!(
+      r#"
+        SELECT w.id
+        FROM organizations o
+        JOIN workspaces w ON w.organization_id = o.id
+        WHERE o.billing_workspace_id = $1 AND w.id <> $1
+        "#,
+      workspace_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(workspace_ids)
+  }
+}
@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  routing::{get, post},
+};
+
+use super::billing_handlers::{create_checkout_session, get_portal_session, get_subscription, stripe_webhook};
+use crate::state::AppState;
+
+/// Billing routes of the current workspace, mounted at `/api/v1/billing` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/subscription", get(get_subscription))
+    .route("/checkout-session", post(create_checkout_session))
+    .route("/portal", get(get_portal_session))
+}
+
+/// Webhook routes, mounted at `/api/v1/billing/webhooks`. Stripe authenticates its deliveries
+/// with a signature instead of a token, so they are mounted outside the JWT middleware.
+pub fn webhook_routes() -> Router<Arc<AppState>> {
+  Router::new().route("/stripe", post(stripe_webhook))
+}
@@ -0,0 +1,14 @@
+//! Stripe subscriptions per workspace, compiled with the `billing` feature.
+//!
+//! Workspace admins start a Stripe Checkout session to subscribe and manage the subscription in
+//! the Stripe customer portal. Stripe reports the outcome through the webhook, which keeps
+//! `workspace_subscriptions` up to date; the API never changes a subscription itself.
+//!
+//! The subscription of a workspace also pays for the other workspaces of its organization when
+//! it is the organization's billing workspace (see `modules::organizations`).
+
+pub mod billing_handlers;
+pub mod billing_models;
+pub mod billing_repository;
+pub mod billing_routes;
+pub mod stripe;
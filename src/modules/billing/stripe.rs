@@ -0,0 +1,162 @@
+//! The parts of the Stripe API the billing module uses: webhook signatures, customers, Checkout
+//! and the customer portal.
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use super::billing_models::{CheckoutSessionResponse, PortalSessionResponse};
+use crate::{AppResult, config::BillingConfig, errors::AppError, internal_error, modules::datastores::workspaces::Workspace};
+
+/// Header carrying the signature of a Stripe webhook delivery.
+pub const SIGNATURE_HEADER: &str = "Stripe-Signature";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The MAC Stripe signs a delivery with: HMAC-SHA256 of `{timestamp}.{payload}`, keyed with the
+/// endpoint's signing secret.
+fn signature_mac(payload: &[u8], secret: &str, timestamp: i64) -> HmacSha256 {
+  let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+  mac.update(timestamp.to_string().as_bytes());
+  mac.update(b".");
+  mac.update(payload);
+  mac
+}
+
+/// Checks the `Stripe-Signature` header of a webhook delivery, `t=<timestamp>,v1=<signature>,...`.
+///
+/// One of the `v1` signatures (there are several while the secret is rolled) must match the
+/// payload, and the timestamp must be at most `tolerance_secs` away from `now`, so a captured
+/// delivery cannot be replayed later.
+pub fn verify_signature(payload: &[u8], header: &str, secret: &str, tolerance_secs: u64, now: i64) -> AppResult<()> {
+  let invalid = || AppError::BadRequest("Invalid Stripe signature".to_string());
+
+  let mut timestamp = None;
+  let mut signatures = Vec::new();
+  for (key, value) in header.split(',').filter_map(|part| part.trim().split_once('=')) {
+    match key {
+      "t" => timestamp = value.parse::<i64>().ok(),
+      "v1" => signatures.extend(hex::decode(value).ok()),
+      _ => {}
+    }
+  }
+
+  let timestamp = timestamp.ok_or_else(invalid)?;
+  if now.abs_diff(timestamp) > tolerance_secs {
+    return Err(AppError::BadRequest("Stripe signature timestamp is outside the tolerance".to_string()));
+  }
+
+  let mac = signature_mac(payload, secret, timestamp);
+  if signatures.iter().any(|signature| mac.clone().verify_slice(signature).is_ok()) {
+    Ok(())
+  } else {
+    Err(invalid())
+  }
+}
+
+/// Builds the `Stripe-Signature` header Stripe would send with `payload`, e.g. to replay a
+/// delivery against a local server.
+pub fn signature_header(payload: &[u8], secret: &str, timestamp: i64) -> String {
+  let signature = signature_mac(payload, secret, timestamp).finalize().into_bytes();
+  format!("t={},v1={}", timestamp, hex::encode(signature))
+}
+
+/// The Stripe API calls made on behalf of a workspace. `StripeClient` calls Stripe; tests and
+/// staging environments can inject another implementation with `AppStateBuilder::with_stripe_gateway`.
+#[async_trait]
+pub trait StripeGateway: Send + Sync {
+  /// Creates the Stripe customer billed for `workspace` and returns its ID.
+  async fn create_customer(&self, workspace: &Workspace) -> AppResult<String>;
+  /// Starts a Checkout session subscribing `customer_id` to the configured price.
+  async fn create_checkout_session(&self, customer_id: &str, workspace_id: Uuid) -> AppResult<CheckoutSessionResponse>;
+  /// Opens a customer portal session for `customer_id`.
+  async fn create_portal_session(&self, customer_id: &str) -> AppResult<PortalSessionResponse>;
+}
+
+/// Calls the Stripe REST API with the account's secret key.
+pub struct StripeClient {
+  http: reqwest::Client,
+  config: BillingConfig,
+  secret_key: String,
+  price_id: String,
+}
+
+impl StripeClient {
+  /// A client for the configured account, `None` until both `secret_key` and `price_id` are set.
+  pub fn from_config(config: &BillingConfig) -> Option<Self> {
+    Some(Self {
+      http: reqwest::Client::new(),
+      secret_key: config.secret_key.clone()?,
+      price_id: config.price_id.clone()?,
+      config: config.clone(),
+    })
+  }
+
+  /// Posts a form to `/v1/{path}`. Stripe replays the first response for a repeated `idempotency_key`.
+  async fn post<T: DeserializeOwned>(&self, path: &str, form: &[(&str, String)], idempotency_key: Option<String>) -> AppResult<T> {
+    let mut request = self
+      .http
+      .post(format!("{}/v1/{}", self.config.api_base.trim_end_matches('/'), path))
+      .bearer_auth(&self.secret_key)
+      .form(form);
+    if let Some(key) = idempotency_key {
+      request = request.header("Idempotency-Key", key);
+    }
+
+    let response = request
+      .send()
+      .await
+      .map_err(|e| internal_error!("Stripe request to {} failed: {}", path, e))?;
+    let status = response.status();
+    if !status.is_success() {
+      let body = response.text().await.unwrap_or_default();
+      return Err(internal_error!("Stripe responded {} to {}: {}", status, path, body));
+    }
+    response
+      .json()
+      .await
+      .map_err(|e| internal_error!("Invalid Stripe response to {}: {}", path, e))
+  }
+}
+
+/// The `id` of a created Stripe object.
+#[derive(serde::Deserialize)]
+struct StripeObject {
+  id: String,
+}
+
+#[async_trait]
+impl StripeGateway for StripeClient {
+  async fn create_customer(&self, workspace: &Workspace) -> AppResult<String> {
+    let form = [("name", workspace.name.clone()), ("metadata[workspace_id]", workspace.id.to_string())];
+    // Concurrent checkouts of the same workspace get the same customer
+    let customer: StripeObject = self
+      .post("customers", &form, Some(format!("workspace-customer-{}", workspace.id)))
+      .await?;
+    Ok(customer.id)
+  }
+
+  async fn create_checkout_session(&self, customer_id: &str, workspace_id: Uuid) -> AppResult<CheckoutSessionResponse> {
+    let form = [
+      ("mode", "subscription".to_string()),
+      ("customer", customer_id.to_string()),
+      ("client_reference_id", workspace_id.to_string()),
+      ("line_items[0][price]", self.price_id.clone()),
+      ("line_items[0][quantity]", "1".to_string()),
+      ("success_url", self.config.success_url.clone()),
+      ("cancel_url", self.config.cancel_url.clone()),
+      ("subscription_data[metadata][workspace_id]", workspace_id.to_string()),
+    ];
+    self.post("checkout/sessions", &form, None).await
+  }
+
+  async fn create_portal_session(&self, customer_id: &str) -> AppResult<PortalSessionResponse> {
+    let form = [
+      ("customer", customer_id.to_string()),
+      ("return_url", self.config.portal_return_url.clone()),
+    ];
+    self.post("billing_portal/sessions", &form, None).await
+  }
+}
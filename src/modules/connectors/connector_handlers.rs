@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use axum::{
+  extract::{Path, State, rejection::JsonRejection},
+  http::StatusCode,
+  response::{IntoResponse, Json, Response},
+};
+use validator::Validate;
+
+use super::{
+  connector_models::{
+    Connector, ConnectorCredentials, ConnectorKind, ConnectorOrder, ConnectorOrdersQuery, DEFAULT_ORDER_LIMIT, DEFAULT_SYNC_INTERVAL_MINUTES,
+    SaveConnectorRequest,
+  },
+  connector_repository::ConnectorSettings,
+};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{
+    RequireRole, RequiredWorkspace, ValidatedQuery,
+    workspace::role::{Admin, Member},
+  },
+  modules::auth::current_user::CurrentUser,
+  responses::ApiResponse,
+  state::AppState,
+};
+
+fn parse_connector(connector: &str) -> AppResult<ConnectorKind> {
+  connector.parse().map_err(|e: String| AppError::validation("connector", &e))
+}
+
+/// The settings and credentials of `request`, checking that the credentials are those of the platform.
+fn connector_settings(connector: ConnectorKind, request: SaveConnectorRequest) -> AppResult<(ConnectorSettings, ConnectorCredentials)> {
+  if !request.store_url.starts_with("https://") {
+    return Err(AppError::validation("store_url", "The store must be reached over HTTPS"));
+  }
+  let credentials = match connector {
+    ConnectorKind::Shopify => {
+      if request.location_id.is_none() {
+        return Err(AppError::validation(
+          "location_id",
+          "Shopify connectors need the location whose stock is set",
+        ));
+      }
+      let Some(access_token) = request.access_token else {
+        return Err(AppError::validation("access_token", "Shopify connectors need an Admin API access token"));
+      };
+      ConnectorCredentials {
+        access_token: Some(access_token),
+        ..Default::default()
+      }
+    }
+    ConnectorKind::WooCommerce => {
+      let (Some(consumer_key), Some(consumer_secret)) = (request.consumer_key, request.consumer_secret) else {
+        return Err(AppError::validation(
+          "consumer_key",
+          "WooCommerce connectors need a REST API consumer key and secret",
+        ));
+      };
+      ConnectorCredentials {
+        consumer_key: Some(consumer_key),
+        consumer_secret: Some(consumer_secret),
+        ..Default::default()
+      }
+    }
+  };
+
+  let settings = ConnectorSettings {
+    store_url: request.store_url.trim_end_matches('/').to_string(),
+    location_id: request.location_id.filter(|_| connector == ConnectorKind::Shopify),
+    sync_interval_minutes: request.sync_interval_minutes.unwrap_or(DEFAULT_SYNC_INTERVAL_MINUTES),
+  };
+  Ok((settings, credentials))
+}
+
+/// The connectors of the workspace, without their credentials.
+pub async fn list_connectors(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+) -> AppResult<Json<ApiResponse<Vec<Connector>>>> {
+  let connectors = state.connector_repository.list(workspace_id).await?;
+  Ok(Json(ApiResponse::success(connectors, "Connectors retrieved successfully")))
+}
+
+pub async fn get_connector(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  Path(connector): Path<String>,
+) -> AppResult<Json<ApiResponse<Connector>>> {
+  let connector = parse_connector(&connector)?;
+  let connector = state
+    .connector_repository
+    .get(workspace_id, connector)
+    .await?
+    .ok_or_else(|| AppError::not_found("Connector"))?;
+  Ok(Json(ApiResponse::success(connector, "Connector retrieved successfully")))
+}
+
+/// Connects the workspace to a store, or replaces its connection to one. Syncs start over, the
+/// first one running with the next check.
+pub async fn save_connector(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  Path(connector): Path<String>,
+  payload: Result<Json<SaveConnectorRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<Connector>>> {
+  let connector = parse_connector(&connector)?;
+  let Json(request) = payload?;
+  request.validate()?;
+  let (settings, credentials) = connector_settings(connector, request)?;
+
+  let connector = state
+    .connector_repository
+    .save(workspace_id, connector, &settings, &credentials, current_user.user_id)
+    .await?;
+  Ok(Json(ApiResponse::success(connector, "Connector saved successfully")))
+}
+
+/// Disconnects the workspace from a store. Orders already pulled are kept.
+pub async fn delete_connector(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  Path(connector): Path<String>,
+) -> AppResult<Json<ApiResponse<()>>> {
+  let connector = parse_connector(&connector)?;
+  if !state.connector_repository.delete(workspace_id, connector).await? {
+    return Err(AppError::not_found("Connector"));
+  }
+  Ok(Json(ApiResponse::success((), "Connector deleted successfully")))
+}
+
+/// Makes the next sync of a connector due now rather than at its interval.
+pub async fn sync_connector(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  Path(connector): Path<String>,
+) -> AppResult<Response> {
+  let connector = parse_connector(&connector)?;
+  if !state.connector_repository.schedule_now(workspace_id, connector).await? {
+    return Err(AppError::not_found("Connector"));
+  }
+  Ok((StatusCode::ACCEPTED, Json(ApiResponse::success((), "Connector sync scheduled"))).into_response())
+}
+
+/// The orders pulled from the stores of the workspace, latest first.
+pub async fn list_connector_orders(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  ValidatedQuery(query): ValidatedQuery<ConnectorOrdersQuery>,
+) -> AppResult<Json<ApiResponse<Vec<ConnectorOrder>>>> {
+  let orders = state
+    .connector_repository
+    .list_orders(workspace_id, query.connector, query.limit.unwrap_or(DEFAULT_ORDER_LIMIT))
+    .await?;
+  Ok(Json(ApiResponse::success(orders, "Connector orders retrieved successfully")))
+}
@@ -0,0 +1,171 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Minutes between syncs when the admin does not choose.
+pub const DEFAULT_SYNC_INTERVAL_MINUTES: u32 = 15;
+pub const MIN_SYNC_INTERVAL_MINUTES: u32 = 5;
+pub const MAX_SYNC_INTERVAL_MINUTES: u32 = 1440;
+/// Orders listed when no limit is given.
+pub const DEFAULT_ORDER_LIMIT: i64 = 50;
+
+/// The platform of an online store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectorKind {
+  Shopify,
+  WooCommerce,
+}
+
+impl ConnectorKind {
+  pub const ALL: [ConnectorKind; 2] = [ConnectorKind::Shopify, ConnectorKind::WooCommerce];
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ConnectorKind::Shopify => "shopify",
+      ConnectorKind::WooCommerce => "woocommerce",
+    }
+  }
+}
+
+impl fmt::Display for ConnectorKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl FromStr for ConnectorKind {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    Self::ALL
+      .into_iter()
+      .find(|kind| kind.as_str() == value)
+      .ok_or_else(|| format!("'{}' is not a connector, expected shopify or woocommerce", value))
+  }
+}
+
+/// What a connector needs to authenticate with its store: an Admin API access token for Shopify,
+/// a REST API key pair for WooCommerce. Stored encrypted and never returned.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ConnectorCredentials {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub access_token: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub consumer_key: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub consumer_secret: Option<String>,
+}
+
+impl fmt::Debug for ConnectorCredentials {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("ConnectorCredentials(..)")
+  }
+}
+
+/// Where a connector is in its syncs.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ConnectorSync {
+  pub next_sync_at: DateTime<Utc>,
+  pub last_synced_at: Option<DateTime<Utc>>,
+  /// Products changed since are pushed with the next sync; all of them before the first one.
+  pub stock_synced_at: Option<DateTime<Utc>>,
+  /// Orders changed since are pulled with the next sync.
+  pub orders_synced_at: Option<DateTime<Utc>>,
+  /// Why the last sync failed, `None` when it succeeded.
+  pub last_error: Option<String>,
+}
+
+/// An online store the workspace pushes its stock levels to and pulls its orders from.
+#[derive(Debug, Clone, Serialize)]
+pub struct Connector {
+  pub connector: ConnectorKind,
+  pub workspace_id: Uuid,
+  /// Base URL of the store, e.g. `https://example.myshopify.com`.
+  pub store_url: String,
+  /// The Shopify location whose inventory is set.
+  pub location_id: Option<String>,
+  pub sync_interval_minutes: u32,
+  pub sync: ConnectorSync,
+  pub updated_by: Option<Uuid>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// Connects the workspace to a store, replacing its previous settings for the platform.
+/// Shopify takes an `access_token` and a `location_id`, WooCommerce a `consumer_key` and
+/// `consumer_secret`.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct SaveConnectorRequest {
+  #[validate(
+    url(message = "Store URL must be a valid URL"),
+    length(max = 255, message = "Store URL must be at most 255 characters")
+  )]
+  pub store_url: String,
+  #[validate(length(min = 1, max = 100, message = "Location ID must be between 1 and 100 characters"))]
+  pub location_id: Option<String>,
+  #[validate(range(
+    min = MIN_SYNC_INTERVAL_MINUTES,
+    max = MAX_SYNC_INTERVAL_MINUTES,
+    message = "Sync interval must be between 5 and 1440 minutes"
+  ))]
+  pub sync_interval_minutes: Option<u32>,
+  #[validate(length(min = 1, max = 255, message = "Access token must be between 1 and 255 characters"))]
+  pub access_token: Option<String>,
+  #[validate(length(min = 1, max = 255, message = "Consumer key must be between 1 and 255 characters"))]
+  pub consumer_key: Option<String>,
+  #[validate(length(min = 1, max = 255, message = "Consumer secret must be between 1 and 255 characters"))]
+  pub consumer_secret: Option<String>,
+}
+
+/// The stock of a product as pushed to the stores, matched there by SKU.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct StockLevel {
+  pub sku: String,
+  pub quantity: i32,
+}
+
+/// An order as read from a store.
+#[derive(Debug, Clone)]
+pub struct ExternalOrder {
+  pub external_id: String,
+  pub number: Option<String>,
+  pub status: Option<String>,
+  pub currency: Option<String>,
+  pub total: Option<Decimal>,
+  pub ordered_at: Option<DateTime<Utc>>,
+  /// When the store last changed the order, the cursor of the next pull.
+  pub updated_at: DateTime<Utc>,
+  pub payload: Value,
+}
+
+/// An order pulled from a store, waiting to be mapped into records of the workspace.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ConnectorOrder {
+  pub id: Uuid,
+  pub workspace_id: Uuid,
+  pub connector: String,
+  pub external_id: String,
+  pub number: Option<String>,
+  pub status: Option<String>,
+  pub currency: Option<String>,
+  pub total: Option<Decimal>,
+  pub ordered_at: Option<DateTime<Utc>>,
+  pub payload: Value,
+  pub pulled_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectorOrdersQuery {
+  pub connector: Option<ConnectorKind>,
+  #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
+  pub limit: Option<i64>,
+}
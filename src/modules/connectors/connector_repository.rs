@@ -0,0 +1,414 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::connector_models::{Connector, ConnectorCredentials, ConnectorKind, ConnectorOrder, ConnectorSync, ExternalOrder, StockLevel};
+use crate::{
+  AppResult, internal_error,
+  utils::{DbExecutor, field_encryption::FieldCipher},
+};
+
+const CREDENTIALS: &str = "workspace_settings.connectors";
+
+/// A connector whose sync is due.
+#[derive(Debug, Clone)]
+pub struct DueSync {
+  pub workspace_id: Uuid,
+  pub connector: ConnectorKind,
+  pub next_sync_at: DateTime<Utc>,
+}
+
+/// The settings of a connector to save, its credentials apart.
+#[derive(Debug, Clone)]
+pub struct ConnectorSettings {
+  pub store_url: String,
+  pub location_id: Option<String>,
+  pub sync_interval_minutes: u32,
+}
+
+/// What a sync moved forward. Cursors are `None` when their step failed and kept as they were.
+#[derive(Debug, Clone)]
+pub struct SyncRecord<'a> {
+  pub synced_at: DateTime<Utc>,
+  pub stock_synced_at: Option<DateTime<Utc>>,
+  pub orders_synced_at: Option<DateTime<Utc>>,
+  pub error: Option<&'a str>,
+}
+
+#[async_trait]
+pub trait ConnectorRepository: Send + Sync {
+  async fn list(&self, workspace_id: Uuid) -> AppResult<Vec<Connector>>;
+  async fn get(&self, workspace_id: Uuid, connector: ConnectorKind) -> AppResult<Option<Connector>>;
+  /// The connector with its decrypted credentials.
+  async fn get_with_credentials(&self, workspace_id: Uuid, connector: ConnectorKind) -> AppResult<Option<(Connector, ConnectorCredentials)>>;
+  /// Saves a connector into the workspace settings and starts its syncs over, the first one due now.
+  async fn save(
+    &self,
+    workspace_id: Uuid,
+    connector: ConnectorKind,
+    settings: &ConnectorSettings,
+    credentials: &ConnectorCredentials,
+    updated_by: Uuid,
+  ) -> AppResult<Connector>;
+  /// Removes a connector, `false` when the workspace had none for the platform. Pulled orders are kept.
+  async fn delete(&self, workspace_id: Uuid, connector: ConnectorKind) -> AppResult<bool>;
+  /// Makes the next sync of a connector due now, `false` when the workspace has no such connector.
+  async fn schedule_now(&self, workspace_id: Uuid, connector: ConnectorKind) -> AppResult<bool>;
+  /// The syncs due at `now`, oldest first.
+  async fn due(&self, now: DateTime<Utc>, limit: i64) -> AppResult<Vec<DueSync>>;
+  /// Moves a due sync to `next_sync_at`. `false` when another instance claimed it first, or the
+  /// connector was saved since.
+  async fn claim(&self, sync: &DueSync, next_sync_at: DateTime<Utc>) -> AppResult<bool>;
+  /// The stock of the published tracked products with a SKU, changed after `changed_after` when given.
+  async fn stock_levels(&self, workspace_id: Uuid, changed_after: Option<DateTime<Utc>>) -> AppResult<Vec<StockLevel>>;
+  /// Saves pulled orders, replacing those pulled before.
+  async fn save_orders(&self, workspace_id: Uuid, connector: ConnectorKind, orders: &[ExternalOrder]) -> AppResult<()>;
+  async fn record_sync(&self, workspace_id: Uuid, connector: ConnectorKind, record: &SyncRecord<'_>) -> AppResult<()>;
+  /// The orders pulled for the workspace, latest first.
+  async fn list_orders(&self, workspace_id: Uuid, connector: Option<ConnectorKind>, limit: i64) -> AppResult<Vec<ConnectorOrder>>;
+}
+
+pub struct PostgresConnectorRepository {
+  db: DbExecutor,
+  cipher: Arc<FieldCipher>,
+}
+
+impl PostgresConnectorRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self {
+      db: db.into(),
+      cipher: Arc::new(FieldCipher::disabled()),
+    }
+  }
+
+  /// Encrypts the credentials with `cipher`, which must have a key to save connectors.
+  pub fn with_cipher(mut self, cipher: Arc<FieldCipher>) -> Self {
+    self.cipher = cipher;
+    self
+  }
+}
+
+/// A connector as kept in `workspace_settings.connectors`, under the name of its platform.
+#[derive(Serialize, Deserialize)]
+struct StoredConnector {
+  store_url: String,
+  location_id: Option<String>,
+  sync_interval_minutes: u32,
+  /// The encrypted JSON of the `ConnectorCredentials`.
+  credentials: String,
+  updated_by: Option<Uuid>,
+  updated_at: DateTime<Utc>,
+}
+
+struct ConnectorRow {
+  workspace_id: Uuid,
+  connector: String,
+  settings: Value,
+  next_sync_at: DateTime<Utc>,
+  last_synced_at: Option<DateTime<Utc>>,
+  stock_synced_at: Option<DateTime<Utc>>,
+  orders_synced_at: Option<DateTime<Utc>>,
+  last_error: Option<String>,
+}
+
+impl ConnectorRow {
+  /// The connector of the row, with its still encrypted credentials.
+  fn into_connector(self) -> AppResult<(Connector, String)> {
+    let invalid = |e: String| internal_error!("Invalid connector {} stored for workspace {}: {}", self.connector, self.workspace_id, e);
+    let connector: ConnectorKind = self.connector.parse().map_err(invalid)?;
+    let stored: StoredConnector = serde_json::from_value(self.settings).map_err(|e| invalid(e.to_string()))?;
+    let connector = Connector {
+      connector,
+      workspace_id: self.workspace_id,
+      store_url: stored.store_url,
+      location_id: stored.location_id,
+      sync_interval_minutes: stored.sync_interval_minutes,
+      sync: ConnectorSync {
+        next_sync_at: self.next_sync_at,
+        last_synced_at: self.last_synced_at,
+        stock_synced_at: self.stock_synced_at,
+        orders_synced_at: self.orders_synced_at,
+        last_error: self.last_error,
+      },
+      updated_by: stored.updated_by,
+      updated_at: stored.updated_at,
+    };
+    Ok((connector, stored.credentials))
+  }
+}
+
+impl PostgresConnectorRepository {
+  async fn find(&self, workspace_id: Uuid, connector: Option<ConnectorKind>) -> AppResult<Vec<(Connector, String)>> {
+    let mut conn = self.db.acquire().await?;
+    let rows = sqlx::query_as!(
+      ConnectorRow,
+      r#"
+        SELECT ws.workspace_id, c.key as "connector!", c.value as "settings!", s.next_sync_at, s.last_synced_at,
+          s.stock_synced_at, s.orders_synced_at, s.last_error
+        FROM workspace_settings ws
+        CROSS JOIN LATERAL jsonb_each(ws.connectors) c
+        JOIN connector_syncs s ON s.workspace_id = ws.workspace_id AND s.connector = c.key
+        WHERE ws.workspace_id = $1 AND ($2::TEXT IS NULL OR c.key = $2)
+        ORDER BY c.key
+        "#,
+      workspace_id,
+      connector.map(|connector| connector.as_str())
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    rows.into_iter().map(ConnectorRow::into_connector).collect()
+  }
+}
+
+#[async_trait]
+impl ConnectorRepository for PostgresConnectorRepository {
+  async fn list(&self, workspace_id: Uuid) -> AppResult<Vec<Connector>> {
+    let connectors = self.find(workspace_id, None).await?;
+    Ok(connectors.into_iter().map(|(connector, _)| connector).collect())
+  }
+
+  async fn get(&self, workspace_id: Uuid, connector: ConnectorKind) -> AppResult<Option<Connector>> {
+    let connectors = self.find(workspace_id, Some(connector)).await?;
+    Ok(connectors.into_iter().next().map(|(connector, _)| connector))
+  }
+
+  async fn get_with_credentials(&self, workspace_id: Uuid, connector: ConnectorKind) -> AppResult<Option<(Connector, ConnectorCredentials)>> {
+    let Some((connector, credentials)) = self.find(workspace_id, Some(connector)).await?.into_iter().next() else {
+      return Ok(None);
+    };
+    let credentials = self.cipher.decrypt(CREDENTIALS, &credentials)?;
+    let credentials = serde_json::from_str(&credentials).map_err(|e| {
+      internal_error!(
+        "Invalid credentials stored for connector {} of workspace {}: {}",
+        connector.connector,
+        workspace_id,
+        e
+      )
+    })?;
+    Ok(Some((connector, credentials)))
+  }
+
+  async fn save(
+    &self,
+    workspace_id: Uuid,
+    connector: ConnectorKind,
+    settings: &ConnectorSettings,
+    credentials: &ConnectorCredentials,
+    updated_by: Uuid,
+  ) -> AppResult<Connector> {
+    let credentials = serde_json::to_string(credentials).map_err(|e| internal_error!("Failed to serialize connector credentials: {}", e))?;
+    let stored = StoredConnector {
+      store_url: settings.store_url.clone(),
+      location_id: settings.location_id.clone(),
+      sync_interval_minutes: settings.sync_interval_minutes,
+      credentials: self.cipher.encrypt(CREDENTIALS, &credentials)?,
+      updated_by: Some(updated_by),
+      updated_at: Utc::now(),
+    };
+    let stored = serde_json::to_value(&stored).map_err(|e| internal_error!("Failed to serialize connector: {}", e))?;
+
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!(
+      r#"
+        WITH settings AS (
+          INSERT INTO workspace_settings (workspace_id, connectors, updated_by)
+          VALUES ($1, jsonb_build_object($2::TEXT, $3::JSONB), $4)
+          ON CONFLICT (workspace_id) DO UPDATE
+          SET connectors = workspace_settings.connectors || EXCLUDED.connectors, updated_by = EXCLUDED.updated_by
+          RETURNING workspace_id
+        )
+        INSERT INTO connector_syncs (workspace_id, connector)
+        SELECT workspace_id, $2 FROM settings
+        ON CONFLICT (workspace_id, connector) DO UPDATE
+        SET next_sync_at = NOW(), stock_synced_at = NULL, orders_synced_at = NULL, last_error = NULL
+        "#,
+      workspace_id,
+      connector.as_str(),
+      stored,
+      updated_by
+    )
+    .execute(&mut *conn)
+    .await?;
+    drop(conn);
+
+    self
+      .get(workspace_id, connector)
+      .await?
+      .ok_or_else(|| internal_error!("Connector {} of workspace {} was not saved", connector, workspace_id))
+  }
+
+  async fn delete(&self, workspace_id: Uuid, connector: ConnectorKind) -> AppResult<bool> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!(
+      r#"
+        WITH removed AS (
+          UPDATE workspace_settings SET connectors = connectors - $2
+          WHERE workspace_id = $1 AND connectors ? $2
+          RETURNING workspace_id
+        )
+        DELETE FROM connector_syncs
+        WHERE workspace_id = $1 AND connector = $2 AND EXISTS (SELECT 1 FROM removed)
+        "#,
+      workspace_id,
+      connector.as_str()
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  async fn schedule_now(&self, workspace_id: Uuid, connector: ConnectorKind) -> AppResult<bool> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!(
+      "UPDATE connector_syncs SET next_sync_at = NOW() WHERE workspace_id = $1 AND connector = $2",
+      workspace_id,
+      connector.as_str()
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  async fn due(&self, now: DateTime<Utc>, limit: i64) -> AppResult<Vec<DueSync>> {
+    let mut conn = self.db.acquire().await?;
+    let rows = sqlx::query!(
+      r#"
+        SELECT workspace_id, connector, next_sync_at
+        FROM connector_syncs
+        WHERE next_sync_at <= $1
+        ORDER BY next_sync_at
+        LIMIT $2
+        "#,
+      now,
+      limit
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    // Platforms no longer supported by this build are left alone
+    let due = rows
+      .into_iter()
+      .filter_map(|row| {
+        Some(DueSync {
+          workspace_id: row.workspace_id,
+          connector: row.connector.parse().ok()?,
+          next_sync_at: row.next_sync_at,
+        })
+      })
+      .collect();
+    Ok(due)
+  }
+
+  async fn claim(&self, sync: &DueSync, next_sync_at: DateTime<Utc>) -> AppResult<bool> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!(
+      "UPDATE connector_syncs SET next_sync_at = $4 WHERE workspace_id = $1 AND connector = $2 AND next_sync_at = $3",
+      sync.workspace_id,
+      sync.connector.as_str(),
+      sync.next_sync_at,
+      next_sync_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  async fn stock_levels(&self, workspace_id: Uuid, changed_after: Option<DateTime<Utc>>) -> AppResult<Vec<StockLevel>> {
+    let mut conn = self.db.acquire().await?;
+    let levels = sqlx::query_as!(
+      StockLevel,
+      r#"
+        SELECT sku as "sku!", current_stock as "quantity!"
+        FROM products
+        WHERE workspace_id = $1 AND deleted_at IS NULL AND status = 'active' AND track_inventory AND sku IS NOT NULL AND sku <> '' AND current_stock IS NOT NULL
+          AND ($2::TIMESTAMPTZ IS NULL OR updated_at > $2)
+        ORDER BY sku
+        "#,
+      workspace_id,
+      changed_after
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(levels)
+  }
+
+  async fn save_orders(&self, workspace_id: Uuid, connector: ConnectorKind, orders: &[ExternalOrder]) -> AppResult<()> {
+    let mut conn = self.db.acquire().await?;
+    for order in orders {
+      sqlx::query!(
+        r#"
+          INSERT INTO connector_orders (workspace_id, connector, external_id, number, status, currency, total, ordered_at, payload)
+          VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+          ON CONFLICT (workspace_id, connector, external_id) DO UPDATE
+          SET number = EXCLUDED.number, status = EXCLUDED.status, currency = EXCLUDED.currency, total = EXCLUDED.total,
+              ordered_at = EXCLUDED.ordered_at, payload = EXCLUDED.payload
+          "#,
+        workspace_id,
+        connector.as_str(),
+        order.external_id,
+        order.number,
+        order.status,
+        order.currency,
+        order.total,
+        order.ordered_at,
+        order.payload
+      )
+      .execute(&mut *conn)
+      .await?;
+    }
+
+    Ok(())
+  }
+
+  async fn record_sync(&self, workspace_id: Uuid, connector: ConnectorKind, record: &SyncRecord<'_>) -> AppResult<()> {
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!(
+      r#"
+        UPDATE connector_syncs
+        SET last_synced_at = $3, stock_synced_at = COALESCE($4, stock_synced_at),
+            orders_synced_at = COALESCE($5, orders_synced_at), last_error = $6
+        WHERE workspace_id = $1 AND connector = $2
+        "#,
+      workspace_id,
+      connector.as_str(),
+      record.synced_at,
+      record.stock_synced_at,
+      record.orders_synced_at,
+      record.error
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn list_orders(&self, workspace_id: Uuid, connector: Option<ConnectorKind>, limit: i64) -> AppResult<Vec<ConnectorOrder>> {
+    let mut conn = self.db.acquire().await?;
+    let orders = sqlx::query_as!(
+      ConnectorOrder,
+      r#"
+        SELECT id, workspace_id, connector, external_id, number, status, currency, total, ordered_at, payload, pulled_at, updated_at
+        FROM connector_orders
+        WHERE workspace_id = $1 AND ($2::TEXT IS NULL OR connector = $2)
+        ORDER BY ordered_at DESC NULLS LAST, pulled_at DESC, id
+        LIMIT $3
+        "#,
+      workspace_id,
+      connector.map(|connector| connector.as_str()),
+      limit
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(orders)
+  }
+}
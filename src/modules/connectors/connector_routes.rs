@@ -0,0 +1,20 @@
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  routing::{delete, get, post, put},
+};
+
+use super::connector_handlers::{delete_connector, get_connector, list_connector_orders, list_connectors, save_connector, sync_connector};
+use crate::state::AppState;
+
+/// Connectors of the current workspace, mounted at `/api/v1/connectors` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/", get(list_connectors))
+    .route("/orders", get(list_connector_orders))
+    .route("/:connector", get(get_connector))
+    .route("/:connector", put(save_connector))
+    .route("/:connector", delete(delete_connector))
+    .route("/:connector/sync", post(sync_connector))
+}
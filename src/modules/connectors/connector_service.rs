@@ -0,0 +1,126 @@
+//! Syncing the workspaces with their stores.
+//!
+//! Every instance polls for due connectors; a sync is claimed by moving its `next_sync_at`
+//! first, so each runs once however many instances poll. A sync pushes the stock of the
+//! products changed since the last push, then pulls the orders changed since the last pull.
+//! A failed step is retried with the next sync, its error shown on the connector until then.
+//! Each sync runs on the tables of its workspace (see `utils::tenant_schema`).
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use tracing::{debug, warn};
+
+use super::{
+  connector_models::{Connector, ConnectorKind},
+  connector_repository::{DueSync, SyncRecord},
+};
+use crate::{AppResult, state::AppState, utils::db_session};
+
+/// Syncs run per check; the others are left for the next check.
+const BATCH_SIZE: i64 = 20;
+
+/// Runs the syncs due now and returns how many succeeded.
+pub async fn run_due(state: &AppState) -> AppResult<usize> {
+  let repository = &state.connector_repository;
+  let now = Utc::now();
+  let mut synced = 0;
+
+  for due in repository.due(now, BATCH_SIZE).await? {
+    let Some(connector) = repository.get(due.workspace_id, due.connector).await? else {
+      continue;
+    };
+    let next_sync_at = now + chrono::Duration::minutes(i64::from(connector.sync_interval_minutes));
+    if !repository.claim(&due, next_sync_at).await? {
+      continue;
+    }
+    if db_session::workspace_scope(&state.db, due.workspace_id, sync(state, &due)).await?? {
+      synced += 1;
+    }
+  }
+
+  Ok(synced)
+}
+
+/// Syncs one connector and records how it went, `false` when a step failed.
+async fn sync(state: &AppState, due: &DueSync) -> AppResult<bool> {
+  let repository = &state.connector_repository;
+  let Some((connector, credentials)) = repository.get_with_credentials(due.workspace_id, due.connector).await? else {
+    return Ok(false);
+  };
+  let synced_at = Utc::now();
+  let store = match state.connector_factory.connect(&connector, &credentials) {
+    Ok(store) => store,
+    Err(e) => {
+      warn!("Could not connect to {}: {}", describe(&connector), e);
+      let record = SyncRecord {
+        synced_at,
+        stock_synced_at: None,
+        orders_synced_at: None,
+        error: Some("Could not connect to the store"),
+      };
+      repository.record_sync(connector.workspace_id, connector.connector, &record).await?;
+      return Ok(false);
+    }
+  };
+
+  let mut errors = Vec::new();
+  let levels = repository.stock_levels(connector.workspace_id, connector.sync.stock_synced_at).await?;
+  let stock_synced_at = match store.push_stock(&levels).await {
+    Ok(pushed) => {
+      debug!("Pushed {} of {} stock levels to {}", pushed, levels.len(), describe(&connector));
+      Some(synced_at)
+    }
+    Err(e) => {
+      warn!("Failed to push stock levels to {}: {}", describe(&connector), e);
+      errors.push("Stock levels could not be pushed");
+      None
+    }
+  };
+
+  let orders_synced_at = match store.pull_orders(connector.sync.orders_synced_at).await {
+    Ok(orders) => {
+      repository.save_orders(connector.workspace_id, connector.connector, &orders).await?;
+      debug!("Pulled {} orders from {}", orders.len(), describe(&connector));
+      orders.iter().map(|order| order.updated_at).max().or(connector.sync.orders_synced_at)
+    }
+    Err(e) => {
+      warn!("Failed to pull orders from {}: {}", describe(&connector), e);
+      errors.push("Orders could not be pulled");
+      None
+    }
+  };
+
+  let error = (!errors.is_empty()).then(|| errors.join("; "));
+  let record = SyncRecord {
+    synced_at,
+    stock_synced_at,
+    orders_synced_at,
+    error: error.as_deref(),
+  };
+  repository.record_sync(connector.workspace_id, connector.connector, &record).await?;
+  Ok(error.is_none())
+}
+
+fn describe(connector: &Connector) -> String {
+  let platform = match connector.connector {
+    ConnectorKind::Shopify => "Shopify",
+    ConnectorKind::WooCommerce => "WooCommerce",
+  };
+  format!("the {} store of workspace {}", platform, connector.workspace_id)
+}
+
+/// Checks for due syncs every `config.connectors.check_interval_secs`, for as long as the server runs.
+pub async fn run_scheduler(state: Arc<AppState>) {
+  let mut interval = tokio::time::interval(Duration::from_secs(state.config.connectors.check_interval_secs));
+  loop {
+    interval.tick().await;
+    let result = run_due(&state).await;
+    state.task_health.record("connector_scheduler", &result);
+    match result {
+      Ok(0) => {}
+      Ok(synced) => debug!("Synced {} connectors", synced),
+      Err(e) => warn!("Failed to run connector syncs, retrying with the next check: {}", e),
+    }
+  }
+}
@@ -0,0 +1,18 @@
+//! Connectors to online stores, compiled with the `connectors` feature.
+//!
+//! Admins of a workspace connect it to a Shopify or WooCommerce store under
+//! `/api/v1/connectors/{shopify|woocommerce}`. The settings are kept with the other workspace
+//! settings, the credentials encrypted with the field encryption keys and never returned. Each
+//! connector is synced on a schedule (see `connector_service`): the stock of the published tracked
+//! products is pushed to the store, matched by SKU, and the store's orders are pulled into
+//! `connector_orders`, listed under `/api/v1/connectors/orders` until they are mapped into
+//! records of the workspace.
+
+pub mod connector_handlers;
+pub mod connector_models;
+pub mod connector_repository;
+pub mod connector_routes;
+pub mod connector_service;
+pub mod shopify;
+pub mod store_connector;
+pub mod woocommerce;
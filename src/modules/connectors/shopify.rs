@@ -0,0 +1,177 @@
+//! Shopify stores, through the Admin API with the access token of a custom app. Stock is set
+//! at one location with GraphQL; orders are read with REST.
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+
+use super::{
+  connector_models::{Connector, ConnectorCredentials, ExternalOrder, StockLevel},
+  store_connector::{MAX_ORDERS_PER_SYNC, StoreConnector},
+};
+use crate::{AppResult, internal_error};
+
+const API_VERSION: &str = "2024-10";
+/// Quantities set per mutation, the most Shopify accepts.
+const MAX_QUANTITIES_PER_MUTATION: usize = 250;
+
+const FIND_VARIANTS: &str = r#"
+query FindVariants($query: String!) {
+  productVariants(first: 10, query: $query) {
+    nodes { sku inventoryItem { id } }
+  }
+}"#;
+
+const SET_QUANTITIES: &str = r#"
+mutation SetQuantities($input: InventorySetQuantitiesInput!) {
+  inventorySetQuantities(input: $input) {
+    userErrors { field message }
+  }
+}"#;
+
+pub struct ShopifyConnector {
+  http: reqwest::Client,
+  base_url: String,
+  access_token: String,
+  location_id: String,
+}
+
+impl ShopifyConnector {
+  pub fn new(http: reqwest::Client, connector: &Connector, credentials: &ConnectorCredentials) -> AppResult<Self> {
+    let access_token = credentials
+      .access_token
+      .clone()
+      .ok_or_else(|| internal_error!("The Shopify connector of workspace {} has no access token", connector.workspace_id))?;
+    let location_id = connector
+      .location_id
+      .as_deref()
+      .ok_or_else(|| internal_error!("The Shopify connector of workspace {} has no location", connector.workspace_id))?;
+    // Locations are shown by number in the Shopify admin, GraphQL wants their global ID
+    let location_id = if location_id.starts_with("gid://") {
+      location_id.to_string()
+    } else {
+      format!("gid://shopify/Location/{}", location_id)
+    };
+
+    Ok(Self {
+      http,
+      base_url: format!("{}/admin/api/{}", connector.store_url.trim_end_matches('/'), API_VERSION),
+      access_token,
+      location_id,
+    })
+  }
+
+  async fn send(&self, request: reqwest::RequestBuilder) -> AppResult<Value> {
+    let response = request
+      .header("X-Shopify-Access-Token", &self.access_token)
+      .send()
+      .await
+      .map_err(|e| internal_error!("Request to Shopify failed: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+      return Err(internal_error!("Shopify responded {}", status));
+    }
+    response
+      .json()
+      .await
+      .map_err(|e| internal_error!("Shopify sent an invalid response: {}", e))
+  }
+
+  async fn graphql(&self, query: &str, variables: Value) -> AppResult<Value> {
+    let request = self
+      .http
+      .post(format!("{}/graphql.json", self.base_url))
+      .json(&json!({ "query": query, "variables": variables }));
+    let mut body = self.send(request).await?;
+    if let Some(errors) = body.get("errors") {
+      return Err(internal_error!("Shopify refused the query: {}", errors));
+    }
+    Ok(body["data"].take())
+  }
+
+  /// The inventory item of the variant with `sku`, `None` when the store has none.
+  async fn inventory_item(&self, sku: &str) -> AppResult<Option<String>> {
+    let query = format!("sku:\"{}\"", sku.replace('\\', "\\\\").replace('"', "\\\""));
+    let data = self.graphql(FIND_VARIANTS, json!({ "query": query })).await?;
+    // The search also matches similar SKUs
+    let item = data["productVariants"]["nodes"]
+      .as_array()
+      .into_iter()
+      .flatten()
+      .find(|variant| variant["sku"].as_str() == Some(sku))
+      .and_then(|variant| variant["inventoryItem"]["id"].as_str())
+      .map(str::to_string);
+    Ok(item)
+  }
+}
+
+#[async_trait]
+impl StoreConnector for ShopifyConnector {
+  async fn push_stock(&self, levels: &[StockLevel]) -> AppResult<usize> {
+    let mut quantities = Vec::with_capacity(levels.len());
+    for level in levels {
+      if let Some(item) = self.inventory_item(&level.sku).await? {
+        quantities.push(json!({ "inventoryItemId": item, "locationId": self.location_id, "quantity": level.quantity }));
+      }
+    }
+
+    for chunk in quantities.chunks(MAX_QUANTITIES_PER_MUTATION) {
+      let input = json!({
+        "name": "available",
+        "reason": "correction",
+        "ignoreCompareQuantity": true,
+        "quantities": chunk,
+      });
+      let data = self.graphql(SET_QUANTITIES, json!({ "input": input })).await?;
+      let errors = &data["inventorySetQuantities"]["userErrors"];
+      if errors.as_array().is_some_and(|errors| !errors.is_empty()) {
+        return Err(internal_error!("Shopify refused the stock levels: {}", errors));
+      }
+    }
+    Ok(quantities.len())
+  }
+
+  async fn pull_orders(&self, since: Option<DateTime<Utc>>) -> AppResult<Vec<ExternalOrder>> {
+    let mut query = vec![
+      ("status", "any".to_string()),
+      ("order", "updated_at asc".to_string()),
+      ("limit", MAX_ORDERS_PER_SYNC.to_string()),
+    ];
+    if let Some(since) = since {
+      query.push(("updated_at_min", since.to_rfc3339()));
+    }
+    let request = self.http.get(format!("{}/orders.json", self.base_url)).query(&query);
+    let mut body = self.send(request).await?;
+
+    let orders = match body["orders"].take() {
+      Value::Array(orders) => orders.into_iter().filter_map(order).collect(),
+      _ => return Err(internal_error!("Shopify sent orders without an orders list")),
+    };
+    Ok(orders)
+  }
+}
+
+/// An order of the REST API, `None` without an ID or update time.
+fn order(payload: Value) -> Option<ExternalOrder> {
+  let time = |field: &str| {
+    payload[field]
+      .as_str()
+      .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+      .map(|value| value.with_timezone(&Utc))
+  };
+  let text = |field: &str| payload[field].as_str().map(str::to_string);
+
+  Some(ExternalOrder {
+    external_id: payload["id"].as_u64()?.to_string(),
+    number: text("name"),
+    status: text("financial_status"),
+    currency: text("currency"),
+    total: payload["total_price"].as_str().and_then(|total| Decimal::from_str(total).ok()),
+    ordered_at: time("created_at"),
+    updated_at: time("updated_at")?,
+    payload,
+  })
+}
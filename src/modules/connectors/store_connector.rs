@@ -0,0 +1,59 @@
+//! The `StoreConnector` trait the syncs talk to stores through, and the factory building one per
+//! platform.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::{
+  connector_models::{Connector, ConnectorCredentials, ConnectorKind, ExternalOrder, StockLevel},
+  shopify::ShopifyConnector,
+  woocommerce::WooCommerceConnector,
+};
+use crate::AppResult;
+
+/// Orders pulled per sync; the others are pulled by the next syncs.
+pub const MAX_ORDERS_PER_SYNC: usize = 250;
+
+/// An online store, as seen by a sync.
+#[async_trait]
+pub trait StoreConnector: Send + Sync {
+  /// Sets the stock of the store's products with the SKUs of `levels`, returning how many were
+  /// found. SKUs the store does not sell are skipped.
+  async fn push_stock(&self, levels: &[StockLevel]) -> AppResult<usize>;
+  /// The orders changed since `since`, all of them when `None`, oldest change first and at most
+  /// `MAX_ORDERS_PER_SYNC`.
+  async fn pull_orders(&self, since: Option<DateTime<Utc>>) -> AppResult<Vec<ExternalOrder>>;
+}
+
+/// Builds the connector of a store. Tests replace it with `AppStateBuilder::with_connector_factory`.
+pub trait ConnectorFactory: Send + Sync {
+  fn connect(&self, connector: &Connector, credentials: &ConnectorCredentials) -> AppResult<Box<dyn StoreConnector>>;
+}
+
+/// Connects to the stores over HTTP. Redirects are not followed, so credentials are only sent
+/// to the configured store.
+pub struct HttpConnectorFactory {
+  http: reqwest::Client,
+}
+
+impl HttpConnectorFactory {
+  pub fn new(timeout: Duration) -> Self {
+    let http = reqwest::Client::builder()
+      .timeout(timeout)
+      .redirect(reqwest::redirect::Policy::none())
+      .build()
+      .unwrap_or_else(|_| reqwest::Client::new());
+    Self { http }
+  }
+}
+
+impl ConnectorFactory for HttpConnectorFactory {
+  fn connect(&self, connector: &Connector, credentials: &ConnectorCredentials) -> AppResult<Box<dyn StoreConnector>> {
+    Ok(match connector.connector {
+      ConnectorKind::Shopify => Box::new(ShopifyConnector::new(self.http.clone(), connector, credentials)?),
+      ConnectorKind::WooCommerce => Box::new(WooCommerceConnector::new(self.http.clone(), connector, credentials)?),
+    })
+  }
+}
@@ -0,0 +1,153 @@
+//! WooCommerce stores, through the REST API (`/wp-json/wc/v3`) with a consumer key pair sent as
+//! basic authentication, which WooCommerce only accepts over HTTPS.
+
+use std::{collections::HashMap, str::FromStr};
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use reqwest::Method;
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+
+use super::{
+  connector_models::{Connector, ConnectorCredentials, ExternalOrder, StockLevel},
+  store_connector::{MAX_ORDERS_PER_SYNC, StoreConnector},
+};
+use crate::{AppResult, internal_error};
+
+/// Records per page and per batch update, the most WooCommerce accepts.
+const PAGE_SIZE: usize = 100;
+/// How WooCommerce writes the GMT dates of its records.
+const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+pub struct WooCommerceConnector {
+  http: reqwest::Client,
+  base_url: String,
+  consumer_key: String,
+  consumer_secret: String,
+}
+
+impl WooCommerceConnector {
+  pub fn new(http: reqwest::Client, connector: &Connector, credentials: &ConnectorCredentials) -> AppResult<Self> {
+    let (Some(consumer_key), Some(consumer_secret)) = (&credentials.consumer_key, &credentials.consumer_secret) else {
+      return Err(internal_error!(
+        "The WooCommerce connector of workspace {} has no consumer key",
+        connector.workspace_id
+      ));
+    };
+
+    Ok(Self {
+      http,
+      base_url: format!("{}/wp-json/wc/v3", connector.store_url.trim_end_matches('/')),
+      consumer_key: consumer_key.clone(),
+      consumer_secret: consumer_secret.clone(),
+    })
+  }
+
+  async fn send(&self, method: Method, path: &str, query: &[(&str, String)], body: Option<Value>) -> AppResult<Value> {
+    let mut request = self
+      .http
+      .request(method, format!("{}/{}", self.base_url, path))
+      .basic_auth(&self.consumer_key, Some(&self.consumer_secret))
+      .query(query);
+    if let Some(body) = body {
+      request = request.json(&body);
+    }
+    let response = request
+      .send()
+      .await
+      .map_err(|e| internal_error!("Request to WooCommerce failed: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+      return Err(internal_error!("WooCommerce responded {}", status));
+    }
+    response
+      .json()
+      .await
+      .map_err(|e| internal_error!("WooCommerce sent an invalid response: {}", e))
+  }
+}
+
+#[async_trait]
+impl StoreConnector for WooCommerceConnector {
+  async fn push_stock(&self, levels: &[StockLevel]) -> AppResult<usize> {
+    // Products are looked up by comma-separated SKUs, which cannot hold a comma themselves
+    let levels: Vec<&StockLevel> = levels.iter().filter(|level| !level.sku.contains(',')).collect();
+    let mut pushed = 0;
+
+    for chunk in levels.chunks(PAGE_SIZE) {
+      let skus = chunk.iter().map(|level| level.sku.as_str()).collect::<Vec<_>>().join(",");
+      let query = [("sku", skus), ("per_page", PAGE_SIZE.to_string())];
+      let products = self.send(Method::GET, "products", &query, None).await?;
+      let ids: HashMap<&str, u64> = products
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|product| Some((product["sku"].as_str()?, product["id"].as_u64()?)))
+        .collect();
+
+      let updates: Vec<Value> = chunk
+        .iter()
+        .filter_map(|level| {
+          let id = ids.get(level.sku.as_str())?;
+          Some(json!({ "id": id, "manage_stock": true, "stock_quantity": level.quantity }))
+        })
+        .collect();
+      if updates.is_empty() {
+        continue;
+      }
+      self.send(Method::POST, "products/batch", &[], Some(json!({ "update": updates }))).await?;
+      pushed += updates.len();
+    }
+    Ok(pushed)
+  }
+
+  async fn pull_orders(&self, since: Option<DateTime<Utc>>) -> AppResult<Vec<ExternalOrder>> {
+    let mut orders = Vec::new();
+    for page in 1.. {
+      let mut query = vec![
+        ("orderby", "modified".to_string()),
+        ("order", "asc".to_string()),
+        ("per_page", PAGE_SIZE.to_string()),
+        ("page", page.to_string()),
+        ("dates_are_gmt", "true".to_string()),
+      ];
+      if let Some(since) = since {
+        query.push(("modified_after", since.naive_utc().format(DATE_FORMAT).to_string()));
+      }
+      let Value::Array(page) = self.send(Method::GET, "orders", &query, None).await? else {
+        return Err(internal_error!("WooCommerce sent orders that are not a list"));
+      };
+
+      let is_last = page.len() < PAGE_SIZE;
+      orders.extend(page.into_iter().filter_map(order));
+      if is_last || orders.len() >= MAX_ORDERS_PER_SYNC {
+        break;
+      }
+    }
+    orders.truncate(MAX_ORDERS_PER_SYNC);
+    Ok(orders)
+  }
+}
+
+/// An order of the REST API, `None` without an ID or modification time.
+fn order(payload: Value) -> Option<ExternalOrder> {
+  let time = |field: &str| {
+    payload[field]
+      .as_str()
+      .and_then(|value| NaiveDateTime::parse_from_str(value, DATE_FORMAT).ok())
+      .map(|value| value.and_utc())
+  };
+  let text = |field: &str| payload[field].as_str().map(str::to_string);
+
+  Some(ExternalOrder {
+    external_id: payload["id"].as_u64()?.to_string(),
+    number: text("number"),
+    status: text("status"),
+    currency: text("currency"),
+    total: payload["total"].as_str().and_then(|total| Decimal::from_str(total).ok()),
+    ordered_at: time("date_created_gmt"),
+    updated_at: time("date_modified_gmt")?,
+    payload,
+  })
+}
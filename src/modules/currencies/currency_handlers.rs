@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use axum::{
+  extract::{Path, State, rejection::JsonRejection},
+  response::Json,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use validator::Validate;
+
+use super::currency_models::{DEFAULT_HISTORY_DAYS, ExchangeRate, ExchangeRatesQuery, MAX_HISTORY_DAYS, OverrideRateRequest, RateHistoryQuery};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{
+    RequireRole, RequiredWorkspace, ValidatedQuery,
+    workspace::role::{Admin, Member},
+  },
+  modules::auth::current_user::CurrentUser,
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// The ISO 4217 code of `currency` in upper case, other than the base currency.
+fn parse_currency(state: &AppState, currency: &str) -> AppResult<String> {
+  let currency = currency.to_ascii_uppercase();
+  if currency.len() != 3 || !currency.bytes().all(|b| b.is_ascii_uppercase()) {
+    return Err(AppError::validation("currency", "Currency must be a three-letter ISO 4217 code"));
+  }
+  if currency == state.config.exchange_rates.base_currency {
+    return Err(AppError::validation("currency", "The base currency has no exchange rate"));
+  }
+  Ok(currency)
+}
+
+fn parse_date(date: &str) -> AppResult<NaiveDate> {
+  NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| AppError::validation("date", "Date must be formatted as YYYY-MM-DD"))
+}
+
+/// The rate of each currency in effect on a day, today by default: the latest one on or before
+/// it, the workspace's own rates replacing the provider's.
+pub async fn list_exchange_rates(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  ValidatedQuery(query): ValidatedQuery<ExchangeRatesQuery>,
+) -> AppResult<Json<ApiResponse<Vec<ExchangeRate>>>> {
+  let date = query.date.unwrap_or_else(|| Utc::now().date_naive());
+  let rates = state
+    .exchange_rate_repository
+    .rates_on(workspace_id, &state.config.exchange_rates.base_currency, date)
+    .await?;
+  Ok(Json(ApiResponse::success(rates, "Exchange rates retrieved successfully")))
+}
+
+/// The rates of a currency by day, newest first, over the last `DEFAULT_HISTORY_DAYS` by default.
+pub async fn get_rate_history(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  Path(currency): Path<String>,
+  ValidatedQuery(query): ValidatedQuery<RateHistoryQuery>,
+) -> AppResult<Json<ApiResponse<Vec<ExchangeRate>>>> {
+  let currency = parse_currency(&state, &currency)?;
+  let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+  let from = query.from.unwrap_or(to - Duration::days(DEFAULT_HISTORY_DAYS));
+  if from > to {
+    return Err(AppError::validation("from", "The history must start before it ends"));
+  }
+  if (to - from).num_days() >= MAX_HISTORY_DAYS {
+    return Err(AppError::validation(
+      "from",
+      &format!("The history covers at most {} days", MAX_HISTORY_DAYS),
+    ));
+  }
+
+  let rates = state
+    .exchange_rate_repository
+    .history(workspace_id, &state.config.exchange_rates.base_currency, &currency, from, to)
+    .await?;
+  Ok(Json(ApiResponse::success(rates, "Exchange rate history retrieved successfully")))
+}
+
+/// Sets the rate of a currency on a day for the workspace, replacing the provider's from that day
+/// until the next rate.
+pub async fn override_exchange_rate(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  Path((currency, date)): Path<(String, String)>,
+  payload: Result<Json<OverrideRateRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<ExchangeRate>>> {
+  let currency = parse_currency(&state, &currency)?;
+  let date = parse_date(&date)?;
+  let Json(request) = payload?;
+  request.validate()?;
+  if request.rate <= Decimal::ZERO {
+    return Err(AppError::validation("rate", "Rate must be greater than zero"));
+  }
+
+  let rate = state
+    .exchange_rate_repository
+    .set_override(
+      workspace_id,
+      &state.config.exchange_rates.base_currency,
+      &currency,
+      date,
+      request.rate,
+      current_user.user_id,
+    )
+    .await?;
+  Ok(Json(ApiResponse::success(rate, "Exchange rate saved successfully")))
+}
+
+/// Removes the rate set by the workspace, the provider's applying again.
+pub async fn delete_exchange_rate_override(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  Path((currency, date)): Path<(String, String)>,
+) -> AppResult<Json<ApiResponse<()>>> {
+  let currency = parse_currency(&state, &currency)?;
+  let date = parse_date(&date)?;
+  let deleted = state
+    .exchange_rate_repository
+    .delete_override(workspace_id, &state.config.exchange_rates.base_currency, &currency, date)
+    .await?;
+  if !deleted {
+    return Err(AppError::not_found("Exchange rate override"));
+  }
+  Ok(Json(ApiResponse::success((), "Exchange rate override deleted successfully")))
+}
@@ -0,0 +1,51 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Days of history returned when no range is given.
+pub const DEFAULT_HISTORY_DAYS: i64 = 30;
+/// Longest range of history returned at once.
+pub const MAX_HISTORY_DAYS: i64 = 366;
+/// Source of the rates set by the workspace.
+pub const MANUAL_SOURCE: &str = "manual";
+
+/// The rate of a currency on a day: one unit of `currency` is worth `rate` of `base`.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ExchangeRate {
+  pub currency: String,
+  pub base: String,
+  pub rate_date: NaiveDate,
+  pub rate: Decimal,
+  /// The provider the rate was fetched from, or `manual`.
+  pub source: String,
+  /// Whether the workspace set the rate itself, replacing the provider's.
+  pub is_override: bool,
+  pub updated_by: Option<Uuid>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ExchangeRatesQuery {
+  /// The rates in effect on this day, today when omitted.
+  pub date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct RateHistoryQuery {
+  /// First day of the history, `DEFAULT_HISTORY_DAYS` before `to` when omitted.
+  pub from: Option<NaiveDate>,
+  /// Last day of the history, today when omitted.
+  pub to: Option<NaiveDate>,
+}
+
+/// Sets the rate of a currency on a day for the workspace, replacing the provider's.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct OverrideRateRequest {
+  pub rate: Decimal,
+}
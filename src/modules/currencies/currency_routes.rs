@@ -0,0 +1,18 @@
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  routing::{delete, get, put},
+};
+
+use super::currency_handlers::{delete_exchange_rate_override, get_rate_history, list_exchange_rates, override_exchange_rate};
+use crate::state::AppState;
+
+/// Exchange rates of the current workspace, mounted at `/api/v1/exchange-rates` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/", get(list_exchange_rates))
+    .route("/:currency/history", get(get_rate_history))
+    .route("/:currency/:date", put(override_exchange_rate))
+    .route("/:currency/:date", delete(delete_exchange_rate_override))
+}
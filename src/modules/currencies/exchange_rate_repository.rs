@@ -0,0 +1,157 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::currency_models::{ExchangeRate, MANUAL_SOURCE};
+use crate::{AppResult, utils::DbExecutor};
+
+#[async_trait]
+pub trait ExchangeRateRepository: Send + Sync {
+  /// The latest rate of each currency on or before `date`, those of the workspace first.
+  async fn rates_on(&self, workspace_id: Uuid, base: &str, date: NaiveDate) -> AppResult<Vec<ExchangeRate>>;
+  /// The rates of `currency` from `from` to `to`, newest first, those of the workspace first.
+  async fn history(&self, workspace_id: Uuid, base: &str, currency: &str, from: NaiveDate, to: NaiveDate) -> AppResult<Vec<ExchangeRate>>;
+  /// Saves the rates of a day fetched from `source`, replacing those fetched before.
+  async fn save_fetched(&self, base: &str, date: NaiveDate, rates: &[(String, Decimal)], source: &str) -> AppResult<()>;
+  /// Sets the rate of `currency` on `date` for the workspace.
+  async fn set_override(
+    &self,
+    workspace_id: Uuid,
+    base: &str,
+    currency: &str,
+    date: NaiveDate,
+    rate: Decimal,
+    updated_by: Uuid,
+  ) -> AppResult<ExchangeRate>;
+  /// Removes the rate set by the workspace, `false` when it had none.
+  async fn delete_override(&self, workspace_id: Uuid, base: &str, currency: &str, date: NaiveDate) -> AppResult<bool>;
+}
+
+pub struct PostgresExchangeRateRepository {
+  db: DbExecutor,
+}
+
+impl PostgresExchangeRateRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl ExchangeRateRepository for PostgresExchangeRateRepository {
+  async fn rates_on(&self, workspace_id: Uuid, base: &str, date: NaiveDate) -> AppResult<Vec<ExchangeRate>> {
+    let mut conn = self.db.acquire().await?;
+    let rates = sqlx::query_as!(
+      ExchangeRate,
+      r#"
+        SELECT DISTINCT ON (currency) currency as "currency!", base as "base!", rate_date, rate, source,
+          workspace_id IS NOT NULL as "is_override!", updated_by, updated_at
+        FROM exchange_rates
+        WHERE base = $1 AND rate_date <= $2 AND (workspace_id IS NULL OR workspace_id = $3)
+        ORDER BY currency, rate_date DESC, workspace_id NULLS LAST
+        "#,
+      base,
+      date,
+      workspace_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rates)
+  }
+
+  async fn history(&self, workspace_id: Uuid, base: &str, currency: &str, from: NaiveDate, to: NaiveDate) -> AppResult<Vec<ExchangeRate>> {
+    let mut conn = self.db.acquire().await?;
+    let rates = sqlx::query_as!(
+      ExchangeRate,
+      r#"
+        SELECT DISTINCT ON (rate_date) currency as "currency!", base as "base!", rate_date, rate, source,
+          workspace_id IS NOT NULL as "is_override!", updated_by, updated_at
+        FROM exchange_rates
+        WHERE base = $1 AND currency = $2 AND rate_date BETWEEN $3 AND $4 AND (workspace_id IS NULL OR workspace_id = $5)
+        ORDER BY rate_date DESC, workspace_id NULLS LAST
+        "#,
+      base,
+      currency,
+      from,
+      to,
+      workspace_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rates)
+  }
+
+  async fn save_fetched(&self, base: &str, date: NaiveDate, rates: &[(String, Decimal)], source: &str) -> AppResult<()> {
+    let (currencies, rates): (Vec<String>, Vec<Decimal>) = rates.iter().cloned().unzip();
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!(
+      r#"
+        INSERT INTO exchange_rates (base, currency, rate_date, rate, source)
+        SELECT $1, currency, $2, rate, $3
+        FROM UNNEST($4::TEXT[], $5::NUMERIC[]) AS fetched(currency, rate)
+        ON CONFLICT ON CONSTRAINT exchange_rates_unique DO UPDATE
+        SET rate = EXCLUDED.rate, source = EXCLUDED.source
+        "#,
+      base,
+      date,
+      source,
+      &currencies,
+      &rates
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn set_override(
+    &self,
+    workspace_id: Uuid,
+    base: &str,
+    currency: &str,
+    date: NaiveDate,
+    rate: Decimal,
+    updated_by: Uuid,
+  ) -> AppResult<ExchangeRate> {
+    let mut conn = self.db.acquire().await?;
+    let rate = sqlx::query_as!(
+      ExchangeRate,
+      r#"
+        INSERT INTO exchange_rates (workspace_id, base, currency, rate_date, rate, source, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT ON CONSTRAINT exchange_rates_unique DO UPDATE
+        SET rate = EXCLUDED.rate, updated_by = EXCLUDED.updated_by
+        RETURNING currency as "currency!", base as "base!", rate_date, rate, source, TRUE as "is_override!", updated_by, updated_at
+        "#,
+      workspace_id,
+      base,
+      currency,
+      date,
+      rate,
+      MANUAL_SOURCE,
+      updated_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(rate)
+  }
+
+  async fn delete_override(&self, workspace_id: Uuid, base: &str, currency: &str, date: NaiveDate) -> AppResult<bool> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!(
+      "DELETE FROM exchange_rates WHERE workspace_id = $1 AND base = $2 AND currency = $3 AND rate_date = $4",
+      workspace_id,
+      base,
+      currency,
+      date
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+}
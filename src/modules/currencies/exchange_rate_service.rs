@@ -0,0 +1,72 @@
+//! Fetching the daily exchange rates.
+//!
+//! Every instance fetches the latest rates at its interval. Saving them is idempotent, the rates
+//! of a day replacing those fetched before for the same day, so no instance needs to claim the
+//! sync. A failed fetch is retried with the next one.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use tracing::{debug, info, warn};
+
+use super::rate_provider::Quotes;
+use crate::{AppResult, internal_error, state::AppState};
+
+/// Decimal places the rates are stored with.
+const RATE_SCALE: u32 = 10;
+
+/// Fetches the latest rates into the base currency and returns their day, `None` without a provider.
+pub async fn sync(state: &AppState) -> AppResult<Option<NaiveDate>> {
+  let Some(provider) = &state.rate_provider else {
+    return Ok(None);
+  };
+  let quotes = provider.latest().await?;
+  let base = &state.config.exchange_rates.base_currency;
+  let rates = rates_in(base, &quotes).ok_or_else(|| internal_error!("{} has no rate for the base currency {}", provider.name(), base))?;
+
+  state
+    .exchange_rate_repository
+    .save_fetched(base, quotes.date, &rates, provider.name())
+    .await?;
+  Ok(Some(quotes.date))
+}
+
+/// The value of one unit of each quoted currency in `base`, `None` when `base` is not quoted.
+pub fn rates_in(base: &str, quotes: &Quotes) -> Option<Vec<(String, Decimal)>> {
+  let quote = |currency: &str| match currency == quotes.anchor {
+    true => Some(Decimal::ONE),
+    false => quotes.quotes.get(currency).copied(),
+  };
+  let base_quote = quote(base).filter(|quote| !quote.is_zero())?;
+
+  let currencies = quotes.quotes.keys().map(String::as_str).chain([quotes.anchor.as_str()]);
+  let mut rates: Vec<(String, Decimal)> = currencies
+    .filter(|currency| *currency != base)
+    .filter_map(|currency| {
+      let rate = base_quote.checked_div(quote(currency)?)?.round_dp(RATE_SCALE);
+      (rate > Decimal::ZERO).then(|| (currency.to_string(), rate))
+    })
+    .collect();
+  rates.sort();
+  rates.dedup_by(|a, b| a.0 == b.0);
+  Some(rates)
+}
+
+/// Fetches the rates every `config.exchange_rates.sync_interval_secs`, for as long as the server runs.
+pub async fn run_scheduler(state: Arc<AppState>) {
+  if state.rate_provider.is_none() {
+    info!("Exchange rates are not fetched: the provider has no credentials");
+    return;
+  }
+  let mut interval = tokio::time::interval(Duration::from_secs(state.config.exchange_rates.sync_interval_secs));
+  loop {
+    interval.tick().await;
+    let result = sync(&state).await;
+    state.task_health.record("exchange_rate_sync", &result);
+    match result {
+      Ok(date) => debug!("Fetched the exchange rates of {:?}", date),
+      Err(e) => warn!("Failed to fetch exchange rates, retrying with the next sync: {}", e),
+    }
+  }
+}
@@ -0,0 +1,15 @@
+//! Exchange rates, compiled with the `currencies` feature.
+//!
+//! The daily rates of the configured provider (the ECB or openexchangerates.org, see
+//! `rate_provider`) are fetched on a schedule by `exchange_rate_service` and stored as the value
+//! of one unit of each currency in the base currency (`EXCHANGE_RATE_BASE`). They are shared by
+//! all workspaces, each of which can set its own rate of a currency on a day under
+//! `/api/v1/exchange-rates/{currency}/{date}`, replacing the provider's. Days without a rate use
+//! the latest one before them.
+
+pub mod currency_handlers;
+pub mod currency_models;
+pub mod currency_routes;
+pub mod exchange_rate_repository;
+pub mod exchange_rate_service;
+pub mod rate_provider;
@@ -0,0 +1,147 @@
+//! Where exchange rates are fetched from: the `RateProvider` trait, the European Central Bank and
+//! openexchangerates.org.
+
+use std::{collections::BTreeMap, str::FromStr, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::{
+  AppResult,
+  config::{ExchangeRateConfig, ExchangeRateSource},
+  internal_error,
+};
+
+const ECB_DAILY_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.xml";
+const OPENEXCHANGERATES_URL: &str = "https://openexchangerates.org/api/latest.json";
+
+/// The latest rates published by a provider, as units of each currency per one unit of `anchor`.
+#[derive(Debug, Clone)]
+pub struct Quotes {
+  pub date: NaiveDate,
+  pub anchor: String,
+  pub quotes: BTreeMap<String, Decimal>,
+}
+
+/// Fetches the latest rates. Tests replace it with `AppStateBuilder::with_rate_provider`.
+#[async_trait]
+pub trait RateProvider: Send + Sync {
+  /// Name of the provider, stored as the source of its rates.
+  fn name(&self) -> &'static str;
+  async fn latest(&self) -> AppResult<Quotes>;
+}
+
+/// The provider of `config`, `None` while openexchangerates.org has no app ID.
+pub fn from_config(config: &ExchangeRateConfig) -> Option<Arc<dyn RateProvider>> {
+  let http = reqwest::Client::builder()
+    .timeout(Duration::from_secs(config.timeout_secs))
+    .build()
+    .unwrap_or_else(|_| reqwest::Client::new());
+  match config.provider {
+    ExchangeRateSource::Ecb => Some(Arc::new(EcbProvider { http })),
+    ExchangeRateSource::OpenExchangeRates => {
+      let app_id = config.openexchangerates_app_id.clone()?;
+      Some(Arc::new(OpenExchangeRatesProvider { http, app_id }))
+    }
+  }
+}
+
+async fn get(request: reqwest::RequestBuilder, provider: &str) -> AppResult<String> {
+  let response = request
+    .send()
+    .await
+    .map_err(|e| internal_error!("Request to {} failed: {}", provider, e))?;
+  let status = response.status();
+  if !status.is_success() {
+    return Err(internal_error!("{} responded {}", provider, status));
+  }
+  response
+    .text()
+    .await
+    .map_err(|e| internal_error!("Failed to read the rates of {}: {}", provider, e))
+}
+
+/// The daily euro reference rates of the European Central Bank, published on working days
+/// around 16:00 CET.
+pub struct EcbProvider {
+  http: reqwest::Client,
+}
+
+#[async_trait]
+impl RateProvider for EcbProvider {
+  fn name(&self) -> &'static str {
+    "ecb"
+  }
+
+  async fn latest(&self) -> AppResult<Quotes> {
+    let xml = get(self.http.get(ECB_DAILY_URL), "ECB").await?;
+    parse_ecb(&xml).ok_or_else(|| internal_error!("ECB sent rates that could not be read"))
+  }
+}
+
+/// Reads the `<Cube time='…'>` of the day and its `<Cube currency='…' rate='…'/>` entries from
+/// the daily file of the ECB, `None` when it has no rates.
+pub fn parse_ecb(xml: &str) -> Option<Quotes> {
+  let mut date = None;
+  let mut quotes = BTreeMap::new();
+  for element in xml.split("<Cube").skip(1) {
+    let element = &element[..element.find('>')?];
+    if let Some(time) = attribute(element, "time") {
+      date = Some(NaiveDate::parse_from_str(time, "%Y-%m-%d").ok()?);
+    }
+    if let (Some(currency), Some(rate)) = (attribute(element, "currency"), attribute(element, "rate")) {
+      quotes.insert(currency.to_string(), Decimal::from_str(rate).ok()?);
+    }
+  }
+  (!quotes.is_empty()).then_some(Quotes {
+    date: date?,
+    anchor: "EUR".to_string(),
+    quotes,
+  })
+}
+
+/// The value of attribute `name` in the inside of an XML tag, quoted either way.
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+  ['\'', '"'].into_iter().find_map(|quote| {
+    let start = element.find(&format!(" {}={}", name, quote))? + name.len() + 3;
+    let end = element[start..].find(quote)?;
+    Some(&element[start..start + end])
+  })
+}
+
+/// The latest rates of openexchangerates.org, in US dollars for the free plans.
+pub struct OpenExchangeRatesProvider {
+  http: reqwest::Client,
+  app_id: String,
+}
+
+#[derive(Deserialize)]
+struct OpenExchangeRatesResponse {
+  timestamp: i64,
+  base: String,
+  rates: BTreeMap<String, Decimal>,
+}
+
+#[async_trait]
+impl RateProvider for OpenExchangeRatesProvider {
+  fn name(&self) -> &'static str {
+    "openexchangerates"
+  }
+
+  async fn latest(&self) -> AppResult<Quotes> {
+    let request = self.http.get(OPENEXCHANGERATES_URL).query(&[("app_id", &self.app_id)]);
+    let body = get(request, "openexchangerates.org").await?;
+    let response: OpenExchangeRatesResponse =
+      serde_json::from_str(&body).map_err(|e| internal_error!("openexchangerates.org sent rates that could not be read: {}", e))?;
+    let date = DateTime::from_timestamp(response.timestamp, 0)
+      .ok_or_else(|| internal_error!("openexchangerates.org sent an invalid timestamp"))?
+      .date_naive();
+    Ok(Quotes {
+      date,
+      anchor: response.base,
+      quotes: response.rates,
+    })
+  }
+}
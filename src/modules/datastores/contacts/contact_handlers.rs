@@ -2,47 +2,98 @@ use std::sync::Arc;
 
 use crate::{
   AppResult, AppState,
-  errors::{AppError, NotFoundError},
-  helper::{WorkspaceContext, workspace::check_workspace_permission},
-  impl_next_code_handler,
+  errors::AppError,
+  events::{RecordAction, WorkspaceEvent},
+  helper::{Pagination, PathUuid, RequireRole, RequiredWorkspace, ValidatedQuery, workspace::role::Member},
+  impl_next_code_handler, internal_error,
   modules::{
+    approvals::approval_service,
     auth::current_user::CurrentUser,
-    datastores::{
-      contacts::contact_models::{ContactFilters, ContactResponse, CreateContactRequest, GetContactsQuery, UpdateContactRequest},
-      workspaces::workspace_models::WorkspaceRole,
+    datastores::contacts::{
+      contact_models::{
+        Contact, ContactFilters, ContactPatchTarget, ContactResponse, CreateContactRequest, GetContactsQuery, TaxIdValidation, UpdateContactRequest,
+        ValidateTaxIdRequest,
+      },
+      contact_repository,
     },
+    operations::operation_service,
+    usage::record_quota::check_record_quota,
+    workspace_settings::field_policy_service::{self, Redacted},
+  },
+  responses::{ApiResponse, Created, PaginatedResponse, PaginationMeta},
+  utils::{
+    db_session,
+    merge_patch::{MergePatch, apply_merge_patch},
+    ndjson,
+    next_code_macro::NextCodeQuery,
+    tax_id,
   },
-  responses::{ApiResponse, PaginatedResponse, PaginationMeta},
-  utils::{code_generator::CodeGeneratorConfig, next_code_macro::NextCodeQuery},
 };
 use axum::{
   Json,
-  extract::{
-    Path, Query, State,
-    rejection::{JsonRejection, QueryRejection},
-  },
-  http::StatusCode,
+  extract::{State, rejection::JsonRejection},
+  http::HeaderMap,
+  response::{IntoResponse, Response},
 };
 use uuid::Uuid;
 use validator::Validate;
 
-const DEFAULT_PAGE: u32 = 1;
-const DEFAULT_LIMIT: u32 = 10;
-const MAX_LIMIT: u32 = 100;
-
 // Generate next_code handler using macro
-impl_next_code_handler!(
-  get_next_code,
-  "contact",
-  CodeGeneratorConfig {
-    table_name: "contacts".to_string(),
-    code_column: "code".to_string(),
-    workspace_column: Some("workspace_id".to_string()),
-    prefix_length: 2,
-    number_length: 5,
-    separator: "-".to_string(),
+impl_next_code_handler!(get_next_code, "contact", contact_repository::code_config());
+
+/// Handles `GET` on the contact collection. Clients sending `Accept: application/x-ndjson` receive
+/// every matching contact as one JSON object per line, streamed for full syncs (`page` and `limit`
+/// do not apply); all others get the paginated list of `get_list`. Adding `Prefer: respond-async`
+/// exports the contacts in the background instead, as an operation whose result lists them.
+///
+/// # Arguments
+///
+/// * `headers`: The request headers, checked for `Accept` and `Prefer`.
+/// * The remaining arguments are those of `get_list`.
+///
+/// # Returns
+///
+/// A streamed `application/x-ndjson` response of `ContactResponse` objects, or the `get_list` response.
+pub async fn list(
+  state: State<Arc<AppState>>,
+  headers: HeaderMap,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+  query: ValidatedQuery<GetContactsQuery>,
+  pagination: Pagination,
+) -> AppResult<Response> {
+  if !ndjson::accepts_ndjson(&headers) {
+    let response = get_list(state, current_user, workspace, member, query, pagination).await?;
+    return Ok(response.into_response());
+  }
+
+  let (State(state), RequiredWorkspace(workspace_id), ValidatedQuery(mut params)) = (state, workspace, query);
+  pagination.apply_sort_defaults(&mut params.sort_by, &mut params.sort_order);
+  let repository = state.contact_repository.clone();
+  let filters = ContactFilters::from(params);
+  let user_id = current_user.user_id;
+  let policy = field_policy_service::resolve(&state, workspace_id, "contacts", member.role).await?;
+
+  let redact = move |contact: Contact| policy.redact(ContactResponse::from(contact));
+
+  if operation_service::prefers_async(&headers) {
+    return operation_service::start(&state, workspace_id, user_id, "contacts.export", move |progress| async move {
+      let contacts = operation_service::collect_pages(&progress, |page, limit| {
+        repository.find_by_filters_paginated(workspace_id, user_id, page, limit, filters.clone())
+      })
+      .await?;
+      let contacts: Vec<_> = contacts.into_iter().map(redact).collect();
+      serde_json::to_value(contacts).map_err(|e| internal_error!("Failed to serialize the exported contacts: {}", e))
+    })
+    .await;
   }
-);
+
+  tracing::debug!("Streaming contacts for workspace_id {}", workspace_id);
+  Ok(ndjson::stream_with(state.db.clone(), redact, move |rows| async move {
+    repository.stream_by_filters(workspace_id, user_id, filters, rows).await
+  }))
+}
 
 /// Handles the request to retrieve a paginated list of contacts for the authenticated user.
 /// This handler will get contacts from the user's default workspace or all accessible workspaces.
@@ -50,8 +101,10 @@ impl_next_code_handler!(
 /// # Arguments
 ///
 /// * `State(state)`: The shared application state.
-/// * `Query(params)`: The query parameters for pagination (`page`, `limit`).
 /// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy applied.
+/// * `ValidatedQuery(params)`: The validated query parameters for filtering and sorting.
+/// * `pagination`: The validated `page` and `limit` query parameters.
 ///
 /// # Returns
 ///
@@ -59,40 +112,29 @@ impl_next_code_handler!(
 #[axum::debug_handler]
 pub async fn get_list(
   State(state): State<Arc<AppState>>,
-  query_params: Result<Query<GetContactsQuery>, QueryRejection>,
   current_user: CurrentUser,
-  WorkspaceContext(workspace_id): WorkspaceContext, // Extracted from request headers
-) -> AppResult<Json<ApiResponse<PaginatedResponse<ContactResponse>>>> {
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
+  ValidatedQuery(mut params): ValidatedQuery<GetContactsQuery>,
+  pagination: Pagination,
+) -> AppResult<Json<ApiResponse<PaginatedResponse<Redacted<ContactResponse>>>>> {
   let repository = &state.contact_repository;
+  let policy = field_policy_service::resolve(&state, workspace_id, "contacts", member.role).await?;
 
-  // Handle query parameter parsing errors
-  let params = match query_params {
-    Ok(Query(params)) => params,
-    Err(rejection) => return Err(crate::errors::AppError::from(rejection)),
-  };
-
-  let page = params.page.unwrap_or(DEFAULT_PAGE);
-  let mut limit = params.limit.unwrap_or(DEFAULT_LIMIT);
-
-  if limit > MAX_LIMIT {
-    limit = MAX_LIMIT;
-  }
+  let Pagination { page, limit, .. } = pagination;
+  pagination.apply_sort_defaults(&mut params.sort_by, &mut params.sort_order);
+  // The unfiltered query always lists the newest first
+  let filtered = super::contact_query_builder::has_filters(&params) || params.sort_by.is_some() || params.sort_order.is_some();
 
   tracing::debug!(
-    "Fetching contacts for workspace_id {}: page={}, limit={}, has_filters={}",
+    "Fetching contacts for workspace_id {}: page={}, limit={}, filtered={}",
     workspace_id,
     page,
     limit,
-    super::contact_query_builder::has_filters(&params)
+    filtered
   );
 
-  // Check workspace permissions
-  let workspace_repository = &state.workspace_repository;
-  if !check_workspace_permission(workspace_repository, workspace_id, current_user.user_id, WorkspaceRole::Member).await? {
-    return Err(AppError::Authorization("You don't have permission to access this workspace".to_string()));
-  }
-
-  let (contacts, total) = if super::contact_query_builder::has_filters(&params) {
+  let (contacts, total) = if filtered {
     let filters = ContactFilters::from(params);
     repository
       .find_by_filters_paginated(workspace_id, current_user.user_id, page, limit, filters)
@@ -108,7 +150,10 @@ pub async fn get_list(
 
   let response = ApiResponse::success(
     PaginatedResponse {
-      list: contacts.into_iter().map(ContactResponse::from).collect(),
+      list: contacts
+        .into_iter()
+        .map(|contact| policy.redact(ContactResponse::from(contact)))
+        .collect(),
       pagination,
     },
     "Contacts retrieved successfully",
@@ -123,6 +168,7 @@ pub async fn get_list(
 ///
 /// * `State(state)`: The shared application state.
 /// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy applied.
 /// * `payload`: The JSON payload containing the new contact's data.
 ///
 /// # Returns
@@ -132,55 +178,60 @@ pub async fn get_list(
 pub async fn create(
   State(state): State<Arc<AppState>>,
   current_user: CurrentUser,
-  WorkspaceContext(workspace_id): WorkspaceContext, // Extracted from request headers
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
   payload: Result<Json<CreateContactRequest>, JsonRejection>,
-) -> AppResult<(StatusCode, Json<ApiResponse<ContactResponse>>)> {
+) -> AppResult<Created<ApiResponse<Redacted<ContactResponse>>>> {
   let repository = &state.contact_repository;
+  let policy = field_policy_service::resolve(&state, workspace_id, "contacts", member.role).await?;
 
   // Extract payload first
   let Json(mut payload) = payload?;
 
-  // Auto-generate code if field is empty
-  if payload.code.trim().is_empty() {
-    let generated_code = repository.get_next_available_code(workspace_id, &payload.name).await?;
-    tracing::debug!("Auto-generated code: {} for name: '{}'", generated_code, payload.name);
-    payload.code = generated_code;
-  }
+  // Code generation and the insert run in one transaction
+  let contact = db_session::transaction::<_, _, AppError>(&state.db, async {
+    // Auto-generate code if field is empty
+    if payload.code.trim().is_empty() {
+      let settings = state.workspace_settings_repository.get(workspace_id).await?;
+      let rules = settings.code_rules.get("contacts");
+      let generated_code = repository
+        .get_next_available_code(workspace_id, &payload.name, rules, Some(&payload.contact_type))
+        .await?;
+      tracing::debug!("Auto-generated code: {} for name: '{}'", generated_code, payload.name);
+      payload.code = generated_code;
+    }
+
+    // Now validate with the final code
+    payload.validate()?;
+    reject_deleted_code(&state, workspace_id, &payload.code).await?;
+
+    tracing::debug!(
+      "Creating contact with code: {} for user: {} in workspace: {}",
+      payload.code,
+      current_user.user_id,
+      workspace_id
+    );
+
+    let contact = repository.create_by_workspace(payload, workspace_id, current_user.user_id).await?;
+    check_record_quota(&state, workspace_id).await?;
+    Ok(contact)
+  })
+  .await?;
 
-  // Now validate with the final code
-  payload.validate()?;
+  tracing::info!("Contact created successfully with ID: {} for user: {}", contact.id, current_user.user_id);
 
-  tracing::debug!(
-    "Creating contact with code: {} for user: {} in workspace: {}",
-    payload.code,
+  let contact = ContactResponse::from(contact);
+  publish_change(
+    &state,
+    RecordAction::Created,
+    workspace_id,
     current_user.user_id,
-    workspace_id
+    contact.id,
+    Some(&contact),
   );
 
-  // Validate workspace access
-  let workspace_repository = &state.workspace_repository;
-  if !check_workspace_permission(&workspace_repository, workspace_id, current_user.user_id, WorkspaceRole::Member).await? {
-    return Err(AppError::Authorization(
-      "You don't have permission to create contacts in this workspace".to_string(),
-    ));
-  }
-
-  // Check if code already exists in this workspace using the new method
-  if repository.code_exists(&payload.code, workspace_id).await? {
-    return Err(AppError::validation_with_code(
-      "code",
-      "Contact code already exists in this workspace",
-      "DUPLICATE_CODE",
-    ));
-  }
-
-  let contact = repository.create_by_workspace(payload, workspace_id, current_user.user_id).await?;
-
-  tracing::info!("Contact created successfully with ID: {} for user: {}", contact.id, current_user.user_id);
-
-  let response = ApiResponse::success(ContactResponse::from(contact), "Contact created successfully");
-
-  Ok((StatusCode::CREATED, Json(response)))
+  let location = format!("/api/v1/contacts/{}", contact.id);
+  Ok(ApiResponse::created(policy.redact(contact), "Contact created successfully", location))
 }
 
 /// Handles the request to retrieve a single contact by its ID for the authenticated user.
@@ -188,8 +239,9 @@ pub async fn create(
 /// # Arguments
 ///
 /// * `State(state)`: The shared application state.
-/// * `Path(id)`: The ID of the contact to retrieve, extracted from the URL path.
+/// * `PathUuid(id)`: The ID of the contact to retrieve, extracted from the URL path.
 /// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy applied.
 ///
 /// # Returns
 ///
@@ -197,30 +249,24 @@ pub async fn create(
 #[axum::debug_handler]
 pub async fn get_by_id(
   State(state): State<Arc<AppState>>,
-  Path(id): Path<String>,
+  PathUuid(id): PathUuid,
   current_user: CurrentUser,
-  WorkspaceContext(workspace_id): WorkspaceContext, // Extracted from request headers
-) -> AppResult<Json<ApiResponse<ContactResponse>>> {
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
+) -> AppResult<Json<ApiResponse<Redacted<ContactResponse>>>> {
   let repository = &state.contact_repository;
-
-  // Parse UUID with global error handling
-  let id = id.parse::<Uuid>()?;
+  let policy = field_policy_service::resolve(&state, workspace_id, "contacts", member.role).await?;
 
   tracing::debug!("Fetching contact with ID: {} for user: {}", id, current_user.user_id);
 
   let contact = repository
     .find_by_id_and_workspace(id, workspace_id, current_user.user_id)
     .await?
-    .ok_or_else(|| {
-      AppError::NotFound(NotFoundError {
-        resource: "Contact".to_string(),
-        id: Some(id),
-      })
-    })?;
+    .ok_or_else(|| AppError::not_found_with_id("Contact", id))?;
 
   tracing::debug!("Contact with ID {} found for user {}", id, current_user.user_id);
 
-  let response = ApiResponse::success(ContactResponse::from(contact), "Contact retrieved successfully");
+  let response = ApiResponse::success(policy.redact(ContactResponse::from(contact)), "Contact retrieved successfully");
   Ok(Json(response))
 }
 
@@ -229,8 +275,9 @@ pub async fn get_by_id(
 /// # Arguments
 ///
 /// * `State(state)`: The shared application state.
-/// * `Path(id)`: The ID of the contact to update.
+/// * `PathUuid(id)`: The ID of the contact to update.
 /// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy applied.
 /// * `payload`: The JSON payload with the fields to update.
 ///
 /// # Returns
@@ -239,18 +286,19 @@ pub async fn get_by_id(
 #[axum::debug_handler]
 pub async fn update(
   State(state): State<Arc<AppState>>,
-  Path(id): Path<String>,
+  PathUuid(id): PathUuid,
   current_user: CurrentUser,
-  WorkspaceContext(workspace_id): WorkspaceContext, // Extracted from request headers
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
   payload: Result<Json<UpdateContactRequest>, JsonRejection>,
-) -> AppResult<Json<ApiResponse<ContactResponse>>> {
+) -> AppResult<Json<ApiResponse<Redacted<ContactResponse>>>> {
   let repository = &state.contact_repository;
 
   // Extract the payload
   let Json(payload) = payload?;
-
-  // Parse UUID with global error handling
-  let id = id.parse::<Uuid>()?;
+  payload.validate()?;
+  let policy = field_policy_service::resolve(&state, workspace_id, "contacts", member.role).await?;
+  policy.check_update(&payload)?;
 
   tracing::debug!(
     "Updating contact with ID: {} for user: {} in workspace: {}",
@@ -259,35 +307,103 @@ pub async fn update(
     workspace_id
   );
 
-  // Validate workspace access
-  let workspace_repository = &state.workspace_repository;
-  if !check_workspace_permission(&workspace_repository, workspace_id, current_user.user_id, WorkspaceRole::Member).await? {
-    return Err(AppError::Authorization(
-      "You don't have permission to update contacts in this workspace".to_string(),
-    ));
+  if let Some(code) = &payload.code {
+    reject_deleted_code(&state, workspace_id, code).await?;
   }
-
   let updated_contact = repository
     .update_by_workspace(id, workspace_id, payload, current_user.user_id)
     .await?
-    .ok_or_else(|| {
-      AppError::NotFound(NotFoundError {
-        resource: "Contact".to_string(),
-        id: Some(id),
-      })
-    })?;
+    .ok_or_else(|| AppError::not_found_with_id("Contact", id))?;
 
   tracing::info!("Contact with ID {} updated successfully for workspace {}", id, workspace_id);
-  let response = ApiResponse::success(ContactResponse::from(updated_contact), "Contact updated successfully");
+  let updated_contact = ContactResponse::from(updated_contact);
+  publish_change(
+    &state,
+    RecordAction::Updated,
+    workspace_id,
+    current_user.user_id,
+    id,
+    Some(&updated_contact),
+  );
+
+  let response = ApiResponse::success(policy.redact(updated_contact), "Contact updated successfully");
   Ok(Json(response))
 }
+/// Handles a JSON Merge Patch (RFC 7396) update of a contact.
+///
+/// Members missing from the patch keep their value, while an explicit `null` clears an
+/// optional field such as `position` or `address`.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `PathUuid(id)`: The ID of the contact to patch.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy applied.
+/// * `MergePatch(patch)`: The merge patch document.
+///
+/// # Returns
+///
+/// A `Json` response containing the patched `ContactResponse` if successful, otherwise a 404 error.
+#[axum::debug_handler]
+pub async fn patch(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
+  MergePatch(patch): MergePatch,
+) -> AppResult<Json<ApiResponse<Redacted<ContactResponse>>>> {
+  let repository = &state.contact_repository;
+  let policy = field_policy_service::resolve(&state, workspace_id, "contacts", member.role).await?;
+  policy.check_patch(&patch)?;
+
+  let not_found = || AppError::not_found_with_id("Contact", id);
+
+  // The lookup and the write run in one transaction
+  let patched_contact = db_session::transaction::<_, _, AppError>(&state.db, async {
+    let current = repository
+      .find_by_id_and_workspace(id, workspace_id, current_user.user_id)
+      .await?
+      .ok_or_else(not_found)?;
+
+    let fields = apply_merge_patch(&ContactPatchTarget::from(&current), &patch)?;
+    fields.validate()?;
+    if fields.code != current.code {
+      reject_deleted_code(&state, workspace_id, &fields.code).await?;
+    }
+
+    repository
+      .replace_by_workspace(id, workspace_id, fields, current_user.user_id)
+      .await?
+      .ok_or_else(not_found)
+  })
+  .await?;
+
+  tracing::info!("Contact with ID {} patched successfully for workspace {}", id, workspace_id);
+  let patched_contact = ContactResponse::from(patched_contact);
+  publish_change(
+    &state,
+    RecordAction::Updated,
+    workspace_id,
+    current_user.user_id,
+    id,
+    Some(&patched_contact),
+  );
+
+  let response = ApiResponse::success(policy.redact(patched_contact), "Contact updated successfully");
+  Ok(Json(response))
+}
+
 /// Handles the request to delete a contact by its ID for the authenticated user.
+/// The contact is only marked deleted, and can be brought back with `restore`.
 ///
 /// # Arguments
 ///
 /// * `State(state)`: The shared application state.
-/// * `Path(id)`: The ID of the contact to delete.
+/// * `PathUuid(id)`: The ID of the contact to delete.
 /// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; members may have to wait for an admin to approve the deletion.
 ///
 /// # Returns
 ///
@@ -295,14 +411,13 @@ pub async fn update(
 #[axum::debug_handler]
 pub async fn delete(
   State(state): State<Arc<AppState>>,
-  Path(id): Path<String>,
+  PathUuid(id): PathUuid,
   current_user: CurrentUser,
-  WorkspaceContext(workspace_id): WorkspaceContext, // Extracted from request headers
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
 ) -> AppResult<Json<ApiResponse<()>>> {
   let repository = &state.contact_repository;
-
-  // Parse UUID with global error handling
-  let id = id.parse::<Uuid>()?;
+  approval_service::hold_contact_deletion(&state, workspace_id, member.role, current_user.user_id, id).await?;
 
   tracing::debug!(
     "Deleting contact with ID: {} for user: {} in workspace: {}",
@@ -311,26 +426,109 @@ pub async fn delete(
     workspace_id
   );
 
-  // Validate workspace access
-  let workspace_repository = &state.workspace_repository;
-  if !check_workspace_permission(&workspace_repository, workspace_id, current_user.user_id, WorkspaceRole::Member).await? {
-    return Err(AppError::Authorization(
-      "You don't have permission to delete contacts in this workspace".to_string(),
-    ));
-  }
-
   // Delete contact by workspace and user
   let deleted = repository.delete_by_workspace_and_user(id, workspace_id, current_user.user_id).await?;
 
   if !deleted {
-    return Err(AppError::NotFound(NotFoundError {
-      resource: "Contact".to_string(),
-      id: Some(id),
-    }));
+    return Err(AppError::not_found_with_id("Contact", id));
   }
 
   tracing::info!("Contact with ID {} deleted successfully for user {}", id, current_user.user_id);
+  publish_change(&state, RecordAction::Deleted, workspace_id, current_user.user_id, id, None);
 
   let response = ApiResponse::success((), "Contact deleted successfully");
   Ok(Json(response))
 }
+
+/// Handles the request to restore a deleted contact, for when it was deleted by accident. The
+/// restored contact counts toward the records of the workspace's plan again.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `PathUuid(id)`: The ID of the deleted contact.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy applied.
+///
+/// # Returns
+///
+/// A `Json` response containing the restored `ContactResponse`, or a 404 error if there is no such
+/// deleted contact.
+#[axum::debug_handler]
+pub async fn restore(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
+) -> AppResult<Json<ApiResponse<Redacted<ContactResponse>>>> {
+  let repository = &state.contact_repository;
+  let policy = field_policy_service::resolve(&state, workspace_id, "contacts", member.role).await?;
+
+  let contact = db_session::transaction::<_, _, AppError>(&state.db, async {
+    let contact = repository
+      .restore_by_workspace_and_user(id, workspace_id, current_user.user_id)
+      .await?
+      .ok_or_else(|| AppError::not_found_with_id("Deleted contact", id))?;
+    check_record_quota(&state, workspace_id).await?;
+    Ok(contact)
+  })
+  .await?;
+
+  tracing::info!("Contact with ID {} restored for user {}", id, current_user.user_id);
+
+  let contact = ContactResponse::from(contact);
+  publish_change(
+    &state,
+    RecordAction::Restored,
+    workspace_id,
+    current_user.user_id,
+    contact.id,
+    Some(&contact),
+  );
+
+  let response = ApiResponse::success(policy.redact(contact), "Contact restored successfully");
+  Ok(Json(response))
+}
+
+/// Checks a tax ID against the rules of its country without saving it, for forms to report a
+/// mistyped NPWP or VAT number before the contact is submitted. Contacts are checked with the
+/// same rules when they are saved.
+pub async fn validate_tax_id(
+  RequiredWorkspace(_workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  payload: Result<Json<ValidateTaxIdRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<TaxIdValidation>>> {
+  let Json(request) = payload?;
+  request.validate()?;
+
+  let validation = match tax_id::parse(&request.tax_id, request.country.as_deref()) {
+    Ok(tax_id) => TaxIdValidation {
+      valid: true,
+      tax_id: Some(tax_id),
+      error: None,
+    },
+    Err(e) => TaxIdValidation {
+      valid: false,
+      tax_id: None,
+      error: Some(e.to_string()),
+    },
+  };
+  Ok(Json(ApiResponse::success(validation, "Tax ID checked successfully")))
+}
+
+/// Notifies real-time clients of the workspace that a contact changed.
+/// Rejects `code` when a deleted contact holds it. Deleted contacts keep their code so they can be
+/// restored; inserting it again would fail as a duplicate of a contact no one can see.
+async fn reject_deleted_code(state: &AppState, workspace_id: Uuid, code: &str) -> AppResult<()> {
+  match state.contact_repository.find_by_code_and_workspace(code, workspace_id).await? {
+    Some(contact) if contact.deleted_at.is_some() => Err(AppError::deleted_code("contact")),
+    _ => Ok(()),
+  }
+}
+
+fn publish_change(state: &AppState, action: RecordAction, workspace_id: Uuid, user_id: Uuid, contact_id: Uuid, contact: Option<&ContactResponse>) {
+  state
+    .events
+    .publish(WorkspaceEvent::record("contact", action, workspace_id, contact_id, user_id, contact));
+}
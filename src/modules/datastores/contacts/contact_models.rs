@@ -4,6 +4,11 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::{
+  helper::pagination::{DEFAULT_LIMIT, DEFAULT_PAGE, MAX_LIMIT},
+  utils::tax_id::{TaxId, validate_tax_id},
+};
+
 /// Represents a contact record in the database.
 /// This struct is derived from `sqlx::FromRow` to allow direct mapping from database query results.
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -16,6 +21,10 @@ pub struct Contact {
   #[sqlx(rename = "type")]
   pub contact_type: String, // Maps to database column "type" to avoid Rust keyword conflict
   pub address: Option<String>,
+  /// Stored encrypted, decrypted by the repository (see `utils::field_encryption`).
+  pub tax_id: Option<String>,
+  /// Stored encrypted like `tax_id`.
+  pub bank_account: Option<String>,
   pub is_active: bool,
 
   // Metadata
@@ -24,13 +33,16 @@ pub struct Contact {
   pub updated_by: Option<Uuid>,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
+  /// When the contact was deleted; deleted contacts can be restored.
+  pub deleted_at: Option<DateTime<Utc>>,
 }
 
 /// Represents the payload for creating a new contact.
 /// This struct uses `validator` to enforce declarative validation rules on the incoming data.
 /// The `created_by` field is automatically set from the authenticated user.
-/// The `workspace_id` is now extracted from request headers via WorkspaceContext, not from the body.
+/// The `workspace_id` is now extracted from request headers via RequiredWorkspace, not from the body.
 #[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(feature = "client", derive(Serialize))]
 pub struct CreateContactRequest {
   #[validate(length(min = 1, message = "Code is required"))]
   pub code: String,
@@ -42,27 +54,76 @@ pub struct CreateContactRequest {
   #[validate(length(min = 1, message = "Contact type is required"))]
   pub contact_type: String,
   pub address: Option<String>,
+  #[validate(custom(function = "validate_tax_id"))]
+  pub tax_id: Option<String>,
+  pub bank_account: Option<String>,
 }
 
 /// Represents the payload for updating an existing contact.
 /// All fields are optional, allowing for partial updates.
 /// The `updated_by` field is automatically set from the authenticated user.
 /// The `workspace_id` cannot be changed via update - it's workspace-scoped.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "client", derive(Default))]
 pub struct UpdateContactRequest {
+  #[validate(length(min = 1, message = "Code cannot be empty"))]
   pub code: Option<String>,
+  #[validate(length(min = 1, message = "Name cannot be empty"))]
   pub name: Option<String>,
+  #[validate(email(message = "Invalid email format"))]
   pub email: Option<String>,
   pub position: Option<String>,
+  #[validate(length(min = 1, message = "Contact type cannot be empty"))]
   pub contact_type: Option<String>,
   pub address: Option<String>,
+  #[validate(custom(function = "validate_tax_id"))]
+  pub tax_id: Option<String>,
+  pub bank_account: Option<String>,
   pub is_active: Option<bool>,
 }
 
+/// The editable fields of a contact, used as the target of JSON Merge Patch (`PATCH`) updates.
+/// Every field is written back, so a `null` in the patch clears an optional field.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ContactPatchTarget {
+  #[validate(length(min = 1, message = "Code is required"))]
+  pub code: String,
+  #[validate(length(min = 1, message = "Name is required"))]
+  pub name: String,
+  #[validate(email(message = "Invalid email format"))]
+  pub email: String,
+  pub position: Option<String>,
+  #[validate(length(min = 1, message = "Contact type is required"))]
+  pub contact_type: String,
+  pub address: Option<String>,
+  #[validate(custom(function = "validate_tax_id"))]
+  pub tax_id: Option<String>,
+  pub bank_account: Option<String>,
+  pub is_active: bool,
+}
+
+impl From<&Contact> for ContactPatchTarget {
+  fn from(contact: &Contact) -> Self {
+    Self {
+      code: contact.code.clone(),
+      name: contact.name.clone(),
+      email: contact.email.clone(),
+      position: contact.position.clone(),
+      contact_type: contact.contact_type.clone(),
+      address: contact.address.clone(),
+      tax_id: contact.tax_id.clone(),
+      bank_account: contact.bank_account.clone(),
+      is_active: contact.is_active,
+    }
+  }
+}
+
 /// Represents the data structure for a contact response.
 /// This struct defines the public-facing representation of a contact,
 /// including ownership and audit information.
 #[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
 pub struct ContactResponse {
   pub id: Uuid,
   pub code: String,
@@ -71,6 +132,8 @@ pub struct ContactResponse {
   pub position: Option<String>,
   pub contact_type: String,
   pub address: Option<String>,
+  pub tax_id: Option<String>,
+  pub bank_account: Option<String>,
   pub is_active: bool,
 
   // Metadata
@@ -79,6 +142,8 @@ pub struct ContactResponse {
   pub updated_by: Option<Uuid>,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
+  /// When the contact was deleted; deleted contacts can be restored.
+  pub deleted_at: Option<DateTime<Utc>>,
 }
 
 /// Converts a `Contact` model into a `ContactResponse`.
@@ -94,6 +159,8 @@ impl From<Contact> for ContactResponse {
       position: contact.position,
       contact_type: contact.contact_type,
       address: contact.address,
+      tax_id: contact.tax_id,
+      bank_account: contact.bank_account,
       is_active: contact.is_active,
 
       // Metadata
@@ -102,16 +169,20 @@ impl From<Contact> for ContactResponse {
       updated_by: contact.updated_by,
       created_at: contact.created_at,
       updated_at: contact.updated_at,
+      deleted_at: contact.deleted_at,
     }
   }
 }
 
 /// Query parameters for paginated requests with advanced filtering
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Deserialize, Validate)]
+#[cfg_attr(feature = "client", derive(Serialize))]
 #[serde(deny_unknown_fields)]
 pub struct GetContactsQuery {
   // Pagination
+  #[validate(range(min = 1, message = "Page must be at least 1"))]
   pub page: Option<u32>,
+  #[validate(range(min = 1, max = MAX_LIMIT, message = "Limit must be between 1 and 100"))]
   pub limit: Option<u32>,
 
   // Basic filtering
@@ -126,16 +197,14 @@ pub struct GetContactsQuery {
   pub exclude_types: Option<String>, // comma-separated: "employee"
   pub include_ids: Option<String>,   // comma-separated UUIDs
   pub exclude_ids: Option<String>,   // comma-separated UUIDs
+  /// Lists deleted contacts too.
+  pub include_deleted: Option<bool>,
 
   // Sorting
   pub sort_by: Option<String>,    // "name", "email", "created_at", "updated_at", "code"
   pub sort_order: Option<String>, // "asc" or "desc"
 }
 
-// Constants untuk consistency dengan handler
-const DEFAULT_PAGE: u32 = 1;
-const DEFAULT_LIMIT: u32 = 10;
-
 #[derive(Debug, Clone)]
 pub struct ContactFilters {
   pub search: Option<String>,
@@ -147,6 +216,7 @@ pub struct ContactFilters {
   pub exclude_types: Vec<String>,
   pub include_ids: Vec<Uuid>,
   pub exclude_ids: Vec<Uuid>,
+  pub include_deleted: bool,
   pub sort_by: String,
   pub sort_order: String,
 }
@@ -204,6 +274,7 @@ impl From<GetContactsQuery> for ContactFilters {
       exclude_types,
       include_ids,
       exclude_ids,
+      include_deleted: query.include_deleted.unwrap_or(false),
       sort_by,
       sort_order,
     }
@@ -224,8 +295,30 @@ impl Default for GetContactsQuery {
       exclude_types: None,
       include_ids: None,
       exclude_ids: None,
+      include_deleted: None,
       sort_by: None,
       sort_order: None,
     }
   }
 }
+
+/// A tax ID to check before it is saved on a contact.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ValidateTaxIdRequest {
+  #[validate(length(min = 1, max = 64, message = "Tax ID must be between 1 and 64 characters"))]
+  pub tax_id: String,
+  /// ISO 3166-1 alpha-2 code of the issuing country, read from the number when omitted.
+  #[validate(length(equal = 2, message = "Country must be a two-letter ISO 3166-1 code"))]
+  pub country: Option<String>,
+}
+
+/// Whether a tax ID follows the rules of its country, and how it is written when it does.
+#[derive(Debug, Serialize)]
+pub struct TaxIdValidation {
+  pub valid: bool,
+  #[serde(flatten)]
+  pub tax_id: Option<TaxId>,
+  /// Why the tax ID is not valid.
+  pub error: Option<String>,
+}
@@ -1,7 +1,7 @@
-use sea_query::{Expr, Iden, Order, PostgresQueryBuilder, Query, SelectStatement};
-use uuid::Uuid;
+use sea_query::{ColumnRef, DynIden, Expr, Iden, IntoColumnRef, Order, SeaRc, SelectStatement};
 
 use super::contact_models::{ContactFilters, GetContactsQuery};
+use crate::utils::search_index;
 
 // Define table and column enums for type safety
 #[derive(Iden)]
@@ -14,80 +14,53 @@ enum Contacts {
   Position,
   Type,
   Address,
+  TaxId,
+  BankAccount,
   IsActive,
   WorkspaceId,
   CreatedBy,
   UpdatedBy,
   CreatedAt,
   UpdatedAt,
-}
-
-#[derive(Iden)]
-enum Workspaces {
-  Table,
-  Id,
-}
-
-#[derive(Iden)]
-enum WorkspaceUsers {
-  Table,
-  WorkspaceId,
-  UserId,
+  DeletedAt,
 }
 
 pub struct ContactQueryBuilder;
 
+/// The contact-specific parts of the list queries, which `PaginatedRepository` combines into the
+/// page, count and stream queries.
 impl ContactQueryBuilder {
-  pub fn build_filtered_query(workspace_id: Uuid, user_id: Uuid, filters: &ContactFilters) -> (String, String) {
-    // Build select query
-    let select_sql = Self::build_select_query(workspace_id, user_id, filters);
-
-    // Build count query
-    let count_sql = Self::build_count_query(workspace_id, user_id, filters);
+  pub fn table() -> DynIden {
+    SeaRc::new(Contacts::Table)
+  }
 
-    (select_sql, count_sql)
+  /// The columns of `Contact`, leaving out the search vector.
+  pub fn columns() -> Vec<ColumnRef> {
+    [
+      Contacts::Id,
+      Contacts::Code,
+      Contacts::Name,
+      Contacts::Email,
+      Contacts::Position,
+      Contacts::Type,
+      Contacts::Address,
+      Contacts::TaxId,
+      Contacts::BankAccount,
+      Contacts::IsActive,
+      Contacts::WorkspaceId,
+      Contacts::CreatedBy,
+      Contacts::UpdatedBy,
+      Contacts::CreatedAt,
+      Contacts::UpdatedAt,
+      Contacts::DeletedAt,
+    ]
+    .into_iter()
+    .map(|column| (Contacts::Table, column).into_column_ref())
+    .collect()
   }
 
-  fn build_select_query(workspace_id: Uuid, user_id: Uuid, filters: &ContactFilters) -> String {
-    let mut query = Query::select();
-
-    // Select columns with alias
-    query
-      .columns([
-        (Contacts::Table, Contacts::Id),
-        (Contacts::Table, Contacts::Code),
-        (Contacts::Table, Contacts::Name),
-        (Contacts::Table, Contacts::Email),
-        (Contacts::Table, Contacts::Position),
-        (Contacts::Table, Contacts::Type),
-        (Contacts::Table, Contacts::Address),
-        (Contacts::Table, Contacts::IsActive),
-        (Contacts::Table, Contacts::WorkspaceId),
-        (Contacts::Table, Contacts::CreatedBy),
-        (Contacts::Table, Contacts::UpdatedBy),
-        (Contacts::Table, Contacts::CreatedAt),
-        (Contacts::Table, Contacts::UpdatedAt),
-      ])
-      .from(Contacts::Table)
-      .inner_join(
-        Workspaces::Table,
-        Expr::col((Contacts::Table, Contacts::WorkspaceId)).equals((Workspaces::Table, Workspaces::Id)),
-      )
-      .inner_join(
-        WorkspaceUsers::Table,
-        Expr::col((Workspaces::Table, Workspaces::Id)).equals((WorkspaceUsers::Table, WorkspaceUsers::WorkspaceId)),
-      );
-
-    // Base conditions - use string values to avoid UUID conversion issues
-    query
-      .and_where(Expr::col((Contacts::Table, Contacts::WorkspaceId)).eq(workspace_id.to_string()))
-      .and_where(Expr::col((WorkspaceUsers::Table, WorkspaceUsers::UserId)).eq(user_id.to_string()));
-
-    // Apply filters
-    Self::apply_filters(&mut query, filters);
-
-    // Apply sorting
-    let sort_column = match filters.sort_by.as_str() {
+  pub fn sort(filters: &ContactFilters) -> (DynIden, Order) {
+    let column = match filters.sort_by.as_str() {
       "name" => Contacts::Name,
       "email" => Contacts::Email,
       "code" => Contacts::Code,
@@ -95,53 +68,20 @@ impl ContactQueryBuilder {
       "updated_at" => Contacts::UpdatedAt,
       _ => Contacts::CreatedAt,
     };
+    let order = if filters.sort_order == "ASC" { Order::Asc } else { Order::Desc };
 
-    let sort_order = if filters.sort_order == "ASC" { Order::Asc } else { Order::Desc };
-
-    query.order_by((Contacts::Table, sort_column), sort_order);
-
-    // Build SQL
-    query.to_string(PostgresQueryBuilder)
+    (SeaRc::new(column), order)
   }
 
-  fn build_count_query(workspace_id: Uuid, user_id: Uuid, filters: &ContactFilters) -> String {
-    let mut query = Query::select();
-
-    query
-      .expr(Expr::col((Contacts::Table, Contacts::Id)).count())
-      .from(Contacts::Table)
-      .inner_join(
-        Workspaces::Table,
-        Expr::col((Contacts::Table, Contacts::WorkspaceId)).equals((Workspaces::Table, Workspaces::Id)),
-      )
-      .inner_join(
-        WorkspaceUsers::Table,
-        Expr::col((Workspaces::Table, Workspaces::Id)).equals((WorkspaceUsers::Table, WorkspaceUsers::WorkspaceId)),
-      );
-
-    // Base conditions - use string values to avoid UUID conversion issues
-    query
-      .and_where(Expr::col((Contacts::Table, Contacts::WorkspaceId)).eq(workspace_id.to_string()))
-      .and_where(Expr::col((WorkspaceUsers::Table, WorkspaceUsers::UserId)).eq(user_id.to_string()));
-
-    // Apply same filters
-    Self::apply_filters(&mut query, filters);
-
-    // Build SQL
-    query.to_string(PostgresQueryBuilder)
-  }
+  pub fn apply_filters(query: &mut SelectStatement, filters: &ContactFilters) {
+    // Deleted contacts are left out unless asked for
+    if !filters.include_deleted {
+      query.and_where(Expr::col((Contacts::Table, Contacts::DeletedAt)).is_null());
+    }
 
-  fn apply_filters(query: &mut SelectStatement, filters: &ContactFilters) {
-    // Search filter (across multiple fields)
-    if let Some(search) = &filters.search {
-      let search_pattern = format!("%{}%", search);
-      let search_condition = Expr::col((Contacts::Table, Contacts::Name))
-        .like(&search_pattern)
-        .or(Expr::col((Contacts::Table, Contacts::Email)).like(&search_pattern))
-        .or(Expr::col((Contacts::Table, Contacts::Code)).like(&search_pattern))
-        .or(Expr::col((Contacts::Table, Contacts::Position)).like(&search_pattern));
-
-      query.and_where(search_condition);
+    // Full-text search over code, name, email, position and address
+    if let Some(condition) = filters.search.as_deref().and_then(|search| search_index::matches("contacts", search)) {
+      query.and_where(condition);
     }
 
     // Contact type filter
@@ -176,16 +116,14 @@ impl ContactQueryBuilder {
       query.and_where(Expr::col((Contacts::Table, Contacts::Type)).is_not_in(types));
     }
 
-    // Include IDs filter - convert UUIDs to strings
+    // Include IDs filter
     if !filters.include_ids.is_empty() {
-      let id_strings: Vec<String> = filters.include_ids.iter().map(|id| id.to_string()).collect();
-      query.and_where(Expr::col((Contacts::Table, Contacts::Id)).is_in(id_strings));
+      query.and_where(Expr::col((Contacts::Table, Contacts::Id)).is_in(filters.include_ids.iter().copied()));
     }
 
-    // Exclude IDs filter - convert UUIDs to strings
+    // Exclude IDs filter
     if !filters.exclude_ids.is_empty() {
-      let id_strings: Vec<String> = filters.exclude_ids.iter().map(|id| id.to_string()).collect();
-      query.and_where(Expr::col((Contacts::Table, Contacts::Id)).is_not_in(id_strings));
+      query.and_where(Expr::col((Contacts::Table, Contacts::Id)).is_not_in(filters.exclude_ids.iter().copied()));
     }
   }
 }
@@ -201,6 +139,7 @@ pub fn has_filters(query: &GetContactsQuery) -> bool {
     || query.exclude_types.is_some()
     || query.include_ids.is_some()
     || query.exclude_ids.is_some()
+    || query.include_deleted.is_some()
     || query.sort_by.is_some()
     || query.sort_order.is_some()
 }
@@ -1,19 +1,48 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use sqlx::PgPool;
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use sea_query::{ColumnRef, DynIden, Order, SelectStatement};
+use sqlx::Connection;
 use uuid::Uuid;
 
-use super::contact_models::{Contact, ContactFilters, CreateContactRequest, UpdateContactRequest};
+use super::{
+  contact_models::{Contact, ContactFilters, ContactPatchTarget, CreateContactRequest, UpdateContactRequest},
+  contact_query_builder::ContactQueryBuilder,
+};
 use crate::{
   AppResult,
-  utils::code_generator::{CodeGenerator, CodeGeneratorConfig},
+  utils::{
+    DbExecutor, ReadPool,
+    code_generator::{CodeGenerator, CodeGeneratorConfig, CodeRules},
+    field_encryption::FieldCipher,
+    ndjson::RowSender,
+    paginated_repository::PaginatedRepository,
+    pagination::{self, Counted},
+  },
 };
 
+/// The built-in format of contact codes, before the rules of the workspace.
+pub fn code_config() -> CodeGeneratorConfig {
+  CodeGeneratorConfig {
+    table_name: "contacts".to_string(),
+    prefix_length: 2,
+    number_length: 5,
+    ..CodeGeneratorConfig::default()
+  }
+}
+
 #[async_trait]
 pub trait ContactRepository {
   // Core workspace-scoped methods - these are the only ones we need
   async fn create_by_workspace(&self, contact: CreateContactRequest, workspace_id: Uuid, user_id: Uuid) -> AppResult<Contact>;
+  /// Inserts all `contacts` in one statement and returns the inserted rows. Contacts whose code
+  /// is already taken in the workspace are skipped, so the result can be shorter than the input.
+  async fn create_many_by_workspace(&self, contacts: Vec<CreateContactRequest>, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Contact>>;
   async fn find_all_by_workspace_paginated(&self, workspace_id: Uuid, user_id: Uuid, page: u32, limit: u32) -> AppResult<(Vec<Contact>, u64)>;
   async fn find_by_id_and_workspace(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<Contact>>;
+  /// The contact holding `code`, deleted or not: deleted contacts keep their code.
   async fn find_by_code_and_workspace(&self, code: &str, workspace_id: Uuid) -> AppResult<Option<Contact>>;
   async fn update_by_workspace(
     &self,
@@ -22,15 +51,35 @@ pub trait ContactRepository {
     contact_data: UpdateContactRequest,
     updated_by: Uuid,
   ) -> AppResult<Option<Contact>>;
+  async fn replace_by_workspace(&self, id: Uuid, workspace_id: Uuid, fields: ContactPatchTarget, updated_by: Uuid) -> AppResult<Option<Contact>>;
+  /// Marks the contact deleted, leaving it out of every read until restored.
   async fn delete_by_workspace_and_user(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<bool>;
+  /// Brings back a deleted contact; `None` if there is no such deleted contact.
+  async fn restore_by_workspace_and_user(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<Contact>>;
 
   // Code generation methods
-  async fn get_next_available_code(&self, workspace_id: Uuid, contact_name: &str) -> AppResult<String>;
-  async fn code_exists(&self, code: &str, workspace_id: Uuid) -> AppResult<bool>;
+  /// The next free code for `contact_name`, in the format set by `rules` for the record's `category`.
+  async fn get_next_available_code(
+    &self,
+    workspace_id: Uuid,
+    contact_name: &str,
+    rules: Option<&CodeRules>,
+    category: Option<&str>,
+  ) -> AppResult<String>;
 
   // Optional methods for specific use cases
   async fn find_by_type_and_workspace(&self, contact_type: &str, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Contact>>;
   async fn find_active_by_workspace(&self, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Contact>>;
+  /// Up to `limit` contacts changed after the `(updated_at, id)` position `after` and before
+  /// `settled_before`, oldest first. Without `after`, the latest `limit` changes, still oldest first.
+  async fn find_changed(
+    &self,
+    workspace_id: Uuid,
+    user_id: Uuid,
+    after: Option<(DateTime<Utc>, Uuid)>,
+    settled_before: DateTime<Utc>,
+    limit: i64,
+  ) -> AppResult<Vec<Contact>>;
 
   // Advanced filtering method
   async fn find_by_filters_paginated(
@@ -41,21 +90,92 @@ pub trait ContactRepository {
     limit: u32,
     filters: ContactFilters,
   ) -> AppResult<(Vec<Contact>, u64)>;
+
+  // Streaming method: sends every matching row to `rows`, in the requested order
+  async fn stream_by_filters(&self, workspace_id: Uuid, user_id: Uuid, filters: ContactFilters, rows: RowSender<Contact>) -> AppResult<()>;
+
+  /// Re-encrypts the sensitive fields of all workspaces still encrypted with an older key, in
+  /// batches of `batch_size`, and returns the number of contacts updated.
+  async fn reencrypt_sensitive_fields(&self, batch_size: u32) -> AppResult<u64>;
 }
 
+const TAX_ID: &str = "contacts.tax_id";
+const BANK_ACCOUNT: &str = "contacts.bank_account";
+
 pub struct SqlxContactRepository {
-  db: PgPool,
+  db: DbExecutor,
+  read_pool: ReadPool,
+  cipher: Arc<FieldCipher>,
 }
 
 impl SqlxContactRepository {
-  pub fn new(db: PgPool) -> Self {
-    Self { db }
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    let db = db.into();
+    Self {
+      read_pool: ReadPool::primary_only(db.clone()),
+      db,
+      cipher: Arc::new(FieldCipher::disabled()),
+    }
   }
 
-  /// Get access to the underlying database pool
-  pub fn get_pool(&self) -> PgPool {
+  /// Encrypts and decrypts `tax_id` and `bank_account`; without it, contacts having them can
+  /// neither be stored nor read.
+  pub fn with_cipher(mut self, cipher: Arc<FieldCipher>) -> Self {
+    self.cipher = cipher;
+    self
+  }
+
+  /// Runs the read-only queries (lookups, lists and counts) on `read_pool` instead of the primary.
+  pub fn with_read_pool(mut self, read_pool: ReadPool) -> Self {
+    self.read_pool = read_pool;
+    self
+  }
+
+  /// The executor the repository's writes run on
+  pub fn executor(&self) -> DbExecutor {
     self.db.clone()
   }
+
+  /// Encrypts the sensitive fields before they are written.
+  fn seal(&self, tax_id: Option<&str>, bank_account: Option<&str>) -> AppResult<(Option<String>, Option<String>)> {
+    Ok((
+      self.cipher.encrypt_opt(TAX_ID, tax_id)?,
+      self.cipher.encrypt_opt(BANK_ACCOUNT, bank_account)?,
+    ))
+  }
+
+  /// Decrypts the sensitive fields of a contact read from the database.
+  fn open(&self, mut contact: Contact) -> AppResult<Contact> {
+    contact.tax_id = self.cipher.decrypt_opt(TAX_ID, contact.tax_id.as_deref())?;
+    contact.bank_account = self.cipher.decrypt_opt(BANK_ACCOUNT, contact.bank_account.as_deref())?;
+    Ok(contact)
+  }
+
+  fn open_all(&self, contacts: Vec<Contact>) -> AppResult<Vec<Contact>> {
+    contacts.into_iter().map(|contact| self.open(contact)).collect()
+  }
+}
+
+impl PaginatedRepository<Contact, ContactFilters> for SqlxContactRepository {
+  fn table() -> DynIden {
+    ContactQueryBuilder::table()
+  }
+
+  fn columns() -> Vec<ColumnRef> {
+    ContactQueryBuilder::columns()
+  }
+
+  fn apply_filters(query: &mut SelectStatement, filters: &ContactFilters) {
+    ContactQueryBuilder::apply_filters(query, filters)
+  }
+
+  fn sort(filters: &ContactFilters) -> (DynIden, Order) {
+    ContactQueryBuilder::sort(filters)
+  }
+
+  fn read_pool(&self) -> &ReadPool {
+    &self.read_pool
+  }
 }
 
 #[async_trait]
@@ -63,14 +183,16 @@ impl ContactRepository for SqlxContactRepository {
   // Workspace-scoped methods
 
   async fn create_by_workspace(&self, contact: CreateContactRequest, workspace_id: Uuid, user_id: Uuid) -> AppResult<Contact> {
+    let (tax_id, bank_account) = self.seal(contact.tax_id.as_deref(), contact.bank_account.as_deref())?;
+    let mut conn = self.db.acquire().await?;
     let new_contact = sqlx::query_as!(
       Contact,
       r#"
-        INSERT INTO contacts (code, name, email, position, type, address, workspace_id, created_by)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        INSERT INTO contacts (code, name, email, position, type, address, tax_id, bank_account, workspace_id, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         RETURNING 
           id, code, name, email, position, type as contact_type, 
-          address, is_active, workspace_id, created_by, updated_by, created_at, updated_at
+          address, tax_id, bank_account, is_active, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
       "#,
       contact.code,
       contact.name,
@@ -78,109 +200,171 @@ impl ContactRepository for SqlxContactRepository {
       contact.position,
       contact.contact_type,
       contact.address,
+      tax_id,
+      bank_account,
       workspace_id,
       user_id
     )
-    .fetch_one(&self.db)
+    .fetch_one(&mut *conn)
     .await
     .map_err(|e| {
       tracing::error!("Failed to create contact: {}", e);
       crate::errors::AppError::from_sqlx_error(e, "INSERT INTO contacts")
     })?;
 
-    Ok(new_contact)
+    self.open(new_contact)
   }
 
-  async fn find_all_by_workspace_paginated(&self, workspace_id: Uuid, user_id: Uuid, page: u32, limit: u32) -> AppResult<(Vec<Contact>, u64)> {
-    let offset = (page - 1) * limit;
+  async fn create_many_by_workspace(&self, contacts: Vec<CreateContactRequest>, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Contact>> {
+    if contacts.is_empty() {
+      return Ok(Vec::new());
+    }
 
-    let total_count = sqlx::query_scalar!(
+    // One array per column, unnested back into rows by Postgres
+    let mut codes = Vec::with_capacity(contacts.len());
+    let mut names = Vec::with_capacity(contacts.len());
+    let mut emails = Vec::with_capacity(contacts.len());
+    let mut positions = Vec::with_capacity(contacts.len());
+    let mut types = Vec::with_capacity(contacts.len());
+    let mut addresses = Vec::with_capacity(contacts.len());
+    let mut tax_ids = Vec::with_capacity(contacts.len());
+    let mut bank_accounts = Vec::with_capacity(contacts.len());
+    for contact in contacts {
+      let (tax_id, bank_account) = self.seal(contact.tax_id.as_deref(), contact.bank_account.as_deref())?;
+      codes.push(contact.code);
+      names.push(contact.name);
+      emails.push(contact.email);
+      positions.push(contact.position);
+      types.push(contact.contact_type);
+      addresses.push(contact.address);
+      tax_ids.push(tax_id);
+      bank_accounts.push(bank_account);
+    }
+
+    let mut conn = self.db.acquire().await?;
+    let new_contacts = sqlx::query_as::<_, Contact>(
       r#"
-        SELECT COUNT(*) 
-        FROM contacts 
-        WHERE workspace_id = $1 
-          AND id IN (
-            SELECT c.id FROM contacts c
-            JOIN workspaces w ON c.workspace_id = w.id
-            JOIN workspace_users wu ON w.id = wu.workspace_id
-            WHERE wu.user_id = $2
-          )
+        INSERT INTO contacts (code, name, email, position, type, address, tax_id, bank_account, workspace_id, created_by)
+        SELECT code, name, email, position, type, address, tax_id, bank_account, $9, $10
+        FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[], $5::text[], $6::text[], $7::text[], $8::text[])
+          AS rows(code, name, email, position, type, address, tax_id, bank_account)
+        ON CONFLICT (workspace_id, code) DO NOTHING
+        RETURNING
+          id, code, name, email, position, type,
+          address, tax_id, bank_account, is_active, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
       "#,
-      workspace_id,
-      user_id
     )
-    .fetch_one(&self.db)
-    .await?
-    .unwrap_or(0);
+    .bind(codes)
+    .bind(names)
+    .bind(emails)
+    .bind(positions)
+    .bind(types)
+    .bind(addresses)
+    .bind(tax_ids)
+    .bind(bank_accounts)
+    .bind(workspace_id)
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+      tracing::error!("Failed to bulk create contacts: {}", e);
+      crate::errors::AppError::from_sqlx_error(e, "INSERT INTO contacts SELECT FROM UNNEST")
+    })?;
 
-    let contacts = sqlx::query_as!(
-      Contact,
+    self.open_all(new_contacts)
+  }
+
+  async fn find_all_by_workspace_paginated(&self, workspace_id: Uuid, user_id: Uuid, page: u32, limit: u32) -> AppResult<(Vec<Contact>, u64)> {
+    let mut conn = self.read_pool.acquire().await?;
+    let offset = pagination::offset(page, limit);
+
+    // The total count comes back with every row, so one roundtrip serves the whole page
+    let rows = sqlx::query_as::<_, Counted<Contact>>(
       r#"
         SELECT 
-          id, code, name, email, position, type as contact_type, 
-          address, is_active, workspace_id, created_by, updated_by, created_at, updated_at
+          id, code, name, email, position, type, 
+          address, tax_id, bank_account, is_active, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at,
+          COUNT(*) OVER () AS total_count
         FROM contacts 
-        WHERE workspace_id = $1 
-          AND id IN (
-            SELECT c.id FROM contacts c
-            JOIN workspaces w ON c.workspace_id = w.id
-            JOIN workspace_users wu ON w.id = wu.workspace_id
-            WHERE wu.user_id = $2
+        WHERE workspace_id = $1 AND deleted_at IS NULL
+          AND EXISTS (
+            SELECT 1 FROM workspace_access wu
+            WHERE wu.workspace_id = $1 AND wu.user_id = $2
           )
         ORDER BY created_at DESC
         LIMIT $3 OFFSET $4
       "#,
-      workspace_id,
-      user_id,
-      limit as i64,
-      offset as i64
     )
-    .fetch_all(&self.db)
+    .bind(workspace_id)
+    .bind(user_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&mut *conn)
     .await?;
 
-    Ok((contacts, total_count as u64))
+    let (contacts, total_count) = pagination::into_page(rows, offset);
+    let total_count = match total_count {
+      Some(total_count) => total_count,
+      // Past the last page there is no row carrying the total, so count separately
+      None => sqlx::query_scalar!(
+        r#"
+          SELECT COUNT(*) 
+          FROM contacts 
+          WHERE workspace_id = $1 AND deleted_at IS NULL
+            AND EXISTS (
+              SELECT 1 FROM workspace_access wu
+              WHERE wu.workspace_id = $1 AND wu.user_id = $2
+            )
+        "#,
+        workspace_id,
+        user_id
+      )
+      .fetch_one(&mut *conn)
+      .await?
+      .unwrap_or(0) as u64,
+    };
+
+    Ok((self.open_all(contacts)?, total_count))
   }
 
   async fn find_by_id_and_workspace(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<Contact>> {
+    let mut conn = self.read_pool.acquire().await?;
     let contact = sqlx::query_as!(
       Contact,
       r#"
         SELECT 
           id, code, name, email, position, type as contact_type, 
-          address, is_active, workspace_id, created_by, updated_by, created_at, updated_at
+          address, tax_id, bank_account, is_active, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
         FROM contacts 
-        WHERE id = $1 AND workspace_id = $2
-          AND id IN (
-            SELECT c.id FROM contacts c
-            JOIN workspaces w ON c.workspace_id = w.id
-            JOIN workspace_users wu ON w.id = wu.workspace_id
-            WHERE wu.user_id = $3
+        WHERE id = $1 AND workspace_id = $2 AND deleted_at IS NULL
+          AND EXISTS (
+            SELECT 1 FROM workspace_access wu
+            WHERE wu.workspace_id = $2 AND wu.user_id = $3
           )
       "#,
       id,
       workspace_id,
       user_id
     )
-    .fetch_optional(&self.db)
+    .fetch_optional(&mut *conn)
     .await?;
 
-    Ok(contact)
+    contact.map(|contact| self.open(contact)).transpose()
   }
 
   async fn find_by_type_and_workspace(&self, contact_type: &str, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Contact>> {
+    let mut conn = self.read_pool.acquire().await?;
     let contacts = sqlx::query_as!(
       Contact,
       r#"
         SELECT 
           id, code, name, email, position, type as contact_type, 
-          address, is_active, workspace_id, created_by, updated_by, created_at, updated_at
+          address, tax_id, bank_account, is_active, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
         FROM contacts 
-        WHERE type = $1 AND workspace_id = $2
-          AND id IN (
-            SELECT c.id FROM contacts c
-            JOIN workspaces w ON c.workspace_id = w.id
-            JOIN workspace_users wu ON w.id = wu.workspace_id
-            WHERE wu.user_id = $3
+        WHERE type = $1 AND workspace_id = $2 AND deleted_at IS NULL
+          AND EXISTS (
+            SELECT 1 FROM workspace_access wu
+            WHERE wu.workspace_id = $2 AND wu.user_id = $3
           )
         ORDER BY created_at DESC
       "#,
@@ -188,55 +372,123 @@ impl ContactRepository for SqlxContactRepository {
       workspace_id,
       user_id
     )
-    .fetch_all(&self.db)
+    .fetch_all(&mut *conn)
     .await?;
 
-    Ok(contacts)
+    self.open_all(contacts)
   }
 
   async fn find_active_by_workspace(&self, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Contact>> {
+    let mut conn = self.read_pool.acquire().await?;
     let contacts = sqlx::query_as!(
       Contact,
       r#"
         SELECT 
           id, code, name, email, position, type as contact_type, 
-          address, is_active, workspace_id, created_by, updated_by, created_at, updated_at
+          address, tax_id, bank_account, is_active, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
         FROM contacts 
-        WHERE workspace_id = $1 AND is_active = true
-          AND id IN (
-            SELECT c.id FROM contacts c
-            JOIN workspaces w ON c.workspace_id = w.id
-            JOIN workspace_users wu ON w.id = wu.workspace_id
-            WHERE wu.user_id = $2
+        WHERE workspace_id = $1 AND is_active = true AND deleted_at IS NULL
+          AND EXISTS (
+            SELECT 1 FROM workspace_access wu
+            WHERE wu.workspace_id = $1 AND wu.user_id = $2
           )
         ORDER BY created_at DESC
       "#,
       workspace_id,
       user_id
     )
-    .fetch_all(&self.db)
+    .fetch_all(&mut *conn)
     .await?;
 
-    Ok(contacts)
+    self.open_all(contacts)
+  }
+
+  async fn find_changed(
+    &self,
+    workspace_id: Uuid,
+    user_id: Uuid,
+    after: Option<(DateTime<Utc>, Uuid)>,
+    settled_before: DateTime<Utc>,
+    limit: i64,
+  ) -> AppResult<Vec<Contact>> {
+    // On the primary: a replica lagging behind would hand out cursors past rows it has not seen yet
+    let mut conn = self.db.acquire().await?;
+    let contacts = match after {
+      Some((updated_at, id)) => {
+        sqlx::query_as!(
+          Contact,
+          r#"
+            SELECT
+              id, code, name, email, position, type as contact_type,
+              address, tax_id, bank_account, is_active, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
+            FROM contacts
+            WHERE workspace_id = $1 AND (updated_at, id) > ($3, $4) AND updated_at < $5 AND deleted_at IS NULL
+              AND EXISTS (
+                SELECT 1 FROM workspace_access wu
+                WHERE wu.workspace_id = $1 AND wu.user_id = $2
+              )
+            ORDER BY updated_at, id
+            LIMIT $6
+          "#,
+          workspace_id,
+          user_id,
+          updated_at,
+          id,
+          settled_before,
+          limit
+        )
+        .fetch_all(&mut *conn)
+        .await?
+      }
+      None => {
+        let mut latest = sqlx::query_as!(
+          Contact,
+          r#"
+            SELECT
+              id, code, name, email, position, type as contact_type,
+              address, tax_id, bank_account, is_active, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
+            FROM contacts
+            WHERE workspace_id = $1 AND updated_at < $3 AND deleted_at IS NULL
+              AND EXISTS (
+                SELECT 1 FROM workspace_access wu
+                WHERE wu.workspace_id = $1 AND wu.user_id = $2
+              )
+            ORDER BY updated_at DESC, id DESC
+            LIMIT $4
+          "#,
+          workspace_id,
+          user_id,
+          settled_before,
+          limit
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        latest.reverse();
+        latest
+      }
+    };
+
+    self.open_all(contacts)
   }
 
   async fn find_by_code_and_workspace(&self, code: &str, workspace_id: Uuid) -> AppResult<Option<Contact>> {
+    let mut conn = self.db.acquire().await?;
     let contact = sqlx::query_as!(
       Contact,
       r#"
         SELECT 
           id, code, name, email, position, type as contact_type, 
-          address, is_active, workspace_id, created_by, updated_by, created_at, updated_at
+          address, tax_id, bank_account, is_active, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
         FROM contacts 
         WHERE code = $1 AND workspace_id = $2
       "#,
       code,
       workspace_id
     )
-    .fetch_optional(&self.db)
+    .fetch_optional(&mut *conn)
     .await?;
 
-    Ok(contact)
+    contact.map(|contact| self.open(contact)).transpose()
   }
 
   async fn update_by_workspace(
@@ -246,6 +498,8 @@ impl ContactRepository for SqlxContactRepository {
     contact_data: UpdateContactRequest,
     updated_by: Uuid,
   ) -> AppResult<Option<Contact>> {
+    let (tax_id, bank_account) = self.seal(contact_data.tax_id.as_deref(), contact_data.bank_account.as_deref())?;
+    let mut conn = self.db.acquire().await?;
     let contact = sqlx::query_as!(
       Contact,
       r#"
@@ -257,13 +511,15 @@ impl ContactRepository for SqlxContactRepository {
           position = COALESCE($4, position),
           type = COALESCE($5, type),
           address = COALESCE($6, address),
-          is_active = COALESCE($7, is_active),
-          updated_by = $8,
+          tax_id = COALESCE($7, tax_id),
+          bank_account = COALESCE($8, bank_account),
+          is_active = COALESCE($9, is_active),
+          updated_by = $10,
           updated_at = NOW()
-        WHERE id = $9 AND workspace_id = $10
+        WHERE id = $11 AND workspace_id = $12 AND deleted_at IS NULL
         RETURNING 
           id, code, name, email, position, type as contact_type, 
-          address, is_active, workspace_id, created_by, updated_by, created_at, updated_at
+          address, tax_id, bank_account, is_active, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
       "#,
       contact_data.code,
       contact_data.name,
@@ -271,54 +527,113 @@ impl ContactRepository for SqlxContactRepository {
       contact_data.position,
       contact_data.contact_type,
       contact_data.address,
+      tax_id,
+      bank_account,
       contact_data.is_active,
       updated_by,
       id,
       workspace_id
     )
-    .fetch_optional(&self.db)
+    .fetch_optional(&mut *conn)
     .await?;
 
-    Ok(contact)
+    contact.map(|contact| self.open(contact)).transpose()
+  }
+
+  async fn replace_by_workspace(&self, id: Uuid, workspace_id: Uuid, fields: ContactPatchTarget, updated_by: Uuid) -> AppResult<Option<Contact>> {
+    let (tax_id, bank_account) = self.seal(fields.tax_id.as_deref(), fields.bank_account.as_deref())?;
+    let mut conn = self.db.acquire().await?;
+    let contact = sqlx::query_as!(
+      Contact,
+      r#"
+        UPDATE contacts
+        SET
+          code = $1,
+          name = $2,
+          email = $3,
+          position = $4,
+          type = $5,
+          address = $6,
+          tax_id = $7,
+          bank_account = $8,
+          is_active = $9,
+          updated_by = $10,
+          updated_at = NOW()
+        WHERE id = $11 AND workspace_id = $12 AND deleted_at IS NULL
+        RETURNING
+          id, code, name, email, position, type as contact_type,
+          address, tax_id, bank_account, is_active, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
+      "#,
+      fields.code,
+      fields.name,
+      fields.email,
+      fields.position,
+      fields.contact_type,
+      fields.address,
+      tax_id,
+      bank_account,
+      fields.is_active,
+      updated_by,
+      id,
+      workspace_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    contact.map(|contact| self.open(contact)).transpose()
   }
 
   async fn delete_by_workspace_and_user(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<bool> {
+    let mut conn = self.db.acquire().await?;
     let result = sqlx::query!(
-      "DELETE FROM contacts WHERE id = $1 AND workspace_id = $2 AND created_by = $3",
+      r#"
+        UPDATE contacts
+        SET deleted_at = NOW(), updated_by = $3, updated_at = NOW()
+        WHERE id = $1 AND workspace_id = $2 AND created_by = $3 AND deleted_at IS NULL
+      "#,
       id,
       workspace_id,
       user_id
     )
-    .execute(&self.db)
+    .execute(&mut *conn)
     .await?;
 
     Ok(result.rows_affected() > 0)
   }
 
-  async fn get_next_available_code(&self, workspace_id: Uuid, contact_name: &str) -> AppResult<String> {
-    let code_generator = CodeGenerator::new(self.db.clone());
-    let config = CodeGeneratorConfig {
-      table_name: "contacts".to_string(),
-      code_column: "code".to_string(),
-      workspace_column: Some("workspace_id".to_string()),
-      prefix_length: 2,
-      number_length: 5,
-      separator: "-".to_string(),
-    };
+  async fn restore_by_workspace_and_user(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<Contact>> {
+    let mut conn = self.db.acquire().await?;
+    let contact = sqlx::query_as!(
+      Contact,
+      r#"
+        UPDATE contacts
+        SET deleted_at = NULL, updated_by = $3, updated_at = NOW()
+        WHERE id = $1 AND workspace_id = $2 AND created_by = $3 AND deleted_at IS NOT NULL
+        RETURNING
+          id, code, name, email, position, type as contact_type,
+          address, tax_id, bank_account, is_active, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
+      "#,
+      id,
+      workspace_id,
+      user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
 
-    code_generator.get_next_available_code(&config, contact_name, Some(workspace_id)).await
+    contact.map(|contact| self.open(contact)).transpose()
   }
 
-  async fn code_exists(&self, code: &str, workspace_id: Uuid) -> AppResult<bool> {
+  async fn get_next_available_code(
+    &self,
+    workspace_id: Uuid,
+    contact_name: &str,
+    rules: Option<&CodeRules>,
+    category: Option<&str>,
+  ) -> AppResult<String> {
     let code_generator = CodeGenerator::new(self.db.clone());
-    let config = CodeGeneratorConfig {
-      table_name: "contacts".to_string(),
-      code_column: "code".to_string(),
-      workspace_column: Some("workspace_id".to_string()),
-      ..Default::default()
-    };
+    let config = code_config().with_rules(rules, category);
 
-    code_generator.code_exists(&config, code, Some(workspace_id)).await
+    code_generator.get_next_available_code(&config, contact_name, Some(workspace_id)).await
   }
 
   async fn find_by_filters_paginated(
@@ -329,44 +644,76 @@ impl ContactRepository for SqlxContactRepository {
     limit: u32,
     filters: ContactFilters,
   ) -> AppResult<(Vec<Contact>, u64)> {
-    use super::contact_query_builder::ContactQueryBuilder;
+    let (contacts, total_count) = self.find_page(workspace_id, user_id, page, limit, &filters).await?;
+    let contacts = self.open_all(contacts)?;
 
-    let offset = (page - 1) * limit;
+    tracing::debug!("Found {} contacts with total count {}", contacts.len(), total_count);
 
-    // Build queries using Sea Query
-    let (mut select_sql, count_sql) = ContactQueryBuilder::build_filtered_query(workspace_id, user_id, &filters);
+    Ok((contacts, total_count))
+  }
 
-    // Add pagination to select query
-    select_sql = format!("{} LIMIT {} OFFSET {}", select_sql, limit, offset);
+  async fn stream_by_filters(&self, workspace_id: Uuid, user_id: Uuid, filters: ContactFilters, rows: RowSender<Contact>) -> AppResult<()> {
+    let mut conn = self.read_pool.acquire().await?;
+    let (sql, values) = Self::build_stream_query(workspace_id, user_id, &filters);
+
+    let mut contacts = sqlx::query_as_with::<_, Contact, _>(&sql, values).fetch(&mut *conn);
+    while let Some(contact) = contacts.try_next().await.map_err(|e| {
+      tracing::error!("Failed to stream contacts: {}", e);
+      crate::errors::AppError::from_sqlx_error(e, "SELECT streamed contacts")
+    })? {
+      if rows.send(self.open(contact)).await.is_err() {
+        // The client went away; stop reading
+        break;
+      }
+    }
 
-    tracing::debug!("Executing count query: {}", count_sql);
-    tracing::debug!("Executing select query: {}", select_sql);
+    Ok(())
+  }
 
-    // Execute count query first
-    let total_count: i64 = sqlx::query_scalar::<_, Option<i64>>(&count_sql)
-      .fetch_one(&self.db)
-      .await
-      .map_err(|e| {
-        tracing::error!("Failed to execute count query: {}", e);
-        tracing::error!("Count query: {}", count_sql);
-        crate::errors::AppError::from_sqlx_error(e, &count_sql)
-      })?
-      .unwrap_or(0);
+  async fn reencrypt_sensitive_fields(&self, batch_size: u32) -> AppResult<u64> {
+    let Some(current) = self.cipher.current_prefix() else {
+      return Ok(0);
+    };
 
-    // If count is 0, return empty result
-    if total_count == 0 {
-      return Ok((vec![], 0));
+    let mut conn = self.db.acquire().await?;
+    let mut updated = 0;
+    loop {
+      // Each batch is locked and rewritten in its own transaction
+      let mut tx = conn.begin().await?;
+      let stale = sqlx::query!(
+        r#"
+          SELECT id, tax_id, bank_account
+          FROM contacts
+          WHERE LEFT(tax_id, LENGTH($1)) <> $1 OR LEFT(bank_account, LENGTH($1)) <> $1
+          LIMIT $2
+          FOR UPDATE SKIP LOCKED
+        "#,
+        current,
+        batch_size as i64
+      )
+      .fetch_all(&mut *tx)
+      .await?;
+      if stale.is_empty() {
+        break;
+      }
+
+      for row in &stale {
+        let tax_id = self.cipher.decrypt_opt(TAX_ID, row.tax_id.as_deref())?;
+        let bank_account = self.cipher.decrypt_opt(BANK_ACCOUNT, row.bank_account.as_deref())?;
+        let (tax_id, bank_account) = self.seal(tax_id.as_deref(), bank_account.as_deref())?;
+        sqlx::query!(
+          "UPDATE contacts SET tax_id = $1, bank_account = $2 WHERE id = $3",
+          tax_id,
+          bank_account,
+          row.id
+        )
+        .execute(&mut *tx)
+        .await?;
+      }
+      tx.commit().await?;
+      updated += stale.len() as u64;
     }
 
-    // Execute data query
-    let contacts = sqlx::query_as::<_, Contact>(&select_sql).fetch_all(&self.db).await.map_err(|e| {
-      tracing::error!("Failed to execute filtered query: {}", e);
-      tracing::error!("Query: {}", select_sql);
-      crate::errors::AppError::from_sqlx_error(e, &select_sql)
-    })?;
-
-    tracing::debug!("Found {} contacts with total count {}", contacts.len(), total_count);
-
-    Ok((contacts, total_count as u64))
+    Ok(updated)
   }
 }
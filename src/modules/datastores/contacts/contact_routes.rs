@@ -2,17 +2,29 @@ use std::sync::Arc;
 
 use axum::{
   Router,
-  routing::{delete, get, post, put},
+  routing::{delete, get, patch, post, put},
 };
 
-use crate::{AppState, modules::datastores::contacts::contact_handlers};
+use crate::{
+  AppState,
+  modules::{datastores::contacts::contact_handlers, record_locks::record_lock_handlers, statements::statement_handlers},
+};
 
 pub fn router() -> Router<Arc<AppState>> {
-  Router::new()
-    .route("/", get(contact_handlers::get_list))
+  let router = Router::new()
+    .route("/", get(contact_handlers::list))
     .route("/", post(contact_handlers::create))
     .route("/next-code", get(contact_handlers::get_next_code))
+    .route("/validate-tax-id", post(contact_handlers::validate_tax_id))
     .route("/:id", get(contact_handlers::get_by_id))
     .route("/:id", put(contact_handlers::update))
+    .route("/:id", patch(contact_handlers::patch))
     .route("/:id", delete(contact_handlers::delete))
+    .route("/:id/restore", post(contact_handlers::restore))
+    .route("/:id/statement", get(statement_handlers::get_statement))
+    .route("/:id/lock", post(record_lock_handlers::lock_contact))
+    .route("/:id/lock", delete(record_lock_handlers::unlock_contact));
+  #[cfg(feature = "rendering")]
+  let router = router.route("/:id/pdf", get(crate::modules::rendering::rendering_handlers::print_contact));
+  router
 }
@@ -1,3 +1,5 @@
+#[cfg(feature = "contacts")]
 pub mod contacts;
+#[cfg(feature = "products")]
 pub mod products;
 pub mod workspaces;
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+
+use crate::{
+  AppResult, AppState,
+  helper::{RequireRole, RequiredWorkspace, workspace::role::Member},
+  modules::{
+    auth::current_user::CurrentUser,
+    datastores::products::category_models::{CategoryTreeNode, build_category_tree},
+  },
+  responses::ApiResponse,
+};
+
+/// Handles the request for the product categories of the workspace as a tree.
+///
+/// Every node carries the product count and stock value of its own products and of its whole
+/// subtree, all computed by one query.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `_member`: Rejects callers below the member role in the workspace.
+///
+/// # Returns
+///
+/// A `Json` response with the root categories, ordered by name at every level.
+pub async fn get_tree(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+) -> AppResult<Json<ApiResponse<Vec<CategoryTreeNode>>>> {
+  let categories = state
+    .product_repository
+    .find_category_aggregates(workspace_id, current_user.user_id)
+    .await?;
+
+  let response = ApiResponse::success(build_category_tree(categories), "Category tree retrieved successfully");
+  Ok(Json(response))
+}
@@ -0,0 +1,82 @@
+use std::collections::{HashMap, HashSet};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A product category with the aggregates of its products, as returned by
+/// `ProductRepository::find_category_aggregates`.
+#[derive(Debug, Clone, FromRow)]
+pub struct CategoryAggregate {
+  pub id: Uuid,
+  pub code: String,
+  pub name: String,
+  pub parent_id: Option<Uuid>,
+  pub is_active: bool,
+  pub product_count: i64,
+  pub stock_value: Decimal,
+  pub total_product_count: i64,
+  pub total_stock_value: Decimal,
+}
+
+/// A node of the category tree returned by `GET /api/v1/categories/tree`.
+///
+/// Counts are of active products; the stock value is the stock of those tracking inventory
+/// valued at their unit cost. `product_count` and `stock_value` cover the products of this
+/// category only, the `total_` fields also those of every category below it.
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryTreeNode {
+  pub id: Uuid,
+  pub code: String,
+  pub name: String,
+  pub parent_id: Option<Uuid>,
+  pub is_active: bool,
+  pub product_count: i64,
+  pub stock_value: Decimal,
+  pub total_product_count: i64,
+  pub total_stock_value: Decimal,
+  pub children: Vec<CategoryTreeNode>,
+}
+
+impl CategoryTreeNode {
+  fn new(category: CategoryAggregate) -> Self {
+    Self {
+      id: category.id,
+      code: category.code,
+      name: category.name,
+      parent_id: category.parent_id,
+      is_active: category.is_active,
+      product_count: category.product_count,
+      stock_value: category.stock_value,
+      total_product_count: category.total_product_count,
+      total_stock_value: category.total_stock_value,
+      children: Vec::new(),
+    }
+  }
+}
+
+/// Nests `categories` under their parents, keeping their order at every level. Categories whose
+/// parent is not among them are roots; categories in a parent cycle are left out, as no root
+/// leads to them.
+pub fn build_category_tree(categories: Vec<CategoryAggregate>) -> Vec<CategoryTreeNode> {
+  let ids: HashSet<Uuid> = categories.iter().map(|category| category.id).collect();
+  let mut children: HashMap<Option<Uuid>, Vec<CategoryTreeNode>> = HashMap::new();
+  for category in categories {
+    let parent = category.parent_id.filter(|parent| ids.contains(parent));
+    children.entry(parent).or_default().push(CategoryTreeNode::new(category));
+  }
+
+  fn attach(node: &mut CategoryTreeNode, children: &mut HashMap<Option<Uuid>, Vec<CategoryTreeNode>>) {
+    node.children = children.remove(&Some(node.id)).unwrap_or_default();
+    for child in &mut node.children {
+      attach(child, children);
+    }
+  }
+
+  let mut roots = children.remove(&None).unwrap_or_default();
+  for root in &mut roots {
+    attach(root, &mut children);
+  }
+  roots
+}
@@ -1,3 +1,5 @@
+pub mod category_handlers;
+pub mod category_models;
 pub mod product_handlers;
 pub mod product_models;
 pub mod product_query_builder;
@@ -2,47 +2,101 @@ use std::sync::Arc;
 
 use crate::{
   AppResult, AppState,
-  errors::{AppError, NotFoundError},
-  helper::{WorkspaceContext, workspace::check_workspace_permission},
-  impl_next_code_handler,
+  errors::AppError,
+  events::{RecordAction, WorkspaceEvent},
+  helper::{Pagination, PathUuid, RequireRole, RequiredWorkspace, ValidatedQuery, workspace::role::Member},
+  impl_next_code_handler, internal_error,
   modules::{
+    approvals::approval_service,
     auth::current_user::CurrentUser,
-    datastores::{
-      products::product_models::{CreateProductRequest, GetProductsQuery, ProductFilters, ProductResponse, UpdateProductRequest},
-      workspaces::workspace_models::WorkspaceRole,
+    datastores::products::{
+      product_models::{
+        AvailabilityRequest, ChangeProductStatusRequest, CreateProductQuery, CreateProductRequest, GetProductQuery, GetProductsQuery,
+        LineAvailability, Product, ProductFilters, ProductPatchTarget, ProductResponse, ProductStatus, UpdateProductRequest, check_availability,
+        check_stock_levels,
+      },
+      product_repository,
     },
+    operations::operation_service,
+    usage::record_quota::check_record_quota,
+    workspace_settings::field_policy_service::{self, Redacted},
+  },
+  responses::{ApiResponse, Created, PaginatedResponse, PaginationMeta},
+  utils::{
+    db_session,
+    merge_patch::{MergePatch, apply_merge_patch},
+    ndjson,
+    next_code_macro::NextCodeQuery,
   },
-  responses::{ApiResponse, PaginatedResponse, PaginationMeta},
-  utils::{code_generator::CodeGeneratorConfig, next_code_macro::NextCodeQuery},
 };
 use axum::{
   Json,
-  extract::{
-    Path, Query, State,
-    rejection::{JsonRejection, QueryRejection},
-  },
-  http::StatusCode,
+  extract::{State, rejection::JsonRejection},
+  http::HeaderMap,
+  response::{IntoResponse, Response},
 };
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationErrors};
 
-const DEFAULT_PAGE: u32 = 1;
-const DEFAULT_LIMIT: u32 = 10;
-const MAX_LIMIT: u32 = 100;
+/// Possible duplicates listed when a create is refused as one.
+const MAX_DUPLICATE_CANDIDATES: i64 = 5;
 
 // Generate next_code handler using macro
-impl_next_code_handler!(
-  get_next_code,
-  "product",
-  CodeGeneratorConfig {
-    table_name: "products".to_string(),
-    code_column: "code".to_string(),
-    workspace_column: Some("workspace_id".to_string()),
-    prefix_length: 2,
-    number_length: 5,
-    separator: "-".to_string(),
+impl_next_code_handler!(get_next_code, "product", product_repository::code_config());
+
+/// Handles `GET` on the product collection. Clients sending `Accept: application/x-ndjson` receive
+/// every matching product as one JSON object per line, streamed for full syncs (`page` and `limit`
+/// do not apply); all others get the paginated list of `get_list`. Adding `Prefer: respond-async`
+/// exports the products in the background instead, as an operation whose result lists them.
+///
+/// # Arguments
+///
+/// * `headers`: The request headers, checked for `Accept` and `Prefer`.
+/// * The remaining arguments are those of `get_list`.
+///
+/// # Returns
+///
+/// A streamed `application/x-ndjson` response of `ProductResponse` objects, or the `get_list` response.
+pub async fn list(
+  state: State<Arc<AppState>>,
+  headers: HeaderMap,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+  query: ValidatedQuery<GetProductsQuery>,
+  pagination: Pagination,
+) -> AppResult<Response> {
+  if !ndjson::accepts_ndjson(&headers) {
+    let response = get_list(state, current_user, workspace, member, query, pagination).await?;
+    return Ok(response.into_response());
+  }
+
+  let (State(state), RequiredWorkspace(workspace_id), ValidatedQuery(mut params)) = (state, workspace, query);
+  pagination.apply_sort_defaults(&mut params.sort_by, &mut params.sort_order);
+  let repository = state.product_repository.clone();
+  let filters = ProductFilters::from(params);
+  let user_id = current_user.user_id;
+  let policy = field_policy_service::resolve(&state, workspace_id, "products", member.role).await?;
+
+  let redact = move |product: Product| policy.redact(ProductResponse::from(product));
+
+  if operation_service::prefers_async(&headers) {
+    return operation_service::start(&state, workspace_id, user_id, "products.export", move |progress| async move {
+      let products = operation_service::collect_pages(&progress, |page, limit| {
+        repository.find_by_filters_paginated(workspace_id, user_id, page, limit, filters.clone())
+      })
+      .await?;
+      let products: Vec<_> = products.into_iter().map(redact).collect();
+      serde_json::to_value(products).map_err(|e| internal_error!("Failed to serialize the exported products: {}", e))
+    })
+    .await;
   }
-);
+
+  tracing::debug!("Streaming products for workspace_id {}", workspace_id);
+  Ok(ndjson::stream_with(state.db.clone(), redact, move |rows| async move {
+    repository.stream_by_filters(workspace_id, user_id, filters, rows).await
+  }))
+}
 
 /// Handles the request to retrieve a paginated list of products for the authenticated user.
 /// This handler will get products from the user's default workspace or all accessible workspaces.
@@ -50,8 +104,10 @@ impl_next_code_handler!(
 /// # Arguments
 ///
 /// * `State(state)`: The shared application state.
-/// * `Query(params)`: The query parameters for pagination (`page`, `limit`).
 /// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy applied.
+/// * `ValidatedQuery(params)`: The validated query parameters for filtering and sorting.
+/// * `pagination`: The validated `page` and `limit` query parameters.
 ///
 /// # Returns
 ///
@@ -59,40 +115,29 @@ impl_next_code_handler!(
 #[axum::debug_handler]
 pub async fn get_list(
   State(state): State<Arc<AppState>>,
-  query_params: Result<Query<GetProductsQuery>, QueryRejection>,
   current_user: CurrentUser,
-  WorkspaceContext(workspace_id): WorkspaceContext, // Extracted from request headers
-) -> AppResult<Json<ApiResponse<PaginatedResponse<ProductResponse>>>> {
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
+  ValidatedQuery(mut params): ValidatedQuery<GetProductsQuery>,
+  pagination: Pagination,
+) -> AppResult<Json<ApiResponse<PaginatedResponse<Redacted<ProductResponse>>>>> {
   let repository = &state.product_repository;
+  let policy = field_policy_service::resolve(&state, workspace_id, "products", member.role).await?;
 
-  // Handle query parameter parsing errors
-  let params = match query_params {
-    Ok(Query(params)) => params,
-    Err(rejection) => return Err(crate::errors::AppError::from(rejection)),
-  };
-
-  let page = params.page.unwrap_or(DEFAULT_PAGE);
-  let mut limit = params.limit.unwrap_or(DEFAULT_LIMIT);
-
-  if limit > MAX_LIMIT {
-    limit = MAX_LIMIT;
-  }
+  let Pagination { page, limit, .. } = pagination;
+  pagination.apply_sort_defaults(&mut params.sort_by, &mut params.sort_order);
+  // The unfiltered query always lists the newest first
+  let filtered = super::product_query_builder::has_filters(&params) || params.sort_by.is_some() || params.sort_order.is_some();
 
   tracing::debug!(
-    "Fetching products for workspace_id {}: page={}, limit={}, has_filters={}",
+    "Fetching products for workspace_id {}: page={}, limit={}, filtered={}",
     workspace_id,
     page,
     limit,
-    super::product_query_builder::has_filters(&params)
+    filtered
   );
 
-  // Check workspace permissions
-  let workspace_repository = &state.workspace_repository;
-  if !check_workspace_permission(workspace_repository, workspace_id, current_user.user_id, WorkspaceRole::Member).await? {
-    return Err(AppError::Authorization("You don't have permission to access this workspace".to_string()));
-  }
-
-  let (products, total) = if super::product_query_builder::has_filters(&params) {
+  let (products, total) = if filtered {
     let filters = ProductFilters::from(params);
     repository
       .find_by_filters_paginated(workspace_id, current_user.user_id, page, limit, filters)
@@ -108,7 +153,10 @@ pub async fn get_list(
 
   let response = ApiResponse::success(
     PaginatedResponse {
-      list: products.into_iter().map(ProductResponse::from).collect(),
+      list: products
+        .into_iter()
+        .map(|product| policy.redact(ProductResponse::from(product)))
+        .collect(),
       pagination,
     },
     "Products retrieved successfully",
@@ -123,54 +171,73 @@ pub async fn get_list(
 ///
 /// * `State(state)`: The shared application state.
 /// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy applied.
+/// * `ValidatedQuery(query)`: `force=true` skips the check for duplicates.
 /// * `payload`: The JSON payload containing the new product's data.
 ///
 /// # Returns
 ///
-/// A `Json` response containing the newly created `ProductResponse`.
+/// A `Json` response containing the newly created `ProductResponse`, or a 409 listing the
+/// products of the workspace with the same barcode or a very similar name, if any.
 #[axum::debug_handler]
 pub async fn create(
   State(state): State<Arc<AppState>>,
   current_user: CurrentUser,
-  WorkspaceContext(workspace_id): WorkspaceContext, // Extracted from request headers
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
+  ValidatedQuery(query): ValidatedQuery<CreateProductQuery>,
   payload: Result<Json<CreateProductRequest>, JsonRejection>,
-) -> AppResult<(StatusCode, Json<ApiResponse<ProductResponse>>)> {
+) -> AppResult<Created<ApiResponse<Redacted<ProductResponse>>>> {
   let repository = &state.product_repository;
+  let policy = field_policy_service::resolve(&state, workspace_id, "products", member.role).await?;
 
   // Extract payload first
   let Json(mut payload) = payload?;
 
-  // Auto-generate code if field is empty
-  if payload.code.trim().is_empty() {
-    let generated_code = repository.get_next_available_code(workspace_id, &payload.name).await?;
-    tracing::debug!("Auto-generated code: {} for name: '{}'", generated_code, payload.name);
-    payload.code = generated_code;
-  }
-
-  // Now validate with the final code
-  payload.validate()?;
-
-  tracing::debug!(
-    "Creating product with code: {} for user: {} in workspace: {}",
-    payload.code,
-    current_user.user_id,
-    workspace_id
-  );
+  // Code generation and the insert run in one transaction
+  let new_product = db_session::transaction::<_, _, AppError>(&state.db, async {
+    // Auto-generate code if field is empty
+    if payload.code.trim().is_empty() {
+      let settings = state.workspace_settings_repository.get(workspace_id).await?;
+      let rules = settings.code_rules.get("products");
+      let category = payload.category_id.map(|id| id.to_string());
+      let generated_code = repository
+        .get_next_available_code(workspace_id, &payload.name, rules, category.as_deref())
+        .await?;
+      tracing::debug!("Auto-generated code: {} for name: '{}'", generated_code, payload.name);
+      payload.code = generated_code;
+    }
 
-  // Check workspace permissions
-  let workspace_repository = &state.workspace_repository;
-  if !check_workspace_permission(workspace_repository, workspace_id, current_user.user_id, WorkspaceRole::Member).await? {
-    return Err(AppError::Authorization(
-      "You don't have permission to create products in this workspace".to_string(),
-    ));
-  }
+    // Now validate with the final code
+    payload.validate()?;
+    reject_deleted_code(&state, workspace_id, &payload.code).await?;
+
+    if !query.force {
+      let barcode = payload.barcode.as_deref().filter(|barcode| !barcode.trim().is_empty());
+      let mut candidates = repository
+        .find_possible_duplicates(workspace_id, &payload.name, barcode, MAX_DUPLICATE_CANDIDATES)
+        .await?;
+      if !candidates.is_empty() {
+        if policy.is_hidden("barcode") {
+          candidates.iter_mut().for_each(|candidate| candidate.barcode = None);
+        }
+        let candidates = serde_json::to_value(candidates).map_err(|e| internal_error!("Failed to serialize duplicate candidates: {}", e))?;
+        return Err(AppError::PossibleDuplicates(candidates));
+      }
+    }
 
-  // Check if code already exists in this workspace
-  if repository.code_exists(&payload.code, workspace_id).await? {
-    return Err(AppError::Conflict("Product code already exists in this workspace".to_string()));
-  }
+    tracing::debug!(
+      "Creating product with code: {} for user: {} in workspace: {}",
+      payload.code,
+      current_user.user_id,
+      workspace_id
+    );
 
-  let new_product = repository.create_by_workspace(payload, workspace_id, current_user.user_id).await?;
+    let product = repository.create_by_workspace(payload, workspace_id, current_user.user_id).await?;
+    check_record_quota(&state, workspace_id).await?;
+    Ok(product)
+  })
+  .await?;
 
   tracing::info!(
     "Product created successfully: id={}, code={}, name={}",
@@ -179,8 +246,48 @@ pub async fn create(
     new_product.name
   );
 
-  let response = ApiResponse::success(ProductResponse::from(new_product), "Product created successfully");
-  Ok((StatusCode::CREATED, Json(response)))
+  let new_product = publish_change(&state, RecordAction::Created, workspace_id, current_user.user_id, new_product, false);
+
+  let location = format!("/api/v1/products/{}", new_product.id);
+  Ok(ApiResponse::created(policy.redact(new_product), "Product created successfully", location))
+}
+
+/// Handles the request to check the stock of several products at once, so order forms can
+/// validate all their lines in one call.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `_member`: Rejects callers below the member role in the workspace.
+/// * `payload`: The lines to check, each a product, a quantity and optionally a warehouse.
+///
+/// # Returns
+///
+/// A `Json` response with the availability of each line, in the order of the request.
+pub async fn availability(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  payload: Result<Json<AvailabilityRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<Vec<LineAvailability>>>> {
+  let Json(payload) = payload?;
+  payload.validate()?;
+  if payload.lines.iter().any(|line| line.quantity < 1) {
+    return Err(AppError::validation("lines", "Quantities must be at least 1"));
+  }
+
+  let mut ids: Vec<Uuid> = payload.lines.iter().map(|line| line.product_id).collect();
+  ids.sort_unstable();
+  ids.dedup();
+  let stock = state
+    .product_repository
+    .find_stock_by_ids(&ids, workspace_id, current_user.user_id)
+    .await?;
+
+  let response = ApiResponse::success(check_availability(&payload.lines, &stock), "Availability checked successfully");
+  Ok(Json(response))
 }
 
 /// Handles the request to retrieve a specific product by its ID.
@@ -189,8 +296,11 @@ pub async fn create(
 /// # Arguments
 ///
 /// * `State(state)`: The shared application state.
-/// * `Path(id)`: The UUID of the product to retrieve.
+/// * `PathUuid(id)`: The UUID of the product to retrieve.
 /// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy applied.
+/// * `ValidatedQuery(query)`: With `as_of`, the product is read as it was at that time from its
+///   revisions, e.g. to settle a dispute over a past price. A 404 if it did not exist then.
 ///
 /// # Returns
 ///
@@ -198,11 +308,14 @@ pub async fn create(
 #[axum::debug_handler]
 pub async fn get_by_id(
   State(state): State<Arc<AppState>>,
-  Path(id): Path<Uuid>,
+  PathUuid(id): PathUuid,
   current_user: CurrentUser,
-  WorkspaceContext(workspace_id): WorkspaceContext, // Extracted from request headers
-) -> AppResult<Json<ApiResponse<ProductResponse>>> {
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
+  ValidatedQuery(query): ValidatedQuery<GetProductQuery>,
+) -> AppResult<Json<ApiResponse<Redacted<ProductResponse>>>> {
   let repository = &state.product_repository;
+  let policy = field_policy_service::resolve(&state, workspace_id, "products", member.role).await?;
 
   tracing::debug!(
     "Fetching product with id: {} for user: {} in workspace: {}",
@@ -211,23 +324,13 @@ pub async fn get_by_id(
     workspace_id
   );
 
-  // Check workspace permissions
-  let workspace_repository = &state.workspace_repository;
-  if !check_workspace_permission(workspace_repository, workspace_id, current_user.user_id, WorkspaceRole::Member).await? {
-    return Err(AppError::Authorization("You don't have permission to access this workspace".to_string()));
+  let product = match query.as_of {
+    Some(as_of) => repository.find_as_of(id, workspace_id, current_user.user_id, as_of).await?,
+    None => repository.find_by_id_and_workspace(id, workspace_id, current_user.user_id).await?,
   }
+  .ok_or_else(|| AppError::not_found_with_id("Product", id))?;
 
-  let product = repository
-    .find_by_id_and_workspace(id, workspace_id, current_user.user_id)
-    .await?
-    .ok_or_else(|| {
-      AppError::NotFound(NotFoundError {
-        resource: "Product".to_string(),
-        id: Some(id),
-      })
-    })?;
-
-  let response = ApiResponse::success(ProductResponse::from(product), "Product retrieved successfully");
+  let response = ApiResponse::success(policy.redact(ProductResponse::from(product)), "Product retrieved successfully");
   Ok(Json(response))
 }
 
@@ -237,8 +340,9 @@ pub async fn get_by_id(
 /// # Arguments
 ///
 /// * `State(state)`: The shared application state.
-/// * `Path(id)`: The UUID of the product to update.
+/// * `PathUuid(id)`: The UUID of the product to update.
 /// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy and approval rules applied.
 /// * `payload`: The JSON payload containing the updated product data.
 ///
 /// # Returns
@@ -247,13 +351,23 @@ pub async fn get_by_id(
 #[axum::debug_handler]
 pub async fn update(
   State(state): State<Arc<AppState>>,
-  Path(id): Path<Uuid>,
+  PathUuid(id): PathUuid,
   current_user: CurrentUser,
-  WorkspaceContext(workspace_id): WorkspaceContext, // Extracted from request headers
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
   payload: Result<Json<UpdateProductRequest>, JsonRejection>,
-) -> AppResult<Json<ApiResponse<ProductResponse>>> {
+) -> AppResult<Json<ApiResponse<Redacted<ProductResponse>>>> {
   let repository = &state.product_repository;
   let Json(payload) = payload?;
+  payload.validate()?;
+  let policy = field_policy_service::resolve(&state, workspace_id, "products", member.role).await?;
+  policy.check_update(&payload)?;
+  // Fields left out or null keep their value, so they are no part of the change held
+  let mut changes = serde_json::to_value(&payload).map_err(|e| internal_error!("Failed to serialize update request: {}", e))?;
+  if let Some(fields) = changes.as_object_mut() {
+    fields.retain(|_, value| !value.is_null());
+  }
+  approval_service::hold_product_change(&state, workspace_id, member.role, current_user.user_id, id, &changes).await?;
 
   tracing::debug!(
     "Updating product with id: {} for user: {} in workspace: {}",
@@ -262,45 +376,36 @@ pub async fn update(
     workspace_id
   );
 
-  // Check workspace permissions
-  let workspace_repository = &state.workspace_repository;
-  if !check_workspace_permission(workspace_repository, workspace_id, current_user.user_id, WorkspaceRole::Member).await? {
-    return Err(AppError::Authorization(
-      "You don't have permission to update products in this workspace".to_string(),
-    ));
-  }
-
-  // Check if the product exists before updating
-  if repository
-    .find_by_id_and_workspace(id, workspace_id, current_user.user_id)
-    .await?
-    .is_none()
-  {
-    return Err(AppError::NotFound(NotFoundError {
-      resource: "Product".to_string(),
-      id: Some(id),
-    }));
-  }
+  // The lookup and the update run in one transaction
+  let (current, updated_product) = db_session::transaction::<_, _, AppError>(&state.db, async {
+    // Check if the product exists before updating
+    let current = repository
+      .find_by_id_and_workspace(id, workspace_id, current_user.user_id)
+      .await?
+      .ok_or_else(|| AppError::not_found_with_id("Product", id))?;
+
+    // Fields left out keep their value, so check the stock levels the product ends up with
+    check_stock_levels(
+      payload.minimum_stock.or(current.minimum_stock),
+      payload.maximum_stock.or(current.maximum_stock),
+    )
+    .map_err(|e| {
+      let mut errors = ValidationErrors::new();
+      errors.add("maximum_stock", e);
+      errors
+    })?;
 
-  // If updating code, check if the new code already exists (excluding current product)
-  if let Some(ref new_code) = payload.code {
-    let existing_product = repository.find_by_code_and_workspace(new_code, workspace_id).await?;
-    if let Some(existing) = existing_product {
-      if existing.id != id {
-        return Err(AppError::Conflict("Product code already exists in this workspace".to_string()));
-      }
+    if let Some(code) = &payload.code {
+      reject_deleted_code(&state, workspace_id, code).await?;
     }
-  }
+    let updated_product = repository
+      .update_by_workspace(id, workspace_id, payload, current_user.user_id)
+      .await?
+      .ok_or_else(|| AppError::not_found_with_id("Product", id))?;
 
-  let updated_product = repository
-    .update_by_workspace(id, workspace_id, payload, current_user.user_id)
-    .await?
-    .ok_or_else(|| {
-      AppError::NotFound(NotFoundError {
-        resource: "Product".to_string(),
-        id: Some(id),
-      })
-    })?;
+    Ok((current, updated_product))
+  })
+  .await?;
 
   tracing::info!(
     "Product updated successfully: id={}, code={}, name={}",
@@ -309,18 +414,173 @@ pub async fn update(
     updated_product.name
   );
 
-  let response = ApiResponse::success(ProductResponse::from(updated_product), "Product updated successfully");
+  let updated_product = publish_change(
+    &state,
+    RecordAction::Updated,
+    workspace_id,
+    current_user.user_id,
+    updated_product,
+    current.is_low_stock(),
+  );
+
+  let response = ApiResponse::success(policy.redact(updated_product), "Product updated successfully");
+  Ok(Json(response))
+}
+
+/// Handles a JSON Merge Patch (RFC 7396) update of a product.
+/// This handler ensures that the product belongs to the user's workspace.
+///
+/// Members missing from the patch keep their value, while an explicit `null` clears an
+/// optional field, e.g. `{"supplier_id": null}` detaches the supplier.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `PathUuid(id)`: The UUID of the product to patch.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy and approval rules applied.
+/// * `MergePatch(patch)`: The merge patch document.
+///
+/// # Returns
+///
+/// A `Json` response containing the patched `ProductResponse`.
+#[axum::debug_handler]
+pub async fn patch(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
+  MergePatch(patch): MergePatch,
+) -> AppResult<Json<ApiResponse<Redacted<ProductResponse>>>> {
+  let repository = &state.product_repository;
+  let policy = field_policy_service::resolve(&state, workspace_id, "products", member.role).await?;
+  policy.check_patch(&patch)?;
+  approval_service::hold_product_change(&state, workspace_id, member.role, current_user.user_id, id, &patch).await?;
+
+  let not_found = || AppError::not_found_with_id("Product", id);
+
+  // The lookup and the write run in one transaction
+  let (current, patched_product) = db_session::transaction::<_, _, AppError>(&state.db, async {
+    let current = repository
+      .find_by_id_and_workspace(id, workspace_id, current_user.user_id)
+      .await?
+      .ok_or_else(not_found)?;
+
+    let fields = apply_merge_patch(&ProductPatchTarget::from(&current), &patch)?;
+    fields.validate()?;
+    if fields.code != current.code {
+      reject_deleted_code(&state, workspace_id, &fields.code).await?;
+    }
+
+    let patched_product = repository
+      .replace_by_workspace(id, workspace_id, fields, current_user.user_id)
+      .await?
+      .ok_or_else(not_found)?;
+
+    Ok((current, patched_product))
+  })
+  .await?;
+
+  tracing::info!("Product patched successfully: id={}, code={}", patched_product.id, patched_product.code);
+
+  let patched_product = publish_change(
+    &state,
+    RecordAction::Updated,
+    workspace_id,
+    current_user.user_id,
+    patched_product,
+    current.is_low_stock(),
+  );
+
+  let response = ApiResponse::success(policy.redact(patched_product), "Product updated successfully");
+  Ok(Json(response))
+}
+
+/// Moves a product along its lifecycle: publishes a draft, discontinues a published product or
+/// publishes a discontinued one again (see `ProductStatus::can_become`).
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `PathUuid(id)`: The UUID of the product.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy applied.
+/// * `payload`: The status to move to.
+///
+/// # Returns
+///
+/// A `Json` response with the product, a 409 when the product cannot move to the status, or a
+/// 422 when publishing a product without a selling price.
+#[axum::debug_handler]
+pub async fn change_status(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
+  payload: Result<Json<ChangeProductStatusRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<Redacted<ProductResponse>>>> {
+  let repository = &state.product_repository;
+  let Json(ChangeProductStatusRequest { status }) = payload?;
+  let policy = field_policy_service::resolve(&state, workspace_id, "products", member.role).await?;
+
+  let not_found = || AppError::not_found_with_id("Product", id);
+
+  let (current, product) = db_session::transaction::<_, _, AppError>(&state.db, async {
+    let current = repository
+      .find_by_id_and_workspace(id, workspace_id, current_user.user_id)
+      .await?
+      .ok_or_else(not_found)?;
+
+    let conflict = || AppError::Conflict(format!("A {} product cannot become {}", current.status.as_str(), status.as_str()));
+    if !current.status.can_become(status) {
+      return Err(conflict());
+    }
+    if status == ProductStatus::Active && current.selling_price.is_zero() {
+      return Err(AppError::validation("selling_price", "A product needs a selling price to be published"));
+    }
+
+    // Only moves the product from the status checked, in case another request moved it meanwhile
+    let product = repository
+      .set_status_by_workspace(id, workspace_id, current.status, status, current_user.user_id)
+      .await?
+      .ok_or_else(conflict)?;
+
+    Ok((current, product))
+  })
+  .await?;
+
+  tracing::info!(
+    "Product status changed: id={}, {} -> {}",
+    id,
+    current.status.as_str(),
+    product.status.as_str()
+  );
+
+  let product = publish_change(
+    &state,
+    RecordAction::Updated,
+    workspace_id,
+    current_user.user_id,
+    product,
+    current.is_low_stock(),
+  );
+
+  let response = ApiResponse::success(policy.redact(product), "Product status changed successfully");
   Ok(Json(response))
 }
 
 /// Handles the request to delete a product.
+/// The product is only marked deleted, and can be brought back with `restore`.
 /// This handler ensures that the product belongs to the user's workspace.
 ///
 /// # Arguments
 ///
 /// * `State(state)`: The shared application state.
-/// * `Path(id)`: The UUID of the product to delete.
+/// * `PathUuid(id)`: The UUID of the product to delete.
 /// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; members may have to wait for an admin to approve the deletion.
 ///
 /// # Returns
 ///
@@ -328,11 +588,13 @@ pub async fn update(
 #[axum::debug_handler]
 pub async fn delete(
   State(state): State<Arc<AppState>>,
-  Path(id): Path<Uuid>,
+  PathUuid(id): PathUuid,
   current_user: CurrentUser,
-  WorkspaceContext(workspace_id): WorkspaceContext, // Extracted from request headers
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
 ) -> AppResult<Json<ApiResponse<()>>> {
   let repository = &state.product_repository;
+  approval_service::hold_product_deletion(&state, workspace_id, member.role, current_user.user_id, id).await?;
 
   tracing::debug!(
     "Deleting product with id: {} for user: {} in workspace: {}",
@@ -341,25 +603,104 @@ pub async fn delete(
     workspace_id
   );
 
-  // Check workspace permissions
-  let workspace_repository = &state.workspace_repository;
-  if !check_workspace_permission(workspace_repository, workspace_id, current_user.user_id, WorkspaceRole::Member).await? {
-    return Err(AppError::Authorization(
-      "You don't have permission to delete products in this workspace".to_string(),
-    ));
-  }
-
   let deleted = repository.delete_by_workspace_and_user(id, workspace_id, current_user.user_id).await?;
 
   if !deleted {
-    return Err(AppError::NotFound(NotFoundError {
-      resource: "Product".to_string(),
-      id: Some(id),
-    }));
+    return Err(AppError::not_found_with_id("Product", id));
   }
 
   tracing::info!("Product deleted successfully: id={}", id);
+  state.events.publish(WorkspaceEvent::record::<ProductResponse>(
+    "product",
+    RecordAction::Deleted,
+    workspace_id,
+    id,
+    current_user.user_id,
+    None,
+  ));
 
   let response = ApiResponse::success((), "Product deleted successfully");
   Ok(Json(response))
 }
+
+/// Handles the request to restore a deleted product, for when it was deleted by accident. The
+/// restored product counts toward the records of the workspace's plan again.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `PathUuid(id)`: The UUID of the deleted product.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy applied.
+///
+/// # Returns
+///
+/// A `Json` response with the restored product, or a 404 error if there is no such deleted product.
+#[axum::debug_handler]
+pub async fn restore(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace, // Extracted from request headers
+  member: RequireRole<Member>,
+) -> AppResult<Json<ApiResponse<Redacted<ProductResponse>>>> {
+  let repository = &state.product_repository;
+  let policy = field_policy_service::resolve(&state, workspace_id, "products", member.role).await?;
+
+  let product = db_session::transaction::<_, _, AppError>(&state.db, async {
+    let product = repository
+      .restore_by_workspace_and_user(id, workspace_id, current_user.user_id)
+      .await?
+      .ok_or_else(|| AppError::not_found_with_id("Deleted product", id))?;
+    check_record_quota(&state, workspace_id).await?;
+    Ok(product)
+  })
+  .await?;
+
+  tracing::info!("Product restored successfully: id={}", id);
+
+  // Its stock did not change while deleted, so a low stock is not news
+  let was_low_stock = product.is_low_stock();
+  let product = publish_change(&state, RecordAction::Restored, workspace_id, current_user.user_id, product, was_low_stock);
+
+  let response = ApiResponse::success(policy.redact(product), "Product restored successfully");
+  Ok(Json(response))
+}
+
+/// Notifies real-time clients of the workspace that a product was written, plus a
+/// `product.low_stock` event when the write took its stock down to the reorder level.
+/// Returns the product's public representation.
+/// Rejects `code` when a deleted product holds it. Deleted products keep their code so they can be
+/// restored; inserting it again would fail as a duplicate of a product no one can see.
+async fn reject_deleted_code(state: &AppState, workspace_id: Uuid, code: &str) -> AppResult<()> {
+  match state.product_repository.find_by_code_and_workspace(code, workspace_id).await? {
+    Some(product) if product.deleted_at.is_some() => Err(AppError::deleted_code("product")),
+    _ => Ok(()),
+  }
+}
+
+fn publish_change(
+  state: &AppState,
+  action: RecordAction,
+  workspace_id: Uuid,
+  user_id: Uuid,
+  product: Product,
+  was_low_stock: bool,
+) -> ProductResponse {
+  let became_low_stock = product.is_low_stock() && !was_low_stock;
+  let product = ProductResponse::from(product);
+
+  state.events.publish(WorkspaceEvent::record(
+    "product",
+    action,
+    workspace_id,
+    product.id,
+    user_id,
+    Some(&product),
+  ));
+  if became_low_stock {
+    state.events.publish(WorkspaceEvent::low_stock(workspace_id, product.id, &product));
+  }
+
+  product
+}
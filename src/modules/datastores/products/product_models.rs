@@ -1,8 +1,11 @@
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
+
+use crate::helper::pagination::{DEFAULT_LIMIT, DEFAULT_PAGE, MAX_LIMIT};
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "tax_type", rename_all = "snake_case")]
@@ -11,6 +14,40 @@ pub enum TaxType {
   FixedAmount,
 }
 
+/// Where a product stands in its lifecycle. Drafts, such as the products of bulk imports, stay out
+/// of the sales flows (availability checks, stock pushed to online stores) until published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "product_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ProductStatus {
+  Draft,
+  /// Published: sold and ordered.
+  Active,
+  /// No longer sold; can be published again.
+  Discontinued,
+}
+
+impl ProductStatus {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ProductStatus::Draft => "draft",
+      ProductStatus::Active => "active",
+      ProductStatus::Discontinued => "discontinued",
+    }
+  }
+
+  /// Whether a product can move from `self` to `next`: drafts are published, published products
+  /// are discontinued, and discontinued products published again. Products never return to draft.
+  pub fn can_become(self, next: ProductStatus) -> bool {
+    matches!(
+      (self, next),
+      (ProductStatus::Draft, ProductStatus::Active)
+        | (ProductStatus::Active, ProductStatus::Discontinued)
+        | (ProductStatus::Discontinued, ProductStatus::Active)
+    )
+  }
+}
+
 /// Represents a product record in the database.
 /// This struct is derived from `sqlx::FromRow` to allow direct mapping from database query results.
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -33,11 +70,12 @@ pub struct Product {
   pub minimum_stock: Option<i32>,
   pub maximum_stock: Option<i32>,
   pub reorder_level: Option<i32>,
-  pub stock: Option<i32>,
+  pub current_stock: Option<i32>,
   pub tax_type: Option<TaxType>,
   pub tax_rate: Option<rust_decimal::Decimal>,
   pub tax_amount: Option<rust_decimal::Decimal>,
   pub is_active: bool,
+  pub status: ProductStatus,
 
   // Metadata
   pub workspace_id: Option<Uuid>,
@@ -45,13 +83,24 @@ pub struct Product {
   pub updated_by: Option<Uuid>,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
+  /// When the product was deleted; deleted products can be restored.
+  pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl Product {
+  /// Whether inventory is tracked and the stock is at or below the reorder level.
+  pub fn is_low_stock(&self) -> bool {
+    self.track_inventory && matches!((self.current_stock, self.reorder_level), (Some(stock), Some(level)) if stock <= level)
+  }
 }
 
 /// Represents the payload for creating a new product.
 /// This struct uses `validator` to enforce declarative validation rules on the incoming data.
 /// The `created_by` field is automatically set from the authenticated user.
-/// The `workspace_id` is now extracted from request headers via WorkspaceContext, not from the body.
+/// The `workspace_id` is now extracted from request headers via RequiredWorkspace, not from the body.
 #[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[validate(schema(function = "validate_stock_levels"))]
 pub struct CreateProductRequest {
   #[validate(length(min = 1, message = "Code is required"))]
   pub code: String,
@@ -61,7 +110,11 @@ pub struct CreateProductRequest {
   #[validate(length(min = 1, message = "Base unit is required"))]
   pub base_unit: String,
   pub unit_on_report_preview: Option<String>,
+  #[validate(custom(function = "validate_non_negative", message = "Selling price cannot be negative"))]
+  #[validate(custom(function = "validate_amount"))]
   pub selling_price: rust_decimal::Decimal,
+  #[validate(custom(function = "validate_non_negative", message = "Unit cost cannot be negative"))]
+  #[validate(custom(function = "validate_amount"))]
   pub unit_cost: rust_decimal::Decimal,
   pub supplier_id: Option<Uuid>,
   pub track_inventory: Option<bool>,
@@ -70,27 +123,55 @@ pub struct CreateProductRequest {
   pub description: Option<String>,
   pub sku: Option<String>,
   pub barcode: Option<String>,
+  #[validate(range(min = 0, message = "Minimum stock cannot be negative"))]
   pub minimum_stock: Option<i32>,
+  #[validate(range(min = 0, message = "Maximum stock cannot be negative"))]
   pub maximum_stock: Option<i32>,
+  #[validate(range(min = 0, message = "Reorder level cannot be negative"))]
   pub reorder_level: Option<i32>,
-  pub stock: Option<i32>,
+  #[serde(alias = "stock")]
+  pub current_stock: Option<i32>,
   pub tax_type: Option<TaxType>,
+  #[validate(custom(function = "validate_non_negative", message = "Tax rate cannot be negative"))]
+  #[validate(custom(function = "validate_tax_rate"))]
   pub tax_rate: Option<rust_decimal::Decimal>,
+  #[validate(custom(function = "validate_non_negative", message = "Tax amount cannot be negative"))]
+  #[validate(custom(function = "validate_amount"))]
   pub tax_amount: Option<rust_decimal::Decimal>,
+  /// `active` unless given; products cannot be created discontinued.
+  #[validate(custom(function = "validate_initial_status"))]
+  pub status: Option<ProductStatus>,
+}
+
+/// The body of `POST /products/{id}/status`, moving the product along its lifecycle.
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[serde(deny_unknown_fields)]
+pub struct ChangeProductStatusRequest {
+  pub status: ProductStatus,
 }
 
 /// Represents the payload for updating an existing product.
 /// All fields are optional, allowing for partial updates.
 /// The `updated_by` field is automatically set from the authenticated user.
 /// The `workspace_id` cannot be changed via update - it's workspace-scoped.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "client", derive(Default))]
+#[validate(schema(function = "validate_stock_levels"))]
 pub struct UpdateProductRequest {
+  #[validate(length(min = 1, message = "Code cannot be empty"))]
   pub code: Option<String>,
+  #[validate(length(min = 1, message = "Name cannot be empty"))]
   pub name: Option<String>,
   pub category_id: Option<Uuid>,
+  #[validate(length(min = 1, message = "Base unit cannot be empty"))]
   pub base_unit: Option<String>,
   pub unit_on_report_preview: Option<String>,
+  #[validate(custom(function = "validate_non_negative", message = "Selling price cannot be negative"))]
+  #[validate(custom(function = "validate_amount"))]
   pub selling_price: Option<rust_decimal::Decimal>,
+  #[validate(custom(function = "validate_non_negative", message = "Unit cost cannot be negative"))]
+  #[validate(custom(function = "validate_amount"))]
   pub unit_cost: Option<rust_decimal::Decimal>,
   pub supplier_id: Option<Uuid>,
   pub track_inventory: Option<bool>,
@@ -99,20 +180,190 @@ pub struct UpdateProductRequest {
   pub description: Option<String>,
   pub sku: Option<String>,
   pub barcode: Option<String>,
+  #[validate(range(min = 0, message = "Minimum stock cannot be negative"))]
   pub minimum_stock: Option<i32>,
+  #[validate(range(min = 0, message = "Maximum stock cannot be negative"))]
   pub maximum_stock: Option<i32>,
+  #[validate(range(min = 0, message = "Reorder level cannot be negative"))]
   pub reorder_level: Option<i32>,
-  pub stock: Option<i32>,
+  #[serde(alias = "stock")]
+  pub current_stock: Option<i32>,
   pub tax_type: Option<TaxType>,
+  #[validate(custom(function = "validate_non_negative", message = "Tax rate cannot be negative"))]
+  #[validate(custom(function = "validate_tax_rate"))]
   pub tax_rate: Option<rust_decimal::Decimal>,
+  #[validate(custom(function = "validate_non_negative", message = "Tax amount cannot be negative"))]
+  #[validate(custom(function = "validate_amount"))]
   pub tax_amount: Option<rust_decimal::Decimal>,
   pub is_active: Option<bool>,
 }
 
+/// The editable fields of a product, used as the target of JSON Merge Patch (`PATCH`) updates.
+/// Every field is written back, so a `null` in the patch clears an optional field such as `supplier_id`.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+#[validate(schema(function = "validate_stock_levels"))]
+pub struct ProductPatchTarget {
+  #[validate(length(min = 1, message = "Code is required"))]
+  pub code: String,
+  #[validate(length(min = 1, message = "Name is required"))]
+  pub name: String,
+  pub category_id: Option<Uuid>,
+  #[validate(length(min = 1, message = "Base unit is required"))]
+  pub base_unit: String,
+  pub unit_on_report_preview: Option<String>,
+  #[validate(custom(function = "validate_non_negative", message = "Selling price cannot be negative"))]
+  #[validate(custom(function = "validate_amount"))]
+  pub selling_price: rust_decimal::Decimal,
+  #[validate(custom(function = "validate_non_negative", message = "Unit cost cannot be negative"))]
+  #[validate(custom(function = "validate_amount"))]
+  pub unit_cost: rust_decimal::Decimal,
+  pub supplier_id: Option<Uuid>,
+  pub track_inventory: bool,
+
+  // Additional fields
+  pub description: Option<String>,
+  pub sku: Option<String>,
+  pub barcode: Option<String>,
+  #[validate(range(min = 0, message = "Minimum stock cannot be negative"))]
+  pub minimum_stock: Option<i32>,
+  #[validate(range(min = 0, message = "Maximum stock cannot be negative"))]
+  pub maximum_stock: Option<i32>,
+  #[validate(range(min = 0, message = "Reorder level cannot be negative"))]
+  pub reorder_level: Option<i32>,
+  pub current_stock: Option<i32>,
+  pub tax_type: Option<TaxType>,
+  #[validate(custom(function = "validate_non_negative", message = "Tax rate cannot be negative"))]
+  #[validate(custom(function = "validate_tax_rate"))]
+  pub tax_rate: Option<rust_decimal::Decimal>,
+  #[validate(custom(function = "validate_non_negative", message = "Tax amount cannot be negative"))]
+  #[validate(custom(function = "validate_amount"))]
+  pub tax_amount: Option<rust_decimal::Decimal>,
+  pub is_active: bool,
+}
+
+/// Minimum/maximum stock pairs shared by the product payloads, checked for coherence.
+trait StockLevels {
+  fn stock_levels(&self) -> (Option<i32>, Option<i32>);
+}
+
+impl StockLevels for CreateProductRequest {
+  fn stock_levels(&self) -> (Option<i32>, Option<i32>) {
+    (self.minimum_stock, self.maximum_stock)
+  }
+}
+
+impl StockLevels for UpdateProductRequest {
+  fn stock_levels(&self) -> (Option<i32>, Option<i32>) {
+    (self.minimum_stock, self.maximum_stock)
+  }
+}
+
+impl StockLevels for ProductPatchTarget {
+  fn stock_levels(&self) -> (Option<i32>, Option<i32>) {
+    (self.minimum_stock, self.maximum_stock)
+  }
+}
+
+fn validate_initial_status(status: &ProductStatus) -> Result<(), ValidationError> {
+  if *status == ProductStatus::Discontinued {
+    return Err(ValidationError::new("status").with_message("Products are created as drafts or active".into()));
+  }
+  Ok(())
+}
+
+fn validate_non_negative(value: &Decimal) -> Result<(), ValidationError> {
+  if value.is_sign_negative() && !value.is_zero() {
+    return Err(ValidationError::new("non_negative"));
+  }
+  Ok(())
+}
+
+/// Decimal places stored for prices, costs and tax (`NUMERIC(15,2)` and `NUMERIC(5,2)`). The
+/// database would silently round anything finer, so it is rejected instead.
+const AMOUNT_SCALE: u32 = 2;
+
+/// Amounts must stay below this to fit `NUMERIC(15,2)`.
+const MAX_AMOUNT: i64 = 10_000_000_000_000;
+
+fn validate_scale(value: &Decimal) -> Result<(), ValidationError> {
+  if value.normalize().scale() > AMOUNT_SCALE {
+    return Err(ValidationError::new("precision").with_message(format!("Must have at most {} decimal places", AMOUNT_SCALE).into()));
+  }
+  Ok(())
+}
+
+fn validate_amount(value: &Decimal) -> Result<(), ValidationError> {
+  validate_scale(value)?;
+  if value.abs() >= Decimal::from(MAX_AMOUNT) {
+    return Err(ValidationError::new("out_of_range").with_message(format!("Must be less than {}", MAX_AMOUNT).into()));
+  }
+  Ok(())
+}
+
+fn validate_tax_rate(value: &Decimal) -> Result<(), ValidationError> {
+  validate_scale(value)?;
+  if *value > Decimal::ONE_HUNDRED {
+    return Err(ValidationError::new("out_of_range").with_message("Tax rate cannot exceed 100%".into()));
+  }
+  Ok(())
+}
+
+// The `schema` validator passes the payload by reference
+impl<T: StockLevels> StockLevels for &T {
+  fn stock_levels(&self) -> (Option<i32>, Option<i32>) {
+    (**self).stock_levels()
+  }
+}
+
+fn validate_stock_levels<T: StockLevels>(payload: &T) -> Result<(), ValidationError> {
+  let (minimum_stock, maximum_stock) = payload.stock_levels();
+  check_stock_levels(minimum_stock, maximum_stock)
+}
+
+/// Rejects a minimum stock above the maximum stock. Also used by the update handler to check
+/// the levels a partial update leaves the product with.
+pub fn check_stock_levels(minimum_stock: Option<i32>, maximum_stock: Option<i32>) -> Result<(), ValidationError> {
+  match (minimum_stock, maximum_stock) {
+    (Some(minimum), Some(maximum)) if minimum > maximum => {
+      Err(ValidationError::new("stock_levels").with_message("Minimum stock cannot exceed maximum stock".into()))
+    }
+    _ => Ok(()),
+  }
+}
+
+impl From<&Product> for ProductPatchTarget {
+  fn from(product: &Product) -> Self {
+    Self {
+      code: product.code.clone(),
+      name: product.name.clone(),
+      category_id: product.category_id,
+      base_unit: product.base_unit.clone(),
+      unit_on_report_preview: product.unit_on_report_preview.clone(),
+      selling_price: product.selling_price,
+      unit_cost: product.unit_cost,
+      supplier_id: product.supplier_id,
+      track_inventory: product.track_inventory,
+      description: product.description.clone(),
+      sku: product.sku.clone(),
+      barcode: product.barcode.clone(),
+      minimum_stock: product.minimum_stock,
+      maximum_stock: product.maximum_stock,
+      reorder_level: product.reorder_level,
+      current_stock: product.current_stock,
+      tax_type: product.tax_type.clone(),
+      tax_rate: product.tax_rate,
+      tax_amount: product.tax_amount,
+      is_active: product.is_active,
+    }
+  }
+}
+
 /// Represents the data structure for a product response.
 /// This struct defines the public-facing representation of a product,
 /// including ownership and audit information.
 #[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
 pub struct ProductResponse {
   pub id: Uuid,
   pub code: String,
@@ -132,11 +383,12 @@ pub struct ProductResponse {
   pub minimum_stock: Option<i32>,
   pub maximum_stock: Option<i32>,
   pub reorder_level: Option<i32>,
-  pub stock: Option<i32>,
+  pub current_stock: Option<i32>,
   pub tax_type: Option<TaxType>,
   pub tax_rate: Option<rust_decimal::Decimal>,
   pub tax_amount: Option<rust_decimal::Decimal>,
   pub is_active: bool,
+  pub status: ProductStatus,
 
   // Metadata
   pub workspace_id: Option<Uuid>,
@@ -144,6 +396,8 @@ pub struct ProductResponse {
   pub updated_by: Option<Uuid>,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
+  /// When the product was deleted; deleted products can be restored.
+  pub deleted_at: Option<DateTime<Utc>>,
 }
 
 /// Converts a `Product` model into a `ProductResponse`.
@@ -170,11 +424,12 @@ impl From<Product> for ProductResponse {
       minimum_stock: product.minimum_stock,
       maximum_stock: product.maximum_stock,
       reorder_level: product.reorder_level,
-      stock: product.stock,
+      current_stock: product.current_stock,
       tax_type: product.tax_type,
       tax_rate: product.tax_rate,
       tax_amount: product.tax_amount,
       is_active: product.is_active,
+      status: product.status,
 
       // Metadata
       workspace_id: product.workspace_id,
@@ -182,16 +437,53 @@ impl From<Product> for ProductResponse {
       updated_by: product.updated_by,
       created_at: product.created_at,
       updated_at: product.updated_at,
+      deleted_at: product.deleted_at,
     }
   }
 }
 
+/// Query parameters of `POST /products`.
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateProductQuery {
+  /// Creates the product even when it looks like a duplicate of an existing one.
+  #[serde(default)]
+  pub force: bool,
+}
+
+/// Names at least this similar (see `pg_trgm`'s `similarity`, from 0 to 1) make a product a
+/// possible duplicate of another.
+pub const DUPLICATE_NAME_SIMILARITY: f32 = 0.6;
+
+/// An existing product that a new one may duplicate: it has the same barcode or a very similar name.
+#[derive(Debug, Serialize)]
+pub struct DuplicateCandidate {
+  pub id: Uuid,
+  pub code: String,
+  pub name: String,
+  pub barcode: Option<String>,
+  pub same_barcode: bool,
+  /// How similar the names are, from 0 to 1.
+  pub name_similarity: f32,
+}
+
+/// Query parameters of `GET /products/:id`.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct GetProductQuery {
+  /// Reads the product as it was at this time, e.g. `2024-01-01T00:00:00Z`, instead of as it is.
+  pub as_of: Option<DateTime<Utc>>,
+}
+
 /// Query parameters for paginated requests with advanced filtering
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Deserialize, Validate)]
+#[cfg_attr(feature = "client", derive(Serialize))]
 #[serde(deny_unknown_fields)]
 pub struct GetProductsQuery {
   // Pagination
+  #[validate(range(min = 1, message = "Page must be at least 1"))]
   pub page: Option<u32>,
+  #[validate(range(min = 1, max = MAX_LIMIT, message = "Limit must be between 1 and 100"))]
   pub limit: Option<u32>,
 
   // Basic filtering
@@ -199,6 +491,7 @@ pub struct GetProductsQuery {
   pub category_id: Option<Uuid>,
   pub supplier_id: Option<Uuid>,
   pub is_active: Option<bool>,
+  pub status: Option<ProductStatus>,
   pub track_inventory: Option<bool>,
 
   // Advanced filtering
@@ -213,6 +506,8 @@ pub struct GetProductsQuery {
   pub exclude_suppliers: Option<String>,  // comma-separated UUIDs
   pub include_ids: Option<String>,        // comma-separated UUIDs
   pub exclude_ids: Option<String>,        // comma-separated UUIDs
+  /// Lists deleted products too.
+  pub include_deleted: Option<bool>,
 
   // Price filtering
   pub min_selling_price: Option<rust_decimal::Decimal>,
@@ -230,16 +525,13 @@ pub struct GetProductsQuery {
   pub sort_order: Option<String>, // "asc" or "desc"
 }
 
-// Constants for consistency with handler
-const DEFAULT_PAGE: u32 = 1;
-const DEFAULT_LIMIT: u32 = 10;
-
 #[derive(Debug, Clone)]
 pub struct ProductFilters {
   pub search: Option<String>,
   pub category_id: Option<Uuid>,
   pub supplier_id: Option<Uuid>,
   pub is_active: Option<bool>,
+  pub status: Option<ProductStatus>,
   pub track_inventory: Option<bool>,
   pub code: Option<String>,
   pub sku: Option<String>,
@@ -252,6 +544,7 @@ pub struct ProductFilters {
   pub exclude_suppliers: Vec<Uuid>,
   pub include_ids: Vec<Uuid>,
   pub exclude_ids: Vec<Uuid>,
+  pub include_deleted: bool,
 
   // Price filtering
   pub min_selling_price: Option<rust_decimal::Decimal>,
@@ -327,6 +620,7 @@ impl From<GetProductsQuery> for ProductFilters {
       category_id: query.category_id,
       supplier_id: query.supplier_id,
       is_active: query.is_active,
+      status: query.status,
       track_inventory: query.track_inventory,
       code: query.code,
       sku: query.sku,
@@ -339,6 +633,7 @@ impl From<GetProductsQuery> for ProductFilters {
       exclude_suppliers,
       include_ids,
       exclude_ids,
+      include_deleted: query.include_deleted.unwrap_or(false),
       min_selling_price: query.min_selling_price,
       max_selling_price: query.max_selling_price,
       min_unit_cost: query.min_unit_cost,
@@ -361,6 +656,7 @@ impl Default for GetProductsQuery {
       category_id: None,
       supplier_id: None,
       is_active: None,
+      status: None,
       track_inventory: None,
       code: None,
       sku: None,
@@ -373,6 +669,7 @@ impl Default for GetProductsQuery {
       exclude_suppliers: None,
       include_ids: None,
       exclude_ids: None,
+      include_deleted: None,
       min_selling_price: None,
       max_selling_price: None,
       min_unit_cost: None,
@@ -385,3 +682,104 @@ impl Default for GetProductsQuery {
     }
   }
 }
+
+/// Lines checked by one availability request.
+pub const MAX_AVAILABILITY_LINES: u64 = 200;
+
+/// The body of `POST /products/availability`: the lines of an order being entered.
+///
+/// Stock is only kept as one total per product, without warehouses, reservations or incoming
+/// purchase orders. A line's `warehouse_id` is accepted and echoed back so order forms can send
+/// it already, but every line is checked against the product's total stock.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct AvailabilityRequest {
+  #[validate(length(min = 1, max = MAX_AVAILABILITY_LINES, message = "Between 1 and 200 lines can be checked at once"))]
+  pub lines: Vec<AvailabilityLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AvailabilityLine {
+  pub product_id: Uuid,
+  pub quantity: i32,
+  /// The warehouse the line is to be supplied from; not checked, see `AvailabilityRequest`.
+  #[serde(default)]
+  pub warehouse_id: Option<Uuid>,
+}
+
+/// The stock of a product as needed by availability checks.
+#[derive(Debug, Clone, FromRow)]
+pub struct ProductStock {
+  pub id: Uuid,
+  pub is_active: bool,
+  pub status: ProductStatus,
+  pub track_inventory: bool,
+  pub current_stock: Option<i32>,
+}
+
+/// Whether the quantity of a line can be supplied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AvailabilityStatus {
+  /// The stock covers the quantity.
+  Available,
+  /// The stock is below the quantity.
+  Insufficient,
+  /// Inventory is not tracked for the product, so any quantity is accepted.
+  Untracked,
+  /// The product is deactivated and cannot be ordered.
+  Inactive,
+  /// The product is a draft, not published yet.
+  Draft,
+  /// The product is no longer sold.
+  Discontinued,
+  /// No such product in the workspace.
+  NotFound,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LineAvailability {
+  pub product_id: Uuid,
+  pub quantity: i32,
+  /// The `warehouse_id` of the line, as sent.
+  pub warehouse_id: Option<Uuid>,
+  /// The current stock, `None` unless inventory is tracked.
+  pub available: Option<i32>,
+  pub status: AvailabilityStatus,
+}
+
+/// Checks every line against `stock`. Lines for the same product are checked against the sum of
+/// their quantities, as they draw on the same stock.
+pub fn check_availability(lines: &[AvailabilityLine], stock: &[ProductStock]) -> Vec<LineAvailability> {
+  let mut requested: std::collections::HashMap<Uuid, i64> = std::collections::HashMap::new();
+  for line in lines {
+    *requested.entry(line.product_id).or_default() += i64::from(line.quantity);
+  }
+
+  lines
+    .iter()
+    .map(|line| {
+      let product = stock.iter().find(|product| product.id == line.product_id);
+      let available = product
+        .filter(|product| product.track_inventory)
+        .map(|product| product.current_stock.unwrap_or(0));
+      let status = match product {
+        None => AvailabilityStatus::NotFound,
+        Some(product) if !product.is_active => AvailabilityStatus::Inactive,
+        Some(product) if product.status == ProductStatus::Draft => AvailabilityStatus::Draft,
+        Some(product) if product.status == ProductStatus::Discontinued => AvailabilityStatus::Discontinued,
+        Some(product) if !product.track_inventory => AvailabilityStatus::Untracked,
+        Some(_) if i64::from(available.unwrap_or(0)) >= requested[&line.product_id] => AvailabilityStatus::Available,
+        Some(_) => AvailabilityStatus::Insufficient,
+      };
+      LineAvailability {
+        product_id: line.product_id,
+        quantity: line.quantity,
+        warehouse_id: line.warehouse_id,
+        available,
+        status,
+      }
+    })
+    .collect()
+}
@@ -1,8 +1,7 @@
-use sea_query::extension::postgres::PgExpr;
-use sea_query::{Expr, Iden, Order, PostgresQueryBuilder, Query, SelectStatement};
-use uuid::Uuid;
+use sea_query::{Alias, ColumnRef, DynIden, Expr, Iden, IntoColumnRef, Order, SeaRc, SelectStatement};
 
 use super::product_models::{GetProductsQuery, ProductFilters};
+use crate::utils::search_index;
 
 // Define table and column enums for type safety
 #[derive(Iden)]
@@ -29,104 +28,100 @@ enum Products {
   TaxRate,
   TaxAmount,
   IsActive,
+  Status,
   WorkspaceId,
   CreatedBy,
   UpdatedBy,
   CreatedAt,
   UpdatedAt,
+  DeletedAt,
 }
 
 pub struct ProductQueryBuilder;
 
+/// The product-specific parts of the list queries, which `PaginatedRepository` combines into the
+/// page, count and stream queries.
 impl ProductQueryBuilder {
-  pub fn build_filtered_query(workspace_id: Uuid, _user_id: Uuid, filters: &ProductFilters) -> (String, String) {
-    // Build select query
-    let select_sql = Self::build_select_query(workspace_id, _user_id, filters);
-
-    // Build count query
-    let count_sql = Self::build_count_query(workspace_id, _user_id, filters);
-
-    (select_sql, count_sql)
+  pub fn table() -> DynIden {
+    SeaRc::new(Products::Table)
   }
 
-  fn build_select_query(workspace_id: Uuid, _user_id: Uuid, filters: &ProductFilters) -> String {
-    let mut query = Query::select()
-      .columns([
-        Products::Id,
-        Products::Code,
-        Products::Name,
-        Products::CategoryId,
-        Products::BaseUnit,
-        Products::UnitOnReportPreview,
-        Products::SellingPrice,
-        Products::UnitCost,
-        Products::SupplierId,
-        Products::TrackInventory,
-        Products::Description,
-        Products::Sku,
-        Products::Barcode,
-        Products::MinimumStock,
-        Products::MaximumStock,
-        Products::ReorderLevel,
-        Products::CurrentStock,
-        Products::TaxType,
-        Products::TaxRate,
-        Products::TaxAmount,
-        Products::IsActive,
-        Products::WorkspaceId,
-        Products::CreatedBy,
-        Products::UpdatedBy,
-        Products::CreatedAt,
-        Products::UpdatedAt,
-      ])
-      .from(Products::Table)
-      .and_where(Expr::col(Products::WorkspaceId).eq(workspace_id.to_string()))
-      .to_owned();
-
-    // Apply filters
-    Self::apply_filters(&mut query, filters);
-
-    // Apply sorting
-    Self::apply_sorting(&mut query, &filters.sort_by, &filters.sort_order);
-
-    query.to_string(PostgresQueryBuilder)
+  /// The columns of `Product`, leaving out the search vector.
+  pub fn columns() -> Vec<ColumnRef> {
+    [
+      Products::Id,
+      Products::Code,
+      Products::Name,
+      Products::CategoryId,
+      Products::BaseUnit,
+      Products::UnitOnReportPreview,
+      Products::SellingPrice,
+      Products::UnitCost,
+      Products::SupplierId,
+      Products::TrackInventory,
+      Products::Description,
+      Products::Sku,
+      Products::Barcode,
+      Products::MinimumStock,
+      Products::MaximumStock,
+      Products::ReorderLevel,
+      Products::CurrentStock,
+      Products::TaxType,
+      Products::TaxRate,
+      Products::TaxAmount,
+      Products::IsActive,
+      Products::Status,
+      Products::WorkspaceId,
+      Products::CreatedBy,
+      Products::UpdatedBy,
+      Products::CreatedAt,
+      Products::UpdatedAt,
+      Products::DeletedAt,
+    ]
+    .into_iter()
+    .map(|column| (Products::Table, column).into_column_ref())
+    .collect()
   }
 
-  fn build_count_query(workspace_id: Uuid, _user_id: Uuid, filters: &ProductFilters) -> String {
-    let mut query = Query::select()
-      .expr(Expr::col((Products::Table, Products::Id)).count())
-      .from(Products::Table)
-      .and_where(Expr::col(Products::WorkspaceId).eq(workspace_id.to_string()))
-      .to_owned();
+  pub fn sort(filters: &ProductFilters) -> (DynIden, Order) {
+    let order = if filters.sort_order.to_uppercase() == "ASC" {
+      Order::Asc
+    } else {
+      Order::Desc
+    };
 
-    // Apply the same filters as select query (except sorting)
-    Self::apply_filters(&mut query, filters);
+    let column = match filters.sort_by.as_str() {
+      "name" => Products::Name,
+      "code" => Products::Code,
+      "selling_price" => Products::SellingPrice,
+      "unit_cost" => Products::UnitCost,
+      "created_at" => Products::CreatedAt,
+      "updated_at" => Products::UpdatedAt,
+      _ => Products::CreatedAt, // default
+    };
 
-    query.to_string(PostgresQueryBuilder)
+    (SeaRc::new(column), order)
   }
 
-  fn apply_filters(query: &mut SelectStatement, filters: &ProductFilters) {
-    // Search filter (across multiple fields)
-    if let Some(search) = &filters.search {
-      let search_pattern = format!("%{}%", search.to_lowercase());
-      query.and_where(
-        Expr::col(Products::Name)
-          .ilike(&search_pattern)
-          .or(Expr::col(Products::Code).ilike(&search_pattern))
-          .or(Expr::col(Products::Sku).ilike(&search_pattern))
-          .or(Expr::col(Products::Barcode).ilike(&search_pattern))
-          .or(Expr::col(Products::Description).ilike(&search_pattern)),
-      );
+  pub fn apply_filters(query: &mut SelectStatement, filters: &ProductFilters) {
+    // Deleted products are left out unless asked for
+    if !filters.include_deleted {
+      query.and_where(Expr::col((Products::Table, Products::DeletedAt)).is_null());
+    }
+
+    // Full-text search over code, name, SKU, barcode, description, category and supplier
+    if let Some(condition) = filters.search.as_deref().and_then(|search| search_index::matches("products", search)) {
+      query.and_where(condition);
     }
 
     // Category filter
     if let Some(category_id) = filters.category_id {
-      query.and_where(Expr::col(Products::CategoryId).eq(category_id.to_string()));
+      query.and_where(Expr::col(Products::CategoryId).eq(category_id));
     }
 
     // Supplier filter
     if let Some(supplier_id) = filters.supplier_id {
-      query.and_where(Expr::col(Products::SupplierId).eq(supplier_id.to_string()));
+      query.and_where(Expr::col(Products::SupplierId).eq(supplier_id));
     }
 
     // Active filter
@@ -134,6 +129,11 @@ impl ProductQueryBuilder {
       query.and_where(Expr::col(Products::IsActive).eq(is_active));
     }
 
+    // Lifecycle filter, the enum column compared as text
+    if let Some(status) = filters.status {
+      query.and_where(Expr::col(Products::Status).cast_as(Alias::new("text")).eq(status.as_str()));
+    }
+
     // Track inventory filter
     if let Some(track_inventory) = filters.track_inventory {
       query.and_where(Expr::col(Products::TrackInventory).eq(track_inventory));
@@ -159,62 +159,56 @@ impl ProductQueryBuilder {
       query.and_where(Expr::col(Products::BaseUnit).eq(base_unit));
     }
 
-    // Tax type filter
+    // Tax type filter; the enum column is compared as text so an unknown value simply matches nothing
     if let Some(tax_type) = &filters.tax_type {
-      query.and_where(Expr::col(Products::TaxType).eq(tax_type));
+      query.and_where(Expr::col(Products::TaxType).cast_as(Alias::new("text")).eq(tax_type));
     }
 
     // Include categories
     if !filters.include_categories.is_empty() {
-      let category_strings: Vec<String> = filters.include_categories.iter().map(|uuid| uuid.to_string()).collect();
-      query.and_where(Expr::col(Products::CategoryId).is_in(category_strings));
+      query.and_where(Expr::col(Products::CategoryId).is_in(filters.include_categories.iter().copied()));
     }
 
     // Exclude categories
     if !filters.exclude_categories.is_empty() {
-      let category_strings: Vec<String> = filters.exclude_categories.iter().map(|uuid| uuid.to_string()).collect();
-      query.and_where(Expr::col(Products::CategoryId).is_not_in(category_strings));
+      query.and_where(Expr::col(Products::CategoryId).is_not_in(filters.exclude_categories.iter().copied()));
     }
 
     // Include suppliers
     if !filters.include_suppliers.is_empty() {
-      let supplier_strings: Vec<String> = filters.include_suppliers.iter().map(|uuid| uuid.to_string()).collect();
-      query.and_where(Expr::col(Products::SupplierId).is_in(supplier_strings));
+      query.and_where(Expr::col(Products::SupplierId).is_in(filters.include_suppliers.iter().copied()));
     }
 
     // Exclude suppliers
     if !filters.exclude_suppliers.is_empty() {
-      let supplier_strings: Vec<String> = filters.exclude_suppliers.iter().map(|uuid| uuid.to_string()).collect();
-      query.and_where(Expr::col(Products::SupplierId).is_not_in(supplier_strings));
+      query.and_where(Expr::col(Products::SupplierId).is_not_in(filters.exclude_suppliers.iter().copied()));
     }
 
     // Include IDs
     if !filters.include_ids.is_empty() {
-      let id_strings: Vec<String> = filters.include_ids.iter().map(|uuid| uuid.to_string()).collect();
-      query.and_where(Expr::col(Products::Id).is_in(id_strings));
+      query.and_where(Expr::col(Products::Id).is_in(filters.include_ids.iter().copied()));
     }
 
     // Exclude IDs
     if !filters.exclude_ids.is_empty() {
-      let id_strings: Vec<String> = filters.exclude_ids.iter().map(|uuid| uuid.to_string()).collect();
-      query.and_where(Expr::col(Products::Id).is_not_in(id_strings));
+      query.and_where(Expr::col(Products::Id).is_not_in(filters.exclude_ids.iter().copied()));
     }
 
     // Price filters
     if let Some(min_selling_price) = filters.min_selling_price {
-      query.and_where(Expr::col(Products::SellingPrice).gte(min_selling_price.to_string()));
+      query.and_where(Expr::col(Products::SellingPrice).gte(min_selling_price));
     }
 
     if let Some(max_selling_price) = filters.max_selling_price {
-      query.and_where(Expr::col(Products::SellingPrice).lte(max_selling_price.to_string()));
+      query.and_where(Expr::col(Products::SellingPrice).lte(max_selling_price));
     }
 
     if let Some(min_unit_cost) = filters.min_unit_cost {
-      query.and_where(Expr::col(Products::UnitCost).gte(min_unit_cost.to_string()));
+      query.and_where(Expr::col(Products::UnitCost).gte(min_unit_cost));
     }
 
     if let Some(max_unit_cost) = filters.max_unit_cost {
-      query.and_where(Expr::col(Products::UnitCost).lte(max_unit_cost.to_string()));
+      query.and_where(Expr::col(Products::UnitCost).lte(max_unit_cost));
     }
 
     // Stock filters
@@ -237,22 +231,6 @@ impl ProductQueryBuilder {
       );
     }
   }
-
-  fn apply_sorting(query: &mut SelectStatement, sort_by: &str, sort_order: &str) {
-    let order = if sort_order.to_uppercase() == "ASC" { Order::Asc } else { Order::Desc };
-
-    let column = match sort_by {
-      "name" => Products::Name,
-      "code" => Products::Code,
-      "selling_price" => Products::SellingPrice,
-      "unit_cost" => Products::UnitCost,
-      "created_at" => Products::CreatedAt,
-      "updated_at" => Products::UpdatedAt,
-      _ => Products::CreatedAt, // default
-    };
-
-    query.order_by(column, order);
-  }
 }
 
 /// Utility function to check if any filters are applied
@@ -261,6 +239,7 @@ pub fn has_filters(query: &GetProductsQuery) -> bool {
     || query.category_id.is_some()
     || query.supplier_id.is_some()
     || query.is_active.is_some()
+    || query.status.is_some()
     || query.track_inventory.is_some()
     || query.code.is_some()
     || query.sku.is_some()
@@ -273,6 +252,7 @@ pub fn has_filters(query: &GetProductsQuery) -> bool {
     || query.exclude_suppliers.is_some()
     || query.include_ids.is_some()
     || query.exclude_ids.is_some()
+    || query.include_deleted.is_some()
     || query.min_selling_price.is_some()
     || query.max_selling_price.is_some()
     || query.min_unit_cost.is_some()
@@ -1,20 +1,55 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use sea_query::{ColumnRef, DynIden, Order, SelectStatement};
 use uuid::Uuid;
 
-use super::product_models::{CreateProductRequest, Product, ProductFilters, TaxType, UpdateProductRequest};
+use super::{
+  category_models::CategoryAggregate,
+  product_models::{
+    CreateProductRequest, DUPLICATE_NAME_SIMILARITY, DuplicateCandidate, Product, ProductFilters, ProductPatchTarget, ProductStatus, ProductStock,
+    TaxType, UpdateProductRequest,
+  },
+  product_query_builder::ProductQueryBuilder,
+};
 use crate::{
   AppResult,
-  utils::code_generator::{CodeGenerator, CodeGeneratorConfig},
+  utils::{
+    DbExecutor, ReadPool,
+    code_generator::{CodeGenerator, CodeGeneratorConfig, CodeRules},
+    ndjson::RowSender,
+    paginated_repository::PaginatedRepository,
+    pagination::{self, Counted},
+  },
 };
 
+/// The built-in format of product codes, before the rules of the workspace.
+pub fn code_config() -> CodeGeneratorConfig {
+  CodeGeneratorConfig {
+    table_name: "products".to_string(),
+    prefix_length: 2,
+    number_length: 5,
+    ..CodeGeneratorConfig::default()
+  }
+}
+
 #[async_trait]
 pub trait ProductRepository {
   // Core workspace-scoped methods - these are the only ones we need
   async fn create_by_workspace(&self, product: CreateProductRequest, workspace_id: Uuid, user_id: Uuid) -> AppResult<Product>;
+  /// Inserts all `products` in one statement and returns the inserted rows. Products whose code
+  /// is already taken in the workspace are skipped, so the result can be shorter than the input.
+  async fn create_many_by_workspace(&self, products: Vec<CreateProductRequest>, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Product>>;
   async fn find_all_by_workspace_paginated(&self, workspace_id: Uuid, user_id: Uuid, page: u32, limit: u32) -> AppResult<(Vec<Product>, u64)>;
   async fn find_by_id_and_workspace(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<Product>>;
+  /// The product holding `code`, deleted or not: deleted products keep their code.
   async fn find_by_code_and_workspace(&self, code: &str, workspace_id: Uuid) -> AppResult<Option<Product>>;
+  /// Products of the workspace with `barcode`, or with a name at least `DUPLICATE_NAME_SIMILARITY`
+  /// similar to `name`, those sharing the barcode and then the most similar first. At most `limit`.
+  async fn find_possible_duplicates(&self, workspace_id: Uuid, name: &str, barcode: Option<&str>, limit: i64) -> AppResult<Vec<DuplicateCandidate>>;
+  /// The product as it was at `as_of`, from its revisions. `None` when it did not exist yet, was
+  /// already deleted, or only has revisions from after `as_of`.
+  async fn find_as_of(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid, as_of: DateTime<Utc>) -> AppResult<Option<Product>>;
   async fn update_by_workspace(
     &self,
     id: Uuid,
@@ -22,17 +57,53 @@ pub trait ProductRepository {
     product_data: UpdateProductRequest,
     updated_by: Uuid,
   ) -> AppResult<Option<Product>>;
+  async fn replace_by_workspace(&self, id: Uuid, workspace_id: Uuid, fields: ProductPatchTarget, updated_by: Uuid) -> AppResult<Option<Product>>;
+  /// Moves the product to `status` when it is still `from`, `None` otherwise (or when missing).
+  async fn set_status_by_workspace(
+    &self,
+    id: Uuid,
+    workspace_id: Uuid,
+    from: ProductStatus,
+    status: ProductStatus,
+    updated_by: Uuid,
+  ) -> AppResult<Option<Product>>;
+  /// Marks the product deleted, leaving it out of every read until restored.
   async fn delete_by_workspace_and_user(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<bool>;
+  /// Brings back a deleted product; `None` if there is no such deleted product.
+  async fn restore_by_workspace_and_user(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<Product>>;
 
   // Code generation methods
-  async fn get_next_available_code(&self, workspace_id: Uuid, product_name: &str) -> AppResult<String>;
-  async fn code_exists(&self, code: &str, workspace_id: Uuid) -> AppResult<bool>;
+  /// The next free code for `product_name`, in the format set by `rules` for the record's `category`.
+  async fn get_next_available_code(
+    &self,
+    workspace_id: Uuid,
+    product_name: &str,
+    rules: Option<&CodeRules>,
+    category: Option<&str>,
+  ) -> AppResult<String>;
 
   // Optional methods for specific use cases
   async fn find_by_category_and_workspace(&self, category_id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Product>>;
   async fn find_by_supplier_and_workspace(&self, supplier_id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Product>>;
   async fn find_active_by_workspace(&self, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Product>>;
   async fn find_low_stock_by_workspace(&self, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Product>>;
+  /// Up to `limit` products changed after the `(updated_at, id)` position `after` and before
+  /// `settled_before`, oldest first. Without `after`, the latest `limit` changes, still oldest first.
+  async fn find_changed(
+    &self,
+    workspace_id: Uuid,
+    user_id: Uuid,
+    after: Option<(DateTime<Utc>, Uuid)>,
+    settled_before: DateTime<Utc>,
+    limit: i64,
+  ) -> AppResult<Vec<Product>>;
+  /// The stock of the products among `ids` that exist in the workspace, in one query.
+  async fn find_stock_by_ids(&self, ids: &[Uuid], workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<ProductStock>>;
+  /// The products among `ids` that exist in the workspace, in one query and in no particular order.
+  async fn find_by_ids(&self, ids: &[Uuid], workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Product>>;
+  /// Every category of the workspace with the product count and stock value of its own products
+  /// and of its whole subtree, ordered by name. See `category_models::build_category_tree`.
+  async fn find_category_aggregates(&self, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<CategoryAggregate>>;
 
   // Advanced filtering method
   async fn find_by_filters_paginated(
@@ -43,28 +114,65 @@ pub trait ProductRepository {
     limit: u32,
     filters: ProductFilters,
   ) -> AppResult<(Vec<Product>, u64)>;
+
+  // Streaming method: sends every matching row to `rows`, in the requested order
+  async fn stream_by_filters(&self, workspace_id: Uuid, user_id: Uuid, filters: ProductFilters, rows: RowSender<Product>) -> AppResult<()>;
 }
 
 pub struct SqlxProductRepository {
-  db: PgPool,
+  db: DbExecutor,
+  read_pool: ReadPool,
 }
 
 impl SqlxProductRepository {
-  pub fn new(db: PgPool) -> Self {
-    Self { db }
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    let db = db.into();
+    Self {
+      read_pool: ReadPool::primary_only(db.clone()),
+      db,
+    }
+  }
+
+  /// Runs the read-only queries (lookups, lists and counts) on `read_pool` instead of the primary.
+  pub fn with_read_pool(mut self, read_pool: ReadPool) -> Self {
+    self.read_pool = read_pool;
+    self
   }
 
-  /// Get access to the underlying database pool
-  pub fn get_pool(&self) -> PgPool {
+  /// The executor the repository's writes run on
+  pub fn executor(&self) -> DbExecutor {
     self.db.clone()
   }
 }
 
+impl PaginatedRepository<Product, ProductFilters> for SqlxProductRepository {
+  fn table() -> DynIden {
+    ProductQueryBuilder::table()
+  }
+
+  fn columns() -> Vec<ColumnRef> {
+    ProductQueryBuilder::columns()
+  }
+
+  fn apply_filters(query: &mut SelectStatement, filters: &ProductFilters) {
+    ProductQueryBuilder::apply_filters(query, filters)
+  }
+
+  fn sort(filters: &ProductFilters) -> (DynIden, Order) {
+    ProductQueryBuilder::sort(filters)
+  }
+
+  fn read_pool(&self) -> &ReadPool {
+    &self.read_pool
+  }
+}
+
 #[async_trait]
 impl ProductRepository for SqlxProductRepository {
   // Workspace-scoped methods
 
   async fn create_by_workspace(&self, product: CreateProductRequest, workspace_id: Uuid, user_id: Uuid) -> AppResult<Product> {
+    let mut conn = self.db.acquire().await?;
     let new_product = sqlx::query_as!(
       Product,
       r#"
@@ -72,16 +180,16 @@ impl ProductRepository for SqlxProductRepository {
                     code, name, category_id, base_unit, unit_on_report_preview,
                     selling_price, unit_cost, supplier_id, track_inventory,
                     description, sku, barcode, minimum_stock, maximum_stock,
-                    reorder_level, stock, tax_type, tax_rate, tax_amount,
-                    workspace_id, created_by
+                    reorder_level, current_stock, tax_type, tax_rate, tax_amount,
+                    workspace_id, created_by, status
                 )
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22)
                 RETURNING 
                     id, code, name, category_id, base_unit, unit_on_report_preview,
                     selling_price, unit_cost, supplier_id, track_inventory,
                     description, sku, barcode, minimum_stock, maximum_stock,
-                    reorder_level, stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
-                    is_active, workspace_id, created_by, updated_by, created_at, updated_at
+                    reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+                    is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
             "#,
       product.code,
       product.name,
@@ -98,14 +206,15 @@ impl ProductRepository for SqlxProductRepository {
       product.minimum_stock,
       product.maximum_stock,
       product.reorder_level,
-      product.stock,
+      product.current_stock,
       product.tax_type as Option<TaxType>,
       product.tax_rate,
       product.tax_amount,
       workspace_id,
-      user_id
+      user_id,
+      product.status.unwrap_or(ProductStatus::Active) as ProductStatus
     )
-    .fetch_one(&self.db)
+    .fetch_one(&mut *conn)
     .await
     .map_err(|e| {
       tracing::error!("Failed to create product: {}", e);
@@ -115,68 +224,192 @@ impl ProductRepository for SqlxProductRepository {
     Ok(new_product)
   }
 
-  async fn find_all_by_workspace_paginated(&self, workspace_id: Uuid, user_id: Uuid, page: u32, limit: u32) -> AppResult<(Vec<Product>, u64)> {
-    let offset = (page - 1) * limit;
+  async fn create_many_by_workspace(&self, products: Vec<CreateProductRequest>, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Product>> {
+    if products.is_empty() {
+      return Ok(Vec::new());
+    }
+
+    // One array per column, unnested back into rows by Postgres
+    let count = products.len();
+    let mut codes = Vec::with_capacity(count);
+    let mut names = Vec::with_capacity(count);
+    let mut category_ids = Vec::with_capacity(count);
+    let mut base_units = Vec::with_capacity(count);
+    let mut report_units = Vec::with_capacity(count);
+    let mut selling_prices = Vec::with_capacity(count);
+    let mut unit_costs = Vec::with_capacity(count);
+    let mut supplier_ids = Vec::with_capacity(count);
+    let mut track_inventory = Vec::with_capacity(count);
+    let mut descriptions = Vec::with_capacity(count);
+    let mut skus = Vec::with_capacity(count);
+    let mut barcodes = Vec::with_capacity(count);
+    let mut minimum_stocks = Vec::with_capacity(count);
+    let mut maximum_stocks = Vec::with_capacity(count);
+    let mut reorder_levels = Vec::with_capacity(count);
+    let mut current_stocks = Vec::with_capacity(count);
+    let mut tax_types = Vec::with_capacity(count);
+    let mut tax_rates = Vec::with_capacity(count);
+    let mut tax_amounts = Vec::with_capacity(count);
+    let mut statuses = Vec::with_capacity(count);
+    for product in products {
+      codes.push(product.code);
+      names.push(product.name);
+      category_ids.push(product.category_id);
+      base_units.push(product.base_unit);
+      report_units.push(product.unit_on_report_preview);
+      selling_prices.push(product.selling_price);
+      unit_costs.push(product.unit_cost);
+      supplier_ids.push(product.supplier_id);
+      track_inventory.push(product.track_inventory.unwrap_or(false));
+      descriptions.push(product.description);
+      skus.push(product.sku);
+      barcodes.push(product.barcode);
+      minimum_stocks.push(product.minimum_stock);
+      maximum_stocks.push(product.maximum_stock);
+      reorder_levels.push(product.reorder_level);
+      current_stocks.push(product.current_stock);
+      tax_types.push(product.tax_type);
+      tax_rates.push(product.tax_rate);
+      tax_amounts.push(product.tax_amount);
+      statuses.push(product.status.unwrap_or(ProductStatus::Active));
+    }
 
-    let total_count = sqlx::query_scalar!(
+    let mut conn = self.db.acquire().await?;
+    let new_products = sqlx::query_as::<_, Product>(
       r#"
-                SELECT COUNT(*) 
-                FROM products 
-                WHERE workspace_id = $1
-                  AND id IN (
-                    SELECT p.id FROM products p
-                    JOIN workspaces w ON p.workspace_id = w.id
-                    JOIN workspace_users wu ON w.id = wu.workspace_id
-                    WHERE wu.user_id = $2
-                  )
+                INSERT INTO products (
+                    code, name, category_id, base_unit, unit_on_report_preview,
+                    selling_price, unit_cost, supplier_id, track_inventory,
+                    description, sku, barcode, minimum_stock, maximum_stock,
+                    reorder_level, current_stock, tax_type, tax_rate, tax_amount,
+                    workspace_id, created_by, status
+                )
+                SELECT
+                    code, name, category_id, base_unit, unit_on_report_preview,
+                    selling_price, unit_cost, supplier_id, track_inventory,
+                    description, sku, barcode, minimum_stock, maximum_stock,
+                    reorder_level, current_stock, tax_type, tax_rate, tax_amount,
+                    $20, $21, status
+                FROM UNNEST(
+                    $1::text[], $2::text[], $3::uuid[], $4::text[], $5::text[],
+                    $6::numeric[], $7::numeric[], $8::uuid[], $9::bool[],
+                    $10::text[], $11::text[], $12::text[], $13::int4[], $14::int4[],
+                    $15::int4[], $16::int4[], $17::tax_type[], $18::numeric[], $19::numeric[],
+                    $22::product_status[]
+                ) AS rows(
+                    code, name, category_id, base_unit, unit_on_report_preview,
+                    selling_price, unit_cost, supplier_id, track_inventory,
+                    description, sku, barcode, minimum_stock, maximum_stock,
+                    reorder_level, current_stock, tax_type, tax_rate, tax_amount,
+                    status
+                )
+                ON CONFLICT (workspace_id, code) DO NOTHING
+                RETURNING
+                    id, code, name, category_id, base_unit, unit_on_report_preview,
+                    selling_price, unit_cost, supplier_id, track_inventory,
+                    description, sku, barcode, minimum_stock, maximum_stock,
+                    reorder_level, current_stock, tax_type, tax_rate, tax_amount,
+                    is_active, status, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
             "#,
-      workspace_id,
-      user_id
     )
-    .fetch_one(&self.db)
+    .bind(codes)
+    .bind(names)
+    .bind(category_ids)
+    .bind(base_units)
+    .bind(report_units)
+    .bind(selling_prices)
+    .bind(unit_costs)
+    .bind(supplier_ids)
+    .bind(track_inventory)
+    .bind(descriptions)
+    .bind(skus)
+    .bind(barcodes)
+    .bind(minimum_stocks)
+    .bind(maximum_stocks)
+    .bind(reorder_levels)
+    .bind(current_stocks)
+    .bind(tax_types)
+    .bind(tax_rates)
+    .bind(tax_amounts)
+    .bind(workspace_id)
+    .bind(user_id)
+    .bind(statuses)
+    .fetch_all(&mut *conn)
     .await
     .map_err(|e| {
-      tracing::error!("Failed to count products: {}", e);
-      crate::errors::AppError::from_sqlx_error(e, "COUNT products")
-    })?
-    .unwrap_or(0) as u64;
+      tracing::error!("Failed to bulk create products: {}", e);
+      crate::errors::AppError::from_sqlx_error(e, "INSERT INTO products SELECT FROM UNNEST")
+    })?;
 
-    let products = sqlx::query_as!(
-      Product,
+    Ok(new_products)
+  }
+
+  async fn find_all_by_workspace_paginated(&self, workspace_id: Uuid, user_id: Uuid, page: u32, limit: u32) -> AppResult<(Vec<Product>, u64)> {
+    let mut conn = self.read_pool.acquire().await?;
+    let offset = pagination::offset(page, limit);
+
+    // The total count comes back with every row, so one roundtrip serves the whole page
+    let rows = sqlx::query_as::<_, Counted<Product>>(
       r#"
                 SELECT 
                     id, code, name, category_id, base_unit, unit_on_report_preview,
                     selling_price, unit_cost, supplier_id, track_inventory,
                     description, sku, barcode, minimum_stock, maximum_stock,
-                    reorder_level, stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
-                    is_active, workspace_id, created_by, updated_by, created_at, updated_at
+                    reorder_level, current_stock, tax_type, tax_rate, tax_amount,
+                    is_active, status, workspace_id, created_by, updated_by, created_at, updated_at, deleted_at,
+                    COUNT(*) OVER () AS total_count
                 FROM products
-                WHERE workspace_id = $1
-                  AND id IN (
-                    SELECT p.id FROM products p
-                    JOIN workspaces w ON p.workspace_id = w.id
-                    JOIN workspace_users wu ON w.id = wu.workspace_id
-                    WHERE wu.user_id = $2
+                WHERE workspace_id = $1 AND deleted_at IS NULL
+                  AND EXISTS (
+                    SELECT 1 FROM workspace_access wu
+                    WHERE wu.workspace_id = $1 AND wu.user_id = $2
                   )
                 ORDER BY created_at DESC
                 LIMIT $3 OFFSET $4
             "#,
-      workspace_id,
-      user_id,
-      limit as i64,
-      offset as i64
     )
-    .fetch_all(&self.db)
+    .bind(workspace_id)
+    .bind(user_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&mut *conn)
     .await
     .map_err(|e| {
       tracing::error!("Failed to fetch products: {}", e);
       crate::errors::AppError::from_sqlx_error(e, "SELECT FROM products")
     })?;
 
+    let (products, total_count) = pagination::into_page(rows, offset);
+    let total_count = match total_count {
+      Some(total_count) => total_count,
+      // Past the last page there is no row carrying the total, so count separately
+      None => sqlx::query_scalar!(
+        r#"
+                SELECT COUNT(*) 
+                FROM products 
+                WHERE workspace_id = $1 AND deleted_at IS NULL
+                  AND EXISTS (
+                    SELECT 1 FROM workspace_access wu
+                    WHERE wu.workspace_id = $1 AND wu.user_id = $2
+                  )
+            "#,
+        workspace_id,
+        user_id
+      )
+      .fetch_one(&mut *conn)
+      .await
+      .map_err(|e| {
+        tracing::error!("Failed to count products: {}", e);
+        crate::errors::AppError::from_sqlx_error(e, "COUNT products")
+      })?
+      .unwrap_or(0) as u64,
+    };
+
     Ok((products, total_count))
   }
 
   async fn find_by_id_and_workspace(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<Product>> {
+    let mut conn = self.read_pool.acquire().await?;
     let product = sqlx::query_as!(
       Product,
       r#"
@@ -184,22 +417,20 @@ impl ProductRepository for SqlxProductRepository {
                     id, code, name, category_id, base_unit, unit_on_report_preview,
                     selling_price, unit_cost, supplier_id, track_inventory,
                     description, sku, barcode, minimum_stock, maximum_stock,
-                    reorder_level, stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
-                    is_active, workspace_id, created_by, updated_by, created_at, updated_at
+                    reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+                    is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
                 FROM products 
-                WHERE id = $1 AND workspace_id = $2
-                  AND id IN (
-                    SELECT p.id FROM products p
-                    JOIN workspaces w ON p.workspace_id = w.id
-                    JOIN workspace_users wu ON w.id = wu.workspace_id
-                    WHERE wu.user_id = $3
+                WHERE id = $1 AND workspace_id = $2 AND deleted_at IS NULL
+                  AND EXISTS (
+                    SELECT 1 FROM workspace_access wu
+                    WHERE wu.workspace_id = $2 AND wu.user_id = $3
                   )
             "#,
       id,
       workspace_id,
       user_id
     )
-    .fetch_optional(&self.db)
+    .fetch_optional(&mut *conn)
     .await
     .map_err(|e| {
       tracing::error!("Failed to fetch product by id: {}", e);
@@ -209,7 +440,35 @@ impl ProductRepository for SqlxProductRepository {
     Ok(product)
   }
 
+  async fn find_possible_duplicates(&self, workspace_id: Uuid, name: &str, barcode: Option<&str>, limit: i64) -> AppResult<Vec<DuplicateCandidate>> {
+    let mut conn = self.db.acquire().await?;
+    // `%` finds the names over pg_trgm's own, lower threshold through the trigram index
+    let candidates = sqlx::query_as!(
+      DuplicateCandidate,
+      r#"
+        SELECT
+          id, code, name, barcode,
+          COALESCE(barcode = $3, false) AS "same_barcode!",
+          similarity(name, $2) AS "name_similarity!"
+        FROM products
+        WHERE workspace_id = $1 AND (barcode = $3 OR (name % $2 AND similarity(name, $2) >= $4)) AND deleted_at IS NULL
+        ORDER BY 5 DESC, 6 DESC, code
+        LIMIT $5
+        "#,
+      workspace_id,
+      name,
+      barcode,
+      DUPLICATE_NAME_SIMILARITY,
+      limit
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(candidates)
+  }
+
   async fn find_by_code_and_workspace(&self, code: &str, workspace_id: Uuid) -> AppResult<Option<Product>> {
+    let mut conn = self.db.acquire().await?;
     let product = sqlx::query_as!(
       Product,
       r#"
@@ -217,15 +476,15 @@ impl ProductRepository for SqlxProductRepository {
                     id, code, name, category_id, base_unit, unit_on_report_preview,
                     selling_price, unit_cost, supplier_id, track_inventory,
                     description, sku, barcode, minimum_stock, maximum_stock,
-                    reorder_level, stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
-                    is_active, workspace_id, created_by, updated_by, created_at, updated_at
+                    reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+                    is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
                 FROM products 
                 WHERE code = $1 AND workspace_id = $2
             "#,
       code,
       workspace_id
     )
-    .fetch_optional(&self.db)
+    .fetch_optional(&mut *conn)
     .await
     .map_err(|e| {
       tracing::error!("Failed to fetch product by code: {}", e);
@@ -235,6 +494,46 @@ impl ProductRepository for SqlxProductRepository {
     Ok(product)
   }
 
+  async fn find_as_of(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid, as_of: DateTime<Utc>) -> AppResult<Option<Product>> {
+    let mut conn = self.read_pool.acquire().await?;
+    let product = sqlx::query_as!(
+      Product,
+      r#"
+                SELECT
+                    p.id as "id!", p.code as "code!", p.name as "name!", p.category_id, p.base_unit as "base_unit!",
+                    p.unit_on_report_preview, p.selling_price as "selling_price!", p.unit_cost as "unit_cost!", p.supplier_id,
+                    p.track_inventory as "track_inventory!", p.description, p.sku, p.barcode, p.minimum_stock, p.maximum_stock,
+                    p.reorder_level, p.current_stock, p.tax_type as "tax_type: TaxType", p.tax_rate, p.tax_amount,
+                    p.is_active as "is_active!", p.status as "status!: ProductStatus", p.workspace_id, p.created_by, p.updated_by,
+                    p.created_at as "created_at!", p.updated_at as "updated_at!", p.deleted_at
+                FROM (
+                    SELECT data FROM product_revisions
+                    WHERE product_id = $1 AND workspace_id = $2 AND recorded_at <= $4
+                      AND EXISTS (
+                        SELECT 1 FROM workspace_access wu
+                        WHERE wu.workspace_id = $2 AND wu.user_id = $3
+                      )
+                    ORDER BY recorded_at DESC, id DESC
+                    LIMIT 1
+                ) revision
+                CROSS JOIN LATERAL jsonb_populate_record(NULL::products, revision.data) p
+                WHERE revision.data IS NOT NULL AND p.deleted_at IS NULL
+            "#,
+      id,
+      workspace_id,
+      user_id,
+      as_of
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| {
+      tracing::error!("Failed to fetch product revision: {}", e);
+      crate::errors::AppError::from_sqlx_error(e, "SELECT FROM product_revisions")
+    })?;
+
+    Ok(product)
+  }
+
   async fn update_by_workspace(
     &self,
     id: Uuid,
@@ -242,6 +541,7 @@ impl ProductRepository for SqlxProductRepository {
     product_data: UpdateProductRequest,
     updated_by: Uuid,
   ) -> AppResult<Option<Product>> {
+    let mut conn = self.db.acquire().await?;
     let updated_product = sqlx::query_as!(
       Product,
       r#"
@@ -262,20 +562,20 @@ impl ProductRepository for SqlxProductRepository {
                     minimum_stock = COALESCE($15, minimum_stock),
                     maximum_stock = COALESCE($16, maximum_stock),
                     reorder_level = COALESCE($17, reorder_level),
-                    stock = COALESCE($18, stock),
+                    current_stock = COALESCE($18, current_stock),
                     tax_type = COALESCE($19, tax_type),
                     tax_rate = COALESCE($20, tax_rate),
                     tax_amount = COALESCE($21, tax_amount),
                     is_active = COALESCE($22, is_active),
                     updated_by = $23,
                     updated_at = NOW()
-                WHERE id = $1 AND workspace_id = $2
+                WHERE id = $1 AND workspace_id = $2 AND deleted_at IS NULL
                 RETURNING 
                     id, code, name, category_id, base_unit, unit_on_report_preview,
                     selling_price, unit_cost, supplier_id, track_inventory,
                     description, sku, barcode, minimum_stock, maximum_stock,
-                    reorder_level, stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
-                    is_active, workspace_id, created_by, updated_by, created_at, updated_at
+                    reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+                    is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
             "#,
       id,
       workspace_id,
@@ -294,14 +594,14 @@ impl ProductRepository for SqlxProductRepository {
       product_data.minimum_stock,
       product_data.maximum_stock,
       product_data.reorder_level,
-      product_data.stock,
+      product_data.current_stock,
       product_data.tax_type as Option<TaxType>,
       product_data.tax_rate,
       product_data.tax_amount,
       product_data.is_active,
       updated_by
     )
-    .fetch_optional(&self.db)
+    .fetch_optional(&mut *conn)
     .await
     .map_err(|e| {
       tracing::error!("Failed to update product: {}", e);
@@ -311,70 +611,187 @@ impl ProductRepository for SqlxProductRepository {
     Ok(updated_product)
   }
 
+  async fn replace_by_workspace(&self, id: Uuid, workspace_id: Uuid, fields: ProductPatchTarget, updated_by: Uuid) -> AppResult<Option<Product>> {
+    let mut conn = self.db.acquire().await?;
+    let product = sqlx::query_as!(
+      Product,
+      r#"
+                UPDATE products
+                SET
+                    code = $3,
+                    name = $4,
+                    category_id = $5,
+                    base_unit = $6,
+                    unit_on_report_preview = $7,
+                    selling_price = $8,
+                    unit_cost = $9,
+                    supplier_id = $10,
+                    track_inventory = $11,
+                    description = $12,
+                    sku = $13,
+                    barcode = $14,
+                    minimum_stock = $15,
+                    maximum_stock = $16,
+                    reorder_level = $17,
+                    current_stock = $18,
+                    tax_type = $19,
+                    tax_rate = $20,
+                    tax_amount = $21,
+                    is_active = $22,
+                    updated_by = $23,
+                    updated_at = NOW()
+                WHERE id = $1 AND workspace_id = $2 AND deleted_at IS NULL
+                RETURNING
+                    id, code, name, category_id, base_unit, unit_on_report_preview,
+                    selling_price, unit_cost, supplier_id, track_inventory,
+                    description, sku, barcode, minimum_stock, maximum_stock,
+                    reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+                    is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
+            "#,
+      id,
+      workspace_id,
+      fields.code,
+      fields.name,
+      fields.category_id,
+      fields.base_unit,
+      fields.unit_on_report_preview,
+      fields.selling_price,
+      fields.unit_cost,
+      fields.supplier_id,
+      fields.track_inventory,
+      fields.description,
+      fields.sku,
+      fields.barcode,
+      fields.minimum_stock,
+      fields.maximum_stock,
+      fields.reorder_level,
+      fields.current_stock,
+      fields.tax_type as Option<TaxType>,
+      fields.tax_rate,
+      fields.tax_amount,
+      fields.is_active,
+      updated_by
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(product)
+  }
+
+  async fn set_status_by_workspace(
+    &self,
+    id: Uuid,
+    workspace_id: Uuid,
+    from: ProductStatus,
+    status: ProductStatus,
+    updated_by: Uuid,
+  ) -> AppResult<Option<Product>> {
+    let mut conn = self.db.acquire().await?;
+    let product = sqlx::query_as!(
+      Product,
+      r#"
+                UPDATE products
+                SET status = $4, updated_by = $5, updated_at = NOW()
+                WHERE id = $1 AND workspace_id = $2 AND status = $3 AND deleted_at IS NULL
+                RETURNING
+                    id, code, name, category_id, base_unit, unit_on_report_preview,
+                    selling_price, unit_cost, supplier_id, track_inventory,
+                    description, sku, barcode, minimum_stock, maximum_stock,
+                    reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+                    is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
+            "#,
+      id,
+      workspace_id,
+      from as ProductStatus,
+      status as ProductStatus,
+      updated_by
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| {
+      tracing::error!("Failed to set product status: {}", e);
+      crate::errors::AppError::from_sqlx_error(e, "UPDATE products SET status")
+    })?;
+
+    Ok(product)
+  }
+
   async fn delete_by_workspace_and_user(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<bool> {
+    let mut conn = self.db.acquire().await?;
     let result = sqlx::query!(
       r#"
-                DELETE FROM products 
-                WHERE id = $1 AND workspace_id = $2
-                  AND id IN (
-                    SELECT p.id FROM products p
-                    JOIN workspaces w ON p.workspace_id = w.id
-                    JOIN workspace_users wu ON w.id = wu.workspace_id
-                    WHERE wu.user_id = $3
+                UPDATE products
+                SET deleted_at = NOW(), updated_by = $3, updated_at = NOW()
+                WHERE id = $1 AND workspace_id = $2 AND deleted_at IS NULL
+                  AND EXISTS (
+                    SELECT 1 FROM workspace_access wu
+                    WHERE wu.workspace_id = $2 AND wu.user_id = $3
                   )
             "#,
       id,
       workspace_id,
       user_id
     )
-    .execute(&self.db)
+    .execute(&mut *conn)
     .await
     .map_err(|e| {
       tracing::error!("Failed to delete product: {}", e);
-      crate::errors::AppError::from_sqlx_error(e, "DELETE FROM products")
+      crate::errors::AppError::from_sqlx_error(e, "UPDATE products SET deleted_at")
     })?;
 
     Ok(result.rows_affected() > 0)
   }
 
-  // Code generation methods
-  async fn get_next_available_code(&self, workspace_id: Uuid, product_name: &str) -> AppResult<String> {
-    let code_generator = CodeGenerator::new(self.db.clone());
-    let config = CodeGeneratorConfig {
-      table_name: "products".to_string(),
-      code_column: "code".to_string(),
-      workspace_column: Some("workspace_id".to_string()),
-      prefix_length: 2,
-      number_length: 5,
-      separator: "-".to_string(),
-    };
-
-    code_generator.get_next_available_code(&config, product_name, Some(workspace_id)).await
-  }
-
-  async fn code_exists(&self, code: &str, workspace_id: Uuid) -> AppResult<bool> {
-    let count = sqlx::query_scalar!(
+  async fn restore_by_workspace_and_user(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<Product>> {
+    let mut conn = self.db.acquire().await?;
+    let product = sqlx::query_as!(
+      Product,
       r#"
-                SELECT COUNT(*) 
-                FROM products 
-                WHERE code = $1 AND workspace_id = $2
+                UPDATE products
+                SET deleted_at = NULL, updated_by = $3, updated_at = NOW()
+                WHERE id = $1 AND workspace_id = $2 AND deleted_at IS NOT NULL
+                  AND EXISTS (
+                    SELECT 1 FROM workspace_access wu
+                    WHERE wu.workspace_id = $2 AND wu.user_id = $3
+                  )
+                RETURNING
+                    id, code, name, category_id, base_unit, unit_on_report_preview,
+                    selling_price, unit_cost, supplier_id, track_inventory,
+                    description, sku, barcode, minimum_stock, maximum_stock,
+                    reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+                    is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
             "#,
-      code,
-      workspace_id
+      id,
+      workspace_id,
+      user_id
     )
-    .fetch_one(&self.db)
+    .fetch_optional(&mut *conn)
     .await
     .map_err(|e| {
-      tracing::error!("Failed to check if product code exists: {}", e);
-      crate::errors::AppError::from_sqlx_error(e, "SELECT COUNT FROM products")
-    })?
-    .unwrap_or(0);
+      tracing::error!("Failed to restore product: {}", e);
+      crate::errors::AppError::from_sqlx_error(e, "UPDATE products SET deleted_at = NULL")
+    })?;
+
+    Ok(product)
+  }
 
-    Ok(count > 0)
+  // Code generation methods
+  async fn get_next_available_code(
+    &self,
+    workspace_id: Uuid,
+    product_name: &str,
+    rules: Option<&CodeRules>,
+    category: Option<&str>,
+  ) -> AppResult<String> {
+    let code_generator = CodeGenerator::new(self.db.clone());
+    let config = code_config().with_rules(rules, category);
+
+    code_generator.get_next_available_code(&config, product_name, Some(workspace_id)).await
   }
 
   // Optional methods for specific use cases
   async fn find_by_category_and_workspace(&self, category_id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Product>> {
+    let mut conn = self.read_pool.acquire().await?;
     let products = sqlx::query_as!(
       Product,
       r#"
@@ -382,15 +799,13 @@ impl ProductRepository for SqlxProductRepository {
                     id, code, name, category_id, base_unit, unit_on_report_preview,
                     selling_price, unit_cost, supplier_id, track_inventory,
                     description, sku, barcode, minimum_stock, maximum_stock,
-                    reorder_level, stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
-                    is_active, workspace_id, created_by, updated_by, created_at, updated_at
+                    reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+                    is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
                 FROM products 
-                WHERE category_id = $1 AND workspace_id = $2 AND is_active = true
-                  AND id IN (
-                    SELECT p.id FROM products p
-                    JOIN workspaces w ON p.workspace_id = w.id
-                    JOIN workspace_users wu ON w.id = wu.workspace_id
-                    WHERE wu.user_id = $3
+                WHERE category_id = $1 AND workspace_id = $2 AND is_active = true AND status = 'active' AND deleted_at IS NULL
+                  AND EXISTS (
+                    SELECT 1 FROM workspace_access wu
+                    WHERE wu.workspace_id = $2 AND wu.user_id = $3
                   )
                 ORDER BY name ASC
             "#,
@@ -398,7 +813,7 @@ impl ProductRepository for SqlxProductRepository {
       workspace_id,
       user_id
     )
-    .fetch_all(&self.db)
+    .fetch_all(&mut *conn)
     .await
     .map_err(|e| {
       tracing::error!("Failed to fetch products by category: {}", e);
@@ -409,6 +824,7 @@ impl ProductRepository for SqlxProductRepository {
   }
 
   async fn find_by_supplier_and_workspace(&self, supplier_id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Product>> {
+    let mut conn = self.read_pool.acquire().await?;
     let products = sqlx::query_as!(
       Product,
       r#"
@@ -416,15 +832,13 @@ impl ProductRepository for SqlxProductRepository {
                     id, code, name, category_id, base_unit, unit_on_report_preview,
                     selling_price, unit_cost, supplier_id, track_inventory,
                     description, sku, barcode, minimum_stock, maximum_stock,
-                    reorder_level, stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
-                    is_active, workspace_id, created_by, updated_by, created_at, updated_at
+                    reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+                    is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
                 FROM products 
-                WHERE supplier_id = $1 AND workspace_id = $2 AND is_active = true
-                  AND id IN (
-                    SELECT p.id FROM products p
-                    JOIN workspaces w ON p.workspace_id = w.id
-                    JOIN workspace_users wu ON w.id = wu.workspace_id
-                    WHERE wu.user_id = $3
+                WHERE supplier_id = $1 AND workspace_id = $2 AND is_active = true AND status = 'active' AND deleted_at IS NULL
+                  AND EXISTS (
+                    SELECT 1 FROM workspace_access wu
+                    WHERE wu.workspace_id = $2 AND wu.user_id = $3
                   )
                 ORDER BY name ASC
             "#,
@@ -432,7 +846,7 @@ impl ProductRepository for SqlxProductRepository {
       workspace_id,
       user_id
     )
-    .fetch_all(&self.db)
+    .fetch_all(&mut *conn)
     .await
     .map_err(|e| {
       tracing::error!("Failed to fetch products by supplier: {}", e);
@@ -443,6 +857,7 @@ impl ProductRepository for SqlxProductRepository {
   }
 
   async fn find_active_by_workspace(&self, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Product>> {
+    let mut conn = self.read_pool.acquire().await?;
     let products = sqlx::query_as!(
       Product,
       r#"
@@ -450,22 +865,20 @@ impl ProductRepository for SqlxProductRepository {
                     id, code, name, category_id, base_unit, unit_on_report_preview,
                     selling_price, unit_cost, supplier_id, track_inventory,
                     description, sku, barcode, minimum_stock, maximum_stock,
-                    reorder_level, stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
-                    is_active, workspace_id, created_by, updated_by, created_at, updated_at
+                    reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+                    is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
                 FROM products 
-                WHERE workspace_id = $1 AND is_active = true
-                  AND id IN (
-                    SELECT p.id FROM products p
-                    JOIN workspaces w ON p.workspace_id = w.id
-                    JOIN workspace_users wu ON w.id = wu.workspace_id
-                    WHERE wu.user_id = $2
+                WHERE workspace_id = $1 AND is_active = true AND status = 'active' AND deleted_at IS NULL
+                  AND EXISTS (
+                    SELECT 1 FROM workspace_access wu
+                    WHERE wu.workspace_id = $1 AND wu.user_id = $2
                   )
                 ORDER BY name ASC
             "#,
       workspace_id,
       user_id
     )
-    .fetch_all(&self.db)
+    .fetch_all(&mut *conn)
     .await
     .map_err(|e| {
       tracing::error!("Failed to fetch active products: {}", e);
@@ -475,7 +888,82 @@ impl ProductRepository for SqlxProductRepository {
     Ok(products)
   }
 
+  async fn find_changed(
+    &self,
+    workspace_id: Uuid,
+    user_id: Uuid,
+    after: Option<(DateTime<Utc>, Uuid)>,
+    settled_before: DateTime<Utc>,
+    limit: i64,
+  ) -> AppResult<Vec<Product>> {
+    // On the primary: a replica lagging behind would hand out cursors past rows it has not seen yet
+    let mut conn = self.db.acquire().await?;
+    let products = match after {
+      Some((updated_at, id)) => {
+        sqlx::query_as!(
+          Product,
+          r#"
+            SELECT
+              id, code, name, category_id, base_unit, unit_on_report_preview,
+              selling_price, unit_cost, supplier_id, track_inventory,
+              description, sku, barcode, minimum_stock, maximum_stock,
+              reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+              is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
+            FROM products
+            WHERE workspace_id = $1 AND (updated_at, id) > ($3, $4) AND updated_at < $5 AND deleted_at IS NULL
+              AND EXISTS (
+                SELECT 1 FROM workspace_access wu
+                WHERE wu.workspace_id = $1 AND wu.user_id = $2
+              )
+            ORDER BY updated_at, id
+            LIMIT $6
+          "#,
+          workspace_id,
+          user_id,
+          updated_at,
+          id,
+          settled_before,
+          limit
+        )
+        .fetch_all(&mut *conn)
+        .await?
+      }
+      None => {
+        let mut latest = sqlx::query_as!(
+          Product,
+          r#"
+            SELECT
+              id, code, name, category_id, base_unit, unit_on_report_preview,
+              selling_price, unit_cost, supplier_id, track_inventory,
+              description, sku, barcode, minimum_stock, maximum_stock,
+              reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+              is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
+            FROM products
+            WHERE workspace_id = $1 AND updated_at < $3 AND deleted_at IS NULL
+              AND EXISTS (
+                SELECT 1 FROM workspace_access wu
+                WHERE wu.workspace_id = $1 AND wu.user_id = $2
+              )
+            ORDER BY updated_at DESC, id DESC
+            LIMIT $4
+          "#,
+          workspace_id,
+          user_id,
+          settled_before,
+          limit
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        latest.reverse();
+        latest
+      }
+    };
+
+    Ok(products)
+  }
+
   async fn find_low_stock_by_workspace(&self, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Product>> {
+    let mut conn = self.read_pool.acquire().await?;
     let products = sqlx::query_as!(
       Product,
       r#"
@@ -483,27 +971,25 @@ impl ProductRepository for SqlxProductRepository {
                     id, code, name, category_id, base_unit, unit_on_report_preview,
                     selling_price, unit_cost, supplier_id, track_inventory,
                     description, sku, barcode, minimum_stock, maximum_stock,
-                    reorder_level, stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
-                    is_active, workspace_id, created_by, updated_by, created_at, updated_at
+                    reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+                    is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
                 FROM products 
-                WHERE workspace_id = $1 
+                WHERE workspace_id = $1 AND deleted_at IS NULL
                     AND is_active = true 
                     AND track_inventory = true
-                    AND stock IS NOT NULL 
+                    AND current_stock IS NOT NULL 
                     AND reorder_level IS NOT NULL
-                    AND stock <= reorder_level
-                    AND id IN (
-                      SELECT p.id FROM products p
-                      JOIN workspaces w ON p.workspace_id = w.id
-                      JOIN workspace_users wu ON w.id = wu.workspace_id
-                      WHERE wu.user_id = $2
+                    AND current_stock <= reorder_level
+                    AND EXISTS (
+                      SELECT 1 FROM workspace_access wu
+                      WHERE wu.workspace_id = $1 AND wu.user_id = $2
                     )
-                ORDER BY stock ASC
+                ORDER BY current_stock ASC
             "#,
       workspace_id,
       user_id
     )
-    .fetch_all(&self.db)
+    .fetch_all(&mut *conn)
     .await
     .map_err(|e| {
       tracing::error!("Failed to fetch low stock products: {}", e);
@@ -513,6 +999,130 @@ impl ProductRepository for SqlxProductRepository {
     Ok(products)
   }
 
+  async fn find_stock_by_ids(&self, ids: &[Uuid], workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<ProductStock>> {
+    let mut conn = self.read_pool.acquire().await?;
+    let stock = sqlx::query_as!(
+      ProductStock,
+      r#"
+                SELECT id, is_active, status as "status: ProductStatus", track_inventory, current_stock
+                FROM products
+                WHERE id = ANY($1) AND workspace_id = $2 AND deleted_at IS NULL
+                  AND EXISTS (
+                    SELECT 1 FROM workspace_access wu
+                    WHERE wu.workspace_id = $2 AND wu.user_id = $3
+                  )
+            "#,
+      ids,
+      workspace_id,
+      user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+      tracing::error!("Failed to fetch product stock: {}", e);
+      crate::errors::AppError::from_sqlx_error(e, "SELECT FROM products WHERE id = ANY")
+    })?;
+
+    Ok(stock)
+  }
+
+  async fn find_by_ids(&self, ids: &[Uuid], workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<Product>> {
+    let mut conn = self.read_pool.acquire().await?;
+    let products = sqlx::query_as!(
+      Product,
+      r#"
+                SELECT 
+                    id, code, name, category_id, base_unit, unit_on_report_preview,
+                    selling_price, unit_cost, supplier_id, track_inventory,
+                    description, sku, barcode, minimum_stock, maximum_stock,
+                    reorder_level, current_stock, tax_type as "tax_type: TaxType", tax_rate, tax_amount,
+                    is_active, status as "status: ProductStatus", workspace_id, created_by, updated_by, created_at, updated_at, deleted_at
+                FROM products
+                WHERE id = ANY($1) AND workspace_id = $2 AND deleted_at IS NULL
+                  AND EXISTS (
+                    SELECT 1 FROM workspace_access wu
+                    WHERE wu.workspace_id = $2 AND wu.user_id = $3
+                  )
+            "#,
+      ids,
+      workspace_id,
+      user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+      tracing::error!("Failed to fetch products by ids: {}", e);
+      crate::errors::AppError::from_sqlx_error(e, "SELECT FROM products WHERE id = ANY")
+    })?;
+
+    Ok(products)
+  }
+
+  async fn find_category_aggregates(&self, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<CategoryAggregate>> {
+    let mut conn = self.read_pool.acquire().await?;
+    // `subtree` pairs every category with itself and each category below it; the depth limit
+    // stops the recursion on categories whose parents form a cycle.
+    let categories = sqlx::query_as!(
+      CategoryAggregate,
+      r#"
+                WITH RECURSIVE categories AS (
+                    SELECT id, parent_id
+                    FROM product_categories
+                    WHERE workspace_id = $1
+                      AND EXISTS (
+                        SELECT 1 FROM workspace_access wu
+                        WHERE wu.workspace_id = $1 AND wu.user_id = $2
+                      )
+                ),
+                subtree (ancestor_id, category_id, depth) AS (
+                    SELECT id, id, 0 FROM categories
+                    UNION ALL
+                    SELECT s.ancestor_id, c.id, s.depth + 1
+                    FROM subtree s
+                    JOIN categories c ON c.parent_id = s.category_id
+                    WHERE s.depth < 32
+                ),
+                own AS (
+                    SELECT category_id,
+                        COUNT(*) AS product_count,
+                        SUM(CASE WHEN track_inventory THEN COALESCE(current_stock, 0) * unit_cost ELSE 0 END) AS stock_value
+                    FROM products
+                    WHERE workspace_id = $1 AND is_active = true AND category_id IS NOT NULL AND deleted_at IS NULL
+                    GROUP BY category_id
+                ),
+                totals AS (
+                    SELECT s.ancestor_id,
+                        SUM(o.product_count)::BIGINT AS product_count,
+                        SUM(o.stock_value) AS stock_value
+                    FROM subtree s
+                    JOIN own o ON o.category_id = s.category_id
+                    GROUP BY s.ancestor_id
+                )
+                SELECT
+                    pc.id, pc.code, pc.name, pc.parent_id, pc.is_active,
+                    COALESCE(o.product_count, 0) AS "product_count!",
+                    COALESCE(o.stock_value, 0) AS "stock_value!",
+                    COALESCE(t.product_count, 0) AS "total_product_count!",
+                    COALESCE(t.stock_value, 0) AS "total_stock_value!"
+                FROM product_categories pc
+                JOIN categories ON categories.id = pc.id
+                LEFT JOIN own o ON o.category_id = pc.id
+                LEFT JOIN totals t ON t.ancestor_id = pc.id
+                ORDER BY pc.name ASC, pc.id
+            "#,
+      workspace_id,
+      user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+      tracing::error!("Failed to fetch category aggregates: {}", e);
+      crate::errors::AppError::from_sqlx_error(e, "SELECT FROM product_categories WITH RECURSIVE")
+    })?;
+
+    Ok(categories)
+  }
+
   // Advanced filtering method
   async fn find_by_filters_paginated(
     &self,
@@ -522,35 +1132,24 @@ impl ProductRepository for SqlxProductRepository {
     limit: u32,
     filters: ProductFilters,
   ) -> AppResult<(Vec<Product>, u64)> {
-    let (select_sql, count_sql) = super::product_query_builder::ProductQueryBuilder::build_filtered_query(workspace_id, user_id, &filters);
-
-    // Execute count query
-    let total_count_result = sqlx::query_scalar::<_, i64>(&count_sql)
-      .bind(workspace_id)
-      .fetch_one(&self.db)
-      .await
-      .map_err(|e| {
-        tracing::error!("Failed to count filtered products: {}", e);
-        crate::errors::AppError::from_sqlx_error(e, "COUNT filtered products")
-      })?;
-
-    let total_count = total_count_result as u64;
-
-    // Calculate pagination
-    let offset = (page - 1) * limit;
+    self.find_page(workspace_id, user_id, page, limit, &filters).await
+  }
 
-    // Execute select query with pagination
-    let final_query = format!("{} LIMIT {} OFFSET {}", select_sql, limit, offset);
+  async fn stream_by_filters(&self, workspace_id: Uuid, user_id: Uuid, filters: ProductFilters, rows: RowSender<Product>) -> AppResult<()> {
+    let mut conn = self.read_pool.acquire().await?;
+    let (sql, values) = Self::build_stream_query(workspace_id, user_id, &filters);
 
-    let products = sqlx::query_as::<_, Product>(&final_query)
-      .bind(workspace_id)
-      .fetch_all(&self.db)
-      .await
-      .map_err(|e| {
-        tracing::error!("Failed to fetch filtered products: {}", e);
-        crate::errors::AppError::from_sqlx_error(e, "SELECT filtered products")
-      })?;
+    let mut products = sqlx::query_as_with::<_, Product, _>(&sql, values).fetch(&mut *conn);
+    while let Some(product) = products.try_next().await.map_err(|e| {
+      tracing::error!("Failed to stream products: {}", e);
+      crate::errors::AppError::from_sqlx_error(e, "SELECT streamed products")
+    })? {
+      if rows.send(Ok(product)).await.is_err() {
+        // The client went away; stop reading
+        break;
+      }
+    }
 
-    Ok((products, total_count))
+    Ok(())
   }
 }
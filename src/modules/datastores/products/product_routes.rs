@@ -2,17 +2,39 @@ use std::sync::Arc;
 
 use axum::{
   Router,
-  routing::{delete, get, post, put},
+  routing::{delete, get, patch, post, put},
 };
 
-use crate::{AppState, modules::datastores::products::product_handlers};
+use crate::{
+  AppState,
+  modules::{
+    datastores::products::{category_handlers, product_handlers},
+    record_locks::record_lock_handlers,
+  },
+};
 
 pub fn router() -> Router<Arc<AppState>> {
-  Router::new()
-    .route("/", get(product_handlers::get_list))
+  let router = Router::new()
+    .route("/", get(product_handlers::list))
     .route("/", post(product_handlers::create))
     .route("/next-code", get(product_handlers::get_next_code))
+    .route("/availability", post(product_handlers::availability))
     .route("/:id", get(product_handlers::get_by_id))
     .route("/:id", put(product_handlers::update))
+    .route("/:id", patch(product_handlers::patch))
     .route("/:id", delete(product_handlers::delete))
+    .route("/:id/restore", post(product_handlers::restore))
+    .route("/:id/status", post(product_handlers::change_status))
+    .route("/:id/lock", post(record_lock_handlers::lock_product))
+    .route("/:id/lock", delete(record_lock_handlers::unlock_product));
+  #[cfg(feature = "rendering")]
+  let router = router
+    .route("/labels", post(crate::modules::rendering::rendering_handlers::print_product_labels))
+    .route("/:id/pdf", get(crate::modules::rendering::rendering_handlers::print_product));
+  router
+}
+
+/// Product categories, mounted at `/api/v1/categories`.
+pub fn category_router() -> Router<Arc<AppState>> {
+  Router::new().route("/tree", get(category_handlers::get_tree))
 }
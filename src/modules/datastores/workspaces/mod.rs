@@ -1,9 +1,11 @@
 pub mod workspace_handlers;
 pub mod workspace_models;
 pub mod workspace_repository;
+pub mod workspace_role_cache;
 pub mod workspace_routes;
 
 pub use workspace_handlers::*;
 pub use workspace_models::*;
 pub use workspace_repository::*;
+pub use workspace_role_cache::*;
 pub use workspace_routes::*;
@@ -1,28 +1,42 @@
 use axum::{
-  extract::{Path, State},
+  extract::{State, rejection::JsonRejection},
   response::Json,
 };
+use serde_json::json;
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
   AppResult,
-  errors::AppError, 
-  modules::auth::current_user::CurrentUser, 
+  errors::AppError,
+  helper::PathUuid,
+  modules::{
+    audit::{
+      audit_models::{AuditAction, NewAuditEvent},
+      audit_service,
+    },
+    auth::current_user::CurrentUser,
+  },
   responses::ApiResponse,
-  state::AppState
+  state::AppState,
+  utils::merge_patch::{MergePatch, apply_merge_patch},
 };
 
 use super::workspace_models::{
-  AddUserToWorkspaceRequest, CreateWorkspaceRequest, UpdateUserRoleRequest, UpdateWorkspaceRequest, Workspace, WorkspaceUserInfo, WorkspaceWithRole,
+  AddUserToWorkspaceRequest, CreateWorkspaceRequest, UpdateUserRoleRequest, UpdateWorkspaceRequest, Workspace, WorkspacePatchTarget,
+  WorkspaceUserInfo, WorkspaceWithRole,
 };
 
 pub async fn create_workspace(
   State(state): State<Arc<AppState>>,
   current_user: CurrentUser,
-  Json(request): Json<CreateWorkspaceRequest>,
+  payload: Result<Json<CreateWorkspaceRequest>, JsonRejection>,
 ) -> AppResult<Json<ApiResponse<Workspace>>> {
-  let workspace = state.workspace_repository.create_and_assign_owner(request, current_user.user_id).await?;
+  let Json(request) = payload?;
+  let workspace = state
+    .workspace_repository
+    .create_and_assign_owner(request, current_user.user_id, state.config.database.tenant_isolation)
+    .await?;
 
   let response = ApiResponse::success(workspace, "Workspace created successfully");
   Ok(Json(response))
@@ -31,11 +45,8 @@ pub async fn create_workspace(
 pub async fn get_workspace(
   State(state): State<Arc<AppState>>,
   current_user: CurrentUser,
-  Path(workspace_id): Path<String>,
+  PathUuid(workspace_id): PathUuid,
 ) -> AppResult<Json<ApiResponse<Workspace>>> {
-  // Parse UUID with global error handling
-  let workspace_id = workspace_id.parse::<Uuid>()?;
-
   // Check if user has access to this workspace
   let role = state
     .workspace_repository
@@ -46,12 +57,11 @@ pub async fn get_workspace(
     return Err(AppError::Authorization("Access denied to workspace".to_string()));
   }
 
-  let workspace = state.workspace_repository.get_workspace_by_id(workspace_id).await?.ok_or_else(|| {
-    AppError::NotFound(crate::errors::NotFoundError {
-      resource: "Workspace".to_string(),
-      id: Some(workspace_id),
-    })
-  })?;
+  let workspace = state
+    .workspace_repository
+    .get_workspace_by_id(workspace_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Workspace", workspace_id))?;
 
   let response = ApiResponse::success(workspace, "Workspace retrieved successfully");
   Ok(Json(response))
@@ -60,12 +70,10 @@ pub async fn get_workspace(
 pub async fn update_workspace(
   State(state): State<Arc<AppState>>,
   current_user: CurrentUser,
-  Path(workspace_id): Path<String>,
-  Json(request): Json<UpdateWorkspaceRequest>,
+  PathUuid(workspace_id): PathUuid,
+  payload: Result<Json<UpdateWorkspaceRequest>, JsonRejection>,
 ) -> AppResult<Json<ApiResponse<Workspace>>> {
-  // Parse UUID with global error handling
-  let workspace_id = workspace_id.parse::<Uuid>()?;
-
+  let Json(request) = payload?;
   // Check if user is workspace owner
   let is_owner = state.workspace_repository.is_workspace_owner(current_user.user_id, workspace_id).await?;
 
@@ -79,14 +87,42 @@ pub async fn update_workspace(
   Ok(Json(response))
 }
 
+/// Applies a JSON Merge Patch (RFC 7396) to a workspace; `{"description": null}` clears the description.
+pub async fn patch_workspace(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(workspace_id): PathUuid,
+  MergePatch(patch): MergePatch,
+) -> AppResult<Json<ApiResponse<Workspace>>> {
+  // Check if user is workspace owner
+  let is_owner = state.workspace_repository.is_workspace_owner(current_user.user_id, workspace_id).await?;
+
+  if !is_owner {
+    return Err(AppError::Authorization("Only workspace owner can update workspace".to_string()));
+  }
+
+  let current = state
+    .workspace_repository
+    .get_workspace_by_id(workspace_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Workspace", workspace_id))?;
+
+  let fields = apply_merge_patch(&WorkspacePatchTarget::from(&current), &patch)?;
+  if fields.name.trim().is_empty() {
+    return Err(AppError::BadRequest("Workspace name cannot be empty".to_string()));
+  }
+
+  let workspace = state.workspace_repository.replace_workspace(workspace_id, &fields).await?;
+
+  let response = ApiResponse::success(workspace, "Workspace updated successfully");
+  Ok(Json(response))
+}
+
 pub async fn delete_workspace(
   State(state): State<Arc<AppState>>,
   current_user: CurrentUser,
-  Path(workspace_id): Path<String>,
+  PathUuid(workspace_id): PathUuid,
 ) -> AppResult<Json<ApiResponse<()>>> {
-  // Parse UUID with global error handling
-  let workspace_id = workspace_id.parse::<Uuid>()?;
-
   // Check if user is workspace owner
   let is_owner = state.workspace_repository.is_workspace_owner(current_user.user_id, workspace_id).await?;
 
@@ -95,12 +131,16 @@ pub async fn delete_workspace(
   }
 
   state.workspace_repository.delete_workspace(workspace_id).await?;
+  state.role_cache.invalidate_workspace(workspace_id);
 
   let response = ApiResponse::success((), "Workspace deleted successfully");
   Ok(Json(response))
 }
 
-pub async fn get_user_workspaces(State(state): State<Arc<AppState>>, current_user: CurrentUser) -> AppResult<Json<ApiResponse<Vec<WorkspaceWithRole>>>> {
+pub async fn get_user_workspaces(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+) -> AppResult<Json<ApiResponse<Vec<WorkspaceWithRole>>>> {
   let workspaces = state.workspace_repository.get_user_workspaces(current_user.user_id).await?;
 
   let response = ApiResponse::success(workspaces, "User workspaces retrieved successfully");
@@ -110,11 +150,8 @@ pub async fn get_user_workspaces(State(state): State<Arc<AppState>>, current_use
 pub async fn get_workspace_users(
   State(state): State<Arc<AppState>>,
   current_user: CurrentUser,
-  Path(workspace_id): Path<String>,
+  PathUuid(workspace_id): PathUuid,
 ) -> AppResult<Json<ApiResponse<Vec<WorkspaceUserInfo>>>> {
-  // Parse UUID with global error handling
-  let workspace_id = workspace_id.parse::<Uuid>()?;
-
   // Check if user has access to this workspace
   let role = state
     .workspace_repository
@@ -134,12 +171,10 @@ pub async fn get_workspace_users(
 pub async fn add_user_to_workspace(
   State(state): State<Arc<AppState>>,
   current_user: CurrentUser,
-  Path(workspace_id): Path<String>,
-  Json(request): Json<AddUserToWorkspaceRequest>,
+  PathUuid(workspace_id): PathUuid,
+  payload: Result<Json<AddUserToWorkspaceRequest>, JsonRejection>,
 ) -> AppResult<Json<ApiResponse<()>>> {
-  // Parse UUID with global error handling
-  let workspace_id = workspace_id.parse::<Uuid>()?;
-
+  let Json(request) = payload?;
   // Check if user is workspace owner
   let is_owner = state.workspace_repository.is_workspace_owner(current_user.user_id, workspace_id).await?;
 
@@ -151,6 +186,14 @@ pub async fn add_user_to_workspace(
     .workspace_repository
     .add_user_to_workspace(workspace_id, request.user_id, request.role)
     .await?;
+  let event = NewAuditEvent {
+    workspace_id,
+    action: AuditAction::MemberAdded,
+    actor_id: current_user.user_id,
+    target_user_id: Some(request.user_id),
+    details: json!({ "role": request.role }),
+  };
+  audit_service::record(&state, event).await;
 
   let response = ApiResponse::success((), "User added to workspace successfully");
   Ok(Json(response))
@@ -159,12 +202,8 @@ pub async fn add_user_to_workspace(
 pub async fn remove_user_from_workspace(
   State(state): State<Arc<AppState>>,
   current_user: CurrentUser,
-  Path((workspace_id, user_id)): Path<(String, String)>,
+  PathUuid((workspace_id, user_id)): PathUuid<(Uuid, Uuid)>,
 ) -> AppResult<Json<ApiResponse<()>>> {
-  // Parse UUIDs with global error handling
-  let workspace_id = workspace_id.parse::<Uuid>()?;
-  let user_id = user_id.parse::<Uuid>()?;
-
   // Check if user is workspace owner
   let is_owner = state.workspace_repository.is_workspace_owner(current_user.user_id, workspace_id).await?;
 
@@ -177,7 +216,19 @@ pub async fn remove_user_from_workspace(
     return Err(AppError::BadRequest("Cannot remove workspace owner".to_string()));
   }
 
+  let previous_role = state.workspace_repository.check_user_workspace_access(user_id, workspace_id).await?;
   state.workspace_repository.remove_user_from_workspace(workspace_id, user_id).await?;
+  state.role_cache.invalidate(user_id, workspace_id);
+  if let Some(previous_role) = previous_role {
+    let event = NewAuditEvent {
+      workspace_id,
+      action: AuditAction::MemberRemoved,
+      actor_id: current_user.user_id,
+      target_user_id: Some(user_id),
+      details: json!({ "role": previous_role }),
+    };
+    audit_service::record(&state, event).await;
+  }
 
   let response = ApiResponse::success((), "User removed from workspace successfully");
   Ok(Json(response))
@@ -186,13 +237,10 @@ pub async fn remove_user_from_workspace(
 pub async fn update_user_role(
   State(state): State<Arc<AppState>>,
   current_user: CurrentUser,
-  Path((workspace_id, user_id)): Path<(String, String)>,
-  Json(request): Json<UpdateUserRoleRequest>,
+  PathUuid((workspace_id, user_id)): PathUuid<(Uuid, Uuid)>,
+  payload: Result<Json<UpdateUserRoleRequest>, JsonRejection>,
 ) -> AppResult<Json<ApiResponse<()>>> {
-  // Parse UUIDs with global error handling
-  let workspace_id = workspace_id.parse::<Uuid>()?;
-  let user_id = user_id.parse::<Uuid>()?;
-
+  let Json(request) = payload?;
   // Check if user is workspace owner
   let is_owner = state.workspace_repository.is_workspace_owner(current_user.user_id, workspace_id).await?;
 
@@ -200,7 +248,19 @@ pub async fn update_user_role(
     return Err(AppError::Authorization("Only workspace owner can update user roles".to_string()));
   }
 
+  let previous_role = state.workspace_repository.check_user_workspace_access(user_id, workspace_id).await?;
   state.workspace_repository.update_user_role(workspace_id, user_id, request.role).await?;
+  state.role_cache.invalidate(user_id, workspace_id);
+  if previous_role != Some(request.role) {
+    let event = NewAuditEvent {
+      workspace_id,
+      action: AuditAction::RoleChanged,
+      actor_id: current_user.user_id,
+      target_user_id: Some(user_id),
+      details: json!({ "from": previous_role, "to": request.role }),
+    };
+    audit_service::record(&state, event).await;
+  }
 
   let response = ApiResponse::success((), "User role updated successfully");
   Ok(Json(response))
@@ -23,7 +23,7 @@ pub struct WorkspaceUser {
   pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "workspace_role", rename_all = "lowercase")]
 pub enum WorkspaceRole {
   Admin,
@@ -31,6 +31,17 @@ pub enum WorkspaceRole {
   Viewer,
 }
 
+impl WorkspaceRole {
+  /// Whether this role grants at least the permissions of `required` (admin > member > viewer).
+  pub fn includes(self, required: WorkspaceRole) -> bool {
+    match required {
+      WorkspaceRole::Viewer => true,
+      WorkspaceRole::Member => matches!(self, WorkspaceRole::Member | WorkspaceRole::Admin),
+      WorkspaceRole::Admin => matches!(self, WorkspaceRole::Admin),
+    }
+  }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateWorkspaceRequest {
   pub name: String,
@@ -43,6 +54,24 @@ pub struct UpdateWorkspaceRequest {
   pub description: Option<String>,
 }
 
+/// The editable fields of a workspace, used as the target of JSON Merge Patch (`PATCH`) updates.
+/// A `null` description in the patch clears it.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspacePatchTarget {
+  pub name: String,
+  pub description: Option<String>,
+}
+
+impl From<&Workspace> for WorkspacePatchTarget {
+  fn from(workspace: &Workspace) -> Self {
+    Self {
+      name: workspace.name.clone(),
+      description: workspace.description.clone(),
+    }
+  }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AddUserToWorkspaceRequest {
   pub user_id: Uuid,
@@ -55,6 +84,7 @@ pub struct UpdateUserRoleRequest {
 }
 
 #[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
 pub struct WorkspaceWithRole {
   #[serde(flatten)]
   pub workspace: Workspace,
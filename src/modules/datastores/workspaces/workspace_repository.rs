@@ -1,18 +1,25 @@
 use super::workspace_models::{
-  CreateWorkspaceRequest, UpdateWorkspaceRequest, Workspace, WorkspaceRole, WorkspaceUser, WorkspaceUserInfo, WorkspaceWithRole,
+  CreateWorkspaceRequest, UpdateWorkspaceRequest, Workspace, WorkspacePatchTarget, WorkspaceRole, WorkspaceUser, WorkspaceUserInfo, WorkspaceWithRole,
+};
+use crate::{
+  config::TenantIsolation,
+  errors::AppError,
+  utils::{DbExecutor, ReadPool, database_ext::PostgresSessionExt, tenant_schema},
 };
-use crate::{errors::AppError, utils::database_ext::PostgresSessionExt};
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::Connection;
 use uuid::Uuid;
 
 #[async_trait]
 pub trait WorkspaceRepository: Send + Sync {
-  // Workspace CRUD operations
-  async fn create_workspace(&self, request: &CreateWorkspaceRequest, owner_id: Uuid) -> Result<Workspace, AppError>;
-  async fn create_and_assign_owner(&self, payload: CreateWorkspaceRequest, owner_id: Uuid) -> Result<Workspace, AppError>;
+  // Workspace CRUD operations. With `TenantIsolation::Schema` the workspace is created with a
+  // schema of its own (see `utils::tenant_schema`).
+  async fn create_workspace(&self, request: &CreateWorkspaceRequest, owner_id: Uuid, isolation: TenantIsolation) -> Result<Workspace, AppError>;
+  async fn create_and_assign_owner(&self, payload: CreateWorkspaceRequest, owner_id: Uuid, isolation: TenantIsolation)
+  -> Result<Workspace, AppError>;
   async fn get_workspace_by_id(&self, workspace_id: Uuid) -> Result<Option<Workspace>, AppError>;
   async fn update_workspace(&self, workspace_id: Uuid, request: &UpdateWorkspaceRequest) -> Result<Workspace, AppError>;
+  async fn replace_workspace(&self, workspace_id: Uuid, fields: &WorkspacePatchTarget) -> Result<Workspace, AppError>;
   async fn delete_workspace(&self, workspace_id: Uuid) -> Result<(), AppError>;
 
   // User workspace access
@@ -31,21 +38,38 @@ pub trait WorkspaceRepository: Send + Sync {
 }
 
 pub struct PostgresWorkspaceRepository {
-  pool: PgPool,
+  db: DbExecutor,
+  read_pool: ReadPool,
 }
 
 impl PostgresWorkspaceRepository {
-  pub fn new(pool: PgPool) -> Self {
-    Self { pool }
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    let db = db.into();
+    Self {
+      read_pool: ReadPool::primary_only(db.clone()),
+      db,
+    }
+  }
+
+  /// Runs the read-only queries (lookups, lists and counts) on `read_pool` instead of the primary.
+  pub fn with_read_pool(mut self, read_pool: ReadPool) -> Self {
+    self.read_pool = read_pool;
+    self
   }
 }
 
 #[async_trait]
 impl WorkspaceRepository for PostgresWorkspaceRepository {
-  async fn create_and_assign_owner(&self, payload: CreateWorkspaceRequest, owner_id: Uuid) -> Result<Workspace, AppError> {
+  async fn create_and_assign_owner(
+    &self,
+    payload: CreateWorkspaceRequest,
+    owner_id: Uuid,
+    isolation: TenantIsolation,
+  ) -> Result<Workspace, AppError> {
+    let mut conn = self.db.acquire().await?;
     // Set RLS context for the current user
-    self.pool.set_session_settings(&owner_id, None).await?;
-    
+    conn.set_session_settings(&owner_id, None).await?;
+
     // Create the workspace - the database trigger will automatically add the creator to workspace_users
     let workspace = sqlx::query_as!(
       Workspace,
@@ -59,53 +83,68 @@ impl WorkspaceRepository for PostgresWorkspaceRepository {
       owner_id,
       owner_id
     )
-    .fetch_one(&self.pool)
+    .fetch_one(&mut *conn)
     .await?;
+    if isolation == TenantIsolation::Schema {
+      tenant_schema::provision(&mut conn, workspace.id).await?;
+    }
 
     Ok(workspace)
   }
 
-  async fn create_workspace(&self, request: &CreateWorkspaceRequest, owner_id: Uuid) -> Result<Workspace, AppError> {
+  async fn create_workspace(&self, request: &CreateWorkspaceRequest, owner_id: Uuid, isolation: TenantIsolation) -> Result<Workspace, AppError> {
     let workspace_id = Uuid::new_v4();
 
-    let mut tx = self.pool.begin().await?;
-
-    // Create workspace
-    let workspace = sqlx::query_as!(
-      Workspace,
-      r#"
+    // The transaction is replayed as a whole if it hits a deadlock or a dropped connection
+    let workspace = self
+      .db
+      .retry(|| async {
+        let mut conn = self.db.acquire().await?;
+        let mut tx = conn.begin().await?;
+
+        // Create workspace
+        let workspace = sqlx::query_as!(
+          Workspace,
+          r#"
             INSERT INTO workspaces (id, name, description, owner_id, created_by)
             VALUES ($1, $2, $3, $4, $5)
             RETURNING id, name, description, owner_id, created_by, updated_by, created_at, updated_at
             "#,
-      workspace_id,
-      request.name,
-      request.description,
-      owner_id,
-      owner_id
-    )
-    .fetch_one(&mut *tx)
-    .await?;
-
-    // Add owner as admin
-    sqlx::query!(
-      r#"
+          workspace_id,
+          request.name,
+          request.description,
+          owner_id,
+          owner_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // Add owner as admin
+        sqlx::query!(
+          r#"
             INSERT INTO workspace_users (workspace_id, user_id, role)
             VALUES ($1, $2, $3)
             "#,
-      workspace_id,
-      owner_id,
-      WorkspaceRole::Admin as WorkspaceRole
-    )
-    .execute(&mut *tx)
-    .await?;
-
-    tx.commit().await?;
+          workspace_id,
+          owner_id,
+          WorkspaceRole::Admin as WorkspaceRole
+        )
+        .execute(&mut *tx)
+        .await?;
+        if isolation == TenantIsolation::Schema {
+          tenant_schema::provision(&mut tx, workspace_id).await?;
+        }
+
+        tx.commit().await?;
+        Ok(workspace)
+      })
+      .await?;
 
     Ok(workspace)
   }
 
   async fn get_workspace_by_id(&self, workspace_id: Uuid) -> Result<Option<Workspace>, AppError> {
+    let mut conn = self.read_pool.acquire().await?;
     let workspace = sqlx::query_as!(
       Workspace,
       r#"
@@ -115,13 +154,14 @@ impl WorkspaceRepository for PostgresWorkspaceRepository {
             "#,
       workspace_id
     )
-    .fetch_optional(&self.pool)
+    .fetch_optional(&mut *conn)
     .await?;
 
     Ok(workspace)
   }
 
   async fn update_workspace(&self, workspace_id: Uuid, request: &UpdateWorkspaceRequest) -> Result<Workspace, AppError> {
+    let mut conn = self.db.acquire().await?;
     let workspace = sqlx::query_as!(
       Workspace,
       r#"
@@ -137,45 +177,79 @@ impl WorkspaceRepository for PostgresWorkspaceRepository {
       request.name,
       request.description
     )
-    .fetch_one(&self.pool)
+    .fetch_one(&mut *conn)
     .await?;
 
     Ok(workspace)
   }
 
-  async fn delete_workspace(&self, workspace_id: Uuid) -> Result<(), AppError> {
-    let mut tx = self.pool.begin().await?;
+  async fn replace_workspace(&self, workspace_id: Uuid, fields: &WorkspacePatchTarget) -> Result<Workspace, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let workspace = sqlx::query_as!(
+      Workspace,
+      r#"
+            UPDATE workspaces
+            SET
+                name = $2,
+                description = $3,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            RETURNING id, name, description, owner_id, created_by, updated_by, created_at, updated_at
+            "#,
+      workspace_id,
+      fields.name,
+      fields.description
+    )
+    .fetch_one(&mut *conn)
+    .await?;
 
-    // Remove all users from workspace
-    sqlx::query!("DELETE FROM workspace_users WHERE workspace_id = $1", workspace_id)
-      .execute(&mut *tx)
-      .await?;
+    Ok(workspace)
+  }
 
-    // Delete workspace
-    sqlx::query!("DELETE FROM workspaces WHERE id = $1", workspace_id)
-      .execute(&mut *tx)
+  async fn delete_workspace(&self, workspace_id: Uuid) -> Result<(), AppError> {
+    // The transaction is replayed as a whole if it hits a deadlock or a dropped connection
+    self
+      .db
+      .retry(|| async {
+        let mut conn = self.db.acquire().await?;
+        let mut tx = conn.begin().await?;
+
+        // The schema's tables reference the workspace, so they go first
+        tenant_schema::drop_schema(&mut tx, workspace_id).await?;
+
+        // Remove all users from workspace
+        sqlx::query!("DELETE FROM workspace_users WHERE workspace_id = $1", workspace_id)
+          .execute(&mut *tx)
+          .await?;
+
+        // Delete workspace
+        sqlx::query!("DELETE FROM workspaces WHERE id = $1", workspace_id)
+          .execute(&mut *tx)
+          .await?;
+
+        tx.commit().await
+      })
       .await?;
 
-    tx.commit().await?;
-
     Ok(())
   }
 
   async fn get_user_workspaces(&self, user_id: Uuid) -> Result<Vec<WorkspaceWithRole>, AppError> {
+    let mut conn = self.read_pool.acquire().await?;
     let workspaces = sqlx::query!(
       r#"
             SELECT w.id, w.name, w.description, w.owner_id, w.created_by, w.updated_by, w.created_at, w.updated_at,
                    wu.role as "role!: WorkspaceRole",
                    u.username as "owner_name?"
             FROM workspaces w
-            JOIN workspace_users wu ON w.id = wu.workspace_id
+            JOIN workspace_access wu ON w.id = wu.workspace_id
             LEFT JOIN users u ON w.owner_id = u.id
             WHERE wu.user_id = $1
             ORDER BY w.name
             "#,
       user_id
     )
-    .fetch_all(&self.pool)
+    .fetch_all(&mut *conn)
     .await?
     .into_iter()
     .map(|row| WorkspaceWithRole {
@@ -198,13 +272,14 @@ impl WorkspaceRepository for PostgresWorkspaceRepository {
   }
 
   async fn get_user_default_workspace(&self, user_id: Uuid) -> Result<Option<WorkspaceWithRole>, AppError> {
+    let mut conn = self.read_pool.acquire().await?;
     let workspace = sqlx::query!(
       r#"
             SELECT w.id, w.name, w.description, w.owner_id, w.created_by, w.updated_by, w.created_at, w.updated_at,
                    wu.role as "role!: WorkspaceRole",
                    u.username as "owner_name?"
             FROM workspaces w
-            JOIN workspace_users wu ON w.id = wu.workspace_id
+            JOIN workspace_access wu ON w.id = wu.workspace_id
             LEFT JOIN users u ON w.owner_id = u.id
             WHERE wu.user_id = $1
             ORDER BY w.created_at ASC
@@ -212,7 +287,7 @@ impl WorkspaceRepository for PostgresWorkspaceRepository {
             "#,
       user_id
     )
-    .fetch_optional(&self.pool)
+    .fetch_optional(&mut *conn)
     .await?;
 
     Ok(workspace.map(|row| WorkspaceWithRole {
@@ -232,6 +307,7 @@ impl WorkspaceRepository for PostgresWorkspaceRepository {
   }
 
   async fn get_workspace_users(&self, workspace_id: Uuid) -> Result<Vec<WorkspaceUserInfo>, AppError> {
+    let mut conn = self.read_pool.acquire().await?;
     let users = sqlx::query_as!(
       WorkspaceUserInfo,
       r#"
@@ -242,13 +318,14 @@ impl WorkspaceRepository for PostgresWorkspaceRepository {
             "#,
       workspace_id
     )
-    .fetch_all(&self.pool)
+    .fetch_all(&mut *conn)
     .await?;
 
     Ok(users)
   }
 
   async fn add_user_to_workspace(&self, workspace_id: Uuid, user_id: Uuid, role: WorkspaceRole) -> Result<WorkspaceUser, AppError> {
+    let mut conn = self.db.acquire().await?;
     let workspace_user = sqlx::query_as!(
       WorkspaceUser,
       r#"
@@ -260,25 +337,27 @@ impl WorkspaceRepository for PostgresWorkspaceRepository {
       user_id,
       role as WorkspaceRole
     )
-    .fetch_one(&self.pool)
+    .fetch_one(&mut *conn)
     .await?;
 
     Ok(workspace_user)
   }
 
   async fn remove_user_from_workspace(&self, workspace_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+    let mut conn = self.db.acquire().await?;
     sqlx::query!(
       "DELETE FROM workspace_users WHERE workspace_id = $1 AND user_id = $2",
       workspace_id,
       user_id
     )
-    .execute(&self.pool)
+    .execute(&mut *conn)
     .await?;
 
     Ok(())
   }
 
   async fn update_user_role(&self, workspace_id: Uuid, user_id: Uuid, role: WorkspaceRole) -> Result<WorkspaceUser, AppError> {
+    let mut conn = self.db.acquire().await?;
     let workspace_user = sqlx::query_as!(
       WorkspaceUser,
       r#"
@@ -291,23 +370,24 @@ impl WorkspaceRepository for PostgresWorkspaceRepository {
       user_id,
       role as WorkspaceRole
     )
-    .fetch_one(&self.pool)
+    .fetch_one(&mut *conn)
     .await?;
 
     Ok(workspace_user)
   }
 
   async fn check_user_workspace_access(&self, user_id: Uuid, workspace_id: Uuid) -> Result<Option<WorkspaceRole>, AppError> {
+    let mut conn = self.db.acquire().await?;
     let role = sqlx::query!(
       r#"
             SELECT role as "role!: WorkspaceRole"
-            FROM workspace_users
+            FROM workspace_access
             WHERE user_id = $1 AND workspace_id = $2
             "#,
       user_id,
       workspace_id
     )
-    .fetch_optional(&self.pool)
+    .fetch_optional(&mut *conn)
     .await?
     .map(|row| row.role);
 
@@ -315,12 +395,13 @@ impl WorkspaceRepository for PostgresWorkspaceRepository {
   }
 
   async fn is_workspace_owner(&self, user_id: Uuid, workspace_id: Uuid) -> Result<bool, AppError> {
+    let mut conn = self.db.acquire().await?;
     let count = sqlx::query!(
       "SELECT COUNT(*) as count FROM workspaces WHERE id = $1 AND owner_id = $2",
       workspace_id,
       user_id
     )
-    .fetch_one(&self.pool)
+    .fetch_one(&mut *conn)
     .await?;
 
     Ok(count.count.unwrap_or(0) > 0)
@@ -0,0 +1,71 @@
+use std::{
+  collections::HashMap,
+  sync::{Mutex, MutexGuard},
+  time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+use super::workspace_models::WorkspaceRole;
+
+/// Number of cached roles above which expired entries are purged on the next insert.
+const PURGE_THRESHOLD: usize = 10_000;
+
+/// Workspace roles recently loaded for a `(user, workspace)` pair.
+///
+/// Every workspace-scoped request needs the caller's role, first to authorize it and then for
+/// the `app.current_user_role` RLS variable. The cache lets consecutive requests of the same
+/// caller skip the `workspace_users` lookup. Role changes made through this instance invalidate
+/// the entry right away; other instances pick them up once it expires, so the TTL bounds how
+/// long a revoked role keeps working. Only memberships are cached, so a user added to a
+/// workspace never has to wait for an entry to expire.
+pub struct WorkspaceRoleCache {
+  ttl: Duration,
+  roles: Mutex<HashMap<(Uuid, Uuid), (WorkspaceRole, Instant)>>,
+}
+
+impl WorkspaceRoleCache {
+  /// A cache keeping roles for `ttl_secs`; 0 disables it.
+  pub fn new(ttl_secs: u64) -> Self {
+    Self {
+      ttl: Duration::from_secs(ttl_secs),
+      roles: Mutex::new(HashMap::new()),
+    }
+  }
+
+  pub fn get(&self, user_id: Uuid, workspace_id: Uuid) -> Option<WorkspaceRole> {
+    let now = Instant::now();
+    self
+      .lock()
+      .get(&(user_id, workspace_id))
+      .filter(|(_, expires_at)| *expires_at > now)
+      .map(|(role, _)| *role)
+  }
+
+  pub fn insert(&self, user_id: Uuid, workspace_id: Uuid, role: WorkspaceRole) {
+    if self.ttl.is_zero() {
+      return;
+    }
+    let now = Instant::now();
+    let mut roles = self.lock();
+    if roles.len() > PURGE_THRESHOLD {
+      roles.retain(|_, (_, expires_at)| *expires_at > now);
+    }
+    roles.insert((user_id, workspace_id), (role, now + self.ttl));
+  }
+
+  /// Forgets the role of `user_id` in `workspace_id`, after it was changed or revoked.
+  pub fn invalidate(&self, user_id: Uuid, workspace_id: Uuid) {
+    self.lock().remove(&(user_id, workspace_id));
+  }
+
+  /// Forgets every role in `workspace_id`, after the workspace was deleted.
+  pub fn invalidate_workspace(&self, workspace_id: Uuid) {
+    self.lock().retain(|(_, cached_workspace), _| *cached_workspace != workspace_id);
+  }
+
+  // The map holds no invariants a panicking writer could break, so a poisoned lock is reused
+  fn lock(&self) -> MutexGuard<'_, HashMap<(Uuid, Uuid), (WorkspaceRole, Instant)>> {
+    self.roles.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
@@ -1,14 +1,23 @@
 use axum::{
   Router,
-  routing::{delete, get, post, put},
+  routing::{delete, get, patch, post, put},
 };
 use std::sync::Arc;
 
-use crate::state::AppState;
+use crate::{
+  modules::{
+    audit::audit_handlers::list_audit_events,
+    retention::retention_handlers::{get_retention, preview_retention, set_retention_policy},
+    trial::trial_handlers::get_trial,
+    usage::usage_handlers::get_api_usage,
+    workspace_settings::workspace_settings_handlers::{get_workspace_settings, update_workspace_settings},
+  },
+  state::AppState,
+};
 
 use super::workspace_handlers::{
-  add_user_to_workspace, create_workspace, delete_workspace, get_user_workspaces, get_workspace, get_workspace_users, remove_user_from_workspace,
-  update_user_role, update_workspace,
+  add_user_to_workspace, create_workspace, delete_workspace, get_user_workspaces, get_workspace, get_workspace_users, patch_workspace,
+  remove_user_from_workspace, update_user_role, update_workspace,
 };
 
 pub fn workspace_routes() -> Router<Arc<AppState>> {
@@ -18,10 +27,24 @@ pub fn workspace_routes() -> Router<Arc<AppState>> {
     .route("/workspaces", get(get_user_workspaces))
     .route("/workspaces/:workspace_id", get(get_workspace))
     .route("/workspaces/:workspace_id", put(update_workspace))
+    .route("/workspaces/:workspace_id", patch(patch_workspace))
     .route("/workspaces/:workspace_id", delete(delete_workspace))
     // Workspace user management
     .route("/workspaces/:workspace_id/users", get(get_workspace_users))
     .route("/workspaces/:workspace_id/users", post(add_user_to_workspace))
     .route("/workspaces/:workspace_id/users/:user_id", delete(remove_user_from_workspace))
     .route("/workspaces/:workspace_id/users/:user_id/role", put(update_user_role))
+    // Usage metering
+    .route("/workspaces/:workspace_id/usage/api", get(get_api_usage))
+    // Trial
+    .route("/workspaces/:workspace_id/trial", get(get_trial))
+    // Data retention
+    .route("/workspaces/:workspace_id/retention", get(get_retention))
+    .route("/workspaces/:workspace_id/retention", put(set_retention_policy))
+    .route("/workspaces/:workspace_id/retention/preview", get(preview_retention))
+    // Settings, such as the IP allowlist
+    .route("/workspaces/:workspace_id/settings", get(get_workspace_settings))
+    .route("/workspaces/:workspace_id/settings", put(update_workspace_settings))
+    // Audit log
+    .route("/workspaces/:workspace_id/audit-events", get(list_audit_events))
 }
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use axum::{
+  extract::{State, rejection::JsonRejection},
+  response::Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{
+  email_template_models::{EmailTemplate, EmailTemplateRequest, PreviewRequest, RenderedEmail},
+  email_template_renderer::{check_template, render_email},
+};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{
+    PathUuid, RequireRole, RequiredWorkspace,
+    workspace::role::{Admin, Member},
+  },
+  modules::auth::current_user::CurrentUser,
+  responses::{ApiResponse, Created},
+  state::AppState,
+};
+
+/// A workspace has one template per kind, so another template of the kind is a conflict.
+async fn ensure_kind_available(state: &AppState, workspace_id: Uuid, request: &EmailTemplateRequest, template_id: Option<Uuid>) -> AppResult<()> {
+  let existing = state.email_template_repository.find_by_kind(workspace_id, request.kind).await?;
+  match existing {
+    Some(existing) if Some(existing.id) != template_id => Err(AppError::Conflict(format!(
+      "The workspace already has a template for this kind of email ({})",
+      existing.id
+    ))),
+    _ => Ok(()),
+  }
+}
+
+/// The email templates of the workspace.
+pub async fn list_email_templates(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+) -> AppResult<Json<ApiResponse<Vec<EmailTemplate>>>> {
+  let templates = state.email_template_repository.list(workspace_id).await?;
+  Ok(Json(ApiResponse::success(templates, "Email templates retrieved successfully")))
+}
+
+pub async fn get_email_template(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  PathUuid(template_id): PathUuid,
+) -> AppResult<Json<ApiResponse<EmailTemplate>>> {
+  let template = state
+    .email_template_repository
+    .get(template_id, workspace_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Email template", template_id))?;
+  Ok(Json(ApiResponse::success(template, "Email template retrieved successfully")))
+}
+
+/// Customizes an email of the workspace. The subject and body must render with the variables of
+/// the kind, so mistakes surface here rather than when the email is sent.
+pub async fn create_email_template(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  payload: Result<Json<EmailTemplateRequest>, JsonRejection>,
+) -> AppResult<Created<ApiResponse<EmailTemplate>>> {
+  let Json(request) = payload?;
+  request.validate()?;
+  check_template(request.kind, &request.subject, &request.body)?;
+  ensure_kind_available(&state, workspace_id, &request, None).await?;
+
+  let template = state
+    .email_template_repository
+    .create(workspace_id, &request, current_user.user_id)
+    .await?;
+
+  let location = format!("/api/v1/templates/{}", template.id);
+  Ok(ApiResponse::created(template, "Email template created successfully", location))
+}
+
+/// Replaces an email template, checked as when it was created.
+pub async fn replace_email_template(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  PathUuid(template_id): PathUuid,
+  payload: Result<Json<EmailTemplateRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<EmailTemplate>>> {
+  let Json(request) = payload?;
+  request.validate()?;
+  check_template(request.kind, &request.subject, &request.body)?;
+  ensure_kind_available(&state, workspace_id, &request, Some(template_id)).await?;
+
+  let template = state
+    .email_template_repository
+    .replace(template_id, workspace_id, &request, current_user.user_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Email template", template_id))?;
+  Ok(Json(ApiResponse::success(template, "Email template updated successfully")))
+}
+
+/// Deletes an email template.
+pub async fn delete_email_template(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  PathUuid(template_id): PathUuid,
+) -> AppResult<Json<ApiResponse<()>>> {
+  if !state.email_template_repository.delete(template_id, workspace_id).await? {
+    return Err(AppError::not_found_with_id("Email template", template_id));
+  }
+  Ok(Json(ApiResponse::success((), "Email template deleted successfully")))
+}
+
+/// Renders an email template with the sample values of its variables, or the `variables` sent.
+pub async fn preview_email_template(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  PathUuid(template_id): PathUuid,
+  payload: Result<Json<PreviewRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<RenderedEmail>>> {
+  let Json(request) = payload?;
+
+  let template = state
+    .email_template_repository
+    .get(template_id, workspace_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Email template", template_id))?;
+  let rendered = render_email(template.kind, &template.subject, &template.body, request.variables)?;
+  Ok(Json(ApiResponse::success(rendered, "Email template rendered successfully")))
+}
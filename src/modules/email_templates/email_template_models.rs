@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Longest template body, in characters.
+pub const MAX_BODY_LENGTH: u64 = 20_000;
+
+/// An email a workspace sends, whose template it can customize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "email_template_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum EmailTemplateKind {
+  /// Invites someone to join the workspace.
+  Invitation,
+  /// Sends an invoice to a contact.
+  Invoice,
+  /// Reminds a contact of an unpaid invoice.
+  Reminder,
+}
+
+impl EmailTemplateKind {
+  /// The variables the templates of this kind can use, with the values previews render them with.
+  pub fn sample_variables(self) -> Map<String, Value> {
+    let samples: &[(&str, &str)] = match self {
+      EmailTemplateKind::Invitation => &[
+        ("workspace_name", "Acme Trading"),
+        ("inviter_name", "Jane Doe"),
+        ("invitee_email", "john@example.com"),
+        ("role", "member"),
+        ("accept_url", "https://app.example.com/invitations/accept?token=sample"),
+        ("expires_at", "2030-01-31"),
+      ],
+      EmailTemplateKind::Invoice => &[
+        ("workspace_name", "Acme Trading"),
+        ("contact_name", "PT Sumber Makmur"),
+        ("invoice_number", "INV-2030-0001"),
+        ("amount", "1,500,000.00"),
+        ("currency", "IDR"),
+        ("issued_at", "2030-01-01"),
+        ("due_date", "2030-01-31"),
+        ("invoice_url", "https://app.example.com/invoices/sample"),
+      ],
+      EmailTemplateKind::Reminder => &[
+        ("workspace_name", "Acme Trading"),
+        ("contact_name", "PT Sumber Makmur"),
+        ("invoice_number", "INV-2030-0001"),
+        ("amount_due", "1,500,000.00"),
+        ("currency", "IDR"),
+        ("due_date", "2030-01-31"),
+        ("days_overdue", "7"),
+        ("invoice_url", "https://app.example.com/invoices/sample"),
+      ],
+    };
+    samples
+      .iter()
+      .map(|(name, value)| (name.to_string(), Value::String(value.to_string())))
+      .collect()
+  }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EmailTemplate {
+  pub id: Uuid,
+  pub workspace_id: Uuid,
+  pub kind: EmailTemplateKind,
+  pub subject: String,
+  pub body: String,
+  pub created_by: Option<Uuid>,
+  pub updated_by: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// A template as created or replaced. The subject and body are Handlebars templates, which may
+/// only use the variables of their kind.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct EmailTemplateRequest {
+  pub kind: EmailTemplateKind,
+  #[validate(length(min = 1, max = 255, message = "Subject must be between 1 and 255 characters"))]
+  pub subject: String,
+  #[validate(length(min = 1, max = MAX_BODY_LENGTH, message = "Body must be between 1 and 20000 characters"))]
+  pub body: String,
+}
+
+/// Values to preview a template with instead of the samples of its kind.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PreviewRequest {
+  #[serde(default)]
+  pub variables: Map<String, Value>,
+}
+
+/// A template rendered with the sample or given values of its variables.
+#[derive(Debug, Serialize)]
+pub struct RenderedEmail {
+  pub subject: String,
+  pub body: String,
+  /// Every variable of the template's kind, with the value it was rendered with.
+  pub variables: Map<String, Value>,
+}
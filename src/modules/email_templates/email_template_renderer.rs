@@ -0,0 +1,62 @@
+use std::sync::LazyLock;
+
+use handlebars::{Handlebars, RenderError, RenderErrorReason, no_escape};
+use serde_json::{Map, Value};
+
+use super::email_template_models::{EmailTemplateKind, RenderedEmail};
+use crate::{AppResult, errors::AppError};
+
+/// Renders in strict mode, so a misspelled variable fails instead of leaving a blank in the email.
+static HANDLEBARS: LazyLock<Handlebars<'static>> = LazyLock::new(|| {
+  let mut handlebars = Handlebars::new();
+  handlebars.set_strict_mode(true);
+  // Emails are sent as plain text, where HTML escapes would show as is
+  handlebars.register_escape_fn(no_escape);
+  handlebars
+});
+
+/// Renders the subject and body of a template of `kind`. `values` replace the sample values of
+/// the kind's variables, and may not name other variables.
+pub fn render_email(kind: EmailTemplateKind, subject: &str, body: &str, values: Map<String, Value>) -> AppResult<RenderedEmail> {
+  let mut variables = kind.sample_variables();
+  for (name, value) in values {
+    if !variables.contains_key(&name) {
+      return Err(AppError::validation(
+        "variables",
+        &format!("Unknown variable '{}', {}", name, available(&variables)),
+      ));
+    }
+    variables.insert(name, value);
+  }
+
+  Ok(RenderedEmail {
+    subject: render("subject", subject, &variables)?,
+    body: render("body", body, &variables)?,
+    variables,
+  })
+}
+
+/// Checks that a template parses and only uses the variables of its kind, by rendering it with
+/// their sample values.
+pub fn check_template(kind: EmailTemplateKind, subject: &str, body: &str) -> AppResult<()> {
+  render_email(kind, subject, body, Map::new()).map(|_| ())
+}
+
+fn render(field: &str, template: &str, variables: &Map<String, Value>) -> AppResult<String> {
+  HANDLEBARS
+    .render_template(template, variables)
+    .map_err(|e| AppError::validation(field, &describe(&e, variables)))
+}
+
+fn describe(error: &RenderError, variables: &Map<String, Value>) -> String {
+  match error.reason() {
+    RenderErrorReason::MissingVariable(Some(name)) => format!("Unknown variable '{}', {}", name, available(variables)),
+    RenderErrorReason::TemplateError(e) => format!("Invalid template: {}", e.reason()),
+    reason => format!("Invalid template: {}", reason),
+  }
+}
+
+fn available(variables: &Map<String, Value>) -> String {
+  let names: Vec<&str> = variables.keys().map(String::as_str).collect();
+  format!("expected one of {}", names.join(", "))
+}
@@ -0,0 +1,136 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::email_template_models::{EmailTemplate, EmailTemplateKind, EmailTemplateRequest};
+use crate::{errors::AppError, utils::DbExecutor};
+
+#[async_trait]
+pub trait EmailTemplateRepository: Send + Sync {
+  async fn list(&self, workspace_id: Uuid) -> Result<Vec<EmailTemplate>, AppError>;
+  async fn get(&self, id: Uuid, workspace_id: Uuid) -> Result<Option<EmailTemplate>, AppError>;
+  /// The template of `kind` of the workspace, for the features sending that email.
+  async fn find_by_kind(&self, workspace_id: Uuid, kind: EmailTemplateKind) -> Result<Option<EmailTemplate>, AppError>;
+  async fn create(&self, workspace_id: Uuid, request: &EmailTemplateRequest, created_by: Uuid) -> Result<EmailTemplate, AppError>;
+  async fn replace(&self, id: Uuid, workspace_id: Uuid, request: &EmailTemplateRequest, updated_by: Uuid) -> Result<Option<EmailTemplate>, AppError>;
+  async fn delete(&self, id: Uuid, workspace_id: Uuid) -> Result<bool, AppError>;
+}
+
+pub struct PostgresEmailTemplateRepository {
+  db: DbExecutor,
+}
+
+impl PostgresEmailTemplateRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl EmailTemplateRepository for PostgresEmailTemplateRepository {
+  async fn list(&self, workspace_id: Uuid) -> Result<Vec<EmailTemplate>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let templates = sqlx::query_as!(
+      EmailTemplate,
+      r#"
+        SELECT id, workspace_id, kind as "kind: EmailTemplateKind", subject, body, created_by, updated_by, created_at, updated_at
+        FROM email_templates
+        WHERE workspace_id = $1
+        ORDER BY kind
+        "#,
+      workspace_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(templates)
+  }
+
+  async fn get(&self, id: Uuid, workspace_id: Uuid) -> Result<Option<EmailTemplate>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let template = sqlx::query_as!(
+      EmailTemplate,
+      r#"
+        SELECT id, workspace_id, kind as "kind: EmailTemplateKind", subject, body, created_by, updated_by, created_at, updated_at
+        FROM email_templates
+        WHERE id = $1 AND workspace_id = $2
+        "#,
+      id,
+      workspace_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(template)
+  }
+
+  async fn find_by_kind(&self, workspace_id: Uuid, kind: EmailTemplateKind) -> Result<Option<EmailTemplate>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let template = sqlx::query_as!(
+      EmailTemplate,
+      r#"
+        SELECT id, workspace_id, kind as "kind: EmailTemplateKind", subject, body, created_by, updated_by, created_at, updated_at
+        FROM email_templates
+        WHERE workspace_id = $1 AND kind = $2
+        "#,
+      workspace_id,
+      kind as EmailTemplateKind
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(template)
+  }
+
+  async fn create(&self, workspace_id: Uuid, request: &EmailTemplateRequest, created_by: Uuid) -> Result<EmailTemplate, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let template = sqlx::query_as!(
+      EmailTemplate,
+      r#"
+        INSERT INTO email_templates (workspace_id, kind, subject, body, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, workspace_id, kind as "kind: EmailTemplateKind", subject, body, created_by, updated_by, created_at, updated_at
+        "#,
+      workspace_id,
+      request.kind as EmailTemplateKind,
+      request.subject,
+      request.body,
+      created_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(template)
+  }
+
+  async fn replace(&self, id: Uuid, workspace_id: Uuid, request: &EmailTemplateRequest, updated_by: Uuid) -> Result<Option<EmailTemplate>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let template = sqlx::query_as!(
+      EmailTemplate,
+      r#"
+        UPDATE email_templates
+        SET kind = $3, subject = $4, body = $5, updated_by = $6
+        WHERE id = $1 AND workspace_id = $2
+        RETURNING id, workspace_id, kind as "kind: EmailTemplateKind", subject, body, created_by, updated_by, created_at, updated_at
+        "#,
+      id,
+      workspace_id,
+      request.kind as EmailTemplateKind,
+      request.subject,
+      request.body,
+      updated_by
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(template)
+  }
+
+  async fn delete(&self, id: Uuid, workspace_id: Uuid) -> Result<bool, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!("DELETE FROM email_templates WHERE id = $1 AND workspace_id = $2", id, workspace_id)
+      .execute(&mut *conn)
+      .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+}
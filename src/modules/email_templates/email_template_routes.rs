@@ -0,0 +1,22 @@
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  routing::{delete, get, post, put},
+};
+
+use super::email_template_handlers::{
+  create_email_template, delete_email_template, get_email_template, list_email_templates, preview_email_template, replace_email_template,
+};
+use crate::state::AppState;
+
+/// Email templates of the current workspace, mounted at `/api/v1/templates` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/", get(list_email_templates))
+    .route("/", post(create_email_template))
+    .route("/:template_id", get(get_email_template))
+    .route("/:template_id", put(replace_email_template))
+    .route("/:template_id", delete(delete_email_template))
+    .route("/:template_id/preview", post(preview_email_template))
+}
@@ -0,0 +1,13 @@
+//! Email templates, compiled with the `email_templates` feature.
+//!
+//! Admins of a workspace customize the subject and body of the emails it sends (invitations,
+//! invoices and payment reminders) under `/api/v1/templates`. Templates use Handlebars syntax and
+//! are checked against the variables of their kind when saved; `POST /api/v1/templates/{id}/preview`
+//! renders one with sample values. A workspace has at most one template per kind, which the
+//! features sending that email look up with `EmailTemplateRepository::find_by_kind`.
+
+pub mod email_template_handlers;
+pub mod email_template_models;
+pub mod email_template_renderer;
+pub mod email_template_repository;
+pub mod email_template_routes;
@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use axum::{
+  extract::{Path, State, rejection::JsonRejection},
+  http::header,
+  response::{IntoResponse, Json, Response},
+};
+use chrono::Utc;
+use validator::Validate;
+
+use super::{
+  export_models::{ExportEntity, ExportLayout, ExportProfile, ExportRequest, SaveExportProfileRequest},
+  export_service,
+};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{PathUuid, RequireRole, RequiredWorkspace, workspace::role::Member},
+  modules::auth::current_user::CurrentUser,
+  responses::{ApiResponse, Created},
+  state::AppState,
+};
+
+fn parse_entity(entity: &str) -> AppResult<ExportEntity> {
+  ExportEntity::parse(entity).ok_or_else(|| {
+    let entities: Vec<&str> = ExportEntity::ALL.iter().map(|entity| entity.as_str()).collect();
+    AppError::validation(
+      "entity",
+      &format!("Unknown export entity '{}', expected one of {}", entity, entities.join(", ")),
+    )
+  })
+}
+
+/// Checks a profile the same way as an export laid out by it.
+fn check_profile(request: &SaveExportProfileRequest) -> AppResult<()> {
+  let layout = ExportLayout {
+    columns: request.columns.clone(),
+    headers: request.headers.clone(),
+    date_format: request.date_format.clone(),
+    filters: request.filters.clone(),
+  };
+  export_service::check_layout(request.entity, &layout)
+}
+
+/// Downloads the contacts or products matching the filters of the export as a CSV file, with the
+/// columns, headers and date format of the request or of the profile it names. Fields hidden from
+/// the caller by a field policy are left empty.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policy applied.
+/// * `Path(entity)`: `contacts` or `products`.
+/// * `payload`: The layout of the export.
+///
+/// # Returns
+///
+/// A `text/csv` attachment named after the entity and the day.
+pub async fn export_records(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  member: RequireRole<Member>,
+  Path(entity): Path<String>,
+  payload: Result<Json<ExportRequest>, JsonRejection>,
+) -> AppResult<Response> {
+  let entity = parse_entity(&entity)?;
+  let Json(request) = payload?;
+  request.validate()?;
+
+  let layout = export_service::resolve_layout(&state, workspace_id, current_user.user_id, entity, request).await?;
+  let csv = export_service::export_csv(&state, workspace_id, current_user.user_id, member.role, entity, &layout).await?;
+
+  let disposition = format!("attachment; filename=\"{}-{}.csv\"", entity.as_str(), Utc::now().format("%Y%m%d"));
+  Ok(
+    (
+      [
+        (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+        (header::CONTENT_DISPOSITION, disposition),
+      ],
+      csv,
+    )
+      .into_response(),
+  )
+}
+
+/// The export profiles of the caller in the workspace.
+pub async fn list_export_profiles(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+) -> AppResult<Json<ApiResponse<Vec<ExportProfile>>>> {
+  let profiles = state.export_profile_repository.list(workspace_id, current_user.user_id).await?;
+  Ok(Json(ApiResponse::success(profiles, "Export profiles retrieved successfully")))
+}
+
+pub async fn get_export_profile(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  PathUuid(id): PathUuid,
+) -> AppResult<Json<ApiResponse<ExportProfile>>> {
+  let profile = state
+    .export_profile_repository
+    .get(id, workspace_id, current_user.user_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Export profile", id))?;
+  Ok(Json(ApiResponse::success(profile, "Export profile retrieved successfully")))
+}
+
+/// Saves a layout under a name for later exports. Names are unique per member and workspace.
+pub async fn create_export_profile(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  payload: Result<Json<SaveExportProfileRequest>, JsonRejection>,
+) -> AppResult<Created<ApiResponse<ExportProfile>>> {
+  let Json(request) = payload?;
+  request.validate()?;
+  check_profile(&request)?;
+
+  let profile = state
+    .export_profile_repository
+    .create(workspace_id, current_user.user_id, &request)
+    .await?;
+  let location = profile.location();
+  Ok(ApiResponse::created(profile, "Export profile created successfully", location))
+}
+
+/// Replaces every field of a profile.
+pub async fn update_export_profile(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  PathUuid(id): PathUuid,
+  payload: Result<Json<SaveExportProfileRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<ExportProfile>>> {
+  let Json(request) = payload?;
+  request.validate()?;
+  check_profile(&request)?;
+
+  let profile = state
+    .export_profile_repository
+    .update(id, workspace_id, current_user.user_id, &request)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Export profile", id))?;
+  Ok(Json(ApiResponse::success(profile, "Export profile updated successfully")))
+}
+
+pub async fn delete_export_profile(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  PathUuid(id): PathUuid,
+) -> AppResult<Json<ApiResponse<()>>> {
+  if !state.export_profile_repository.delete(id, workspace_id, current_user.user_id).await? {
+    return Err(AppError::not_found_with_id("Export profile", id));
+  }
+  Ok(Json(ApiResponse::success((), "Export profile deleted successfully")))
+}
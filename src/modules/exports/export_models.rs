@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+
+use chrono::{
+  DateTime, Utc,
+  format::{Item, StrftimeItems},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// Most columns in one export.
+pub const MAX_COLUMNS: u64 = 50;
+
+/// Records that can be exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "export_entity", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ExportEntity {
+  Contacts,
+  Products,
+}
+
+impl ExportEntity {
+  pub const ALL: &[ExportEntity] = &[ExportEntity::Contacts, ExportEntity::Products];
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ExportEntity::Contacts => "contacts",
+      ExportEntity::Products => "products",
+    }
+  }
+
+  pub fn parse(value: &str) -> Option<Self> {
+    Self::ALL.iter().copied().find(|entity| entity.as_str() == value)
+  }
+
+  /// The columns that can be exported, in their default order: the fields of the API responses.
+  pub fn columns(self) -> &'static [&'static str] {
+    match self {
+      ExportEntity::Contacts => CONTACT_COLUMNS,
+      ExportEntity::Products => PRODUCT_COLUMNS,
+    }
+  }
+}
+
+const CONTACT_COLUMNS: &[&str] = &[
+  "id",
+  "code",
+  "name",
+  "email",
+  "position",
+  "contact_type",
+  "address",
+  "tax_id",
+  "bank_account",
+  "is_active",
+  "created_by",
+  "updated_by",
+  "created_at",
+  "updated_at",
+];
+
+const PRODUCT_COLUMNS: &[&str] = &[
+  "id",
+  "code",
+  "name",
+  "status",
+  "category_id",
+  "supplier_id",
+  "base_unit",
+  "unit_on_report_preview",
+  "selling_price",
+  "unit_cost",
+  "track_inventory",
+  "description",
+  "sku",
+  "barcode",
+  "minimum_stock",
+  "maximum_stock",
+  "reorder_level",
+  "current_stock",
+  "tax_type",
+  "tax_rate",
+  "tax_amount",
+  "is_active",
+  "created_by",
+  "updated_by",
+  "created_at",
+  "updated_at",
+];
+
+/// Columns holding a timestamp, written with the `date_format` of the export.
+pub const DATE_COLUMNS: &[&str] = &["created_at", "updated_at"];
+
+fn validate_date_format(date_format: &str) -> Result<(), ValidationError> {
+  if date_format.is_empty() || StrftimeItems::new(date_format).any(|item| matches!(item, Item::Error)) {
+    return Err(ValidationError::new("date_format").with_message("Date format must be a valid strftime format, e.g. %Y-%m-%d".into()));
+  }
+  Ok(())
+}
+
+/// Requests a CSV export, laid out by a saved profile, by the fields given here, or both, the
+/// fields given here replacing those of the profile.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ExportRequest {
+  /// A profile of the caller to start from.
+  pub profile_id: Option<Uuid>,
+  /// The columns in order, every column of the entity by default.
+  #[validate(length(min = 1, max = MAX_COLUMNS, message = "Between 1 and 50 columns can be exported"))]
+  pub columns: Option<Vec<String>>,
+  /// Column name to header, for the columns not headed by their name.
+  pub headers: Option<BTreeMap<String, String>>,
+  /// strftime format of the dates, RFC 3339 by default.
+  #[validate(custom(function = "validate_date_format"))]
+  pub date_format: Option<String>,
+  /// The query parameters of the list endpoint of the entity, e.g. `{"status": "active"}`.
+  pub filters: Option<Map<String, Value>>,
+}
+
+/// Creates or replaces an export profile.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct SaveExportProfileRequest {
+  #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+  pub name: String,
+  pub entity: ExportEntity,
+  #[validate(length(min = 1, max = MAX_COLUMNS, message = "Between 1 and 50 columns can be exported"))]
+  pub columns: Vec<String>,
+  #[serde(default)]
+  pub headers: BTreeMap<String, String>,
+  #[validate(custom(function = "validate_date_format"))]
+  pub date_format: Option<String>,
+  #[serde(default)]
+  pub filters: Map<String, Value>,
+}
+
+/// An export layout a member saved for themselves.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ExportProfile {
+  pub id: Uuid,
+  pub workspace_id: Uuid,
+  pub user_id: Uuid,
+  pub name: String,
+  pub entity: ExportEntity,
+  pub columns: Vec<String>,
+  pub headers: Value,
+  pub date_format: Option<String>,
+  pub filters: Value,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+impl ExportProfile {
+  pub fn location(&self) -> String {
+    format!("/api/v1/exports/profiles/{}", self.id)
+  }
+}
+
+/// The layout of an export once the request and its profile are combined.
+#[derive(Debug, Clone)]
+pub struct ExportLayout {
+  pub columns: Vec<String>,
+  pub headers: BTreeMap<String, String>,
+  pub date_format: Option<String>,
+  pub filters: Map<String, Value>,
+}
+
+impl ExportLayout {
+  /// The layout of `profile`, whose headers and filters were checked when it was saved.
+  pub fn from_profile(profile: ExportProfile) -> Self {
+    Self {
+      columns: profile.columns,
+      headers: serde_json::from_value(profile.headers).unwrap_or_default(),
+      date_format: profile.date_format,
+      filters: serde_json::from_value(profile.filters).unwrap_or_default(),
+    }
+  }
+
+  /// Every column of `entity` under its own name, with dates in RFC 3339.
+  pub fn all_columns(entity: ExportEntity) -> Self {
+    Self {
+      columns: entity.columns().iter().map(|column| column.to_string()).collect(),
+      headers: BTreeMap::new(),
+      date_format: None,
+      filters: Map::new(),
+    }
+  }
+
+  /// The header row.
+  pub fn header(&self) -> Vec<&str> {
+    self
+      .columns
+      .iter()
+      .map(|column| self.headers.get(column).unwrap_or(column).as_str())
+      .collect()
+  }
+
+  /// The cells of `record`, one of the entity's API responses, in the order of the columns.
+  /// Fields hidden from the caller by a field policy are left empty.
+  pub fn row(&self, record: &Value) -> Vec<String> {
+    self
+      .columns
+      .iter()
+      .map(|column| match record.get(column) {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => match &self.date_format {
+          Some(date_format) if DATE_COLUMNS.contains(&column.as_str()) => DateTime::parse_from_rfc3339(text)
+            .map(|date| date.with_timezone(&Utc).format(date_format).to_string())
+            .unwrap_or_else(|_| text.clone()),
+          _ => text.clone(),
+        },
+        Some(value) => value.to_string(),
+      })
+      .collect()
+  }
+}
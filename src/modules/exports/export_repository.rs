@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::export_models::{ExportEntity, ExportProfile, SaveExportProfileRequest};
+use crate::{AppResult, utils::DbExecutor};
+
+#[async_trait]
+pub trait ExportProfileRepository: Send + Sync {
+  /// The profiles `user_id` saved in `workspace_id`, by name.
+  async fn list(&self, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<ExportProfile>>;
+  /// A profile of `user_id`; those of other members are not found.
+  async fn get(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<ExportProfile>>;
+  async fn create(&self, workspace_id: Uuid, user_id: Uuid, profile: &SaveExportProfileRequest) -> AppResult<ExportProfile>;
+  /// Replaces a profile of `user_id`, `None` when it is not found.
+  async fn update(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid, profile: &SaveExportProfileRequest) -> AppResult<Option<ExportProfile>>;
+  /// Removes a profile of `user_id`, `false` when it is not found.
+  async fn delete(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<bool>;
+}
+
+pub struct PostgresExportProfileRepository {
+  db: DbExecutor,
+}
+
+impl PostgresExportProfileRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+fn headers_and_filters(profile: &SaveExportProfileRequest) -> (Value, Value) {
+  (
+    serde_json::to_value(&profile.headers).unwrap_or_default(),
+    Value::Object(profile.filters.clone()),
+  )
+}
+
+#[async_trait]
+impl ExportProfileRepository for PostgresExportProfileRepository {
+  async fn list(&self, workspace_id: Uuid, user_id: Uuid) -> AppResult<Vec<ExportProfile>> {
+    let mut conn = self.db.acquire().await?;
+    let profiles = sqlx::query_as!(
+      ExportProfile,
+      r#"
+        SELECT id, workspace_id, user_id, name, entity as "entity: ExportEntity", columns, headers, date_format, filters,
+          created_at, updated_at
+        FROM export_profiles
+        WHERE workspace_id = $1 AND user_id = $2
+        ORDER BY name
+        "#,
+      workspace_id,
+      user_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(profiles)
+  }
+
+  async fn get(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<ExportProfile>> {
+    let mut conn = self.db.acquire().await?;
+    let profile = sqlx::query_as!(
+      ExportProfile,
+      r#"
+        SELECT id, workspace_id, user_id, name, entity as "entity: ExportEntity", columns, headers, date_format, filters,
+          created_at, updated_at
+        FROM export_profiles
+        WHERE id = $1 AND workspace_id = $2 AND user_id = $3
+        "#,
+      id,
+      workspace_id,
+      user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(profile)
+  }
+
+  async fn create(&self, workspace_id: Uuid, user_id: Uuid, profile: &SaveExportProfileRequest) -> AppResult<ExportProfile> {
+    let (headers, filters) = headers_and_filters(profile);
+    let mut conn = self.db.acquire().await?;
+    let profile = sqlx::query_as!(
+      ExportProfile,
+      r#"
+        INSERT INTO export_profiles (workspace_id, user_id, name, entity, columns, headers, date_format, filters)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, workspace_id, user_id, name, entity as "entity: ExportEntity", columns, headers, date_format, filters,
+          created_at, updated_at
+        "#,
+      workspace_id,
+      user_id,
+      profile.name,
+      profile.entity as ExportEntity,
+      &profile.columns,
+      headers,
+      profile.date_format,
+      filters
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(profile)
+  }
+
+  async fn update(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid, profile: &SaveExportProfileRequest) -> AppResult<Option<ExportProfile>> {
+    let (headers, filters) = headers_and_filters(profile);
+    let mut conn = self.db.acquire().await?;
+    let profile = sqlx::query_as!(
+      ExportProfile,
+      r#"
+        UPDATE export_profiles
+        SET name = $4, entity = $5, columns = $6, headers = $7, date_format = $8, filters = $9
+        WHERE id = $1 AND workspace_id = $2 AND user_id = $3
+        RETURNING id, workspace_id, user_id, name, entity as "entity: ExportEntity", columns, headers, date_format, filters,
+          created_at, updated_at
+        "#,
+      id,
+      workspace_id,
+      user_id,
+      profile.name,
+      profile.entity as ExportEntity,
+      &profile.columns,
+      headers,
+      profile.date_format,
+      filters
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(profile)
+  }
+
+  async fn delete(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<bool> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!(
+      "DELETE FROM export_profiles WHERE id = $1 AND workspace_id = $2 AND user_id = $3",
+      id,
+      workspace_id,
+      user_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+}
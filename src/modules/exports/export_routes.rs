@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  routing::{delete, get, post, put},
+};
+
+use super::export_handlers::{
+  create_export_profile, delete_export_profile, export_records, get_export_profile, list_export_profiles, update_export_profile,
+};
+use crate::state::AppState;
+
+/// CSV exports and the export profiles of the caller, mounted at `/api/v1/exports` behind the JWT
+/// middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/profiles", get(list_export_profiles))
+    .route("/profiles", post(create_export_profile))
+    .route("/profiles/:profile_id", get(get_export_profile))
+    .route("/profiles/:profile_id", put(update_export_profile))
+    .route("/profiles/:profile_id", delete(delete_export_profile))
+    .route("/:entity", post(export_records))
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeSet;
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::export_models::{ExportEntity, ExportLayout, ExportRequest};
+use crate::{
+  AppResult,
+  errors::AppError,
+  internal_error,
+  modules::{
+    datastores::{
+      contacts::contact_models::{ContactFilters, ContactResponse, GetContactsQuery},
+      products::product_models::{GetProductsQuery, ProductFilters, ProductResponse},
+      workspaces::WorkspaceRole,
+    },
+    workspace_settings::field_policy_service,
+  },
+  state::AppState,
+  utils::ndjson::RowSender,
+};
+
+/// Rows buffered between the database and the CSV writer.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Checks that `layout` only names columns of `entity`, each once, and only heads those.
+pub fn check_layout(entity: ExportEntity, layout: &ExportLayout) -> AppResult<()> {
+  let mut seen = BTreeSet::new();
+  for column in &layout.columns {
+    if !entity.columns().contains(&column.as_str()) {
+      return Err(AppError::validation(
+        "columns",
+        &format!(
+          "Unknown column '{}' for {}, expected some of {}",
+          column,
+          entity.as_str(),
+          entity.columns().join(", ")
+        ),
+      ));
+    }
+    if !seen.insert(column) {
+      return Err(AppError::validation("columns", &format!("Column '{}' is listed twice", column)));
+    }
+  }
+  if let Some(column) = layout.headers.keys().find(|column| !seen.contains(column)) {
+    return Err(AppError::validation(
+      "headers",
+      &format!("Header given for column '{}', which is not exported", column),
+    ));
+  }
+  Ok(())
+}
+
+/// Combines `request` with the profile it names, if any, and checks the result.
+pub async fn resolve_layout(
+  state: &AppState,
+  workspace_id: Uuid,
+  user_id: Uuid,
+  entity: ExportEntity,
+  request: ExportRequest,
+) -> AppResult<ExportLayout> {
+  let mut layout = match request.profile_id {
+    Some(profile_id) => {
+      let profile = state
+        .export_profile_repository
+        .get(profile_id, workspace_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::not_found_with_id("Export profile", profile_id))?;
+      if profile.entity != entity {
+        return Err(AppError::validation(
+          "profile_id",
+          &format!("Export profile {} exports {}", profile_id, profile.entity.as_str()),
+        ));
+      }
+      ExportLayout::from_profile(profile)
+    }
+    None => ExportLayout::all_columns(entity),
+  };
+
+  if let Some(columns) = request.columns {
+    layout.columns = columns;
+  }
+  if let Some(headers) = request.headers {
+    layout.headers = headers;
+  }
+  if request.date_format.is_some() {
+    layout.date_format = request.date_format;
+  }
+  if let Some(filters) = request.filters {
+    layout.filters = filters;
+  }
+  check_layout(entity, &layout)?;
+  Ok(layout)
+}
+
+/// Reads the filters of an export as the query parameters of the entity's list endpoint.
+fn parse_filters<Q: DeserializeOwned + Validate>(filters: &Map<String, Value>) -> AppResult<Q> {
+  let query: Q = serde_json::from_value(Value::Object(filters.clone())).map_err(|e| AppError::validation("filters", &e.to_string()))?;
+  query.validate()?;
+  Ok(query)
+}
+
+/// Receives every row `produce` sends, failing with the first error.
+async fn collect<T, Fut>(produce: impl FnOnce(RowSender<T>) -> Fut) -> AppResult<Vec<T>>
+where
+  Fut: Future<Output = AppResult<()>>,
+{
+  let (tx, mut rx) = mpsc::channel(CHANNEL_CAPACITY);
+  let receive = async {
+    let mut rows = Vec::new();
+    while let Some(row) = rx.recv().await {
+      rows.push(row?);
+    }
+    Ok::<_, AppError>(rows)
+  };
+  let (produced, rows) = tokio::join!(produce(tx), receive);
+  produced?;
+  rows
+}
+
+fn to_values<T: Serialize>(records: Vec<T>) -> AppResult<Vec<Value>> {
+  records
+    .into_iter()
+    .map(|record| serde_json::to_value(record).map_err(|e| internal_error!("Failed to serialize an exported record: {}", e)))
+    .collect()
+}
+
+/// The records of `entity` matching the filters of `layout`, as returned by the API to the caller.
+async fn records(
+  state: &AppState,
+  workspace_id: Uuid,
+  user_id: Uuid,
+  role: WorkspaceRole,
+  entity: ExportEntity,
+  layout: &ExportLayout,
+) -> AppResult<Vec<Value>> {
+  let policy = field_policy_service::resolve(state, workspace_id, entity.as_str(), role).await?;
+  match entity {
+    ExportEntity::Contacts => {
+      let filters = ContactFilters::from(parse_filters::<GetContactsQuery>(&layout.filters)?);
+      let repository = &state.contact_repository;
+      let contacts = collect(|rows| repository.stream_by_filters(workspace_id, user_id, filters, rows)).await?;
+      to_values(
+        contacts
+          .into_iter()
+          .map(|contact| policy.redact(ContactResponse::from(contact)))
+          .collect(),
+      )
+    }
+    ExportEntity::Products => {
+      let filters = ProductFilters::from(parse_filters::<GetProductsQuery>(&layout.filters)?);
+      let repository = &state.product_repository;
+      let products = collect(|rows| repository.stream_by_filters(workspace_id, user_id, filters, rows)).await?;
+      to_values(
+        products
+          .into_iter()
+          .map(|product| policy.redact(ProductResponse::from(product)))
+          .collect(),
+      )
+    }
+  }
+}
+
+/// Writes the CSV file of the records of `entity` laid out by `layout`.
+pub async fn export_csv(
+  state: &AppState,
+  workspace_id: Uuid,
+  user_id: Uuid,
+  role: WorkspaceRole,
+  entity: ExportEntity,
+  layout: &ExportLayout,
+) -> AppResult<Vec<u8>> {
+  let records = records(state, workspace_id, user_id, role, entity, layout).await?;
+  write_csv(layout.header(), records.iter().map(|record| layout.row(record)))
+}
+
+/// Writes a CSV file of `rows` under `header`.
+pub fn write_csv<H: AsRef<[u8]>, F: AsRef<[u8]>>(header: Vec<H>, rows: impl IntoIterator<Item = Vec<F>>) -> AppResult<Vec<u8>> {
+  let mut writer = csv::Writer::from_writer(Vec::new());
+  let write_error = |e: csv::Error| internal_error!("Failed to write the export: {}", e);
+  writer.write_record(header).map_err(write_error)?;
+  for row in rows {
+    writer.write_record(row).map_err(write_error)?;
+  }
+  writer.into_inner().map_err(|e| internal_error!("Failed to write the export: {}", e))
+}
@@ -0,0 +1,14 @@
+//! CSV exports of contacts and products laid out by the client.
+//!
+//! `POST /api/v1/exports/:entity` downloads the records matching `filters` (the query parameters
+//! of the entity's list endpoint) with the chosen `columns` in order, each headed by its name or by
+//! the header given in `headers`, and the dates written with `date_format`. Members can save a
+//! layout as an export profile of their own and export with `profile_id`, overriding any of its
+//! fields in the request. Field policies apply as on the list endpoints: hidden fields export as
+//! empty cells.
+
+pub mod export_handlers;
+pub mod export_models;
+pub mod export_repository;
+pub mod export_routes;
+pub mod export_service;
@@ -0,0 +1,64 @@
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex, MutexGuard},
+  time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+/// Number of cached workspaces above which expired entries are purged on the next insert.
+const PURGE_THRESHOLD: usize = 10_000;
+
+/// The flags in effect for a workspace (or globally, for `None`), by key.
+pub type EffectiveFlags = Arc<HashMap<String, bool>>;
+
+/// Flags recently resolved for a workspace.
+///
+/// Gated routes check a flag on every request; the cache lets them skip the lookup. Toggles made
+/// through this instance clear it right away; other instances pick them up once the entries
+/// expire.
+pub struct FeatureFlagCache {
+  ttl: Duration,
+  flags: Mutex<HashMap<Option<Uuid>, (EffectiveFlags, Instant)>>,
+}
+
+impl FeatureFlagCache {
+  /// A cache keeping resolved flags for `ttl_secs`; 0 disables it.
+  pub fn new(ttl_secs: u64) -> Self {
+    Self {
+      ttl: Duration::from_secs(ttl_secs),
+      flags: Mutex::new(HashMap::new()),
+    }
+  }
+
+  pub fn get(&self, workspace_id: Option<Uuid>) -> Option<EffectiveFlags> {
+    let now = Instant::now();
+    self
+      .lock()
+      .get(&workspace_id)
+      .filter(|(_, expires_at)| *expires_at > now)
+      .map(|(flags, _)| flags.clone())
+  }
+
+  pub fn insert(&self, workspace_id: Option<Uuid>, flags: EffectiveFlags) {
+    if self.ttl.is_zero() {
+      return;
+    }
+    let now = Instant::now();
+    let mut cached = self.lock();
+    if cached.len() > PURGE_THRESHOLD {
+      cached.retain(|_, (_, expires_at)| *expires_at > now);
+    }
+    cached.insert(workspace_id, (flags, now + self.ttl));
+  }
+
+  /// Forgets every resolved flag, after a flag or an override changed.
+  pub fn clear(&self) {
+    self.lock().clear();
+  }
+
+  // The map holds no invariants a panicking writer could break, so a poisoned lock is reused
+  fn lock(&self) -> MutexGuard<'_, HashMap<Option<Uuid>, (EffectiveFlags, Instant)>> {
+    self.flags.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
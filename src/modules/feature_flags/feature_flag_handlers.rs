@@ -0,0 +1,183 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use axum::{
+  async_trait,
+  extract::{FromRequestParts, Path, State, rejection::JsonRejection},
+  http::request::Parts,
+  response::Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{
+  feature_flag_models::{
+    EffectiveFlagsResponse, FeatureFlag, FeatureFlagResponse, SetWorkspaceFlagRequest, UpsertFeatureFlagRequest, WorkspaceFlagOverride,
+  },
+  feature_flag_service,
+};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{OptionalWorkspace, path_uuid::parse_uuid, superadmin::is_superadmin},
+  modules::auth::current_user::CurrentUser,
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// Proof that the caller is a superadmin or one of the feature flag admins (`FEATURE_FLAG_ADMIN_IDS`).
+pub struct FlagAdmin;
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for FlagAdmin {
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+    let current_user = CurrentUser::from_request_parts(parts, state).await?;
+    if state.config.feature_flags.admin_user_ids.contains(&current_user.user_id) || is_superadmin(state, current_user.user_id).await? {
+      Ok(FlagAdmin)
+    } else {
+      Err(AppError::Authorization("Only feature flag admins can manage feature flags".to_string()))
+    }
+  }
+}
+
+/// The `:key` of a flag route and, for override routes, its `:workspace_id`.
+pub struct FlagPath {
+  pub key: String,
+  pub workspace_id: Option<Uuid>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for FlagPath
+where
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    let Path(params) = Path::<Vec<(String, String)>>::from_request_parts(parts, state)
+      .await
+      .map_err(|rejection| AppError::BadRequest(rejection.body_text()))?;
+
+    let mut path = FlagPath {
+      key: String::new(),
+      workspace_id: None,
+    };
+    for (name, value) in params {
+      match name.as_str() {
+        "key" => path.key = value,
+        "workspace_id" => path.workspace_id = Some(parse_uuid(&name, &value)?),
+        _ => {}
+      }
+    }
+
+    let valid_key = (1..=64).contains(&path.key.len()) && path.key.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+    if !valid_key {
+      return Err(AppError::validation(
+        "key",
+        "Flag keys are 1 to 64 lowercase letters, digits or underscores",
+      ));
+    }
+    Ok(path)
+  }
+}
+
+/// The flags in effect for the caller's workspace, so clients can show or hide experimental features.
+pub async fn get_effective_flags(
+  State(state): State<Arc<AppState>>,
+  _current_user: CurrentUser,
+  OptionalWorkspace(workspace_id): OptionalWorkspace,
+) -> AppResult<Json<ApiResponse<EffectiveFlagsResponse>>> {
+  let flags = feature_flag_service::effective_flags(&state, workspace_id).await?;
+
+  let response = ApiResponse::success(
+    EffectiveFlagsResponse {
+      workspace_id,
+      flags: flags.iter().map(|(key, enabled)| (key.clone(), *enabled)).collect::<BTreeMap<_, _>>(),
+    },
+    "Feature flags retrieved successfully",
+  );
+  Ok(Json(response))
+}
+
+pub async fn list_flags(State(state): State<Arc<AppState>>, _admin: FlagAdmin) -> AppResult<Json<ApiResponse<Vec<FeatureFlagResponse>>>> {
+  let repository = &state.feature_flag_repository;
+  let mut overrides = repository.list_overrides().await?;
+  let flags = repository
+    .list_flags()
+    .await?
+    .into_iter()
+    .map(|flag| FeatureFlagResponse {
+      overrides: overrides.extract_if(.., |o| o.flag_key == flag.key).collect(),
+      flag,
+    })
+    .collect();
+
+  let response = ApiResponse::success(flags, "Feature flags retrieved successfully");
+  Ok(Json(response))
+}
+
+/// Creates a flag or sets its global state.
+pub async fn upsert_flag(
+  State(state): State<Arc<AppState>>,
+  _admin: FlagAdmin,
+  path: FlagPath,
+  payload: Result<Json<UpsertFeatureFlagRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<FeatureFlag>>> {
+  let Json(request) = payload?;
+  request.validate()?;
+
+  let flag = state.feature_flag_repository.upsert_flag(&path.key, &request).await?;
+  state.feature_flag_cache.clear();
+
+  let response = ApiResponse::success(flag, "Feature flag saved successfully");
+  Ok(Json(response))
+}
+
+pub async fn delete_flag(State(state): State<Arc<AppState>>, _admin: FlagAdmin, path: FlagPath) -> AppResult<Json<ApiResponse<()>>> {
+  if !state.feature_flag_repository.delete_flag(&path.key).await? {
+    return Err(AppError::not_found("Feature flag"));
+  }
+  state.feature_flag_cache.clear();
+
+  let response = ApiResponse::success((), "Feature flag deleted successfully");
+  Ok(Json(response))
+}
+
+/// Sets the state of a flag for one workspace, regardless of its global state.
+pub async fn set_workspace_override(
+  State(state): State<Arc<AppState>>,
+  _admin: FlagAdmin,
+  path: FlagPath,
+  payload: Result<Json<SetWorkspaceFlagRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<WorkspaceFlagOverride>>> {
+  let Json(request) = payload?;
+  let workspace_id = path
+    .workspace_id
+    .ok_or_else(|| AppError::Internal("Route has no workspace_id".to_string()))?;
+
+  let flag_override = state
+    .feature_flag_repository
+    .set_override(&path.key, workspace_id, request.enabled)
+    .await?
+    .ok_or_else(|| AppError::not_found("Feature flag or workspace"))?;
+  state.feature_flag_cache.clear();
+
+  let response = ApiResponse::success(flag_override, "Feature flag override saved successfully");
+  Ok(Json(response))
+}
+
+/// Returns a workspace to the global state of a flag.
+pub async fn remove_workspace_override(State(state): State<Arc<AppState>>, _admin: FlagAdmin, path: FlagPath) -> AppResult<Json<ApiResponse<()>>> {
+  let workspace_id = path
+    .workspace_id
+    .ok_or_else(|| AppError::Internal("Route has no workspace_id".to_string()))?;
+
+  if !state.feature_flag_repository.remove_override(&path.key, workspace_id).await? {
+    return Err(AppError::not_found("Feature flag override"));
+  }
+  state.feature_flag_cache.clear();
+
+  let response = ApiResponse::success((), "Feature flag override removed successfully");
+  Ok(Json(response))
+}
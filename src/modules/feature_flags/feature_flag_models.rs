@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FeatureFlag {
+  pub key: String,
+  pub description: Option<String>,
+  /// Whether the flag is on for workspaces without an override.
+  pub enabled: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct WorkspaceFlagOverride {
+  pub flag_key: String,
+  pub workspace_id: Uuid,
+  pub enabled: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// A flag as listed in the admin API, with its workspace overrides.
+#[derive(Debug, Serialize)]
+pub struct FeatureFlagResponse {
+  #[serde(flatten)]
+  pub flag: FeatureFlag,
+  pub overrides: Vec<WorkspaceFlagOverride>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpsertFeatureFlagRequest {
+  pub enabled: bool,
+  #[validate(length(max = 500, message = "Description must be at most 500 characters"))]
+  pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetWorkspaceFlagRequest {
+  pub enabled: bool,
+}
+
+/// The flags in effect for the caller's workspace, or the global flags without one.
+#[derive(Debug, Serialize)]
+pub struct EffectiveFlagsResponse {
+  pub workspace_id: Option<Uuid>,
+  pub flags: BTreeMap<String, bool>,
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::feature_flag_models::{FeatureFlag, UpsertFeatureFlagRequest, WorkspaceFlagOverride};
+use crate::{errors::AppError, utils::DbExecutor};
+
+#[async_trait]
+pub trait FeatureFlagRepository: Send + Sync {
+  async fn list_flags(&self) -> Result<Vec<FeatureFlag>, AppError>;
+  async fn list_overrides(&self) -> Result<Vec<WorkspaceFlagOverride>, AppError>;
+  /// Creates the flag, or updates its state and description.
+  async fn upsert_flag(&self, key: &str, request: &UpsertFeatureFlagRequest) -> Result<FeatureFlag, AppError>;
+  /// Deletes the flag and its overrides; `false` when there was no such flag.
+  async fn delete_flag(&self, key: &str) -> Result<bool, AppError>;
+  /// Sets the state of the flag for one workspace; `None` when the flag or the workspace does not exist.
+  async fn set_override(&self, key: &str, workspace_id: Uuid, enabled: bool) -> Result<Option<WorkspaceFlagOverride>, AppError>;
+  /// Returns the workspace to the global state of the flag; `false` when it had no override.
+  async fn remove_override(&self, key: &str, workspace_id: Uuid) -> Result<bool, AppError>;
+  /// The state of every flag for `workspace_id`, or the global states for `None`.
+  async fn effective_flags(&self, workspace_id: Option<Uuid>) -> Result<HashMap<String, bool>, AppError>;
+}
+
+/// Flags are read from the primary, so a toggle is never hidden by replication lag on top of the cache TTL.
+pub struct PostgresFeatureFlagRepository {
+  db: DbExecutor,
+}
+
+impl PostgresFeatureFlagRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl FeatureFlagRepository for PostgresFeatureFlagRepository {
+  async fn list_flags(&self) -> Result<Vec<FeatureFlag>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let flags = sqlx::query_as!(
+      FeatureFlag,
+      "SELECT key, description, enabled, created_at, updated_at FROM feature_flags ORDER BY key"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(flags)
+  }
+
+  async fn list_overrides(&self) -> Result<Vec<WorkspaceFlagOverride>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let overrides = sqlx::query_as!(
+      WorkspaceFlagOverride,
+      r#"
+        SELECT flag_key, workspace_id, enabled, created_at, updated_at
+        FROM workspace_feature_flags
+        ORDER BY flag_key, created_at
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(overrides)
+  }
+
+  async fn upsert_flag(&self, key: &str, request: &UpsertFeatureFlagRequest) -> Result<FeatureFlag, AppError> {
+    let mut conn = self.db.acquire().await?;
+    // A toggle without a description keeps the existing one
+    let flag = sqlx::query_as!(
+      FeatureFlag,
+      r#"
+        INSERT INTO feature_flags (key, description, enabled)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (key) DO UPDATE
+        SET enabled = EXCLUDED.enabled,
+            description = COALESCE(EXCLUDED.description, feature_flags.description)
+        RETURNING key, description, enabled, created_at, updated_at
+        "#,
+      key,
+      request.description,
+      request.enabled
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(flag)
+  }
+
+  async fn delete_flag(&self, key: &str) -> Result<bool, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!("DELETE FROM feature_flags WHERE key = $1", key).execute(&mut *conn).await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  async fn set_override(&self, key: &str, workspace_id: Uuid, enabled: bool) -> Result<Option<WorkspaceFlagOverride>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let flag_override = sqlx::query_as!(
+      WorkspaceFlagOverride,
+      r#"
+        INSERT INTO workspace_feature_flags (flag_key, workspace_id, enabled)
+        SELECT f.key, w.id, $3
+        FROM feature_flags f, workspaces w
+        WHERE f.key = $1 AND w.id = $2
+        ON CONFLICT (flag_key, workspace_id) DO UPDATE SET enabled = EXCLUDED.enabled
+        RETURNING flag_key, workspace_id, enabled, created_at, updated_at
+        "#,
+      key,
+      workspace_id,
+      enabled
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(flag_override)
+  }
+
+  async fn remove_override(&self, key: &str, workspace_id: Uuid) -> Result<bool, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!(
+      "DELETE FROM workspace_feature_flags WHERE flag_key = $1 AND workspace_id = $2",
+      key,
+      workspace_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  async fn effective_flags(&self, workspace_id: Option<Uuid>) -> Result<HashMap<String, bool>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let rows = sqlx::query!(
+      r#"
+        SELECT f.key, COALESCE(o.enabled, f.enabled) AS "enabled!"
+        FROM feature_flags f
+        LEFT JOIN workspace_feature_flags o ON o.flag_key = f.key AND o.workspace_id = $1
+        "#,
+      workspace_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| (row.key, row.enabled)).collect())
+  }
+}
@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  routing::{delete, get, put},
+};
+
+use super::feature_flag_handlers::{delete_flag, get_effective_flags, list_flags, remove_workspace_override, set_workspace_override, upsert_flag};
+use crate::state::AppState;
+
+/// The flags in effect for the caller, mounted at `/api/v1/feature-flags` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new().route("/", get(get_effective_flags))
+}
+
+/// Flag management for the feature flag admins, mounted at `/api/v1/admin/feature-flags` behind the JWT middleware.
+pub fn admin_router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/", get(list_flags))
+    .route("/:key", put(upsert_flag))
+    .route("/:key", delete(delete_flag))
+    .route("/:key/workspaces/:workspace_id", put(set_workspace_override))
+    .route("/:key/workspaces/:workspace_id", delete(remove_workspace_override))
+}
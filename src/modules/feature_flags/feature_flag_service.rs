@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use super::feature_flag_cache::EffectiveFlags;
+use crate::{AppResult, state::AppState};
+
+/// The state of every flag for `workspace_id` (the global states for `None`), from the cache
+/// when it holds them.
+pub async fn effective_flags(state: &AppState, workspace_id: Option<Uuid>) -> AppResult<EffectiveFlags> {
+  if let Some(flags) = state.feature_flag_cache.get(workspace_id) {
+    return Ok(flags);
+  }
+
+  let flags = Arc::new(state.feature_flag_repository.effective_flags(workspace_id).await?);
+  state.feature_flag_cache.insert(workspace_id, flags.clone());
+  Ok(flags)
+}
+
+/// Whether the flag `key` is on for `workspace_id`. Unknown flags are off.
+pub async fn is_enabled(state: &AppState, key: &str, workspace_id: Option<Uuid>) -> AppResult<bool> {
+  Ok(effective_flags(state, workspace_id).await?.get(key).copied().unwrap_or(false))
+}
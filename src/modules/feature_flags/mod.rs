@@ -0,0 +1,14 @@
+//! Feature flags gating experimental features, globally and per workspace.
+//!
+//! A flag is on for a workspace when the workspace has an override turning it on, or has no
+//! override and the flag is on globally; unknown flags are off. Handlers gate routes with the
+//! `helper::RequireFeature` extractor. The flags of a workspace are resolved in one query and
+//! kept in the `FeatureFlagCache` of `AppState` for `FEATURE_FLAG_CACHE_TTL_SECS`; flags are
+//! managed under `/api/v1/admin/feature-flags` by the users listed in `FEATURE_FLAG_ADMIN_IDS`.
+
+pub mod feature_flag_cache;
+pub mod feature_flag_handlers;
+pub mod feature_flag_models;
+pub mod feature_flag_repository;
+pub mod feature_flag_routes;
+pub mod feature_flag_service;
@@ -0,0 +1,58 @@
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex, MutexGuard},
+  time::{Duration, Instant},
+};
+
+use super::geo_models::{AddressLookup, AddressSuggestion};
+
+/// Number of cached lookups above which expired entries are purged on the next insert.
+const PURGE_THRESHOLD: usize = 10_000;
+
+/// The suggestions of a lookup, shared with the requests served from the cache.
+pub type Suggestions = Arc<Vec<AddressSuggestion>>;
+
+/// Suggestions recently fetched from the provider, shared by all workspaces.
+///
+/// Each keystroke of an address field is a lookup, and users of a region type the same streets:
+/// answers from the cache spare the provider, and the quota of the workspace.
+pub struct GeoCache {
+  ttl: Duration,
+  lookups: Mutex<HashMap<AddressLookup, (Suggestions, Instant)>>,
+}
+
+impl GeoCache {
+  /// A cache keeping suggestions for `ttl_secs`; 0 disables it.
+  pub fn new(ttl_secs: u64) -> Self {
+    Self {
+      ttl: Duration::from_secs(ttl_secs),
+      lookups: Mutex::new(HashMap::new()),
+    }
+  }
+
+  pub fn get(&self, lookup: &AddressLookup) -> Option<Suggestions> {
+    let now = Instant::now();
+    self
+      .lock()
+      .get(lookup)
+      .filter(|(_, expires_at)| *expires_at > now)
+      .map(|(suggestions, _)| suggestions.clone())
+  }
+
+  pub fn insert(&self, lookup: AddressLookup, suggestions: Suggestions) {
+    if self.ttl.is_zero() {
+      return;
+    }
+    let now = Instant::now();
+    let mut cached = self.lock();
+    if cached.len() > PURGE_THRESHOLD {
+      cached.retain(|_, (_, expires_at)| *expires_at > now);
+    }
+    cached.insert(lookup, (suggestions, now + self.ttl));
+  }
+
+  // The map holds no invariants a panicking writer could break, so a poisoned lock is reused
+  fn lock(&self) -> MutexGuard<'_, HashMap<AddressLookup, (Suggestions, Instant)>> {
+    self.lookups.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
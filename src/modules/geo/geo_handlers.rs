@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use axum::{extract::State, response::Json};
+
+use super::{
+  geo_models::{AddressLookup, AddressSuggestion, AutocompleteQuery},
+  geo_service,
+};
+use crate::{
+  AppResult,
+  helper::{RequireRole, RequiredWorkspace, ValidatedQuery, workspace::role::Member},
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// Addresses matching what was typed into an address field, best match first.
+pub async fn autocomplete_address(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  ValidatedQuery(query): ValidatedQuery<AutocompleteQuery>,
+) -> AppResult<Json<ApiResponse<Vec<AddressSuggestion>>>> {
+  let suggestions = geo_service::autocomplete(&state, workspace_id, AddressLookup::from(&query)).await?;
+  Ok(Json(ApiResponse::success(
+    suggestions.to_vec(),
+    "Address suggestions retrieved successfully",
+  )))
+}
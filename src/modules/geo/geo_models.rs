@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+/// Suggestions returned when no limit is given.
+pub const DEFAULT_SUGGESTION_LIMIT: u32 = 5;
+
+/// An address suggested for a query, split into the parts of a structured address. Parts the
+/// provider does not know are `None`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressSuggestion {
+  /// The whole address on one line, as the provider writes it.
+  pub label: String,
+  /// Street and house number.
+  pub street: Option<String>,
+  pub city: Option<String>,
+  /// Province or state.
+  pub region: Option<String>,
+  pub postal_code: Option<String>,
+  pub country: Option<String>,
+  /// ISO 3166-1 alpha-2 code of the country, in upper case.
+  pub country_code: Option<String>,
+  pub latitude: f64,
+  pub longitude: f64,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct AutocompleteQuery {
+  /// What was typed so far.
+  #[validate(length(min = 3, max = 200, message = "Query must be between 3 and 200 characters"))]
+  pub q: String,
+  /// Only suggest addresses in this country, an ISO 3166-1 alpha-2 code.
+  #[validate(custom(function = "validate_country"))]
+  pub country: Option<String>,
+  #[validate(range(min = 1, max = 10, message = "Limit must be between 1 and 10"))]
+  pub limit: Option<u32>,
+}
+
+fn validate_country(country: &str) -> Result<(), ValidationError> {
+  match country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()) {
+    true => Ok(()),
+    false => Err(ValidationError::new("country").with_message("Country must be a two-letter ISO 3166-1 code".into())),
+  }
+}
+
+/// A lookup as sent to the provider and cached: the query trimmed, in lower case and with single
+/// spaces, so that the same address typed differently is looked up once.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AddressLookup {
+  pub query: String,
+  /// In lower case.
+  pub country: Option<String>,
+  pub limit: u32,
+}
+
+impl From<&AutocompleteQuery> for AddressLookup {
+  fn from(query: &AutocompleteQuery) -> Self {
+    Self {
+      query: query.q.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase(),
+      country: query.country.as_ref().map(|country| country.to_ascii_lowercase()),
+      limit: query.limit.unwrap_or(DEFAULT_SUGGESTION_LIMIT),
+    }
+  }
+}
@@ -0,0 +1,48 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::{AppResult, utils::DbExecutor};
+
+#[async_trait]
+pub trait GeoLookupRepository: Send + Sync {
+  /// Counts a lookup of the workspace on `day`, unless it already sent `limit` that day; returns
+  /// whether it was counted.
+  async fn consume(&self, workspace_id: Uuid, day: NaiveDate, limit: Option<u32>) -> AppResult<bool>;
+}
+
+pub struct PostgresGeoLookupRepository {
+  db: DbExecutor,
+}
+
+impl PostgresGeoLookupRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl GeoLookupRepository for PostgresGeoLookupRepository {
+  async fn consume(&self, workspace_id: Uuid, day: NaiveDate, limit: Option<u32>) -> AppResult<bool> {
+    let limit = limit.map(|limit| i32::try_from(limit).unwrap_or(i32::MAX));
+    let mut conn = self.db.acquire().await?;
+    // The row is locked by the upsert, so concurrent lookups cannot both take the last one
+    let counted = sqlx::query_scalar!(
+      r#"
+        INSERT INTO geo_lookups (workspace_id, day, lookups)
+        VALUES ($1, $2, 1)
+        ON CONFLICT (workspace_id, day) DO UPDATE
+        SET lookups = geo_lookups.lookups + 1
+        WHERE $3::INTEGER IS NULL OR geo_lookups.lookups < $3
+        RETURNING lookups
+        "#,
+      workspace_id,
+      day,
+      limit
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(counted.is_some())
+  }
+}
@@ -0,0 +1,11 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+
+use super::geo_handlers::autocomplete_address;
+use crate::state::AppState;
+
+/// Address lookups for the current workspace, mounted at `/api/v1/geo` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new().route("/autocomplete", get(autocomplete_address))
+}
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use chrono::{Days, Utc};
+use uuid::Uuid;
+
+use super::{geo_cache::Suggestions, geo_models::AddressLookup};
+use crate::{AppResult, errors::AppError, internal_error, state::AppState};
+
+/// Suggests addresses for a lookup of `workspace_id`, from the cache or else from the provider.
+/// Only lookups sent to the provider count against the daily quota of the workspace, which
+/// rejects them with `AppError::RateLimited` until midnight UTC once it is used up.
+pub async fn autocomplete(state: &AppState, workspace_id: Uuid, lookup: AddressLookup) -> AppResult<Suggestions> {
+  if let Some(suggestions) = state.geo_cache.get(&lookup) {
+    return Ok(suggestions);
+  }
+  let geocoder = state
+    .geocoder
+    .as_ref()
+    .ok_or_else(|| internal_error!("Address lookups are not configured: MAPBOX_ACCESS_TOKEN must be set"))?;
+
+  let now = Utc::now();
+  let today = now.date_naive();
+  let counted = state
+    .geo_lookup_repository
+    .consume(workspace_id, today, state.config.geo.daily_quota())
+    .await?;
+  if !counted {
+    let midnight = (today + Days::new(1)).and_hms_opt(0, 0, 0).expect("midnight is a valid time").and_utc();
+    return Err(AppError::RateLimited((midnight - now).num_seconds().max(1) as u64));
+  }
+
+  let suggestions = Arc::new(geocoder.autocomplete(&lookup).await?);
+  state.geo_cache.insert(lookup, suggestions.clone());
+  Ok(suggestions)
+}
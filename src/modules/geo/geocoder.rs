@@ -0,0 +1,174 @@
+//! Where address lookups are sent: the `Geocoder` trait, Nominatim and Mapbox.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::geo_models::{AddressLookup, AddressSuggestion};
+use crate::{
+  AppResult,
+  config::{GeoConfig, GeocoderSource},
+  internal_error,
+};
+
+const MAPBOX_URL: &str = "https://api.mapbox.com/geocoding/v5/mapbox.places";
+/// Nominatim's usage policy asks for an identifying user agent.
+const USER_AGENT: &str = concat!("myapp-api-rust/", env!("CARGO_PKG_VERSION"));
+
+/// Suggests addresses for what was typed. Tests replace it with `AppStateBuilder::with_geocoder`.
+#[async_trait]
+pub trait Geocoder: Send + Sync {
+  async fn autocomplete(&self, lookup: &AddressLookup) -> AppResult<Vec<AddressSuggestion>>;
+}
+
+/// The geocoder of `config`, `None` while Mapbox has no access token.
+pub fn from_config(config: &GeoConfig) -> Option<Arc<dyn Geocoder>> {
+  let http = reqwest::Client::builder()
+    .timeout(Duration::from_secs(config.timeout_secs))
+    .user_agent(USER_AGENT)
+    .build()
+    .unwrap_or_else(|_| reqwest::Client::new());
+  match config.provider {
+    GeocoderSource::Nominatim => Some(Arc::new(NominatimGeocoder {
+      http,
+      base_url: config.nominatim_url.clone(),
+    })),
+    GeocoderSource::Mapbox => {
+      let access_token = config.mapbox_access_token.clone()?;
+      Some(Arc::new(MapboxGeocoder { http, access_token }))
+    }
+  }
+}
+
+async fn get(request: reqwest::RequestBuilder, provider: &str) -> AppResult<Value> {
+  let response = request
+    .send()
+    .await
+    .map_err(|e| internal_error!("Request to {} failed: {}", provider, e))?;
+  let status = response.status();
+  if !status.is_success() {
+    return Err(internal_error!("{} responded {}", provider, status));
+  }
+  response
+    .json()
+    .await
+    .map_err(|e| internal_error!("{} sent an invalid response: {}", provider, e))
+}
+
+fn text(value: &Value) -> Option<String> {
+  value.as_str().map(str::trim).filter(|text| !text.is_empty()).map(str::to_string)
+}
+
+/// The search API of a Nominatim instance, with the address details of each place.
+pub struct NominatimGeocoder {
+  http: reqwest::Client,
+  base_url: String,
+}
+
+#[async_trait]
+impl Geocoder for NominatimGeocoder {
+  async fn autocomplete(&self, lookup: &AddressLookup) -> AppResult<Vec<AddressSuggestion>> {
+    let mut query = vec![
+      ("q", lookup.query.clone()),
+      ("format", "jsonv2".to_string()),
+      ("addressdetails", "1".to_string()),
+      ("limit", lookup.limit.to_string()),
+    ];
+    if let Some(country) = &lookup.country {
+      query.push(("countrycodes", country.clone()));
+    }
+    let request = self.http.get(format!("{}/search", self.base_url)).query(&query);
+    Ok(parse_nominatim(&get(request, "Nominatim").await?))
+  }
+}
+
+/// The suggestions of a Nominatim search, skipping places without coordinates.
+pub fn parse_nominatim(body: &Value) -> Vec<AddressSuggestion> {
+  let places = body.as_array().map(Vec::as_slice).unwrap_or_default();
+  places
+    .iter()
+    .filter_map(|place| {
+      let address = &place["address"];
+      let street = match (text(&address["road"]), text(&address["house_number"])) {
+        (Some(road), Some(number)) => Some(format!("{} {}", road, number)),
+        (road, _) => road,
+      };
+      Some(AddressSuggestion {
+        label: text(&place["display_name"])?,
+        street,
+        city: ["city", "town", "village", "municipality", "county"]
+          .into_iter()
+          .find_map(|field| text(&address[field])),
+        region: text(&address["state"]),
+        postal_code: text(&address["postcode"]),
+        country: text(&address["country"]),
+        country_code: text(&address["country_code"]).map(|code| code.to_ascii_uppercase()),
+        latitude: place["lat"].as_str()?.parse().ok()?,
+        longitude: place["lon"].as_str()?.parse().ok()?,
+      })
+    })
+    .collect()
+}
+
+/// The forward geocoding API of Mapbox in autocomplete mode, limited to addresses and places of
+/// interest.
+pub struct MapboxGeocoder {
+  http: reqwest::Client,
+  access_token: String,
+}
+
+#[async_trait]
+impl Geocoder for MapboxGeocoder {
+  async fn autocomplete(&self, lookup: &AddressLookup) -> AppResult<Vec<AddressSuggestion>> {
+    let mut url = reqwest::Url::parse(MAPBOX_URL).map_err(|e| internal_error!("Invalid Mapbox URL: {}", e))?;
+    url
+      .path_segments_mut()
+      .map_err(|_| internal_error!("Invalid Mapbox URL"))?
+      .push(&format!("{}.json", lookup.query));
+    let mut query = vec![
+      ("access_token", self.access_token.clone()),
+      ("autocomplete", "true".to_string()),
+      ("types", "address,poi".to_string()),
+      ("limit", lookup.limit.to_string()),
+    ];
+    if let Some(country) = &lookup.country {
+      query.push(("country", country.clone()));
+    }
+    Ok(parse_mapbox(&get(self.http.get(url).query(&query), "Mapbox").await?))
+  }
+}
+
+/// The suggestions of a Mapbox geocoding response, the parts of each address taken from its context.
+pub fn parse_mapbox(body: &Value) -> Vec<AddressSuggestion> {
+  let features = body["features"].as_array().map(Vec::as_slice).unwrap_or_default();
+  features
+    .iter()
+    .filter_map(|feature| {
+      let context = feature["context"].as_array().map(Vec::as_slice).unwrap_or_default();
+      let part = |kind: &str| {
+        context
+          .iter()
+          .find(|part| part["id"].as_str().is_some_and(|id| id.starts_with(&format!("{}.", kind))))
+      };
+      let is_address = feature["place_type"].as_array().is_some_and(|types| types.iter().any(|t| t == "address"));
+      let street = match (is_address.then(|| text(&feature["text"])).flatten(), text(&feature["address"])) {
+        (Some(street), Some(number)) => Some(format!("{} {}", street, number)),
+        (street, _) => street,
+      };
+      Some(AddressSuggestion {
+        label: text(&feature["place_name"])?,
+        street,
+        city: part("place").and_then(|part| text(&part["text"])),
+        region: part("region").and_then(|part| text(&part["text"])),
+        postal_code: part("postcode").and_then(|part| text(&part["text"])),
+        country: part("country").and_then(|part| text(&part["text"])),
+        country_code: part("country")
+          .and_then(|part| text(&part["short_code"]))
+          .map(|code| code.to_ascii_uppercase()),
+        longitude: feature["center"][0].as_f64()?,
+        latitude: feature["center"][1].as_f64()?,
+      })
+    })
+    .collect()
+}
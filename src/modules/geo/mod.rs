@@ -0,0 +1,15 @@
+//! Address autocomplete, compiled with the `geo` feature.
+//!
+//! `GET /api/v1/geo/autocomplete?q=` proxies the configured geocoding provider (Nominatim or
+//! Mapbox, see `geocoder`) so that address fields can suggest structured addresses without the
+//! clients holding provider credentials. Suggestions are cached in memory for
+//! `GEOCODER_CACHE_TTL_SECS`, and the lookups each workspace sends to the provider are counted in
+//! `geo_lookups` against a daily quota (`GEOCODER_DAILY_QUOTA`).
+
+pub mod geo_cache;
+pub mod geo_handlers;
+pub mod geo_models;
+pub mod geo_repository;
+pub mod geo_routes;
+pub mod geo_service;
+pub mod geocoder;
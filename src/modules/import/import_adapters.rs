@@ -0,0 +1,466 @@
+//! Reading the CSV exports of each source into the crate's create requests.
+//!
+//! Every source maps its column headers onto the fields of `CreateContactRequest` and
+//! `CreateProductRequest`. Headers are compared ignoring case, punctuation and spacing, so
+//! `No. Pelanggan` matches `no pelanggan` and `Product/Service Name` matches
+//! `product service name`. Columns without a field, such as categories and account balances,
+//! are ignored.
+
+use std::{collections::HashMap, str::FromStr};
+
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+use serde_json::json;
+use validator::Validate;
+
+use super::import_models::{ImportSource, MAX_IMPORT_ROWS};
+use crate::{
+  AppResult,
+  errors::{AppError, ValidationError},
+  modules::datastores::{
+    contacts::contact_models::CreateContactRequest,
+    products::product_models::{CreateProductRequest, ProductStatus},
+  },
+};
+
+/// Each field and the headers it is read from, the first header present winning.
+type Columns = &'static [(&'static str, &'static [&'static str])];
+
+const GENERIC_CONTACTS: Columns = &[
+  ("code", &["code"]),
+  ("name", &["name"]),
+  ("email", &["email"]),
+  ("position", &["position"]),
+  ("contact_type", &["contact type", "type"]),
+  ("address", &["address"]),
+  ("tax_id", &["tax id"]),
+  ("bank_account", &["bank account"]),
+];
+
+const ACCURATE_CONTACTS: Columns = &[
+  ("code", &["no pelanggan", "kode pelanggan", "no pemasok", "kode pemasok", "no karyawan"]),
+  ("name", &["nama pelanggan", "nama pemasok", "nama karyawan", "nama"]),
+  ("email", &["email", "e mail"]),
+  ("position", &["jabatan"]),
+  ("contact_type", &["tipe kontak", "tipe"]),
+  ("address", &["alamat penagihan", "alamat"]),
+  ("tax_id", &["npwp", "no npwp"]),
+  ("bank_account", &["no rekening", "nomor rekening"]),
+];
+
+const JURNAL_CONTACTS: Columns = &[
+  ("code", &["contact code", "kode kontak"]),
+  ("name", &["display name", "nama panggilan", "contact name", "nama kontak"]),
+  ("email", &["email"]),
+  ("position", &["position", "jabatan"]),
+  ("contact_type", &["contact type", "tipe kontak"]),
+  ("address", &["billing address", "alamat penagihan", "address", "alamat"]),
+  ("tax_id", &["tax number", "tax no", "npwp"]),
+  ("bank_account", &["account number", "nomor rekening"]),
+];
+
+const QUICKBOOKS_CONTACTS: Columns = &[
+  ("code", &["customer id", "vendor id", "employee id"]),
+  ("name", &["display name", "customer", "vendor", "employee", "name", "company"]),
+  ("email", &["email"]),
+  ("position", &["title"]),
+  ("address", &["billing address", "address"]),
+  ("tax_id", &["tax id", "tax registration number", "business id no"]),
+  ("bank_account", &["account no", "account number"]),
+];
+
+const GENERIC_PRODUCTS: Columns = &[
+  ("code", &["code"]),
+  ("name", &["name"]),
+  ("base_unit", &["base unit", "unit"]),
+  ("selling_price", &["selling price"]),
+  ("unit_cost", &["unit cost"]),
+  ("description", &["description"]),
+  ("sku", &["sku"]),
+  ("barcode", &["barcode"]),
+  ("minimum_stock", &["minimum stock"]),
+  ("maximum_stock", &["maximum stock"]),
+  ("reorder_level", &["reorder level"]),
+  ("current_stock", &["current stock", "stock"]),
+  ("track_inventory", &["track inventory"]),
+];
+
+const ACCURATE_PRODUCTS: Columns = &[
+  ("code", &["no barang", "kode barang"]),
+  ("name", &["nama barang", "deskripsi barang"]),
+  ("base_unit", &["satuan", "unit"]),
+  ("selling_price", &["harga jual", "harga jual satuan"]),
+  ("unit_cost", &["harga beli", "biaya satuan", "harga pokok"]),
+  ("description", &["keterangan"]),
+  ("barcode", &["barcode", "kode barcode"]),
+  ("minimum_stock", &["stok minimum", "minimum stok"]),
+  ("maximum_stock", &["stok maksimum", "maksimum stok"]),
+  ("reorder_level", &["titik pemesanan ulang"]),
+  ("current_stock", &["kuantitas", "stok", "qty"]),
+  ("track_inventory", &["jenis barang"]),
+];
+
+const JURNAL_PRODUCTS: Columns = &[
+  ("code", &["product code", "kode produk"]),
+  ("name", &["product name", "nama produk"]),
+  ("base_unit", &["unit", "satuan"]),
+  ("selling_price", &["sell price", "harga jual"]),
+  ("unit_cost", &["buy price", "harga beli", "average cost"]),
+  ("description", &["description", "deskripsi"]),
+  ("barcode", &["barcode"]),
+  ("minimum_stock", &["minimum stock", "stok minimum"]),
+  ("current_stock", &["quantity", "kuantitas"]),
+  ("track_inventory", &["track inventory", "lacak persediaan"]),
+];
+
+const QUICKBOOKS_PRODUCTS: Columns = &[
+  ("code", &["sku", "item code"]),
+  ("name", &["product service name", "product service", "item name", "name"]),
+  ("base_unit", &["unit"]),
+  ("selling_price", &["sales price rate", "sales price", "rate", "price"]),
+  ("unit_cost", &["purchase cost", "cost"]),
+  ("description", &["sales description", "description"]),
+  ("sku", &["sku"]),
+  ("reorder_level", &["reorder point"]),
+  ("current_stock", &["quantity on hand", "qty on hand"]),
+  ("track_inventory", &["type"]),
+];
+
+const CONTACT_REQUIRED: &[&str] = &["name", "email"];
+const PRODUCT_REQUIRED: &[&str] = &["name"];
+
+/// Leading rows searched for the header row; QuickBooks and Accurate put report titles above it.
+const HEADER_SEARCH_ROWS: usize = 10;
+/// Row errors reported for a rejected file.
+const MAX_ROW_ERRORS: usize = 50;
+/// Unit of the products whose file has no unit.
+const DEFAULT_BASE_UNIT: &str = "pcs";
+
+fn contact_columns(source: ImportSource) -> Columns {
+  match source {
+    ImportSource::Generic => GENERIC_CONTACTS,
+    ImportSource::Accurate => ACCURATE_CONTACTS,
+    ImportSource::Jurnal => JURNAL_CONTACTS,
+    ImportSource::Quickbooks => QUICKBOOKS_CONTACTS,
+  }
+}
+
+fn product_columns(source: ImportSource) -> Columns {
+  match source {
+    ImportSource::Generic => GENERIC_PRODUCTS,
+    ImportSource::Accurate => ACCURATE_PRODUCTS,
+    ImportSource::Jurnal => JURNAL_PRODUCTS,
+    ImportSource::Quickbooks => QUICKBOOKS_PRODUCTS,
+  }
+}
+
+/// Lowercases `value` and reduces everything but letters and digits to single spaces.
+fn normalize(value: &str) -> String {
+  value
+    .to_lowercase()
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// The data rows of a file with the columns of their fields located.
+struct Sheet {
+  columns: HashMap<&'static str, usize>,
+  /// Each row with its line in the file, for error messages.
+  rows: Vec<(u64, Vec<String>)>,
+}
+
+impl Sheet {
+  /// Reads `body`, finding the header row among the first rows by the `required` fields.
+  fn read(body: &[u8], columns: Columns, required: &[&str]) -> AppResult<Self> {
+    let body = body.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(body);
+    let mut reader = csv::ReaderBuilder::new()
+      .has_headers(false)
+      .flexible(true)
+      .trim(csv::Trim::All)
+      .delimiter(delimiter(body))
+      .from_reader(body);
+
+    let mut located = None;
+    let mut rows = Vec::new();
+    for record in reader.records() {
+      let record = record.map_err(|e| AppError::validation("file", &format!("The file is not valid CSV: {}", e)))?;
+      let line = record.position().map_or(0, |position| position.line());
+      let cells: Vec<String> = record.iter().map(str::to_string).collect();
+      match &located {
+        None => {
+          let headers: Vec<String> = cells.iter().map(|cell| normalize(cell)).collect();
+          let found = locate(&headers, columns);
+          if required.iter().all(|field| found.contains_key(field)) {
+            located = Some(found);
+          } else if line as usize >= HEADER_SEARCH_ROWS {
+            break;
+          }
+        }
+        Some(_) if cells.iter().all(String::is_empty) => {}
+        Some(_) => {
+          if rows.len() == MAX_IMPORT_ROWS {
+            return Err(AppError::validation(
+              "file",
+              &format!("At most {} rows can be imported at once", MAX_IMPORT_ROWS),
+            ));
+          }
+          rows.push((line, cells));
+        }
+      }
+    }
+
+    let Some(located) = located else {
+      return Err(AppError::validation("file", &missing_header_message(columns, required)));
+    };
+    if rows.is_empty() {
+      return Err(AppError::validation("file", "The file has no rows to import"));
+    }
+    Ok(Self { columns: located, rows })
+  }
+
+  /// The trimmed value of `field` in `row`, `None` when the column is absent or the cell empty.
+  fn cell<'a>(&self, row: &'a [String], field: &str) -> Option<&'a str> {
+    let index = *self.columns.get(field)?;
+    row.get(index).map(String::as_str).filter(|value| !value.is_empty())
+  }
+}
+
+/// Semicolon and tab separated exports are common where the comma is the decimal separator.
+fn delimiter(body: &[u8]) -> u8 {
+  let first_line = body.split(|byte| *byte == b'\n').next().unwrap_or_default();
+  [b',', b';', b'\t']
+    .into_iter()
+    .max_by_key(|delimiter| first_line.iter().filter(|byte| *byte == delimiter).count())
+    .filter(|delimiter| first_line.contains(delimiter))
+    .unwrap_or(b',')
+}
+
+fn locate(headers: &[String], columns: Columns) -> HashMap<&'static str, usize> {
+  columns
+    .iter()
+    .filter_map(|(field, aliases)| {
+      let index = aliases.iter().find_map(|alias| headers.iter().position(|header| header == alias))?;
+      Some((*field, index))
+    })
+    .collect()
+}
+
+fn missing_header_message(columns: Columns, required: &[&str]) -> String {
+  let expected: Vec<String> = columns
+    .iter()
+    .filter(|(field, _)| required.contains(field))
+    .map(|(field, aliases)| format!("{} ({})", field, aliases.join(", ")))
+    .collect();
+  format!("No header row found with the columns {}", expected.join(" and "))
+}
+
+/// Problems found in the rows of a file, reported together.
+#[derive(Default)]
+struct RowErrors(Vec<ValidationError>);
+
+impl RowErrors {
+  fn push(&mut self, line: u64, field: &str, message: impl std::fmt::Display) {
+    self.0.push(ValidationError {
+      field: field.to_string(),
+      message: format!("Line {}: {}", line, message),
+      code: None,
+    });
+  }
+
+  /// Adds the validation errors of a row, except those on an empty code, which is generated.
+  fn push_invalid(&mut self, line: u64, request: &impl Validate, code_is_empty: bool) {
+    let Err(errors) = request.validate() else {
+      return;
+    };
+    for (field, errors) in errors.field_errors() {
+      if code_is_empty && field == "code" {
+        continue;
+      }
+      for error in errors {
+        let message = error.message.as_ref().map_or_else(|| error.code.to_string(), ToString::to_string);
+        self.push(line, field, message);
+      }
+    }
+  }
+
+  fn into_result(self) -> AppResult<()> {
+    if self.0.is_empty() {
+      return Ok(());
+    }
+    let total = self.0.len();
+    let mut errors = self.0;
+    errors.truncate(MAX_ROW_ERRORS);
+    if total > MAX_ROW_ERRORS {
+      errors.push(ValidationError {
+        field: "rows".to_string(),
+        message: format!("{} more errors not shown", total - MAX_ROW_ERRORS),
+        code: None,
+      });
+    }
+    Err(AppError::Validation(json!({ "rows": errors })))
+  }
+}
+
+/// Rejects codes used by more than one row, which would otherwise silently skip the later rows.
+fn check_duplicate_codes<'a>(codes: impl Iterator<Item = (u64, &'a str)>, errors: &mut RowErrors) {
+  let mut seen = HashMap::new();
+  for (line, code) in codes.filter(|(_, code)| !code.is_empty()) {
+    if let Some(first) = seen.insert(code, line) {
+      errors.push(line, "code", format!("Code '{}' is also used on line {}", code, first));
+    }
+  }
+}
+
+/// Reads an amount such as `Rp 1.500,00`, `$1,500.00` or `(25.00)`, with the decimal separator of `source`.
+fn parse_decimal(value: &str, source: ImportSource) -> Option<Decimal> {
+  let compact: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+  let (negative, compact) = match compact.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+    Some(inner) => (true, inner),
+    None => (false, compact.as_str()),
+  };
+  let digits = compact.trim_start_matches("Rp").trim_start_matches("IDR").trim_start_matches('$');
+  let (thousands, decimal) = if source.decimal_comma() { ('.', ',') } else { (',', '.') };
+  let digits: String = digits
+    .chars()
+    .filter(|c| *c != thousands)
+    .map(|c| if c == decimal { '.' } else { c })
+    .collect();
+  let value = Decimal::from_str(&digits).ok()?;
+  Some(if negative { -value } else { value })
+}
+
+/// Reads a whole count, accepting the `12.00` some exports write quantities as.
+fn parse_count(value: &str, source: ImportSource) -> Option<i32> {
+  parse_decimal(value, source).filter(|value| value.fract().is_zero())?.to_i32()
+}
+
+fn parse_track_inventory(value: &str) -> Option<bool> {
+  match normalize(value).as_str() {
+    "yes" | "y" | "true" | "1" | "ya" | "inventory" | "persediaan" => Some(true),
+    "no" | "n" | "false" | "0" | "tidak" | "service" | "jasa" | "non inventory" | "noninventory" | "non persediaan" => Some(false),
+    _ => None,
+  }
+}
+
+fn parse_contact_type(value: &str) -> Option<&'static str> {
+  match normalize(value).as_str() {
+    "customer" | "pelanggan" => Some("customer"),
+    "supplier" | "vendor" | "pemasok" => Some("supplier"),
+    "employee" | "karyawan" | "pegawai" => Some("employee"),
+    "salesman" | "sales" | "penjual" => Some("salesman"),
+    _ => None,
+  }
+}
+
+/// Contacts read from `body`, of `default_type` unless a row has a type of its own.
+pub fn read_contacts(body: &[u8], source: ImportSource, default_type: &str) -> AppResult<Vec<CreateContactRequest>> {
+  let sheet = Sheet::read(body, contact_columns(source), CONTACT_REQUIRED)?;
+  let mut errors = RowErrors::default();
+  let mut contacts = Vec::with_capacity(sheet.rows.len());
+
+  for (line, row) in &sheet.rows {
+    let text = |field| sheet.cell(row, field).map(str::to_string);
+    let contact_type = match sheet.cell(row, "contact_type") {
+      None => default_type,
+      Some(value) => parse_contact_type(value).unwrap_or_else(|| {
+        errors.push(*line, "contact_type", format!("Unknown contact type '{}'", value));
+        default_type
+      }),
+    };
+    let contact = CreateContactRequest {
+      code: text("code").unwrap_or_default(),
+      name: text("name").unwrap_or_default(),
+      email: text("email").unwrap_or_default(),
+      position: text("position"),
+      contact_type: contact_type.to_string(),
+      address: text("address"),
+      tax_id: text("tax_id"),
+      bank_account: text("bank_account"),
+    };
+    errors.push_invalid(*line, &contact, contact.code.is_empty());
+    contacts.push(contact);
+  }
+
+  check_duplicate_codes(
+    sheet.rows.iter().map(|(line, _)| *line).zip(contacts.iter().map(|c| c.code.as_str())),
+    &mut errors,
+  );
+  errors.into_result()?;
+  Ok(contacts)
+}
+
+/// Products read from `body`. Missing prices are zero and a missing unit is `pcs`.
+pub fn read_products(body: &[u8], source: ImportSource) -> AppResult<Vec<CreateProductRequest>> {
+  let sheet = Sheet::read(body, product_columns(source), PRODUCT_REQUIRED)?;
+  let mut errors = RowErrors::default();
+  let mut products = Vec::with_capacity(sheet.rows.len());
+
+  for (line, row) in &sheet.rows {
+    let text = |field| sheet.cell(row, field).map(str::to_string);
+    let mut amount = |field: &str| {
+      let value = sheet.cell(row, field)?;
+      let amount = parse_decimal(value, source);
+      if amount.is_none() {
+        errors.push(*line, field, format!("'{}' is not an amount", value));
+      }
+      amount
+    };
+    let selling_price = amount("selling_price").unwrap_or_default();
+    let unit_cost = amount("unit_cost").unwrap_or_default();
+
+    let mut count = |field: &str| {
+      let value = sheet.cell(row, field)?;
+      let count = parse_count(value, source);
+      if count.is_none() {
+        errors.push(*line, field, format!("'{}' is not a whole number", value));
+      }
+      count
+    };
+    let minimum_stock = count("minimum_stock");
+    let maximum_stock = count("maximum_stock");
+    let reorder_level = count("reorder_level");
+    let current_stock = count("current_stock");
+
+    let track_inventory = sheet.cell(row, "track_inventory").and_then(|value| {
+      let track = parse_track_inventory(value);
+      if track.is_none() {
+        errors.push(*line, "track_inventory", format!("'{}' does not say whether stock is tracked", value));
+      }
+      track
+    });
+
+    let product = CreateProductRequest {
+      code: text("code").unwrap_or_default(),
+      name: text("name").unwrap_or_default(),
+      category_id: None,
+      base_unit: text("base_unit").unwrap_or_else(|| DEFAULT_BASE_UNIT.to_string()),
+      unit_on_report_preview: None,
+      selling_price,
+      unit_cost,
+      supplier_id: None,
+      track_inventory,
+      description: text("description"),
+      sku: text("sku"),
+      barcode: text("barcode"),
+      minimum_stock,
+      maximum_stock,
+      reorder_level,
+      current_stock,
+      tax_type: None,
+      tax_rate: None,
+      tax_amount: None,
+      // Imported products are reviewed and published before they are sold
+      status: Some(ProductStatus::Draft),
+    };
+    errors.push_invalid(*line, &product, product.code.is_empty());
+    products.push(product);
+  }
+
+  check_duplicate_codes(
+    sheet.rows.iter().map(|(line, _)| *line).zip(products.iter().map(|p| p.code.as_str())),
+    &mut errors,
+  );
+  errors.into_result()?;
+  Ok(products)
+}
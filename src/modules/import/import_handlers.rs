@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use axum::{
+  Json,
+  body::Bytes,
+  extract::{Path, State},
+  http::HeaderMap,
+  response::{IntoResponse, Response},
+};
+use uuid::Uuid;
+
+use super::{
+  import_adapters,
+  import_models::{ImportEntity, ImportQuery, ImportSource, ImportSummary},
+  import_service::{self, ImportOutcome},
+};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{RequireRole, RequiredWorkspace, ValidatedQuery, workspace::role::Member},
+  internal_error,
+  modules::{
+    auth::current_user::CurrentUser,
+    datastores::{contacts::contact_models::CreateContactRequest, products::product_models::CreateProductRequest},
+    operations::operation_service,
+  },
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// The validated rows of a file.
+enum ImportRows {
+  Contacts(Vec<CreateContactRequest>),
+  Products(Vec<CreateProductRequest>),
+}
+
+/// Writes the rows of a file and summarizes the import.
+async fn run_import(state: &AppState, workspace_id: Uuid, user_id: Uuid, source: ImportSource, rows: ImportRows) -> AppResult<ImportSummary> {
+  let (entity, rows, ImportOutcome { created, skipped }) = match rows {
+    ImportRows::Contacts(contacts) => (
+      ImportEntity::Contacts,
+      contacts.len(),
+      import_service::import_contacts(state, workspace_id, user_id, contacts).await?,
+    ),
+    ImportRows::Products(products) => (
+      ImportEntity::Products,
+      products.len(),
+      import_service::import_products(state, workspace_id, user_id, products).await?,
+    ),
+  };
+
+  tracing::info!(
+    "Imported {} of {} {:?} rows from {} for workspace {}",
+    created,
+    rows,
+    entity,
+    source.as_str(),
+    workspace_id
+  );
+
+  Ok(ImportSummary {
+    source,
+    entity,
+    rows,
+    created,
+    skipped,
+  })
+}
+
+/// Imports the contacts or products of the CSV file in the body, as exported by `:source`.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `headers`: The request headers, checked for `Prefer: respond-async`.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `_member`: Rejects callers below the member role in the workspace.
+/// * `Path(source)`: The tool the file was exported from, such as `accurate` or `quickbooks`.
+/// * `ValidatedQuery(query)`: The entity to import and, for contacts, their default type.
+/// * `body`: The CSV file.
+///
+/// # Returns
+///
+/// A `Json` response with the `ImportSummary`, or a 422 listing the invalid rows, in which case
+/// nothing was imported. With `Prefer: respond-async`, a valid file is imported in the background
+/// and the response is a 202 with the operation reporting the import.
+#[allow(clippy::too_many_arguments)]
+pub async fn import(
+  State(state): State<Arc<AppState>>,
+  headers: HeaderMap,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  Path(source): Path<String>,
+  ValidatedQuery(query): ValidatedQuery<ImportQuery>,
+  body: Bytes,
+) -> AppResult<Response> {
+  let source = ImportSource::parse(&source).ok_or_else(|| {
+    let sources: Vec<&str> = ImportSource::ALL.iter().map(|source| source.as_str()).collect();
+    AppError::validation(
+      "source",
+      &format!("Unknown import source '{}', expected one of {}", source, sources.join(", ")),
+    )
+  })?;
+
+  let (rows, kind) = match query.entity {
+    ImportEntity::Contacts => {
+      let contacts = import_adapters::read_contacts(&body, source, query.contact_type.as_deref().unwrap_or("customer"))?;
+      (ImportRows::Contacts(contacts), "contacts.import")
+    }
+    ImportEntity::Products => (ImportRows::Products(import_adapters::read_products(&body, source)?), "products.import"),
+  };
+  let user_id = current_user.user_id;
+
+  if operation_service::prefers_async(&headers) {
+    let task_state = state.clone();
+    return operation_service::start(&state, workspace_id, user_id, kind, move |_progress| async move {
+      let summary = run_import(&task_state, workspace_id, user_id, source, rows).await?;
+      serde_json::to_value(summary).map_err(|e| internal_error!("Failed to serialize the import summary: {}", e))
+    })
+    .await;
+  }
+
+  let summary = run_import(&state, workspace_id, user_id, source, rows).await?;
+  Ok(Json(ApiResponse::success(summary, "Import completed successfully")).into_response())
+}
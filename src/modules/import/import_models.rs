@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+/// Data rows read from one file.
+pub const MAX_IMPORT_ROWS: usize = 5000;
+
+/// The tool a file was exported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+  /// The crate's own field names, e.g. `code`, `name`, `selling_price`.
+  Generic,
+  /// Accurate Online, with Indonesian column names.
+  Accurate,
+  /// Jurnal, with English or Indonesian column names.
+  Jurnal,
+  /// QuickBooks Online customer, vendor and product lists.
+  Quickbooks,
+}
+
+impl ImportSource {
+  pub const ALL: [ImportSource; 4] = [
+    ImportSource::Generic,
+    ImportSource::Accurate,
+    ImportSource::Jurnal,
+    ImportSource::Quickbooks,
+  ];
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ImportSource::Generic => "generic",
+      ImportSource::Accurate => "accurate",
+      ImportSource::Jurnal => "jurnal",
+      ImportSource::Quickbooks => "quickbooks",
+    }
+  }
+
+  pub fn parse(value: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|source| source.as_str() == value)
+  }
+
+  /// Whether amounts are written the Indonesian way, `1.500,25`, rather than `1,500.25`.
+  pub fn decimal_comma(self) -> bool {
+    matches!(self, ImportSource::Accurate)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportEntity {
+  Contacts,
+  Products,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ImportQuery {
+  pub entity: ImportEntity,
+  /// Type of the imported contacts without a type column or value, `customer` by default.
+  #[validate(custom(function = "validate_contact_type"))]
+  pub contact_type: Option<String>,
+}
+
+/// The contact types of the `contacts.type` check constraint.
+pub const CONTACT_TYPES: [&str; 4] = ["customer", "supplier", "employee", "salesman"];
+
+fn validate_contact_type(contact_type: &str) -> Result<(), ValidationError> {
+  if !CONTACT_TYPES.contains(&contact_type) {
+    return Err(ValidationError::new("contact_type").with_message("Contact type must be customer, supplier, employee or salesman".into()));
+  }
+  Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportSummary {
+  pub source: ImportSource,
+  pub entity: ImportEntity,
+  /// Data rows in the file.
+  pub rows: usize,
+  pub created: usize,
+  /// Codes of the rows skipped because the code already exists in the workspace.
+  pub skipped: Vec<String>,
+}
@@ -0,0 +1,11 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post};
+
+use super::import_handlers::import;
+use crate::state::AppState;
+
+/// Imports from accounting tools, mounted at `/api/v1/import` with the upload body limit.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new().route("/:source", post(import))
+}
@@ -0,0 +1,91 @@
+use uuid::Uuid;
+
+use crate::{
+  AppResult,
+  errors::AppError,
+  modules::{
+    datastores::{contacts::contact_models::CreateContactRequest, products::product_models::CreateProductRequest},
+    usage::record_quota::check_record_quota,
+  },
+  state::AppState,
+  utils::db_session,
+};
+
+/// What an import wrote: the number of records created and the codes skipped as existing.
+pub struct ImportOutcome {
+  pub created: usize,
+  pub skipped: Vec<String>,
+}
+
+/// Codes among `requested` that were not created, in file order.
+fn skipped(requested: Vec<String>, created: &[String]) -> Vec<String> {
+  requested.into_iter().filter(|code| !created.contains(code)).collect()
+}
+
+/// Inserts validated contacts in one transaction. Rows with a code go in one statement that
+/// skips existing codes; codes are then generated for the others one by one, so each sees the last.
+/// An import taking the workspace past the records of its plan is rolled back as a whole.
+pub async fn import_contacts(state: &AppState, workspace_id: Uuid, user_id: Uuid, contacts: Vec<CreateContactRequest>) -> AppResult<ImportOutcome> {
+  let repository = &state.contact_repository;
+  db_session::transaction::<_, _, AppError>(&state.db, async {
+    let (coded, uncoded): (Vec<_>, Vec<_>) = contacts.into_iter().partition(|contact| !contact.code.is_empty());
+    let requested: Vec<String> = coded.iter().map(|contact| contact.code.clone()).collect();
+    let created: Vec<String> = repository
+      .create_many_by_workspace(coded, workspace_id, user_id)
+      .await?
+      .into_iter()
+      .map(|contact| contact.code)
+      .collect();
+    let mut outcome = ImportOutcome {
+      created: created.len(),
+      skipped: skipped(requested, &created),
+    };
+
+    if !uncoded.is_empty() {
+      let settings = state.workspace_settings_repository.get(workspace_id).await?;
+      let rules = settings.code_rules.get("contacts");
+      for mut contact in uncoded {
+        contact.code = repository
+          .get_next_available_code(workspace_id, &contact.name, rules, Some(&contact.contact_type))
+          .await?;
+        repository.create_by_workspace(contact, workspace_id, user_id).await?;
+        outcome.created += 1;
+      }
+    }
+    check_record_quota(state, workspace_id).await?;
+    Ok(outcome)
+  })
+  .await
+}
+
+/// Inserts validated products in one transaction, like `import_contacts`.
+pub async fn import_products(state: &AppState, workspace_id: Uuid, user_id: Uuid, products: Vec<CreateProductRequest>) -> AppResult<ImportOutcome> {
+  let repository = &state.product_repository;
+  db_session::transaction::<_, _, AppError>(&state.db, async {
+    let (coded, uncoded): (Vec<_>, Vec<_>) = products.into_iter().partition(|product| !product.code.is_empty());
+    let requested: Vec<String> = coded.iter().map(|product| product.code.clone()).collect();
+    let created: Vec<String> = repository
+      .create_many_by_workspace(coded, workspace_id, user_id)
+      .await?
+      .into_iter()
+      .map(|product| product.code)
+      .collect();
+    let mut outcome = ImportOutcome {
+      created: created.len(),
+      skipped: skipped(requested, &created),
+    };
+
+    if !uncoded.is_empty() {
+      let settings = state.workspace_settings_repository.get(workspace_id).await?;
+      let rules = settings.code_rules.get("products");
+      for mut product in uncoded {
+        product.code = repository.get_next_available_code(workspace_id, &product.name, rules, None).await?;
+        repository.create_by_workspace(product, workspace_id, user_id).await?;
+        outcome.created += 1;
+      }
+    }
+    check_record_quota(state, workspace_id).await?;
+    Ok(outcome)
+  })
+  .await
+}
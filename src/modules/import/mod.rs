@@ -0,0 +1,16 @@
+//! Importing contacts and products from the CSV exports of accounting tools.
+//!
+//! `POST /api/v1/import/:source?entity=contacts|products` takes the CSV file as the request body.
+//! The `source` names the tool the file was exported from, which decides the column names and
+//! number format read (see `import_adapters`); `generic` reads the crate's own field names.
+//!
+//! An import is all or nothing: every row is checked before anything is written, and the rows
+//! are inserted in one transaction. Rows whose code already exists in the workspace are skipped
+//! and listed in the summary; rows without a code get one generated as on create. Imported
+//! records do not publish change events, and imported products are drafts until published.
+
+pub mod import_adapters;
+pub mod import_handlers;
+pub mod import_models;
+pub mod import_routes;
+pub mod import_service;
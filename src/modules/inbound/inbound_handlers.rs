@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use axum::{
+  body::Bytes,
+  extract::{Path, State, rejection::JsonRejection},
+  http::{HeaderMap, StatusCode},
+  response::{IntoResponse, Json, Response},
+};
+use serde_json::Value;
+use validator::Validate;
+
+use super::{
+  inbound_models::{
+    CreateInboundIntegrationRequest, CreatedInboundIntegration, DEFAULT_EVENT_LIMIT, InboundEvent, InboundEventsQuery, InboundIntegration,
+    InboundReceipt,
+  },
+  inbound_signature::verify_signature,
+};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{PathUuid, RequireRole, RequiredWorkspace, ValidatedQuery, workspace::role::Admin},
+  modules::auth::current_user::CurrentUser,
+  responses::{ApiResponse, Created},
+  state::AppState,
+};
+
+/// Receives a payload from a third-party system. It must be JSON, signed with the secret of the
+/// integration (see `inbound_signature`). Responds `202 Accepted` once the payload is queued;
+/// senders retry on any other status.
+pub async fn receive_inbound(State(state): State<Arc<AppState>>, Path(key): Path<String>, headers: HeaderMap, body: Bytes) -> AppResult<Response> {
+  let (integration, secret) = state
+    .inbound_repository
+    .find_by_key(&key)
+    .await?
+    .ok_or_else(|| AppError::not_found("Inbound integration"))?;
+  verify_signature(&headers, &body, &secret)?;
+  let payload: Value = serde_json::from_slice(&body).map_err(|e| AppError::validation("payload", &format!("Payload is not JSON: {}", e)))?;
+
+  let event = state.inbound_repository.enqueue(&integration, &payload).await?;
+  tracing::info!(
+    "Queued inbound event {} from integration {} ({})",
+    event.id,
+    integration.id,
+    integration.source
+  );
+
+  let receipt = InboundReceipt {
+    event_id: event.id,
+    status: event.status,
+  };
+  Ok((StatusCode::ACCEPTED, Json(ApiResponse::success(receipt, "Payload queued"))).into_response())
+}
+
+/// The inbound integrations of the workspace.
+pub async fn list_inbound_integrations(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+) -> AppResult<Json<ApiResponse<Vec<InboundIntegration>>>> {
+  let integrations = state.inbound_repository.list_integrations(workspace_id).await?;
+  Ok(Json(ApiResponse::success(integrations, "Inbound integrations retrieved successfully")))
+}
+
+pub async fn get_inbound_integration(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  PathUuid(id): PathUuid,
+) -> AppResult<Json<ApiResponse<InboundIntegration>>> {
+  let integration = state
+    .inbound_repository
+    .get_integration(id, workspace_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Inbound integration", id))?;
+  Ok(Json(ApiResponse::success(integration, "Inbound integration retrieved successfully")))
+}
+
+/// Creates an integration with a random key and signing secret. The secret is only returned here.
+pub async fn create_inbound_integration(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  payload: Result<Json<CreateInboundIntegrationRequest>, JsonRejection>,
+) -> AppResult<Created<ApiResponse<CreatedInboundIntegration>>> {
+  let Json(request) = payload?;
+  request.validate()?;
+
+  let key = hex::encode(rand::random::<[u8; 16]>());
+  let secret = hex::encode(rand::random::<[u8; 32]>());
+  let integration = state
+    .inbound_repository
+    .create_integration(workspace_id, &request, &key, &secret, current_user.user_id)
+    .await?;
+
+  let location = format!("/api/v1/inbound-integrations/{}", integration.id);
+  let created = CreatedInboundIntegration { integration, secret };
+  Ok(ApiResponse::created(created, "Inbound integration created successfully", location))
+}
+
+/// Deletes an integration and its events; deliveries to its key are then refused.
+pub async fn delete_inbound_integration(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  PathUuid(id): PathUuid,
+) -> AppResult<Json<ApiResponse<()>>> {
+  if !state.inbound_repository.delete_integration(id, workspace_id).await? {
+    return Err(AppError::not_found_with_id("Inbound integration", id));
+  }
+  Ok(Json(ApiResponse::success((), "Inbound integration deleted successfully")))
+}
+
+/// The latest payloads received by an integration, optionally of one `status`.
+pub async fn list_inbound_events(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  PathUuid(id): PathUuid,
+  ValidatedQuery(query): ValidatedQuery<InboundEventsQuery>,
+) -> AppResult<Json<ApiResponse<Vec<InboundEvent>>>> {
+  let repository = &state.inbound_repository;
+  repository
+    .get_integration(id, workspace_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Inbound integration", id))?;
+
+  let events = repository
+    .list_events(id, workspace_id, query.status, query.limit.unwrap_or(DEFAULT_EVENT_LIMIT))
+    .await?;
+  Ok(Json(ApiResponse::success(events, "Inbound events retrieved successfully")))
+}
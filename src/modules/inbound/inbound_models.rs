@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Events listed when no limit is given.
+pub const DEFAULT_EVENT_LIMIT: i64 = 50;
+
+/// Where an accepted payload is in its mapping into records of the workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "inbound_event_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum InboundEventStatus {
+  /// Waiting in the queue.
+  Pending,
+  Processed,
+  /// Could not be mapped; `last_error` tells why.
+  Failed,
+}
+
+/// A third-party system allowed to post payloads to `/api/v1/inbound/{key}`. Its signing secret
+/// is only returned when the integration is created.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct InboundIntegration {
+  pub id: Uuid,
+  pub workspace_id: Uuid,
+  pub name: String,
+  /// The system posting, e.g. `shopify` or `woocommerce`, which tells consumers how to map its
+  /// payloads.
+  pub source: String,
+  pub key: String,
+  pub last_received_at: Option<DateTime<Utc>>,
+  pub created_by: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// A newly created integration, with the secret to configure in the system posting to it.
+#[derive(Debug, Serialize)]
+pub struct CreatedInboundIntegration {
+  #[serde(flatten)]
+  pub integration: InboundIntegration,
+  pub secret: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateInboundIntegrationRequest {
+  #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+  pub name: String,
+  #[validate(length(min = 1, max = 50, message = "Source must be between 1 and 50 characters"))]
+  pub source: String,
+}
+
+/// A payload accepted from an integration.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct InboundEvent {
+  pub id: Uuid,
+  pub integration_id: Uuid,
+  pub workspace_id: Uuid,
+  pub payload: Value,
+  pub status: InboundEventStatus,
+  pub attempts: i32,
+  pub last_error: Option<String>,
+  pub received_at: DateTime<Utc>,
+  pub processed_at: Option<DateTime<Utc>>,
+}
+
+/// The answer to a delivery: where its payload was queued.
+#[derive(Debug, Serialize)]
+pub struct InboundReceipt {
+  pub event_id: Uuid,
+  pub status: InboundEventStatus,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct InboundEventsQuery {
+  pub status: Option<InboundEventStatus>,
+  #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
+  pub limit: Option<i64>,
+}
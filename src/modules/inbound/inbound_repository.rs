@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::inbound_models::{CreateInboundIntegrationRequest, InboundEvent, InboundEventStatus, InboundIntegration};
+use crate::{
+  errors::AppError,
+  utils::{DbExecutor, field_encryption::FieldCipher},
+};
+
+const SECRET: &str = "inbound_integrations.secret";
+
+#[async_trait]
+pub trait InboundRepository: Send + Sync {
+  async fn list_integrations(&self, workspace_id: Uuid) -> Result<Vec<InboundIntegration>, AppError>;
+  async fn get_integration(&self, id: Uuid, workspace_id: Uuid) -> Result<Option<InboundIntegration>, AppError>;
+  /// Creates an integration posting to `key`, signing with `secret`.
+  async fn create_integration(
+    &self,
+    workspace_id: Uuid,
+    request: &CreateInboundIntegrationRequest,
+    key: &str,
+    secret: &str,
+    created_by: Uuid,
+  ) -> Result<InboundIntegration, AppError>;
+  /// Deletes an integration with its events, `false` when it did not exist in the workspace.
+  async fn delete_integration(&self, id: Uuid, workspace_id: Uuid) -> Result<bool, AppError>;
+  /// The integration posting to `key`, with its decrypted secret.
+  async fn find_by_key(&self, key: &str) -> Result<Option<(InboundIntegration, String)>, AppError>;
+  /// Queues a payload of `integration` as a pending event.
+  async fn enqueue(&self, integration: &InboundIntegration, payload: &Value) -> Result<InboundEvent, AppError>;
+  /// The latest events of an integration, newest first.
+  async fn list_events(
+    &self,
+    integration_id: Uuid,
+    workspace_id: Uuid,
+    status: Option<InboundEventStatus>,
+    limit: i64,
+  ) -> Result<Vec<InboundEvent>, AppError>;
+}
+
+pub struct PostgresInboundRepository {
+  db: DbExecutor,
+  cipher: Arc<FieldCipher>,
+}
+
+impl PostgresInboundRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self {
+      db: db.into(),
+      cipher: Arc::new(FieldCipher::disabled()),
+    }
+  }
+
+  /// Encrypts the signing secrets with `cipher`, which must have a key to create integrations.
+  pub fn with_cipher(mut self, cipher: Arc<FieldCipher>) -> Self {
+    self.cipher = cipher;
+    self
+  }
+}
+
+#[async_trait]
+impl InboundRepository for PostgresInboundRepository {
+  async fn list_integrations(&self, workspace_id: Uuid) -> Result<Vec<InboundIntegration>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let integrations = sqlx::query_as!(
+      InboundIntegration,
+      r#"
+        SELECT id, workspace_id, name, source, key, last_received_at, created_by, created_at, updated_at
+        FROM inbound_integrations
+        WHERE workspace_id = $1
+        ORDER BY created_at
+        "#,
+      workspace_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(integrations)
+  }
+
+  async fn get_integration(&self, id: Uuid, workspace_id: Uuid) -> Result<Option<InboundIntegration>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let integration = sqlx::query_as!(
+      InboundIntegration,
+      r#"
+        SELECT id, workspace_id, name, source, key, last_received_at, created_by, created_at, updated_at
+        FROM inbound_integrations
+        WHERE id = $1 AND workspace_id = $2
+        "#,
+      id,
+      workspace_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(integration)
+  }
+
+  async fn create_integration(
+    &self,
+    workspace_id: Uuid,
+    request: &CreateInboundIntegrationRequest,
+    key: &str,
+    secret: &str,
+    created_by: Uuid,
+  ) -> Result<InboundIntegration, AppError> {
+    let secret = self.cipher.encrypt(SECRET, secret)?;
+    let mut conn = self.db.acquire().await?;
+    let integration = sqlx::query_as!(
+      InboundIntegration,
+      r#"
+        INSERT INTO inbound_integrations (workspace_id, name, source, key, secret, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, workspace_id, name, source, key, last_received_at, created_by, created_at, updated_at
+        "#,
+      workspace_id,
+      request.name,
+      request.source,
+      key,
+      secret,
+      created_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(integration)
+  }
+
+  async fn delete_integration(&self, id: Uuid, workspace_id: Uuid) -> Result<bool, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!("DELETE FROM inbound_integrations WHERE id = $1 AND workspace_id = $2", id, workspace_id)
+      .execute(&mut *conn)
+      .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  async fn find_by_key(&self, key: &str) -> Result<Option<(InboundIntegration, String)>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let row = sqlx::query!(
+      r#"
+        SELECT id, workspace_id, name, source, key, secret, last_received_at, created_by, created_at, updated_at
+        FROM inbound_integrations
+        WHERE key = $1
+        "#,
+      key
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some(row) = row else {
+      return Ok(None);
+    };
+    let secret = self.cipher.decrypt(SECRET, &row.secret)?;
+    let integration = InboundIntegration {
+      id: row.id,
+      workspace_id: row.workspace_id,
+      name: row.name,
+      source: row.source,
+      key: row.key,
+      last_received_at: row.last_received_at,
+      created_by: row.created_by,
+      created_at: row.created_at,
+      updated_at: row.updated_at,
+    };
+    Ok(Some((integration, secret)))
+  }
+
+  async fn enqueue(&self, integration: &InboundIntegration, payload: &Value) -> Result<InboundEvent, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let event = sqlx::query_as!(
+      InboundEvent,
+      r#"
+        WITH received AS (
+          UPDATE inbound_integrations SET last_received_at = NOW() WHERE id = $1
+        )
+        INSERT INTO inbound_events (integration_id, workspace_id, payload)
+        VALUES ($1, $2, $3)
+        RETURNING id, integration_id, workspace_id, payload, status as "status: InboundEventStatus", attempts, last_error,
+          received_at, processed_at
+        "#,
+      integration.id,
+      integration.workspace_id,
+      payload
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(event)
+  }
+
+  async fn list_events(
+    &self,
+    integration_id: Uuid,
+    workspace_id: Uuid,
+    status: Option<InboundEventStatus>,
+    limit: i64,
+  ) -> Result<Vec<InboundEvent>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let events = sqlx::query_as!(
+      InboundEvent,
+      r#"
+        SELECT id, integration_id, workspace_id, payload, status as "status: InboundEventStatus", attempts, last_error,
+          received_at, processed_at
+        FROM inbound_events
+        WHERE integration_id = $1 AND workspace_id = $2 AND ($3::inbound_event_status IS NULL OR status = $3)
+        ORDER BY received_at DESC, id DESC
+        LIMIT $4
+        "#,
+      integration_id,
+      workspace_id,
+      status as Option<InboundEventStatus>,
+      limit
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(events)
+  }
+}
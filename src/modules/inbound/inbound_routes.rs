@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  routing::{delete, get, post},
+};
+
+use super::inbound_handlers::{
+  create_inbound_integration, delete_inbound_integration, get_inbound_integration, list_inbound_events, list_inbound_integrations, receive_inbound,
+};
+use crate::state::AppState;
+
+/// Inbound integrations of the current workspace, mounted at `/api/v1/inbound-integrations`
+/// behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/", get(list_inbound_integrations))
+    .route("/", post(create_inbound_integration))
+    .route("/:integration_id", get(get_inbound_integration))
+    .route("/:integration_id", delete(delete_inbound_integration))
+    .route("/:integration_id/events", get(list_inbound_events))
+}
+
+/// Deliveries, mounted at `/api/v1/inbound`. Senders authenticate each payload with the
+/// signature of their integration instead of a token.
+pub fn receive_routes() -> Router<Arc<AppState>> {
+  Router::new().route("/:integration_key", post(receive_inbound))
+}
@@ -0,0 +1,14 @@
+//! Inbound webhooks, compiled with the `inbound` feature.
+//!
+//! Admins of a workspace create an integration for each third-party system posting to it, e.g.
+//! an e-commerce platform notifying new orders, under `/api/v1/inbound-integrations`. The system
+//! posts JSON payloads to `POST /api/v1/inbound/{key}`, signed with the secret of the integration
+//! (see `inbound_signature`). Accepted payloads are queued as pending `inbound_events`, for the
+//! consumers mapping them into records of the workspace, and listed with their status under
+//! `/api/v1/inbound-integrations/{id}/events`.
+
+pub mod inbound_handlers;
+pub mod inbound_models;
+pub mod inbound_repository;
+pub mod inbound_routes;
+pub mod inbound_signature;
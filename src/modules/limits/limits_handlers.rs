@@ -0,0 +1,70 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{extract::State, response::Json};
+use chrono::Utc;
+
+use super::limits_models::{LimitsResponse, PlanQuotas, QuotaUsage, RateLimits};
+use crate::{
+  AppResult,
+  helper::OptionalWorkspace,
+  middleware::rate_limit::user_bucket,
+  modules::{
+    auth::current_user::CurrentUser,
+    usage::{
+      usage_meter::{month_start, next_month_start},
+      usage_models::UsagePlan,
+    },
+  },
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// The caller's rate limit budgets and, in a workspace, the quotas of its plan. Counters do not
+/// move: this request is already counted in the read budget, and the monthly requests include
+/// those not flushed yet.
+pub async fn get_limits(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  OptionalWorkspace(workspace_id): OptionalWorkspace,
+) -> AppResult<Json<ApiResponse<LimitsResponse>>> {
+  let config = &state.config.rate_limit;
+  let rate_limit = if config.enabled {
+    let window = Duration::from_secs(config.window_secs);
+    let (read_key, read_max) = user_bucket(config, current_user.user_id, workspace_id, true);
+    let (write_key, write_max) = user_bucket(config, current_user.user_id, workspace_id, false);
+    Some(RateLimits {
+      window_secs: config.window_secs,
+      read: state.rate_limiter.peek(&read_key, read_max, window).await?.into(),
+      write: state.rate_limiter.peek(&write_key, write_max, window).await?.into(),
+    })
+  } else {
+    None
+  };
+
+  let quotas = match workspace_id {
+    Some(workspace_id) => {
+      let usage_config = &state.config.usage;
+      let now = Utc::now();
+      let records = state.usage_repository.record_usage(workspace_id).await?;
+      let requests = if usage_config.enabled {
+        let month_start = month_start(now);
+        let monthly = state.usage_repository.monthly_usage(workspace_id, month_start).await?;
+        let used = monthly.requests + state.usage_meter.pending_requests(workspace_id, month_start)?;
+        Some(QuotaUsage::new(usage_config.monthly_quota(records.paid), used))
+      } else {
+        None
+      };
+      Some(PlanQuotas {
+        workspace_id,
+        plan: if records.paid { UsagePlan::Paid } else { UsagePlan::Free },
+        requests,
+        requests_reset_at: next_month_start(now),
+        records: QuotaUsage::new(usage_config.record_limit(records.paid), records.records),
+      })
+    }
+    None => None,
+  };
+
+  let response = ApiResponse::success(LimitsResponse { rate_limit, quotas }, "Limits retrieved successfully");
+  Ok(Json(response))
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{middleware::rate_limit::RateLimitDecision, modules::usage::usage_models::UsagePlan};
+
+/// A rate limit budget in its current window, as the `X-RateLimit-*` headers report it.
+#[derive(Debug, Serialize)]
+pub struct RateLimitBudget {
+  pub limit: u32,
+  pub remaining: u32,
+  /// Seconds until the window resets.
+  pub reset_after: u64,
+}
+
+impl From<RateLimitDecision> for RateLimitBudget {
+  fn from(decision: RateLimitDecision) -> Self {
+    Self {
+      limit: decision.limit,
+      remaining: decision.remaining,
+      reset_after: decision.reset_after,
+    }
+  }
+}
+
+/// The caller's budgets for read and write requests in the workspace of the request.
+#[derive(Debug, Serialize)]
+pub struct RateLimits {
+  pub window_secs: u64,
+  pub read: RateLimitBudget,
+  pub write: RateLimitBudget,
+}
+
+/// The consumption of one quota. `limit` and `remaining` are `null` when the plan is unlimited.
+#[derive(Debug, Serialize)]
+pub struct QuotaUsage {
+  pub limit: Option<u64>,
+  pub used: i64,
+  pub remaining: Option<u64>,
+}
+
+impl QuotaUsage {
+  pub fn new(limit: Option<u64>, used: i64) -> Self {
+    let used_unsigned = u64::try_from(used).unwrap_or(0);
+    Self {
+      limit,
+      used,
+      remaining: limit.map(|limit| limit.saturating_sub(used_unsigned)),
+    }
+  }
+}
+
+/// The quotas of the workspace's plan.
+#[derive(Debug, Serialize)]
+pub struct PlanQuotas {
+  pub workspace_id: Uuid,
+  pub plan: UsagePlan,
+  /// Requests this calendar month (UTC), `null` while usage is not metered.
+  pub requests: Option<QuotaUsage>,
+  /// When the monthly request quota starts over.
+  pub requests_reset_at: DateTime<Utc>,
+  /// Contacts and products held by the workspace.
+  pub records: QuotaUsage,
+}
+
+/// The response of `GET /api/v1/limits`. `rate_limit` is `null` while rate limiting is disabled,
+/// `quotas` outside a workspace.
+#[derive(Debug, Serialize)]
+pub struct LimitsResponse {
+  pub rate_limit: Option<RateLimits>,
+  pub quotas: Option<PlanQuotas>,
+}
@@ -0,0 +1,11 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+
+use super::limits_handlers::get_limits;
+use crate::state::AppState;
+
+/// The caller's limits, mounted at `/api/v1/limits` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new().route("/", get(get_limits))
+}
@@ -0,0 +1,10 @@
+//! What the caller may still do before hitting a hard limit, in one call.
+//!
+//! `GET /api/v1/limits` reports the caller's rate limit budgets in the current window and, in a
+//! workspace, the quotas of its plan: requests this month and records held. Client apps can warn
+//! their users ahead of a 429 or 402 instead of reacting to one. Reading the limits counts
+//! against the read budget like any other request.
+
+pub mod limits_handlers;
+pub mod limits_models;
+pub mod limits_routes;
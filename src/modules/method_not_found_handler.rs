@@ -4,7 +4,7 @@ use axum::{
 };
 use tracing::warn;
 
-use crate::errors::{AppError, NotFoundError};
+use crate::errors::AppError;
 
 pub async fn fallback(uri: Uri) -> Response {
   warn!("Route not found: {}", uri);
@@ -14,9 +14,5 @@ pub async fn fallback(uri: Uri) -> Response {
     uri
   );
 
-  let app_error = AppError::NotFound(NotFoundError {
-    resource: error_message,
-    id: None,
-  });
-  app_error.into_response()
+  AppError::not_found(&error_message).into_response()
 }
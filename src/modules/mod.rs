@@ -1,5 +1,53 @@
+pub mod admin;
+#[cfg(any(feature = "contacts", feature = "products"))]
+pub mod approvals;
+pub mod audit;
 pub mod auth;
+#[cfg(feature = "backups")]
+pub mod backups;
+#[cfg(feature = "billing")]
+pub mod billing;
+#[cfg(feature = "connectors")]
+pub mod connectors;
+#[cfg(feature = "currencies")]
+pub mod currencies;
 pub mod datastores;
+#[cfg(feature = "email_templates")]
+pub mod email_templates;
+#[cfg(feature = "exports")]
+pub mod exports;
+pub mod feature_flags;
+#[cfg(feature = "geo")]
+pub mod geo;
+#[cfg(feature = "import")]
+pub mod import;
+#[cfg(feature = "inbound")]
+pub mod inbound;
+pub mod limits;
+pub mod notifications;
+#[cfg(any(feature = "contacts", feature = "products"))]
+pub mod operations;
+pub mod organizations;
+pub mod overview;
+pub mod realtime;
+#[cfg(any(feature = "contacts", feature = "products"))]
+pub mod record_locks;
+#[cfg(feature = "rendering")]
+pub mod rendering;
+#[cfg(feature = "reports")]
+pub mod reports;
+pub mod retention;
+#[cfg(any(feature = "contacts", feature = "products"))]
+pub mod search;
+#[cfg(feature = "contacts")]
+pub mod statements;
+pub mod trial;
+#[cfg(any(feature = "contacts", feature = "products"))]
+pub mod triggers;
+pub mod usage;
+pub mod user_export;
+pub mod v2;
+pub mod workspace_settings;
 
 pub mod method_not_allowed_handler;
 pub mod method_not_found_handler;
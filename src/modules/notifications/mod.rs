@@ -0,0 +1,7 @@
+//! Notifications sent to users about their account, such as the links of password resets.
+//!
+//! They are emailed through the `Mailer` in `AppState`, in the background: the request that
+//! triggered one answers without waiting for the email provider, so how long sending takes
+//! cannot tell a caller whether an email went out.
+
+pub mod notification_service;
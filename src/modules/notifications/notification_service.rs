@@ -0,0 +1,28 @@
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{mailer::EmailMessage, modules::auth::user_model::User, state::AppState};
+
+/// Emails `user` the link to set a new password with `token`.
+pub fn send_password_reset(state: &AppState, user: &User, token: &str) {
+  let message = EmailMessage {
+    to: user.email.clone(),
+    subject: "Reset your password".to_string(),
+    body: format!(
+      "Someone asked to reset the password of your account. If it was you, choose a new password within {} minutes:\n\n{}/{}\n\nOtherwise you can ignore this email.\n",
+      state.config.password_reset.token_ttl_minutes, state.config.password_reset.link_base_url, token
+    ),
+  };
+  send_in_background(state, user.id, message);
+}
+
+/// Sends `message` to `user_id` without waiting for it; failures are only logged.
+fn send_in_background(state: &AppState, user_id: Uuid, message: EmailMessage) {
+  let mailer = state.mailer.clone();
+  tokio::spawn(async move {
+    let subject = message.subject.clone();
+    if let Err(e) = mailer.send(message).await {
+      warn!("Failed to email '{}' to user {}: {}", subject, user_id, e);
+    }
+  });
+}
@@ -0,0 +1,14 @@
+//! Long requests running in the background, polled as operations.
+//!
+//! Clients sending `Prefer: respond-async` to an expensive endpoint get `202 Accepted` with the
+//! operation and its URL in `Location` instead of waiting: CSV imports, and NDJSON exports of
+//! contacts and products. The request is checked first, so an invalid one still fails at once.
+//! `GET /api/v1/operations/:id` reports the status and percentage done, and once the operation
+//! succeeded, `result_location` points at `GET /api/v1/operations/:id/result` with the import
+//! summary or the exported records. Members only see the operations they started.
+
+pub mod operation_handlers;
+pub mod operation_models;
+pub mod operation_repository;
+pub mod operation_routes;
+pub mod operation_service;
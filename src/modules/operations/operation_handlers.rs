@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use serde_json::Value;
+
+use super::operation_models::OperationResponse;
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{PathUuid, RequiredWorkspace},
+  modules::auth::current_user::CurrentUser,
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// Reports the status and progress of an operation the caller started.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `PathUuid(id)`: The UUID of the operation.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+///
+/// # Returns
+///
+/// A `Json` response with the operation and, once it succeeded, the location of its result.
+pub async fn get_operation(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+) -> AppResult<Json<ApiResponse<OperationResponse>>> {
+  let operation = state
+    .operation_repository
+    .get(id, workspace_id, current_user.user_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Operation", id))?;
+
+  let response = ApiResponse::success(OperationResponse::from(operation), "Operation retrieved successfully");
+  Ok(Json(response))
+}
+
+/// Returns the result of a succeeded operation, such as the summary of an import or the exported
+/// records. A 409 while the operation is still running or when it failed.
+pub async fn get_operation_result(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+) -> AppResult<Json<ApiResponse<Value>>> {
+  let repository = &state.operation_repository;
+  let Some(result) = repository.get_result(id, workspace_id, current_user.user_id).await? else {
+    return match repository.get(id, workspace_id, current_user.user_id).await? {
+      Some(operation) => Err(AppError::Conflict(format!(
+        "Operation {} has no result: it is {:?}",
+        id, operation.status
+      ))),
+      None => Err(AppError::not_found_with_id("Operation", id)),
+    };
+  };
+
+  let response = ApiResponse::success(result, "Operation result retrieved successfully");
+  Ok(Json(response))
+}
@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "operation_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum OperationStatus {
+  Running,
+  /// The result can be fetched from `result_location`.
+  Succeeded,
+  Failed,
+}
+
+/// A long request running in the background, as returned by the API; the result itself is only
+/// sent by the result endpoint.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Operation {
+  pub id: Uuid,
+  pub workspace_id: Uuid,
+  pub user_id: Uuid,
+  /// What runs, e.g. `products.import` or `contacts.export`.
+  pub kind: String,
+  pub status: OperationStatus,
+  /// Percentage done, from 0 to 100.
+  pub progress: i16,
+  pub error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+  pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl Operation {
+  /// Where the operation is polled.
+  pub fn location(&self) -> String {
+    format!("/api/v1/operations/{}", self.id)
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OperationResponse {
+  #[serde(flatten)]
+  pub operation: Operation,
+  /// Where the result is fetched once the operation succeeded.
+  pub result_location: Option<String>,
+}
+
+impl From<Operation> for OperationResponse {
+  fn from(operation: Operation) -> Self {
+    let result_location = (operation.status == OperationStatus::Succeeded).then(|| format!("{}/result", operation.location()));
+    Self { operation, result_location }
+  }
+}
@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::operation_models::{Operation, OperationStatus};
+use crate::{AppResult, utils::DbExecutor};
+
+#[async_trait]
+pub trait OperationRepository: Send + Sync {
+  /// Records a new running operation of `user_id` in `workspace_id`.
+  async fn create(&self, workspace_id: Uuid, user_id: Uuid, kind: &str) -> AppResult<Operation>;
+  /// Records the percentage done, up to 100, of a running operation.
+  async fn set_progress(&self, id: Uuid, progress: u8) -> AppResult<()>;
+  async fn complete(&self, id: Uuid, result: &Value) -> AppResult<()>;
+  async fn fail(&self, id: Uuid, error: &str) -> AppResult<()>;
+  /// An operation `user_id` started in `workspace_id`; those of other members are not found.
+  async fn get(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<Operation>>;
+  /// The result of a succeeded operation, like `get`.
+  async fn get_result(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<Value>>;
+}
+
+pub struct PostgresOperationRepository {
+  db: DbExecutor,
+}
+
+impl PostgresOperationRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl OperationRepository for PostgresOperationRepository {
+  async fn create(&self, workspace_id: Uuid, user_id: Uuid, kind: &str) -> AppResult<Operation> {
+    let mut conn = self.db.acquire().await?;
+    let operation = sqlx::query_as!(
+      Operation,
+      r#"
+        INSERT INTO operations (workspace_id, user_id, kind)
+        VALUES ($1, $2, $3)
+        RETURNING id, workspace_id, user_id, kind, status as "status: OperationStatus", progress, error,
+          created_at, updated_at, completed_at
+        "#,
+      workspace_id,
+      user_id,
+      kind
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(operation)
+  }
+
+  async fn set_progress(&self, id: Uuid, progress: u8) -> AppResult<()> {
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!(
+      r#"
+        UPDATE operations
+        SET progress = $2, updated_at = NOW()
+        WHERE id = $1 AND status = 'running'
+        "#,
+      id,
+      i16::from(progress)
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn complete(&self, id: Uuid, result: &Value) -> AppResult<()> {
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!(
+      r#"
+        UPDATE operations
+        SET status = 'succeeded', progress = 100, result = $2, updated_at = NOW(), completed_at = NOW()
+        WHERE id = $1
+        "#,
+      id,
+      result
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn fail(&self, id: Uuid, error: &str) -> AppResult<()> {
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!(
+      r#"
+        UPDATE operations
+        SET status = 'failed', error = $2, updated_at = NOW(), completed_at = NOW()
+        WHERE id = $1
+        "#,
+      id,
+      error
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn get(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<Operation>> {
+    let mut conn = self.db.acquire().await?;
+    let operation = sqlx::query_as!(
+      Operation,
+      r#"
+        SELECT id, workspace_id, user_id, kind, status as "status: OperationStatus", progress, error,
+          created_at, updated_at, completed_at
+        FROM operations
+        WHERE id = $1 AND workspace_id = $2 AND user_id = $3
+        "#,
+      id,
+      workspace_id,
+      user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(operation)
+  }
+
+  async fn get_result(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<Value>> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query_scalar!(
+      r#"
+        SELECT result as "result!"
+        FROM operations
+        WHERE id = $1 AND workspace_id = $2 AND user_id = $3 AND status = 'succeeded'
+        "#,
+      id,
+      workspace_id,
+      user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(result)
+  }
+}
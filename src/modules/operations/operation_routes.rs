@@ -0,0 +1,13 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+
+use super::operation_handlers::{get_operation, get_operation_result};
+use crate::state::AppState;
+
+/// The operations of the caller, mounted at `/api/v1/operations` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/:operation_id", get(get_operation))
+    .route("/:operation_id/result", get(get_operation_result))
+}
@@ -0,0 +1,122 @@
+//! Running requests as operations in the background.
+//!
+//! Operations run on a task spawned by the request, as the caller, so an operation in progress is
+//! lost when the process stops and stays `running`; the caller can then start it again.
+
+use std::{future::Future, sync::Arc};
+
+use axum::{
+  Json,
+  http::{HeaderMap, StatusCode, header},
+  response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::{operation_models::OperationResponse, operation_repository::OperationRepository};
+use crate::{AppResult, errors::AppError, responses::ApiResponse, state::AppState, utils::db_session};
+
+/// Rows fetched per query by exports running as operations.
+pub const EXPORT_PAGE_SIZE: u32 = 500;
+
+/// Whether the client asked for the request to run in the background, with `Prefer: respond-async`
+/// (RFC 7240).
+pub fn prefers_async(headers: &HeaderMap) -> bool {
+  headers
+    .get_all("prefer")
+    .iter()
+    .filter_map(|value| value.to_str().ok())
+    .flat_map(|value| value.split(','))
+    .any(|preference| {
+      preference
+        .split(';')
+        .next()
+        .is_some_and(|p| p.trim().eq_ignore_ascii_case("respond-async"))
+    })
+}
+
+/// Records the percentage done of a running operation.
+#[derive(Clone)]
+pub struct Progress {
+  repository: Arc<dyn OperationRepository + Send + Sync>,
+  operation_id: Uuid,
+}
+
+impl Progress {
+  /// Records that `done` of `total` items are done. A failure is only logged, since the
+  /// operation itself can go on.
+  pub async fn report(&self, done: u64, total: u64) {
+    let progress = (done.min(total) * 100).checked_div(total).unwrap_or(100) as u8;
+    if let Err(e) = self.repository.set_progress(self.operation_id, progress).await {
+      warn!("Failed to record the progress of operation {}: {}", self.operation_id, e);
+    }
+  }
+}
+
+/// Starts `work` as an operation of the caller and responds `202 Accepted` with the operation and
+/// its URL in `Location`. `work` runs as the caller and returns the result kept for
+/// `GET /api/v1/operations/:id/result`.
+pub async fn start<F, Fut>(state: &Arc<AppState>, workspace_id: Uuid, user_id: Uuid, kind: &str, work: F) -> AppResult<Response>
+where
+  F: FnOnce(Progress) -> Fut + Send + 'static,
+  Fut: Future<Output = AppResult<Value>> + Send + 'static,
+{
+  let repository = state.operation_repository.clone();
+  let operation = repository.create(workspace_id, user_id, kind).await?;
+  let progress = Progress {
+    repository: repository.clone(),
+    operation_id: operation.id,
+  };
+  let settings = db_session::current_settings();
+  let pool = state.db.clone();
+  let operation_id = operation.id;
+  let future = work(progress);
+
+  tokio::spawn(async move {
+    let result = match settings {
+      Some(settings) => db_session::scope(&pool, settings, future)
+        .await
+        .map_err(AppError::from)
+        .and_then(|result| result),
+      None => future.await,
+    };
+    let recorded = match result {
+      Ok(result) => {
+        info!("Operation {} succeeded", operation_id);
+        repository.complete(operation_id, &result).await
+      }
+      Err(e) => {
+        warn!("Operation {} failed: {}", operation_id, e);
+        repository.fail(operation_id, &e.to_string()).await
+      }
+    };
+    if let Err(e) = recorded {
+      warn!("Failed to record the outcome of operation {}: {}", operation_id, e);
+    }
+  });
+
+  let location = operation.location();
+  let response = ApiResponse::success(OperationResponse::from(operation), "The request is being processed");
+  Ok((StatusCode::ACCEPTED, [(header::LOCATION, location)], Json(response)).into_response())
+}
+
+/// Collects every row of a paginated query, `EXPORT_PAGE_SIZE` at a time, reporting progress
+/// after each page. `fetch` returns a page and the total number of rows.
+pub async fn collect_pages<T, F, Fut>(progress: &Progress, mut fetch: F) -> AppResult<Vec<T>>
+where
+  F: FnMut(u32, u32) -> Fut,
+  Fut: Future<Output = AppResult<(Vec<T>, u64)>>,
+{
+  let mut rows = Vec::new();
+  for page in 1.. {
+    let (batch, total) = fetch(page, EXPORT_PAGE_SIZE).await?;
+    let last = (batch.len() as u32) < EXPORT_PAGE_SIZE;
+    rows.extend(batch);
+    progress.report(rows.len() as u64, total).await;
+    if last {
+      break;
+    }
+  }
+  Ok(rows)
+}
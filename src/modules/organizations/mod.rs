@@ -0,0 +1,18 @@
+//! Organizations grouping the workspaces of a company, such as one per branch.
+//!
+//! * Members of an organization get a role in each of its workspaces, resolved together with
+//!   their workspace memberships by the `workspace_access` view: admins of the organization are
+//!   admins of every workspace, members get the workspace role set on their membership. A user
+//!   keeps the highest of the two roles.
+//! * The subscription of the organization's billing workspace pays for all its workspaces (see
+//!   the `billed_subscription_status` function), so a company subscribes once.
+//! * `POST /api/v1/organizations/:id/switch` is the organization switcher: it returns the
+//!   caller's workspaces in the organization with a token for one of them.
+//!
+//! A workspace belongs to one organization at most. Only its owner can move it into one, since
+//! the members of the organization gain access to it.
+
+pub mod organization_handlers;
+pub mod organization_models;
+pub mod organization_repository;
+pub mod organization_routes;
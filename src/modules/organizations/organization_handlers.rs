@@ -0,0 +1,386 @@
+use std::sync::Arc;
+
+use axum::{
+  extract::{State, rejection::JsonRejection},
+  response::Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::organization_models::{
+  AddOrganizationMemberRequest, AttachWorkspaceRequest, CreateOrganizationRequest, Organization, OrganizationBilling, OrganizationMember,
+  OrganizationRole, OrganizationWithRole, SetBillingWorkspaceRequest, SwitchOrganizationRequest, SwitchOrganizationResponse,
+  UpdateOrganizationMemberRequest, UpdateOrganizationRequest,
+};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::PathUuid,
+  modules::{
+    auth::{auth_service, current_user::CurrentUser},
+    datastores::workspaces::{WorkspaceRole, WorkspaceWithRole},
+  },
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// The caller's role in the organization, or an authorization error when they are not a member.
+async fn member_role(state: &AppState, organization_id: Uuid, user_id: Uuid) -> AppResult<OrganizationRole> {
+  state
+    .organization_repository
+    .member_role(organization_id, user_id)
+    .await?
+    .ok_or_else(|| AppError::Authorization("Access denied to organization".to_string()))
+}
+
+async fn require_admin(state: &AppState, organization_id: Uuid, user_id: Uuid) -> AppResult<()> {
+  match member_role(state, organization_id, user_id).await? {
+    OrganizationRole::Admin => Ok(()),
+    OrganizationRole::Member => Err(AppError::Authorization("Only organization admins can do this".to_string())),
+  }
+}
+
+async fn get_existing(state: &AppState, organization_id: Uuid) -> AppResult<Organization> {
+  state
+    .organization_repository
+    .get_organization(organization_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Organization", organization_id))
+}
+
+/// Forgets the cached roles in the organization's workspaces after they changed, those of
+/// `user_id` only when given.
+async fn forget_roles(state: &AppState, organization_id: Uuid, user_id: Option<Uuid>) -> AppResult<()> {
+  for workspace_id in state.organization_repository.workspace_ids(organization_id).await? {
+    match user_id {
+      Some(user_id) => state.role_cache.invalidate(user_id, workspace_id),
+      None => state.role_cache.invalidate_workspace(workspace_id),
+    }
+  }
+  Ok(())
+}
+
+pub async fn create_organization(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  payload: Result<Json<CreateOrganizationRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<Organization>>> {
+  let Json(request) = payload?;
+  request.validate()?;
+
+  let organization = state.organization_repository.create_organization(&request, current_user.user_id).await?;
+
+  let response = ApiResponse::success(organization, "Organization created successfully");
+  Ok(Json(response))
+}
+
+/// The caller's organizations, for the organization switcher.
+pub async fn list_organizations(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+) -> AppResult<Json<ApiResponse<Vec<OrganizationWithRole>>>> {
+  let organizations = state.organization_repository.user_organizations(current_user.user_id).await?;
+
+  let response = ApiResponse::success(organizations, "Organizations retrieved successfully");
+  Ok(Json(response))
+}
+
+pub async fn get_organization(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(organization_id): PathUuid,
+) -> AppResult<Json<ApiResponse<Organization>>> {
+  member_role(&state, organization_id, current_user.user_id).await?;
+  let organization = get_existing(&state, organization_id).await?;
+
+  let response = ApiResponse::success(organization, "Organization retrieved successfully");
+  Ok(Json(response))
+}
+
+pub async fn update_organization(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(organization_id): PathUuid,
+  payload: Result<Json<UpdateOrganizationRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<Organization>>> {
+  let Json(request) = payload?;
+  request.validate()?;
+  require_admin(&state, organization_id, current_user.user_id).await?;
+
+  let organization = state
+    .organization_repository
+    .update_organization(organization_id, &request, current_user.user_id)
+    .await?;
+
+  let response = ApiResponse::success(organization, "Organization updated successfully");
+  Ok(Json(response))
+}
+
+/// Deletes the organization. Its workspaces stay with their own members and subscriptions.
+pub async fn delete_organization(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(organization_id): PathUuid,
+) -> AppResult<Json<ApiResponse<()>>> {
+  member_role(&state, organization_id, current_user.user_id).await?;
+  let organization = get_existing(&state, organization_id).await?;
+  if organization.owner_id != current_user.user_id {
+    return Err(AppError::Authorization(
+      "Only the organization owner can delete the organization".to_string(),
+    ));
+  }
+
+  let workspace_ids = state.organization_repository.workspace_ids(organization_id).await?;
+  state.organization_repository.delete_organization(organization_id).await?;
+  for workspace_id in workspace_ids {
+    state.role_cache.invalidate_workspace(workspace_id);
+    state.usage_meter.invalidate(workspace_id);
+  }
+
+  let response = ApiResponse::success((), "Organization deleted successfully");
+  Ok(Json(response))
+}
+
+pub async fn list_members(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(organization_id): PathUuid,
+) -> AppResult<Json<ApiResponse<Vec<OrganizationMember>>>> {
+  member_role(&state, organization_id, current_user.user_id).await?;
+  let members = state.organization_repository.members(organization_id).await?;
+
+  let response = ApiResponse::success(members, "Organization members retrieved successfully");
+  Ok(Json(response))
+}
+
+pub async fn add_member(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(organization_id): PathUuid,
+  payload: Result<Json<AddOrganizationMemberRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<OrganizationMember>>> {
+  let Json(request) = payload?;
+  require_admin(&state, organization_id, current_user.user_id).await?;
+
+  if state
+    .organization_repository
+    .member_role(organization_id, request.user_id)
+    .await?
+    .is_some()
+  {
+    return Err(AppError::Conflict("User is already a member of the organization".to_string()));
+  }
+  let workspace_role = request.workspace_role.unwrap_or(WorkspaceRole::Viewer);
+  let member = state
+    .organization_repository
+    .add_member(organization_id, request.user_id, request.role, workspace_role)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("User", request.user_id))?;
+  forget_roles(&state, organization_id, Some(request.user_id)).await?;
+
+  let response = ApiResponse::success(member, "Member added to organization successfully");
+  Ok(Json(response))
+}
+
+pub async fn update_member(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid((organization_id, user_id)): PathUuid<(Uuid, Uuid)>,
+  payload: Result<Json<UpdateOrganizationMemberRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<OrganizationMember>>> {
+  let Json(request) = payload?;
+  require_admin(&state, organization_id, current_user.user_id).await?;
+
+  let organization = get_existing(&state, organization_id).await?;
+  if organization.owner_id == user_id && request.role != OrganizationRole::Admin {
+    return Err(AppError::validation("role", "The organization owner stays an admin"));
+  }
+  let member = state
+    .organization_repository
+    .update_member(organization_id, user_id, request.role, request.workspace_role)
+    .await?
+    .ok_or_else(|| AppError::not_found("Organization member"))?;
+  forget_roles(&state, organization_id, Some(user_id)).await?;
+
+  let response = ApiResponse::success(member, "Organization member updated successfully");
+  Ok(Json(response))
+}
+
+/// Removes a member. Admins remove anyone but the owner; members can only leave themselves.
+pub async fn remove_member(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid((organization_id, user_id)): PathUuid<(Uuid, Uuid)>,
+) -> AppResult<Json<ApiResponse<()>>> {
+  if user_id != current_user.user_id {
+    require_admin(&state, organization_id, current_user.user_id).await?;
+  } else {
+    member_role(&state, organization_id, current_user.user_id).await?;
+  }
+
+  let organization = get_existing(&state, organization_id).await?;
+  if organization.owner_id == user_id {
+    return Err(AppError::validation("user_id", "The organization owner cannot be removed"));
+  }
+  if !state.organization_repository.remove_member(organization_id, user_id).await? {
+    return Err(AppError::not_found("Organization member"));
+  }
+  forget_roles(&state, organization_id, Some(user_id)).await?;
+
+  let response = ApiResponse::success((), "Member removed from organization successfully");
+  Ok(Json(response))
+}
+
+/// The workspaces of the organization, with the caller's role in each.
+pub async fn list_workspaces(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(organization_id): PathUuid,
+) -> AppResult<Json<ApiResponse<Vec<WorkspaceWithRole>>>> {
+  member_role(&state, organization_id, current_user.user_id).await?;
+  let workspaces = state
+    .organization_repository
+    .user_workspaces(organization_id, current_user.user_id)
+    .await?;
+
+  let response = ApiResponse::success(workspaces, "Organization workspaces retrieved successfully");
+  Ok(Json(response))
+}
+
+/// Moves a workspace into the organization. The caller must be an admin of the organization and
+/// the owner of the workspace, whose data the members of the organization gain access to.
+pub async fn attach_workspace(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(organization_id): PathUuid,
+  payload: Result<Json<AttachWorkspaceRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<()>>> {
+  let Json(request) = payload?;
+  require_admin(&state, organization_id, current_user.user_id).await?;
+  if !state
+    .workspace_repository
+    .is_workspace_owner(current_user.user_id, request.workspace_id)
+    .await?
+  {
+    return Err(AppError::Authorization(
+      "Only the workspace owner can add the workspace to an organization".to_string(),
+    ));
+  }
+
+  if !state
+    .organization_repository
+    .attach_workspace(organization_id, request.workspace_id)
+    .await?
+  {
+    return Err(AppError::Conflict("Workspace belongs to another organization".to_string()));
+  }
+  state.role_cache.invalidate_workspace(request.workspace_id);
+  state.usage_meter.invalidate(request.workspace_id);
+
+  let response = ApiResponse::success((), "Workspace added to organization successfully");
+  Ok(Json(response))
+}
+
+/// Takes a workspace out of the organization; allowed to organization admins and the workspace owner.
+pub async fn detach_workspace(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid((organization_id, workspace_id)): PathUuid<(Uuid, Uuid)>,
+) -> AppResult<Json<ApiResponse<()>>> {
+  let is_owner = state.workspace_repository.is_workspace_owner(current_user.user_id, workspace_id).await?;
+  if !is_owner {
+    require_admin(&state, organization_id, current_user.user_id).await?;
+  }
+
+  if !state.organization_repository.detach_workspace(organization_id, workspace_id).await? {
+    return Err(AppError::not_found("Organization workspace"));
+  }
+  state.role_cache.invalidate_workspace(workspace_id);
+  // The workspace may have lost the subscription paying for it, or taken it away from the others
+  state.usage_meter.invalidate(workspace_id);
+  for other in state.organization_repository.workspace_ids(organization_id).await? {
+    state.usage_meter.invalidate(other);
+  }
+
+  let response = ApiResponse::success((), "Workspace removed from organization successfully");
+  Ok(Json(response))
+}
+
+/// Which workspaces of the organization are paid for, and whether by the billing workspace.
+pub async fn get_billing(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(organization_id): PathUuid,
+) -> AppResult<Json<ApiResponse<OrganizationBilling>>> {
+  member_role(&state, organization_id, current_user.user_id).await?;
+  let organization = get_existing(&state, organization_id).await?;
+  let workspaces = state.organization_repository.billing(organization_id).await?;
+
+  let billing = OrganizationBilling {
+    organization_id,
+    billing_workspace_id: organization.billing_workspace_id,
+    workspaces,
+  };
+  let response = ApiResponse::success(billing, "Organization billing retrieved successfully");
+  Ok(Json(response))
+}
+
+/// Sets the workspace whose subscription pays for the whole organization.
+pub async fn set_billing_workspace(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(organization_id): PathUuid,
+  payload: Result<Json<SetBillingWorkspaceRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<Organization>>> {
+  let Json(request) = payload?;
+  require_admin(&state, organization_id, current_user.user_id).await?;
+
+  let organization = state
+    .organization_repository
+    .set_billing_workspace(organization_id, request.workspace_id)
+    .await?
+    .ok_or_else(|| AppError::validation("workspace_id", "Workspace does not belong to the organization"))?;
+  // The quotas of the workspaces follow the subscription paying for them
+  for workspace_id in state.organization_repository.workspace_ids(organization_id).await? {
+    state.usage_meter.invalidate(workspace_id);
+  }
+
+  let response = ApiResponse::success(organization, "Billing workspace updated successfully");
+  Ok(Json(response))
+}
+
+/// The organization switcher: issues an access token for a workspace of the organization, the
+/// caller's first one by name unless `workspace_id` is given, and lists the others to switch to.
+/// The current token stays valid.
+pub async fn switch_organization(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(organization_id): PathUuid,
+  payload: Result<Json<SwitchOrganizationRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<SwitchOrganizationResponse>>> {
+  let Json(request) = payload?;
+  member_role(&state, organization_id, current_user.user_id).await?;
+
+  let workspaces = state
+    .organization_repository
+    .user_workspaces(organization_id, current_user.user_id)
+    .await?;
+  let workspace_id = match request.workspace_id {
+    Some(workspace_id) if workspaces.iter().any(|w| w.workspace.id == workspace_id) => workspace_id,
+    Some(_) => return Err(AppError::validation("workspace_id", "Not a workspace of the organization")),
+    None => workspaces
+      .first()
+      .map(|w| w.workspace.id)
+      .ok_or_else(|| AppError::validation("organization_id", "The organization has no workspaces"))?,
+  };
+  let (token, role) = auth_service::switch_workspace(&state, current_user.user_id, workspace_id).await?;
+
+  let response = SwitchOrganizationResponse {
+    token,
+    organization_id,
+    workspace_id,
+    role,
+    workspaces,
+  };
+  Ok(Json(ApiResponse::success(response, "Organization switched successfully")))
+}
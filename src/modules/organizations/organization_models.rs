@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::modules::datastores::workspaces::{WorkspaceRole, WorkspaceWithRole};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Organization {
+  pub id: Uuid,
+  pub name: String,
+  pub description: Option<String>,
+  pub owner_id: Uuid,
+  /// The workspace whose subscription pays for every workspace of the organization.
+  pub billing_workspace_id: Option<Uuid>,
+  pub created_by: Option<Uuid>,
+  pub updated_by: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "organization_role", rename_all = "lowercase")]
+pub enum OrganizationRole {
+  /// Manages the organization and is an admin of each of its workspaces.
+  Admin,
+  Member,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OrganizationMember {
+  pub organization_id: Uuid,
+  pub user_id: Uuid,
+  pub role: OrganizationRole,
+  /// The role the membership grants in each workspace of the organization; admins are admins regardless.
+  pub workspace_role: WorkspaceRole,
+  pub created_at: DateTime<Utc>,
+}
+
+/// An organization of the caller, as listed by the organization switcher.
+#[derive(Debug, Serialize)]
+pub struct OrganizationWithRole {
+  #[serde(flatten)]
+  pub organization: Organization,
+  pub role: OrganizationRole,
+  pub workspace_count: i64,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateOrganizationRequest {
+  #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+  pub name: String,
+  pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateOrganizationRequest {
+  #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+  pub name: Option<String>,
+  pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AddOrganizationMemberRequest {
+  pub user_id: Uuid,
+  pub role: OrganizationRole,
+  /// Defaults to `Viewer`.
+  pub workspace_role: Option<WorkspaceRole>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateOrganizationMemberRequest {
+  pub role: OrganizationRole,
+  pub workspace_role: WorkspaceRole,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttachWorkspaceRequest {
+  pub workspace_id: Uuid,
+}
+
+/// Sets the workspace paying for the organization; `null` ends the consolidated billing.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetBillingWorkspaceRequest {
+  pub workspace_id: Option<Uuid>,
+}
+
+/// Whether each workspace of an organization is paid for, and by which subscription.
+#[derive(Debug, Serialize)]
+pub struct OrganizationBilling {
+  pub organization_id: Uuid,
+  pub billing_workspace_id: Option<Uuid>,
+  pub workspaces: Vec<WorkspaceBilling>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct WorkspaceBilling {
+  pub workspace_id: Uuid,
+  pub name: String,
+  /// Whether the workspace has an active or past due subscription, its own or the organization's.
+  pub paid: bool,
+  /// Whether it is paid for by the subscription of the billing workspace rather than its own.
+  pub covered_by_organization: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SwitchOrganizationRequest {
+  /// The workspace to issue the token for, the caller's first workspace in the organization by name when omitted.
+  pub workspace_id: Option<Uuid>,
+}
+
+/// An access token for a workspace of the organization switched to, with the caller's other
+/// workspaces in it.
+#[derive(Debug, Serialize)]
+pub struct SwitchOrganizationResponse {
+  pub token: String,
+  pub organization_id: Uuid,
+  pub workspace_id: Uuid,
+  pub role: WorkspaceRole,
+  pub workspaces: Vec<WorkspaceWithRole>,
+}
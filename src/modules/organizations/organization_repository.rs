@@ -0,0 +1,429 @@
+use async_trait::async_trait;
+use sqlx::Connection;
+use uuid::Uuid;
+
+use super::organization_models::{
+  CreateOrganizationRequest, Organization, OrganizationMember, OrganizationRole, OrganizationWithRole, UpdateOrganizationRequest, WorkspaceBilling,
+};
+use crate::{
+  AppResult,
+  modules::datastores::workspaces::{Workspace, WorkspaceRole, WorkspaceWithRole},
+  utils::DbExecutor,
+};
+
+#[async_trait]
+pub trait OrganizationRepository: Send + Sync {
+  /// Creates an organization with `owner_id` as its first admin.
+  async fn create_organization(&self, request: &CreateOrganizationRequest, owner_id: Uuid) -> AppResult<Organization>;
+  async fn get_organization(&self, organization_id: Uuid) -> AppResult<Option<Organization>>;
+  async fn update_organization(&self, organization_id: Uuid, request: &UpdateOrganizationRequest, updated_by: Uuid) -> AppResult<Organization>;
+  /// Deletes an organization; its workspaces stay, without an organization.
+  async fn delete_organization(&self, organization_id: Uuid) -> AppResult<()>;
+  /// The organizations `user_id` is a member of, by name.
+  async fn user_organizations(&self, user_id: Uuid) -> AppResult<Vec<OrganizationWithRole>>;
+
+  async fn member_role(&self, organization_id: Uuid, user_id: Uuid) -> AppResult<Option<OrganizationRole>>;
+  async fn members(&self, organization_id: Uuid) -> AppResult<Vec<OrganizationMember>>;
+  /// Adds a member, `None` when the user does not exist or is a member already.
+  async fn add_member(
+    &self,
+    organization_id: Uuid,
+    user_id: Uuid,
+    role: OrganizationRole,
+    workspace_role: WorkspaceRole,
+  ) -> AppResult<Option<OrganizationMember>>;
+  async fn update_member(
+    &self,
+    organization_id: Uuid,
+    user_id: Uuid,
+    role: OrganizationRole,
+    workspace_role: WorkspaceRole,
+  ) -> AppResult<Option<OrganizationMember>>;
+  async fn remove_member(&self, organization_id: Uuid, user_id: Uuid) -> AppResult<bool>;
+
+  /// The IDs of the workspaces of the organization.
+  async fn workspace_ids(&self, organization_id: Uuid) -> AppResult<Vec<Uuid>>;
+  /// The workspaces of the organization `user_id` can access, with their role, by name.
+  async fn user_workspaces(&self, organization_id: Uuid, user_id: Uuid) -> AppResult<Vec<WorkspaceWithRole>>;
+  /// Moves a workspace into the organization, `false` when it belongs to another one already.
+  async fn attach_workspace(&self, organization_id: Uuid, workspace_id: Uuid) -> AppResult<bool>;
+  /// Takes a workspace out of the organization, ending its consolidated billing. `false` when it
+  /// was not in the organization.
+  async fn detach_workspace(&self, organization_id: Uuid, workspace_id: Uuid) -> AppResult<bool>;
+  /// Sets the billing workspace, `None` when `workspace_id` is not a workspace of the organization.
+  async fn set_billing_workspace(&self, organization_id: Uuid, workspace_id: Option<Uuid>) -> AppResult<Option<Organization>>;
+  /// Whether each workspace of the organization is paid for, by name.
+  async fn billing(&self, organization_id: Uuid) -> AppResult<Vec<WorkspaceBilling>>;
+}
+
+pub struct PostgresOrganizationRepository {
+  db: DbExecutor,
+}
+
+impl PostgresOrganizationRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl OrganizationRepository for PostgresOrganizationRepository {
+  async fn create_organization(&self, request: &CreateOrganizationRequest, owner_id: Uuid) -> AppResult<Organization> {
+    let organization_id = Uuid::new_v4();
+
+    // The transaction is replayed as a whole if it hits a deadlock or a dropped connection
+    let organization = self
+      .db
+      .retry(|| async {
+        let mut conn = self.db.acquire().await?;
+        let mut tx = conn.begin().await?;
+
+        let organization = sqlx::query_as!(
+          Organization,
+          r#"
+            INSERT INTO organizations (id, name, description, owner_id, created_by)
+            VALUES ($1, $2, $3, $4, $4)
+            RETURNING id, name, description, owner_id, billing_workspace_id, created_by, updated_by, created_at, updated_at
+            "#,
+          organization_id,
+          request.name,
+          request.description,
+          owner_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+          r#"
+            INSERT INTO organization_members (organization_id, user_id, role, workspace_role)
+            VALUES ($1, $2, $3, $4)
+            "#,
+          organization_id,
+          owner_id,
+          OrganizationRole::Admin as OrganizationRole,
+          WorkspaceRole::Admin as WorkspaceRole
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(organization)
+      })
+      .await?;
+
+    Ok(organization)
+  }
+
+  async fn get_organization(&self, organization_id: Uuid) -> AppResult<Option<Organization>> {
+    let mut conn = self.db.acquire().await?;
+    let organization = sqlx::query_as!(
+      Organization,
+      r#"
+        SELECT id, name, description, owner_id, billing_workspace_id, created_by, updated_by, created_at, updated_at
+        FROM organizations
+        WHERE id = $1
+        "#,
+      organization_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(organization)
+  }
+
+  async fn update_organization(&self, organization_id: Uuid, request: &UpdateOrganizationRequest, updated_by: Uuid) -> AppResult<Organization> {
+    let mut conn = self.db.acquire().await?;
+    let organization = sqlx::query_as!(
+      Organization,
+      r#"
+        UPDATE organizations
+        SET name = COALESCE($2, name), description = COALESCE($3, description), updated_by = $4
+        WHERE id = $1
+        RETURNING id, name, description, owner_id, billing_workspace_id, created_by, updated_by, created_at, updated_at
+        "#,
+      organization_id,
+      request.name,
+      request.description,
+      updated_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(organization)
+  }
+
+  async fn delete_organization(&self, organization_id: Uuid) -> AppResult<()> {
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!("DELETE FROM organizations WHERE id = $1", organization_id)
+      .execute(&mut *conn)
+      .await?;
+
+    Ok(())
+  }
+
+  async fn user_organizations(&self, user_id: Uuid) -> AppResult<Vec<OrganizationWithRole>> {
+    let mut conn = self.db.acquire().await?;
+    let organizations = sqlx::query!(
+      r#"
+        SELECT o.id, o.name, o.description, o.owner_id, o.billing_workspace_id, o.created_by, o.updated_by, o.created_at, o.updated_at,
+               om.role as "role: OrganizationRole",
+               (SELECT COUNT(*) FROM workspaces w WHERE w.organization_id = o.id) as "workspace_count!"
+        FROM organizations o
+        JOIN organization_members om ON om.organization_id = o.id
+        WHERE om.user_id = $1
+        ORDER BY o.name, o.id
+        "#,
+      user_id
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|row| OrganizationWithRole {
+      organization: Organization {
+        id: row.id,
+        name: row.name,
+        description: row.description,
+        owner_id: row.owner_id,
+        billing_workspace_id: row.billing_workspace_id,
+        created_by: row.created_by,
+        updated_by: row.updated_by,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+      },
+      role: row.role,
+      workspace_count: row.workspace_count,
+    })
+    .collect();
+
+    Ok(organizations)
+  }
+
+  async fn member_role(&self, organization_id: Uuid, user_id: Uuid) -> AppResult<Option<OrganizationRole>> {
+    let mut conn = self.db.acquire().await?;
+    let role = sqlx::query_scalar!(
+      r#"SELECT role as "role: OrganizationRole" FROM organization_members WHERE organization_id = $1 AND user_id = $2"#,
+      organization_id,
+      user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(role)
+  }
+
+  async fn members(&self, organization_id: Uuid) -> AppResult<Vec<OrganizationMember>> {
+    let mut conn = self.db.acquire().await?;
+    let members = sqlx::query_as!(
+      OrganizationMember,
+      r#"
+        SELECT organization_id, user_id, role as "role: OrganizationRole", workspace_role as "workspace_role: WorkspaceRole", created_at
+        FROM organization_members
+        WHERE organization_id = $1
+        ORDER BY created_at, user_id
+        "#,
+      organization_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(members)
+  }
+
+  async fn add_member(
+    &self,
+    organization_id: Uuid,
+    user_id: Uuid,
+    role: OrganizationRole,
+    workspace_role: WorkspaceRole,
+  ) -> AppResult<Option<OrganizationMember>> {
+    let mut conn = self.db.acquire().await?;
+    let member = sqlx::query_as!(
+      OrganizationMember,
+      r#"
+        INSERT INTO organization_members (organization_id, user_id, role, workspace_role)
+        SELECT $1, id, $3, $4 FROM users WHERE id = $2
+        ON CONFLICT (organization_id, user_id) DO NOTHING
+        RETURNING organization_id, user_id, role as "role: OrganizationRole", workspace_role as "workspace_role: WorkspaceRole", created_at
+        "#,
+      organization_id,
+      user_id,
+      role as OrganizationRole,
+      workspace_role as WorkspaceRole
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(member)
+  }
+
+  async fn update_member(
+    &self,
+    organization_id: Uuid,
+    user_id: Uuid,
+    role: OrganizationRole,
+    workspace_role: WorkspaceRole,
+  ) -> AppResult<Option<OrganizationMember>> {
+    let mut conn = self.db.acquire().await?;
+    let member = sqlx::query_as!(
+      OrganizationMember,
+      r#"
+        UPDATE organization_members
+        SET role = $3, workspace_role = $4
+        WHERE organization_id = $1 AND user_id = $2
+        RETURNING organization_id, user_id, role as "role: OrganizationRole", workspace_role as "workspace_role: WorkspaceRole", created_at
+        "#,
+      organization_id,
+      user_id,
+      role as OrganizationRole,
+      workspace_role as WorkspaceRole
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(member)
+  }
+
+  async fn remove_member(&self, organization_id: Uuid, user_id: Uuid) -> AppResult<bool> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!(
+      "DELETE FROM organization_members WHERE organization_id = $1 AND user_id = $2",
+      organization_id,
+      user_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  async fn workspace_ids(&self, organization_id: Uuid) -> AppResult<Vec<Uuid>> {
+    let mut conn = self.db.acquire().await?;
+    let ids = sqlx::query_scalar!("SELECT id FROM workspaces WHERE organization_id = $1", organization_id)
+      .fetch_all(&mut *conn)
+      .await?;
+
+    Ok(ids)
+  }
+
+  async fn user_workspaces(&self, organization_id: Uuid, user_id: Uuid) -> AppResult<Vec<WorkspaceWithRole>> {
+    let mut conn = self.db.acquire().await?;
+    let workspaces = sqlx::query!(
+      r#"
+        SELECT w.id, w.name, w.description, w.owner_id, w.created_by, w.updated_by, w.created_at, w.updated_at,
+               wa.role as "role!: WorkspaceRole",
+               u.username as "owner_name?"
+        FROM workspaces w
+        JOIN workspace_access wa ON wa.workspace_id = w.id
+        LEFT JOIN users u ON w.owner_id = u.id
+        WHERE w.organization_id = $1 AND wa.user_id = $2
+        ORDER BY w.name, w.id
+        "#,
+      organization_id,
+      user_id
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|row| WorkspaceWithRole {
+      workspace: Workspace {
+        id: row.id,
+        name: row.name,
+        description: row.description,
+        owner_id: row.owner_id,
+        created_by: row.created_by,
+        updated_by: row.updated_by,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+      },
+      user_role: row.role,
+      owner_name: row.owner_name,
+    })
+    .collect();
+
+    Ok(workspaces)
+  }
+
+  async fn attach_workspace(&self, organization_id: Uuid, workspace_id: Uuid) -> AppResult<bool> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!(
+      r#"
+        UPDATE workspaces
+        SET organization_id = $1
+        WHERE id = $2 AND (organization_id IS NULL OR organization_id = $1)
+        "#,
+      organization_id,
+      workspace_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  async fn detach_workspace(&self, organization_id: Uuid, workspace_id: Uuid) -> AppResult<bool> {
+    let mut conn = self.db.acquire().await?;
+    let mut tx = conn.begin().await?;
+
+    let result = sqlx::query!(
+      "UPDATE workspaces SET organization_id = NULL WHERE id = $1 AND organization_id = $2",
+      workspace_id,
+      organization_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+      "UPDATE organizations SET billing_workspace_id = NULL WHERE id = $1 AND billing_workspace_id = $2",
+      organization_id,
+      workspace_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(result.rows_affected() > 0)
+  }
+
+  async fn set_billing_workspace(&self, organization_id: Uuid, workspace_id: Option<Uuid>) -> AppResult<Option<Organization>> {
+    let mut conn = self.db.acquire().await?;
+    let organization = sqlx::query_as!(
+      Organization,
+      r#"
+        UPDATE organizations o
+        SET billing_workspace_id = $2
+        WHERE o.id = $1
+          AND ($2::UUID IS NULL OR EXISTS (SELECT 1 FROM workspaces w WHERE w.id = $2 AND w.organization_id = o.id))
+        RETURNING id, name, description, owner_id, billing_workspace_id, created_by, updated_by, created_at, updated_at
+        "#,
+      organization_id,
+      workspace_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(organization)
+  }
+
+  async fn billing(&self, organization_id: Uuid) -> AppResult<Vec<WorkspaceBilling>> {
+    let mut conn = self.db.acquire().await?;
+    let workspaces = sqlx::query_as!(
+      WorkspaceBilling,
+      r#"
+        SELECT workspace_id as "workspace_id!", name as "name!",
+               COALESCE(billed IN ('active', 'past_due'), false) as "paid!",
+               COALESCE(billed IN ('active', 'past_due') AND own IS DISTINCT FROM billed, false) as "covered_by_organization!"
+        FROM (
+          SELECT w.id as workspace_id, w.name, billed_subscription_status(w.id) as billed,
+                 (SELECT s.status FROM workspace_subscriptions s WHERE s.workspace_id = w.id) as own
+          FROM workspaces w
+          WHERE w.organization_id = $1
+        ) statuses
+        ORDER BY name, workspace_id
+        "#,
+      organization_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(workspaces)
+  }
+}
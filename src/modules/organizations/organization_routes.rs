@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  routing::{delete, get, post, put},
+};
+
+use super::organization_handlers::{
+  add_member, attach_workspace, create_organization, delete_organization, detach_workspace, get_billing, get_organization, list_members,
+  list_organizations, list_workspaces, remove_member, set_billing_workspace, switch_organization, update_member, update_organization,
+};
+use crate::state::AppState;
+
+/// Organization routes, mounted at `/api/v1/organizations` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/", post(create_organization))
+    .route("/", get(list_organizations))
+    .route("/:organization_id", get(get_organization))
+    .route("/:organization_id", put(update_organization))
+    .route("/:organization_id", delete(delete_organization))
+    // Organization switcher
+    .route("/:organization_id/switch", post(switch_organization))
+    // Members
+    .route("/:organization_id/members", get(list_members))
+    .route("/:organization_id/members", post(add_member))
+    .route("/:organization_id/members/:user_id", put(update_member))
+    .route("/:organization_id/members/:user_id", delete(remove_member))
+    // Workspaces
+    .route("/:organization_id/workspaces", get(list_workspaces))
+    .route("/:organization_id/workspaces", post(attach_workspace))
+    .route("/:organization_id/workspaces/:workspace_id", delete(detach_workspace))
+    // Consolidated billing
+    .route("/:organization_id/billing", get(get_billing))
+    .route("/:organization_id/billing", put(set_billing_workspace))
+}
@@ -0,0 +1,10 @@
+//! The overview of every workspace a user belongs to, in one call.
+//!
+//! `GET /api/v1/overview` lists the key numbers of each of the caller's workspaces, and their
+//! totals, so users of several workspaces need not switch between them to see what needs their
+//! attention. Each number is only given where the caller's role can read the data behind it.
+
+pub mod overview_handlers;
+pub mod overview_models;
+pub mod overview_repository;
+pub mod overview_routes;
@@ -0,0 +1,15 @@
+use std::sync::Arc;
+
+use axum::{extract::State, response::Json};
+use chrono::Utc;
+
+use super::overview_models::OverviewResponse;
+use crate::{AppResult, modules::auth::current_user::CurrentUser, responses::ApiResponse, state::AppState};
+
+/// The key numbers of every workspace the caller belongs to, with their totals.
+pub async fn get_overview(State(state): State<Arc<AppState>>, current_user: CurrentUser) -> AppResult<Json<ApiResponse<OverviewResponse>>> {
+  let workspaces = state.overview_repository.workspace_overviews(current_user.user_id).await?;
+
+  let response = ApiResponse::success(OverviewResponse::new(workspaces, Utc::now()), "Overview retrieved successfully");
+  Ok(Json(response))
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::modules::datastores::workspaces::WorkspaceRole;
+
+/// The key numbers of one workspace of the caller.
+#[derive(Debug, Serialize)]
+pub struct WorkspaceOverview {
+  pub workspace_id: Uuid,
+  pub name: String,
+  pub role: WorkspaceRole,
+  /// Active products tracking inventory at or below their reorder level. `None` for viewers,
+  /// who cannot read products.
+  pub low_stock_products: Option<i64>,
+  /// Whether the workspace's subscription has an unpaid invoice.
+  pub payment_overdue: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverviewTotals {
+  pub workspaces: usize,
+  /// Low stock products of the workspaces where the caller can read products.
+  pub low_stock_products: i64,
+  pub workspaces_payment_overdue: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverviewResponse {
+  pub generated_at: DateTime<Utc>,
+  pub totals: OverviewTotals,
+  /// By workspace name, like `GET /api/v1/workspaces`.
+  pub workspaces: Vec<WorkspaceOverview>,
+}
+
+impl OverviewResponse {
+  pub fn new(workspaces: Vec<WorkspaceOverview>, generated_at: DateTime<Utc>) -> Self {
+    let totals = OverviewTotals {
+      workspaces: workspaces.len(),
+      low_stock_products: workspaces.iter().filter_map(|workspace| workspace.low_stock_products).sum(),
+      workspaces_payment_overdue: workspaces.iter().filter(|workspace| workspace.payment_overdue).count(),
+    };
+    Self {
+      generated_at,
+      totals,
+      workspaces,
+    }
+  }
+}
@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::overview_models::WorkspaceOverview;
+use crate::{
+  errors::AppError,
+  modules::datastores::workspaces::WorkspaceRole,
+  utils::{DbExecutor, ReadPool, tenant_schema},
+};
+
+#[async_trait]
+pub trait OverviewRepository: Send + Sync {
+  /// The key numbers of every workspace `user_id` belongs to, by workspace name.
+  async fn workspace_overviews(&self, user_id: Uuid) -> Result<Vec<WorkspaceOverview>, AppError>;
+}
+
+pub struct PostgresOverviewRepository {
+  read_pool: ReadPool,
+}
+
+impl PostgresOverviewRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self {
+      read_pool: ReadPool::primary_only(db),
+    }
+  }
+
+  /// Counts on `read_pool` instead of the primary; an overview can do with figures a little behind.
+  pub fn with_read_pool(mut self, read_pool: ReadPool) -> Self {
+    self.read_pool = read_pool;
+    self
+  }
+}
+
+#[async_trait]
+impl OverviewRepository for PostgresOverviewRepository {
+  async fn workspace_overviews(&self, user_id: Uuid) -> Result<Vec<WorkspaceOverview>, AppError> {
+    let mut conn = self.read_pool.acquire().await?;
+    let rows = sqlx::query!(
+      r#"
+        SELECT
+          w.id AS workspace_id,
+          w.name,
+          wu.role AS "role!: WorkspaceRole",
+          (
+            SELECT COUNT(*) FROM products p
+            WHERE p.workspace_id = w.id
+              AND p.deleted_at IS NULL
+              AND p.is_active
+              AND p.track_inventory
+              AND p.current_stock <= p.reorder_level
+          ) AS "low_stock_products!",
+          COALESCE(billed_subscription_status(w.id) = 'past_due', false) AS "payment_overdue!"
+        FROM workspace_access wu
+        JOIN workspaces w ON w.id = wu.workspace_id
+        WHERE wu.user_id = $1
+        ORDER BY w.name, w.id
+        "#,
+      user_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut overviews: Vec<_> = rows
+      .into_iter()
+      .map(|row| WorkspaceOverview {
+        workspace_id: row.workspace_id,
+        name: row.name,
+        role: row.role,
+        // Product reads need the member role
+        low_stock_products: row.role.includes(WorkspaceRole::Member).then_some(row.low_stock_products),
+        payment_overdue: row.payment_overdue,
+      })
+      .collect();
+
+    // The products of isolated workspaces are in their own schema, not among those counted above
+    let isolated = tenant_schema::isolated_workspaces(&mut conn).await?;
+    let mut counted: Vec<_> = overviews
+      .iter_mut()
+      .filter(|overview| overview.low_stock_products.is_some() && isolated.contains(&overview.workspace_id))
+      .collect();
+    let workspaces = counted.iter().map(|overview| overview.workspace_id).collect();
+    let counts = tenant_schema::for_each_workspace(&mut conn, workspaces, |conn, _| {
+      Box::pin(
+        sqlx::query_scalar!(
+          r#"
+            SELECT COUNT(*) AS "count!" FROM products
+            WHERE deleted_at IS NULL AND is_active AND track_inventory AND current_stock <= reorder_level
+            "#
+        )
+        .fetch_one(conn),
+      )
+    })
+    .await?;
+    for (overview, count) in counted.iter_mut().zip(counts) {
+      overview.low_stock_products = Some(count);
+    }
+
+    Ok(overviews)
+  }
+}
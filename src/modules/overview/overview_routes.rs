@@ -0,0 +1,11 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+
+use super::overview_handlers::get_overview;
+use crate::state::AppState;
+
+/// The overview of the caller's workspaces, mounted at `/api/v1/overview` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new().route("/", get(get_overview))
+}
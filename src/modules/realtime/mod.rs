@@ -0,0 +1,2 @@
+pub mod realtime_handlers;
+pub mod realtime_routes;
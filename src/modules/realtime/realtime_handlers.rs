@@ -0,0 +1,250 @@
+use std::{collections::VecDeque, convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::{
+  extract::{
+    ConnectInfo, Query, State,
+    rejection::QueryRejection,
+    ws::{Message, WebSocket, WebSocketUpgrade},
+  },
+  http::{HeaderMap, header::AUTHORIZATION},
+  response::{
+    Response,
+    sse::{Event, KeepAlive, Sse},
+  },
+};
+use futures_util::{Stream, stream};
+use serde::Deserialize;
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+use uuid::Uuid;
+
+use crate::{
+  AppResult, AppState,
+  errors::AppError,
+  events::{Replay, WorkspaceEvent, resync_payload},
+  helper::OptionalWorkspace,
+  middleware::{access_log::record_caller, rate_limit::client_ip},
+  modules::auth::jwt_middleware::authenticate_workspace_member,
+};
+
+/// Connection parameters for clients that cannot send headers, such as browser WebSockets and `EventSource`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RealtimeParams {
+  /// Access token, used when no `Authorization` header is present.
+  pub token: Option<String>,
+  /// Workspace to subscribe to, used when no `X-Workspace-ID` header is present.
+  pub workspace_id: Option<Uuid>,
+  /// Resume after this event id, used when no `Last-Event-ID` header is present.
+  pub last_event_id: Option<u64>,
+}
+
+/// Upgrades the connection to a WebSocket that pushes the events of one workspace.
+///
+/// Every event is sent as a JSON text frame (see `WorkspaceEvent`). When the client falls too far
+/// behind, a `{"event": "resync", "missed": n}` frame is sent instead of the dropped events and
+/// the client should refetch the data it displays. Messages from the client are ignored.
+///
+/// # Arguments
+///
+/// * `ws`: The WebSocket upgrade request.
+/// * `State(state)`: The shared application state.
+/// * `headers`: The request headers, checked for `Authorization` and `Last-Event-ID`.
+/// * `OptionalWorkspace(header_workspace)`: The `X-Workspace-ID` header, if sent.
+/// * `connect_info`: The peer address, checked with the headers against the workspace's IP allowlist.
+/// * `params`: The `token`, `workspace_id` and `last_event_id` query parameters, used when the headers are missing.
+///
+/// # Returns
+///
+/// A `101 Switching Protocols` response, or an authentication error before the upgrade.
+pub async fn ws_handler(
+  ws: WebSocketUpgrade,
+  State(state): State<Arc<AppState>>,
+  headers: HeaderMap,
+  OptionalWorkspace(header_workspace): OptionalWorkspace,
+  connect_info: Option<ConnectInfo<SocketAddr>>,
+  params: Result<Query<RealtimeParams>, QueryRejection>,
+) -> AppResult<Response> {
+  let Query(params) = params?;
+  let (user_id, workspace_id) = authenticate_connection(&state, &headers, header_workspace, connect_info, &params).await?;
+
+  // Subscribe before upgrading so no event published during the handshake is missed
+  let replay = state.events.subscribe_from(last_event_id(&headers, &params)?);
+  let heartbeat = Duration::from_secs(state.config.realtime.heartbeat_secs);
+
+  tracing::debug!("WebSocket connected for user {} in workspace {}", user_id, workspace_id);
+  Ok(ws.on_upgrade(move |socket| stream_events(socket, replay, user_id, workspace_id, heartbeat)))
+}
+
+/// Streams the events of one workspace as Server-Sent Events, for clients that cannot use WebSockets.
+///
+/// Each event carries its `id` and uses the event name (e.g. `contact.created`) as the SSE event
+/// type, with the `WorkspaceEvent` as JSON data. A reconnecting client sends `Last-Event-ID` and
+/// receives the retained events it missed; if some are no longer available a `resync` event is
+/// sent first and the client should refetch the data it displays.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `headers`: The request headers, checked for `Authorization` and `Last-Event-ID`.
+/// * `OptionalWorkspace(header_workspace)`: The `X-Workspace-ID` header, if sent.
+/// * `connect_info`: The peer address, checked with the headers against the workspace's IP allowlist.
+/// * `params`: The `token`, `workspace_id` and `last_event_id` query parameters, used when the headers are missing.
+///
+/// # Returns
+///
+/// A `text/event-stream` response that stays open until the client disconnects.
+pub async fn sse_handler(
+  State(state): State<Arc<AppState>>,
+  headers: HeaderMap,
+  OptionalWorkspace(header_workspace): OptionalWorkspace,
+  connect_info: Option<ConnectInfo<SocketAddr>>,
+  params: Result<Query<RealtimeParams>, QueryRejection>,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+  let Query(params) = params?;
+  let (user_id, workspace_id) = authenticate_connection(&state, &headers, header_workspace, connect_info, &params).await?;
+
+  let replay = state.events.subscribe_from(last_event_id(&headers, &params)?);
+  let heartbeat = Duration::from_secs(state.config.realtime.heartbeat_secs);
+
+  tracing::debug!("Event stream opened for user {} in workspace {}", user_id, workspace_id);
+  Ok(Sse::new(sse_events(replay, user_id, workspace_id)).keep_alive(KeepAlive::new().interval(heartbeat)))
+}
+
+/// Resolves the caller from the headers, falling back to the query parameters. Connections are
+/// made outside of `ip_allowlist_middleware`, so the client address is checked here.
+pub(crate) async fn authenticate_connection(
+  state: &AppState,
+  headers: &HeaderMap,
+  header_workspace: Option<Uuid>,
+  connect_info: Option<ConnectInfo<SocketAddr>>,
+  params: &RealtimeParams,
+) -> AppResult<(Uuid, Uuid)> {
+  let header_token = headers
+    .get(AUTHORIZATION)
+    .and_then(|value| value.to_str().ok())
+    .map(|value| value.strip_prefix("Bearer ").ok_or(AppError::invalid_token()))
+    .transpose()?;
+  let token = header_token.or(params.token.as_deref()).ok_or(AppError::missing_token())?;
+
+  let workspace_id = header_workspace
+    .or(params.workspace_id)
+    .ok_or_else(|| AppError::BadRequest("X-Workspace-ID header or workspace_id parameter is required".to_string()))?;
+
+  let peer = connect_info.map(|ConnectInfo(addr)| addr);
+  let ip = client_ip(headers, peer, &state.config.server.trusted_proxies);
+  let (user_id, _) = authenticate_workspace_member(state, token, workspace_id, ip).await?;
+  record_caller(user_id, Some(workspace_id));
+  Ok((user_id, workspace_id))
+}
+
+/// Reads the id to resume after from `Last-Event-ID`, falling back to the `last_event_id` parameter.
+fn last_event_id(headers: &HeaderMap, params: &RealtimeParams) -> AppResult<Option<u64>> {
+  match headers.get("Last-Event-ID") {
+    Some(value) => value
+      .to_str()
+      .ok()
+      .and_then(|value| value.trim().parse().ok())
+      .map(Some)
+      .ok_or_else(|| AppError::BadRequest("Last-Event-ID must be an event id".to_string())),
+    None => Ok(params.last_event_id),
+  }
+}
+
+/// Next item of a workspace feed: replayed events first, then live ones.
+enum FeedItem {
+  Event(WorkspaceEvent),
+  Resync(Option<u64>),
+}
+
+struct Feed {
+  resync: Option<Option<u64>>,
+  backlog: VecDeque<WorkspaceEvent>,
+  receiver: Receiver<WorkspaceEvent>,
+  user_id: Uuid,
+  workspace_id: Uuid,
+}
+
+impl Feed {
+  fn new(replay: Replay, user_id: Uuid, workspace_id: Uuid) -> Self {
+    Self {
+      resync: replay.incomplete.then_some(None),
+      backlog: replay.events.into(),
+      receiver: replay.receiver,
+      user_id,
+      workspace_id,
+    }
+  }
+
+  /// Waits for the next item visible to the client; `None` once the bus is closed.
+  async fn next(&mut self) -> Option<FeedItem> {
+    if let Some(missed) = self.resync.take() {
+      return Some(FeedItem::Resync(missed));
+    }
+
+    loop {
+      let event = match self.backlog.pop_front() {
+        Some(event) => event,
+        None => match self.receiver.recv().await {
+          Ok(event) => event,
+          Err(RecvError::Lagged(missed)) => return Some(FeedItem::Resync(Some(missed))),
+          Err(RecvError::Closed) => return None,
+        },
+      };
+
+      if event.is_visible_to(self.workspace_id, self.user_id) {
+        return Some(FeedItem::Event(event));
+      }
+    }
+  }
+}
+
+fn sse_events(replay: Replay, user_id: Uuid, workspace_id: Uuid) -> impl Stream<Item = Result<Event, Infallible>> {
+  stream::unfold(Feed::new(replay, user_id, workspace_id), |mut feed| async move {
+    loop {
+      let event = match feed.next().await? {
+        FeedItem::Event(event) => match Event::default().id(event.id.to_string()).event(event.event.as_str()).json_data(&event) {
+          Ok(sse_event) => sse_event,
+          Err(e) => {
+            tracing::error!("Failed to serialize event {}: {}", event.id, e);
+            continue;
+          }
+        },
+        FeedItem::Resync(missed) => Event::default().event("resync").data(resync_payload(missed).to_string()),
+      };
+      return Some((Ok(event), feed));
+    }
+  })
+}
+
+async fn stream_events(mut socket: WebSocket, replay: Replay, user_id: Uuid, workspace_id: Uuid, heartbeat: Duration) {
+  let mut feed = Feed::new(replay, user_id, workspace_id);
+  let mut heartbeat = tokio::time::interval_at(tokio::time::Instant::now() + heartbeat, heartbeat);
+
+  loop {
+    let outgoing = tokio::select! {
+      item = feed.next() => match item {
+        Some(FeedItem::Event(event)) => match serde_json::to_string(&event) {
+          Ok(text) => Message::Text(text),
+          Err(e) => {
+            tracing::error!("Failed to serialize event {}: {}", event.id, e);
+            continue;
+          }
+        },
+        Some(FeedItem::Resync(missed)) => Message::Text(resync_payload(missed).to_string()),
+        None => break,
+      },
+      incoming = socket.recv() => match incoming {
+        // Pings are answered by the protocol layer; other client messages are ignored
+        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+        Some(Ok(_)) => continue,
+      },
+      _ = heartbeat.tick() => Message::Ping(Vec::new()),
+    };
+
+    if socket.send(outgoing).await.is_err() {
+      break;
+    }
+  }
+
+  tracing::debug!("WebSocket closed for user {} in workspace {}", user_id, workspace_id);
+}
@@ -0,0 +1,19 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+
+use crate::{AppState, modules::realtime::realtime_handlers};
+
+/// WebSocket route, mounted at `/api/v1/ws`.
+///
+/// It authenticates the connection itself, because browsers cannot set an `Authorization` header
+/// on WebSocket handshakes, so it is mounted outside the JWT middleware.
+pub fn ws_router() -> Router<Arc<AppState>> {
+  Router::new().route("/", get(realtime_handlers::ws_handler))
+}
+
+/// Server-Sent Events route, mounted at `/api/v1/events`. Like the WebSocket route it
+/// authenticates the connection itself, since `EventSource` cannot send custom headers.
+pub fn events_router() -> Router<Arc<AppState>> {
+  Router::new().route("/", get(realtime_handlers::sse_handler))
+}
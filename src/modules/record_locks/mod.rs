@@ -0,0 +1,12 @@
+//! Soft locks on the records being edited, so two members editing the same record are warned.
+//!
+//! A member opening a contact or product for editing takes its lock with `POST .../:id/lock` and
+//! keeps it by posting again before it expires (`RECORD_LOCK_TTL_SECS`). Taking a lock held by
+//! another member fails with a 409 naming when it expires, unless an admin overrides it with
+//! `?force=true`. Locks are published on the events stream as `product.locked` and
+//! `product.unlocked` (or `contact.…`) so open editors can warn their users. Locks do not block
+//! writes: a member ignoring the warning still saves, and a lock nobody renews simply expires.
+
+pub mod record_lock_handlers;
+pub mod record_lock_models;
+pub mod record_lock_repository;
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use uuid::Uuid;
+
+use super::record_lock_models::{LockQuery, RecordLock};
+use crate::{
+  AppResult,
+  errors::AppError,
+  events::WorkspaceEvent,
+  helper::{PathUuid, RequireRole, RequiredWorkspace, ValidatedQuery, workspace::role::Member},
+  modules::{auth::current_user::CurrentUser, datastores::workspaces::WorkspaceRole},
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// A kind of record that can be locked: its table, its event resource and its name in messages.
+#[derive(Clone, Copy)]
+struct Lockable {
+  entity: &'static str,
+  resource: &'static str,
+  label: &'static str,
+}
+
+#[cfg(feature = "contacts")]
+const CONTACT: Lockable = Lockable {
+  entity: "contacts",
+  resource: "contact",
+  label: "Contact",
+};
+#[cfg(feature = "products")]
+const PRODUCT: Lockable = Lockable {
+  entity: "products",
+  resource: "product",
+  label: "Product",
+};
+
+async fn record_exists(state: &AppState, kind: Lockable, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<bool> {
+  match kind.entity {
+    #[cfg(feature = "contacts")]
+    "contacts" => Ok(
+      state
+        .contact_repository
+        .find_by_id_and_workspace(id, workspace_id, user_id)
+        .await?
+        .is_some(),
+    ),
+    #[cfg(feature = "products")]
+    "products" => Ok(
+      state
+        .product_repository
+        .find_by_id_and_workspace(id, workspace_id, user_id)
+        .await?
+        .is_some(),
+    ),
+    _ => Ok(false),
+  }
+}
+
+async fn lock(
+  state: &AppState,
+  kind: Lockable,
+  id: Uuid,
+  workspace_id: Uuid,
+  user_id: Uuid,
+  role: WorkspaceRole,
+  force: bool,
+) -> AppResult<RecordLock> {
+  if force && role != WorkspaceRole::Admin {
+    return Err(AppError::Authorization(
+      "Only admins can take over the lock of another member".to_string(),
+    ));
+  }
+  if !record_exists(state, kind, id, workspace_id, user_id).await? {
+    return Err(AppError::not_found_with_id(kind.label, id));
+  }
+
+  let ttl_secs = state.config.realtime.lock_ttl_secs;
+  let Some(lock) = state
+    .record_lock_repository
+    .acquire(workspace_id, kind.entity, id, user_id, ttl_secs, force)
+    .await?
+  else {
+    // Held by another member; the lock may have expired since, in which case locking again succeeds
+    let holder = state.record_lock_repository.get(workspace_id, kind.entity, id).await?;
+    let until = holder.map(|lock| format!(" until {}", lock.expires_at.to_rfc3339())).unwrap_or_default();
+    return Err(AppError::Conflict(format!(
+      "{} {} is being edited by another member{}",
+      kind.label, id, until
+    )));
+  };
+
+  state
+    .events
+    .publish(WorkspaceEvent::lock(kind.resource, true, workspace_id, id, user_id, &lock));
+  Ok(lock)
+}
+
+async fn unlock(state: &AppState, kind: Lockable, id: Uuid, workspace_id: Uuid, user_id: Uuid, role: WorkspaceRole) -> AppResult<()> {
+  let locked_by = (role != WorkspaceRole::Admin).then_some(user_id);
+  let Some(lock) = state.record_lock_repository.release(workspace_id, kind.entity, id, locked_by).await? else {
+    return Err(AppError::not_found_with_id(&format!("{} lock", kind.label), id));
+  };
+
+  state
+    .events
+    .publish(WorkspaceEvent::lock(kind.resource, false, workspace_id, id, user_id, &lock));
+  Ok(())
+}
+
+/// Takes or renews the lock on a product being edited, so other members are warned.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `PathUuid(id)`: The UUID of the product.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; only admins may `force`.
+/// * `ValidatedQuery(query)`: `force=true` takes over the lock of another member.
+///
+/// # Returns
+///
+/// A `Json` response with the lock, or a 409 when another member holds it.
+#[cfg(feature = "products")]
+pub async fn lock_product(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  member: RequireRole<Member>,
+  ValidatedQuery(query): ValidatedQuery<LockQuery>,
+) -> AppResult<Json<ApiResponse<RecordLock>>> {
+  let lock = lock(&state, PRODUCT, id, workspace_id, current_user.user_id, member.role, query.force).await?;
+
+  let response = ApiResponse::success(lock, "Product locked successfully");
+  Ok(Json(response))
+}
+
+/// Releases the lock on a product. Members release their own lock, admins any lock.
+#[cfg(feature = "products")]
+pub async fn unlock_product(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  member: RequireRole<Member>,
+) -> AppResult<Json<ApiResponse<()>>> {
+  unlock(&state, PRODUCT, id, workspace_id, current_user.user_id, member.role).await?;
+
+  let response = ApiResponse::success((), "Product unlocked successfully");
+  Ok(Json(response))
+}
+
+/// Takes or renews the lock on a contact being edited, like `lock_product`.
+#[cfg(feature = "contacts")]
+pub async fn lock_contact(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  member: RequireRole<Member>,
+  ValidatedQuery(query): ValidatedQuery<LockQuery>,
+) -> AppResult<Json<ApiResponse<RecordLock>>> {
+  let lock = lock(&state, CONTACT, id, workspace_id, current_user.user_id, member.role, query.force).await?;
+
+  let response = ApiResponse::success(lock, "Contact locked successfully");
+  Ok(Json(response))
+}
+
+/// Releases the lock on a contact. Members release their own lock, admins any lock.
+#[cfg(feature = "contacts")]
+pub async fn unlock_contact(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  member: RequireRole<Member>,
+) -> AppResult<Json<ApiResponse<()>>> {
+  unlock(&state, CONTACT, id, workspace_id, current_user.user_id, member.role).await?;
+
+  let response = ApiResponse::success((), "Contact unlocked successfully");
+  Ok(Json(response))
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// The member editing a record, until the lock expires.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct RecordLock {
+  pub workspace_id: Uuid,
+  /// `contacts` or `products`.
+  pub entity: String,
+  pub record_id: Uuid,
+  pub locked_by: Uuid,
+  pub locked_at: DateTime<Utc>,
+  pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct LockQuery {
+  /// Takes over a lock held by another member; admins only.
+  #[serde(default)]
+  pub force: bool,
+}
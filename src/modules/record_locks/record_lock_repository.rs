@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::record_lock_models::RecordLock;
+use crate::{AppResult, utils::DbExecutor};
+
+#[async_trait]
+pub trait RecordLockRepository: Send + Sync {
+  /// Locks the record for `user_id` for `ttl_secs`, or extends the lock the user already holds.
+  /// `None` when another member holds an unexpired lock, unless `force` takes it over.
+  async fn acquire(
+    &self,
+    workspace_id: Uuid,
+    entity: &str,
+    record_id: Uuid,
+    user_id: Uuid,
+    ttl_secs: u64,
+    force: bool,
+  ) -> AppResult<Option<RecordLock>>;
+  /// The unexpired lock on the record, if any.
+  async fn get(&self, workspace_id: Uuid, entity: &str, record_id: Uuid) -> AppResult<Option<RecordLock>>;
+  /// Removes the unexpired lock on the record, only when held by `locked_by` if given.
+  async fn release(&self, workspace_id: Uuid, entity: &str, record_id: Uuid, locked_by: Option<Uuid>) -> AppResult<Option<RecordLock>>;
+}
+
+pub struct PostgresRecordLockRepository {
+  db: DbExecutor,
+}
+
+impl PostgresRecordLockRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl RecordLockRepository for PostgresRecordLockRepository {
+  async fn acquire(
+    &self,
+    workspace_id: Uuid,
+    entity: &str,
+    record_id: Uuid,
+    user_id: Uuid,
+    ttl_secs: u64,
+    force: bool,
+  ) -> AppResult<Option<RecordLock>> {
+    let mut conn = self.db.acquire().await?;
+    // A renewed lock keeps the time it was first taken
+    let lock = sqlx::query_as!(
+      RecordLock,
+      r#"
+        INSERT INTO record_locks (workspace_id, entity, record_id, locked_by, expires_at)
+        VALUES ($1, $2, $3, $4, NOW() + make_interval(secs => $5))
+        ON CONFLICT (workspace_id, entity, record_id) DO UPDATE
+        SET locked_by = EXCLUDED.locked_by,
+          locked_at = CASE
+            WHEN record_locks.locked_by = EXCLUDED.locked_by AND record_locks.expires_at > NOW() THEN record_locks.locked_at
+            ELSE NOW()
+          END,
+          expires_at = EXCLUDED.expires_at
+        WHERE record_locks.locked_by = EXCLUDED.locked_by OR record_locks.expires_at <= NOW() OR $6
+        RETURNING workspace_id, entity, record_id, locked_by, locked_at, expires_at
+        "#,
+      workspace_id,
+      entity,
+      record_id,
+      user_id,
+      ttl_secs as f64,
+      force
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(lock)
+  }
+
+  async fn get(&self, workspace_id: Uuid, entity: &str, record_id: Uuid) -> AppResult<Option<RecordLock>> {
+    let mut conn = self.db.acquire().await?;
+    let lock = sqlx::query_as!(
+      RecordLock,
+      r#"
+        SELECT workspace_id, entity, record_id, locked_by, locked_at, expires_at
+        FROM record_locks
+        WHERE workspace_id = $1 AND entity = $2 AND record_id = $3 AND expires_at > NOW()
+        "#,
+      workspace_id,
+      entity,
+      record_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(lock)
+  }
+
+  async fn release(&self, workspace_id: Uuid, entity: &str, record_id: Uuid, locked_by: Option<Uuid>) -> AppResult<Option<RecordLock>> {
+    let mut conn = self.db.acquire().await?;
+    let lock = sqlx::query_as!(
+      RecordLock,
+      r#"
+        DELETE FROM record_locks
+        WHERE workspace_id = $1 AND entity = $2 AND record_id = $3 AND expires_at > NOW()
+          AND ($4::UUID IS NULL OR locked_by = $4)
+        RETURNING workspace_id, entity, record_id, locked_by, locked_at, expires_at
+        "#,
+      workspace_id,
+      entity,
+      record_id,
+      locked_by
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(lock)
+  }
+}
@@ -0,0 +1,46 @@
+use barcoders::sym::code128::Code128;
+
+/// Blank modules on each side of a barcode, which scanners need to find its start and end.
+const QUIET_ZONE: usize = 10;
+
+/// A Code 128 barcode drawn as one SVG path, in modules (the width of the thinnest bar) across
+/// and one unit high, to be stretched over the space it is printed in.
+pub struct Barcode {
+  /// Width of the barcode with its quiet zones, in modules.
+  pub width: usize,
+  pub path: String,
+}
+
+impl Barcode {
+  /// Encodes `value`, `None` unless it is printable ASCII. Values of digits only use the denser
+  /// character set C when their length allows it.
+  pub fn code128(value: &str) -> Option<Self> {
+    if value.is_empty() || !value.bytes().all(|b| (b' '..=b'~').contains(&b)) {
+      return None;
+    }
+    let set = if value.len().is_multiple_of(2) && value.bytes().all(|b| b.is_ascii_digit()) {
+      'Ć'
+    } else {
+      'Ɓ'
+    };
+    let modules = Code128::new(format!("{}{}", set, value)).ok()?.encode();
+
+    let mut path = String::new();
+    let mut x = 0;
+    for (bar, run) in runs(&modules) {
+      if bar {
+        path.push_str(&format!("M{} 0h{}v1h-{}z", QUIET_ZONE + x, run, run));
+      }
+      x += run;
+    }
+    Some(Self {
+      width: modules.len() + 2 * QUIET_ZONE,
+      path,
+    })
+  }
+}
+
+/// Splits modules into runs of bars and spaces.
+fn runs(modules: &[u8]) -> impl Iterator<Item = (bool, usize)> + '_ {
+  modules.chunk_by(|a, b| a == b).map(|run| (run[0] == 1, run.len()))
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Most products on one sheet.
+pub const MAX_LABEL_ITEMS: u64 = 50;
+/// Most labels on one sheet, across its products. The sheet must stay small enough to print
+/// (see `pdf_renderer::MAX_HTML_BYTES`).
+pub const MAX_LABELS: u32 = 200;
+
+/// The body of `POST /products/labels`: how many labels of which products, and their size. Labels
+/// are laid out on A4 pages in the order of the items.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct LabelSheetRequest {
+  #[validate(length(min = 1, max = MAX_LABEL_ITEMS, message = "Labels of between 1 and 50 products can be printed at once"))]
+  pub items: Vec<LabelItem>,
+  #[serde(default = "default_width_mm")]
+  #[validate(range(min = 20, max = 200, message = "Labels must be between 20 and 200 mm wide"))]
+  pub width_mm: u32,
+  #[serde(default = "default_height_mm")]
+  #[validate(range(min = 15, max = 280, message = "Labels must be between 15 and 280 mm high"))]
+  pub height_mm: u32,
+  /// Whether labels show the selling price.
+  #[serde(default = "default_show_price")]
+  pub show_price: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LabelItem {
+  pub product_id: Uuid,
+  pub quantity: u32,
+}
+
+fn default_width_mm() -> u32 {
+  50
+}
+
+fn default_height_mm() -> u32 {
+  30
+}
+
+fn default_show_price() -> bool {
+  true
+}
+
+/// A product as printed on its labels.
+#[derive(Debug, Serialize)]
+pub struct Label {
+  pub name: String,
+  pub code: String,
+  /// The encoded value: the barcode of the product, else its SKU, else its code.
+  pub value: String,
+  pub price: Option<String>,
+  /// The id of the barcode's SVG symbol on the sheet.
+  pub symbol: String,
+}
@@ -0,0 +1,87 @@
+use std::sync::LazyLock;
+
+use handlebars::Handlebars;
+use serde_json::json;
+
+use super::{
+  barcode::Barcode,
+  label_models::{Label, LabelSheetRequest},
+};
+use crate::{AppResult, errors::AppError, internal_error, modules::datastores::products::product_models::Product, state::AppState};
+
+/// Renders with HTML escaping, so product names cannot inject markup.
+static HANDLEBARS: LazyLock<Handlebars<'static>> = LazyLock::new(Handlebars::new);
+
+/// Prints `quantity` labels of each product on A4 sheets. Each barcode is drawn once as an SVG
+/// symbol that its labels reuse, which keeps long sheets small.
+pub async fn render_label_sheet(state: &AppState, request: &LabelSheetRequest, products: &[(&Product, u32)]) -> AppResult<Vec<u8>> {
+  let renderer = state
+    .pdf_renderer
+    .as_ref()
+    .ok_or_else(|| internal_error!("PDF rendering is not configured: PDF_CHROMIUM must be set"))?;
+
+  let mut symbols = Vec::with_capacity(products.len());
+  let mut labels = Vec::new();
+  for (index, (product, quantity)) in products.iter().enumerate() {
+    let value = [product.barcode.as_deref(), product.sku.as_deref()]
+      .into_iter()
+      .flatten()
+      .map(str::trim)
+      .find(|value| !value.is_empty())
+      .unwrap_or(&product.code);
+    let barcode = Barcode::code128(value).ok_or_else(|| {
+      AppError::validation(
+        "items",
+        &format!("The barcode '{}' of product {} is not printable ASCII", value, product.code),
+      )
+    })?;
+    let symbol = format!("b{}", index);
+    symbols.push(json!({ "id": symbol, "width": barcode.width, "path": barcode.path }));
+
+    let label = Label {
+      name: product.name.clone(),
+      code: product.code.clone(),
+      value: value.to_string(),
+      price: request.show_price.then(|| product.selling_price.round_dp(2).to_string()),
+      symbol,
+    };
+    labels.extend(std::iter::repeat_n(json!(label), *quantity as usize));
+  }
+
+  let data = json!({
+    "width_mm": request.width_mm,
+    "height_mm": request.height_mm,
+    "symbols": symbols,
+    "labels": labels,
+  });
+  let html = HANDLEBARS
+    .render_template(LABEL_SHEET_TEMPLATE, &data)
+    .map_err(|e| internal_error!("Failed to render label sheet: {}", e))?;
+  renderer.render(&html).await
+}
+
+const LABEL_SHEET_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+  @page { size: A4; margin: 5mm; }
+  body { margin: 0; font-family: sans-serif; }
+  .sheet { display: flex; flex-wrap: wrap; }
+  .l { box-sizing: border-box; width: {{width_mm}}mm; height: {{height_mm}}mm; padding: 1.5mm; border: 0.1mm dashed #bbb; display: flex; flex-direction: column; overflow: hidden; break-inside: avoid; }
+  .l b { font-size: 8pt; font-weight: bold; white-space: nowrap; overflow: hidden; text-overflow: ellipsis; }
+  .l svg { flex: 1; width: 100%; min-height: 0; margin: 0.5mm 0; }
+  .l i { font-style: normal; font-family: monospace; font-size: 7pt; text-align: center; }
+  .l p { margin: 0; display: flex; justify-content: space-between; font-size: 7pt; }
+</style>
+</head>
+<body>
+<svg width="0" height="0" style="position: absolute">
+{{#each symbols}}<symbol id="{{id}}" viewBox="0 0 {{width}} 1" preserveAspectRatio="none"><path d="{{path}}"/></symbol>
+{{/each}}</svg>
+<div class="sheet">
+{{#each labels}}<div class="l"><b>{{name}}</b><svg><use href="#{{symbol}}" width="100%" height="100%"/></svg><i>{{value}}</i><p><span>{{code}}</span>{{#if price}}<span>{{price}}</span>{{/if}}</p></div>
+{{/each}}</div>
+</body>
+</html>
+"##;
@@ -0,0 +1,20 @@
+//! PDF printing of records, compiled with the `rendering` feature.
+//!
+//! `GET /api/v1/contacts/{id}/pdf` and `GET /api/v1/products/{id}/pdf` render the record into an
+//! HTML template and print it with a headless Chromium (`PDF_CHROMIUM`). Each workspace may
+//! replace the built-in template of an entity under `/api/v1/print-templates/{entity}`; templates
+//! use Handlebars syntax with HTML escaping. The `statements` template prints the statements of
+//! contacts (see `modules::statements`).
+//!
+//! `POST /api/v1/products/labels` prints sheets of Code 128 barcode labels of products.
+
+pub mod barcode;
+pub mod label_models;
+#[cfg(feature = "products")]
+pub mod label_sheet;
+pub mod pdf_renderer;
+pub mod print_template_models;
+pub mod print_template_repository;
+pub mod rendering_handlers;
+pub mod rendering_routes;
+pub mod rendering_service;
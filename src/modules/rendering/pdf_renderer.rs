@@ -0,0 +1,98 @@
+use std::{path::PathBuf, time::Duration};
+
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::{AppResult, config::RenderingConfig, errors::AppError, internal_error};
+
+/// Largest HTML document printed. It is passed to the browser as a `data:` URL, which must fit
+/// in one command line argument.
+pub const MAX_HTML_BYTES: usize = 90 * 1024;
+
+/// Prints HTML documents to PDF.
+#[async_trait]
+pub trait PdfRenderer: Send + Sync {
+  async fn render(&self, html: &str) -> AppResult<Vec<u8>>;
+}
+
+/// Prints with a headless Chrome or Chromium.
+///
+/// The document is loaded from a `data:` URL rather than a file, so templates cannot pull files
+/// of the server into a PDF, and every request goes to an unreachable proxy, so they cannot
+/// reach the network either.
+pub struct ChromiumRenderer {
+  program: String,
+  args: Vec<String>,
+  timeout: Duration,
+}
+
+impl ChromiumRenderer {
+  /// The renderer configured by `config`, `None` while no browser is set.
+  pub fn from_config(config: &RenderingConfig) -> Option<Self> {
+    Some(Self {
+      program: config.chromium.clone()?,
+      args: config.chromium_args.clone(),
+      timeout: Duration::from_secs(config.timeout_secs),
+    })
+  }
+}
+
+/// A scratch file for a printed document, removed when dropped.
+struct PdfFile(PathBuf);
+
+impl PdfFile {
+  fn new() -> Self {
+    Self(std::env::temp_dir().join(format!("myapp-print-{}.pdf", Uuid::new_v4())))
+  }
+}
+
+impl Drop for PdfFile {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_file(&self.0);
+  }
+}
+
+#[async_trait]
+impl PdfRenderer for ChromiumRenderer {
+  async fn render(&self, html: &str) -> AppResult<Vec<u8>> {
+    if html.len() > MAX_HTML_BYTES {
+      return Err(AppError::validation(
+        "html",
+        &format!("The document is larger than the {} KiB that can be printed", MAX_HTML_BYTES / 1024),
+      ));
+    }
+
+    let pdf = PdfFile::new();
+    let output = Command::new(&self.program)
+      .args([
+        "--headless",
+        "--disable-gpu",
+        "--no-pdf-header-footer",
+        "--proxy-server=127.0.0.1:9",
+        "--proxy-bypass-list=<-loopback>",
+      ])
+      .arg(format!("--print-to-pdf={}", pdf.0.display()))
+      .args(&self.args)
+      .arg(format!("data:text/html;charset=utf-8;base64,{}", STANDARD.encode(html)))
+      .kill_on_drop(true)
+      .output();
+    let output = tokio::time::timeout(self.timeout, output)
+      .await
+      .map_err(|_| internal_error!("Printing a PDF took longer than {}s", self.timeout.as_secs()))?
+      .map_err(|e| internal_error!("Failed to run {}: {}", self.program, e))?;
+    if !output.status.success() {
+      return Err(internal_error!(
+        "{} exited with {}: {}",
+        self.program,
+        output.status,
+        String::from_utf8_lossy(&output.stderr).trim()
+      ));
+    }
+
+    tokio::fs::read(&pdf.0)
+      .await
+      .map_err(|e| internal_error!("{} did not print a PDF: {}", self.program, e))
+  }
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Longest template, in characters.
+pub const MAX_TEMPLATE_LENGTH: u64 = 32_000;
+
+/// Records that can be printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "print_entity", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PrintEntity {
+  Contacts,
+  Products,
+  /// Statements of contacts (see `modules::statements`).
+  Statements,
+}
+
+impl PrintEntity {
+  pub const ALL: &[PrintEntity] = &[PrintEntity::Contacts, PrintEntity::Products, PrintEntity::Statements];
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      PrintEntity::Contacts => "contacts",
+      PrintEntity::Products => "products",
+      PrintEntity::Statements => "statements",
+    }
+  }
+
+  pub fn parse(value: &str) -> Option<Self> {
+    Self::ALL.iter().copied().find(|entity| entity.as_str() == value)
+  }
+
+  /// The template printing the records of workspaces that have not customized it.
+  pub fn default_template(self) -> &'static str {
+    match self {
+      PrintEntity::Contacts => DEFAULT_CONTACT_TEMPLATE,
+      PrintEntity::Products => DEFAULT_PRODUCT_TEMPLATE,
+      PrintEntity::Statements => DEFAULT_STATEMENT_TEMPLATE,
+    }
+  }
+}
+
+/// A template customized by a workspace.
+#[derive(Debug, Clone, FromRow)]
+pub struct PrintTemplate {
+  pub workspace_id: Uuid,
+  pub entity: PrintEntity,
+  pub html: String,
+  pub updated_by: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// The template printing an entity in a workspace, customized or not.
+#[derive(Debug, Serialize)]
+pub struct PrintTemplateResponse {
+  pub entity: PrintEntity,
+  pub html: String,
+  /// Whether this is the built-in template, the workspace not having customized it.
+  pub is_default: bool,
+  pub updated_by: Option<Uuid>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl PrintTemplateResponse {
+  pub fn new(entity: PrintEntity, template: Option<PrintTemplate>) -> Self {
+    match template {
+      Some(template) => Self {
+        entity,
+        html: template.html,
+        is_default: false,
+        updated_by: template.updated_by,
+        updated_at: Some(template.updated_at),
+      },
+      None => Self {
+        entity,
+        html: entity.default_template().to_string(),
+        is_default: true,
+        updated_by: None,
+        updated_at: None,
+      },
+    }
+  }
+}
+
+/// An HTML template in Handlebars syntax. It is rendered with `record` (the record as returned by
+/// the API), `workspace` (its `id` and `name`) and `generated_at`.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct PrintTemplateRequest {
+  #[validate(length(min = 1, max = MAX_TEMPLATE_LENGTH, message = "Template must be between 1 and 32000 characters"))]
+  pub html: String,
+}
+
+const DEFAULT_CONTACT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+  body { font-family: sans-serif; font-size: 12px; margin: 32px; }
+  h1 { font-size: 20px; margin-bottom: 4px; }
+  table { border-collapse: collapse; width: 100%; margin-top: 16px; }
+  th { text-align: left; width: 30%; color: #555; }
+  th, td { padding: 6px 8px; border-bottom: 1px solid #ddd; }
+  footer { margin-top: 24px; color: #888; font-size: 10px; }
+</style>
+</head>
+<body>
+<h1>{{record.name}}</h1>
+<div>{{workspace.name}}</div>
+<table>
+  <tr><th>Code</th><td>{{record.code}}</td></tr>
+  <tr><th>Type</th><td>{{record.contact_type}}</td></tr>
+  <tr><th>Email</th><td>{{record.email}}</td></tr>
+  <tr><th>Position</th><td>{{record.position}}</td></tr>
+  <tr><th>Address</th><td>{{record.address}}</td></tr>
+  <tr><th>Tax ID</th><td>{{record.tax_id}}</td></tr>
+  <tr><th>Bank account</th><td>{{record.bank_account}}</td></tr>
+</table>
+<footer>Printed {{generated_at}}</footer>
+</body>
+</html>
+"#;
+
+const DEFAULT_PRODUCT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+  body { font-family: sans-serif; font-size: 12px; margin: 32px; }
+  h1 { font-size: 20px; margin-bottom: 4px; }
+  table { border-collapse: collapse; width: 100%; margin-top: 16px; }
+  th { text-align: left; width: 30%; color: #555; }
+  th, td { padding: 6px 8px; border-bottom: 1px solid #ddd; }
+  footer { margin-top: 24px; color: #888; font-size: 10px; }
+</style>
+</head>
+<body>
+<h1>{{record.name}}</h1>
+<div>{{workspace.name}}</div>
+<table>
+  <tr><th>Code</th><td>{{record.code}}</td></tr>
+  <tr><th>SKU</th><td>{{record.sku}}</td></tr>
+  <tr><th>Barcode</th><td>{{record.barcode}}</td></tr>
+  <tr><th>Unit</th><td>{{record.base_unit}}</td></tr>
+  <tr><th>Selling price</th><td>{{record.selling_price}}</td></tr>
+  <tr><th>Current stock</th><td>{{record.current_stock}}</td></tr>
+  <tr><th>Description</th><td>{{record.description}}</td></tr>
+</table>
+<footer>Printed {{generated_at}}</footer>
+</body>
+</html>
+"#;
+
+const DEFAULT_STATEMENT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+  body { font-family: sans-serif; font-size: 12px; margin: 32px; }
+  h1 { font-size: 20px; margin-bottom: 4px; }
+  table { border-collapse: collapse; width: 100%; margin-top: 16px; }
+  th { text-align: left; color: #555; }
+  th, td { padding: 6px 8px; border-bottom: 1px solid #ddd; }
+  .n { text-align: right; }
+  footer { margin-top: 24px; color: #888; font-size: 10px; }
+</style>
+</head>
+<body>
+<h1>Statement of {{record.contact_name}}</h1>
+<div>{{workspace.name}} &middot; {{record.contact_code}} &middot; {{#if record.from}}{{record.from}} to {{/if}}{{record.to}}</div>
+<table>
+  <tr><th>Date</th><th>Reference</th><th>Description</th><th class="n">Debit</th><th class="n">Credit</th><th class="n">Balance</th></tr>
+  <tr><td>{{record.from}}</td><td colspan="4">Opening balance</td><td class="n">{{record.opening_balance}}</td></tr>
+  {{#each record.lines}}<tr><td>{{occurred_on}}</td><td>{{reference}}</td><td>{{description}}</td><td class="n">{{debit}}</td><td class="n">{{credit}}</td><td class="n">{{balance}}</td></tr>
+  {{/each}}<tr><th colspan="3">Closing balance</th><th class="n">{{record.total_debit}}</th><th class="n">{{record.total_credit}}</th><th class="n">{{record.closing_balance}}</th></tr>
+</table>
+<footer>Printed {{generated_at}}</footer>
+</body>
+</html>
+"#;
@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::print_template_models::{PrintEntity, PrintTemplate};
+use crate::{errors::AppError, utils::DbExecutor};
+
+#[async_trait]
+pub trait PrintTemplateRepository: Send + Sync {
+  /// The templates the workspace customized.
+  async fn list(&self, workspace_id: Uuid) -> Result<Vec<PrintTemplate>, AppError>;
+  async fn get(&self, workspace_id: Uuid, entity: PrintEntity) -> Result<Option<PrintTemplate>, AppError>;
+  /// Creates or replaces the template of `entity`.
+  async fn upsert(&self, workspace_id: Uuid, entity: PrintEntity, html: &str, updated_by: Uuid) -> Result<PrintTemplate, AppError>;
+  /// Removes the template of `entity`, `false` when the workspace had not customized it.
+  async fn delete(&self, workspace_id: Uuid, entity: PrintEntity) -> Result<bool, AppError>;
+}
+
+pub struct PostgresPrintTemplateRepository {
+  db: DbExecutor,
+}
+
+impl PostgresPrintTemplateRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl PrintTemplateRepository for PostgresPrintTemplateRepository {
+  async fn list(&self, workspace_id: Uuid) -> Result<Vec<PrintTemplate>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let templates = sqlx::query_as!(
+      PrintTemplate,
+      r#"
+        SELECT workspace_id, entity as "entity: PrintEntity", html, updated_by, created_at, updated_at
+        FROM print_templates
+        WHERE workspace_id = $1
+        ORDER BY entity
+        "#,
+      workspace_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(templates)
+  }
+
+  async fn get(&self, workspace_id: Uuid, entity: PrintEntity) -> Result<Option<PrintTemplate>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let template = sqlx::query_as!(
+      PrintTemplate,
+      r#"
+        SELECT workspace_id, entity as "entity: PrintEntity", html, updated_by, created_at, updated_at
+        FROM print_templates
+        WHERE workspace_id = $1 AND entity = $2
+        "#,
+      workspace_id,
+      entity as PrintEntity
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(template)
+  }
+
+  async fn upsert(&self, workspace_id: Uuid, entity: PrintEntity, html: &str, updated_by: Uuid) -> Result<PrintTemplate, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let template = sqlx::query_as!(
+      PrintTemplate,
+      r#"
+        INSERT INTO print_templates (workspace_id, entity, html, updated_by)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (workspace_id, entity) DO UPDATE SET html = EXCLUDED.html, updated_by = EXCLUDED.updated_by
+        RETURNING workspace_id, entity as "entity: PrintEntity", html, updated_by, created_at, updated_at
+        "#,
+      workspace_id,
+      entity as PrintEntity,
+      html,
+      updated_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(template)
+  }
+
+  async fn delete(&self, workspace_id: Uuid, entity: PrintEntity) -> Result<bool, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!(
+      "DELETE FROM print_templates WHERE workspace_id = $1 AND entity = $2",
+      workspace_id,
+      entity as PrintEntity
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+}
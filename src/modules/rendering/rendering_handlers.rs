@@ -0,0 +1,219 @@
+use std::sync::Arc;
+
+use axum::{
+  extract::{Path, State, rejection::JsonRejection},
+  response::Json,
+};
+#[cfg(any(feature = "contacts", feature = "products"))]
+use axum::{
+  http::header,
+  response::{IntoResponse, Response},
+};
+use validator::Validate;
+
+#[cfg(any(feature = "contacts", feature = "products"))]
+use super::rendering_service::render_pdf;
+#[cfg(feature = "products")]
+use super::{
+  label_models::{LabelSheetRequest, MAX_LABELS},
+  label_sheet::render_label_sheet,
+};
+use super::{
+  print_template_models::{PrintEntity, PrintTemplateRequest, PrintTemplateResponse},
+  rendering_service::check_template,
+};
+#[cfg(any(feature = "contacts", feature = "products"))]
+use crate::helper::PathUuid;
+#[cfg(feature = "contacts")]
+use crate::modules::datastores::contacts::contact_models::ContactResponse;
+#[cfg(feature = "products")]
+use crate::modules::datastores::products::product_models::ProductResponse;
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{
+    RequireRole, RequiredWorkspace,
+    workspace::role::{Admin, Member},
+  },
+  modules::auth::current_user::CurrentUser,
+  responses::ApiResponse,
+  state::AppState,
+};
+
+fn parse_entity(entity: &str) -> AppResult<PrintEntity> {
+  PrintEntity::parse(entity).ok_or_else(|| {
+    let entities: Vec<&str> = PrintEntity::ALL.iter().map(|entity| entity.as_str()).collect();
+    AppError::validation(
+      "entity",
+      &format!("Unknown print entity '{}', expected one of {}", entity, entities.join(", ")),
+    )
+  })
+}
+
+/// Serves a printed record inline, named after its code.
+#[cfg(any(feature = "contacts", feature = "products"))]
+pub(crate) fn pdf_response(code: &str, pdf: Vec<u8>) -> Response {
+  let name: String = code
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+    .collect();
+  let disposition = format!("inline; filename=\"{}.pdf\"", name);
+  (
+    [
+      (header::CONTENT_TYPE, "application/pdf".to_string()),
+      (header::CONTENT_DISPOSITION, disposition),
+    ],
+    pdf,
+  )
+    .into_response()
+}
+
+/// Prints a contact with the contact template of the workspace.
+#[cfg(feature = "contacts")]
+pub async fn print_contact(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+) -> AppResult<Response> {
+  let contact = state
+    .contact_repository
+    .find_by_id_and_workspace(id, workspace_id, current_user.user_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Contact", id))?;
+  let contact = ContactResponse::from(contact);
+
+  let pdf = render_pdf(&state, workspace_id, PrintEntity::Contacts, &contact).await?;
+  Ok(pdf_response(&contact.code, pdf))
+}
+
+/// Prints a product with the product template of the workspace.
+#[cfg(feature = "products")]
+pub async fn print_product(
+  State(state): State<Arc<AppState>>,
+  PathUuid(id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+) -> AppResult<Response> {
+  let product = state
+    .product_repository
+    .find_by_id_and_workspace(id, workspace_id, current_user.user_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Product", id))?;
+  let product = ProductResponse::from(product);
+
+  let pdf = render_pdf(&state, workspace_id, PrintEntity::Products, &product).await?;
+  Ok(pdf_response(&product.code, pdf))
+}
+
+/// Prints a sheet of barcode labels, `quantity` of each product in the order of the items.
+#[cfg(feature = "products")]
+pub async fn print_product_labels(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  payload: Result<Json<LabelSheetRequest>, JsonRejection>,
+) -> AppResult<Response> {
+  let Json(request) = payload?;
+  request.validate()?;
+  if request.items.iter().any(|item| item.quantity < 1) {
+    return Err(AppError::validation("items", "Quantities must be at least 1"));
+  }
+  if request.items.iter().map(|item| u64::from(item.quantity)).sum::<u64>() > u64::from(MAX_LABELS) {
+    return Err(AppError::validation(
+      "items",
+      &format!("At most {} labels can be printed at once", MAX_LABELS),
+    ));
+  }
+
+  let ids: Vec<_> = request.items.iter().map(|item| item.product_id).collect();
+  let found = state.product_repository.find_by_ids(&ids, workspace_id, current_user.user_id).await?;
+  let mut products = Vec::with_capacity(request.items.len());
+  for item in &request.items {
+    let product = found
+      .iter()
+      .find(|product| product.id == item.product_id)
+      .ok_or_else(|| AppError::not_found_with_id("Product", item.product_id))?;
+    products.push((product, item.quantity));
+  }
+
+  let pdf = render_label_sheet(&state, &request, &products).await?;
+  Ok(pdf_response("labels", pdf))
+}
+
+/// The template of every printable entity, customized or built in.
+pub async fn list_print_templates(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+) -> AppResult<Json<ApiResponse<Vec<PrintTemplateResponse>>>> {
+  let mut customized = state.print_template_repository.list(workspace_id).await?;
+  let templates = PrintEntity::ALL
+    .iter()
+    .map(|&entity| {
+      let template = customized
+        .iter()
+        .position(|template| template.entity == entity)
+        .map(|index| customized.swap_remove(index));
+      PrintTemplateResponse::new(entity, template)
+    })
+    .collect();
+  Ok(Json(ApiResponse::success(templates, "Print templates retrieved successfully")))
+}
+
+pub async fn get_print_template(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  Path(entity): Path<String>,
+) -> AppResult<Json<ApiResponse<PrintTemplateResponse>>> {
+  let entity = parse_entity(&entity)?;
+  let template = state.print_template_repository.get(workspace_id, entity).await?;
+  Ok(Json(ApiResponse::success(
+    PrintTemplateResponse::new(entity, template),
+    "Print template retrieved successfully",
+  )))
+}
+
+/// Customizes how the workspace prints an entity. The template must compile, so mistakes surface
+/// here rather than when printing.
+pub async fn replace_print_template(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  Path(entity): Path<String>,
+  payload: Result<Json<PrintTemplateRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<PrintTemplateResponse>>> {
+  let entity = parse_entity(&entity)?;
+  let Json(request) = payload?;
+  request.validate()?;
+  check_template(&request.html)?;
+
+  let template = state
+    .print_template_repository
+    .upsert(workspace_id, entity, &request.html, current_user.user_id)
+    .await?;
+  Ok(Json(ApiResponse::success(
+    PrintTemplateResponse::new(entity, Some(template)),
+    "Print template saved successfully",
+  )))
+}
+
+/// Goes back to the built-in template, which is returned.
+pub async fn reset_print_template(
+  State(state): State<Arc<AppState>>,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _admin: RequireRole<Admin>,
+  Path(entity): Path<String>,
+) -> AppResult<Json<ApiResponse<PrintTemplateResponse>>> {
+  let entity = parse_entity(&entity)?;
+  state.print_template_repository.delete(workspace_id, entity).await?;
+  Ok(Json(ApiResponse::success(
+    PrintTemplateResponse::new(entity, None),
+    "Print template reset successfully",
+  )))
+}
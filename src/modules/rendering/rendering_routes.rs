@@ -0,0 +1,19 @@
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  routing::{delete, get, put},
+};
+
+use super::rendering_handlers::{get_print_template, list_print_templates, replace_print_template, reset_print_template};
+use crate::state::AppState;
+
+/// Print templates of the current workspace, mounted at `/api/v1/print-templates` behind the JWT
+/// middleware. Records are printed from their own routes, e.g. `/api/v1/contacts/:id/pdf`.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/", get(list_print_templates))
+    .route("/:entity", get(get_print_template))
+    .route("/:entity", put(replace_print_template))
+    .route("/:entity", delete(reset_print_template))
+}
@@ -0,0 +1,55 @@
+use std::sync::LazyLock;
+
+use chrono::Utc;
+use handlebars::{Handlebars, RenderError, RenderErrorReason};
+use serde::Serialize;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use super::print_template_models::PrintEntity;
+use crate::{AppResult, errors::AppError, internal_error, state::AppState};
+
+/// Renders with HTML escaping, so record values cannot inject markup. Fields a record does not
+/// have print as blanks, as optional fields are missing from some records only.
+static HANDLEBARS: LazyLock<Handlebars<'static>> = LazyLock::new(Handlebars::new);
+
+/// Checks that `html` parses and only uses built-in helpers, by rendering it without data.
+pub fn check_template(html: &str) -> AppResult<()> {
+  render_html(html, &json!({ "record": {}, "workspace": {} })).map(|_| ())
+}
+
+/// Prints a record of `entity`, as returned by the API, with the template of the workspace or the
+/// built-in one.
+pub async fn render_pdf(state: &AppState, workspace_id: Uuid, entity: PrintEntity, record: impl Serialize) -> AppResult<Vec<u8>> {
+  let renderer = state
+    .pdf_renderer
+    .as_ref()
+    .ok_or_else(|| internal_error!("PDF rendering is not configured: PDF_CHROMIUM must be set"))?;
+  let workspace = state
+    .workspace_repository
+    .get_workspace_by_id(workspace_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Workspace", workspace_id))?;
+  let template = state.print_template_repository.get(workspace_id, entity).await?;
+  let html = template.as_ref().map_or(entity.default_template(), |template| template.html.as_str());
+
+  let data = json!({
+    "record": record,
+    "workspace": { "id": workspace.id, "name": workspace.name },
+    "generated_at": Utc::now().format("%Y-%m-%d %H:%M UTC").to_string(),
+  });
+  renderer.render(&render_html(html, &data)?).await
+}
+
+fn render_html(html: &str, data: &Value) -> AppResult<String> {
+  HANDLEBARS
+    .render_template(html, data)
+    .map_err(|e| AppError::validation("html", &describe(&e)))
+}
+
+fn describe(error: &RenderError) -> String {
+  match error.reason() {
+    RenderErrorReason::TemplateError(e) => format!("Invalid template: {}", e.reason()),
+    reason => format!("Invalid template: {}", reason),
+  }
+}
@@ -0,0 +1,14 @@
+//! Scheduled reports, compiled with the `reports` feature.
+//!
+//! Members of a workspace schedule a report (low stock, stock value by category) daily, weekly or
+//! monthly under `/api/v1/workspaces/{workspace_id}/report-schedules`. Every
+//! `REPORT_CHECK_INTERVAL_SECS` the scheduler generates the due reports with the access of the
+//! schedule's creator, emails them as a text table through the `Mailer` and posts them as JSON to
+//! the schedule's HTTPS webhook.
+
+pub mod report_handlers;
+pub mod report_models;
+pub mod report_repository;
+pub mod report_routes;
+pub mod report_service;
+pub mod report_webhook;
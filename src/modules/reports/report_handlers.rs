@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use axum::{
+  extract::{State, rejection::JsonRejection},
+  response::Json,
+};
+use chrono::Utc;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::report_models::{ReportSchedule, ReportScheduleRequest};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::PathUuid,
+  modules::{auth::current_user::CurrentUser, datastores::workspaces::WorkspaceRole},
+  responses::{ApiResponse, Created},
+  state::AppState,
+};
+
+/// Schedules of one workspace.
+const MAX_SCHEDULES: usize = 50;
+
+/// Schedules are managed by the members of the workspace, as their reports show its products.
+async fn require_member(state: &AppState, user_id: Uuid, workspace_id: Uuid) -> AppResult<()> {
+  let role = state.workspace_repository.check_user_workspace_access(user_id, workspace_id).await?;
+  match role {
+    Some(role) if role.includes(WorkspaceRole::Member) => Ok(()),
+    _ => Err(AppError::Authorization("Only workspace members can manage report schedules".to_string())),
+  }
+}
+
+/// The report schedules of a workspace, oldest first.
+pub async fn list_report_schedules(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(workspace_id): PathUuid,
+) -> AppResult<Json<ApiResponse<Vec<ReportSchedule>>>> {
+  require_member(&state, current_user.user_id, workspace_id).await?;
+
+  let schedules = state.report_schedule_repository.list(workspace_id).await?;
+  Ok(Json(ApiResponse::success(schedules, "Report schedules retrieved successfully")))
+}
+
+/// Schedules a report. It is generated with the access of the caller, as long as they remain a
+/// member of the workspace.
+pub async fn create_report_schedule(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(workspace_id): PathUuid,
+  payload: Result<Json<ReportScheduleRequest>, JsonRejection>,
+) -> AppResult<Created<ApiResponse<ReportSchedule>>> {
+  let Json(request) = payload?;
+  request.validate()?;
+  require_member(&state, current_user.user_id, workspace_id).await?;
+
+  let repository = &state.report_schedule_repository;
+  if repository.list(workspace_id).await?.len() >= MAX_SCHEDULES {
+    return Err(AppError::validation(
+      "name",
+      &format!("A workspace can have at most {} report schedules", MAX_SCHEDULES),
+    ));
+  }
+  let next_run_at = request.starts_at.unwrap_or_else(Utc::now);
+  let schedule = repository.create(workspace_id, &request, next_run_at, current_user.user_id).await?;
+
+  let location = format!("/api/v1/workspaces/{}/report-schedules/{}", workspace_id, schedule.id);
+  Ok(ApiResponse::created(schedule, "Report schedule created successfully", location))
+}
+
+/// Replaces a report schedule.
+pub async fn replace_report_schedule(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid((workspace_id, schedule_id)): PathUuid<(Uuid, Uuid)>,
+  payload: Result<Json<ReportScheduleRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<ReportSchedule>>> {
+  let Json(request) = payload?;
+  request.validate()?;
+  require_member(&state, current_user.user_id, workspace_id).await?;
+
+  let schedule = state
+    .report_schedule_repository
+    .replace(schedule_id, workspace_id, &request, current_user.user_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Report schedule", schedule_id))?;
+  Ok(Json(ApiResponse::success(schedule, "Report schedule updated successfully")))
+}
+
+/// Deletes a report schedule.
+pub async fn delete_report_schedule(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid((workspace_id, schedule_id)): PathUuid<(Uuid, Uuid)>,
+) -> AppResult<Json<ApiResponse<()>>> {
+  require_member(&state, current_user.user_id, workspace_id).await?;
+
+  if !state.report_schedule_repository.delete(schedule_id, workspace_id).await? {
+    return Err(AppError::not_found_with_id("Report schedule", schedule_id));
+  }
+  Ok(Json(ApiResponse::success((), "Report schedule deleted successfully")))
+}
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Months, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::{Validate, ValidateEmail, ValidationError};
+
+/// Email recipients of one schedule.
+pub const MAX_RECIPIENTS: usize = 20;
+
+/// A report that can be scheduled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "report_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+  /// Active products tracking inventory at or below their reorder level.
+  LowStock,
+  /// The product count and stock value of each product category.
+  StockValue,
+}
+
+impl ReportKind {
+  pub fn title(self) -> &'static str {
+    match self {
+      ReportKind::LowStock => "Low stock",
+      ReportKind::StockValue => "Stock value by category",
+    }
+  }
+}
+
+/// How often a scheduled report is generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "report_frequency", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFrequency {
+  Daily,
+  Weekly,
+  Monthly,
+}
+
+impl ReportFrequency {
+  /// The first run after `now` of a schedule that was due at `due`, keeping its time of day.
+  /// Runs missed while no instance was polling are skipped rather than caught up on.
+  pub fn next_run(self, due: DateTime<Utc>, now: DateTime<Utc>) -> DateTime<Utc> {
+    let mut next = due;
+    let mut periods = 1;
+    while next <= now {
+      next = match self {
+        ReportFrequency::Daily => due + chrono::Duration::days(periods),
+        ReportFrequency::Weekly => due + chrono::Duration::weeks(periods),
+        // From `due` each time, so the 31st does not drift to the 28th after February
+        ReportFrequency::Monthly => due.checked_add_months(Months::new(periods as u32)).unwrap_or(DateTime::<Utc>::MAX_UTC),
+      };
+      periods += 1;
+    }
+    next
+  }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ReportSchedule {
+  pub id: Uuid,
+  pub workspace_id: Uuid,
+  pub name: String,
+  pub report: ReportKind,
+  pub frequency: ReportFrequency,
+  pub recipients: Vec<String>,
+  pub webhook_url: Option<String>,
+  pub is_active: bool,
+  pub next_run_at: DateTime<Utc>,
+  pub last_run_at: Option<DateTime<Utc>>,
+  /// Why the last run failed, `None` when it was delivered.
+  pub last_error: Option<String>,
+  pub created_by: Uuid,
+  pub updated_by: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// A schedule as created or replaced: the report is delivered to the `recipients` by email
+/// and/or posted as JSON to the `webhook_url`. The first run is at `starts_at`; when omitted, now
+/// for a new schedule and the next run already planned for a replaced one.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+#[validate(schema(function = "validate_destination"))]
+pub struct ReportScheduleRequest {
+  #[validate(length(min = 1, max = 255, message = "Name must be between 1 and 255 characters"))]
+  pub name: String,
+  pub report: ReportKind,
+  pub frequency: ReportFrequency,
+  #[serde(default)]
+  #[validate(custom(function = "validate_recipients"))]
+  pub recipients: Vec<String>,
+  #[validate(custom(function = "validate_webhook_url"))]
+  pub webhook_url: Option<String>,
+  #[serde(default = "active")]
+  pub is_active: bool,
+  pub starts_at: Option<DateTime<Utc>>,
+}
+
+fn active() -> bool {
+  true
+}
+
+/// A generated report, as emailed and posted to webhooks.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+  pub schedule_id: Uuid,
+  pub workspace_id: Uuid,
+  pub report: ReportKind,
+  pub title: String,
+  pub generated_at: DateTime<Utc>,
+  pub columns: Vec<&'static str>,
+  pub rows: Vec<Vec<String>>,
+}
+
+impl Report {
+  /// The report as a plain text table, for emails.
+  pub fn to_text(&self) -> String {
+    let mut widths: Vec<usize> = self.columns.iter().map(|column| column.len()).collect();
+    for row in &self.rows {
+      for (width, cell) in widths.iter_mut().zip(row) {
+        *width = (*width).max(cell.chars().count());
+      }
+    }
+    let line = |cells: Vec<&str>| {
+      let cells: Vec<String> = cells
+        .iter()
+        .zip(&widths)
+        .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+        .collect();
+      cells.join("  ").trim_end().to_string()
+    };
+
+    let mut text = format!("{}\nGenerated {}\n\n", self.title, self.generated_at.format("%Y-%m-%d %H:%M UTC"));
+    if self.rows.is_empty() {
+      text.push_str("Nothing to report.\n");
+      return text;
+    }
+    text.push_str(&line(self.columns.clone()));
+    text.push('\n');
+    for row in &self.rows {
+      text.push_str(&line(row.iter().map(String::as_str).collect()));
+      text.push('\n');
+    }
+    text
+  }
+}
+
+fn validate_recipients(recipients: &[String]) -> Result<(), ValidationError> {
+  if recipients.len() > MAX_RECIPIENTS {
+    return Err(ValidationError::new("recipients").with_message(format!("At most {} recipients can be set", MAX_RECIPIENTS).into()));
+  }
+  if let Some(invalid) = recipients.iter().find(|recipient| !recipient.validate_email()) {
+    return Err(ValidationError::new("recipients").with_message(format!("'{}' is not a valid email address", invalid).into()));
+  }
+  Ok(())
+}
+
+/// Webhooks are posted over HTTPS only, as the reports carry the workspace's data.
+fn validate_webhook_url(url: &str) -> Result<(), ValidationError> {
+  let valid = url.len() <= 2048
+    && url
+      .strip_prefix("https://")
+      .is_some_and(|rest| !rest.is_empty() && !rest.starts_with('/'));
+  if !valid {
+    return Err(ValidationError::new("webhook_url").with_message("Webhook URL must be an https:// URL".into()));
+  }
+  Ok(())
+}
+
+fn validate_destination(request: &ReportScheduleRequest) -> Result<(), ValidationError> {
+  if request.recipients.is_empty() && request.webhook_url.is_none() {
+    return Err(ValidationError::new("destination").with_message("Set recipients, a webhook URL, or both".into()));
+  }
+  Ok(())
+}
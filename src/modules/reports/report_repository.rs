@@ -0,0 +1,191 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::report_models::{ReportFrequency, ReportKind, ReportSchedule, ReportScheduleRequest};
+use crate::{errors::AppError, utils::DbExecutor};
+
+#[async_trait]
+pub trait ReportScheduleRepository: Send + Sync {
+  async fn list(&self, workspace_id: Uuid) -> Result<Vec<ReportSchedule>, AppError>;
+  async fn create(
+    &self,
+    workspace_id: Uuid,
+    request: &ReportScheduleRequest,
+    next_run_at: DateTime<Utc>,
+    created_by: Uuid,
+  ) -> Result<ReportSchedule, AppError>;
+  /// Replaces a schedule of the workspace, keeping its next run unless the request moves it.
+  async fn replace(
+    &self,
+    id: Uuid,
+    workspace_id: Uuid,
+    request: &ReportScheduleRequest,
+    updated_by: Uuid,
+  ) -> Result<Option<ReportSchedule>, AppError>;
+  async fn delete(&self, id: Uuid, workspace_id: Uuid) -> Result<bool, AppError>;
+  /// Active schedules of every workspace due at `now`, the longest overdue first.
+  async fn due(&self, now: DateTime<Utc>, limit: i64) -> Result<Vec<ReportSchedule>, AppError>;
+  /// Moves the next run of a schedule due at `due_at` to `next_run_at`. `false` when another
+  /// instance claimed the run first, or the schedule was changed since it was loaded.
+  async fn claim(&self, id: Uuid, due_at: DateTime<Utc>, next_run_at: DateTime<Utc>) -> Result<bool, AppError>;
+  /// Records the outcome of a run, `error` being `None` when it was delivered.
+  async fn record_run(&self, id: Uuid, ran_at: DateTime<Utc>, error: Option<&str>) -> Result<(), AppError>;
+}
+
+pub struct PostgresReportScheduleRepository {
+  db: DbExecutor,
+}
+
+impl PostgresReportScheduleRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl ReportScheduleRepository for PostgresReportScheduleRepository {
+  async fn list(&self, workspace_id: Uuid) -> Result<Vec<ReportSchedule>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let schedules = sqlx::query_as!(
+      ReportSchedule,
+      r#"
+        SELECT id, workspace_id, name, report as "report: ReportKind", frequency as "frequency: ReportFrequency", recipients,
+          webhook_url, is_active, next_run_at, last_run_at, last_error, created_by, updated_by, created_at, updated_at
+        FROM report_schedules
+        WHERE workspace_id = $1
+        ORDER BY created_at, id
+        "#,
+      workspace_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(schedules)
+  }
+
+  async fn create(
+    &self,
+    workspace_id: Uuid,
+    request: &ReportScheduleRequest,
+    next_run_at: DateTime<Utc>,
+    created_by: Uuid,
+  ) -> Result<ReportSchedule, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let schedule = sqlx::query_as!(
+      ReportSchedule,
+      r#"
+        INSERT INTO report_schedules (workspace_id, name, report, frequency, recipients, webhook_url, is_active, next_run_at, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, workspace_id, name, report as "report: ReportKind", frequency as "frequency: ReportFrequency", recipients,
+          webhook_url, is_active, next_run_at, last_run_at, last_error, created_by, updated_by, created_at, updated_at
+        "#,
+      workspace_id,
+      request.name,
+      request.report as ReportKind,
+      request.frequency as ReportFrequency,
+      &request.recipients,
+      request.webhook_url,
+      request.is_active,
+      next_run_at,
+      created_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(schedule)
+  }
+
+  async fn replace(
+    &self,
+    id: Uuid,
+    workspace_id: Uuid,
+    request: &ReportScheduleRequest,
+    updated_by: Uuid,
+  ) -> Result<Option<ReportSchedule>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let schedule = sqlx::query_as!(
+      ReportSchedule,
+      r#"
+        UPDATE report_schedules
+        SET name = $3, report = $4, frequency = $5, recipients = $6, webhook_url = $7, is_active = $8,
+            next_run_at = COALESCE($9, next_run_at), updated_by = $10
+        WHERE id = $1 AND workspace_id = $2
+        RETURNING id, workspace_id, name, report as "report: ReportKind", frequency as "frequency: ReportFrequency", recipients,
+          webhook_url, is_active, next_run_at, last_run_at, last_error, created_by, updated_by, created_at, updated_at
+        "#,
+      id,
+      workspace_id,
+      request.name,
+      request.report as ReportKind,
+      request.frequency as ReportFrequency,
+      &request.recipients,
+      request.webhook_url,
+      request.is_active,
+      request.starts_at,
+      updated_by
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(schedule)
+  }
+
+  async fn delete(&self, id: Uuid, workspace_id: Uuid) -> Result<bool, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!("DELETE FROM report_schedules WHERE id = $1 AND workspace_id = $2", id, workspace_id)
+      .execute(&mut *conn)
+      .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  async fn due(&self, now: DateTime<Utc>, limit: i64) -> Result<Vec<ReportSchedule>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let schedules = sqlx::query_as!(
+      ReportSchedule,
+      r#"
+        SELECT id, workspace_id, name, report as "report: ReportKind", frequency as "frequency: ReportFrequency", recipients,
+          webhook_url, is_active, next_run_at, last_run_at, last_error, created_by, updated_by, created_at, updated_at
+        FROM report_schedules
+        WHERE is_active AND next_run_at <= $1
+        ORDER BY next_run_at
+        LIMIT $2
+        "#,
+      now,
+      limit
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(schedules)
+  }
+
+  async fn claim(&self, id: Uuid, due_at: DateTime<Utc>, next_run_at: DateTime<Utc>) -> Result<bool, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!(
+      "UPDATE report_schedules SET next_run_at = $3 WHERE id = $1 AND next_run_at = $2 AND is_active",
+      id,
+      due_at,
+      next_run_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  async fn record_run(&self, id: Uuid, ran_at: DateTime<Utc>, error: Option<&str>) -> Result<(), AppError> {
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!(
+      "UPDATE report_schedules SET last_run_at = $2, last_error = $3 WHERE id = $1",
+      id,
+      ran_at,
+      error
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+}
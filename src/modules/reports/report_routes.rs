@@ -0,0 +1,18 @@
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  routing::{delete, get, post, put},
+};
+
+use super::report_handlers::{create_report_schedule, delete_report_schedule, list_report_schedules, replace_report_schedule};
+use crate::state::AppState;
+
+/// Report schedules of a workspace, mounted at `/api/v1/workspaces`.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/:workspace_id/report-schedules", get(list_report_schedules))
+    .route("/:workspace_id/report-schedules", post(create_report_schedule))
+    .route("/:workspace_id/report-schedules/:schedule_id", put(replace_report_schedule))
+    .route("/:workspace_id/report-schedules/:schedule_id", delete(delete_report_schedule))
+}
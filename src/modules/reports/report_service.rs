@@ -0,0 +1,148 @@
+//! Generating scheduled reports and delivering them.
+//!
+//! Every instance polls for due schedules; a run is claimed by moving the schedule's
+//! `next_run_at` first, so it is delivered once however many instances poll. A failed run is not
+//! retried: its error is shown on the schedule until the next run. Reports are generated from the
+//! tables of their workspace (see `utils::tenant_schema`).
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use tracing::{debug, warn};
+
+use super::report_models::{Report, ReportKind, ReportSchedule};
+use crate::{AppResult, errors::AppError, mailer::EmailMessage, state::AppState, utils::db_session};
+
+/// Schedules run per check; the others are left for the next check.
+const BATCH_SIZE: i64 = 100;
+
+/// Runs the schedules due now and returns how many were delivered.
+pub async fn run_due(state: &AppState) -> AppResult<usize> {
+  let repository = &state.report_schedule_repository;
+  let now = Utc::now();
+  let mut delivered = 0;
+
+  for schedule in repository.due(now, BATCH_SIZE).await? {
+    let next_run_at = schedule.frequency.next_run(schedule.next_run_at, now);
+    if !repository.claim(schedule.id, schedule.next_run_at, next_run_at).await? {
+      continue;
+    }
+
+    let result = run(state, &schedule, now).await;
+    if let Err(e) = &result {
+      warn!("Report schedule {} of workspace {} failed: {}", schedule.id, schedule.workspace_id, e);
+    } else {
+      delivered += 1;
+    }
+    repository.record_run(schedule.id, now, result.err().as_deref()).await?;
+  }
+
+  Ok(delivered)
+}
+
+/// Generates and delivers one run of `schedule`, describing what failed for its members.
+async fn run(state: &AppState, schedule: &ReportSchedule, now: DateTime<Utc>) -> Result<(), String> {
+  let access = state
+    .workspace_repository
+    .check_user_workspace_access(schedule.created_by, schedule.workspace_id)
+    .await
+    .map_err(|e| format!("Could not check the access of the schedule's creator: {}", e))?;
+  if access.is_none() {
+    return Err("The creator of this schedule is no longer a member of the workspace".to_string());
+  }
+
+  let report = db_session::workspace_scope(&state.db, schedule.workspace_id, generate(state, schedule, now));
+  let report = report.await.map_err(AppError::from).and_then(|report| report).map_err(|e| {
+    warn!("Failed to generate report for schedule {}: {}", schedule.id, e);
+    "The report could not be generated".to_string()
+  })?;
+  deliver(state, schedule, &report).await
+}
+
+/// The report of `schedule` as of `now`, with the data its creator can see.
+pub async fn generate(state: &AppState, schedule: &ReportSchedule, now: DateTime<Utc>) -> AppResult<Report> {
+  let products = &state.product_repository;
+  let (columns, rows) = match schedule.report {
+    ReportKind::LowStock => {
+      let products = products.find_low_stock_by_workspace(schedule.workspace_id, schedule.created_by).await?;
+      let count = |value: Option<i32>| value.map_or_else(String::new, |value| value.to_string());
+      let rows = products
+        .into_iter()
+        .map(|product| vec![product.code, product.name, count(product.current_stock), count(product.reorder_level)])
+        .collect();
+      (vec!["Code", "Name", "Stock", "Reorder level"], rows)
+    }
+    ReportKind::StockValue => {
+      let categories = products.find_category_aggregates(schedule.workspace_id, schedule.created_by).await?;
+      let rows = categories
+        .into_iter()
+        .map(|category| {
+          vec![
+            category.code,
+            category.name,
+            category.product_count.to_string(),
+            category.stock_value.round_dp(2).to_string(),
+          ]
+        })
+        .collect();
+      (vec!["Code", "Category", "Products", "Stock value"], rows)
+    }
+  };
+
+  Ok(Report {
+    schedule_id: schedule.id,
+    workspace_id: schedule.workspace_id,
+    report: schedule.report,
+    title: format!("{}: {}", schedule.name, schedule.report.title()),
+    generated_at: now,
+    columns,
+    rows,
+  })
+}
+
+/// Sends `report` to every destination of `schedule`, attempting all of them before failing.
+async fn deliver(state: &AppState, schedule: &ReportSchedule, report: &Report) -> Result<(), String> {
+  let mut failures = Vec::new();
+
+  let body = report.to_text();
+  for recipient in &schedule.recipients {
+    let email = EmailMessage {
+      to: recipient.clone(),
+      subject: report.title.clone(),
+      body: body.clone(),
+    };
+    if let Err(e) = state.mailer.send(email).await {
+      warn!("Failed to email report of schedule {} to {}: {}", schedule.id, recipient, e);
+      failures.push(format!("email to {}", recipient));
+    }
+  }
+
+  if let Some(url) = &schedule.webhook_url {
+    let payload = serde_json::to_value(report).map_err(|e| format!("The report could not be serialized: {}", e))?;
+    if let Err(e) = state.webhook_sender.post(url, &payload).await {
+      warn!("Failed to post report of schedule {} to its webhook: {}", schedule.id, e);
+      failures.push("webhook".to_string());
+    }
+  }
+
+  if failures.is_empty() {
+    Ok(())
+  } else {
+    Err(format!("Delivery failed: {}", failures.join(", ")))
+  }
+}
+
+/// Runs due schedules every `reports.check_interval_secs` for the lifetime of the process.
+pub async fn run_scheduler(state: Arc<AppState>) {
+  let mut interval = tokio::time::interval(Duration::from_secs(state.config.reports.check_interval_secs));
+  loop {
+    interval.tick().await;
+    let result = run_due(&state).await;
+    state.task_health.record("report_scheduler", &result);
+    match result {
+      Ok(0) => {}
+      Ok(delivered) => debug!("Delivered {} scheduled reports", delivered),
+      Err(e) => warn!("Failed to run report schedules, retrying with the next check: {}", e),
+    }
+  }
+}
@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::{AppResult, internal_error};
+
+/// Posts reports to the webhooks of their schedules. Tests replace it with
+/// `AppStateBuilder::with_webhook_sender`.
+#[async_trait]
+pub trait WebhookSender: Send + Sync {
+  /// Posts `body` as JSON to `url`, failing unless the response is a 2xx.
+  async fn post(&self, url: &str, body: &Value) -> AppResult<()>;
+}
+
+/// Posts with reqwest. Redirects are not followed, so a webhook cannot be bounced to another
+/// host or to plain HTTP.
+pub struct HttpWebhookSender {
+  http: reqwest::Client,
+}
+
+impl HttpWebhookSender {
+  pub fn new(timeout: Duration) -> Self {
+    let http = reqwest::Client::builder()
+      .timeout(timeout)
+      .redirect(reqwest::redirect::Policy::none())
+      .build()
+      .unwrap_or_else(|_| reqwest::Client::new());
+    Self { http }
+  }
+}
+
+#[async_trait]
+impl WebhookSender for HttpWebhookSender {
+  async fn post(&self, url: &str, body: &Value) -> AppResult<()> {
+    let response = self
+      .http
+      .post(url)
+      .json(body)
+      .send()
+      .await
+      .map_err(|e| internal_error!("Webhook request to {} failed: {}", url, e))?;
+    let status = response.status();
+    if !status.is_success() {
+      return Err(internal_error!("Webhook {} responded {}", url, status));
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,11 @@
+//! Data retention policies per workspace.
+//!
+//! Workspace admins set how many days each category of data is kept; `retention_purger` removes
+//! older data every `RETENTION_PURGE_INTERVAL_SECS` and logs each purge in `retention_purges`.
+//! The preview endpoint counts what the next purge would remove. Categories are listed in
+//! `RetentionCategory`; data of a category without a policy is kept forever.
+
+pub mod retention_handlers;
+pub mod retention_models;
+pub mod retention_purger;
+pub mod retention_repository;
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use axum::{
+  extract::{State, rejection::JsonRejection},
+  response::Json,
+};
+use chrono::Utc;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::retention_models::{RetentionPolicy, RetentionPreview, RetentionSettingsResponse, SetRetentionPolicyRequest};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::PathUuid,
+  modules::{auth::current_user::CurrentUser, datastores::workspaces::WorkspaceRole},
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// Purges listed with the policies.
+const RECENT_PURGES: i64 = 20;
+
+/// Retention is managed by the admins of the workspace.
+async fn require_admin(state: &AppState, user_id: Uuid, workspace_id: Uuid) -> AppResult<()> {
+  let role = state.workspace_repository.check_user_workspace_access(user_id, workspace_id).await?;
+  match role {
+    Some(role) if role.includes(WorkspaceRole::Admin) => Ok(()),
+    _ => Err(AppError::Authorization("Only workspace admins can manage data retention".to_string())),
+  }
+}
+
+/// The retention policies of a workspace and its most recent purges.
+pub async fn get_retention(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(workspace_id): PathUuid,
+) -> AppResult<Json<ApiResponse<RetentionSettingsResponse>>> {
+  require_admin(&state, current_user.user_id, workspace_id).await?;
+
+  let repository = &state.retention_repository;
+  let response = ApiResponse::success(
+    RetentionSettingsResponse {
+      workspace_id,
+      policies: repository.list_policies(workspace_id).await?,
+      recent_purges: repository.recent_purges(workspace_id, RECENT_PURGES).await?,
+    },
+    "Retention policies retrieved successfully",
+  );
+  Ok(Json(response))
+}
+
+/// Sets how long one category of data is kept, or keeps it forever again with a `null` `retain_days`.
+pub async fn set_retention_policy(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(workspace_id): PathUuid,
+  payload: Result<Json<SetRetentionPolicyRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<Option<RetentionPolicy>>>> {
+  let Json(request) = payload?;
+  request.validate()?;
+  require_admin(&state, current_user.user_id, workspace_id).await?;
+
+  let repository = &state.retention_repository;
+  let policy = match request.retain_days {
+    Some(retain_days) => Some(
+      repository
+        .set_policy(workspace_id, request.category, retain_days, current_user.user_id)
+        .await?,
+    ),
+    None => {
+      repository.remove_policy(workspace_id, request.category).await?;
+      None
+    }
+  };
+
+  let response = ApiResponse::success(policy, "Retention policy saved successfully");
+  Ok(Json(response))
+}
+
+/// What the next purge would remove from the workspace under its current policies.
+pub async fn preview_retention(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(workspace_id): PathUuid,
+) -> AppResult<Json<ApiResponse<Vec<RetentionPreview>>>> {
+  require_admin(&state, current_user.user_id, workspace_id).await?;
+
+  let repository = &state.retention_repository;
+  let now = Utc::now();
+  let mut previews = Vec::new();
+  for policy in repository.list_policies(workspace_id).await? {
+    let cutoff = policy.category.cutoff(policy.retain_days, now);
+    previews.push(RetentionPreview {
+      category: policy.category,
+      retain_days: policy.retain_days,
+      cutoff,
+      rows: repository.count_expired(workspace_id, policy.category, cutoff).await?,
+    });
+  }
+
+  let response = ApiResponse::success(previews, "Retention preview retrieved successfully");
+  Ok(Json(response))
+}
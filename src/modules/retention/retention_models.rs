@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::modules::usage::usage_meter::month_start;
+
+/// A kind of workspace data a retention policy applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "retention_category", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionCategory {
+  /// The daily API usage counters of the workspace.
+  ApiUsage,
+  /// Deleted contacts, counted from their deletion. Contacts still the supplier of a product are
+  /// kept.
+  DeletedContacts,
+  /// Deleted products, counted from their deletion.
+  DeletedProducts,
+  /// The audit trail of the workspace: member and role changes.
+  AuditEvents,
+}
+
+impl RetentionCategory {
+  /// The point in time before which data kept for `retain_days` is removed. API usage of the
+  /// current month is always kept, since the monthly quota is counted from it.
+  pub fn cutoff(self, retain_days: i32, now: DateTime<Utc>) -> DateTime<Utc> {
+    let cutoff = now - chrono::Duration::days(retain_days.into());
+    match self {
+      RetentionCategory::ApiUsage => {
+        let month_start = month_start(now).and_hms_opt(0, 0, 0).expect("midnight is a valid time").and_utc();
+        cutoff.min(month_start)
+      }
+      RetentionCategory::DeletedContacts | RetentionCategory::DeletedProducts | RetentionCategory::AuditEvents => cutoff,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct RetentionPolicy {
+  pub workspace_id: Uuid,
+  pub category: RetentionCategory,
+  pub retain_days: i32,
+  pub updated_by: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct RetentionPurge {
+  pub id: Uuid,
+  pub workspace_id: Uuid,
+  pub category: RetentionCategory,
+  pub cutoff: DateTime<Utc>,
+  pub rows_removed: i64,
+  pub purged_at: DateTime<Utc>,
+}
+
+/// Sets the retention of one category; a `null` `retain_days` keeps its data forever again.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetRetentionPolicyRequest {
+  pub category: RetentionCategory,
+  #[validate(range(min = 1, max = 3650, message = "Retention must be between 1 and 3650 days"))]
+  pub retain_days: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionSettingsResponse {
+  pub workspace_id: Uuid,
+  pub policies: Vec<RetentionPolicy>,
+  /// The most recent purges, newest first.
+  pub recent_purges: Vec<RetentionPurge>,
+}
+
+/// What the next purge would remove for one policy.
+#[derive(Debug, Serialize)]
+pub struct RetentionPreview {
+  pub category: RetentionCategory,
+  pub retain_days: i32,
+  pub cutoff: DateTime<Utc>,
+  pub rows: i64,
+}
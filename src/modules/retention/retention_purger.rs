@@ -0,0 +1,66 @@
+//! Scheduled removal of data past the retention of its workspace.
+//!
+//! Personal data exports of users past their expiry are removed along with it.
+//!
+//! Every instance runs the purge; they do not coordinate, since a purge running twice only
+//! removes nothing the second time. Each workspace is purged in its own tables (see
+//! `utils::tenant_schema`).
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use tracing::{info, warn};
+
+use crate::{AppResult, errors::AppError, state::AppState, utils::db_session};
+
+/// Purges the expired data of every workspace with a retention policy and returns the number of
+/// rows removed; expired data exports are removed as well, without being counted. A failing
+/// policy is logged and does not stop the others.
+pub async fn purge_expired(state: &AppState) -> AppResult<i64> {
+  let repository = &state.retention_repository;
+  let now = Utc::now();
+  let mut removed = 0;
+
+  for policy in repository.all_policies().await? {
+    let cutoff = policy.category.cutoff(policy.retain_days, now);
+    let purge = db_session::workspace_scope(
+      &state.db,
+      policy.workspace_id,
+      repository.purge(policy.workspace_id, policy.category, cutoff),
+    );
+    match purge.await.map_err(AppError::from).and_then(|purge| purge) {
+      Ok(Some(purge)) => {
+        info!(
+          "Retention purge removed {} {:?} rows of workspace {} older than {}",
+          purge.rows_removed, purge.category, purge.workspace_id, purge.cutoff
+        );
+        removed += purge.rows_removed;
+      }
+      Ok(None) => {}
+      Err(e) => warn!(
+        "Retention purge of {:?} failed for workspace {}: {}",
+        policy.category, policy.workspace_id, e
+      ),
+    }
+  }
+
+  let exports = state.user_export_repository.delete_expired(now).await?;
+  if exports > 0 {
+    info!("Retention purge removed {} expired data exports", exports);
+  }
+
+  Ok(removed)
+}
+
+/// Purges expired data every `retention.purge_interval_secs` for the lifetime of the process.
+pub async fn run_purger(state: Arc<AppState>) {
+  let mut interval = tokio::time::interval(Duration::from_secs(state.config.retention.purge_interval_secs));
+  loop {
+    interval.tick().await;
+    let result = purge_expired(&state).await;
+    state.task_health.record("retention_purger", &result);
+    if let Err(e) = result {
+      warn!("Retention purge failed, retrying with the next purge: {}", e);
+    }
+  }
+}
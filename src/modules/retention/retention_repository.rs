@@ -0,0 +1,269 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::retention_models::{RetentionCategory, RetentionPolicy, RetentionPurge};
+use crate::{errors::AppError, utils::DbExecutor};
+
+#[async_trait]
+pub trait RetentionRepository: Send + Sync {
+  async fn list_policies(&self, workspace_id: Uuid) -> Result<Vec<RetentionPolicy>, AppError>;
+  /// The policies of every workspace, for the purge job.
+  async fn all_policies(&self) -> Result<Vec<RetentionPolicy>, AppError>;
+  async fn set_policy(
+    &self,
+    workspace_id: Uuid,
+    category: RetentionCategory,
+    retain_days: i32,
+    updated_by: Uuid,
+  ) -> Result<RetentionPolicy, AppError>;
+  /// Removes the policy of a category; `false` when it had none.
+  async fn remove_policy(&self, workspace_id: Uuid, category: RetentionCategory) -> Result<bool, AppError>;
+  async fn recent_purges(&self, workspace_id: Uuid, limit: i64) -> Result<Vec<RetentionPurge>, AppError>;
+  /// Counts the data of a category older than `cutoff`, which a purge would remove.
+  async fn count_expired(&self, workspace_id: Uuid, category: RetentionCategory, cutoff: DateTime<Utc>) -> Result<i64, AppError>;
+  /// Removes the data of a category older than `cutoff` and logs the purge in one statement.
+  /// Returns `None` when there was nothing to remove; such purges are not logged.
+  async fn purge(&self, workspace_id: Uuid, category: RetentionCategory, cutoff: DateTime<Utc>) -> Result<Option<RetentionPurge>, AppError>;
+}
+
+pub struct PostgresRetentionRepository {
+  db: DbExecutor,
+}
+
+impl PostgresRetentionRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl RetentionRepository for PostgresRetentionRepository {
+  async fn list_policies(&self, workspace_id: Uuid) -> Result<Vec<RetentionPolicy>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let policies = sqlx::query_as!(
+      RetentionPolicy,
+      r#"
+        SELECT workspace_id, category as "category: RetentionCategory", retain_days, updated_by, created_at, updated_at
+        FROM retention_policies
+        WHERE workspace_id = $1
+        ORDER BY category
+        "#,
+      workspace_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(policies)
+  }
+
+  async fn all_policies(&self) -> Result<Vec<RetentionPolicy>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let policies = sqlx::query_as!(
+      RetentionPolicy,
+      r#"
+        SELECT workspace_id, category as "category: RetentionCategory", retain_days, updated_by, created_at, updated_at
+        FROM retention_policies
+        ORDER BY workspace_id, category
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(policies)
+  }
+
+  async fn set_policy(
+    &self,
+    workspace_id: Uuid,
+    category: RetentionCategory,
+    retain_days: i32,
+    updated_by: Uuid,
+  ) -> Result<RetentionPolicy, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let policy = sqlx::query_as!(
+      RetentionPolicy,
+      r#"
+        INSERT INTO retention_policies (workspace_id, category, retain_days, updated_by)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (workspace_id, category) DO UPDATE
+        SET retain_days = EXCLUDED.retain_days, updated_by = EXCLUDED.updated_by
+        RETURNING workspace_id, category as "category: RetentionCategory", retain_days, updated_by, created_at, updated_at
+        "#,
+      workspace_id,
+      category as RetentionCategory,
+      retain_days,
+      updated_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(policy)
+  }
+
+  async fn remove_policy(&self, workspace_id: Uuid, category: RetentionCategory) -> Result<bool, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!(
+      "DELETE FROM retention_policies WHERE workspace_id = $1 AND category = $2",
+      workspace_id,
+      category as RetentionCategory
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  async fn recent_purges(&self, workspace_id: Uuid, limit: i64) -> Result<Vec<RetentionPurge>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let purges = sqlx::query_as!(
+      RetentionPurge,
+      r#"
+        SELECT id, workspace_id, category as "category: RetentionCategory", cutoff, rows_removed, purged_at
+        FROM retention_purges
+        WHERE workspace_id = $1
+        ORDER BY purged_at DESC
+        LIMIT $2
+        "#,
+      workspace_id,
+      limit
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(purges)
+  }
+
+  async fn count_expired(&self, workspace_id: Uuid, category: RetentionCategory, cutoff: DateTime<Utc>) -> Result<i64, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let rows = match category {
+      RetentionCategory::ApiUsage => {
+        sqlx::query_scalar!(
+          r#"SELECT COUNT(*) AS "count!" FROM api_usage WHERE workspace_id = $1 AND day < ($2::timestamptz AT TIME ZONE 'UTC')::date"#,
+          workspace_id,
+          cutoff
+        )
+        .fetch_one(&mut *conn)
+        .await?
+      }
+      RetentionCategory::DeletedContacts => {
+        sqlx::query_scalar!(
+          r#"
+            SELECT COUNT(*) AS "count!" FROM contacts c
+            WHERE c.workspace_id = $1 AND c.deleted_at < $2
+              AND NOT EXISTS (SELECT 1 FROM products p WHERE p.supplier_id = c.id)
+            "#,
+          workspace_id,
+          cutoff
+        )
+        .fetch_one(&mut *conn)
+        .await?
+      }
+      RetentionCategory::DeletedProducts => {
+        sqlx::query_scalar!(
+          r#"SELECT COUNT(*) AS "count!" FROM products WHERE workspace_id = $1 AND deleted_at < $2"#,
+          workspace_id,
+          cutoff
+        )
+        .fetch_one(&mut *conn)
+        .await?
+      }
+      RetentionCategory::AuditEvents => {
+        sqlx::query_scalar!(
+          r#"SELECT COUNT(*) AS "count!" FROM audit_events WHERE workspace_id = $1 AND created_at < $2"#,
+          workspace_id,
+          cutoff
+        )
+        .fetch_one(&mut *conn)
+        .await?
+      }
+    };
+
+    Ok(rows)
+  }
+
+  async fn purge(&self, workspace_id: Uuid, category: RetentionCategory, cutoff: DateTime<Utc>) -> Result<Option<RetentionPurge>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let purge = match category {
+      RetentionCategory::ApiUsage => {
+        sqlx::query_as!(
+          RetentionPurge,
+          r#"
+            WITH removed AS (
+              DELETE FROM api_usage
+              WHERE workspace_id = $1 AND day < ($2::timestamptz AT TIME ZONE 'UTC')::date
+              RETURNING 1
+            )
+            INSERT INTO retention_purges (workspace_id, category, cutoff, rows_removed)
+            SELECT $1, 'api_usage', $2, COUNT(*) FROM removed HAVING COUNT(*) > 0
+            RETURNING id, workspace_id, category as "category: RetentionCategory", cutoff, rows_removed, purged_at
+            "#,
+          workspace_id,
+          cutoff
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+      }
+      RetentionCategory::DeletedContacts => {
+        sqlx::query_as!(
+          RetentionPurge,
+          r#"
+            WITH removed AS (
+              DELETE FROM contacts c
+              WHERE c.workspace_id = $1 AND c.deleted_at < $2
+                AND NOT EXISTS (SELECT 1 FROM products p WHERE p.supplier_id = c.id)
+              RETURNING 1
+            )
+            INSERT INTO retention_purges (workspace_id, category, cutoff, rows_removed)
+            SELECT $1, 'deleted_contacts', $2, COUNT(*) FROM removed HAVING COUNT(*) > 0
+            RETURNING id, workspace_id, category as "category: RetentionCategory", cutoff, rows_removed, purged_at
+            "#,
+          workspace_id,
+          cutoff
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+      }
+      RetentionCategory::DeletedProducts => {
+        sqlx::query_as!(
+          RetentionPurge,
+          r#"
+            WITH removed AS (
+              DELETE FROM products
+              WHERE workspace_id = $1 AND deleted_at < $2
+              RETURNING 1
+            )
+            INSERT INTO retention_purges (workspace_id, category, cutoff, rows_removed)
+            SELECT $1, 'deleted_products', $2, COUNT(*) FROM removed HAVING COUNT(*) > 0
+            RETURNING id, workspace_id, category as "category: RetentionCategory", cutoff, rows_removed, purged_at
+            "#,
+          workspace_id,
+          cutoff
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+      }
+      RetentionCategory::AuditEvents => {
+        sqlx::query_as!(
+          RetentionPurge,
+          r#"
+            WITH removed AS (
+              DELETE FROM audit_events
+              WHERE workspace_id = $1 AND created_at < $2
+              RETURNING 1
+            )
+            INSERT INTO retention_purges (workspace_id, category, cutoff, rows_removed)
+            SELECT $1, 'audit_events', $2, COUNT(*) FROM removed HAVING COUNT(*) > 0
+            RETURNING id, workspace_id, category as "category: RetentionCategory", cutoff, rows_removed, purged_at
+            "#,
+          workspace_id,
+          cutoff
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+      }
+    };
+
+    Ok(purge)
+  }
+}
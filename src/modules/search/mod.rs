@@ -0,0 +1,13 @@
+//! Search across the records of a workspace, for a global search box.
+//!
+//! `GET /api/v1/search?q=` matches the words typed as prefixes, so results narrow down while
+//! typing, against the full-text search vectors the database keeps on contacts and products (see
+//! `utils::search_index`), and returns the best matches of each entity in its own group. Results
+//! only carry the identifier, code and name, which field policies never hide. Fields hidden from
+//! the caller are not searched either: when a policy hides a field of the search vector, the
+//! caller's matches on that entity are made on codes and names only.
+
+pub mod search_handlers;
+pub mod search_models;
+pub mod search_repository;
+pub mod search_routes;
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+
+#[cfg(feature = "contacts")]
+use super::search_models::CONTACT_SEARCHED_FIELDS;
+#[cfg(feature = "products")]
+use super::search_models::PRODUCT_SEARCHED_FIELDS;
+use super::search_models::{DEFAULT_LIMIT, SearchQuery, SearchResults};
+use crate::{
+  AppResult,
+  helper::{RequireRole, RequiredWorkspace, ValidatedQuery, workspace::role::Member},
+  modules::{auth::current_user::CurrentUser, workspace_settings::field_policy_service},
+  responses::ApiResponse,
+  state::AppState,
+  utils::search_index,
+};
+
+/// Searches the contacts and products of the workspace for the words typed so far.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `member`: Rejects callers below the member role; its role selects the field policies applied.
+/// * `ValidatedQuery(query)`: The words typed and the number of matches of each entity.
+///
+/// # Returns
+///
+/// A `Json` response with the best matches of each entity, grouped by entity.
+pub async fn search(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  member: RequireRole<Member>,
+  ValidatedQuery(query): ValidatedQuery<SearchQuery>,
+) -> AppResult<Json<ApiResponse<SearchResults>>> {
+  let mut results = SearchResults::default();
+  let Some(tsquery) = search_index::prefix_tsquery(&query.q) else {
+    return Ok(Json(ApiResponse::success(results, "Nothing to search for")));
+  };
+  let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+  let repository = &state.search_repository;
+
+  #[cfg(feature = "contacts")]
+  {
+    let policy = field_policy_service::resolve(&state, workspace_id, "contacts", member.role).await?;
+    let names_only = CONTACT_SEARCHED_FIELDS.iter().any(|field| policy.is_hidden(field));
+    results.contacts = repository
+      .search_contacts(workspace_id, current_user.user_id, &tsquery, names_only, limit)
+      .await?;
+  }
+  #[cfg(feature = "products")]
+  {
+    let policy = field_policy_service::resolve(&state, workspace_id, "products", member.role).await?;
+    let names_only = PRODUCT_SEARCHED_FIELDS.iter().any(|field| policy.is_hidden(field));
+    results.products = repository
+      .search_products(workspace_id, current_user.user_id, &tsquery, names_only, limit)
+      .await?;
+  }
+
+  Ok(Json(ApiResponse::success(results, "Search completed successfully")))
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Matches of each entity returned by default.
+pub const DEFAULT_LIMIT: i64 = 5;
+
+/// Fields of contacts in their search vector besides the code and name (see `contact_search_vector`).
+pub const CONTACT_SEARCHED_FIELDS: &[&str] = &["email", "position", "address"];
+
+/// Fields of products in their search vector besides the code and name, the category and
+/// supplier standing for their names (see `product_search_vector`).
+pub const PRODUCT_SEARCHED_FIELDS: &[&str] = &["sku", "barcode", "description", "category_id", "supplier_id"];
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct SearchQuery {
+  /// The words typed so far, each matched as a prefix.
+  #[validate(length(min = 1, max = 100, message = "q must be between 1 and 100 characters"))]
+  pub q: String,
+  /// Matches returned for each entity, 5 by default.
+  #[validate(range(min = 1, max = 20, message = "limit must be between 1 and 20"))]
+  pub limit: Option<i64>,
+}
+
+/// A record matching the search, best matches first.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SearchHit {
+  pub id: Uuid,
+  pub code: String,
+  pub name: String,
+  /// How well the record matches, higher is better.
+  pub rank: f32,
+}
+
+/// The matches of each entity compiled in.
+#[derive(Debug, Default, Serialize)]
+pub struct SearchResults {
+  #[cfg(feature = "contacts")]
+  pub contacts: Vec<SearchHit>,
+  #[cfg(feature = "products")]
+  pub products: Vec<SearchHit>,
+}
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::search_models::SearchHit;
+use crate::{AppResult, utils::DbExecutor};
+
+/// Searches the records of each entity. `tsquery` is a `to_tsquery` expression, see
+/// `search_index::prefix_tsquery`. With `names_only`, only the codes and names are searched.
+#[async_trait]
+pub trait SearchRepository: Send + Sync {
+  #[cfg(feature = "contacts")]
+  async fn search_contacts(&self, workspace_id: Uuid, user_id: Uuid, tsquery: &str, names_only: bool, limit: i64) -> AppResult<Vec<SearchHit>>;
+  #[cfg(feature = "products")]
+  async fn search_products(&self, workspace_id: Uuid, user_id: Uuid, tsquery: &str, names_only: bool, limit: i64) -> AppResult<Vec<SearchHit>>;
+}
+
+pub struct PostgresSearchRepository {
+  db: DbExecutor,
+}
+
+impl PostgresSearchRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl SearchRepository for PostgresSearchRepository {
+  #[cfg(feature = "contacts")]
+  async fn search_contacts(&self, workspace_id: Uuid, user_id: Uuid, tsquery: &str, names_only: bool, limit: i64) -> AppResult<Vec<SearchHit>> {
+    let mut conn = self.db.acquire().await?;
+    let hits = sqlx::query_as!(
+      SearchHit,
+      r#"
+        SELECT c.id, c.code, c.name, ts_rank(v.vector, q.query) as "rank!"
+        FROM contacts c
+        CROSS JOIN to_tsquery('simple', $3) q(query)
+        CROSS JOIN LATERAL (
+          SELECT CASE WHEN $4 THEN to_tsvector('simple', c.code || ' ' || c.name) ELSE c.search_vector END
+        ) v(vector)
+        WHERE c.workspace_id = $1 AND c.deleted_at IS NULL AND v.vector @@ q.query
+          AND EXISTS (
+            SELECT 1 FROM workspace_access wu
+            WHERE wu.workspace_id = $1 AND wu.user_id = $2
+          )
+        ORDER BY 4 DESC, c.name
+        LIMIT $5
+        "#,
+      workspace_id,
+      user_id,
+      tsquery,
+      names_only,
+      limit
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(hits)
+  }
+
+  #[cfg(feature = "products")]
+  async fn search_products(&self, workspace_id: Uuid, user_id: Uuid, tsquery: &str, names_only: bool, limit: i64) -> AppResult<Vec<SearchHit>> {
+    let mut conn = self.db.acquire().await?;
+    let hits = sqlx::query_as!(
+      SearchHit,
+      r#"
+        SELECT p.id, p.code, p.name, ts_rank(v.vector, q.query) as "rank!"
+        FROM products p
+        CROSS JOIN to_tsquery('simple', $3) q(query)
+        CROSS JOIN LATERAL (
+          SELECT CASE WHEN $4 THEN to_tsvector('simple', p.code || ' ' || p.name) ELSE p.search_vector END
+        ) v(vector)
+        WHERE p.workspace_id = $1 AND p.deleted_at IS NULL AND v.vector @@ q.query
+          AND EXISTS (
+            SELECT 1 FROM workspace_access wu
+            WHERE wu.workspace_id = $1 AND wu.user_id = $2
+          )
+        ORDER BY 4 DESC, p.name
+        LIMIT $5
+        "#,
+      workspace_id,
+      user_id,
+      tsquery,
+      names_only,
+      limit
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(hits)
+  }
+}
@@ -0,0 +1,12 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+
+use super::search_handlers::search;
+use crate::state::AppState;
+
+/// Search across the records of the workspace, mounted at `/api/v1/search` behind the JWT
+/// middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new().route("/", get(search))
+}
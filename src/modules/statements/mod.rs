@@ -0,0 +1,12 @@
+//! Statements of contacts, drawn from their invoices, payments and credit notes.
+//!
+//! The documents are kept in `contact_transactions`, in the schema of the workspace when it has one.
+//! `GET /api/v1/contacts/:id/statement?from=&to=` lists those of the period with the balance owed
+//! after each, starting from the balance owed before `from`. With `format=csv` it is downloaded
+//! like the exports (with the `exports` feature), and with `format=pdf` printed with the
+//! `statements` print template of the workspace (with the `rendering` feature).
+
+pub mod statement_handlers;
+pub mod statement_models;
+pub mod statement_repository;
+pub mod statement_service;
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+#[cfg(feature = "exports")]
+use axum::http::header;
+use axum::{
+  extract::State,
+  response::{IntoResponse, Json, Response},
+};
+
+use super::{
+  statement_models::{StatementFormat, StatementQuery},
+  statement_service,
+};
+use crate::{
+  AppResult,
+  helper::{PathUuid, RequireRole, RequiredWorkspace, ValidatedQuery, workspace::role::Member},
+  modules::auth::current_user::CurrentUser,
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// The statement of a contact over `from`..=`to`: the balance it owed before, each invoice,
+/// payment and credit note with the balance after it, and the balance owed at the end. Returned
+/// as JSON, or with `format` as a CSV download or a PDF printed with the `statements` template.
+pub async fn get_statement(
+  State(state): State<Arc<AppState>>,
+  PathUuid(contact_id): PathUuid,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  ValidatedQuery(query): ValidatedQuery<StatementQuery>,
+) -> AppResult<Response> {
+  let statement = statement_service::build_statement(&state, workspace_id, current_user.user_id, contact_id, query.from, query.to).await?;
+
+  match query.format.unwrap_or_default() {
+    StatementFormat::Json => Ok(Json(ApiResponse::success(statement, "Statement retrieved successfully")).into_response()),
+    #[cfg(feature = "exports")]
+    StatementFormat::Csv => {
+      let csv = statement_service::statement_csv(&statement)?;
+      let disposition = format!(
+        "attachment; filename=\"statement-{}-{}.csv\"",
+        file_name(&statement.contact_code),
+        statement.to.format("%Y%m%d")
+      );
+      Ok(
+        (
+          [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+          ],
+          csv,
+        )
+          .into_response(),
+      )
+    }
+    #[cfg(feature = "rendering")]
+    StatementFormat::Pdf => {
+      use crate::modules::rendering::{print_template_models::PrintEntity, rendering_handlers::pdf_response, rendering_service::render_pdf};
+
+      let pdf = render_pdf(&state, workspace_id, PrintEntity::Statements, &statement).await?;
+      Ok(pdf_response(&format!("statement-{}", statement.contact_code), pdf))
+    }
+  }
+}
+
+/// `code` with the characters that do not belong in a file name replaced.
+#[cfg(feature = "exports")]
+fn file_name(code: &str) -> String {
+  code
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+    .collect()
+}
@@ -0,0 +1,107 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// The documents a statement is drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "contact_transaction_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+  Invoice,
+  Payment,
+  CreditNote,
+}
+
+impl TransactionKind {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      TransactionKind::Invoice => "invoice",
+      TransactionKind::Payment => "payment",
+      TransactionKind::CreditNote => "credit_note",
+    }
+  }
+
+  /// Invoices raise what the contact owes; payments and credit notes lower it.
+  pub fn is_debit(self) -> bool {
+    self == TransactionKind::Invoice
+  }
+}
+
+/// An invoice, payment or credit note of a contact.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ContactTransaction {
+  pub id: Uuid,
+  pub workspace_id: Uuid,
+  pub contact_id: Uuid,
+  pub kind: TransactionKind,
+  pub reference: String,
+  pub occurred_on: NaiveDate,
+  pub amount: Decimal,
+  pub description: Option<String>,
+  pub created_by: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// How a statement is returned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementFormat {
+  #[default]
+  Json,
+  /// Written like the exports, with the `exports` feature.
+  #[cfg(feature = "exports")]
+  Csv,
+  /// Printed with the `statements` print template of the workspace, with the `rendering` feature.
+  #[cfg(feature = "rendering")]
+  Pdf,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+#[validate(schema(function = "validate_period"))]
+pub struct StatementQuery {
+  /// First day of the statement, inclusive; defaults to the first transaction of the contact.
+  pub from: Option<NaiveDate>,
+  /// Last day of the statement, inclusive; defaults to today.
+  pub to: Option<NaiveDate>,
+  pub format: Option<StatementFormat>,
+}
+
+fn validate_period(query: &StatementQuery) -> Result<(), ValidationError> {
+  match (query.from, query.to) {
+    (Some(from), Some(to)) if from > to => Err(ValidationError::new("period").with_message("from cannot be after to".into())),
+    _ => Ok(()),
+  }
+}
+
+/// A transaction on a statement, with the balance owed by the contact once it is counted.
+#[derive(Debug, Serialize)]
+pub struct StatementLine {
+  pub id: Uuid,
+  pub occurred_on: NaiveDate,
+  pub kind: TransactionKind,
+  pub reference: String,
+  pub description: Option<String>,
+  pub debit: Option<Decimal>,
+  pub credit: Option<Decimal>,
+  pub balance: Decimal,
+}
+
+/// The transactions of a contact over a period, from the balance owed before it to the balance
+/// owed at its end.
+#[derive(Debug, Serialize)]
+pub struct ContactStatement {
+  pub contact_id: Uuid,
+  pub contact_code: String,
+  pub contact_name: String,
+  pub from: Option<NaiveDate>,
+  pub to: NaiveDate,
+  pub opening_balance: Decimal,
+  pub total_debit: Decimal,
+  pub total_credit: Decimal,
+  pub closing_balance: Decimal,
+  pub lines: Vec<StatementLine>,
+}
@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::statement_models::{ContactTransaction, TransactionKind};
+use crate::{AppResult, utils::DbExecutor};
+
+#[async_trait]
+pub trait StatementRepository: Send + Sync {
+  /// The balance owed by the contact before `before`.
+  async fn balance_before(&self, workspace_id: Uuid, contact_id: Uuid, before: NaiveDate) -> AppResult<Decimal>;
+  /// The transactions of the contact from `from` (if given) to `to`, both inclusive, oldest first.
+  async fn list(&self, workspace_id: Uuid, contact_id: Uuid, from: Option<NaiveDate>, to: NaiveDate) -> AppResult<Vec<ContactTransaction>>;
+}
+
+pub struct PostgresStatementRepository {
+  db: DbExecutor,
+}
+
+impl PostgresStatementRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl StatementRepository for PostgresStatementRepository {
+  async fn balance_before(&self, workspace_id: Uuid, contact_id: Uuid, before: NaiveDate) -> AppResult<Decimal> {
+    let mut conn = self.db.acquire().await?;
+    let balance = sqlx::query_scalar!(
+      r#"
+        SELECT COALESCE(SUM(CASE WHEN kind = 'invoice' THEN amount ELSE -amount END), 0) AS "balance!"
+        FROM contact_transactions
+        WHERE workspace_id = $1 AND contact_id = $2 AND occurred_on < $3
+        "#,
+      workspace_id,
+      contact_id,
+      before
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(balance)
+  }
+
+  async fn list(&self, workspace_id: Uuid, contact_id: Uuid, from: Option<NaiveDate>, to: NaiveDate) -> AppResult<Vec<ContactTransaction>> {
+    let mut conn = self.db.acquire().await?;
+    let transactions = sqlx::query_as!(
+      ContactTransaction,
+      r#"
+        SELECT id, workspace_id, contact_id, kind AS "kind: TransactionKind", reference, occurred_on, amount, description,
+          created_by, created_at
+        FROM contact_transactions
+        WHERE workspace_id = $1 AND contact_id = $2 AND ($3::date IS NULL OR occurred_on >= $3) AND occurred_on <= $4
+        ORDER BY occurred_on, created_at, id
+        "#,
+      workspace_id,
+      contact_id,
+      from,
+      to
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(transactions)
+  }
+}
@@ -0,0 +1,111 @@
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::statement_models::{ContactStatement, StatementLine};
+use crate::{AppResult, errors::AppError, state::AppState};
+
+/// The statement of the contact from `from` (or its first transaction) to `to` (or today).
+pub async fn build_statement(
+  state: &AppState,
+  workspace_id: Uuid,
+  user_id: Uuid,
+  contact_id: Uuid,
+  from: Option<NaiveDate>,
+  to: Option<NaiveDate>,
+) -> AppResult<ContactStatement> {
+  let contact = state
+    .contact_repository
+    .find_by_id_and_workspace(contact_id, workspace_id, user_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Contact", contact_id))?;
+  let to = to.unwrap_or_else(|| Utc::now().date_naive());
+
+  let repository = &state.statement_repository;
+  let opening_balance = match from {
+    Some(from) => repository.balance_before(workspace_id, contact_id, from).await?,
+    None => Decimal::ZERO,
+  };
+  let transactions = repository.list(workspace_id, contact_id, from, to).await?;
+
+  let mut balance = opening_balance;
+  let (mut total_debit, mut total_credit) = (Decimal::ZERO, Decimal::ZERO);
+  let lines = transactions
+    .into_iter()
+    .map(|transaction| {
+      let (debit, credit) = if transaction.kind.is_debit() {
+        total_debit += transaction.amount;
+        balance += transaction.amount;
+        (Some(transaction.amount), None)
+      } else {
+        total_credit += transaction.amount;
+        balance -= transaction.amount;
+        (None, Some(transaction.amount))
+      };
+      StatementLine {
+        id: transaction.id,
+        occurred_on: transaction.occurred_on,
+        kind: transaction.kind,
+        reference: transaction.reference,
+        description: transaction.description,
+        debit,
+        credit,
+        balance,
+      }
+    })
+    .collect();
+
+  Ok(ContactStatement {
+    contact_id,
+    contact_code: contact.code,
+    contact_name: contact.name,
+    from,
+    to,
+    opening_balance,
+    total_debit,
+    total_credit,
+    closing_balance: balance,
+    lines,
+  })
+}
+
+/// The statement as a CSV file: an opening balance row, one row per transaction and a closing
+/// balance row.
+#[cfg(feature = "exports")]
+pub fn statement_csv(statement: &ContactStatement) -> AppResult<Vec<u8>> {
+  let amount = |value: Option<Decimal>| value.map(|value| value.round_dp(2).to_string()).unwrap_or_default();
+  let header = vec!["date", "kind", "reference", "description", "debit", "credit", "balance"];
+
+  let opening_date = statement.from.map(|from| from.to_string()).unwrap_or_default();
+  let mut rows = vec![vec![
+    opening_date,
+    "opening_balance".to_string(),
+    String::new(),
+    String::new(),
+    String::new(),
+    String::new(),
+    amount(Some(statement.opening_balance)),
+  ]];
+  rows.extend(statement.lines.iter().map(|line| {
+    vec![
+      line.occurred_on.to_string(),
+      line.kind.as_str().to_string(),
+      line.reference.clone(),
+      line.description.clone().unwrap_or_default(),
+      amount(line.debit),
+      amount(line.credit),
+      amount(Some(line.balance)),
+    ]
+  }));
+  rows.push(vec![
+    statement.to.to_string(),
+    "closing_balance".to_string(),
+    String::new(),
+    String::new(),
+    amount(Some(statement.total_debit)),
+    amount(Some(statement.total_credit)),
+    amount(Some(statement.closing_balance)),
+  ]);
+
+  crate::modules::exports::export_service::write_csv(header, rows)
+}
@@ -0,0 +1,12 @@
+//! Trial periods of workspaces.
+//!
+//! Every workspace starts a trial when it is created (see the `workspace_trials` migration).
+//! `trial_notifier::run_notifier` warns its members a few days before the trial ends and tells
+//! them once it has ended; from then on `middleware::trial_middleware` rejects writes to the
+//! workspace until it has an active or past due subscription. Reads, signing in and billing keep
+//! working, so members can still get at their data and upgrade.
+
+pub mod trial_handlers;
+pub mod trial_models;
+pub mod trial_notifier;
+pub mod trial_repository;
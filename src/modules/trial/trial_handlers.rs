@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use axum::{extract::State, response::Json};
+use chrono::Utc;
+
+use super::trial_models::TrialResponse;
+use crate::{AppResult, errors::AppError, helper::PathUuid, modules::auth::current_user::CurrentUser, responses::ApiResponse, state::AppState};
+
+/// The trial of a workspace and whether it is still running. Visible to every member of the workspace.
+pub async fn get_trial(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(workspace_id): PathUuid,
+) -> AppResult<Json<ApiResponse<TrialResponse>>> {
+  // Check if user has access to this workspace
+  let role = state
+    .workspace_repository
+    .check_user_workspace_access(current_user.user_id, workspace_id)
+    .await?;
+
+  if role.is_none() {
+    return Err(AppError::Authorization("Access denied to workspace".to_string()));
+  }
+
+  let trial = state
+    .trial_repository
+    .get_trial(workspace_id)
+    .await?
+    .ok_or_else(|| AppError::not_found("Workspace trial"))?;
+
+  let response = ApiResponse::success(TrialResponse::new(&trial, Utc::now()), "Trial retrieved successfully");
+  Ok(Json(response))
+}
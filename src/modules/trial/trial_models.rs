@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// The trial of a workspace, and whether a paid subscription has replaced it.
+#[derive(Debug, Clone)]
+pub struct WorkspaceTrial {
+  pub workspace_id: Uuid,
+  pub started_at: DateTime<Utc>,
+  pub ends_at: DateTime<Utc>,
+  pub paid: bool,
+}
+
+impl WorkspaceTrial {
+  pub fn state(&self, now: DateTime<Utc>) -> TrialState {
+    if self.paid {
+      TrialState::Paid
+    } else if now < self.ends_at {
+      TrialState::Trialing
+    } else {
+      TrialState::Expired
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrialState {
+  /// The trial is running; the workspace is fully usable.
+  Trialing,
+  /// The trial ended without a paid plan; the workspace is read-only.
+  Expired,
+  /// The workspace has an active or past due subscription.
+  Paid,
+}
+
+/// A workspace whose members are due a trial notification.
+#[derive(Debug, Clone)]
+pub struct TrialNotice {
+  pub workspace_id: Uuid,
+  pub ends_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrialResponse {
+  pub workspace_id: Uuid,
+  pub state: TrialState,
+  pub started_at: DateTime<Utc>,
+  pub ends_at: DateTime<Utc>,
+  /// Whole days left in the trial, 0 once it ended.
+  pub days_remaining: i64,
+}
+
+impl TrialResponse {
+  pub fn new(trial: &WorkspaceTrial, now: DateTime<Utc>) -> Self {
+    Self {
+      workspace_id: trial.workspace_id,
+      state: trial.state(now),
+      started_at: trial.started_at,
+      ends_at: trial.ends_at,
+      days_remaining: (trial.ends_at - now).num_days().max(0),
+    }
+  }
+}
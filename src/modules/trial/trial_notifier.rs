@@ -0,0 +1,56 @@
+//! Notifies workspaces about the end of their trial.
+//!
+//! Notifications are published on the event bus as `notification` events for the whole
+//! workspace, so they reach the members connected to the instance that sent them. Each one is
+//! claimed in the database before it is sent and is therefore sent at most once.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use tracing::{debug, warn};
+
+use crate::{AppResult, events::WorkspaceEvent, state::AppState};
+
+/// Sends the due expiry warnings and expiry notices, returning how many were sent.
+pub async fn notify_trials(state: &AppState) -> AppResult<usize> {
+  let repository = &state.trial_repository;
+  let warn_until = Utc::now() + chrono::Duration::days(state.config.trial.warning_days.into());
+
+  let warnings = repository.claim_expiry_warnings(warn_until).await?;
+  for notice in &warnings {
+    let message = format!(
+      "The trial of this workspace ends on {}. Upgrade to a paid plan to keep making changes afterwards.",
+      notice.ends_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    state
+      .events
+      .publish(WorkspaceEvent::notification(notice.workspace_id, None, "Your trial ends soon", &message));
+  }
+
+  let expired = repository.claim_expired().await?;
+  for notice in &expired {
+    state.events.publish(WorkspaceEvent::notification(
+      notice.workspace_id,
+      None,
+      "Your trial has ended",
+      "This workspace is now read-only. Upgrade to a paid plan to make changes again.",
+    ));
+  }
+
+  Ok(warnings.len() + expired.len())
+}
+
+/// Checks for trial notifications every `trial.check_interval_secs` for the lifetime of the process.
+pub async fn run_notifier(state: Arc<AppState>) {
+  let mut interval = tokio::time::interval(Duration::from_secs(state.config.trial.check_interval_secs));
+  loop {
+    interval.tick().await;
+    let result = notify_trials(&state).await;
+    state.task_health.record("trial_notifier", &result);
+    match result {
+      Ok(0) => {}
+      Ok(sent) => debug!("Sent {} trial notifications", sent),
+      Err(e) => warn!("Failed to send trial notifications, retrying with the next check: {}", e),
+    }
+  }
+}
@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::trial_models::{TrialNotice, WorkspaceTrial};
+use crate::{errors::AppError, utils::DbExecutor};
+
+#[async_trait]
+pub trait TrialRepository: Send + Sync {
+  /// The trial of a workspace, `None` when the workspace does not exist.
+  async fn get_trial(&self, workspace_id: Uuid) -> Result<Option<WorkspaceTrial>, AppError>;
+  /// Marks the unpaid trials ending before `warn_until` that were not warned about yet as warned,
+  /// and returns them. Trials that already ended are left to `claim_expired`.
+  async fn claim_expiry_warnings(&self, warn_until: DateTime<Utc>) -> Result<Vec<TrialNotice>, AppError>;
+  /// Marks the unpaid trials that ended and were not reported yet as reported, and returns them.
+  async fn claim_expired(&self) -> Result<Vec<TrialNotice>, AppError>;
+}
+
+/// Trials are read from the primary, so a workspace can write again as soon as its subscription
+/// is recorded.
+pub struct PostgresTrialRepository {
+  db: DbExecutor,
+}
+
+impl PostgresTrialRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl TrialRepository for PostgresTrialRepository {
+  async fn get_trial(&self, workspace_id: Uuid) -> Result<Option<WorkspaceTrial>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let trial = sqlx::query_as!(
+      WorkspaceTrial,
+      r#"
+        SELECT t.workspace_id, t.started_at, t.ends_at,
+               COALESCE(billed_subscription_status(t.workspace_id) IN ('active', 'past_due'), false) AS "paid!"
+        FROM workspace_trials t
+        WHERE t.workspace_id = $1
+        "#,
+      workspace_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(trial)
+  }
+
+  async fn claim_expiry_warnings(&self, warn_until: DateTime<Utc>) -> Result<Vec<TrialNotice>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    // Claiming in the UPDATE sends each warning once, however many instances run the check
+    let notices = sqlx::query_as!(
+      TrialNotice,
+      r#"
+        UPDATE workspace_trials t
+        SET warning_sent_at = NOW()
+        WHERE t.warning_sent_at IS NULL
+          AND t.ends_at > NOW() AND t.ends_at <= $1
+          AND COALESCE(billed_subscription_status(t.workspace_id) NOT IN ('active', 'past_due'), true)
+        RETURNING t.workspace_id, t.ends_at
+        "#,
+      warn_until
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(notices)
+  }
+
+  async fn claim_expired(&self) -> Result<Vec<TrialNotice>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let notices = sqlx::query_as!(
+      TrialNotice,
+      r#"
+        UPDATE workspace_trials t
+        SET expiry_sent_at = NOW()
+        WHERE t.expiry_sent_at IS NULL
+          AND t.ends_at <= NOW()
+          AND COALESCE(billed_subscription_status(t.workspace_id) NOT IN ('active', 'past_due'), true)
+        RETURNING t.workspace_id, t.ends_at
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(notices)
+  }
+}
@@ -0,0 +1,11 @@
+//! Polling triggers for automation platforms that cannot receive webhooks.
+//!
+//! `GET /api/v1/triggers/:entity/new-or-updated` returns the records of a workspace created or
+//! changed since a cursor, newest first, each with an id that is unique per change, so platforms
+//! like Zapier and Make can deduplicate what they have already seen. Changes of the last few
+//! seconds are held back until their transactions have surely committed, which keeps a cursor
+//! from moving past a row that becomes visible later. Deleted records are not reported.
+
+pub mod trigger_handlers;
+pub mod trigger_models;
+pub mod trigger_routes;
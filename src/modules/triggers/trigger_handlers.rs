@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use axum::{
+  Json,
+  extract::{Path, State},
+  response::{IntoResponse, Response},
+};
+use chrono::{Duration, Utc};
+
+use super::trigger_models::{DEFAULT_TRIGGER_LIMIT, TriggerCursor, TriggerEntity, TriggerItem, TriggerPage};
+#[cfg(feature = "contacts")]
+use crate::modules::datastores::contacts::contact_models::ContactResponse;
+#[cfg(feature = "products")]
+use crate::modules::datastores::products::product_models::ProductResponse;
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{RequireRole, RequiredWorkspace, ValidatedQuery, workspace::role::Member},
+  modules::{auth::current_user::CurrentUser, triggers::trigger_models::TriggerQuery},
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// The contacts or products of the workspace created or changed since `since`, newest first.
+///
+/// # Arguments
+///
+/// * `State(state)`: The shared application state.
+/// * `current_user`: The authenticated user extracted from the JWT token.
+/// * `_member`: Rejects callers below the member role in the workspace.
+/// * `Path(entity)`: `contacts` or `products`.
+/// * `ValidatedQuery(query)`: The cursor or timestamp to poll from, and the page size.
+///
+/// # Returns
+///
+/// A `Json` response with the `TriggerPage`, or a 422 when `entity` or `since` is invalid.
+pub async fn new_or_updated(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  RequiredWorkspace(workspace_id): RequiredWorkspace,
+  _member: RequireRole<Member>,
+  Path(entity): Path<String>,
+  ValidatedQuery(query): ValidatedQuery<TriggerQuery>,
+) -> AppResult<Response> {
+  let entity = TriggerEntity::parse(&entity).ok_or_else(|| {
+    let entities: Vec<&str> = TriggerEntity::ALL.iter().map(|entity| entity.as_str()).collect();
+    AppError::validation(
+      "entity",
+      &format!("Unknown trigger entity '{}', expected one of {}", entity, entities.join(", ")),
+    )
+  })?;
+  let since = query.since.as_deref().map(TriggerCursor::parse_since).transpose()?;
+  let limit = query.limit.unwrap_or(DEFAULT_TRIGGER_LIMIT);
+  // One past the limit tells whether more changes follow the cursor
+  let fetch = if since.is_some() { limit + 1 } else { limit };
+  let after = since.map(|since| (since.updated_at, since.id));
+  let settled_before = Utc::now() - Duration::seconds(state.config.triggers.settle_secs as i64);
+
+  let response = match entity {
+    #[cfg(feature = "contacts")]
+    TriggerEntity::Contacts => {
+      let contacts = state
+        .contact_repository
+        .find_changed(workspace_id, current_user.user_id, after, settled_before, fetch)
+        .await?;
+      let items = contacts
+        .into_iter()
+        .map(|contact| TriggerItem::new(contact.id, contact.created_at, contact.updated_at, ContactResponse::from(contact)))
+        .collect();
+      Json(ApiResponse::success(
+        TriggerPage::new(items, since, limit as usize),
+        "Changes retrieved successfully",
+      ))
+      .into_response()
+    }
+    #[cfg(feature = "products")]
+    TriggerEntity::Products => {
+      let products = state
+        .product_repository
+        .find_changed(workspace_id, current_user.user_id, after, settled_before, fetch)
+        .await?;
+      let items = products
+        .into_iter()
+        .map(|product| TriggerItem::new(product.id, product.created_at, product.updated_at, ProductResponse::from(product)))
+        .collect();
+      Json(ApiResponse::success(
+        TriggerPage::new(items, since, limit as usize),
+        "Changes retrieved successfully",
+      ))
+      .into_response()
+    }
+  };
+
+  Ok(response)
+}
@@ -0,0 +1,149 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{AppResult, errors::AppError};
+
+/// Changes returned per poll when no `limit` is given.
+pub const DEFAULT_TRIGGER_LIMIT: i64 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerEntity {
+  #[cfg(feature = "contacts")]
+  Contacts,
+  #[cfg(feature = "products")]
+  Products,
+}
+
+impl TriggerEntity {
+  pub const ALL: &[TriggerEntity] = &[
+    #[cfg(feature = "contacts")]
+    TriggerEntity::Contacts,
+    #[cfg(feature = "products")]
+    TriggerEntity::Products,
+  ];
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      #[cfg(feature = "contacts")]
+      TriggerEntity::Contacts => "contacts",
+      #[cfg(feature = "products")]
+      TriggerEntity::Products => "products",
+    }
+  }
+
+  pub fn parse(value: &str) -> Option<Self> {
+    Self::ALL.iter().copied().find(|entity| entity.as_str() == value)
+  }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct TriggerQuery {
+  /// The `next_cursor` of the previous poll, or an RFC 3339 timestamp. Without it, the latest
+  /// changes are returned, which platforms use as samples when a trigger is set up.
+  pub since: Option<String>,
+  #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
+  pub limit: Option<i64>,
+}
+
+/// A position in the changes of a workspace: everything up to and including the change of
+/// `id` at `updated_at`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerCursor {
+  pub updated_at: DateTime<Utc>,
+  pub id: Uuid,
+}
+
+impl TriggerCursor {
+  pub fn encode(&self) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}:{}", self.updated_at.timestamp_micros(), self.id))
+  }
+
+  fn decode(value: &str) -> Option<Self> {
+    let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(value).ok()?).ok()?;
+    let (micros, id) = decoded.split_once(':')?;
+    Some(Self {
+      updated_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+      id: id.parse().ok()?,
+    })
+  }
+
+  /// Reads `since`, either a cursor or a timestamp. A timestamp becomes the position just before
+  /// any change made at that instant.
+  pub fn parse_since(since: &str) -> AppResult<Self> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(since) {
+      return Ok(Self {
+        updated_at: timestamp.with_timezone(&Utc),
+        id: Uuid::nil(),
+      });
+    }
+    Self::decode(since).ok_or_else(|| AppError::validation("since", "Since must be a cursor returned by this endpoint or an RFC 3339 timestamp"))
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerEvent {
+  Created,
+  Updated,
+}
+
+/// One change of a record.
+#[derive(Debug, Serialize)]
+pub struct TriggerItem<T> {
+  /// Unique per change of the record, for platforms to deduplicate on.
+  pub id: String,
+  pub record_id: Uuid,
+  pub event: TriggerEvent,
+  pub updated_at: DateTime<Utc>,
+  /// Polling with this cursor returns the changes after this one.
+  pub cursor: String,
+  pub record: T,
+}
+
+impl<T> TriggerItem<T> {
+  pub fn new(record_id: Uuid, created_at: DateTime<Utc>, updated_at: DateTime<Utc>, record: T) -> Self {
+    Self {
+      id: format!("{}-{}", record_id, updated_at.timestamp_micros()),
+      record_id,
+      event: if created_at == updated_at {
+        TriggerEvent::Created
+      } else {
+        TriggerEvent::Updated
+      },
+      updated_at,
+      cursor: TriggerCursor { updated_at, id: record_id }.encode(),
+      record,
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TriggerPage<T> {
+  /// Newest first.
+  pub items: Vec<TriggerItem<T>>,
+  /// The cursor to poll with next, or `null` when nothing has changed yet.
+  pub next_cursor: Option<String>,
+  /// Whether more changes follow the returned ones, in which case polling again with
+  /// `next_cursor` returns them.
+  pub has_more: bool,
+}
+
+impl<T> TriggerPage<T> {
+  /// Builds the page from the changes after `since`, oldest first and at most one beyond the
+  /// limit, which only tells whether there are more.
+  pub fn new(mut items: Vec<TriggerItem<T>>, since: Option<TriggerCursor>, limit: usize) -> Self {
+    let has_more = since.is_some() && items.len() > limit;
+    items.truncate(limit);
+    let next_cursor = items.last().map(|item| item.cursor.clone()).or_else(|| since.map(|since| since.encode()));
+    items.reverse();
+    Self {
+      items,
+      next_cursor,
+      has_more,
+    }
+  }
+}
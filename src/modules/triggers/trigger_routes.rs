@@ -0,0 +1,11 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+
+use super::trigger_handlers::new_or_updated;
+use crate::state::AppState;
+
+/// The polling triggers, mounted at `/api/v1/triggers` behind the JWT middleware.
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new().route("/:entity/new-or-updated", get(new_or_updated))
+}
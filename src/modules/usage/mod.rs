@@ -0,0 +1,13 @@
+//! API usage metering per workspace and the monthly request quotas of the plans.
+//!
+//! `middleware::usage_middleware` counts every authenticated request made in a workspace in the
+//! `UsageMeter`, which buffers the counters and writes them to `api_usage` in batches, and
+//! rejects requests once the workspace used up the quota of its plan (see `config::UsageConfig`).
+//! Plans may also limit the contacts and products a workspace holds, checked by `record_quota`
+//! when they are created.
+
+pub mod record_quota;
+pub mod usage_handlers;
+pub mod usage_meter;
+pub mod usage_models;
+pub mod usage_repository;
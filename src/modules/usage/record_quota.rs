@@ -0,0 +1,22 @@
+//! The contacts and products each plan may hold (see `config::UsageConfig`).
+
+use uuid::Uuid;
+
+use crate::{AppResult, errors::AppError, state::AppState};
+
+/// Rejects the change with `AppError::RecordQuotaExceeded` if `workspace_id` now holds more
+/// records than its plan allows. Meant to run after the inserts, in their transaction, so it sees
+/// them and a rejected change is rolled back; creates running at the same time may still
+/// overshoot the limit together. Skips the count while neither plan has a limit.
+pub async fn check_record_quota(state: &AppState, workspace_id: Uuid) -> AppResult<()> {
+  let config = &state.config.usage;
+  if config.record_limit(false).is_none() && config.record_limit(true).is_none() {
+    return Ok(());
+  }
+
+  let usage = state.usage_repository.record_usage(workspace_id).await?;
+  match config.record_limit(usage.paid) {
+    Some(limit) if usage.records > i64::try_from(limit).unwrap_or(i64::MAX) => Err(AppError::RecordQuotaExceeded(limit)),
+    _ => Ok(()),
+  }
+}
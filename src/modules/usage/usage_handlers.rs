@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use axum::{extract::State, response::Json};
+use chrono::Utc;
+
+use super::{
+  usage_meter::{month_start, next_month_start},
+  usage_models::{ApiUsageResponse, GetApiUsageQuery, QuotaStatus, UsageCounts, UsagePlan},
+};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::{PathUuid, ValidatedQuery},
+  modules::auth::current_user::CurrentUser,
+  responses::ApiResponse,
+  state::AppState,
+};
+
+/// API usage of a workspace over a period (the current month by default), broken down by day,
+/// route and user, with the quota of its plan. Visible to every member of the workspace.
+pub async fn get_api_usage(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(workspace_id): PathUuid,
+  ValidatedQuery(query): ValidatedQuery<GetApiUsageQuery>,
+) -> AppResult<Json<ApiResponse<ApiUsageResponse>>> {
+  // Check if user has access to this workspace
+  let role = state
+    .workspace_repository
+    .check_user_workspace_access(current_user.user_id, workspace_id)
+    .await?;
+
+  if role.is_none() {
+    return Err(AppError::Authorization("Access denied to workspace".to_string()));
+  }
+
+  let now = Utc::now();
+  let from = query.from.unwrap_or_else(|| month_start(now));
+  let to = query.to.unwrap_or_else(|| now.date_naive());
+
+  let repository = &state.usage_repository;
+  let by_day = repository.usage_by_day(workspace_id, from, to).await?;
+  let by_route = repository.usage_by_route(workspace_id, from, to).await?;
+  let by_user = repository.usage_by_user(workspace_id, from, to).await?;
+  let mut totals = UsageCounts::default();
+  by_day.iter().for_each(|day| totals.add(day.counts));
+
+  let month_start = month_start(now);
+  let monthly = repository.monthly_usage(workspace_id, month_start).await?;
+  let quota = QuotaStatus {
+    plan: if monthly.paid { UsagePlan::Paid } else { UsagePlan::Free },
+    limit: state.config.usage.monthly_quota(monthly.paid),
+    used: monthly.requests + state.usage_meter.pending_requests(workspace_id, month_start)?,
+    resets_at: next_month_start(now),
+  };
+
+  let response = ApiResponse::success(
+    ApiUsageResponse {
+      workspace_id,
+      from,
+      to,
+      totals,
+      by_day,
+      by_route,
+      by_user,
+      quota,
+    },
+    "API usage retrieved successfully",
+  );
+  Ok(Json(response))
+}
@@ -0,0 +1,188 @@
+//! In-process buffer of usage counters, written to `api_usage` in batches.
+//!
+//! Requests only touch memory: `record` adds to the counters of the request's workspace, user,
+//! route and day, and `run_flusher` writes all of them in one statement every
+//! `usage.flush_interval_secs`. Counters of a crashed process that were not flushed yet are lost.
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use super::{
+  usage_models::{UsageCounts, UsageKey},
+  usage_repository::UsageRepository,
+};
+use crate::{AppResult, config::UsageConfig, errors::AppError, state::AppState};
+
+/// A workspace's requests this month as last loaded, plus those counted since.
+struct CachedQuota {
+  month_start: NaiveDate,
+  used: i64,
+  limit: Option<u64>,
+  loaded_at: Instant,
+}
+
+/// Buffers usage counters until they are flushed, and tracks the monthly total of each
+/// workspace to enforce its quota without a query per request.
+#[derive(Default)]
+pub struct UsageMeter {
+  pending: Mutex<HashMap<UsageKey, UsageCounts>>,
+  quotas: Mutex<HashMap<Uuid, CachedQuota>>,
+}
+
+impl UsageMeter {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Counts one request of `user_id` in `workspace_id` on `route`.
+  pub fn record(&self, workspace_id: Uuid, user_id: Uuid, route: &str, request_bytes: u64, response_bytes: u64) {
+    let key = UsageKey {
+      workspace_id,
+      user_id,
+      route: route.to_string(),
+      day: Utc::now().date_naive(),
+    };
+    let counts = UsageCounts {
+      requests: 1,
+      request_bytes: request_bytes.try_into().unwrap_or(i64::MAX),
+      response_bytes: response_bytes.try_into().unwrap_or(i64::MAX),
+    };
+
+    if let Ok(mut pending) = self.pending.lock() {
+      pending.entry(key).or_default().add(counts);
+    }
+    if let Ok(mut quotas) = self.quotas.lock()
+      && let Some(quota) = quotas.get_mut(&workspace_id)
+    {
+      quota.used += 1;
+    }
+  }
+
+  /// Writes the buffered counters in one batch and returns how many rows it touched. When the
+  /// write fails the counters are put back, to be retried with the next flush.
+  pub async fn flush(&self, repository: &dyn UsageRepository) -> AppResult<usize> {
+    let batch: Vec<(UsageKey, UsageCounts)> = self.lock_pending()?.drain().collect();
+    if batch.is_empty() {
+      return Ok(0);
+    }
+
+    if let Err(e) = repository.record_batch(&batch).await {
+      let mut pending = self.lock_pending()?;
+      for (key, counts) in batch {
+        pending.entry(key).or_default().add(counts);
+      }
+      return Err(e);
+    }
+    Ok(batch.len())
+  }
+
+  /// Rejects the request with `AppError::QuotaExceeded` once `workspace_id` made as many requests
+  /// this month as its plan allows. The monthly total is reloaded every `config.quota_cache_secs`,
+  /// so instances sharing a workspace may together overshoot the quota by what they counted since.
+  pub async fn check_quota(&self, workspace_id: Uuid, repository: &dyn UsageRepository, config: &UsageConfig) -> AppResult<()> {
+    let now = Utc::now();
+    let month_start = month_start(now);
+    let cache_ttl = Duration::from_secs(config.quota_cache_secs);
+
+    let cached = self.lock_quotas()?.get(&workspace_id).and_then(|quota| {
+      let fresh = quota.month_start == month_start && quota.loaded_at.elapsed() < cache_ttl;
+      fresh.then_some((quota.used, quota.limit))
+    });
+
+    let (used, limit) = match cached {
+      Some(cached) => cached,
+      None => {
+        let usage = repository.monthly_usage(workspace_id, month_start).await?;
+        let limit = config.monthly_quota(usage.paid);
+        // Requests counted but not flushed yet are not in the table
+        let used = usage.requests + self.pending_requests(workspace_id, month_start)?;
+        self.lock_quotas()?.insert(
+          workspace_id,
+          CachedQuota {
+            month_start,
+            used,
+            limit,
+            loaded_at: Instant::now(),
+          },
+        );
+        (used, limit)
+      }
+    };
+
+    match limit {
+      Some(limit) if used >= i64::try_from(limit).unwrap_or(i64::MAX) => Err(AppError::QuotaExceeded {
+        limit,
+        resets_at: next_month_start(now),
+      }),
+      _ => Ok(()),
+    }
+  }
+
+  /// Forgets the cached monthly total of a workspace, e.g. after its plan changed.
+  pub fn invalidate(&self, workspace_id: Uuid) {
+    if let Ok(mut quotas) = self.quotas.lock() {
+      quotas.remove(&workspace_id);
+    }
+  }
+
+  /// Requests of `workspace_id` since `since` that were counted but not flushed yet.
+  pub fn pending_requests(&self, workspace_id: Uuid, since: NaiveDate) -> AppResult<i64> {
+    Ok(
+      self
+        .lock_pending()?
+        .iter()
+        .filter(|(key, _)| key.workspace_id == workspace_id && key.day >= since)
+        .map(|(_, counts)| counts.requests)
+        .sum(),
+    )
+  }
+
+  fn lock_pending(&self) -> AppResult<std::sync::MutexGuard<'_, HashMap<UsageKey, UsageCounts>>> {
+    self
+      .pending
+      .lock()
+      .map_err(|_| AppError::Internal("Usage meter lock poisoned".to_string()))
+  }
+
+  fn lock_quotas(&self) -> AppResult<std::sync::MutexGuard<'_, HashMap<Uuid, CachedQuota>>> {
+    self
+      .quotas
+      .lock()
+      .map_err(|_| AppError::Internal("Usage meter lock poisoned".to_string()))
+  }
+}
+
+/// Midnight UTC of the first day of the month of `now`.
+pub fn month_start(now: DateTime<Utc>) -> NaiveDate {
+  now.date_naive().with_day(1).expect("every month has a first day")
+}
+
+/// When the quotas of the month of `now` reset.
+pub fn next_month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+  (month_start(now) + Months::new(1))
+    .and_hms_opt(0, 0, 0)
+    .expect("midnight is a valid time")
+    .and_utc()
+}
+
+/// Flushes the usage counters every `usage.flush_interval_secs` for the lifetime of the process.
+pub async fn run_flusher(state: Arc<AppState>) {
+  let mut interval = tokio::time::interval(Duration::from_secs(state.config.usage.flush_interval_secs));
+  loop {
+    interval.tick().await;
+    let result = state.usage_meter.flush(state.usage_repository.as_ref()).await;
+    state.task_health.record("usage_flusher", &result);
+    match result {
+      Ok(0) => {}
+      Ok(rows) => debug!("Flushed {} usage counters", rows),
+      Err(e) => warn!("Failed to flush usage counters, retrying with the next flush: {}", e),
+    }
+  }
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// One batch row: the counters of a workspace, user and route on one day.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UsageKey {
+  pub workspace_id: Uuid,
+  pub user_id: Uuid,
+  /// The route template, e.g. `/api/v1/contacts/:id`.
+  pub route: String,
+  pub day: NaiveDate,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageCounts {
+  pub requests: i64,
+  pub request_bytes: i64,
+  pub response_bytes: i64,
+}
+
+impl UsageCounts {
+  pub fn add(&mut self, other: UsageCounts) {
+    self.requests += other.requests;
+    self.request_bytes += other.request_bytes;
+    self.response_bytes += other.response_bytes;
+  }
+}
+
+/// The requests a workspace made this month and whether it is on the paid plan.
+#[derive(Debug, Clone, Copy)]
+pub struct MonthlyUsage {
+  pub requests: i64,
+  pub paid: bool,
+}
+
+/// The contacts and products a workspace holds and whether it is on the paid plan.
+#[derive(Debug, Clone, Copy)]
+pub struct RecordUsage {
+  pub records: i64,
+  pub paid: bool,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_period"))]
+pub struct GetApiUsageQuery {
+  /// First day of the period, inclusive; defaults to the first day of the current month.
+  pub from: Option<NaiveDate>,
+  /// Last day of the period, inclusive; defaults to today.
+  pub to: Option<NaiveDate>,
+}
+
+fn validate_period(query: &GetApiUsageQuery) -> Result<(), ValidationError> {
+  match (query.from, query.to) {
+    (Some(from), Some(to)) if from > to => Err(ValidationError::new("period").with_message("from cannot be after to".into())),
+    _ => Ok(()),
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RouteUsage {
+  pub route: String,
+  #[serde(flatten)]
+  pub counts: UsageCounts,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserUsage {
+  pub user_id: Uuid,
+  #[serde(flatten)]
+  pub counts: UsageCounts,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyUsage {
+  pub day: NaiveDate,
+  #[serde(flatten)]
+  pub counts: UsageCounts,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsagePlan {
+  Free,
+  Paid,
+}
+
+/// The monthly quota of the workspace's plan, counted over the current calendar month (UTC).
+#[derive(Debug, Serialize)]
+pub struct QuotaStatus {
+  pub plan: UsagePlan,
+  /// `null` when the plan is unlimited.
+  pub limit: Option<u64>,
+  pub used: i64,
+  pub resets_at: DateTime<Utc>,
+}
+
+/// The response of `GET /workspaces/:workspace_id/usage/api`. Counters lag behind by up to one
+/// flush interval, the quota does not.
+#[derive(Debug, Serialize)]
+pub struct ApiUsageResponse {
+  pub workspace_id: Uuid,
+  pub from: NaiveDate,
+  pub to: NaiveDate,
+  pub totals: UsageCounts,
+  pub by_day: Vec<DailyUsage>,
+  pub by_route: Vec<RouteUsage>,
+  pub by_user: Vec<UserUsage>,
+  pub quota: QuotaStatus,
+}
@@ -0,0 +1,229 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use super::usage_models::{DailyUsage, MonthlyUsage, RecordUsage, RouteUsage, UsageCounts, UsageKey, UserUsage};
+use crate::{
+  errors::AppError,
+  utils::{DbExecutor, ReadPool},
+};
+
+#[async_trait]
+pub trait UsageRepository: Send + Sync {
+  /// Adds a batch of counters to the stored daily totals, in one statement.
+  async fn record_batch(&self, batch: &[(UsageKey, UsageCounts)]) -> Result<(), AppError>;
+  /// The requests a workspace made since `month_start`, and whether it has a paid subscription.
+  async fn monthly_usage(&self, workspace_id: Uuid, month_start: NaiveDate) -> Result<MonthlyUsage, AppError>;
+  /// The contacts and products a workspace holds, deleted ones aside, and whether it has a paid
+  /// subscription.
+  async fn record_usage(&self, workspace_id: Uuid) -> Result<RecordUsage, AppError>;
+  async fn usage_by_day(&self, workspace_id: Uuid, from: NaiveDate, to: NaiveDate) -> Result<Vec<DailyUsage>, AppError>;
+  async fn usage_by_route(&self, workspace_id: Uuid, from: NaiveDate, to: NaiveDate) -> Result<Vec<RouteUsage>, AppError>;
+  async fn usage_by_user(&self, workspace_id: Uuid, from: NaiveDate, to: NaiveDate) -> Result<Vec<UserUsage>, AppError>;
+}
+
+pub struct PostgresUsageRepository {
+  db: DbExecutor,
+  read_pool: ReadPool,
+}
+
+impl PostgresUsageRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    let db = db.into();
+    Self {
+      read_pool: ReadPool::primary_only(db.clone()),
+      db,
+    }
+  }
+
+  /// Runs the usage reports on `read_pool` instead of the primary. The quota lookup stays on the
+  /// primary, so a lagging replica cannot let a workspace exceed its quota.
+  pub fn with_read_pool(mut self, read_pool: ReadPool) -> Self {
+    self.read_pool = read_pool;
+    self
+  }
+}
+
+#[async_trait]
+impl UsageRepository for PostgresUsageRepository {
+  async fn record_batch(&self, batch: &[(UsageKey, UsageCounts)]) -> Result<(), AppError> {
+    if batch.is_empty() {
+      return Ok(());
+    }
+
+    let workspace_ids: Vec<Uuid> = batch.iter().map(|(key, _)| key.workspace_id).collect();
+    let user_ids: Vec<Uuid> = batch.iter().map(|(key, _)| key.user_id).collect();
+    let routes: Vec<String> = batch.iter().map(|(key, _)| key.route.clone()).collect();
+    let days: Vec<NaiveDate> = batch.iter().map(|(key, _)| key.day).collect();
+    let requests: Vec<i64> = batch.iter().map(|(_, counts)| counts.requests).collect();
+    let request_bytes: Vec<i64> = batch.iter().map(|(_, counts)| counts.request_bytes).collect();
+    let response_bytes: Vec<i64> = batch.iter().map(|(_, counts)| counts.response_bytes).collect();
+
+    let mut conn = self.db.acquire().await?;
+    // Workspaces or users deleted since the requests were made are skipped by the join
+    sqlx::query!(
+      r#"
+        INSERT INTO api_usage (workspace_id, user_id, route, day, request_count, request_bytes, response_bytes)
+        SELECT batch.workspace_id, batch.user_id, batch.route, batch.day, batch.requests, batch.request_bytes, batch.response_bytes
+        FROM UNNEST($1::uuid[], $2::uuid[], $3::text[], $4::date[], $5::bigint[], $6::bigint[], $7::bigint[])
+          AS batch(workspace_id, user_id, route, day, requests, request_bytes, response_bytes)
+        JOIN workspaces w ON w.id = batch.workspace_id
+        JOIN users u ON u.id = batch.user_id
+        ON CONFLICT (workspace_id, day, user_id, route) DO UPDATE
+        SET request_count = api_usage.request_count + EXCLUDED.request_count,
+            request_bytes = api_usage.request_bytes + EXCLUDED.request_bytes,
+            response_bytes = api_usage.response_bytes + EXCLUDED.response_bytes
+        "#,
+      &workspace_ids,
+      &user_ids,
+      &routes,
+      &days,
+      &requests,
+      &request_bytes,
+      &response_bytes
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn monthly_usage(&self, workspace_id: Uuid, month_start: NaiveDate) -> Result<MonthlyUsage, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let row = sqlx::query!(
+      r#"
+        SELECT
+          (SELECT COALESCE(SUM(request_count), 0)::BIGINT FROM api_usage WHERE workspace_id = $1 AND day >= $2) AS "requests!",
+          COALESCE(billed_subscription_status($1) IN ('active', 'past_due'), false) AS "paid!"
+        "#,
+      workspace_id,
+      month_start
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(MonthlyUsage {
+      requests: row.requests,
+      paid: row.paid,
+    })
+  }
+
+  async fn record_usage(&self, workspace_id: Uuid) -> Result<RecordUsage, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let row = sqlx::query!(
+      r#"
+        SELECT
+          (SELECT COUNT(*) FROM contacts WHERE workspace_id = $1 AND deleted_at IS NULL)
+            + (SELECT COUNT(*) FROM products WHERE workspace_id = $1 AND deleted_at IS NULL) AS "records!",
+          COALESCE(billed_subscription_status($1) IN ('active', 'past_due'), false) AS "paid!"
+        "#,
+      workspace_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(RecordUsage {
+      records: row.records,
+      paid: row.paid,
+    })
+  }
+
+  async fn usage_by_day(&self, workspace_id: Uuid, from: NaiveDate, to: NaiveDate) -> Result<Vec<DailyUsage>, AppError> {
+    let mut conn = self.read_pool.acquire().await?;
+    let rows = sqlx::query!(
+      r#"
+        SELECT day, SUM(request_count)::BIGINT AS "requests!", SUM(request_bytes)::BIGINT AS "request_bytes!",
+               SUM(response_bytes)::BIGINT AS "response_bytes!"
+        FROM api_usage
+        WHERE workspace_id = $1 AND day BETWEEN $2 AND $3
+        GROUP BY day
+        ORDER BY day
+        "#,
+      workspace_id,
+      from,
+      to
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|row| DailyUsage {
+          day: row.day,
+          counts: UsageCounts {
+            requests: row.requests,
+            request_bytes: row.request_bytes,
+            response_bytes: row.response_bytes,
+          },
+        })
+        .collect(),
+    )
+  }
+
+  async fn usage_by_route(&self, workspace_id: Uuid, from: NaiveDate, to: NaiveDate) -> Result<Vec<RouteUsage>, AppError> {
+    let mut conn = self.read_pool.acquire().await?;
+    let rows = sqlx::query!(
+      r#"
+        SELECT route, SUM(request_count)::BIGINT AS "requests!", SUM(request_bytes)::BIGINT AS "request_bytes!",
+               SUM(response_bytes)::BIGINT AS "response_bytes!"
+        FROM api_usage
+        WHERE workspace_id = $1 AND day BETWEEN $2 AND $3
+        GROUP BY route
+        ORDER BY 2 DESC, route
+        "#,
+      workspace_id,
+      from,
+      to
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|row| RouteUsage {
+          route: row.route,
+          counts: UsageCounts {
+            requests: row.requests,
+            request_bytes: row.request_bytes,
+            response_bytes: row.response_bytes,
+          },
+        })
+        .collect(),
+    )
+  }
+
+  async fn usage_by_user(&self, workspace_id: Uuid, from: NaiveDate, to: NaiveDate) -> Result<Vec<UserUsage>, AppError> {
+    let mut conn = self.read_pool.acquire().await?;
+    let rows = sqlx::query!(
+      r#"
+        SELECT user_id, SUM(request_count)::BIGINT AS "requests!", SUM(request_bytes)::BIGINT AS "request_bytes!",
+               SUM(response_bytes)::BIGINT AS "response_bytes!"
+        FROM api_usage
+        WHERE workspace_id = $1 AND day BETWEEN $2 AND $3
+        GROUP BY user_id
+        ORDER BY 2 DESC, user_id
+        "#,
+      workspace_id,
+      from,
+      to
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|row| UserUsage {
+          user_id: row.user_id,
+          counts: UsageCounts {
+            requests: row.requests,
+            request_bytes: row.request_bytes,
+            response_bytes: row.response_bytes,
+          },
+        })
+        .collect(),
+    )
+  }
+}
@@ -0,0 +1,11 @@
+//! Personal data exports (`POST /api/v1/auth/me/export`).
+//!
+//! A user asks for an export of everything tied to their account; the archive is built on a
+//! spawned task by `user_export_service`, kept as JSON in `user_exports`, and an email with a
+//! link to it is sent through the `Mailer` once it is ready. Ready archives can be downloaded
+//! until they expire after `EXPORT_RETENTION_DAYS`, when the retention purger removes them.
+
+pub mod user_export_handlers;
+pub mod user_export_models;
+pub mod user_export_repository;
+pub mod user_export_service;
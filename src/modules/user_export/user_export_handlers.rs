@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use axum::{
+  extract::State,
+  http::{StatusCode, header},
+  response::{IntoResponse, Json, Response},
+};
+use chrono::Utc;
+
+use super::{user_export_models::UserExport, user_export_service::request_export};
+use crate::{AppResult, errors::AppError, helper::PathUuid, modules::auth::current_user::CurrentUser, responses::ApiResponse, state::AppState};
+
+/// Starts an export of the caller's data. Responds `202 Accepted` with the pending export; the
+/// caller is emailed once it is ready, or can poll `/auth/me/exports/{id}`.
+pub async fn request_user_export(State(state): State<Arc<AppState>>, current_user: CurrentUser) -> AppResult<Response> {
+  let export = request_export(state, current_user.user_id).await?;
+  let response = ApiResponse::success(export, "Your data export is being prepared");
+  Ok((StatusCode::ACCEPTED, Json(response)).into_response())
+}
+
+pub async fn get_user_export(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(id): PathUuid,
+) -> AppResult<Json<ApiResponse<UserExport>>> {
+  let export = state
+    .user_export_repository
+    .get(id, current_user.user_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Export", id))?;
+
+  let response = ApiResponse::success(export, "Export retrieved successfully");
+  Ok(Json(response))
+}
+
+/// Sends the archive of a ready export as a JSON attachment.
+pub async fn download_user_export(State(state): State<Arc<AppState>>, current_user: CurrentUser, PathUuid(id): PathUuid) -> AppResult<Response> {
+  let repository = &state.user_export_repository;
+  let export = repository
+    .get(id, current_user.user_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Export", id))?;
+  if !export.is_downloadable(Utc::now()) {
+    return Err(AppError::BadRequest("This export is not ready to download or has expired".to_string()));
+  }
+  let stored = repository
+    .get_archive(id, current_user.user_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("Export", id))?;
+
+  let disposition = format!("attachment; filename=\"data-export-{}.json\"", stored.completed_at.format("%Y%m%d"));
+  Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(stored.archive)).into_response())
+}
@@ -0,0 +1,90 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::modules::{auth::user_dto::UserResponse, datastores::workspaces::WorkspaceRole};
+
+/// Version of the archive layout, raised whenever a field changes meaning or is removed.
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "user_export_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum UserExportStatus {
+  /// The archive is being built.
+  Pending,
+  /// The archive can be downloaded until `expires_at`.
+  Ready,
+  Failed,
+}
+
+/// An export as returned by the API; the archive itself is only sent by the download endpoint.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct UserExport {
+  pub id: Uuid,
+  pub user_id: Uuid,
+  pub status: UserExportStatus,
+  pub error: Option<String>,
+  pub requested_at: DateTime<Utc>,
+  pub completed_at: Option<DateTime<Utc>>,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl UserExport {
+  /// Whether the archive can be downloaded at `now`.
+  pub fn is_downloadable(&self, now: DateTime<Utc>) -> bool {
+    self.status == UserExportStatus::Ready && self.expires_at.is_none_or(|expires_at| expires_at > now)
+  }
+}
+
+/// Everything tied to a user, as downloaded from `/api/v1/auth/me/exports/{id}/download`.
+#[derive(Debug, Serialize)]
+pub struct UserExportArchive {
+  pub format_version: u32,
+  pub generated_at: DateTime<Utc>,
+  pub profile: UserResponse,
+  pub memberships: Vec<ExportedMembership>,
+  /// The requests the user made, per workspace, day and route.
+  pub api_usage: Vec<ExportedApiUsage>,
+  /// The records the user created, without their content, which belongs to the workspace.
+  pub created_records: Vec<ExportedRecord>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ExportedMembership {
+  pub workspace_id: Uuid,
+  pub workspace_name: String,
+  pub role: WorkspaceRole,
+  pub is_owner: bool,
+  pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ExportedApiUsage {
+  pub workspace_id: Uuid,
+  pub day: NaiveDate,
+  pub route: String,
+  pub request_count: i64,
+  pub request_bytes: i64,
+  pub response_bytes: i64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ExportedRecord {
+  /// The table the record is kept in, e.g. `contacts`.
+  pub kind: String,
+  pub id: Uuid,
+  pub workspace_id: Uuid,
+  pub code: String,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// A stored archive, as read back for download.
+#[derive(Debug)]
+pub struct StoredArchive {
+  pub archive: Value,
+  pub completed_at: DateTime<Utc>,
+}
@@ -0,0 +1,248 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::user_export_models::{ExportedApiUsage, ExportedMembership, ExportedRecord, StoredArchive, UserExport, UserExportStatus};
+use crate::{
+  errors::AppError,
+  modules::datastores::workspaces::WorkspaceRole,
+  utils::{DbExecutor, tenant_schema},
+};
+
+#[async_trait]
+pub trait UserExportRepository: Send + Sync {
+  /// Records a new pending export. Returns `None` while the user already has one pending; a
+  /// pending export older than an hour was interrupted by a restart and is failed first.
+  async fn create(&self, user_id: Uuid) -> Result<Option<UserExport>, AppError>;
+  async fn complete(&self, id: Uuid, archive: &Value, expires_at: DateTime<Utc>) -> Result<(), AppError>;
+  async fn fail(&self, id: Uuid, error: &str) -> Result<(), AppError>;
+  /// An export of `user_id`; exports of other users are not found.
+  async fn get(&self, id: Uuid, user_id: Uuid) -> Result<Option<UserExport>, AppError>;
+  /// The archive of a ready export of `user_id`.
+  async fn get_archive(&self, id: Uuid, user_id: Uuid) -> Result<Option<StoredArchive>, AppError>;
+  /// Removes the exports that expired before `now`, returning how many were removed.
+  async fn delete_expired(&self, now: DateTime<Utc>) -> Result<u64, AppError>;
+  async fn memberships(&self, user_id: Uuid) -> Result<Vec<ExportedMembership>, AppError>;
+  async fn api_usage(&self, user_id: Uuid) -> Result<Vec<ExportedApiUsage>, AppError>;
+  async fn created_records(&self, user_id: Uuid) -> Result<Vec<ExportedRecord>, AppError>;
+}
+
+pub struct PostgresUserExportRepository {
+  db: DbExecutor,
+}
+
+impl PostgresUserExportRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+#[async_trait]
+impl UserExportRepository for PostgresUserExportRepository {
+  async fn create(&self, user_id: Uuid) -> Result<Option<UserExport>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!(
+      r#"
+        UPDATE user_exports
+        SET status = 'failed', error = 'Interrupted before completion', completed_at = NOW()
+        WHERE user_id = $1 AND status = 'pending' AND requested_at < NOW() - INTERVAL '1 hour'
+        "#,
+      user_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let export = sqlx::query_as!(
+      UserExport,
+      r#"
+        INSERT INTO user_exports (user_id)
+        VALUES ($1)
+        ON CONFLICT (user_id) WHERE status = 'pending' DO NOTHING
+        RETURNING id, user_id, status as "status: UserExportStatus", error, requested_at, completed_at, expires_at
+        "#,
+      user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(export)
+  }
+
+  async fn complete(&self, id: Uuid, archive: &Value, expires_at: DateTime<Utc>) -> Result<(), AppError> {
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!(
+      r#"
+        UPDATE user_exports
+        SET status = 'ready', archive = $2, completed_at = NOW(), expires_at = $3
+        WHERE id = $1
+        "#,
+      id,
+      archive,
+      expires_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn fail(&self, id: Uuid, error: &str) -> Result<(), AppError> {
+    let mut conn = self.db.acquire().await?;
+    sqlx::query!(
+      "UPDATE user_exports SET status = 'failed', error = $2, completed_at = NOW() WHERE id = $1",
+      id,
+      error
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+  }
+
+  async fn get(&self, id: Uuid, user_id: Uuid) -> Result<Option<UserExport>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let export = sqlx::query_as!(
+      UserExport,
+      r#"
+        SELECT id, user_id, status as "status: UserExportStatus", error, requested_at, completed_at, expires_at
+        FROM user_exports
+        WHERE id = $1 AND user_id = $2
+        "#,
+      id,
+      user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(export)
+  }
+
+  async fn get_archive(&self, id: Uuid, user_id: Uuid) -> Result<Option<StoredArchive>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let archive = sqlx::query_as!(
+      StoredArchive,
+      r#"
+        SELECT archive as "archive!", completed_at as "completed_at!"
+        FROM user_exports
+        WHERE id = $1 AND user_id = $2 AND status = 'ready' AND archive IS NOT NULL
+        "#,
+      id,
+      user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(archive)
+  }
+
+  async fn delete_expired(&self, now: DateTime<Utc>) -> Result<u64, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let result = sqlx::query!("DELETE FROM user_exports WHERE expires_at < $1", now)
+      .execute(&mut *conn)
+      .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  async fn memberships(&self, user_id: Uuid) -> Result<Vec<ExportedMembership>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let memberships = sqlx::query_as!(
+      ExportedMembership,
+      r#"
+        SELECT
+          w.id as workspace_id,
+          w.name as workspace_name,
+          wu.role as "role: WorkspaceRole",
+          (w.owner_id = wu.user_id) as "is_owner!",
+          wu.created_at as joined_at
+        FROM workspace_users wu
+        JOIN workspaces w ON w.id = wu.workspace_id
+        WHERE wu.user_id = $1
+        ORDER BY wu.created_at
+        "#,
+      user_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(memberships)
+  }
+
+  async fn api_usage(&self, user_id: Uuid) -> Result<Vec<ExportedApiUsage>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    let usage = sqlx::query_as!(
+      ExportedApiUsage,
+      r#"
+        SELECT workspace_id, day, route, request_count, request_bytes, response_bytes
+        FROM api_usage
+        WHERE user_id = $1
+        ORDER BY day, workspace_id, route
+        "#,
+      user_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(usage)
+  }
+
+  async fn created_records(&self, user_id: Uuid) -> Result<Vec<ExportedRecord>, AppError> {
+    let mut conn = self.db.acquire().await?;
+    // Every table whose rows record their creator
+    let mut records = sqlx::query_as!(
+      ExportedRecord,
+      r#"
+        SELECT kind as "kind!", id as "id!", workspace_id as "workspace_id!", code as "code!",
+               created_at as "created_at!", updated_at as "updated_at!"
+        FROM (
+          SELECT 'contacts' AS kind, id, workspace_id, code::TEXT AS code, created_at, updated_at FROM contacts WHERE created_by = $1
+          UNION ALL
+          SELECT 'products', id, workspace_id, code::TEXT, created_at, updated_at FROM products WHERE created_by = $1
+          UNION ALL
+          SELECT 'product_categories', id, workspace_id, code::TEXT, created_at, updated_at FROM product_categories WHERE created_by = $1
+          UNION ALL
+          SELECT 'departments', id, workspace_id, code::TEXT, created_at, updated_at FROM departments WHERE created_by = $1
+          UNION ALL
+          SELECT 'projects', id, workspace_id, code::TEXT, created_at, updated_at FROM projects WHERE created_by = $1
+          UNION ALL
+          SELECT 'discounts', id, workspace_id, code::TEXT, created_at, updated_at FROM discounts WHERE created_by = $1
+          UNION ALL
+          SELECT 'taxes', id, workspace_id, code::TEXT, created_at, updated_at FROM taxes WHERE created_by = $1
+        ) records
+        ORDER BY created_at, kind
+        "#,
+      user_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    // The tables of isolated workspaces are in their own schema
+    let isolated = tenant_schema::isolated_workspaces(&mut conn).await?;
+    let isolated_records = tenant_schema::for_each_workspace(&mut conn, isolated, |conn, _| {
+      Box::pin(
+        sqlx::query_as!(
+          ExportedRecord,
+          r#"
+            SELECT kind as "kind!", id as "id!", workspace_id as "workspace_id!", code as "code!",
+                   created_at as "created_at!", updated_at as "updated_at!"
+            FROM (
+              SELECT 'contacts' AS kind, id, workspace_id, code::TEXT AS code, created_at, updated_at FROM contacts WHERE created_by = $1
+              UNION ALL
+              SELECT 'products', id, workspace_id, code::TEXT, created_at, updated_at FROM products WHERE created_by = $1
+              UNION ALL
+              SELECT 'product_categories', id, workspace_id, code::TEXT, created_at, updated_at FROM product_categories WHERE created_by = $1
+            ) records
+            "#,
+          user_id
+        )
+        .fetch_all(conn),
+      )
+    })
+    .await?;
+    records.extend(isolated_records.into_iter().flatten());
+    records.sort_by(|a, b| (a.created_at, &a.kind).cmp(&(b.created_at, &b.kind)));
+
+    Ok(records)
+  }
+}
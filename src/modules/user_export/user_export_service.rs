@@ -0,0 +1,94 @@
+//! Building personal data exports.
+//!
+//! Archives are built on a task spawned by the request, so an export in progress is lost when
+//! the process stops; `UserExportRepository::create` fails it an hour later, letting the user
+//! ask again.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::user_export_models::{ARCHIVE_FORMAT_VERSION, UserExport, UserExportArchive};
+use crate::{AppResult, errors::AppError, internal_error, mailer::EmailMessage, state::AppState};
+
+/// Starts an export of everything tied to `user_id` and returns it while it is pending.
+pub async fn request_export(state: Arc<AppState>, user_id: Uuid) -> AppResult<UserExport> {
+  let export = state
+    .user_export_repository
+    .create(user_id)
+    .await?
+    .ok_or_else(|| AppError::Conflict("An export of your data is already being prepared".to_string()))?;
+
+  tokio::spawn(run_export(state, export.id, user_id));
+  Ok(export)
+}
+
+/// Collects the archive of a user.
+pub async fn build_archive(state: &AppState, user_id: Uuid) -> AppResult<UserExportArchive> {
+  let user = state
+    .auth_repository
+    .find_by_id(user_id)
+    .await?
+    .ok_or_else(|| AppError::not_found_with_id("User", user_id))?;
+  let repository = &state.user_export_repository;
+
+  Ok(UserExportArchive {
+    format_version: ARCHIVE_FORMAT_VERSION,
+    generated_at: Utc::now(),
+    profile: user.into(),
+    memberships: repository.memberships(user_id).await?,
+    api_usage: repository.api_usage(user_id).await?,
+    created_records: repository.created_records(user_id).await?,
+  })
+}
+
+/// Builds and stores the archive of a pending export, then emails the user a link to it. An
+/// export that cannot be built is marked failed; a failed email is only logged, since the
+/// archive can still be found through the status endpoint.
+async fn run_export(state: Arc<AppState>, export_id: Uuid, user_id: Uuid) {
+  let repository = &state.user_export_repository;
+  let result = async {
+    let archive = build_archive(&state, user_id).await?;
+    let archive = serde_json::to_value(&archive).map_err(|e| internal_error!("Failed to serialize the export: {}", e))?;
+    let expires_at = Utc::now() + chrono::Duration::days(state.config.user_exports.retention_days.into());
+    repository.complete(export_id, &archive, expires_at).await?;
+    Ok::<_, AppError>(expires_at)
+  }
+  .await;
+
+  let expires_at = match result {
+    Ok(expires_at) => expires_at,
+    Err(e) => {
+      warn!("Export {} of user {} failed: {}", export_id, user_id, e);
+      if let Err(e) = repository.fail(export_id, &e.to_string()).await {
+        warn!("Failed to mark export {} as failed: {}", export_id, e);
+      }
+      return;
+    }
+  };
+  info!("Export {} of user {} is ready", export_id, user_id);
+
+  let email = match state.auth_repository.find_by_id(user_id).await {
+    Ok(Some(user)) => user.email,
+    Ok(None) => return,
+    Err(e) => {
+      warn!("Failed to load user {} to email export {}: {}", user_id, export_id, e);
+      return;
+    }
+  };
+  let message = EmailMessage {
+    to: email,
+    subject: "Your data export is ready".to_string(),
+    body: format!(
+      "The export of your data you requested is ready. Download it before {}:\n\n{}/{}\n",
+      expires_at.format("%Y-%m-%d %H:%M UTC"),
+      state.config.user_exports.link_base_url,
+      export_id
+    ),
+  };
+  if let Err(e) = state.mailer.send(message).await {
+    warn!("Failed to email export {} to user {}: {}", export_id, user_id, e);
+  }
+}
@@ -0,0 +1,99 @@
+//! v2 contact endpoints.
+//!
+//! Each handler delegates to the v1 handler, so validation, permissions and repository
+//! access are shared, and only reshapes the response into the v2 envelope.
+
+use std::sync::Arc;
+
+use axum::{
+  Json, Router,
+  extract::{State, rejection::JsonRejection},
+  http::StatusCode,
+  routing::get,
+};
+
+use crate::{
+  AppResult, AppState,
+  helper::{Pagination, PathUuid, RequireRole, RequiredWorkspace, ValidatedQuery, workspace::role::Member},
+  modules::{
+    auth::current_user::CurrentUser,
+    datastores::contacts::{
+      contact_handlers,
+      contact_models::{ContactResponse, CreateContactRequest, GetContactsQuery},
+    },
+    workspace_settings::field_policy_service::Redacted,
+  },
+  responses::Created,
+  utils::merge_patch::MergePatch,
+};
+
+use super::responses::DataResponse;
+
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/", get(get_list).post(create))
+    .route("/:id", get(get_by_id).patch(patch).delete(delete))
+}
+
+/// Lists contacts; pagination metadata is returned under `meta`.
+pub async fn get_list(
+  state: State<Arc<AppState>>,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+  query: ValidatedQuery<GetContactsQuery>,
+  pagination: Pagination,
+) -> AppResult<Json<DataResponse<Vec<Redacted<ContactResponse>>>>> {
+  let Json(response) = contact_handlers::get_list(state, current_user, workspace, member, query, pagination).await?;
+  Ok(Json(DataResponse::from_v1_list(response)?))
+}
+
+/// Creates a contact and returns it with `201 Created`.
+pub async fn create(
+  state: State<Arc<AppState>>,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+  payload: Result<Json<CreateContactRequest>, JsonRejection>,
+) -> AppResult<Created<DataResponse<Redacted<ContactResponse>>>> {
+  let created = contact_handlers::create(state, current_user, workspace, member, payload).await?;
+  let response = DataResponse::from_v1(created.body)?;
+  Ok(Created::new(format!("/api/v2/contacts/{}", response.data.id), response))
+}
+
+/// Returns a single contact.
+pub async fn get_by_id(
+  state: State<Arc<AppState>>,
+  id: PathUuid,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+) -> AppResult<Json<DataResponse<Redacted<ContactResponse>>>> {
+  let Json(response) = contact_handlers::get_by_id(state, id, current_user, workspace, member).await?;
+  Ok(Json(DataResponse::from_v1(response)?))
+}
+
+/// Applies a JSON Merge Patch to a contact. v2 has no COALESCE-style `PUT`.
+pub async fn patch(
+  state: State<Arc<AppState>>,
+  id: PathUuid,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+  merge_patch: MergePatch,
+) -> AppResult<Json<DataResponse<Redacted<ContactResponse>>>> {
+  let Json(response) = contact_handlers::patch(state, id, current_user, workspace, member, merge_patch).await?;
+  Ok(Json(DataResponse::from_v1(response)?))
+}
+
+/// Deletes a contact and returns `204 No Content`.
+pub async fn delete(
+  state: State<Arc<AppState>>,
+  id: PathUuid,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+) -> AppResult<StatusCode> {
+  let _ = contact_handlers::delete(state, id, current_user, workspace, member).await?;
+  Ok(StatusCode::NO_CONTENT)
+}
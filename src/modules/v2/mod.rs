@@ -0,0 +1,26 @@
+//! Version 2 of the HTTP API, mounted at `/api/v2`.
+//!
+//! v2 shares repositories and business logic with v1 and differs only in its DTOs and
+//! response envelope (see `responses::DataResponse`). New breaking changes go here while
+//! v1 keeps its current shapes until its sunset date.
+
+use std::sync::Arc;
+
+use axum::Router;
+
+use crate::AppState;
+
+#[cfg(feature = "contacts")]
+pub mod contacts;
+#[cfg(feature = "products")]
+pub mod products;
+pub mod responses;
+
+pub fn router() -> Router<Arc<AppState>> {
+  let router = Router::new();
+  #[cfg(feature = "contacts")]
+  let router = router.nest("/contacts", contacts::router());
+  #[cfg(feature = "products")]
+  let router = router.nest("/products", products::router());
+  router
+}
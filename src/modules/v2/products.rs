@@ -0,0 +1,116 @@
+//! v2 product endpoints.
+//!
+//! Each handler delegates to the v1 handler, so validation, permissions and repository
+//! access are shared, and only reshapes the response into the v2 envelope.
+
+use std::sync::Arc;
+
+use axum::{
+  Json, Router,
+  extract::{State, rejection::JsonRejection},
+  http::StatusCode,
+  routing::{get, post},
+};
+
+use crate::{
+  AppResult, AppState,
+  helper::{Pagination, PathUuid, RequireRole, RequiredWorkspace, ValidatedQuery, workspace::role::Member},
+  modules::{
+    auth::current_user::CurrentUser,
+    datastores::products::{
+      product_handlers,
+      product_models::{
+        AvailabilityRequest, CreateProductQuery, CreateProductRequest, GetProductQuery, GetProductsQuery, LineAvailability, ProductResponse,
+      },
+    },
+    workspace_settings::field_policy_service::Redacted,
+  },
+  responses::Created,
+  utils::merge_patch::MergePatch,
+};
+
+use super::responses::DataResponse;
+
+pub fn router() -> Router<Arc<AppState>> {
+  Router::new()
+    .route("/", get(get_list).post(create))
+    .route("/availability", post(availability))
+    .route("/:id", get(get_by_id).patch(patch).delete(delete))
+}
+
+/// Lists products; pagination metadata is returned under `meta`.
+pub async fn get_list(
+  state: State<Arc<AppState>>,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+  query: ValidatedQuery<GetProductsQuery>,
+  pagination: Pagination,
+) -> AppResult<Json<DataResponse<Vec<Redacted<ProductResponse>>>>> {
+  let Json(response) = product_handlers::get_list(state, current_user, workspace, member, query, pagination).await?;
+  Ok(Json(DataResponse::from_v1_list(response)?))
+}
+
+/// Creates a product and returns it with `201 Created`, unless it looks like a duplicate (see v1).
+pub async fn create(
+  state: State<Arc<AppState>>,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+  query: ValidatedQuery<CreateProductQuery>,
+  payload: Result<Json<CreateProductRequest>, JsonRejection>,
+) -> AppResult<Created<DataResponse<Redacted<ProductResponse>>>> {
+  let created = product_handlers::create(state, current_user, workspace, member, query, payload).await?;
+  let response = DataResponse::from_v1(created.body)?;
+  Ok(Created::new(format!("/api/v2/products/{}", response.data.id), response))
+}
+
+/// Checks the stock of several products at once.
+pub async fn availability(
+  state: State<Arc<AppState>>,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+  payload: Result<Json<AvailabilityRequest>, JsonRejection>,
+) -> AppResult<Json<DataResponse<Vec<LineAvailability>>>> {
+  let Json(response) = product_handlers::availability(state, current_user, workspace, member, payload).await?;
+  Ok(Json(DataResponse::from_v1(response)?))
+}
+
+/// Returns a single product, as it was at `as_of` when given.
+pub async fn get_by_id(
+  state: State<Arc<AppState>>,
+  id: PathUuid,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+  query: ValidatedQuery<GetProductQuery>,
+) -> AppResult<Json<DataResponse<Redacted<ProductResponse>>>> {
+  let Json(response) = product_handlers::get_by_id(state, id, current_user, workspace, member, query).await?;
+  Ok(Json(DataResponse::from_v1(response)?))
+}
+
+/// Applies a JSON Merge Patch to a product. v2 has no COALESCE-style `PUT`.
+pub async fn patch(
+  state: State<Arc<AppState>>,
+  id: PathUuid,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+  merge_patch: MergePatch,
+) -> AppResult<Json<DataResponse<Redacted<ProductResponse>>>> {
+  let Json(response) = product_handlers::patch(state, id, current_user, workspace, member, merge_patch).await?;
+  Ok(Json(DataResponse::from_v1(response)?))
+}
+
+/// Deletes a product and returns `204 No Content`.
+pub async fn delete(
+  state: State<Arc<AppState>>,
+  id: PathUuid,
+  current_user: CurrentUser,
+  workspace: RequiredWorkspace,
+  member: RequireRole<Member>,
+) -> AppResult<StatusCode> {
+  let _ = product_handlers::delete(state, id, current_user, workspace, member).await?;
+  Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,61 @@
+use serde::Serialize;
+
+use crate::{
+  AppResult,
+  errors::AppError,
+  responses::{ApiResponse, PaginatedResponse, PaginationMeta},
+};
+
+/// The v2 response envelope: the payload under `data`, list metadata under `meta`.
+///
+/// Compared to v1's `ApiResponse`, the redundant `status`/`message`/`timestamp` fields are
+/// gone (the HTTP status carries the outcome) and lists are no longer nested in `results.list`.
+#[derive(Debug, Serialize)]
+pub struct DataResponse<T> {
+  pub data: T,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub meta: Option<ListMeta>,
+}
+
+/// Pagination metadata for v2 list responses.
+#[derive(Debug, Serialize)]
+pub struct ListMeta {
+  pub page: u32,
+  pub limit: u32,
+  pub total: u64,
+  pub total_pages: u32,
+}
+
+impl From<PaginationMeta> for ListMeta {
+  fn from(meta: PaginationMeta) -> Self {
+    Self {
+      page: meta.page,
+      limit: meta.limit,
+      total: meta.total,
+      total_pages: meta.total_pages,
+    }
+  }
+}
+
+impl<T> DataResponse<T> {
+  /// Converts a v1 single-resource response into the v2 envelope.
+  pub fn from_v1(response: ApiResponse<T>) -> AppResult<Self> {
+    let data = response
+      .results
+      .ok_or_else(|| AppError::Internal("v1 handler returned no results".to_string()))?;
+    Ok(Self { data, meta: None })
+  }
+}
+
+impl<T> DataResponse<Vec<T>> {
+  /// Converts a v1 paginated response into the v2 envelope.
+  pub fn from_v1_list(response: ApiResponse<PaginatedResponse<T>>) -> AppResult<Self> {
+    let page = response
+      .results
+      .ok_or_else(|| AppError::Internal("v1 handler returned no results".to_string()))?;
+    Ok(Self {
+      data: page.list,
+      meta: Some(page.pagination.into()),
+    })
+  }
+}
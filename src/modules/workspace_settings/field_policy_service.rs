@@ -0,0 +1,165 @@
+//! Field policies: fields of contacts and products that admins hide from, or make read-only for,
+//! the members and viewers of their workspace, such as the `unit_cost` of products.
+//!
+//! Handlers resolve the `FieldPolicy` of the caller's role with `resolve`, check update requests
+//! and merge patches against it, and wrap the records they return in `Redacted`, which leaves the
+//! hidden fields out when serialized. Hidden fields are also read-only. Creating a record is not
+//! restricted, as some of the fields are required. The internal gRPC API, exports and real-time
+//! events are not filtered.
+
+use std::{collections::BTreeSet, ops::Deref, sync::Arc};
+
+use serde::{Serialize, Serializer, ser::Error};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::workspace_settings_models::{FieldAccess, FieldPolicies};
+use crate::{AppResult, errors::AppError, internal_error, modules::datastores::workspaces::WorkspaceRole, state::AppState};
+
+/// The fields each entity can restrict. Identifiers, codes, names and audit metadata always stay
+/// visible, so restricted callers can still tell records apart.
+pub const RESTRICTABLE_FIELDS: &[(&str, &[&str])] = &[
+  (
+    "contacts",
+    &["email", "position", "contact_type", "address", "tax_id", "bank_account", "is_active"],
+  ),
+  (
+    "products",
+    &[
+      "category_id",
+      "base_unit",
+      "unit_on_report_preview",
+      "selling_price",
+      "unit_cost",
+      "supplier_id",
+      "track_inventory",
+      "description",
+      "sku",
+      "barcode",
+      "minimum_stock",
+      "maximum_stock",
+      "reorder_level",
+      "current_stock",
+      "tax_type",
+      "tax_rate",
+      "tax_amount",
+      "is_active",
+    ],
+  ),
+];
+
+/// The fields of an entity restricted for one role.
+#[derive(Debug, Clone, Default)]
+pub struct FieldPolicy {
+  hidden: Arc<BTreeSet<String>>,
+  read_only: BTreeSet<String>,
+}
+
+impl FieldPolicy {
+  /// The policy of `role` on `entity` among `policies`. Admins are never restricted.
+  pub fn of(policies: &FieldPolicies, entity: &str, role: WorkspaceRole) -> Self {
+    let mut hidden = BTreeSet::new();
+    let mut read_only = BTreeSet::new();
+    for (field, rule) in policies.get(entity).into_iter().flatten() {
+      let access = match role {
+        WorkspaceRole::Admin => None,
+        WorkspaceRole::Member => rule.member,
+        WorkspaceRole::Viewer => rule.viewer,
+      };
+      match access {
+        Some(FieldAccess::Hidden) => {
+          hidden.insert(field.clone());
+          read_only.insert(field.clone());
+        }
+        Some(FieldAccess::ReadOnly) => {
+          read_only.insert(field.clone());
+        }
+        None => {}
+      }
+    }
+    Self {
+      hidden: Arc::new(hidden),
+      read_only,
+    }
+  }
+
+  /// Rejects an update request setting a read-only field. Members left out or `null` keep their
+  /// value, as with the COALESCE-style `PUT` updates.
+  pub fn check_update<T: Serialize>(&self, request: &T) -> AppResult<()> {
+    if self.read_only.is_empty() {
+      return Ok(());
+    }
+    let request = serde_json::to_value(request).map_err(|e| internal_error!("Failed to serialize update request: {}", e))?;
+    let fields = request.as_object().into_iter().flatten().filter(|(_, value)| !value.is_null());
+    self.check_fields(fields.map(|(field, _)| field.as_str()))
+  }
+
+  /// Rejects a merge patch naming a read-only field, including to clear it with `null`.
+  pub fn check_patch(&self, patch: &Value) -> AppResult<()> {
+    self.check_fields(patch.as_object().into_iter().flatten().map(|(field, _)| field.as_str()))
+  }
+
+  fn check_fields<'a>(&self, mut fields: impl Iterator<Item = &'a str>) -> AppResult<()> {
+    match fields.find(|field| self.read_only.contains(*field)) {
+      Some(field) => Err(AppError::validation(field, "This field is read-only for your role")),
+      None => Ok(()),
+    }
+  }
+
+  /// Whether `field` is left out of the records returned to the caller.
+  pub fn is_hidden(&self, field: &str) -> bool {
+    self.hidden.contains(field)
+  }
+
+  /// Wraps `value` to be serialized without the hidden fields.
+  pub fn redact<T>(&self, value: T) -> Redacted<T> {
+    Redacted {
+      value,
+      hidden: self.hidden.clone(),
+    }
+  }
+}
+
+/// The field policy of `role` on `entity` in `workspace_id`.
+pub async fn resolve(state: &AppState, workspace_id: Uuid, entity: &str, role: WorkspaceRole) -> AppResult<FieldPolicy> {
+  if role == WorkspaceRole::Admin {
+    return Ok(FieldPolicy::default());
+  }
+  let settings = state.workspace_settings_repository.get(workspace_id).await?;
+  Ok(FieldPolicy::of(&settings.field_policies, entity, role))
+}
+
+/// A record serialized without the fields hidden from the caller. Dereferences to the record.
+#[derive(Debug)]
+pub struct Redacted<T> {
+  value: T,
+  hidden: Arc<BTreeSet<String>>,
+}
+
+impl<T> Redacted<T> {
+  /// The record with every field, for callers doing their own filtering.
+  pub fn into_inner(self) -> T {
+    self.value
+  }
+}
+
+impl<T> Deref for Redacted<T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    &self.value
+  }
+}
+
+impl<T: Serialize> Serialize for Redacted<T> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    if self.hidden.is_empty() {
+      return self.value.serialize(serializer);
+    }
+    let mut value = serde_json::to_value(&self.value).map_err(S::Error::custom)?;
+    if let Some(fields) = value.as_object_mut() {
+      fields.retain(|field, _| !self.hidden.contains(field));
+    }
+    value.serialize(serializer)
+  }
+}
@@ -0,0 +1,22 @@
+//! Settings of a workspace managed by its admins.
+//!
+//! * The IP ranges the workspace may be used from: once any are set, `ip_allowlist_middleware`
+//!   rejects the workspace's requests coming from other addresses with `IP_NOT_ALLOWED`, for
+//!   customers whose compliance rules require it. Real-time streams and gRPC calls are checked
+//!   when they authenticate. The settings checked are kept in the `WorkspaceSettingsCache` of
+//!   `AppState` for `WORKSPACE_SETTINGS_CACHE_TTL_SECS`.
+//! * The page size and sort of lists when the client omits them, applied by `helper::Pagination`.
+//! * The format of the codes generated for each entity (see `utils::code_generator::CodeRules`).
+//! * The connectors to online stores, with their encrypted credentials. They are managed under
+//!   `/api/v1/connectors` (see `modules::connectors`) and left out of the settings returned here.
+//! * The fields of contacts and products hidden from or read-only for members and viewers,
+//!   enforced by their handlers through `field_policy_service`.
+//! * The changes of members that wait for the approval of an admin (see `modules::approvals`).
+//!
+//! Workspaces without settings have no restrictions and the built-in defaults.
+
+pub mod field_policy_service;
+pub mod workspace_settings_cache;
+pub mod workspace_settings_handlers;
+pub mod workspace_settings_models;
+pub mod workspace_settings_repository;
@@ -0,0 +1,63 @@
+use std::{
+  collections::HashMap,
+  sync::{Mutex, MutexGuard},
+  time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+use super::workspace_settings_models::WorkspaceSettings;
+
+/// Number of cached workspaces above which expired entries are purged on the next insert.
+const PURGE_THRESHOLD: usize = 10_000;
+
+/// Settings recently loaded for a workspace.
+///
+/// The IP allowlist is checked on every request to a workspace; the cache lets the check skip the
+/// lookup. Updates made through this instance invalidate the workspace right away; other
+/// instances pick them up once the entry expires.
+pub struct WorkspaceSettingsCache {
+  ttl: Duration,
+  settings: Mutex<HashMap<Uuid, (WorkspaceSettings, Instant)>>,
+}
+
+impl WorkspaceSettingsCache {
+  /// A cache keeping loaded settings for `ttl_secs`; 0 disables it.
+  pub fn new(ttl_secs: u64) -> Self {
+    Self {
+      ttl: Duration::from_secs(ttl_secs),
+      settings: Mutex::new(HashMap::new()),
+    }
+  }
+
+  pub fn get(&self, workspace_id: Uuid) -> Option<WorkspaceSettings> {
+    let now = Instant::now();
+    self
+      .lock()
+      .get(&workspace_id)
+      .filter(|(_, expires_at)| *expires_at > now)
+      .map(|(settings, _)| settings.clone())
+  }
+
+  pub fn insert(&self, workspace_id: Uuid, settings: WorkspaceSettings) {
+    if self.ttl.is_zero() {
+      return;
+    }
+    let now = Instant::now();
+    let mut cached = self.lock();
+    if cached.len() > PURGE_THRESHOLD {
+      cached.retain(|_, (_, expires_at)| *expires_at > now);
+    }
+    cached.insert(workspace_id, (settings, now + self.ttl));
+  }
+
+  /// Forgets the settings of a workspace, after they were updated.
+  pub fn invalidate(&self, workspace_id: Uuid) {
+    self.lock().remove(&workspace_id);
+  }
+
+  // The map holds no invariants a panicking writer could break, so a poisoned lock is reused
+  fn lock(&self) -> MutexGuard<'_, HashMap<Uuid, (WorkspaceSettings, Instant)>> {
+    self.settings.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
@@ -0,0 +1,199 @@
+use std::{collections::BTreeMap, net::SocketAddr, sync::Arc};
+
+use axum::{
+  extract::{ConnectInfo, State, rejection::JsonRejection},
+  http::HeaderMap,
+  response::Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{
+  field_policy_service::RESTRICTABLE_FIELDS,
+  workspace_settings_models::{FieldRule, IpRange, UpdateWorkspaceSettingsRequest, WorkspaceSettings},
+};
+use crate::{
+  AppResult,
+  errors::AppError,
+  helper::PathUuid,
+  middleware::rate_limit::client_ip,
+  modules::{auth::current_user::CurrentUser, datastores::workspaces::WorkspaceRole},
+  responses::ApiResponse,
+  state::AppState,
+  utils::{code_generator::CodeRules, db_session},
+};
+
+/// Ranges a workspace can list, keeping the check of every request cheap.
+const MAX_IP_RANGES: usize = 100;
+/// Entities a request can set the code rules of.
+const MAX_CODE_RULE_ENTITIES: usize = 50;
+
+/// Settings are managed by the admins of the workspace.
+async fn require_admin(state: &AppState, user_id: Uuid, workspace_id: Uuid) -> AppResult<()> {
+  let role = state.workspace_repository.check_user_workspace_access(user_id, workspace_id).await?;
+  match role {
+    Some(role) if role.includes(WorkspaceRole::Admin) => Ok(()),
+    _ => Err(AppError::Authorization("Only workspace admins can manage workspace settings".to_string())),
+  }
+}
+
+/// The settings of a workspace.
+pub async fn get_workspace_settings(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(workspace_id): PathUuid,
+) -> AppResult<Json<ApiResponse<WorkspaceSettings>>> {
+  require_admin(&state, current_user.user_id, workspace_id).await?;
+
+  let settings = state.workspace_settings_repository.get(workspace_id).await?;
+  let response = ApiResponse::success(settings, "Workspace settings retrieved successfully");
+  Ok(Json(response))
+}
+
+/// Replaces the settings sent, keeping the others. With IP ranges, the caller's own address must
+/// stay allowed, so admins cannot lock themselves out.
+pub async fn update_workspace_settings(
+  State(state): State<Arc<AppState>>,
+  current_user: CurrentUser,
+  PathUuid(workspace_id): PathUuid,
+  headers: HeaderMap,
+  connect_info: Option<ConnectInfo<SocketAddr>>,
+  payload: Result<Json<UpdateWorkspaceSettingsRequest>, JsonRejection>,
+) -> AppResult<Json<ApiResponse<WorkspaceSettings>>> {
+  let Json(request) = payload?;
+  let ranges = request.allowed_ip_ranges.as_deref().map(parse_ranges).transpose()?;
+  if let Some(defaults) = &request.list_defaults {
+    defaults.validate()?;
+    if let (Some(page_size), Some(max_page_size)) = (defaults.page_size, defaults.max_page_size)
+      && page_size > max_page_size
+    {
+      return Err(AppError::validation("list_defaults", "The page size cannot exceed the maximum page size"));
+    }
+  }
+  if let Some(code_rules) = &request.code_rules {
+    validate_code_rules(code_rules)?;
+  }
+  if let Some(field_policies) = &request.field_policies {
+    validate_field_policies(field_policies)?;
+  }
+  if let Some(percent) = request.approval_rules.and_then(|rules| rules.price_change_percent)
+    && percent.is_sign_negative()
+  {
+    return Err(AppError::validation("approval_rules", "The price change percentage cannot be negative"));
+  }
+  if ranges.is_none()
+    && request.list_defaults.is_none()
+    && request.code_rules.is_none()
+    && request.field_policies.is_none()
+    && request.approval_rules.is_none()
+  {
+    return Err(AppError::BadRequest("No settings to update".to_string()));
+  }
+  require_admin(&state, current_user.user_id, workspace_id).await?;
+
+  if let Some(ranges) = &ranges {
+    let peer = connect_info.map(|ConnectInfo(addr)| addr);
+    let caller = client_ip(&headers, peer, &state.config.server.trusted_proxies);
+    if !ranges.is_empty() && !caller.is_some_and(|ip| ranges.iter().any(|range| range.contains(ip))) {
+      let caller = caller.map_or_else(|| "unknown".to_string(), |ip| ip.to_string());
+      return Err(AppError::validation(
+        "allowed_ip_ranges",
+        &format!("The allowed ranges must include your own address ({})", caller),
+      ));
+    }
+  }
+
+  let repository = &state.workspace_settings_repository;
+  let settings = db_session::transaction::<_, _, AppError>(&state.db, async {
+    let mut settings = None;
+    if let Some(ranges) = &ranges {
+      settings = Some(repository.set_allowed_ip_ranges(workspace_id, ranges, current_user.user_id).await?);
+    }
+    if let Some(defaults) = &request.list_defaults {
+      settings = Some(repository.set_list_defaults(workspace_id, defaults, current_user.user_id).await?);
+    }
+    if let Some(code_rules) = &request.code_rules {
+      settings = Some(repository.set_code_rules(workspace_id, code_rules, current_user.user_id).await?);
+    }
+    if let Some(field_policies) = &request.field_policies {
+      settings = Some(repository.set_field_policies(workspace_id, field_policies, current_user.user_id).await?);
+    }
+    if let Some(approval_rules) = &request.approval_rules {
+      settings = Some(repository.set_approval_rules(workspace_id, approval_rules, current_user.user_id).await?);
+    }
+    settings.ok_or_else(|| AppError::BadRequest("No settings to update".to_string()))
+  })
+  .await?;
+  state.workspace_settings_cache.invalidate(workspace_id);
+  let response = ApiResponse::success(settings, "Workspace settings saved successfully");
+  Ok(Json(response))
+}
+
+/// Parses and deduplicates the ranges of a request.
+fn parse_ranges(values: &[String]) -> AppResult<Vec<IpRange>> {
+  if values.len() > MAX_IP_RANGES {
+    return Err(AppError::validation(
+      "allowed_ip_ranges",
+      &format!("At most {} IP ranges can be allowed", MAX_IP_RANGES),
+    ));
+  }
+  let mut ranges: Vec<IpRange> = Vec::with_capacity(values.len());
+  for range in values {
+    let range: IpRange = range.parse().map_err(|e: String| AppError::validation("allowed_ip_ranges", &e))?;
+    if !ranges.contains(&range) {
+      ranges.push(range);
+    }
+  }
+  Ok(ranges)
+}
+
+/// Checks the code rules of a request, keyed by the table name of their entity.
+fn validate_code_rules(code_rules: &BTreeMap<String, Option<CodeRules>>) -> AppResult<()> {
+  if code_rules.len() > MAX_CODE_RULE_ENTITIES {
+    return Err(AppError::validation(
+      "code_rules",
+      &format!("At most {} entities can be set at once", MAX_CODE_RULE_ENTITIES),
+    ));
+  }
+  for (entity, rules) in code_rules {
+    let is_table_name = entity.len() <= 63
+      && entity.starts_with(|c: char| c.is_ascii_lowercase())
+      && entity.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !is_table_name {
+      return Err(AppError::validation(
+        "code_rules",
+        &format!("'{}' is not the table name of an entity", entity),
+      ));
+    }
+    if let Some(rules) = rules {
+      rules.validate()?;
+    }
+  }
+  Ok(())
+}
+
+/// Checks the field policies of a request: known entities and restrictable fields only, each
+/// restricting at least one role.
+fn validate_field_policies(field_policies: &BTreeMap<String, Option<BTreeMap<String, FieldRule>>>) -> AppResult<()> {
+  for (entity, rules) in field_policies {
+    let (_, fields) = RESTRICTABLE_FIELDS
+      .iter()
+      .find(|(name, _)| name == entity)
+      .ok_or_else(|| AppError::validation("field_policies", &format!("'{}' has no field policies", entity)))?;
+    for (field, rule) in rules.iter().flatten() {
+      if !fields.contains(&field.as_str()) {
+        return Err(AppError::validation(
+          "field_policies",
+          &format!("'{}' is not a field of {} that can be restricted", field, entity),
+        ));
+      }
+      if *rule == FieldRule::default() {
+        return Err(AppError::validation(
+          "field_policies",
+          &format!("The policy of {}.{} restricts no role", entity, field),
+        ));
+      }
+    }
+  }
+  Ok(())
+}
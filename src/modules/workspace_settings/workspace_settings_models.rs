@@ -0,0 +1,169 @@
+use std::{
+  collections::BTreeMap,
+  fmt,
+  net::{IpAddr, Ipv4Addr, Ipv6Addr},
+  str::FromStr,
+};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize, Serializer};
+use uuid::Uuid;
+
+use crate::{helper::pagination::ListDefaults, utils::code_generator::CodeRules};
+
+/// A CIDR range such as `203.0.113.0/24` or `2001:db8::/32`; a bare address is a range of one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpRange {
+  network: IpAddr,
+  prefix_len: u8,
+}
+
+impl IpRange {
+  /// Whether `ip` is in the range. IPv4 addresses mapped into IPv6 match IPv4 ranges.
+  pub fn contains(&self, ip: IpAddr) -> bool {
+    match (self.network, ip.to_canonical()) {
+      (IpAddr::V4(network), IpAddr::V4(ip)) => mask_v4(ip, self.prefix_len) == network,
+      (IpAddr::V6(network), IpAddr::V6(ip)) => mask_v6(ip, self.prefix_len) == network,
+      _ => false,
+    }
+  }
+}
+
+fn mask_v4(ip: Ipv4Addr, prefix_len: u8) -> Ipv4Addr {
+  let mask = u32::MAX.checked_shl(32 - u32::from(prefix_len)).unwrap_or(0);
+  Ipv4Addr::from(u32::from(ip) & mask)
+}
+
+fn mask_v6(ip: Ipv6Addr, prefix_len: u8) -> Ipv6Addr {
+  let mask = u128::MAX.checked_shl(128 - u32::from(prefix_len)).unwrap_or(0);
+  Ipv6Addr::from(u128::from(ip) & mask)
+}
+
+/// Parses a range, clearing the host bits of the address: `10.1.2.3/8` is `10.0.0.0/8`.
+impl FromStr for IpRange {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    let value = value.trim();
+    let invalid = || format!("'{}' is not an IP address or CIDR range", value);
+    let (address, prefix_len) = match value.split_once('/') {
+      Some((address, prefix_len)) => (address, Some(prefix_len.parse::<u8>().map_err(|_| invalid())?)),
+      None => (value, None),
+    };
+
+    match address.parse::<IpAddr>().map_err(|_| invalid())? {
+      IpAddr::V4(ip) => {
+        let prefix_len = prefix_len.unwrap_or(32);
+        if prefix_len > 32 {
+          return Err(invalid());
+        }
+        Ok(Self {
+          network: IpAddr::V4(mask_v4(ip, prefix_len)),
+          prefix_len,
+        })
+      }
+      IpAddr::V6(ip) => {
+        let prefix_len = prefix_len.unwrap_or(128);
+        if prefix_len > 128 {
+          return Err(invalid());
+        }
+        Ok(Self {
+          network: IpAddr::V6(mask_v6(ip, prefix_len)),
+          prefix_len,
+        })
+      }
+    }
+  }
+}
+
+impl fmt::Display for IpRange {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}/{}", self.network, self.prefix_len)
+  }
+}
+
+impl Serialize for IpRange {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+
+/// The settings of a workspace; the defaults while an admin never changed them.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceSettings {
+  pub workspace_id: Uuid,
+  /// Ranges the workspace can be used from; any address while empty.
+  pub allowed_ip_ranges: Vec<IpRange>,
+  /// Page size and sort of the list endpoints when the client does not choose them.
+  pub list_defaults: ListDefaults,
+  /// Code formats of the entities, by table name, used by `next-code` and when records are
+  /// created without a code.
+  pub code_rules: BTreeMap<String, CodeRules>,
+  /// Fields restricted for members and viewers, by entity and field (see `field_policy_service`).
+  pub field_policies: FieldPolicies,
+  /// Changes members make only once an admin approves them (see `modules::approvals`).
+  pub approval_rules: ApprovalRules,
+  pub updated_by: Option<Uuid>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl WorkspaceSettings {
+  /// Whether the workspace can be used from `ip`, `None` when the address is unknown.
+  pub fn allows(&self, ip: Option<IpAddr>) -> bool {
+    self.allowed_ip_ranges.is_empty() || ip.is_some_and(|ip| self.allowed_ip_ranges.iter().any(|range| range.contains(ip)))
+  }
+}
+
+/// Replaces some of the settings of a workspace; omitted ones are kept. An empty
+/// `allowed_ip_ranges` lifts the restriction. `code_rules` and `field_policies` replace the rules
+/// of the entities they name, those set to `null` getting back their built-in format or losing
+/// every restriction.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateWorkspaceSettingsRequest {
+  pub allowed_ip_ranges: Option<Vec<String>>,
+  pub list_defaults: Option<ListDefaults>,
+  pub code_rules: Option<BTreeMap<String, Option<CodeRules>>>,
+  pub field_policies: Option<BTreeMap<String, Option<BTreeMap<String, FieldRule>>>>,
+  pub approval_rules: Option<ApprovalRules>,
+}
+
+/// The fields restricted on each entity, by entity name (`contacts`, `products`) and field.
+pub type FieldPolicies = BTreeMap<String, BTreeMap<String, FieldRule>>;
+
+/// What a restricted field allows a role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldAccess {
+  /// Left out of responses and rejected in updates.
+  Hidden,
+  /// Returned but rejected in updates.
+  ReadOnly,
+}
+
+/// The access of each role to a field, unrestricted for the roles left out. Admins always have
+/// full access, so they have no entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldRule {
+  #[serde(rename = "Member", default, skip_serializing_if = "Option::is_none")]
+  pub member: Option<FieldAccess>,
+  #[serde(rename = "Viewer", default, skip_serializing_if = "Option::is_none")]
+  pub viewer: Option<FieldAccess>,
+}
+
+/// The changes of members and viewers that wait for the approval of an admin; none by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApprovalRules {
+  /// Changes of the selling price of a product by more than this percentage, up or down.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub price_change_percent: Option<Decimal>,
+  /// Lowering the stock of a product.
+  #[serde(default)]
+  pub stock_write_offs: bool,
+  /// Deleting contacts and products.
+  #[serde(default)]
+  pub deletions: bool,
+}
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::workspace_settings_models::{ApprovalRules, FieldRule, IpRange, WorkspaceSettings};
+use crate::{
+  AppResult,
+  helper::pagination::ListDefaults,
+  internal_error,
+  utils::{DbExecutor, code_generator::CodeRules},
+};
+
+#[async_trait]
+pub trait WorkspaceSettingsRepository: Send + Sync {
+  /// The settings of a workspace, the defaults when none were saved.
+  async fn get(&self, workspace_id: Uuid) -> AppResult<WorkspaceSettings>;
+  async fn set_allowed_ip_ranges(&self, workspace_id: Uuid, ranges: &[IpRange], updated_by: Uuid) -> AppResult<WorkspaceSettings>;
+  async fn set_list_defaults(&self, workspace_id: Uuid, defaults: &ListDefaults, updated_by: Uuid) -> AppResult<WorkspaceSettings>;
+  /// Replaces the code rules of the entities in `rules`, removing those set to `None`.
+  async fn set_code_rules(&self, workspace_id: Uuid, rules: &BTreeMap<String, Option<CodeRules>>, updated_by: Uuid) -> AppResult<WorkspaceSettings>;
+  /// Replaces the field policies of the entities in `policies`, removing those set to `None`.
+  async fn set_field_policies(
+    &self,
+    workspace_id: Uuid,
+    policies: &BTreeMap<String, Option<BTreeMap<String, FieldRule>>>,
+    updated_by: Uuid,
+  ) -> AppResult<WorkspaceSettings>;
+  async fn set_approval_rules(&self, workspace_id: Uuid, rules: &ApprovalRules, updated_by: Uuid) -> AppResult<WorkspaceSettings>;
+}
+
+pub struct PostgresWorkspaceSettingsRepository {
+  db: DbExecutor,
+}
+
+impl PostgresWorkspaceSettingsRepository {
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
+  }
+}
+
+/// A `workspace_settings` row, the ranges as Postgres writes CIDRs.
+struct SettingsRow {
+  workspace_id: Uuid,
+  allowed_ip_ranges: Vec<String>,
+  default_page_size: Option<i32>,
+  max_page_size: Option<i32>,
+  default_sort_by: Option<String>,
+  default_sort_order: Option<String>,
+  code_rules: Value,
+  field_policies: Value,
+  approval_rules: Value,
+  updated_by: Option<Uuid>,
+  updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<SettingsRow> for WorkspaceSettings {
+  type Error = crate::errors::AppError;
+
+  fn try_from(row: SettingsRow) -> AppResult<Self> {
+    let allowed_ip_ranges = row
+      .allowed_ip_ranges
+      .iter()
+      .map(|range| range.parse())
+      .collect::<Result<_, _>>()
+      .map_err(|e| internal_error!("Invalid IP range stored for workspace {}: {}", row.workspace_id, e))?;
+    let invalid = |e: String| internal_error!("Invalid list defaults stored for workspace {}: {}", row.workspace_id, e);
+    let list_defaults = ListDefaults {
+      page_size: row.default_page_size.map(|size| size as u32),
+      max_page_size: row.max_page_size.map(|size| size as u32),
+      sort_by: row.default_sort_by.as_deref().map(str::parse).transpose().map_err(invalid)?,
+      sort_order: row.default_sort_order.as_deref().map(str::parse).transpose().map_err(invalid)?,
+    };
+    let code_rules =
+      serde_json::from_value(row.code_rules).map_err(|e| internal_error!("Invalid code rules stored for workspace {}: {}", row.workspace_id, e))?;
+    let field_policies = serde_json::from_value(row.field_policies)
+      .map_err(|e| internal_error!("Invalid field policies stored for workspace {}: {}", row.workspace_id, e))?;
+    let approval_rules = serde_json::from_value(row.approval_rules)
+      .map_err(|e| internal_error!("Invalid approval rules stored for workspace {}: {}", row.workspace_id, e))?;
+    Ok(Self {
+      workspace_id: row.workspace_id,
+      allowed_ip_ranges,
+      list_defaults,
+      code_rules,
+      field_policies,
+      approval_rules,
+      updated_by: row.updated_by,
+      updated_at: Some(row.updated_at),
+    })
+  }
+}
+
+#[async_trait]
+impl WorkspaceSettingsRepository for PostgresWorkspaceSettingsRepository {
+  async fn get(&self, workspace_id: Uuid) -> AppResult<WorkspaceSettings> {
+    let mut conn = self.db.acquire().await?;
+    let row = sqlx::query_as!(
+      SettingsRow,
+      r#"
+        SELECT workspace_id, allowed_ip_ranges::TEXT[] as "allowed_ip_ranges!", default_page_size, max_page_size,
+          default_sort_by, default_sort_order, code_rules, field_policies, approval_rules, updated_by, updated_at
+        FROM workspace_settings
+        WHERE workspace_id = $1
+        "#,
+      workspace_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    match row {
+      Some(row) => row.try_into(),
+      None => Ok(WorkspaceSettings {
+        workspace_id,
+        allowed_ip_ranges: Vec::new(),
+        list_defaults: ListDefaults::default(),
+        code_rules: BTreeMap::new(),
+        field_policies: BTreeMap::new(),
+        approval_rules: ApprovalRules::default(),
+        updated_by: None,
+        updated_at: None,
+      }),
+    }
+  }
+
+  async fn set_allowed_ip_ranges(&self, workspace_id: Uuid, ranges: &[IpRange], updated_by: Uuid) -> AppResult<WorkspaceSettings> {
+    let ranges: Vec<String> = ranges.iter().map(IpRange::to_string).collect();
+    let mut conn = self.db.acquire().await?;
+    let row = sqlx::query_as!(
+      SettingsRow,
+      r#"
+        INSERT INTO workspace_settings (workspace_id, allowed_ip_ranges, updated_by)
+        VALUES ($1, $2::TEXT[]::CIDR[], $3)
+        ON CONFLICT (workspace_id) DO UPDATE
+        SET allowed_ip_ranges = EXCLUDED.allowed_ip_ranges, updated_by = EXCLUDED.updated_by
+        RETURNING workspace_id, allowed_ip_ranges::TEXT[] as "allowed_ip_ranges!", default_page_size, max_page_size,
+          default_sort_by, default_sort_order, code_rules, field_policies, approval_rules, updated_by, updated_at
+        "#,
+      workspace_id,
+      &ranges,
+      updated_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    row.try_into()
+  }
+
+  async fn set_list_defaults(&self, workspace_id: Uuid, defaults: &ListDefaults, updated_by: Uuid) -> AppResult<WorkspaceSettings> {
+    let mut conn = self.db.acquire().await?;
+    let row = sqlx::query_as!(
+      SettingsRow,
+      r#"
+        INSERT INTO workspace_settings (workspace_id, default_page_size, max_page_size, default_sort_by, default_sort_order, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (workspace_id) DO UPDATE
+        SET default_page_size = EXCLUDED.default_page_size, max_page_size = EXCLUDED.max_page_size,
+            default_sort_by = EXCLUDED.default_sort_by, default_sort_order = EXCLUDED.default_sort_order,
+            updated_by = EXCLUDED.updated_by
+        RETURNING workspace_id, allowed_ip_ranges::TEXT[] as "allowed_ip_ranges!", default_page_size, max_page_size,
+          default_sort_by, default_sort_order, code_rules, field_policies, approval_rules, updated_by, updated_at
+        "#,
+      workspace_id,
+      defaults.page_size.map(|size| size as i32),
+      defaults.max_page_size.map(|size| size as i32),
+      defaults.sort_by.map(|column| column.as_str()),
+      defaults.sort_order.map(|order| order.to_string()),
+      updated_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    row.try_into()
+  }
+
+  async fn set_code_rules(&self, workspace_id: Uuid, rules: &BTreeMap<String, Option<CodeRules>>, updated_by: Uuid) -> AppResult<WorkspaceSettings> {
+    // Entities set to null are dropped by jsonb_strip_nulls, along with unset fields of the rules
+    let rules = serde_json::to_value(rules).map_err(|e| internal_error!("Failed to serialize code rules: {}", e))?;
+    let mut conn = self.db.acquire().await?;
+    let row = sqlx::query_as!(
+      SettingsRow,
+      r#"
+        INSERT INTO workspace_settings (workspace_id, code_rules, updated_by)
+        VALUES ($1, jsonb_strip_nulls($2), $3)
+        ON CONFLICT (workspace_id) DO UPDATE
+        SET code_rules = jsonb_strip_nulls(workspace_settings.code_rules || $2), updated_by = EXCLUDED.updated_by
+        RETURNING workspace_id, allowed_ip_ranges::TEXT[] as "allowed_ip_ranges!", default_page_size, max_page_size,
+          default_sort_by, default_sort_order, code_rules, field_policies, approval_rules, updated_by, updated_at
+        "#,
+      workspace_id,
+      rules,
+      updated_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    row.try_into()
+  }
+
+  async fn set_field_policies(
+    &self,
+    workspace_id: Uuid,
+    policies: &BTreeMap<String, Option<BTreeMap<String, FieldRule>>>,
+    updated_by: Uuid,
+  ) -> AppResult<WorkspaceSettings> {
+    // Entities set to null are dropped by jsonb_strip_nulls
+    let policies = serde_json::to_value(policies).map_err(|e| internal_error!("Failed to serialize field policies: {}", e))?;
+    let mut conn = self.db.acquire().await?;
+    let row = sqlx::query_as!(
+      SettingsRow,
+      r#"
+        INSERT INTO workspace_settings (workspace_id, field_policies, updated_by)
+        VALUES ($1, jsonb_strip_nulls($2), $3)
+        ON CONFLICT (workspace_id) DO UPDATE
+        SET field_policies = jsonb_strip_nulls(workspace_settings.field_policies || $2), updated_by = EXCLUDED.updated_by
+        RETURNING workspace_id, allowed_ip_ranges::TEXT[] as "allowed_ip_ranges!", default_page_size, max_page_size,
+          default_sort_by, default_sort_order, code_rules, field_policies, approval_rules, updated_by, updated_at
+        "#,
+      workspace_id,
+      policies,
+      updated_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    row.try_into()
+  }
+
+  async fn set_approval_rules(&self, workspace_id: Uuid, rules: &ApprovalRules, updated_by: Uuid) -> AppResult<WorkspaceSettings> {
+    let rules = serde_json::to_value(rules).map_err(|e| internal_error!("Failed to serialize approval rules: {}", e))?;
+    let mut conn = self.db.acquire().await?;
+    let row = sqlx::query_as!(
+      SettingsRow,
+      r#"
+        INSERT INTO workspace_settings (workspace_id, approval_rules, updated_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (workspace_id) DO UPDATE
+        SET approval_rules = EXCLUDED.approval_rules, updated_by = EXCLUDED.updated_by
+        RETURNING workspace_id, allowed_ip_ranges::TEXT[] as "allowed_ip_ranges!", default_page_size, max_page_size,
+          default_sort_by, default_sort_order, code_rules, field_policies, approval_rules, updated_by, updated_at
+        "#,
+      workspace_id,
+      rules,
+      updated_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    row.try_into()
+  }
+}
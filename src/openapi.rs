@@ -0,0 +1,1243 @@
+//! OpenAPI description of the public HTTP API.
+//!
+//! The document is assembled from the route table below rather than derived from the
+//! handlers, so it must be updated whenever a route is added to `app()`. It is written
+//! out by the `generate-openapi` CLI command, and `tests/contract_tests.rs` replays every
+//! operation against the router to catch responses that no longer match it.
+
+use serde_json::{Map, Value, json};
+
+/// A documented route: method, path, tag, summary and whether it requires a bearer token.
+struct Operation {
+  method: &'static str,
+  path: &'static str,
+  tag: &'static str,
+  summary: &'static str,
+  authenticated: bool,
+  has_body: bool,
+}
+
+const fn op(method: &'static str, path: &'static str, tag: &'static str, summary: &'static str, authenticated: bool, has_body: bool) -> Operation {
+  Operation {
+    method,
+    path,
+    tag,
+    summary,
+    authenticated,
+    has_body,
+  }
+}
+
+const OPERATIONS: &[Operation] = &[
+  op(
+    "post",
+    "/api/v1/auth/register",
+    "auth",
+    "Register a new user and their personal workspace",
+    false,
+    true,
+  ),
+  op("post", "/api/v1/auth/login", "auth", "Exchange credentials for a JWT", false, true),
+  op(
+    "post",
+    "/api/v1/auth/forgot-password",
+    "auth",
+    "Email a password reset link; responds the same whether or not the email is registered",
+    false,
+    true,
+  ),
+  op(
+    "post",
+    "/api/v1/auth/reset-password",
+    "auth",
+    "Set a new password with an emailed reset token",
+    false,
+    true,
+  ),
+  op("get", "/api/v1/auth/me", "auth", "Get the authenticated user", true, false),
+  op("post", "/api/v1/auth/logout", "auth", "Revoke the current access token", true, false),
+  op(
+    "post",
+    "/api/v1/auth/switch-workspace",
+    "auth",
+    "Issue a token for another workspace",
+    true,
+    true,
+  ),
+  op(
+    "post",
+    "/api/v1/auth/me/export",
+    "auth",
+    "Start an export of everything tied to the authenticated user; responds 202 and emails a link once it is ready",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/auth/me/exports/{id}",
+    "auth",
+    "Get the status of a data export",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/auth/me/exports/{id}/download",
+    "auth",
+    "Download the archive of a ready data export as a JSON attachment",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/contacts",
+    "contacts",
+    "List contacts in the current workspace",
+    true,
+    false,
+  ),
+  op("post", "/api/v1/contacts", "contacts", "Create a contact", true, true),
+  op(
+    "get",
+    "/api/v1/contacts/next-code",
+    "contacts",
+    "Preview the next contact code",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/contacts/validate-tax-id",
+    "contacts",
+    "Check an NPWP or VAT number against the rules of its country without saving it",
+    true,
+    true,
+  ),
+  op("get", "/api/v1/contacts/{id}", "contacts", "Get a contact by ID", true, false),
+  op("put", "/api/v1/contacts/{id}", "contacts", "Update a contact", true, true),
+  op(
+    "patch",
+    "/api/v1/contacts/{id}",
+    "contacts",
+    "Apply a JSON Merge Patch to a contact",
+    true,
+    true,
+  ),
+  op("delete", "/api/v1/contacts/{id}", "contacts", "Delete a contact", true, false),
+  op(
+    "post",
+    "/api/v1/contacts/{id}/restore",
+    "contacts",
+    "Restore a deleted contact",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/contacts/{id}/lock",
+    "contacts",
+    "Take or renew the lock on a contact being edited (`force=true` lets admins take it over)",
+    true,
+    false,
+  ),
+  op(
+    "delete",
+    "/api/v1/contacts/{id}/lock",
+    "contacts",
+    "Release the lock on a contact",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/contacts/{id}/pdf",
+    "contacts",
+    "Print a contact to PDF with the print template of the workspace",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/contacts/{id}/statement",
+    "contacts",
+    "Statement of a contact with a running balance (`format`: `json`, `csv` or `pdf`)",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/products",
+    "products",
+    "List products in the current workspace",
+    true,
+    false,
+  ),
+  op("post", "/api/v1/products", "products", "Create a product", true, true),
+  op(
+    "post",
+    "/api/v1/products/availability",
+    "products",
+    "Check the stock of several products for the lines of an order",
+    true,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/products/next-code",
+    "products",
+    "Preview the next product code",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/products/{id}",
+    "products",
+    "Get a product by ID, or as it was at the time given by `as_of`",
+    true,
+    false,
+  ),
+  op("put", "/api/v1/products/{id}", "products", "Update a product", true, true),
+  op(
+    "patch",
+    "/api/v1/products/{id}",
+    "products",
+    "Apply a JSON Merge Patch to a product",
+    true,
+    true,
+  ),
+  op("delete", "/api/v1/products/{id}", "products", "Delete a product", true, false),
+  op(
+    "post",
+    "/api/v1/products/{id}/restore",
+    "products",
+    "Restore a deleted product",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/products/{id}/status",
+    "products",
+    "Publish a draft product, discontinue it, or publish it again",
+    true,
+    true,
+  ),
+  op(
+    "post",
+    "/api/v1/products/{id}/lock",
+    "products",
+    "Take or renew the lock on a product being edited (`force=true` lets admins take it over)",
+    true,
+    false,
+  ),
+  op(
+    "delete",
+    "/api/v1/products/{id}/lock",
+    "products",
+    "Release the lock on a product",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/products/labels",
+    "products",
+    "Print a PDF sheet of barcode labels, a quantity of each product, in a chosen label size",
+    true,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/products/{id}/pdf",
+    "products",
+    "Print a product to PDF with the print template of the workspace",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/categories/tree",
+    "products",
+    "Get the product categories as a tree, with product counts and stock values per node",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/workspaces",
+    "workspaces",
+    "List the workspaces of the authenticated user",
+    true,
+    false,
+  ),
+  op("post", "/api/v1/workspaces", "workspaces", "Create a workspace", true, true),
+  op(
+    "get",
+    "/api/v1/overview",
+    "workspaces",
+    "Get the low stock products and overdue payments of all the workspaces of the authenticated user",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/limits",
+    "workspaces",
+    "Get the caller's remaining rate limit budgets and the request and record quotas of the workspace's plan",
+    true,
+    false,
+  ),
+  op("post", "/api/v1/organizations", "organizations", "Create an organization", true, true),
+  op(
+    "get",
+    "/api/v1/organizations",
+    "organizations",
+    "List the organizations of the authenticated user",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/organizations/{organization_id}",
+    "organizations",
+    "Get an organization",
+    true,
+    false,
+  ),
+  op(
+    "put",
+    "/api/v1/organizations/{organization_id}",
+    "organizations",
+    "Update an organization",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/organizations/{organization_id}",
+    "organizations",
+    "Delete an organization",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/organizations/{organization_id}/switch",
+    "organizations",
+    "Switch to an organization and get a token for one of its workspaces",
+    true,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/organizations/{organization_id}/members",
+    "organizations",
+    "List organization members",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/organizations/{organization_id}/members",
+    "organizations",
+    "Add a member to an organization",
+    true,
+    true,
+  ),
+  op(
+    "put",
+    "/api/v1/organizations/{organization_id}/members/{user_id}",
+    "organizations",
+    "Change the roles of an organization member",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/organizations/{organization_id}/members/{user_id}",
+    "organizations",
+    "Remove a member from an organization",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/organizations/{organization_id}/workspaces",
+    "organizations",
+    "List the workspaces of an organization",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/organizations/{organization_id}/workspaces",
+    "organizations",
+    "Add a workspace to an organization",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/organizations/{organization_id}/workspaces/{workspace_id}",
+    "organizations",
+    "Remove a workspace from an organization",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/organizations/{organization_id}/billing",
+    "organizations",
+    "Get which workspaces of an organization are paid for",
+    true,
+    false,
+  ),
+  op(
+    "put",
+    "/api/v1/organizations/{organization_id}/billing",
+    "organizations",
+    "Set the workspace whose subscription pays for an organization",
+    true,
+    true,
+  ),
+  op("get", "/api/v1/workspaces/{workspace_id}", "workspaces", "Get a workspace", true, false),
+  op("put", "/api/v1/workspaces/{workspace_id}", "workspaces", "Update a workspace", true, true),
+  op(
+    "patch",
+    "/api/v1/workspaces/{workspace_id}",
+    "workspaces",
+    "Apply a JSON Merge Patch to a workspace",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/workspaces/{workspace_id}",
+    "workspaces",
+    "Delete a workspace",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/workspaces/{workspace_id}/users",
+    "workspaces",
+    "List workspace members",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/workspaces/{workspace_id}/users",
+    "workspaces",
+    "Add a member to a workspace",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/workspaces/{workspace_id}/users/{user_id}",
+    "workspaces",
+    "Remove a member from a workspace",
+    true,
+    false,
+  ),
+  op(
+    "put",
+    "/api/v1/workspaces/{workspace_id}/users/{user_id}/role",
+    "workspaces",
+    "Change a member's role",
+    true,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/workspaces/{workspace_id}/usage/api",
+    "workspaces",
+    "Get the API usage and quota of a workspace",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/workspaces/{workspace_id}/trial",
+    "workspaces",
+    "Get the trial period of a workspace",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/workspaces/{workspace_id}/retention",
+    "workspaces",
+    "Get the data retention policies of a workspace and its recent purges (admins only)",
+    true,
+    false,
+  ),
+  op(
+    "put",
+    "/api/v1/workspaces/{workspace_id}/retention",
+    "workspaces",
+    "Set how long a category of workspace data is kept (admins only)",
+    true,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/workspaces/{workspace_id}/retention/preview",
+    "workspaces",
+    "Count the data the next retention purge would remove (admins only)",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/workspaces/{workspace_id}/settings",
+    "workspaces",
+    "Get the settings of a workspace, such as its IP allowlist and code rules (admins only)",
+    true,
+    false,
+  ),
+  op(
+    "put",
+    "/api/v1/workspaces/{workspace_id}/settings",
+    "workspaces",
+    "Set the IP allowlist, list defaults or code rules of a workspace (admins only)",
+    true,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/workspaces/{workspace_id}/audit-events",
+    "workspaces",
+    "List the audit log of a workspace, such as member and role changes (admins only)",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/workspaces/{workspace_id}/report-schedules",
+    "reports",
+    "List the report schedules of a workspace",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/workspaces/{workspace_id}/report-schedules",
+    "reports",
+    "Schedule a report, delivered by email and/or webhook",
+    true,
+    true,
+  ),
+  op(
+    "put",
+    "/api/v1/workspaces/{workspace_id}/report-schedules/{schedule_id}",
+    "reports",
+    "Replace a report schedule",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/workspaces/{workspace_id}/report-schedules/{schedule_id}",
+    "reports",
+    "Delete a report schedule",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/import/{source}",
+    "import",
+    "Import the contacts or products (`entity` query parameter) of a CSV file exported from an accounting tool (`Prefer: respond-async` imports in the background)",
+    true,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/templates",
+    "templates",
+    "List the email templates of the workspace",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/templates",
+    "templates",
+    "Customize an email of the workspace (invitation, invoice or reminder) with a Handlebars template",
+    true,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/templates/{template_id}",
+    "templates",
+    "Get an email template",
+    true,
+    false,
+  ),
+  op(
+    "put",
+    "/api/v1/templates/{template_id}",
+    "templates",
+    "Replace an email template",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/templates/{template_id}",
+    "templates",
+    "Delete an email template",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/templates/{template_id}/preview",
+    "templates",
+    "Render an email template with sample or given values of its variables",
+    true,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/print-templates",
+    "rendering",
+    "List the print template of each entity, customized by the workspace or built in",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/print-templates/{entity}",
+    "rendering",
+    "Get the print template of contacts or products",
+    true,
+    false,
+  ),
+  op(
+    "put",
+    "/api/v1/print-templates/{entity}",
+    "rendering",
+    "Customize how the workspace prints contacts or products with an HTML template in Handlebars syntax",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/print-templates/{entity}",
+    "rendering",
+    "Go back to the built-in print template of contacts or products",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/inbound-integrations",
+    "inbound",
+    "List the inbound webhook integrations of the workspace",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/inbound-integrations",
+    "inbound",
+    "Create an inbound webhook integration; its signing secret is only returned here",
+    true,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/inbound-integrations/{integration_id}",
+    "inbound",
+    "Get an inbound webhook integration",
+    true,
+    false,
+  ),
+  op(
+    "delete",
+    "/api/v1/inbound-integrations/{integration_id}",
+    "inbound",
+    "Delete an inbound webhook integration and its events",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/inbound-integrations/{integration_id}/events",
+    "inbound",
+    "List the latest payloads received by an integration (`status` and `limit` query parameters)",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/inbound/{integration_key}",
+    "inbound",
+    "Queue a JSON payload from a third-party system, authenticated by an HMAC-SHA256 signature of the body (X-Signature header)",
+    false,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/connectors",
+    "connectors",
+    "List the connectors of the workspace to online stores, with where they are in their syncs",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/connectors/orders",
+    "connectors",
+    "List the orders pulled from the stores (`connector` and `limit` query parameters)",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/connectors/{connector}",
+    "connectors",
+    "Get the Shopify or WooCommerce connector of the workspace",
+    true,
+    false,
+  ),
+  op(
+    "put",
+    "/api/v1/connectors/{connector}",
+    "connectors",
+    "Connect the workspace to a Shopify or WooCommerce store; the credentials are stored encrypted and never returned",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/connectors/{connector}",
+    "connectors",
+    "Disconnect the workspace from a store, keeping the orders already pulled",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/connectors/{connector}/sync",
+    "connectors",
+    "Sync a connector with the next check instead of at its interval",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/exchange-rates",
+    "currencies",
+    "List the exchange rate of each currency in effect on a day (`date` query parameter), today by default",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/exchange-rates/{currency}/history",
+    "currencies",
+    "List the daily rates of a currency between two days (`from` and `to` query parameters)",
+    true,
+    false,
+  ),
+  op(
+    "put",
+    "/api/v1/exchange-rates/{currency}/{date}",
+    "currencies",
+    "Set the rate of a currency on a day for the workspace, replacing the provider's",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/exchange-rates/{currency}/{date}",
+    "currencies",
+    "Remove a rate set by the workspace, the provider's applying again",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/geo/autocomplete",
+    "geo",
+    "Suggest structured addresses for what was typed (`q`, `country` and `limit` query parameters)",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/triggers/{entity}/new-or-updated",
+    "triggers",
+    "Poll the contacts or products created or changed since a cursor (`since` query parameter), newest first",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/approvals",
+    "approvals",
+    "List the approval requests, all of them for admins and their own for members (`status` and `limit` query parameters)",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/approvals/{approval_id}",
+    "approvals",
+    "Get an approval request",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/approvals/{approval_id}/approve",
+    "approvals",
+    "Approve a pending request and apply its change (workspace admins only)",
+    true,
+    true,
+  ),
+  op(
+    "post",
+    "/api/v1/approvals/{approval_id}/reject",
+    "approvals",
+    "Reject a pending request (workspace admins only)",
+    true,
+    true,
+  ),
+  op(
+    "post",
+    "/api/v1/approvals/{approval_id}/cancel",
+    "approvals",
+    "Cancel a pending request (its requester only)",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/operations/{operation_id}",
+    "operations",
+    "Get the status and progress of a request running in the background",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/operations/{operation_id}/result",
+    "operations",
+    "Get the result of a succeeded operation",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/search",
+    "search",
+    "Search the contacts and products of the workspace as the user types",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/exports/{entity}",
+    "exports",
+    "Export contacts or products as CSV with chosen columns, headers, date format and filters",
+    true,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/exports/profiles",
+    "exports",
+    "List the export profiles of the caller",
+    true,
+    false,
+  ),
+  op("post", "/api/v1/exports/profiles", "exports", "Save an export profile", true, true),
+  op(
+    "get",
+    "/api/v1/exports/profiles/{profile_id}",
+    "exports",
+    "Get an export profile",
+    true,
+    false,
+  ),
+  op(
+    "put",
+    "/api/v1/exports/profiles/{profile_id}",
+    "exports",
+    "Replace an export profile",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/exports/profiles/{profile_id}",
+    "exports",
+    "Delete an export profile",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/feature-flags",
+    "feature flags",
+    "Get the feature flags in effect for the current workspace",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/admin/feature-flags",
+    "feature flags",
+    "List all feature flags with their workspace overrides (feature flag admins only)",
+    true,
+    false,
+  ),
+  op(
+    "put",
+    "/api/v1/admin/feature-flags/{key}",
+    "feature flags",
+    "Create a feature flag or set its global state (feature flag admins only)",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/admin/feature-flags/{key}",
+    "feature flags",
+    "Delete a feature flag and its overrides (feature flag admins only)",
+    true,
+    false,
+  ),
+  op(
+    "put",
+    "/api/v1/admin/feature-flags/{key}/workspaces/{workspace_id}",
+    "feature flags",
+    "Set the state of a feature flag for one workspace (feature flag admins only)",
+    true,
+    true,
+  ),
+  op(
+    "delete",
+    "/api/v1/admin/feature-flags/{key}/workspaces/{workspace_id}",
+    "feature flags",
+    "Return a workspace to the global state of a feature flag (feature flag admins only)",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/billing/subscription",
+    "billing",
+    "Get the subscription of the current workspace",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/billing/checkout-session",
+    "billing",
+    "Start a Stripe Checkout session subscribing the current workspace",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/billing/portal",
+    "billing",
+    "Get a Stripe customer portal URL for the current workspace",
+    true,
+    false,
+  ),
+  op(
+    "post",
+    "/api/v1/billing/webhooks/stripe",
+    "billing",
+    "Receive a Stripe webhook event, authenticated by its Stripe-Signature header",
+    false,
+    true,
+  ),
+  op(
+    "get",
+    "/api/v1/admin/stats",
+    "admin",
+    "Platform statistics: totals, monthly active users, the largest workspaces, error rates and background task health (superadmins only)",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/admin/backups",
+    "backups",
+    "List the database backups of an environment, newest first (backup admins only; ?environment= defaults to this server's)",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v2/contacts",
+    "contacts (v2)",
+    "List contacts in the current workspace",
+    true,
+    false,
+  ),
+  op("post", "/api/v2/contacts", "contacts (v2)", "Create a contact", true, true),
+  op("get", "/api/v2/contacts/{id}", "contacts (v2)", "Get a contact by ID", true, false),
+  op(
+    "patch",
+    "/api/v2/contacts/{id}",
+    "contacts (v2)",
+    "Apply a JSON Merge Patch to a contact",
+    true,
+    true,
+  ),
+  op("delete", "/api/v2/contacts/{id}", "contacts (v2)", "Delete a contact", true, false),
+  op(
+    "get",
+    "/api/v2/products",
+    "products (v2)",
+    "List products in the current workspace",
+    true,
+    false,
+  ),
+  op("post", "/api/v2/products", "products (v2)", "Create a product", true, true),
+  op(
+    "post",
+    "/api/v2/products/availability",
+    "products (v2)",
+    "Check the stock of several products for the lines of an order",
+    true,
+    true,
+  ),
+  op("get", "/api/v2/products/{id}", "products (v2)", "Get a product by ID", true, false),
+  op(
+    "patch",
+    "/api/v2/products/{id}",
+    "products (v2)",
+    "Apply a JSON Merge Patch to a product",
+    true,
+    true,
+  ),
+  op("delete", "/api/v2/products/{id}", "products (v2)", "Delete a product", true, false),
+  op(
+    "get",
+    "/api/v1/ws",
+    "realtime",
+    "Open a WebSocket streaming the workspace's events (token and workspace_id may be passed as query parameters)",
+    true,
+    false,
+  ),
+  op(
+    "get",
+    "/api/v1/events",
+    "realtime",
+    "Stream the workspace's events as Server-Sent Events, resuming after Last-Event-ID",
+    true,
+    false,
+  ),
+];
+
+/// Builds the OpenAPI 3.0 document for the API.
+///
+/// # Returns
+///
+/// * `Value` - The OpenAPI document as JSON.
+pub fn openapi_document() -> Value {
+  let mut paths = Map::new();
+
+  for operation in OPERATIONS.iter().filter(|operation| is_compiled(operation)) {
+    let mut parameters: Vec<Value> = path_parameters(operation.path)
+      .map(|name| json!({ "name": name, "in": "path", "required": true, "schema": path_parameter_schema(name) }))
+      .collect();
+
+    let mut spec = json!({
+      "tags": [operation.tag],
+      "summary": operation.summary,
+      "responses": {
+        "2XX": { "description": "Success", "content": success_content(operation) },
+        "default": { "description": "Error", "content": { "application/json": { "schema": { "$ref": "#/components/schemas/ErrorResponse" } } } }
+      }
+    });
+
+    if operation.authenticated {
+      spec["security"] = json!([{ "bearerAuth": [] }]);
+      parameters.push(json!({
+        "name": "X-Workspace-ID",
+        "in": "header",
+        "required": false,
+        "description": "Workspace the request operates on",
+        "schema": { "type": "string", "format": "uuid" }
+      }));
+    }
+    if !parameters.is_empty() {
+      spec["parameters"] = Value::Array(parameters);
+    }
+    if operation.has_body {
+      spec["requestBody"] = request_body(operation);
+    }
+
+    let path_item = paths.entry(operation.path).or_insert_with(|| json!({}));
+    path_item[operation.method] = spec;
+  }
+
+  json!({
+    "openapi": "3.0.3",
+    "info": {
+      "title": "My App API",
+      "version": env!("CARGO_PKG_VERSION")
+    },
+    "paths": paths,
+    "components": {
+      "securitySchemes": {
+        "bearerAuth": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" }
+      },
+      "schemas": {
+        "ApiResponse": {
+          "type": "object",
+          "required": ["status", "message", "timestamp"],
+          "properties": {
+            "status": { "type": "string" },
+            "message": { "type": "string" },
+            "results": {},
+            "timestamp": { "type": "string", "format": "date-time" }
+          }
+        },
+        "DataResponse": {
+          "type": "object",
+          "required": ["data"],
+          "properties": {
+            "data": {},
+            "meta": {
+              "type": "object",
+              "properties": {
+                "page": { "type": "integer" },
+                "limit": { "type": "integer" },
+                "total": { "type": "integer" },
+                "total_pages": { "type": "integer" }
+              }
+            }
+          }
+        },
+        "UserExportArchive": {
+          "type": "object",
+          "required": ["format_version", "generated_at", "profile", "memberships", "api_usage", "created_records"],
+          "properties": {
+            "format_version": { "type": "integer" },
+            "generated_at": { "type": "string", "format": "date-time" },
+            "profile": { "type": "object" },
+            "memberships": { "type": "array", "items": { "type": "object" } },
+            "api_usage": { "type": "array", "items": { "type": "object" } },
+            "created_records": { "type": "array", "items": { "type": "object" } }
+          }
+        },
+        "ErrorResponse": {
+          "type": "object",
+          "required": ["error", "message", "timestamp"],
+          "properties": {
+            "error": { "type": "string" },
+            "message": { "type": "string" },
+            "details": {},
+            "code": { "type": "string" },
+            "timestamp": { "type": "string", "format": "date-time" }
+          }
+        }
+      }
+    }
+  })
+}
+
+/// The success envelope of an operation: v2 replaced `ApiResponse` with `DataResponse`, and data
+/// export archives are downloaded without one.
+fn response_schema(operation: &Operation) -> &'static str {
+  if operation.tag.ends_with("(v2)") {
+    "DataResponse"
+  } else if operation.path.starts_with("/api/v1/auth/me/exports/") && operation.path.ends_with("/download") {
+    "UserExportArchive"
+  } else {
+    "ApiResponse"
+  }
+}
+
+/// Printed records and label sheets are PDF documents, exports are CSV files, every other success
+/// is JSON.
+fn success_content(operation: &Operation) -> Value {
+  if is_pdf(operation) {
+    json!({ "application/pdf": { "schema": { "type": "string", "format": "binary" } } })
+  } else if operation.path == "/api/v1/exports/{entity}" {
+    json!({ "text/csv": { "schema": { "type": "string" } } })
+  } else {
+    json!({ "application/json": { "schema": { "$ref": format!("#/components/schemas/{}", response_schema(operation)) } } })
+  }
+}
+
+fn is_pdf(operation: &Operation) -> bool {
+  operation.path.ends_with("/pdf") || operation.path == "/api/v1/products/labels"
+}
+
+/// Whether the modules serving an operation are compiled in (see the Cargo features). Records
+/// are printed by the `rendering` feature, from the routes of their own module.
+fn is_compiled(operation: &Operation) -> bool {
+  let module = operation.tag.trim_end_matches(" (v2)");
+  (!is_pdf(operation) || cfg!(feature = "rendering"))
+    && (module != "contacts" || cfg!(feature = "contacts"))
+    && (module != "products" || cfg!(feature = "products"))
+    && (module != "billing" || cfg!(feature = "billing"))
+    && (module != "backups" || cfg!(feature = "backups"))
+    && (module != "reports" || cfg!(feature = "reports"))
+    && (module != "import" || cfg!(feature = "import"))
+    && (module != "templates" || cfg!(feature = "email_templates"))
+    && (module != "rendering" || cfg!(feature = "rendering"))
+    && (module != "inbound" || cfg!(feature = "inbound"))
+    && (module != "connectors" || cfg!(feature = "connectors"))
+    && (module != "currencies" || cfg!(feature = "currencies"))
+    && (module != "geo" || cfg!(feature = "geo"))
+    && (module != "exports" || cfg!(feature = "exports"))
+    && (module != "triggers" || cfg!(any(feature = "contacts", feature = "products")))
+    && (module != "approvals" || cfg!(any(feature = "contacts", feature = "products")))
+    && (module != "operations" || cfg!(any(feature = "contacts", feature = "products")))
+    && (module != "search" || cfg!(any(feature = "contacts", feature = "products")))
+}
+
+/// Request bodies are JSON objects, except for the CSV files of imports.
+fn request_body(operation: &Operation) -> Value {
+  if operation.tag == "import" {
+    json!({ "required": true, "content": { "text/csv": { "schema": { "type": "string" } } } })
+  } else {
+    json!({ "required": true, "content": { "application/json": { "schema": { "type": "object" } } } })
+  }
+}
+
+/// Path parameters are UUIDs, except for the keys of feature flags and inbound integrations, the
+/// sources of imports, the platforms of connectors and the currencies and days of exchange rates.
+fn path_parameter_schema(name: &str) -> Value {
+  match name {
+    "key" => json!({ "type": "string", "pattern": "^[a-z0-9_]{1,64}$" }),
+    "integration_key" => json!({ "type": "string", "pattern": "^[0-9a-f]{32}$" }),
+    "source" => json!({ "type": "string", "enum": ["generic", "accurate", "jurnal", "quickbooks"] }),
+    "connector" => json!({ "type": "string", "enum": ["shopify", "woocommerce"] }),
+    "currency" => json!({ "type": "string", "pattern": "^[A-Za-z]{3}$" }),
+    "date" => json!({ "type": "string", "format": "date" }),
+    "entity" => {
+      let entities: Vec<&str> = [("contacts", cfg!(feature = "contacts")), ("products", cfg!(feature = "products"))]
+        .into_iter()
+        .filter_map(|(entity, compiled)| compiled.then_some(entity))
+        .collect();
+      json!({ "type": "string", "enum": entities })
+    }
+    _ => json!({ "type": "string", "format": "uuid" }),
+  }
+}
+
+/// Yields the `{name}` placeholders of an OpenAPI path.
+fn path_parameters(path: &str) -> impl Iterator<Item = &str> {
+  path
+    .split('/')
+    .filter_map(|segment| segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')))
+}
@@ -0,0 +1,253 @@
+//! Redis implementations of the stores that must be shared between instances.
+//!
+//! Used instead of the in-memory (and Postgres) stores when `REDIS_URL` is set, so that
+//! rate limit budgets, logouts, session activity and idempotency keys apply across every instance behind
+//! the load balancer.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use redis::{AsyncCommands, Script, aio::ConnectionManager};
+use uuid::Uuid;
+
+use crate::{
+  AppResult,
+  errors::AppError,
+  middleware::{IdempotencyStore, RateLimitStore, idempotency::IdempotencyRecord, rate_limit::RateLimitDecision},
+  modules::auth::{auth_service::ACCESS_TOKEN_LIFETIME, session_activity::SessionActivityStore, token_revocation::TokenRevocationStore},
+};
+
+/// Counts a hit and starts the window on the first one; returns the count and the window's remaining milliseconds.
+const RATE_LIMIT_SCRIPT: &str = r#"
+local count = redis.call('INCR', KEYS[1])
+if count == 1 then
+  redis.call('PEXPIRE', KEYS[1], ARGV[1])
+end
+return { count, redis.call('PTTL', KEYS[1]) }
+"#;
+
+/// Opens a managed connection that reconnects automatically.
+pub async fn connect(url: &str) -> redis::RedisResult<ConnectionManager> {
+  redis::Client::open(url)?.get_connection_manager().await
+}
+
+fn store_error(e: redis::RedisError) -> AppError {
+  AppError::Internal(format!("Redis error: {}", e))
+}
+
+/// Fixed-window rate limit store shared by all instances.
+pub struct RedisRateLimitStore {
+  connection: ConnectionManager,
+  script: Script,
+}
+
+impl RedisRateLimitStore {
+  pub fn new(connection: ConnectionManager) -> Self {
+    Self {
+      connection,
+      script: Script::new(RATE_LIMIT_SCRIPT),
+    }
+  }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+  async fn hit(&self, key: &str, limit: u32, window: Duration) -> AppResult<RateLimitDecision> {
+    let mut connection = self.connection.clone();
+    let window_ms = u64::try_from(window.as_millis()).unwrap_or(u64::MAX);
+    let (count, ttl_ms): (u64, i64) = self
+      .script
+      .key(format!("ratelimit:{}", key))
+      .arg(window_ms)
+      .invoke_async(&mut connection)
+      .await
+      .map_err(store_error)?;
+
+    let count = u32::try_from(count).unwrap_or(u32::MAX);
+    let reset_after = u64::try_from(ttl_ms).map_or(window.as_secs(), |ms| ms.div_ceil(1000)).max(1);
+
+    Ok(RateLimitDecision {
+      allowed: count <= limit,
+      limit,
+      remaining: limit.saturating_sub(count),
+      reset_after,
+    })
+  }
+
+  async fn peek(&self, key: &str, limit: u32, window: Duration) -> AppResult<RateLimitDecision> {
+    let mut connection = self.connection.clone();
+    let key = format!("ratelimit:{}", key);
+    let (count, ttl_ms): (Option<u64>, i64) = redis::pipe()
+      .get(&key)
+      .pttl(&key)
+      .query_async(&mut connection)
+      .await
+      .map_err(store_error)?;
+
+    let count = u32::try_from(count.unwrap_or(0)).unwrap_or(u32::MAX);
+    let reset_after = u64::try_from(ttl_ms).map_or(window.as_secs(), |ms| ms.div_ceil(1000)).max(1);
+
+    Ok(RateLimitDecision {
+      allowed: count < limit,
+      limit,
+      remaining: limit.saturating_sub(count),
+      reset_after,
+    })
+  }
+}
+
+/// Token revocation list kept in Redis, with each entry expiring together with its token.
+pub struct RedisTokenRevocationStore {
+  connection: ConnectionManager,
+}
+
+impl RedisTokenRevocationStore {
+  pub fn new(connection: ConnectionManager) -> Self {
+    Self { connection }
+  }
+}
+
+#[async_trait]
+impl TokenRevocationStore for RedisTokenRevocationStore {
+  async fn revoke(&self, jti: Uuid, expires_at: DateTime<Utc>) -> AppResult<()> {
+    let ttl = (expires_at - Utc::now()).num_seconds();
+    if ttl <= 0 {
+      return Ok(());
+    }
+
+    let mut connection = self.connection.clone();
+    connection
+      .set_ex::<_, _, ()>(format!("revoked:{}", jti), 1, ttl as u64)
+      .await
+      .map_err(store_error)
+  }
+
+  async fn is_revoked(&self, jti: Uuid) -> AppResult<bool> {
+    let mut connection = self.connection.clone();
+    connection.exists(format!("revoked:{}", jti)).await.map_err(store_error)
+  }
+
+  /// Kept for as long as the tokens issued before `at` can live.
+  async fn revoke_issued_before(&self, user_id: Uuid, at: DateTime<Utc>) -> AppResult<()> {
+    let mut connection = self.connection.clone();
+    connection
+      .set_ex::<_, _, ()>(
+        format!("revoked_before:{}", user_id),
+        at.timestamp_micros(),
+        ACCESS_TOKEN_LIFETIME.num_seconds() as u64,
+      )
+      .await
+      .map_err(store_error)
+  }
+
+  async fn revoked_before(&self, user_id: Uuid) -> AppResult<Option<DateTime<Utc>>> {
+    let mut connection = self.connection.clone();
+    let micros: Option<i64> = connection.get(format!("revoked_before:{}", user_id)).await.map_err(store_error)?;
+    Ok(micros.and_then(DateTime::from_timestamp_micros))
+  }
+}
+
+/// Session activity kept in Redis: a session's key expires once it has been idle for the timeout.
+pub struct RedisSessionActivityStore {
+  connection: ConnectionManager,
+}
+
+impl RedisSessionActivityStore {
+  pub fn new(connection: ConnectionManager) -> Self {
+    Self { connection }
+  }
+}
+
+#[async_trait]
+impl SessionActivityStore for RedisSessionActivityStore {
+  async fn touch(&self, jti: Uuid, issued_at: DateTime<Utc>, _expires_at: DateTime<Utc>, idle_timeout: Duration) -> AppResult<bool> {
+    let mut connection = self.connection.clone();
+    let key = format!("session:{}", jti);
+    let ttl = idle_timeout.as_secs().max(1);
+
+    // Extends the session while its key has not expired
+    let extended: Option<String> = redis::cmd("SET")
+      .arg(&key)
+      .arg(1)
+      .arg("EX")
+      .arg(ttl)
+      .arg("XX")
+      .query_async(&mut connection)
+      .await
+      .map_err(store_error)?;
+    if extended.is_some() {
+      return Ok(true);
+    }
+
+    // First use, within the timeout of the token's issue
+    if issued_at < Utc::now() - idle_timeout {
+      return Ok(false);
+    }
+    connection.set_ex::<_, _, ()>(&key, 1, ttl).await.map_err(store_error)?;
+    Ok(true)
+  }
+}
+
+/// Idempotency store shared by all instances, so a retry landing on another instance is replayed too.
+pub struct RedisIdempotencyStore {
+  connection: ConnectionManager,
+}
+
+impl RedisIdempotencyStore {
+  pub fn new(connection: ConnectionManager) -> Self {
+    Self { connection }
+  }
+}
+
+fn idempotency_key(key: &str) -> String {
+  format!("idempotency:{}", key)
+}
+
+#[async_trait]
+impl IdempotencyStore for RedisIdempotencyStore {
+  async fn begin(&self, key: &str, fingerprint: &str, lock_ttl: Duration) -> AppResult<Option<IdempotencyRecord>> {
+    let mut connection = self.connection.clone();
+    let record = IdempotencyRecord {
+      fingerprint: fingerprint.to_string(),
+      response: None,
+    };
+    let value = serde_json::to_string(&record).map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let reserved: Option<String> = redis::cmd("SET")
+      .arg(idempotency_key(key))
+      .arg(value)
+      .arg("NX")
+      .arg("EX")
+      .arg(lock_ttl.as_secs().max(1))
+      .query_async(&mut connection)
+      .await
+      .map_err(store_error)?;
+    if reserved.is_some() {
+      return Ok(None);
+    }
+
+    let existing: Option<String> = connection.get(idempotency_key(key)).await.map_err(store_error)?;
+    match existing {
+      Some(existing) => serde_json::from_str(&existing)
+        .map(Some)
+        .map_err(|e| AppError::Internal(format!("Invalid idempotency record: {}", e))),
+      // The record expired between the two commands
+      None => Err(AppError::Internal("Idempotency record expired while being read".to_string())),
+    }
+  }
+
+  async fn complete(&self, key: &str, record: &IdempotencyRecord, ttl: Duration) -> AppResult<()> {
+    let mut connection = self.connection.clone();
+    let value = serde_json::to_string(record).map_err(|e| AppError::Internal(e.to_string()))?;
+    connection
+      .set_ex::<_, _, ()>(idempotency_key(key), value, ttl.as_secs().max(1))
+      .await
+      .map_err(store_error)
+  }
+
+  async fn release(&self, key: &str) -> AppResult<()> {
+    let mut connection = self.connection.clone();
+    connection.del::<_, ()>(idempotency_key(key)).await.map_err(store_error)
+  }
+}
@@ -1,8 +1,16 @@
+use axum::{
+  Json,
+  http::{StatusCode, header},
+  response::{IntoResponse, Response},
+};
 use chrono::{DateTime, Utc};
+#[cfg(feature = "client")]
+use serde::Deserialize;
 use serde::Serialize;
 
 /// Standard API Response wrapper
 #[derive(Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
 pub struct ApiResponse<T> {
   pub status: String,
   pub message: String,
@@ -10,8 +18,16 @@ pub struct ApiResponse<T> {
   pub timestamp: DateTime<Utc>,
 }
 
+/// A `201 Created` response: `body` as JSON, with the path of the new resource in the `Location`
+/// header.
+pub struct Created<T> {
+  pub location: String,
+  pub body: T,
+}
+
 /// Paginated response structure
 #[derive(Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
 pub struct PaginatedResponse<T> {
   pub list: Vec<T>,
   pub pagination: PaginationMeta,
@@ -19,6 +35,7 @@ pub struct PaginatedResponse<T> {
 
 /// Pagination metadata
 #[derive(Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
 pub struct PaginationMeta {
   pub page: u32,
   pub limit: u32,
@@ -53,6 +70,11 @@ impl<T> ApiResponse<T> {
     Self::success(data, "Operation completed successfully")
   }
 
+  /// Create a `201 Created` response for a resource that can be fetched at `location`
+  pub fn created(data: T, message: &str, location: String) -> Created<Self> {
+    Created::new(location, Self::success(data, message))
+  }
+
   /// Create error response for validation failures
   pub fn validation_error(message: &str) -> Self {
     Self {
@@ -64,6 +86,18 @@ impl<T> ApiResponse<T> {
   }
 }
 
+impl<T> Created<T> {
+  pub fn new(location: String, body: T) -> Self {
+    Self { location, body }
+  }
+}
+
+impl<T: Serialize> IntoResponse for Created<T> {
+  fn into_response(self) -> Response {
+    (StatusCode::CREATED, [(header::LOCATION, self.location)], Json(self.body)).into_response()
+  }
+}
+
 impl PaginationMeta {
   pub fn new(page: u32, limit: u32, total: u64) -> Self {
     let total_pages = (total as f64 / limit as f64).ceil() as u32;
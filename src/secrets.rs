@@ -0,0 +1,376 @@
+//! Secrets read from a secrets backend instead of the environment.
+//!
+//! `setup_state()` loads `JWT_SECRET`, `JWT_PREVIOUS_SECRET` and `DATABASE_URL` through the
+//! provider selected by `SECRETS_PROVIDER`. With Vault or AWS Secrets Manager, `run_refresher`
+//! reloads them every `SECRETS_REFRESH_INTERVAL_SECS`:
+//!
+//! - a new `JWT_SECRET` signs new tokens at once, and the replaced one keeps verifying the tokens
+//!   it signed (see `JwtKeys`);
+//! - a new `DATABASE_URL` is used for the connections opened from then on, while the open ones
+//!   are recycled by the pool as usual. The read replica and the backups keep their own settings.
+//!
+//! `FIELD_ENCRYPTION_KEYS` is loaded the same way, but only at startup: a new key has to be
+//! rolled out before the values are re-encrypted with it.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use tracing::{info, warn};
+
+use crate::{
+  AppResult,
+  config::{SecretsBackend, SecretsConfig},
+  internal_error,
+  state::AppState,
+};
+
+const JWT_SECRET: &str = "JWT_SECRET";
+const JWT_PREVIOUS_SECRET: &str = "JWT_PREVIOUS_SECRET";
+const DATABASE_URL: &str = "DATABASE_URL";
+const FIELD_ENCRYPTION_KEYS: &str = "FIELD_ENCRYPTION_KEYS";
+
+/// The secrets the server needs to start.
+#[derive(Clone)]
+pub struct Secrets {
+  pub jwt_secret: String,
+  pub jwt_previous_secret: Option<String>,
+  pub database_url: String,
+  /// Keys of the encrypted columns (see `utils::field_encryption`), only read at startup.
+  pub field_encryption_keys: Option<String>,
+}
+
+/// A backend keeping secrets by name.
+#[async_trait]
+pub trait SecretsProvider: Send + Sync {
+  /// The secrets kept by the backend, by environment variable name.
+  async fn fetch(&self) -> AppResult<HashMap<String, String>>;
+}
+
+/// Keeps nothing, so every secret is read from the environment.
+pub struct EnvProvider;
+
+#[async_trait]
+impl SecretsProvider for EnvProvider {
+  async fn fetch(&self) -> AppResult<HashMap<String, String>> {
+    Ok(HashMap::new())
+  }
+}
+
+/// The provider selected by `config.backend`.
+pub fn provider(config: &SecretsConfig) -> AppResult<Arc<dyn SecretsProvider>> {
+  match config.backend {
+    SecretsBackend::Env => Ok(Arc::new(EnvProvider)),
+    #[cfg(feature = "secrets")]
+    SecretsBackend::Vault => Ok(Arc::new(vault::VaultProvider::from_config(config)?)),
+    #[cfg(feature = "secrets")]
+    SecretsBackend::Aws => Ok(Arc::new(aws::AwsSecretsManagerProvider::from_config(config)?)),
+    #[cfg(not(feature = "secrets"))]
+    backend => Err(internal_error!(
+      "SECRETS_PROVIDER {:?} requires building with the `secrets` feature",
+      backend
+    )),
+  }
+}
+
+/// Loads the secrets from `provider`, reading those it does not keep from the environment.
+pub async fn load(provider: &dyn SecretsProvider) -> AppResult<Secrets> {
+  let mut values = provider.fetch().await?;
+  let mut take = |name: &str| {
+    values
+      .remove(name)
+      .or_else(|| std::env::var(name).ok())
+      .filter(|value| !value.trim().is_empty())
+  };
+
+  Ok(Secrets {
+    jwt_secret: take(JWT_SECRET).ok_or_else(|| internal_error!("{} must be set", JWT_SECRET))?,
+    jwt_previous_secret: take(JWT_PREVIOUS_SECRET),
+    database_url: take(DATABASE_URL).ok_or_else(|| internal_error!("{} must be set", DATABASE_URL))?,
+    field_encryption_keys: take(FIELD_ENCRYPTION_KEYS),
+  })
+}
+
+/// Applies the changes of reloaded secrets to a running server.
+pub struct SecretsRefresher {
+  provider: Arc<dyn SecretsProvider>,
+  database_url: Option<String>,
+}
+
+impl SecretsRefresher {
+  pub fn new(provider: Arc<dyn SecretsProvider>) -> Self {
+    Self {
+      provider,
+      database_url: None,
+    }
+  }
+
+  /// Reloads the secrets, rotating the JWT secret and reconfiguring the pool when they changed.
+  /// The database URL of the first reload is taken as the one the pool was opened with.
+  pub async fn refresh(&mut self, state: &AppState) -> AppResult<()> {
+    let secrets = load(self.provider.as_ref()).await?;
+
+    if state.jwt_keys.rotate(secrets.jwt_secret) {
+      info!("JWT_SECRET changed, signing new tokens with it and still accepting the previous one");
+    }
+
+    match &self.database_url {
+      Some(url) if *url != secrets.database_url => {
+        secrets
+          .database_url
+          .parse::<sqlx::postgres::PgConnectOptions>()
+          .map_err(|e| internal_error!("The new DATABASE_URL is not a valid PostgreSQL URL: {}", e))?;
+        state
+          .db
+          .set_connect_options(crate::connect_options(&secrets.database_url, &state.config.database));
+        info!("DATABASE_URL changed, opening new connections with it");
+      }
+      _ => {}
+    }
+    self.database_url = Some(secrets.database_url);
+
+    Ok(())
+  }
+}
+
+/// Reloads the secrets every `secrets.refresh_interval_secs` for the lifetime of the process,
+/// unless they come from the environment or refreshing is disabled.
+pub async fn run_refresher(state: Arc<AppState>) {
+  let config = &state.config.secrets;
+  if config.backend == SecretsBackend::Env || config.refresh_interval_secs == 0 {
+    return;
+  }
+  let provider = match provider(config) {
+    Ok(provider) => provider,
+    Err(e) => {
+      warn!("Secrets are not refreshed: {}", e);
+      return;
+    }
+  };
+
+  let mut refresher = SecretsRefresher::new(provider);
+  let mut interval = tokio::time::interval(Duration::from_secs(config.refresh_interval_secs));
+  loop {
+    interval.tick().await;
+    let result = refresher.refresh(&state).await;
+    state.task_health.record("secrets_refresher", &result);
+    if let Err(e) = result {
+      warn!("Failed to refresh secrets, keeping the current ones: {}", e);
+    }
+  }
+}
+
+#[cfg(feature = "secrets")]
+pub mod vault {
+  //! HashiCorp Vault, through the KV version 2 secrets engine.
+
+  use std::collections::HashMap;
+
+  use async_trait::async_trait;
+  use serde::Deserialize;
+
+  use super::SecretsProvider;
+  use crate::{AppResult, config::SecretsConfig, internal_error};
+
+  pub struct VaultProvider {
+    http: reqwest::Client,
+    url: String,
+    token: String,
+  }
+
+  #[derive(Deserialize)]
+  struct KvResponse {
+    data: KvData,
+  }
+
+  #[derive(Deserialize)]
+  struct KvData {
+    data: HashMap<String, String>,
+  }
+
+  impl VaultProvider {
+    pub fn from_config(config: &SecretsConfig) -> AppResult<Self> {
+      let addr = config.vault_addr.as_deref().ok_or_else(|| internal_error!("VAULT_ADDR must be set"))?;
+      let token = config.vault_token.clone().ok_or_else(|| internal_error!("VAULT_TOKEN must be set"))?;
+      Ok(Self {
+        http: reqwest::Client::new(),
+        url: format!("{}/v1/{}/data/{}", addr, config.vault_mount, config.vault_path),
+        token,
+      })
+    }
+  }
+
+  #[async_trait]
+  impl SecretsProvider for VaultProvider {
+    async fn fetch(&self) -> AppResult<HashMap<String, String>> {
+      let response = self
+        .http
+        .get(&self.url)
+        .header("X-Vault-Token", &self.token)
+        .send()
+        .await
+        .map_err(|e| internal_error!("Vault request failed: {}", e))?;
+      let status = response.status();
+      if !status.is_success() {
+        return Err(internal_error!("Vault responded {} for {}", status, self.url));
+      }
+      let secret: KvResponse = response.json().await.map_err(|e| internal_error!("Unexpected Vault response: {}", e))?;
+      Ok(secret.data.data)
+    }
+  }
+}
+
+#[cfg(feature = "secrets")]
+pub mod aws {
+  //! AWS Secrets Manager, signed with AWS Signature Version 4.
+
+  use std::collections::HashMap;
+
+  use async_trait::async_trait;
+  use chrono::{DateTime, Utc};
+  use hmac::{Hmac, Mac};
+  use reqwest::Url;
+  use serde::Deserialize;
+  use serde_json::json;
+  use sha2::{Digest, Sha256};
+
+  use super::SecretsProvider;
+  use crate::{AppResult, config::SecretsConfig, internal_error};
+
+  type HmacSha256 = Hmac<Sha256>;
+
+  const TARGET: &str = "secretsmanager.GetSecretValue";
+  const CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+
+  pub struct AwsSecretsManagerProvider {
+    http: reqwest::Client,
+    endpoint: Url,
+    region: String,
+    secret_id: String,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(rename_all = "PascalCase")]
+  struct GetSecretValueResponse {
+    secret_string: Option<String>,
+  }
+
+  impl AwsSecretsManagerProvider {
+    pub fn from_config(config: &SecretsConfig) -> AppResult<Self> {
+      let endpoint = config
+        .aws_endpoint
+        .clone()
+        .unwrap_or_else(|| format!("https://secretsmanager.{}.amazonaws.com", config.aws_region));
+      Ok(Self {
+        http: reqwest::Client::new(),
+        endpoint: Url::parse(&endpoint).map_err(|e| internal_error!("Invalid AWS_SECRETS_MANAGER_ENDPOINT {}: {}", endpoint, e))?,
+        region: config.aws_region.clone(),
+        secret_id: config.aws_secret_id.clone().ok_or_else(|| internal_error!("AWS_SECRET_ID must be set"))?,
+        access_key_id: config
+          .aws_access_key_id
+          .clone()
+          .ok_or_else(|| internal_error!("AWS_ACCESS_KEY_ID must be set"))?,
+        secret_access_key: config
+          .aws_secret_access_key
+          .clone()
+          .ok_or_else(|| internal_error!("AWS_SECRET_ACCESS_KEY must be set"))?,
+        session_token: config.aws_session_token.clone(),
+      })
+    }
+
+    /// The `Authorization` header of a `GetSecretValue` request with `body`, signed at `now`.
+    fn authorization(&self, host: &str, body: &str, now: DateTime<Utc>) -> String {
+      let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+      let date = now.format("%Y%m%d").to_string();
+      let mut headers = vec![
+        ("content-type", CONTENT_TYPE.to_string()),
+        ("host", host.to_string()),
+        ("x-amz-date", amz_date.clone()),
+      ];
+      if let Some(token) = &self.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+      }
+      headers.push(("x-amz-target", TARGET.to_string()));
+      let signed_headers = match self.session_token {
+        Some(_) => "content-type;host;x-amz-date;x-amz-security-token;x-amz-target",
+        None => "content-type;host;x-amz-date;x-amz-target",
+      };
+      let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+      let canonical_request = format!(
+        "POST\n/\n\n{}\n{}\n{}",
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(body.as_bytes()))
+      );
+      let scope = format!("{}/{}/secretsmanager/aws4_request", date, self.region);
+      let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+      );
+
+      let key = [date.as_str(), self.region.as_str(), "secretsmanager", "aws4_request"]
+        .iter()
+        .fold(format!("AWS4{}", self.secret_access_key).into_bytes(), |key, part| hmac(&key, part));
+      format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        self.access_key_id,
+        scope,
+        signed_headers,
+        hex::encode(hmac(&key, &string_to_sign))
+      )
+    }
+  }
+
+  #[async_trait]
+  impl SecretsProvider for AwsSecretsManagerProvider {
+    async fn fetch(&self) -> AppResult<HashMap<String, String>> {
+      let body = json!({ "SecretId": self.secret_id }).to_string();
+      let host = match self.endpoint.port() {
+        Some(port) => format!("{}:{}", self.endpoint.host_str().unwrap_or_default(), port),
+        None => self.endpoint.host_str().unwrap_or_default().to_string(),
+      };
+      let now = Utc::now();
+      let authorization = self.authorization(&host, &body, now);
+
+      let mut request = self
+        .http
+        .post(self.endpoint.clone())
+        .header(reqwest::header::CONTENT_TYPE, CONTENT_TYPE)
+        .header("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string())
+        .header("x-amz-target", TARGET)
+        .header(reqwest::header::AUTHORIZATION, authorization);
+      if let Some(token) = &self.session_token {
+        request = request.header("x-amz-security-token", token);
+      }
+      let response = request
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| internal_error!("Secrets Manager request failed: {}", e))?;
+      let status = response.status();
+      if !status.is_success() {
+        let message = response.text().await.unwrap_or_default();
+        return Err(internal_error!("Secrets Manager responded {}: {}", status, message));
+      }
+
+      let secret: GetSecretValueResponse = response
+        .json()
+        .await
+        .map_err(|e| internal_error!("Unexpected Secrets Manager response: {}", e))?;
+      let secret_string = secret
+        .secret_string
+        .ok_or_else(|| internal_error!("Secret {} has no SecretString", self.secret_id))?;
+      serde_json::from_str(&secret_string).map_err(|e| internal_error!("Secret {} is not a JSON object of strings: {}", self.secret_id, e))
+    }
+  }
+
+  fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+  }
+}
@@ -0,0 +1,252 @@
+//! Demo and load-test data generator.
+//!
+//! Fills a workspace with fake but plausible contacts, suppliers, product categories and
+//! products. Passing a fixed `rng_seed` produces the same names, prices and stock levels on
+//! every run, which keeps demos and benchmark datasets reproducible.
+//!
+//! Rows are written directly with the pool's connection (bypassing RLS) inside a single
+//! transaction, so a failed run leaves the workspace untouched.
+
+use fake::{
+  Fake,
+  faker::{address::en::CityName, address::en::StreetName, company::en::CompanyName, internet::en::SafeEmail, job::en::Title, name::en::Name},
+};
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+use rust_decimal::Decimal;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::{AppResult, errors::AppError, utils::tenant_schema};
+
+const CONTACT_TYPES: &[&str] = &["customer", "customer", "customer", "employee", "salesman"];
+const CATEGORY_NAMES: &[&str] = &[
+  "Beverages",
+  "Snacks",
+  "Office Supplies",
+  "Cleaning",
+  "Electronics",
+  "Hardware",
+  "Packaging",
+  "Personal Care",
+  "Frozen Food",
+  "Stationery",
+];
+const PRODUCT_ADJECTIVES: &[&str] = &[
+  "Premium",
+  "Classic",
+  "Organic",
+  "Compact",
+  "Heavy Duty",
+  "Eco",
+  "Deluxe",
+  "Everyday",
+  "Pro",
+  "Mini",
+];
+const PRODUCT_NOUNS: &[&str] = &[
+  "Coffee Beans",
+  "Green Tea",
+  "Potato Chips",
+  "Notebook",
+  "Ballpoint Pen",
+  "Dish Soap",
+  "Floor Cleaner",
+  "USB Cable",
+  "Desk Lamp",
+  "Screwdriver Set",
+  "Cardboard Box",
+  "Shampoo",
+  "Frozen Dumplings",
+  "Stapler",
+  "Mineral Water",
+];
+const UNITS: &[&str] = &["pcs", "box", "pack", "kg", "liter", "dozen"];
+
+/// How much data to generate and where.
+#[derive(Debug, Clone)]
+pub struct SeedOptions {
+  pub workspace_id: Uuid,
+  /// User recorded as `created_by` on every generated row.
+  pub user_id: Uuid,
+  pub contacts: usize,
+  pub suppliers: usize,
+  pub categories: usize,
+  pub products: usize,
+  /// Fixed RNG seed for reproducible data; random when `None`.
+  pub rng_seed: Option<u64>,
+}
+
+/// Number of rows written by a seed run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SeedSummary {
+  pub contacts: usize,
+  pub suppliers: usize,
+  pub categories: usize,
+  pub products: usize,
+}
+
+/// Generates demo data in the workspace described by `options`.
+///
+/// Codes embed the first eight characters of the workspace ID and continue from rows
+/// seeded earlier, so the command can be run repeatedly against the same workspace.
+///
+/// # Arguments
+///
+/// * `pool` - The database pool to write with.
+/// * `options` - Target workspace, row counts and RNG seed.
+///
+/// # Returns
+///
+/// * `AppResult<SeedSummary>` - The number of rows created per entity.
+pub async fn seed_workspace(pool: &PgPool, options: &SeedOptions) -> AppResult<SeedSummary> {
+  let mut rng = match options.rng_seed {
+    Some(seed) => StdRng::seed_from_u64(seed),
+    None => StdRng::from_entropy(),
+  };
+  let tag = options.workspace_id.simple().to_string()[..8].to_uppercase();
+
+  let mut tx = pool.begin().await?;
+  // Into the workspace's own tables when it has a schema of its own
+  tenant_schema::route(&mut tx, Some(options.workspace_id)).await?;
+
+  let contact_offset = count_codes(&mut tx, "contacts", &format!("SC-{}-", tag)).await?;
+  let category_offset = count_codes(&mut tx, "product_categories", &format!("SG-{}-", tag)).await?;
+  let product_offset = count_codes(&mut tx, "products", &format!("SP-{}-", tag)).await?;
+
+  let mut next_contact = contact_offset;
+  for _ in 0..options.contacts {
+    next_contact += 1;
+    let contact_type = *CONTACT_TYPES.choose(&mut rng).unwrap_or(&"customer");
+    let name: String = Name().fake_with_rng(&mut rng);
+    insert_contact(
+      &mut tx,
+      options,
+      &format!("SC-{}-{:05}", tag, next_contact),
+      &name,
+      contact_type,
+      &mut rng,
+    )
+    .await?;
+  }
+
+  let mut supplier_ids = Vec::with_capacity(options.suppliers);
+  for _ in 0..options.suppliers {
+    next_contact += 1;
+    let name: String = CompanyName().fake_with_rng(&mut rng);
+    let id = insert_contact(&mut tx, options, &format!("SC-{}-{:05}", tag, next_contact), &name, "supplier", &mut rng).await?;
+    supplier_ids.push(id);
+  }
+
+  let mut category_ids = Vec::with_capacity(options.categories);
+  for i in 0..options.categories {
+    let base = CATEGORY_NAMES[i % CATEGORY_NAMES.len()];
+    let name = match i / CATEGORY_NAMES.len() {
+      0 => base.to_string(),
+      n => format!("{} {}", base, n + 1),
+    };
+    let id: Uuid = sqlx::query_scalar(
+      "INSERT INTO product_categories (code, name, description, workspace_id, created_by) VALUES ($1, $2, $3, $4, $5) RETURNING id",
+    )
+    .bind(format!("SG-{}-{:05}", tag, category_offset + i as i64 + 1))
+    .bind(&name)
+    .bind(format!("Demo category for {}", name.to_lowercase()))
+    .bind(options.workspace_id)
+    .bind(options.user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    category_ids.push(id);
+  }
+
+  // Barcodes must be unique too, so derive them from the workspace tag and product number
+  let barcode_prefix = u64::from_str_radix(&tag, 16).unwrap_or_default() % 10_000_000;
+
+  for i in 0..options.products {
+    let number = product_offset + i as i64 + 1;
+    let code = format!("SP-{}-{:05}", tag, number);
+    let name = format!(
+      "{} {}",
+      PRODUCT_ADJECTIVES.choose(&mut rng).unwrap_or(&"Classic"),
+      PRODUCT_NOUNS.choose(&mut rng).unwrap_or(&"Item")
+    );
+    let unit_cost = Decimal::new(rng.gen_range(500..500_000), 2);
+    let margin_percent = Decimal::from(rng.gen_range(10..80));
+    let selling_price = (unit_cost * (Decimal::ONE_HUNDRED + margin_percent) / Decimal::ONE_HUNDRED).round_dp(2);
+    let reorder_level: i32 = rng.gen_range(5..50);
+
+    sqlx::query(
+      "INSERT INTO products (code, name, category_id, base_unit, sku, barcode, description, supplier_id, track_inventory, \
+       minimum_stock, reorder_level, current_stock, unit_cost, selling_price, tax_rate, workspace_id, created_by) \
+       VALUES ($1, $2, $3, $4, $5, $6, $7, $8, true, $9, $10, $11, $12, $13, $14, $15, $16)",
+    )
+    .bind(&code)
+    .bind(&name)
+    .bind(category_ids.choose(&mut rng).copied())
+    .bind(*UNITS.choose(&mut rng).unwrap_or(&"pcs"))
+    .bind(format!("SKU-{}", code))
+    .bind(format!("2{:07}{:05}", barcode_prefix, number))
+    .bind(format!("{} for demo purposes", name))
+    .bind(supplier_ids.choose(&mut rng).copied())
+    .bind(reorder_level / 2)
+    .bind(reorder_level)
+    .bind(rng.gen_range(0..500))
+    .bind(unit_cost)
+    .bind(selling_price)
+    .bind(Decimal::from(*[0, 10, 11].choose(&mut rng).unwrap_or(&0)))
+    .bind(options.workspace_id)
+    .bind(options.user_id)
+    .execute(&mut *tx)
+    .await?;
+  }
+
+  tenant_schema::route(&mut tx, None).await?;
+  tx.commit().await?;
+
+  Ok(SeedSummary {
+    contacts: options.contacts,
+    suppliers: options.suppliers,
+    categories: options.categories,
+    products: options.products,
+  })
+}
+
+async fn insert_contact(
+  tx: &mut Transaction<'_, Postgres>,
+  options: &SeedOptions,
+  code: &str,
+  name: &str,
+  contact_type: &str,
+  rng: &mut StdRng,
+) -> AppResult<Uuid> {
+  let email: String = SafeEmail().fake_with_rng(rng);
+  let position: Option<String> = (contact_type != "supplier").then(|| Title().fake_with_rng(rng));
+  let street: String = StreetName().fake_with_rng(rng);
+  let city: String = CityName().fake_with_rng(rng);
+
+  let id = sqlx::query_scalar(
+    "INSERT INTO contacts (code, name, email, position, type, address, workspace_id, created_by) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id",
+  )
+  .bind(code)
+  .bind(name)
+  .bind(email)
+  .bind(position)
+  .bind(contact_type)
+  .bind(format!("{} {}, {}", rng.gen_range(1..999), street, city))
+  .bind(options.workspace_id)
+  .bind(options.user_id)
+  .fetch_one(&mut **tx)
+  .await?;
+
+  Ok(id)
+}
+
+/// Counts rows whose code starts with `prefix`, used to continue numbering across runs.
+async fn count_codes(tx: &mut Transaction<'_, Postgres>, table: &str, prefix: &str) -> AppResult<i64> {
+  if !matches!(table, "contacts" | "product_categories" | "products") {
+    return Err(AppError::Internal(format!("Unexpected seed table: {}", table)));
+  }
+  let count = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {} WHERE code LIKE $1 || '%'", table))
+    .bind(prefix)
+    .fetch_one(&mut **tx)
+    .await?;
+  Ok(count)
+}
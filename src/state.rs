@@ -1,7 +1,90 @@
-use crate::modules::auth::auth_repository::AuthRepository;
-use crate::modules::datastores::contacts::contact_repository::ContactRepository;
-use crate::modules::datastores::products::product_repository::ProductRepository;
-use crate::modules::datastores::workspaces::workspace_repository::WorkspaceRepository;
+use crate::config::AppConfig;
+use crate::events::EventBus;
+use crate::mailer::{LogMailer, Mailer};
+use crate::middleware::{IdempotencyStore, InMemoryIdempotencyStore, InMemoryRateLimitStore, RateLimitStore};
+use crate::modules::admin::{
+  admin_repository::{AdminRepository, PostgresAdminRepository},
+  request_stats::RequestStats,
+};
+#[cfg(any(feature = "contacts", feature = "products"))]
+use crate::modules::approvals::approval_repository::{ApprovalRepository, PostgresApprovalRepository};
+use crate::modules::audit::audit_repository::{AuditRepository, PostgresAuditRepository};
+use crate::modules::auth::auth_repository::{AuthRepository, AuthRepositoryImpl};
+use crate::modules::auth::jwt_keys::JwtKeys;
+use crate::modules::auth::password_reset_repository::{PasswordResetRepository, PostgresPasswordResetRepository};
+use crate::modules::auth::session_activity::{PostgresSessionActivityStore, SessionActivityStore};
+use crate::modules::auth::token_revocation::{PostgresTokenRevocationStore, TokenRevocationStore};
+#[cfg(feature = "backups")]
+use crate::modules::backups::backup_store::{BackupStore, S3Store};
+#[cfg(feature = "billing")]
+use crate::modules::billing::{
+  billing_repository::{BillingRepository, PostgresBillingRepository},
+  stripe::{StripeClient, StripeGateway},
+};
+#[cfg(feature = "connectors")]
+use crate::modules::connectors::{
+  connector_repository::{ConnectorRepository, PostgresConnectorRepository},
+  store_connector::{ConnectorFactory, HttpConnectorFactory},
+};
+#[cfg(feature = "currencies")]
+use crate::modules::currencies::{
+  exchange_rate_repository::{ExchangeRateRepository, PostgresExchangeRateRepository},
+  rate_provider::{self, RateProvider},
+};
+#[cfg(feature = "contacts")]
+use crate::modules::datastores::contacts::contact_repository::{ContactRepository, SqlxContactRepository};
+#[cfg(feature = "products")]
+use crate::modules::datastores::products::product_repository::{ProductRepository, SqlxProductRepository};
+use crate::modules::datastores::workspaces::workspace_repository::{PostgresWorkspaceRepository, WorkspaceRepository};
+use crate::modules::datastores::workspaces::workspace_role_cache::WorkspaceRoleCache;
+#[cfg(feature = "email_templates")]
+use crate::modules::email_templates::email_template_repository::{EmailTemplateRepository, PostgresEmailTemplateRepository};
+#[cfg(feature = "exports")]
+use crate::modules::exports::export_repository::{ExportProfileRepository, PostgresExportProfileRepository};
+use crate::modules::feature_flags::{
+  feature_flag_cache::FeatureFlagCache,
+  feature_flag_repository::{FeatureFlagRepository, PostgresFeatureFlagRepository},
+};
+#[cfg(feature = "geo")]
+use crate::modules::geo::{
+  geo_cache::GeoCache,
+  geo_repository::{GeoLookupRepository, PostgresGeoLookupRepository},
+  geocoder::{self, Geocoder},
+};
+#[cfg(feature = "inbound")]
+use crate::modules::inbound::inbound_repository::{InboundRepository, PostgresInboundRepository};
+#[cfg(any(feature = "contacts", feature = "products"))]
+use crate::modules::operations::operation_repository::{OperationRepository, PostgresOperationRepository};
+use crate::modules::organizations::organization_repository::{OrganizationRepository, PostgresOrganizationRepository};
+use crate::modules::overview::overview_repository::{OverviewRepository, PostgresOverviewRepository};
+#[cfg(any(feature = "contacts", feature = "products"))]
+use crate::modules::record_locks::record_lock_repository::{PostgresRecordLockRepository, RecordLockRepository};
+#[cfg(feature = "rendering")]
+use crate::modules::rendering::{
+  pdf_renderer::{ChromiumRenderer, PdfRenderer},
+  print_template_repository::{PostgresPrintTemplateRepository, PrintTemplateRepository},
+};
+#[cfg(feature = "reports")]
+use crate::modules::reports::{
+  report_repository::{PostgresReportScheduleRepository, ReportScheduleRepository},
+  report_webhook::{HttpWebhookSender, WebhookSender},
+};
+use crate::modules::retention::retention_repository::{PostgresRetentionRepository, RetentionRepository};
+#[cfg(any(feature = "contacts", feature = "products"))]
+use crate::modules::search::search_repository::{PostgresSearchRepository, SearchRepository};
+#[cfg(feature = "contacts")]
+use crate::modules::statements::statement_repository::{PostgresStatementRepository, StatementRepository};
+use crate::modules::trial::trial_repository::{PostgresTrialRepository, TrialRepository};
+use crate::modules::usage::{
+  usage_meter::UsageMeter,
+  usage_repository::{PostgresUsageRepository, UsageRepository},
+};
+use crate::modules::user_export::user_export_repository::{PostgresUserExportRepository, UserExportRepository};
+use crate::modules::workspace_settings::{
+  workspace_settings_cache::WorkspaceSettingsCache,
+  workspace_settings_repository::{PostgresWorkspaceSettingsRepository, WorkspaceSettingsRepository},
+};
+use crate::utils::{ReadPool, TaskHealth, db_resilience, field_encryption::FieldCipher};
 use sqlx::PgPool;
 use std::sync::Arc;
 
@@ -12,18 +95,753 @@ use std::sync::Arc;
 ///
 /// # Fields
 ///
-/// * `db`: A `PgPool` for asynchronous connections to the PostgreSQL database.
+/// * `db`: A `PgPool` for asynchronous connections to the PostgreSQL database. Queries made while
+///   handling a request go through `utils::db_session::acquire` to run on the request's connection.
+/// * `db_read`: The pool for read-only queries, backed by the read replica when one is configured.
 /// * `contact_repository`: An `Arc` wrapped trait object for the contact repository.
 ///   This allows for dependency injection and easy mocking in tests. `Send` and `Sync` are
-///   required to share the repository safely across threads.
+///   required to share the repository safely across threads. Only with the `contacts` feature.
+/// * `product_repository`: The product repository, only with the `products` feature.
 /// * `auth_repository`: An `Arc` wrapped trait object for the auth repository.
-/// * `jwt_secret`: The secret key used for signing JWTs.
+/// * `jwt_keys`: The secret signing JWTs, and the one replaced by the last key rotation, still accepted when verifying tokens.
+/// * `config`: Runtime settings loaded from the environment.
+/// * `rate_limiter`: The store backing the request rate limiter.
+/// * `events`: The bus carrying real-time workspace events to WebSocket clients.
+/// * `token_revocations`: The access tokens revoked by logging out or by a password reset.
+/// * `session_activity`: When each access token was last used, for the session idle timeout.
+/// * `idempotency_store`: The store replaying responses of retried `Idempotency-Key` requests.
+/// * `role_cache`: Workspace roles recently checked by `jwt_middleware` and the gRPC services.
+/// * `usage_repository`: The metered API usage of each workspace.
+/// * `usage_meter`: Usage counters not written to `usage_repository` yet, and the monthly totals checked against quotas.
+/// * `trial_repository`: The trial period of each workspace.
+/// * `feature_flag_repository`: Feature flags and their workspace overrides.
+/// * `feature_flag_cache`: Flags recently resolved for a workspace by `RequireFeature`.
+/// * `retention_repository`: The data retention policies of each workspace and the purges they caused.
+/// * `workspace_settings_repository`: The settings of each workspace, such as its IP allowlist.
+/// * `workspace_settings_cache`: Settings recently loaded for the IP allowlist check.
+/// * `audit_repository`: The audit log of each workspace.
+/// * `admin_repository`: Platform-wide statistics for the superadmins.
+/// * `overview_repository`: The key numbers of the workspaces of a user, for `GET /api/v1/overview`.
+/// * `organization_repository`: The organizations grouping workspaces, their members and billing workspace.
+/// * `request_stats`: Responses of the last hour by outcome, counted by the access log.
+/// * `task_health`: The outcome of the recent runs of each background task.
+/// * `user_export_repository`: The personal data exports requested by users.
+/// * `password_reset_repository`: The tokens emailed to users who forgot their password.
+/// * `mailer`: Sends emails; logs them unless replaced with `AppStateBuilder::with_mailer`.
+/// * `field_cipher`: The keys of the encrypted columns, also used by the default contact repository.
+/// * `billing_repository`: The Stripe customer and subscription of each workspace, only with the `billing` feature.
+/// * `stripe`: The Stripe API, `None` while billing is not configured. Only with the `billing` feature.
+/// * `backup_store`: Where database backups are kept, `None` while backups are not configured. Only with the `backups` feature.
+/// * `report_schedule_repository`: The report schedules of each workspace, only with the `reports` feature.
+/// * `webhook_sender`: Posts scheduled reports to their webhooks, only with the `reports` feature.
+/// * `email_template_repository`: The email templates of each workspace, only with the `email_templates` feature.
+/// * `pdf_renderer`: Prints HTML to PDF, `None` while no browser is configured. Only with the `rendering` feature.
+/// * `print_template_repository`: The print templates of each workspace, only with the `rendering` feature.
+/// * `export_profile_repository`: The export profiles saved by each member, only with the `exports` feature.
+/// * `inbound_repository`: The inbound integrations of each workspace and their queued events, only with the `inbound` feature.
+/// * `connector_repository`: The connectors of each workspace to online stores and the orders pulled from them, only with the `connectors` feature.
+/// * `connector_factory`: Connects to the stores of the connectors, only with the `connectors` feature.
+/// * `exchange_rate_repository`: The fetched exchange rates and those set by each workspace, only with the `currencies` feature.
+/// * `rate_provider`: Fetches the daily exchange rates, `None` while the provider has no credentials. Only with the `currencies` feature.
+/// * `geocoder`: Suggests addresses, `None` while the provider has no credentials. Only with the `geo` feature.
+/// * `geo_cache`: Suggestions recently fetched from the geocoder, only with the `geo` feature.
+/// * `geo_lookup_repository`: The daily lookups of each workspace counted against its quota, only with the `geo` feature.
+/// * `approval_repository`: The changes of each workspace waiting for an admin, only with the `contacts` or `products` feature.
+/// * `record_lock_repository`: The locks on the records being edited, only with the `contacts` or `products` feature.
+/// * `operation_repository`: The requests running in the background, only with the `contacts` or `products` feature.
+/// * `search_repository`: Searches the records of each workspace, only with the `contacts` or `products` feature.
+/// * `statement_repository`: The invoices, payments and credit notes of contacts, only with the `contacts` feature.
 #[derive(Clone)]
 pub struct AppState {
   pub db: PgPool,
+  pub db_read: ReadPool,
+  #[cfg(feature = "contacts")]
   pub contact_repository: Arc<dyn ContactRepository + Send + Sync>,
+  #[cfg(feature = "products")]
   pub product_repository: Arc<dyn ProductRepository + Send + Sync>,
   pub auth_repository: Arc<dyn AuthRepository + Send + Sync>,
   pub workspace_repository: Arc<dyn WorkspaceRepository + Send + Sync>,
-  pub jwt_secret: String,
+  pub jwt_keys: Arc<JwtKeys>,
+  pub config: AppConfig,
+  pub rate_limiter: Arc<dyn RateLimitStore>,
+  pub events: Arc<EventBus>,
+  pub token_revocations: Arc<dyn TokenRevocationStore>,
+  pub session_activity: Arc<dyn SessionActivityStore>,
+  pub idempotency_store: Arc<dyn IdempotencyStore>,
+  pub role_cache: Arc<WorkspaceRoleCache>,
+  pub usage_repository: Arc<dyn UsageRepository + Send + Sync>,
+  pub usage_meter: Arc<UsageMeter>,
+  pub trial_repository: Arc<dyn TrialRepository + Send + Sync>,
+  pub feature_flag_repository: Arc<dyn FeatureFlagRepository + Send + Sync>,
+  pub feature_flag_cache: Arc<FeatureFlagCache>,
+  pub retention_repository: Arc<dyn RetentionRepository + Send + Sync>,
+  pub workspace_settings_repository: Arc<dyn WorkspaceSettingsRepository + Send + Sync>,
+  pub workspace_settings_cache: Arc<WorkspaceSettingsCache>,
+  pub audit_repository: Arc<dyn AuditRepository + Send + Sync>,
+  pub admin_repository: Arc<dyn AdminRepository + Send + Sync>,
+  pub overview_repository: Arc<dyn OverviewRepository + Send + Sync>,
+  pub organization_repository: Arc<dyn OrganizationRepository + Send + Sync>,
+  pub request_stats: Arc<RequestStats>,
+  pub task_health: Arc<TaskHealth>,
+  pub user_export_repository: Arc<dyn UserExportRepository + Send + Sync>,
+  pub password_reset_repository: Arc<dyn PasswordResetRepository + Send + Sync>,
+  pub mailer: Arc<dyn Mailer>,
+  pub field_cipher: Arc<FieldCipher>,
+  #[cfg(feature = "billing")]
+  pub billing_repository: Arc<dyn BillingRepository + Send + Sync>,
+  #[cfg(feature = "billing")]
+  pub stripe: Option<Arc<dyn StripeGateway>>,
+  #[cfg(feature = "backups")]
+  pub backup_store: Option<Arc<dyn BackupStore>>,
+  #[cfg(feature = "reports")]
+  pub report_schedule_repository: Arc<dyn ReportScheduleRepository + Send + Sync>,
+  #[cfg(feature = "reports")]
+  pub webhook_sender: Arc<dyn WebhookSender>,
+  #[cfg(feature = "email_templates")]
+  pub email_template_repository: Arc<dyn EmailTemplateRepository + Send + Sync>,
+  #[cfg(feature = "rendering")]
+  pub pdf_renderer: Option<Arc<dyn PdfRenderer>>,
+  #[cfg(feature = "rendering")]
+  pub print_template_repository: Arc<dyn PrintTemplateRepository + Send + Sync>,
+  #[cfg(feature = "exports")]
+  pub export_profile_repository: Arc<dyn ExportProfileRepository + Send + Sync>,
+  #[cfg(feature = "inbound")]
+  pub inbound_repository: Arc<dyn InboundRepository + Send + Sync>,
+  #[cfg(feature = "connectors")]
+  pub connector_repository: Arc<dyn ConnectorRepository + Send + Sync>,
+  #[cfg(feature = "connectors")]
+  pub connector_factory: Arc<dyn ConnectorFactory>,
+  #[cfg(feature = "currencies")]
+  pub exchange_rate_repository: Arc<dyn ExchangeRateRepository + Send + Sync>,
+  #[cfg(feature = "currencies")]
+  pub rate_provider: Option<Arc<dyn RateProvider>>,
+  #[cfg(feature = "geo")]
+  pub geocoder: Option<Arc<dyn Geocoder>>,
+  #[cfg(feature = "geo")]
+  pub geo_cache: Arc<GeoCache>,
+  #[cfg(feature = "geo")]
+  pub geo_lookup_repository: Arc<dyn GeoLookupRepository + Send + Sync>,
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  pub approval_repository: Arc<dyn ApprovalRepository + Send + Sync>,
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  pub record_lock_repository: Arc<dyn RecordLockRepository + Send + Sync>,
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  pub operation_repository: Arc<dyn OperationRepository + Send + Sync>,
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  pub search_repository: Arc<dyn SearchRepository + Send + Sync>,
+  #[cfg(feature = "contacts")]
+  pub statement_repository: Arc<dyn StatementRepository + Send + Sync>,
+}
+
+impl AppState {
+  /// Starts building a state on `db`, signing tokens with `jwt_secret`. Everything else defaults
+  /// to what `setup_state()` uses without Redis or a read replica, and can be replaced, e.g. by
+  /// mock repositories in tests or by an application embedding this crate.
+  pub fn builder(db: PgPool, jwt_secret: impl Into<String>) -> AppStateBuilder {
+    AppStateBuilder {
+      db,
+      jwt_secret: jwt_secret.into(),
+      jwt_previous_secret: None,
+      config: AppConfig::default(),
+      db_read: None,
+      #[cfg(feature = "contacts")]
+      contact_repository: None,
+      #[cfg(feature = "products")]
+      product_repository: None,
+      auth_repository: None,
+      workspace_repository: None,
+      rate_limiter: None,
+      events: None,
+      token_revocations: None,
+      session_activity: None,
+      idempotency_store: None,
+      usage_repository: None,
+      trial_repository: None,
+      feature_flag_repository: None,
+      retention_repository: None,
+      workspace_settings_repository: None,
+      audit_repository: None,
+      admin_repository: None,
+      overview_repository: None,
+      organization_repository: None,
+      user_export_repository: None,
+      password_reset_repository: None,
+      mailer: None,
+      field_cipher: None,
+      #[cfg(feature = "billing")]
+      billing_repository: None,
+      #[cfg(feature = "billing")]
+      stripe: None,
+      #[cfg(feature = "backups")]
+      backup_store: None,
+      #[cfg(feature = "reports")]
+      report_schedule_repository: None,
+      #[cfg(feature = "reports")]
+      webhook_sender: None,
+      #[cfg(feature = "email_templates")]
+      email_template_repository: None,
+      #[cfg(feature = "rendering")]
+      pdf_renderer: None,
+      #[cfg(feature = "rendering")]
+      print_template_repository: None,
+      #[cfg(feature = "exports")]
+      export_profile_repository: None,
+      #[cfg(feature = "inbound")]
+      inbound_repository: None,
+      #[cfg(feature = "connectors")]
+      connector_repository: None,
+      #[cfg(feature = "connectors")]
+      connector_factory: None,
+      #[cfg(feature = "currencies")]
+      exchange_rate_repository: None,
+      #[cfg(feature = "currencies")]
+      rate_provider: None,
+      #[cfg(feature = "geo")]
+      geocoder: None,
+      #[cfg(feature = "geo")]
+      geo_lookup_repository: None,
+      #[cfg(any(feature = "contacts", feature = "products"))]
+      approval_repository: None,
+      #[cfg(any(feature = "contacts", feature = "products"))]
+      record_lock_repository: None,
+      #[cfg(any(feature = "contacts", feature = "products"))]
+      operation_repository: None,
+      #[cfg(any(feature = "contacts", feature = "products"))]
+      search_repository: None,
+      #[cfg(feature = "contacts")]
+      statement_repository: None,
+    }
+  }
+}
+
+/// Builds an `AppState` from explicit parts instead of the environment, see `AppState::builder`.
+pub struct AppStateBuilder {
+  db: PgPool,
+  jwt_secret: String,
+  jwt_previous_secret: Option<String>,
+  config: AppConfig,
+  db_read: Option<ReadPool>,
+  #[cfg(feature = "contacts")]
+  contact_repository: Option<Arc<dyn ContactRepository + Send + Sync>>,
+  #[cfg(feature = "products")]
+  product_repository: Option<Arc<dyn ProductRepository + Send + Sync>>,
+  auth_repository: Option<Arc<dyn AuthRepository + Send + Sync>>,
+  workspace_repository: Option<Arc<dyn WorkspaceRepository + Send + Sync>>,
+  rate_limiter: Option<Arc<dyn RateLimitStore>>,
+  events: Option<Arc<EventBus>>,
+  token_revocations: Option<Arc<dyn TokenRevocationStore>>,
+  session_activity: Option<Arc<dyn SessionActivityStore>>,
+  idempotency_store: Option<Arc<dyn IdempotencyStore>>,
+  usage_repository: Option<Arc<dyn UsageRepository + Send + Sync>>,
+  trial_repository: Option<Arc<dyn TrialRepository + Send + Sync>>,
+  feature_flag_repository: Option<Arc<dyn FeatureFlagRepository + Send + Sync>>,
+  retention_repository: Option<Arc<dyn RetentionRepository + Send + Sync>>,
+  workspace_settings_repository: Option<Arc<dyn WorkspaceSettingsRepository + Send + Sync>>,
+  audit_repository: Option<Arc<dyn AuditRepository + Send + Sync>>,
+  admin_repository: Option<Arc<dyn AdminRepository + Send + Sync>>,
+  overview_repository: Option<Arc<dyn OverviewRepository + Send + Sync>>,
+  organization_repository: Option<Arc<dyn OrganizationRepository + Send + Sync>>,
+  user_export_repository: Option<Arc<dyn UserExportRepository + Send + Sync>>,
+  password_reset_repository: Option<Arc<dyn PasswordResetRepository + Send + Sync>>,
+  mailer: Option<Arc<dyn Mailer>>,
+  field_cipher: Option<Arc<FieldCipher>>,
+  #[cfg(feature = "billing")]
+  billing_repository: Option<Arc<dyn BillingRepository + Send + Sync>>,
+  #[cfg(feature = "billing")]
+  stripe: Option<Arc<dyn StripeGateway>>,
+  #[cfg(feature = "backups")]
+  backup_store: Option<Arc<dyn BackupStore>>,
+  #[cfg(feature = "reports")]
+  report_schedule_repository: Option<Arc<dyn ReportScheduleRepository + Send + Sync>>,
+  #[cfg(feature = "reports")]
+  webhook_sender: Option<Arc<dyn WebhookSender>>,
+  #[cfg(feature = "email_templates")]
+  email_template_repository: Option<Arc<dyn EmailTemplateRepository + Send + Sync>>,
+  #[cfg(feature = "rendering")]
+  pdf_renderer: Option<Arc<dyn PdfRenderer>>,
+  #[cfg(feature = "rendering")]
+  print_template_repository: Option<Arc<dyn PrintTemplateRepository + Send + Sync>>,
+  #[cfg(feature = "exports")]
+  export_profile_repository: Option<Arc<dyn ExportProfileRepository + Send + Sync>>,
+  #[cfg(feature = "inbound")]
+  inbound_repository: Option<Arc<dyn InboundRepository + Send + Sync>>,
+  #[cfg(feature = "connectors")]
+  connector_repository: Option<Arc<dyn ConnectorRepository + Send + Sync>>,
+  #[cfg(feature = "connectors")]
+  connector_factory: Option<Arc<dyn ConnectorFactory>>,
+  #[cfg(feature = "currencies")]
+  exchange_rate_repository: Option<Arc<dyn ExchangeRateRepository + Send + Sync>>,
+  #[cfg(feature = "currencies")]
+  rate_provider: Option<Arc<dyn RateProvider>>,
+  #[cfg(feature = "geo")]
+  geocoder: Option<Arc<dyn Geocoder>>,
+  #[cfg(feature = "geo")]
+  geo_lookup_repository: Option<Arc<dyn GeoLookupRepository + Send + Sync>>,
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  approval_repository: Option<Arc<dyn ApprovalRepository + Send + Sync>>,
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  record_lock_repository: Option<Arc<dyn RecordLockRepository + Send + Sync>>,
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  operation_repository: Option<Arc<dyn OperationRepository + Send + Sync>>,
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  search_repository: Option<Arc<dyn SearchRepository + Send + Sync>>,
+  #[cfg(feature = "contacts")]
+  statement_repository: Option<Arc<dyn StatementRepository + Send + Sync>>,
+}
+
+impl AppStateBuilder {
+  /// Settings to use instead of the defaults; nothing is read from the environment.
+  pub fn with_config(mut self, config: AppConfig) -> Self {
+    self.config = config;
+    self
+  }
+
+  /// A secret replaced by a key rotation, still accepted when verifying tokens.
+  pub fn with_jwt_previous_secret(mut self, secret: impl Into<String>) -> Self {
+    self.jwt_previous_secret = Some(secret.into());
+    self
+  }
+
+  /// The pool for read-only queries; defaults to the primary. Also used by the default repositories.
+  pub fn with_read_pool(mut self, db_read: ReadPool) -> Self {
+    self.db_read = Some(db_read);
+    self
+  }
+
+  #[cfg(feature = "contacts")]
+  pub fn with_contact_repository(mut self, repository: Arc<dyn ContactRepository + Send + Sync>) -> Self {
+    self.contact_repository = Some(repository);
+    self
+  }
+
+  #[cfg(feature = "products")]
+  pub fn with_product_repository(mut self, repository: Arc<dyn ProductRepository + Send + Sync>) -> Self {
+    self.product_repository = Some(repository);
+    self
+  }
+
+  pub fn with_auth_repository(mut self, repository: Arc<dyn AuthRepository + Send + Sync>) -> Self {
+    self.auth_repository = Some(repository);
+    self
+  }
+
+  pub fn with_workspace_repository(mut self, repository: Arc<dyn WorkspaceRepository + Send + Sync>) -> Self {
+    self.workspace_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to a per-process in-memory store.
+  pub fn with_rate_limiter(mut self, rate_limiter: Arc<dyn RateLimitStore>) -> Self {
+    self.rate_limiter = Some(rate_limiter);
+    self
+  }
+
+  /// Defaults to a new bus sized by `config.realtime`.
+  pub fn with_events(mut self, events: Arc<EventBus>) -> Self {
+    self.events = Some(events);
+    self
+  }
+
+  /// Defaults to the `revoked_tokens` table.
+  pub fn with_token_revocations(mut self, token_revocations: Arc<dyn TokenRevocationStore>) -> Self {
+    self.token_revocations = Some(token_revocations);
+    self
+  }
+
+  /// Defaults to the `session_activity` table.
+  pub fn with_session_activity(mut self, session_activity: Arc<dyn SessionActivityStore>) -> Self {
+    self.session_activity = Some(session_activity);
+    self
+  }
+
+  /// Defaults to a per-process in-memory store.
+  pub fn with_idempotency_store(mut self, idempotency_store: Arc<dyn IdempotencyStore>) -> Self {
+    self.idempotency_store = Some(idempotency_store);
+    self
+  }
+
+  pub fn with_usage_repository(mut self, repository: Arc<dyn UsageRepository + Send + Sync>) -> Self {
+    self.usage_repository = Some(repository);
+    self
+  }
+
+  pub fn with_trial_repository(mut self, repository: Arc<dyn TrialRepository + Send + Sync>) -> Self {
+    self.trial_repository = Some(repository);
+    self
+  }
+
+  pub fn with_feature_flag_repository(mut self, repository: Arc<dyn FeatureFlagRepository + Send + Sync>) -> Self {
+    self.feature_flag_repository = Some(repository);
+    self
+  }
+
+  pub fn with_retention_repository(mut self, repository: Arc<dyn RetentionRepository + Send + Sync>) -> Self {
+    self.retention_repository = Some(repository);
+    self
+  }
+
+  pub fn with_workspace_settings_repository(mut self, repository: Arc<dyn WorkspaceSettingsRepository + Send + Sync>) -> Self {
+    self.workspace_settings_repository = Some(repository);
+    self
+  }
+
+  pub fn with_audit_repository(mut self, repository: Arc<dyn AuditRepository + Send + Sync>) -> Self {
+    self.audit_repository = Some(repository);
+    self
+  }
+
+  pub fn with_admin_repository(mut self, repository: Arc<dyn AdminRepository + Send + Sync>) -> Self {
+    self.admin_repository = Some(repository);
+    self
+  }
+
+  pub fn with_overview_repository(mut self, repository: Arc<dyn OverviewRepository + Send + Sync>) -> Self {
+    self.overview_repository = Some(repository);
+    self
+  }
+
+  pub fn with_organization_repository(mut self, repository: Arc<dyn OrganizationRepository + Send + Sync>) -> Self {
+    self.organization_repository = Some(repository);
+    self
+  }
+
+  pub fn with_user_export_repository(mut self, repository: Arc<dyn UserExportRepository + Send + Sync>) -> Self {
+    self.user_export_repository = Some(repository);
+    self
+  }
+
+  pub fn with_password_reset_repository(mut self, repository: Arc<dyn PasswordResetRepository + Send + Sync>) -> Self {
+    self.password_reset_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to a `LogMailer`, which only logs the emails.
+  pub fn with_mailer(mut self, mailer: Arc<dyn Mailer>) -> Self {
+    self.mailer = Some(mailer);
+    self
+  }
+
+  /// Defaults to the keys of `config.encryption`.
+  pub fn with_field_cipher(mut self, cipher: Arc<FieldCipher>) -> Self {
+    self.field_cipher = Some(cipher);
+    self
+  }
+
+  #[cfg(feature = "billing")]
+  pub fn with_billing_repository(mut self, repository: Arc<dyn BillingRepository + Send + Sync>) -> Self {
+    self.billing_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to a `StripeClient` once `config.billing` has a secret key and price.
+  #[cfg(feature = "billing")]
+  pub fn with_stripe_gateway(mut self, stripe: Arc<dyn StripeGateway>) -> Self {
+    self.stripe = Some(stripe);
+    self
+  }
+
+  /// Defaults to an `S3Store` once `config.backups` has a bucket and access keys.
+  #[cfg(feature = "backups")]
+  pub fn with_backup_store(mut self, store: Arc<dyn BackupStore>) -> Self {
+    self.backup_store = Some(store);
+    self
+  }
+
+  #[cfg(feature = "reports")]
+  pub fn with_report_schedule_repository(mut self, repository: Arc<dyn ReportScheduleRepository + Send + Sync>) -> Self {
+    self.report_schedule_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to an `HttpWebhookSender` timing out after `config.reports.webhook_timeout_secs`.
+  #[cfg(feature = "reports")]
+  pub fn with_webhook_sender(mut self, sender: Arc<dyn WebhookSender>) -> Self {
+    self.webhook_sender = Some(sender);
+    self
+  }
+
+  #[cfg(feature = "email_templates")]
+  pub fn with_email_template_repository(mut self, repository: Arc<dyn EmailTemplateRepository + Send + Sync>) -> Self {
+    self.email_template_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to a `ChromiumRenderer` once `config.rendering` names a browser.
+  #[cfg(feature = "rendering")]
+  pub fn with_pdf_renderer(mut self, renderer: Arc<dyn PdfRenderer>) -> Self {
+    self.pdf_renderer = Some(renderer);
+    self
+  }
+
+  #[cfg(feature = "rendering")]
+  pub fn with_print_template_repository(mut self, repository: Arc<dyn PrintTemplateRepository + Send + Sync>) -> Self {
+    self.print_template_repository = Some(repository);
+    self
+  }
+
+  #[cfg(feature = "exports")]
+  pub fn with_export_profile_repository(mut self, repository: Arc<dyn ExportProfileRepository + Send + Sync>) -> Self {
+    self.export_profile_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to a `PostgresInboundRepository` encrypting secrets with the field cipher.
+  #[cfg(feature = "inbound")]
+  pub fn with_inbound_repository(mut self, repository: Arc<dyn InboundRepository + Send + Sync>) -> Self {
+    self.inbound_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to a `PostgresConnectorRepository` encrypting credentials with the field cipher.
+  #[cfg(feature = "connectors")]
+  pub fn with_connector_repository(mut self, repository: Arc<dyn ConnectorRepository + Send + Sync>) -> Self {
+    self.connector_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to an `HttpConnectorFactory` timing out after `config.connectors.request_timeout_secs`.
+  #[cfg(feature = "connectors")]
+  pub fn with_connector_factory(mut self, factory: Arc<dyn ConnectorFactory>) -> Self {
+    self.connector_factory = Some(factory);
+    self
+  }
+
+  /// Defaults to a `PostgresExchangeRateRepository`.
+  #[cfg(feature = "currencies")]
+  pub fn with_exchange_rate_repository(mut self, repository: Arc<dyn ExchangeRateRepository + Send + Sync>) -> Self {
+    self.exchange_rate_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to the provider of `config.exchange_rates` once it has its credentials.
+  #[cfg(feature = "currencies")]
+  pub fn with_rate_provider(mut self, provider: Arc<dyn RateProvider>) -> Self {
+    self.rate_provider = Some(provider);
+    self
+  }
+
+  /// Defaults to the geocoder of `config.geo` once it has its credentials.
+  #[cfg(feature = "geo")]
+  pub fn with_geocoder(mut self, geocoder: Arc<dyn Geocoder>) -> Self {
+    self.geocoder = Some(geocoder);
+    self
+  }
+
+  /// Defaults to a `PostgresGeoLookupRepository`.
+  #[cfg(feature = "geo")]
+  pub fn with_geo_lookup_repository(mut self, repository: Arc<dyn GeoLookupRepository + Send + Sync>) -> Self {
+    self.geo_lookup_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to a `PostgresApprovalRepository`.
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  pub fn with_approval_repository(mut self, repository: Arc<dyn ApprovalRepository + Send + Sync>) -> Self {
+    self.approval_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to a `PostgresRecordLockRepository`.
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  pub fn with_record_lock_repository(mut self, repository: Arc<dyn RecordLockRepository + Send + Sync>) -> Self {
+    self.record_lock_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to a `PostgresOperationRepository`.
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  pub fn with_operation_repository(mut self, repository: Arc<dyn OperationRepository + Send + Sync>) -> Self {
+    self.operation_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to a `PostgresSearchRepository`.
+  #[cfg(any(feature = "contacts", feature = "products"))]
+  pub fn with_search_repository(mut self, repository: Arc<dyn SearchRepository + Send + Sync>) -> Self {
+    self.search_repository = Some(repository);
+    self
+  }
+
+  /// Defaults to a `PostgresStatementRepository`.
+  #[cfg(feature = "contacts")]
+  pub fn with_statement_repository(mut self, repository: Arc<dyn StatementRepository + Send + Sync>) -> Self {
+    self.statement_repository = Some(repository);
+    self
+  }
+
+  /// Assembles the state. The first state built in a process also installs its database retry
+  /// and circuit breaker settings (see `utils::db_resilience`).
+  ///
+  /// Panics when no field cipher was given and the keys of `config.encryption` are invalid.
+  pub fn build(self) -> Arc<AppState> {
+    let config = self.config;
+    db_resilience::install(&config.db_resilience);
+
+    let db = self.db;
+    let db_read = self.db_read.unwrap_or_else(|| ReadPool::primary_only(db.clone()));
+    let field_cipher = self
+      .field_cipher
+      .unwrap_or_else(|| Arc::new(FieldCipher::from_config(&config.encryption).expect("Invalid FIELD_ENCRYPTION_KEYS")));
+
+    Arc::new(AppState {
+      #[cfg(feature = "contacts")]
+      contact_repository: self.contact_repository.unwrap_or_else(|| {
+        Arc::new(
+          SqlxContactRepository::new(db.clone())
+            .with_read_pool(db_read.clone())
+            .with_cipher(field_cipher.clone()),
+        )
+      }),
+      #[cfg(feature = "products")]
+      product_repository: self
+        .product_repository
+        .unwrap_or_else(|| Arc::new(SqlxProductRepository::new(db.clone()).with_read_pool(db_read.clone()))),
+      auth_repository: self
+        .auth_repository
+        .unwrap_or_else(|| Arc::new(AuthRepositoryImpl::new(db.clone()).with_read_pool(db_read.clone()))),
+      workspace_repository: self
+        .workspace_repository
+        .unwrap_or_else(|| Arc::new(PostgresWorkspaceRepository::new(db.clone()).with_read_pool(db_read.clone()))),
+      rate_limiter: self.rate_limiter.unwrap_or_else(|| Arc::new(InMemoryRateLimitStore::new())),
+      events: self.events.unwrap_or_else(|| Arc::new(EventBus::new(config.realtime.event_bus_capacity))),
+      token_revocations: self
+        .token_revocations
+        .unwrap_or_else(|| Arc::new(PostgresTokenRevocationStore::new(db.clone()))),
+      session_activity: self
+        .session_activity
+        .unwrap_or_else(|| Arc::new(PostgresSessionActivityStore::new(db.clone()))),
+      idempotency_store: self.idempotency_store.unwrap_or_else(|| Arc::new(InMemoryIdempotencyStore::new())),
+      role_cache: Arc::new(WorkspaceRoleCache::new(config.role_cache.ttl_secs)),
+      usage_repository: self
+        .usage_repository
+        .unwrap_or_else(|| Arc::new(PostgresUsageRepository::new(db.clone()).with_read_pool(db_read.clone()))),
+      usage_meter: Arc::new(UsageMeter::new()),
+      trial_repository: self
+        .trial_repository
+        .unwrap_or_else(|| Arc::new(PostgresTrialRepository::new(db.clone()))),
+      feature_flag_repository: self
+        .feature_flag_repository
+        .unwrap_or_else(|| Arc::new(PostgresFeatureFlagRepository::new(db.clone()))),
+      feature_flag_cache: Arc::new(FeatureFlagCache::new(config.feature_flags.cache_ttl_secs)),
+      retention_repository: self
+        .retention_repository
+        .unwrap_or_else(|| Arc::new(PostgresRetentionRepository::new(db.clone()))),
+      workspace_settings_repository: self
+        .workspace_settings_repository
+        .unwrap_or_else(|| Arc::new(PostgresWorkspaceSettingsRepository::new(db.clone()))),
+      workspace_settings_cache: Arc::new(WorkspaceSettingsCache::new(config.workspace_settings.cache_ttl_secs)),
+      audit_repository: self
+        .audit_repository
+        .unwrap_or_else(|| Arc::new(PostgresAuditRepository::new(db.clone()))),
+      admin_repository: self
+        .admin_repository
+        .unwrap_or_else(|| Arc::new(PostgresAdminRepository::new(db.clone()).with_read_pool(db_read.clone()))),
+      overview_repository: self
+        .overview_repository
+        .unwrap_or_else(|| Arc::new(PostgresOverviewRepository::new(db.clone()).with_read_pool(db_read.clone()))),
+      organization_repository: self
+        .organization_repository
+        .unwrap_or_else(|| Arc::new(PostgresOrganizationRepository::new(db.clone()))),
+      request_stats: Arc::new(RequestStats::new()),
+      task_health: Arc::new(TaskHealth::new()),
+      user_export_repository: self
+        .user_export_repository
+        .unwrap_or_else(|| Arc::new(PostgresUserExportRepository::new(db.clone()))),
+      password_reset_repository: self
+        .password_reset_repository
+        .unwrap_or_else(|| Arc::new(PostgresPasswordResetRepository::new(db.clone()))),
+      mailer: self.mailer.unwrap_or_else(|| Arc::new(LogMailer)),
+      field_cipher: field_cipher.clone(),
+      #[cfg(feature = "billing")]
+      billing_repository: self
+        .billing_repository
+        .unwrap_or_else(|| Arc::new(PostgresBillingRepository::new(db.clone()).with_read_pool(db_read.clone()))),
+      #[cfg(feature = "billing")]
+      stripe: self
+        .stripe
+        .or_else(|| StripeClient::from_config(&config.billing).map(|client| Arc::new(client) as Arc<dyn StripeGateway>)),
+      #[cfg(feature = "backups")]
+      backup_store: self
+        .backup_store
+        .or_else(|| S3Store::from_config(&config.backups).map(|store| Arc::new(store) as Arc<dyn BackupStore>)),
+      #[cfg(feature = "reports")]
+      report_schedule_repository: self
+        .report_schedule_repository
+        .unwrap_or_else(|| Arc::new(PostgresReportScheduleRepository::new(db.clone()))),
+      #[cfg(feature = "reports")]
+      webhook_sender: self.webhook_sender.unwrap_or_else(|| {
+        Arc::new(HttpWebhookSender::new(std::time::Duration::from_secs(
+          config.reports.webhook_timeout_secs,
+        )))
+      }),
+      #[cfg(feature = "email_templates")]
+      email_template_repository: self
+        .email_template_repository
+        .unwrap_or_else(|| Arc::new(PostgresEmailTemplateRepository::new(db.clone()))),
+      #[cfg(feature = "rendering")]
+      pdf_renderer: self
+        .pdf_renderer
+        .or_else(|| ChromiumRenderer::from_config(&config.rendering).map(|renderer| Arc::new(renderer) as Arc<dyn PdfRenderer>)),
+      #[cfg(feature = "rendering")]
+      print_template_repository: self
+        .print_template_repository
+        .unwrap_or_else(|| Arc::new(PostgresPrintTemplateRepository::new(db.clone()))),
+      #[cfg(feature = "exports")]
+      export_profile_repository: self
+        .export_profile_repository
+        .unwrap_or_else(|| Arc::new(PostgresExportProfileRepository::new(db.clone()))),
+      #[cfg(feature = "inbound")]
+      inbound_repository: self
+        .inbound_repository
+        .unwrap_or_else(|| Arc::new(PostgresInboundRepository::new(db.clone()).with_cipher(field_cipher.clone()))),
+      #[cfg(feature = "connectors")]
+      connector_repository: self
+        .connector_repository
+        .unwrap_or_else(|| Arc::new(PostgresConnectorRepository::new(db.clone()).with_cipher(field_cipher.clone()))),
+      #[cfg(feature = "connectors")]
+      connector_factory: self.connector_factory.unwrap_or_else(|| {
+        Arc::new(HttpConnectorFactory::new(std::time::Duration::from_secs(
+          config.connectors.request_timeout_secs,
+        )))
+      }),
+      #[cfg(feature = "currencies")]
+      exchange_rate_repository: self
+        .exchange_rate_repository
+        .unwrap_or_else(|| Arc::new(PostgresExchangeRateRepository::new(db.clone()))),
+      #[cfg(feature = "currencies")]
+      rate_provider: self.rate_provider.or_else(|| rate_provider::from_config(&config.exchange_rates)),
+      #[cfg(feature = "geo")]
+      geocoder: self.geocoder.or_else(|| geocoder::from_config(&config.geo)),
+      #[cfg(feature = "geo")]
+      geo_cache: Arc::new(GeoCache::new(config.geo.cache_ttl_secs)),
+      #[cfg(feature = "geo")]
+      geo_lookup_repository: self
+        .geo_lookup_repository
+        .unwrap_or_else(|| Arc::new(PostgresGeoLookupRepository::new(db.clone()))),
+      #[cfg(any(feature = "contacts", feature = "products"))]
+      approval_repository: self
+        .approval_repository
+        .unwrap_or_else(|| Arc::new(PostgresApprovalRepository::new(db.clone()))),
+      #[cfg(any(feature = "contacts", feature = "products"))]
+      record_lock_repository: self
+        .record_lock_repository
+        .unwrap_or_else(|| Arc::new(PostgresRecordLockRepository::new(db.clone()))),
+      #[cfg(any(feature = "contacts", feature = "products"))]
+      operation_repository: self
+        .operation_repository
+        .unwrap_or_else(|| Arc::new(PostgresOperationRepository::new(db.clone()))),
+      #[cfg(any(feature = "contacts", feature = "products"))]
+      search_repository: self
+        .search_repository
+        .unwrap_or_else(|| Arc::new(PostgresSearchRepository::new(db.clone()))),
+      #[cfg(feature = "contacts")]
+      statement_repository: self
+        .statement_repository
+        .unwrap_or_else(|| Arc::new(PostgresStatementRepository::new(db.clone()))),
+      jwt_keys: Arc::new(JwtKeys::new(self.jwt_secret, self.jwt_previous_secret)),
+      db,
+      db_read,
+      config,
+    })
+  }
 }
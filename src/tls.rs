@@ -0,0 +1,53 @@
+//! Native HTTPS support.
+//!
+//! Loads a rustls configuration from the PEM files named in `TlsConfig` and, on Unix,
+//! reloads them whenever the process receives `SIGHUP` so renewed certificates are
+//! picked up without a restart.
+
+use axum_server::tls_rustls::RustlsConfig;
+use tracing::{error, info};
+
+/// Loads the certificate chain and private key into a `RustlsConfig`.
+///
+/// # Arguments
+///
+/// * `cert_path` - Path to the PEM encoded certificate chain.
+/// * `key_path` - Path to the PEM encoded private key.
+///
+/// # Returns
+///
+/// * `std::io::Result<RustlsConfig>` - The loaded configuration, or the I/O or parse error.
+pub async fn load_rustls_config(cert_path: &str, key_path: &str) -> std::io::Result<RustlsConfig> {
+  // Only the ring provider is compiled in; installing it twice is harmless.
+  let _ = rustls::crypto::ring::default_provider().install_default();
+  RustlsConfig::from_pem_file(cert_path, key_path).await
+}
+
+/// Spawns a task that reloads the certificate and key on every `SIGHUP`.
+///
+/// A failed reload is logged and the previous certificate stays in use.
+#[cfg(unix)]
+pub fn spawn_reload_on_sighup(config: RustlsConfig, cert_path: String, key_path: String) {
+  use tokio::signal::unix::{SignalKind, signal};
+
+  tokio::spawn(async move {
+    let mut hangups = match signal(SignalKind::hangup()) {
+      Ok(stream) => stream,
+      Err(e) => {
+        error!("Failed to listen for SIGHUP, TLS hot reload disabled: {}", e);
+        return;
+      }
+    };
+
+    while hangups.recv().await.is_some() {
+      match config.reload_from_pem_file(&cert_path, &key_path).await {
+        Ok(()) => info!("🔐 Reloaded TLS certificate from {}", cert_path),
+        Err(e) => error!("Failed to reload TLS certificate, keeping the previous one: {}", e),
+      }
+    }
+  });
+}
+
+/// `SIGHUP` does not exist outside Unix; certificates are only loaded at startup.
+#[cfg(not(unix))]
+pub fn spawn_reload_on_sighup(_config: RustlsConfig, _cert_path: String, _key_path: String) {}
@@ -1,6 +1,15 @@
-use crate::{AppResult, errors::AppError};
-use sqlx::{Pool, Postgres, Row};
+use std::collections::BTreeMap;
+
+use crate::{AppResult, errors::AppError, utils::DbExecutor};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
 use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// Characters a workspace may separate the prefix and number of its codes with.
+const SEPARATOR_CHARS: &str = "-_./";
+/// Categories a workspace can give a fixed prefix, per entity.
+pub const MAX_CATEGORY_PREFIXES: usize = 200;
 
 #[derive(Debug, Clone)]
 pub struct CodeGeneratorConfig {
@@ -10,6 +19,7 @@ pub struct CodeGeneratorConfig {
   pub prefix_length: usize,             // 1-3 characters
   pub number_length: usize,             // default 5 digits
   pub separator: String,                // default "-"
+  pub prefix: Option<String>,           // used instead of one derived from the name
 }
 
 impl Default for CodeGeneratorConfig {
@@ -21,33 +31,113 @@ impl Default for CodeGeneratorConfig {
       prefix_length: 2,
       number_length: 5,
       separator: "-".to_string(),
+      prefix: None,
+    }
+  }
+}
+
+impl CodeGeneratorConfig {
+  /// This config with the rules a workspace set for its table, if any. The prefix is fixed when
+  /// the rules give one to `category`, the category of the record the code is for.
+  pub fn with_rules(mut self, rules: Option<&CodeRules>, category: Option<&str>) -> Self {
+    let Some(rules) = rules else {
+      return self;
+    };
+    if let Some(prefix_length) = rules.prefix_length {
+      self.prefix_length = prefix_length;
+    }
+    if let Some(number_length) = rules.number_length {
+      self.number_length = number_length;
+    }
+    if let Some(separator) = &rules.separator {
+      self.separator = separator.clone();
+    }
+    if let Some(prefix) = category.and_then(|category| rules.category_prefixes.get(category)) {
+      self.prefix = Some(prefix.clone());
+    }
+    self
+  }
+}
+
+/// The code format a workspace chose for an entity, stored in its settings under the entity's
+/// table name. Omitted fields keep the entity's built-in format.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CodeRules {
+  #[validate(range(min = 1, max = 3, message = "Prefix length must be between 1 and 3"))]
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub prefix_length: Option<usize>,
+  #[validate(range(min = 1, max = 9, message = "Number length must be between 1 and 9"))]
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub number_length: Option<usize>,
+  #[validate(custom(function = "validate_separator"))]
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub separator: Option<String>,
+  /// Prefixes of the records of a category (a product category id, a contact type), used
+  /// instead of one derived from the name.
+  #[validate(custom(function = "validate_category_prefixes"))]
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub category_prefixes: BTreeMap<String, String>,
+}
+
+fn validate_separator(separator: &str) -> Result<(), ValidationError> {
+  if separator.is_empty() || separator.chars().count() > 3 || !separator.chars().all(|c| SEPARATOR_CHARS.contains(c)) {
+    return Err(ValidationError::new("separator").with_message(format!("Separator must be 1 to 3 of the characters '{}'", SEPARATOR_CHARS).into()));
+  }
+  Ok(())
+}
+
+fn validate_category_prefixes(prefixes: &BTreeMap<String, String>) -> Result<(), ValidationError> {
+  if prefixes.len() > MAX_CATEGORY_PREFIXES {
+    return Err(
+      ValidationError::new("category_prefixes").with_message(format!("At most {} category prefixes can be set", MAX_CATEGORY_PREFIXES).into()),
+    );
+  }
+  for (category, prefix) in prefixes {
+    let valid_prefix = (1..=10).contains(&prefix.len()) && prefix.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+    if category.trim().is_empty() || category.len() > 100 || !valid_prefix {
+      return Err(
+        ValidationError::new("category_prefixes")
+          .with_message(format!("Invalid prefix for category '{}': use 1 to 10 uppercase letters and digits", category).into()),
+      );
     }
   }
+  Ok(())
 }
 
 pub struct CodeGenerator {
-  pool: Pool<Postgres>,
+  db: DbExecutor,
 }
 
 impl CodeGenerator {
-  pub fn new(pool: Pool<Postgres>) -> Self {
-    Self { pool }
+  pub fn new(db: impl Into<DbExecutor>) -> Self {
+    Self { db: db.into() }
   }
 
   /// Generate next available code based on name and configuration
   pub async fn get_next_available_code(&self, config: &CodeGeneratorConfig, name: &str, workspace_id: Option<Uuid>) -> AppResult<String> {
-    let prefix = self.generate_prefix_from_name(name, config.prefix_length);
+    let prefix = match &config.prefix {
+      Some(prefix) => prefix.clone(),
+      None => self.generate_prefix_from_name(name, config.prefix_length),
+    };
 
-    let (query, _params) = self.build_query(config, &prefix, workspace_id);
+    let (query, _params) = self.build_query(config, workspace_id);
+    let pattern = format!(
+      r"^{}{}\d{{{}}}$",
+      regex::escape(&prefix),
+      regex::escape(&config.separator),
+      config.number_length
+    );
 
     let row = sqlx::query(&query);
     let row = match (workspace_id, &config.workspace_column) {
-      (Some(ws_id), Some(_)) => row.bind(ws_id).bind(format!("{}{}%", prefix, config.separator)),
-      (None, None) => row.bind(format!("{}{}%", prefix, config.separator)),
+      (Some(ws_id), Some(_)) => row.bind(ws_id).bind(format!("{}{}%", prefix, config.separator)).bind(pattern),
+      (None, None) => row.bind(format!("{}{}%", prefix, config.separator)).bind(pattern),
       _ => return Err(AppError::Internal("Workspace configuration mismatch".to_string())),
     };
 
-    let row = row.fetch_optional(&self.pool).await?;
+    let mut conn = self.db.acquire().await?;
+    let row = row.fetch_optional(&mut *conn).await?;
 
     let next_code = match row {
       Some(row) => {
@@ -71,25 +161,26 @@ impl CodeGenerator {
       _ => return Err(AppError::Internal("Workspace configuration mismatch".to_string())),
     };
 
-    let result = row.fetch_optional(&self.pool).await?;
+    let mut conn = self.db.acquire().await?;
+    let result = row.fetch_optional(&mut *conn).await?;
     Ok(result.is_some())
   }
 
-  fn build_query(&self, config: &CodeGeneratorConfig, prefix: &str, workspace_id: Option<Uuid>) -> (String, Vec<String>) {
-    let pattern_regex = format!(r"^{}\{}{}\d{{{}}}$", prefix, config.separator, config.separator, config.number_length);
-
+  /// The query of the highest code of a prefix, its `LIKE` pattern and regex bound after the
+  /// workspace.
+  fn build_query(&self, config: &CodeGeneratorConfig, workspace_id: Option<Uuid>) -> (String, Vec<String>) {
     match (workspace_id, &config.workspace_column) {
       (Some(_), Some(ws_col)) => {
         let query = format!(
-          "SELECT {} FROM {} WHERE {} = $1 AND {} LIKE $2 AND {} ~ '{}' ORDER BY {} DESC LIMIT 1",
-          config.code_column, config.table_name, ws_col, config.code_column, config.code_column, pattern_regex, config.code_column
+          "SELECT {} FROM {} WHERE {} = $1 AND {} LIKE $2 AND {} ~ $3 ORDER BY {} DESC LIMIT 1",
+          config.code_column, config.table_name, ws_col, config.code_column, config.code_column, config.code_column
         );
         (query, vec![])
       }
       (None, None) => {
         let query = format!(
-          "SELECT {} FROM {} WHERE {} LIKE $1 AND {} ~ '{}' ORDER BY {} DESC LIMIT 1",
-          config.code_column, config.table_name, config.code_column, config.code_column, pattern_regex, config.code_column
+          "SELECT {} FROM {} WHERE {} LIKE $1 AND {} ~ $2 ORDER BY {} DESC LIMIT 1",
+          config.code_column, config.table_name, config.code_column, config.code_column, config.code_column
         );
         (query, vec![])
       }
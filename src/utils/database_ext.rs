@@ -1,21 +1,28 @@
-use sqlx::{Error as SqlxError, PgPool};
+use sqlx::{Connection, Error as SqlxError, PgConnection};
 use tracing::debug;
 use uuid::Uuid;
 
+use super::tenant_schema;
+use crate::modules::datastores::workspaces::workspace_models::WorkspaceRole;
+
 /// Extension trait for PostgreSQL session management
+///
+/// The variables are session-scoped, so they must be set on the connection that runs the
+/// queries (see `utils::db_session`), never on a pool, and cleared before the connection is reused.
 #[async_trait::async_trait]
 pub trait PostgresSessionExt {
-  /// Set session variables for Row Level Security
-  async fn set_session_settings(&self, user_id: &Uuid, workspace_id: Option<&Uuid>) -> Result<(), SqlxError>;
+  /// Set session variables for Row Level Security. `workspace` is the caller's current
+  /// workspace and their role in it, as already checked by the caller.
+  async fn set_session_settings(&mut self, user_id: &Uuid, workspace: Option<(&Uuid, WorkspaceRole)>) -> Result<(), SqlxError>;
 
   /// Clear session variables
-  async fn clear_session_settings(&self) -> Result<(), SqlxError>;
+  async fn clear_session_settings(&mut self) -> Result<(), SqlxError>;
 }
 
 #[async_trait::async_trait]
-impl PostgresSessionExt for PgPool {
-  async fn set_session_settings(&self, user_id: &Uuid, workspace_id: Option<&Uuid>) -> Result<(), SqlxError> {
-    debug!("Setting session variables: user_id={}, workspace_id={:?}", user_id, workspace_id);
+impl PostgresSessionExt for PgConnection {
+  async fn set_session_settings(&mut self, user_id: &Uuid, workspace: Option<(&Uuid, WorkspaceRole)>) -> Result<(), SqlxError> {
+    debug!("Setting session variables: user_id={}, workspace={:?}", user_id, workspace);
 
     // Start a transaction to ensure all settings are applied atomically
     let mut tx = self.begin().await?;
@@ -26,29 +33,26 @@ impl PostgresSessionExt for PgPool {
       .execute(&mut *tx)
       .await?;
 
-    if let Some(ws_id) = workspace_id {
-      let role_opt: Option<String> = sqlx::query_scalar("SELECT role::text FROM workspace_users WHERE user_id = $1 AND workspace_id = $2")
-        .bind(user_id)
-        .bind(ws_id)
-        .fetch_optional(&mut *tx)
-        .await?;
-
-      let (ws_id_str, role_str) = match role_opt {
-        Some(role) => (Some(ws_id.to_string()), Some(role)),
-        None => (None, None),
-      };
-
-      // Set both workspace and role in a single query
-      sqlx::query("SELECT set_config('app.current_workspace_id', $1, false), set_config('app.current_user_role', $2, false)")
-        .bind(ws_id_str)
-        .bind(role_str)
-        .execute(&mut *tx)
-        .await?;
+    if let Some((ws_id, role)) = workspace {
+      // Set the workspace, the role and the workspace's tables (see `tenant_schema`) in a single query
+      sqlx::query(
+        "SELECT set_config('app.current_workspace_id', $1, false), set_config('app.current_user_role', $2::text, false),
+          set_config('search_path', quote_ident($3) || ', public', false)",
+      )
+      .bind(ws_id.to_string())
+      .bind(role)
+      .bind(tenant_schema::schema_name(*ws_id))
+      .execute(&mut *tx)
+      .await?;
     } else {
-      // Clear both workspace and role in a single query
-      sqlx::query("SELECT set_config('app.current_workspace_id', NULL, false), set_config('app.current_user_role', NULL, false)")
-        .execute(&mut *tx)
-        .await?;
+      // Clear the workspace and role and go back to the shared tables in a single query
+      sqlx::query(
+        "SELECT set_config('app.current_workspace_id', NULL, false), set_config('app.current_user_role', NULL, false),
+          set_config('search_path', reset_val, false)
+        FROM pg_settings WHERE name = 'search_path'",
+      )
+      .execute(&mut *tx)
+      .await?;
     }
 
     tx.commit().await?;
@@ -56,16 +60,18 @@ impl PostgresSessionExt for PgPool {
     Ok(())
   }
 
-  async fn clear_session_settings(&self) -> Result<(), SqlxError> {
+  async fn clear_session_settings(&mut self) -> Result<(), SqlxError> {
     debug!("Clearing all session variables");
     // Clear all variables in a single query for efficiency
     sqlx::query(
       "SELECT 
-        set_config('app.current_user_id', NULL, true), 
-        set_config('app.current_workspace_id', NULL, true),
-        set_config('app.current_user_role', NULL, true)",
+        set_config('app.current_user_id', NULL, false),
+        set_config('app.current_workspace_id', NULL, false),
+        set_config('app.current_user_role', NULL, false),
+        set_config('search_path', reset_val, false)
+      FROM pg_settings WHERE name = 'search_path'",
     )
-    .execute(self)
+    .execute(&mut *self)
     .await?;
     debug!("Session variables cleared");
     Ok(())
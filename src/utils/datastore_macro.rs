@@ -0,0 +1,709 @@
+/// Macro to generate a workspace-scoped CRUD module from a field specification
+///
+/// Emits the same layers as the contacts and products modules, as submodules of the module the
+/// macro is invoked in:
+///
+/// * `models`: the row, response, create/update/patch payloads, list query and filters.
+/// * `repository`: the repository trait and its sqlx implementation, listing through
+///   `PaginatedRepository` (values always bound).
+/// * `handlers`: list, create, get, update (`PUT`), merge patch (`PATCH`), delete and next code,
+///   publishing `<resource>.created|updated|deleted` real-time events.
+/// * `routes`: `router()`, to be nested under `/api/v1/<table>` in `lib.rs`; the create handler
+///   answers with a `Location` under that path.
+///
+/// The table must have the columns `id`, `code`, `name`, `is_active`, `workspace_id`,
+/// `created_by`, `updated_by`, `created_at` and `updated_at` (see the contacts migration), plus
+/// one column per field, named like the field, and a unique `(workspace_id, code)` constraint.
+/// Field types must convert into `sea_query::Value` (strings, integers, booleans, `Decimal`,
+/// `Uuid` and `Option`s of them); `#[validate(...)]` attributes apply to the create, update and
+/// patch payloads. Codes are generated from the name when a create request leaves them empty.
+///
+/// Handlers build the repository from the state's pools, as the repositories are not part of
+/// `AppState`. The new paths still have to be added to the OpenAPI document.
+///
+/// ```ignore
+/// // src/modules/datastores/warehouses/mod.rs
+/// crate::define_datastore_module! {
+///   resource: "warehouse",
+///   label: "Warehouse",
+///   table: "warehouses",
+///   types: {
+///     model: Warehouse,
+///     response: WarehouseResponse,
+///     create: CreateWarehouseRequest,
+///     update: UpdateWarehouseRequest,
+///     patch: WarehousePatchTarget,
+///     query: GetWarehousesQuery,
+///     filters: WarehouseFilters,
+///     repository: WarehouseRepository,
+///     sqlx_repository: SqlxWarehouseRepository,
+///   },
+///   code: { prefix_length: 2, number_length: 5 },
+///   fields: {
+///     #[validate(length(min = 1, message = "Location is required"))]
+///     location: String,
+///     capacity: Option<i32>,
+///   },
+/// }
+/// ```
+#[macro_export]
+macro_rules! define_datastore_module {
+  (
+    resource: $resource:literal,
+    label: $label:literal,
+    table: $table:literal,
+    types: {
+      model: $Model:ident,
+      response: $Response:ident,
+      create: $Create:ident,
+      update: $Update:ident,
+      patch: $Patch:ident,
+      query: $Query:ident,
+      filters: $Filters:ident,
+      repository: $Repository:ident,
+      sqlx_repository: $SqlxRepository:ident $(,)?
+    },
+    code: { prefix_length: $prefix_length:literal, number_length: $number_length:literal $(,)? },
+    fields: {
+      $( $(#[$attr:meta])* $field:ident : $ty:ty ),* $(,)?
+    } $(,)?
+  ) => {
+    pub mod models {
+      #[allow(unused_imports)]
+      use super::*;
+
+      use chrono::{DateTime, Utc};
+      use serde::{Deserialize, Serialize};
+      use sqlx::FromRow;
+      use uuid::Uuid;
+      use validator::Validate;
+
+      use $crate::helper::pagination::MAX_LIMIT;
+
+      /// Table the module reads and writes.
+      pub const TABLE: &str = $table;
+
+      /// Represents a row of the table.
+      #[derive(Debug, Serialize, Deserialize, FromRow)]
+      pub struct $Model {
+        pub id: Uuid,
+        pub code: String,
+        pub name: String,
+        $( pub $field: $ty, )*
+        pub is_active: bool,
+
+        // Metadata
+        pub workspace_id: Option<Uuid>,
+        pub created_by: Option<Uuid>,
+        pub updated_by: Option<Uuid>,
+        pub created_at: DateTime<Utc>,
+        pub updated_at: DateTime<Utc>,
+      }
+
+      /// The payload for creating a record; an empty `code` is generated from the name.
+      #[derive(Debug, Deserialize, Validate)]
+      pub struct $Create {
+        #[serde(default)]
+        #[validate(length(min = 1, message = "Code is required"))]
+        pub code: String,
+        #[validate(length(min = 1, message = "Name is required"))]
+        pub name: String,
+        $( $(#[$attr])* pub $field: $ty, )*
+      }
+
+      /// The payload for updating a record. Missing fields keep their value.
+      #[derive(Debug, Deserialize, Validate)]
+      pub struct $Update {
+        #[validate(length(min = 1, message = "Code cannot be empty"))]
+        pub code: Option<String>,
+        #[validate(length(min = 1, message = "Name cannot be empty"))]
+        pub name: Option<String>,
+        $( $(#[$attr])* pub $field: Option<$ty>, )*
+        pub is_active: Option<bool>,
+      }
+
+      /// The editable fields of a record, used as the target of JSON Merge Patch (`PATCH`) updates.
+      #[derive(Debug, Serialize, Deserialize, Validate)]
+      #[serde(deny_unknown_fields)]
+      pub struct $Patch {
+        #[validate(length(min = 1, message = "Code is required"))]
+        pub code: String,
+        #[validate(length(min = 1, message = "Name is required"))]
+        pub name: String,
+        $( $(#[$attr])* pub $field: $ty, )*
+        pub is_active: bool,
+      }
+
+      impl From<&$Model> for $Patch {
+        fn from(record: &$Model) -> Self {
+          Self {
+            code: record.code.clone(),
+            name: record.name.clone(),
+            $( $field: record.$field.clone(), )*
+            is_active: record.is_active,
+          }
+        }
+      }
+
+      /// The public representation of a record.
+      #[derive(Debug, Serialize)]
+      pub struct $Response {
+        pub id: Uuid,
+        pub code: String,
+        pub name: String,
+        $( pub $field: $ty, )*
+        pub is_active: bool,
+
+        // Metadata
+        pub workspace_id: Option<Uuid>,
+        pub created_by: Option<Uuid>,
+        pub updated_by: Option<Uuid>,
+        pub created_at: DateTime<Utc>,
+        pub updated_at: DateTime<Utc>,
+      }
+
+      impl From<$Model> for $Response {
+        fn from(record: $Model) -> Self {
+          Self {
+            id: record.id,
+            code: record.code,
+            name: record.name,
+            $( $field: record.$field, )*
+            is_active: record.is_active,
+
+            // Metadata
+            workspace_id: record.workspace_id,
+            created_by: record.created_by,
+            updated_by: record.updated_by,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+          }
+        }
+      }
+
+      /// Query parameters of the list endpoint
+      #[derive(Debug, Default, Deserialize, Validate)]
+      #[serde(deny_unknown_fields)]
+      pub struct $Query {
+        // Pagination
+        #[validate(range(min = 1, message = "Page must be at least 1"))]
+        pub page: Option<u32>,
+        #[validate(range(min = 1, max = MAX_LIMIT, message = "Limit must be between 1 and 100"))]
+        pub limit: Option<u32>,
+
+        // Filtering
+        pub search: Option<String>, // matches code or name
+        pub code: Option<String>,
+        pub is_active: Option<bool>,
+
+        // Sorting
+        pub sort_by: Option<String>,    // "code", "name", any field, "created_at" or "updated_at"
+        pub sort_order: Option<String>, // "asc" or "desc"
+      }
+
+      #[derive(Debug, Clone)]
+      pub struct $Filters {
+        pub search: Option<String>,
+        pub code: Option<String>,
+        pub is_active: Option<bool>,
+        pub sort_by: String,
+        pub sort_order: String,
+      }
+
+      /// Columns the list can be sorted by.
+      pub const SORTABLE_COLUMNS: &[&str] = &["code", "name", $( stringify!($field), )* "created_at", "updated_at"];
+
+      impl From<$Query> for $Filters {
+        fn from(query: $Query) -> Self {
+          let sort_by = query
+            .sort_by
+            .filter(|column| SORTABLE_COLUMNS.contains(&column.as_str()))
+            .unwrap_or_else(|| "created_at".to_string());
+
+          let sort_order = match query.sort_order.as_deref() {
+            Some("asc") | Some("ASC") => "ASC",
+            _ => "DESC", // default
+          }
+          .to_string();
+
+          Self {
+            search: query.search,
+            code: query.code,
+            is_active: query.is_active,
+            sort_by,
+            sort_order,
+          }
+        }
+      }
+    }
+
+    pub mod repository {
+      #[allow(unused_imports)]
+      use super::*;
+
+      use async_trait::async_trait;
+      use sea_query::{Alias, Asterisk, DynIden, Expr, Order, PostgresQueryBuilder, Query, SeaRc, SelectStatement, extension::postgres::PgExpr};
+      use sea_query_binder::SqlxBinder;
+      use uuid::Uuid;
+
+      use super::models::{$Create, $Filters, $Model, $Patch, $Update, TABLE};
+      use $crate::{
+        AppResult,
+        errors::AppError,
+        utils::{
+          DbExecutor, ReadPool,
+          code_generator::{CodeGenerator, CodeGeneratorConfig, CodeRules},
+          paginated_repository::PaginatedRepository,
+        },
+      };
+
+      /// The code generator settings of the table.
+      pub fn code_config() -> CodeGeneratorConfig {
+        CodeGeneratorConfig {
+          table_name: TABLE.to_string(),
+          code_column: "code".to_string(),
+          workspace_column: Some("workspace_id".to_string()),
+          prefix_length: $prefix_length,
+          number_length: $number_length,
+          separator: "-".to_string(),
+          prefix: None,
+        }
+      }
+
+      #[async_trait]
+      pub trait $Repository {
+        async fn create_by_workspace(&self, record: $Create, workspace_id: Uuid, user_id: Uuid) -> AppResult<$Model>;
+        async fn find_by_filters_paginated(
+          &self,
+          workspace_id: Uuid,
+          user_id: Uuid,
+          page: u32,
+          limit: u32,
+          filters: $Filters,
+        ) -> AppResult<(Vec<$Model>, u64)>;
+        async fn find_by_id_and_workspace(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<$Model>>;
+        async fn update_by_workspace(&self, id: Uuid, workspace_id: Uuid, record: $Update, updated_by: Uuid) -> AppResult<Option<$Model>>;
+        async fn replace_by_workspace(&self, id: Uuid, workspace_id: Uuid, fields: $Patch, updated_by: Uuid) -> AppResult<Option<$Model>>;
+        async fn delete_by_workspace_and_user(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<bool>;
+        async fn get_next_available_code(&self, workspace_id: Uuid, name: &str, rules: Option<&CodeRules>) -> AppResult<String>;
+      }
+
+      pub struct $SqlxRepository {
+        db: DbExecutor,
+        read_pool: ReadPool,
+      }
+
+      impl $SqlxRepository {
+        pub fn new(db: impl Into<DbExecutor>) -> Self {
+          let db = db.into();
+          Self {
+            read_pool: ReadPool::primary_only(db.clone()),
+            db,
+          }
+        }
+
+        /// Runs the read-only queries (lookups and lists) on `read_pool` instead of the primary.
+        pub fn with_read_pool(mut self, read_pool: ReadPool) -> Self {
+          self.read_pool = read_pool;
+          self
+        }
+
+        /// The repository of a request, on the state's pools.
+        pub fn from_state(state: &$crate::AppState) -> Self {
+          Self::new(state.db.clone()).with_read_pool(state.db_read.clone())
+        }
+      }
+
+      impl PaginatedRepository<$Model, $Filters> for $SqlxRepository {
+        fn table() -> DynIden {
+          SeaRc::new(Alias::new(TABLE))
+        }
+
+        fn apply_filters(query: &mut SelectStatement, filters: &$Filters) {
+          let column = |name: &str| (Alias::new(TABLE), Alias::new(name));
+
+          // Search on code or name
+          if let Some(search) = filters.search.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+            let pattern = format!("%{}%", search);
+            query.and_where(Expr::col(column("code")).ilike(pattern.clone()).or(Expr::col(column("name")).ilike(pattern)));
+          }
+
+          if let Some(code) = &filters.code {
+            query.and_where(Expr::col(column("code")).like(format!("%{}%", code)));
+          }
+
+          if let Some(is_active) = filters.is_active {
+            query.and_where(Expr::col(column("is_active")).eq(is_active));
+          }
+        }
+
+        fn sort(filters: &$Filters) -> (DynIden, Order) {
+          let order = if filters.sort_order == "ASC" { Order::Asc } else { Order::Desc };
+          (SeaRc::new(Alias::new(filters.sort_by.as_str())), order)
+        }
+
+        fn read_pool(&self) -> &ReadPool {
+          &self.read_pool
+        }
+      }
+
+      #[async_trait]
+      impl $Repository for $SqlxRepository {
+        async fn create_by_workspace(&self, record: $Create, workspace_id: Uuid, user_id: Uuid) -> AppResult<$Model> {
+          let (sql, values) = Query::insert()
+            .into_table(Alias::new(TABLE))
+            .columns([
+              Alias::new("code"),
+              Alias::new("name"),
+              $( Alias::new(stringify!($field)), )*
+              Alias::new("workspace_id"),
+              Alias::new("created_by"),
+            ])
+            .values_panic([
+              record.code.into(),
+              record.name.into(),
+              $( record.$field.into(), )*
+              workspace_id.into(),
+              user_id.into(),
+            ])
+            .returning_all()
+            .build_sqlx(PostgresQueryBuilder);
+
+          let mut conn = self.db.acquire().await?;
+          sqlx::query_as_with::<_, $Model, _>(&sql, values)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| {
+              tracing::error!("Failed to create {}: {}", $resource, e);
+              AppError::from_sqlx_error(e, &sql)
+            })
+        }
+
+        async fn find_by_filters_paginated(
+          &self,
+          workspace_id: Uuid,
+          user_id: Uuid,
+          page: u32,
+          limit: u32,
+          filters: $Filters,
+        ) -> AppResult<(Vec<$Model>, u64)> {
+          self.find_page(workspace_id, user_id, page, limit, &filters).await
+        }
+
+        async fn find_by_id_and_workspace(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<Option<$Model>> {
+          let mut query = Query::select();
+          query
+            .column((Alias::new(TABLE), Asterisk))
+            .from(Alias::new(TABLE))
+            .and_where(Expr::col((Alias::new(TABLE), Alias::new("id"))).eq(id));
+          <Self as PaginatedRepository<$Model, $Filters>>::apply_scope(&mut query, workspace_id, user_id);
+          let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+
+          let mut conn = self.read_pool.acquire().await?;
+          let record = sqlx::query_as_with::<_, $Model, _>(&sql, values).fetch_optional(&mut *conn).await?;
+          Ok(record)
+        }
+
+        async fn update_by_workspace(&self, id: Uuid, workspace_id: Uuid, record: $Update, updated_by: Uuid) -> AppResult<Option<$Model>> {
+          let mut query = Query::update();
+          query.table(Alias::new(TABLE));
+          if let Some(code) = record.code {
+            query.value(Alias::new("code"), code);
+          }
+          if let Some(name) = record.name {
+            query.value(Alias::new("name"), name);
+          }
+          $(
+            if let Some(value) = record.$field {
+              query.value(Alias::new(stringify!($field)), value);
+            }
+          )*
+          if let Some(is_active) = record.is_active {
+            query.value(Alias::new("is_active"), is_active);
+          }
+          let (sql, values) = query
+            .value(Alias::new("updated_by"), updated_by)
+            .value(Alias::new("updated_at"), Expr::current_timestamp())
+            .and_where(Expr::col(Alias::new("id")).eq(id))
+            .and_where(Expr::col(Alias::new("workspace_id")).eq(workspace_id))
+            .returning_all()
+            .build_sqlx(PostgresQueryBuilder);
+
+          let mut conn = self.db.acquire().await?;
+          sqlx::query_as_with::<_, $Model, _>(&sql, values)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| AppError::from_sqlx_error(e, &sql))
+        }
+
+        async fn replace_by_workspace(&self, id: Uuid, workspace_id: Uuid, fields: $Patch, updated_by: Uuid) -> AppResult<Option<$Model>> {
+          let (sql, values) = Query::update()
+            .table(Alias::new(TABLE))
+            .value(Alias::new("code"), fields.code)
+            .value(Alias::new("name"), fields.name)
+            $( .value(Alias::new(stringify!($field)), fields.$field) )*
+            .value(Alias::new("is_active"), fields.is_active)
+            .value(Alias::new("updated_by"), updated_by)
+            .value(Alias::new("updated_at"), Expr::current_timestamp())
+            .and_where(Expr::col(Alias::new("id")).eq(id))
+            .and_where(Expr::col(Alias::new("workspace_id")).eq(workspace_id))
+            .returning_all()
+            .build_sqlx(PostgresQueryBuilder);
+
+          let mut conn = self.db.acquire().await?;
+          sqlx::query_as_with::<_, $Model, _>(&sql, values)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| AppError::from_sqlx_error(e, &sql))
+        }
+
+        async fn delete_by_workspace_and_user(&self, id: Uuid, workspace_id: Uuid, user_id: Uuid) -> AppResult<bool> {
+          let (sql, values) = Query::delete()
+            .from_table(Alias::new(TABLE))
+            .and_where(Expr::col(Alias::new("id")).eq(id))
+            .and_where(Expr::col(Alias::new("workspace_id")).eq(workspace_id))
+            .and_where(Expr::col(Alias::new("created_by")).eq(user_id))
+            .build_sqlx(PostgresQueryBuilder);
+
+          let mut conn = self.db.acquire().await?;
+          let result = sqlx::query_with(&sql, values).execute(&mut *conn).await?;
+          Ok(result.rows_affected() > 0)
+        }
+
+        async fn get_next_available_code(&self, workspace_id: Uuid, name: &str, rules: Option<&CodeRules>) -> AppResult<String> {
+          CodeGenerator::new(self.db.clone())
+            .get_next_available_code(&code_config().with_rules(rules, None), name, Some(workspace_id))
+            .await
+        }
+      }
+    }
+
+    pub mod handlers {
+      use std::sync::Arc;
+
+      use axum::{
+        Json,
+        extract::{State, rejection::JsonRejection},
+      };
+      use uuid::Uuid;
+      use validator::Validate;
+
+      use super::{
+        models::{$Create, $Filters, $Patch, $Query, $Response, $Update, TABLE},
+        repository::{$Repository, $SqlxRepository},
+      };
+      use $crate::{
+        AppResult, AppState,
+        errors::{AppError, NotFoundError},
+        events::{RecordAction, WorkspaceEvent},
+        helper::{Pagination, PathUuid, RequireRole, RequiredWorkspace, ValidatedQuery, workspace::role::Member},
+        modules::auth::current_user::CurrentUser,
+        responses::{ApiResponse, Created, PaginatedResponse, PaginationMeta},
+        utils::{
+          db_session,
+          merge_patch::{MergePatch, apply_merge_patch},
+          next_code_macro::NextCodeQuery,
+        },
+      };
+
+      $crate::impl_next_code_handler!(get_next_code, $resource, super::repository::code_config());
+
+      fn not_found(id: Uuid) -> AppError {
+        AppError::not_found_with_id($label, id)
+      }
+
+      /// Handles the request to retrieve a paginated, filtered list of records of the workspace.
+      #[axum::debug_handler]
+      pub async fn list(
+        State(state): State<Arc<AppState>>,
+        current_user: CurrentUser,
+        RequiredWorkspace(workspace_id): RequiredWorkspace,
+        _member: RequireRole<Member>,
+        ValidatedQuery(mut params): ValidatedQuery<$Query>,
+        pagination: Pagination,
+      ) -> AppResult<Json<ApiResponse<PaginatedResponse<$Response>>>> {
+        let Pagination { page, limit, .. } = pagination;
+        pagination.apply_sort_defaults(&mut params.sort_by, &mut params.sort_order);
+        let (records, total) = $SqlxRepository::from_state(&state)
+          .find_by_filters_paginated(workspace_id, current_user.user_id, page, limit, $Filters::from(params))
+          .await?;
+
+        let response = ApiResponse::success(
+          PaginatedResponse {
+            list: records.into_iter().map($Response::from).collect(),
+            pagination: PaginationMeta::new(page, limit, total),
+          },
+          &format!("{}s retrieved successfully", $label),
+        );
+        Ok(Json(response))
+      }
+
+      /// Handles the request to create a record in the workspace, generating its code when empty.
+      #[axum::debug_handler]
+      pub async fn create(
+        State(state): State<Arc<AppState>>,
+        current_user: CurrentUser,
+        RequiredWorkspace(workspace_id): RequiredWorkspace,
+        _member: RequireRole<Member>,
+        payload: Result<Json<$Create>, JsonRejection>,
+      ) -> AppResult<Created<ApiResponse<$Response>>> {
+        let repository = $SqlxRepository::from_state(&state);
+        let Json(mut payload) = payload?;
+
+        // Code generation and the insert run in one transaction
+        let record = db_session::transaction::<_, _, AppError>(&state.db, async {
+          if payload.code.trim().is_empty() {
+            let settings = state.workspace_settings_repository.get(workspace_id).await?;
+            payload.code = repository
+              .get_next_available_code(workspace_id, &payload.name, settings.code_rules.get(TABLE))
+              .await?;
+          }
+          payload.validate()?;
+
+          repository.create_by_workspace(payload, workspace_id, current_user.user_id).await
+        })
+        .await?;
+
+        tracing::info!("{} created successfully with ID: {}", $label, record.id);
+
+        let record = $Response::from(record);
+        publish_change(&state, RecordAction::Created, workspace_id, current_user.user_id, record.id, Some(&record));
+
+        let location = format!("/api/v1/{}/{}", $table, record.id);
+        Ok(ApiResponse::created(record, &format!("{} created successfully", $label), location))
+      }
+
+      /// Handles the request to retrieve a single record of the workspace by its ID.
+      #[axum::debug_handler]
+      pub async fn get_by_id(
+        State(state): State<Arc<AppState>>,
+        PathUuid(id): PathUuid,
+        current_user: CurrentUser,
+        RequiredWorkspace(workspace_id): RequiredWorkspace,
+        _member: RequireRole<Member>,
+      ) -> AppResult<Json<ApiResponse<$Response>>> {
+        let record = $SqlxRepository::from_state(&state)
+          .find_by_id_and_workspace(id, workspace_id, current_user.user_id)
+          .await?
+          .ok_or_else(|| not_found(id))?;
+
+        let response = ApiResponse::success($Response::from(record), &format!("{} retrieved successfully", $label));
+        Ok(Json(response))
+      }
+
+      /// Handles the request to update a record; fields missing from the payload keep their value.
+      #[axum::debug_handler]
+      pub async fn update(
+        State(state): State<Arc<AppState>>,
+        PathUuid(id): PathUuid,
+        current_user: CurrentUser,
+        RequiredWorkspace(workspace_id): RequiredWorkspace,
+        _member: RequireRole<Member>,
+        payload: Result<Json<$Update>, JsonRejection>,
+      ) -> AppResult<Json<ApiResponse<$Response>>> {
+        let Json(payload) = payload?;
+        payload.validate()?;
+
+        let record = $SqlxRepository::from_state(&state)
+          .update_by_workspace(id, workspace_id, payload, current_user.user_id)
+          .await?
+          .ok_or_else(|| not_found(id))?;
+
+        let record = $Response::from(record);
+        publish_change(&state, RecordAction::Updated, workspace_id, current_user.user_id, id, Some(&record));
+
+        let response = ApiResponse::success(record, &format!("{} updated successfully", $label));
+        Ok(Json(response))
+      }
+
+      /// Handles a JSON Merge Patch (RFC 7396) update of a record; an explicit `null` clears an
+      /// optional field.
+      #[axum::debug_handler]
+      pub async fn patch(
+        State(state): State<Arc<AppState>>,
+        PathUuid(id): PathUuid,
+        current_user: CurrentUser,
+        RequiredWorkspace(workspace_id): RequiredWorkspace,
+        _member: RequireRole<Member>,
+        MergePatch(patch): MergePatch,
+      ) -> AppResult<Json<ApiResponse<$Response>>> {
+        let repository = $SqlxRepository::from_state(&state);
+
+        // The lookup and the write run in one transaction
+        let record = db_session::transaction::<_, _, AppError>(&state.db, async {
+          let current = repository
+            .find_by_id_and_workspace(id, workspace_id, current_user.user_id)
+            .await?
+            .ok_or_else(|| not_found(id))?;
+
+          let fields = apply_merge_patch(&$Patch::from(&current), &patch)?;
+          fields.validate()?;
+
+          repository
+            .replace_by_workspace(id, workspace_id, fields, current_user.user_id)
+            .await?
+            .ok_or_else(|| not_found(id))
+        })
+        .await?;
+
+        let record = $Response::from(record);
+        publish_change(&state, RecordAction::Updated, workspace_id, current_user.user_id, id, Some(&record));
+
+        let response = ApiResponse::success(record, &format!("{} updated successfully", $label));
+        Ok(Json(response))
+      }
+
+      /// Handles the request to delete a record created by the caller.
+      #[axum::debug_handler]
+      pub async fn delete(
+        State(state): State<Arc<AppState>>,
+        PathUuid(id): PathUuid,
+        current_user: CurrentUser,
+        RequiredWorkspace(workspace_id): RequiredWorkspace,
+        _member: RequireRole<Member>,
+      ) -> AppResult<Json<ApiResponse<()>>> {
+        let deleted = $SqlxRepository::from_state(&state)
+          .delete_by_workspace_and_user(id, workspace_id, current_user.user_id)
+          .await?;
+        if !deleted {
+          return Err(not_found(id));
+        }
+
+        publish_change(&state, RecordAction::Deleted, workspace_id, current_user.user_id, id, None);
+
+        let response = ApiResponse::success((), &format!("{} deleted successfully", $label));
+        Ok(Json(response))
+      }
+
+      /// Notifies real-time clients of the workspace that a record changed.
+      fn publish_change(state: &AppState, action: RecordAction, workspace_id: Uuid, user_id: Uuid, id: Uuid, record: Option<&$Response>) {
+        state
+          .events
+          .publish(WorkspaceEvent::record($resource, action, workspace_id, id, user_id, record));
+      }
+    }
+
+    pub mod routes {
+      use std::sync::Arc;
+
+      use axum::{
+        Router,
+        routing::{delete, get, patch, post, put},
+      };
+
+      use super::handlers;
+      use $crate::AppState;
+
+      pub fn router() -> Router<Arc<AppState>> {
+        Router::new()
+          .route("/", get(handlers::list))
+          .route("/", post(handlers::create))
+          .route("/next-code", get(handlers::get_next_code))
+          .route("/:id", get(handlers::get_by_id))
+          .route("/:id", put(handlers::update))
+          .route("/:id", patch(handlers::patch))
+          .route("/:id", delete(handlers::delete))
+      }
+    }
+  };
+}
@@ -0,0 +1,79 @@
+//! The executor repositories run their queries on.
+//!
+//! Repositories are built once and shared through `AppState`, so they cannot be handed a
+//! connection per call. They own a `DbExecutor` instead and ask it for a connection for every
+//! query. The pool executor defers to `db_session`, which returns the current request's
+//! RLS-scoped connection, including any transaction a handler opened on it. A pinned executor
+//! runs every query on one connection chosen when the repository is built, such as a test
+//! transaction that is rolled back afterwards.
+
+use std::{future::Future, sync::Arc};
+
+use sqlx::{PgPool, TransactionManager, postgres::PgTransactionManager};
+use tokio::sync::Mutex;
+
+use super::{
+  db_resilience,
+  db_session::{self, DbConnection, SessionBoundConnection, SessionSettings},
+};
+
+#[derive(Clone)]
+pub enum DbExecutor {
+  /// Queries run on the current request's connection inside `db_session::scope`, on a pooled
+  /// connection anywhere else.
+  Pool(PgPool),
+  /// Queries run on this connection, whatever request or transaction is current. Inside a request
+  /// the connection takes on the request's session variables.
+  Pinned(Arc<Mutex<SessionBoundConnection>>),
+}
+
+impl DbExecutor {
+  /// Runs every query on a dedicated connection carrying `settings`, so Row Level Security
+  /// applies as for that caller even outside a request (CLI commands, background jobs).
+  pub async fn scoped(pool: &PgPool, settings: SessionSettings) -> Result<Self, sqlx::Error> {
+    let connection = db_session::dedicated(pool, Some(settings)).await?;
+    Ok(Self::Pinned(Arc::new(Mutex::new(connection))))
+  }
+
+  /// Runs every query in one transaction that is rolled back once the last clone is dropped, so
+  /// tests and dry runs leave nothing behind.
+  pub async fn rolled_back(pool: &PgPool) -> Result<Self, sqlx::Error> {
+    let mut connection = db_session::dedicated(pool, None).await?;
+    PgTransactionManager::begin(&mut connection, None).await?;
+    Ok(Self::Pinned(Arc::new(Mutex::new(connection))))
+  }
+
+  /// Whether queries may be routed elsewhere, e.g. to a read replica. Pinned executors must see
+  /// their own uncommitted writes.
+  pub fn is_pooled(&self) -> bool {
+    matches!(self, Self::Pool(_))
+  }
+
+  /// Runs `op` as a unit of work that is replayed on transient failures (see `db_resilience`).
+  /// Inside an outer transaction, or on a pinned executor, `op` only runs once.
+  pub async fn retry<T, F, Fut>(&self, op: F) -> Result<T, sqlx::Error>
+  where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+  {
+    db_resilience::retry(self.is_pooled() && !db_session::in_transaction(), op).await
+  }
+
+  /// The connection to run the next query on, usable as an executor via `&mut *conn`.
+  pub async fn acquire(&self) -> Result<DbConnection, sqlx::Error> {
+    match self {
+      Self::Pool(pool) => db_session::acquire(pool).await,
+      Self::Pinned(connection) => {
+        let mut connection = connection.clone().lock_owned().await;
+        db_session::adopt_current_settings(&mut connection).await?;
+        Ok(DbConnection::Pinned(connection))
+      }
+    }
+  }
+}
+
+impl From<PgPool> for DbExecutor {
+  fn from(pool: PgPool) -> Self {
+    Self::Pool(pool)
+  }
+}
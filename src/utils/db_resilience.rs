@@ -0,0 +1,174 @@
+//! Retries and a circuit breaker for transient database failures.
+//!
+//! Every connection `db_session` hands out is taken from the pool through `acquire`, which
+//! retries refused or dropped connections with jittered exponential backoff. Once
+//! `circuit_failure_threshold` acquisitions in a row have failed the circuit opens, and for
+//! `circuit_open_secs` requests fail at once with a 503 instead of each one waiting out the
+//! pool's acquire timeout. After that acquisitions are let through again to probe the database:
+//! the first success closes the circuit and the first failure reopens it.
+//!
+//! Self-contained units of work, such as a repository's own transaction, can additionally be
+//! replayed with `retry` when they fail with a serialization failure or a deadlock.
+//!
+//! The database is shared by the whole process, so the state is too: `install` applies the
+//! configuration at startup and the defaults are used until then (CLI commands, tests).
+
+use std::{
+  future::Future,
+  io,
+  sync::{Mutex, OnceLock},
+  time::{Duration, Instant},
+};
+
+use rand::Rng;
+use sqlx::{PgPool, Postgres, pool::PoolConnection};
+use tracing::{info, warn};
+
+use crate::config::DbResilienceConfig;
+
+static RESILIENCE: OnceLock<DbResilience> = OnceLock::new();
+
+struct DbResilience {
+  config: DbResilienceConfig,
+  breaker: Mutex<Breaker>,
+}
+
+#[derive(Default)]
+struct Breaker {
+  consecutive_failures: u32,
+  open_until: Option<Instant>,
+}
+
+/// Applies `config` for the rest of the process. Only the first call has an effect.
+pub fn install(config: &DbResilienceConfig) {
+  let _ = RESILIENCE.set(DbResilience::new(config.clone()));
+}
+
+fn current() -> &'static DbResilience {
+  RESILIENCE.get_or_init(|| DbResilience::new(DbResilienceConfig::default()))
+}
+
+/// Takes a connection from `pool`, retrying connection failures while the circuit is closed.
+pub async fn acquire(pool: &PgPool) -> Result<PoolConnection<Postgres>, sqlx::Error> {
+  let resilience = current();
+  let mut attempt = 1;
+  loop {
+    resilience.check_circuit()?;
+    match pool.acquire().await {
+      Ok(connection) => {
+        resilience.record_success();
+        return Ok(connection);
+      }
+      Err(e) if is_connection_error(&e) => {
+        resilience.record_failure();
+        // The pool has already waited its acquire timeout; waiting again only piles up requests
+        if attempt >= resilience.config.retry_attempts || matches!(e, sqlx::Error::PoolTimedOut) {
+          return Err(e);
+        }
+        warn!("Database connection failed (attempt {}), retrying: {}", attempt, e);
+        tokio::time::sleep(resilience.backoff(attempt)).await;
+        attempt += 1;
+      }
+      Err(e) => return Err(e),
+    }
+  }
+}
+
+/// Runs `op`, replaying it while it fails with a transient error: a dropped connection, a
+/// serialization failure or a deadlock. `op` must be safe to run again from the start, so callers
+/// inside an outer transaction (whose work a replay would not repeat) pass `replayable: false`.
+pub async fn retry<T, F, Fut>(replayable: bool, mut op: F) -> Result<T, sqlx::Error>
+where
+  F: FnMut() -> Fut,
+  Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+  let resilience = current();
+  let mut attempt = 1;
+  loop {
+    match op().await {
+      Err(e) if replayable && attempt < resilience.config.retry_attempts && is_transient(&e) => {
+        warn!("Transient database error (attempt {}), retrying: {}", attempt, e);
+        tokio::time::sleep(resilience.backoff(attempt)).await;
+        attempt += 1;
+      }
+      result => return result,
+    }
+  }
+}
+
+/// Whether `err` means the database could not be reached, as opposed to a failing query.
+pub fn is_connection_error(err: &sqlx::Error) -> bool {
+  match err {
+    sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::PoolTimedOut => true,
+    // Class 08 is connection exceptions; 57P01-57P03 are shutdowns and startups
+    sqlx::Error::Database(db_err) => db_err
+      .code()
+      .is_some_and(|code| code.starts_with("08") || matches!(code.as_ref(), "57P01" | "57P02" | "57P03")),
+    _ => false,
+  }
+}
+
+/// Whether running the failed operation again may succeed.
+pub fn is_transient(err: &sqlx::Error) -> bool {
+  match err {
+    sqlx::Error::PoolTimedOut => false,
+    sqlx::Error::Database(db_err) if matches!(db_err.code().as_deref(), Some("40001" | "40P01")) => true,
+    _ => is_connection_error(err),
+  }
+}
+
+impl DbResilience {
+  fn new(config: DbResilienceConfig) -> Self {
+    Self {
+      config,
+      breaker: Mutex::new(Breaker::default()),
+    }
+  }
+
+  fn check_circuit(&self) -> Result<(), sqlx::Error> {
+    let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+    match breaker.open_until {
+      Some(until) if Instant::now() < until => Err(sqlx::Error::Io(io::Error::new(
+        io::ErrorKind::ConnectionRefused,
+        "database circuit breaker is open",
+      ))),
+      Some(_) => {
+        // Half-open: let this attempt probe the database; another failure reopens the circuit
+        breaker.open_until = None;
+        Ok(())
+      }
+      None => Ok(()),
+    }
+  }
+
+  fn record_success(&self) {
+    let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+    if breaker.consecutive_failures >= self.config.circuit_failure_threshold && self.config.circuit_failure_threshold > 0 {
+      info!("✅ Database reachable again, closing the circuit");
+    }
+    *breaker = Breaker::default();
+  }
+
+  fn record_failure(&self) {
+    let threshold = self.config.circuit_failure_threshold;
+    let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+    breaker.consecutive_failures = breaker.consecutive_failures.saturating_add(1);
+    if threshold > 0 && breaker.consecutive_failures >= threshold && breaker.open_until.is_none() {
+      warn!(
+        "Database unreachable after {} attempts, failing requests for {}s",
+        breaker.consecutive_failures, self.config.circuit_open_secs
+      );
+      breaker.open_until = Some(Instant::now() + Duration::from_secs(self.config.circuit_open_secs));
+    }
+  }
+
+  /// Exponential backoff with jitter, so retrying callers do not hit the database in lockstep.
+  fn backoff(&self, attempt: u32) -> Duration {
+    let delay = self
+      .config
+      .retry_base_delay_ms
+      .saturating_mul(1 << (attempt - 1).min(16))
+      .min(self.config.retry_max_delay_ms);
+    Duration::from_millis(delay / 2 + rand::thread_rng().gen_range(0..=delay / 2))
+  }
+}
@@ -0,0 +1,341 @@
+//! Request-scoped database connections for Row Level Security.
+//!
+//! The RLS policies read the `app.current_*` session variables, which only exist on the
+//! connection they were set on. `jwt_middleware` therefore acquires one connection per request,
+//! sets the variables there and runs the handler inside `scope`; repositories obtain their
+//! connections through `acquire` (via their `DbExecutor`), which hands out that connection while
+//! the request runs and a plain pooled connection anywhere else (CLI commands, background tasks). The variables are
+//! cleared before the connection goes back to the pool.
+//!
+//! Background jobs working on one workspace run in `workspace_scope` instead, which routes their
+//! queries to the workspace's tables without setting any variables (see `utils::tenant_schema`).
+//!
+//! Since every query of a request runs on that one connection, `transaction` only has to open a
+//! transaction on it for all repository calls made in between to become atomic. While the
+//! connection is in use, by a concurrent query of the request or by a caller still holding it, a
+//! query outside a transaction runs on a pooled connection with the same variables instead.
+
+use std::{
+  future::Future,
+  ops::{Deref, DerefMut},
+  sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+  },
+};
+
+use sqlx::{PgConnection, PgPool, Postgres, TransactionManager, pool::PoolConnection, postgres::PgTransactionManager};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tracing::warn;
+use uuid::Uuid;
+
+use super::{database_ext::PostgresSessionExt, db_resilience, tenant_schema};
+use crate::modules::datastores::workspaces::workspace_models::WorkspaceRole;
+
+tokio::task_local! {
+  static REQUEST_SESSION: RequestSession;
+}
+
+/// The caller a request's session variables are set for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionSettings {
+  pub user_id: Uuid,
+  pub workspace_id: Option<Uuid>,
+  /// The caller's role in `workspace_id`, checked before the session is opened. Without it the
+  /// workspace variables stay unset, as for a caller who is not a member.
+  pub role: Option<WorkspaceRole>,
+}
+
+#[derive(Clone)]
+struct RequestSession {
+  /// `None` for a connection pinned by `transaction` or `workspace_scope` outside a request.
+  settings: Option<SessionSettings>,
+  /// The workspace whose tables a `workspace_scope` is routed to.
+  workspace_id: Option<Uuid>,
+  connection: Arc<Mutex<SessionBoundConnection>>,
+  /// Whether `transaction` has a transaction open on `connection`.
+  transaction_open: Arc<AtomicBool>,
+}
+
+impl RequestSession {
+  fn new(settings: Option<SessionSettings>, connection: SessionBoundConnection) -> Self {
+    Self {
+      settings,
+      workspace_id: None,
+      connection: Arc::new(Mutex::new(connection)),
+      transaction_open: Arc::new(AtomicBool::new(false)),
+    }
+  }
+
+  /// Whether queries must run on `connection`: in a transaction, or pinned by `transaction`
+  /// outside a request.
+  fn in_transaction(&self) -> bool {
+    self.settings.is_none() || self.transaction_open.load(Ordering::Acquire)
+  }
+
+  /// Waits for `connection`, at most for the acquire timeout of `pool`. A caller that already
+  /// holds it would otherwise wait forever.
+  async fn lock(&self, pool: &PgPool) -> Result<OwnedMutexGuard<SessionBoundConnection>, sqlx::Error> {
+    tokio::time::timeout(pool.options().get_acquire_timeout(), self.connection.clone().lock_owned())
+      .await
+      .map_err(|_| sqlx::Error::PoolTimedOut)
+  }
+}
+
+/// Runs `future` with a dedicated connection carrying the session variables of `settings`.
+///
+/// Every `acquire` made while `future` runs (on the same task) returns that connection, so all
+/// queries of the request see the same variables. Queries of one request are serialized on it.
+pub async fn scope<F: Future>(pool: &PgPool, settings: SessionSettings, future: F) -> Result<F::Output, sqlx::Error> {
+  let connection = bind(db_resilience::acquire(pool).await?, settings).await?;
+  let session = RequestSession::new(Some(settings), connection);
+  Ok(REQUEST_SESSION.scope(session, future).await)
+}
+
+/// Runs `future` with the tables of `workspace_id`, for work on one workspace outside of a
+/// request, such as a background job.
+///
+/// Every `acquire` made while `future` runs returns one pooled connection routed to the
+/// workspace's own schema, if it has one (see `utils::tenant_schema`), so its queries are
+/// serialized on it as in `transaction`. No session variables are set on it.
+pub async fn workspace_scope<F: Future>(pool: &PgPool, workspace_id: Uuid, future: F) -> Result<F::Output, sqlx::Error> {
+  let mut connection = SessionBoundConnection::new(db_resilience::acquire(pool).await?);
+  connection.route(workspace_id).await?;
+  let mut session = RequestSession::new(None, connection);
+  session.workspace_id = Some(workspace_id);
+  Ok(REQUEST_SESSION.scope(session, future).await)
+}
+
+/// Runs `future` in a database transaction, committing when it returns `Ok` and rolling back
+/// when it returns `Err` or is cancelled.
+///
+/// The transaction is opened on the current request's connection, so every repository call made
+/// by `future` joins it, and `ReadPool` keeps reads on the primary while it is open. Outside a
+/// request a pooled connection is pinned for the duration instead. A nested call joins the
+/// transaction that is already open.
+pub async fn transaction<F, T, E>(pool: &PgPool, future: F) -> Result<T, E>
+where
+  F: Future<Output = Result<T, E>>,
+  E: From<sqlx::Error>,
+{
+  if let Ok(session) = REQUEST_SESSION.try_with(RequestSession::clone) {
+    return run_transaction(pool, &session, future).await;
+  }
+
+  let session = RequestSession::new(None, SessionBoundConnection::new(db_resilience::acquire(pool).await?));
+  REQUEST_SESSION.scope(session.clone(), run_transaction(pool, &session, future)).await
+}
+
+async fn run_transaction<F, T, E>(pool: &PgPool, session: &RequestSession, future: F) -> Result<T, E>
+where
+  F: Future<Output = Result<T, E>>,
+  E: From<sqlx::Error>,
+{
+  {
+    let mut connection = session.lock(pool).await?;
+    if PgTransactionManager::get_transaction_depth(&connection) > 0 {
+      drop(connection);
+      return future.await;
+    }
+    PgTransactionManager::begin(&mut connection, None).await?;
+    session.transaction_open.store(true, Ordering::Release);
+  }
+
+  let result = future.await;
+
+  let mut connection = session.lock(pool).await?;
+  session.transaction_open.store(false, Ordering::Release);
+  match result {
+    Ok(value) => {
+      PgTransactionManager::commit(&mut connection).await?;
+      Ok(value)
+    }
+    Err(e) => {
+      if let Err(rollback) = PgTransactionManager::rollback(&mut connection).await {
+        warn!("Failed to roll back transaction: {}", rollback);
+      }
+      Err(e)
+    }
+  }
+}
+
+/// Whether the current request has a transaction open. Reads must then stay on its connection.
+pub fn in_transaction() -> bool {
+  REQUEST_SESSION.try_with(RequestSession::in_transaction).unwrap_or(false)
+}
+
+/// The session variables of the current request, if any. Work that outlives the request (such as
+/// a streamed response body) passes them to `scope` to query as the same caller.
+pub fn current_settings() -> Option<SessionSettings> {
+  REQUEST_SESSION.try_with(|session| session.settings).ok().flatten()
+}
+
+/// Returns the connection to run queries on: the current request's connection inside `scope`,
+/// a pooled connection without session variables otherwise.
+///
+/// When the request's connection is in use, a query outside a transaction gets a pooled
+/// connection with the request's variables. In a transaction it waits for the connection, and
+/// fails with `PoolTimedOut` after the pool's acquire timeout, as when the caller itself still
+/// holds it.
+pub async fn acquire(pool: &PgPool) -> Result<DbConnection, sqlx::Error> {
+  let Ok(session) = REQUEST_SESSION.try_with(RequestSession::clone) else {
+    return Ok(DbConnection::Pooled(db_resilience::acquire(pool).await?));
+  };
+  if let Ok(connection) = session.connection.clone().try_lock_owned() {
+    return Ok(DbConnection::Request(connection));
+  }
+  match session.settings {
+    Some(settings) if !session.in_transaction() => Ok(DbConnection::Bound(bind(db_resilience::acquire(pool).await?, settings).await?)),
+    _ => Ok(DbConnection::Request(session.lock(pool).await?)),
+  }
+}
+
+/// Acquires a connection from another pool (such as the read replica) and applies the current
+/// request's session variables to it, if any.
+pub async fn acquire_from(pool: &PgPool) -> Result<DbConnection, sqlx::Error> {
+  let connection = db_resilience::acquire(pool).await?;
+  match REQUEST_SESSION.try_with(|session| session.settings).ok().flatten() {
+    Some(settings) => Ok(DbConnection::Bound(bind(connection, settings).await?)),
+    None => Ok(DbConnection::Pooled(connection)),
+  }
+}
+
+/// Takes a connection out of `pool` for one caller's exclusive use, with the session variables of
+/// `settings` applied if given. Like a request's connection, it is cleared (and any transaction
+/// left open rolled back) when dropped.
+pub async fn dedicated(pool: &PgPool, settings: Option<SessionSettings>) -> Result<SessionBoundConnection, sqlx::Error> {
+  let connection = db_resilience::acquire(pool).await?;
+  match settings {
+    Some(settings) => bind(connection, settings).await,
+    None => Ok(SessionBoundConnection::new(connection)),
+  }
+}
+
+async fn bind(connection: PoolConnection<Postgres>, settings: SessionSettings) -> Result<SessionBoundConnection, sqlx::Error> {
+  let mut connection = SessionBoundConnection::new(connection);
+  connection.apply(settings).await?;
+  Ok(connection)
+}
+
+/// Gives a connection pinned outside of the request the current request's session variables, so
+/// queries on it are subject to Row Level Security as the caller, or the tables of the current
+/// `workspace_scope`. Outside of both it keeps the variables and tables it has.
+pub(crate) async fn adopt_current_settings(connection: &mut SessionBoundConnection) -> Result<(), sqlx::Error> {
+  let Ok((settings, workspace_id)) = REQUEST_SESSION.try_with(|session| (session.settings, session.workspace_id)) else {
+    return Ok(());
+  };
+  match (settings, workspace_id) {
+    (Some(settings), _) if connection.settings != Some(settings) => connection.apply(settings).await,
+    (None, Some(workspace_id)) if connection.workspace_id != Some(workspace_id) => connection.route(workspace_id).await,
+    _ => Ok(()),
+  }
+}
+
+/// A database connection handed out by `acquire`, usable as an executor via `&mut *conn`.
+pub enum DbConnection {
+  Request(OwnedMutexGuard<SessionBoundConnection>),
+  Pinned(OwnedMutexGuard<SessionBoundConnection>),
+  Bound(SessionBoundConnection),
+  Pooled(PoolConnection<Postgres>),
+}
+
+impl Deref for DbConnection {
+  type Target = PgConnection;
+
+  fn deref(&self) -> &PgConnection {
+    match self {
+      DbConnection::Request(guard) | DbConnection::Pinned(guard) => guard,
+      DbConnection::Bound(connection) => connection,
+      DbConnection::Pooled(connection) => connection,
+    }
+  }
+}
+
+impl DerefMut for DbConnection {
+  fn deref_mut(&mut self) -> &mut PgConnection {
+    match self {
+      DbConnection::Request(guard) | DbConnection::Pinned(guard) => &mut *guard,
+      DbConnection::Bound(connection) => &mut *connection,
+      DbConnection::Pooled(connection) => &mut *connection,
+    }
+  }
+}
+
+/// A pooled connection with session variables set, cleared again when it is dropped, together
+/// with any transaction `transaction` left open.
+pub struct SessionBoundConnection {
+  connection: Option<PoolConnection<Postgres>>,
+  settings: Option<SessionSettings>,
+  /// The workspace whose tables the connection is routed to.
+  workspace_id: Option<Uuid>,
+}
+
+impl SessionBoundConnection {
+  fn new(connection: PoolConnection<Postgres>) -> Self {
+    Self {
+      connection: Some(connection),
+      settings: None,
+      workspace_id: None,
+    }
+  }
+
+  async fn apply(&mut self, settings: SessionSettings) -> Result<(), sqlx::Error> {
+    let workspace = settings.workspace_id.zip(settings.role);
+    self
+      .set_session_settings(&settings.user_id, workspace.as_ref().map(|(workspace_id, role)| (workspace_id, *role)))
+      .await?;
+    self.settings = Some(settings);
+    self.workspace_id = workspace.map(|(workspace_id, _)| workspace_id);
+    Ok(())
+  }
+
+  async fn route(&mut self, workspace_id: Uuid) -> Result<(), sqlx::Error> {
+    tenant_schema::route(self, Some(workspace_id)).await?;
+    self.workspace_id = Some(workspace_id);
+    Ok(())
+  }
+}
+
+impl Deref for SessionBoundConnection {
+  type Target = PgConnection;
+
+  fn deref(&self) -> &PgConnection {
+    self.connection.as_ref().expect("connection is only taken on drop")
+  }
+}
+
+impl DerefMut for SessionBoundConnection {
+  fn deref_mut(&mut self) -> &mut PgConnection {
+    self.connection.as_mut().expect("connection is only taken on drop")
+  }
+}
+
+impl Drop for SessionBoundConnection {
+  fn drop(&mut self) {
+    let Some(mut connection) = self.connection.take() else {
+      return;
+    };
+
+    // Clearing needs a round trip, so it runs in the background; a connection that cannot be
+    // cleared is closed instead of going back to the pool with another user's variables.
+    match tokio::runtime::Handle::try_current() {
+      Ok(runtime) => {
+        runtime.spawn(async move {
+          // A transaction abandoned by a cancelled request is rolled back first, savepoints included
+          while PgTransactionManager::get_transaction_depth(&connection) > 0 {
+            if let Err(e) = PgTransactionManager::rollback(&mut connection).await {
+              warn!("Failed to roll back abandoned transaction, closing connection: {}", e);
+              connection.close_on_drop();
+              return;
+            }
+          }
+          if let Err(e) = connection.clear_session_settings().await {
+            warn!("Failed to clear session settings, closing connection: {}", e);
+            connection.close_on_drop();
+          }
+        });
+      }
+      Err(_) => connection.close_on_drop(),
+    }
+  }
+}
@@ -0,0 +1,154 @@
+//! Application-level encryption of sensitive columns, such as the tax ID and bank details of
+//! contacts.
+//!
+//! Values are encrypted with AES-256-GCM before they are written and decrypted after they are
+//! read, so the database (and its backups) only ever hold ciphertext. Each value is stored as
+//! `enc:v1:{key_id}:{base64(nonce || ciphertext)}` and is bound to its column, so a value copied
+//! into another column does not decrypt.
+//!
+//! The keys come from `FIELD_ENCRYPTION_KEYS`, which can be kept in Vault or AWS Secrets Manager
+//! like the other secrets. The first key encrypts; all of them decrypt. To rotate, put a new key
+//! in front, roll it out, run the `reencrypt-fields` subcommand, then drop the old key.
+
+use std::fmt;
+
+use aes_gcm::{
+  Aes256Gcm, KeyInit, Nonce,
+  aead::{Aead, AeadCore, OsRng, Payload},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::{AppResult, config::EncryptionConfig, internal_error};
+
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+struct FieldKey {
+  id: String,
+  cipher: Aes256Gcm,
+}
+
+/// The keys sensitive columns are encrypted with, the current one first.
+#[derive(Default)]
+pub struct FieldCipher {
+  keys: Vec<FieldKey>,
+}
+
+impl fmt::Debug for FieldCipher {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let ids: Vec<&str> = self.keys.iter().map(|key| key.id.as_str()).collect();
+    f.debug_struct("FieldCipher").field("key_ids", &ids).finish()
+  }
+}
+
+impl FieldCipher {
+  /// A cipher without keys: reading and writing sensitive fields fails, all other columns work.
+  pub fn disabled() -> Self {
+    Self::default()
+  }
+
+  /// Parses the `{key_id}:{base64 key}` entries of `config`, each key being 32 random bytes.
+  pub fn from_config(config: &EncryptionConfig) -> AppResult<Self> {
+    let mut keys: Vec<FieldKey> = Vec::with_capacity(config.keys.len());
+    for entry in &config.keys {
+      let (id, key) = entry
+        .split_once(':')
+        .ok_or_else(|| internal_error!("Field encryption keys must be written as key_id:base64_key"))?;
+      let id = id.trim();
+      if id.is_empty() || keys.iter().any(|known| known.id == id) {
+        return Err(internal_error!("Field encryption key ids must be unique and not empty"));
+      }
+      let key = STANDARD
+        .decode(key.trim())
+        .ok()
+        .filter(|key| key.len() == KEY_LEN)
+        .ok_or_else(|| internal_error!("Field encryption key '{}' must be {} bytes encoded in base64", id, KEY_LEN))?;
+      keys.push(FieldKey {
+        id: id.to_string(),
+        cipher: Aes256Gcm::new_from_slice(&key).map_err(|_| internal_error!("Invalid field encryption key '{}'", id))?,
+      });
+    }
+    Ok(Self { keys })
+  }
+
+  /// Generates a new random key, base64 encoded as expected in `FIELD_ENCRYPTION_KEYS`.
+  pub fn generate_key() -> String {
+    STANDARD.encode(Aes256Gcm::generate_key(OsRng))
+  }
+
+  /// Whether a key is configured to encrypt with.
+  pub fn is_enabled(&self) -> bool {
+    !self.keys.is_empty()
+  }
+
+  /// Encrypts `value` of `column` with the current key.
+  pub fn encrypt(&self, column: &str, value: &str) -> AppResult<String> {
+    let key = self
+      .keys
+      .first()
+      .ok_or_else(|| internal_error!("FIELD_ENCRYPTION_KEYS must be set to store {}", column))?;
+    let nonce = Aes256Gcm::generate_nonce(OsRng);
+    let ciphertext = key
+      .cipher
+      .encrypt(
+        &nonce,
+        Payload {
+          msg: value.as_bytes(),
+          aad: column.as_bytes(),
+        },
+      )
+      .map_err(|_| internal_error!("Failed to encrypt {}", column))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}:{}", PREFIX, key.id, STANDARD.encode(sealed)))
+  }
+
+  /// Decrypts a value of `column` written by `encrypt`, with whichever key encrypted it.
+  pub fn decrypt(&self, column: &str, stored: &str) -> AppResult<String> {
+    let (key_id, sealed) = stored
+      .strip_prefix(PREFIX)
+      .and_then(|rest| rest.split_once(':'))
+      .ok_or_else(|| internal_error!("Value of {} is not encrypted", column))?;
+    let key = self
+      .keys
+      .iter()
+      .find(|key| key.id == key_id)
+      .ok_or_else(|| internal_error!("Value of {} is encrypted with unknown key '{}'", column, key_id))?;
+    let sealed = STANDARD
+      .decode(sealed)
+      .ok()
+      .filter(|sealed| sealed.len() > NONCE_LEN)
+      .ok_or_else(|| internal_error!("Value of {} is corrupted", column))?;
+
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let plaintext = key
+      .cipher
+      .decrypt(
+        Nonce::from_slice(nonce),
+        Payload {
+          msg: ciphertext,
+          aad: column.as_bytes(),
+        },
+      )
+      .map_err(|_| internal_error!("Failed to decrypt {} with key '{}'", column, key_id))?;
+    String::from_utf8(plaintext).map_err(|_| internal_error!("Value of {} is not valid UTF-8", column))
+  }
+
+  /// `encrypt` for optional columns.
+  pub fn encrypt_opt(&self, column: &str, value: Option<&str>) -> AppResult<Option<String>> {
+    value.map(|value| self.encrypt(column, value)).transpose()
+  }
+
+  /// `decrypt` for optional columns.
+  pub fn decrypt_opt(&self, column: &str, stored: Option<&str>) -> AppResult<Option<String>> {
+    stored.map(|stored| self.decrypt(column, stored)).transpose()
+  }
+
+  /// The stored-value prefix of the current key, for finding the values still encrypted with
+  /// older keys in SQL.
+  pub fn current_prefix(&self) -> Option<String> {
+    self.keys.first().map(|key| format!("{}{}:", PREFIX, key.id))
+  }
+}
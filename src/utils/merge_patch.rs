@@ -0,0 +1,98 @@
+//! JSON Merge Patch (RFC 7396) support for `PATCH` endpoints.
+//!
+//! Unlike the COALESCE-based `PUT` updates, a merge patch distinguishes a missing member
+//! (leave the field alone) from an explicit `null` (clear the field). Handlers load the current
+//! record, project it onto its editable fields, apply the patch and write every field back.
+
+use axum::{
+  async_trait,
+  body::Bytes,
+  extract::{FromRequest, Request},
+  http::header::CONTENT_TYPE,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::{AppResult, errors::AppError};
+
+/// Media type registered for merge patch documents.
+pub const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+
+/// Extractor for a merge patch request body.
+///
+/// Accepts `application/merge-patch+json` as well as plain `application/json`, and
+/// requires the document to be a JSON object.
+#[derive(Debug, Clone)]
+pub struct MergePatch(pub Value);
+
+#[async_trait]
+impl<S> FromRequest<S> for MergePatch
+where
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+    let content_type = request
+      .headers()
+      .get(CONTENT_TYPE)
+      .and_then(|value| value.to_str().ok())
+      .unwrap_or_default()
+      .to_string();
+
+    if !content_type.starts_with(MERGE_PATCH_CONTENT_TYPE) && !content_type.starts_with("application/json") {
+      return Err(AppError::BadRequest(format!(
+        "Expected Content-Type '{}' or 'application/json'",
+        MERGE_PATCH_CONTENT_TYPE
+      )));
+    }
+
+    let body = Bytes::from_request(request, state)
+      .await
+      .map_err(|rejection| AppError::BadRequest(rejection.body_text()))?;
+    let patch: Value = serde_json::from_slice(&body).map_err(|e| AppError::BadRequest(format!("Invalid JSON in patch document: {}", e)))?;
+
+    if !patch.is_object() {
+      return Err(AppError::BadRequest("Merge patch document must be a JSON object".to_string()));
+    }
+
+    Ok(MergePatch(patch))
+  }
+}
+
+/// Applies `patch` to `target` following RFC 7396.
+///
+/// Object members are merged recursively, `null` removes a member and any other value
+/// (including arrays) replaces the target value wholesale.
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+  let Value::Object(patch_members) = patch else {
+    *target = patch.clone();
+    return;
+  };
+
+  if !target.is_object() {
+    *target = Value::Object(serde_json::Map::new());
+  }
+  if let Value::Object(target_members) = target {
+    for (key, value) in patch_members {
+      if value.is_null() {
+        target_members.remove(key);
+      } else {
+        merge_patch(target_members.entry(key.clone()).or_insert(Value::Null), value);
+      }
+    }
+  }
+}
+
+/// Applies a merge patch to a typed value and deserializes the result.
+///
+/// Removed members deserialize as `None` for optional fields; removing a required field, or
+/// adding an unknown one to a `deny_unknown_fields` type, is reported as `BadRequest`.
+pub fn apply_merge_patch<T>(current: &T, patch: &Value) -> AppResult<T>
+where
+  T: Serialize + DeserializeOwned,
+{
+  let mut document = serde_json::to_value(current)?;
+  merge_patch(&mut document, patch);
+  serde_json::from_value(document).map_err(|e| AppError::BadRequest(format!("Invalid patch: {}", e)))
+}
@@ -1,5 +1,22 @@
 pub mod code_generator;
 pub mod database_ext;
+pub mod datastore_macro;
+pub mod db_executor;
+pub mod db_resilience;
+pub mod db_session;
+pub mod field_encryption;
+pub mod merge_patch;
+pub mod ndjson;
 pub mod next_code_macro;
+pub mod paginated_repository;
+pub mod pagination;
+pub mod read_pool;
+pub mod search_index;
+pub mod task_health;
+pub mod tax_id;
+pub mod tenant_schema;
 
 pub use database_ext::PostgresSessionExt;
+pub use db_executor::DbExecutor;
+pub use read_pool::ReadPool;
+pub use task_health::TaskHealth;
@@ -0,0 +1,126 @@
+//! Newline-delimited JSON (`application/x-ndjson`) list responses.
+//!
+//! Clients doing a full sync can ask a list endpoint for `Accept: application/x-ndjson` and
+//! receive every matching row as one JSON object per line, streamed from the database cursor
+//! instead of collected into a page first.
+//!
+//! The rows are produced on a spawned task: the request's connection is released once the
+//! handler returns, before the body has been sent, so the task opens its own `db_session::scope`
+//! with the caller's session variables. A bounded channel applies backpressure, so a slow client
+//! pauses the query instead of buffering the result set in memory.
+
+use std::{convert::Infallible, future::Future};
+
+use axum::{
+  body::Body,
+  http::{HeaderMap, HeaderValue, header},
+  response::{IntoResponse, Response},
+};
+use futures_util::stream;
+use serde::Serialize;
+use serde_json::json;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+
+use super::db_session;
+use crate::{AppResult, errors::AppError};
+
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Rows buffered between the database and the client.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Receives the rows of a streamed list response.
+pub type RowSender<T> = mpsc::Sender<AppResult<T>>;
+
+/// Whether the client asked for an NDJSON response.
+pub fn accepts_ndjson(headers: &HeaderMap) -> bool {
+  headers
+    .get_all(header::ACCEPT)
+    .iter()
+    .filter_map(|value| value.to_str().ok())
+    .flat_map(|value| value.split(','))
+    .any(|media_type| {
+      media_type
+        .split(';')
+        .next()
+        .is_some_and(|m| m.trim().eq_ignore_ascii_case(NDJSON_CONTENT_TYPE))
+    })
+}
+
+/// Streams the rows `produce` sends as an NDJSON response, each converted to `R` first.
+///
+/// `produce` runs on its own task and connection, as the current caller. It should stop once
+/// sending fails, which means the client went away. If it fails, the error is logged and the
+/// response ends with a single `{"error": ...}` line, since the status has already been sent.
+pub fn stream<T, R, F, Fut>(pool: PgPool, produce: F) -> Response
+where
+  T: Send + 'static,
+  R: From<T> + Serialize + 'static,
+  F: FnOnce(RowSender<T>) -> Fut + Send + 'static,
+  Fut: Future<Output = AppResult<()>> + Send + 'static,
+{
+  stream_with(pool, R::from, produce)
+}
+
+/// Like `stream`, converting each row with `convert`, e.g. to leave out the fields hidden from
+/// the caller.
+pub fn stream_with<T, R, C, F, Fut>(pool: PgPool, convert: C, produce: F) -> Response
+where
+  T: Send + 'static,
+  R: Serialize,
+  C: Fn(T) -> R + Send + 'static,
+  F: FnOnce(RowSender<T>) -> Fut + Send + 'static,
+  Fut: Future<Output = AppResult<()>> + Send + 'static,
+{
+  let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+  let settings = db_session::current_settings();
+
+  tokio::spawn(async move {
+    let errors = tx.clone();
+    let result = match settings {
+      Some(settings) => db_session::scope(&pool, settings, produce(tx))
+        .await
+        .map_err(AppError::from)
+        .and_then(|result| result),
+      None => produce(tx).await,
+    };
+    if let Err(e) = result {
+      let _ = errors.send(Err(e)).await;
+    }
+  });
+
+  let body = stream::unfold((rx, convert), |(mut rx, convert)| async move {
+    let row = rx.recv().await?;
+    let line = encode(row.map(&convert));
+    Some((Ok::<_, Infallible>(line), (rx, convert)))
+  });
+
+  (
+    [(header::CONTENT_TYPE, HeaderValue::from_static(NDJSON_CONTENT_TYPE))],
+    Body::from_stream(body),
+  )
+    .into_response()
+}
+
+fn encode<T: Serialize>(row: AppResult<T>) -> Vec<u8> {
+  let mut line = match row.map(|row| serde_json::to_vec(&row)) {
+    Ok(Ok(line)) => line,
+    Ok(Err(e)) => {
+      tracing::error!("Failed to serialize streamed row: {}", e);
+      error_line()
+    }
+    Err(e) => {
+      tracing::error!("Streamed list response failed: {}", e);
+      error_line()
+    }
+  };
+  line.push(b'\n');
+  line
+}
+
+fn error_line() -> Vec<u8> {
+  json!({ "error": "STREAM_FAILED", "message": "The response was cut short by a server error" })
+    .to_string()
+    .into_bytes()
+}
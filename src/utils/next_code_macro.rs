@@ -7,15 +7,17 @@ macro_rules! impl_next_code_handler {
         $module_name:literal,
         $config:expr
     ) => {
-    /// Get the next available code for this module based on name
+    /// Get the next available code for this module based on name, in the format the workspace
+    /// set for the module's table and the record's category, if any
     #[axum::debug_handler]
     pub async fn $handler_name(
       State(state): State<Arc<AppState>>,
-      current_user: CurrentUser,
-      WorkspaceContext(workspace_id): WorkspaceContext,
-      Query(params): Query<NextCodeQuery>,
+      _current_user: CurrentUser,
+      RequiredWorkspace(workspace_id): RequiredWorkspace,
+      _member: $crate::helper::RequireRole<$crate::helper::workspace::role::Member>,
+      $crate::helper::ValidatedQuery(params): $crate::helper::ValidatedQuery<NextCodeQuery>,
     ) -> AppResult<Json<ApiResponse<String>>> {
-      use crate::utils::code_generator::CodeGenerator;
+      use $crate::utils::code_generator::CodeGenerator;
 
       tracing::debug!(
         "Getting next available {} code for name: '{}' in workspace: {}",
@@ -24,19 +26,15 @@ macro_rules! impl_next_code_handler {
         workspace_id
       );
 
-      // Validate workspace access
-      let workspace_repository = &state.workspace_repository;
-      if !check_workspace_permission(workspace_repository, workspace_id, current_user.user_id, WorkspaceRole::Member).await? {
-        return Err(AppError::Authorization(format!(
-          "You don't have permission to access {} in this workspace",
-          $module_name
-        )));
-      }
+      let settings = state.workspace_settings_repository.get(workspace_id).await?;
+      let config = $config;
+      let rules = settings.code_rules.get(&config.table_name).cloned();
+      let config = config.with_rules(rules.as_ref(), params.category.as_deref());
 
       // Generate next code using the shared utility
       // Access the database pool directly from AppState
       let code_generator = CodeGenerator::new(state.db.clone());
-      let next_code = code_generator.get_next_available_code(&$config, &params.name, Some(workspace_id)).await?;
+      let next_code = code_generator.get_next_available_code(&config, &params.name, Some(workspace_id)).await?;
 
       tracing::debug!("Next available {} code: {} for name: '{}'", $module_name, next_code, params.name);
 
@@ -47,7 +45,9 @@ macro_rules! impl_next_code_handler {
 }
 
 /// Query parameters for next code request
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Deserialize, validator::Validate)]
 pub struct NextCodeQuery {
   pub name: String,
+  /// The category of the record, for workspaces giving categories their own prefix.
+  pub category: Option<String>,
 }
@@ -0,0 +1,133 @@
+//! Paginated, filtered and sorted listing shared by the datastore repositories.
+//!
+//! A repository implements `PaginatedRepository` with the entity-specific parts only: the
+//! table, the selected columns, how its filters become conditions and which column a filter set
+//! sorts by. Workspace scoping, the page and count queries and running them (see
+//! `utils::pagination`) come from the default methods.
+
+use async_trait::async_trait;
+use sea_query::{Alias, ColumnRef, DynIden, Expr, Order, PostgresQueryBuilder, Query, SelectStatement};
+use sea_query_binder::{SqlxBinder, SqlxValues};
+use sqlx::{FromRow, postgres::PgRow};
+use uuid::Uuid;
+
+use super::{
+  ReadPool,
+  pagination::{self, Counted, TOTAL_COUNT_COLUMN},
+};
+use crate::{AppResult, errors::AppError};
+
+/// SQL text with `$n` placeholders and the values to bind to them.
+pub type BoundQuery = (String, SqlxValues);
+
+/// Lists rows of type `T` of one workspace, matching filters of type `F`.
+#[async_trait]
+pub trait PaginatedRepository<T, F>: Send + Sync
+where
+  T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+  F: Sync,
+{
+  /// The table rows are listed from; it needs a `workspace_id` column.
+  fn table() -> DynIden;
+
+  /// The columns selected for each row; every column of the table by default.
+  fn columns() -> Vec<ColumnRef> {
+    vec![ColumnRef::TableAsterisk(Self::table())]
+  }
+
+  /// Adds the conditions of `filters` to `query`.
+  fn apply_filters(query: &mut SelectStatement, filters: &F);
+
+  /// The column of `table()` and the direction rows are ordered by.
+  fn sort(filters: &F) -> (DynIden, Order);
+
+  /// The pool the list queries run on.
+  fn read_pool(&self) -> &ReadPool;
+
+  /// Restricts the query to the workspace, provided the user is a member of it. Membership is
+  /// checked once through an uncorrelated `EXISTS` instead of being joined onto every row.
+  fn apply_scope(query: &mut SelectStatement, workspace_id: Uuid, user_id: Uuid) {
+    query
+      .and_where(Expr::col((Self::table(), Alias::new("workspace_id"))).eq(workspace_id))
+      .and_where(Expr::exists(
+        Query::select()
+          .expr(Expr::val(1))
+          .from(Alias::new("workspace_users"))
+          .and_where(Expr::col((Alias::new("workspace_users"), Alias::new("workspace_id"))).eq(workspace_id))
+          .and_where(Expr::col((Alias::new("workspace_users"), Alias::new("user_id"))).eq(user_id))
+          .to_owned(),
+      ));
+  }
+
+  /// The scoped, filtered and sorted select, without pagination.
+  fn select_query(workspace_id: Uuid, user_id: Uuid, filters: &F) -> SelectStatement {
+    let mut query = Query::select();
+    query.columns(Self::columns()).from(Self::table());
+    Self::apply_scope(&mut query, workspace_id, user_id);
+    Self::apply_filters(&mut query, filters);
+
+    let (column, order) = Self::sort(filters);
+    query.order_by((Self::table(), column), order);
+    query
+  }
+
+  /// Builds the page query, which also selects the total row count, and a count query for pages
+  /// past the end. Filter values are never interpolated into the SQL text; they are returned
+  /// separately to be bound with `sqlx::query_with`.
+  fn build_page_query(workspace_id: Uuid, user_id: Uuid, filters: &F, limit: u64, offset: u64) -> (BoundQuery, BoundQuery) {
+    let select = Self::select_query(workspace_id, user_id, filters)
+      .expr_as(Expr::cust("COUNT(*) OVER ()"), Alias::new(TOTAL_COUNT_COLUMN))
+      .limit(limit)
+      .offset(offset)
+      .build_sqlx(PostgresQueryBuilder);
+
+    let mut count = Query::select();
+    count.expr(Expr::cust("COUNT(*)")).from(Self::table());
+    Self::apply_scope(&mut count, workspace_id, user_id);
+    Self::apply_filters(&mut count, filters);
+
+    (select, count.build_sqlx(PostgresQueryBuilder))
+  }
+
+  /// Builds a query returning every matching row in the requested order, without the total row
+  /// count, for streaming the whole result set.
+  fn build_stream_query(workspace_id: Uuid, user_id: Uuid, filters: &F) -> BoundQuery {
+    Self::select_query(workspace_id, user_id, filters).build_sqlx(PostgresQueryBuilder)
+  }
+
+  /// Returns page `page` (1-based) of `limit` rows and the total number of matching rows.
+  async fn find_page(&self, workspace_id: Uuid, user_id: Uuid, page: u32, limit: u32, filters: &F) -> AppResult<(Vec<T>, u64)> {
+    let offset = pagination::offset(page, limit);
+    let ((select_sql, select_values), (count_sql, count_values)) = Self::build_page_query(workspace_id, user_id, filters, limit as u64, offset);
+
+    tracing::debug!("Executing select query: {}", select_sql);
+
+    // The page query carries the total count, so the count query only runs past the last page
+    let mut conn = self.read_pool().acquire().await?;
+    let rows = sqlx::query_as_with::<_, Counted<T>, _>(&select_sql, select_values)
+      .fetch_all(&mut *conn)
+      .await
+      .map_err(|e| {
+        tracing::error!("Failed to execute filtered query: {}", e);
+        AppError::from_sqlx_error(e, &select_sql)
+      })?;
+
+    let (items, total_count) = pagination::into_page(rows, offset);
+    let total_count = match total_count {
+      Some(total_count) => total_count,
+      None => {
+        tracing::debug!("Executing count query: {}", count_sql);
+        sqlx::query_scalar_with::<_, i64, _>(&count_sql, count_values)
+          .fetch_one(&mut *conn)
+          .await
+          .map_err(|e| {
+            tracing::error!("Failed to execute count query: {}", e);
+            AppError::from_sqlx_error(e, &count_sql)
+          })?
+          .max(0) as u64
+      }
+    };
+
+    Ok((items, total_count))
+  }
+}
@@ -0,0 +1,49 @@
+//! Single-roundtrip pagination.
+//!
+//! List queries select `COUNT(*) OVER () AS total_count` next to the row columns, so the page
+//! and the total number of matching rows come back from one query instead of a `COUNT` query
+//! followed by a `SELECT`.
+
+use sqlx::{FromRow, Row, postgres::PgRow};
+
+/// Name of the window-function column carrying the total row count.
+pub const TOTAL_COUNT_COLUMN: &str = "total_count";
+
+/// A row of a paginated query: the mapped item plus the total row count of the result set.
+#[derive(Debug)]
+pub struct Counted<T> {
+  pub item: T,
+  pub total_count: i64,
+}
+
+impl<'r, T> FromRow<'r, PgRow> for Counted<T>
+where
+  T: FromRow<'r, PgRow>,
+{
+  fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+    Ok(Self {
+      item: T::from_row(row)?,
+      total_count: row.try_get(TOTAL_COUNT_COLUMN)?,
+    })
+  }
+}
+
+/// Number of rows to skip to reach page `page` (1-based) of `limit` rows. Computed in `u64`, so
+/// no page a client can ask for overflows it.
+pub fn offset(page: u32, limit: u32) -> u64 {
+  u64::from(page.saturating_sub(1)) * u64::from(limit)
+}
+
+/// Splits the rows of a page into its items and the total row count.
+///
+/// The total is `None` for an empty page, as there is no row to read it from. That is only
+/// ambiguous past the first page: an empty first page means nothing matched, while an empty later
+/// page may just be past the end, in which case the caller has to count separately.
+pub fn into_page<T>(rows: Vec<Counted<T>>, offset: u64) -> (Vec<T>, Option<u64>) {
+  let total = match rows.first() {
+    Some(row) => Some(row.total_count.max(0) as u64),
+    None if offset == 0 => Some(0),
+    None => None,
+  };
+  (rows.into_iter().map(|row| row.item).collect(), total)
+}
@@ -0,0 +1,89 @@
+use std::{
+  sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+  },
+  time::Duration,
+};
+
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use super::{
+  db_executor::DbExecutor,
+  db_session::{self, DbConnection},
+};
+
+/// How often the replica is probed to decide whether reads may use it.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Pool for read-only queries.
+///
+/// Routes reads to the read replica (`DATABASE_READ_URL`) while it answers health checks and
+/// falls back to the primary otherwise, or when no replica is configured. Replicas lag behind
+/// the primary, so queries that must see the caller's own latest writes (uniqueness checks,
+/// authentication, access control) should keep using the primary pool.
+#[derive(Clone)]
+pub struct ReadPool {
+  primary: DbExecutor,
+  replica: Option<Replica>,
+}
+
+#[derive(Clone)]
+struct Replica {
+  pool: PgPool,
+  healthy: Arc<AtomicBool>,
+}
+
+impl ReadPool {
+  /// A read pool without replica; every read goes to the primary.
+  pub fn primary_only(primary: impl Into<DbExecutor>) -> Self {
+    Self {
+      primary: primary.into(),
+      replica: None,
+    }
+  }
+
+  /// A read pool backed by `replica`, which is only used once a health check succeeded.
+  ///
+  /// Spawns the background health check, so it must be called from within a Tokio runtime.
+  pub fn with_replica(primary: PgPool, replica: PgPool) -> Self {
+    let healthy = Arc::new(AtomicBool::new(false));
+    tokio::spawn(monitor(replica.clone(), healthy.clone()));
+
+    Self {
+      primary: primary.into(),
+      replica: Some(Replica { pool: replica, healthy }),
+    }
+  }
+
+  /// A connection to run a read-only query on, carrying the current request's session variables.
+  /// Inside `db_session::transaction` this is the transaction's connection on the primary, and
+  /// a pinned primary executor is always used as is.
+  pub async fn acquire(&self) -> Result<DbConnection, sqlx::Error> {
+    match &self.replica {
+      Some(replica) if replica.healthy.load(Ordering::Relaxed) && !db_session::in_transaction() && self.primary.is_pooled() => {
+        db_session::acquire_from(&replica.pool).await
+      }
+      _ => self.primary.acquire().await,
+    }
+  }
+}
+
+async fn monitor(replica: PgPool, healthy: Arc<AtomicBool>) {
+  let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+  loop {
+    interval.tick().await;
+    let ok = matches!(
+      tokio::time::timeout(HEALTH_CHECK_INTERVAL, sqlx::query("SELECT 1").execute(&replica)).await,
+      Ok(Ok(_))
+    );
+
+    let was_healthy = healthy.swap(ok, Ordering::Relaxed);
+    match (was_healthy, ok) {
+      (false, true) => info!("✅ Read replica available, routing reads to it"),
+      (true, false) => warn!("Read replica unreachable, routing reads to the primary"),
+      _ => {}
+    }
+  }
+}
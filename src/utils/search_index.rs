@@ -0,0 +1,102 @@
+//! Full-text search on the `search_vector` columns of contacts and products.
+//!
+//! The columns are maintained by database triggers as rows are written, so they never need a
+//! manual reindex. Products also index the names of their category and supplier, which live in
+//! other rows: renaming one sends a `search_index` notification, and `run_refresher` recomputes
+//! the affected products in the background instead of inside the renaming request, in the tables
+//! of the renamed row's workspace (see `utils::tenant_schema`). Every instance receives the
+//! notification; the refresh is idempotent, so running it twice is only wasted work.
+
+use std::{sync::Arc, time::Duration};
+
+use sea_query::{Expr, SimpleExpr};
+use serde::Deserialize;
+use sqlx::{PgPool, postgres::PgListener};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::utils::{TaskHealth, tenant_schema};
+
+/// Channel the triggers notify when product vectors went stale.
+pub const CHANNEL: &str = "search_index";
+
+/// Name of the refresher in `TaskHealth`.
+const TASK: &str = "search_index_refresher";
+
+/// Delay before listening again after the listener failed.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Turns the words a user typed into a `to_tsquery` expression matching rows containing every
+/// word as a prefix, so results narrow down while typing. `None` if there is nothing to search for.
+pub fn prefix_tsquery(search: &str) -> Option<String> {
+  let terms = search
+    .split_whitespace()
+    .map(|word| format!("'{}':*", word.replace('\\', "\\\\").replace('\'', "''")))
+    .collect::<Vec<_>>();
+  (!terms.is_empty()).then(|| terms.join(" & "))
+}
+
+/// A condition matching the rows of `table` whose search vector matches `search`.
+pub fn matches(table: &str, search: &str) -> Option<SimpleExpr> {
+  prefix_tsquery(search).map(|query| Expr::cust_with_values(format!("{table}.search_vector @@ to_tsquery('simple', $1)"), [query]))
+}
+
+#[derive(Deserialize)]
+struct StaleSource {
+  source: String,
+  id: Uuid,
+  workspace_id: Option<Uuid>,
+}
+
+/// Listens for `search_index` notifications for the lifetime of the process and refreshes the
+/// product vectors they name. Reconnects after failures; notifications sent while disconnected
+/// are lost, so every product is refreshed then, in the shared tables and in every workspace schema.
+pub async fn run_refresher(pool: PgPool, task_health: Arc<TaskHealth>) {
+  loop {
+    if let Err(e) = listen(&pool, &task_health).await {
+      task_health.failed(TASK, &e);
+      warn!("Search index refresher failed, retrying in {:?}: {}", RETRY_DELAY, e);
+    }
+    tokio::time::sleep(RETRY_DELAY).await;
+  }
+}
+
+async fn listen(pool: &PgPool, task_health: &TaskHealth) -> Result<(), sqlx::Error> {
+  let mut listener = PgListener::connect_with(pool).await?;
+  listener.listen(CHANNEL).await?;
+  task_health.succeeded(TASK);
+  info!("🔎 Search index refresher listening on '{}'", CHANNEL);
+
+  loop {
+    match listener.try_recv().await? {
+      Some(notification) => match serde_json::from_str::<StaleSource>(notification.payload()) {
+        Ok(stale) => refresh(pool, Some(&stale.source), Some(stale.id), stale.workspace_id).await?,
+        Err(e) => warn!("Ignoring malformed search index notification '{}': {}", notification.payload(), e),
+      },
+      None => {
+        warn!("Search index refresher lost its connection, refreshing every product");
+        refresh(pool, None, None, None).await?;
+        let workspaces = tenant_schema::isolated_workspaces(&mut *pool.acquire().await?).await?;
+        for workspace_id in workspaces {
+          refresh(pool, None, None, Some(workspace_id)).await?;
+        }
+      }
+    }
+  }
+}
+
+/// Refreshes the products of `workspace_id` referencing `id` in `source`; the products of every
+/// workspace without a schema of its own when `workspace_id` has none.
+async fn refresh(pool: &PgPool, source: Option<&str>, id: Option<Uuid>, workspace_id: Option<Uuid>) -> Result<(), sqlx::Error> {
+  let refreshed: i32 = sqlx::query_scalar("SELECT refresh_product_search_vectors($1, $2, $3)")
+    .bind(source)
+    .bind(id)
+    .bind(workspace_id)
+    .fetch_one(pool)
+    .await?;
+  debug!(
+    "Refreshed the search vectors of {} products ({:?} {:?} in {:?})",
+    refreshed, source, id, workspace_id
+  );
+  Ok(())
+}
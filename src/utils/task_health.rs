@@ -0,0 +1,81 @@
+//! Health of the background tasks started by `run()`.
+//!
+//! The server has no job queue: periodic work (flushing usage counters, trial notifications,
+//! retention purges, backups, the search index refresher) runs in tasks of its own process. Each
+//! task reports the outcome of every run here, so the admin statistics can show which ones are
+//! failing. The registry is per process, like the tasks.
+
+use std::{collections::BTreeMap, fmt::Display, sync::Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// The runs of one background task since the process started.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TaskStatus {
+  pub name: &'static str,
+  pub runs: u64,
+  pub failures: u64,
+  /// Failed runs since the last successful one; the task is healthy while this is zero.
+  pub consecutive_failures: u64,
+  pub last_run_at: Option<DateTime<Utc>>,
+  pub last_success_at: Option<DateTime<Utc>>,
+  pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct TaskHealth {
+  tasks: Mutex<BTreeMap<&'static str, TaskStatus>>,
+}
+
+impl TaskHealth {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Records the outcome of one run of `name`.
+  pub fn record<T, E: Display>(&self, name: &'static str, result: &Result<T, E>) {
+    match result {
+      Ok(_) => self.succeeded(name),
+      Err(e) => self.failed(name, e),
+    }
+  }
+
+  pub fn succeeded(&self, name: &'static str) {
+    self.update(name, |status, now| {
+      status.consecutive_failures = 0;
+      status.last_success_at = Some(now);
+    });
+  }
+
+  pub fn failed(&self, name: &'static str, error: impl Display) {
+    self.update(name, |status, _| {
+      status.failures += 1;
+      status.consecutive_failures += 1;
+      status.last_error = Some(error.to_string());
+    });
+  }
+
+  fn update(&self, name: &'static str, apply: impl FnOnce(&mut TaskStatus, DateTime<Utc>)) {
+    let now = Utc::now();
+    let mut tasks = self.tasks.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let status = tasks.entry(name).or_insert_with(|| TaskStatus {
+      name,
+      ..TaskStatus::default()
+    });
+    status.runs += 1;
+    status.last_run_at = Some(now);
+    apply(status, now);
+  }
+
+  /// The tasks that ran at least once, by name.
+  pub fn snapshot(&self) -> Vec<TaskStatus> {
+    self
+      .tasks
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .values()
+      .cloned()
+      .collect()
+  }
+}
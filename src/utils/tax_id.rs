@@ -0,0 +1,177 @@
+//! Format rules for the tax identification numbers of contacts: Indonesian NPWPs and the VAT
+//! numbers of the EU and the UK.
+//!
+//! Numbers are compared without the separators they are usually written with, so
+//! `01.234.567.8-901.000` and `012345678901000` are the same NPWP. A number starting with the
+//! prefix of a country with VAT numbers (`DE`, `EL`, `GB`, …) is read as one of its VAT numbers,
+//! any other as a number of `DEFAULT_COUNTRY` unless a country is given. Only the format is
+//! checked: a number that passes may still not be registered.
+
+use serde::Serialize;
+use validator::ValidationError;
+
+/// Country of the numbers written without a VAT prefix.
+pub const DEFAULT_COUNTRY: &str = "ID";
+
+/// Formats of the VAT numbers of each country after its prefix: `9` is a digit, `A` a letter,
+/// `X` either, anything else itself. Greece writes its numbers with `EL`, Northern Ireland with `XI`.
+const VAT_FORMATS: &[(&str, &[&str])] = &[
+  ("AT", &["U99999999"]),
+  ("BE", &["0999999999", "1999999999"]),
+  ("BG", &["999999999", "9999999999"]),
+  ("CY", &["99999999A"]),
+  ("CZ", &["99999999", "999999999", "9999999999"]),
+  ("DE", &["999999999"]),
+  ("DK", &["99999999"]),
+  ("EE", &["999999999"]),
+  ("EL", &["999999999"]),
+  ("ES", &["X9999999X"]),
+  ("FI", &["99999999"]),
+  ("FR", &["XX999999999"]),
+  ("GB", &["999999999", "999999999999", "GD999", "HA999"]),
+  ("HR", &["99999999999"]),
+  ("HU", &["99999999"]),
+  ("IE", &["9999999A", "9999999AA", "9A99999A"]),
+  ("IT", &["99999999999"]),
+  ("LT", &["999999999", "999999999999"]),
+  ("LU", &["99999999"]),
+  ("LV", &["99999999999"]),
+  ("MT", &["99999999"]),
+  ("NL", &["999999999B99"]),
+  ("PL", &["9999999999"]),
+  ("PT", &["999999999"]),
+  (
+    "RO",
+    &["99", "999", "9999", "99999", "999999", "9999999", "99999999", "999999999", "9999999999"],
+  ),
+  ("SE", &["999999999901"]),
+  ("SI", &["99999999"]),
+  ("SK", &["9999999999"]),
+  ("XI", &["999999999", "999999999999", "GD999", "HA999"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxIdKind {
+  /// Nomor Pokok Wajib Pajak, 15 digits, or 16 for those derived from a national ID number.
+  Npwp,
+  Vat,
+}
+
+/// A tax ID that passed the rules of its country.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaxId {
+  /// ISO 3166-1 alpha-2 code of the issuing country, `GR` for Greece.
+  pub country: String,
+  pub kind: TaxIdKind,
+  /// The number without separators or VAT prefix.
+  pub number: String,
+  /// The number as it is usually written, e.g. `01.234.567.8-901.000` or `DE123456789`.
+  pub formatted: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TaxIdError {
+  #[error("Tax ID is empty")]
+  Empty,
+  #[error("Tax IDs of country {0} are not supported")]
+  UnsupportedCountry(String),
+  #[error("An NPWP has 15 or 16 digits")]
+  InvalidNpwp,
+  #[error("Not a valid VAT number of country {0}")]
+  InvalidVat(String),
+}
+
+/// Checks `value` against the rules of `country`, or of the country its prefix names or else
+/// `DEFAULT_COUNTRY` when none is given.
+pub fn parse(value: &str, country: Option<&str>) -> Result<TaxId, TaxIdError> {
+  let number: String = value
+    .chars()
+    .filter(|c| !matches!(c, ' ' | '.' | '-' | '/' | ','))
+    .collect::<String>()
+    .to_ascii_uppercase();
+  if number.is_empty() {
+    return Err(TaxIdError::Empty);
+  }
+
+  let country = match country {
+    Some(country) => country.to_ascii_uppercase(),
+    None => number
+      .get(..2)
+      .and_then(vat_country)
+      .map_or_else(|| DEFAULT_COUNTRY.to_string(), str::to_string),
+  };
+  if country == "ID" {
+    return parse_npwp(number.strip_prefix("ID").unwrap_or(&number));
+  }
+
+  let prefix = vat_prefix(&country).ok_or_else(|| TaxIdError::UnsupportedCountry(country.clone()))?;
+  let (_, formats) = VAT_FORMATS.iter().find(|(code, _)| *code == prefix).expect("every prefix has formats");
+  let number = number.strip_prefix(prefix).unwrap_or(&number);
+  if !formats.iter().any(|format| matches_format(number, format)) {
+    return Err(TaxIdError::InvalidVat(country));
+  }
+  Ok(TaxId {
+    formatted: format!("{}{}", prefix, number),
+    number: number.to_string(),
+    kind: TaxIdKind::Vat,
+    country,
+  })
+}
+
+/// For `#[validate(custom(function = "validate_tax_id"))]` on optional tax ID fields, checking
+/// them with the default rules of `parse`. Blank values pass, as a field left empty.
+pub fn validate_tax_id(value: &str) -> Result<(), ValidationError> {
+  if value.trim().is_empty() {
+    return Ok(());
+  }
+  parse(value, None)
+    .map(|_| ())
+    .map_err(|e| ValidationError::new("tax_id").with_message(e.to_string().into()))
+}
+
+fn parse_npwp(number: &str) -> Result<TaxId, TaxIdError> {
+  if !matches!(number.len(), 15 | 16) || !number.bytes().all(|b| b.is_ascii_digit()) {
+    return Err(TaxIdError::InvalidNpwp);
+  }
+  let formatted = match number.len() {
+    15 => format!(
+      "{}.{}.{}.{}-{}.{}",
+      &number[..2],
+      &number[2..5],
+      &number[5..8],
+      &number[8..9],
+      &number[9..12],
+      &number[12..]
+    ),
+    _ => number.to_string(),
+  };
+  Ok(TaxId {
+    country: "ID".to_string(),
+    kind: TaxIdKind::Npwp,
+    number: number.to_string(),
+    formatted,
+  })
+}
+
+/// The country of a VAT prefix.
+fn vat_country(prefix: &str) -> Option<&'static str> {
+  let (code, _) = VAT_FORMATS.iter().find(|(code, _)| *code == prefix)?;
+  Some(if *code == "EL" { "GR" } else { code })
+}
+
+/// The VAT prefix of a country.
+fn vat_prefix(country: &str) -> Option<&'static str> {
+  let country = if country == "GR" { "EL" } else { country };
+  VAT_FORMATS.iter().find(|(code, _)| *code == country).map(|(code, _)| *code)
+}
+
+fn matches_format(number: &str, format: &str) -> bool {
+  number.len() == format.len()
+    && number.chars().zip(format.chars()).all(|(c, f)| match f {
+      '9' => c.is_ascii_digit(),
+      'A' => c.is_ascii_alphabetic(),
+      'X' => c.is_ascii_alphanumeric(),
+      f => c == f,
+    })
+}
@@ -0,0 +1,390 @@
+//! Schema-per-workspace isolation, for customers with strict isolation requirements.
+//!
+//! With `TENANT_ISOLATION=schema` the contacts, contact transactions, products and categories of
+//! each workspace live in a schema of its own (`ws_<workspace id>`) holding a copy of those tables,
+//! created together with the workspace. Queries keep naming the tables without a schema: when `db_session` binds a
+//! connection to a workspace, it puts the workspace's schema first on its `search_path`, so the
+//! same queries read and write the workspace's own tables. Every other table, and workspaces
+//! without a schema of their own, stay in `public`, still separated by Row Level Security.
+//!
+//! Existing workspaces keep the shared tables until the `isolate-workspaces` command moves their
+//! rows into their schema. Work on one workspace outside of a request, such as the background
+//! jobs, runs in `db_session::workspace_scope` to be routed the same way; work going over many
+//! workspaces, such as the platform statistics, routes its connection to each isolated workspace
+//! in turn. Schemas copy the tables as they are when created, so the `migrate` command rebuilds
+//! the schemas older than the last migration afterwards (see `migrate`).
+
+use futures_util::future::BoxFuture;
+use sqlx::{Connection, PgConnection};
+use uuid::Uuid;
+
+/// The tables copied into the schema of each workspace, referenced tables first.
+pub const TENANT_TABLES: [&str; 4] = ["contacts", "product_categories", "products", "contact_transactions"];
+
+/// Matches the names of the workspace schemas, and no other.
+const SCHEMA_PATTERN: &str = "^ws_[0-9a-f]{32}$";
+
+/// The schema holding the tables of `workspace_id`.
+pub fn schema_name(workspace_id: Uuid) -> String {
+  format!("ws_{}", workspace_id.simple())
+}
+
+/// The workspaces whose tables are in a schema of their own.
+pub async fn isolated_workspaces(connection: &mut PgConnection) -> Result<Vec<Uuid>, sqlx::Error> {
+  sqlx::query_scalar("SELECT substr(nspname, 4)::UUID FROM pg_namespace WHERE nspname ~ $1 ORDER BY nspname")
+    .bind(SCHEMA_PATTERN)
+    .fetch_all(connection)
+    .await
+}
+
+/// The workspace the unqualified table names of `connection` point at, `None` for the shared tables.
+pub async fn routed_workspace(connection: &mut PgConnection) -> Result<Option<Uuid>, sqlx::Error> {
+  sqlx::query_scalar("SELECT substr(schema, 4)::UUID FROM (SELECT (current_schemas(false))[1] AS schema) s WHERE schema ~ $1")
+    .bind(SCHEMA_PATTERN)
+    .fetch_optional(connection)
+    .await
+}
+
+/// Points the unqualified table names of `connection` at the schema of `workspace_id`, or back at
+/// the shared tables without a workspace. A workspace without a schema keeps the shared tables,
+/// since Postgres skips missing schemas on the `search_path`.
+pub async fn route(connection: &mut PgConnection, workspace_id: Option<Uuid>) -> Result<(), sqlx::Error> {
+  match workspace_id {
+    Some(workspace_id) => {
+      sqlx::query("SELECT set_config('search_path', quote_ident($1) || ', public', false)")
+        .bind(schema_name(workspace_id))
+        .execute(connection)
+        .await?;
+    }
+    None => {
+      sqlx::query("SELECT set_config('search_path', reset_val, false) FROM pg_settings WHERE name = 'search_path'")
+        .execute(connection)
+        .await?;
+    }
+  }
+  Ok(())
+}
+
+/// Runs `query` with the tables of each of `workspaces` in turn, returning the results in the same
+/// order. `connection` points at the tables it pointed at before afterwards, even when a query failed.
+/// The query is boxed so that repositories can await it in their `Send` futures.
+pub async fn for_each_workspace<T>(
+  connection: &mut PgConnection,
+  workspaces: Vec<Uuid>,
+  query: impl Fn(&mut PgConnection, Uuid) -> BoxFuture<'_, Result<T, sqlx::Error>>,
+) -> Result<Vec<T>, sqlx::Error> {
+  let routed = routed_workspace(connection).await?;
+  let results = async {
+    let mut results = Vec::new();
+    for workspace_id in workspaces {
+      route(connection, Some(workspace_id)).await?;
+      results.push(query(&mut *connection, workspace_id).await?);
+    }
+    Ok(results)
+  }
+  .await;
+  route(connection, routed).await?;
+  results
+}
+
+/// Creates the schema of `workspace_id` with empty copies of `TENANT_TABLES`: their columns,
+/// defaults, constraints, indexes, foreign keys, triggers and the functions taking their rows. Does
+/// nothing when the schema already exists. Runs in a transaction, nested in the caller's if one is
+/// open.
+pub async fn provision(connection: &mut PgConnection, workspace_id: Uuid) -> Result<(), sqlx::Error> {
+  let schema = schema_name(workspace_id);
+  let mut tx = connection.begin().await?;
+
+  let exists: bool = sqlx::query_scalar("SELECT to_regnamespace(quote_ident($1)) IS NOT NULL")
+    .bind(&schema)
+    .fetch_one(&mut *tx)
+    .await?;
+  if exists {
+    return tx.commit().await;
+  }
+
+  let definitions = Definitions::read(&mut tx).await?;
+  definitions.create_tables(&mut tx, &schema).await?;
+  definitions.link_tables(&mut tx, &schema).await?;
+
+  tracing::info!("Provisioned schema {} for workspace {}", schema, workspace_id);
+  tx.commit().await
+}
+
+/// Rebuilds the schemas created before the last migration applied to the shared tables, returning
+/// the workspaces whose schema was rebuilt. Runs after the migrations, as they only change the
+/// shared tables.
+///
+/// A rebuilt schema gets fresh copies of the tables, as `provision` would create them now, and the
+/// rows of the old copies in the columns both have. New columns take their defaults: a migration
+/// filling a new column from other data has to fill it in the schemas too. Each schema is rebuilt in
+/// a transaction of its own, so instances migrating at the same time rebuild it once.
+pub async fn migrate(connection: &mut PgConnection) -> Result<Vec<Uuid>, sqlx::Error> {
+  let version = latest_migration(connection).await?;
+  let outdated: Vec<Uuid> = sqlx::query_scalar(
+    r#"
+      SELECT substr(nspname, 4)::UUID
+      FROM pg_namespace
+      WHERE nspname ~ $1 AND COALESCE(NULLIF(obj_description(oid, 'pg_namespace'), '')::BIGINT, 0) < $2
+      ORDER BY nspname
+      "#,
+  )
+  .bind(SCHEMA_PATTERN)
+  .bind(version)
+  .fetch_all(&mut *connection)
+  .await?;
+
+  let mut rebuilt = Vec::new();
+  for workspace_id in outdated {
+    if rebuild(connection, workspace_id, version).await? {
+      rebuilt.push(workspace_id);
+    }
+  }
+  Ok(rebuilt)
+}
+
+/// Rebuilds the schema of `workspace_id` unless it is already at `version`; `false` when it was.
+async fn rebuild(connection: &mut PgConnection, workspace_id: Uuid, version: i64) -> Result<bool, sqlx::Error> {
+  let schema = schema_name(workspace_id);
+  let old = format!("{schema}_old");
+  let mut tx = connection.begin().await?;
+
+  sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+    .bind(&schema)
+    .execute(&mut *tx)
+    .await?;
+  let current: Option<i64> =
+    sqlx::query_scalar("SELECT COALESCE(NULLIF(obj_description(oid, 'pg_namespace'), '')::BIGINT, 0) FROM pg_namespace WHERE nspname = $1")
+      .bind(&schema)
+      .fetch_optional(&mut *tx)
+      .await?;
+  if current.is_none_or(|current| current >= version) {
+    tx.commit().await?;
+    return Ok(false);
+  }
+
+  sqlx::query(&format!("ALTER SCHEMA \"{schema}\" RENAME TO \"{old}\""))
+    .execute(&mut *tx)
+    .await?;
+  let definitions = Definitions::read(&mut tx).await?;
+  definitions.create_tables(&mut tx, &schema).await?;
+  // The rows are copied before the triggers and foreign keys exist: they are the same records,
+  // and categories may come before their parent
+  let copied = copy_rows(&mut tx, &old, &schema, workspace_id).await?;
+  definitions.link_tables(&mut tx, &schema).await?;
+  sqlx::query(&format!("DROP SCHEMA \"{old}\" CASCADE")).execute(&mut *tx).await?;
+
+  tracing::info!("Rebuilt schema {} of workspace {} with {} rows", schema, workspace_id, copied);
+  tx.commit().await?;
+  Ok(true)
+}
+
+/// The version of the last migration applied, recorded on the schemas built after it. 0 when the
+/// migrations were not applied by `migrate`, which has no versions to compare then.
+async fn latest_migration(connection: &mut PgConnection) -> Result<i64, sqlx::Error> {
+  let recorded: bool = sqlx::query_scalar("SELECT to_regclass('public._sqlx_migrations') IS NOT NULL")
+    .fetch_one(&mut *connection)
+    .await?;
+  if !recorded {
+    return Ok(0);
+  }
+  sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM public._sqlx_migrations WHERE success")
+    .fetch_one(connection)
+    .await
+}
+
+/// The definitions of `TENANT_TABLES` a schema is built from.
+struct Definitions {
+  foreign_keys: Vec<(String, String, String)>,
+  triggers: Vec<(String, String)>,
+  functions: Vec<String>,
+}
+
+impl Definitions {
+  /// Reads the definitions of the shared tables, naming the tables without a schema.
+  async fn read(connection: &mut PgConnection) -> Result<Self, sqlx::Error> {
+    // The definitions name the tables without a schema as long as only `public` is searched, so
+    // they point at the copies once the new schema is searched first
+    let search_path: String = sqlx::query_scalar("SELECT current_setting('search_path')")
+      .fetch_one(&mut *connection)
+      .await?;
+    sqlx::query("SELECT set_config('search_path', 'public', true)")
+      .execute(&mut *connection)
+      .await?;
+    let foreign_keys = sqlx::query_as(
+      r#"
+        SELECT conrelid::regclass::TEXT, quote_ident(conname), pg_get_constraintdef(oid)
+        FROM pg_constraint
+        WHERE contype = 'f' AND conrelid = ANY(SELECT unnest($1::TEXT[])::regclass)
+        "#,
+    )
+    .bind(TENANT_TABLES)
+    .fetch_all(&mut *connection)
+    .await?;
+    let triggers = sqlx::query_as(
+      r#"
+        SELECT tgrelid::regclass::TEXT, pg_get_triggerdef(oid)
+        FROM pg_trigger
+        WHERE NOT tgisinternal AND tgrelid = ANY(SELECT unnest($1::TEXT[])::regclass)
+        "#,
+    )
+    .bind(TENANT_TABLES)
+    .fetch_all(&mut *connection)
+    .await?;
+    // Functions taking the rows of a table, like the search vectors, need a copy for the copy's rows
+    let functions = sqlx::query_scalar(
+      r#"
+        SELECT pg_get_functiondef(p.oid)
+        FROM pg_proc p
+        JOIN pg_namespace n ON n.oid = p.pronamespace
+        WHERE n.nspname = 'public'
+          AND p.proargtypes::OID[] && ARRAY(SELECT unnest($1::TEXT[])::regtype::OID)
+        "#,
+    )
+    .bind(TENANT_TABLES)
+    .fetch_all(&mut *connection)
+    .await?;
+    sqlx::query("SELECT set_config('search_path', $1, true)")
+      .bind(search_path)
+      .execute(&mut *connection)
+      .await?;
+
+    Ok(Self {
+      foreign_keys,
+      triggers,
+      functions,
+    })
+  }
+
+  /// Creates `schema` with empty copies of the tables and of the functions taking their rows, and
+  /// records the last migration on it.
+  async fn create_tables(&self, connection: &mut PgConnection, schema: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(&format!("CREATE SCHEMA \"{}\"", schema)).execute(&mut *connection).await?;
+    for table in TENANT_TABLES {
+      sqlx::query(&format!("CREATE TABLE \"{schema}\".{table} (LIKE public.{table} INCLUDING ALL)"))
+        .execute(&mut *connection)
+        .await?;
+    }
+    let version = latest_migration(connection).await?;
+    sqlx::query(&format!("COMMENT ON SCHEMA \"{schema}\" IS '{version}'"))
+      .execute(&mut *connection)
+      .await?;
+
+    in_schema(connection, schema, async |connection| {
+      for definition in &self.functions {
+        let definition = definition.replacen("FUNCTION public.", "FUNCTION ", 1);
+        sqlx::query(&definition).execute(&mut *connection).await?;
+      }
+      Ok(())
+    })
+    .await
+  }
+
+  /// Adds the foreign keys and triggers of the tables to their copies in `schema`.
+  async fn link_tables(&self, connection: &mut PgConnection, schema: &str) -> Result<(), sqlx::Error> {
+    in_schema(connection, schema, async |connection| {
+      for (table, name, definition) in &self.foreign_keys {
+        sqlx::query(&format!("ALTER TABLE {table} ADD CONSTRAINT {name} {definition}"))
+          .execute(&mut *connection)
+          .await?;
+      }
+      for (table, definition) in &self.triggers {
+        // Trigger definitions always name their table with its schema; the trigger functions
+        // stay shared and find the copies through the `search_path`
+        let definition = definition.replacen(&format!(" ON public.{table} "), &format!(" ON {table} "), 1);
+        sqlx::query(&definition).execute(&mut *connection).await?;
+      }
+      Ok(())
+    })
+    .await
+  }
+}
+
+/// Runs `statements` with `schema` searched first. The `search_path` is set for the transaction
+/// only, and restored once `statements` succeeded.
+async fn in_schema(
+  connection: &mut PgConnection,
+  schema: &str,
+  statements: impl AsyncFnOnce(&mut PgConnection) -> Result<(), sqlx::Error>,
+) -> Result<(), sqlx::Error> {
+  let search_path: String = sqlx::query_scalar("SELECT current_setting('search_path')")
+    .fetch_one(&mut *connection)
+    .await?;
+  sqlx::query("SELECT set_config('search_path', quote_ident($1) || ', public', true)")
+    .bind(schema)
+    .execute(&mut *connection)
+    .await?;
+  statements(&mut *connection).await?;
+  sqlx::query("SELECT set_config('search_path', $1, true)")
+    .bind(search_path)
+    .execute(connection)
+    .await?;
+  Ok(())
+}
+
+/// Copies the rows of `workspace_id` from the tables of schema `from` to those of schema `to`, in
+/// the columns both have. Tables `from` does not have yet are skipped. Returns the number of rows
+/// copied.
+async fn copy_rows(connection: &mut PgConnection, from: &str, to: &str, workspace_id: Uuid) -> Result<u64, sqlx::Error> {
+  let mut copied = 0;
+  for table in TENANT_TABLES {
+    let columns: Option<String> = sqlx::query_scalar(
+      r#"
+        SELECT string_agg(quote_ident(column_name), ', ' ORDER BY ordinal_position)
+        FROM information_schema.columns
+        WHERE table_schema = $2 AND table_name = $3 AND is_generated = 'NEVER'
+          AND column_name IN (SELECT column_name FROM information_schema.columns WHERE table_schema = $1 AND table_name = $3)
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(table)
+    .fetch_one(&mut *connection)
+    .await?;
+    let Some(columns) = columns else {
+      continue;
+    };
+    copied += sqlx::query(&format!(
+      "INSERT INTO \"{to}\".{table} ({columns}) SELECT {columns} FROM \"{from}\".{table} WHERE workspace_id = $1"
+    ))
+    .bind(workspace_id)
+    .execute(&mut *connection)
+    .await?
+    .rows_affected();
+  }
+  Ok(copied)
+}
+
+/// Moves the rows of `workspace_id` from the shared tables into its schema, which must exist.
+/// References to the moved contacts from tables outside the schema are cleared.
+///
+/// # Returns
+///
+/// The number of rows moved.
+pub async fn move_rows(connection: &mut PgConnection, workspace_id: Uuid) -> Result<u64, sqlx::Error> {
+  let schema = schema_name(workspace_id);
+  let mut tx = connection.begin().await?;
+  // The rows stay the same records, so no revisions are recorded for the move
+  sqlx::query("SELECT set_config('app.skip_revisions', 'on', true)")
+    .execute(&mut *tx)
+    .await?;
+
+  let moved = copy_rows(&mut tx, "public", &schema, workspace_id).await?;
+  for table in TENANT_TABLES.iter().rev() {
+    sqlx::query(&format!("DELETE FROM public.{table} WHERE workspace_id = $1"))
+      .bind(workspace_id)
+      .execute(&mut *tx)
+      .await?;
+  }
+
+  tx.commit().await?;
+  Ok(moved)
+}
+
+/// Drops the schema of `workspace_id` with its tables, if it has one.
+pub async fn drop_schema(connection: &mut PgConnection, workspace_id: Uuid) -> Result<(), sqlx::Error> {
+  sqlx::query(&format!("DROP SCHEMA IF EXISTS \"{}\" CASCADE", schema_name(workspace_id)))
+    .execute(connection)
+    .await?;
+  Ok(())
+}
@@ -0,0 +1,110 @@
+//! Platform administration: the superadmin role, and the statistics it gives access to (totals,
+//! the largest workspaces, request outcomes and background task health).
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::helper::superadmin::is_superadmin;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_stats_are_reported_to_superadmins_only() {
+  let app = TestApp::isolated().await;
+  let superadmin = UserFactory::new().create(&app).await;
+  let owner = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &owner).await;
+  let mut conn = app.db.acquire().await.unwrap();
+  // Larger than any workspace left behind by other tests
+  sqlx::query(
+    "INSERT INTO contacts (code, name, email, position, type, workspace_id, created_by) \
+     SELECT 'STATS-' || n, 'Contact ' || n, 'stats' || n || '@example.com', 'Buyer', 'customer', $1, $2 FROM generate_series(1, 5000) n",
+  )
+  .bind(workspace.id)
+  .bind(owner.id())
+  .execute(&mut *conn)
+  .await
+  .unwrap();
+  drop(conn);
+  app.state.task_health.failed("retention_purger", "connection refused");
+
+  let (status, _) = app.call(http::Method::GET, "/api/v1/admin/stats", &superadmin, None, None).await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+
+  // Granted as the grant-superadmin subcommand does; workspace roles play no part
+  let user = app
+    .state
+    .auth_repository
+    .set_superadmin(&superadmin.user.email, true)
+    .await
+    .unwrap()
+    .unwrap();
+  assert!(user.is_superadmin);
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/admin/stats?top=1", &superadmin, None, None).await;
+  assert_eq!(status, StatusCode::OK);
+  let stats = &body["results"];
+  assert!(stats["totals"]["users"].as_i64().unwrap() >= 2);
+  assert!(stats["totals"]["workspaces"].as_i64().unwrap() >= 1);
+  let top = stats["top_workspaces"].as_array().unwrap();
+  assert_eq!(top.len(), 1);
+  assert_eq!(top[0]["workspace_id"], workspace.id.to_string());
+  assert_eq!(top[0]["contacts"], 5000);
+  assert_eq!(top[0]["members"], 1);
+  // The forbidden request was counted as a client error
+  assert!(stats["requests"]["last_hour"]["client_errors"].as_u64().unwrap() >= 1);
+  assert_eq!(stats["background_tasks"]["healthy"], false);
+  assert_eq!(stats["background_tasks"]["tasks"][0]["name"], "retention_purger");
+  assert_eq!(stats["background_tasks"]["tasks"][0]["last_error"], "connection refused");
+
+  let (status, _) = app.call(http::Method::GET, "/api/v1/admin/stats?top=0", &superadmin, None, None).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+  // Superadmins administer feature flags without being listed in FEATURE_FLAG_ADMIN_IDS
+  let (status, _) = app.call(http::Method::GET, "/api/v1/admin/feature-flags", &superadmin, None, None).await;
+  assert_eq!(status, StatusCode::OK);
+  let (_, body) = app.call(http::Method::GET, "/api/v1/auth/me", &superadmin, None, None).await;
+  assert_eq!(body["results"]["user"]["is_superadmin"], true);
+
+  app.state.auth_repository.set_superadmin(&superadmin.user.email, false).await.unwrap();
+  let (status, _) = app.call(http::Method::GET, "/api/v1/admin/stats", &superadmin, None, None).await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_workspace_admins_and_deactivated_superadmins_are_refused() {
+  let app = TestApp::isolated().await;
+  let owner = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &owner).await;
+
+  // Owning a workspace grants nothing outside it
+  let (status, body) = app.call(http::Method::GET, "/api/v1/admin/stats", &owner, workspace.id, None).await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+  assert_eq!(body["details"]["details"], "Superadmin access required");
+  let (status, _) = app
+    .call(http::Method::GET, "/api/v1/admin/feature-flags", &owner, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+
+  assert!(
+    app
+      .state
+      .auth_repository
+      .set_superadmin("nobody@example.com", true)
+      .await
+      .unwrap()
+      .is_none()
+  );
+
+  app.state.auth_repository.set_superadmin(&owner.user.email, true).await.unwrap();
+  assert!(is_superadmin(&app.state, owner.id()).await.unwrap());
+  sqlx::query("UPDATE users SET is_active = false WHERE id = $1")
+    .bind(owner.id())
+    .execute(&mut *app.db.acquire().await.unwrap())
+    .await
+    .unwrap();
+  assert!(!is_superadmin(&app.state, owner.id()).await.unwrap(), "deactivated users lose the role");
+}
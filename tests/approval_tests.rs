@@ -0,0 +1,215 @@
+//! Approval workflows: changes of members held until an admin approves them.
+
+use axum::http::{self, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, TestUser, UserFactory, WorkspaceFactory},
+};
+use myapp_api_rust::modules::datastores::workspaces::WorkspaceRole;
+
+mod common;
+
+async fn set_rules(app: &TestApp, admin: &TestUser, workspace_id: Uuid, rules: Value) {
+  let settings_uri = format!("/api/v1/workspaces/{}/settings", workspace_id);
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &settings_uri,
+      admin,
+      workspace_id,
+      Some(json!({ "approval_rules": rules })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+#[tokio::test]
+async fn test_price_changes_above_the_limit_wait_for_an_admin() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+  let product = ProductFactory::new().create(&app, &workspace, &admin).await;
+  let product_uri = format!("/api/v1/products/{}", product.id);
+  set_rules(
+    &app,
+    &admin,
+    workspace.id,
+    json!({ "price_change_percent": 10, "stock_write_offs": true }),
+  )
+  .await;
+
+  // 150 → 160 stays within the limit
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &product_uri,
+      &member,
+      workspace.id,
+      Some(json!({ "selling_price": 160 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let (status, body) = app
+    .call(
+      http::Method::PATCH,
+      &product_uri,
+      &member,
+      workspace.id,
+      Some(json!({ "selling_price": 200 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+  assert_eq!(body["error"], "APPROVAL_REQUIRED");
+  let approval_id = body["details"]["approval_id"].as_str().unwrap().to_string();
+  let (_, body) = app.call(http::Method::GET, &product_uri, &member, workspace.id, None).await;
+  assert_eq!(body["results"]["selling_price"].as_f64(), Some(160.0), "{body}");
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/approvals", &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let requests = body["results"].as_array().unwrap();
+  assert_eq!(requests.len(), 1);
+  assert_eq!(requests[0]["action"], "price_change");
+  assert_eq!(requests[0]["status"], "pending");
+  assert_eq!(requests[0]["summary"], "selling_price 160 → 200 (+25%)");
+
+  let approve_uri = format!("/api/v1/approvals/{}/approve", approval_id);
+  let (status, body) = app.call(http::Method::POST, &approve_uri, &member, workspace.id, Some(json!({}))).await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      &approve_uri,
+      &admin,
+      workspace.id,
+      Some(json!({ "note": "Supplier raised prices" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["status"], "approved");
+  assert_eq!(body["results"]["decided_by"], admin.id().to_string());
+  let (_, body) = app.call(http::Method::GET, &product_uri, &member, workspace.id, None).await;
+  assert_eq!(body["results"]["selling_price"].as_f64(), Some(200.0), "{body}");
+  let (status, body) = app.call(http::Method::POST, &approve_uri, &admin, workspace.id, Some(json!({}))).await;
+  assert_eq!(status, StatusCode::CONFLICT, "{body}");
+
+  // Lowering the stock is a write-off, except for admins
+  let (status, body) = app
+    .call(
+      http::Method::PATCH,
+      &product_uri,
+      &member,
+      workspace.id,
+      Some(json!({ "current_stock": 90 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+  let (status, body) = app
+    .call(
+      http::Method::PATCH,
+      &product_uri,
+      &admin,
+      workspace.id,
+      Some(json!({ "current_stock": 50 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/approvals?status=pending", &admin, workspace.id, None)
+    .await;
+  let requests = body["results"].as_array().unwrap();
+  assert_eq!(requests.len(), 1, "{body}");
+  assert_eq!(requests[0]["action"], "stock_write_off");
+}
+
+#[tokio::test]
+async fn test_deletions_wait_and_can_be_rejected_or_cancelled() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let other = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new()
+    .member(&member, WorkspaceRole::Member)
+    .member(&other, WorkspaceRole::Member)
+    .create(&app, &admin)
+    .await;
+  let product = ProductFactory::new().create(&app, &workspace, &admin).await;
+  let product_uri = format!("/api/v1/products/{}", product.id);
+  let contact = json!({ "code": "C-1", "name": "Acme", "email": "acme@example.com", "contact_type": "customer" });
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", &admin, workspace.id, Some(contact))
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  let contact_uri = format!("/api/v1/contacts/{}", body["results"]["id"].as_str().unwrap());
+  set_rules(&app, &admin, workspace.id, json!({ "deletions": true })).await;
+
+  // The requester cancels, other members do not even see the request
+  let (status, body) = app.call(http::Method::DELETE, &product_uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+  let approval_id = body["details"]["approval_id"].as_str().unwrap().to_string();
+  let (status, _) = app.call(http::Method::GET, &product_uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  let cancel_uri = format!("/api/v1/approvals/{}/cancel", approval_id);
+  let (status, body) = app.call(http::Method::POST, &cancel_uri, &other, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+  let (status, body) = app.call(http::Method::POST, &cancel_uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["status"], "cancelled");
+  let approve_uri = format!("/api/v1/approvals/{}/approve", approval_id);
+  let (status, body) = app.call(http::Method::POST, &approve_uri, &admin, workspace.id, Some(json!({}))).await;
+  assert_eq!(status, StatusCode::CONFLICT, "{body}");
+
+  let (status, body) = app.call(http::Method::DELETE, &contact_uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+  let reject_uri = format!("/api/v1/approvals/{}/reject", body["details"]["approval_id"].as_str().unwrap());
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      &reject_uri,
+      &admin,
+      workspace.id,
+      Some(json!({ "note": "Still a customer" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["status"], "rejected");
+  assert_eq!(body["results"]["decision_note"], "Still a customer");
+  assert_eq!(body["results"]["summary"], "Delete contact C-1 (Acme)");
+  let (status, _) = app.call(http::Method::GET, &contact_uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+
+  let (status, body) = app.call(http::Method::DELETE, &contact_uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+  let approve_uri = format!("/api/v1/approvals/{}/approve", body["details"]["approval_id"].as_str().unwrap());
+  let (status, body) = app.call(http::Method::POST, &approve_uri, &admin, workspace.id, Some(json!({}))).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, _) = app.call(http::Method::GET, &contact_uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_approval_rules_are_validated() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &admin).await;
+  let settings_uri = format!("/api/v1/workspaces/{}/settings", workspace.id);
+
+  let body = json!({ "approval_rules": { "price_change_percent": -5 } });
+  let (status, response) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(body)).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{response}");
+  let body = json!({ "approval_rules": { "imports": true } });
+  let (status, response) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(body)).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{response}");
+
+  let body = json!({ "approval_rules": { "price_change_percent": 12.5, "deletions": true } });
+  let (status, response) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(body)).await;
+  assert_eq!(status, StatusCode::OK, "{response}");
+  assert_eq!(
+    response["results"]["approval_rules"],
+    json!({ "price_change_percent": 12.5, "stock_write_offs": false, "deletions": true })
+  );
+}
@@ -0,0 +1,139 @@
+//! Audit events of privilege changes, and the alerts sent to workspace admins.
+
+use std::{
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::http::{self, StatusCode};
+use myapp_api_rust::{
+  AppResult,
+  mailer::{EmailMessage, Mailer},
+  modules::datastores::workspaces::WorkspaceRole,
+};
+use serde_json::json;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+#[derive(Default)]
+struct RecordingMailer {
+  sent: Mutex<Vec<EmailMessage>>,
+}
+
+#[async_trait]
+impl Mailer for RecordingMailer {
+  async fn send(&self, message: EmailMessage) -> AppResult<()> {
+    self.sent.lock().unwrap().push(message);
+    Ok(())
+  }
+}
+
+#[tokio::test]
+async fn test_privilege_changes_are_audited_and_alerted() {
+  let mailer = Arc::new(RecordingMailer::default());
+  let app = TestApp::isolated_with(|builder| builder.with_mailer(mailer.clone())).await;
+  let owner = UserFactory::new().create(&app).await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let newcomer = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new()
+    .member(&admin, WorkspaceRole::Admin)
+    .member(&member, WorkspaceRole::Member)
+    .create(&app, &owner)
+    .await;
+  let users_uri = format!("/api/v1/workspaces/{}/users", workspace.id);
+  let mut receiver = app.state.events.subscribe();
+
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &format!("{users_uri}/{}/role", member.id()),
+      &owner,
+      workspace.id,
+      Some(json!({ "role": "Admin" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      &users_uri,
+      &owner,
+      workspace.id,
+      Some(json!({ "user_id": newcomer.id(), "role": "Viewer" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = app
+    .call(
+      http::Method::DELETE,
+      &format!("{users_uri}/{}", newcomer.id()),
+      &owner,
+      workspace.id,
+      None,
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let audit_uri = format!("/api/v1/workspaces/{}/audit-events", workspace.id);
+  let (status, body) = app.call(http::Method::GET, &audit_uri, &owner, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let events = body["results"].as_array().unwrap();
+  let actions: Vec<&str> = events.iter().map(|e| e["action"].as_str().unwrap()).collect();
+  assert_eq!(actions, ["member.removed", "member.added", "member.role_changed"]);
+  let role_change = &events[2];
+  assert_eq!(role_change["priority"], "high");
+  assert_eq!(role_change["actor_id"], owner.id().to_string());
+  assert_eq!(role_change["target_user_id"], member.id().to_string());
+  assert_eq!(role_change["details"], json!({ "from": "Member", "to": "Admin" }));
+
+  // Each admin after the change is notified of it
+  let mut alerted = Vec::new();
+  while let Ok(event) = receiver.try_recv() {
+    if event.event == "notification" && event.workspace_id == workspace.id {
+      alerted.push((event.recipient_id.unwrap(), event.data["title"].as_str().unwrap().to_string()));
+    }
+  }
+  for user in [&owner, &admin, &member] {
+    assert!(alerted.contains(&(user.id(), "Member removed".to_string())), "{alerted:?}");
+  }
+  assert!(!alerted.iter().any(|(recipient, _)| *recipient == newcomer.id()));
+
+  // And emailed, in the background
+  for _ in 0..100 {
+    if mailer.sent.lock().unwrap().len() >= 9 {
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(20)).await;
+  }
+  let sent = mailer.sent.lock().unwrap();
+  assert_eq!(sent.len(), 9, "the 3 admins after the role change, for each of the changes");
+  assert!(sent.iter().any(|m| {
+    m.to == admin.user.email
+      && m
+        .body
+        .contains(&format!("changed the role of {} from member to admin", member.user.username))
+  }));
+}
+
+#[tokio::test]
+async fn test_audit_log_is_for_admins_only() {
+  let app = TestApp::isolated().await;
+  let owner = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &owner).await;
+  let audit_uri = format!("/api/v1/workspaces/{}/audit-events", workspace.id);
+
+  let (status, _) = app.call(http::Method::GET, &audit_uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+  let (status, _) = app
+    .call(http::Method::GET, &format!("{audit_uri}?limit=0"), &owner, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
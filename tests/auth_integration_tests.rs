@@ -3,78 +3,12 @@ use axum::{
   http::{self, Request, StatusCode},
 };
 use http_body_util::BodyExt;
-use myapp_api_rust::{app, setup_state};
 use serde_json::{Value, json};
-use sqlx::PgPool;
 use tower::ServiceExt;
 
-/// Helper to setup test database connection
-async fn setup_test_db() -> PgPool {
-  dotenvy::dotenv().ok();
-  let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
-  let pool = PgPool::connect(&db_url).await.expect("Failed to connect to test database");
-
-  // Run migrations to ensure all tables exist
-  // Note: This assumes sqlx-cli is installed and migrations are available
-  let output = std::process::Command::new("sqlx")
-    .args(&["migrate", "run", "--database-url", &db_url])
-    .output();
-
-  match output {
-    Ok(output) if output.status.success() => {
-      eprintln!("Migrations applied successfully");
-    }
-    Ok(output) => {
-      eprintln!("Migration failed: {}", String::from_utf8_lossy(&output.stderr));
-    }
-    Err(e) => {
-      eprintln!("Failed to run migrations: {}. Continuing anyway...", e);
-    }
-  }
-
-  pool
-}
-
-/// Helper to setup the Axum app with test state
-async fn setup_app() -> axum::Router {
-  let state = setup_state().await;
-  app(state)
-}
-
-/// Helper to generate unique test identifiers
-fn generate_test_id() -> String {
-  std::time::SystemTime::now()
-    .duration_since(std::time::UNIX_EPOCH)
-    .unwrap()
-    .as_nanos()
-    .to_string()
-}
+use crate::common::TestApp;
 
-/// Helper to clean up test user data
-async fn cleanup_test_user(pool: &PgPool, email: &str) {
-  // Clean up contacts first (foreign key constraint)
-  sqlx::query("DELETE FROM contacts WHERE created_by IN (SELECT id FROM users WHERE email = $1)")
-    .bind(email)
-    .execute(pool)
-    .await
-    .ok();
-
-  // Clean up user
-  sqlx::query("DELETE FROM users WHERE email = $1").bind(email).execute(pool).await.ok();
-
-  // Also cleanup by username pattern (for tests)
-  sqlx::query("DELETE FROM contacts WHERE created_by IN (SELECT id FROM users WHERE username LIKE 'test%')")
-    .execute(pool)
-    .await
-    .ok();
-
-  sqlx::query("DELETE FROM users WHERE username LIKE 'test%'").execute(pool).await.ok();
-}
-
-/// Helper to clean up test contact
-async fn cleanup_test_contact(pool: &PgPool, code: &str) {
-  sqlx::query("DELETE FROM contacts WHERE code = $1").bind(code).execute(pool).await.ok();
-}
+mod common;
 
 /// Helper to register a test user and return the response
 async fn register_test_user(app: &axum::Router, username: &str, email: &str, password: &str) -> (StatusCode, Value) {
@@ -119,11 +53,12 @@ async fn login_test_user(app: &axum::Router, email: &str, password: &str) -> Str
   let body = response.into_body().collect().await.unwrap().to_bytes();
   let json: Value = serde_json::from_slice(&body).unwrap();
 
-  json["token"].as_str().unwrap().to_string()
+  assert!(json["results"]["user"].get("password_hash").is_none());
+  json["results"]["token"].as_str().unwrap().to_string()
 }
 
-/// Helper to create a test contact for a user
-async fn create_test_contact(app: &axum::Router, token: &str, code: &str) -> (StatusCode, Value) {
+/// Helper to create a test contact in a workspace of the user
+async fn create_test_contact(app: &axum::Router, token: &str, workspace_id: &str, code: &str) -> (StatusCode, Value) {
   let payload = json!({
       "code": code,
       "name": "Test Contact",
@@ -138,6 +73,7 @@ async fn create_test_contact(app: &axum::Router, token: &str, code: &str) -> (St
     .uri("/api/v1/contacts")
     .header(http::header::CONTENT_TYPE, "application/json")
     .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+    .header("X-Workspace-ID", workspace_id)
     .body(Body::from(serde_json::to_string(&payload).unwrap()))
     .unwrap();
 
@@ -151,16 +87,12 @@ async fn create_test_contact(app: &axum::Router, token: &str, code: &str) -> (St
 
 #[tokio::test]
 async fn test_auth_user_registration_success() {
-  let pool = setup_test_db().await;
-  let app = setup_app().await;
+  let app = TestApp::isolated().await;
 
   let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
   let test_email = format!("test_register_{}@example.com", timestamp);
   let test_username = format!("testuser_{}", timestamp);
 
-  // Cleanup before test
-  cleanup_test_user(&pool, &test_email).await;
-
   let (status, response) = register_test_user(&app, &test_username, &test_email, "password123").await;
 
   // Debug output
@@ -171,25 +103,20 @@ async fn test_auth_user_registration_success() {
 
   assert_eq!(status, StatusCode::CREATED);
   assert_eq!(response["status"], "success");
-  assert!(response["data"]["id"].is_string());
-  assert_eq!(response["data"]["email"], test_email);
-  assert_eq!(response["data"]["username"], test_username);
-  assert_eq!(response["data"]["is_active"], true);
-
-  // Cleanup after test
-  cleanup_test_user(&pool, &test_email).await;
+  assert!(response["results"]["user"]["id"].is_string());
+  assert_eq!(response["results"]["user"]["email"], test_email);
+  assert_eq!(response["results"]["user"]["username"], test_username);
+  assert_eq!(response["results"]["user"]["is_active"], true);
+  assert!(response["results"]["user"].get("password_hash").is_none());
+  assert!(response["results"]["workspace"]["id"].is_string());
 }
 
 #[tokio::test]
 async fn test_auth_user_registration_duplicate_email() {
-  let pool = setup_test_db().await;
-  let app = setup_app().await;
+  let app = TestApp::isolated().await;
 
   let test_email = "test_duplicate@example.com";
 
-  // Cleanup before test
-  cleanup_test_user(&pool, test_email).await;
-
   // Register first user
   let (status1, _) = register_test_user(&app, "testuser1", test_email, "password123").await;
   assert_eq!(status1, StatusCode::CREATED);
@@ -198,21 +125,34 @@ async fn test_auth_user_registration_duplicate_email() {
   let (status2, response2) = register_test_user(&app, "testuser2", test_email, "password456").await;
   assert_eq!(status2, StatusCode::CONFLICT);
   assert!(response2["error"].as_str().unwrap().contains("CONFLICT"));
+}
 
-  // Cleanup after test
-  cleanup_test_user(&pool, test_email).await;
+#[tokio::test]
+async fn test_auth_user_registration_duplicate_email_different_case() {
+  let app = TestApp::isolated().await;
+
+  let test_email = "test_duplicate_case@example.com";
+
+  // Register first user with a mixed-case, padded email; it is stored normalized
+  let (status1, _) = register_test_user(&app, "testuser_case1", " Test_Duplicate_Case@Example.COM ", "password123").await;
+  assert_eq!(status1, StatusCode::CREATED);
+
+  // The same address in another case is the same user
+  let (status2, response2) = register_test_user(&app, "testuser_case2", test_email, "password456").await;
+  assert_eq!(status2, StatusCode::CONFLICT);
+  assert!(response2["error"].as_str().unwrap().contains("CONFLICT"));
+
+  // Login matches regardless of case
+  let token = login_test_user(&app, "TEST_DUPLICATE_CASE@example.com", "password123").await;
+  assert!(!token.is_empty());
 }
 
 #[tokio::test]
 async fn test_auth_user_login_success() {
-  let pool = setup_test_db().await;
-  let app = setup_app().await;
+  let app = TestApp::isolated().await;
 
   let test_email = "test_login@example.com";
 
-  // Cleanup before test
-  cleanup_test_user(&pool, test_email).await;
-
   // Register user first
   let (status, _) = register_test_user(&app, "testuser", test_email, "password123").await;
   assert_eq!(status, StatusCode::CREATED);
@@ -221,22 +161,15 @@ async fn test_auth_user_login_success() {
   let token = login_test_user(&app, test_email, "password123").await;
   assert!(!token.is_empty());
   assert!(token.starts_with("eyJ")); // JWT tokens start with eyJ
-
-  // Cleanup after test
-  cleanup_test_user(&pool, test_email).await;
 }
 
 #[tokio::test]
 async fn test_auth_user_login_invalid_credentials() {
-  let pool = setup_test_db().await;
-  let app = setup_app().await;
+  let app = TestApp::isolated().await;
 
-  let test_id = generate_test_id();
+  let test_id = common::test_id();
   let test_email = &format!("test_invalid_login_{}@example.com", test_id);
 
-  // Cleanup before test
-  cleanup_test_user(&pool, test_email).await;
-
   // Register user first
   let (status, _) = register_test_user(&app, &format!("testuser_{}", test_id), test_email, "password123").await;
   assert_eq!(status, StatusCode::CREATED);
@@ -256,14 +189,11 @@ async fn test_auth_user_login_invalid_credentials() {
 
   let response = app.clone().oneshot(request).await.unwrap();
   assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
-
-  // Cleanup after test
-  cleanup_test_user(&pool, test_email).await;
 }
 
 #[tokio::test]
 async fn test_auth_protected_endpoint_without_token() {
-  let app = setup_app().await;
+  let app = TestApp::isolated().await;
 
   let request = Request::builder()
     .method(http::Method::GET)
@@ -281,7 +211,7 @@ async fn test_auth_protected_endpoint_without_token() {
 
 #[tokio::test]
 async fn test_auth_protected_endpoint_with_invalid_token() {
-  let app = setup_app().await;
+  let app = TestApp::isolated().await;
 
   let request = Request::builder()
     .method(http::Method::GET)
@@ -300,16 +230,12 @@ async fn test_auth_protected_endpoint_with_invalid_token() {
 
 #[tokio::test]
 async fn test_auth_me_endpoint_success() {
-  let pool = setup_test_db().await;
-  let app = setup_app().await;
+  let app = TestApp::isolated().await;
 
-  let test_id = generate_test_id();
+  let test_id = common::test_id();
   let test_email = &format!("test_me_{}@example.com", test_id);
   let test_username = &format!("testuser_{}", test_id);
 
-  // Cleanup before test
-  cleanup_test_user(&pool, test_email).await;
-
   // Register and login user
   let (status, _) = register_test_user(&app, test_username, test_email, "password123").await;
   assert_eq!(status, StatusCode::CREATED);
@@ -331,50 +257,45 @@ async fn test_auth_me_endpoint_success() {
   let json: Value = serde_json::from_slice(&body).unwrap();
 
   assert_eq!(json["status"], "success");
-  assert_eq!(json["data"]["email"], *test_email);
-  assert_eq!(json["data"]["username"], *test_username);
-
-  // Cleanup after test
-  cleanup_test_user(&pool, test_email).await;
+  assert_eq!(json["results"]["user"]["email"], *test_email);
+  assert_eq!(json["results"]["user"]["username"], *test_username);
+  assert!(json["results"]["user"].get("password_hash").is_none());
 }
 
 #[tokio::test]
 async fn test_user_data_isolation() {
-  let pool = setup_test_db().await;
-  let app = setup_app().await;
+  let app = TestApp::isolated().await;
 
-  let test_id1 = generate_test_id();
-  let test_id2 = generate_test_id();
+  let test_id1 = common::test_id();
+  let test_id2 = common::test_id();
   let user1_email = &format!("test_user1_{}@example.com", test_id1);
   let user2_email = &format!("test_user2_{}@example.com", test_id2);
   let contact_code = &format!("ISO_{}", &test_id1[0..8]); // Use only first 8 chars
 
-  // Cleanup before test
-  cleanup_test_user(&pool, user1_email).await;
-  cleanup_test_user(&pool, user2_email).await;
-  cleanup_test_contact(&pool, contact_code).await;
-
   // Register two users
-  let (status1, _) = register_test_user(&app, &format!("user1_{}", test_id1), user1_email, "password123").await;
+  let (status1, registered1) = register_test_user(&app, &format!("user1_{}", test_id1), user1_email, "password123").await;
   assert_eq!(status1, StatusCode::CREATED);
+  let workspace1 = registered1["results"]["workspace"]["id"].as_str().unwrap();
 
-  let (status2, _) = register_test_user(&app, &format!("user2_{}", test_id2), user2_email, "password123").await;
+  let (status2, registered2) = register_test_user(&app, &format!("user2_{}", test_id2), user2_email, "password123").await;
   assert_eq!(status2, StatusCode::CREATED);
+  let workspace2 = registered2["results"]["workspace"]["id"].as_str().unwrap();
 
   // Login both users
   let token1 = login_test_user(&app, user1_email, "password123").await;
   let token2 = login_test_user(&app, user2_email, "password123").await;
 
   // User 1 creates a contact
-  let (create_status, create_response) = create_test_contact(&app, &token1, contact_code).await;
+  let (create_status, create_response) = create_test_contact(&app, &token1, workspace1, contact_code).await;
   assert_eq!(create_status, StatusCode::CREATED);
-  let contact_id = create_response["data"]["id"].as_str().unwrap();
+  let contact_id = create_response["results"]["id"].as_str().unwrap();
 
   // User 1 can see their contacts
   let request = Request::builder()
     .method(http::Method::GET)
     .uri("/api/v1/contacts")
     .header(http::header::AUTHORIZATION, format!("Bearer {}", token1))
+    .header("X-Workspace-ID", workspace1)
     .body(Body::empty())
     .unwrap();
 
@@ -383,13 +304,14 @@ async fn test_user_data_isolation() {
 
   let body = response.into_body().collect().await.unwrap().to_bytes();
   let json: Value = serde_json::from_slice(&body).unwrap();
-  assert_eq!(json["data"]["pagination"]["total"], 1);
+  assert_eq!(json["results"]["pagination"]["total"], 1);
 
   // User 2 cannot see User 1's contacts
   let request = Request::builder()
     .method(http::Method::GET)
     .uri("/api/v1/contacts")
     .header(http::header::AUTHORIZATION, format!("Bearer {}", token2))
+    .header("X-Workspace-ID", workspace2)
     .body(Body::empty())
     .unwrap();
 
@@ -398,50 +320,42 @@ async fn test_user_data_isolation() {
 
   let body = response.into_body().collect().await.unwrap().to_bytes();
   let json: Value = serde_json::from_slice(&body).unwrap();
-  assert_eq!(json["data"]["pagination"]["total"], 0);
+  assert_eq!(json["results"]["pagination"]["total"], 0);
 
   // User 2 cannot access User 1's contact by ID
   let request = Request::builder()
     .method(http::Method::GET)
-    .uri(&format!("/api/v1/contacts/{}", contact_id))
+    .uri(format!("/api/v1/contacts/{}", contact_id))
     .header(http::header::AUTHORIZATION, format!("Bearer {}", token2))
+    .header("X-Workspace-ID", workspace2)
     .body(Body::empty())
     .unwrap();
 
   let response = app.clone().oneshot(request).await.unwrap();
   assert_eq!(response.status(), StatusCode::NOT_FOUND);
-
-  // Cleanup after test
-  cleanup_test_contact(&pool, contact_code).await;
-  cleanup_test_user(&pool, user1_email).await;
-  cleanup_test_user(&pool, user2_email).await;
 }
 
 #[tokio::test]
 async fn test_contact_audit_trail() {
-  let pool = setup_test_db().await;
-  let app = setup_app().await;
+  let app = TestApp::isolated().await;
 
-  let test_id = generate_test_id();
+  let test_id = common::test_id();
   let test_email = &format!("test_audit_{}@example.com", test_id);
   let contact_code = &format!("AUDIT_{}", &test_id[0..8]); // Use only first 8 chars of timestamp
 
-  // Cleanup before test
-  cleanup_test_user(&pool, test_email).await;
-  cleanup_test_contact(&pool, contact_code).await;
-
   // Register and login user
   let (status, register_response) = register_test_user(&app, &format!("testuser_{}", test_id), test_email, "password123").await;
   assert_eq!(status, StatusCode::CREATED);
-  let user_id = register_response["data"]["id"].as_str().unwrap();
+  let user_id = register_response["results"]["user"]["id"].as_str().unwrap();
+  let workspace = register_response["results"]["workspace"]["id"].as_str().unwrap();
 
   let token = login_test_user(&app, test_email, "password123").await;
 
   // Create contact
-  let (create_status, create_response) = create_test_contact(&app, &token, contact_code).await;
+  let (create_status, create_response) = create_test_contact(&app, &token, workspace, contact_code).await;
   assert_eq!(create_status, StatusCode::CREATED);
 
-  let contact_data = &create_response["data"];
+  let contact_data = &create_response["results"];
   assert_eq!(contact_data["created_by"], user_id);
   assert!(contact_data["updated_by"].is_null());
 
@@ -455,9 +369,10 @@ async fn test_contact_audit_trail() {
 
   let request = Request::builder()
     .method(http::Method::PUT)
-    .uri(&format!("/api/v1/contacts/{}", contact_id))
+    .uri(format!("/api/v1/contacts/{}", contact_id))
     .header(http::header::CONTENT_TYPE, "application/json")
     .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+    .header("X-Workspace-ID", workspace)
     .body(Body::from(serde_json::to_string(&update_payload).unwrap()))
     .unwrap();
 
@@ -467,57 +382,50 @@ async fn test_contact_audit_trail() {
   let body = response.into_body().collect().await.unwrap().to_bytes();
   let json: Value = serde_json::from_slice(&body).unwrap();
 
-  let updated_data = &json["data"];
+  let updated_data = &json["results"];
   assert_eq!(updated_data["name"], "Updated Contact Name");
   assert_eq!(updated_data["position"], "Senior Manager");
   assert_eq!(updated_data["created_by"], user_id);
   assert_eq!(updated_data["updated_by"], user_id); // Should be set after update
-
-  // Cleanup after test
-  cleanup_test_contact(&pool, contact_code).await;
-  cleanup_test_user(&pool, test_email).await;
 }
 
 #[tokio::test]
 async fn test_contact_access_control() {
-  let pool = setup_test_db().await;
-  let app = setup_app().await;
+  let app = TestApp::isolated().await;
 
-  let test_id1 = generate_test_id();
-  let test_id2 = generate_test_id();
+  let test_id1 = common::test_id();
+  let test_id2 = common::test_id();
   let user1_email = &format!("test_access1_{}@example.com", test_id1);
   let user2_email = &format!("test_access2_{}@example.com", test_id2);
   let contact_code = &format!("ACC_{}", &test_id1[0..8]); // Use only first 8 chars
 
-  // Cleanup before test
-  cleanup_test_user(&pool, user1_email).await;
-  cleanup_test_user(&pool, user2_email).await;
-  cleanup_test_contact(&pool, contact_code).await;
-
   // Register two users
-  let (status1, _) = register_test_user(&app, &format!("user1_{}", test_id1), user1_email, "password123").await;
+  let (status1, registered1) = register_test_user(&app, &format!("user1_{}", test_id1), user1_email, "password123").await;
   assert_eq!(status1, StatusCode::CREATED);
+  let workspace1 = registered1["results"]["workspace"]["id"].as_str().unwrap();
 
-  let (status2, _) = register_test_user(&app, &format!("user2_{}", test_id2), user2_email, "password123").await;
+  let (status2, registered2) = register_test_user(&app, &format!("user2_{}", test_id2), user2_email, "password123").await;
   assert_eq!(status2, StatusCode::CREATED);
+  let workspace2 = registered2["results"]["workspace"]["id"].as_str().unwrap();
 
   // Login both users
   let token1 = login_test_user(&app, user1_email, "password123").await;
   let token2 = login_test_user(&app, user2_email, "password123").await;
 
   // User 1 creates a contact
-  let (create_status, create_response) = create_test_contact(&app, &token1, contact_code).await;
+  let (create_status, create_response) = create_test_contact(&app, &token1, workspace1, contact_code).await;
   assert_eq!(create_status, StatusCode::CREATED);
-  let contact_id = create_response["data"]["id"].as_str().unwrap();
+  let contact_id = create_response["results"]["id"].as_str().unwrap();
 
   // User 2 tries to update User 1's contact (should fail)
   let update_payload = json!({"name": "Unauthorized Update"});
 
   let request = Request::builder()
     .method(http::Method::PUT)
-    .uri(&format!("/api/v1/contacts/{}", contact_id))
+    .uri(format!("/api/v1/contacts/{}", contact_id))
     .header(http::header::CONTENT_TYPE, "application/json")
     .header(http::header::AUTHORIZATION, format!("Bearer {}", token2))
+    .header("X-Workspace-ID", workspace2)
     .body(Body::from(serde_json::to_string(&update_payload).unwrap()))
     .unwrap();
 
@@ -527,8 +435,9 @@ async fn test_contact_access_control() {
   // User 2 tries to delete User 1's contact (should fail)
   let request = Request::builder()
     .method(http::Method::DELETE)
-    .uri(&format!("/api/v1/contacts/{}", contact_id))
+    .uri(format!("/api/v1/contacts/{}", contact_id))
     .header(http::header::AUTHORIZATION, format!("Bearer {}", token2))
+    .header("X-Workspace-ID", workspace2)
     .body(Body::empty())
     .unwrap();
 
@@ -538,16 +447,105 @@ async fn test_contact_access_control() {
   // User 1 can still access their contact
   let request = Request::builder()
     .method(http::Method::GET)
-    .uri(&format!("/api/v1/contacts/{}", contact_id))
+    .uri(format!("/api/v1/contacts/{}", contact_id))
     .header(http::header::AUTHORIZATION, format!("Bearer {}", token1))
+    .header("X-Workspace-ID", workspace1)
     .body(Body::empty())
     .unwrap();
 
   let response = app.clone().oneshot(request).await.unwrap();
   assert_eq!(response.status(), StatusCode::OK);
+}
+
+/// Sends an auth request with an optional bearer token and JSON body.
+async fn auth_request(app: &axum::Router, method: http::Method, uri: &str, token: Option<&str>, body: Option<Value>) -> (StatusCode, Value) {
+  let mut request = Request::builder().method(method).uri(uri);
+  if let Some(token) = token {
+    request = request.header(http::header::AUTHORIZATION, format!("Bearer {}", token));
+  }
+  let body = match body {
+    Some(body) => {
+      request = request.header(http::header::CONTENT_TYPE, "application/json");
+      Body::from(body.to_string())
+    }
+    None => Body::empty(),
+  };
+
+  let response = app.clone().oneshot(request.body(body).unwrap()).await.unwrap();
+  let status = response.status();
+  let body = response.into_body().collect().await.unwrap().to_bytes();
+  (status, serde_json::from_slice(&body).unwrap())
+}
+
+/// The keys of the object at `value`, sorted.
+fn keys(value: &Value) -> Vec<&str> {
+  let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+  keys.sort_unstable();
+  keys
+}
 
-  // Cleanup after test
-  cleanup_test_contact(&pool, contact_code).await;
-  cleanup_test_user(&pool, user1_email).await;
-  cleanup_test_user(&pool, user2_email).await;
+#[tokio::test]
+async fn test_auth_responses_are_wrapped_in_the_api_envelope() {
+  let app = TestApp::isolated().await;
+  let test_id = common::test_id();
+  let email = format!("test_envelope_{}@example.com", test_id);
+  let envelope = ["message", "results", "status", "timestamp"];
+  let user_keys = ["created_at", "email", "id", "is_active", "is_superadmin", "updated_at", "username"];
+
+  let (status, registered) = register_test_user(&app, &format!("envelope_{}", test_id), &email, "password123").await;
+  assert_eq!(status, StatusCode::CREATED);
+  assert_eq!(keys(&registered), envelope);
+  assert_eq!(registered["message"], "User registered successfully");
+  assert_eq!(keys(&registered["results"]), ["user", "workspace"]);
+  assert_eq!(keys(&registered["results"]["user"]), user_keys);
+  let workspace_id = registered["results"]["workspace"]["id"].clone();
+
+  let credentials = json!({ "email": email, "password": "password123" });
+  let (status, logged_in) = auth_request(&app, http::Method::POST, "/api/v1/auth/login", None, Some(credentials)).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(keys(&logged_in), envelope);
+  assert_eq!(logged_in["message"], "Logged in successfully");
+  assert_eq!(keys(&logged_in["results"]), ["token", "user", "workspaces"]);
+  assert_eq!(keys(&logged_in["results"]["user"]), user_keys);
+  assert_eq!(logged_in["results"]["workspaces"][0]["id"], workspace_id);
+  let token = logged_in["results"]["token"].as_str().unwrap();
+
+  let (status, me) = auth_request(&app, http::Method::GET, "/api/v1/auth/me", Some(token), None).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(keys(&me), envelope);
+  assert_eq!(keys(&me["results"]), ["user", "workspace"]);
+  assert_eq!(keys(&me["results"]["user"]), user_keys);
+
+  let switch = json!({ "workspace_id": workspace_id });
+  let (status, switched) = auth_request(&app, http::Method::POST, "/api/v1/auth/switch-workspace", Some(token), Some(switch)).await;
+  assert_eq!(status, StatusCode::OK, "{switched}");
+  assert_eq!(keys(&switched), envelope);
+  assert_eq!(keys(&switched["results"]), ["role", "token", "workspace_id"]);
+  assert_eq!(switched["results"]["workspace_id"], workspace_id);
+  assert_eq!(switched["results"]["role"], "Admin");
+
+  let (status, logged_out) = auth_request(&app, http::Method::POST, "/api/v1/auth/logout", Some(token), None).await;
+  assert_eq!(status, StatusCode::OK, "{logged_out}");
+  assert_eq!(keys(&logged_out), envelope);
+  assert_eq!(logged_out["message"], "Logged out successfully");
+}
+
+#[tokio::test]
+async fn test_auth_responses_never_carry_the_password_hash() {
+  let app = TestApp::isolated().await;
+  let test_id = common::test_id();
+  let email = format!("test_no_hash_{}@example.com", test_id);
+
+  let (_, registered) = register_test_user(&app, &format!("no_hash_{}", test_id), &email, "password123").await;
+  let credentials = json!({ "email": email, "password": "password123" });
+  let (_, logged_in) = auth_request(&app, http::Method::POST, "/api/v1/auth/login", None, Some(credentials)).await;
+  let token = logged_in["results"]["token"].as_str().unwrap();
+  let (_, me) = auth_request(&app, http::Method::GET, "/api/v1/auth/me", Some(token), None).await;
+
+  // Anywhere in the body, under any key
+  for body in [registered, logged_in, me] {
+    let body = body.to_string();
+    assert!(!body.contains("password_hash"), "{body}");
+    assert!(!body.contains("$argon2"), "{body}");
+  }
 }
@@ -0,0 +1,136 @@
+//! Database backups: a `pg_dump` uploaded to the store, rotation past `BACKUP_KEEP` and the
+//! admin listing per environment. The store is kept in memory instead of S3.
+
+use std::{
+  path::Path,
+  sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use axum::http::{self, StatusCode};
+use chrono::{Duration, Utc};
+use myapp_api_rust::{
+  AppResult, AppState,
+  config::AppConfig,
+  modules::backups::{
+    backup_models::{BackupObject, backup_key},
+    backup_service::run_backup,
+    backup_store::BackupStore,
+  },
+};
+
+use crate::common::{TestApp, fixtures::UserFactory};
+
+mod common;
+
+#[derive(Default)]
+struct MemoryStore {
+  objects: Mutex<Vec<BackupObject>>,
+}
+
+impl MemoryStore {
+  fn keys(&self) -> Vec<String> {
+    let mut keys: Vec<String> = self.objects.lock().unwrap().iter().map(|object| object.key.clone()).collect();
+    keys.sort();
+    keys
+  }
+}
+
+#[async_trait]
+impl BackupStore for MemoryStore {
+  async fn upload(&self, key: &str, path: &Path) -> AppResult<u64> {
+    let size_bytes = std::fs::metadata(path).unwrap().len();
+    self.objects.lock().unwrap().push(BackupObject {
+      key: key.to_string(),
+      size_bytes,
+      created_at: Utc::now(),
+    });
+    Ok(size_bytes)
+  }
+
+  async fn download(&self, _key: &str, _path: &Path) -> AppResult<()> {
+    unimplemented!("not used by these tests")
+  }
+
+  async fn list(&self, prefix: &str) -> AppResult<Vec<BackupObject>> {
+    Ok(
+      self
+        .objects
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|object| object.key.starts_with(prefix))
+        .cloned()
+        .collect(),
+    )
+  }
+
+  async fn delete(&self, key: &str) -> AppResult<()> {
+    self.objects.lock().unwrap().retain(|object| object.key != key);
+    Ok(())
+  }
+}
+
+#[tokio::test]
+async fn test_backups_are_rotated_and_listed_per_environment() {
+  let store = Arc::new(MemoryStore::default());
+  let now = Utc::now();
+  let old_backup = |environment: &str, days: i64| BackupObject {
+    key: backup_key("backups", environment, now - Duration::days(days)),
+    size_bytes: 1,
+    created_at: now - Duration::days(days),
+  };
+  store
+    .objects
+    .lock()
+    .unwrap()
+    .extend([old_backup("production", 2), old_backup("production", 1), old_backup("staging", 5)]);
+
+  let mut config = AppConfig::from_env();
+  config.backups.environment = "production".to_string();
+  config.backups.keep = 2;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config).with_backup_store(store.clone())).await;
+  let admin = UserFactory::new().create(&app).await;
+
+  let backup = run_backup(&app.state.config.backups, store.as_ref()).await.unwrap();
+  assert!(backup.key.starts_with("backups/production/"));
+  assert!(backup.size_bytes > 0, "pg_dump wrote an archive");
+  // The oldest production backup is past BACKUP_KEEP; other environments are left alone
+  assert_eq!(
+    store.keys(),
+    [old_backup("production", 1).key, backup.key.clone(), old_backup("staging", 5).key]
+  );
+
+  let (status, _) = app.call(http::Method::GET, "/api/v1/admin/backups", &admin, None, None).await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+
+  // The same app, with `admin` listed in BACKUP_ADMIN_IDS
+  let mut state = AppState::clone(&app.state);
+  state.config.backups.admin_user_ids = vec![admin.id()];
+  let state = Arc::new(state);
+  let admin_app = TestApp {
+    router: myapp_api_rust::app(state.clone()),
+    state,
+    db: app.db.clone(),
+  };
+
+  let (status, body) = admin_app.call(http::Method::GET, "/api/v1/admin/backups", &admin, None, None).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(body["results"]["environment"], "production");
+  let keys: Vec<&str> = body["results"]["backups"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|b| b["key"].as_str().unwrap())
+    .collect();
+  assert_eq!(keys, [backup.key.as_str(), old_backup("production", 1).key.as_str()]);
+
+  let (_, body) = admin_app
+    .call(http::Method::GET, "/api/v1/admin/backups?environment=staging", &admin, None, None)
+    .await;
+  assert_eq!(body["results"]["backups"][0]["key"], old_backup("staging", 5).key);
+  let (status, _) = admin_app
+    .call(http::Method::GET, "/api/v1/admin/backups?environment=../prod", &admin, None, None)
+    .await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,199 @@
+//! Stripe billing: checkout through a fake gateway, and signed webhook deliveries updating the
+//! workspace subscription.
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use axum::{
+  body::Body,
+  http::{self, Request, StatusCode},
+};
+use chrono::Utc;
+use myapp_api_rust::{
+  AppResult,
+  config::AppConfig,
+  modules::{
+    billing::{
+      billing_models::{CheckoutSessionResponse, PortalSessionResponse},
+      stripe::{self, SIGNATURE_HEADER, StripeGateway},
+    },
+    datastores::workspaces::{Workspace, WorkspaceRole},
+  },
+};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+  test_id,
+};
+
+mod common;
+
+const WEBHOOK_SECRET: &str = "whsec_test";
+
+/// Answers like Stripe would and records the customers it created.
+#[derive(Default)]
+struct FakeStripe {
+  customers: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl StripeGateway for FakeStripe {
+  async fn create_customer(&self, _workspace: &Workspace) -> AppResult<String> {
+    let customer_id = format!("cus_{}", test_id());
+    self.customers.lock().unwrap().push(customer_id.clone());
+    Ok(customer_id)
+  }
+
+  async fn create_checkout_session(&self, customer_id: &str, workspace_id: Uuid) -> AppResult<CheckoutSessionResponse> {
+    Ok(CheckoutSessionResponse {
+      id: format!("cs_{}", workspace_id.simple()),
+      url: format!("https://checkout.stripe.test/{}", customer_id),
+    })
+  }
+
+  async fn create_portal_session(&self, customer_id: &str) -> AppResult<PortalSessionResponse> {
+    Ok(PortalSessionResponse {
+      url: format!("https://billing.stripe.test/{}", customer_id),
+    })
+  }
+}
+
+async fn billing_app(stripe: Arc<FakeStripe>) -> TestApp {
+  let mut config = AppConfig::from_env();
+  config.billing.webhook_secret = Some(WEBHOOK_SECRET.to_string());
+  TestApp::isolated_with(|builder| builder.with_config(config).with_stripe_gateway(stripe)).await
+}
+
+async fn deliver(app: &TestApp, event: &Value, signature: Option<String>) -> (StatusCode, Value) {
+  let payload = serde_json::to_vec(event).unwrap();
+  let mut request = Request::builder()
+    .method(http::Method::POST)
+    .uri("/api/v1/billing/webhooks/stripe")
+    .header(http::header::CONTENT_TYPE, "application/json");
+  if let Some(signature) = signature {
+    request = request.header(SIGNATURE_HEADER, signature);
+  }
+  let (status, _, body) = app.send(request.body(Body::from(payload)).unwrap()).await;
+  (status, serde_json::from_slice(&body).unwrap())
+}
+
+async fn deliver_signed(app: &TestApp, event: &Value) -> (StatusCode, Value) {
+  let signature = stripe::signature_header(&serde_json::to_vec(event).unwrap(), WEBHOOK_SECRET, Utc::now().timestamp());
+  deliver(app, event, Some(signature)).await
+}
+
+fn subscription_event(event_type: &str, customer_id: &str, status: &str, created: i64) -> Value {
+  json!({
+    "id": format!("evt_{}", test_id()),
+    "type": event_type,
+    "created": created,
+    "data": { "object": { "id": "sub_123", "customer": customer_id, "status": status, "current_period_end": 1_900_000_000 } }
+  })
+}
+
+#[tokio::test]
+async fn test_checkout_and_webhooks_update_subscription() {
+  let stripe = Arc::new(FakeStripe::default());
+  let app = billing_app(stripe.clone()).await;
+  let owner = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &owner).await;
+
+  // Only admins manage billing
+  let (status, _) = app
+    .call(http::Method::POST, "/api/v1/billing/checkout-session", &member, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/billing/checkout-session", &owner, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  let customer_id = stripe.customers.lock().unwrap()[0].clone();
+  assert_eq!(body["results"]["url"], format!("https://checkout.stripe.test/{}", customer_id));
+
+  // A second checkout reuses the customer
+  app
+    .call(http::Method::POST, "/api/v1/billing/checkout-session", &owner, workspace.id, None)
+    .await;
+  assert_eq!(stripe.customers.lock().unwrap().len(), 1);
+
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/billing/subscription", &member, workspace.id, None)
+    .await;
+  assert!(body["results"]["status"].is_null());
+
+  let now = Utc::now().timestamp();
+  let (status, _) = deliver_signed(&app, &subscription_event("customer.subscription.created", &customer_id, "active", now)).await;
+  assert_eq!(status, StatusCode::OK);
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/billing/subscription", &member, workspace.id, None)
+    .await;
+  assert_eq!(body["results"]["status"], "active");
+
+  // An older event delivered late does not overwrite the newer state
+  deliver_signed(
+    &app,
+    &subscription_event("customer.subscription.updated", &customer_id, "past_due", now - 60),
+  )
+  .await;
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/billing/subscription", &member, workspace.id, None)
+    .await;
+  assert_eq!(body["results"]["status"], "active");
+
+  let (status, _) = app
+    .call(http::Method::POST, "/api/v1/billing/checkout-session", &owner, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::CONFLICT);
+
+  deliver_signed(
+    &app,
+    &subscription_event("customer.subscription.updated", &customer_id, "past_due", now + 1),
+  )
+  .await;
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/billing/subscription", &member, workspace.id, None)
+    .await;
+  assert_eq!(body["results"]["status"], "past_due");
+
+  deliver_signed(
+    &app,
+    &subscription_event("customer.subscription.deleted", &customer_id, "canceled", now + 2),
+  )
+  .await;
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/billing/subscription", &member, workspace.id, None)
+    .await;
+  assert_eq!(body["results"]["status"], "canceled");
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/billing/portal", &owner, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(body["results"]["url"], format!("https://billing.stripe.test/{}", customer_id));
+}
+
+#[tokio::test]
+async fn test_webhook_requires_a_valid_signature() {
+  let app = billing_app(Arc::new(FakeStripe::default())).await;
+  let event = subscription_event("customer.subscription.updated", "cus_unknown", "active", Utc::now().timestamp());
+  let payload = serde_json::to_vec(&event).unwrap();
+
+  let (status, _) = deliver(&app, &event, None).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+
+  let wrong_secret = stripe::signature_header(&payload, "whsec_other", Utc::now().timestamp());
+  let (status, _) = deliver(&app, &event, Some(wrong_secret)).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+
+  // A captured delivery cannot be replayed once the tolerance has passed
+  let stale = stripe::signature_header(&payload, WEBHOOK_SECRET, Utc::now().timestamp() - 3600);
+  let (status, _) = deliver(&app, &event, Some(stale)).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+
+  // Events for customers without a workspace are acknowledged, so Stripe stops retrying them
+  let (status, _) = deliver_signed(&app, &event).await;
+  assert_eq!(status, StatusCode::OK);
+}
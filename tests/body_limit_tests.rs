@@ -0,0 +1,88 @@
+//! Request body limits: regular endpoints take `BODY_LIMIT_BYTES`, imports the larger
+//! `UPLOAD_BODY_LIMIT_BYTES`, and oversized bodies are refused with a 400 naming the limit.
+
+use axum::{
+  body::Body,
+  http::{self, HeaderValue, StatusCode},
+};
+use myapp_api_rust::config::AppConfig;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{TestUser, UserFactory, WorkspaceFactory},
+  request,
+};
+
+mod common;
+
+async fn app() -> (TestApp, TestUser, Uuid) {
+  let mut config = AppConfig::from_env();
+  config.body_limit.default_bytes = 1024;
+  config.body_limit.upload_bytes = 4 * 1024;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  (app, user, workspace.id)
+}
+
+/// Posts `body` to `uri`, declaring its length only when `declared`.
+async fn post(app: &TestApp, user: &TestUser, workspace_id: Uuid, uri: &str, body: String, declared: bool) -> (StatusCode, Value) {
+  let mut request = request(http::Method::POST, uri, user, workspace_id, None);
+  if declared {
+    request.headers_mut().insert(http::header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+  }
+  *request.body_mut() = Body::from(body);
+  let (status, _, body) = app.send(request).await;
+  (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn contact(size: usize) -> String {
+  let contact = json!({ "code": "BIG-1", "name": "Big", "email": "big@example.com", "contact_type": "customer", "address": "" });
+  let padding = size - contact.to_string().len();
+  json!({ "code": "BIG-1", "name": "Big", "email": "big@example.com", "contact_type": "customer", "address": "x".repeat(padding) }).to_string()
+}
+
+#[tokio::test]
+async fn test_declared_oversized_bodies_are_refused_before_reading() {
+  let (app, user, workspace_id) = app().await;
+
+  let (status, body) = post(&app, &user, workspace_id, "/api/v1/contacts", contact(2048), true).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  assert_eq!(
+    body["message"], "Request body is too large (2 KiB); the maximum allowed size is 1 KiB",
+    "{body}"
+  );
+}
+
+#[tokio::test]
+async fn test_streamed_oversized_bodies_are_refused_with_the_limit() {
+  let (app, user, workspace_id) = app().await;
+
+  let (status, body) = post(&app, &user, workspace_id, "/api/v1/contacts", contact(1500), false).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  assert_eq!(
+    body["message"], "Request body exceeds the maximum allowed size of 1 KiB for this endpoint",
+    "{body}"
+  );
+}
+
+#[tokio::test]
+async fn test_imports_get_the_upload_limit() {
+  let (app, user, workspace_id) = app().await;
+  let csv = |rows: usize| format!("Name,Email\n{}", "Someone,someone@example.com\n".repeat(rows));
+
+  // Over the regular limit but within the upload one: refused for its contents, not its size
+  let uri = "/api/v1/import/unknown?entity=contacts";
+  let (status, body) = post(&app, &user, workspace_id, uri, csv(70), true).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+  assert!(body["details"]["source"].is_array(), "{body}");
+
+  let (status, body) = post(&app, &user, workspace_id, uri, csv(200), false).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  assert_eq!(
+    body["message"], "Request body exceeds the maximum allowed size of 4 KiB for this endpoint",
+    "{body}"
+  );
+}
@@ -0,0 +1,81 @@
+//! The product category tree and its per-node aggregates.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::modules::datastores::workspaces::{Workspace, WorkspaceRole};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, TestUser, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+async fn get_tree(app: &TestApp, user: &TestUser, workspace_id: Uuid) -> (StatusCode, Value) {
+  app.call(http::Method::GET, "/api/v1/categories/tree", user, workspace_id, None).await
+}
+
+async fn create_category(app: &TestApp, workspace: &Workspace, name: &str, parent_id: Option<Uuid>) -> Uuid {
+  let mut conn = app.db.acquire().await.unwrap();
+  let code = format!("CT-{}", &Uuid::new_v4().simple().to_string()[..12]);
+  sqlx::query_scalar("INSERT INTO product_categories (code, name, parent_id, workspace_id) VALUES ($1, $2, $3, $4) RETURNING id")
+    .bind(code)
+    .bind(name)
+    .bind(parent_id)
+    .bind(workspace.id)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap()
+}
+
+fn decimal(value: &Value) -> Decimal {
+  Decimal::try_from(value.as_f64().unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn test_tree_nests_categories_with_subtree_aggregates() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let viewer = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&viewer, WorkspaceRole::Viewer).create(&app, &user).await;
+  let other = WorkspaceFactory::new().create(&app, &user).await;
+
+  let hardware = create_category(&app, &workspace, "Hardware", None).await;
+  let tools = create_category(&app, &workspace, "Tools", Some(hardware)).await;
+  let drills = create_category(&app, &workspace, "Drills", Some(tools)).await;
+  create_category(&app, &workspace, "Apparel", None).await;
+  create_category(&app, &other, "Elsewhere", None).await;
+
+  // Unit cost 100.00 each
+  ProductFactory::new().category(hardware).stock(2).create(&app, &workspace, &user).await;
+  ProductFactory::new().category(tools).stock(3).create(&app, &workspace, &user).await;
+  ProductFactory::new().category(drills).stock(5).create(&app, &workspace, &user).await;
+  ProductFactory::new().category(drills).stock(1).create(&app, &workspace, &user).await;
+
+  let (status, body) = get_tree(&app, &user, workspace.id).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let roots = body["results"].as_array().unwrap();
+  let names: Vec<&str> = roots.iter().map(|node| node["name"].as_str().unwrap()).collect();
+  assert_eq!(names, ["Apparel", "Hardware"], "only this workspace's roots, by name");
+  assert_eq!(roots[0]["total_product_count"], 0);
+  assert_eq!(decimal(&roots[0]["total_stock_value"]), Decimal::ZERO);
+
+  let hardware = &roots[1];
+  assert_eq!(hardware["product_count"], 1);
+  assert_eq!(hardware["total_product_count"], 4);
+  assert_eq!(decimal(&hardware["stock_value"]), Decimal::new(200, 0));
+  assert_eq!(decimal(&hardware["total_stock_value"]), Decimal::new(1100, 0));
+  let tools = &hardware["children"][0];
+  assert_eq!(tools["name"], "Tools");
+  assert_eq!(tools["total_product_count"], 3);
+  assert_eq!(decimal(&tools["total_stock_value"]), Decimal::new(900, 0));
+  let drills = &tools["children"][0];
+  assert_eq!(drills["product_count"], 2);
+  assert_eq!(decimal(&drills["stock_value"]), Decimal::new(600, 0));
+  assert_eq!(drills["children"], Value::Array(Vec::new()));
+
+  let (status, _) = get_tree(&app, &viewer, workspace.id).await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "reads need the member role like the other product routes");
+}
@@ -0,0 +1,88 @@
+//! Drives the router through `ApiClient` over a real socket, to check that the shared DTOs
+//! round-trip. Runs with `cargo test --features client --test client_tests`.
+
+use myapp_api_rust::{
+  client::{ApiClient, ClientError},
+  modules::{
+    auth::user_dto::LoginUserDto,
+    datastores::contacts::contact_models::{CreateContactRequest, GetContactsQuery, UpdateContactRequest},
+  },
+};
+use reqwest::StatusCode;
+use tokio::net::TcpListener;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_client_contact_crud_cycle() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let base_url = format!("http://{}", listener.local_addr().unwrap());
+  tokio::spawn(axum::serve(listener, app.router.clone()).into_future());
+
+  let mut client = ApiClient::new(base_url);
+  let login = client
+    .login(&LoginUserDto {
+      email: user.user.email.clone(),
+      password: user.password.clone(),
+      workspace_id: None,
+    })
+    .await
+    .unwrap();
+  assert_eq!(login.user.id, user.id());
+  let client = client.with_workspace(workspace.id);
+
+  let created = client
+    .create_contact(&CreateContactRequest {
+      code: String::new(),
+      name: "Client Contact".to_string(),
+      email: "client.contact@example.com".to_string(),
+      position: None,
+      contact_type: "customer".to_string(),
+      address: None,
+      tax_id: None,
+      bank_account: None,
+    })
+    .await
+    .unwrap();
+  assert!(!created.code.is_empty());
+
+  let page = client
+    .list_contacts(&GetContactsQuery {
+      search: Some("client contact".to_string()),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+  assert_eq!(page.pagination.total, 1);
+  assert_eq!(page.list[0].id, created.id);
+
+  let updated = client
+    .update_contact(
+      created.id,
+      &UpdateContactRequest {
+        position: Some("Buyer".to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(updated.position.as_deref(), Some("Buyer"));
+
+  client.delete_contact(created.id).await.unwrap();
+  match client.get_contact(created.id).await {
+    Err(ClientError::Api { status, error }) => {
+      assert_eq!(status, StatusCode::NOT_FOUND);
+      assert!(!error.message.is_empty());
+    }
+    other => panic!("expected a not found error, got {:?}", other.map(|contact| contact.id)),
+  }
+}
@@ -0,0 +1,87 @@
+//! Code formats chosen per entity in the workspace settings.
+
+use axum::http::{self, StatusCode};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, TestUser, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+async fn next_code(app: &TestApp, user: &TestUser, workspace_id: Uuid, query: &str) -> String {
+  let (status, body) = app.call(http::Method::GET, &format!("/api/v1/{query}"), user, workspace_id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  body["results"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_codes_follow_the_workspace_rules() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &admin).await;
+  let category_id: Uuid = {
+    let mut conn = app.db.acquire().await.unwrap();
+    sqlx::query_scalar("INSERT INTO product_categories (code, name, workspace_id) VALUES ('TOOLS', 'Tools', $1) RETURNING id")
+      .bind(workspace.id)
+      .fetch_one(&mut *conn)
+      .await
+      .unwrap()
+  };
+  assert_eq!(next_code(&app, &admin, workspace.id, "products/next-code?name=Hammer").await, "HA-00001");
+
+  let settings_uri = format!("/api/v1/workspaces/{}/settings", workspace.id);
+  let rules = json!({ "code_rules": { "products": {
+    "number_length": 3,
+    "separator": ".",
+    "category_prefixes": { category_id.to_string(): "TL" },
+  }}});
+  let (status, body) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(rules)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["code_rules"]["products"]["separator"], ".");
+
+  assert_eq!(next_code(&app, &admin, workspace.id, "products/next-code?name=Hammer").await, "HA.001");
+  let query = format!("products/next-code?name=Hammer&category={category_id}");
+  assert_eq!(next_code(&app, &admin, workspace.id, &query).await, "TL.001");
+  assert_eq!(next_code(&app, &admin, workspace.id, "contacts/next-code?name=Hammer").await, "HA-00001");
+
+  // Records created without a code get the next one in the same format
+  ProductFactory::new().code("TL.041").create(&app, &workspace, &admin).await;
+  let product = json!({
+    "code": "", "name": "Claw Hammer", "category_id": category_id, "base_unit": "pcs",
+    "selling_price": 10, "unit_cost": 5,
+  });
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/products", &admin, workspace.id, Some(product))
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  assert_eq!(body["results"]["code"], "TL.042");
+
+  // Null brings back the built-in format
+  let reset = json!({ "code_rules": { "products": null } });
+  let (status, body) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(reset)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["code_rules"], json!({}));
+  assert_eq!(next_code(&app, &admin, workspace.id, "products/next-code?name=Hammer").await, "HA-00001");
+}
+
+#[tokio::test]
+async fn test_invalid_rules_are_rejected() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &admin).await;
+  let settings_uri = format!("/api/v1/workspaces/{}/settings", workspace.id);
+
+  for rules in [
+    json!({ "products": { "prefix_length": 4 } }),
+    json!({ "products": { "separator": "x" } }),
+    json!({ "products": { "category_prefixes": { "retail": "r-1" } } }),
+    json!({ "Products; DROP": { "number_length": 3 } }),
+  ] {
+    let body = json!({ "code_rules": rules });
+    let (status, body) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(body)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+  }
+}
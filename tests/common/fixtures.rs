@@ -0,0 +1,250 @@
+//! Builders creating realistic, linked records directly through the repositories of a `TestApp`,
+//! so tests only spell out the fields they are about.
+//!
+//! Every record gets unique values by default, and, like anything else a `TestApp` writes, is
+//! rolled back at the end of the test.
+//!
+//! ```ignore
+//! let owner = UserFactory::new().create(&app).await;
+//! let workspace = WorkspaceFactory::new().create(&app, &owner).await;
+//! let product = ProductFactory::new().with_low_stock().create(&app, &workspace, &owner).await;
+//! ```
+
+use argon2::{
+  Argon2,
+  password_hash::{PasswordHasher, SaltString, rand_core::OsRng},
+};
+#[cfg(feature = "products")]
+use myapp_api_rust::modules::datastores::products::product_models::{CreateProductRequest, Product};
+use myapp_api_rust::modules::{
+  auth::{
+    auth_service,
+    user_dto::{LoginUserDto, RegisterUserDto},
+    user_model::User,
+  },
+  datastores::workspaces::workspace_models::{CreateWorkspaceRequest, Workspace, WorkspaceRole},
+};
+#[cfg(feature = "products")]
+use rust_decimal::Decimal;
+
+use super::{TestApp, test_id};
+
+/// A user created by `UserFactory`, with a token to call the API as them.
+pub struct TestUser {
+  pub user: User,
+  pub password: String,
+  pub token: String,
+}
+
+impl TestUser {
+  pub fn id(&self) -> uuid::Uuid {
+    self.user.id
+  }
+
+  /// The `Authorization` header value for this user.
+  pub fn bearer(&self) -> String {
+    format!("Bearer {}", self.token)
+  }
+}
+
+pub struct UserFactory {
+  username: String,
+  email: String,
+  password: String,
+}
+
+impl UserFactory {
+  pub fn new() -> Self {
+    let id = test_id();
+    Self {
+      username: format!("user_{}", id),
+      email: format!("user_{}@example.com", id),
+      password: "password123".to_string(),
+    }
+  }
+
+  pub fn username(mut self, username: impl Into<String>) -> Self {
+    self.username = username.into();
+    self
+  }
+
+  pub fn email(mut self, email: impl Into<String>) -> Self {
+    self.email = email.into();
+    self
+  }
+
+  pub fn password(mut self, password: impl Into<String>) -> Self {
+    self.password = password.into();
+    self
+  }
+
+  /// Creates the user, without the personal workspace registration adds, and logs them in.
+  pub async fn create(self, app: &TestApp) -> TestUser {
+    let salt = SaltString::generate(&mut OsRng);
+    let password_hash = Argon2::default()
+      .hash_password(self.password.as_bytes(), &salt)
+      .expect("Failed to hash password")
+      .to_string();
+    let dto = RegisterUserDto {
+      username: self.username,
+      email: self.email,
+      password: self.password.clone(),
+    };
+    let user = app
+      .state
+      .auth_repository
+      .create_user(&dto, &password_hash)
+      .await
+      .expect("Failed to create user");
+
+    let login = LoginUserDto {
+      email: user.email.clone(),
+      password: self.password.clone(),
+      workspace_id: None,
+    };
+    let (token, _) = auth_service::login_user(app.state.clone(), login).await.expect("Failed to log in user");
+
+    TestUser {
+      user,
+      password: self.password,
+      token,
+    }
+  }
+}
+
+pub struct WorkspaceFactory {
+  name: String,
+  description: Option<String>,
+  members: Vec<(uuid::Uuid, WorkspaceRole)>,
+}
+
+impl WorkspaceFactory {
+  pub fn new() -> Self {
+    Self {
+      name: format!("Workspace {}", test_id()),
+      description: None,
+      members: Vec::new(),
+    }
+  }
+
+  pub fn name(mut self, name: impl Into<String>) -> Self {
+    self.name = name.into();
+    self
+  }
+
+  pub fn description(mut self, description: impl Into<String>) -> Self {
+    self.description = Some(description.into());
+    self
+  }
+
+  /// Adds `user` to the workspace with `role`, besides the owner.
+  pub fn member(mut self, user: &TestUser, role: WorkspaceRole) -> Self {
+    self.members.push((user.id(), role));
+    self
+  }
+
+  /// Creates the workspace as the API does, with `owner` as its admin, then adds the members.
+  pub async fn create(self, app: &TestApp, owner: &TestUser) -> Workspace {
+    let repository = &app.state.workspace_repository;
+    let request = CreateWorkspaceRequest {
+      name: self.name,
+      description: self.description,
+    };
+    let workspace = repository
+      .create_and_assign_owner(request, owner.id(), app.state.config.database.tenant_isolation)
+      .await
+      .expect("Failed to create workspace");
+
+    for (user_id, role) in self.members {
+      repository
+        .add_user_to_workspace(workspace.id, user_id, role)
+        .await
+        .expect("Failed to add workspace member");
+    }
+
+    workspace
+  }
+}
+
+#[cfg(feature = "products")]
+pub struct ProductFactory {
+  request: CreateProductRequest,
+}
+
+#[cfg(feature = "products")]
+impl ProductFactory {
+  /// A tracked product comfortably above its reorder level.
+  pub fn new() -> Self {
+    let id = test_id();
+    Self {
+      request: CreateProductRequest {
+        code: format!("PRD-{}", &id[id.len() - 10..]),
+        name: format!("Product {}", id),
+        category_id: None,
+        base_unit: "pcs".to_string(),
+        unit_on_report_preview: None,
+        selling_price: Decimal::new(15_000, 2),
+        unit_cost: Decimal::new(10_000, 2),
+        supplier_id: None,
+        track_inventory: Some(true),
+        description: None,
+        sku: None,
+        barcode: None,
+        minimum_stock: Some(10),
+        maximum_stock: Some(500),
+        reorder_level: Some(20),
+        current_stock: Some(100),
+        tax_type: None,
+        tax_rate: None,
+        tax_amount: None,
+        status: None,
+      },
+    }
+  }
+
+  /// Stock below the minimum and the reorder level, so the product needs restocking.
+  pub fn with_low_stock(mut self) -> Self {
+    self.request.current_stock = Some(3);
+    self
+  }
+
+  pub fn code(mut self, code: impl Into<String>) -> Self {
+    self.request.code = code.into();
+    self
+  }
+
+  pub fn name(mut self, name: impl Into<String>) -> Self {
+    self.request.name = name.into();
+    self
+  }
+
+  pub fn sku(mut self, sku: impl Into<String>) -> Self {
+    self.request.sku = Some(sku.into());
+    self
+  }
+
+  pub fn barcode(mut self, barcode: impl Into<String>) -> Self {
+    self.request.barcode = Some(barcode.into());
+    self
+  }
+
+  pub fn stock(mut self, current_stock: i32) -> Self {
+    self.request.current_stock = Some(current_stock);
+    self
+  }
+
+  pub fn category(mut self, category_id: uuid::Uuid) -> Self {
+    self.request.category_id = Some(category_id);
+    self
+  }
+
+  /// Creates the product in `workspace` as `creator`.
+  pub async fn create(self, app: &TestApp, workspace: &Workspace, creator: &TestUser) -> Product {
+    app
+      .state
+      .product_repository
+      .create_by_workspace(self.request, workspace.id, creator.id())
+      .await
+      .expect("Failed to create product")
+  }
+}
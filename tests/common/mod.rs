@@ -0,0 +1,271 @@
+//! Setup shared by the integration test binaries.
+//!
+//! Each test gets its own `TestApp`, whose repositories and stores all run in one database
+//! transaction that is rolled back when the app is dropped. Tests can therefore run in parallel
+//! and leave nothing behind, without cleaning up after themselves.
+
+// Each test binary compiles this module and uses a different part of it
+#![allow(dead_code)]
+
+pub mod fixtures;
+
+use std::{ops::Deref, sync::Arc};
+
+use axum::{
+  Router,
+  body::{Body, Bytes},
+  http::{self, HeaderMap, Request, StatusCode},
+};
+use fixtures::TestUser;
+use http_body_util::BodyExt;
+#[cfg(any(feature = "contacts", feature = "products"))]
+use myapp_api_rust::modules::approvals::approval_repository::PostgresApprovalRepository;
+#[cfg(feature = "billing")]
+use myapp_api_rust::modules::billing::billing_repository::PostgresBillingRepository;
+#[cfg(feature = "connectors")]
+use myapp_api_rust::modules::connectors::connector_repository::PostgresConnectorRepository;
+#[cfg(feature = "currencies")]
+use myapp_api_rust::modules::currencies::exchange_rate_repository::PostgresExchangeRateRepository;
+#[cfg(feature = "contacts")]
+use myapp_api_rust::modules::datastores::contacts::contact_repository::SqlxContactRepository;
+#[cfg(feature = "products")]
+use myapp_api_rust::modules::datastores::products::product_repository::SqlxProductRepository;
+#[cfg(feature = "email_templates")]
+use myapp_api_rust::modules::email_templates::email_template_repository::PostgresEmailTemplateRepository;
+#[cfg(feature = "exports")]
+use myapp_api_rust::modules::exports::export_repository::PostgresExportProfileRepository;
+#[cfg(feature = "geo")]
+use myapp_api_rust::modules::geo::geo_repository::PostgresGeoLookupRepository;
+#[cfg(feature = "inbound")]
+use myapp_api_rust::modules::inbound::inbound_repository::PostgresInboundRepository;
+#[cfg(feature = "rendering")]
+use myapp_api_rust::modules::rendering::print_template_repository::PostgresPrintTemplateRepository;
+#[cfg(feature = "reports")]
+use myapp_api_rust::modules::reports::report_repository::PostgresReportScheduleRepository;
+#[cfg(feature = "contacts")]
+use myapp_api_rust::modules::statements::statement_repository::PostgresStatementRepository;
+#[cfg(any(feature = "contacts", feature = "products"))]
+use myapp_api_rust::modules::{
+  operations::operation_repository::PostgresOperationRepository, record_locks::record_lock_repository::PostgresRecordLockRepository,
+  search::search_repository::PostgresSearchRepository,
+};
+use myapp_api_rust::{
+  AppState, AppStateBuilder, app,
+  config::AppConfig,
+  modules::{
+    admin::admin_repository::PostgresAdminRepository,
+    audit::audit_repository::PostgresAuditRepository,
+    auth::{
+      auth_repository::AuthRepositoryImpl, password_reset_repository::PostgresPasswordResetRepository,
+      session_activity::PostgresSessionActivityStore, token_revocation::PostgresTokenRevocationStore,
+    },
+    datastores::workspaces::workspace_repository::PostgresWorkspaceRepository,
+    feature_flags::feature_flag_repository::PostgresFeatureFlagRepository,
+    organizations::organization_repository::PostgresOrganizationRepository,
+    overview::overview_repository::PostgresOverviewRepository,
+    retention::retention_repository::PostgresRetentionRepository,
+    trial::trial_repository::PostgresTrialRepository,
+    usage::usage_repository::PostgresUsageRepository,
+    user_export::user_export_repository::PostgresUserExportRepository,
+    workspace_settings::workspace_settings_repository::PostgresWorkspaceSettingsRepository,
+  },
+  utils::{DbExecutor, field_encryption::FieldCipher},
+};
+use serde_json::Value;
+use sqlx::PgPool;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Connects to the test database, applying the migrations first when `sqlx-cli` is installed.
+pub async fn test_pool() -> PgPool {
+  dotenvy::dotenv().ok();
+  let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+  let pool = PgPool::connect(&db_url).await.expect("Failed to connect to test database");
+
+  let output = std::process::Command::new("sqlx")
+    .args(["migrate", "run", "--database-url", &db_url])
+    .output();
+
+  match output {
+    Ok(output) if output.status.success() => {
+      eprintln!("Migrations applied successfully");
+    }
+    Ok(output) => {
+      eprintln!("Migration failed: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    Err(e) => {
+      eprintln!("Failed to run migrations: {}. Continuing anyway...", e);
+    }
+  }
+
+  pool
+}
+
+/// The key encrypting the sensitive columns in tests, as an entry of `FIELD_ENCRYPTION_KEYS`.
+pub const TEST_ENCRYPTION_KEY: &str = "test:AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+
+/// A unique suffix for values that must not collide with other tests, such as usernames.
+pub fn test_id() -> String {
+  std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .unwrap()
+    .as_nanos()
+    .to_string()
+}
+
+/// Whom a test request is sent as: a `TestUser`, or a bare access token such as one returned by
+/// a workspace switch.
+pub trait Caller {
+  /// The `Authorization` header value.
+  fn bearer(&self) -> String;
+}
+
+impl Caller for TestUser {
+  fn bearer(&self) -> String {
+    TestUser::bearer(self)
+  }
+}
+
+impl Caller for str {
+  fn bearer(&self) -> String {
+    format!("Bearer {}", self)
+  }
+}
+
+/// A JSON request sent as `caller`, in `workspace_id` when given.
+pub fn request(
+  method: http::Method,
+  uri: &str,
+  caller: &(impl Caller + ?Sized),
+  workspace_id: impl Into<Option<Uuid>>,
+  body: Option<Value>,
+) -> Request<Body> {
+  let mut request = Request::builder()
+    .method(method)
+    .uri(uri)
+    .header(http::header::AUTHORIZATION, caller.bearer())
+    .header(http::header::CONTENT_TYPE, "application/json");
+  if let Some(workspace_id) = workspace_id.into() {
+    request = request.header("X-Workspace-ID", workspace_id.to_string());
+  }
+  request.body(body.map_or_else(Body::empty, |body| Body::from(body.to_string()))).unwrap()
+}
+
+/// The application on a rolled-back transaction. Dereferences to its `Router`, so it can be
+/// passed wherever a router is expected.
+///
+/// Rows written in one test are invisible to the others until the end of the test, when they
+/// are discarded. Two tests writing the same unique value (an email, a username) wait for each
+/// other instead of failing, so prefer values made unique with `test_id()`.
+pub struct TestApp {
+  pub router: Router,
+  pub state: Arc<AppState>,
+  /// The transaction the app runs in, for querying the rows a test created.
+  pub db: DbExecutor,
+}
+
+impl TestApp {
+  pub async fn isolated() -> Self {
+    Self::isolated_with(|builder| builder).await
+  }
+
+  /// Like `isolated`, with state parts replaced by `customize`, e.g. the config or a fake gateway.
+  pub async fn isolated_with(customize: impl FnOnce(AppStateBuilder) -> AppStateBuilder) -> Self {
+    let pool = test_pool().await;
+    let db = DbExecutor::rolled_back(&pool).await.expect("Failed to open test transaction");
+    let jwt_secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set for tests");
+
+    let mut config = AppConfig::from_env();
+    config.encryption.keys = vec![TEST_ENCRYPTION_KEY.to_string()];
+    let cipher = Arc::new(FieldCipher::from_config(&config.encryption).expect("Invalid test encryption key"));
+
+    let builder = AppState::builder(pool, jwt_secret).with_config(config).with_field_cipher(cipher.clone());
+    #[cfg(feature = "contacts")]
+    let builder = builder.with_contact_repository(Arc::new(SqlxContactRepository::new(db.clone()).with_cipher(cipher.clone())));
+    #[cfg(feature = "products")]
+    let builder = builder.with_product_repository(Arc::new(SqlxProductRepository::new(db.clone())));
+    #[cfg(feature = "billing")]
+    let builder = builder.with_billing_repository(Arc::new(PostgresBillingRepository::new(db.clone())));
+    #[cfg(feature = "reports")]
+    let builder = builder.with_report_schedule_repository(Arc::new(PostgresReportScheduleRepository::new(db.clone())));
+    #[cfg(feature = "email_templates")]
+    let builder = builder.with_email_template_repository(Arc::new(PostgresEmailTemplateRepository::new(db.clone())));
+    #[cfg(feature = "rendering")]
+    let builder = builder.with_print_template_repository(Arc::new(PostgresPrintTemplateRepository::new(db.clone())));
+    #[cfg(feature = "exports")]
+    let builder = builder.with_export_profile_repository(Arc::new(PostgresExportProfileRepository::new(db.clone())));
+    #[cfg(feature = "inbound")]
+    let builder = builder.with_inbound_repository(Arc::new(PostgresInboundRepository::new(db.clone()).with_cipher(cipher.clone())));
+    #[cfg(feature = "connectors")]
+    let builder = builder.with_connector_repository(Arc::new(PostgresConnectorRepository::new(db.clone()).with_cipher(cipher.clone())));
+    #[cfg(feature = "currencies")]
+    let builder = builder.with_exchange_rate_repository(Arc::new(PostgresExchangeRateRepository::new(db.clone())));
+    #[cfg(feature = "geo")]
+    let builder = builder.with_geo_lookup_repository(Arc::new(PostgresGeoLookupRepository::new(db.clone())));
+    #[cfg(any(feature = "contacts", feature = "products"))]
+    let builder = builder.with_approval_repository(Arc::new(PostgresApprovalRepository::new(db.clone())));
+    #[cfg(any(feature = "contacts", feature = "products"))]
+    let builder = builder.with_record_lock_repository(Arc::new(PostgresRecordLockRepository::new(db.clone())));
+    #[cfg(any(feature = "contacts", feature = "products"))]
+    let builder = builder.with_operation_repository(Arc::new(PostgresOperationRepository::new(db.clone())));
+    #[cfg(any(feature = "contacts", feature = "products"))]
+    let builder = builder.with_search_repository(Arc::new(PostgresSearchRepository::new(db.clone())));
+    #[cfg(feature = "contacts")]
+    let builder = builder.with_statement_repository(Arc::new(PostgresStatementRepository::new(db.clone())));
+    let builder = builder
+      .with_auth_repository(Arc::new(AuthRepositoryImpl::new(db.clone())))
+      .with_workspace_repository(Arc::new(PostgresWorkspaceRepository::new(db.clone())))
+      .with_token_revocations(Arc::new(PostgresTokenRevocationStore::new(db.clone())))
+      .with_session_activity(Arc::new(PostgresSessionActivityStore::new(db.clone())))
+      .with_usage_repository(Arc::new(PostgresUsageRepository::new(db.clone())))
+      .with_trial_repository(Arc::new(PostgresTrialRepository::new(db.clone())))
+      .with_feature_flag_repository(Arc::new(PostgresFeatureFlagRepository::new(db.clone())))
+      .with_retention_repository(Arc::new(PostgresRetentionRepository::new(db.clone())))
+      .with_workspace_settings_repository(Arc::new(PostgresWorkspaceSettingsRepository::new(db.clone())))
+      .with_audit_repository(Arc::new(PostgresAuditRepository::new(db.clone())))
+      .with_admin_repository(Arc::new(PostgresAdminRepository::new(db.clone())))
+      .with_overview_repository(Arc::new(PostgresOverviewRepository::new(db.clone())))
+      .with_organization_repository(Arc::new(PostgresOrganizationRepository::new(db.clone())))
+      .with_user_export_repository(Arc::new(PostgresUserExportRepository::new(db.clone())))
+      .with_password_reset_repository(Arc::new(PostgresPasswordResetRepository::new(db.clone())));
+    let state = customize(builder).build();
+
+    Self {
+      router: app(state.clone()),
+      state,
+      db,
+    }
+  }
+}
+
+impl Deref for TestApp {
+  type Target = Router;
+
+  fn deref(&self) -> &Router {
+    &self.router
+  }
+}
+
+impl TestApp {
+  /// Sends `request` to the app and reads the whole response.
+  pub async fn send(&self, request: Request<Body>) -> (StatusCode, HeaderMap, Bytes) {
+    let response = self.router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    (status, headers, response.into_body().collect().await.unwrap().to_bytes())
+  }
+
+  /// Sends a JSON request (see `request`) and returns the status with the JSON body, `Null` when
+  /// the body is empty or not JSON.
+  pub async fn call(
+    &self,
+    method: http::Method,
+    uri: &str,
+    caller: &(impl Caller + ?Sized),
+    workspace_id: impl Into<Option<Uuid>>,
+    body: Option<Value>,
+  ) -> (StatusCode, Value) {
+    let (status, _, body) = self.send(request(method, uri, caller, workspace_id, body)).await;
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+  }
+}
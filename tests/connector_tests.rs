@@ -0,0 +1,243 @@
+//! Connectors to online stores: their settings and credentials, and the syncs pushing stock and
+//! pulling orders, against a fake store.
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use axum::http::{self, StatusCode};
+use chrono::{DateTime, TimeZone, Utc};
+use myapp_api_rust::{
+  AppResult,
+  errors::AppError,
+  modules::{
+    connectors::{
+      connector_models::{Connector, ConnectorCredentials, ExternalOrder, StockLevel},
+      connector_service,
+      store_connector::{ConnectorFactory, StoreConnector},
+    },
+    datastores::workspaces::WorkspaceRole,
+  },
+};
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+/// A store recording what the syncs send it, with one order to pull.
+#[derive(Default)]
+struct FakeStore {
+  fail_orders: bool,
+  pushed: Mutex<Vec<StockLevel>>,
+  pulled_since: Mutex<Vec<Option<DateTime<Utc>>>>,
+  credentials: Mutex<Vec<Option<String>>>,
+}
+
+struct FakeConnection(Arc<FakeStore>);
+
+#[async_trait]
+impl StoreConnector for FakeConnection {
+  async fn push_stock(&self, levels: &[StockLevel]) -> AppResult<usize> {
+    self.0.pushed.lock().unwrap().extend_from_slice(levels);
+    Ok(levels.len())
+  }
+
+  async fn pull_orders(&self, since: Option<DateTime<Utc>>) -> AppResult<Vec<ExternalOrder>> {
+    self.0.pulled_since.lock().unwrap().push(since);
+    if self.0.fail_orders {
+      return Err(AppError::Internal("store unavailable".to_string()));
+    }
+    let payload = json!({ "id": 1001, "name": "#1001", "line_items": [{ "sku": "KOPI-1", "quantity": 2 }] });
+    Ok(vec![ExternalOrder {
+      external_id: "1001".to_string(),
+      number: Some("#1001".to_string()),
+      status: Some("paid".to_string()),
+      currency: Some("IDR".to_string()),
+      total: Some(Decimal::new(50_000, 0)),
+      ordered_at: Some(Utc.with_ymd_and_hms(2025, 11, 1, 8, 0, 0).unwrap()),
+      updated_at: Utc.with_ymd_and_hms(2025, 11, 1, 9, 30, 0).unwrap(),
+      payload,
+    }])
+  }
+}
+
+struct FakeFactory(Arc<FakeStore>);
+
+impl ConnectorFactory for FakeFactory {
+  fn connect(&self, _connector: &Connector, credentials: &ConnectorCredentials) -> AppResult<Box<dyn StoreConnector>> {
+    self.0.credentials.lock().unwrap().push(credentials.access_token.clone());
+    Ok(Box::new(FakeConnection(self.0.clone())))
+  }
+}
+
+async fn app_with(store: Arc<FakeStore>) -> TestApp {
+  TestApp::isolated_with(|builder| builder.with_connector_factory(Arc::new(FakeFactory(store)))).await
+}
+
+fn shopify() -> Value {
+  json!({
+    "store_url": "https://kopi.myshopify.com/",
+    "location_id": "655441491",
+    "access_token": "shpat_0123456789abcdef",
+    "sync_interval_minutes": 30,
+  })
+}
+
+#[tokio::test]
+async fn test_syncs_push_stock_and_pull_orders() {
+  let store = Arc::new(FakeStore::default());
+  let app = app_with(store.clone()).await;
+  let admin = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &admin).await;
+  ProductFactory::new().sku("KOPI-1").stock(42).create(&app, &workspace, &admin).await;
+  ProductFactory::new().create(&app, &workspace, &admin).await;
+
+  let (status, body) = app
+    .call(http::Method::PUT, "/api/v1/connectors/shopify", &admin, workspace.id, Some(shopify()))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let connector = &body["results"];
+  assert_eq!(connector["connector"], "shopify");
+  assert_eq!(connector["store_url"], "https://kopi.myshopify.com");
+  assert!(connector.get("access_token").is_none(), "credentials are never returned");
+  assert!(connector["sync"]["last_synced_at"].is_null());
+
+  // Encrypted in the workspace settings, and left out of them when they are read
+  let mut conn = app.db.acquire().await.unwrap();
+  let stored: String = sqlx::query_scalar("SELECT connectors::TEXT FROM workspace_settings WHERE workspace_id = $1")
+    .bind(workspace.id)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+  drop(conn);
+  assert!(stored.contains("kopi.myshopify.com"), "{stored}");
+  assert!(!stored.contains("shpat_"), "{stored}");
+  let settings = format!("/api/v1/workspaces/{}/settings", workspace.id);
+  let (_, body) = app.call(http::Method::GET, &settings, &admin, workspace.id, None).await;
+  assert!(body["results"].get("connectors").is_none(), "{body}");
+
+  assert_eq!(connector_service::run_due(&app.state).await.unwrap(), 1);
+  assert_eq!(store.credentials.lock().unwrap().as_slice(), [Some("shpat_0123456789abcdef".to_string())]);
+  let pushed = store.pushed.lock().unwrap().clone();
+  assert_eq!(
+    pushed,
+    [StockLevel {
+      sku: "KOPI-1".to_string(),
+      quantity: 42
+    }]
+  );
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/connectors/orders", &admin, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let orders = body["results"].as_array().unwrap();
+  assert_eq!(orders.len(), 1);
+  assert_eq!(orders[0]["connector"], "shopify");
+  assert_eq!(orders[0]["number"], "#1001");
+  assert_eq!(orders[0]["payload"]["line_items"][0]["sku"], "KOPI-1");
+
+  let (_, body) = app.call(http::Method::GET, "/api/v1/connectors", &admin, workspace.id, None).await;
+  let sync = &body["results"][0]["sync"];
+  assert!(sync["last_synced_at"].is_string(), "{body}");
+  assert!(sync["last_error"].is_null(), "{body}");
+  assert_eq!(sync["orders_synced_at"], "2025-11-01T09:30:00Z");
+
+  // Not due again before its interval, unless an admin asks
+  assert_eq!(connector_service::run_due(&app.state).await.unwrap(), 0);
+  let (status, _) = app
+    .call(http::Method::POST, "/api/v1/connectors/shopify/sync", &admin, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::ACCEPTED);
+  assert_eq!(connector_service::run_due(&app.state).await.unwrap(), 1);
+  let since = store.pulled_since.lock().unwrap().clone();
+  assert_eq!(since, [None, Some(Utc.with_ymd_and_hms(2025, 11, 1, 9, 30, 0).unwrap())]);
+  let (_, body) = app.call(http::Method::GET, "/api/v1/connectors/orders", &admin, workspace.id, None).await;
+  assert_eq!(body["results"].as_array().unwrap().len(), 1, "orders pulled again are replaced");
+
+  // Disconnecting keeps the orders
+  let (status, _) = app
+    .call(http::Method::DELETE, "/api/v1/connectors/shopify", &admin, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  let (status, _) = app
+    .call(http::Method::GET, "/api/v1/connectors/shopify", &admin, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+  let (_, body) = app.call(http::Method::GET, "/api/v1/connectors/orders", &admin, workspace.id, None).await;
+  assert_eq!(body["results"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_failed_steps_are_retried() {
+  let store = Arc::new(FakeStore {
+    fail_orders: true,
+    ..Default::default()
+  });
+  let app = app_with(store.clone()).await;
+  let admin = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &admin).await;
+
+  let request = json!({
+    "store_url": "https://kopi.example.com",
+    "consumer_key": "ck_0123",
+    "consumer_secret": "cs_4567",
+  });
+  let (status, body) = app
+    .call(http::Method::PUT, "/api/v1/connectors/woocommerce", &admin, workspace.id, Some(request))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["sync_interval_minutes"], 15);
+
+  assert_eq!(connector_service::run_due(&app.state).await.unwrap(), 0, "the sync failed");
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/connectors/woocommerce", &admin, workspace.id, None)
+    .await;
+  let sync = &body["results"]["sync"];
+  assert_eq!(sync["last_error"], "Orders could not be pulled");
+  assert!(sync["stock_synced_at"].is_string(), "{body}");
+  assert!(sync["orders_synced_at"].is_null(), "{body}");
+}
+
+#[tokio::test]
+async fn test_connectors_are_managed_by_admins() {
+  let app = app_with(Arc::new(FakeStore::default())).await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+
+  let (status, _) = app
+    .call(http::Method::PUT, "/api/v1/connectors/shopify", &member, workspace.id, Some(shopify()))
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+  let (status, _) = app.call(http::Method::GET, "/api/v1/connectors", &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+  let (status, _) = app
+    .call(http::Method::GET, "/api/v1/connectors/orders", &member, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK);
+
+  // Credentials of the platform, over HTTPS, to a known platform
+  let mut request = shopify();
+  request["access_token"] = Value::Null;
+  let (status, _) = app
+    .call(http::Method::PUT, "/api/v1/connectors/shopify", &admin, workspace.id, Some(request))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let mut request = shopify();
+  request["store_url"] = json!("http://kopi.myshopify.com");
+  let (status, _) = app
+    .call(http::Method::PUT, "/api/v1/connectors/shopify", &admin, workspace.id, Some(request))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let (status, _) = app
+    .call(http::Method::PUT, "/api/v1/connectors/woocommerce", &admin, workspace.id, Some(shopify()))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let (status, _) = app
+    .call(http::Method::PUT, "/api/v1/connectors/magento", &admin, workspace.id, Some(shopify()))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,138 @@
+//! Replays every operation of the OpenAPI document against the router and checks the responses
+//! against the documented schemas, so the document cannot drift from the handlers unnoticed.
+
+use axum::{
+  body::Body,
+  http::{Request, StatusCode, header},
+};
+use jsonschema::JSONSchema;
+use myapp_api_rust::{modules::auth::auth_service, openapi::openapi_document};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{TestUser, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+/// Tags of operations that do not exchange JSON (WebSocket upgrades and event streams).
+const STREAMING_TAGS: &[&str] = &["realtime"];
+
+#[tokio::test]
+async fn test_responses_match_openapi_document() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  // Path parameters point at a workspace of its own, so documented deletes leave `workspace` intact
+  let scratch = WorkspaceFactory::new().create(&app, &user).await;
+
+  let document = openapi_document();
+  let mut failures = Vec::new();
+
+  for (path, item) in document["paths"].as_object().unwrap() {
+    for (method, operation) in item.as_object().unwrap() {
+      if operation["tags"]
+        .as_array()
+        .is_some_and(|tags| tags.iter().any(|tag| STREAMING_TAGS.contains(&tag.as_str().unwrap_or_default())))
+      {
+        continue;
+      }
+
+      let uri = path
+        .replace("{workspace_id}", &scratch.id.to_string())
+        .replace("{user_id}", &user.id().to_string())
+        .replace("{id}", &Uuid::new_v4().to_string())
+        .replace("{key}", "contract_test");
+      let name = format!("{} {}", method.to_uppercase(), path);
+      let authenticated = operation.get("security").is_some();
+
+      // A token of its own, since a documented logout revokes the one it is sent with
+      let caller = match authenticated {
+        true => Some((token(&app, &user, workspace.id).await, workspace.id)),
+        false => None,
+      };
+      let (status, body) = send(&app, method, &uri, operation, caller).await;
+      if let Err(e) = check_response(&document, operation, status, &body) {
+        failures.push(format!("{name}: {e}"));
+      }
+
+      // The documented security requirement must actually be enforced
+      if authenticated {
+        let (status, body) = send(&app, method, &uri, operation, None).await;
+        if status != StatusCode::UNAUTHORIZED {
+          failures.push(format!("{name}: responded {status} without a token, expected 401"));
+        } else if let Err(e) = check_response(&document, operation, status, &body) {
+          failures.push(format!("{name} without a token: {e}"));
+        }
+      }
+    }
+  }
+
+  assert!(
+    failures.is_empty(),
+    "Responses do not match the OpenAPI document:\n{}",
+    failures.join("\n")
+  );
+}
+
+async fn token(app: &TestApp, user: &TestUser, workspace_id: Uuid) -> String {
+  let (token, _) = auth_service::switch_workspace(&app.state, user.id(), workspace_id)
+    .await
+    .expect("Failed to issue token");
+  token
+}
+
+async fn send(app: &TestApp, method: &str, uri: &str, operation: &Value, caller: Option<(String, Uuid)>) -> (StatusCode, Vec<u8>) {
+  let mut request = Request::builder().method(method.to_uppercase().as_str()).uri(uri);
+  if let Some((token, workspace_id)) = caller {
+    request = request
+      .header(header::AUTHORIZATION, format!("Bearer {}", token))
+      .header("X-Workspace-ID", workspace_id.to_string());
+  }
+  let body = if operation.get("requestBody").is_some() {
+    request = request.header(header::CONTENT_TYPE, "application/json");
+    Body::from("{}")
+  } else {
+    Body::empty()
+  };
+
+  let (status, _, body) = app.send(request.body(body).unwrap()).await;
+  (status, body.to_vec())
+}
+
+/// Checks a response against the operation's documented response for `status`.
+fn check_response(document: &Value, operation: &Value, status: StatusCode, body: &[u8]) -> Result<(), String> {
+  let responses = &operation["responses"];
+  let documented = [status.as_str().to_string(), format!("{}XX", status.as_u16() / 100), "default".to_string()]
+    .into_iter()
+    .find_map(|key| responses.get(key))
+    .ok_or_else(|| format!("status {status} is not documented"))?;
+
+  let Some(schema) = documented.pointer("/content/application~1json/schema") else {
+    return if body.is_empty() {
+      Ok(())
+    } else {
+      Err(format!("status {status} is documented without a body, but one was sent"))
+    };
+  };
+
+  let instance: Value = serde_json::from_slice(body).map_err(|e| format!("status {status} did not return JSON ({e})"))?;
+  let schema = resolve(document, schema);
+  let validator = JSONSchema::compile(schema).map_err(|e| format!("invalid schema {schema}: {e}"))?;
+  validator.validate(&instance).map_err(|errors| {
+    let errors: Vec<String> = errors.map(|e| format!("{} at '{}'", e, e.instance_path)).collect();
+    format!("status {status} does not match the documented schema: {}", errors.join("; "))
+  })
+}
+
+/// Follows a local `$ref` to the schema it points at.
+fn resolve<'a>(document: &'a Value, schema: &'a Value) -> &'a Value {
+  match schema["$ref"].as_str() {
+    Some(reference) => document
+      .pointer(reference.trim_start_matches('#'))
+      .unwrap_or_else(|| panic!("Unresolved reference {reference}")),
+    None => schema,
+  }
+}
@@ -0,0 +1,144 @@
+//! Generates a module with `define_datastore_module!` over a table created in the test
+//! transaction, and runs its repository through the CRUD cycle.
+
+use myapp_api_rust::utils::merge_patch::apply_merge_patch;
+use serde_json::json;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+mod warehouses {
+  myapp_api_rust::define_datastore_module! {
+    resource: "warehouse",
+    label: "Warehouse",
+    table: "warehouses",
+    types: {
+      model: Warehouse,
+      response: WarehouseResponse,
+      create: CreateWarehouseRequest,
+      update: UpdateWarehouseRequest,
+      patch: WarehousePatchTarget,
+      query: GetWarehousesQuery,
+      filters: WarehouseFilters,
+      repository: WarehouseRepository,
+      sqlx_repository: SqlxWarehouseRepository,
+    },
+    code: { prefix_length: 2, number_length: 5 },
+    fields: {
+      #[validate(length(min = 1, message = "Location is required"))]
+      location: String,
+      capacity: Option<i32>,
+    },
+  }
+}
+
+use warehouses::{
+  models::{CreateWarehouseRequest, GetWarehousesQuery, UpdateWarehouseRequest, WarehouseFilters, WarehousePatchTarget},
+  repository::{SqlxWarehouseRepository, WarehouseRepository},
+};
+
+const CREATE_TABLE: &str = r#"
+  CREATE TABLE warehouses (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    code VARCHAR(50) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    location TEXT NOT NULL,
+    capacity INTEGER,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    workspace_id UUID REFERENCES workspaces(id) ON DELETE CASCADE,
+    created_by UUID REFERENCES users(id),
+    updated_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (workspace_id, code)
+  )
+"#;
+
+#[tokio::test]
+async fn test_generated_repository_crud_cycle() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  {
+    let mut conn = app.db.acquire().await.unwrap();
+    sqlx::query(CREATE_TABLE).execute(&mut *conn).await.unwrap();
+  }
+  let repository = SqlxWarehouseRepository::new(app.db.clone());
+
+  let code = repository.get_next_available_code(workspace.id, "Main Depot", None).await.unwrap();
+  let created = repository
+    .create_by_workspace(
+      CreateWarehouseRequest {
+        code: code.clone(),
+        name: "Main Depot".to_string(),
+        location: "Jakarta".to_string(),
+        capacity: Some(500),
+      },
+      workspace.id,
+      user.id(),
+    )
+    .await
+    .unwrap();
+  assert_eq!(created.code, code);
+  assert_eq!(created.capacity, Some(500));
+  assert!(created.is_active);
+
+  let filters = WarehouseFilters::from(GetWarehousesQuery {
+    search: Some("depot".to_string()),
+    ..Default::default()
+  });
+  let (list, total) = repository
+    .find_by_filters_paginated(workspace.id, user.id(), 1, 10, filters)
+    .await
+    .unwrap();
+  assert_eq!(total, 1);
+  assert_eq!(list[0].id, created.id);
+
+  let updated = repository
+    .update_by_workspace(
+      created.id,
+      workspace.id,
+      UpdateWarehouseRequest {
+        code: None,
+        name: Some("North Depot".to_string()),
+        location: None,
+        capacity: None,
+        is_active: None,
+      },
+      user.id(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(updated.name, "North Depot");
+  assert_eq!(updated.location, "Jakarta");
+  assert_eq!(updated.updated_by, Some(user.id()));
+
+  // A merge patch `null` clears the optional field
+  let fields = apply_merge_patch(&WarehousePatchTarget::from(&updated), &json!({ "capacity": null })).unwrap();
+  let patched = repository
+    .replace_by_workspace(created.id, workspace.id, fields, user.id())
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(patched.capacity, None);
+  assert_eq!(patched.name, "North Depot");
+
+  assert!(
+    repository
+      .delete_by_workspace_and_user(created.id, workspace.id, user.id())
+      .await
+      .unwrap()
+  );
+  assert!(
+    repository
+      .find_by_id_and_workspace(created.id, workspace.id, user.id())
+      .await
+      .unwrap()
+      .is_none()
+  );
+}
@@ -0,0 +1,95 @@
+//! Transient database failures: serialization failures are replayed with backoff, and a database
+//! that cannot be reached opens the circuit, failing requests at once with a 503.
+//!
+//! The retry and circuit settings are process-wide, so every test installs the same ones.
+
+use std::{
+  sync::atomic::{AtomicU32, Ordering},
+  time::{Duration, Instant},
+};
+
+use axum::{http::StatusCode, response::IntoResponse};
+use myapp_api_rust::{config::DbResilienceConfig, errors::AppError, utils::db_resilience};
+use sqlx::{
+  PgPool,
+  postgres::{PgConnectOptions, PgPoolOptions},
+};
+
+fn install() {
+  db_resilience::install(&DbResilienceConfig {
+    retry_attempts: 3,
+    retry_base_delay_ms: 1,
+    retry_max_delay_ms: 5,
+    circuit_failure_threshold: 2,
+    circuit_open_secs: 60,
+  });
+}
+
+/// A pool on the test database that bypasses `db_resilience::acquire`, so an open circuit
+/// does not affect it.
+async fn pool() -> PgPool {
+  dotenvy::dotenv().ok();
+  let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+  PgPool::connect(&db_url).await.expect("Failed to connect to test database")
+}
+
+/// Runs a query failing with `sqlstate` on its first `failures` attempts, counting them.
+async fn flaky(pool: &PgPool, attempts: &AtomicU32, failures: u32, sqlstate: &str) -> Result<i32, sqlx::Error> {
+  if attempts.fetch_add(1, Ordering::SeqCst) < failures {
+    sqlx::query(&format!("DO $$ BEGIN RAISE SQLSTATE '{sqlstate}'; END $$"))
+      .execute(pool)
+      .await?;
+  }
+  sqlx::query_scalar("SELECT 1").fetch_one(pool).await
+}
+
+#[tokio::test]
+async fn test_serialization_failures_and_deadlocks_are_replayed() {
+  install();
+  let pool = pool().await;
+
+  for sqlstate in ["40001", "40P01"] {
+    let attempts = AtomicU32::new(0);
+    let result = db_resilience::retry(true, || flaky(&pool, &attempts, 2, sqlstate)).await;
+    assert_eq!(result.unwrap(), 1, "{sqlstate}");
+    assert_eq!(attempts.load(Ordering::SeqCst), 3, "{sqlstate}");
+  }
+
+  // Given up after the configured attempts
+  let attempts = AtomicU32::new(0);
+  assert!(db_resilience::retry(true, || flaky(&pool, &attempts, 5, "40001")).await.is_err());
+  assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_other_failures_and_enclosed_work_are_not_replayed() {
+  install();
+  let pool = pool().await;
+
+  let attempts = AtomicU32::new(0);
+  assert!(db_resilience::retry(true, || flaky(&pool, &attempts, 1, "23505")).await.is_err());
+  assert_eq!(attempts.load(Ordering::SeqCst), 1, "unique violations fail for good");
+
+  let attempts = AtomicU32::new(0);
+  assert!(db_resilience::retry(false, || flaky(&pool, &attempts, 1, "40001")).await.is_err());
+  assert_eq!(attempts.load(Ordering::SeqCst), 1, "work inside an outer transaction is left to it");
+}
+
+#[tokio::test]
+async fn test_an_unreachable_database_opens_the_circuit() {
+  install();
+  let unreachable = PgPoolOptions::new()
+    .acquire_timeout(Duration::from_millis(200))
+    .connect_lazy_with(PgConnectOptions::new().host("127.0.0.1").port(1).username("postgres"));
+
+  for _ in 0..2 {
+    let err = db_resilience::acquire(&unreachable).await.unwrap_err();
+    assert!(db_resilience::is_connection_error(&err), "{err}");
+  }
+
+  let started = Instant::now();
+  let err = db_resilience::acquire(&unreachable).await.unwrap_err();
+  assert!(started.elapsed() < Duration::from_millis(100), "an open circuit fails at once");
+  assert!(err.to_string().contains("circuit breaker is open"), "{err}");
+  assert_eq!(AppError::from(err).into_response().status(), StatusCode::SERVICE_UNAVAILABLE);
+}
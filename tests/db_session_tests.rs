@@ -0,0 +1,145 @@
+//! Request-scoped connections: the RLS session variables of one request never reach another,
+//! abandoned transactions are rolled back, and a nested `acquire` does not wait on itself.
+
+use std::time::Duration;
+
+use myapp_api_rust::{
+  modules::datastores::workspaces::workspace_models::WorkspaceRole,
+  utils::db_session::{self, SessionSettings},
+};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+use uuid::Uuid;
+
+/// A pool of at most `connections`, failing acquires after a second.
+async fn pool(connections: u32) -> PgPool {
+  dotenvy::dotenv().ok();
+  let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
+  PgPoolOptions::new()
+    .max_connections(connections)
+    .acquire_timeout(Duration::from_secs(1))
+    .connect(&db_url)
+    .await
+    .expect("Failed to connect to test database")
+}
+
+fn settings() -> SessionSettings {
+  SessionSettings {
+    user_id: Uuid::new_v4(),
+    workspace_id: Some(Uuid::new_v4()),
+    role: Some(WorkspaceRole::Member),
+  }
+}
+
+/// The user and workspace session variables seen by the next query.
+async fn session_variables(pool: &PgPool) -> (Option<String>, Option<String>) {
+  let mut conn = db_session::acquire(pool).await.unwrap();
+  sqlx::query_as("SELECT NULLIF(current_setting('app.current_user_id', true), ''), NULLIF(current_setting('app.current_workspace_id', true), '')")
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap()
+}
+
+fn expected(settings: SessionSettings) -> (Option<String>, Option<String>) {
+  (Some(settings.user_id.to_string()), settings.workspace_id.map(|id| id.to_string()))
+}
+
+#[tokio::test]
+async fn test_session_variables_stay_with_their_request() {
+  // One connection, so every request reuses the one before
+  let pool = pool(1).await;
+  let (first, second) = (settings(), settings());
+
+  let seen = db_session::scope(&pool, first, session_variables(&pool)).await.unwrap();
+  assert_eq!(seen, expected(first));
+  let seen = db_session::scope(&pool, second, session_variables(&pool)).await.unwrap();
+  assert_eq!(seen, expected(second));
+  assert_eq!(session_variables(&pool).await, (None, None), "cleared once the request ends");
+
+  // Concurrent requests each see their own
+  let pool = self::pool(2).await;
+  let (seen_first, seen_second) = tokio::join!(
+    db_session::scope(&pool, first, async {
+      tokio::time::sleep(Duration::from_millis(50)).await;
+      session_variables(&pool).await
+    }),
+    db_session::scope(&pool, second, session_variables(&pool)),
+  );
+  assert_eq!(seen_first.unwrap(), expected(first));
+  assert_eq!(seen_second.unwrap(), expected(second));
+}
+
+#[tokio::test]
+async fn test_transactions_roll_back_unless_they_succeed() {
+  // One connection, so the temporary table is seen by every request
+  let pool = pool(1).await;
+  {
+    let mut conn = db_session::acquire(&pool).await.unwrap();
+    sqlx::query("CREATE TEMPORARY TABLE session_probe (label TEXT)")
+      .execute(&mut *conn)
+      .await
+      .unwrap();
+  }
+  let insert = |label: &'static str| {
+    let pool = pool.clone();
+    async move {
+      let mut conn = db_session::acquire(&pool).await?;
+      sqlx::query("INSERT INTO session_probe VALUES ($1)")
+        .bind(label)
+        .execute(&mut *conn)
+        .await?;
+      Ok::<_, sqlx::Error>(())
+    }
+  };
+
+  let committed = db_session::transaction(&pool, insert("committed"));
+  db_session::scope(&pool, settings(), committed).await.unwrap().unwrap();
+  let failed = db_session::transaction(&pool, async {
+    insert("failed").await?;
+    Err::<(), _>(sqlx::Error::RowNotFound)
+  });
+  assert!(db_session::scope(&pool, settings(), failed).await.unwrap().is_err());
+
+  // A request dropped halfway, as when the client goes away
+  let abandoned = db_session::transaction(&pool, async {
+    insert("abandoned").await?;
+    std::future::pending::<Result<(), sqlx::Error>>().await
+  });
+  let request = db_session::scope(&pool, settings(), abandoned);
+  assert!(tokio::time::timeout(Duration::from_millis(200), request).await.is_err());
+
+  let mut conn = db_session::acquire(&pool).await.unwrap();
+  let labels: Vec<String> = sqlx::query_scalar("SELECT label FROM session_probe").fetch_all(&mut *conn).await.unwrap();
+  assert_eq!(labels, ["committed"]);
+  let user_id: Option<String> = sqlx::query_scalar("SELECT NULLIF(current_setting('app.current_user_id', true), '')")
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+  assert_eq!(user_id, None, "cleared once the request ends");
+}
+
+#[tokio::test]
+async fn test_nested_acquire_does_not_wait_on_itself() {
+  let pool = pool(2).await;
+  let caller = settings();
+
+  // Outside a transaction, a second connection with the same variables serves the nested query
+  let nested = db_session::scope(&pool, caller, async {
+    let _held = db_session::acquire(&pool).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), session_variables(&pool)).await
+  });
+  assert_eq!(nested.await.unwrap().expect("nested acquire hung"), expected(caller));
+
+  // In a transaction the query has to wait for the connection, and gives up instead of hanging
+  let nested = db_session::scope(
+    &pool,
+    caller,
+    db_session::transaction(&pool, async {
+      let _held = db_session::acquire(&pool).await?;
+      tokio::time::timeout(Duration::from_secs(5), db_session::acquire(&pool))
+        .await
+        .expect("nested acquire hung")
+        .map(|_| ())
+    }),
+  );
+  assert!(matches!(nested.await.unwrap(), Err(sqlx::Error::PoolTimedOut)));
+}
@@ -0,0 +1,58 @@
+//! Registration limited to configured email domains, with disposable providers refused.
+
+use axum::{
+  body::Body,
+  http::{self, Request, StatusCode},
+};
+use myapp_api_rust::config::AppConfig;
+use serde_json::{Value, json};
+
+use crate::common::{TestApp, test_id};
+
+mod common;
+
+async fn register(app: &TestApp, email: &str) -> (StatusCode, Value) {
+  let payload = json!({ "username": format!("user_{}", test_id()), "email": email, "password": "password123" });
+  let request = Request::builder()
+    .method(http::Method::POST)
+    .uri("/api/v1/auth/register")
+    .header(http::header::CONTENT_TYPE, "application/json")
+    .body(Body::from(serde_json::to_vec(&payload).unwrap()))
+    .unwrap();
+  let (status, _, body) = app.send(request).await;
+  (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_registration_follows_the_domain_lists() {
+  let mut config = AppConfig::from_env();
+  config.registration.allowed_email_domains = vec!["acme.com".to_string(), "mailinator.com".to_string()];
+  config.registration.denied_email_domains = vec!["contractors.acme.com".to_string()];
+  config.registration.block_disposable_domains = true;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let id = test_id();
+
+  let (status, _) = register(&app, &format!("jane_{}@acme.com", id)).await;
+  assert_eq!(status, StatusCode::CREATED);
+  let (status, _) = register(&app, &format!("joe_{}@EU.Acme.com", id)).await;
+  assert_eq!(status, StatusCode::CREATED, "subdomains of allowed domains may register");
+
+  for (email, message) in [
+    (format!("jane_{}@example.com", id), "Registration is not open to this email domain"),
+    (format!("jane_{}@notacme.com", id), "Registration is not open to this email domain"),
+    (
+      format!("jane_{}@contractors.acme.com", id),
+      "Registration is not open to this email domain",
+    ),
+    (
+      format!("jane_{}@mailinator.com", id),
+      "Disposable email addresses cannot be used to register",
+    ),
+  ] {
+    let (status, body) = register(&app, &email).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", email);
+    let error = &body["details"]["email"][0];
+    assert_eq!(error["code"], "EMAIL_DOMAIN_NOT_ALLOWED", "{}", email);
+    assert_eq!(error["message"], message, "{}", email);
+  }
+}
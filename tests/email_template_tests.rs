@@ -0,0 +1,106 @@
+//! Email templates: management and previews.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::modules::datastores::workspaces::WorkspaceRole;
+use serde_json::{Value, json};
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+fn invitation() -> Value {
+  json!({
+    "kind": "invitation",
+    "subject": "Join {{workspace_name}}",
+    "body": "Hi,\n\n{{inviter_name}} invited you to {{workspace_name}} as a {{role}}.\n{{#if accept_url}}Accept: {{accept_url}}{{/if}}\n",
+  })
+}
+
+#[tokio::test]
+async fn test_templates_are_managed_and_previewed() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/templates", &admin, workspace.id, Some(invitation()))
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  let id = body["results"]["id"].as_str().unwrap().to_string();
+  assert_eq!(body["results"]["kind"], "invitation");
+
+  // One template per kind
+  let (status, _) = app
+    .call(http::Method::POST, "/api/v1/templates", &admin, workspace.id, Some(invitation()))
+    .await;
+  assert_eq!(status, StatusCode::CONFLICT);
+
+  // Members preview with the sample values, or their own
+  let preview = format!("/api/v1/templates/{id}/preview");
+  let (status, body) = app.call(http::Method::POST, &preview, &member, workspace.id, Some(json!({}))).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["subject"], "Join Acme Trading");
+  assert!(body["results"]["body"].as_str().unwrap().contains("Accept: https://"));
+  assert!(body["results"]["variables"]["invitee_email"].is_string());
+
+  let variables = json!({ "variables": { "workspace_name": "Toko <Maju>", "accept_url": "" } });
+  let (status, body) = app.call(http::Method::POST, &preview, &member, workspace.id, Some(variables)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["subject"], "Join Toko <Maju>", "plain text is not escaped");
+  assert!(!body["results"]["body"].as_str().unwrap().contains("Accept:"));
+
+  let unknown = json!({ "variables": { "invoice_number": "INV-1" } });
+  let (status, _) = app.call(http::Method::POST, &preview, &member, workspace.id, Some(unknown)).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+  // Admins replace and delete
+  let uri = format!("/api/v1/templates/{id}");
+  let mut replacement = invitation();
+  replacement["subject"] = json!("{{inviter_name}} invited you");
+  let (status, body) = app.call(http::Method::PUT, &uri, &admin, workspace.id, Some(replacement)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["subject"], "{{inviter_name}} invited you");
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/templates", &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(body["results"].as_array().unwrap().len(), 1);
+
+  let (status, _) = app.call(http::Method::DELETE, &uri, &admin, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  let (status, _) = app.call(http::Method::GET, &uri, &admin, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_invalid_templates_are_rejected() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+
+  // A variable of another kind
+  let mut template = invitation();
+  template["body"] = json!("Invoice {{invoice_number}}");
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/templates", &admin, workspace.id, Some(template))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  assert!(body.to_string().contains("invoice_number"), "{body}");
+
+  // Unclosed block
+  let mut template = invitation();
+  template["subject"] = json!("{{#if role}}Join");
+  let (status, _) = app
+    .call(http::Method::POST, "/api/v1/templates", &admin, workspace.id, Some(template))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+  let (status, _) = app
+    .call(http::Method::POST, "/api/v1/templates", &member, workspace.id, Some(invitation()))
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,154 @@
+//! Pins the status, error type, code and body of every `AppError` variant as rendered by
+//! `IntoResponse`. The error contract is documented for clients, so a change to the snapshot
+//! has to be deliberate: review it with `cargo insta review` and commit the updated file.
+
+use std::collections::BTreeMap;
+
+use axum::response::IntoResponse;
+use http_body_util::BodyExt;
+use myapp_api_rust::{
+  errors::{AppError, AuthError, CookieError, DatabaseError, NotFoundError, ValidationError},
+  internal_error,
+  modules::auth::user_dto::RegisterUserDto,
+  not_found,
+};
+use serde_json::{Value, json};
+use uuid::Uuid;
+use validator::Validate;
+
+/// The status and JSON body `error` renders to, with the timestamp replaced so it is stable.
+async fn render(error: AppError) -> Value {
+  let response = error.into_response();
+  let status = response.status().as_u16();
+  let body = response.into_body().collect().await.unwrap().to_bytes();
+  let mut body: Value = serde_json::from_slice(&body).unwrap();
+  assert!(body["timestamp"].is_string(), "error body without a timestamp: {}", body);
+  body["timestamp"] = json!("[timestamp]");
+  json!({ "status": status, "body": body })
+}
+
+#[tokio::test]
+async fn test_error_bodies_match_snapshot() {
+  let invalid_registration = RegisterUserDto {
+    username: "ab".to_string(),
+    email: "not-an-email".to_string(),
+    password: "short".to_string(),
+  };
+  let id = Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+
+  let cases = [
+    ("auth_invalid_credentials", AppError::from(AuthError::InvalidCredentials)),
+    ("auth_missing_token", AppError::from(AuthError::MissingToken)),
+    ("auth_invalid_token", AppError::from(AuthError::InvalidToken)),
+    ("auth_invalid_workspace", AppError::from(AuthError::InvalidWorkspace)),
+    ("auth_expired_token", AppError::from(AuthError::ExpiredToken)),
+    ("auth_missing_workspace", AppError::from(AuthError::MissingWorkspace)),
+    ("auth_session_idle_timeout", AppError::from(AuthError::SessionIdleTimeout)),
+    ("authorization", AppError::Authorization("Admin role required".to_string())),
+    ("validation_field", AppError::validation("code", "Code is required")),
+    ("validation_errors", AppError::from(invalid_registration.validate().unwrap_err())),
+    (
+      "database_connection_failed",
+      AppError::from(DatabaseError::ConnectionFailed("pool timed out".to_string())),
+    ),
+    (
+      "database_query_failed",
+      AppError::from(DatabaseError::QueryFailed("syntax error".to_string())),
+    ),
+    (
+      "database_transaction_failed",
+      AppError::from(DatabaseError::TransactionFailed("deadlock".to_string())),
+    ),
+    (
+      "database_migration_failed",
+      AppError::from(DatabaseError::MigrationFailed("checksum".to_string())),
+    ),
+    (
+      "database_size_exceeded",
+      AppError::from(DatabaseError::SizeExceeded("trial limit".to_string())),
+    ),
+    (
+      "database_schema_mismatch",
+      AppError::from(DatabaseError::SchemaMismatch("missing table".to_string())),
+    ),
+    (
+      "database_column_not_found",
+      AppError::from(DatabaseError::ColumnNotFound("Column 'x' not found".to_string())),
+    ),
+    ("not_found", AppError::not_found("Contact")),
+    ("not_found_with_id", AppError::not_found_with_id("Contact", id)),
+    ("conflict", AppError::Conflict("Code already exists".to_string())),
+    ("bad_request", AppError::BadRequest("Malformed request".to_string())),
+    ("cookie_invalid_format", AppError::from(CookieError::InvalidFormat)),
+    ("cookie_missing", AppError::from(CookieError::Missing)),
+    ("cookie_expired", AppError::from(CookieError::Expired)),
+    ("serialization", AppError::Serialization("unexpected token".to_string())),
+    ("internal", AppError::Internal("invariant violated".to_string())),
+    ("not_allowed", AppError::not_allowed("Method PATCH is not allowed")),
+    ("rate_limited", AppError::RateLimited(30)),
+    (
+      "quota_exceeded",
+      AppError::QuotaExceeded {
+        limit: 10_000,
+        resets_at: "2025-11-01T00:00:00Z".parse().unwrap(),
+      },
+    ),
+    ("trial_expired", AppError::TrialExpired("2025-10-01T00:00:00Z".parse().unwrap())),
+    ("ip_not_allowed", AppError::IpNotAllowed("203.0.113.7".to_string())),
+    ("approval_required", AppError::ApprovalRequired(id)),
+    ("timeout", AppError::Timeout("request exceeded 30s".to_string())),
+    ("overloaded", AppError::Overloaded("concurrency limit reached".to_string())),
+    ("unhandled", AppError::Unhandled("panic in handler".to_string())),
+  ];
+
+  let mut rendered = BTreeMap::new();
+  for (name, error) in cases {
+    rendered.insert(name, render(error).await);
+  }
+
+  insta::assert_json_snapshot!("error_bodies", rendered);
+}
+
+#[tokio::test]
+async fn test_helpers_render_like_the_errors_they_stand_for() {
+  let id = Uuid::new_v4();
+  let missing = |id| {
+    AppError::NotFound(NotFoundError {
+      resource: "Contact".to_string(),
+      id,
+    })
+  };
+  let invalid_code = ValidationError {
+    field: "code".to_string(),
+    message: "Code is required".to_string(),
+    code: None,
+  };
+
+  let cases = [
+    (AppError::not_found("Contact"), missing(None)),
+    (AppError::not_found_with_id("Contact", id), missing(Some(id))),
+    (not_found!("Contact"), missing(None)),
+    (not_found!("Contact", id), missing(Some(id))),
+    (
+      AppError::validation("code", "Code is required"),
+      AppError::Validation(json!({ "code": [invalid_code] })),
+    ),
+    (AppError::invalid_credentials(), AppError::from(AuthError::InvalidCredentials)),
+    (AppError::missing_token(), AppError::from(AuthError::MissingToken)),
+    (AppError::invalid_token(), AppError::from(AuthError::InvalidToken)),
+    (
+      AppError::not_allowed("Method PUT is not allowed"),
+      AppError::NotAllowed("Method PUT is not allowed".to_string()),
+    ),
+    (
+      internal_error!("Failed to publish event for {}", id),
+      AppError::Internal(format!("Failed to publish event for {id}")),
+    ),
+    (internal_error!("plain message"), AppError::Internal("plain message".to_string())),
+  ];
+
+  for (helper, expected) in cases {
+    let expected = render(expected).await;
+    assert_eq!(render(helper).await, expected);
+  }
+}
@@ -0,0 +1,70 @@
+//! Weak ETags on JSON `GET` responses, and the bodies left untagged because they are too large or
+//! of unknown size.
+
+use axum::{
+  Json, Router,
+  body::Body,
+  http::{Request, StatusCode, header},
+  middleware::from_fn,
+  response::IntoResponse,
+  routing::get,
+};
+use futures_util::stream;
+use http_body_util::BodyExt;
+use myapp_api_rust::middleware::etag_middleware;
+use serde_json::json;
+use tower::ServiceExt;
+
+fn router() -> Router {
+  Router::new()
+    .route("/small", get(|| async { Json(json!({ "results": { "code": "SMALL" } })) }))
+    .route("/large", get(|| async { Json(json!({ "results": "x".repeat(9 * 1024 * 1024) })) }))
+    .route(
+      "/streamed",
+      get(|| async {
+        let chunks = stream::iter([Ok::<_, std::io::Error>("{\"results\":"), Ok("[]}")]);
+        ([(header::CONTENT_TYPE, "application/json")], Body::from_stream(chunks)).into_response()
+      }),
+    )
+    .layer(from_fn(etag_middleware))
+}
+
+async fn get_with(uri: &str, if_none_match: Option<&str>) -> axum::response::Response {
+  let mut request = Request::get(uri);
+  if let Some(etag) = if_none_match {
+    request = request.header(header::IF_NONE_MATCH, etag);
+  }
+  router().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_matching_etags_get_not_modified() {
+  let response = get_with("/small", None).await;
+  assert_eq!(response.status(), StatusCode::OK);
+  let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+  assert!(etag.starts_with("W/\""), "{etag}");
+
+  let response = get_with("/small", Some(&etag)).await;
+  assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+  assert_eq!(response.headers()[header::ETAG], etag.as_str());
+}
+
+#[tokio::test]
+async fn test_bodies_over_the_limit_pass_through_untagged() {
+  let response = get_with("/large", None).await;
+  assert_eq!(response.status(), StatusCode::OK);
+  assert!(response.headers().get(header::ETAG).is_none());
+
+  let body = response.into_body().collect().await.unwrap().to_bytes();
+  assert!(body.len() > 9 * 1024 * 1024, "{} bytes", body.len());
+}
+
+#[tokio::test]
+async fn test_bodies_of_unknown_size_pass_through_untagged() {
+  let response = get_with("/streamed", None).await;
+  assert_eq!(response.status(), StatusCode::OK);
+  assert!(response.headers().get(header::ETAG).is_none());
+
+  let body = response.into_body().collect().await.unwrap().to_bytes();
+  assert_eq!(&body[..], b"{\"results\":[]}");
+}
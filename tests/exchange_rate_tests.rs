@@ -0,0 +1,220 @@
+//! Exchange rates: the daily sync from a fake provider, the rates set by workspaces and the
+//! history of a currency.
+
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use axum::http::{self, StatusCode};
+use chrono::NaiveDate;
+use myapp_api_rust::{
+  AppResult,
+  modules::{
+    currencies::{
+      exchange_rate_service,
+      rate_provider::{Quotes, RateProvider, parse_ecb},
+    },
+    datastores::workspaces::WorkspaceRole,
+  },
+};
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+/// Publishes the euro rates of one day, as the ECB does.
+struct FakeProvider {
+  date: NaiveDate,
+}
+
+#[async_trait]
+impl RateProvider for FakeProvider {
+  fn name(&self) -> &'static str {
+    "fake"
+  }
+
+  async fn latest(&self) -> AppResult<Quotes> {
+    let quotes = [("USD", "1.1"), ("IDR", "17600"), ("JPY", "165")]
+      .into_iter()
+      .map(|(currency, quote)| (currency.to_string(), Decimal::from_str(quote).unwrap()))
+      .collect::<BTreeMap<_, _>>();
+    Ok(Quotes {
+      date: self.date,
+      anchor: "EUR".to_string(),
+      quotes,
+    })
+  }
+}
+
+fn day(day: u32) -> NaiveDate {
+  NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+}
+
+async fn app_on(date: NaiveDate) -> TestApp {
+  TestApp::isolated_with(|builder| builder.with_rate_provider(Arc::new(FakeProvider { date }))).await
+}
+
+/// The rates of `body` by currency.
+fn rates(body: &Value) -> BTreeMap<String, Value> {
+  body["results"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|rate| (rate["currency"].as_str().unwrap().to_string(), rate.clone()))
+    .collect()
+}
+
+#[tokio::test]
+async fn test_sync_converts_rates_into_the_base_currency() {
+  let app = app_on(day(7)).await;
+  let admin = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &admin).await;
+
+  assert_eq!(exchange_rate_service::sync(&app.state).await.unwrap(), Some(day(7)));
+  // Fetching the same day again replaces its rates
+  assert_eq!(exchange_rate_service::sync(&app.state).await.unwrap(), Some(day(7)));
+
+  let (status, body) = app
+    .call(http::Method::GET, "/api/v1/exchange-rates?date=2025-11-10", &admin, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let rates = rates(&body);
+  assert_eq!(rates.keys().collect::<Vec<_>>(), ["EUR", "JPY", "USD"], "the base currency has no rate");
+  assert_eq!(rates["EUR"]["rate"], 17600.0);
+  assert_eq!(rates["USD"]["rate"], 16000.0);
+  assert_eq!(rates["JPY"]["rate"], 106.6666666667);
+  assert_eq!(rates["USD"]["base"], "IDR");
+  assert_eq!(rates["USD"]["source"], "fake");
+  assert_eq!(rates["USD"]["rate_date"], "2025-11-07");
+  assert_eq!(rates["USD"]["is_override"], false);
+
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/exchange-rates?date=2025-11-06", &admin, workspace.id, None)
+    .await;
+  assert!(body["results"].as_array().unwrap().is_empty(), "no rates before the first day");
+}
+
+#[tokio::test]
+async fn test_overrides_replace_the_provider_rates_of_the_workspace() {
+  let app = app_on(day(7)).await;
+  let admin = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &admin).await;
+  let other = WorkspaceFactory::new().create(&app, &admin).await;
+  exchange_rate_service::sync(&app.state).await.unwrap();
+
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      "/api/v1/exchange-rates/usd/2025-11-08",
+      &admin,
+      workspace.id,
+      Some(json!({ "rate": 15950 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["currency"], "USD");
+  assert_eq!(body["results"]["source"], "manual");
+  assert_eq!(body["results"]["is_override"], true);
+  assert_eq!(body["results"]["updated_by"], admin.id().to_string());
+
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/exchange-rates?date=2025-11-09", &admin, workspace.id, None)
+    .await;
+  let usd = &rates(&body)["USD"];
+  assert_eq!(usd["rate"], 15950.0);
+  assert_eq!(usd["is_override"], true);
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/exchange-rates?date=2025-11-07", &admin, workspace.id, None)
+    .await;
+  assert_eq!(rates(&body)["USD"]["rate"], 16000.0, "the override starts on its day");
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/exchange-rates?date=2025-11-09", &admin, other.id, None)
+    .await;
+  assert_eq!(rates(&body)["USD"]["rate"], 16000.0, "other workspaces keep the provider's rate");
+
+  let uri = "/api/v1/exchange-rates/USD/history?from=2025-11-01&to=2025-11-30";
+  let (status, body) = app.call(http::Method::GET, uri, &admin, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let history: Vec<(&str, f64)> = body["results"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|rate| (rate["rate_date"].as_str().unwrap(), rate["rate"].as_f64().unwrap()))
+    .collect();
+  assert_eq!(history, [("2025-11-08", 15950.0), ("2025-11-07", 16000.0)]);
+
+  let (status, _) = app
+    .call(http::Method::DELETE, "/api/v1/exchange-rates/USD/2025-11-08", &admin, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  let (status, _) = app
+    .call(http::Method::DELETE, "/api/v1/exchange-rates/USD/2025-11-08", &admin, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+  let (status, _) = app
+    .call(http::Method::DELETE, "/api/v1/exchange-rates/USD/2025-11-07", &admin, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "provider rates cannot be deleted");
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/exchange-rates?date=2025-11-09", &admin, workspace.id, None)
+    .await;
+  assert_eq!(rates(&body)["USD"]["rate"], 16000.0);
+}
+
+#[tokio::test]
+async fn test_overrides_are_set_by_admins() {
+  let app = app_on(day(7)).await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+  let uri = "/api/v1/exchange-rates/USD/2025-11-08";
+
+  let (status, _) = app
+    .call(http::Method::PUT, uri, &member, workspace.id, Some(json!({ "rate": 15950 })))
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+  let (status, _) = app.call(http::Method::GET, "/api/v1/exchange-rates", &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  let (status, _) = app
+    .call(http::Method::GET, "/api/v1/exchange-rates/USD/history", &member, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK);
+
+  for (uri, body) in [
+    (uri, json!({ "rate": 0 })),
+    (uri, json!({ "rate": -1 })),
+    ("/api/v1/exchange-rates/IDR/2025-11-08", json!({ "rate": 1 })),
+    ("/api/v1/exchange-rates/US/2025-11-08", json!({ "rate": 1 })),
+    ("/api/v1/exchange-rates/USD/08-11-2025", json!({ "rate": 1 })),
+  ] {
+    let (status, _) = app.call(http::Method::PUT, uri, &admin, workspace.id, Some(body)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{uri}");
+  }
+  let uri = "/api/v1/exchange-rates/USD/history?from=2024-01-01&to=2025-11-30";
+  let (status, _) = app.call(http::Method::GET, uri, &admin, workspace.id, None).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "the history covers at most a year");
+}
+
+#[test]
+fn test_parse_ecb_reads_the_daily_file() {
+  let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+  <gesmes:subject>Reference rates</gesmes:subject>
+  <Cube>
+    <Cube time='2025-11-07'>
+      <Cube currency='USD' rate='1.1561'/>
+      <Cube currency='IDR' rate='19281.37'/>
+    </Cube>
+  </Cube>
+</gesmes:Envelope>"#;
+  let quotes = parse_ecb(xml).unwrap();
+  assert_eq!(quotes.date, day(7));
+  assert_eq!(quotes.anchor, "EUR");
+  assert_eq!(quotes.quotes["USD"], Decimal::from_str("1.1561").unwrap());
+  assert_eq!(quotes.quotes["IDR"], Decimal::from_str("19281.37").unwrap());
+  assert!(parse_ecb("<html>Maintenance</html>").is_none());
+}
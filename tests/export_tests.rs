@@ -0,0 +1,141 @@
+//! CSV exports with client-chosen columns, headers, date formats and filters, and export profiles.
+
+use axum::http::{self, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, TestUser, UserFactory, WorkspaceFactory},
+  request,
+};
+use myapp_api_rust::modules::datastores::workspaces::WorkspaceRole;
+
+mod common;
+
+/// Like `TestApp::call`, with the body as text: CSV for exports, JSON otherwise.
+async fn send(app: &TestApp, method: http::Method, uri: &str, user: &TestUser, workspace_id: Uuid, body: Option<Value>) -> (StatusCode, String) {
+  let (status, _, body) = app.send(request(method, uri, user, workspace_id, body)).await;
+  (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+async fn export(app: &TestApp, user: &TestUser, workspace_id: Uuid, body: Value) -> (StatusCode, String) {
+  send(app, http::Method::POST, "/api/v1/exports/products", user, workspace_id, Some(body)).await
+}
+
+async fn save_profile(app: &TestApp, user: &TestUser, workspace_id: Uuid, profile: Value) -> (StatusCode, String) {
+  send(app, http::Method::POST, "/api/v1/exports/profiles", user, workspace_id, Some(profile)).await
+}
+
+#[tokio::test]
+async fn test_exports_follow_the_requested_layout() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let drill = ProductFactory::new()
+    .code("EXP-1")
+    .name("Drill, cordless")
+    .sku("D-1")
+    .create(&app, &workspace, &user)
+    .await;
+  ProductFactory::new().code("EXP-2").name("Saw").create(&app, &workspace, &user).await;
+
+  let (status, csv) = export(
+    &app,
+    &user,
+    workspace.id,
+    json!({
+      "columns": ["sku", "code", "name", "created_at"],
+      "headers": { "code": "Item code", "created_at": "Created" },
+      "date_format": "%d/%m/%Y",
+      "filters": { "code": "EXP-1" },
+    }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{csv}");
+  let created = drill.created_at.format("%d/%m/%Y");
+  assert_eq!(csv, format!("sku,Item code,name,Created\nD-1,EXP-1,\"Drill, cordless\",{created}\n"));
+
+  // Every column under its own name by default
+  let (status, csv) = export(&app, &user, workspace.id, json!({})).await;
+  assert_eq!(status, StatusCode::OK, "{csv}");
+  let mut lines = csv.lines();
+  assert!(lines.next().unwrap().starts_with("id,code,name,status,"));
+  assert_eq!(lines.count(), 2);
+
+  for body in [
+    json!({ "columns": ["code", "colour"] }),
+    json!({ "columns": ["code", "code"] }),
+    json!({ "columns": ["code"], "headers": { "name": "Name" } }),
+    json!({ "date_format": "%Q" }),
+    json!({ "filters": { "colour": "red" } }),
+  ] {
+    let (status, response) = export(&app, &user, workspace.id, body.clone()).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}: {response}");
+  }
+  let (status, response) = send(&app, http::Method::POST, "/api/v1/exports/invoices", &user, workspace.id, Some(json!({}))).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{response}");
+}
+
+#[tokio::test]
+async fn test_export_profiles_are_saved_per_member() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+  ProductFactory::new().code("EXP-1").name("Drill").create(&app, &workspace, &admin).await;
+  ProductFactory::new().code("EXP-2").name("Saw").create(&app, &workspace, &admin).await;
+
+  let profile = json!({
+    "name": "Price list",
+    "entity": "products",
+    "columns": ["code", "name", "selling_price"],
+    "headers": { "selling_price": "Price" },
+    "filters": { "search": "Drill" },
+  });
+  let (status, body) = save_profile(&app, &member, workspace.id, profile.clone()).await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  let profile_id = serde_json::from_str::<Value>(&body).unwrap()["results"]["id"]
+    .as_str()
+    .unwrap()
+    .to_string();
+
+  let (status, csv) = export(&app, &member, workspace.id, json!({ "profile_id": profile_id })).await;
+  assert_eq!(status, StatusCode::OK, "{csv}");
+  assert_eq!(csv, "code,name,Price\nEXP-1,Drill,150.0\n");
+
+  // The request overrides the fields of the profile it names
+  let (status, csv) = export(&app, &member, workspace.id, json!({ "profile_id": profile_id, "filters": {} })).await;
+  assert_eq!(status, StatusCode::OK, "{csv}");
+  assert_eq!(csv.lines().count(), 3);
+
+  // Profiles are only seen by the member who saved them
+  let (status, body) = export(&app, &admin, workspace.id, json!({ "profile_id": profile_id })).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+  let (status, body) = send(&app, http::Method::GET, "/api/v1/exports/profiles", &admin, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(serde_json::from_str::<Value>(&body).unwrap()["results"], json!([]));
+
+  let uri = format!("/api/v1/exports/profiles/{profile_id}");
+  let replacement = json!({ "name": "Codes", "entity": "products", "columns": ["code"] });
+  let (status, body) = send(&app, http::Method::PUT, &uri, &member, workspace.id, Some(replacement)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, csv) = export(&app, &member, workspace.id, json!({ "profile_id": profile_id })).await;
+  assert_eq!(status, StatusCode::OK, "{csv}");
+  assert_eq!(csv, "code\nEXP-1\nEXP-2\n");
+
+  let invalid = json!({ "name": "Codes", "entity": "contacts", "columns": ["sku"] });
+  let (status, body) = send(&app, http::Method::PUT, &uri, &member, workspace.id, Some(invalid)).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+  let (status, body) = send(&app, http::Method::DELETE, &uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = send(&app, http::Method::GET, &uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+
+  // Names are unique per member. Last, since the failed insert aborts the test's transaction
+  let (status, body) = save_profile(&app, &member, workspace.id, profile.clone()).await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  let (status, body) = save_profile(&app, &member, workspace.id, profile).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+}
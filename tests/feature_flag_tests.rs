@@ -0,0 +1,111 @@
+//! Feature flags: the admin API, workspace overrides and routes gated with `RequireFeature`.
+
+use std::sync::Arc;
+
+use axum::{
+  Router,
+  http::{self, StatusCode},
+  routing::get,
+};
+use myapp_api_rust::{
+  AppState,
+  helper::{RequireFeature, feature::flag::InventoryV2},
+  modules::datastores::workspaces::WorkspaceRole,
+};
+use serde_json::json;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_flags_gate_routes_globally_and_per_workspace() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let user = UserFactory::new().create(&app).await;
+  let pilot = WorkspaceFactory::new().member(&user, WorkspaceRole::Member).create(&app, &admin).await;
+  let other = WorkspaceFactory::new().member(&user, WorkspaceRole::Member).create(&app, &admin).await;
+
+  // The same app, with `admin` listed in FEATURE_FLAG_ADMIN_IDS
+  let mut state = AppState::clone(&app.state);
+  state.config.feature_flags.admin_user_ids = vec![admin.id()];
+  let state = Arc::new(state);
+  let api = TestApp {
+    router: myapp_api_rust::app(state.clone()),
+    state: state.clone(),
+    db: app.db.clone(),
+  };
+  let gated = TestApp {
+    router: Router::new()
+      .route("/inventory", get(|_: RequireFeature<InventoryV2>| async { "inventory v2" }))
+      .with_state(state.clone()),
+    state,
+    db: app.db.clone(),
+  };
+  let flag_uri = "/api/v1/admin/feature-flags/inventory_v2";
+
+  // Unknown flags are off
+  let (status, _) = gated.call(http::Method::GET, "/inventory", &user, pilot.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+
+  let (status, _) = api
+    .call(http::Method::PUT, flag_uri, &user, pilot.id, Some(json!({ "enabled": true })))
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+
+  let (status, body) = api
+    .call(
+      http::Method::PUT,
+      flag_uri,
+      &admin,
+      pilot.id,
+      Some(json!({ "enabled": false, "description": "Reworked inventory endpoints" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(body["results"]["enabled"], false);
+
+  let override_uri = format!("{}/workspaces/{}", flag_uri, pilot.id);
+  let (status, _) = api
+    .call(http::Method::PUT, &override_uri, &admin, pilot.id, Some(json!({ "enabled": true })))
+    .await;
+  assert_eq!(status, StatusCode::OK);
+
+  let (status, _) = gated.call(http::Method::GET, "/inventory", &user, pilot.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  let (status, _) = gated.call(http::Method::GET, "/inventory", &user, other.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+  let (_, body) = api.call(http::Method::GET, "/api/v1/feature-flags", &user, pilot.id, None).await;
+  assert_eq!(body["results"]["flags"]["inventory_v2"], true);
+
+  let (_, body) = api.call(http::Method::GET, "/api/v1/admin/feature-flags", &admin, pilot.id, None).await;
+  let flag = &body["results"][0];
+  assert_eq!(flag["key"], "inventory_v2");
+  assert_eq!(flag["description"], "Reworked inventory endpoints");
+  assert_eq!(flag["overrides"][0]["workspace_id"], pilot.id.to_string());
+
+  // Turning the flag on globally takes effect right away, and removing the override keeps it on
+  api
+    .call(http::Method::PUT, flag_uri, &admin, pilot.id, Some(json!({ "enabled": true })))
+    .await;
+  let (status, _) = gated.call(http::Method::GET, "/inventory", &user, other.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  let (status, _) = api.call(http::Method::DELETE, &override_uri, &admin, pilot.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  let (status, _) = gated.call(http::Method::GET, "/inventory", &user, pilot.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+
+  let (status, _) = api
+    .call(
+      http::Method::PUT,
+      "/api/v1/admin/feature-flags/Not-A-Key",
+      &admin,
+      pilot.id,
+      Some(json!({ "enabled": true })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
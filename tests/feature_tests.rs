@@ -0,0 +1,37 @@
+//! Cargo features: the contacts and products modules answer, and are documented, only when their
+//! feature is compiled in. Run with `--no-default-features` to check the minimal build.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::openapi::openapi_document;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_modules_are_routed_and_documented_only_when_compiled() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let document = openapi_document();
+  let (unknown, _) = app.call(http::Method::GET, "/api/v1/unknown", &user, workspace.id, None).await;
+
+  for (module, compiled) in [("contacts", cfg!(feature = "contacts")), ("products", cfg!(feature = "products"))] {
+    for version in ["v1", "v2"] {
+      let uri = format!("/api/{version}/{module}");
+      let (status, body) = app.call(http::Method::GET, &uri, &user, workspace.id, None).await;
+      // Without the feature the route is as unknown as any other
+      let expected = if compiled { StatusCode::OK } else { unknown };
+      assert_eq!(status, expected, "{uri}: {body}");
+      assert_eq!(document["paths"].get(&uri).is_some(), compiled, "{uri} in the OpenAPI document");
+    }
+  }
+
+  // Modules outside the features are always there
+  let (status, _) = app.call(http::Method::GET, "/api/v1/workspaces", &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  assert!(document["paths"].get("/api/v1/workspaces").is_some());
+}
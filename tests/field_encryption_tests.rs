@@ -0,0 +1,137 @@
+//! Sensitive contact fields encrypted at rest, their re-encryption after a key rotation and their
+//! redaction from payload logs.
+
+use std::sync::Arc;
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::{
+  config::EncryptionConfig,
+  middleware::access_log::redact_json,
+  modules::datastores::contacts::contact_repository::{ContactRepository, SqlxContactRepository},
+  utils::field_encryption::FieldCipher,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::common::{
+  TEST_ENCRYPTION_KEY, TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+const NEW_KEY: &str = "next:ICEiIyQlJicoKSorLC0uLzAxMjM0NTY3ODk6Ozw9Pj8=";
+
+async fn stored_fields(app: &TestApp, id: Uuid) -> (Option<String>, Option<String>) {
+  let mut conn = app.db.acquire().await.unwrap();
+  sqlx::query_as("SELECT tax_id, bank_account FROM contacts WHERE id = $1")
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap()
+}
+
+fn cipher(keys: &[&str]) -> Arc<FieldCipher> {
+  let config = EncryptionConfig {
+    keys: keys.iter().map(|key| key.to_string()).collect(),
+  };
+  Arc::new(FieldCipher::from_config(&config).unwrap())
+}
+
+#[tokio::test]
+async fn test_sensitive_fields_are_stored_encrypted() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  let payload = json!({
+    "code": "ENC-1",
+    "name": "Encrypted Supplier",
+    "email": "encrypted@example.com",
+    "contact_type": "supplier",
+    "tax_id": "01.234.567.8-901.000",
+    "bank_account": "BCA 1234567890",
+  });
+  let (status, body) = app.call(http::Method::POST, "/api/v1/contacts", &user, workspace.id, Some(payload)).await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  assert_eq!(body["results"]["tax_id"], "01.234.567.8-901.000");
+  let id: Uuid = body["results"]["id"].as_str().unwrap().parse().unwrap();
+
+  let (tax_id, bank_account) = stored_fields(&app, id).await;
+  let (tax_id, bank_account) = (tax_id.unwrap(), bank_account.unwrap());
+  assert!(tax_id.starts_with("enc:v1:test:"), "{tax_id}");
+  assert!(!tax_id.contains("01.234.567.8-901.000") && !bank_account.contains("BCA 1234567890"));
+
+  let uri = format!("/api/v1/contacts/{id}");
+  let (status, body) = app.call(http::Method::GET, &uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["tax_id"], "01.234.567.8-901.000");
+  assert_eq!(body["results"]["bank_account"], "BCA 1234567890");
+
+  // A value moved to another column does not decrypt
+  {
+    let mut conn = app.db.acquire().await.unwrap();
+    sqlx::query("UPDATE contacts SET bank_account = tax_id WHERE id = $1")
+      .bind(id)
+      .execute(&mut *conn)
+      .await
+      .unwrap();
+  }
+  let (status, _) = app.call(http::Method::GET, &uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn test_fields_are_reencrypted_with_the_new_key() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  let payload = json!({
+    "code": "ROT-1",
+    "name": "Rotated Supplier",
+    "email": "rotated@example.com",
+    "contact_type": "supplier",
+    "tax_id": "99.888.777.6-555.000",
+  });
+  let (status, body) = app.call(http::Method::POST, "/api/v1/contacts", &user, workspace.id, Some(payload)).await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  let id: Uuid = body["results"]["id"].as_str().unwrap().parse().unwrap();
+
+  // The new key encrypts, the old one still decrypts until the values are moved over
+  let rotated = SqlxContactRepository::new(app.db.clone()).with_cipher(cipher(&[NEW_KEY, TEST_ENCRYPTION_KEY]));
+  let contact = rotated.find_by_id_and_workspace(id, workspace.id, user.id()).await.unwrap().unwrap();
+  assert_eq!(contact.tax_id.as_deref(), Some("99.888.777.6-555.000"));
+
+  let updated = rotated.reencrypt_sensitive_fields(100).await.unwrap();
+  assert!(updated >= 1);
+  let (tax_id, bank_account) = stored_fields(&app, id).await;
+  assert!(tax_id.unwrap().starts_with("enc:v1:next:"));
+  assert_eq!(bank_account, None, "empty fields stay empty");
+  assert_eq!(rotated.reencrypt_sensitive_fields(100).await.unwrap(), 0);
+
+  let new_only = SqlxContactRepository::new(app.db.clone()).with_cipher(cipher(&[NEW_KEY]));
+  let contact = new_only.find_by_id_and_workspace(id, workspace.id, user.id()).await.unwrap().unwrap();
+  assert_eq!(contact.tax_id.as_deref(), Some("99.888.777.6-555.000"));
+}
+
+#[test]
+fn test_encrypted_fields_are_redacted_in_payload_logs() {
+  let mut payload = json!({
+    "code": "C-1",
+    "tax_id": "01.234.567.8-901.000",
+    "bank_account": "1234567890",
+    "contacts": [{ "tax_id": "DE123456789", "name": "Nested" }],
+  });
+  redact_json(&mut payload);
+
+  assert_eq!(
+    payload,
+    json!({
+      "code": "C-1",
+      "tax_id": "[REDACTED]",
+      "bank_account": "[REDACTED]",
+      "contacts": [{ "tax_id": "[REDACTED]", "name": "Nested" }],
+    })
+  );
+}
@@ -0,0 +1,156 @@
+//! Field policies: fields of contacts and products hidden from, or read-only for, some roles.
+
+use axum::http::{self, HeaderValue, StatusCode};
+use serde_json::{Value, json};
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+  request,
+};
+use myapp_api_rust::modules::datastores::workspaces::WorkspaceRole;
+
+mod common;
+
+#[tokio::test]
+async fn test_members_do_not_see_or_change_restricted_product_fields() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+  let product = ProductFactory::new().create(&app, &workspace, &admin).await;
+  let product_uri = format!("/api/v1/products/{}", product.id);
+
+  let policies = json!({ "field_policies": { "products": {
+    "unit_cost": { "Member": "hidden" },
+    "selling_price": { "Member": "read_only" },
+  }}});
+  let settings_uri = format!("/api/v1/workspaces/{}/settings", workspace.id);
+  let (status, body) = app
+    .call(http::Method::PUT, &settings_uri, &member, workspace.id, Some(policies.clone()))
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+  let (status, body) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(policies)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["field_policies"]["products"]["unit_cost"]["Member"], "hidden");
+
+  let (status, body) = app.call(http::Method::GET, &product_uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert!(body["results"].get("unit_cost").is_none(), "{body}");
+  assert!(body["results"].get("selling_price").is_some(), "{body}");
+  let (_, body) = app.call(http::Method::GET, "/api/v1/products", &member, workspace.id, None).await;
+  assert!(body["results"]["list"][0].get("unit_cost").is_none(), "{body}");
+  let (_, body) = app
+    .call(
+      http::Method::GET,
+      &format!("/api/v2/products/{}", product.id),
+      &member,
+      workspace.id,
+      None,
+    )
+    .await;
+  assert!(body["data"].get("unit_cost").is_none(), "{body}");
+  let (_, body) = app.call(http::Method::GET, &product_uri, &admin, workspace.id, None).await;
+  assert!(body["results"].get("unit_cost").is_some(), "admins see every field: {body}");
+
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &product_uri,
+      &member,
+      workspace.id,
+      Some(json!({ "selling_price": 99 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+  let (status, body) = app
+    .call(http::Method::PATCH, &product_uri, &member, workspace.id, Some(json!({ "unit_cost": 1 })))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+  let (status, body) = app
+    .call(http::Method::PUT, &product_uri, &member, workspace.id, Some(json!({ "name": "Renamed" })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["name"], "Renamed");
+  assert!(body["results"].get("unit_cost").is_none(), "{body}");
+
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &product_uri,
+      &admin,
+      workspace.id,
+      Some(json!({ "selling_price": 99 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+#[tokio::test]
+async fn test_hidden_contact_fields_are_left_out_of_streamed_lists() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+  let contact = json!({ "code": "C-1", "name": "Acme", "email": "acme@example.com", "contact_type": "customer", "tax_id": "01.234.567.8-901.000" });
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", &admin, workspace.id, Some(contact))
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+
+  let policies = json!({ "field_policies": { "contacts": { "tax_id": { "Member": "hidden" } } } });
+  let settings_uri = format!("/api/v1/workspaces/{}/settings", workspace.id);
+  let (status, body) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(policies)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let mut request = request(http::Method::GET, "/api/v1/contacts", &member, workspace.id, None);
+  request
+    .headers_mut()
+    .insert(http::header::ACCEPT, HeaderValue::from_static("application/x-ndjson"));
+  let (status, _, body) = app.send(request).await;
+  assert_eq!(status, StatusCode::OK);
+  let lines: Vec<Value> = body
+    .split(|b| *b == b'\n')
+    .filter(|line| !line.is_empty())
+    .map(|line| serde_json::from_slice(line).unwrap())
+    .collect();
+  assert_eq!(lines.len(), 1);
+  assert_eq!(lines[0]["name"], "Acme");
+  assert!(lines[0].get("tax_id").is_none(), "{}", lines[0]);
+
+  // Null lifts the restrictions of the entity
+  let policies = json!({ "field_policies": { "contacts": null } });
+  let (status, body) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(policies)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["field_policies"], json!({}));
+  let (_, body) = app.call(http::Method::GET, "/api/v1/contacts", &member, workspace.id, None).await;
+  assert!(body["results"]["list"][0].get("tax_id").is_some(), "{body}");
+}
+
+#[tokio::test]
+async fn test_field_policies_are_validated() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &admin).await;
+  let settings_uri = format!("/api/v1/workspaces/{}/settings", workspace.id);
+
+  for policies in [
+    json!({ "invoices": { "total": { "Member": "hidden" } } }),
+    json!({ "products": { "code": { "Member": "hidden" } } }),
+    json!({ "products": { "unit_cost": {} } }),
+  ] {
+    let body = json!({ "field_policies": policies });
+    let (status, response) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(body)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{policies}: {response}");
+  }
+
+  // Admins are never restricted, and fields are either hidden or read-only
+  for policies in [
+    json!({ "products": { "unit_cost": { "Admin": "hidden" } } }),
+    json!({ "products": { "unit_cost": { "Member": "secret" } } }),
+  ] {
+    let body = json!({ "field_policies": policies });
+    let (status, response) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(body)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{policies}: {response}");
+  }
+}
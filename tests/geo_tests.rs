@@ -0,0 +1,205 @@
+//! Address autocomplete: suggestions from a fake geocoder, the cache and the daily quota of
+//! workspaces, and the parsing of the providers' responses.
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use axum::http::{self, StatusCode};
+use myapp_api_rust::{
+  AppResult,
+  config::AppConfig,
+  modules::geo::{
+    geo_models::{AddressLookup, AddressSuggestion},
+    geocoder::{Geocoder, parse_mapbox, parse_nominatim},
+  },
+};
+use serde_json::json;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+/// Suggests one address per lookup, recording the lookups it received.
+#[derive(Default)]
+struct FakeGeocoder {
+  lookups: Mutex<Vec<AddressLookup>>,
+}
+
+#[async_trait]
+impl Geocoder for FakeGeocoder {
+  async fn autocomplete(&self, lookup: &AddressLookup) -> AppResult<Vec<AddressSuggestion>> {
+    self.lookups.lock().unwrap().push(lookup.clone());
+    Ok(vec![AddressSuggestion {
+      label: "Jalan Sudirman 1, Jakarta Pusat, DKI Jakarta 10220, Indonesia".to_string(),
+      street: Some("Jalan Sudirman 1".to_string()),
+      city: Some("Jakarta Pusat".to_string()),
+      region: Some("DKI Jakarta".to_string()),
+      postal_code: Some("10220".to_string()),
+      country: Some("Indonesia".to_string()),
+      country_code: Some("ID".to_string()),
+      latitude: -6.2146,
+      longitude: 106.8227,
+    }])
+  }
+}
+
+async fn app_with(geocoder: Arc<FakeGeocoder>, daily_quota: u32) -> TestApp {
+  let mut config = AppConfig::from_env();
+  config.geo.daily_quota = daily_quota;
+  TestApp::isolated_with(|builder| builder.with_config(config).with_geocoder(geocoder)).await
+}
+
+#[tokio::test]
+async fn test_autocomplete_suggests_structured_addresses_from_the_cache() {
+  let geocoder = Arc::new(FakeGeocoder::default());
+  let app = app_with(geocoder.clone(), 10).await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  let (status, body) = app
+    .call(
+      http::Method::GET,
+      "/api/v1/geo/autocomplete?q=Jalan%20Sudirman%201&country=ID",
+      &user,
+      workspace.id,
+      None,
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let suggestion = &body["results"][0];
+  assert_eq!(suggestion["street"], "Jalan Sudirman 1");
+  assert_eq!(suggestion["postal_code"], "10220");
+  assert_eq!(suggestion["country_code"], "ID");
+
+  // The same query typed differently is served from the cache
+  let (status, body) = app
+    .call(
+      http::Method::GET,
+      "/api/v1/geo/autocomplete?q=%20jalan%20%20SUDIRMAN%201&country=id",
+      &user,
+      workspace.id,
+      None,
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"].as_array().unwrap().len(), 1);
+  let lookups = geocoder.lookups.lock().unwrap().clone();
+  assert_eq!(
+    lookups,
+    [AddressLookup {
+      query: "jalan sudirman 1".to_string(),
+      country: Some("id".to_string()),
+      limit: 5,
+    }]
+  );
+}
+
+#[tokio::test]
+async fn test_lookups_are_limited_per_workspace_and_day() {
+  let geocoder = Arc::new(FakeGeocoder::default());
+  let app = app_with(geocoder.clone(), 2).await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let other = WorkspaceFactory::new().create(&app, &user).await;
+
+  for q in ["Jalan Thamrin", "Jalan Gatot Subroto"] {
+    let (status, body) = app
+      .call(
+        http::Method::GET,
+        &format!("/api/v1/geo/autocomplete?q={}", q.replace(' ', "%20")),
+        &user,
+        workspace.id,
+        None,
+      )
+      .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+  }
+  let (status, body) = app
+    .call(
+      http::Method::GET,
+      "/api/v1/geo/autocomplete?q=Jalan%20Kuningan",
+      &user,
+      workspace.id,
+      None,
+    )
+    .await;
+  assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "{body}");
+  assert!(body["details"]["retry_after"].as_u64().unwrap() <= 86_400, "{body}");
+
+  // Cached answers and other workspaces are not limited
+  let (status, _) = app
+    .call(http::Method::GET, "/api/v1/geo/autocomplete?q=Jalan%20Thamrin", &user, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  let (status, _) = app
+    .call(http::Method::GET, "/api/v1/geo/autocomplete?q=Jalan%20Kuningan", &user, other.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(geocoder.lookups.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_autocomplete_validates_the_query() {
+  let app = app_with(Arc::new(FakeGeocoder::default()), 10).await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  for uri in [
+    "/api/v1/geo/autocomplete?q=Ja",
+    "/api/v1/geo/autocomplete?q=Jalan&country=IDN",
+    "/api/v1/geo/autocomplete?q=Jalan&limit=50",
+  ] {
+    let (status, _) = app.call(http::Method::GET, uri, &user, workspace.id, None).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{uri}");
+  }
+}
+
+#[test]
+fn test_provider_responses_are_parsed_into_structured_addresses() {
+  let nominatim = json!([{
+    "display_name": "1, Jalan Jenderal Sudirman, Jakarta Pusat, DKI Jakarta, 10220, Indonesia",
+    "lat": "-6.2146",
+    "lon": "106.8227",
+    "address": {
+      "house_number": "1",
+      "road": "Jalan Jenderal Sudirman",
+      "city": "Jakarta Pusat",
+      "state": "DKI Jakarta",
+      "postcode": "10220",
+      "country": "Indonesia",
+      "country_code": "id"
+    }
+  }, { "display_name": "No coordinates" }]);
+  let suggestions = parse_nominatim(&nominatim);
+  assert_eq!(suggestions.len(), 1);
+  assert_eq!(suggestions[0].street.as_deref(), Some("Jalan Jenderal Sudirman 1"));
+  assert_eq!(suggestions[0].city.as_deref(), Some("Jakarta Pusat"));
+  assert_eq!(suggestions[0].country_code.as_deref(), Some("ID"));
+  assert_eq!(suggestions[0].latitude, -6.2146);
+
+  let mapbox = json!({
+    "features": [{
+      "place_type": ["address"],
+      "place_name": "Jalan Jenderal Sudirman 1, Jakarta Pusat, DKI Jakarta 10220, Indonesia",
+      "text": "Jalan Jenderal Sudirman",
+      "address": "1",
+      "center": [106.8227, -6.2146],
+      "context": [
+        { "id": "postcode.123", "text": "10220" },
+        { "id": "place.456", "text": "Jakarta Pusat" },
+        { "id": "region.789", "text": "DKI Jakarta", "short_code": "ID-JK" },
+        { "id": "country.1", "text": "Indonesia", "short_code": "id" }
+      ]
+    }]
+  });
+  let suggestions = parse_mapbox(&mapbox);
+  assert_eq!(suggestions.len(), 1);
+  assert_eq!(suggestions[0].street.as_deref(), Some("Jalan Jenderal Sudirman 1"));
+  assert_eq!(suggestions[0].region.as_deref(), Some("DKI Jakarta"));
+  assert_eq!(suggestions[0].postal_code.as_deref(), Some("10220"));
+  assert_eq!(suggestions[0].country_code.as_deref(), Some("ID"));
+  assert_eq!((suggestions[0].latitude, suggestions[0].longitude), (-6.2146, 106.8227));
+}
@@ -0,0 +1,200 @@
+//! The internal gRPC API, called in-process through its service implementations.
+
+use std::net::SocketAddr;
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::{
+  config::AppConfig,
+  grpc::{
+    contact_service::contact_service_server::ContactService,
+    contacts::ContactGrpcService,
+    messages::{CreateContactRequest, ListContactsRequest},
+  },
+};
+use serde_json::json;
+use tonic::{Code, Request, transport::server::TcpConnectInfo};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{TestUser, UserFactory, WorkspaceFactory},
+  request,
+};
+
+mod common;
+
+/// A call carrying `message`, made by `user` in `workspace_id` from the client address `ip` if any.
+fn call<T>(message: T, user: &TestUser, workspace_id: Uuid, ip: Option<&str>) -> Request<T> {
+  let mut request = Request::new(message);
+  let metadata = request.metadata_mut();
+  metadata.insert("authorization", format!("Bearer {}", user.token).parse().unwrap());
+  metadata.insert("x-workspace-id", workspace_id.to_string().parse().unwrap());
+  request.extensions_mut().insert(TcpConnectInfo {
+    local_addr: None,
+    remote_addr: ip.map(|ip| SocketAddr::new(ip.parse().unwrap(), 40_000)),
+  });
+  request
+}
+
+/// A `ListContacts` call by `user` in `workspace_id`, from the client address `ip` if any.
+fn list_contacts(user: &TestUser, workspace_id: Uuid, ip: Option<&str>) -> Request<ListContactsRequest> {
+  call(ListContactsRequest::default(), user, workspace_id, ip)
+}
+
+/// A `CreateContact` call by `user` in `workspace_id`.
+fn create_contact(user: &TestUser, workspace_id: Uuid, code: &str) -> Request<CreateContactRequest> {
+  let message = CreateContactRequest {
+    code: code.to_string(),
+    name: "Grpc Contact".to_string(),
+    email: "grpc@example.com".to_string(),
+    contact_type: "customer".to_string(),
+    ..Default::default()
+  };
+  call(message, user, workspace_id, None)
+}
+
+#[tokio::test]
+async fn test_calls_from_outside_the_allowlist_are_rejected() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &admin).await;
+  let service = ContactGrpcService::new(app.state.clone());
+
+  assert!(service.list_contacts(list_contacts(&admin, workspace.id, None)).await.is_ok());
+
+  let mut settings = request(
+    http::Method::PUT,
+    &format!("/api/v1/workspaces/{}/settings", workspace.id),
+    &admin,
+    workspace.id,
+    Some(json!({ "allowed_ip_ranges": ["203.0.113.0/24"] })),
+  );
+  settings
+    .extensions_mut()
+    .insert(axum::extract::ConnectInfo(SocketAddr::new("203.0.113.7".parse().unwrap(), 40_000)));
+  let (status, _, body) = app.send(settings).await;
+  assert_eq!(status, StatusCode::OK, "{body:?}");
+
+  assert!(
+    service
+      .list_contacts(list_contacts(&admin, workspace.id, Some("203.0.113.9")))
+      .await
+      .is_ok()
+  );
+  for ip in [Some("198.51.100.1"), None] {
+    let status = service.list_contacts(list_contacts(&admin, workspace.id, ip)).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied, "{ip:?}: {status:?}");
+  }
+}
+
+#[tokio::test]
+async fn test_writes_are_refused_once_the_trial_expired() {
+  let app = TestApp::isolated().await;
+  let owner = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &owner).await;
+  let service = ContactGrpcService::new(app.state.clone());
+
+  assert!(service.create_contact(create_contact(&owner, workspace.id, "GRPC-1")).await.is_ok());
+
+  sqlx::query("UPDATE workspace_trials SET started_at = NOW() - INTERVAL '15 days', ends_at = NOW() - INTERVAL '1 minute' WHERE workspace_id = $1")
+    .bind(workspace.id)
+    .execute(&mut *app.db.acquire().await.unwrap())
+    .await
+    .unwrap();
+
+  let status = service.create_contact(create_contact(&owner, workspace.id, "GRPC-2")).await.unwrap_err();
+  assert_eq!(status.code(), Code::FailedPrecondition, "{status:?}");
+  let contacts = service
+    .list_contacts(list_contacts(&owner, workspace.id, None))
+    .await
+    .unwrap()
+    .into_inner();
+  assert_eq!(contacts.contacts.len(), 1, "data stays readable");
+}
+
+#[tokio::test]
+async fn test_calls_share_the_rate_limits_of_rest_requests() {
+  let mut config = AppConfig::from_env();
+  config.rate_limit.enabled = true;
+  config.rate_limit.read_max = 100;
+  config.rate_limit.write_max = 2;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let owner = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &owner).await;
+  let service = ContactGrpcService::new(app.state.clone());
+
+  assert!(service.create_contact(create_contact(&owner, workspace.id, "GRPC-1")).await.is_ok());
+  let contact = Some(json!({ "code": "REST-1", "name": "Rest Contact", "email": "rest@example.com", "contact_type": "customer" }));
+  let (status, _) = app.call(http::Method::POST, "/api/v1/contacts", &owner, workspace.id, contact).await;
+  assert_eq!(status, StatusCode::CREATED);
+
+  let status = service.create_contact(create_contact(&owner, workspace.id, "GRPC-2")).await.unwrap_err();
+  assert_eq!(status.code(), Code::ResourceExhausted, "{status:?}");
+  assert!(
+    service.list_contacts(list_contacts(&owner, workspace.id, None)).await.is_ok(),
+    "reads have their own budget"
+  );
+}
+
+#[tokio::test]
+async fn test_calls_are_metered_and_limited_by_the_plan_quota() {
+  let mut config = AppConfig::from_env();
+  config.usage.free_monthly_requests = 2;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let owner = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &owner).await;
+  let service = ContactGrpcService::new(app.state.clone());
+
+  for _ in 0..2 {
+    assert!(service.list_contacts(list_contacts(&owner, workspace.id, None)).await.is_ok());
+  }
+  let status = service.list_contacts(list_contacts(&owner, workspace.id, None)).await.unwrap_err();
+  assert_eq!(status.code(), Code::ResourceExhausted, "{status:?}");
+
+  app.state.usage_meter.flush(app.state.usage_repository.as_ref()).await.unwrap();
+  let (status, body) = app
+    .call(
+      http::Method::GET,
+      &format!("/api/v1/workspaces/{}/usage/api", workspace.id),
+      &owner,
+      workspace.id,
+      None,
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  let route = body["results"]["by_route"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .find(|route| route["route"] == "/myapp.v1.ContactService/ListContacts")
+    .expect("gRPC method metered");
+  assert_eq!(route["requests"], 2);
+}
+
+#[tokio::test]
+async fn test_creates_with_an_idempotency_key_are_replayed() {
+  let app = TestApp::isolated().await;
+  let owner = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &owner).await;
+  let service = ContactGrpcService::new(app.state.clone());
+  let keyed = |code: &str| {
+    let mut request = create_contact(&owner, workspace.id, code);
+    request.metadata_mut().insert("idempotency-key", "create-once".parse().unwrap());
+    request
+  };
+
+  let first = service.create_contact(keyed("GRPC-1")).await.unwrap();
+  assert!(first.metadata().get("idempotent-replayed").is_none());
+  let retry = service.create_contact(keyed("GRPC-1")).await.unwrap();
+  assert_eq!(retry.metadata().get("idempotent-replayed").unwrap(), "true");
+  assert_eq!(retry.get_ref().id, first.get_ref().id);
+
+  let status = service.create_contact(keyed("GRPC-2")).await.unwrap_err();
+  assert_eq!(status.code(), Code::AlreadyExists, "a different message reuses the key: {status:?}");
+  let contacts = service
+    .list_contacts(list_contacts(&owner, workspace.id, None))
+    .await
+    .unwrap()
+    .into_inner();
+  assert_eq!(contacts.contacts.len(), 1);
+}
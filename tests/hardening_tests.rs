@@ -0,0 +1,192 @@
+//! Hostile or mistaken input and stale permissions: filter values are bound, pagination and path
+//! UUIDs are checked, codes stay unique per workspace and role changes apply to the next request.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::{config::AppConfig, modules::datastores::workspaces::workspace_models::WorkspaceRole};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+fn contact(code: &str) -> Value {
+  json!({ "code": code, "name": code, "email": format!("{}@example.com", code.to_lowercase()), "contact_type": "customer" })
+}
+
+fn product(code: &str) -> Value {
+  json!({ "code": code, "name": code, "base_unit": "pcs", "selling_price": 1, "unit_cost": 1 })
+}
+
+#[tokio::test]
+async fn test_filter_values_are_bound_not_interpolated() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let (status, _) = app
+    .call(http::Method::POST, "/api/v1/contacts", &user, workspace.id, Some(contact("BOUND-1")))
+    .await;
+  assert_eq!(status, StatusCode::CREATED);
+  ProductFactory::new().code("BOUND-P1").create(&app, &workspace, &user).await;
+
+  // x' OR '1'='1 and '; DROP TABLE products; --
+  let always_true = "x%27%20OR%20%271%27%3D%271";
+  let drop_table = "%27%3B%20DROP%20TABLE%20products%3B%20--";
+  for uri in [
+    format!("/api/v1/contacts?search={always_true}"),
+    format!("/api/v1/contacts?code={always_true}"),
+    format!("/api/v1/contacts?email={drop_table}"),
+    format!("/api/v1/products?search={always_true}"),
+    format!("/api/v1/products?search={drop_table}"),
+  ] {
+    let (status, body) = app.call(http::Method::GET, &uri, &user, workspace.id, None).await;
+    assert_eq!(status, StatusCode::OK, "{uri}: {body}");
+    assert_eq!(body["results"]["pagination"]["total"], 0, "{uri} matched as SQL");
+  }
+
+  for uri in ["/api/v1/contacts", "/api/v1/products"] {
+    let (status, body) = app.call(http::Method::GET, uri, &user, workspace.id, None).await;
+    assert_eq!(status, StatusCode::OK, "{uri}: {body}");
+    assert_eq!(body["results"]["pagination"]["total"], 1, "{uri}");
+  }
+}
+
+#[tokio::test]
+async fn test_pagination_outside_its_bounds_is_rejected() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  for list in ["/api/v1/contacts", "/api/v1/products"] {
+    for (query, field) in [("page=0", "page"), ("limit=0", "limit"), ("limit=101", "limit")] {
+      let uri = format!("{list}?{query}");
+      let (status, body) = app.call(http::Method::GET, &uri, &user, workspace.id, None).await;
+      assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{uri}: {body}");
+      assert!(body["details"][field].is_array(), "{uri}: {body}");
+    }
+
+    let uri = format!("{list}?page=1&limit=100");
+    let (status, body) = app.call(http::Method::GET, &uri, &user, workspace.id, None).await;
+    assert_eq!(status, StatusCode::OK, "{uri}: {body}");
+  }
+}
+
+#[tokio::test]
+async fn test_pages_far_past_the_end_are_empty() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  ProductFactory::new().code("FAR-P1").create(&app, &workspace, &user).await;
+
+  // (page - 1) * limit does not fit in a u32; the second query of each list is filtered
+  for uri in [
+    "/api/v1/contacts?page=4000000000&limit=100",
+    "/api/v1/contacts?page=4000000000&limit=100&search=x",
+    "/api/v1/products?page=4000000000&limit=100",
+    "/api/v1/products?page=4000000000&limit=100&search=FAR",
+  ] {
+    let (status, body) = app.call(http::Method::GET, uri, &user, workspace.id, None).await;
+    assert_eq!(status, StatusCode::OK, "{uri}: {body}");
+    assert_eq!(body["results"]["list"], json!([]), "{uri}");
+    assert_eq!(body["results"]["pagination"]["page"], 4_000_000_000u32, "{uri}");
+  }
+}
+
+#[tokio::test]
+async fn test_malformed_uuids_are_bad_requests() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  for uri in ["/api/v1/contacts/not-a-uuid", "/api/v1/products/not-a-uuid"] {
+    let (status, body) = app.call(http::Method::GET, uri, &user, workspace.id, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}: {body}");
+    let message = body["message"].as_str().unwrap();
+    assert!(message.contains("'id'") && message.contains("'not-a-uuid'"), "{message}");
+  }
+  let uri = format!("/api/v1/workspaces/{}/users/not-a-uuid", workspace.id);
+  let (status, body) = app.call(http::Method::DELETE, &uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  assert!(body["message"].as_str().unwrap().contains("'user_id'"), "{body}");
+
+  // A well-formed id that matches nothing is still missing
+  let uri = format!("/api/v1/contacts/{}", Uuid::new_v4());
+  let (status, _) = app.call(http::Method::GET, &uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+/// Creates `payload` in two workspaces, then again in the first. The duplicate goes last: the
+/// violation aborts the rolled-back transaction the test app runs in.
+async fn assert_unique_per_workspace(uri: &str, payload: Value) {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let other = WorkspaceFactory::new().create(&app, &user).await;
+
+  for workspace_id in [workspace.id, other.id] {
+    let (status, body) = app.call(http::Method::POST, uri, &user, workspace_id, Some(payload.clone())).await;
+    assert_eq!(status, StatusCode::CREATED, "{uri}: {body}");
+  }
+
+  // Caught by the unique index, as a second request racing the first would be
+  let (status, body) = app.call(http::Method::POST, uri, &user, workspace.id, Some(payload)).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{uri}: {body}");
+  assert_eq!(body["details"]["code"][0]["code"], "DUPLICATE_CODE", "{uri}: {body}");
+}
+
+#[tokio::test]
+async fn test_contact_codes_are_unique_per_workspace() {
+  assert_unique_per_workspace("/api/v1/contacts", contact("DUP-1")).await;
+}
+
+#[tokio::test]
+async fn test_product_codes_are_unique_per_workspace() {
+  // Forced past the similar-name check, which would otherwise answer first
+  assert_unique_per_workspace("/api/v1/products?force=true", product("DUP-1")).await;
+}
+
+#[tokio::test]
+async fn test_role_changes_apply_to_the_next_request() {
+  let mut config = AppConfig::from_env();
+  config.role_cache.ttl_secs = 60;
+  config.role_cache.claim_ttl_secs = 0;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let owner = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &owner).await;
+  let role_uri = format!("/api/v1/workspaces/{}/users/{}/role", workspace.id, member.id());
+
+  // Caches the member's role
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", &member, workspace.id, Some(contact("ROLE-1")))
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+
+  let (status, body) = app
+    .call(http::Method::PUT, &role_uri, &owner, workspace.id, Some(json!({ "role": "Viewer" })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", &member, workspace.id, Some(contact("ROLE-2")))
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "demotion not seen: {body}");
+
+  let (status, body) = app
+    .call(http::Method::PUT, &role_uri, &owner, workspace.id, Some(json!({ "role": "Member" })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", &member, workspace.id, Some(contact("ROLE-3")))
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "promotion not seen: {body}");
+
+  let uri = format!("/api/v1/workspaces/{}/users/{}", workspace.id, member.id());
+  let (status, body) = app.call(http::Method::DELETE, &uri, &owner, workspace.id, None).await;
+  assert!(status.is_success(), "{body}");
+  let (status, body) = app.call(http::Method::GET, "/api/v1/contacts", &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED, "removal not seen: {body}");
+  assert_eq!(body["error"], "WORKSPACE_INVALID", "{body}");
+}
@@ -0,0 +1,131 @@
+//! Importing contacts and products from the CSV exports of accounting tools.
+
+use axum::{
+  body::Body,
+  http::{self, HeaderValue, StatusCode},
+};
+use myapp_api_rust::modules::datastores::workspaces::WorkspaceRole;
+use rust_decimal::Decimal;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, TestUser, UserFactory, WorkspaceFactory},
+  request,
+};
+
+mod common;
+
+async fn import(app: &TestApp, uri: &str, user: &TestUser, workspace_id: Uuid, csv: &str) -> (StatusCode, Value) {
+  let mut request = request(http::Method::POST, uri, user, workspace_id, None);
+  request
+    .headers_mut()
+    .insert(http::header::CONTENT_TYPE, HeaderValue::from_static("text/csv"));
+  *request.body_mut() = Body::from(csv.to_string());
+  let (status, _, body) = app.send(request).await;
+  (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_exports_are_mapped_onto_products_and_contacts() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let existing = ProductFactory::new().create(&app, &workspace, &user).await;
+
+  // QuickBooks puts the report title above the header row
+  let quickbooks = format!(
+    "Product/Service List\n\
+     \n\
+     Product/Service Name,SKU,Type,Sales Price / Rate,Purchase Cost,Quantity On Hand,Reorder Point\n\
+     Cordless Drill,QB-DRILL,Inventory,\"$1,250.00\",$800.50,12.00,4\n\
+     Installation,,Service,$75.00,,,\n\
+     Duplicate,{},Inventory,$1.00,$1.00,1,\n",
+    existing.code
+  );
+  let (status, body) = import(&app, "/api/v1/import/quickbooks?entity=products", &user, workspace.id, &quickbooks).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let summary = &body["results"];
+  assert_eq!(summary["rows"], 3);
+  assert_eq!(summary["created"], 2);
+  assert_eq!(summary["skipped"], serde_json::json!([existing.code]));
+
+  let mut conn = app.db.acquire().await.unwrap();
+  let (selling_price, unit_cost, stock, reorder_level, tracked): (Decimal, Decimal, i32, i32, bool) = sqlx::query_as(
+    "SELECT selling_price, unit_cost, current_stock, reorder_level, track_inventory FROM products WHERE workspace_id = $1 AND code = 'QB-DRILL'",
+  )
+  .bind(workspace.id)
+  .fetch_one(&mut *conn)
+  .await
+  .unwrap();
+  assert_eq!(
+    (selling_price, unit_cost, stock, reorder_level, tracked),
+    (Decimal::new(125_000, 2), Decimal::new(80_050, 2), 12, 4, true)
+  );
+  let (code, tracked, status): (String, bool, String) =
+    sqlx::query_as("SELECT code, track_inventory, status::TEXT FROM products WHERE workspace_id = $1 AND name = 'Installation'")
+      .bind(workspace.id)
+      .fetch_one(&mut *conn)
+      .await
+      .unwrap();
+  assert!(!code.is_empty(), "a code is generated for rows without one");
+  assert!(!tracked);
+  assert_eq!(status, "draft", "imported products wait to be published");
+  drop(conn);
+
+  // Accurate writes semicolon separated files with Indonesian headers
+  let accurate = "No. Pelanggan;Nama Pelanggan;Email;Alamat;NPWP;Tipe Kontak\n\
+                  C-001;Toko Maju;maju@example.com;Jl. Merdeka 1;01.234.567.8-901.000;\n\
+                  S-001;PT Sumber;sumber@example.com;;;Pemasok\n";
+  let (status, body) = import(&app, "/api/v1/import/accurate?entity=contacts", &user, workspace.id, accurate).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["created"], 2);
+
+  let mut conn = app.db.acquire().await.unwrap();
+  let contacts: Vec<(String, String, String, Option<String>)> =
+    sqlx::query_as("SELECT code, name, type, address FROM contacts WHERE workspace_id = $1 ORDER BY code")
+      .bind(workspace.id)
+      .fetch_all(&mut *conn)
+      .await
+      .unwrap();
+  assert_eq!(
+    contacts,
+    [
+      ("C-001".into(), "Toko Maju".into(), "customer".into(), Some("Jl. Merdeka 1".into())),
+      ("S-001".into(), "PT Sumber".into(), "supplier".into(), None),
+    ]
+  );
+}
+
+#[tokio::test]
+async fn test_invalid_files_import_nothing() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let viewer = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&viewer, WorkspaceRole::Viewer).create(&app, &user).await;
+  let uri = "/api/v1/import/generic?entity=contacts";
+
+  let csv = "code,name,email\nA-1,Alpha,alpha@example.com\nA-2,Beta,not-an-email\nA-1,Gamma,gamma@example.com\n";
+  let (status, body) = import(&app, uri, &user, workspace.id, csv).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+  let messages = body.to_string();
+  assert!(messages.contains("Line 3"), "{body}");
+  assert!(messages.contains("Code 'A-1' is also used on line 2"), "{body}");
+
+  let mut conn = app.db.acquire().await.unwrap();
+  let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM contacts WHERE workspace_id = $1")
+    .bind(workspace.id)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+  assert_eq!(count, 0);
+  drop(conn);
+
+  let (status, _) = import(&app, uri, &user, workspace.id, "name,phone\nAlpha,123\n").await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "the email column is required");
+  let (status, _) = import(&app, "/api/v1/import/xero?entity=contacts", &user, workspace.id, csv).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let (status, _) = import(&app, uri, &viewer, workspace.id, csv).await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,139 @@
+//! Inbound webhooks: integrations, signed deliveries and their queue.
+
+use axum::{
+  body::Body,
+  http::{self, Request, StatusCode},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use myapp_api_rust::{
+  config::AppConfig,
+  modules::{datastores::workspaces::WorkspaceRole, inbound::inbound_signature::sign},
+};
+use serde_json::{Value, json};
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+/// Posts `payload` to the inbound endpoint of `key`, as the sending system would.
+async fn deliver(app: &TestApp, key: &str, payload: &str, signature: Option<(&str, String)>) -> (StatusCode, Value) {
+  let mut request = Request::builder()
+    .method(http::Method::POST)
+    .uri(format!("/api/v1/inbound/{key}"))
+    .header(http::header::CONTENT_TYPE, "application/json");
+  if let Some((header, signature)) = signature {
+    request = request.header(header, signature);
+  }
+  let (status, _, body) = app.send(request.body(Body::from(payload.to_string())).unwrap()).await;
+  (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_signed_payloads_are_queued() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &admin).await;
+
+  let request = json!({ "name": "Online store", "source": "shopify" });
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/inbound-integrations", &admin, workspace.id, Some(request))
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  let id = body["results"]["id"].as_str().unwrap().to_string();
+  let key = body["results"]["key"].as_str().unwrap().to_string();
+  let secret = body["results"]["secret"].as_str().unwrap().to_string();
+
+  let order = r#"{"id":1001,"line_items":[{"sku":"KOPI-1","quantity":2}]}"#;
+  let (status, body) = deliver(&app, &key, order, Some(("X-Signature", sign(order.as_bytes(), &secret)))).await;
+  assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+  assert_eq!(body["results"]["status"], "pending");
+
+  // Shopify-style: base64 in its own header
+  let base64_mac = STANDARD.encode(hex::decode(sign(order.as_bytes(), &secret)).unwrap());
+  let (status, _) = deliver(&app, &key, order, Some(("X-Shopify-Hmac-Sha256", base64_mac))).await;
+  assert_eq!(status, StatusCode::ACCEPTED);
+
+  // Refused: unsigned, signed with another secret, tampered with, or to an unknown key
+  let (status, _) = deliver(&app, &key, order, None).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  let (status, _) = deliver(&app, &key, order, Some(("X-Signature", sign(order.as_bytes(), "other")))).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  let tampered = order.replace("1001", "1002");
+  let (status, _) = deliver(&app, &key, &tampered, Some(("X-Signature", sign(order.as_bytes(), &secret)))).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  let (status, _) = deliver(
+    &app,
+    "0123456789abcdef0123456789abcdef",
+    order,
+    Some(("X-Signature", sign(order.as_bytes(), &secret))),
+  )
+  .await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+  let (status, _) = deliver(&app, &key, "not json", Some(("X-Signature", sign(b"not json", &secret)))).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+  let events = format!("/api/v1/inbound-integrations/{id}/events?status=pending");
+  let (status, body) = app.call(http::Method::GET, &events, &admin, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let events = body["results"].as_array().unwrap();
+  assert_eq!(events.len(), 2);
+  assert_eq!(events[0]["payload"]["line_items"][0]["sku"], "KOPI-1");
+
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/inbound-integrations", &admin, workspace.id, None)
+    .await;
+  assert!(body["results"][0]["last_received_at"].is_string());
+  assert!(body["results"][0].get("secret").is_none(), "the secret is only shown once");
+
+  // Deleted integrations refuse deliveries
+  let uri = format!("/api/v1/inbound-integrations/{id}");
+  let (status, _) = app.call(http::Method::DELETE, &uri, &admin, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  let (status, _) = deliver(&app, &key, order, Some(("X-Signature", sign(order.as_bytes(), &secret)))).await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_integrations_are_managed_by_admins() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+
+  let request = json!({ "name": "Online store", "source": "woocommerce" });
+  let (status, _) = app
+    .call(http::Method::POST, "/api/v1/inbound-integrations", &member, workspace.id, Some(request))
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+  let (status, _) = app
+    .call(http::Method::GET, "/api/v1/inbound-integrations", &member, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+
+  let (status, _) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/inbound-integrations",
+      &admin,
+      workspace.id,
+      Some(json!({ "name": "", "source": "woocommerce" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_oversized_deliveries_are_refused_with_the_limit() {
+  let mut config = AppConfig::from_env();
+  config.body_limit.default_bytes = 16;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+
+  // Sent without a Content-Length, so the size only shows once the body is read
+  let order = r#"{"id":1001,"line_items":[]}"#;
+  let (status, body) = deliver(&app, "0123456789abcdef0123456789abcdef", order, None).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+  assert!(body["message"].as_str().unwrap().contains("maximum allowed size of 16 bytes"), "{body}");
+}
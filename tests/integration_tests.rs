@@ -1,127 +1,37 @@
+use std::error::Error;
+
 use axum::{
   body::Body,
   http::{self, Request, StatusCode},
+  response::IntoResponse,
 };
 use http_body_util::BodyExt;
-use myapp_api_rust::{app, setup_state};
+use myapp_api_rust::{
+  AppState, app,
+  config::AppConfig,
+  errors::{AppError, AuthError},
+  modules::datastores::contacts::{
+    contact_models::CreateContactRequest,
+    contact_repository::{ContactRepository, SqlxContactRepository},
+  },
+  utils::DbExecutor,
+};
 use serde_json::{Value, json};
-use sqlx::PgPool;
 use tower::ServiceExt;
 
-async fn setup_test_db() -> PgPool {
-  dotenvy::dotenv().ok();
-  let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for tests");
-  let pool = PgPool::connect(&db_url).await.expect("Failed to connect to test database");
-
-  // Run migrations to ensure all tables exist
-  let output = std::process::Command::new("sqlx")
-    .args(&["migrate", "run", "--database-url", &db_url])
-    .output();
-
-  match output {
-    Ok(output) if output.status.success() => {
-      eprintln!("Migrations applied successfully");
-    }
-    Ok(output) => {
-      eprintln!("Migration failed: {}", String::from_utf8_lossy(&output.stderr));
-    }
-    Err(e) => {
-      eprintln!("Failed to run migrations: {}. Continuing anyway...", e);
-    }
-  }
-
-  pool
-}
-
-/// Helper to clean up test user data
-async fn cleanup_test_user(pool: &PgPool, email: &str) {
-  // Clean up contacts first (foreign key constraint)
-  sqlx::query("DELETE FROM contacts WHERE created_by IN (SELECT id FROM users WHERE email = $1)")
-    .bind(email)
-    .execute(pool)
-    .await
-    .ok();
-
-  // Clean up user
-  sqlx::query("DELETE FROM users WHERE email = $1").bind(email).execute(pool).await.ok();
-}
-
-async fn clean_up_contact(pool: &PgPool, code: &str) {
-  sqlx::query("DELETE FROM contacts WHERE code = $1")
-    .bind(code)
-    .execute(pool)
-    .await
-    .expect("Failed to clean up contact");
-}
-
-/// Helper to setup the Axum app with test state
-async fn setup_app() -> axum::Router {
-  let state = setup_state().await;
-  app(state)
-}
-
-/// Helper to register a test user and return the response
-async fn register_test_user(app: &axum::Router, username: &str, email: &str, password: &str) -> (StatusCode, Value) {
-  let payload = json!({
-      "username": username,
-      "email": email,
-      "password": password
-  });
-
-  let request = Request::builder()
-    .method(http::Method::POST)
-    .uri("/api/v1/auth/register")
-    .header(http::header::CONTENT_TYPE, "application/json")
-    .body(Body::from(serde_json::to_string(&payload).unwrap()))
-    .unwrap();
-
-  let response = app.clone().oneshot(request).await.unwrap();
-  let status = response.status();
-  let body = response.into_body().collect().await.unwrap().to_bytes();
-  let json: Value = serde_json::from_slice(&body).unwrap();
-
-  (status, json)
-}
-
-/// Helper to login a test user and return the JWT token
-async fn login_test_user(app: &axum::Router, email: &str, password: &str) -> String {
-  let payload = json!({
-      "email": email,
-      "password": password
-  });
-
-  let request = Request::builder()
-    .method(http::Method::POST)
-    .uri("/api/v1/auth/login")
-    .header(http::header::CONTENT_TYPE, "application/json")
-    .body(Body::from(serde_json::to_string(&payload).unwrap()))
-    .unwrap();
-
-  let response = app.clone().oneshot(request).await.unwrap();
-  assert_eq!(response.status(), StatusCode::OK);
-
-  let body = response.into_body().collect().await.unwrap().to_bytes();
-  let json: Value = serde_json::from_slice(&body).unwrap();
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
 
-  json["token"].as_str().unwrap().to_string()
-}
+mod common;
 
 #[tokio::test]
 async fn test_create_contact_success() {
-  let pool = setup_test_db().await;
-  let app = setup_app().await;
-
-  let test_email = "test_contact_creation@example.com";
-  let test_username = "testuser_contact";
-
-  // Cleanup before test
-  cleanup_test_user(&pool, test_email).await;
-
-  // Register and login user to get auth token
-  let (status, _) = register_test_user(&app, test_username, test_email, "password123").await;
-  assert_eq!(status, StatusCode::CREATED);
+  let app = TestApp::isolated().await;
 
-  let token = login_test_user(&app, test_email, "password123").await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
 
   let test_code = "TEST_SUCCESS";
   let payload = json!({
@@ -136,41 +46,30 @@ async fn test_create_contact_success() {
     .method(http::Method::POST)
     .uri("/api/v1/contacts")
     .header(http::header::CONTENT_TYPE, "application/json")
-    .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+    .header(http::header::AUTHORIZATION, user.bearer())
+    .header("X-Workspace-ID", workspace.id.to_string())
     .body(Body::from(serde_json::to_vec(&payload).unwrap()))
     .unwrap();
 
-  let response = app.oneshot(request).await.unwrap();
+  let response = app.clone().oneshot(request).await.unwrap();
 
   assert_eq!(response.status(), StatusCode::CREATED);
+  let location = response.headers()[http::header::LOCATION].to_str().unwrap().to_string();
 
   let body = response.into_body().collect().await.unwrap().to_bytes();
   let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
 
+  assert_eq!(location, format!("/api/v1/contacts/{}", body["results"]["id"].as_str().unwrap()));
   assert_eq!(body["status"], "success");
-  assert_eq!(body["data"]["code"], test_code);
-
-  // Clean up the created contact and user
-  clean_up_contact(&pool, &test_code).await;
-  cleanup_test_user(&pool, test_email).await;
+  assert_eq!(body["results"]["code"], test_code);
 }
 
 #[tokio::test]
 async fn test_create_contact_validation_error() {
-  let pool = setup_test_db().await;
-  let app = setup_app().await;
-
-  let test_email = "test_validation@example.com";
-  let test_username = "testuser_validation";
+  let app = TestApp::isolated().await;
 
-  // Cleanup before test
-  cleanup_test_user(&pool, test_email).await;
-
-  // Register and login user to get auth token
-  let (status, _) = register_test_user(&app, test_username, test_email, "password123").await;
-  assert_eq!(status, StatusCode::CREATED);
-
-  let token = login_test_user(&app, test_email, "password123").await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
 
   // Payload with missing name and invalid email
   let payload = json!({
@@ -185,11 +84,12 @@ async fn test_create_contact_validation_error() {
     .method(http::Method::POST)
     .uri("/api/v1/contacts")
     .header(http::header::CONTENT_TYPE, "application/json")
-    .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+    .header(http::header::AUTHORIZATION, user.bearer())
+    .header("X-Workspace-ID", workspace.id.to_string())
     .body(Body::from(serde_json::to_vec(&payload).unwrap()))
     .unwrap();
 
-  let response = app.oneshot(request).await.unwrap();
+  let response = app.clone().oneshot(request).await.unwrap();
 
   assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
 
@@ -200,30 +100,16 @@ async fn test_create_contact_validation_error() {
   // Check the actual response structure
   assert!(body["details"]["name"].is_array());
   assert!(body["details"]["email"].is_array());
-
-  // Cleanup after test
-  cleanup_test_user(&pool, test_email).await;
 }
 
 #[tokio::test]
 async fn test_create_contact_duplicate_code() {
-  let pool = setup_test_db().await;
-  let app = setup_app().await;
+  let app = TestApp::isolated().await;
 
-  let test_email = "test_duplicate@example.com";
-  let test_username = "testuser_duplicate";
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
   let test_code = "TEST_DUPLICATE";
 
-  // Cleanup before test
-  cleanup_test_user(&pool, test_email).await;
-  clean_up_contact(&pool, test_code).await;
-
-  // Register and login user to get auth token
-  let (status, _) = register_test_user(&app, test_username, test_email, "password123").await;
-  assert_eq!(status, StatusCode::CREATED);
-
-  let token = login_test_user(&app, test_email, "password123").await;
-
   let payload = json!({
       "code": test_code,
       "name": "Original User",
@@ -237,7 +123,8 @@ async fn test_create_contact_duplicate_code() {
     .method(http::Method::POST)
     .uri("/api/v1/contacts")
     .header(http::header::CONTENT_TYPE, "application/json")
-    .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+    .header(http::header::AUTHORIZATION, user.bearer())
+    .header("X-Workspace-ID", workspace.id.to_string())
     .body(Body::from(serde_json::to_vec(&payload).unwrap()))
     .unwrap();
 
@@ -249,11 +136,12 @@ async fn test_create_contact_duplicate_code() {
     .method(http::Method::POST)
     .uri("/api/v1/contacts")
     .header(http::header::CONTENT_TYPE, "application/json")
-    .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+    .header(http::header::AUTHORIZATION, user.bearer())
+    .header("X-Workspace-ID", workspace.id.to_string())
     .body(Body::from(serde_json::to_vec(&payload).unwrap()))
     .unwrap();
 
-  let response2 = app.oneshot(request2).await.unwrap();
+  let response2 = app.clone().oneshot(request2).await.unwrap();
   assert_eq!(
     response2.status(),
     StatusCode::UNPROCESSABLE_ENTITY,
@@ -265,10 +153,125 @@ async fn test_create_contact_duplicate_code() {
 
   assert_eq!(body["error"], "VALIDATION_FAILED");
   // Check the actual response structure for duplicate code
-  assert_eq!(body["details"]["code"][0]["message"], "Contact code already exists");
+  assert_eq!(body["details"]["code"][0]["message"], "Contact code already exists in this workspace");
   assert_eq!(body["details"]["code"][0]["code"], "DUPLICATE_CODE");
+}
+
+#[tokio::test]
+async fn test_list_products_low_stock_filter() {
+  let app = TestApp::isolated().await;
+
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let low = ProductFactory::new().with_low_stock().create(&app, &workspace, &user).await;
+  ProductFactory::new().create(&app, &workspace, &user).await;
+
+  let request = Request::builder()
+    .method(http::Method::GET)
+    .uri("/api/v1/products?low_stock=true")
+    .header(http::header::AUTHORIZATION, user.bearer())
+    .header("X-Workspace-ID", workspace.id.to_string())
+    .body(Body::empty())
+    .unwrap();
+
+  let response = app.clone().oneshot(request).await.unwrap();
+  assert_eq!(response.status(), StatusCode::OK);
+
+  let body = response.into_body().collect().await.unwrap().to_bytes();
+  let body: Value = serde_json::from_slice(&body).unwrap();
+  assert_eq!(body["results"]["pagination"]["total"], 1);
+  assert_eq!(body["results"]["list"][0]["id"], low.id.to_string());
+}
+
+#[tokio::test]
+async fn test_rolled_back_executor_leaves_no_rows() {
+  let pool = common::test_pool().await;
+  let db = DbExecutor::rolled_back(&pool).await.expect("Failed to open test transaction");
+  let test_code = "TEST_ROLLBACK";
+
+  let (user_id, workspace_id): (uuid::Uuid, uuid::Uuid) = {
+    let mut conn = db.acquire().await.unwrap();
+    let user_id =
+      sqlx::query_scalar("INSERT INTO users (username, email, password_hash) VALUES ('rollback_user', 'rollback@example.com', 'x') RETURNING id")
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+    let workspace_id = sqlx::query_scalar("INSERT INTO workspaces (name, owner_id, created_by) VALUES ('Rollback workspace', $1, $1) RETURNING id")
+      .bind(user_id)
+      .fetch_one(&mut *conn)
+      .await
+      .unwrap();
+    (user_id, workspace_id)
+  };
+
+  // Repository calls made through the executor see each other's uncommitted rows
+  let repository = SqlxContactRepository::new(db.clone());
+  let contact = repository
+    .create_by_workspace(
+      CreateContactRequest {
+        code: test_code.to_string(),
+        name: "Rollback Contact".to_string(),
+        email: "rollback.contact@example.com".to_string(),
+        position: None,
+        contact_type: "customer".to_string(),
+        address: None,
+        tax_id: None,
+        bank_account: None,
+      },
+      workspace_id,
+      user_id,
+    )
+    .await
+    .unwrap();
+  let found = repository.find_by_id_and_workspace(contact.id, workspace_id, user_id).await.unwrap();
+  assert!(found.is_some(), "Contact should be visible inside the transaction");
+
+  // Nothing is committed once the executor is dropped
+  drop(repository);
+  drop(db);
+  let committed: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM contacts WHERE id = $1")
+    .bind(contact.id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+  assert_eq!(committed, 0, "Contact should be rolled back");
+}
+
+#[tokio::test]
+async fn test_state_builder_applies_injected_config() {
+  let pool = common::test_pool().await;
+  let mut config = AppConfig::default();
+  config.body_limit.default_bytes = 16;
+  let app = app(AppState::builder(pool, "builder-test-secret").with_config(config).build());
+
+  let payload = json!({ "email": "builder@example.com", "password": "password123" });
+  let request = Request::builder()
+    .method(http::Method::POST)
+    .uri("/api/v1/auth/login")
+    .header(http::header::CONTENT_TYPE, "application/json")
+    .body(Body::from(serde_json::to_string(&payload).unwrap()))
+    .unwrap();
+
+  let response = app.clone().oneshot(request).await.unwrap();
+
+  // The injected body limit applies, not the one from the environment
+  assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+  let body = response.into_body().collect().await.unwrap().to_bytes();
+  let body: Value = serde_json::from_slice(&body).unwrap();
+  assert!(body["message"].as_str().unwrap().contains("maximum allowed size of 16 bytes"));
+}
+
+#[test]
+fn test_app_error_is_a_std_error() {
+  let err = AppError::from(AuthError::ExpiredToken);
+  assert_eq!(err.to_string(), "Authentication error: Authentication token has expired");
+  assert_eq!(err.source().unwrap().to_string(), "Authentication token has expired");
+
+  // Boxing and unboxing keeps the variant, and with it the HTTP mapping
+  let boxed: Box<dyn Error + Send + Sync> = Box::new(err);
+  let err = *boxed.downcast::<AppError>().unwrap();
+  assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
 
-  // Clean up the created contact and user
-  clean_up_contact(&pool, test_code).await;
-  cleanup_test_user(&pool, test_email).await;
+  let err = AppError::from(Box::<dyn Error + Send + Sync>::from("layer failed"));
+  assert_eq!(err.into_response().status(), StatusCode::INTERNAL_SERVER_ERROR);
 }
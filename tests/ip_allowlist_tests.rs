@@ -0,0 +1,126 @@
+//! Workspaces restricted to the IP ranges set by their admins.
+
+use std::{net::SocketAddr, time::Duration};
+
+use axum::{
+  extract::ConnectInfo,
+  http::{self, StatusCode},
+};
+use myapp_api_rust::modules::datastores::workspaces::WorkspaceRole;
+use serde_json::{Value, json};
+use tokio::time::timeout;
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{TestUser, UserFactory, WorkspaceFactory},
+  request,
+};
+
+mod common;
+
+/// `TestApp::call` from the client address `ip`.
+async fn call(
+  app: &TestApp,
+  method: http::Method,
+  uri: &str,
+  user: &TestUser,
+  workspace_id: Uuid,
+  ip: &str,
+  body: Option<Value>,
+) -> (StatusCode, Value) {
+  let mut request = request(method, uri, user, workspace_id, body);
+  request.extensions_mut().insert(ConnectInfo(SocketAddr::new(ip.parse().unwrap(), 40_000)));
+  let (status, _, body) = app.send(request).await;
+  (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_requests_from_outside_the_allowlist_are_rejected() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+  let settings_uri = format!("/api/v1/workspaces/{}/settings", workspace.id);
+  let put = http::Method::PUT;
+
+  let ranges = json!({ "allowed_ip_ranges": ["203.0.113.0/24", "2001:db8::/32"] });
+  let (status, _) = call(
+    &app,
+    put.clone(),
+    &settings_uri,
+    &member,
+    workspace.id,
+    "203.0.113.7",
+    Some(ranges.clone()),
+  )
+  .await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "only admins manage the settings");
+
+  let (status, body) = call(
+    &app,
+    put.clone(),
+    &settings_uri,
+    &admin,
+    workspace.id,
+    "198.51.100.1",
+    Some(ranges.clone()),
+  )
+  .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "admins cannot lock themselves out: {body}");
+
+  let invalid = json!({ "allowed_ip_ranges": ["203.0.113.0/33"] });
+  let (status, _) = call(&app, put.clone(), &settings_uri, &admin, workspace.id, "203.0.113.7", Some(invalid)).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+  let (status, body) = call(&app, put.clone(), &settings_uri, &admin, workspace.id, "203.0.113.7", Some(ranges)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["allowed_ip_ranges"], json!(["203.0.113.0/24", "2001:db8::/32"]));
+
+  for ip in ["203.0.113.200", "2001:db8:1::5", "::ffff:203.0.113.9"] {
+    let (status, body) = call(&app, http::Method::GET, "/api/v1/contacts", &member, workspace.id, ip, None).await;
+    assert_eq!(status, StatusCode::OK, "{ip}: {body}");
+  }
+
+  let (status, body) = call(&app, http::Method::GET, "/api/v1/contacts", &member, workspace.id, "198.51.100.1", None).await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+  assert_eq!(body["error"], "IP_NOT_ALLOWED");
+  assert_eq!(body["details"]["ip"], "198.51.100.1");
+  let (status, _) = call(&app, http::Method::GET, &settings_uri, &admin, workspace.id, "198.51.100.1", None).await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "workspace management is restricted too");
+
+  // Other workspaces of the same user are not affected
+  let other = WorkspaceFactory::new().create(&app, &member).await;
+  let (status, _) = call(&app, http::Method::GET, "/api/v1/contacts", &member, other.id, "198.51.100.1", None).await;
+  assert_eq!(status, StatusCode::OK);
+
+  let clear = json!({ "allowed_ip_ranges": [] });
+  let (status, _) = call(&app, put, &settings_uri, &admin, workspace.id, "203.0.113.7", Some(clear)).await;
+  assert_eq!(status, StatusCode::OK);
+  let (status, _) = call(&app, http::Method::GET, "/api/v1/contacts", &member, workspace.id, "198.51.100.1", None).await;
+  assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_event_streams_from_outside_the_allowlist_are_rejected() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &admin).await;
+  let settings_uri = format!("/api/v1/workspaces/{}/settings", workspace.id);
+  let ranges = json!({ "allowed_ip_ranges": ["203.0.113.0/24"] });
+  let (status, body) = call(&app, http::Method::PUT, &settings_uri, &admin, workspace.id, "203.0.113.7", Some(ranges)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  // Streams authenticate outside of the HTTP middleware, with the token in the query if need be.
+  // An accepted stream never ends, hence the timeout.
+  let query_uri = format!("/api/v1/events?token={}&workspace_id={}", admin.token, workspace.id);
+  for uri in ["/api/v1/events", query_uri.as_str()] {
+    let stream = call(&app, http::Method::GET, uri, &admin, workspace.id, "198.51.100.1", None);
+    let (status, body) = timeout(Duration::from_secs(10), stream).await.expect("stream accepted");
+    assert_eq!(status, StatusCode::FORBIDDEN, "{uri}: {body}");
+    assert_eq!(body["error"], "IP_NOT_ALLOWED", "{uri}: {body}");
+  }
+  let stream = app.send(request(http::Method::GET, "/api/v1/events", &admin, workspace.id, None));
+  let (status, _, body) = timeout(Duration::from_secs(10), stream).await.expect("stream accepted");
+  assert_eq!(status, StatusCode::FORBIDDEN, "unknown addresses are rejected too: {body:?}");
+}
@@ -0,0 +1,131 @@
+//! The caller's limits: rate limit budgets, the monthly request quota and the records allowed by
+//! the plan, which creates and imports are held to.
+
+use std::net::SocketAddr;
+
+use axum::{
+  body::Body,
+  extract::ConnectInfo,
+  http::{self, Request, StatusCode},
+};
+use myapp_api_rust::config::AppConfig;
+use serde_json::{Value, json};
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_limits_report_budgets_and_quotas() {
+  let mut config = AppConfig::from_env();
+  config.rate_limit.enabled = true;
+  config.rate_limit.read_max = 10;
+  config.rate_limit.write_max = 5;
+  config.usage.enabled = true;
+  config.usage.free_monthly_requests = 100;
+  config.usage.free_max_records = 3;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  ProductFactory::new().create(&app, &workspace, &user).await;
+
+  app.call(http::Method::GET, "/api/v1/products", &user, Some(workspace.id), None).await;
+  let (status, body) = app.call(http::Method::GET, "/api/v1/limits", &user, Some(workspace.id), None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let limits = &body["results"];
+  // Both reads are counted, this one included
+  assert_eq!(limits["rate_limit"]["read"]["limit"], 10);
+  assert_eq!(limits["rate_limit"]["read"]["remaining"], 8);
+  assert_eq!(limits["rate_limit"]["write"]["remaining"], 5);
+  let quotas = &limits["quotas"];
+  assert_eq!(quotas["workspace_id"], workspace.id.to_string());
+  assert_eq!(quotas["plan"], "free");
+  assert_eq!(quotas["requests"]["limit"], 100);
+  assert_eq!(quotas["requests"]["used"], 1, "only the requests before this one");
+  assert_eq!(quotas["requests"]["remaining"], 99);
+  assert_eq!(quotas["records"], json!({ "limit": 3, "used": 1, "remaining": 2 }));
+
+  // Budgets are per workspace; outside one there are no plan quotas
+  let (status, body) = app.call(http::Method::GET, "/api/v1/limits", &user, None, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["rate_limit"]["read"]["remaining"], 9);
+  assert_eq!(body["results"]["quotas"], Value::Null);
+}
+
+/// A login attempt from `peer`, claiming to be forwarded for `forwarded_for`.
+async fn anonymous_login(app: &TestApp, peer: &str, forwarded_for: &str) -> StatusCode {
+  let request = Request::builder()
+    .method(http::Method::POST)
+    .uri("/api/v1/auth/login")
+    .header("X-Forwarded-For", forwarded_for)
+    .header(http::header::CONTENT_TYPE, "application/json")
+    .extension(ConnectInfo(SocketAddr::new(peer.parse().unwrap(), 40_000)))
+    .body(Body::from(
+      json!({ "email": "nobody@example.com", "password": "password123" }).to_string(),
+    ))
+    .unwrap();
+  app.send(request).await.0
+}
+
+#[tokio::test]
+async fn test_anonymous_budget_ignores_forwarding_headers_from_untrusted_peers() {
+  let mut config = AppConfig::from_env();
+  config.rate_limit.enabled = true;
+  config.rate_limit.anonymous_max = 2;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+
+  for hop in ["198.51.100.1", "198.51.100.2"] {
+    assert_ne!(anonymous_login(&app, "203.0.113.7", hop).await, StatusCode::TOO_MANY_REQUESTS);
+  }
+  // A new address in the header is not a new client
+  assert_eq!(anonymous_login(&app, "203.0.113.7", "198.51.100.3").await, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_anonymous_budget_follows_the_client_behind_trusted_proxies() {
+  let mut config = AppConfig::from_env();
+  config.rate_limit.enabled = true;
+  config.rate_limit.anonymous_max = 1;
+  config.server.trusted_proxies = vec!["10.0.0.0/8".parse().unwrap()];
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+
+  assert_ne!(anonymous_login(&app, "10.0.0.2", "198.51.100.1").await, StatusCode::TOO_MANY_REQUESTS);
+  // Only the hops appended by trusted proxies are believed, not those sent by the client
+  assert_eq!(
+    anonymous_login(&app, "10.0.0.3", "192.0.2.9, 198.51.100.1, 10.0.0.4").await,
+    StatusCode::TOO_MANY_REQUESTS
+  );
+  assert_ne!(anonymous_login(&app, "10.0.0.2", "198.51.100.2").await, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_creates_are_held_to_the_records_of_the_plan() {
+  let mut config = AppConfig::from_env();
+  config.usage.free_max_records = 2;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  ProductFactory::new().create(&app, &workspace, &user).await;
+
+  let contact =
+    |code: &str| json!({ "code": code, "name": "Limited", "email": format!("{}@example.com", code.to_lowercase()), "contact_type": "customer" });
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", &user, Some(workspace.id), Some(contact("LIM-1")))
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/limits", &user, Some(workspace.id), None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["quotas"]["records"]["remaining"], 0);
+
+  // One more would take the workspace past its plan
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", &user, Some(workspace.id), Some(contact("LIM-2")))
+    .await;
+  assert_eq!(status, StatusCode::PAYMENT_REQUIRED, "{body}");
+  assert_eq!(body["error"], "RECORD_QUOTA_EXCEEDED");
+  assert_eq!(body["details"]["limit"], 2);
+}
@@ -0,0 +1,78 @@
+//! Per-workspace defaults for the page size and sort order of lists.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::modules::datastores::workspaces::WorkspaceRole;
+use serde_json::{Value, json};
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+fn names(body: &Value) -> Vec<&str> {
+  body["results"]["list"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|p| p["name"].as_str().unwrap())
+    .collect()
+}
+
+#[tokio::test]
+async fn test_lists_use_the_workspace_defaults() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &admin).await;
+  let other = WorkspaceFactory::new().create(&app, &admin).await;
+  for name in ["Cable", "Anchor", "Drill", "Bolt"] {
+    ProductFactory::new().name(name).create(&app, &workspace, &admin).await;
+    ProductFactory::new().name(name).create(&app, &other, &admin).await;
+  }
+  let settings_uri = format!("/api/v1/workspaces/{}/settings", workspace.id);
+  let defaults = json!({ "list_defaults": { "page_size": 2, "max_page_size": 3, "sort_by": "name", "sort_order": "asc" } });
+  let (status, body) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(defaults)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["list_defaults"]["page_size"], 2);
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/products", &admin, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(names(&body), ["Anchor", "Bolt"]);
+  assert_eq!(body["results"]["pagination"]["limit"], 2);
+  assert_eq!(body["results"]["pagination"]["total_pages"], 2);
+
+  // Parameters sent by the client still win, within the cap
+  let (status, body) = app
+    .call(http::Method::GET, "/api/v1/products?limit=3&sort_order=desc", &admin, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(names(&body), ["Drill", "Cable", "Bolt"]);
+  let (status, _) = app.call(http::Method::GET, "/api/v1/products?limit=4", &admin, workspace.id, None).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+  // Other workspaces keep the built-in defaults
+  let (status, body) = app.call(http::Method::GET, "/api/v1/products", &admin, other.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(names(&body).len(), 4);
+}
+
+#[tokio::test]
+async fn test_invalid_defaults_are_rejected() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+  let settings_uri = format!("/api/v1/workspaces/{}/settings", workspace.id);
+  let put = http::Method::PUT;
+
+  let above_cap = json!({ "list_defaults": { "page_size": 50, "max_page_size": 20 } });
+  let (status, _) = app.call(put.clone(), &settings_uri, &admin, workspace.id, Some(above_cap)).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let unknown_column = json!({ "list_defaults": { "sort_by": "password" } });
+  let (status, _) = app.call(put.clone(), &settings_uri, &admin, workspace.id, Some(unknown_column)).await;
+  assert!(status.is_client_error(), "{status}");
+  let valid = json!({ "list_defaults": { "page_size": 5 } });
+  let (status, _) = app.call(put, &settings_uri, &member, workspace.id, Some(valid)).await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+}
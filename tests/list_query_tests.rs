@@ -0,0 +1,105 @@
+//! Hostile list query strings, run through what the contact and product list endpoints do with
+//! them: user input ends up in the bound values, never in the SQL text. The `list_query` fuzz
+//! target checks the same properties on generated input.
+
+use std::collections::BTreeSet;
+
+use axum::{extract::Query, http::Uri};
+use myapp_api_rust::{
+  helper::ValidatedQuery,
+  modules::datastores::{
+    contacts::{
+      contact_models::{ContactFilters, GetContactsQuery},
+      contact_repository::SqlxContactRepository,
+    },
+    products::{
+      product_models::{GetProductsQuery, ProductFilters},
+      product_repository::SqlxProductRepository,
+    },
+  },
+  utils::{
+    paginated_repository::{BoundQuery, PaginatedRepository},
+    search_index::prefix_tsquery,
+  },
+};
+use uuid::Uuid;
+
+const HOSTILE: &[&str] = &[
+  "search=x%27%3B%20DROP%20TABLE%20contacts%3B--",
+  "search=%27%3A*%20%7C%20!a",
+  "search=back%5Cslash%27%27",
+  "code=%27%20OR%201%3D1%20--",
+  "email=%25%27%20OR%20%27%27%3D%27",
+  "sku=%27)%3B%20DROP%20TABLE%20products%3B--&barcode=%27&base_unit=1%3D1",
+  "sort_by=name%3B%20DROP%20TABLE%20contacts&sort_order=desc",
+  "sort_by=created_at&sort_order=asc%27%3B%20DROP",
+  "include_types=customer%27,%27supplier&exclude_types=%27%3B%20DROP",
+  "include_ids=00000000-0000-0000-0000-000000000001,%27%20OR%201%3D1",
+  "include_categories=%27%20OR%201%3D1,00000000-0000-0000-0000-000000000001&exclude_suppliers=%27%3B%20DROP",
+];
+
+/// Deserializes and validates the query like `ValidatedQuery` does, `None` when it is rejected.
+fn parse<T>(query: &str) -> Option<T>
+where
+  T: serde::de::DeserializeOwned + validator::Validate,
+{
+  let uri: Uri = format!("/?{}", query).parse().unwrap();
+  let Query(query) = Query::<T>::try_from_uri(&uri).ok()?;
+  ValidatedQuery::new(query).ok().map(|ValidatedQuery(query)| query)
+}
+
+/// The SQL text holds no literal but the text search configuration and no SQL from the input, and
+/// one `$n` placeholder per bound value.
+fn assert_bound(query: &str, (sql, values): &BoundQuery) {
+  assert!(!sql.replace("'simple'", "").contains('\''), "{query}: {sql}");
+  assert!(!sql.contains("DROP") && !sql.contains("1=1"), "{query}: {sql}");
+  let placeholders: BTreeSet<usize> = sql
+    .split('$')
+    .skip(1)
+    .filter_map(|rest| rest.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok())
+    .collect();
+  assert!(placeholders.into_iter().eq(1..=values.0.0.len()), "{query}: {sql}");
+}
+
+#[test]
+fn test_hostile_query_strings_stay_in_the_bound_values() {
+  let (workspace_id, user_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+  for query in HOSTILE {
+    let contacts = parse::<GetContactsQuery>(query).map(ContactFilters::from);
+    if let Some(filters) = &contacts {
+      let (page, count) = SqlxContactRepository::build_page_query(workspace_id, user_id, filters, 20, 40);
+      assert_bound(query, &page);
+      assert_bound(query, &count);
+      assert_bound(query, &SqlxContactRepository::build_stream_query(workspace_id, user_id, filters));
+    }
+
+    let products = parse::<GetProductsQuery>(query).map(ProductFilters::from);
+    if let Some(filters) = &products {
+      let (page, count) = SqlxProductRepository::build_page_query(workspace_id, user_id, filters, 20, 40);
+      assert_bound(query, &page);
+      assert_bound(query, &count);
+      assert_bound(query, &SqlxProductRepository::build_stream_query(workspace_id, user_id, filters));
+    }
+
+    assert!(contacts.is_some() || products.is_some(), "{query} was rejected by both lists");
+  }
+}
+
+#[test]
+fn test_out_of_range_pages_are_rejected() {
+  for query in ["limit=0", "limit=101", "page=0", "page=-1", "limit=ten"] {
+    assert!(parse::<GetContactsQuery>(query).is_none(), "{query}");
+    assert!(parse::<GetProductsQuery>(query).is_none(), "{query}");
+  }
+  assert!(parse::<GetContactsQuery>("page=2&limit=100").is_some());
+}
+
+#[test]
+fn test_search_words_become_quoted_prefix_terms() {
+  assert_eq!(prefix_tsquery("o'neil hammer").as_deref(), Some("'o''neil':* & 'hammer':*"));
+  assert_eq!(prefix_tsquery(r"a\b").as_deref(), Some(r"'a\\b':*"));
+  // tsquery operators are searched for, not applied
+  assert_eq!(prefix_tsquery("a | !b").as_deref(), Some("'a':* & '|':* & '!b':*"));
+  assert_eq!(prefix_tsquery(" \t "), None);
+}
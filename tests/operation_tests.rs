@@ -0,0 +1,127 @@
+//! Long requests running in the background as operations: imports and exports.
+
+use std::time::Duration;
+
+use axum::{
+  body::Body,
+  http::{self, Request, StatusCode},
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, TestUser, UserFactory, WorkspaceFactory},
+};
+use myapp_api_rust::modules::datastores::workspaces::WorkspaceRole;
+
+mod common;
+
+/// Sends `request`, returning the status, the `Location` header and the JSON body.
+async fn send(app: &TestApp, request: Request<Body>) -> (StatusCode, Option<String>, Value) {
+  let (status, headers, body) = app.send(request).await;
+  let location = headers.get(http::header::LOCATION).map(|location| location.to_str().unwrap().to_string());
+  (status, location, serde_json::from_slice(&body).unwrap())
+}
+
+fn request(method: http::Method, uri: &str, user: &TestUser, workspace_id: Uuid) -> http::request::Builder {
+  Request::builder()
+    .method(method)
+    .uri(uri)
+    .header(http::header::AUTHORIZATION, user.bearer())
+    .header("X-Workspace-ID", workspace_id.to_string())
+}
+
+/// Polls the operation at `location` until it is no longer running.
+async fn wait_for(app: &TestApp, location: &str, user: &TestUser, workspace_id: Uuid) -> Value {
+  for _ in 0..100 {
+    let (status, body) = app.call(http::Method::GET, location, user, workspace_id, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    if body["results"]["status"] != "running" {
+      return body["results"].clone();
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+  }
+  panic!("operation {location} is still running");
+}
+
+#[tokio::test]
+async fn test_imports_run_in_the_background_on_request() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let uri = "/api/v1/import/generic?entity=products";
+  let import = |csv: &str| {
+    request(http::Method::POST, uri, &user, workspace.id)
+      .header(http::header::CONTENT_TYPE, "text/csv")
+      .header("Prefer", "respond-async")
+      .body(Body::from(csv.to_string()))
+      .unwrap()
+  };
+
+  // The file is checked before the request is accepted
+  let (status, _, body) = send(&app, import("code,name,base_unit,selling_price\nP-1,,pcs,abc\n")).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+  let csv = "code,name,base_unit,selling_price,unit_cost\nP-1,Drill,pcs,10,5\nP-2,Saw,pcs,20,8\n";
+  let (status, location, body) = send(&app, import(csv)).await;
+  assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+  let location = location.unwrap();
+  assert_eq!(location, format!("/api/v1/operations/{}", body["results"]["id"].as_str().unwrap()));
+  assert_eq!(body["results"]["kind"], "products.import");
+
+  let operation = wait_for(&app, &location, &user, workspace.id).await;
+  assert_eq!(operation["status"], "succeeded", "{operation}");
+  assert_eq!(operation["progress"], 100);
+  let result_location = operation["result_location"].as_str().unwrap();
+  let (status, body) = app.call(http::Method::GET, result_location, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["created"], 2);
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/products?status=draft", &user, workspace.id, None)
+    .await;
+  assert_eq!(body["results"]["list"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_exports_are_collected_into_the_operation_result() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+  for _ in 0..3 {
+    ProductFactory::new().create(&app, &workspace, &admin).await;
+  }
+
+  let export = request(http::Method::GET, "/api/v1/products", &admin, workspace.id)
+    .header(http::header::ACCEPT, "application/x-ndjson")
+    .header("Prefer", "respond-async")
+    .body(Body::empty())
+    .unwrap();
+  let (status, location, body) = send(&app, export).await;
+  assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+  let location = location.unwrap();
+
+  // Operations are only seen by the member who started them
+  let (status, body) = app.call(http::Method::GET, &location, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+
+  let operation = wait_for(&app, &location, &admin, workspace.id).await;
+  assert_eq!(operation["status"], "succeeded", "{operation}");
+  let (status, body) = app
+    .call(http::Method::GET, &format!("{location}/result"), &admin, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"].as_array().unwrap().len(), 3);
+
+  let (status, body) = app
+    .call(
+      http::Method::GET,
+      &format!("/api/v1/operations/{}/result", Uuid::new_v4()),
+      &admin,
+      workspace.id,
+      None,
+    )
+    .await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+}
@@ -0,0 +1,303 @@
+//! Organizations: access to their workspaces through the membership, consolidated billing and
+//! the organization switcher.
+
+use axum::http::{self, StatusCode};
+use chrono::NaiveDate;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, TestUser, UserFactory, WorkspaceFactory},
+  test_id,
+};
+
+mod common;
+
+/// Creates an organization owned by `owner` through the API and returns its ID.
+async fn create_organization(app: &TestApp, owner: &TestUser, name: &str) -> Uuid {
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/organizations", owner, None, Some(json!({ "name": name })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  body["results"]["id"].as_str().unwrap().parse().unwrap()
+}
+
+async fn attach(app: &TestApp, organization_id: Uuid, workspace_id: Uuid, user: &TestUser) -> (StatusCode, Value) {
+  app
+    .call(
+      http::Method::POST,
+      &format!("/api/v1/organizations/{}/workspaces", organization_id),
+      user,
+      None,
+      Some(json!({ "workspace_id": workspace_id })),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_organization_members_get_a_role_in_every_workspace() {
+  let app = TestApp::isolated().await;
+  let owner = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let branch = WorkspaceFactory::new().name("Branch").create(&app, &owner).await;
+  ProductFactory::new().create(&app, &branch, &owner).await;
+  let organization_id = create_organization(&app, &owner, "Acme").await;
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/products", &member, Some(branch.id), None).await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+
+  let (status, body) = attach(&app, organization_id, branch.id, &owner).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      &format!("/api/v1/organizations/{}/members", organization_id),
+      &owner,
+      None,
+      Some(json!({ "user_id": member.id(), "role": "Member", "workspace_role": "Member" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/products", &member, Some(branch.id), None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["list"].as_array().unwrap().len(), 1, "{body}");
+  let (_, body) = app.call(http::Method::GET, "/api/v1/workspaces", &member, None, None).await;
+  let workspace = body["results"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .find(|workspace| workspace["id"] == branch.id.to_string())
+    .unwrap_or_else(|| panic!("organization workspace missing from {body}"));
+  assert_eq!(workspace["user_role"], "Member");
+
+  // Members cannot manage the organization
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &format!("/api/v1/organizations/{}", organization_id),
+      &member,
+      None,
+      Some(json!({ "name": "Renamed" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+
+  // Leaving the organization revokes the access right away
+  let (status, body) = app
+    .call(
+      http::Method::DELETE,
+      &format!("/api/v1/organizations/{}/members/{}", organization_id, member.id()),
+      &member,
+      None,
+      None,
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = app.call(http::Method::GET, "/api/v1/products", &member, Some(branch.id), None).await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+}
+
+/// The rows of `table` in `workspace_id` that `user` sees through row level security, queried
+/// as a role that does not own the tables.
+async fn visible_rows(app: &TestApp, table: &str, user: &TestUser, workspace_id: Uuid) -> i64 {
+  let mut conn = app.db.acquire().await.unwrap();
+  let role = format!("rls_probe_{}", test_id());
+  sqlx::query(&format!("CREATE ROLE {role} NOLOGIN")).execute(&mut *conn).await.unwrap();
+  sqlx::query(&format!("GRANT SELECT ON {table} TO {role}"))
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+  sqlx::query(&format!("SET LOCAL ROLE {role}")).execute(&mut *conn).await.unwrap();
+  sqlx::query("SELECT set_config('app.current_user_id', $1, true), set_config('app.current_workspace_id', $2, true)")
+    .bind(user.id().to_string())
+    .bind(workspace_id.to_string())
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+  let count = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table} WHERE workspace_id = $1"))
+    .bind(workspace_id)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+  sqlx::query("RESET ROLE").execute(&mut *conn).await.unwrap();
+  count
+}
+
+#[tokio::test]
+async fn test_row_level_security_follows_organization_membership() {
+  let app = TestApp::isolated().await;
+  let owner = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let branch = WorkspaceFactory::new().name("Branch").create(&app, &owner).await;
+  ProductFactory::new().create(&app, &branch, &owner).await;
+  let organization_id = create_organization(&app, &owner, "Acme").await;
+  attach(&app, organization_id, branch.id, &owner).await;
+
+  assert_eq!(visible_rows(&app, "products", &owner, branch.id).await, 1);
+  assert_eq!(visible_rows(&app, "products", &member, branch.id).await, 0);
+
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      &format!("/api/v1/organizations/{}/members", organization_id),
+      &owner,
+      None,
+      Some(json!({ "user_id": member.id(), "role": "Member", "workspace_role": "Viewer" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(visible_rows(&app, "products", &member, branch.id).await, 1);
+}
+
+#[tokio::test]
+async fn test_only_workspace_owners_add_their_workspaces() {
+  let app = TestApp::isolated().await;
+  let owner = UserFactory::new().create(&app).await;
+  let other = UserFactory::new().create(&app).await;
+  let foreign = WorkspaceFactory::new().create(&app, &other).await;
+  let own = WorkspaceFactory::new().create(&app, &owner).await;
+  let organization_id = create_organization(&app, &owner, "Acme").await;
+  let other_organization_id = create_organization(&app, &other, "Globex").await;
+
+  let (status, body) = attach(&app, organization_id, foreign.id, &owner).await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+
+  let (status, body) = attach(&app, organization_id, own.id, &owner).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      &format!("/api/v1/organizations/{}/members", other_organization_id),
+      &other,
+      None,
+      Some(json!({ "user_id": owner.id(), "role": "Admin" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = attach(&app, other_organization_id, own.id, &owner).await;
+  assert_eq!(status, StatusCode::CONFLICT, "a workspace belongs to one organization: {body}");
+
+  // Non-members cannot see the organization
+  let (status, body) = app
+    .call(
+      http::Method::GET,
+      &format!("/api/v1/organizations/{}", organization_id),
+      &other,
+      None,
+      None,
+    )
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+}
+
+#[tokio::test]
+async fn test_billing_workspace_pays_for_the_organization() {
+  let app = TestApp::isolated().await;
+  let owner = UserFactory::new().create(&app).await;
+  let head_office = WorkspaceFactory::new().name("Head office").create(&app, &owner).await;
+  let branch = WorkspaceFactory::new().name("Branch").create(&app, &owner).await;
+  let organization_id = create_organization(&app, &owner, "Acme").await;
+  attach(&app, organization_id, head_office.id, &owner).await;
+  attach(&app, organization_id, branch.id, &owner).await;
+  {
+    let mut conn = app.db.acquire().await.unwrap();
+    sqlx::query("INSERT INTO workspace_subscriptions (workspace_id, stripe_customer_id, status) VALUES ($1, $2, 'active')")
+      .bind(head_office.id)
+      .bind(format!("cus_{}", head_office.id.simple()))
+      .execute(&mut *conn)
+      .await
+      .unwrap();
+  }
+  let month = NaiveDate::from_ymd_opt(2025, 11, 1).unwrap();
+  let billing_uri = format!("/api/v1/organizations/{}/billing", organization_id);
+
+  assert!(!app.state.usage_repository.monthly_usage(branch.id, month).await.unwrap().paid);
+
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &billing_uri,
+      &owner,
+      None,
+      Some(json!({ "workspace_id": Uuid::new_v4() })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &billing_uri,
+      &owner,
+      None,
+      Some(json!({ "workspace_id": head_office.id })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  assert!(app.state.usage_repository.monthly_usage(branch.id, month).await.unwrap().paid);
+  let (status, body) = app.call(http::Method::GET, &billing_uri, &owner, None, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["billing_workspace_id"], head_office.id.to_string());
+  let workspaces = body["results"]["workspaces"].as_array().unwrap();
+  assert_eq!(workspaces[0]["workspace_id"], branch.id.to_string());
+  assert_eq!(workspaces[0]["paid"], true);
+  assert_eq!(workspaces[0]["covered_by_organization"], true);
+  assert_eq!(workspaces[1]["workspace_id"], head_office.id.to_string());
+  assert_eq!(workspaces[1]["covered_by_organization"], false);
+
+  // A workspace leaving the organization no longer shares its subscription
+  let (status, body) = app
+    .call(
+      http::Method::DELETE,
+      &format!("/api/v1/organizations/{}/workspaces/{}", organization_id, branch.id),
+      &owner,
+      None,
+      None,
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert!(!app.state.usage_repository.monthly_usage(branch.id, month).await.unwrap().paid);
+}
+
+#[tokio::test]
+async fn test_switching_organization_issues_a_token_for_one_of_its_workspaces() {
+  let app = TestApp::isolated().await;
+  let owner = UserFactory::new().create(&app).await;
+  let stranger = UserFactory::new().create(&app).await;
+  let second = WorkspaceFactory::new().name("B branch").create(&app, &owner).await;
+  let first = WorkspaceFactory::new().name("A branch").create(&app, &owner).await;
+  let outside = WorkspaceFactory::new().create(&app, &owner).await;
+  let organization_id = create_organization(&app, &owner, "Acme").await;
+  attach(&app, organization_id, second.id, &owner).await;
+  attach(&app, organization_id, first.id, &owner).await;
+  let switch_uri = format!("/api/v1/organizations/{}/switch", organization_id);
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/organizations", &owner, None, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"][0]["id"], organization_id.to_string());
+  assert_eq!(body["results"][0]["role"], "Admin");
+  assert_eq!(body["results"][0]["workspace_count"], 2);
+
+  let (status, body) = app.call(http::Method::POST, &switch_uri, &owner, None, Some(json!({}))).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["workspace_id"], first.id.to_string(), "first workspace by name");
+  assert_eq!(body["results"]["role"], "Admin");
+  assert_eq!(body["results"]["workspaces"].as_array().unwrap().len(), 2);
+  assert!(body["results"]["token"].as_str().is_some_and(|token| !token.is_empty()));
+
+  let (status, body) = app
+    .call(http::Method::POST, &switch_uri, &owner, None, Some(json!({ "workspace_id": second.id })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["workspace_id"], second.id.to_string());
+
+  let (status, body) = app
+    .call(http::Method::POST, &switch_uri, &owner, None, Some(json!({ "workspace_id": outside.id })))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+  let (status, body) = app.call(http::Method::POST, &switch_uri, &stranger, None, Some(json!({}))).await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+}
@@ -0,0 +1,69 @@
+//! The overview of all the workspaces of a user.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::modules::datastores::workspaces::WorkspaceRole;
+use serde_json::Value;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, TestUser, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+async fn get_overview(app: &TestApp, user: &TestUser) -> (StatusCode, Value) {
+  app.call(http::Method::GET, "/api/v1/overview", user, None, None).await
+}
+
+#[tokio::test]
+async fn test_overview_covers_every_workspace_of_the_caller() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let owner = UserFactory::new().create(&app).await;
+  let stranger = UserFactory::new().create(&app).await;
+  let own = WorkspaceFactory::new().create(&app, &user).await;
+  let shared = WorkspaceFactory::new().member(&user, WorkspaceRole::Member).create(&app, &owner).await;
+  let viewed = WorkspaceFactory::new().member(&user, WorkspaceRole::Viewer).create(&app, &owner).await;
+  WorkspaceFactory::new().create(&app, &stranger).await;
+
+  ProductFactory::new().with_low_stock().create(&app, &own, &user).await;
+  ProductFactory::new().create(&app, &own, &user).await;
+  ProductFactory::new().with_low_stock().create(&app, &shared, &owner).await;
+  ProductFactory::new().with_low_stock().create(&app, &shared, &owner).await;
+  ProductFactory::new().with_low_stock().create(&app, &viewed, &owner).await;
+  {
+    let mut conn = app.db.acquire().await.unwrap();
+    sqlx::query("INSERT INTO workspace_subscriptions (workspace_id, stripe_customer_id, status) VALUES ($1, $2, 'past_due')")
+      .bind(shared.id)
+      .bind(format!("cus_{}", shared.id.simple()))
+      .execute(&mut *conn)
+      .await
+      .unwrap();
+  }
+
+  let (status, body) = get_overview(&app, &user).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let overview = &body["results"];
+  let workspaces = overview["workspaces"].as_array().unwrap();
+  let find = |id: uuid::Uuid| {
+    workspaces
+      .iter()
+      .find(|workspace| workspace["workspace_id"] == id.to_string())
+      .unwrap_or_else(|| panic!("{id} missing from {body}"))
+  };
+  assert_eq!(workspaces.len(), 3, "only the caller's workspaces");
+  assert_eq!(find(own.id)["low_stock_products"], 1);
+  assert_eq!(find(own.id)["payment_overdue"], false);
+  assert_eq!(find(shared.id)["low_stock_products"], 2);
+  assert_eq!(find(shared.id)["payment_overdue"], true);
+  assert_eq!(find(viewed.id)["role"], "Viewer");
+  assert_eq!(find(viewed.id)["low_stock_products"], Value::Null, "viewers cannot read products");
+
+  assert_eq!(overview["totals"]["workspaces"], 3);
+  assert_eq!(overview["totals"]["low_stock_products"], 3);
+  assert_eq!(overview["totals"]["workspaces_payment_overdue"], 1);
+
+  let (status, body) = get_overview(&app, &stranger).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["workspaces"].as_array().unwrap().len(), 1);
+}
@@ -0,0 +1,188 @@
+//! Password resets: the emailed link, the new password and the single use of each token.
+
+use std::{
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::{
+  body::Body,
+  http::{self, Request, StatusCode},
+};
+use myapp_api_rust::{
+  AppResult,
+  mailer::{EmailMessage, Mailer},
+};
+use serde_json::{Value, json};
+
+use crate::common::{TestApp, fixtures::UserFactory};
+
+mod common;
+
+#[derive(Default)]
+struct RecordingMailer {
+  sent: Mutex<Vec<EmailMessage>>,
+}
+
+impl RecordingMailer {
+  /// The emails sent so far, once there are `count`: they are sent in the background.
+  async fn wait_for(&self, count: usize) -> Vec<EmailMessage> {
+    for _ in 0..100 {
+      if self.sent.lock().unwrap().len() >= count {
+        break;
+      }
+      tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let sent = self.sent.lock().unwrap().clone();
+    assert_eq!(sent.len(), count, "{sent:?}");
+    sent
+  }
+}
+
+#[async_trait]
+impl Mailer for RecordingMailer {
+  async fn send(&self, message: EmailMessage) -> AppResult<()> {
+    self.sent.lock().unwrap().push(message);
+    Ok(())
+  }
+}
+
+async fn post(app: &TestApp, uri: &str, body: Value) -> (StatusCode, Value) {
+  let request = Request::builder()
+    .method(http::Method::POST)
+    .uri(uri)
+    .header(http::header::CONTENT_TYPE, "application/json")
+    .body(Body::from(body.to_string()))
+    .unwrap();
+  let (status, _, body) = app.send(request).await;
+  (status, serde_json::from_slice(&body).unwrap())
+}
+
+/// The token at the end of the link in a password reset email.
+fn emailed_token(message: &EmailMessage) -> String {
+  let link = message
+    .body
+    .lines()
+    .find(|line| line.starts_with("http"))
+    .expect("the email carries a link");
+  link.rsplit('/').next().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_password_is_reset_with_the_emailed_token() {
+  let mailer = Arc::new(RecordingMailer::default());
+  let app = TestApp::isolated_with(|builder| builder.with_mailer(mailer.clone())).await;
+  let user = UserFactory::new().create(&app).await;
+
+  let (status, body) = post(&app, "/api/v1/auth/forgot-password", json!({ "email": user.user.email.to_uppercase() })).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let sent = mailer.wait_for(1).await;
+  assert_eq!(sent[0].to, user.user.email);
+  let token = emailed_token(&sent[0]);
+
+  let (status, body) = post(&app, "/api/v1/auth/reset-password", json!({ "token": token, "password": "short" })).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+  let (status, body) = post(
+    &app,
+    "/api/v1/auth/reset-password",
+    json!({ "token": token, "password": "brand-new-password" }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let (status, body) = post(&app, "/api/v1/auth/login", json!({ "email": user.user.email, "password": user.password })).await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+  let (status, body) = post(
+    &app,
+    "/api/v1/auth/login",
+    json!({ "email": user.user.email, "password": "brand-new-password" }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  // Each token can only be used once
+  let (status, body) = post(
+    &app,
+    "/api/v1/auth/reset-password",
+    json!({ "token": token, "password": "another-password" }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+}
+
+#[tokio::test]
+async fn test_reset_requests_do_not_reveal_registered_emails() {
+  let mailer = Arc::new(RecordingMailer::default());
+  let app = TestApp::isolated_with(|builder| builder.with_mailer(mailer.clone())).await;
+  let user = UserFactory::new().create(&app).await;
+
+  let (status, unknown) = post(&app, "/api/v1/auth/forgot-password", json!({ "email": "nobody@example.com" })).await;
+  assert_eq!(status, StatusCode::OK, "{unknown}");
+  let (status, known) = post(&app, "/api/v1/auth/forgot-password", json!({ "email": user.user.email })).await;
+  assert_eq!(status, StatusCode::OK, "{known}");
+  assert_eq!(unknown["message"], known["message"]);
+  let first = emailed_token(&mailer.wait_for(1).await[0]);
+
+  // A new request replaces the token emailed before
+  post(&app, "/api/v1/auth/forgot-password", json!({ "email": user.user.email })).await;
+  let second = emailed_token(&mailer.wait_for(2).await[1]);
+  let (status, body) = post(
+    &app,
+    "/api/v1/auth/reset-password",
+    json!({ "token": first, "password": "brand-new-password" }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+  let (status, body) = post(
+    &app,
+    "/api/v1/auth/reset-password",
+    json!({ "token": second, "password": "brand-new-password" }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let (status, body) = post(
+    &app,
+    "/api/v1/auth/reset-password",
+    json!({ "token": "made-up", "password": "brand-new-password" }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+}
+
+async fn get_me(app: &TestApp, token: &str) -> StatusCode {
+  app.call(http::Method::GET, "/api/v1/auth/me", token, None, None).await.0
+}
+
+#[tokio::test]
+async fn test_reset_signs_out_existing_sessions() {
+  let mailer = Arc::new(RecordingMailer::default());
+  let app = TestApp::isolated_with(|builder| builder.with_mailer(mailer.clone())).await;
+  let user = UserFactory::new().create(&app).await;
+  assert_eq!(get_me(&app, &user.token).await, StatusCode::OK);
+  // Tokens only carry whole seconds, and those issued in the second of the reset are kept
+  tokio::time::sleep(Duration::from_millis(1_100)).await;
+
+  post(&app, "/api/v1/auth/forgot-password", json!({ "email": user.user.email })).await;
+  let token = emailed_token(&mailer.wait_for(1).await[0]);
+  let (status, body) = post(
+    &app,
+    "/api/v1/auth/reset-password",
+    json!({ "token": token, "password": "brand-new-password" }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(get_me(&app, &user.token).await, StatusCode::UNAUTHORIZED);
+
+  let (status, body) = post(
+    &app,
+    "/api/v1/auth/login",
+    json!({ "email": user.user.email, "password": "brand-new-password" }),
+  )
+  .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let new_token = body["results"]["token"].as_str().expect("the login returns a token");
+  assert_eq!(get_me(&app, new_token).await, StatusCode::OK);
+}
@@ -0,0 +1,80 @@
+//! Products read as they were at a past time (`GET /products/:id?as_of=...`).
+
+use std::time::Duration;
+
+use axum::http::{self, StatusCode};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::json;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+/// The current time, after letting the clock move past the last change.
+async fn now() -> DateTime<Utc> {
+  tokio::time::sleep(Duration::from_millis(5)).await;
+  let now = Utc::now();
+  tokio::time::sleep(Duration::from_millis(5)).await;
+  now
+}
+
+fn as_of(uri: &str, time: DateTime<Utc>) -> String {
+  format!("{uri}?as_of={}", time.to_rfc3339_opts(SecondsFormat::Micros, true))
+}
+
+#[tokio::test]
+async fn test_products_are_read_as_they_were() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let before_creation = now().await;
+  let product = ProductFactory::new().name("Drill").create(&app, &workspace, &user).await;
+  let uri = format!("/api/v1/products/{}", product.id);
+  let created = now().await;
+
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &uri,
+      &user,
+      workspace.id,
+      Some(json!({ "name": "Cordless drill", "selling_price": 175 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let updated = now().await;
+
+  let (status, body) = app.call(http::Method::GET, &as_of(&uri, created), &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["name"], "Drill");
+  assert_eq!(body["results"]["selling_price"], json!(150.0));
+  let (_, body) = app.call(http::Method::GET, &as_of(&uri, updated), &user, workspace.id, None).await;
+  assert_eq!(body["results"]["name"], "Cordless drill");
+  assert_eq!(body["results"]["selling_price"], json!(175.0));
+
+  // Nothing to read before the product existed, nor once it was deleted
+  let (status, body) = app
+    .call(http::Method::GET, &as_of(&uri, before_creation), &user, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+  let (status, body) = app.call(http::Method::DELETE, &uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = app.call(http::Method::GET, &as_of(&uri, now().await), &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+  let (status, body) = app.call(http::Method::GET, &as_of(&uri, updated), &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["name"], "Cordless drill");
+
+  // Revisions stay in their workspace
+  let other = WorkspaceFactory::new().create(&app, &user).await;
+  let (status, body) = app.call(http::Method::GET, &as_of(&uri, updated), &user, other.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+
+  let (status, body) = app
+    .call(http::Method::GET, &format!("{uri}?as_of=yesterday"), &user, workspace.id, None)
+    .await;
+  assert!(status.is_client_error(), "{body}");
+}
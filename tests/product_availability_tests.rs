@@ -0,0 +1,110 @@
+//! Checking the stock of the lines of an order in one call.
+
+use axum::http::{self, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_lines_are_checked_against_the_stock() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let other = WorkspaceFactory::new().create(&app, &user).await;
+
+  let screws = ProductFactory::new().stock(10).create(&app, &workspace, &user).await;
+  let nails = ProductFactory::new().stock(5).create(&app, &workspace, &user).await;
+  let service = ProductFactory::new().create(&app, &workspace, &user).await;
+  let retired = ProductFactory::new().create(&app, &workspace, &user).await;
+  let elsewhere = ProductFactory::new().create(&app, &other, &user).await;
+  {
+    let mut conn = app.db.acquire().await.unwrap();
+    sqlx::query("UPDATE products SET track_inventory = (id <> $1), is_active = (id <> $2) WHERE id IN ($1, $2)")
+      .bind(service.id)
+      .bind(retired.id)
+      .execute(&mut *conn)
+      .await
+      .unwrap();
+  }
+
+  let lines = json!({ "lines": [
+    { "product_id": screws.id, "quantity": 4 },
+    { "product_id": nails.id, "quantity": 3 },
+    { "product_id": nails.id, "quantity": 3 },
+    { "product_id": service.id, "quantity": 1000 },
+    { "product_id": retired.id, "quantity": 1 },
+    { "product_id": elsewhere.id, "quantity": 1 },
+  ]});
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/products/availability",
+      &user,
+      workspace.id,
+      Some(lines.clone()),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let results = body["results"].as_array().unwrap();
+  let statuses: Vec<&str> = results.iter().map(|line| line["status"].as_str().unwrap()).collect();
+  assert_eq!(
+    statuses,
+    ["available", "insufficient", "insufficient", "untracked", "inactive", "not_found"],
+    "lines of the same product draw on the same stock"
+  );
+  assert_eq!(results[0]["available"], 10);
+  assert_eq!(results[1]["available"], 5);
+  assert_eq!(results[3]["available"], Value::Null);
+
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v2/products/availability", &user, workspace.id, Some(lines))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["data"].as_array().unwrap().len(), 6);
+}
+
+#[tokio::test]
+async fn test_invalid_requests_are_rejected() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let uri = "/api/v1/products/availability";
+
+  let (status, _) = app.call(http::Method::POST, uri, &user, workspace.id, Some(json!({ "lines": [] }))).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let zero = json!({ "lines": [{ "product_id": Uuid::new_v4(), "quantity": 0 }] });
+  let (status, _) = app.call(http::Method::POST, uri, &user, workspace.id, Some(zero)).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let unknown = json!({ "lines": [{ "product_id": Uuid::new_v4(), "quantity": 1, "bin": "A1" }] });
+  let (status, _) = app.call(http::Method::POST, uri, &user, workspace.id, Some(unknown)).await;
+  assert!(status.is_client_error(), "{status}");
+}
+
+#[tokio::test]
+async fn test_warehouses_are_echoed_and_checked_against_the_total_stock() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let screws = ProductFactory::new().stock(10).create(&app, &workspace, &user).await;
+  let warehouse_id = Uuid::new_v4();
+
+  let lines = json!({ "lines": [
+    { "product_id": screws.id, "quantity": 6, "warehouse_id": warehouse_id },
+    { "product_id": screws.id, "quantity": 6 },
+  ]});
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/products/availability", &user, workspace.id, Some(lines))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let results = body["results"].as_array().unwrap();
+  assert_eq!(results[0]["warehouse_id"], warehouse_id.to_string());
+  assert_eq!(results[1]["warehouse_id"], Value::Null);
+  assert_eq!(results[0]["available"], 10);
+  assert_eq!(results[0]["status"], "insufficient", "{body}");
+}
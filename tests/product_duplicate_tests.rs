@@ -0,0 +1,99 @@
+//! Possible duplicates flagged when creating products, and `force` to create them anyway.
+
+use axum::http::{self, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+fn product(code: &str, name: &str, barcode: Option<&str>) -> Value {
+  json!({ "code": code, "name": name, "barcode": barcode, "base_unit": "pcs", "selling_price": 10, "unit_cost": 5 })
+}
+
+#[tokio::test]
+async fn test_similar_names_are_flagged_unless_forced() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let existing = ProductFactory::new()
+    .code("DUP-1")
+    .name("Arabica Coffee Beans 1kg")
+    .create(&app, &workspace, &user)
+    .await;
+
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/products",
+      &user,
+      workspace.id,
+      Some(product("DUP-2", "Arabica Coffee Beans 1 kg", None)),
+    )
+    .await;
+  assert_eq!(status, StatusCode::CONFLICT, "{body}");
+  assert_eq!(body["error"], "POSSIBLE_DUPLICATE");
+  let candidates = body["details"]["candidates"].as_array().unwrap();
+  assert_eq!(candidates.len(), 1);
+  assert_eq!(candidates[0]["id"], existing.id.to_string());
+  assert_eq!(candidates[0]["code"], "DUP-1");
+  assert_eq!(candidates[0]["same_barcode"], false);
+  assert!(candidates[0]["name_similarity"].as_f64().unwrap() >= 0.6);
+
+  // Nothing like it in the workspace
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/products",
+      &user,
+      workspace.id,
+      Some(product("DUP-3", "Steel Water Bottle", None)),
+    )
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/products?force=true",
+      &user,
+      workspace.id,
+      Some(product("DUP-2", "Arabica Coffee Beans 1 kg", None)),
+    )
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+}
+
+#[tokio::test]
+async fn test_same_barcode_is_flagged_whatever_the_name() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let barcode = format!("89{}", &Uuid::new_v4().simple().to_string()[..10]);
+  ProductFactory::new()
+    .code("BAR-1")
+    .name("Green Tea")
+    .barcode(&barcode)
+    .create(&app, &workspace, &user)
+    .await;
+
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v2/products",
+      &user,
+      workspace.id,
+      Some(product("BAR-2", "Printer Paper A4", Some(&barcode))),
+    )
+    .await;
+  assert_eq!(status, StatusCode::CONFLICT, "{body}");
+  let candidates = body["details"]["candidates"].as_array().unwrap();
+  assert_eq!(candidates.len(), 1);
+  assert_eq!(candidates[0]["code"], "BAR-1");
+  assert_eq!(candidates[0]["barcode"], barcode);
+  assert_eq!(candidates[0]["same_barcode"], true);
+}
@@ -0,0 +1,173 @@
+//! The lifecycle of products: drafts, published and discontinued.
+
+use axum::http::{self, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{TestUser, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+fn product(code: &str, selling_price: u32, status: Option<&str>) -> Value {
+  let mut product = json!({ "code": code, "name": code, "base_unit": "pcs", "selling_price": selling_price, "unit_cost": 1 });
+  if let Some(status) = status {
+    product["status"] = json!(status);
+  }
+  product
+}
+
+async fn availability(app: &TestApp, user: &TestUser, workspace_id: Uuid, product_id: &str) -> Value {
+  let lines = json!({ "lines": [{ "product_id": product_id, "quantity": 1 }] });
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/products/availability", user, workspace_id, Some(lines))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  body["results"][0]["status"].clone()
+}
+
+#[tokio::test]
+async fn test_drafts_stay_out_of_sales_until_published() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/products",
+      &user,
+      workspace.id,
+      Some(product("P-1", 10, None)),
+    )
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  assert_eq!(body["results"]["status"], "active");
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/products",
+      &user,
+      workspace.id,
+      Some(product("P-2", 10, Some("draft"))),
+    )
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  assert_eq!(body["results"]["status"], "draft");
+  let draft_id = body["results"]["id"].as_str().unwrap().to_string();
+
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/products?status=draft", &user, workspace.id, None)
+    .await;
+  let codes: Vec<&str> = body["results"]["list"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|p| p["code"].as_str().unwrap())
+    .collect();
+  assert_eq!(codes, ["P-2"]);
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/products?status=active", &user, workspace.id, None)
+    .await;
+  let codes: Vec<&str> = body["results"]["list"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|p| p["code"].as_str().unwrap())
+    .collect();
+  assert_eq!(codes, ["P-1"]);
+  assert_eq!(availability(&app, &user, workspace.id, &draft_id).await, "draft");
+
+  let status_uri = format!("/api/v1/products/{}/status", draft_id);
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      &status_uri,
+      &user,
+      workspace.id,
+      Some(json!({ "status": "discontinued" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::CONFLICT, "drafts are published first: {body}");
+  let (status, body) = app
+    .call(http::Method::POST, &status_uri, &user, workspace.id, Some(json!({ "status": "active" })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["status"], "active");
+  assert_eq!(availability(&app, &user, workspace.id, &draft_id).await, "untracked");
+
+  let (status, body) = app
+    .call(http::Method::POST, &status_uri, &user, workspace.id, Some(json!({ "status": "draft" })))
+    .await;
+  assert_eq!(status, StatusCode::CONFLICT, "{body}");
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      &status_uri,
+      &user,
+      workspace.id,
+      Some(json!({ "status": "discontinued" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["status"], "discontinued");
+  assert_eq!(availability(&app, &user, workspace.id, &draft_id).await, "discontinued");
+  let (status, body) = app
+    .call(http::Method::POST, &status_uri, &user, workspace.id, Some(json!({ "status": "active" })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "discontinued products can be published again: {body}");
+}
+
+#[tokio::test]
+async fn test_incomplete_products_are_not_published() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/products",
+      &user,
+      workspace.id,
+      Some(product("P-1", 10, Some("discontinued"))),
+    )
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/products",
+      &user,
+      workspace.id,
+      Some(product("P-2", 0, Some("draft"))),
+    )
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  let product_uri = format!("/api/v1/products/{}", body["results"]["id"].as_str().unwrap());
+  let status_uri = format!("{}/status", product_uri);
+  let (status, body) = app
+    .call(http::Method::POST, &status_uri, &user, workspace.id, Some(json!({ "status": "active" })))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+  // The status only moves through its own endpoint
+  let (status, body) = app
+    .call(
+      http::Method::PATCH,
+      &product_uri,
+      &user,
+      workspace.id,
+      Some(json!({ "selling_price": 5 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["status"], "draft");
+  let (status, body) = app
+    .call(http::Method::POST, &status_uri, &user, workspace.id, Some(json!({ "status": "active" })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+}
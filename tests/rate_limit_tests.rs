@@ -0,0 +1,102 @@
+//! Request budgets per user, workspace and method, and per client address for anonymous callers,
+//! with the address taken from the headers of trusted proxies only.
+
+use std::net::SocketAddr;
+
+use axum::{
+  body::Body,
+  extract::ConnectInfo,
+  http::{self, Request, StatusCode, header::RETRY_AFTER},
+};
+use myapp_api_rust::config::AppConfig;
+use serde_json::json;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+  request,
+};
+
+mod common;
+
+/// A login attempt from `peer`, with `headers` set by the proxies in between, if any.
+async fn anonymous_login(app: &TestApp, peer: &str, headers: &[(&str, &str)]) -> StatusCode {
+  let mut request = Request::builder()
+    .method(http::Method::POST)
+    .uri("/api/v1/auth/login")
+    .header(http::header::CONTENT_TYPE, "application/json")
+    .extension(ConnectInfo(SocketAddr::new(peer.parse().unwrap(), 40_000)));
+  for (name, value) in headers {
+    request = request.header(*name, *value);
+  }
+  let body = Body::from(json!({ "email": "nobody@example.com", "password": "password123" }).to_string());
+  app.send(request.body(body).unwrap()).await.0
+}
+
+#[tokio::test]
+async fn test_user_budgets_are_kept_per_workspace_and_method() {
+  let mut config = AppConfig::from_env();
+  config.rate_limit.enabled = true;
+  config.rate_limit.read_max = 2;
+  config.rate_limit.write_max = 1;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let user = UserFactory::new().create(&app).await;
+  let first = WorkspaceFactory::new().create(&app, &user).await;
+  let second = WorkspaceFactory::new().create(&app, &user).await;
+
+  for remaining in ["1", "0"] {
+    let (status, headers, _) = app.send(request(http::Method::GET, "/api/v1/auth/me", &user, first.id, None)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["x-ratelimit-limit"], "2");
+    assert_eq!(headers["x-ratelimit-remaining"], remaining);
+  }
+  let (status, headers, _) = app.send(request(http::Method::GET, "/api/v1/auth/me", &user, first.id, None)).await;
+  assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+  assert!(headers.contains_key(RETRY_AFTER));
+
+  // Reads in another workspace and writes have budgets of their own
+  let (status, _) = app.call(http::Method::GET, "/api/v1/auth/me", &user, second.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  let uri = format!("/api/v1/workspaces/{}", first.id);
+  let rename = || Some(json!({ "name": "Renamed" }));
+  let (status, body) = app.call(http::Method::PUT, &uri, &user, first.id, rename()).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, _) = app.call(http::Method::PUT, &uri, &user, first.id, rename()).await;
+  assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_fly_client_ip_is_the_client_behind_trusted_proxies() {
+  let mut config = AppConfig::from_env();
+  config.rate_limit.enabled = true;
+  config.rate_limit.anonymous_max = 1;
+  config.server.trusted_proxies = vec!["172.16.0.0/12".parse().unwrap()];
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+
+  let client = [("Fly-Client-IP", "198.51.100.21")];
+  assert_ne!(anonymous_login(&app, "172.19.0.2", &client).await, StatusCode::TOO_MANY_REQUESTS);
+  // The same client through another edge proxy is still the same client
+  assert_eq!(anonymous_login(&app, "172.19.0.3", &client).await, StatusCode::TOO_MANY_REQUESTS);
+  assert_ne!(
+    anonymous_login(&app, "172.19.0.2", &[("Fly-Client-IP", "198.51.100.22")]).await,
+    StatusCode::TOO_MANY_REQUESTS
+  );
+}
+
+#[tokio::test]
+async fn test_peers_are_the_client_without_trusted_proxies() {
+  let mut config = AppConfig::from_env();
+  config.rate_limit.enabled = true;
+  config.rate_limit.anonymous_max = 1;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+
+  assert_ne!(
+    anonymous_login(&app, "203.0.113.31", &[("Fly-Client-IP", "198.51.100.31")]).await,
+    StatusCode::TOO_MANY_REQUESTS
+  );
+  // Neither a spoofed header nor the IPv4-mapped form of the peer is a new client
+  assert_eq!(
+    anonymous_login(&app, "::ffff:203.0.113.31", &[("Fly-Client-IP", "198.51.100.32")]).await,
+    StatusCode::TOO_MANY_REQUESTS
+  );
+}
@@ -0,0 +1,110 @@
+//! Soft locks on the records being edited, and the events warning other members.
+
+use axum::http::{self, StatusCode};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
+use myapp_api_rust::modules::datastores::workspaces::WorkspaceRole;
+
+mod common;
+
+#[tokio::test]
+async fn test_second_editor_is_warned_until_the_lock_is_released() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let alice = UserFactory::new().create(&app).await;
+  let bob = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new()
+    .member(&alice, WorkspaceRole::Member)
+    .member(&bob, WorkspaceRole::Member)
+    .create(&app, &admin)
+    .await;
+  let product = ProductFactory::new().create(&app, &workspace, &admin).await;
+  let lock_uri = format!("/api/v1/products/{}/lock", product.id);
+  let mut receiver = app.state.events.subscribe();
+
+  let (status, body) = app.call(http::Method::POST, &lock_uri, &alice, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["locked_by"], alice.id().to_string());
+  let locked_at = body["results"]["locked_at"].clone();
+
+  let (status, body) = app.call(http::Method::POST, &lock_uri, &bob, workspace.id, None).await;
+  assert_eq!(status, StatusCode::CONFLICT, "{body}");
+  let (status, body) = app.call(http::Method::DELETE, &lock_uri, &bob, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "members only release their own lock: {body}");
+  let (status, body) = app
+    .call(http::Method::POST, &format!("{lock_uri}?force=true"), &bob, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+
+  // Renewing keeps the time the lock was taken
+  let (status, body) = app.call(http::Method::POST, &lock_uri, &alice, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["locked_at"], locked_at);
+
+  let (status, body) = app.call(http::Method::DELETE, &lock_uri, &alice, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = app.call(http::Method::POST, &lock_uri, &bob, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let mut events = Vec::new();
+  while let Ok(event) = receiver.try_recv() {
+    events.push((event.event, event.actor_id.unwrap()));
+  }
+  assert_eq!(
+    events,
+    [
+      ("product.locked".to_string(), alice.id()),
+      ("product.locked".to_string(), alice.id()),
+      ("product.unlocked".to_string(), alice.id()),
+      ("product.locked".to_string(), bob.id()),
+    ]
+  );
+}
+
+#[tokio::test]
+async fn test_locks_expire_and_admins_can_take_them_over() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+  let product = ProductFactory::new().create(&app, &workspace, &admin).await;
+  let lock_uri = format!("/api/v1/products/{}/lock", product.id);
+
+  let (status, body) = app.call(http::Method::POST, &lock_uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = app.call(http::Method::POST, &lock_uri, &admin, workspace.id, None).await;
+  assert_eq!(status, StatusCode::CONFLICT, "{body}");
+  let (status, body) = app
+    .call(http::Method::POST, &format!("{lock_uri}?force=true"), &admin, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["locked_by"], admin.id().to_string());
+
+  // The member ignoring the warning still saves: locks do not block writes
+  let uri = format!("/api/v1/products/{}", product.id);
+  let (status, body) = app
+    .call(http::Method::PATCH, &uri, &member, workspace.id, Some(json!({ "name": "Renamed" })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let mut conn = app.db.acquire().await.unwrap();
+  sqlx::query("UPDATE record_locks SET expires_at = NOW() - INTERVAL '1 second' WHERE record_id = $1")
+    .bind(product.id)
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+  drop(conn);
+  let (status, body) = app.call(http::Method::DELETE, &lock_uri, &admin, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "expired locks are gone: {body}");
+  let (status, body) = app.call(http::Method::POST, &lock_uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let missing_uri = format!("/api/v1/products/{}/lock", Uuid::new_v4());
+  let (status, body) = app.call(http::Method::POST, &missing_uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+}
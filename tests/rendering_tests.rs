@@ -0,0 +1,201 @@
+//! PDF printing of records and print templates.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::{
+  body::Bytes,
+  http::{self, StatusCode},
+};
+use myapp_api_rust::{AppResult, modules::datastores::workspaces::WorkspaceRole, modules::rendering::pdf_renderer::PdfRenderer};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+  request,
+};
+
+mod common;
+
+/// "Prints" the HTML as is, behind a PDF signature.
+struct FakeRenderer;
+
+#[async_trait]
+impl PdfRenderer for FakeRenderer {
+  async fn render(&self, html: &str) -> AppResult<Vec<u8>> {
+    Ok(format!("%PDF-{html}").into_bytes())
+  }
+}
+
+async fn app() -> TestApp {
+  TestApp::isolated_with(|builder| builder.with_pdf_renderer(Arc::new(FakeRenderer))).await
+}
+
+fn json(body: &Bytes) -> Value {
+  serde_json::from_slice(body).unwrap()
+}
+
+#[tokio::test]
+async fn test_products_print_with_the_template_of_the_workspace() {
+  let app = app().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+  let product = ProductFactory::new()
+    .code("PRD/001")
+    .name("Kopi <Arabika>")
+    .create(&app, &workspace, &admin)
+    .await;
+  let uri = format!("/api/v1/products/{}/pdf", product.id);
+
+  // The built-in template, with record values escaped
+  let (status, headers, body) = app.send(request(http::Method::GET, &uri, &member, workspace.id, None)).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(headers[http::header::CONTENT_TYPE], "application/pdf");
+  assert_eq!(headers[http::header::CONTENT_DISPOSITION], "inline; filename=\"PRD_001.pdf\"");
+  let pdf = String::from_utf8(body.to_vec()).unwrap();
+  assert!(pdf.starts_with("%PDF-"));
+  assert!(pdf.contains("Kopi &lt;Arabika&gt;"), "{pdf}");
+  assert!(pdf.contains(&workspace.name));
+
+  // A custom template
+  let template = json!({ "html": "<p>{{record.code}} by {{workspace.name}}{{#if record.sku}} ({{record.sku}}){{/if}}</p>" });
+  let (status, _, body) = app
+    .send(request(
+      http::Method::PUT,
+      "/api/v1/print-templates/products",
+      &admin,
+      workspace.id,
+      Some(template),
+    ))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{}", json(&body));
+  assert_eq!(json(&body)["results"]["is_default"], false);
+
+  let (status, _, body) = app.send(request(http::Method::GET, &uri, &member, workspace.id, None)).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(&body[..], format!("%PDF-<p>PRD/001 by {}</p>", workspace.name).as_bytes());
+
+  let (status, _, body) = app
+    .send(request(http::Method::GET, "/api/v1/print-templates", &member, workspace.id, None))
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  let templates = json(&body)["results"].as_array().unwrap().clone();
+  assert_eq!(templates.len(), 3);
+  assert!(templates.iter().any(|t| t["entity"] == "contacts" && t["is_default"] == true));
+
+  // Reset to the built-in template
+  let (status, _, body) = app
+    .send(request(
+      http::Method::DELETE,
+      "/api/v1/print-templates/products",
+      &admin,
+      workspace.id,
+      None,
+    ))
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(json(&body)["results"]["is_default"], true);
+  let (_, _, body) = app.send(request(http::Method::GET, &uri, &member, workspace.id, None)).await;
+  assert!(String::from_utf8_lossy(&body).contains("<!DOCTYPE html>"));
+
+  // Products of other workspaces are not found
+  let other = WorkspaceFactory::new().create(&app, &admin).await;
+  let (status, _, _) = app.send(request(http::Method::GET, &uri, &admin, other.id, None)).await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_invalid_print_templates_are_rejected() {
+  let app = app().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+
+  let unclosed = json!({ "html": "{{#if record.sku}}<p>" });
+  let (status, _, _) = app
+    .send(request(
+      http::Method::PUT,
+      "/api/v1/print-templates/products",
+      &admin,
+      workspace.id,
+      Some(unclosed),
+    ))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+  let template = json!({ "html": "<p>{{record.name}}</p>" });
+  let (status, _, _) = app
+    .send(request(
+      http::Method::PUT,
+      "/api/v1/print-templates/invoices",
+      &admin,
+      workspace.id,
+      Some(template.clone()),
+    ))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+  let (status, _, _) = app
+    .send(request(
+      http::Method::PUT,
+      "/api/v1/print-templates/products",
+      &member,
+      workspace.id,
+      Some(template),
+    ))
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_product_labels_print_on_a_sheet() {
+  let app = app().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let coffee = ProductFactory::new()
+    .code("PRD-1")
+    .name("Kopi & Teh")
+    .create(&app, &workspace, &user)
+    .await;
+  let tea = ProductFactory::new().code("12345678").create(&app, &workspace, &user).await;
+
+  let labels = json!({
+    "items": [{ "product_id": coffee.id, "quantity": 3 }, { "product_id": tea.id, "quantity": 2 }],
+    "width_mm": 38,
+    "height_mm": 21,
+  });
+  let (status, headers, body) = app
+    .send(request(http::Method::POST, "/api/v1/products/labels", &user, workspace.id, Some(labels)))
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(headers[http::header::CONTENT_DISPOSITION], "inline; filename=\"labels.pdf\"");
+  let sheet = String::from_utf8(body.to_vec()).unwrap();
+  assert!(sheet.starts_with("%PDF-"));
+  assert!(sheet.contains("width: 38mm; height: 21mm;"));
+  assert_eq!(sheet.matches("<div class=\"l\">").count(), 5);
+  assert_eq!(sheet.matches("<symbol ").count(), 2, "one barcode per product");
+  assert_eq!(sheet.matches("Kopi &amp; Teh").count(), 3);
+  // Digits are encoded in character set C: start, 4 pairs, checksum and stop, in quiet zones
+  assert!(sheet.contains("<symbol id=\"b1\" viewBox=\"0 0 99 1\""), "{sheet}");
+
+  let too_many = json!({ "items": [{ "product_id": coffee.id, "quantity": 201 }] });
+  let (status, _, _) = app
+    .send(request(
+      http::Method::POST,
+      "/api/v1/products/labels",
+      &user,
+      workspace.id,
+      Some(too_many),
+    ))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+  let unknown = json!({ "items": [{ "product_id": Uuid::new_v4(), "quantity": 1 }] });
+  let (status, _, _) = app
+    .send(request(http::Method::POST, "/api/v1/products/labels", &user, workspace.id, Some(unknown)))
+    .await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+}
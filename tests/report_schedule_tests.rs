@@ -0,0 +1,174 @@
+//! Report schedules, and their delivery by email and webhook.
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use axum::http::{self, StatusCode};
+use chrono::{DateTime, Duration, Utc};
+use myapp_api_rust::{
+  AppResult, internal_error,
+  mailer::{EmailMessage, Mailer},
+  modules::{
+    datastores::workspaces::WorkspaceRole,
+    reports::{report_service, report_webhook::WebhookSender},
+  },
+};
+use serde_json::{Value, json};
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+#[derive(Default)]
+struct RecordingMailer {
+  sent: Mutex<Vec<EmailMessage>>,
+}
+
+#[async_trait]
+impl Mailer for RecordingMailer {
+  async fn send(&self, message: EmailMessage) -> AppResult<()> {
+    self.sent.lock().unwrap().push(message);
+    Ok(())
+  }
+}
+
+#[derive(Default)]
+struct RecordingWebhook {
+  fail: bool,
+  posted: Mutex<Vec<(String, Value)>>,
+}
+
+#[async_trait]
+impl WebhookSender for RecordingWebhook {
+  async fn post(&self, url: &str, body: &Value) -> AppResult<()> {
+    if self.fail {
+      return Err(internal_error!("Webhook {} responded 500", url));
+    }
+    self.posted.lock().unwrap().push((url.to_string(), body.clone()));
+    Ok(())
+  }
+}
+
+fn timestamp(value: &Value) -> DateTime<Utc> {
+  value.as_str().unwrap().parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_due_schedules_are_delivered_once_per_run() {
+  let mailer = Arc::new(RecordingMailer::default());
+  let webhook = Arc::new(RecordingWebhook::default());
+  let app = TestApp::isolated_with(|builder| builder.with_mailer(mailer.clone()).with_webhook_sender(webhook.clone())).await;
+  let owner = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &owner).await;
+  let low = ProductFactory::new().with_low_stock().create(&app, &workspace, &owner).await;
+  let stocked = ProductFactory::new().create(&app, &workspace, &owner).await;
+  let uri = format!("/api/v1/workspaces/{}/report-schedules", workspace.id);
+
+  let starts_at = Utc::now() - Duration::hours(1);
+  let request = json!({
+    "name": "Weekly restock",
+    "report": "low_stock",
+    "frequency": "weekly",
+    "recipients": ["ops@example.com", "buyer@example.com"],
+    "webhook_url": "https://hooks.example.com/reports",
+    "starts_at": starts_at,
+  });
+  let (status, body) = app.call(http::Method::POST, &uri, &member, workspace.id, Some(request)).await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  let schedule_id = body["results"]["id"].as_str().unwrap().to_string();
+
+  assert_eq!(report_service::run_due(&app.state).await.unwrap(), 1);
+  {
+    let sent = mailer.sent.lock().unwrap();
+    let recipients: Vec<&str> = sent.iter().map(|email| email.to.as_str()).collect();
+    assert_eq!(recipients, ["ops@example.com", "buyer@example.com"]);
+    assert_eq!(sent[0].subject, "Weekly restock: Low stock");
+    assert!(sent[0].body.contains(&low.name), "{}", sent[0].body);
+    assert!(!sent[0].body.contains(&stocked.name), "{}", sent[0].body);
+  }
+  {
+    let posted = webhook.posted.lock().unwrap();
+    assert_eq!(posted.len(), 1);
+    let (url, payload) = &posted[0];
+    assert_eq!(url, "https://hooks.example.com/reports");
+    assert_eq!(payload["schedule_id"], schedule_id.as_str());
+    assert_eq!(payload["report"], "low_stock");
+    assert_eq!(payload["columns"], json!(["Code", "Name", "Stock", "Reorder level"]));
+    assert_eq!(payload["rows"], json!([[low.code, low.name, "3", "20"]]));
+  }
+
+  assert_eq!(report_service::run_due(&app.state).await.unwrap(), 0, "the next run is a week later");
+  assert_eq!(mailer.sent.lock().unwrap().len(), 2);
+
+  let (status, body) = app.call(http::Method::GET, &uri, &member, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let schedule = &body["results"][0];
+  assert_eq!(schedule["last_error"], Value::Null);
+  assert!(schedule["last_run_at"].is_string());
+  let next_run_at = timestamp(&schedule["next_run_at"]);
+  assert_eq!(next_run_at.timestamp(), (starts_at + Duration::weeks(1)).timestamp());
+}
+
+#[tokio::test]
+async fn test_failed_deliveries_and_invalid_schedules() {
+  let mailer = Arc::new(RecordingMailer::default());
+  let webhook = Arc::new(RecordingWebhook {
+    fail: true,
+    ..Default::default()
+  });
+  let app = TestApp::isolated_with(|builder| builder.with_mailer(mailer.clone()).with_webhook_sender(webhook.clone())).await;
+  let owner = UserFactory::new().create(&app).await;
+  let viewer = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&viewer, WorkspaceRole::Viewer).create(&app, &owner).await;
+  let uri = format!("/api/v1/workspaces/{}/report-schedules", workspace.id);
+  let request = |changes: Value| {
+    let mut request = json!({
+      "name": "Stock value",
+      "report": "stock_value",
+      "frequency": "daily",
+      "recipients": ["ops@example.com"],
+      "webhook_url": "https://hooks.example.com/reports",
+    });
+    request.as_object_mut().unwrap().extend(changes.as_object().unwrap().clone());
+    request
+  };
+
+  let (status, _) = app.call(http::Method::POST, &uri, &viewer, workspace.id, Some(request(json!({})))).await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+  let plain_http = request(json!({ "webhook_url": "http://hooks.example.com/reports" }));
+  let (status, _) = app.call(http::Method::POST, &uri, &owner, workspace.id, Some(plain_http)).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let nowhere = request(json!({ "recipients": [], "webhook_url": null }));
+  let (status, _) = app.call(http::Method::POST, &uri, &owner, workspace.id, Some(nowhere)).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let (status, _) = app
+    .call(
+      http::Method::POST,
+      &uri,
+      &owner,
+      workspace.id,
+      Some(request(json!({ "recipients": ["not-an-email"] }))),
+    )
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+  let (status, body) = app.call(http::Method::POST, &uri, &owner, workspace.id, Some(request(json!({})))).await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  let schedule_uri = format!("{uri}/{}", body["results"]["id"].as_str().unwrap());
+
+  assert_eq!(report_service::run_due(&app.state).await.unwrap(), 0);
+  assert_eq!(mailer.sent.lock().unwrap().len(), 1, "the email is sent even though the webhook failed");
+  let (_, body) = app.call(http::Method::GET, &uri, &owner, workspace.id, None).await;
+  assert_eq!(body["results"][0]["last_error"], "Delivery failed: webhook");
+
+  let (status, body) = app.call(http::Method::DELETE, &schedule_uri, &owner, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, _) = app
+    .call(http::Method::PUT, &schedule_uri, &owner, workspace.id, Some(request(json!({}))))
+    .await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,151 @@
+//! Data retention: per-workspace policies, the purge preview and the scheduled purge.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::modules::{datastores::workspaces::WorkspaceRole, retention::retention_purger::purge_expired};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+/// Records one request for `user_id` at each of `days_ago`.
+async fn record_usage(app: &TestApp, workspace_id: Uuid, user_id: Uuid, days_ago: &[i32]) {
+  let mut conn = app.db.acquire().await.unwrap();
+  for days in days_ago {
+    sqlx::query("INSERT INTO api_usage (workspace_id, user_id, route, day, request_count) VALUES ($1, $2, '/api/v1/contacts', CURRENT_DATE - $3, 1)")
+      .bind(workspace_id)
+      .bind(user_id)
+      .bind(days)
+      .execute(&mut *conn)
+      .await
+      .unwrap();
+  }
+}
+
+#[tokio::test]
+async fn test_policies_preview_and_purge_expired_usage() {
+  let app = TestApp::isolated().await;
+  let owner = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &owner).await;
+  let other = WorkspaceFactory::new().create(&app, &owner).await;
+  record_usage(&app, workspace.id, owner.id(), &[100, 200, 400]).await;
+  record_usage(&app, other.id, owner.id(), &[400]).await;
+  let uri = format!("/api/v1/workspaces/{}/retention", workspace.id);
+
+  let (status, _) = app.call(http::Method::GET, &uri, &member, None, None).await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+  let (status, _) = app
+    .call(
+      http::Method::PUT,
+      &uri,
+      &owner,
+      None,
+      Some(json!({ "category": "api_usage", "retain_days": 0 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &uri,
+      &owner,
+      None,
+      Some(json!({ "category": "api_usage", "retain_days": 150 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(body["results"]["retain_days"], 150);
+
+  let (status, body) = app.call(http::Method::GET, &format!("{}/preview", uri), &owner, None, None).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(body["results"][0]["category"], "api_usage");
+  assert_eq!(body["results"][0]["rows"], 2);
+
+  // Only the workspace with a policy loses data
+  assert!(purge_expired(&app.state).await.unwrap() >= 2);
+  let (_, body) = app.call(http::Method::GET, &format!("{}/preview", uri), &owner, None, None).await;
+  assert_eq!(body["results"][0]["rows"], 0);
+  let mut conn = app.db.acquire().await.unwrap();
+  let remaining: Vec<(Uuid,)> = sqlx::query_as("SELECT workspace_id FROM api_usage WHERE workspace_id IN ($1, $2) ORDER BY day")
+    .bind(workspace.id)
+    .bind(other.id)
+    .fetch_all(&mut *conn)
+    .await
+    .unwrap();
+  drop(conn);
+  assert_eq!(remaining, [(other.id,), (workspace.id,)]);
+
+  let (_, body) = app.call(http::Method::GET, &uri, &owner, None, None).await;
+  let purge = &body["results"]["recent_purges"][0];
+  assert_eq!(purge["category"], "api_usage");
+  assert_eq!(purge["rows_removed"], 2);
+
+  // Removing the policy keeps the data forever again
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &uri,
+      &owner,
+      None,
+      Some(json!({ "category": "api_usage", "retain_days": null })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  assert!(body["results"].is_null());
+  let (_, body) = app.call(http::Method::GET, &uri, &owner, None, None).await;
+  assert!(body["results"]["policies"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_purge_expired_audit_events() {
+  let app = TestApp::isolated().await;
+  let owner = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &owner).await;
+  let mut conn = app.db.acquire().await.unwrap();
+  for days in [10, 100, 400] {
+    sqlx::query(
+      "INSERT INTO audit_events (workspace_id, action, actor_id, created_at) VALUES ($1, 'member.added', $2, NOW() - make_interval(days => $3))",
+    )
+    .bind(workspace.id)
+    .bind(owner.id())
+    .bind(days)
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+  }
+  drop(conn);
+  let uri = format!("/api/v1/workspaces/{}/retention", workspace.id);
+
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &uri,
+      &owner,
+      None,
+      Some(json!({ "category": "audit_events", "retain_days": 90 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (_, body) = app.call(http::Method::GET, &format!("{}/preview", uri), &owner, None, None).await;
+  assert_eq!(body["results"][0]["category"], "audit_events");
+  assert_eq!(body["results"][0]["rows"], 2);
+
+  assert!(purge_expired(&app.state).await.unwrap() >= 2);
+  let mut conn = app.db.acquire().await.unwrap();
+  let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_events WHERE workspace_id = $1")
+    .bind(workspace.id)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+  drop(conn);
+  assert_eq!(remaining, 1);
+  let (_, body) = app.call(http::Method::GET, &uri, &owner, None, None).await;
+  assert_eq!(body["results"]["recent_purges"][0]["category"], "audit_events");
+  assert_eq!(body["results"]["recent_purges"][0]["rows_removed"], 2);
+}
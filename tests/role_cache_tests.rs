@@ -0,0 +1,89 @@
+//! The workspace role cache: roles loaded by one request are reused by the next ones until they
+//! expire, so a membership revoked behind the cache's back (by another instance) gets past the
+//! JWT middleware for at most `ROLE_CACHE_TTL_SECS`.
+
+use std::time::Duration;
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::{
+  config::AppConfig,
+  modules::datastores::workspaces::{WorkspaceRole, workspace_role_cache::WorkspaceRoleCache},
+};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{TestUser, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+/// An app caching roles for `ttl_secs`, with a member of a workspace of someone else.
+async fn app(ttl_secs: u64) -> (TestApp, TestUser, Uuid) {
+  let mut config = AppConfig::from_env();
+  config.role_cache.ttl_secs = ttl_secs;
+  config.role_cache.claim_ttl_secs = 0;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let owner = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &owner).await;
+  (app, member, workspace.id)
+}
+
+/// Removes the membership without going through the API, as another instance would.
+async fn revoke_elsewhere(app: &TestApp, user: &TestUser, workspace_id: Uuid) {
+  sqlx::query("DELETE FROM workspace_users WHERE user_id = $1 AND workspace_id = $2")
+    .bind(user.id())
+    .bind(workspace_id)
+    .execute(&mut *app.db.acquire().await.unwrap())
+    .await
+    .unwrap();
+}
+
+async fn trial_status(app: &TestApp, user: &TestUser, workspace_id: Uuid) -> StatusCode {
+  let uri = format!("/api/v1/workspaces/{}/trial", workspace_id);
+  app.call(http::Method::GET, &uri, user, workspace_id, None).await.0
+}
+
+#[tokio::test]
+async fn test_roles_are_cached_until_they_expire() {
+  let (app, member, workspace_id) = app(1).await;
+  assert_eq!(trial_status(&app, &member, workspace_id).await, StatusCode::OK);
+  assert_eq!(app.state.role_cache.get(member.id(), workspace_id), Some(WorkspaceRole::Member));
+
+  // Admitted on the cached role; the handler's own access check sees the revocation
+  revoke_elsewhere(&app, &member, workspace_id).await;
+  assert_eq!(trial_status(&app, &member, workspace_id).await, StatusCode::FORBIDDEN);
+
+  tokio::time::sleep(Duration::from_millis(1100)).await;
+  assert_eq!(app.state.role_cache.get(member.id(), workspace_id), None);
+  assert_eq!(trial_status(&app, &member, workspace_id).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_without_a_ttl_every_request_loads_the_role() {
+  let (app, member, workspace_id) = app(0).await;
+  assert_eq!(trial_status(&app, &member, workspace_id).await, StatusCode::OK);
+  assert_eq!(app.state.role_cache.get(member.id(), workspace_id), None);
+
+  revoke_elsewhere(&app, &member, workspace_id).await;
+  assert_eq!(trial_status(&app, &member, workspace_id).await, StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+fn test_invalidation_forgets_a_member_or_a_whole_workspace() {
+  let cache = WorkspaceRoleCache::new(60);
+  let (user, other_user) = (Uuid::new_v4(), Uuid::new_v4());
+  let (workspace, other_workspace) = (Uuid::new_v4(), Uuid::new_v4());
+  cache.insert(user, workspace, WorkspaceRole::Admin);
+  cache.insert(other_user, workspace, WorkspaceRole::Viewer);
+  cache.insert(user, other_workspace, WorkspaceRole::Member);
+
+  cache.invalidate(user, workspace);
+  assert_eq!(cache.get(user, workspace), None);
+  assert_eq!(cache.get(other_user, workspace), Some(WorkspaceRole::Viewer));
+
+  cache.invalidate_workspace(workspace);
+  assert_eq!(cache.get(other_user, workspace), None);
+  assert_eq!(cache.get(user, other_workspace), Some(WorkspaceRole::Member));
+}
@@ -0,0 +1,138 @@
+//! Search across the contacts and products of a workspace (`GET /api/v1/search`).
+
+use axum::http::{self, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
+use myapp_api_rust::modules::datastores::workspaces::WorkspaceRole;
+
+mod common;
+
+fn names(hits: &Value) -> Vec<&str> {
+  hits.as_array().unwrap().iter().map(|hit| hit["name"].as_str().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn test_search_groups_matches_by_entity_as_the_user_types() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let other = WorkspaceFactory::new().create(&app, &user).await;
+  ProductFactory::new().name("Hammer drill").create(&app, &workspace, &user).await;
+  ProductFactory::new().name("Hammock").create(&app, &workspace, &user).await;
+  ProductFactory::new().name("Saw").create(&app, &workspace, &user).await;
+  ProductFactory::new().name("Hammer").create(&app, &other, &user).await;
+  let contact = json!({ "code": "C-1", "name": "Hamid Supplies", "email": "orders@hamid.example", "contact_type": "supplier" });
+  let (status, body) = app.call(http::Method::POST, "/api/v1/contacts", &user, workspace.id, Some(contact)).await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/search?q=ham", &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let mut products = names(&body["results"]["products"]);
+  products.sort();
+  assert_eq!(products, ["Hammer drill", "Hammock"]);
+  assert_eq!(names(&body["results"]["contacts"]), ["Hamid Supplies"]);
+  assert_eq!(body["results"]["contacts"][0]["code"], "C-1");
+
+  // Every word narrows the matches down
+  let (_, body) = app.call(http::Method::GET, "/api/v1/search?q=hamm%20dr", &user, workspace.id, None).await;
+  assert_eq!(names(&body["results"]["products"]), ["Hammer drill"]);
+  assert_eq!(body["results"]["contacts"], json!([]));
+
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/search?q=ham&limit=1", &user, workspace.id, None)
+    .await;
+  assert_eq!(body["results"]["products"].as_array().unwrap().len(), 1);
+
+  for uri in ["/api/v1/search", "/api/v1/search?q=", "/api/v1/search?q=ham&limit=50"] {
+    let (status, body) = app.call(http::Method::GET, uri, &user, workspace.id, None).await;
+    assert!(status.is_client_error(), "{uri}: {body}");
+  }
+}
+
+#[tokio::test]
+async fn test_search_skips_fields_hidden_from_the_caller() {
+  let app = TestApp::isolated().await;
+  let admin = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &admin).await;
+  let contact = json!({ "code": "C-1", "name": "Acme", "email": "billing@acme.example", "contact_type": "customer" });
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", &admin, workspace.id, Some(contact))
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+
+  let (_, body) = app.call(http::Method::GET, "/api/v1/search?q=billing", &member, workspace.id, None).await;
+  assert_eq!(names(&body["results"]["contacts"]), ["Acme"]);
+
+  let policies = json!({ "field_policies": { "contacts": { "email": { "Member": "hidden" } } } });
+  let settings_uri = format!("/api/v1/workspaces/{}/settings", workspace.id);
+  let (status, body) = app.call(http::Method::PUT, &settings_uri, &admin, workspace.id, Some(policies)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  let (_, body) = app.call(http::Method::GET, "/api/v1/search?q=billing", &member, workspace.id, None).await;
+  assert_eq!(body["results"]["contacts"], json!([]));
+  let (_, body) = app.call(http::Method::GET, "/api/v1/search?q=acm", &member, workspace.id, None).await;
+  assert_eq!(names(&body["results"]["contacts"]), ["Acme"]);
+  let (_, body) = app.call(http::Method::GET, "/api/v1/search?q=billing", &admin, workspace.id, None).await;
+  assert_eq!(names(&body["results"]["contacts"]), ["Acme"]);
+}
+
+#[tokio::test]
+async fn test_search_follows_edits_and_refreshes_renamed_categories() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let mut conn = app.db.acquire().await.unwrap();
+  let category: Uuid = sqlx::query_scalar("INSERT INTO product_categories (code, name, workspace_id) VALUES ('HAND', 'Hand tools', $1) RETURNING id")
+    .bind(workspace.id)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+  drop(conn);
+  let product = ProductFactory::new()
+    .name("Claw hammer")
+    .category(category)
+    .create(&app, &workspace, &user)
+    .await;
+  let search = async |words: &str| {
+    let (_, body) = app
+      .call(http::Method::GET, &format!("/api/v1/search?q={words}"), &user, workspace.id, None)
+      .await;
+    names(&body["results"]["products"]).into_iter().map(str::to_string).collect::<Vec<_>>()
+  };
+  assert_eq!(search("hand").await, ["Claw hammer"], "the category name is indexed");
+
+  let uri = format!("/api/v1/products/{}", product.id);
+  let (status, body) = app
+    .call(http::Method::PATCH, &uri, &user, workspace.id, Some(json!({ "name": "Mallet" })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert!(search("claw").await.is_empty());
+  assert_eq!(search("mallet").await, ["Mallet"]);
+
+  // Renaming the category leaves its products to the refresher, which the rename notifies
+  let mut conn = app.db.acquire().await.unwrap();
+  sqlx::query("UPDATE product_categories SET name = 'Garden tools' WHERE id = $1")
+    .bind(category)
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+  drop(conn);
+  assert!(search("garden").await.is_empty());
+
+  let mut conn = app.db.acquire().await.unwrap();
+  let refreshed: i32 = sqlx::query_scalar("SELECT refresh_product_search_vectors('product_categories', $1, NULL)")
+    .bind(category)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+  drop(conn);
+  assert_eq!(refreshed, 1);
+  assert_eq!(search("garden").await, ["Mallet"]);
+  assert!(search("hand").await.is_empty());
+}
@@ -0,0 +1,102 @@
+//! Secrets loaded from Vault at startup and refreshed while running.
+
+use std::{
+  collections::HashMap,
+  future::IntoFuture,
+  sync::{Arc, Mutex},
+};
+
+use axum::{
+  Json, Router,
+  extract::State,
+  http::{HeaderMap, StatusCode},
+  routing::get,
+};
+use myapp_api_rust::{
+  config::{SecretsBackend, SecretsConfig},
+  modules::auth::jwt_middleware::decode_access_token,
+  secrets::{self, SecretsRefresher},
+};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+
+use crate::common::{TestApp, fixtures::UserFactory};
+
+mod common;
+
+const VAULT_TOKEN: &str = "test-vault-token";
+
+type VaultSecret = Arc<Mutex<HashMap<String, String>>>;
+
+/// Serves `secret` at `secret/myapp` of a KV version 2 engine, like Vault does.
+async fn fake_vault(secret: VaultSecret) -> SecretsConfig {
+  async fn read(State(secret): State<VaultSecret>, headers: HeaderMap) -> Result<Json<Value>, StatusCode> {
+    if headers.get("x-vault-token").is_none_or(|token| token != VAULT_TOKEN) {
+      return Err(StatusCode::FORBIDDEN);
+    }
+    let data = secret.lock().unwrap().clone();
+    Ok(Json(json!({ "data": { "data": data, "metadata": { "version": 1 } } })))
+  }
+
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let addr = listener.local_addr().unwrap();
+  let router = Router::new().route("/v1/secret/data/myapp", get(read)).with_state(secret);
+  tokio::spawn(axum::serve(listener, router).into_future());
+
+  SecretsConfig {
+    backend: SecretsBackend::Vault,
+    vault_addr: Some(format!("http://{}", addr)),
+    vault_token: Some(VAULT_TOKEN.to_string()),
+    ..SecretsConfig::default()
+  }
+}
+
+#[tokio::test]
+async fn test_secrets_are_loaded_from_vault_and_fall_back_to_the_environment() {
+  let secret = VaultSecret::default();
+  secret.lock().unwrap().insert("JWT_SECRET".to_string(), "from-vault".to_string());
+  let config = fake_vault(secret).await;
+
+  let provider = secrets::provider(&config).unwrap();
+  let loaded = secrets::load(provider.as_ref()).await.unwrap();
+  assert_eq!(loaded.jwt_secret, "from-vault");
+  assert_eq!(
+    loaded.database_url,
+    std::env::var("DATABASE_URL").unwrap(),
+    "not in Vault, read from the environment"
+  );
+
+  let config = SecretsConfig {
+    vault_token: Some("wrong".to_string()),
+    ..config
+  };
+  let provider = secrets::provider(&config).unwrap();
+  assert!(secrets::load(provider.as_ref()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_refreshed_jwt_secret_keeps_older_tokens_valid() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let original = app.state.jwt_keys.current();
+
+  let secret = VaultSecret::default();
+  secret.lock().unwrap().insert("JWT_SECRET".to_string(), original.clone());
+  let config = fake_vault(secret.clone()).await;
+  let mut refresher = SecretsRefresher::new(secrets::provider(&config).unwrap());
+
+  refresher.refresh(&app.state).await.unwrap();
+  assert_eq!(app.state.jwt_keys.current(), original, "an unchanged secret is not rotated");
+
+  secret.lock().unwrap().insert("JWT_SECRET".to_string(), "rotated-secret".to_string());
+  refresher.refresh(&app.state).await.unwrap();
+  assert_eq!(app.state.jwt_keys.current(), "rotated-secret");
+  assert_eq!(app.state.jwt_keys.previous().as_deref(), Some(original.as_str()));
+  assert!(
+    decode_access_token(&app.state, &user.token).is_ok(),
+    "tokens signed before the rotation are still accepted"
+  );
+
+  let rotated = UserFactory::new().create(&app).await;
+  assert!(decode_access_token(&app.state, &rotated.token).is_ok());
+}
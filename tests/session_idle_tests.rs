@@ -0,0 +1,54 @@
+//! Sessions ending after a period of inactivity, before their token expires.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::{config::AppConfig, modules::auth::jwt_middleware::decode_access_token};
+use serde_json::Value;
+
+use crate::common::{
+  TestApp,
+  fixtures::{TestUser, UserFactory},
+};
+
+mod common;
+
+async fn get_me(app: &TestApp, user: &TestUser) -> (StatusCode, Value) {
+  app.call(http::Method::GET, "/api/v1/auth/me", user, None, None).await
+}
+
+/// Pretends the session of `user`'s token was last used `minutes` ago.
+async fn idle_for(app: &TestApp, user: &TestUser, minutes: i32) {
+  let jti = decode_access_token(&app.state, &user.token).unwrap().jti.unwrap();
+  let mut conn = app.db.acquire().await.unwrap();
+  sqlx::query("UPDATE session_activity SET last_active_at = NOW() - make_interval(mins => $2) WHERE jti = $1")
+    .bind(jti)
+    .bind(minutes)
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_idle_sessions_expire() {
+  let mut config = AppConfig::from_env();
+  config.sessions.idle_timeout_secs = 15 * 60;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let idle = UserFactory::new().create(&app).await;
+  let active = UserFactory::new().create(&app).await;
+
+  for user in [&idle, &active] {
+    let (status, body) = get_me(&app, user).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+  }
+
+  idle_for(&app, &idle, 20).await;
+  idle_for(&app, &active, 10).await;
+
+  let (status, body) = get_me(&app, &idle).await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+  assert_eq!(body["error"], "SESSION_IDLE_TIMEOUT");
+  let (status, _) = get_me(&app, &idle).await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED, "an idle session does not come back");
+
+  let (status, body) = get_me(&app, &active).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+}
@@ -0,0 +1,216 @@
+//! Deleted contacts and products: left out of reads, listed on request and restored.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::{config::AppConfig, modules::retention::retention_purger::purge_expired};
+use serde_json::{Value, json};
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+fn ids(body: &Value) -> Vec<String> {
+  body["results"]["list"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|record| record["id"].as_str().unwrap().to_string())
+    .collect()
+}
+
+#[tokio::test]
+async fn test_deleted_contacts_are_hidden_until_restored() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  let contact = json!({ "code": "SD-1", "name": "Soft Deleted", "email": "soft@example.com", "contact_type": "customer" });
+  let (status, body) = app.call(http::Method::POST, "/api/v1/contacts", &user, workspace.id, Some(contact)).await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  let id = body["results"]["id"].as_str().unwrap().to_string();
+  let uri = format!("/api/v1/contacts/{}", id);
+
+  let (status, body) = app.call(http::Method::DELETE, &uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = app.call(http::Method::GET, &uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+  let (status, body) = app
+    .call(http::Method::PUT, &uri, &user, workspace.id, Some(json!({ "name": "Edited" })))
+    .await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+  let (status, body) = app.call(http::Method::DELETE, &uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+
+  let (_, body) = app.call(http::Method::GET, "/api/v1/contacts", &user, workspace.id, None).await;
+  assert!(!ids(&body).contains(&id), "{body}");
+  let (status, body) = app
+    .call(http::Method::GET, "/api/v1/contacts?include_deleted=true", &user, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let listed = &body["results"]["list"][0];
+  assert_eq!(listed["id"], id);
+  assert!(listed["deleted_at"].is_string(), "{body}");
+
+  let (status, body) = app.call(http::Method::POST, &format!("{}/restore", uri), &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["id"], id);
+  assert_eq!(body["results"]["deleted_at"], Value::Null);
+  let (status, body) = app.call(http::Method::GET, &uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  // Only deleted contacts can be restored
+  let (status, body) = app.call(http::Method::POST, &format!("{}/restore", uri), &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+}
+
+#[tokio::test]
+async fn test_deleted_products_free_their_records_until_restored() {
+  let mut config = AppConfig::from_env();
+  config.usage.free_max_records = 1;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let product = ProductFactory::new().code("SD-P1").create(&app, &workspace, &user).await;
+  let uri = format!("/api/v1/products/{}", product.id);
+
+  let (status, body) = app.call(http::Method::DELETE, &uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let (status, body) = app.call(http::Method::GET, &uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+  let (_, body) = app
+    .call(http::Method::GET, "/api/v1/products?code=SD-P1", &user, workspace.id, None)
+    .await;
+  assert!(ids(&body).is_empty(), "{body}");
+  let (_, body) = app
+    .call(
+      http::Method::GET,
+      "/api/v1/products?code=SD-P1&include_deleted=true",
+      &user,
+      workspace.id,
+      None,
+    )
+    .await;
+  assert_eq!(ids(&body), vec![product.id.to_string()], "{body}");
+
+  // The deleted product no longer counts toward the plan
+  let (_, body) = app.call(http::Method::GET, "/api/v1/limits", &user, workspace.id, None).await;
+  assert_eq!(body["results"]["quotas"]["records"]["used"], 0);
+
+  let (status, body) = app.call(http::Method::POST, &format!("{}/restore", uri), &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["code"], "SD-P1");
+  let (status, body) = app.call(http::Method::GET, &uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  // Restoring takes the place of a new record
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/products",
+      &user,
+      workspace.id,
+      Some(json!({ "code": "SD-P2", "name": "Over the plan", "base_unit": "pcs", "selling_price": 1, "unit_cost": 1 })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::PAYMENT_REQUIRED, "{body}");
+}
+
+#[tokio::test]
+async fn test_deleted_codes_are_restored_until_purged() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  let contact = json!({ "code": "SD-3", "name": "Deleted Code", "email": "deleted@example.com", "contact_type": "customer" });
+  let (_, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", &user, workspace.id, Some(contact.clone()))
+    .await;
+  let id = body["results"]["id"].as_str().unwrap().to_string();
+  app
+    .call(http::Method::DELETE, &format!("/api/v1/contacts/{}", id), &user, workspace.id, None)
+    .await;
+
+  // The deleted contact keeps its code
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", &user, workspace.id, Some(contact.clone()))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+  assert_eq!(body["details"]["code"][0]["code"], "DELETED_CODE");
+  let other = json!({ "code": "SD-4", "name": "Other", "email": "other@example.com", "contact_type": "customer" });
+  let (_, body) = app.call(http::Method::POST, "/api/v1/contacts", &user, workspace.id, Some(other)).await;
+  let other_uri = format!("/api/v1/contacts/{}", body["results"]["id"].as_str().unwrap());
+  let (status, body) = app
+    .call(http::Method::PUT, &other_uri, &user, workspace.id, Some(json!({ "code": "SD-3" })))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+  assert_eq!(body["details"]["code"][0]["code"], "DELETED_CODE");
+
+  let product = ProductFactory::new().code("SD-P3").create(&app, &workspace, &user).await;
+  app
+    .call(
+      http::Method::DELETE,
+      &format!("/api/v1/products/{}", product.id),
+      &user,
+      workspace.id,
+      None,
+    )
+    .await;
+  let payload = json!({ "code": "SD-P3", "name": "Recreated", "base_unit": "pcs", "selling_price": 1, "unit_cost": 1 });
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/products?force=true",
+      &user,
+      workspace.id,
+      Some(payload.clone()),
+    )
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+  assert_eq!(body["details"]["code"][0]["code"], "DELETED_CODE");
+
+  // Purged by their retention policies, the records free their codes
+  let retention = format!("/api/v1/workspaces/{}/retention", workspace.id);
+  for category in ["deleted_contacts", "deleted_products"] {
+    let (status, body) = app
+      .call(
+        http::Method::PUT,
+        &retention,
+        &user,
+        workspace.id,
+        Some(json!({ "category": category, "retain_days": 30 })),
+      )
+      .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+  }
+  let mut conn = app.db.acquire().await.unwrap();
+  for table in ["contacts", "products"] {
+    sqlx::query(&format!(
+      "UPDATE {table} SET deleted_at = NOW() - INTERVAL '31 days' WHERE workspace_id = $1 AND deleted_at IS NOT NULL"
+    ))
+    .bind(workspace.id)
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+  }
+  drop(conn);
+  let (_, body) = app
+    .call(http::Method::GET, &format!("{}/preview", retention), &user, workspace.id, None)
+    .await;
+  let rows: Vec<i64> = body["results"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|preview| preview["rows"].as_i64().unwrap())
+    .collect();
+  assert_eq!(rows, [1, 1], "{body}");
+  assert!(purge_expired(&app.state).await.unwrap() >= 2);
+
+  let (status, body) = app.call(http::Method::POST, "/api/v1/contacts", &user, workspace.id, Some(contact)).await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/products?force=true", &user, workspace.id, Some(payload))
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+}
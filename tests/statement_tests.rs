@@ -0,0 +1,185 @@
+//! Statements of contacts: their invoices, payments and credit notes with a running balance.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::{
+  body::Bytes,
+  http::{self, StatusCode},
+};
+use myapp_api_rust::{
+  AppResult,
+  config::{AppConfig, TenantIsolation},
+  modules::rendering::pdf_renderer::PdfRenderer,
+  utils::tenant_schema,
+};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{TestUser, UserFactory, WorkspaceFactory},
+  request,
+};
+
+mod common;
+
+/// "Prints" the HTML as is, behind a PDF signature.
+struct FakeRenderer;
+
+#[async_trait]
+impl PdfRenderer for FakeRenderer {
+  async fn render(&self, html: &str) -> AppResult<Vec<u8>> {
+    Ok(format!("%PDF-{html}").into_bytes())
+  }
+}
+
+fn json(body: &Bytes) -> Value {
+  serde_json::from_slice(body).unwrap()
+}
+
+fn amount(value: &Value) -> f64 {
+  value.as_f64().unwrap()
+}
+
+/// A contact with an invoice on each of the first three days of March, less a payment and a
+/// credit note. The documents are written as the systems issuing them would, in the tables of
+/// the workspace.
+async fn contact_with_transactions(app: &TestApp, user: &TestUser, workspace_id: Uuid) -> String {
+  let contact = json!({ "code": "ST/1", "name": "Statement Customer", "email": "statement@example.com", "contact_type": "customer" });
+  let (status, _, body) = app
+    .send(request(http::Method::POST, "/api/v1/contacts", user, workspace_id, Some(contact)))
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{}", json(&body));
+  let id = json(&body)["results"]["id"].as_str().unwrap().to_string();
+
+  let mut conn = app.db.acquire().await.unwrap();
+  let routed = tenant_schema::routed_workspace(&mut conn).await.unwrap();
+  tenant_schema::route(&mut conn, Some(workspace_id)).await.unwrap();
+  for (kind, reference, occurred_on, amount) in [
+    ("invoice", "INV-1", "2025-02-20", "100.00"),
+    ("invoice", "INV-2", "2025-03-01", "250.50"),
+    ("payment", "PAY-1", "2025-03-02", "300"),
+    ("credit_note", "CN-1", "2025-03-03", "20.25"),
+  ] {
+    sqlx::query(
+      "INSERT INTO contact_transactions (workspace_id, contact_id, kind, reference, occurred_on, amount) \
+       VALUES ($1, $2::UUID, $3::contact_transaction_kind, $4, $5::DATE, $6::NUMERIC)",
+    )
+    .bind(workspace_id)
+    .bind(&id)
+    .bind(kind)
+    .bind(reference)
+    .bind(occurred_on)
+    .bind(amount)
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+  }
+  tenant_schema::route(&mut conn, routed).await.unwrap();
+  id
+}
+
+#[tokio::test]
+async fn test_statement_runs_the_balance_over_the_period() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let id = contact_with_transactions(&app, &user, workspace.id).await;
+
+  let uri = format!("/api/v1/contacts/{}/statement?from=2025-03-01&to=2025-03-02", id);
+  let (status, _, body) = app.send(request(http::Method::GET, &uri, &user, workspace.id, None)).await;
+  assert_eq!(status, StatusCode::OK, "{}", json(&body));
+  let statement = &json(&body)["results"];
+  assert_eq!(statement["contact_code"], "ST/1");
+  assert_eq!(amount(&statement["opening_balance"]), 100.0);
+  let lines = statement["lines"].as_array().unwrap();
+  assert_eq!(lines.len(), 2, "the credit note is after the period");
+  assert_eq!(lines[0]["reference"], "INV-2");
+  assert_eq!(amount(&lines[0]["debit"]), 250.5);
+  assert_eq!(amount(&lines[0]["balance"]), 350.5);
+  assert_eq!(amount(&lines[1]["credit"]), 300.0);
+  assert_eq!(amount(&lines[1]["balance"]), 50.5);
+  assert_eq!(amount(&statement["closing_balance"]), 50.5);
+
+  // Without a period, from the first transaction to today
+  let uri = format!("/api/v1/contacts/{}/statement", id);
+  let (_, _, body) = app.send(request(http::Method::GET, &uri, &user, workspace.id, None)).await;
+  let statement = &json(&body)["results"];
+  assert_eq!(amount(&statement["opening_balance"]), 0.0);
+  assert_eq!(statement["lines"].as_array().unwrap().len(), 4);
+  assert_eq!(amount(&statement["closing_balance"]), 30.25);
+
+  let uri = format!("/api/v1/contacts/{}/statement?from=2025-03-02&to=2025-03-01", id);
+  let (status, _, _) = app.send(request(http::Method::GET, &uri, &user, workspace.id, None)).await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+  // Contacts of other workspaces are not found
+  let other = WorkspaceFactory::new().create(&app, &user).await;
+  let uri = format!("/api/v1/contacts/{}/statement", id);
+  let (status, _, _) = app.send(request(http::Method::GET, &uri, &user, other.id, None)).await;
+  assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_statement_downloads_as_csv_and_prints_as_pdf() {
+  let app = TestApp::isolated_with(|builder| builder.with_pdf_renderer(Arc::new(FakeRenderer))).await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let id = contact_with_transactions(&app, &user, workspace.id).await;
+
+  let uri = format!("/api/v1/contacts/{}/statement?from=2025-03-01&to=2025-03-31&format=csv", id);
+  let (status, headers, body) = app.send(request(http::Method::GET, &uri, &user, workspace.id, None)).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(headers[http::header::CONTENT_TYPE], "text/csv; charset=utf-8");
+  assert_eq!(
+    headers[http::header::CONTENT_DISPOSITION],
+    "attachment; filename=\"statement-ST_1-20250331.csv\""
+  );
+  let csv = String::from_utf8(body.to_vec()).unwrap();
+  let rows: Vec<&str> = csv.lines().collect();
+  assert_eq!(rows[0], "date,kind,reference,description,debit,credit,balance");
+  assert_eq!(rows[1], "2025-03-01,opening_balance,,,,,100.00");
+  assert_eq!(rows[2], "2025-03-01,invoice,INV-2,,250.50,,350.50");
+  assert_eq!(rows[4], "2025-03-03,credit_note,CN-1,,,20.25,30.25");
+  assert_eq!(rows[5], "2025-03-31,closing_balance,,,250.50,320.25,30.25");
+
+  let uri = format!("/api/v1/contacts/{}/statement?format=pdf", id);
+  let (status, headers, body) = app.send(request(http::Method::GET, &uri, &user, workspace.id, None)).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(headers[http::header::CONTENT_DISPOSITION], "inline; filename=\"statement-ST_1.pdf\"");
+  let pdf = String::from_utf8(body.to_vec()).unwrap();
+  assert!(pdf.starts_with("%PDF-"));
+  assert!(pdf.contains("Statement of Statement Customer"));
+  assert!(pdf.contains("<td>CN-1</td>"));
+
+  let uri = format!("/api/v1/contacts/{}/statement?format=xlsx", id);
+  let (status, _, _) = app.send(request(http::Method::GET, &uri, &user, workspace.id, None)).await;
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_statements_of_isolated_workspaces_come_from_their_schema() {
+  let mut config = AppConfig::from_env();
+  config.database.tenant_isolation = TenantIsolation::Schema;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  // The contact is in the workspace's schema; its transactions reference it there
+  let id = contact_with_transactions(&app, &user, workspace.id).await;
+
+  let uri = format!("/api/v1/contacts/{}/statement", id);
+  let (status, _, body) = app.send(request(http::Method::GET, &uri, &user, workspace.id, None)).await;
+  assert_eq!(status, StatusCode::OK, "{}", json(&body));
+  let statement = &json(&body)["results"];
+  assert_eq!(statement["lines"].as_array().unwrap().len(), 4);
+  assert_eq!(amount(&statement["closing_balance"]), 30.25);
+
+  let mut conn = app.db.acquire().await.unwrap();
+  let shared: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM public.contact_transactions WHERE workspace_id = $1")
+    .bind(workspace.id)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+  assert_eq!(shared, 0);
+}
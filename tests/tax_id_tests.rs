@@ -0,0 +1,116 @@
+//! Tax IDs: the format rules of each country, the validation endpoint and the checks made when
+//! contacts are saved.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::utils::tax_id::{self, TaxIdError, TaxIdKind};
+use serde_json::json;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+#[test]
+fn test_tax_ids_follow_the_rules_of_their_country() {
+  let npwp = tax_id::parse("012345678901000", None).unwrap();
+  assert_eq!(npwp.country, "ID");
+  assert_eq!(npwp.kind, TaxIdKind::Npwp);
+  assert_eq!(npwp.formatted, "01.234.567.8-901.000");
+  assert_eq!(tax_id::parse("01.234.567.8-901.000", None).unwrap().number, "012345678901000");
+  assert_eq!(tax_id::parse("3171012345678901", None).unwrap().formatted, "3171012345678901");
+  assert_eq!(tax_id::parse("01.234.567.8-901", None), Err(TaxIdError::InvalidNpwp));
+
+  let vat = tax_id::parse("de 123 456 789", None).unwrap();
+  assert_eq!(
+    (vat.country.as_str(), vat.kind, vat.formatted.as_str()),
+    ("DE", TaxIdKind::Vat, "DE123456789")
+  );
+  assert_eq!(tax_id::parse("123456789", Some("de")).unwrap().formatted, "DE123456789");
+  assert_eq!(tax_id::parse("EL123456789", None).unwrap().country, "GR");
+  assert_eq!(tax_id::parse("NL123456789B01", None).unwrap().number, "123456789B01");
+  assert_eq!(tax_id::parse("ESX1234567R", None).unwrap().formatted, "ESX1234567R");
+  assert_eq!(tax_id::parse("DE12345678", None), Err(TaxIdError::InvalidVat("DE".to_string())));
+  assert_eq!(tax_id::parse("123", Some("US")), Err(TaxIdError::UnsupportedCountry("US".to_string())));
+  assert_eq!(tax_id::parse(" - ", None), Err(TaxIdError::Empty));
+}
+
+#[tokio::test]
+async fn test_validate_tax_id_endpoint() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let uri = "/api/v1/contacts/validate-tax-id";
+
+  let (status, body) = app
+    .call(http::Method::POST, uri, &user, workspace.id, Some(json!({ "tax_id": "012345678901000" })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["valid"], true);
+  assert_eq!(body["results"]["kind"], "npwp");
+  assert_eq!(body["results"]["formatted"], "01.234.567.8-901.000");
+
+  let request = json!({ "tax_id": "FR12345", "country": "FR" });
+  let (status, body) = app.call(http::Method::POST, uri, &user, workspace.id, Some(request)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["valid"], false);
+  assert_eq!(body["results"]["error"], "Not a valid VAT number of country FR");
+
+  let (status, _) = app
+    .call(http::Method::POST, uri, &user, workspace.id, Some(json!({ "tax_id": "" })))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_contacts_are_saved_with_valid_tax_ids_only() {
+  let app = TestApp::isolated().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let contact = |tax_id: &str| {
+    json!({
+      "code": "",
+      "name": "PT Sumber Makmur",
+      "email": "finance@sumbermakmur.co.id",
+      "contact_type": "supplier",
+      "tax_id": tax_id,
+    })
+  };
+
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", &user, workspace.id, Some(contact("12.345")))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+  assert!(body.to_string().contains("An NPWP has 15 or 16 digits"), "{body}");
+
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/contacts",
+      &user,
+      workspace.id,
+      Some(contact("01.234.567.8-901.000")),
+    )
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+  let uri = format!("/api/v1/contacts/{}", body["results"]["id"].as_str().unwrap());
+
+  let (status, _) = app
+    .call(http::Method::PATCH, &uri, &user, workspace.id, Some(json!({ "tax_id": "DE1234" })))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let (status, _) = app
+    .call(http::Method::PUT, &uri, &user, workspace.id, Some(json!({ "tax_id": "DE1234" })))
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let (status, body) = app
+    .call(http::Method::PATCH, &uri, &user, workspace.id, Some(json!({ "tax_id": "DE123456789" })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["tax_id"], "DE123456789");
+  let (status, body) = app
+    .call(http::Method::PATCH, &uri, &user, workspace.id, Some(json!({ "tax_id": null })))
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+}
@@ -0,0 +1,284 @@
+//! Schema-per-workspace isolation: the routing of queries, the move of existing rows, the rebuild
+//! of schemas after migrations and the work going over workspaces outside of a request.
+
+use axum::http::{self, StatusCode};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, TestUser, UserFactory, WorkspaceFactory},
+};
+use myapp_api_rust::{
+  config::{AppConfig, TenantIsolation},
+  modules::retention::retention_purger::purge_expired,
+  utils::{db_session, tenant_schema},
+};
+
+mod common;
+
+async fn schema_app() -> TestApp {
+  let mut config = AppConfig::from_env();
+  config.database.tenant_isolation = TenantIsolation::Schema;
+  TestApp::isolated_with(|builder| builder.with_config(config)).await
+}
+
+async fn create_product(app: &TestApp, user: &TestUser, workspace_id: Uuid, code: &str) {
+  let product = json!({ "code": code, "name": code, "base_unit": "pcs", "selling_price": 10, "unit_cost": 1 });
+  let (status, body) = app.call(http::Method::POST, "/api/v1/products", user, workspace_id, Some(product)).await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+}
+
+async fn product_codes(app: &TestApp, user: &TestUser, workspace_id: Uuid) -> Vec<String> {
+  let (status, body) = app.call(http::Method::GET, "/api/v1/products", user, workspace_id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  body["results"]["list"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|product| product["code"].as_str().unwrap().to_string())
+    .collect()
+}
+
+async fn count(app: &TestApp, table: &str, code: &str) -> i64 {
+  let mut conn = app.db.acquire().await.unwrap();
+  sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table} WHERE code = $1"))
+    .bind(code)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_workspace_rows_live_in_their_own_schema() {
+  let app = schema_app().await;
+  let alice = UserFactory::new().create(&app).await;
+  let bob = UserFactory::new().create(&app).await;
+  let first = WorkspaceFactory::new().create(&app, &alice).await;
+  let second = WorkspaceFactory::new().create(&app, &bob).await;
+
+  create_product(&app, &alice, first.id, "P-1").await;
+  create_product(&app, &bob, second.id, "P-2").await;
+
+  let first_schema = tenant_schema::schema_name(first.id);
+  assert_eq!(count(&app, &format!("{first_schema}.products"), "P-1").await, 1);
+  assert_eq!(count(&app, "public.products", "P-1").await, 0);
+  assert_eq!(count(&app, &format!("{first_schema}.products"), "P-2").await, 0);
+  assert_eq!(product_codes(&app, &alice, first.id).await, ["P-1"]);
+  assert_eq!(product_codes(&app, &bob, second.id).await, ["P-2"]);
+
+  let (status, body) = app
+    .call(http::Method::DELETE, &format!("/api/v1/workspaces/{}", first.id), &alice, first.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let mut conn = app.db.acquire().await.unwrap();
+  let exists: bool = sqlx::query_scalar("SELECT to_regnamespace($1) IS NOT NULL")
+    .bind(&first_schema)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+  assert!(!exists, "the schema goes with its workspace");
+}
+
+#[tokio::test]
+async fn test_existing_rows_move_into_the_schema() {
+  let app = schema_app().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  // A workspace from before the isolation keeps using the shared tables
+  let mut conn = app.db.acquire().await.unwrap();
+  tenant_schema::drop_schema(&mut conn, workspace.id).await.unwrap();
+  drop(conn);
+  create_product(&app, &user, workspace.id, "P-1").await;
+  assert_eq!(count(&app, "public.products", "P-1").await, 1);
+
+  let mut conn = app.db.acquire().await.unwrap();
+  tenant_schema::provision(&mut conn, workspace.id).await.unwrap();
+  let moved = tenant_schema::move_rows(&mut conn, workspace.id).await.unwrap();
+  drop(conn);
+  assert_eq!(moved, 1);
+
+  let schema = tenant_schema::schema_name(workspace.id);
+  assert_eq!(count(&app, "public.products", "P-1").await, 0);
+  assert_eq!(count(&app, &format!("{schema}.products"), "P-1").await, 1);
+  assert_eq!(product_codes(&app, &user, workspace.id).await, ["P-1"]);
+}
+
+#[tokio::test]
+async fn test_migrate_rebuilds_schemas_older_than_the_last_migration() {
+  let app = schema_app().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  create_product(&app, &user, workspace.id, "P-1").await;
+  let schema = tenant_schema::schema_name(workspace.id);
+
+  // A schema built before the soft delete and search migrations, which only changed `public`
+  let mut conn = app.db.acquire().await.unwrap();
+  for statement in [
+    format!("ALTER TABLE {schema}.products DROP COLUMN deleted_at"),
+    format!("DROP TRIGGER products_search_vector ON {schema}.products"),
+    format!("COMMENT ON SCHEMA {schema} IS '1'"),
+    // As recorded by `migrate` when the migrations were not applied through it
+    "CREATE TABLE IF NOT EXISTS public._sqlx_migrations (version BIGINT PRIMARY KEY, description TEXT NOT NULL, \
+     installed_on TIMESTAMPTZ NOT NULL DEFAULT NOW(), success BOOLEAN NOT NULL, checksum BYTEA NOT NULL, execution_time BIGINT NOT NULL)"
+      .to_string(),
+    "INSERT INTO public._sqlx_migrations (version, description, success, checksum, execution_time) \
+     VALUES (99991231000000, 'test', true, '', 0) ON CONFLICT DO NOTHING"
+      .to_string(),
+  ] {
+    sqlx::query(&statement).execute(&mut *conn).await.unwrap();
+  }
+
+  assert_eq!(tenant_schema::migrate(&mut conn).await.unwrap(), [workspace.id]);
+  assert!(tenant_schema::migrate(&mut conn).await.unwrap().is_empty(), "rebuilt once");
+  let (columns, triggers): (i64, i64) = sqlx::query_as(
+    r#"
+      SELECT
+        (SELECT COUNT(*) FROM information_schema.columns WHERE table_schema = $1 AND table_name = 'products' AND column_name = 'deleted_at'),
+        (SELECT COUNT(*) FROM pg_trigger WHERE tgrelid = to_regclass($1 || '.products') AND tgname = 'products_search_vector')
+      "#,
+  )
+  .bind(&schema)
+  .fetch_one(&mut *conn)
+  .await
+  .unwrap();
+  drop(conn);
+  assert_eq!((columns, triggers), (1, 1));
+
+  // The rows survive the rebuild, and the new column works for them
+  assert_eq!(count(&app, &format!("{schema}.products"), "P-1").await, 1);
+  assert_eq!(product_codes(&app, &user, workspace.id).await, ["P-1"]);
+  let (status, body) = app
+    .call(http::Method::GET, "/api/v1/products?search=P-1", &user, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["pagination"]["total"], 1, "search vectors kept: {body}");
+}
+
+#[tokio::test]
+async fn test_overview_and_platform_statistics_count_isolated_workspaces() {
+  let app = schema_app().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let other = WorkspaceFactory::new().create(&app, &user).await;
+  // Created as a background job would, routed to the workspace's schema
+  for workspace in [&workspace, &workspace, &other] {
+    db_session::workspace_scope(
+      &app.state.db,
+      workspace.id,
+      ProductFactory::new().with_low_stock().create(&app, workspace, &user),
+    )
+    .await
+    .unwrap();
+  }
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/overview", &user, None, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let low_stock = |id: Uuid| {
+    body["results"]["workspaces"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .find(|overview| overview["workspace_id"] == id.to_string())
+      .unwrap()["low_stock_products"]
+      .clone()
+  };
+  assert_eq!(low_stock(workspace.id), 2);
+  assert_eq!(low_stock(other.id), 1);
+
+  let mut conn = app.db.acquire().await.unwrap();
+  tenant_schema::route(&mut conn, Some(workspace.id)).await.unwrap();
+  sqlx::query(
+    "INSERT INTO contacts (code, name, email, position, type, workspace_id, created_by) \
+     SELECT 'ISO-' || n, 'Contact ' || n, 'iso' || n || '@example.com', 'Buyer', 'customer', $1, $2 FROM generate_series(1, 6000) n",
+  )
+  .bind(workspace.id)
+  .bind(user.id())
+  .execute(&mut *conn)
+  .await
+  .unwrap();
+  tenant_schema::route(&mut conn, None).await.unwrap();
+  drop(conn);
+  let admin = UserFactory::new().create(&app).await;
+  app.state.auth_repository.set_superadmin(&admin.user.email, true).await.unwrap();
+
+  let (status, body) = app.call(http::Method::GET, "/api/v1/admin/stats?top=1", &admin, None, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let top = &body["results"]["top_workspaces"][0];
+  assert_eq!(top["workspace_id"], workspace.id.to_string(), "{body}");
+  assert_eq!((top["contacts"].clone(), top["products"].clone()), (json!(6000), json!(2)));
+}
+
+#[tokio::test]
+async fn test_retention_purges_the_rows_of_isolated_workspaces() {
+  let app = schema_app().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  create_product(&app, &user, workspace.id, "P-1").await;
+  let schema = tenant_schema::schema_name(workspace.id);
+
+  let uri = format!("/api/v1/workspaces/{}/retention", workspace.id);
+  let policy = json!({ "category": "deleted_products", "retain_days": 30 });
+  let (status, body) = app.call(http::Method::PUT, &uri, &user, workspace.id, Some(policy)).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let mut conn = app.db.acquire().await.unwrap();
+  sqlx::query(&format!("UPDATE {schema}.products SET deleted_at = NOW() - INTERVAL '31 days'"))
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+  drop(conn);
+
+  assert_eq!(purge_expired(&app.state).await.unwrap(), 1);
+  assert_eq!(count(&app, &format!("{schema}.products"), "P-1").await, 0);
+}
+
+#[tokio::test]
+async fn test_search_vectors_are_refreshed_in_the_schema_of_the_renamed_row() {
+  let app = schema_app().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  let mut conn = app.db.acquire().await.unwrap();
+  tenant_schema::route(&mut conn, Some(workspace.id)).await.unwrap();
+  let category: Uuid = sqlx::query_scalar("INSERT INTO product_categories (code, name, workspace_id) VALUES ('CAT-1', 'Hammers', $1) RETURNING id")
+    .bind(workspace.id)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+  tenant_schema::route(&mut conn, None).await.unwrap();
+  drop(conn);
+  let product = ProductFactory::new().category(category).create(&app, &workspace, &user);
+  db_session::workspace_scope(&app.state.db, workspace.id, product).await.unwrap();
+
+  let mut conn = app.db.acquire().await.unwrap();
+  tenant_schema::route(&mut conn, Some(workspace.id)).await.unwrap();
+  sqlx::query("UPDATE product_categories SET name = 'Mallets' WHERE id = $1")
+    .bind(category)
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+  tenant_schema::route(&mut conn, None).await.unwrap();
+  // As the refresher does on the rename's notification, from an unrouted connection
+  let refresh = "SELECT refresh_product_search_vectors('product_categories', $1, $2)";
+  let shared: i32 = sqlx::query_scalar(refresh)
+    .bind(category)
+    .bind(None::<Uuid>)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+  let routed: i32 = sqlx::query_scalar(refresh)
+    .bind(category)
+    .bind(workspace.id)
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+  drop(conn);
+  assert_eq!((shared, routed), (0, 1));
+
+  let (status, body) = app
+    .call(http::Method::GET, "/api/v1/products?search=Mallets", &user, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["pagination"]["total"], 1, "{body}");
+}
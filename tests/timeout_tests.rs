@@ -0,0 +1,58 @@
+//! Slow requests and load: requests running past `REQUEST_TIMEOUT_SECS` are aborted with a 504,
+//! and requests arriving while `MAX_CONCURRENT_REQUESTS` are in flight are shed with a 503.
+//!
+//! A request is kept in flight by holding the test's database connection, which every
+//! authenticated request waits for.
+
+use std::time::Duration;
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::config::AppConfig;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_requests_past_the_timeout_are_aborted() {
+  let mut config = AppConfig::from_env();
+  config.server.request_timeout_secs = 1;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  let held = app.db.acquire().await.unwrap();
+  let (status, body) = app.call(http::Method::GET, "/api/v1/workspaces", &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::GATEWAY_TIMEOUT, "{body}");
+  assert_eq!(body["error"], "REQUEST_TIMEOUT");
+  drop(held);
+
+  let (status, _) = app.call(http::Method::GET, "/api/v1/workspaces", &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_requests_over_the_concurrency_limit_are_shed() {
+  let mut config = AppConfig::from_env();
+  config.server.max_concurrent_requests = 1;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+
+  let held = app.db.acquire().await.unwrap();
+  let in_flight = app.call(http::Method::GET, "/api/v1/workspaces", &user, workspace.id, None);
+  let shed = async {
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let response = app.call(http::Method::GET, "/api/v1/workspaces", &user, workspace.id, None).await;
+    drop(held);
+    response
+  };
+  let ((in_flight, _), (status, body)) = tokio::join!(in_flight, shed);
+
+  assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{body}");
+  assert_eq!(body["error"], "SERVICE_OVERLOADED");
+  assert_eq!(in_flight, StatusCode::OK, "the request holding the slot completes");
+}
@@ -0,0 +1,91 @@
+//! Workspace trials: the expiry warning and notice, and the read-only mode of expired workspaces.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::modules::trial::trial_notifier::notify_trials;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+async fn set_trial_end(app: &TestApp, workspace_id: Uuid, interval: &str) {
+  let mut conn = app.db.acquire().await.unwrap();
+  sqlx::query("UPDATE workspace_trials SET started_at = NOW() - INTERVAL '14 days', ends_at = NOW() + $2::interval WHERE workspace_id = $1")
+    .bind(workspace_id)
+    .bind(interval)
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+}
+
+/// Sends the due trial notifications and returns the titles of those sent to `workspace_id`.
+async fn notifications(app: &TestApp, workspace_id: Uuid) -> Vec<String> {
+  let mut receiver = app.state.events.subscribe();
+  notify_trials(&app.state).await.unwrap();
+  let mut titles = Vec::new();
+  while let Ok(event) = receiver.try_recv() {
+    if event.event == "notification" && event.workspace_id == workspace_id {
+      titles.push(event.data["title"].as_str().unwrap().to_string());
+    }
+  }
+  titles
+}
+
+fn contact(code: &str) -> Option<Value> {
+  Some(json!({ "code": code, "name": "Trial Contact", "email": "trial@example.com", "position": "Buyer", "contact_type": "customer" }))
+}
+
+#[tokio::test]
+async fn test_expired_trial_makes_the_workspace_read_only() {
+  let app = TestApp::isolated().await;
+  let owner = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &owner).await;
+  let trial_uri = format!("/api/v1/workspaces/{}/trial", workspace.id);
+
+  let (status, body) = app.call(http::Method::GET, &trial_uri, &owner, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(body["results"]["state"], "trialing");
+  assert_eq!(body["results"]["days_remaining"], 13);
+  assert!(notifications(&app, workspace.id).await.is_empty());
+
+  // Warned once when the end comes near
+  set_trial_end(&app, workspace.id, "1 day").await;
+  assert_eq!(notifications(&app, workspace.id).await, ["Your trial ends soon"]);
+  assert!(notifications(&app, workspace.id).await.is_empty());
+  let (status, _) = app
+    .call(http::Method::POST, "/api/v1/contacts", &owner, workspace.id, contact("TRIAL-1"))
+    .await;
+  assert_eq!(status, StatusCode::CREATED);
+
+  set_trial_end(&app, workspace.id, "-1 minute").await;
+  assert_eq!(notifications(&app, workspace.id).await, ["Your trial has ended"]);
+
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", &owner, workspace.id, contact("TRIAL-2"))
+    .await;
+  assert_eq!(status, StatusCode::PAYMENT_REQUIRED);
+  assert_eq!(body["error"], "TRIAL_EXPIRED");
+
+  // Data stays readable, and signing in is not blocked
+  let (status, body) = app.call(http::Method::GET, "/api/v1/contacts", &owner, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(body["results"]["pagination"]["total"], 1);
+  let (status, body) = app.call(http::Method::GET, &trial_uri, &owner, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+  assert_eq!(body["results"]["state"], "expired");
+  assert_eq!(body["results"]["days_remaining"], 0);
+  let (status, _) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/auth/switch-workspace",
+      &owner,
+      workspace.id,
+      Some(json!({ "workspace_id": workspace.id })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK);
+}
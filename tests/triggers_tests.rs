@@ -0,0 +1,133 @@
+//! Polling triggers for new or updated records.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::{config::AppConfig, modules::datastores::workspaces::WorkspaceRole};
+use serde_json::Value;
+
+use crate::common::{
+  TestApp,
+  fixtures::{ProductFactory, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+fn record_ids(page: &Value) -> Vec<&str> {
+  page["items"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|item| item["record_id"].as_str().unwrap())
+    .collect()
+}
+
+async fn settled_app() -> TestApp {
+  let mut config = AppConfig::from_env();
+  config.triggers.settle_secs = 0;
+  TestApp::isolated_with(|builder| builder.with_config(config)).await
+}
+
+#[tokio::test]
+async fn test_changes_are_paged_by_cursor() {
+  let app = settled_app().await;
+  let user = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  let other = WorkspaceFactory::new().create(&app, &user).await;
+  let first = ProductFactory::new().create(&app, &workspace, &user).await;
+  let second = ProductFactory::new().create(&app, &workspace, &user).await;
+  ProductFactory::new().create(&app, &other, &user).await;
+
+  // Products created in one transaction share their timestamp, so make the first an older record updated since,
+  // with the trigger stamping `updated_at` switched off for the update
+  let mut conn = app.db.acquire().await.unwrap();
+  sqlx::query("SET LOCAL session_replication_role = replica")
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+  sqlx::query("UPDATE products SET created_at = created_at - INTERVAL '1 day', updated_at = updated_at - INTERVAL '1 hour' WHERE id = $1")
+    .bind(first.id)
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+  sqlx::query("SET LOCAL session_replication_role = DEFAULT")
+    .execute(&mut *conn)
+    .await
+    .unwrap();
+  drop(conn);
+
+  let uri = "/api/v1/triggers/products/new-or-updated";
+  let (status, body) = app.call(http::Method::GET, uri, &user, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let page = &body["results"];
+  let (first_id, second_id) = (first.id.to_string(), second.id.to_string());
+  assert_eq!(record_ids(page), [second_id.as_str(), first_id.as_str()], "newest first");
+  assert_eq!(page["items"][0]["event"], "created");
+  assert_eq!(page["items"][1]["event"], "updated");
+  assert_eq!(page["items"][1]["record"]["code"], first.code.as_str());
+  assert_eq!(page["next_cursor"], page["items"][0]["cursor"]);
+
+  // From an hour and a half ago, one change at a time
+  let since = (chrono::Utc::now() - chrono::Duration::minutes(90)).to_rfc3339_opts(chrono::SecondsFormat::Micros, true);
+  let (status, body) = app
+    .call(
+      http::Method::GET,
+      &format!("{uri}?since={}&limit=1", since.replace('+', "%2B")),
+      &user,
+      workspace.id,
+      None,
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(record_ids(&body["results"]), [first_id.as_str()]);
+  assert_eq!(body["results"]["has_more"], true);
+  let cursor = body["results"]["next_cursor"].as_str().unwrap().to_string();
+
+  let (_, body) = app
+    .call(http::Method::GET, &format!("{uri}?since={cursor}&limit=1"), &user, workspace.id, None)
+    .await;
+  assert_eq!(record_ids(&body["results"]), [second_id.as_str()]);
+  assert_eq!(body["results"]["has_more"], false);
+  let cursor = body["results"]["next_cursor"].as_str().unwrap().to_string();
+
+  let (_, body) = app
+    .call(http::Method::GET, &format!("{uri}?since={cursor}"), &user, workspace.id, None)
+    .await;
+  assert!(record_ids(&body["results"]).is_empty());
+  assert_eq!(body["results"]["next_cursor"], cursor.as_str(), "an empty poll keeps the cursor");
+}
+
+#[tokio::test]
+async fn test_invalid_polls_are_rejected() {
+  let app = settled_app().await;
+  let user = UserFactory::new().create(&app).await;
+  let viewer = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&viewer, WorkspaceRole::Viewer).create(&app, &user).await;
+
+  let (status, _) = app
+    .call(http::Method::GET, "/api/v1/triggers/invoices/new-or-updated", &user, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let (status, _) = app
+    .call(
+      http::Method::GET,
+      "/api/v1/triggers/products/new-or-updated?since=yesterday",
+      &user,
+      workspace.id,
+      None,
+    )
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let (status, _) = app
+    .call(
+      http::Method::GET,
+      "/api/v1/triggers/products/new-or-updated?limit=500",
+      &user,
+      workspace.id,
+      None,
+    )
+    .await;
+  assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+  let (status, _) = app
+    .call(http::Method::GET, "/api/v1/triggers/products/new-or-updated", &viewer, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,56 @@
+//! API usage metering: requests counted per route and user, flushed in batches, and the monthly
+//! quota of the free plan.
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::config::AppConfig;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_requests_are_metered_and_limited_by_the_plan_quota() {
+  let mut config = AppConfig::from_env();
+  config.usage.free_monthly_requests = 2;
+  let app = TestApp::isolated_with(|builder| builder.with_config(config)).await;
+  let owner = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &owner).await;
+
+  for _ in 0..2 {
+    let (status, _) = app.call(http::Method::GET, "/api/v1/contacts", &owner, workspace.id, None).await;
+    assert_eq!(status, StatusCode::OK);
+  }
+  let (status, body) = app.call(http::Method::GET, "/api/v1/contacts", &owner, workspace.id, None).await;
+  assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+  assert_eq!(body["error"], "QUOTA_EXCEEDED");
+  assert_eq!(body["details"]["limit"], 2);
+
+  // Workspace management stays available, so the usage can be checked and the plan upgraded
+  let workspace_uri = format!("/api/v1/workspaces/{}", workspace.id);
+  let (status, _) = app.call(http::Method::GET, &workspace_uri, &owner, workspace.id, None).await;
+  assert_eq!(status, StatusCode::OK);
+
+  let flushed = app.state.usage_meter.flush(app.state.usage_repository.as_ref()).await.unwrap();
+  assert_eq!(flushed, 2, "one row per route");
+
+  let (status, body) = app
+    .call(http::Method::GET, &format!("{}/usage/api", workspace_uri), &owner, workspace.id, None)
+    .await;
+  assert_eq!(status, StatusCode::OK);
+  let usage = &body["results"];
+  assert_eq!(usage["totals"]["requests"], 3, "the rejected request is not counted");
+  let contacts = usage["by_route"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .find(|route| route["route"] == "/api/v1/contacts")
+    .expect("contacts route metered");
+  assert_eq!(contacts["requests"], 2);
+  assert!(contacts["response_bytes"].as_i64().unwrap() > 0);
+  assert_eq!(usage["by_user"][0]["user_id"], owner.id().to_string());
+  assert_eq!(usage["quota"]["plan"], "free");
+  assert_eq!(usage["quota"]["limit"], 2);
+}
@@ -0,0 +1,96 @@
+//! Personal data exports: the archive, its download and the email sent once it is ready.
+
+use std::{
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::http::{self, StatusCode};
+use myapp_api_rust::{
+  AppResult,
+  mailer::{EmailMessage, Mailer},
+};
+use serde_json::Value;
+
+use crate::common::{
+  TestApp,
+  fixtures::{UserFactory, WorkspaceFactory},
+  request,
+};
+
+mod common;
+
+#[derive(Default)]
+struct RecordingMailer {
+  sent: Mutex<Vec<EmailMessage>>,
+}
+
+#[async_trait]
+impl Mailer for RecordingMailer {
+  async fn send(&self, message: EmailMessage) -> AppResult<()> {
+    self.sent.lock().unwrap().push(message);
+    Ok(())
+  }
+}
+
+#[tokio::test]
+async fn test_export_is_built_emailed_and_downloaded() {
+  let mailer = Arc::new(RecordingMailer::default());
+  let app = TestApp::isolated_with(|builder| builder.with_mailer(mailer.clone())).await;
+  let user = UserFactory::new().create(&app).await;
+  let other = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &user).await;
+  {
+    let mut conn = app.db.acquire().await.unwrap();
+    sqlx::query("INSERT INTO contacts (code, name, email, type, workspace_id, created_by) VALUES ('EXP-1', 'Exported', 'exported@example.com', 'customer', $1, $2)")
+      .bind(workspace.id)
+      .bind(user.id())
+      .execute(&mut *conn)
+      .await
+      .unwrap();
+  }
+
+  let (status, body) = app.call(http::Method::POST, "/api/v1/auth/me/export", &user, None, None).await;
+  assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+  assert_eq!(body["results"]["status"], "pending");
+  let id = body["results"]["id"].as_str().unwrap().to_string();
+  let uri = format!("/api/v1/auth/me/exports/{id}");
+
+  // Built in the background
+  let mut export = Value::Null;
+  for _ in 0..100 {
+    let (status, body) = app.call(http::Method::GET, &uri, &user, None, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    export = body["results"].clone();
+    if export["status"] != "pending" {
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(20)).await;
+  }
+  assert_eq!(export["status"], "ready", "{export}");
+  assert!(export["expires_at"].is_string());
+
+  let (status, _) = app.call(http::Method::GET, &uri, &other, None, None).await;
+  assert_eq!(status, StatusCode::NOT_FOUND, "exports are private to their user");
+
+  let (status, headers, archive) = app.send(request(http::Method::GET, &format!("{uri}/download"), &user, None, None)).await;
+  let archive: Value = serde_json::from_slice(&archive).unwrap();
+  assert_eq!(status, StatusCode::OK, "{archive}");
+  assert!(headers[http::header::CONTENT_DISPOSITION].to_str().unwrap().starts_with("attachment;"));
+  assert_eq!(archive["profile"]["email"], user.user.email.as_str());
+  assert!(archive["profile"].get("password_hash").is_none());
+  let memberships = archive["memberships"].as_array().unwrap();
+  assert!(
+    memberships
+      .iter()
+      .any(|m| m["workspace_id"] == workspace.id.to_string() && m["is_owner"] == true)
+  );
+  let records = archive["created_records"].as_array().unwrap();
+  assert!(records.iter().any(|r| r["kind"] == "contacts" && r["code"] == "EXP-1"));
+
+  let sent = mailer.sent.lock().unwrap();
+  assert_eq!(sent.len(), 1);
+  assert_eq!(sent[0].to, user.user.email);
+  assert!(sent[0].body.contains(&id), "the email links to the export: {}", sent[0].body);
+}
@@ -0,0 +1,136 @@
+//! Workspace claims: the workspace and role embedded in access tokens by the workspace switch.
+
+use std::time::Duration;
+
+use axum::http::{self, StatusCode};
+use myapp_api_rust::{config::AppConfig, modules::datastores::workspaces::WorkspaceRole};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::common::{
+  TestApp,
+  fixtures::{TestUser, UserFactory, WorkspaceFactory},
+};
+
+mod common;
+
+/// How long the claims of these tests are trusted.
+const CLAIM_TTL_SECS: u64 = 2;
+
+async fn claims_app() -> TestApp {
+  let mut config = AppConfig::from_env();
+  config.role_cache.claim_ttl_secs = CLAIM_TTL_SECS;
+  TestApp::isolated_with(|builder| builder.with_config(config)).await
+}
+
+/// Switches to `workspace_id` and returns the token carrying the claim.
+async fn switch(app: &TestApp, user: &TestUser, workspace_id: Uuid) -> (StatusCode, Value) {
+  app
+    .call(
+      http::Method::POST,
+      "/api/v1/auth/switch-workspace",
+      user,
+      None,
+      Some(json!({ "workspace_id": workspace_id })),
+    )
+    .await
+}
+
+fn contact(code: &str) -> Value {
+  json!({ "code": code, "name": "Claimed", "email": format!("{}@example.com", code.to_lowercase()), "contact_type": "customer" })
+}
+
+#[tokio::test]
+async fn test_switching_to_a_workspace_of_others_is_forbidden() {
+  let app = claims_app().await;
+  let owner = UserFactory::new().create(&app).await;
+  let stranger = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().create(&app, &owner).await;
+
+  let (status, body) = switch(&app, &stranger, workspace.id).await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+
+  let (status, body) = switch(&app, &owner, workspace.id).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  assert_eq!(body["results"]["role"], "Admin");
+}
+
+#[tokio::test]
+async fn test_claims_of_demoted_users_expire() {
+  let app = claims_app().await;
+  let owner = UserFactory::new().create(&app).await;
+  let member = UserFactory::new().create(&app).await;
+  let workspace = WorkspaceFactory::new().member(&member, WorkspaceRole::Member).create(&app, &owner).await;
+
+  let (status, body) = switch(&app, &member, workspace.id).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let token = body["results"]["token"].as_str().unwrap().to_string();
+
+  let (status, body) = app
+    .call(
+      http::Method::PUT,
+      &format!("/api/v1/workspaces/{}/users/{}/role", workspace.id, member.id()),
+      &owner,
+      None,
+      Some(json!({ "role": "Viewer" })),
+    )
+    .await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+
+  // Until it expires, the claim vouches for the role the member had when switching
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", token.as_str(), None, Some(contact("CLAIM-1")))
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+
+  tokio::time::sleep(Duration::from_secs(CLAIM_TTL_SECS + 1)).await;
+  let (status, body) = app
+    .call(http::Method::POST, "/api/v1/contacts", token.as_str(), None, Some(contact("CLAIM-2")))
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+  // The token itself stays valid
+  let (status, body) = app.call(http::Method::GET, "/api/v1/workspaces", token.as_str(), None, None).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+#[tokio::test]
+async fn test_claims_only_cover_their_own_workspace() {
+  let app = claims_app().await;
+  let owner = UserFactory::new().create(&app).await;
+  let user = UserFactory::new().create(&app).await;
+  let claimed = WorkspaceFactory::new().create(&app, &user).await;
+  let viewed = WorkspaceFactory::new().member(&user, WorkspaceRole::Viewer).create(&app, &owner).await;
+  let foreign = WorkspaceFactory::new().create(&app, &owner).await;
+
+  let (status, body) = switch(&app, &user, claimed.id).await;
+  assert_eq!(status, StatusCode::OK, "{body}");
+  let token = body["results"]["token"].as_str().unwrap().to_string();
+
+  // The admin role claimed in one workspace is not taken for the role in another
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/contacts",
+      token.as_str(),
+      Some(viewed.id),
+      Some(contact("CLAIM-3")),
+    )
+    .await;
+  assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+  let (status, body) = app
+    .call(http::Method::GET, "/api/v1/contacts", token.as_str(), Some(foreign.id), None)
+    .await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+  assert_eq!(body["error"], "WORKSPACE_INVALID");
+
+  let (status, body) = app
+    .call(
+      http::Method::POST,
+      "/api/v1/contacts",
+      token.as_str(),
+      Some(claimed.id),
+      Some(contact("CLAIM-4")),
+    )
+    .await;
+  assert_eq!(status, StatusCode::CREATED, "{body}");
+}